@@ -44,6 +44,12 @@ export default function ApiKeyDetail() {
   const [masterPassword, setMasterPassword] = useState('')
   const [showGenerationModal, setShowGenerationModal] = useState(false)
 
+  // A revealed value belongs to the key it was revealed for
+  useEffect(() => {
+    setDecryptedKey(null)
+    setKeyVisible(false)
+  }, [selectedKey?.id])
+
   // Carica lo stato VSCode per la chiave selezionata
   useEffect(() => {
     const loadVSCodeStatus = async () => {
@@ -72,9 +78,42 @@ export default function ApiKeyDetail() {
     }
   }
 
-  const decryptApiKey = () => {
+  // The selected key comes from a listing, masked; fetch its value when needed
+  const revealValue = async (reason: string) => {
+    if (!selectedKey) return null
+    if (decryptedKey || !selectedKey.masked) return decryptedKey || selectedKey.key
+    try {
+      const value = await TauriAPI.revealApiKey(selectedKey.id, reason)
+      setDecryptedKey(value)
+      return value
+    } catch (error) {
+      console.error('Failed to reveal API key:', error)
+      return null
+    }
+  }
+
+  const copyValue = async () => {
+    const value = await revealValue('Copied from key details')
+    if (value) await copyToClipboard(value)
+  }
+
+  const toggleKeyVisible = async () => {
+    if (!keyVisible && !(await revealValue('Shown in key details'))) return
+    setKeyVisible(!keyVisible)
+  }
+
+  // The unlocked session usually suffices; the password is only asked for
+  // values it has no key for
+  const decryptApiKey = async () => {
     if (!selectedKey) return
-    setShowDecryptModal(true)
+    setIsDecrypting(true)
+    try {
+      setDecryptedKey(await TauriAPI.getDecryptedApiKey(selectedKey.id))
+    } catch {
+      setShowDecryptModal(true)
+    } finally {
+      setIsDecrypting(false)
+    }
   }
 
   const handleDecryptSubmit = async () => {
@@ -86,11 +125,7 @@ export default function ApiKeyDetail() {
 
     setIsDecrypting(true)
     try {
-      const { invoke } = await import('@tauri-apps/api/core')
-      const decrypted = await invoke<string>('get_decrypted_api_key', {
-        keyId: selectedKey.id,
-        masterPassword
-      })
+      const decrypted = await TauriAPI.getDecryptedApiKey(selectedKey.id, masterPassword)
 
       setDecryptedKey(decrypted)
       setShowDecryptModal(false)
@@ -231,7 +266,7 @@ export default function ApiKeyDetail() {
             </h3>
             <div className="flex items-center space-x-2">
               <button
-                onClick={() => copyToClipboard(decryptedKey || selectedKey.key)}
+                onClick={copyValue}
                 className="p-2 btn-secondary hover-lift focus-native"
                 style={{
                   minWidth: '32px',
@@ -242,7 +277,7 @@ export default function ApiKeyDetail() {
                 <Copy className="w-4 h-4" />
               </button>
               <button
-                onClick={() => setKeyVisible(!keyVisible)}
+                onClick={toggleKeyVisible}
                 className="p-2 btn-secondary hover-lift focus-native"
                 style={{
                   minWidth: '32px',
@@ -381,14 +416,23 @@ export default function ApiKeyDetail() {
           className="mb-6"
         />
 
-        {/* Rate Limiting */}
-        {selectedKey.rate_limit && (
+        {/* Quota */}
+        {selectedKey.quota && (
           <div className="p-4 glass-card">
             <h3 className="flex items-center mb-3 space-x-2 text-heading">
               <Globe className="w-4 h-4" />
-              <span>Rate Limiting</span>
+              <span>Quota</span>
             </h3>
-            <p className="text-body">{selectedKey.rate_limit}</p>
+            {selectedKey.quota.requests != null && (
+              <p className="text-body">
+                {selectedKey.quota.requests} requests per {selectedKey.quota.window}
+              </p>
+            )}
+            {selectedKey.quota.monthly_spend_cap != null && (
+              <p className="text-body">
+                {selectedKey.quota.monthly_spend_cap} {selectedKey.quota.currency} per month
+              </p>
+            )}
           </div>
         )}
 
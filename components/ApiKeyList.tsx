@@ -14,6 +14,7 @@ import {
 import { useState, useEffect } from 'react'
 import { useAppStore } from '../lib/store'
 import type { ApiKey } from '../lib/store'
+import { TauriAPI } from '../lib/tauri-api'
 import VSCodeStatusIndicator from './VSCodeStatusIndicator'
 
 export default function ApiKeyList() {
@@ -26,7 +27,8 @@ export default function ApiKeyList() {
     getProjectVSCodeStatus
   } = useAppStore()
 
-  const [visibleKeys, setVisibleKeys] = useState<Set<string>>(new Set())
+  // Values revealed for display, by key id
+  const [visibleKeys, setVisibleKeys] = useState<Record<string, string>>({})
   const [vscodeStatuses, setVscodeStatuses] = useState<Record<string, string>>({})
 
   // Carica gli stati VSCode per le chiavi importate da .env
@@ -54,21 +56,32 @@ export default function ApiKeyList() {
     }
   }, [filteredKeys, getProjectVSCodeStatus])
 
-  const toggleKeyVisibility = (keyId: string, e: React.MouseEvent) => {
+  // Listed keys are masked, the value is fetched when it's asked for
+  const revealKey = async (apiKey: ApiKey, reason: string) => {
+    if (!apiKey.masked) return apiKey.key
+    return await TauriAPI.revealApiKey(apiKey.id, reason)
+  }
+
+  const toggleKeyVisibility = async (apiKey: ApiKey, e: React.MouseEvent) => {
     e.stopPropagation()
-    const newVisible = new Set(visibleKeys)
-    if (newVisible.has(keyId)) {
-      newVisible.delete(keyId)
-    } else {
-      newVisible.add(keyId)
+    if (apiKey.id in visibleKeys) {
+      const { [apiKey.id]: _hidden, ...rest } = visibleKeys
+      setVisibleKeys(rest)
+      return
+    }
+    try {
+      const value = await revealKey(apiKey, 'Shown in key list')
+      setVisibleKeys(prev => ({ ...prev, [apiKey.id]: value }))
+    } catch (error) {
+      console.error('Failed to reveal key:', error)
     }
-    setVisibleKeys(newVisible)
   }
 
-  const copyToClipboard = async (text: string, e: React.MouseEvent) => {
+  const copyToClipboard = async (apiKey: ApiKey, e: React.MouseEvent) => {
     e.stopPropagation()
     try {
-      await navigator.clipboard.writeText(text)
+      const value = visibleKeys[apiKey.id] ?? (await revealKey(apiKey, 'Copied from key list'))
+      await navigator.clipboard.writeText(value)
       // You could add a toast notification here
     } catch (error) {
       console.error('Failed to copy:', error)
@@ -88,8 +101,8 @@ export default function ApiKeyList() {
     }
   }
 
-  const formatKey = (key: string, isVisible: boolean) => {
-    if (isVisible) return key
+  const formatKey = (key: string | undefined) => {
+    if (key !== undefined) return key
     // Show a fixed number of dots, e.g., 16
     return '•'.repeat(16)
   }
@@ -201,7 +214,7 @@ export default function ApiKeyList() {
                         <span className="font-medium text-caption">API Key</span>
                         <div className="flex items-center space-x-1">
                           <button
-                            onClick={(e) => copyToClipboard(apiKey.key, e)}
+                            onClick={(e) => copyToClipboard(apiKey, e)}
                             className="flex justify-center items-center p-1 btn-secondary hover-lift focus-native"
                             style={{
                               minWidth: '28px',
@@ -212,7 +225,7 @@ export default function ApiKeyList() {
                             <Copy className="flex-shrink-0 w-3 h-3" />
                           </button>
                           <button
-                            onClick={(e) => toggleKeyVisibility(apiKey.id, e)}
+                            onClick={(e) => toggleKeyVisibility(apiKey, e)}
                             className="flex justify-center items-center p-1 btn-secondary hover-lift focus-native"
                             style={{
                               minWidth: '28px',
@@ -220,7 +233,7 @@ export default function ApiKeyList() {
                               borderRadius: 'var(--radius-sm)'
                             }}
                           >
-                            {visibleKeys.has(apiKey.id) ? (
+                            {apiKey.id in visibleKeys ? (
                               <EyeOff className="flex-shrink-0 w-3 h-3" />
                             ) : (
                               <Eye className="flex-shrink-0 w-3 h-3" />
@@ -240,7 +253,7 @@ export default function ApiKeyList() {
                         overflowWrap: 'break-word', // extra safety
                         maxWidth: '100%', // never overflow
                       }}>
-                        {formatKey(apiKey.key, visibleKeys.has(apiKey.id))}
+                        {formatKey(visibleKeys[apiKey.id])}
                       </p>
                     </div>
 
@@ -2,38 +2,56 @@ import React, { useState, useCallback, useRef, useEffect } from 'react'
 import { motion, AnimatePresence } from 'framer-motion'
 import { CloudArrowUpIcon, DocumentIcon, FolderIcon } from '@heroicons/react/24/outline'
 import { useAppStore } from '../lib/store'
-import { invoke } from '@tauri-apps/api/core'
 import { open } from '@tauri-apps/plugin-dialog'
 import ProjectPathDisplay from './ProjectPathDisplay'
+import { TauriAPI, ImportAction, IngestProposal, IngestStage } from '../lib/tauri-api'
 
 interface DragDropZoneProps {
   onFileImport?: (filePath: string, projectPath: string) => void
 }
 
-interface EnvVariable {
-  name: string
-  value: string
-  is_secret: boolean
+interface PendingReview {
+  ingestionId: string
+  proposal: IngestProposal
+  vscodeStatus: string
 }
 
-interface DroppedEnvFile {
-  path: string
-  project_path: string
-  file_name: string
-  keys: EnvVariable[]
-}
-
-interface EnvFileWithStatus extends DroppedEnvFile {
-  vscode_status: string
+const STAGE_LABELS: Partial<Record<IngestStage, string>> = {
+  parsing: 'Reading .env file...',
+  classifying: 'Classifying variables...',
+  deduplicating: 'Looking for duplicates...',
+  associating: 'Finding the project...',
+  proposed: 'Review the import below',
+  applying: 'Importing...',
 }
 
 export default function DragDropZone({ onFileImport }: DragDropZoneProps) {
   const [isDragOver, setIsDragOver] = useState(false)
   const [isProcessing, setIsProcessing] = useState(false)
-  const [importResult, setImportResult] = useState<EnvFileWithStatus | null>(null)
-  const { addApiKey, setError, getProjectVSCodeStatus } = useAppStore()
+  const [stage, setStage] = useState<IngestStage | null>(null)
+  const [review, setReview] = useState<PendingReview | null>(null)
+  // Variables left out of the import, and whether to associate the project
+  const [excluded, setExcluded] = useState<Set<string>>(new Set())
+  const [associate, setAssociate] = useState(true)
+  const { setError, getProjectVSCodeStatus, loadApiKeys } = useAppStore()
   const fileInputRef = useRef<HTMLInputElement>(null)
 
+  useEffect(() => {
+    const unlisten = TauriAPI.onEnvIngestProgress(async (progress) => {
+      setStage(progress.stage)
+      if (progress.stage === 'proposed' && progress.proposal) {
+        const proposal = progress.proposal
+        const vscodeStatus = await getProjectVSCodeStatus(proposal.project.project_path)
+        setExcluded(new Set())
+        setAssociate(true)
+        setReview({ ingestionId: progress.ingestion_id, proposal, vscodeStatus })
+      }
+    })
+    return () => {
+      unlisten.then(stop => stop())
+    }
+  }, [getProjectVSCodeStatus])
+
   const processFiles = useCallback(async (filePaths: string[]) => {
     setIsProcessing(true)
 
@@ -49,28 +67,27 @@ export default function DragDropZone({ onFileImport }: DragDropZoneProps) {
         return
       }
 
+      // One at a time, each waiting for its review
       for (const filePath of envFiles) {
         try {
-          const result = await invoke<DroppedEnvFile>('parse_and_register_env_file', {
-            filePath: filePath
-          })
-
-          // Get VSCode status for the project
-          const vscodeStatus = await getProjectVSCodeStatus(result.project_path)
-
-          const resultWithStatus: EnvFileWithStatus = {
-            ...result,
-            vscode_status: vscodeStatus
+          const result = await TauriAPI.ingestEnvFile(filePath)
+          if (result.stage === 'done') {
+            const failed = result.import?.failed ?? []
+            const warnings = result.import?.warnings ?? []
+            if (failed.length > 0) {
+              setError(`Not imported: ${failed.map(f => `${f.name} (${f.reason})`).join(', ')}`)
+            } else if (warnings.length > 0) {
+              setError(warnings.join('; '))
+            }
+            await loadApiKeys()
+            onFileImport?.(filePath, result.project_path)
           }
-
-          setImportResult(resultWithStatus)
-          onFileImport?.(result.path, result.project_path)
-          break
         } catch (error) {
           console.error(`Error processing file ${filePath}:`, error)
           const errorMessage = typeof error === 'string' ? error : 'Unknown error occurred'
           setError(`Error processing file: ${errorMessage}`)
-          continue
+        } finally {
+          setReview(null)
         }
       }
     } catch (error) {
@@ -80,7 +97,7 @@ export default function DragDropZone({ onFileImport }: DragDropZoneProps) {
     } finally {
       setIsProcessing(false)
     }
-  }, [setError, onFileImport])
+  }, [setError, onFileImport, loadApiKeys])
 
   const handleDragOver = useCallback((e: React.DragEvent) => {
     e.preventDefault()
@@ -174,52 +191,48 @@ export default function DragDropZone({ onFileImport }: DragDropZoneProps) {
     }
   }, [setError, isDragOver, processFiles])
 
-  const handleImportKeys = async () => {
-    if (!importResult) return
-
+  const respond = async (apply: boolean) => {
+    if (!review) return
+    const { ingestionId, proposal } = review
+    setReview(null)
     try {
-      for (const key of importResult.keys) {
-        if (key.is_secret) {
-          await addApiKey({
-            name: key.name,
-            service: 'Environment Variable',
-            key: key.value,
-            environment: detectEnvironment(importResult.file_name) as 'development' | 'staging' | 'production',
-            description: `Imported from ${importResult.file_name}`,
-            scopes: ['env'],
-            tags: ['imported', 'env-file'],
-            is_active: true,
-            source_type: 'env_file',
-            env_file_path: importResult.path,
-            project_path: importResult.project_path,
-            env_file_name: importResult.file_name
-          })
-        }
-      }
-
-      // Register project association
-      await invoke('associate_project_with_env', {
-        projectPath: importResult.project_path,
-        envPath: importResult.path,
-        fileName: importResult.file_name
-      })
-
-      setImportResult(null)
-      setError(null)
+      await TauriAPI.reviewEnvIngestion(
+        ingestionId,
+        apply
+          ? {
+              decisions: proposal.import.variables.map(v => ({
+                name: v.name,
+                action: (excluded.has(v.name) ? 'skip' : v.action) as ImportAction,
+                key_id: v.key_id,
+                service: v.provider,
+              })),
+              associate,
+            }
+          : null
+      )
     } catch (error) {
-      console.error('Error importing keys:', error)
+      console.error('Error reviewing import:', error)
       const errorMessage = typeof error === 'string' ? error : 'Failed to import API keys'
       setError(errorMessage)
     }
   }
 
-  const detectEnvironment = (fileName: string): string => {
-    if (fileName.includes('.production')) return 'production'
-    if (fileName.includes('.development')) return 'development'
-    if (fileName.includes('.local')) return 'development'
-    return 'development'
+  const toggle = (name: string) => {
+    setExcluded(current => {
+      const next = new Set(current)
+      if (next.has(name)) {
+        next.delete(name)
+      } else {
+        next.add(name)
+      }
+      return next
+    })
   }
 
+  const toImport = review
+    ? review.proposal.import.variables.filter(v => v.action !== 'skip' && !excluded.has(v.name))
+    : []
+
   return (
     <div className="relative">
       {/* Main Drop Zone */}
@@ -254,7 +267,7 @@ export default function DragDropZone({ onFileImport }: DragDropZoneProps) {
           )}
         </AnimatePresence>
 
-        {!isProcessing && !importResult && (
+        {!isProcessing && !review && (
           <div className="space-y-3">
             <DocumentIcon className="mx-auto w-8 h-8 text-gray-400" />
             <div>
@@ -290,14 +303,16 @@ export default function DragDropZone({ onFileImport }: DragDropZoneProps) {
               transition={{ duration: 2, repeat: Infinity, ease: "linear" }}
               className="mx-auto w-8 h-8 rounded-full border-4 border-blue-500 border-t-transparent"
             />
-            <p className="text-base font-medium">Processing .env file...</p>
+            <p className="text-base font-medium">
+              {(stage && STAGE_LABELS[stage]) || 'Processing .env file...'}
+            </p>
           </div>
         )}
       </motion.div>
 
-      {/* Import Result Modal */}
+      {/* Import Review */}
       <AnimatePresence>
-        {importResult && (
+        {review && (
           <motion.div
             initial={{ opacity: 0, y: 20 }}
             animate={{ opacity: 1, y: 0 }}
@@ -309,10 +324,10 @@ export default function DragDropZone({ onFileImport }: DragDropZoneProps) {
                 <FolderIcon className="w-6 h-6 text-blue-500" />
                 <div>
                   <h3 className="text-base font-semibold text-gray-900 dark:text-gray-100">
-                    .env file detected
+                    {review.proposal.import.file_name}
                   </h3>
                   <p className="text-xs text-gray-500 dark:text-gray-400">
-                    Ready to import API keys
+                    {review.proposal.import.environment} values
                   </p>
                 </div>
               </div>
@@ -325,49 +340,71 @@ export default function DragDropZone({ onFileImport }: DragDropZoneProps) {
                 </h4>
                 <div className="p-3 bg-gray-50 rounded-lg dark:bg-gray-700">
                   <ProjectPathDisplay
-                    envFilePath={importResult.path}
-                    projectPath={importResult.project_path}
-                    fileName={importResult.file_name}
-                    vscodeStatus={importResult.vscode_status}
+                    envFilePath={review.proposal.import.path}
+                    projectPath={review.proposal.project.project_path}
+                    fileName={review.proposal.import.file_name}
+                    vscodeStatus={review.vscodeStatus}
                     showActions={false}
                   />
                 </div>
+                {!review.proposal.project.already_associated && (
+                  <label className="flex items-center mt-2 space-x-2 text-xs text-gray-600 dark:text-gray-300">
+                    <input
+                      type="checkbox"
+                      checked={associate}
+                      onChange={e => setAssociate(e.target.checked)}
+                    />
+                    <span>
+                      Associate with {review.proposal.project.project_name ?? review.proposal.project.project_path}
+                    </span>
+                  </label>
+                )}
               </div>
 
               <div>
                 <h4 className="mb-2 text-sm font-medium text-gray-900 dark:text-gray-100">
-                  Variables found ({importResult.keys.length}):
+                  Variables found ({review.proposal.import.variables.length}):
                 </h4>
                 <div className="overflow-y-auto space-y-1 max-h-32">
-                  {importResult.keys.map((key, index) => (
-                    <div
-                      key={index}
-                      className={`flex items-center justify-between p-1.5 rounded text-xs ${key.is_secret
+                  {review.proposal.import.variables.map(variable => (
+                    <label
+                      key={variable.name}
+                      title={variable.reasons.join('\n')}
+                      className={`flex items-center justify-between p-1.5 rounded text-xs ${variable.action !== 'skip'
                         ? 'bg-yellow-50 dark:bg-yellow-900/20 border border-yellow-200 dark:border-yellow-800'
                         : 'bg-gray-50 dark:bg-gray-700'
                         }`}
                     >
-                      <span className="font-mono">{key.name}</span>
-                      <span className={`px-2 py-1 rounded text-xs ${key.is_secret
-                        ? 'bg-yellow-200 dark:bg-yellow-800 text-yellow-800 dark:text-yellow-200'
-                        : 'bg-gray-200 dark:bg-gray-600 text-gray-600 dark:text-gray-300'
-                        }`}>
-                        {key.is_secret ? 'Secret' : 'Config'}
+                      <span className="flex items-center space-x-2">
+                        <input
+                          type="checkbox"
+                          disabled={variable.action === 'skip'}
+                          checked={variable.action !== 'skip' && !excluded.has(variable.name)}
+                          onChange={() => toggle(variable.name)}
+                        />
+                        <span className="font-mono">{variable.name}</span>
+                      </span>
+                      <span className="px-2 py-1 text-xs text-gray-600 bg-gray-200 rounded dark:bg-gray-600 dark:text-gray-300">
+                        {variable.action === 'update'
+                          ? `Update ${variable.key_name}`
+                          : variable.action === 'create'
+                            ? 'New'
+                            : variable.reasons[variable.reasons.length - 1]}
                       </span>
-                    </div>
+                    </label>
                   ))}
                 </div>
               </div>
 
               <div className="flex pt-3 space-x-3">
                 <button
-                  onClick={handleImportKeys}
+                  onClick={() => respond(true)}
                   className="flex-1 px-3 py-1.5 text-sm font-medium text-white bg-blue-600 rounded-lg transition-colors hover:bg-blue-700"
                 >
-                  Import {importResult.keys.filter(k => k.is_secret).length} API Keys
+                  Import {toImport.length} API Keys
                 </button>
                 <button
-                  onClick={() => setImportResult(null)}
+                  onClick={() => respond(false)}
                   className="px-3 py-1.5 text-sm text-gray-600 transition-colors dark:text-gray-400 hover:text-gray-800 dark:hover:text-gray-200"
                 >
                   Cancel
@@ -1,9 +1,9 @@
-import { useState } from 'react'
+import { useEffect, useState } from 'react'
 import { motion, AnimatePresence } from 'framer-motion'
 import { Key, Shield, Eye, EyeOff, CheckCircle2, AlertTriangle, Lock, Sparkles } from 'lucide-react'
 import { useAppStore } from '../lib/store'
 import { invoke } from '@tauri-apps/api/core'
-import KeyringService from '../lib/services/keyringService'
+import { TauriAPI, PasswordStrength } from '../lib/tauri-api'
 
 export default function SetMasterPasswordScreen() {
   const [password, setPassword] = useState('')
@@ -11,8 +11,22 @@ export default function SetMasterPasswordScreen() {
   const [showPassword, setShowPassword] = useState(false)
   const [isLoading, setIsLoading] = useState(false)
   const [successMessage, setSuccessMessage] = useState('')
+  const [strength, setStrength] = useState<PasswordStrength | null>(null)
   const { error, setError, setHasMasterPassword, setIsUnlocked } = useAppStore()
 
+  useEffect(() => {
+    if (!password) {
+      setStrength(null)
+      return
+    }
+    const timer = setTimeout(() => {
+      TauriAPI.estimatePasswordStrength(password)
+        .then(setStrength)
+        .catch((err) => console.warn('Password strength unavailable:', err))
+    }, 250)
+    return () => clearTimeout(timer)
+  }, [password])
+
   const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault()
     setIsLoading(true)
@@ -25,8 +39,8 @@ export default function SetMasterPasswordScreen() {
       return
     }
 
-    if (password.length < 8) {
-      setError('Master password must be at least 8 characters long.')
+    if (strength && !strength.meets_policy) {
+      setError(strength.violations.join('. '))
       setIsLoading(false)
       return
     }
@@ -35,8 +49,12 @@ export default function SetMasterPasswordScreen() {
       // Set master password in the vault system
       const success = await invoke<boolean>('set_master_password', { password })
       if (success) {
-        // Save master password to keyring for future authentication
-        await KeyringService.saveMasterPassword(password)
+        // Let this device unlock the vault without keeping the password itself
+        try {
+          await TauriAPI.enableHardwareUnlock()
+        } catch (err) {
+          console.warn('Hardware unlock not enabled:', err)
+        }
         
         setHasMasterPassword(true)
         setIsUnlocked(true)
@@ -219,6 +237,33 @@ export default function SetMasterPasswordScreen() {
               </div>
             </div>
 
+            {strength && (
+              <div className="space-y-1">
+                <div className="flex gap-1">
+                  {[0, 1, 2, 3].map((i) => (
+                    <div
+                      key={i}
+                      className="h-1 flex-1 rounded-full"
+                      style={{
+                        background: i < strength.score
+                          ? ['#ff453a', '#ff9f0a', '#ffd60a', '#30d158'][strength.score - 1]
+                          : 'rgba(255, 255, 255, 0.1)'
+                      }}
+                    />
+                  ))}
+                </div>
+                <p className="text-caption text-contrast-medium">
+                  Time to crack: {strength.crack_time}
+                  {strength.warning ? ` · ${strength.warning}` : ''}
+                </p>
+                {!strength.meets_policy && strength.violations.map((violation) => (
+                  <p key={violation} className="text-caption" style={{ color: '#ff9f0a' }}>
+                    {violation}
+                  </p>
+                ))}
+              </div>
+            )}
+
             <div className="space-y-2">
               <label
                 htmlFor="confirmPassword"
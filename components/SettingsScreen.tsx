@@ -1917,7 +1917,7 @@ function SecurityAuditSettings() {
                         vulnerabilities.push(`Expired API key: ${key.name}`)
                     }
                 }
-                if (key.key.length < 32) {
+                if ((key.value_length ?? key.key.length) < 32) {
                     vulnerabilityCount++
                     vulnerabilities.push(`Potentially weak key: ${key.name}`)
                 }
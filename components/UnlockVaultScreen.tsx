@@ -1,7 +1,8 @@
 import { useEffect, useState } from 'react'
 import { motion, AnimatePresence } from 'framer-motion'
-import { Key, Shield, Eye, EyeOff, Lock } from 'lucide-react'
+import { Key, Shield, Eye, EyeOff, Lock, Fingerprint } from 'lucide-react'
 import { useAppStore } from '../lib/store'
+import { TauriAPI, type HardwareBackend } from '../lib/tauri-api'
 import KeyringService from '../lib/services/keyringService'
 
 export default function UnlockVaultScreen() {
@@ -10,7 +11,8 @@ export default function UnlockVaultScreen() {
   const [isLoading, setIsLoading] = useState(false)
   const [successMessage, setSuccessMessage] = useState('')
   const [isDark, setIsDark] = useState(false)
-  const { unlockVault, error, setError } = useAppStore()
+  const [hardwareBackend, setHardwareBackend] = useState<HardwareBackend | null>(null)
+  const { unlockVault, unlockWithHardwareKey, error, setError } = useAppStore()
 
   const updateTheme = (dark: boolean) => {
     if (dark) {
@@ -34,6 +36,24 @@ export default function UnlockVaultScreen() {
     }
   }, [])
 
+  useEffect(() => {
+    TauriAPI.getHardwareUnlockStatus()
+      .then(status => setHardwareBackend(status.enabled_backend))
+      .catch(() => setHardwareBackend(null))
+  }, [])
+
+  const handleHardwareUnlock = async () => {
+    setIsLoading(true)
+    setError(null)
+    try {
+      if (await unlockWithHardwareKey()) {
+        setSuccessMessage('Vault unlocked successfully!')
+      }
+    } finally {
+      setIsLoading(false)
+    }
+  }
+
 
 
 
@@ -309,6 +329,26 @@ export default function UnlockVaultScreen() {
                   )}
                 </AnimatePresence>
               </motion.button>
+              {hardwareBackend && (
+                <button
+                  type="button"
+                  onClick={handleHardwareUnlock}
+                  disabled={isLoading}
+                  className="flex justify-center items-center py-3 space-x-2 w-full text-base font-medium btn-secondary focus-native"
+                  style={{
+                    borderRadius: 'var(--radius-lg)',
+                    opacity: isLoading ? '0.5' : '1',
+                    cursor: isLoading ? 'not-allowed' : 'pointer'
+                  }}
+                >
+                  <Fingerprint className="w-5 h-5" />
+                  <span>
+                    {hardwareBackend === 'secure_enclave' && 'Unlock with Touch ID'}
+                    {hardwareBackend === 'tpm' && 'Unlock with Windows Security'}
+                    {hardwareBackend === 'keyring' && 'Unlock with this device'}
+                  </span>
+                </button>
+              )}
             </div>
           </form>
 
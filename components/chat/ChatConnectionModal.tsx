@@ -13,7 +13,7 @@ import {
   Download,
   Server
 } from 'lucide-react'
-import { TauriAPI } from '../../lib/tauri-api'
+import { TauriAPI, errorMessage } from '../../lib/tauri-api'
 
 interface ChatConnectionModalProps {
   isOpen: boolean
@@ -114,7 +114,7 @@ export default function ChatConnectionModal({ isOpen, onClose }: ChatConnectionM
     } catch (error) {
       console.error('Connection test failed:', error)
       setConnectionStatus('error')
-      setErrorMessage(error as string)
+      setErrorMessage(errorMessage(error))
     } finally {
       setIsConnecting(false)
     }
@@ -131,7 +131,7 @@ export default function ChatConnectionModal({ isOpen, onClose }: ChatConnectionM
       await checkOllamaStatus() // Refresh status
     } catch (error) {
       console.error('Failed to pull model:', error)
-      setErrorMessage(error as string)
+      setErrorMessage(errorMessage(error))
     } finally {
       setIsConnecting(false)
     }
@@ -2,6 +2,7 @@ import { useState } from 'react'
 import { motion, AnimatePresence } from 'framer-motion'
 import { X, Plus, Tag, Eye, EyeOff } from 'lucide-react'
 import { useAppStore } from '../../lib/store'
+import { costFromForm, quotaFromForm, type QuotaWindow } from '../../lib/tauri-api'
 
 export default function AddApiKeyModal() {
   const { setShowAddModal, addApiKey, isLoading } = useAppStore()
@@ -12,7 +13,11 @@ export default function AddApiKeyModal() {
     key: '',
     description: '',
     environment: 'development' as 'development' | 'staging' | 'production',
-    rate_limit: '',
+    quota_requests: '',
+    quota_window: 'minute' as QuotaWindow,
+    quota_spend_cap: '',
+    cost_estimate: '',
+    billing_account: '',
     expires_at: '',
     scopes: [] as string[],
     tags: [] as string[],
@@ -26,10 +31,12 @@ export default function AddApiKeyModal() {
   const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault()
     if (formData.name && formData.service && formData.key) {
+      const { quota_requests, quota_window, quota_spend_cap, cost_estimate, billing_account, ...fields } = formData
       await addApiKey({
-        ...formData,
+        ...fields,
         expires_at: formData.expires_at || undefined,
-        rate_limit: formData.rate_limit || undefined,
+        quota: quotaFromForm(quota_requests, quota_window, quota_spend_cap),
+        cost: costFromForm(cost_estimate, billing_account),
         description: formData.description || undefined
       })
     }
@@ -184,18 +191,40 @@ export default function AddApiKeyModal() {
               />
             </div>
 
-            {/* Rate Limit & Expiry */}
+            {/* Quota & Expiry */}
             <div className="grid grid-cols-1 gap-4 md:grid-cols-2">
               <div>
                 <label className="block mb-2 text-sm font-medium text-contrast-medium">
-                  Rate Limit
+                  Quota
                 </label>
+                <div className="flex gap-2">
+                  <input
+                    type="number"
+                    min={1}
+                    value={formData.quota_requests}
+                    onChange={(e) => setFormData(prev => ({ ...prev, quota_requests: e.target.value }))}
+                    className="input-native"
+                    placeholder="Requests"
+                  />
+                  <select
+                    value={formData.quota_window}
+                    onChange={(e) => setFormData(prev => ({ ...prev, quota_window: e.target.value as QuotaWindow }))}
+                    className="input-native"
+                  >
+                    <option value="minute">per minute</option>
+                    <option value="hour">per hour</option>
+                    <option value="day">per day</option>
+                    <option value="month">per month</option>
+                  </select>
+                </div>
                 <input
-                  type="text"
-                  value={formData.rate_limit}
-                  onChange={(e) => setFormData(prev => ({ ...prev, rate_limit: e.target.value }))}
-                  className="input-native"
-                  placeholder="es. 1000 req/min, unlimited"
+                  type="number"
+                  min={0}
+                  step="0.01"
+                  value={formData.quota_spend_cap}
+                  onChange={(e) => setFormData(prev => ({ ...prev, quota_spend_cap: e.target.value }))}
+                  className="mt-2 input-native"
+                  placeholder="Monthly spend cap (USD)"
                 />
               </div>
 
@@ -212,6 +241,36 @@ export default function AddApiKeyModal() {
               </div>
             </div>
 
+            {/* Cost */}
+            <div className="grid grid-cols-1 gap-4 md:grid-cols-2">
+              <div>
+                <label className="block mb-2 text-sm font-medium text-contrast-medium">
+                  Estimated Monthly Cost
+                </label>
+                <input
+                  type="number"
+                  min={0}
+                  step="0.01"
+                  value={formData.cost_estimate}
+                  onChange={(e) => setFormData(prev => ({ ...prev, cost_estimate: e.target.value }))}
+                  className="input-native"
+                  placeholder="USD"
+                />
+              </div>
+              <div>
+                <label className="block mb-2 text-sm font-medium text-contrast-medium">
+                  Billing Account
+                </label>
+                <input
+                  type="text"
+                  value={formData.billing_account}
+                  onChange={(e) => setFormData(prev => ({ ...prev, billing_account: e.target.value }))}
+                  className="input-native"
+                  placeholder="es. acme-prod"
+                />
+              </div>
+            </div>
+
             {/* Scopes */}
             <div>
               <label className="block mb-2 text-sm font-medium text-contrast-medium">
@@ -2,6 +2,7 @@ import { useState, useEffect } from 'react'
 import { motion, AnimatePresence } from 'framer-motion'
 import { X, Plus, Tag, Eye, EyeOff, Edit3 } from 'lucide-react'
 import { useAppStore } from '../../lib/store'
+import { TauriAPI, costFromForm, quotaFromForm, type QuotaWindow } from '../../lib/tauri-api'
 
 export default function EditApiKeyModal() {
   const { selectedKey, setShowEditModal, updateApiKey, isLoading } = useAppStore()
@@ -12,7 +13,11 @@ export default function EditApiKeyModal() {
     key: '',
     description: '',
     environment: 'development' as 'development' | 'staging' | 'production',
-    rate_limit: '',
+    quota_requests: '',
+    quota_window: 'minute' as QuotaWindow,
+    quota_spend_cap: '',
+    cost_estimate: '',
+    billing_account: '',
     expires_at: '',
     scopes: [] as string[],
     tags: [] as string[],
@@ -31,7 +36,11 @@ export default function EditApiKeyModal() {
         key: selectedKey.key,
         description: selectedKey.description || '',
         environment: selectedKey.environment,
-        rate_limit: selectedKey.rate_limit || '',
+        quota_requests: selectedKey.quota?.requests?.toString() ?? '',
+        quota_window: selectedKey.quota?.window ?? 'minute',
+        quota_spend_cap: selectedKey.quota?.monthly_spend_cap?.toString() ?? '',
+        cost_estimate: selectedKey.cost?.estimated_monthly?.toString() ?? '',
+        billing_account: selectedKey.cost?.billing_account ?? '',
         expires_at: selectedKey.expires_at ? selectedKey.expires_at.split('T')[0] : '',
         scopes: [...selectedKey.scopes],
         tags: [...selectedKey.tags],
@@ -40,14 +49,31 @@ export default function EditApiKeyModal() {
     }
   }, [selectedKey])
 
+  // The form starts with the listing's masked value, which saves as unchanged;
+  // showing the key fetches the real one
+  const toggleKeyVisible = async () => {
+    if (!keyVisible && selectedKey?.masked && formData.key === selectedKey.key) {
+      try {
+        const value = await TauriAPI.revealApiKey(selectedKey.id, 'Shown while editing')
+        setFormData(prev => ({ ...prev, key: value }))
+      } catch (error) {
+        console.error('Failed to reveal API key:', error)
+        return
+      }
+    }
+    setKeyVisible(!keyVisible)
+  }
+
   const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault()
     if (selectedKey && formData.name && formData.service && formData.key) {
+      const { quota_requests, quota_window, quota_spend_cap, cost_estimate, billing_account, ...fields } = formData
       await updateApiKey({
         ...selectedKey,
-        ...formData,
+        ...fields,
         expires_at: formData.expires_at || undefined,
-        rate_limit: formData.rate_limit || undefined,
+        quota: quotaFromForm(quota_requests, quota_window, quota_spend_cap, selectedKey.quota),
+        cost: costFromForm(cost_estimate, billing_account, selectedKey.cost),
         description: formData.description || undefined
       })
     }
@@ -168,7 +194,7 @@ export default function EditApiKeyModal() {
                 />
                 <button
                   type="button"
-                  onClick={() => setKeyVisible(!keyVisible)}
+                  onClick={toggleKeyVisible}
                   className="flex absolute inset-y-0 right-0 items-center pr-3 text-gray-500 dark:text-gray-400 hover:text-gray-700 dark:hover:text-gray-200 focus:outline-none"
                 >
                   {keyVisible ? (
@@ -210,18 +236,40 @@ export default function EditApiKeyModal() {
               />
             </div>
 
-            {/* Rate Limit & Expiry */}
+            {/* Quota & Expiry */}
             <div className="grid grid-cols-1 gap-4 md:grid-cols-2">
               <div>
                 <label className="block mb-2 text-sm font-medium text-gray-700 dark:text-gray-300">
-                  Rate Limit
+                  Quota
                 </label>
+                <div className="flex gap-2">
+                  <input
+                    type="number"
+                    min={1}
+                    value={formData.quota_requests}
+                    onChange={(e) => setFormData(prev => ({ ...prev, quota_requests: e.target.value }))}
+                    className="px-3 py-2 w-full placeholder-gray-500 text-gray-900 bg-white rounded-lg border border-gray-300 transition-colors dark:border-gray-600 dark:bg-gray-700 dark:text-gray-100 dark:placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                    placeholder="Requests"
+                  />
+                  <select
+                    value={formData.quota_window}
+                    onChange={(e) => setFormData(prev => ({ ...prev, quota_window: e.target.value as QuotaWindow }))}
+                    className="px-3 py-2 w-full placeholder-gray-500 text-gray-900 bg-white rounded-lg border border-gray-300 transition-colors dark:border-gray-600 dark:bg-gray-700 dark:text-gray-100 dark:placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                  >
+                    <option value="minute">per minute</option>
+                    <option value="hour">per hour</option>
+                    <option value="day">per day</option>
+                    <option value="month">per month</option>
+                  </select>
+                </div>
                 <input
-                  type="text"
-                  value={formData.rate_limit}
-                  onChange={(e) => setFormData(prev => ({ ...prev, rate_limit: e.target.value }))}
-                  className="px-3 py-2 w-full placeholder-gray-500 text-gray-900 bg-white rounded-lg border border-gray-300 transition-colors dark:border-gray-600 dark:bg-gray-700 dark:text-gray-100 dark:placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent"
-                  placeholder="es. 1000 req/min, unlimited"
+                  type="number"
+                  min={0}
+                  step="0.01"
+                  value={formData.quota_spend_cap}
+                  onChange={(e) => setFormData(prev => ({ ...prev, quota_spend_cap: e.target.value }))}
+                  className="mt-2 px-3 py-2 w-full placeholder-gray-500 text-gray-900 bg-white rounded-lg border border-gray-300 transition-colors dark:border-gray-600 dark:bg-gray-700 dark:text-gray-100 dark:placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                  placeholder="Monthly spend cap (USD)"
                 />
               </div>
 
@@ -238,6 +286,36 @@ export default function EditApiKeyModal() {
               </div>
             </div>
 
+            {/* Cost */}
+            <div className="grid grid-cols-1 gap-4 md:grid-cols-2">
+              <div>
+                <label className="block mb-2 text-sm font-medium text-gray-700 dark:text-gray-300">
+                  Estimated Monthly Cost
+                </label>
+                <input
+                  type="number"
+                  min={0}
+                  step="0.01"
+                  value={formData.cost_estimate}
+                  onChange={(e) => setFormData(prev => ({ ...prev, cost_estimate: e.target.value }))}
+                  className="px-3 py-2 w-full text-gray-900 bg-white rounded-lg border border-gray-300 transition-colors dark:border-gray-600 dark:bg-gray-700 dark:text-gray-100 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                  placeholder="USD"
+                />
+              </div>
+              <div>
+                <label className="block mb-2 text-sm font-medium text-gray-700 dark:text-gray-300">
+                  Billing Account
+                </label>
+                <input
+                  type="text"
+                  value={formData.billing_account}
+                  onChange={(e) => setFormData(prev => ({ ...prev, billing_account: e.target.value }))}
+                  className="px-3 py-2 w-full text-gray-900 bg-white rounded-lg border border-gray-300 transition-colors dark:border-gray-600 dark:bg-gray-700 dark:text-gray-100 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                  placeholder="es. acme-prod"
+                />
+              </div>
+            </div>
+
             {/* Scopes */}
             <div>
               <label className="block mb-2 text-sm font-medium text-gray-700 dark:text-gray-300">
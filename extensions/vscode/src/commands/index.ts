@@ -47,7 +47,7 @@ export async function insertKeyCommand(service: KeyKeeperService, key?: ApiKey):
 
             switch (format) {
                 case 'value':
-                    insertText = selectedKey.key;
+                    insertText = await service.formatKeyForInsertion(selectedKey, 'value');
                     break;
                 case 'environment':
                     insertText = keyVarName;
@@ -91,7 +91,7 @@ async function insertKeyLegacy(service: KeyKeeperService, selectedKey: ApiKey):
         const format = config.get<string>('insertFormat', 'process.env');
 
         // Format the key for insertion
-        const formattedKey = service.formatKeyForInsertion(selectedKey, format);
+        const formattedKey = await service.formatKeyForInsertion(selectedKey, format);
 
         // Insert at cursor position
         await editor.edit(editBuilder => {
@@ -44,6 +44,7 @@ export function activate(context: vscode.ExtensionContext) {
 
     // Initialize service
     keykeeperService = new KeyKeeperService();
+    keykeeperService.ensureRegistered(context.secrets);
 
     // Initialize providers
     apiKeysProvider = new ApiKeysProvider(keykeeperService);
@@ -322,6 +323,7 @@ function startAutoSync() {
     // Sync every 30 seconds if auto-sync is enabled
     setInterval(async () => {
         try {
+            await keykeeperService.prefetch();
             await apiKeysProvider.refresh();
             await projectsProvider.refresh();
             await recentProvider.refresh();
@@ -584,6 +586,7 @@ function isEnvFile(filePath: string): boolean {
 
 async function refreshProviders() {
     try {
+        await keykeeperService.prefetch();
         await apiKeysProvider.refresh();
         await projectsProvider.refresh();
         await recentProvider.refresh();
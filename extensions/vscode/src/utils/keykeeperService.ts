@@ -1,5 +1,6 @@
 import axios, { AxiosInstance } from 'axios';
 import WebSocket from 'ws';
+import * as os from 'os';
 import * as vscode from 'vscode';
 
 export interface ApiKey {
@@ -15,8 +16,16 @@ export interface ApiKey {
     scopes: string[];
     tags: string[];
     is_active: boolean;
+    /** Listed keys are masked; `getKeyValue` returns the real value */
+    masked?: boolean;
     expires_at?: string;
-    rate_limit?: string;
+    quota?: {
+        requests?: number | null;
+        window: 'minute' | 'hour' | 'day' | 'month';
+        monthly_spend_cap?: number | null;
+        currency: string;
+        alert_thresholds: number[];
+    } | null;
 }
 
 export interface Project {
@@ -36,10 +45,26 @@ export interface Project {
 
 export interface RecentActivity {
     id: string;
-    type: 'key_used' | 'key_created' | 'key_updated';
+    type: 'key_used' | 'key_created' | 'key_updated' | 'key_deleted' | 'key_archived' | 'key_restored';
     keyId: string;
     keyName: string;
     timestamp: string;
+    details?: string | null;
+    actor?: { kind: 'ui' | 'http' | 'cli' | 'system'; name?: string | null } | null;
+    source?: { ip?: string | null; device?: string | null } | null;
+}
+
+export interface BatchRequest {
+    id?: string;
+    method?: string;
+    /** Path with its query string */
+    path: string;
+}
+
+export interface BatchResponse {
+    id?: string | null;
+    status: number;
+    body: any;
 }
 
 export interface AuthResult {
@@ -187,6 +212,21 @@ export interface ChatSearchRequest {
     doc_types?: string[];
 }
 
+const REGISTRATION_SECRET = 'keykeeper.clientRegistration';
+
+interface ClientRegistration {
+    client_id: string;
+    client_secret: string;
+    status: 'pending' | 'approved' | 'revoked';
+}
+
+/** Same `hostname-user-os` form the desktop app uses for its devices */
+function deviceId(): string {
+    const platforms: Record<string, string> = { darwin: 'macos', win32: 'windows' };
+    const platform = platforms[process.platform] ?? process.platform;
+    return `${os.hostname()}-${os.userInfo().username}-${platform}`;
+}
+
 export class KeyKeeperService {
     private client: AxiosInstance;
     private ws: WebSocket | null = null;
@@ -198,6 +238,14 @@ export class KeyKeeperService {
     private lastHeartbeat: Date | null = null;
     private connectionId: string;
     private _isLoggedIn: boolean = false;
+    /** Results of the last prefetch, used once by the matching getter */
+    private prefetched = new Map<string, any>();
+    /** Last body and ETag of each list endpoint, for conditional requests */
+    private listCache = new Map<string, { etag: string; data: any }>();
+    /** Where the client registration is kept between sessions */
+    private secrets: vscode.SecretStorage | null = null;
+    /** Sent with every request once the desktop app has approved it */
+    private registration: ClientRegistration | null = null;
 
     constructor() {
         const config = vscode.workspace.getConfiguration('keykeeper');
@@ -226,6 +274,10 @@ export class KeyKeeperService {
             if (this.authToken) {
                 config.headers.Authorization = `Bearer ${this.authToken}`;
             }
+            if (this.registration?.status === 'approved') {
+                config.headers['X-KeyKeeper-Client-Id'] = this.registration.client_id;
+                config.headers['X-KeyKeeper-Client-Secret'] = this.registration.client_secret;
+            }
             return config;
         });
 
@@ -287,6 +339,62 @@ export class KeyKeeperService {
         }
     }
 
+    /**
+     * Identify this extension to the desktop app, registering it on first use.
+     * The registration only takes effect once approved in KeyKeeper.
+     */
+    async ensureRegistered(secrets: vscode.SecretStorage): Promise<void> {
+        this.secrets = secrets;
+        const stored = await secrets.get(REGISTRATION_SECRET);
+        this.registration = stored ? JSON.parse(stored) : null;
+
+        if (this.registration) {
+            try {
+                const response = await this.client.get('/api/clients/status', {
+                    headers: {
+                        'X-KeyKeeper-Client-Id': this.registration.client_id,
+                        'X-KeyKeeper-Client-Secret': this.registration.client_secret,
+                    },
+                });
+                this.registration.status = response.data.status;
+                if (this.registration.status !== 'revoked') {
+                    await this.saveRegistration();
+                    return;
+                }
+            } catch (error: any) {
+                // Locked vault or app not running: try again next session
+                if (error.response?.status !== 403) {
+                    return;
+                }
+            }
+        }
+
+        try {
+            const response = await this.client.post('/api/clients/register', {
+                name: `VS Code on ${os.hostname()}`,
+                device_id: deviceId(),
+            });
+            this.registration = {
+                client_id: response.data.client_id,
+                client_secret: response.data.client_secret,
+                status: response.data.status,
+            };
+            await this.saveRegistration();
+            vscode.window.showInformationMessage(
+                'Approve this VS Code window in the KeyKeeper desktop app to finish connecting.'
+            );
+        } catch (error: any) {
+            this.registration = null;
+            this.logAuditEvent('client_registration', 'error', `Registration failed: ${error.message}`);
+        }
+    }
+
+    private async saveRegistration(): Promise<void> {
+        if (this.secrets && this.registration) {
+            await this.secrets.store(REGISTRATION_SECRET, JSON.stringify(this.registration));
+        }
+    }
+
     isLoggedIn(): boolean {
         return this._isLoggedIn;
     }
@@ -488,14 +596,73 @@ export class KeyKeeperService {
         }
     }
 
+    /** Several read requests in one round trip; each has its own status */
+    async batch(requests: BatchRequest[]): Promise<BatchResponse[]> {
+        const response = await this.client.post('/api/batch', requests);
+        return response.data;
+    }
+
+    /**
+     * Fetch keys, projects and recent activity with a single request. The
+     * next getApiKeys(), getProjects() and getRecentActivity() calls use the
+     * results instead of asking the app again.
+     */
+    async prefetch(): Promise<void> {
+        if (!this.isLoggedIn()) {
+            return;
+        }
+        this.prefetched.clear();
+        try {
+            const responses = await this.batch([
+                { id: 'keys', path: '/api/keys' },
+                { id: 'projects', path: '/api/projects' },
+                { id: 'activity', path: '/api/activity/recent' },
+            ]);
+            for (const response of responses) {
+                if (response.id && response.status === 200) {
+                    this.prefetched.set(response.id, response.body);
+                }
+            }
+        } catch (error) {
+            // Older apps have no batch endpoint; the getters ask one by one
+            console.warn('Batch prefetch failed:', error);
+        }
+    }
+
+    /** GET a list endpoint, reusing the cached body when the app answers 304 */
+    private async getList<T>(url: string): Promise<T> {
+        const cached = this.listCache.get(url);
+        const response = await this.client.get(url, {
+            headers: cached ? { 'If-None-Match': cached.etag } : {},
+            validateStatus: status => (status >= 200 && status < 300) || status === 304,
+        });
+        if (response.status === 304 && cached) {
+            return cached.data;
+        }
+        const etag = response.headers['etag'];
+        if (etag) {
+            this.listCache.set(url, { etag, data: response.data });
+        }
+        return response.data;
+    }
+
+    private takePrefetched<T>(id: string): T | undefined {
+        const value = this.prefetched.get(id);
+        this.prefetched.delete(id);
+        return value;
+    }
+
     async getApiKeys(projectId?: string): Promise<ApiKey[]> {
         if (!this.isLoggedIn()) {
             throw new Error('Not logged in to KeyKeeper. Please log in first.');
         }
+        const prefetched = projectId ? undefined : this.takePrefetched<ApiKey[]>('keys');
+        if (prefetched) {
+            return prefetched;
+        }
         try {
             const url = projectId ? `/api/keys?projectId=${projectId}` : '/api/keys';
-            const response = await this.client.get(url);
-            return response.data;
+            return await this.getList<ApiKey[]>(url);
         } catch (error) {
             console.error('Error fetching API keys:', error);
             throw new Error('Failed to fetch API keys from KeyKeeper');
@@ -506,9 +673,12 @@ export class KeyKeeperService {
         if (!this.isLoggedIn()) {
             throw new Error('Not logged in to KeyKeeper. Please log in first.');
         }
+        const prefetched = this.takePrefetched<Project[]>('projects');
+        if (prefetched) {
+            return prefetched;
+        }
         try {
-            const response = await this.client.get('/api/projects');
-            return response.data;
+            return await this.getList<Project[]>('/api/projects');
         } catch (error) {
             console.error('Error fetching projects:', error);
             throw new Error('Failed to fetch projects from KeyKeeper');
@@ -519,6 +689,10 @@ export class KeyKeeperService {
         if (!this.isLoggedIn()) {
             throw new Error('Not logged in to KeyKeeper. Please log in first.');
         }
+        const prefetched = this.takePrefetched<RecentActivity[]>('activity');
+        if (prefetched) {
+            return prefetched;
+        }
         try {
             const response = await this.client.get('/api/activity/recent');
             return response.data;
@@ -567,13 +741,34 @@ export class KeyKeeperService {
         }
     }
 
-    async recordKeyUsage(keyId: string): Promise<void> {
+    /**
+     * Full value of a key. Listings only carry masked values; reading one
+     * may need approval in the app and is recorded in the key's activity.
+     */
+    async getKeyValue(keyId: string): Promise<string> {
+        if (!this.isLoggedIn()) {
+            throw new Error('Not logged in to KeyKeeper. Please log in first.');
+        }
+        try {
+            const response = await this.client.post(`/api/keys/${encodeURIComponent(keyId)}/decrypt`);
+            return response.data.value;
+        } catch (error: any) {
+            console.error('Error reading API key value:', error);
+            throw new Error(error.response?.data?.message || 'Failed to read API key value');
+        }
+    }
+
+    /**
+     * Record a use of a key; `usage` counts several requests or their cost
+     * against the key's quota
+     */
+    async recordKeyUsage(keyId: string, usage?: { requests?: number; cost?: number }): Promise<void> {
         if (!this.isLoggedIn()) {
             console.warn('Not logged in to KeyKeeper. Key usage not recorded.');
             return;
         }
         try {
-            await this.client.post(`/api/keys/${keyId}/usage`);
+            await this.client.post(`/api/keys/${keyId}/usage`, usage ?? {});
         } catch (error) {
             console.error('Error recording key usage:', error);
             // Don't throw here as this is not critical
@@ -636,16 +831,16 @@ export class KeyKeeperService {
         }
     }
 
-    formatKeyForInsertion(key: ApiKey, format: string): string {
+    async formatKeyForInsertion(key: ApiKey, format: string): Promise<string> {
         switch (format) {
             case 'value':
-                return key.key;
+                return key.masked ? await this.getKeyValue(key.id) : key.key;
             case 'environment':
                 return `${key.name.toUpperCase().replace(/[^A-Z0-9]/g, '_')}`;
             case 'process.env':
                 return `process.env.${key.name.toUpperCase().replace(/[^A-Z0-9]/g, '_')}`;
             default:
-                return key.key;
+                return key.masked ? await this.getKeyValue(key.id) : key.key;
         }
     }
 
@@ -899,7 +1094,7 @@ export class KeyKeeperService {
 
             // Format key based on context
             const format = this.getOptimalKeyFormat(context);
-            const formattedKey = this.formatKeyForInsertion(selectedKey, format);
+            const formattedKey = await this.formatKeyForInsertion(selectedKey, format);
 
             // Insert key at cursor
             const editor = vscode.window.activeTextEditor;
@@ -1020,7 +1215,7 @@ export class KeyKeeperService {
             }
 
             // Insert with the determined format
-            const formattedKey = this.formatKeyForInsertion(key, format);
+            const formattedKey = await this.formatKeyForInsertion(key, format);
             const editor = vscode.window.activeTextEditor;
             if (editor) {
                 const position = editor.selection.active;
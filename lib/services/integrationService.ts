@@ -160,7 +160,7 @@ class IntegrationService {
                 timestamp: new Date().toISOString(),
                 apiKeys: apiKeys.map(key => ({
                     ...key,
-                    key: '*'.repeat(key.key.length) // Mask the actual key for security
+                    key: '*'.repeat(key.value_length ?? key.key.length) // Mask the actual key for security
                 })),
                 settings,
                 metadata: {
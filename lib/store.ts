@@ -27,7 +27,7 @@ import type {
   ContentType,
   GenerateIntegrationRequest
 } from './types'
-import { TauriAPI } from './tauri-api'
+import { TauriAPI, errorMessage, type KeyCost, type KeyQuota } from './tauri-api'
 import { integrationService } from './services/integrationService'
 import { initializeNativeStorage } from './services/nativeStorageService'
 import { ChatService } from './services/chatService'
@@ -43,7 +43,8 @@ export interface ApiKey {
   key: string
   description?: string
   environment: 'development' | 'staging' | 'production'
-  rate_limit?: string
+  quota?: KeyQuota | null
+  cost?: KeyCost | null
   expires_at?: string
   scopes: string[]
   created_at: string
@@ -55,6 +56,9 @@ export interface ApiKey {
   env_file_path?: string
   project_path?: string
   env_file_name?: string
+  // Listings mask `key`; revealApiKey returns the real value
+  masked?: boolean
+  value_length?: number
   // Documentation fields
 
   documentation_url?: string
@@ -71,7 +75,11 @@ export interface Project {
   path: string
   created_at: string
   updated_at: string
+  // Resolved from the organization defaults, template and overrides
   settings: ProjectSettings
+  settings_template_id?: string | null
+  settings_overrides?: Partial<ProjectSettings>
+  group_id?: string | null
 }
 
 export interface ProjectSettings {
@@ -80,6 +88,10 @@ export interface ProjectSettings {
   vscode_integration: boolean
   cursor_integration: boolean
   notifications: boolean
+  // .env file (relative to the project) -> environment whose keys it gets
+  env_mapping?: Record<string, string>
+  // <SERVICE>, <ENV>, <NAME> and <PROJECT> placeholders; default <SERVICE>_API_KEY
+  var_name_template?: string | null
 }
 
 export interface ProjectStats {
@@ -376,7 +388,10 @@ interface AppState {
 
   // API Actions
   unlockVault: (password: string) => Promise<boolean>
+  unlockWithHardwareKey: () => Promise<boolean>
   lockVault: () => Promise<void>
+  // Drop decrypted state after the backend locked the vault
+  clearUnlockedState: () => void
   loadApiKeys: () => Promise<void>
   addApiKey: (key: Omit<ApiKey, 'id' | 'created_at' | 'updated_at'>) => Promise<void>
   updateApiKey: (key: ApiKey) => Promise<void>
@@ -394,7 +409,7 @@ interface AppState {
 
   // Project Management Actions
   loadProjects: () => Promise<void>
-  createProject: (name: string, description?: string, path?: string) => Promise<void>
+  createProject: (name: string, description?: string, path?: string, templateId?: string) => Promise<void>
   updateProject: (id: string, name?: string, description?: string, settings?: ProjectSettings) => Promise<void>
   deleteProject: (id: string, reassignKeysTo?: string) => Promise<void>
   selectProject: (project: Project | null) => void
@@ -614,7 +629,7 @@ export const useAppStore = create<AppState>()(
         // Save settings (in a real app this would persist to storage)
         set({ settings })
       } catch (error) {
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
       } finally {
         set({ isLoading: false })
       }
@@ -661,7 +676,7 @@ export const useAppStore = create<AppState>()(
         return success
       } catch (error) {
         console.error('❌ Frontend unlock error:', error)
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
         return false
       } finally {
         console.log('🔓 Frontend: Setting loading to false')
@@ -669,31 +684,56 @@ export const useAppStore = create<AppState>()(
       }
     },
 
+    unlockWithHardwareKey: async () => {
+      try {
+        set({ isLoading: true, error: null })
+        const success = await TauriAPI.unlockWithHardwareKey()
+        set({ isUnlocked: success })
+        if (success) {
+          await Promise.allSettled([get().loadApiKeys(), get().loadProjects()])
+          try {
+            await integrationService.initialize(get().settings)
+          } catch (error) {
+            console.error('Failed to initialize integration service:', error)
+          }
+        }
+        return success
+      } catch (error) {
+        set({ error: errorMessage(error) })
+        return false
+      } finally {
+        set({ isLoading: false })
+      }
+    },
+
     lockVault: async () => {
       try {
         await invoke('lock_vault')
-
-        // Reset integration service
-        integrationService.reset()
-
-        set({
-          isUnlocked: false,
-          apiKeys: [],
-          filteredKeys: [],
-          selectedKey: null,
-          searchQuery: '',
-          projects: [],
-          selectedProject: null,
-          currentProjectId: null,
-          projectStats: new Map(),
-          showProjectModal: false,
-          showAssignKeysModal: false
-        })
+        get().clearUnlockedState()
       } catch (error) {
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
       }
     },
 
+    clearUnlockedState: () => {
+      // Reset integration service
+      integrationService.reset()
+
+      set({
+        isUnlocked: false,
+        apiKeys: [],
+        filteredKeys: [],
+        selectedKey: null,
+        searchQuery: '',
+        projects: [],
+        selectedProject: null,
+        currentProjectId: null,
+        projectStats: new Map(),
+        showProjectModal: false,
+        showAssignKeysModal: false
+      })
+    },
+
     loadApiKeys: async () => {
       try {
         set({ isLoading: true, error: null })
@@ -701,7 +741,7 @@ export const useAppStore = create<AppState>()(
         set({ apiKeys: keys })
         get().updateFilteredKeys()
       } catch (error) {
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
       } finally {
         set({ isLoading: false })
       }
@@ -725,7 +765,7 @@ export const useAppStore = create<AppState>()(
         await get().loadApiKeys()
         set({ showAddModal: false })
       } catch (error) {
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
       } finally {
         set({ isLoading: false })
       }
@@ -742,7 +782,7 @@ export const useAppStore = create<AppState>()(
         await get().loadApiKeys()
         set({ showEditModal: false, selectedKey: null })
       } catch (error) {
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
       } finally {
         set({ isLoading: false })
       }
@@ -755,7 +795,7 @@ export const useAppStore = create<AppState>()(
         await get().loadApiKeys()
         set({ showDeleteModal: false, selectedKey: null })
       } catch (error) {
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
       } finally {
         set({ isLoading: false })
       }
@@ -771,7 +811,7 @@ export const useAppStore = create<AppState>()(
         const keys = await invoke<ApiKey[]>('search_api_keys', { query })
         set({ filteredKeys: keys, searchQuery: query })
       } catch (error) {
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
       } finally {
         set({ isLoading: false })
       }
@@ -783,7 +823,7 @@ export const useAppStore = create<AppState>()(
         const exportData = await invoke<string>('export_vault')
         return exportData
       } catch (error) {
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
         throw error
       } finally {
         set({ isLoading: false })
@@ -798,7 +838,7 @@ export const useAppStore = create<AppState>()(
         const workspaces = await invoke<VSCodeWorkspace[]>('get_vscode_workspaces')
         set({ vscodeWorkspaces: workspaces, lastWorkspaceUpdate: new Date() })
       } catch (error) {
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
       } finally {
         set({ isLoading: false })
       }
@@ -810,7 +850,7 @@ export const useAppStore = create<AppState>()(
         await invoke('update_vscode_workspaces', { workspaces })
         await get().loadVSCodeWorkspaces()
       } catch (error) {
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
       } finally {
         set({ isLoading: false })
       }
@@ -833,22 +873,22 @@ export const useAppStore = create<AppState>()(
         const projects = await invoke<Project[]>('get_projects')
         set({ projects })
       } catch (error) {
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
       } finally {
         set({ isLoading: false })
       }
     },
 
-    createProject: async (name: string, description?: string, path?: string) => {
+    createProject: async (name: string, description?: string, path?: string, templateId?: string) => {
       try {
         set({ isLoading: true, error: null })
-        const newProject = await invoke<Project>('create_project', { name, description, path })
+        const newProject = await invoke<Project>('create_project', { name, description, path, templateId })
 
         // Add to projects list
         const { projects } = get()
         set({ projects: [...projects, newProject], showProjectModal: false })
       } catch (error) {
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
       } finally {
         set({ isLoading: false })
       }
@@ -870,7 +910,7 @@ export const useAppStore = create<AppState>()(
           set({ selectedProject: updatedProject })
         }
       } catch (error) {
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
       } finally {
         set({ isLoading: false })
       }
@@ -898,7 +938,7 @@ export const useAppStore = create<AppState>()(
         // Reload API keys to reflect changes
         await get().loadApiKeys()
       } catch (error) {
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
       } finally {
         set({ isLoading: false })
       }
@@ -925,7 +965,7 @@ export const useAppStore = create<AppState>()(
         // Recalculate project stats
         await get().calculateProjectStats(projectId)
       } catch (error) {
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
       } finally {
         set({ isLoading: false })
       }
@@ -1152,7 +1192,7 @@ export const useAppStore = create<AppState>()(
         return prediction
       } catch (error) {
         console.error('Failed to analyze context:', error)
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
         throw error
       } finally {
         set({ isLoading: false })
@@ -1211,7 +1251,7 @@ export const useAppStore = create<AppState>()(
         return response
       } catch (error) {
         console.error('Failed to process with LLM:', error)
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
         throw error
       } finally {
         set({ isLLMProcessing: false })
@@ -1254,7 +1294,7 @@ export const useAppStore = create<AppState>()(
         return config
       } catch (error) {
         console.error('Failed to generate API configuration:', error)
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
         throw error
       } finally {
         set({ isLoading: false })
@@ -1279,7 +1319,7 @@ export const useAppStore = create<AppState>()(
         return config
       } catch (error) {
         console.error('Failed to preview generated config:', error)
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
         throw error
       } finally {
         set({ isLoading: false })
@@ -1293,7 +1333,7 @@ export const useAppStore = create<AppState>()(
         return result
       } catch (error) {
         console.error('Failed to scrape API documentation:', error)
-        set({ error: error as string })
+        set({ error: errorMessage(error) })
         throw error
       } finally {
         set({ isLoading: false })
@@ -1,5 +1,6 @@
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
+import type { Project, ProjectSettings } from './store';
 
 export interface ApiKey {
     id: string;
@@ -8,13 +9,90 @@ export interface ApiKey {
     key: string;
     description?: string;
     environment: string;
-    rate_limit?: string;
+    quota?: KeyQuota | null;
+    cost?: KeyCost | null;
     expires_at?: string;
     scopes: string[];
     created_at: string;
     updated_at: string;
     tags: string[];
     is_active: boolean;
+    rotation_interval_days?: number;
+    last_rotated_at?: string;
+    kind?: CredentialKind;
+    credential?: CredentialParts | null;
+    oauth?: OAuthClientConfig | null;
+    /** Values in other environments, by environment */
+    values?: Record<string, string>;
+    is_pinned?: boolean;
+    archived_at?: string | null;
+    last_used_at?: string | null;
+    /** Set on remote keys, whose value is read from a secret manager */
+    backend?: BackendRef | null;
+    /** Set on keys from listings, whose `key` and `values` are masked */
+    masked?: boolean;
+    /** Length of the real value of a masked key */
+    value_length?: number;
+}
+
+/** Error returned by every Tauri command */
+export interface KeyKeeperError {
+    code:
+        | 'VAULT_LOCKED'
+        | 'NOT_FOUND'
+        | 'CRYPTO_ERROR'
+        | 'IO_ERROR'
+        | 'VALIDATION_ERROR'
+        | 'CONFLICT'
+        | 'PERMISSION_DENIED'
+        | 'INTERNAL_ERROR';
+    message: string;
+}
+
+export function isKeyKeeperError(error: unknown): error is KeyKeeperError {
+    return typeof error === 'object' && error !== null && 'code' in error && 'message' in error;
+}
+
+/** Human readable message for a rejected command, whatever its shape */
+export function errorMessage(error: unknown): string {
+    if (isKeyKeeperError(error)) return error.message;
+    if (error instanceof Error) return error.message;
+    return String(error);
+}
+
+/** Quota from the key form's fields; no quota when both limits are empty */
+export function quotaFromForm(
+    requests: string,
+    window: QuotaWindow,
+    monthlySpendCap: string,
+    existing?: KeyQuota | null
+): KeyQuota | null {
+    const requestLimit = parseInt(requests, 10);
+    const spendCap = parseFloat(monthlySpendCap);
+    if (!(requestLimit > 0) && !(spendCap > 0)) return null;
+    return {
+        requests: requestLimit > 0 ? requestLimit : null,
+        window,
+        monthly_spend_cap: spendCap > 0 ? spendCap : null,
+        currency: existing?.currency ?? 'USD',
+        alert_thresholds: existing?.alert_thresholds ?? [80, 100],
+    };
+}
+
+/** Cost metadata from the key form's fields; none when both are empty */
+export function costFromForm(
+    estimatedMonthly: string,
+    billingAccount: string,
+    existing?: KeyCost | null
+): KeyCost | null {
+    const estimate = parseFloat(estimatedMonthly);
+    const account = billingAccount.trim();
+    if (!(estimate >= 0) && !account) return null;
+    return {
+        estimated_monthly: estimate >= 0 ? estimate : null,
+        currency: existing?.currency ?? 'USD',
+        billing_account: account || null,
+    };
 }
 
 export interface VaultStatus {
@@ -30,6 +108,7 @@ export interface EnvVariable {
 export interface DroppedEnvFile {
     path: string;
     project_path: string;
+    package_path?: string;
     file_name: string;
     keys: EnvVariable[];
 }
@@ -42,6 +121,7 @@ export interface ProjectEnvAssociation {
     created_at: string;
     last_accessed: string;
     is_active: boolean;
+    package_path?: string;
 }
 
 export interface PersistentSession {
@@ -55,11 +135,38 @@ export interface PersistentSession {
     is_active: boolean;
 }
 
+export interface LaunchRequest {
+    args: string[];
+    cwd: string;
+    deep_links: string[];
+    paths: string[];
+    received_at: string;
+}
+
+export type LeaseTarget = 'env_file' | 'token';
+
+export interface KeyLease {
+    id: string;
+    key_id: string;
+    target: LeaseTarget;
+    status: 'active' | 'redeemed' | 'expired' | 'revoked';
+    file_path?: string;
+    created_at: string;
+    expires_at: string;
+    ended_at?: string;
+}
+
+export interface LeaseGrant {
+    lease: KeyLease;
+    token?: string;
+}
+
 export interface VSCodeWorkspace {
     path: string;
     name: string;
     is_open: boolean;
     last_updated: string;
+    source?: 'extension' | 'detected';
 }
 
 // ================================
@@ -81,6 +188,7 @@ export interface Documentation {
     language: string; // "en" | "it" | "es" etc.
     is_favorite: boolean;
     search_keywords: string[];
+    attachments?: DocAttachment[];
 }
 
 export interface DocSection {
@@ -350,6 +458,8 @@ export interface GeneratedFile {
 
 export interface DetectionResult {
     provider: ApiProvider;
+    /** Environment the key's value tells, when a value was given */
+    environment?: string | null;
     confidence: number;
     matched_patterns: string[];
     detected_env_vars: string[];
@@ -378,6 +488,44 @@ export class TauriAPI {
         return await invoke('set_master_password', { password });
     }
 
+    /** Replace the master password of the unlocked vault, encrypting it again; rolled back if any step fails. */
+    static async changeMasterPassword(oldPassword: string, newPassword: string): Promise<void> {
+        return await invoke('change_master_password', { oldPassword, newPassword });
+    }
+
+    /** Encrypt the vault again under a fresh salt and key, keeping the password. */
+    static async rekeyVault(password: string): Promise<{ resealed_values: number; rekeyed_at: string }> {
+        return await invoke('rekey_vault', { password });
+    }
+
+    /** Strength and policy check of a new password; `userInputs` are emails or names to treat as guessable. */
+    static async estimatePasswordStrength(password: string, userInputs?: string[]): Promise<PasswordStrength> {
+        return await invoke('estimate_password_strength', { password, userInputs });
+    }
+
+    // Email delivery of recovery tokens and security alerts
+    static async getEmailSettings(): Promise<EmailSettings | null> {
+        return await invoke('get_email_settings');
+    }
+
+    /** Leaving the SMTP password as returned by `getEmailSettings` keeps the stored one. */
+    static async setEmailSettings(settings: EmailSettings): Promise<EmailSettings> {
+        return await invoke('set_email_settings', { settings });
+    }
+
+    static async deleteEmailSettings(): Promise<void> {
+        return await invoke('delete_email_settings');
+    }
+
+    /** Sends a test email; resolves with the addresses it reached. */
+    static async testEmailSettings(): Promise<string[]> {
+        return await invoke('test_email_settings');
+    }
+
+    static async getEmailDeliveries(limit?: number): Promise<EmailDelivery[]> {
+        return await invoke('get_email_deliveries', { limit });
+    }
+
     static async isVaultUnlocked(): Promise<boolean> {
         return await invoke('is_vault_unlocked');
     }
@@ -420,8 +568,47 @@ export class TauriAPI {
         return await invoke('delete_api_key', { id });
     }
 
-    static async searchApiKeys(query: string): Promise<ApiKey[]> {
-        return await invoke('search_api_keys', { query });
+    /**
+     * Decrypted value of a key. Without `masterPassword` the unlocked session
+     * authorizes it, subject to the `decrypt_key` confirmation policy.
+     */
+    static async getDecryptedApiKey(keyId: string, masterPassword?: string): Promise<string> {
+        return await invoke('get_decrypted_api_key', { keyId, masterPassword });
+    }
+
+    /** Full value of a key; listings only carry masked values. Recorded in the key's activity. */
+    static async revealApiKey(keyId: string, reason?: string): Promise<string> {
+        return await invoke('reveal_api_key', { keyId, reason });
+    }
+
+    // Development builds only
+    static async createDemoVault(targetDir: string, projectsRoot?: string): Promise<DemoVaultInfo> {
+        return await invoke('create_demo_vault', { targetDir, projectsRoot });
+    }
+
+    // Naming policies
+    static async listNamingPolicies(): Promise<NamingPolicy[]> {
+        return await invoke('list_naming_policies');
+    }
+
+    static async saveNamingPolicy(policy: NamingPolicy): Promise<NamingPolicy> {
+        return await invoke('save_naming_policy', { policy });
+    }
+
+    static async deleteNamingPolicy(policyId: string): Promise<void> {
+        return await invoke('delete_naming_policy', { policyId });
+    }
+
+    static async lintVaultNaming(): Promise<NamingLintEntry[]> {
+        return await invoke('lint_vault_naming');
+    }
+
+    static async bulkRenameKeys(renames: KeyRename[], updateEnvFiles: boolean): Promise<BulkRenameResult> {
+        return await invoke('bulk_rename_keys', { renames, updateEnvFiles });
+    }
+
+    static async searchApiKeys(query: string, includeArchived?: boolean): Promise<ApiKey[]> {
+        return await invoke('search_api_keys', { query, includeArchived });
     }
 
     // Backup & Export
@@ -461,12 +648,12 @@ export class TauriAPI {
         return await invoke('parse_and_register_env_file', { filePath });
     }
 
-    static async associateProjectWithEnv(projectPath: string, envPath: string, fileName: string): Promise<void> {
-        return await invoke('associate_project_with_env', { projectPath, envPath, fileName });
+    static async associateProjectWithEnv(projectPath: string, envPath: string, fileName: string, packagePath?: string): Promise<void> {
+        return await invoke('associate_project_with_env', { projectPath, envPath, fileName, packagePath });
     }
 
-    static async getProjectEnvAssociations(projectPath?: string): Promise<ProjectEnvAssociation[]> {
-        return await invoke('get_project_env_associations', { projectPath });
+    static async getProjectEnvAssociations(projectPath?: string, packagePath?: string): Promise<ProjectEnvAssociation[]> {
+        return await invoke('get_project_env_associations', { projectPath, packagePath });
     }
 
     static async activateProjectContext(projectPath: string): Promise<boolean> {
@@ -533,6 +720,30 @@ export class TauriAPI {
         return await invoke('hide_window');
     }
 
+    static async openWindow(kind: WindowKind): Promise<void> {
+        return await invoke('open_window', { kind });
+    }
+
+    static async closeWindow(kind: WindowKind): Promise<void> {
+        return await invoke('close_window', { kind });
+    }
+
+    static async focusWindow(kind: WindowKind): Promise<void> {
+        return await invoke('focus_window', { kind });
+    }
+
+    static async listWindows(): Promise<WindowInfo[]> {
+        return await invoke('list_windows');
+    }
+
+    static async openKeyInMainWindow(keyId: string): Promise<void> {
+        return await invoke('open_key_in_main_window', { keyId });
+    }
+
+    static async checkKeyEnvironment(value: string, environment: string): Promise<KeyEnvironmentCheck> {
+        return await invoke('check_key_environment', { value, environment });
+    }
+
     static async quitApplication(): Promise<void> {
         return await invoke('quit_application');
     }
@@ -570,7 +781,8 @@ export class TauriAPI {
             key: data.key || '',
             description: data.description,
             environment: data.environment || 'development',
-            rate_limit: data.rate_limit,
+            quota: data.quota,
+            cost: data.cost,
             expires_at: data.expires_at,
             scopes: data.scopes || [],
             created_at: now,
@@ -631,8 +843,8 @@ export class TauriAPI {
     // ===============================
 
     // Project CRUD Operations
-    static async createProject(name: string, description?: string, path?: string): Promise<any> {
-        return await invoke('create_project', { name, description, path });
+    static async createProject(name: string, description?: string, path?: string, templateId?: string): Promise<any> {
+        return await invoke('create_project', { name, description, path, templateId });
     }
 
     static async updateProject(id: string, name?: string, description?: string, settings?: any): Promise<any> {
@@ -648,12 +860,12 @@ export class TauriAPI {
     }
 
     // Key-Project Assignment
-    static async assignKeysToProject(projectId: string, keyIds: string[]): Promise<void> {
-        return await invoke('assign_keys_to_project', { projectId, keyIds });
+    static async assignKeysToProject(projectId: string, keyIds: string[], packagePath?: string): Promise<void> {
+        return await invoke('assign_keys_to_project', { projectId, keyIds, packagePath });
     }
 
-    static async getKeysByProject(projectId?: string): Promise<ApiKey[]> {
-        return await invoke('get_keys_by_project', { projectId });
+    static async getKeysByProject(projectId?: string, packagePath?: string): Promise<ApiKey[]> {
+        return await invoke('get_keys_by_project', { projectId, packagePath });
     }
 
     static async getUnassignedKeys(): Promise<ApiKey[]> {
@@ -665,8 +877,12 @@ export class TauriAPI {
     }
 
     // VSCode Auto-Sync Functions
-    static async syncKeyToEnvFile(keyId: string, projectPath: string, envFileName?: string): Promise<string> {
-        return await invoke('sync_key_to_env_file', { keyId, projectPath, envFileName });
+    static async syncKeyToEnvFile(keyId: string, projectPath: string, envFileName?: string, packagePath?: string): Promise<string> {
+        return await invoke('sync_key_to_env_file', { keyId, projectPath, envFileName, packagePath });
+    }
+
+    static async previewSyncKeyToEnvFile(keyId: string, projectPath: string, envFileName?: string, packagePath?: string): Promise<SyncPreview> {
+        return await invoke('preview_sync_key_to_env_file', { keyId, projectPath, envFileName, packagePath });
     }
 
     static async checkKeyInEnvFile(keyId: string, projectPath: string, envFileName?: string): Promise<boolean> {
@@ -677,563 +893,2398 @@ export class TauriAPI {
         return await invoke('get_env_file_suggestions', { projectPath });
     }
 
+    /** Ignore rules of a project; with a path, the rule deciding whether scans skip it */
+    static async getEffectiveIgnores(projectPath: string, path?: string): Promise<EffectiveIgnores> {
+        return await invoke('get_effective_ignores', { projectPath, path: path ?? null });
+    }
+
     static async autoSyncWorkspaceEnvFiles(workspacePath: string): Promise<string> {
         return await invoke('auto_sync_workspace_env_files', { workspacePath });
     }
 
-    // ✅ Event Listeners for real-time communication
-    static async onVaultStateChanged(callback: (isUnlocked: boolean) => void) {
-        return await listen('vault-state-changed', (event) => {
-            callback(event.payload as boolean);
-        });
+    static async previewAutoSyncWorkspaceEnvFiles(workspacePath: string): Promise<SyncPreview[]> {
+        return await invoke('preview_auto_sync_workspace_env_files', { workspacePath });
     }
 
-    static async onAuthStateChanged(callback: (state: any) => void) {
-        return await listen('auth-state-changed', (event) => {
-            callback(event.payload);
-        });
+    static async listEnvFileChanges(projectPath: string): Promise<EnvFileChange[]> {
+        return await invoke('list_env_file_changes', { projectPath });
     }
 
-    static async onApiKeysChanged(callback: (keys: ApiKey[]) => void) {
-        return await listen('api-keys-changed', (event) => {
-            callback(event.payload as ApiKey[]);
-        });
+    static async revertEnvFileChange(changeId: string): Promise<EnvFileChange> {
+        return await invoke('revert_env_file_change', { changeId });
     }
 
-    static async onUserAccountChanged(callback: (account: any) => void) {
-        return await listen('user-account-changed', (event) => {
-            callback(event.payload);
-        });
+    static async resolveEnvReferences(envFilePath?: string, variables?: Record<string, string>, environment?: string): Promise<ResolvedEnv> {
+        return await invoke('resolve_env_references', { envFilePath, variables, environment });
     }
 
-    // Native storage commands
-    static async keyringSet(service: string, account: string, password: string): Promise<void> {
-        return await invoke('keyring_set', { service, account, password });
+    static async listPendingApprovals(): Promise<ApprovalRequest[]> {
+        return await invoke('list_pending_approvals');
     }
 
-    static async keyringGet(service: string, account: string): Promise<string> {
-        return await invoke('keyring_get', { service, account });
+    static async respondToApproval(requestId: string, decision: ApprovalDecision): Promise<void> {
+        return await invoke('respond_to_approval', { requestId, decision });
     }
 
-    static async keyringDelete(service: string, account: string): Promise<void> {
-        return await invoke('keyring_delete', { service, account });
+    static async listApprovalRules(): Promise<ApprovalRule[]> {
+        return await invoke('list_approval_rules');
     }
 
-    static async getDeviceInfo(): Promise<any> {
-        return await invoke('get_device_info');
+    static async revokeApprovalRule(ruleId: string): Promise<void> {
+        return await invoke('revoke_approval_rule', { ruleId });
     }
 
-    static async setupAutoStart(enabled: boolean): Promise<void> {
-        return await invoke('setup_auto_start', { enabled });
+    static async onApprovalRequested(callback: (request: ApprovalRequest) => void) {
+        return await listen('approval-requested', (event) => {
+            callback(event.payload as ApprovalRequest);
+        });
     }
 
-    static async disableAutoStart(): Promise<void> {
-        return await invoke('disable_auto_start');
+    static async onApprovalExpired(callback: (requestId: string) => void) {
+        return await listen('approval-expired', (event) => {
+            callback(event.payload as string);
+        });
     }
 
-    static async isAutoStartEnabled(): Promise<boolean> {
-        return await invoke('is_auto_start_enabled');
+    // Called from the confirmation window itself
+    static async getConfirmationRequest(): Promise<ConfirmationRequest> {
+        return await invoke('get_confirmation_request');
     }
 
-    static async showNotification(title: string, body: string): Promise<void> {
-        return await invoke('show_notification', { title, body });
+    static async respondToConfirmation(approved: boolean): Promise<void> {
+        return await invoke('respond_to_confirmation', { approved });
     }
 
-    // ===============================
-    //  ML ENGINE INTEGRATION
-    // ===============================
+    static async getConfirmationPolicies(): Promise<ConfirmationPolicyEntry[]> {
+        return await invoke('get_confirmation_policies');
+    }
 
-    // ML Configuration and Prediction interfaces
-    static async initializeMLEngine(config?: any): Promise<string> {
-        return await invoke('initialize_ml_engine', { request: { config } });
+    static async setConfirmationPolicy(action: RiskyAction, policy: ConfirmationPolicy): Promise<void> {
+        return await invoke('set_confirmation_policy', { action, policy });
     }
 
-    static async analyzeContextML(context: any, availableKeys: string[]): Promise<any> {
-        return await invoke('analyze_context_ml', { request: { context, available_keys: availableKeys } });
+    static async setKeyRotationSchedule(keyId: string, intervalDays: number | null): Promise<void> {
+        return await invoke('set_key_rotation_schedule', { keyId, intervalDays });
     }
 
-    static async recordMLUsage(keyId: string, context: any, success: boolean): Promise<string> {
-        return await invoke('record_ml_usage', { record: { key_id: keyId, context, success } });
+    static async markKeyRotated(keyId: string): Promise<void> {
+        return await invoke('mark_key_rotated', { keyId });
     }
 
-    static async getMLStats(): Promise<any> {
-        return await invoke('get_ml_stats');
+    static async getKeyRotationStatus(): Promise<KeyRotationStatus[]> {
+        return await invoke('get_key_rotation_status');
     }
 
-    static async checkMLStatus(): Promise<boolean> {
-        return await invoke('check_ml_status');
+    static async listRotationReminders(includeResolved?: boolean): Promise<RotationReminder[]> {
+        return await invoke('list_rotation_reminders', { includeResolved });
     }
 
-    static async reinitializeMLEngine(config?: any): Promise<string> {
-        return await invoke('reinitialize_ml_engine', { request: { config } });
+    static async dismissRotationReminder(reminderId: string): Promise<void> {
+        return await invoke('dismiss_rotation_reminder', { reminderId });
     }
 
-    static async getMLConfig(): Promise<any> {
-        return await invoke('get_ml_config');
+    static async scanGitHistory(projectPath: string, depth?: number): Promise<GitHistoryScan> {
+        return await invoke('scan_git_history', { projectPath, depth });
     }
 
-    static async detectContext(): Promise<any> {
-        return await invoke('detect_context');
+    static async scanInfrastructureFiles(projectPath: string): Promise<IacScan> {
+        return await invoke('scan_infrastructure_files', { projectPath });
     }
 
-    // ===============================
-    //  LLM PROXY INTEGRATION
-    // ===============================
+    /** `variables` maps Terraform variable names to key ids; defaults to the project's keys */
+    static async generateTfvars(
+        projectPath: string,
+        environment: string,
+        variables?: Record<string, string>,
+        outputPath?: string
+    ): Promise<TfvarsOutput> {
+        return await invoke('generate_tfvars', { projectPath, environment, variables, outputPath });
+    }
 
-    static async processWithLLM(request: LLMRequest): Promise<LLMResponse> {
-        return await invoke('process_with_llm', {
-            prompt: request.prompt,
-            context: request.context || null,
-            config: request.config
-        });
+    static async getSecurityReport(): Promise<SecurityReport> {
+        return await invoke('get_security_report');
     }
 
-    static async clearLLMCache(): Promise<string> {
-        return await invoke('clear_llm_cache');
+    static async startAccessReview(dueInDays?: number, intervalDays?: number): Promise<AccessReview> {
+        return await invoke('start_access_review', { dueInDays, intervalDays });
     }
 
-    static async getLLMCacheStats(): Promise<any> {
-        return await invoke('get_llm_cache_stats');
+    static async getAccessReview(reviewId?: string): Promise<AccessReview | null> {
+        return await invoke('get_access_review', { reviewId });
     }
 
-    // ===============================
-    //  API GENERATOR INTEGRATION
-    // ===============================
+    static async listAccessReviews(): Promise<AccessReview[]> {
+        return await invoke('list_access_reviews');
+    }
 
-    static async getAPIProviders(): Promise<ApiProvider[]> {
-        return await invoke('get_api_providers');
+    static async getAccessReviewStatus(): Promise<AccessReviewStatus> {
+        return await invoke('get_access_review_status');
     }
 
-    static async scrapeAPIDocumentation(providerId: string, docsUrl: string): Promise<any> {
-        return await invoke('scrape_api_documentation', { provider_id: providerId, docs_url: docsUrl });
+    static async attestAccess(
+        reviewId: string,
+        itemId: string,
+        attestation: Attestation,
+        note?: string
+    ): Promise<AccessReview> {
+        return await invoke('attest_access', { reviewId, itemId, attestation, note });
     }
 
-    static async generateAPIConfiguration(request: any): Promise<any> {
-        return await invoke('generate_api_configuration', { request });
+    static async createVaultSnapshot(options?: SnapshotOptions): Promise<VaultSnapshot> {
+        return await invoke('create_vault_snapshot', { options });
     }
 
-    static async detectProviderFromEnv(envVarName: string): Promise<any> {
-        return await invoke('detect_provider_from_env', { env_var_name: envVarName });
+    static async getVaultStateAt(timestamp: string): Promise<VaultStateAt> {
+        return await invoke('get_vault_state_at', { timestamp });
     }
 
-    static async previewGeneratedConfig(request: any): Promise<any> {
-        return await invoke('preview_generated_config', { request });
+    static async initiateIncident(title: string, scope?: IncidentScope): Promise<Incident> {
+        return await invoke('initiate_incident', { title, scope });
     }
 
-    static async generateBetterAuthConfig(providerId: string, env_vars: Record<string, string>): Promise<any> {
-        return await invoke('generate_better_auth_config', { provider_id: providerId, env_vars });
+    static async getIncident(incidentId?: string): Promise<Incident | null> {
+        return await invoke('get_incident', { incidentId });
     }
 
-    static async generateOpenAIConfig(env_vars: Record<string, string>): Promise<any> {
-        return await invoke('generate_openai_config', { env_vars });
+    static async listIncidents(): Promise<Incident[]> {
+        return await invoke('list_incidents');
     }
 
-    static async getProviderTemplates(): Promise<any> {
-        return await invoke('get_provider_templates');
+    static async updateIncidentItem(
+        incidentId: string,
+        keyId: string,
+        status: IncidentItemStatus,
+        note?: string
+    ): Promise<Incident> {
+        return await invoke('update_incident_item', { incidentId, keyId, status, note });
     }
 
-    // ===============================
-    //  NATIVE DOCUMENTATION STORE INTEGRATION
-    // ===============================
+    static async closeIncident(incidentId: string, summary?: string): Promise<Incident> {
+        return await invoke('close_incident', { incidentId, summary });
+    }
 
-    static async getNativeDocumentation(): Promise<Documentation[]> {
-        return await invoke('get_documentation');
+    static async addRemoteKey(apiKey: ApiKey, backend: BackendRef): Promise<void> {
+        return await invoke('add_remote_key', { apiKey, backend });
     }
 
-    static async createNativeDocumentation(doc: Omit<Documentation, 'id' | 'created_at' | 'updated_at'>): Promise<Documentation> {
-        return await invoke('create_documentation', { doc });
+    static async testRemoteKey(keyId: string): Promise<void> {
+        return await invoke('test_remote_key', { keyId });
     }
 
-    static async updateNativeDocumentation(id: string, updates: Partial<Documentation>): Promise<void> {
-        return await invoke('update_documentation', { id, updates });
+    static async syncAllProjects(options?: SyncAllOptions): Promise<SyncAllSummary> {
+        return await invoke('sync_all_projects', { options });
     }
 
-    static async deleteNativeDocumentation(id: string): Promise<void> {
-        return await invoke('delete_documentation', { id });
+    static async onProjectSyncProgress(callback: (progress: ProjectSyncProgress) => void) {
+        return await listen('project-sync-progress', (event) => {
+            callback(event.payload as ProjectSyncProgress);
+        });
     }
 
-    static async searchNativeDocumentation(query: string, projectId?: string, docType?: string): Promise<any> {
-        return await invoke('search_native_documentation', { query, project_id: projectId, doc_type: docType });
+    /** Returns the stored key with its values masked */
+    static async importCredentialFiles(request: ImportCredentialRequest): Promise<ApiKey> {
+        return await invoke('import_credential_files', { request });
     }
 
-    static async getDocumentationByProject(projectId: string): Promise<Documentation[]> {
-        return await invoke('get_documentation_by_project', { project_id: projectId });
+    static async exportCredentialFiles(keyId: string, directory: string): Promise<string[]> {
+        return await invoke('export_credential_files', { keyId, directory });
     }
 
-    static async getDocumentationByProvider(providerId: string): Promise<Documentation[]> {
-        return await invoke('get_documentation_by_provider', { provider_id: providerId });
+    /** Returns the stored client with its secret masked */
+    static async addOAuthClient(request: AddOAuthClientRequest): Promise<ApiKey> {
+        return await invoke('add_oauth_client', { request });
     }
 
-    static async toggleDocumentationFavorite(id: string): Promise<void> {
-        return await invoke('toggle_documentation_favorite', { id });
+    static async updateOAuthClient(keyId: string, config: OAuthClientConfig): Promise<void> {
+        return await invoke('update_oauth_client', { keyId, config });
     }
 
-    static async scrapeAndSaveDocumentation(url: string, title?: string): Promise<Documentation> {
-        return await invoke('scrape_and_save_documentation', { url, title });
+    static async getAccessToken(credentialId: string, forceRefresh?: boolean): Promise<AccessToken> {
+        return await invoke('get_access_token', { credentialId, forceRefresh });
     }
 
-    // ===============================
-    //  ENHANCED DOCUMENTATION COMMANDS
-    // ===============================
+    static async setKeyEnvironmentValue(keyId: string, environment: string, value: string): Promise<void> {
+        return await invoke('set_key_environment_value', { keyId, environment, value });
+    }
 
-    static async addProviderDocumentation(providerId: string, docsUrl: string): Promise<any> {
-        return await invoke('add_provider_documentation', { request: { provider_id: providerId, docs_url: docsUrl } });
+    static async removeKeyEnvironmentValue(keyId: string, environment: string): Promise<void> {
+        return await invoke('remove_key_environment_value', { keyId, environment });
     }
 
-    static async getProviderDocumentation(providerId: string): Promise<any> {
-        return await invoke('get_provider_documentation', { provider_id: providerId });
+    static async pinApiKey(keyId: string): Promise<void> {
+        return await invoke('pin_api_key', { keyId });
     }
 
-    static async searchDocumentationAdvanced(query: string, providerId?: string): Promise<any> {
-        return await invoke('search_documentation', { request: { query, provider_id: providerId } });
+    static async unpinApiKey(keyId: string): Promise<void> {
+        return await invoke('unpin_api_key', { keyId });
     }
 
-    static async getDocumentationById(docId: string): Promise<any> {
-        return await invoke('get_documentation_by_id', { doc_id: docId });
+    static async getPinnedKeys(): Promise<ApiKey[]> {
+        return await invoke('get_pinned_keys');
     }
 
-    static async updateProviderDocumentation(providerId: string, docsUrl: string): Promise<any> {
-        return await invoke('update_provider_documentation', { provider_id: providerId, docs_url: docsUrl });
+    static async reorderPinnedKeys(keyIds: string[]): Promise<void> {
+        return await invoke('reorder_pinned_keys', { keyIds });
     }
 
-    static async removeProviderDocumentation(providerId: string): Promise<any> {
-        return await invoke('remove_provider_documentation', { provider_id: providerId });
+    static async bulkUpdateKeys(keyIds: string[], patch: KeyPatch): Promise<BulkResult> {
+        return await invoke('bulk_update_keys', { keyIds, patch });
     }
 
-    static async getIndexedProviders(): Promise<string[]> {
-        return await invoke('get_indexed_providers');
+    static async bulkDeleteKeys(keyIds: string[]): Promise<BulkResult> {
+        return await invoke('bulk_delete_keys', { keyIds });
     }
 
-    static async autoIndexProviderDocs(): Promise<any> {
-        return await invoke('auto_index_provider_docs');
+    static async archiveApiKey(keyId: string): Promise<void> {
+        return await invoke('archive_api_key', { keyId });
     }
 
-    static async getContextDocumentationSuggestions(providerIds: string[], contextKeywords: string[]): Promise<any> {
-        return await invoke('get_context_documentation_suggestions', { provider_ids: providerIds, context_keywords: contextKeywords });
+    static async restoreApiKey(keyId: string): Promise<void> {
+        return await invoke('restore_api_key', { keyId });
     }
 
-    // ===============================
-    //  LLM-ENHANCED ML ENGINE METHODS
-    // ===============================
+    static async getArchivedKeys(): Promise<ApiKey[]> {
+        return await invoke('get_archived_keys');
+    }
 
-    // Documentation Generation via LLM
-    static async generateDocumentation(request: DocumentationGenerationRequest): Promise<GeneratedDocumentation> {
-        return await invoke('generate_documentation', { request });
+    static async getArchivePolicy(): Promise<ArchivePolicy> {
+        return await invoke('get_archive_policy');
     }
 
-    static async generateUsageExamples(provider: string, apiKeyFormat: string): Promise<CodeExample[]> {
-        return await invoke('generate_usage_examples', { provider, api_key_format: apiKeyFormat });
+    static async setArchivePolicy(policy: ArchivePolicy): Promise<void> {
+        return await invoke('set_archive_policy', { policy });
     }
 
-    static async generateConfigurationTemplate(request: ConfigTemplateRequest): Promise<GeneratedConfigTemplate> {
-        return await invoke('generate_config_template', { request });
+    static async getRecentActivity(filter?: ActivityFilter): Promise<RecentActivity[]> {
+        return await invoke('get_recent_activity', { filter });
     }
 
-    // Enhanced ML Analysis with LLM Insights
-    static async getEnhancedMLPrediction(context: ContextInfo, availableKeys: string[]): Promise<EnhancedMLPrediction> {
-        return await invoke('get_enhanced_ml_prediction', {
-            request: { context, available_keys: availableKeys }
-        });
+    static async getRetentionPolicy(): Promise<RetentionPolicy> {
+        return await invoke('get_retention_policy');
     }
 
-    static async getContextualDocumentationSuggestions(provider: string, context: ContextInfo): Promise<DocumentationSuggestion[]> {
-        return await invoke('get_contextual_doc_suggestions', { provider, context });
+    static async setRetentionPolicy(policy: RetentionPolicy): Promise<void> {
+        return await invoke('set_retention_policy', { policy });
     }
 
-    static async getConfigurationRecommendations(provider: string, context: ContextInfo): Promise<ConfigurationRecommendation[]> {
-        return await invoke('get_config_recommendations', { provider, context });
+    static async runRetentionNow(): Promise<RetentionReport> {
+        return await invoke('run_retention_now');
     }
 
-    // LLM Engine Management
-    static async initializeLLMEngine(config: LLMEngineConfig): Promise<string> {
-        return await invoke('initialize_llm_engine', { config });
+    static async getRetentionReport(): Promise<RetentionReport | null> {
+        return await invoke('get_retention_report');
     }
 
-    static async isLLMEngineLoaded(): Promise<boolean> {
-        return await invoke('is_llm_engine_loaded');
+    /** Counter that moves whenever the vault changes */
+    static async getVaultGeneration(): Promise<number> {
+        return await invoke('get_vault_generation');
     }
 
-    static async getLLMEngineStatus(): Promise<{ loaded: boolean; model_path?: string; config?: LLMEngineConfig }> {
-        return await invoke('get_llm_engine_status');
+    static async getQuotaStatus(keyId: string): Promise<QuotaStatus> {
+        return await invoke('get_quota_status', { keyId });
     }
 
-    static async unloadLLMEngine(): Promise<string> {
-        return await invoke('unload_llm_engine');
+    /** Estimated and actual costs of a month (YYYY-MM), the current one by default */
+    static async getCostOverview(period?: string): Promise<CostOverview> {
+        return await invoke('get_cost_overview', { period: period ?? null });
     }
 
-    // Configuration Template Management
-    static async previewConfigurationTemplate(request: ConfigTemplateRequest): Promise<{ preview: string; files: ConfigTemplateFile[] }> {
-        return await invoke('preview_config_template', { request });
+    static async recordCostEntry(entry: CostEntryInput): Promise<CostEntry> {
+        return await invoke('record_cost_entry', { entry });
     }
 
-    static async saveConfigurationTemplate(template: GeneratedConfigTemplate, outputPath: string): Promise<string> {
-        return await invoke('save_config_template', { template, output_path: outputPath });
+    // Settings inheritance: organization defaults -> template -> project
+    static async getOrganizationSettings(): Promise<ProjectSettings> {
+        return await invoke('get_organization_settings');
     }
 
-    // Documentation Enhancement
-    static async enhanceDocumentationWithLLM(docId: string, content: string): Promise<{ enhanced_content: string; sections: DocumentationSection[] }> {
-        return await invoke('enhance_documentation_with_llm', { doc_id: docId, content });
+    static async updateOrganizationSettings(settings: ProjectSettings): Promise<void> {
+        return await invoke('update_organization_settings', { settings });
     }
 
-    static async summarizeDocumentation(content: string, maxLength?: number): Promise<string> {
-        return await invoke('summarize_documentation', { content, max_length: maxLength });
+    static async listSettingsTemplates(): Promise<SettingsTemplate[]> {
+        return await invoke('list_settings_templates');
     }
 
-    static async extractKeyInformation(content: string, extractionType: 'authentication' | 'endpoints' | 'examples' | 'configuration'): Promise<DocumentationSection[]> {
-        return await invoke('extract_key_information', { content, extraction_type: extractionType });
+    static async saveSettingsTemplate(template: SettingsTemplateInput): Promise<SettingsTemplate> {
+        return await invoke('save_settings_template', { template });
     }
 
-    // ===============================
-    //  CHAT SYSTEM COMMANDS
-    // ===============================
+    static async deleteSettingsTemplate(id: string): Promise<void> {
+        return await invoke('delete_settings_template', { id });
+    }
 
-    static async createChatSession(request: { userId: string; title: string; description?: string; contextLibraries: string[] }): Promise<{ sessionId: string; title: string; createdAt: string }> {
-        return await invoke('create_chat_session', { request });
+    static async applySettingsTemplate(projectId: string, templateId: string): Promise<Project> {
+        return await invoke('apply_settings_template', { projectId, templateId });
     }
 
-    static async sendChatMessage(request: {
-        sessionId: string;
-        message: string;
-        contextLibraries: string[];
-        userPreferences: {
-            preferredLanguage: string;
-            preferredFramework: string;
-            codeStyle: string;
-            detailLevel: string;
-            includeExamples: boolean;
-            includeTests: boolean;
-            securityFocused: boolean;
-        };
-        generationContext?: {
-            targetFramework: string;
-            targetLanguage: string;
-            projectContext?: string;
-            existingCode?: string;
-            requirements: string[];
-            constraints: string[];
-        };
-        includeCodeGeneration: boolean;
-    }): Promise<any> {
-        return await invoke('send_chat_message', { request });
+    static async getEffectiveProjectSettings(projectId: string): Promise<EffectiveProjectSettings> {
+        return await invoke('get_effective_project_settings', { projectId });
     }
 
-    static async getChatMessages(sessionId: string): Promise<any[]> {
-        return await invoke('get_chat_messages', { sessionId });
+    static async listProjectGroups(): Promise<ProjectGroup[]> {
+        return await invoke('list_project_groups');
     }
 
-    static async getUserChatSessions(userId: string): Promise<any[]> {
-        return await invoke('get_user_chat_sessions', { userId });
+    static async createProjectGroup(name: string, kind?: GroupKind, parentId?: string): Promise<ProjectGroup> {
+        return await invoke('create_project_group', { name, kind, parentId });
     }
 
-    static async searchDocumentationForChat(request: {
-        query: string;
-        provider_id?: string;
-        content_types?: string[];
-        max_results?: number;
-        min_similarity?: number;
-    }): Promise<any[]> {
-        return await invoke('search_documentation_for_chat', { request });
+    static async renameProjectGroup(id: string, name: string): Promise<ProjectGroup> {
+        return await invoke('rename_project_group', { id, name });
     }
 
-    static async generateIntegration(request: {
-        session_id: string;
-        provider_name: string;
-        framework: string;
-        language: string;
-        requirements: string[];
-        constraints: string[];
-        project_context?: string;
-        existing_code?: string;
-    }): Promise<any> {
-        return await invoke('generate_integration', { request });
+    static async deleteProjectGroup(id: string): Promise<void> {
+        return await invoke('delete_project_group', { id });
     }
 
-    static async archiveChatSession(sessionId: string): Promise<boolean> {
-        return await invoke('archive_chat_session', { sessionId });
+    /** Pass no group to take the project out of its group */
+    static async moveProjectToGroup(projectId: string, groupId?: string): Promise<Project> {
+        return await invoke('move_project_to_group', { projectId, groupId: groupId ?? null });
     }
 
-    static async deleteChatSession(sessionId: string): Promise<boolean> {
-        return await invoke('delete_chat_session', { sessionId });
+    // Hardware unlock: a device key wrapped by the Secure Enclave or TPM
+    static async getHardwareUnlockStatus(): Promise<HardwareUnlockStatus> {
+        return await invoke('get_hardware_unlock_status');
     }
 
-    static async getChatStatistics(): Promise<Record<string, number>> {
-        return await invoke('get_chat_statistics');
+    /** Uses the machine's secure element, or the OS keyring when it has none */
+    static async enableHardwareUnlock(backend?: HardwareBackend): Promise<HardwareUnlockStatus> {
+        return await invoke('enable_hardware_unlock', { backend: backend ?? null });
     }
 
-    static async updateSessionPreferences(sessionId: string, preferences: {
-        preferredLanguage: string;
-        preferredFramework: string;
-        codeStyle: string;
-        detailLevel: string;
-        includeExamples: boolean;
-        includeTests: boolean;
-        securityFocused: boolean;
-    }): Promise<boolean> {
-        return await invoke('update_session_preferences', { sessionId, preferences });
+    static async disableHardwareUnlock(): Promise<HardwareUnlockStatus> {
+        return await invoke('disable_hardware_unlock');
     }
 
-    static async getAvailableDocumentationLibraries(): Promise<any[]> {
-        return await invoke('get_available_documentation_libraries');
+    static async unlockWithHardwareKey(): Promise<boolean> {
+        return await invoke('unlock_with_hardware_key');
     }
 
-    static async exportChatSession(sessionId: string, format: 'json' | 'markdown' | 'text'): Promise<string> {
-        return await invoke('export_chat_session', { sessionId, format });
+    static async listClients(): Promise<RegisteredClient[]> {
+        return await invoke('list_clients');
     }
 
-    // ===============================
-    //  DOCUMENTATION LIBRARY COMMANDS
-    // ===============================
+    static async approveClient(clientId: string): Promise<RegisteredClient> {
+        return await invoke('approve_client', { clientId });
+    }
 
-    static async addDocumentationFromUrl(request: {
-        provider_id: string;
-        provider_name: string;
-        provider_category: string;
-        docs_url: string;
-        description?: string;
-        tags: string[];
-        version?: string;
-    }): Promise<string> {
-        return await invoke('add_documentation_from_url', { request });
+    static async revokeClient(clientId: string): Promise<void> {
+        return await invoke('revoke_client', { clientId });
     }
 
-    static async addManualDocumentation(request: {
-        provider_id: string;
-        provider_name: string;
-        title: string;
-        content: string;
-        section_path: string[];
-        content_type: string;
-        tags: string[];
-        importance_score?: number;
-    }): Promise<string> {
-        return await invoke('add_manual_documentation', { request });
+    static async onClientRegistrationRequested(callback: (client: RegisteredClient) => void) {
+        return await listen('client-registration-requested', (event) => {
+            callback(event.payload as RegisteredClient);
+        });
     }
 
-    static async searchDocumentationLibrary(request: {
-        query: string;
-        library_ids?: string[];
-        provider_ids?: string[];
-        content_types?: string[];
-        section_filter?: string[];
-        min_similarity?: number;
-        max_results?: number;
-        include_metadata?: boolean;
-        boost_recent?: boolean;
-    }): Promise<any[]> {
-        return await invoke('search_documentation_library', { request });
+    // ✅ Event Listeners for real-time communication
+    static async onVaultStateChanged(callback: (isUnlocked: boolean) => void) {
+        return await listen('vault-state-changed', (event) => {
+            callback(event.payload as boolean);
+        });
     }
 
-    static async getDocumentationLibraries(): Promise<any[]> {
-        return await invoke('get_documentation_libraries');
+    static async onVaultLocked(callback: (event: LockEvent) => void) {
+        return await listen('vault-locked', (event) => {
+            callback(event.payload as LockEvent);
+        });
     }
 
-    static async getDocumentationLibrary(library_id: string): Promise<any | null> {
-        return await invoke('get_documentation_library', { library_id });
+    /** Every state change published on the backend's event bus */
+    static async onVaultEvent(callback: (event: VaultEvent) => void) {
+        return await listen('vault-event', (event) => {
+            callback(event.payload as VaultEvent);
+        });
     }
 
-    static async getLibraryChunks(library_id: string, offset?: number, limit?: number): Promise<any[]> {
-        return await invoke('get_library_chunks', { library_id, offset, limit });
+    // Inactivity lock
+    static async recordActivity(): Promise<void> {
+        return await invoke('record_activity');
     }
 
-    static async updateDocumentationLibrary(library_id: string, name?: string, description?: string, tags?: string[]): Promise<boolean> {
-        return await invoke('update_documentation_library', { library_id, name, description, tags });
+    static async getLockState(): Promise<LockEvent> {
+        return await invoke('get_lock_state');
     }
 
-    static async deleteDocumentationLibrary(library_id: string): Promise<boolean> {
-        return await invoke('delete_documentation_library', { library_id });
+    static async onAuthStateChanged(callback: (state: any) => void) {
+        return await listen('auth-state-changed', (event) => {
+            callback(event.payload);
+        });
     }
 
-    static async refreshDocumentationLibrary(library_id: string): Promise<boolean> {
-        return await invoke('refresh_documentation_library', { library_id });
+    static async onApiKeysChanged(callback: (keys: ApiKey[]) => void) {
+        return await listen('api-keys-changed', (event) => {
+            callback(event.payload as ApiKey[]);
+        });
     }
 
-    static async getLibraryStatistics(): Promise<{
-        total_libraries: number;
-        total_chunks: number;
-        total_embeddings: number;
-        libraries_by_provider: Record<string, number>;
-        chunks_by_content_type: Record<string, number>;
-        average_chunk_size: number;
-        last_updated: string;
-    }> {
-        return await invoke('get_library_statistics');
+    static async onUserAccountChanged(callback: (account: any) => void) {
+        return await listen('user-account-changed', (event) => {
+            callback(event.payload);
+        });
     }
 
-    static async bulkImportDocumentation(request: {
-        provider_id: string;
-        provider_name: string;
-        documents: {
-            title: string;
-            content: string;
-            url?: string;
-            section_path: string[];
-            content_type: string;
-            tags: string[];
-        }[];
-    }): Promise<string> {
-        return await invoke('bulk_import_documentation', { request });
+    static async onInstanceLaunch(callback: (request: LaunchRequest) => void) {
+        return await listen('instance-launch', (event) => {
+            callback(event.payload as LaunchRequest);
+        });
     }
 
-    static async exportDocumentationLibrary(library_id: string, format: 'json' | 'markdown' | 'csv'): Promise<string> {
-        return await invoke('export_documentation_library', { library_id, format });
+    static async takePendingLaunches(): Promise<LaunchRequest[]> {
+        return await invoke('take_pending_launches');
     }
 
-    static async getDocumentationChunk(chunk_id: string): Promise<any | null> {
-        return await invoke('get_documentation_chunk', { chunk_id });
+    // Logs
+    static async getRecentLogs(level?: string, module?: string, limit?: number): Promise<LogEntry[]> {
+        return await invoke('get_recent_logs', { level, module, limit });
     }
 
-    static async updateDocumentationChunk(chunk_id: string, title?: string, content?: string, tags?: string[], importance_score?: number): Promise<boolean> {
-        return await invoke('update_documentation_chunk', { chunk_id, title, content, tags, importance_score });
+    static async getHttpRequestLog(path?: string, errorsOnly?: boolean, limit?: number): Promise<HttpRequestEntry[]> {
+        return await invoke('get_http_request_log', { path, errorsOnly, limit });
     }
 
-    static async deleteDocumentationChunk(chunk_id: string): Promise<boolean> {
-        return await invoke('delete_documentation_chunk', { chunk_id });
+    // keykeeper.toml
+    static async getConfig(): Promise<ConfigView> {
+        return await invoke('get_config');
     }
 
-    static async validateDocumentationUrl(url: string): Promise<boolean> {
-        return await invoke('validate_documentation_url', { url });
+    static async setConfig(config: AppConfig): Promise<ConfigView> {
+        return await invoke('set_config', { config });
     }
 
-    // LLM Configuration Commands
-    static async setEnvVar(key: string, value: string): Promise<boolean> {
-        return await invoke('set_env_var', { key, value });
+    static async getAuditSinkStatus(): Promise<AuditSinkStatus[]> {
+        return await invoke('get_audit_sink_status');
     }
 
-    static async configureLLMProvider(config: {
-        provider: string;
-        model: string;
-        temperature?: number;
-        maxTokens?: number;
-    }): Promise<boolean> {
-        return await invoke('configure_llm_provider', { config });
+    static async getLegacyTcpUsage(): Promise<LegacyRouteUsage[]> {
+        return await invoke('get_legacy_tcp_usage');
     }
 
-    static async testLLMConnection(provider: string): Promise<string> {
-        return await invoke('test_llm_connection', { provider });
+    // Background tasks
+    static async listBackgroundTasks(): Promise<BackgroundTaskInfo[]> {
+        return await invoke('list_background_tasks');
     }
 
-    static async checkOllamaStatus(): Promise<{
-        is_running: boolean;
-        models: { name: string; size: number; modified: string }[];
-        server_url: string;
-    }> {
-        return await invoke('check_ollama_status');
+    static async cancelBackgroundTask(name: string): Promise<boolean> {
+        return await invoke('cancel_background_task', { name });
     }
 
-    static async pullOllamaModel(model: string): Promise<string> {
-        return await invoke('pull_ollama_model_command', { model });
+    // Health
+    static async getHealthReport(): Promise<HealthReport> {
+        return await invoke('get_health_report');
     }
-}
 
-// Types for biometric and user management
-export interface UserPreferences {
-    theme: string;
-    language: string;
-    auto_lock_timeout: number;
-    clipboard_clear_timeout: number;
-    show_notifications: boolean;
-    audit_logging: boolean;
-    biometric_unlock: boolean;
-    auto_backup: boolean;
-    encryption_level: string;
-} 
\ No newline at end of file
+    // Crash reports and diagnostics
+    static async takeCrashReport(): Promise<CrashReport | null> {
+        return await invoke('take_crash_report');
+    }
+
+    static async exportDiagnostics(exportPath?: string): Promise<DiagnosticsBundle> {
+        return await invoke('export_diagnostics', { exportPath });
+    }
+
+    // Key leases
+    static async leaseKey(keyId: string, ttlSeconds: number, target: LeaseTarget): Promise<LeaseGrant> {
+        return await invoke('lease_key', { keyId, ttlSeconds, target });
+    }
+
+    static async listLeases(): Promise<KeyLease[]> {
+        return await invoke('list_leases');
+    }
+
+    static async revokeLease(leaseId: string): Promise<KeyLease> {
+        return await invoke('revoke_lease', { leaseId });
+    }
+
+    // Native storage commands
+    static async keyringSet(service: string, account: string, password: string): Promise<void> {
+        return await invoke('keyring_set', { service, account, password });
+    }
+
+    static async keyringGet(service: string, account: string): Promise<string> {
+        return await invoke('keyring_get', { service, account });
+    }
+
+    static async keyringDelete(service: string, account: string): Promise<void> {
+        return await invoke('keyring_delete', { service, account });
+    }
+
+    /** Keyring entries KeyKeeper wrote, for every vault and profile */
+    static async listKeyringEntries(): Promise<KeyringEntry[]> {
+        return await invoke('list_keyring_entries');
+    }
+
+    /**
+     * Delete this profile's entries of another vault, or without a vault ID
+     * forget entries that no longer exist. Returns how many were removed.
+     */
+    static async cleanupKeyringEntries(vaultId?: string): Promise<number> {
+        return await invoke('cleanup_keyring_entries', { vaultId: vaultId ?? null });
+    }
+
+    /** Where keyring entries are kept; `refresh` probes the OS keyring again */
+    static async getKeystoreBackend(refresh?: boolean): Promise<KeystoreStatus> {
+        return await invoke('get_keystore_backend', { refresh: refresh ?? null });
+    }
+
+    static async getDeviceInfo(): Promise<any> {
+        return await invoke('get_device_info');
+    }
+
+    static async setupAutoStart(enabled: boolean): Promise<void> {
+        return await invoke('setup_auto_start', { enabled });
+    }
+
+    static async disableAutoStart(): Promise<void> {
+        return await invoke('disable_auto_start');
+    }
+
+    static async isAutoStartEnabled(): Promise<boolean> {
+        return await invoke('is_auto_start_enabled');
+    }
+
+    static async showNotification(title: string, body: string): Promise<void> {
+        return await invoke('show_notification', { title, body });
+    }
+
+    // ===============================
+    //  ML ENGINE INTEGRATION
+    // ===============================
+
+    // ML Configuration and Prediction interfaces
+    static async initializeMLEngine(config?: any): Promise<string> {
+        return await invoke('initialize_ml_engine', { request: { config } });
+    }
+
+    static async analyzeContextML(context: any, availableKeys: string[]): Promise<any> {
+        return await invoke('analyze_context_ml', { request: { context, available_keys: availableKeys } });
+    }
+
+    static async recordMLUsage(keyId: string, context: any, success: boolean): Promise<string> {
+        return await invoke('record_ml_usage', { record: { key_id: keyId, context, success } });
+    }
+
+    static async getMLStats(): Promise<any> {
+        return await invoke('get_ml_stats');
+    }
+
+    static async checkMLStatus(): Promise<boolean> {
+        return await invoke('check_ml_status');
+    }
+
+    static async reinitializeMLEngine(config?: any): Promise<string> {
+        return await invoke('reinitialize_ml_engine', { request: { config } });
+    }
+
+    static async getMLConfig(): Promise<any> {
+        return await invoke('get_ml_config');
+    }
+
+    static async detectContext(): Promise<any> {
+        return await invoke('detect_context');
+    }
+
+    // ===============================
+    //  LLM PROXY INTEGRATION
+    // ===============================
+
+    static async processWithLLM(request: LLMRequest): Promise<LLMResponse> {
+        return await invoke('process_with_llm', {
+            prompt: request.prompt,
+            context: request.context || null,
+            config: request.config
+        });
+    }
+
+    static async clearLLMCache(): Promise<string> {
+        return await invoke('clear_llm_cache');
+    }
+
+    static async getLLMCacheStats(): Promise<any> {
+        return await invoke('get_llm_cache_stats');
+    }
+
+    // ===============================
+    //  API GENERATOR INTEGRATION
+    // ===============================
+
+    static async getAPIProviders(): Promise<ApiProvider[]> {
+        return await invoke('get_api_providers');
+    }
+
+    static async scrapeAPIDocumentation(providerId: string, docsUrl: string): Promise<any> {
+        return await invoke('scrape_api_documentation', { provider_id: providerId, docs_url: docsUrl });
+    }
+
+    static async generateAPIConfiguration(request: any): Promise<any> {
+        return await invoke('generate_api_configuration', { request });
+    }
+
+    static async detectProviderFromEnv(envVarName: string, value?: string): Promise<any> {
+        return await invoke('detect_provider_from_env', { env_var_name: envVarName, value: value ?? null });
+    }
+
+    static async previewGeneratedConfig(request: any): Promise<any> {
+        return await invoke('preview_generated_config', { request });
+    }
+
+    static async generateBetterAuthConfig(providerId: string, env_vars: Record<string, string>): Promise<any> {
+        return await invoke('generate_better_auth_config', { provider_id: providerId, env_vars });
+    }
+
+    static async generateOpenAIConfig(env_vars: Record<string, string>): Promise<any> {
+        return await invoke('generate_openai_config', { env_vars });
+    }
+
+    static async getProviderTemplates(): Promise<any> {
+        return await invoke('get_provider_templates');
+    }
+
+    // ===============================
+    //  NATIVE DOCUMENTATION STORE INTEGRATION
+    // ===============================
+
+    static async getNativeDocumentation(): Promise<Documentation[]> {
+        return await invoke('get_documentation');
+    }
+
+    static async createNativeDocumentation(doc: Omit<Documentation, 'id' | 'created_at' | 'updated_at'>): Promise<Documentation> {
+        return await invoke('create_documentation', { doc });
+    }
+
+    static async updateNativeDocumentation(id: string, updates: Partial<Documentation>): Promise<void> {
+        return await invoke('update_documentation', { id, updates });
+    }
+
+    static async deleteNativeDocumentation(id: string): Promise<void> {
+        return await invoke('delete_documentation', { id });
+    }
+
+    static async searchNativeDocumentation(query: string, projectId?: string, docType?: string, tags?: string[]): Promise<any> {
+        return await invoke('search_native_documentation', { query, project_id: projectId, doc_type: docType, tags });
+    }
+
+    // Documentation tags
+    static async listDocTags(): Promise<DocTag[]> {
+        return await invoke('list_doc_tags');
+    }
+
+    static async renameDocTag(from: string, to: string): Promise<number> {
+        return await invoke('rename_doc_tag', { from, to });
+    }
+
+    // Markdown bundles; all docs when docIds is omitted
+    static async exportDocs(dir: string, docIds?: string[]): Promise<DocsExport> {
+        return await invoke('export_docs', { docIds: docIds ?? null, dir });
+    }
+
+    static async importDocs(dir: string): Promise<DocsImport> {
+        return await invoke('import_docs', { dir });
+    }
+
+    // Attachments; up to 10 MB each and 50 MB per doc
+    static async addDocAttachment(docId: string, path: string, encrypted?: boolean): Promise<DocAttachment> {
+        return await invoke('add_doc_attachment', { docId, path, encrypted: encrypted ?? null });
+    }
+
+    static async removeDocAttachment(docId: string, attachmentId: string): Promise<void> {
+        return await invoke('remove_doc_attachment', { docId, attachmentId });
+    }
+
+    static async exportDocAttachment(docId: string, attachmentId: string, path: string): Promise<void> {
+        return await invoke('export_doc_attachment', { docId, attachmentId, path });
+    }
+
+    /** Deletes stored files no doc refers to; returns how many */
+    static async cleanupDocAttachments(): Promise<number> {
+        return await invoke('cleanup_doc_attachments');
+    }
+
+    // Cross-references; entityId is a key ID, env var name, provider or project ID
+    static async getBacklinks(entityId: string): Promise<Backlink[]> {
+        return await invoke('get_backlinks', { entityId });
+    }
+
+    static async getDocReferences(docId: string): Promise<DocReference[]> {
+        return await invoke('get_doc_references', { docId });
+    }
+
+    /** Rescans every doc; returns how many references were found */
+    static async refreshDocReferences(): Promise<number> {
+        return await invoke('refresh_doc_references');
+    }
+
+    // Revisions, newest first
+    static async getDocRevisions(docId: string): Promise<DocRevision[]> {
+        return await invoke('get_doc_revisions', { docId });
+    }
+
+    /** Unified diff of revision a against revision b, or the current doc */
+    static async diffDocRevisions(docId: string, a: number, b?: number): Promise<string> {
+        return await invoke('diff_doc_revisions', { docId, a, b: b ?? null });
+    }
+
+    static async restoreDocRevision(docId: string, number: number): Promise<Documentation> {
+        return await invoke('restore_doc_revision', { docId, number });
+    }
+
+    /** Drafts a doc for a key with an LLM and saves it tagged needs-review */
+    static async generateKeyDocumentation(keyId: string, config?: LLMConfig): Promise<Documentation> {
+        return await invoke('generate_key_documentation', { keyId, config: config ?? null });
+    }
+
+    // Prompt templates of the LLM features
+    static async listPromptTemplates(): Promise<PromptTemplate[]> {
+        return await invoke('list_prompt_templates');
+    }
+
+    /** Creates a template for the feature when id is omitted, otherwise edits it */
+    static async savePromptTemplate(feature: string, body: string, id?: string, name?: string): Promise<PromptTemplate> {
+        return await invoke('save_prompt_template', { id: id ?? null, feature, name: name ?? null, body });
+    }
+
+    /** Restores a built-in template as shipped, or deletes a user template */
+    static async resetPromptTemplate(id: string): Promise<void> {
+        return await invoke('reset_prompt_template', { id });
+    }
+
+    /** Without an id the feature goes back to its built-in template */
+    static async setActivePromptTemplate(feature: string, id?: string): Promise<void> {
+        return await invoke('set_active_prompt_template', { feature, id: id ?? null });
+    }
+
+    // Reviewed .env imports
+    static async analyzeEnvImport(filePath: string): Promise<EnvImportProposal> {
+        return await invoke('analyze_env_import', { filePath });
+    }
+
+    static async importEnvVariables(filePath: string, decisions: ImportDecision[], environment?: string): Promise<EnvImportResult> {
+        return await invoke('import_env_variables', { filePath, decisions, environment: environment ?? null });
+    }
+
+    /** Resolves once the file is imported or the ingestion is cancelled */
+    static async ingestEnvFile(path: string, autoApply?: boolean): Promise<IngestResult> {
+        return await invoke('ingest_env_file', { path, autoApply: autoApply ?? null });
+    }
+
+    /** Without a review the ingestion is cancelled */
+    static async reviewEnvIngestion(ingestionId: string, review: IngestReview | null): Promise<void> {
+        return await invoke('review_env_ingestion', { ingestionId, review });
+    }
+
+    static async onEnvIngestProgress(callback: (progress: IngestProgress) => void) {
+        return await listen('env-ingest-progress', (event) => {
+            callback(event.payload as IngestProgress);
+        });
+    }
+
+    static async suggestDocTags(prefix?: string, providerId?: string, projectId?: string): Promise<string[]> {
+        return await invoke('suggest_doc_tags', {
+            prefix: prefix ?? null,
+            providerId: providerId ?? null,
+            projectId: projectId ?? null,
+        });
+    }
+
+    static async getDocumentationByProject(projectId: string): Promise<Documentation[]> {
+        return await invoke('get_documentation_by_project', { project_id: projectId });
+    }
+
+    static async getDocumentationByProvider(providerId: string): Promise<Documentation[]> {
+        return await invoke('get_documentation_by_provider', { provider_id: providerId });
+    }
+
+    static async toggleDocumentationFavorite(id: string): Promise<void> {
+        return await invoke('toggle_documentation_favorite', { id });
+    }
+
+    static async scrapeAndSaveDocumentation(url: string, title?: string): Promise<Documentation> {
+        return await invoke('scrape_and_save_documentation', { url, title });
+    }
+
+    // ===============================
+    //  ENHANCED DOCUMENTATION COMMANDS
+    // ===============================
+
+    static async addProviderDocumentation(providerId: string, docsUrl: string): Promise<any> {
+        return await invoke('add_provider_documentation', { request: { provider_id: providerId, docs_url: docsUrl } });
+    }
+
+    static async getProviderDocumentation(providerId: string): Promise<any> {
+        return await invoke('get_provider_documentation', { provider_id: providerId });
+    }
+
+    static async searchDocumentationAdvanced(query: string, providerId?: string): Promise<any> {
+        return await invoke('search_documentation', { request: { query, provider_id: providerId } });
+    }
+
+    static async getDocumentationById(docId: string): Promise<any> {
+        return await invoke('get_documentation_by_id', { doc_id: docId });
+    }
+
+    static async updateProviderDocumentation(providerId: string, docsUrl: string): Promise<any> {
+        return await invoke('update_provider_documentation', { provider_id: providerId, docs_url: docsUrl });
+    }
+
+    static async removeProviderDocumentation(providerId: string): Promise<any> {
+        return await invoke('remove_provider_documentation', { provider_id: providerId });
+    }
+
+    static async getIndexedProviders(): Promise<string[]> {
+        return await invoke('get_indexed_providers');
+    }
+
+    static async autoIndexProviderDocs(): Promise<any> {
+        return await invoke('auto_index_provider_docs');
+    }
+
+    static async getContextDocumentationSuggestions(providerIds: string[], contextKeywords: string[]): Promise<any> {
+        return await invoke('get_context_documentation_suggestions', { provider_ids: providerIds, context_keywords: contextKeywords });
+    }
+
+    // ===============================
+    //  LLM-ENHANCED ML ENGINE METHODS
+    // ===============================
+
+    // Documentation Generation via LLM
+    static async generateDocumentation(request: DocumentationGenerationRequest): Promise<GeneratedDocumentation> {
+        return await invoke('generate_documentation', { request });
+    }
+
+    static async generateUsageExamples(provider: string, apiKeyFormat: string): Promise<CodeExample[]> {
+        return await invoke('generate_usage_examples', { provider, api_key_format: apiKeyFormat });
+    }
+
+    static async generateConfigurationTemplate(request: ConfigTemplateRequest): Promise<GeneratedConfigTemplate> {
+        return await invoke('generate_config_template', { request });
+    }
+
+    // Enhanced ML Analysis with LLM Insights
+    static async getEnhancedMLPrediction(context: ContextInfo, availableKeys: string[]): Promise<EnhancedMLPrediction> {
+        return await invoke('get_enhanced_ml_prediction', {
+            request: { context, available_keys: availableKeys }
+        });
+    }
+
+    static async getContextualDocumentationSuggestions(provider: string, context: ContextInfo): Promise<DocumentationSuggestion[]> {
+        return await invoke('get_contextual_doc_suggestions', { provider, context });
+    }
+
+    static async getConfigurationRecommendations(provider: string, context: ContextInfo): Promise<ConfigurationRecommendation[]> {
+        return await invoke('get_config_recommendations', { provider, context });
+    }
+
+    // LLM Engine Management
+    static async initializeLLMEngine(config: LLMEngineConfig): Promise<string> {
+        return await invoke('initialize_llm_engine', { config });
+    }
+
+    static async isLLMEngineLoaded(): Promise<boolean> {
+        return await invoke('is_llm_engine_loaded');
+    }
+
+    static async getLLMEngineStatus(): Promise<{ loaded: boolean; model_path?: string; config?: LLMEngineConfig }> {
+        return await invoke('get_llm_engine_status');
+    }
+
+    static async unloadLLMEngine(): Promise<string> {
+        return await invoke('unload_llm_engine');
+    }
+
+    // Configuration Template Management
+    static async previewConfigurationTemplate(request: ConfigTemplateRequest): Promise<{ preview: string; files: ConfigTemplateFile[] }> {
+        return await invoke('preview_config_template', { request });
+    }
+
+    static async saveConfigurationTemplate(template: GeneratedConfigTemplate, outputPath: string): Promise<string> {
+        return await invoke('save_config_template', { template, output_path: outputPath });
+    }
+
+    // Documentation Enhancement
+    static async enhanceDocumentationWithLLM(docId: string, content: string): Promise<{ enhanced_content: string; sections: DocumentationSection[] }> {
+        return await invoke('enhance_documentation_with_llm', { doc_id: docId, content });
+    }
+
+    static async summarizeDocumentation(content: string, maxLength?: number): Promise<string> {
+        return await invoke('summarize_documentation', { content, max_length: maxLength });
+    }
+
+    static async extractKeyInformation(content: string, extractionType: 'authentication' | 'endpoints' | 'examples' | 'configuration'): Promise<DocumentationSection[]> {
+        return await invoke('extract_key_information', { content, extraction_type: extractionType });
+    }
+
+    // ===============================
+    //  CHAT SYSTEM COMMANDS
+    // ===============================
+
+    static async createChatSession(request: { userId: string; title: string; description?: string; contextLibraries: string[] }): Promise<{ sessionId: string; title: string; createdAt: string }> {
+        return await invoke('create_chat_session', { request });
+    }
+
+    static async sendChatMessage(request: {
+        sessionId: string;
+        message: string;
+        contextLibraries: string[];
+        userPreferences: {
+            preferredLanguage: string;
+            preferredFramework: string;
+            codeStyle: string;
+            detailLevel: string;
+            includeExamples: boolean;
+            includeTests: boolean;
+            securityFocused: boolean;
+        };
+        generationContext?: {
+            targetFramework: string;
+            targetLanguage: string;
+            projectContext?: string;
+            existingCode?: string;
+            requirements: string[];
+            constraints: string[];
+        };
+        includeCodeGeneration: boolean;
+    }): Promise<any> {
+        return await invoke('send_chat_message', { request });
+    }
+
+    static async getChatMessages(sessionId: string): Promise<any[]> {
+        return await invoke('get_chat_messages', { sessionId });
+    }
+
+    static async getUserChatSessions(userId: string): Promise<any[]> {
+        return await invoke('get_user_chat_sessions', { userId });
+    }
+
+    static async searchDocumentationForChat(request: {
+        query: string;
+        provider_id?: string;
+        content_types?: string[];
+        max_results?: number;
+        min_similarity?: number;
+    }): Promise<any[]> {
+        return await invoke('search_documentation_for_chat', { request });
+    }
+
+    static async generateIntegration(request: {
+        session_id: string;
+        provider_name: string;
+        framework: string;
+        language: string;
+        requirements: string[];
+        constraints: string[];
+        project_context?: string;
+        existing_code?: string;
+    }): Promise<any> {
+        return await invoke('generate_integration', { request });
+    }
+
+    static async archiveChatSession(sessionId: string): Promise<boolean> {
+        return await invoke('archive_chat_session', { sessionId });
+    }
+
+    static async deleteChatSession(sessionId: string): Promise<boolean> {
+        return await invoke('delete_chat_session', { sessionId });
+    }
+
+    static async getChatStatistics(): Promise<Record<string, number>> {
+        return await invoke('get_chat_statistics');
+    }
+
+    static async updateSessionPreferences(sessionId: string, preferences: {
+        preferredLanguage: string;
+        preferredFramework: string;
+        codeStyle: string;
+        detailLevel: string;
+        includeExamples: boolean;
+        includeTests: boolean;
+        securityFocused: boolean;
+    }): Promise<boolean> {
+        return await invoke('update_session_preferences', { sessionId, preferences });
+    }
+
+    static async getAvailableDocumentationLibraries(): Promise<any[]> {
+        return await invoke('get_available_documentation_libraries');
+    }
+
+    static async exportChatSession(sessionId: string, format: 'json' | 'markdown' | 'text'): Promise<string> {
+        return await invoke('export_chat_session', { sessionId, format });
+    }
+
+    // ===============================
+    //  DOCUMENTATION LIBRARY COMMANDS
+    // ===============================
+
+    static async addDocumentationFromUrl(request: {
+        provider_id: string;
+        provider_name: string;
+        provider_category: string;
+        docs_url: string;
+        description?: string;
+        tags: string[];
+        version?: string;
+    }): Promise<string> {
+        return await invoke('add_documentation_from_url', { request });
+    }
+
+    static async addManualDocumentation(request: {
+        provider_id: string;
+        provider_name: string;
+        title: string;
+        content: string;
+        section_path: string[];
+        content_type: string;
+        tags: string[];
+        importance_score?: number;
+    }): Promise<string> {
+        return await invoke('add_manual_documentation', { request });
+    }
+
+    static async searchDocumentationLibrary(request: {
+        query: string;
+        library_ids?: string[];
+        provider_ids?: string[];
+        content_types?: string[];
+        section_filter?: string[];
+        min_similarity?: number;
+        max_results?: number;
+        include_metadata?: boolean;
+        boost_recent?: boolean;
+    }): Promise<any[]> {
+        return await invoke('search_documentation_library', { request });
+    }
+
+    static async getDocumentationLibraries(): Promise<any[]> {
+        return await invoke('get_documentation_libraries');
+    }
+
+    static async getDocumentationLibrary(library_id: string): Promise<any | null> {
+        return await invoke('get_documentation_library', { library_id });
+    }
+
+    static async getLibraryChunks(library_id: string, offset?: number, limit?: number): Promise<any[]> {
+        return await invoke('get_library_chunks', { library_id, offset, limit });
+    }
+
+    static async updateDocumentationLibrary(library_id: string, name?: string, description?: string, tags?: string[]): Promise<boolean> {
+        return await invoke('update_documentation_library', { library_id, name, description, tags });
+    }
+
+    static async deleteDocumentationLibrary(library_id: string): Promise<boolean> {
+        return await invoke('delete_documentation_library', { library_id });
+    }
+
+    static async refreshDocumentationLibrary(library_id: string): Promise<boolean> {
+        return await invoke('refresh_documentation_library', { library_id });
+    }
+
+    static async getLibraryStatistics(): Promise<{
+        total_libraries: number;
+        total_chunks: number;
+        total_embeddings: number;
+        libraries_by_provider: Record<string, number>;
+        chunks_by_content_type: Record<string, number>;
+        average_chunk_size: number;
+        last_updated: string;
+    }> {
+        return await invoke('get_library_statistics');
+    }
+
+    static async bulkImportDocumentation(request: {
+        provider_id: string;
+        provider_name: string;
+        documents: {
+            title: string;
+            content: string;
+            url?: string;
+            section_path: string[];
+            content_type: string;
+            tags: string[];
+        }[];
+    }): Promise<string> {
+        return await invoke('bulk_import_documentation', { request });
+    }
+
+    static async exportDocumentationLibrary(library_id: string, format: 'json' | 'markdown' | 'csv'): Promise<string> {
+        return await invoke('export_documentation_library', { library_id, format });
+    }
+
+    static async getDocumentationChunk(chunk_id: string): Promise<any | null> {
+        return await invoke('get_documentation_chunk', { chunk_id });
+    }
+
+    static async updateDocumentationChunk(chunk_id: string, title?: string, content?: string, tags?: string[], importance_score?: number): Promise<boolean> {
+        return await invoke('update_documentation_chunk', { chunk_id, title, content, tags, importance_score });
+    }
+
+    static async deleteDocumentationChunk(chunk_id: string): Promise<boolean> {
+        return await invoke('delete_documentation_chunk', { chunk_id });
+    }
+
+    static async validateDocumentationUrl(url: string): Promise<boolean> {
+        return await invoke('validate_documentation_url', { url });
+    }
+
+    // LLM Configuration Commands
+    static async setEnvVar(key: string, value: string): Promise<boolean> {
+        return await invoke('set_env_var', { key, value });
+    }
+
+    static async configureLLMProvider(config: {
+        provider: string;
+        model: string;
+        temperature?: number;
+        maxTokens?: number;
+    }): Promise<boolean> {
+        return await invoke('configure_llm_provider', { config });
+    }
+
+    static async testLLMConnection(provider: string): Promise<string> {
+        return await invoke('test_llm_connection', { provider });
+    }
+
+    static async checkOllamaStatus(): Promise<{
+        is_running: boolean;
+        models: { name: string; size: number; modified: string }[];
+        server_url: string;
+    }> {
+        return await invoke('check_ollama_status');
+    }
+
+    static async pullOllamaModel(model: string): Promise<string> {
+        return await invoke('pull_ollama_model_command', { model });
+    }
+}
+
+// Types for biometric and user management
+export interface UserPreferences {
+    theme: string;
+    language: string;
+    auto_lock_timeout: number;
+    clipboard_clear_timeout: number;
+    show_notifications: boolean;
+    audit_logging: boolean;
+    biometric_unlock: boolean;
+    auto_backup: boolean;
+    encryption_level: string;
+    logging?: LogSettings;
+    env_sync_mode?: 'reference' | 'plaintext';
+    key_ordering?: KeyOrdering;
+}
+
+export interface KeyOrdering {
+    pinned_first: boolean;
+    sort: 'name_asc' | 'name_desc' | 'created_desc' | 'created_asc' | 'updated_desc' | 'service';
+    /** Pinned key ids as arranged by the user */
+    pinned_order: string[];
+}
+
+export interface LogSettings {
+    level: string;
+    modules: Record<string, string>;
+}
+
+export interface AppConfig {
+    server: {
+        bind: string;
+        port: number;
+        auto_start: boolean;
+        cors: { allowed_origins: string[]; key_origins: string[] };
+        require_registered_clients: boolean;
+        pause_when_locked: boolean;
+        log_requests: boolean;
+    };
+    vault: { path?: string; auto_lock_minutes?: number; inactivity_lock_minutes?: number };
+    logging: { level?: string };
+    legacy_tcp: { enabled: boolean; port: number };
+    audit: { sinks: AuditSinkConfig[] };
+}
+
+export type AuditSinkConfig = (
+    | { type: 'jsonl'; path: string }
+    | { type: 'syslog'; address: string; protocol?: 'udp' | 'tcp' }
+    | { type: 'webhook'; url: string; headers?: Record<string, string> }
+) & {
+    events?: string[];
+    failures_only?: boolean;
+};
+
+export type BackgroundTaskStatus = 'running' | 'restarting' | 'finished' | 'failed' | 'cancelled';
+
+export interface BackgroundTaskInfo {
+    name: string;
+    supervised: boolean;
+    status: BackgroundTaskStatus;
+    started_at: string;
+    restarts: number;
+    last_error?: string;
+}
+
+export interface AuditSinkStatus {
+    sink: string;
+    events: string[];
+    failures_only: boolean;
+    queued: number;
+    delivered: number;
+    failed: number;
+    dropped: number;
+    last_error?: string;
+    last_error_at?: string;
+}
+
+export interface ConfigView {
+    path: string;
+    exists: boolean;
+    config: AppConfig;
+    effective: AppConfig;
+    env_overrides: string[];
+    load_error?: string;
+    restart_required: string[];
+}
+
+export interface SyncPreview {
+    path: string;
+    created: boolean;
+    added: string[];
+    updated: string[];
+    diff: string;
+}
+
+export interface PasswordStrength {
+    /** 0 (trivial) to 4 (strong) */
+    score: number;
+    entropy_bits: number;
+    crack_time: string;
+    warning?: string | null;
+    suggestions: string[];
+    breached: boolean;
+    meets_policy: boolean;
+    violations: string[];
+}
+
+export type EmailTemplate = 'recovery_token' | 'security_alert' | 'test';
+
+export interface EmailSettings {
+    transport:
+    | {
+        type: 'smtp';
+        host: string;
+        port: number;
+        security: 'tls' | 'start_tls' | 'none';
+        username?: string | null;
+        password?: string | null;
+    }
+    | { type: 'sendmail'; command?: string | null };
+    from: string;
+    enabled: boolean;
+    recovery_tokens: boolean;
+    security_alerts: boolean;
+    include_backup_email: boolean;
+    /** Replacements of the built-in templates, with `{{placeholder}}` substitution */
+    templates?: Partial<Record<EmailTemplate, { subject: string; body: string }>>;
+}
+
+export interface EmailDelivery {
+    id: string;
+    template: EmailTemplate;
+    recipient: string;
+    attempt: number;
+    timestamp: string;
+    success: boolean;
+    error?: string | null;
+}
+
+export interface DemoVaultInfo {
+    vault_path: string;
+    docs_path: string;
+    password: string;
+    projects_root: string;
+    keys: number;
+    projects: number;
+    docs: number;
+    activity: number;
+}
+
+export interface NamingPolicy {
+    id: string;
+    name: string;
+    kind: 'regex' | 'template';
+    // Regex, or a template such as `<SERVICE>_<ENV>_KEY`
+    pattern: string;
+    enforce: boolean;
+    services: string[];
+    created_at: string;
+}
+
+export interface NamingViolation {
+    policy_id: string;
+    policy_name: string;
+    enforced: boolean;
+    message: string;
+}
+
+export interface NamingLintEntry {
+    key_id: string;
+    name: string;
+    service: string;
+    environment: string;
+    violations: NamingViolation[];
+    suggestion?: string;
+}
+
+export interface KeyRename {
+    key_id: string;
+    new_name: string;
+}
+
+export interface BulkRenameResult {
+    renamed: { key_id: string; old_name: string; new_name: string; env_file?: string }[];
+    env_file_errors: string[];
+}
+
+export interface EnvFileChange {
+    id: string;
+    path: string;
+    project_path?: string;
+    action: string;
+    timestamp: string;
+    existed: boolean;
+    added: string[];
+    updated: string[];
+    after_sha256?: string;
+    encrypted: boolean;
+    reverts?: string;
+    reverted_at?: string;
+    modified_since?: boolean;
+}
+
+export interface ResolvedEnv {
+    variables: Record<string, string>;
+    resolved: string[];
+    unresolved: { name: string; reference: string; reason: string }[];
+}
+
+export type ApprovalDecision = 'approve' | 'always_allow' | 'deny';
+
+export interface ApprovalRequest {
+    id: string;
+    client: string;
+    /** Registered client that proved its identity; only those can be always allowed */
+    verified: boolean;
+    purpose: string;
+    keys: { id: string; name: string; service: string }[];
+    requested_at: string;
+    expires_at: string;
+}
+
+export interface ApprovalRule {
+    id: string;
+    key_id: string;
+    /** Registered client the rule is for; rules without one never apply */
+    client_id?: string | null;
+    client: string;
+    created_at: string;
+    last_used_at?: string;
+}
+
+export type RiskyAction = 'delete_key' | 'overwrite_env_file' | 'delete_document' | 'push_secrets' | 'decrypt_key' | 'sync_production_key';
+
+export type ConfirmationPolicy = 'always_ask' | 'once_per_session' | 'never';
+
+export interface ConfirmationRequest {
+    id: string;
+    client: string;
+    action: RiskyAction;
+    summary: string;
+    details: string[];
+    requested_at: string;
+    expires_at: string;
+}
+
+export interface ConfirmationPolicyEntry {
+    action: RiskyAction;
+    policy: ConfirmationPolicy;
+}
+
+export interface RotationInstructions {
+    provider?: string;
+    steps: string[];
+    console_url?: string;
+    docs_url?: string;
+    doc_ids: string[];
+}
+
+export interface RotationReminder {
+    id: string;
+    key_id: string;
+    key_name: string;
+    service: string;
+    due_at: string;
+    created_at: string;
+    instructions: RotationInstructions;
+    resolved_at?: string;
+    dismissed: boolean;
+}
+
+export type Attestation = 'still_needed' | 'revoke';
+
+export interface AccessReviewItem {
+    id: string;
+    key_id: string;
+    key_name: string;
+    service: string;
+    attestation: Attestation | null;
+    attested_at?: string;
+    note: string | null;
+}
+
+export interface AccessReviewGroup {
+    kind: 'project' | 'client' | 'unassigned';
+    /** Project path or client name; null for unassigned keys */
+    subject: string | null;
+    items: AccessReviewItem[];
+}
+
+export interface AccessReview {
+    id: string;
+    created_at: string;
+    due_at: string;
+    interval_days: number;
+    completed_at?: string;
+    groups: AccessReviewGroup[];
+}
+
+export interface AccessReviewStatus {
+    open_review_id: string | null;
+    due_at?: string;
+    overdue: boolean;
+    last_completed_at?: string;
+}
+
+export interface SnapshotOptions {
+    /** File to write the snapshot to; it must not exist yet */
+    path?: string;
+    include_archived?: boolean;
+    /** Days of audit log to summarize, 0 to leave the summary out */
+    audit_days?: number;
+}
+
+export interface KeySnapshot {
+    id: string;
+    name: string;
+    service: string;
+    environment: string;
+    environments: string[];
+    project_path: string | null;
+    package_path: string | null;
+    tags: string[];
+    scopes: string[];
+    is_active: boolean;
+    archived: boolean;
+    created_at: string;
+    updated_at: string;
+    expires_at: string | null;
+    last_used_at: string | null;
+    last_rotated_at: string | null;
+    rotation_interval_days: number | null;
+}
+
+export interface ProjectSnapshot {
+    id: string;
+    name: string;
+    path: string;
+    group_id: string | null;
+    default_environment: string;
+    key_count: number;
+    env_file_count: number;
+    created_at: string;
+    updated_at: string;
+}
+
+export interface VaultSnapshot {
+    format_version: number;
+    snapshot_id: string;
+    generated_at: string;
+    vault_id: string;
+    schema_version: number;
+    keys: KeySnapshot[];
+    projects: ProjectSnapshot[];
+    stats: Record<string, unknown>;
+    audit: {
+        since: string;
+        total: number;
+        failed: number;
+        by_action: Record<string, number>;
+        last_event_at: string | null;
+    } | null;
+    /** SHA-256 of the snapshot with this field empty */
+    checksum: string;
+}
+
+export interface KeyStateAt extends KeySnapshot {
+    /** Not from a revision recorded by then */
+    approximate: boolean;
+    /** Clients that used the key by then, per the audit log */
+    accessed_by: string[];
+}
+
+export interface VaultStateAt {
+    at: string;
+    keys: KeyStateAt[];
+    /** Oldest revision kept; states before it are approximate */
+    history_since: string | null;
+}
+
+/** Keys an incident covers; no criteria at all covers every key */
+export interface IncidentScope {
+    key_ids?: string[];
+    project_paths?: string[];
+    services?: string[];
+    /** Keys these clients used, per the audit log */
+    client_ids?: string[];
+    since?: string;
+}
+
+export type IncidentItemStatus = 'pending' | 'rotated' | 'skipped';
+
+export interface IncidentChecklistItem {
+    key_id: string;
+    key_name: string;
+    service: string;
+    project_path: string | null;
+    instructions: RotationInstructions;
+    status: IncidentItemStatus;
+    updated_at: string | null;
+    note: string | null;
+}
+
+export interface IncidentTimelineEntry {
+    timestamp: string;
+    action: string;
+    resource_type: string;
+    resource_id: string | null;
+    success: boolean;
+    details: string | null;
+}
+
+export interface Incident {
+    id: string;
+    title: string;
+    scope: IncidentScope;
+    opened_at: string;
+    closed_at: string | null;
+    revoked: { sessions: number; clients: number; leases: number };
+    items: IncidentChecklistItem[];
+    /** Filled when the incident is closed */
+    timeline: IncidentTimelineEntry[];
+    summary: string | null;
+}
+
+/** Credentials are the ids of vault keys, never values */
+export type SecretManagerTarget =
+    | {
+          provider: 'aws_secrets_manager';
+          region: string;
+          access_key_id_key_id: string;
+          secret_access_key_key_id: string;
+          session_token_key_id?: string | null;
+      }
+    | { provider: 'gcp_secret_manager'; project_id: string; access_token_key_id: string }
+    | {
+          provider: 'hashicorp_vault';
+          address: string;
+          mount: string;
+          token_key_id: string;
+          namespace?: string | null;
+      };
+
+export interface BackendRef {
+    target: SecretManagerTarget;
+    /** ARN (AWS), resource name (GCP) or KV path (Vault) */
+    remote_id: string;
+}
+
+export interface KeyRotationStatus {
+    key_id: string;
+    key_name: string;
+    service: string;
+    interval_days: number;
+    last_rotated_at?: string;
+    next_due_at: string;
+    overdue: boolean;
+}
+
+export interface GitLeakFinding {
+    commit: string;
+    author: string;
+    committed_at: string;
+    summary: string;
+    file: string;
+    line?: number;
+    kind: 'vault_key' | 'pattern';
+    key_id?: string;
+    key_name?: string;
+    pattern?: string;
+    preview: string;
+}
+
+export interface GitHistoryScan {
+    project_path: string;
+    scanned_at: string;
+    depth: number;
+    commits_scanned: number;
+    findings: GitLeakFinding[];
+    truncated: boolean;
+    remediation: string[];
+}
+
+export type IacFormat = 'terraform' | 'terraform_vars' | 'cloud_formation' | 'serverless';
+
+export interface IacFinding {
+    file: string;
+    line: number;
+    format: IacFormat;
+    kind: 'vault_key' | 'pattern' | 'literal';
+    attribute?: string;
+    key_id?: string;
+    key_name?: string;
+    pattern?: string;
+    preview: string;
+    /** Variable to move the value to */
+    variable: string;
+    /** What to write instead of the value; tfvars lines are removed instead */
+    replacement?: string;
+    declaration?: string;
+}
+
+export interface IacScan {
+    project_path: string;
+    scanned_at: string;
+    files_scanned: number;
+    findings: IacFinding[];
+    truncated: boolean;
+    remediation: string[];
+}
+
+export interface TfvarsOutput {
+    content: string;
+    variables: string[];
+    /** Left out: not in the vault, no value in the environment, or remote */
+    missing: string[];
+    written_to?: string;
+}
+
+export type Severity = 'low' | 'medium' | 'high' | 'critical';
+
+export interface SecurityIssue {
+    severity: Severity;
+    category:
+        | 'expired_key'
+        | 'rotation_overdue'
+        | 'committed_secret'
+        | 'environment_mismatch'
+        | 'policy_violation'
+        | 'access_review_overdue';
+    title: string;
+    detail: string;
+    key_id?: string;
+    project_path?: string;
+}
+
+export interface SecurityReport {
+    generated_at: string;
+    issues: SecurityIssue[];
+    git_scans: {
+        project_path: string;
+        scanned_at: string;
+        commits_scanned: number;
+        findings: number;
+        remediation: string[];
+    }[];
+}
+
+export interface SyncAllOptions {
+    project_ids?: string[];
+    /** Only projects in this group and its subgroups */
+    group_id?: string;
+    concurrency?: number;
+    dry_run?: boolean;
+    include_manual?: boolean;
+}
+
+export type ProjectSyncStatus = 'synced' | 'up_to_date' | 'skipped' | 'failed';
+
+export interface ProjectSyncResult {
+    project_id: string;
+    project_name: string;
+    project_path: string;
+    status: ProjectSyncStatus;
+    files: SyncPreview[];
+    message?: string;
+    /** Production keys written, or that would be, into non-production files */
+    violations: PolicyViolation[];
+    duration_ms: number;
+}
+
+export interface PolicyViolation {
+    key_id: string;
+    key_name: string;
+    /** File, or GitHub repository, written to */
+    target: string;
+    environment: string;
+}
+
+export interface ProjectSyncProgress {
+    completed: number;
+    total: number;
+    result: ProjectSyncResult;
+}
+
+export interface SyncAllSummary {
+    dry_run: boolean;
+    synced: number;
+    up_to_date: number;
+    skipped: number;
+    failed: number;
+    results: ProjectSyncResult[];
+}
+
+export type CredentialKind = 'api_key' | 'ssh_key' | 'tls_certificate' | 'oauth_client';
+
+export interface CredentialParts {
+    public_part: string;
+    passphrase?: string | null;
+    fingerprint: string;
+    algorithm?: string | null;
+    comment?: string | null;
+    subject?: string | null;
+    issuer?: string | null;
+    not_before?: string | null;
+}
+
+export interface ImportCredentialRequest {
+    kind: 'ssh_key' | 'tls_certificate';
+    name: string;
+    private_key_path: string;
+    public_path?: string;
+    passphrase?: string;
+    service?: string;
+    environment?: string;
+    description?: string;
+    project_path?: string;
+    tags?: string[];
+}
+
+export interface OAuthClientConfig {
+    client_id: string;
+    token_url: string;
+    audience?: string | null;
+    auth_method?: 'client_secret_basic' | 'client_secret_post';
+}
+
+export interface AddOAuthClientRequest extends OAuthClientConfig {
+    name: string;
+    service: string;
+    client_secret: string;
+    scopes?: string[];
+    environment?: string;
+    description?: string;
+    project_path?: string;
+    tags?: string[];
+}
+
+export interface AccessToken {
+    access_token: string;
+    token_type: string;
+    expires_at: string;
+    scope?: string | null;
+    cached: boolean;
+}
+
+export interface KeyPatch {
+    environment?: string;
+    is_active?: boolean;
+    /** Replaces the tags, before add_tags and remove_tags apply */
+    tags?: string[];
+    add_tags?: string[];
+    remove_tags?: string[];
+}
+
+export interface BulkKeyResult {
+    key_id: string;
+    success: boolean;
+    error?: string;
+}
+
+export interface BulkResult {
+    /** False when any key failed, in which case nothing was changed */
+    applied: boolean;
+    results: BulkKeyResult[];
+}
+
+export interface ArchivePolicy {
+    /** Archive keys unused for this many days; null turns it off */
+    auto_archive_after_days: number | null;
+}
+
+export interface ActivityActor {
+    kind: 'ui' | 'http' | 'cli' | 'system';
+    /** Client name for HTTP and CLI actors, job name for system ones */
+    name?: string | null;
+    /** Set for HTTP clients that proved their registration */
+    client_id?: string;
+}
+
+export interface ActivitySource {
+    ip?: string | null;
+    /** The client's user agent */
+    device?: string | null;
+}
+
+export interface RecentActivity {
+    id: string;
+    activity_type: string;
+    key_id: string;
+    key_name: string;
+    timestamp: string;
+    /** What changed, for updates */
+    details?: string | null;
+    actor?: ActivityActor | null;
+    source?: ActivitySource | null;
+}
+
+export interface ActivityFilter {
+    key_id?: string;
+    /** An actor kind or a client name */
+    actor?: string;
+    activity_type?: string;
+    limit?: number;
+}
+
+export type RetentionDataClass =
+    | 'audit_logs'
+    | 'recent_activity'
+    | 'sessions'
+    | 'webauthn_challenges'
+    | 'recovery_tokens';
+
+export interface RetentionRule {
+    /** null keeps entries regardless of age */
+    max_age_days: number | null;
+    /** null keeps any number of entries */
+    max_entries: number | null;
+}
+
+export interface RetentionPolicy {
+    audit_logs: RetentionRule;
+    recent_activity: RetentionRule;
+    sessions: RetentionRule;
+    webauthn_challenges: RetentionRule;
+    recovery_tokens: RetentionRule;
+}
+
+export interface RetentionReport {
+    ran_at: string;
+    purged: Partial<Record<RetentionDataClass, number>>;
+    bytes_before: number;
+    bytes_after: number;
+}
+
+export type QuotaWindow = 'minute' | 'hour' | 'day' | 'month';
+
+export interface KeyQuota {
+    /** Requests allowed per window */
+    requests?: number | null;
+    window: QuotaWindow;
+    /** Spend allowed per calendar month, in `currency` */
+    monthly_spend_cap?: number | null;
+    currency: string;
+    /** Percentages of a limit that raise a notification */
+    alert_thresholds: number[];
+}
+
+export interface QuotaStatus {
+    key_id: string;
+    quota: KeyQuota | null;
+    window: QuotaWindow;
+    requests_used: number;
+    requests_allowed: number | null;
+    window_resets_at: string;
+    month_requests: number;
+    month_spend: number;
+    monthly_spend_cap: number | null;
+    month_resets_at: string;
+    /** Share of the tightest limit used, in percent */
+    percent_used: number | null;
+    exceeded: boolean;
+}
+
+export interface KeyCost {
+    estimated_monthly?: number | null;
+    /** ISO 4217 code */
+    currency: string;
+    billing_account?: string | null;
+}
+
+export type CostSource = 'manual' | 'webhook';
+
+export interface CostEntry {
+    id: string;
+    key_id: string;
+    /** Calendar month, YYYY-MM */
+    period: string;
+    amount: number;
+    currency: string;
+    source: CostSource;
+    note?: string | null;
+    recorded_at: string;
+}
+
+export interface CostEntryInput {
+    key_id: string;
+    /** Current month when omitted */
+    period?: string;
+    amount: number;
+    currency?: string;
+    note?: string;
+}
+
+export interface CostGroup {
+    name: string;
+    currency: string;
+    estimated_monthly: number;
+    actual: number;
+    keys: number;
+}
+
+export interface KeyCostLine {
+    key_id: string;
+    key_name: string;
+    service: string;
+    project: string;
+    billing_account: string | null;
+    currency: string;
+    estimated_monthly: number | null;
+    actual: number | null;
+}
+
+export interface CostOverview {
+    period: string;
+    by_service: CostGroup[];
+    by_project: CostGroup[];
+    keys: KeyCostLine[];
+}
+
+export interface SettingsTemplate {
+    id: string;
+    name: string;
+    description?: string | null;
+    /** Only the settings the template sets */
+    settings: Partial<ProjectSettings>;
+    created_at: string;
+    updated_at: string;
+}
+
+export interface SettingsTemplateInput {
+    /** Updates the template with this id, creates one when unset */
+    id?: string;
+    name: string;
+    description?: string;
+    settings: Partial<ProjectSettings>;
+}
+
+export type SettingSource = 'organization' | 'template' | 'project';
+
+export interface EffectiveProjectSettings {
+    project_id: string;
+    template_id: string | null;
+    settings: ProjectSettings;
+    sources: Record<keyof ProjectSettings, SettingSource>;
+}
+
+export type GroupKind = 'client' | 'team' | 'area' | 'other';
+
+export interface ProjectGroup {
+    id: string;
+    name: string;
+    kind: GroupKind;
+    /** Top-level group this one is nested in */
+    parent_id: string | null;
+    created_at: string;
+    updated_at: string;
+}
+
+export interface KeyringEntry {
+    service: string;
+    account: string;
+    vault_id: string;
+    profile: string;
+    name: string;
+    updated_at: string;
+    /** Belongs to the open vault and the current profile */
+    current: boolean;
+    /** Still present in the keystore */
+    exists: boolean;
+}
+
+export interface KeystoreStatus {
+    backend: 'os_keyring' | 'encrypted_file';
+    /** e.g. "macOS Keychain" or "Secret Service" */
+    os_keyring: string;
+    os_keyring_available: boolean;
+    /** Why the OS keyring can't be used */
+    os_keyring_error: string | null;
+    fallback_path: string | null;
+    fallback_entries: number;
+    checked_at: string;
+    /** Last move of fallback entries into the OS keyring */
+    migrated_at: string | null;
+    migrated_entries: number;
+}
+
+export interface DocTag {
+    /** The most used spelling */
+    name: string;
+    normalized: string;
+    doc_count: number;
+}
+
+export interface DocsExport {
+    dir: string;
+    files: string[];
+}
+
+export interface DocsImport {
+    created: string[];
+    updated: string[];
+    skipped: { file: string; reason: string }[];
+}
+
+export interface DocAttachment {
+    id: string;
+    file_name: string;
+    media_type: string;
+    size: number;
+    /** SHA-256 of the stored file */
+    blob: string;
+    encrypted: boolean;
+    added_at: string;
+}
+
+export type DocRefKind = 'key' | 'env_var' | 'provider' | 'project';
+
+export interface DocReference {
+    kind: DocRefKind;
+    /** Key or project ID, env var name, or lowercased provider name */
+    entity_id: string;
+    text: string;
+}
+
+export interface DocRevision {
+    number: number;
+    title: string;
+    content: string;
+    edited_at: string;
+    /** When a later edit replaced this version */
+    replaced_at: string;
+}
+
+export interface PromptTemplateVersion {
+    version: number;
+    body: string;
+    saved_at: string;
+}
+
+export interface PromptTemplate {
+    id: string;
+    feature: string;
+    name: string;
+    description: string;
+    /** Placeholders the body can use as {{name}} */
+    variables: string[];
+    body: string;
+    version: number;
+    builtin: boolean;
+    customized: boolean;
+    active: boolean;
+    updated_at: string | null;
+    history: PromptTemplateVersion[];
+}
+
+export interface Backlink {
+    doc_id: string;
+    title: string;
+    kind: DocRefKind;
+    text: string;
+}
+
+export interface IgnoreRule {
+    source: 'built_in' | 'gitignore' | 'keykeeper';
+    /** Ignore file, relative to the project; null for built-in rules */
+    file: string | null;
+    line: number;
+    pattern: string;
+}
+
+export interface IgnoreVerdict {
+    path: string;
+    is_dir: boolean;
+    ignored: boolean;
+    ignored_as_env_file: boolean;
+    ignored_in_history: boolean;
+    rule: IgnoreRule | null;
+}
+
+export interface EffectiveIgnores {
+    project_path: string;
+    files: string[];
+    rules: IgnoreRule[];
+    verdict: IgnoreVerdict | null;
+}
+
+export type ImportAction = 'create' | 'update' | 'skip';
+
+export interface VariableProposal {
+    name: string;
+    /** Masked for secrets */
+    preview: string;
+    is_secret: boolean;
+    secret_confidence: number;
+    provider: string | null;
+    provider_confidence: number;
+    /** Vault key matching by value or by name */
+    key_id: string | null;
+    key_name: string | null;
+    /** The file's environment, or the one the key's format tells */
+    environment: string;
+    action: ImportAction;
+    /** Between 0 and 1 */
+    confidence: number;
+    reasons: string[];
+}
+
+export interface EnvImportProposal {
+    path: string;
+    file_name: string;
+    project_path: string;
+    package_path: string | null;
+    environment: string;
+    environment_confidence: number;
+    variables: VariableProposal[];
+}
+
+export interface ImportDecision {
+    name: string;
+    action: ImportAction;
+    /** Key to update */
+    key_id?: string | null;
+    /** Service of a created key; the detected provider when unset */
+    service?: string | null;
+    /** Environment of the value; the inferred one when unset */
+    environment?: string | null;
+}
+
+export interface EnvImportResult {
+    created: string[];
+    updated: string[];
+    skipped: string[];
+    failed: { name: string; reason: string }[];
+    /** Values kept for an environment their format contradicts */
+    warnings: string[];
+}
+
+export interface KeyEnvironmentCheck {
+    provider: string | null;
+    /** Environment the key's format tells */
+    environment: string | null;
+    warning: string | null;
+}
+
+export type IngestStage =
+    | 'parsing'
+    | 'classifying'
+    | 'deduplicating'
+    | 'associating'
+    | 'proposed'
+    | 'applying'
+    | 'done'
+    | 'cancelled'
+    | 'failed';
+
+export interface IngestProposal {
+    import: EnvImportProposal;
+    project: {
+        project_path: string;
+        package_path: string | null;
+        project_id: string | null;
+        project_name: string | null;
+        already_associated: boolean;
+    };
+}
+
+export interface IngestProgress {
+    ingestion_id: string;
+    path: string;
+    stage: IngestStage;
+    /** Set for `proposed` */
+    proposal?: IngestProposal;
+    /** Set for `failed` */
+    error?: string;
+}
+
+export interface IngestReview {
+    decisions: ImportDecision[];
+    environment?: string | null;
+    /** Associate the file with the proposed project, true when unset */
+    associate?: boolean;
+}
+
+export interface IngestResult {
+    ingestion_id: string;
+    project_path: string;
+    stage: 'done' | 'cancelled';
+    associated: boolean;
+    import: EnvImportResult | null;
+}
+
+export type LockReason =
+    | 'manual'
+    | 'session_expired'
+    | 'sessions_revoked'
+    | 'inactivity'
+    | 'window_closed'
+    | 'incident';
+
+export interface LockEvent {
+    locked: boolean;
+    /** Why the vault was locked; null when unlocked */
+    reason: LockReason | null;
+    at: string;
+}
+
+export type VaultEvent = {
+    /** Increases by one per event */
+    seq: number;
+    at: string;
+} & (
+    | { type: 'locked'; reason: LockReason }
+    | { type: 'unlocked' }
+    | { type: 'saved' }
+    | {
+        type: 'audit';
+        action: string;
+        resource_type: string;
+        resource_id: string | null;
+        success: boolean;
+        error: string | null;
+    }
+    | { type: 'syncing'; running: boolean }
+);
+
+export type WindowKind = 'main' | 'palette' | 'approvals';
+
+export interface WindowInfo {
+    kind: WindowKind;
+    label: string;
+    visible: boolean;
+    focused: boolean;
+}
+
+export type HardwareBackend = 'secure_enclave' | 'tpm' | 'keyring';
+
+export interface HardwareUnlockStatus {
+    /** Secure element of this machine, if it has one */
+    hardware_backend: HardwareBackend | null;
+    /** Set while hardware unlock is enabled */
+    enabled_backend: HardwareBackend | null;
+    requires_presence: boolean;
+    enabled_at: string | null;
+    /** A master password saved by an earlier version is still in the keyring */
+    legacy_password_stored: boolean;
+}
+
+export type ClientStatus = 'pending' | 'approved' | 'revoked';
+
+export interface RegisteredClient {
+    client_id: string;
+    name: string;
+    status: ClientStatus;
+    device_id?: string | null;
+    registered_from?: string | null;
+    created_at: string;
+    expires_at: string;
+    approved_at?: string | null;
+    last_seen_at?: string | null;
+}
+
+export interface LegacyRouteUsage {
+    route: string;
+    count: number;
+    first_seen: string;
+    last_seen: string;
+    clients: string[];
+}
+
+export interface HealthReport {
+    status: 'ok' | 'degraded';
+    ready: boolean;
+    version: string;
+    timestamp: string;
+    uptime_seconds: number;
+    server_uptime_seconds?: number;
+    vault: {
+        unlocked: boolean;
+        file_exists: boolean;
+        pending_reload: boolean;
+        synced_folder: boolean;
+        last_saved_at?: string;
+    };
+    docs_store: {
+        loaded: boolean;
+        document_count: number;
+        error?: string;
+    };
+    ml_engine: 'not_loaded' | 'loading' | 'loaded';
+    pending_tasks: { name: string; count: number }[];
+    issues: string[];
+}
+
+export interface CrashReport {
+    id: string;
+    occurred_at: string;
+    version: string;
+    os: string;
+    arch: string;
+    thread: string;
+    message: string;
+    location?: string;
+    backtrace: string;
+}
+
+export interface DiagnosticsBundle {
+    path: string;
+    size_bytes: number;
+    files: string[];
+}
+
+export interface LogEntry {
+    timestamp: string;
+    level: string;
+    module: string;
+    message: string;
+}
+
+export interface HttpRequestEntry {
+    timestamp: string;
+    request_id?: string;
+    method: string;
+    /** Path and query, with secrets redacted */
+    path: string;
+    status: number;
+    latency_ms: number;
+    client_id?: string;
+    client_name?: string;
+    bytes_in?: number;
+    bytes_out?: number;
+}
@@ -1,3 +1,5 @@
+import type { KeyCost, KeyQuota } from './tauri-api'
+
 // Core API Key types
 export interface ApiKey {
     id: string
@@ -6,7 +8,8 @@ export interface ApiKey {
     key: string
     description?: string
     environment: 'development' | 'staging' | 'production'
-    rate_limit?: string
+    quota?: KeyQuota | null
+    cost?: KeyCost | null
     expires_at?: string
     scopes: string[]
     tags: string[]
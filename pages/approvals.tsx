@@ -0,0 +1,93 @@
+import { useEffect, useState } from 'react'
+import { TauriAPI, ApprovalDecision, ApprovalRequest } from '../lib/tauri-api'
+
+// Shown in the approvals window, opened when an external client asks for
+// secrets. Lists the waiting requests; the window closes once none are left.
+export default function Approvals() {
+  const [requests, setRequests] = useState<ApprovalRequest[]>([])
+  const [error, setError] = useState<string | null>(null)
+
+  useEffect(() => {
+    TauriAPI.listPendingApprovals()
+      .then(setRequests)
+      .catch((e) => setError(String(e)))
+
+    const unlisten = [
+      TauriAPI.onApprovalRequested((request) =>
+        setRequests((current) => [
+          ...current.filter((r) => r.id !== request.id),
+          request,
+        ])
+      ),
+      TauriAPI.onApprovalExpired((id) =>
+        setRequests((current) => current.filter((r) => r.id !== id))
+      ),
+    ]
+    return () => {
+      unlisten.forEach((p) => p.then((stop) => stop()))
+    }
+  }, [])
+
+  const respond = async (id: string, decision: ApprovalDecision) => {
+    try {
+      await TauriAPI.respondToApproval(id, decision)
+      const left = requests.filter((r) => r.id !== id)
+      setRequests(left)
+      if (left.length === 0) {
+        await TauriAPI.closeWindow('approvals')
+      }
+    } catch (e) {
+      setError(String(e))
+    }
+  }
+
+  if (error) {
+    return <div className="p-6 text-sm text-red-600">{error}</div>
+  }
+  if (requests.length === 0) {
+    return <div className="p-6 text-sm text-gray-500">No pending requests</div>
+  }
+
+  return (
+    <div className="flex h-screen flex-col gap-4 overflow-y-auto p-6">
+      {requests.map((request) => (
+        <div key={request.id} className="rounded border border-gray-200 p-4">
+          <h1 className="font-semibold">
+            {request.client}
+            {!request.verified && <span className="ml-2 text-xs font-normal text-amber-600">unregistered</span>}
+          </h1>
+          <p className="text-sm text-gray-500">{request.purpose}</p>
+          <ul className="my-3 space-y-1 font-mono text-xs">
+            {request.keys.map((key) => (
+              <li key={key.id}>
+                {key.name} <span className="text-gray-500">({key.service})</span>
+              </li>
+            ))}
+          </ul>
+          <div className="flex justify-end gap-2">
+            <button
+              className="rounded border border-gray-300 px-3 py-1.5 text-sm"
+              onClick={() => respond(request.id, 'deny')}
+            >
+              Deny
+            </button>
+            {request.verified && (
+              <button
+                className="rounded border border-gray-300 px-3 py-1.5 text-sm"
+                onClick={() => respond(request.id, 'always_allow')}
+              >
+                Always allow
+              </button>
+            )}
+            <button
+              className="rounded bg-blue-600 px-3 py-1.5 text-sm text-white"
+              onClick={() => respond(request.id, 'approve')}
+            >
+              Approve
+            </button>
+          </div>
+        </div>
+      ))}
+    </div>
+  )
+}
@@ -0,0 +1,83 @@
+import { useCallback, useEffect, useState } from 'react'
+import { TauriAPI, ConfirmationRequest } from '../lib/tauri-api'
+
+// Shown in the small window opened when an integration asks for a
+// destructive action. Ctrl/Cmd+Enter approves, Escape refuses; plain Enter
+// does nothing so a stray keypress can't approve.
+export default function ConfirmAction() {
+  const [request, setRequest] = useState<ConfirmationRequest | null>(null)
+  const [error, setError] = useState<string | null>(null)
+  const [answered, setAnswered] = useState(false)
+
+  useEffect(() => {
+    TauriAPI.getConfirmationRequest()
+      .then(setRequest)
+      .catch((e) => setError(String(e)))
+  }, [])
+
+  const respond = useCallback(
+    async (approved: boolean) => {
+      if (answered) return
+      setAnswered(true)
+      try {
+        await TauriAPI.respondToConfirmation(approved)
+      } catch (e) {
+        setError(String(e))
+      }
+    },
+    [answered]
+  )
+
+  useEffect(() => {
+    const onKeyDown = (event: KeyboardEvent) => {
+      if (event.key === 'Escape') {
+        event.preventDefault()
+        respond(false)
+      } else if (event.key === 'Enter' && (event.ctrlKey || event.metaKey)) {
+        event.preventDefault()
+        respond(true)
+      }
+    }
+    window.addEventListener('keydown', onKeyDown)
+    return () => window.removeEventListener('keydown', onKeyDown)
+  }, [respond])
+
+  if (error) {
+    return <div className="p-6 text-sm text-red-600">{error}</div>
+  }
+  if (!request) {
+    return <div className="p-6 text-sm text-gray-500">Loading…</div>
+  }
+
+  return (
+    <div className="flex h-screen flex-col gap-4 p-6">
+      <div>
+        <h1 className="text-lg font-semibold">{request.summary}</h1>
+        <p className="text-sm text-gray-500">Requested by {request.client}</p>
+      </div>
+
+      <ul className="flex-1 space-y-1 overflow-y-auto rounded border border-gray-200 p-3 font-mono text-xs">
+        {request.details.map((detail, i) => (
+          <li key={i}>{detail}</li>
+        ))}
+      </ul>
+
+      <div className="flex justify-end gap-2">
+        <button
+          className="rounded border border-gray-300 px-4 py-2 text-sm"
+          disabled={answered}
+          onClick={() => respond(false)}
+        >
+          Refuse <kbd className="ml-1 text-xs text-gray-500">Esc</kbd>
+        </button>
+        <button
+          className="rounded bg-red-600 px-4 py-2 text-sm text-white"
+          disabled={answered}
+          onClick={() => respond(true)}
+        >
+          Approve <kbd className="ml-1 text-xs opacity-75">Ctrl/⌘ + Enter</kbd>
+        </button>
+      </div>
+    </div>
+  )
+}
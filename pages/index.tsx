@@ -9,6 +9,9 @@ import KeyringService from '../lib/services/keyringService'
 
 type AuthStep = 'loading' | 'setMasterPassword' | 'unlockVault' | 'mainApp'
 
+// User input is reported to the inactivity lock at most this often
+const ACTIVITY_REPORT_MS = 30_000
+
 export default function Home() {
   const { isUnlocked, hasMasterPassword, setHasMasterPassword, setIsUnlocked } = useAppStore()
   const [currentAuthStep, setCurrentAuthStep] = useState<AuthStep>('loading')
@@ -44,6 +47,36 @@ export default function Home() {
     checkAuthStatus()
   }, [setHasMasterPassword, setIsUnlocked])
 
+  // The backend locks on its own (session expiry, inactivity), so follow it
+  useEffect(() => {
+    const unlisten = TauriAPI.onVaultStateChanged((unlocked) => {
+      if (!unlocked && useAppStore.getState().isUnlocked) {
+        useAppStore.getState().clearUnlockedState()
+      }
+    })
+    return () => {
+      unlisten.then((fn) => fn())
+    }
+  }, [])
+
+  // Report user input so the inactivity lock doesn't fire while in use
+  useEffect(() => {
+    if (currentAuthStep !== 'mainApp') return
+
+    let lastReport = 0
+    const report = () => {
+      const now = Date.now()
+      if (now - lastReport < ACTIVITY_REPORT_MS) return
+      lastReport = now
+      TauriAPI.recordActivity().catch(() => {})
+    }
+    const events = ['mousedown', 'keydown', 'wheel', 'touchstart']
+    events.forEach((name) => window.addEventListener(name, report, { passive: true }))
+    return () => {
+      events.forEach((name) => window.removeEventListener(name, report))
+    }
+  }, [currentAuthStep])
+
   // React to authentication state changes - simplified
   useEffect(() => {
     if (currentAuthStep === 'loading') return
@@ -0,0 +1,87 @@
+import { useEffect, useRef, useState } from 'react'
+import { TauriAPI, ApiKey } from '../lib/tauri-api'
+
+// Quick palette: a frameless window that searches the keys. Arrow keys move
+// the selection, Enter opens the key in the main window and Escape hides
+// the palette, which also hides when it loses focus.
+export default function Palette() {
+  const [query, setQuery] = useState('')
+  const [keys, setKeys] = useState<ApiKey[]>([])
+  const [selected, setSelected] = useState(0)
+  const [error, setError] = useState<string | null>(null)
+  const input = useRef<HTMLInputElement>(null)
+
+  useEffect(() => {
+    input.current?.focus()
+  }, [])
+
+  useEffect(() => {
+    const search = query.trim()
+      ? TauriAPI.searchApiKeys(query.trim())
+      : TauriAPI.getApiKeys()
+    search
+      .then((found) => {
+        setKeys(found.slice(0, 50))
+        setSelected(0)
+        setError(null)
+      })
+      .catch((e) => setError(String(e)))
+  }, [query])
+
+  const open = async (key: ApiKey) => {
+    try {
+      await TauriAPI.openKeyInMainWindow(key.id)
+      await TauriAPI.closeWindow('palette')
+      setQuery('')
+    } catch (e) {
+      setError(String(e))
+    }
+  }
+
+  const onKeyDown = (event: React.KeyboardEvent) => {
+    if (event.key === 'Escape') {
+      event.preventDefault()
+      TauriAPI.closeWindow('palette')
+    } else if (event.key === 'ArrowDown') {
+      event.preventDefault()
+      setSelected((i) => Math.min(i + 1, keys.length - 1))
+    } else if (event.key === 'ArrowUp') {
+      event.preventDefault()
+      setSelected((i) => Math.max(i - 1, 0))
+    } else if (event.key === 'Enter' && keys[selected]) {
+      event.preventDefault()
+      open(keys[selected])
+    }
+  }
+
+  return (
+    <div className="flex h-screen flex-col bg-white" onKeyDown={onKeyDown}>
+      <input
+        ref={input}
+        className="border-b border-gray-200 px-4 py-3 text-base outline-none"
+        placeholder="Search keys…"
+        value={query}
+        onChange={(e) => setQuery(e.target.value)}
+      />
+      {error ? (
+        <div className="p-4 text-sm text-red-600">{error}</div>
+      ) : (
+        <ul className="flex-1 overflow-y-auto py-1">
+          {keys.map((key, i) => (
+            <li
+              key={key.id}
+              className={`cursor-pointer px-4 py-2 text-sm ${
+                i === selected ? 'bg-blue-50' : ''
+              }`}
+              onMouseEnter={() => setSelected(i)}
+              onClick={() => open(key)}
+            >
+              <span className="font-medium">{key.name}</span>
+              <span className="ml-2 text-gray-500">{key.service}</span>
+            </li>
+          ))}
+        </ul>
+      )}
+    </div>
+  )
+}
@@ -0,0 +1,87 @@
+//! Vault performance at 100, 1k and 10k keys.
+//!
+//! Run with `cargo bench --bench vault`. Save and unlock write to a vault
+//! file in the temp dir, so they include the filesystem; `/api/keys` covers
+//! the listing and serialization done by the HTTP route, not the socket.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use keykeeper_lib::bench_support::{self, FileStorage};
+use std::path::PathBuf;
+
+const SIZES: &[usize] = &[100, 1_000, 10_000];
+
+fn vault_file(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("keykeeper-bench-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("bench temp dir");
+    dir.join(format!("{}.json", name))
+}
+
+fn load_and_save(c: &mut Criterion) {
+    let mut group = c.benchmark_group("vault");
+    // Every unlock derives the key from the password
+    group.sample_size(20);
+    for &size in SIZES {
+        let vault = bench_support::vault_with_keys(size);
+        let storage = FileStorage::new(vault_file(&format!("vault-{}", size)));
+        bench_support::save(&storage, &vault).expect("initial save");
+        group.throughput(Throughput::Elements(size as u64));
+
+        group.bench_with_input(BenchmarkId::new("save", size), &vault, |b, vault| {
+            b.iter(|| bench_support::save(&storage, black_box(vault)).unwrap())
+        });
+        group.bench_function(BenchmarkId::new("unlock", size), |b| {
+            b.iter(|| bench_support::unlock(&storage).unwrap())
+        });
+        group.bench_function(BenchmarkId::new("load_locked", size), |b| {
+            b.iter(|| bench_support::load_locked(&storage).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn search(c: &mut Criterion) {
+    let mut group = c.benchmark_group("search");
+    for &size in SIZES {
+        let vault = bench_support::vault_with_keys(size);
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::new("keys", size), &vault, |b, vault| {
+            b.iter(|| bench_support::search_keys(vault, black_box("stripe")))
+        });
+
+        let docs = bench_support::docs_with(size);
+        group.bench_with_input(BenchmarkId::new("docs", size), &docs, |b, docs| {
+            b.iter(|| bench_support::search_docs(docs, black_box("webhooks")))
+        });
+    }
+    group.finish();
+}
+
+fn api_keys(c: &mut Criterion) {
+    let mut group = c.benchmark_group("api_keys");
+    for &size in SIZES {
+        let vault = bench_support::vault_with_keys(size);
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::new("list", size), &vault, |b, vault| {
+            let mut index = bench_support::index();
+            b.iter(|| bench_support::get_keys(vault, &mut index, None))
+        });
+        group.bench_with_input(BenchmarkId::new("page", size), &vault, |b, vault| {
+            let mut index = bench_support::index();
+            b.iter(|| bench_support::get_keys(vault, &mut index, Some("limit=50")))
+        });
+        group.bench_with_input(BenchmarkId::new("filtered", size), &vault, |b, vault| {
+            let mut index = bench_support::index();
+            b.iter(|| {
+                bench_support::get_keys(
+                    vault,
+                    &mut index,
+                    Some("service=Stripe&environment=production"),
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, load_and_save, search, api_keys);
+criterion_main!(benches);
@@ -0,0 +1,510 @@
+//! Periodic access reviews.
+//!
+//! A review cycle lists every active key under whoever uses it: the project
+//! it's assigned to and each client with an "always allow" rule for it.
+//! Keys used by nobody are listed on their own. Each entry is attested as
+//! still needed or revoked; revoking takes effect right away (the client's
+//! rule is removed, the key is unassigned from the project, an unused key is
+//! deactivated). The cycle completes once every entry is attested, and the
+//! next one is due `interval_days` later.
+//!
+//! Attestations are kept in the vault with their time. An open cycle past
+//! its due date, or a next cycle not started in time, is overdue: it's in
+//! the security report and the scheduled notifications.
+
+use crate::errors::KeyKeeperError;
+use crate::{archive, guards, key_history, log_audit_event, save_vault, ApiKeyVault, AppState};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+const DEFAULT_DUE_IN_DAYS: u32 = 14;
+const DEFAULT_INTERVAL_DAYS: u32 = 90;
+const MAX_DAYS: u32 = 3650;
+/// Completed reviews kept for history
+const MAX_REVIEWS: usize = 50;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum SubjectKind {
+    Project,
+    Client,
+    /// Keys no project or client uses
+    Unassigned,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Attestation {
+    StillNeeded,
+    Revoke,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewItem {
+    pub id: String,
+    pub key_id: String,
+    pub key_name: String,
+    pub service: String,
+    pub attestation: Option<Attestation>,
+    #[serde(default, with = "crate::timestamps::rfc3339_option")]
+    pub attested_at: Option<DateTime<Utc>>,
+    pub note: Option<String>,
+}
+
+/// Keys used by one project or client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewGroup {
+    pub kind: SubjectKind,
+    /// Project path or client name; `None` for unassigned keys
+    pub subject: Option<String>,
+    pub items: Vec<ReviewItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessReview {
+    pub id: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub due_at: DateTime<Utc>,
+    /// Days from completion to the next cycle
+    pub interval_days: u32,
+    #[serde(default, with = "crate::timestamps::rfc3339_option")]
+    pub completed_at: Option<DateTime<Utc>>,
+    pub groups: Vec<ReviewGroup>,
+}
+
+impl AccessReview {
+    fn items(&self) -> impl Iterator<Item = &ReviewItem> {
+        self.groups.iter().flat_map(|g| g.items.iter())
+    }
+
+    /// Attested entries out of all of them
+    pub fn progress(&self) -> (usize, usize) {
+        let attested = self.items().filter(|i| i.attestation.is_some()).count();
+        (attested, self.items().count())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessReviewStatus {
+    pub open_review_id: Option<String>,
+    #[serde(default, with = "crate::timestamps::rfc3339_option")]
+    pub due_at: Option<DateTime<Utc>>,
+    pub overdue: bool,
+    #[serde(default, with = "crate::timestamps::rfc3339_option")]
+    pub last_completed_at: Option<DateTime<Utc>>,
+}
+
+pub fn open_review(vault: &ApiKeyVault) -> Option<&AccessReview> {
+    vault
+        .access_reviews
+        .iter()
+        .find(|r| r.completed_at.is_none())
+}
+
+fn last_completed(vault: &ApiKeyVault) -> Option<&AccessReview> {
+    vault
+        .access_reviews
+        .iter()
+        .filter(|r| r.completed_at.is_some())
+        .max_by_key(|r| r.completed_at)
+}
+
+/// When the open cycle, or else the next one, is due. `None` before the
+/// first cycle.
+pub fn next_due(vault: &ApiKeyVault) -> Option<DateTime<Utc>> {
+    match open_review(vault) {
+        Some(review) => Some(review.due_at),
+        None => last_completed(vault).and_then(|r| {
+            r.completed_at
+                .map(|at| at + Duration::days(r.interval_days as i64))
+        }),
+    }
+}
+
+pub fn is_overdue(vault: &ApiKeyVault, now: DateTime<Utc>) -> bool {
+    next_due(vault).is_some_and(|due| due <= now)
+}
+
+pub fn status(vault: &ApiKeyVault, now: DateTime<Utc>) -> AccessReviewStatus {
+    AccessReviewStatus {
+        open_review_id: open_review(vault).map(|r| r.id.clone()),
+        due_at: next_due(vault),
+        overdue: is_overdue(vault, now),
+        last_completed_at: last_completed(vault).and_then(|r| r.completed_at),
+    }
+}
+
+/// A new cycle over the vault's active keys, grouped by who uses them
+pub fn build(
+    vault: &ApiKeyVault,
+    now: DateTime<Utc>,
+    due_in_days: u32,
+    interval_days: u32,
+) -> AccessReview {
+    let mut entries: Vec<(SubjectKind, Option<String>, ReviewItem)> = Vec::new();
+    for key in vault
+        .keys
+        .values()
+        .filter(|k| k.is_active && !archive::is_archived(k))
+    {
+        let item = || ReviewItem {
+            id: uuid::Uuid::new_v4().to_string(),
+            key_id: key.id.clone(),
+            key_name: key.name.clone(),
+            service: key.service.clone(),
+            attestation: None,
+            attested_at: None,
+            note: None,
+        };
+        let mut users: Vec<(SubjectKind, Option<String>)> = key
+            .project_path
+            .iter()
+            .map(|path| (SubjectKind::Project, Some(path.clone())))
+            .collect();
+        users.extend(
+            vault
+                .approval_rules
+                .iter()
+                .filter(|rule| rule.key_id == key.id)
+                .map(|rule| (SubjectKind::Client, Some(rule.client.clone()))),
+        );
+        users.sort();
+        users.dedup();
+        if users.is_empty() {
+            users.push((SubjectKind::Unassigned, None));
+        }
+        entries.extend(
+            users
+                .into_iter()
+                .map(|(kind, subject)| (kind, subject, item())),
+        );
+    }
+    entries.sort_by(|a, b| (a.0, &a.1, &a.2.key_name).cmp(&(b.0, &b.1, &b.2.key_name)));
+
+    let mut groups: Vec<ReviewGroup> = Vec::new();
+    for (kind, subject, item) in entries {
+        match groups.last_mut() {
+            Some(group) if group.kind == kind && group.subject == subject => group.items.push(item),
+            _ => groups.push(ReviewGroup {
+                kind,
+                subject,
+                items: vec![item],
+            }),
+        }
+    }
+
+    AccessReview {
+        id: uuid::Uuid::new_v4().to_string(),
+        created_at: now,
+        due_at: now + Duration::days(due_in_days as i64),
+        interval_days,
+        completed_at: None,
+        groups,
+    }
+}
+
+/// Take away the subject's use of the key
+fn revoke(vault: &mut ApiKeyVault, kind: SubjectKind, subject: Option<&str>, key_id: &str) {
+    match kind {
+        SubjectKind::Client => vault
+            .approval_rules
+            .retain(|rule| !(rule.key_id == key_id && Some(rule.client.as_str()) == subject)),
+        SubjectKind::Project => {
+            if let Some(key) = vault
+                .keys
+                .get_mut(key_id)
+                .filter(|k| k.project_path.as_deref() == subject)
+            {
+                key.project_path = None;
+                key.package_path = None;
+                key_history::record(&mut vault.key_history, key, false);
+            }
+        }
+        SubjectKind::Unassigned => {
+            if let Some(key) = vault.keys.get_mut(key_id) {
+                key.is_active = false;
+                key_history::record(&mut vault.key_history, key, false);
+            }
+        }
+    }
+}
+
+/// Record an attestation on the open review, revoking if asked. Returns a
+/// description for the audit log and whether the review is now complete.
+pub fn attest(
+    vault: &mut ApiKeyVault,
+    review_id: &str,
+    item_id: &str,
+    attestation: Attestation,
+    note: Option<String>,
+    now: DateTime<Utc>,
+) -> Result<(String, bool), KeyKeeperError> {
+    let review = vault
+        .access_reviews
+        .iter_mut()
+        .find(|r| r.id == review_id)
+        .ok_or_else(|| KeyKeeperError::not_found("Access review"))?;
+    if review.completed_at.is_some() {
+        return Err(KeyKeeperError::Conflict(
+            "The access review is already complete".to_string(),
+        ));
+    }
+    let (kind, subject, item) = review
+        .groups
+        .iter_mut()
+        .find_map(|group| {
+            let item = group.items.iter_mut().find(|i| i.id == item_id)?;
+            Some((group.kind, group.subject.clone(), item))
+        })
+        .ok_or_else(|| KeyKeeperError::not_found("Review entry"))?;
+    if item.attestation == Some(Attestation::Revoke) {
+        return Err(KeyKeeperError::Conflict(
+            "Access was already revoked".to_string(),
+        ));
+    }
+    item.attestation = Some(attestation);
+    item.attested_at = Some(now);
+    item.note = note.filter(|n| !n.trim().is_empty());
+    let key_id = item.key_id.clone();
+    let description = format!(
+        "{} for {}: {}",
+        item.key_name,
+        subject.as_deref().unwrap_or("nobody"),
+        match attestation {
+            Attestation::StillNeeded => "still needed",
+            Attestation::Revoke => "revoked",
+        }
+    );
+
+    let (attested, total) = review.progress();
+    let complete = attested == total;
+    if complete {
+        review.completed_at = Some(now);
+    }
+    if attestation == Attestation::Revoke {
+        revoke(vault, kind, subject.as_deref(), &key_id);
+    }
+    Ok((description, complete))
+}
+
+fn days(value: Option<u32>, default: u32, what: &str) -> Result<u32, KeyKeeperError> {
+    match value.unwrap_or(default) {
+        days @ 1..=MAX_DAYS => Ok(days),
+        _ => Err(KeyKeeperError::Validation(format!(
+            "{} must be between 1 and {} days",
+            what, MAX_DAYS
+        ))),
+    }
+}
+
+/// Start a review cycle. There is at most one open at a time.
+#[tauri::command]
+pub async fn start_access_review(
+    due_in_days: Option<u32>,
+    interval_days: Option<u32>,
+    state: State<'_, AppState>,
+) -> Result<AccessReview, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let due_in_days = days(due_in_days, DEFAULT_DUE_IN_DAYS, "The due date")?;
+    let interval_days = days(interval_days, DEFAULT_INTERVAL_DAYS, "The interval")?;
+
+    let mut vault_guard = state.vault.lock().await;
+    if open_review(&vault_guard).is_some() {
+        return Err(KeyKeeperError::Conflict(
+            "An access review is already open".to_string(),
+        ));
+    }
+    let review = build(&vault_guard, Utc::now(), due_in_days, interval_days);
+    vault_guard.access_reviews.push(review.clone());
+    if vault_guard.access_reviews.len() > MAX_REVIEWS {
+        let excess = vault_guard.access_reviews.len() - MAX_REVIEWS;
+        vault_guard.access_reviews.drain(..excess);
+    }
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    let (_, total) = review.progress();
+    log_audit_event(
+        &state,
+        "start_access_review",
+        "access_review",
+        Some(&review.id),
+        true,
+        Some(&format!("{} entries", total)),
+    )
+    .await;
+    Ok(review)
+}
+
+/// The review with `review_id`, or the open one
+#[tauri::command]
+pub async fn get_access_review(
+    review_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Option<AccessReview>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    Ok(match review_id {
+        Some(id) => vault_guard
+            .access_reviews
+            .iter()
+            .find(|r| r.id == id)
+            .cloned(),
+        None => open_review(&vault_guard).cloned(),
+    })
+}
+
+/// Every review kept, the latest first
+#[tauri::command]
+pub async fn list_access_reviews(
+    state: State<'_, AppState>,
+) -> Result<Vec<AccessReview>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    Ok(vault_guard.access_reviews.iter().rev().cloned().collect())
+}
+
+#[tauri::command]
+pub async fn get_access_review_status(
+    state: State<'_, AppState>,
+) -> Result<AccessReviewStatus, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    Ok(status(&*state.vault.lock().await, Utc::now()))
+}
+
+/// Attest one entry of the open review as still needed or revoked
+#[tauri::command]
+pub async fn attest_access(
+    review_id: String,
+    item_id: String,
+    attestation: Attestation,
+    note: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<AccessReview, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let (description, complete) = attest(
+        &mut vault_guard,
+        &review_id,
+        &item_id,
+        attestation,
+        note,
+        Utc::now(),
+    )?;
+    let review = vault_guard
+        .access_reviews
+        .iter()
+        .find(|r| r.id == review_id)
+        .cloned()
+        .ok_or_else(|| KeyKeeperError::not_found("Access review"))?;
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "attest_access",
+        "access_review",
+        Some(&review_id),
+        true,
+        Some(&description),
+    )
+    .await;
+    if complete {
+        log_audit_event(
+            &state,
+            "complete_access_review",
+            "access_review",
+            Some(&review_id),
+            true,
+            None,
+        )
+        .await;
+    }
+    Ok(review)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approvals::ApprovalRule;
+    use crate::vault_tests::test_key;
+
+    #[test]
+    fn revoking_every_use_completes_the_cycle() {
+        let now = Utc::now();
+        let mut vault = ApiKeyVault::default();
+        for key in [test_key("shop").project(Some("/shop")), test_key("spare")] {
+            let key = key.secret("sk_1").environment("production").build();
+            vault.keys.insert(key.id.clone(), key);
+        }
+        vault.approval_rules.push(ApprovalRule {
+            id: "r1".to_string(),
+            key_id: "shop".to_string(),
+            client_id: Some("c1".to_string()),
+            client: "vscode".to_string(),
+            created_at: now,
+            last_used_at: None,
+        });
+
+        let review = build(&vault, now, 14, 90);
+        let subjects: Vec<(SubjectKind, Option<&str>)> = review
+            .groups
+            .iter()
+            .map(|g| (g.kind, g.subject.as_deref()))
+            .collect();
+        assert_eq!(
+            subjects,
+            [
+                (SubjectKind::Project, Some("/shop")),
+                (SubjectKind::Client, Some("vscode")),
+                (SubjectKind::Unassigned, None),
+            ]
+        );
+        let ids: Vec<String> = review.items().map(|i| i.id.clone()).collect();
+        let review_id = review.id.clone();
+        vault.access_reviews.push(review);
+        assert!(!is_overdue(&vault, now));
+        assert!(is_overdue(&vault, now + Duration::days(15)));
+
+        attest(
+            &mut vault,
+            &review_id,
+            &ids[0],
+            Attestation::StillNeeded,
+            None,
+            now,
+        )
+        .unwrap();
+        let (description, complete) = attest(
+            &mut vault,
+            &review_id,
+            &ids[1],
+            Attestation::Revoke,
+            None,
+            now,
+        )
+        .unwrap();
+        assert_eq!(description, "shop for vscode: revoked");
+        assert!(!complete && vault.approval_rules.is_empty());
+
+        let (_, complete) = attest(
+            &mut vault,
+            &review_id,
+            &ids[2],
+            Attestation::Revoke,
+            None,
+            now,
+        )
+        .unwrap();
+        assert!(complete && !vault.keys["spare"].is_active);
+        assert_eq!(vault.keys["shop"].project_path.as_deref(), Some("/shop"));
+        assert_eq!(next_due(&vault), Some(now + Duration::days(90)));
+    }
+}
@@ -0,0 +1,296 @@
+//! Recent activity feed.
+//!
+//! Each entry says who acted (the desktop UI, an HTTP client by the name it
+//! gives, `keykeeper exec`, or KeyKeeper itself), where HTTP requests came
+//! from, and for updates a readable summary of what changed
+//! ("environment: dev → production"). Secret values are never part of a
+//! summary, only the fact that they changed.
+
+use crate::errors::KeyKeeperError;
+use crate::{guards, ApiKey, ApiKeyVault, AppState, RecentActivity};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 1000;
+/// Client name `keykeeper exec` sends
+const CLI_CLIENT: &str = "keykeeper-exec";
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActorKind {
+    #[default]
+    Ui,
+    Http,
+    Cli,
+    System,
+}
+
+impl ActorKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            ActorKind::Ui => "ui",
+            ActorKind::Http => "http",
+            ActorKind::Cli => "cli",
+            ActorKind::System => "system",
+        }
+    }
+}
+
+/// Who performed an action
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Actor {
+    pub kind: ActorKind,
+    /// Client name for HTTP and CLI actors, job name for system ones
+    pub name: Option<String>,
+    /// Set for HTTP clients that proved their registration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+}
+
+impl Actor {
+    pub fn ui() -> Self {
+        Self::default()
+    }
+
+    pub fn system(job: &str) -> Self {
+        Self {
+            kind: ActorKind::System,
+            name: Some(job.to_string()),
+            client_id: None,
+        }
+    }
+
+    /// The client behind an HTTP request, as `approvals::client_name` names it
+    pub fn http(headers: &hyper::HeaderMap) -> Self {
+        let name = crate::approvals::client_name(headers);
+        let kind = if name == CLI_CLIENT {
+            ActorKind::Cli
+        } else {
+            ActorKind::Http
+        };
+        Self {
+            kind,
+            name: Some(name),
+            client_id: crate::clients::current().and_then(|c| c.client_id),
+        }
+    }
+
+    fn matches(&self, query: &str) -> bool {
+        self.kind.as_str().eq_ignore_ascii_case(query)
+            || self
+                .name
+                .as_deref()
+                .is_some_and(|name| name.eq_ignore_ascii_case(query))
+    }
+}
+
+/// Where an HTTP-originated action came from
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ActivitySource {
+    pub ip: Option<String>,
+    /// The client's user agent
+    pub device: Option<String>,
+}
+
+impl ActivitySource {
+    pub fn http(headers: &hyper::HeaderMap, addr: std::net::SocketAddr) -> Self {
+        Self {
+            ip: Some(addr.ip().to_string()),
+            device: headers
+                .get("user-agent")
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.trim().chars().take(200).collect())
+                .filter(|v: &String| !v.is_empty()),
+        }
+    }
+}
+
+fn show(value: &str) -> &str {
+    if value.is_empty() {
+        "(none)"
+    } else {
+        value
+    }
+}
+
+/// "field: before → after" for every changed field, `None` when nothing
+/// visible changed
+pub fn describe_changes(before: &ApiKey, after: &ApiKey) -> Option<String> {
+    let mut changes: Vec<String> = Vec::new();
+    let mut field = |name: &str, old: &str, new: &str| {
+        if old != new {
+            changes.push(format!("{}: {} → {}", name, show(old), show(new)));
+        }
+    };
+    field("name", &before.name, &after.name);
+    field("service", &before.service, &after.service);
+    field("environment", &before.environment, &after.environment);
+    field(
+        "description",
+        before.description.as_deref().unwrap_or_default(),
+        after.description.as_deref().unwrap_or_default(),
+    );
+    field(
+        "project",
+        before.project_path.as_deref().unwrap_or_default(),
+        after.project_path.as_deref().unwrap_or_default(),
+    );
+    let expires = |key: &ApiKey| key.expires_at.map(|e| e.to_rfc3339()).unwrap_or_default();
+    field("expires", &expires(before), &expires(after));
+    if before.is_active != after.is_active {
+        changes.push(if after.is_active {
+            "activated".to_string()
+        } else {
+            "deactivated".to_string()
+        });
+    }
+    for (name, old, new) in [
+        ("tags", &before.tags, &after.tags),
+        ("scopes", &before.scopes, &after.scopes),
+    ] {
+        let added: Vec<String> = new
+            .iter()
+            .filter(|item| !old.contains(item))
+            .map(|item| format!("+{}", item))
+            .collect();
+        let removed: Vec<String> = old
+            .iter()
+            .filter(|item| !new.contains(item))
+            .map(|item| format!("-{}", item))
+            .collect();
+        if !added.is_empty() || !removed.is_empty() {
+            changes.push(format!("{}: {}", name, [added, removed].concat().join(" ")));
+        }
+    }
+    if before.key != after.key || before.values != after.values {
+        changes.push("value changed".to_string());
+    }
+
+    (!changes.is_empty()).then(|| changes.join("; "))
+}
+
+/// Add an entry to the feed, dropping the oldest beyond the retention limit,
+/// and a revision of the key to its history
+pub fn record(
+    vault: &mut ApiKeyVault,
+    activity_type: &str,
+    key: &ApiKey,
+    actor: Actor,
+    source: Option<ActivitySource>,
+    details: Option<String>,
+) {
+    vault.recent_activity.push(RecentActivity {
+        id: format!("activity_{}", uuid::Uuid::new_v4()),
+        activity_type: activity_type.to_string(),
+        key_id: key.id.clone(),
+        key_name: key.name.clone(),
+        timestamp: Utc::now(),
+        details,
+        actor: Some(actor),
+        source,
+    });
+    crate::retention::cap_recent_activity(vault);
+    crate::key_history::record(&mut vault.key_history, key, activity_type == "key_deleted");
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActivityFilter {
+    pub key_id: Option<String>,
+    /// An actor kind (`ui`, `http`, `cli`, `system`) or a client name
+    pub actor: Option<String>,
+    pub activity_type: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Matching entries, most recent first
+pub fn query(vault: &ApiKeyVault, filter: &ActivityFilter) -> Vec<RecentActivity> {
+    let mut activities: Vec<RecentActivity> = vault
+        .recent_activity
+        .iter()
+        .filter(|a| filter.key_id.as_ref().is_none_or(|id| a.key_id == *id))
+        .filter(|a| {
+            filter
+                .activity_type
+                .as_ref()
+                .is_none_or(|t| a.activity_type == *t)
+        })
+        .filter(|a| {
+            filter.actor.as_ref().is_none_or(|query| {
+                // Entries from before actors were recorded came from the UI
+                a.actor.clone().unwrap_or_default().matches(query)
+            })
+        })
+        .cloned()
+        .collect();
+    activities.sort_by_key(|a| std::cmp::Reverse(a.timestamp));
+    activities.truncate(filter.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT));
+    activities
+}
+
+#[tauri::command]
+pub async fn get_recent_activity(
+    filter: Option<ActivityFilter>,
+    state: State<'_, AppState>,
+) -> Result<Vec<RecentActivity>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    Ok(query(&vault_guard, &filter.unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault_tests::test_key;
+
+    #[test]
+    fn summaries_name_the_changed_fields_but_not_values() {
+        let before = test_key("k")
+            .name("STRIPE_KEY")
+            .environment("dev")
+            .tags(&["billing", "legacy"])
+            .build();
+        let mut after = test_key("k")
+            .name("STRIPE_KEY")
+            .environment("production")
+            .tags(&["billing", "live"])
+            .build();
+        after.key = "sk_live_secret".to_string();
+
+        let summary = describe_changes(&before, &after).unwrap();
+        assert_eq!(
+            summary,
+            "environment: dev → production; tags: +live -legacy; value changed"
+        );
+        assert!(!summary.contains("sk_live_secret"));
+        assert_eq!(describe_changes(&before, &before), None);
+    }
+
+    #[test]
+    fn feed_filters_by_actor_kind_or_client_name() {
+        let mut vault = ApiKeyVault::default();
+        let key = test_key("k").name("STRIPE_KEY").environment("dev").build();
+        record(&mut vault, "key_updated", &key, Actor::ui(), None, None);
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert(crate::approvals::CLIENT_HEADER, CLI_CLIENT.parse().unwrap());
+        record(
+            &mut vault,
+            "key_used",
+            &key,
+            Actor::http(&headers),
+            None,
+            None,
+        );
+
+        let by = |actor: &str| ActivityFilter {
+            actor: Some(actor.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(query(&vault, &by("cli")).len(), 1);
+        assert_eq!(query(&vault, &by(CLI_CLIENT)).len(), 1);
+        assert_eq!(query(&vault, &by("ui"))[0].activity_type, "key_updated");
+        assert!(query(&vault, &by("http")).is_empty());
+    }
+}
@@ -4,6 +4,7 @@ use crate::contextual_suggestions::{
     SmartTemplateMatchingResult,
 };
 use crate::enhanced_api_commands::GenerationSession;
+use crate::errors::KeyKeeperError;
 use crate::llm_proxy::LLMResponse;
 use crate::llm_wrapper::LLMEngine;
 use crate::template_engine::EnhancedTemplateEngine;
@@ -162,7 +163,7 @@ pub struct ContextualDocumentationResponse {
 pub async fn analyze_project_with_ai(
     app_handle: AppHandle,
     request: AnalyzeProjectRequest,
-) -> Result<AnalyzeProjectResponse, String> {
+) -> Result<AnalyzeProjectResponse, KeyKeeperError> {
     let start_time = std::time::Instant::now();
     info!("Starting AI project analysis for: {}", request.project_path);
 
@@ -219,7 +220,7 @@ pub async fn analyze_project_with_ai(
 pub async fn get_contextual_suggestions(
     app_handle: AppHandle,
     request: ContextualSuggestionsRequest,
-) -> Result<ContextualSuggestionsResponse, String> {
+) -> Result<ContextualSuggestionsResponse, KeyKeeperError> {
     let start_time = std::time::Instant::now();
     info!("Generating contextual suggestions for: {}", request.project_path);
 
@@ -297,7 +298,7 @@ pub async fn get_contextual_suggestions(
 pub async fn get_smart_template_recommendations(
     app_handle: AppHandle,
     request: SmartTemplateMatchingRequest,
-) -> Result<SmartTemplateMatchingResult, String> {
+) -> Result<SmartTemplateMatchingResult, KeyKeeperError> {
     info!("Getting smart template recommendations for: {}", request.project_path);
 
     let ai_state = app_handle.state::<AIState>();
@@ -309,7 +310,7 @@ pub async fn get_smart_template_recommendations(
         }
         Err(e) => {
             error!("Failed to generate smart template recommendations: {}", e);
-            Err(e.to_string())
+            Err(KeyKeeperError::Internal(e.to_string()))
         }
     }
 }
@@ -319,7 +320,7 @@ pub async fn get_smart_template_recommendations(
 pub async fn analyze_code_quality_with_ai(
     app_handle: AppHandle,
     request: CodeQualityAnalysisRequest,
-) -> Result<CodeQualityAnalysisResponse, String> {
+) -> Result<CodeQualityAnalysisResponse, KeyKeeperError> {
     let start_time = std::time::Instant::now();
     info!("Analyzing code quality for: {}", request.project_path);
 
@@ -341,7 +342,7 @@ pub async fn analyze_code_quality_with_ai(
 
     // Extract code quality information
     let quality_analysis = serde_json::to_value(&project_analysis.code_quality)
-        .map_err(|e| format!("Failed to serialize quality analysis: {}", e))?;
+        .map_err(|e| KeyKeeperError::Internal(format!("Failed to serialize quality analysis: {}", e)))?;
 
     info!("Code quality analysis completed for: {}", request.project_path);
     Ok(CodeQualityAnalysisResponse {
@@ -357,7 +358,7 @@ pub async fn analyze_code_quality_with_ai(
 pub async fn generate_contextual_documentation(
     app_handle: AppHandle,
     request: ContextualDocumentationRequest,
-) -> Result<ContextualDocumentationResponse, String> {
+) -> Result<ContextualDocumentationResponse, KeyKeeperError> {
     let start_time = std::time::Instant::now();
     info!("Generating contextual documentation for: {} (type: {})", 
           request.project_path, request.doc_type);
@@ -392,7 +393,7 @@ pub async fn generate_contextual_documentation(
 
     // Build documentation prompt
     let prompt = build_documentation_prompt(&request, &project_analysis).await
-        .map_err(|e| format!("Failed to build documentation prompt: {}", e))?;
+        .map_err(|e| KeyKeeperError::Internal(format!("Failed to build documentation prompt: {}", e)))?;
 
     // Generate documentation using LLM
     let response_result = {
@@ -435,7 +436,7 @@ pub async fn generate_contextual_documentation(
 
 /// Check if AI features are available
 #[tauri::command]
-pub async fn check_ai_features_status(app_handle: AppHandle) -> Result<HashMap<String, bool>, String> {
+pub async fn check_ai_features_status(app_handle: AppHandle) -> Result<HashMap<String, bool>, KeyKeeperError> {
     let ai_state = app_handle.state::<AIState>();
     let llm_guard = ai_state.llm_engine.read().await;
     
@@ -452,7 +453,7 @@ pub async fn check_ai_features_status(app_handle: AppHandle) -> Result<HashMap<S
 
 /// Clear AI analysis cache
 #[tauri::command]
-pub async fn clear_ai_analysis_cache(app_handle: AppHandle) -> Result<bool, String> {
+pub async fn clear_ai_analysis_cache(app_handle: AppHandle) -> Result<bool, KeyKeeperError> {
     let ai_state = app_handle.state::<AIState>();
     let mut cache = ai_state.analysis_cache.write().await;
     let cache_size = cache.len();
@@ -464,7 +465,7 @@ pub async fn clear_ai_analysis_cache(app_handle: AppHandle) -> Result<bool, Stri
 
 /// Get AI analysis cache statistics
 #[tauri::command]
-pub async fn get_ai_cache_stats(app_handle: AppHandle) -> Result<HashMap<String, Value>, String> {
+pub async fn get_ai_cache_stats(app_handle: AppHandle) -> Result<HashMap<String, Value>, KeyKeeperError> {
     let ai_state = app_handle.state::<AIState>();
     let cache = ai_state.analysis_cache.read().await;
     
@@ -637,7 +638,7 @@ fn create_documentation_metadata(
 async fn generate_basic_documentation(
     request: &ContextualDocumentationRequest,
     start_time: std::time::Instant,
-) -> Result<ContextualDocumentationResponse, String> {
+) -> Result<ContextualDocumentationResponse, KeyKeeperError> {
     let basic_doc = match request.doc_type.as_str() {
         "README" => generate_basic_readme(&request.project_path),
         "API" => "# API Documentation\n\nPlease refer to the source code for API details.".to_string(),
@@ -2,6 +2,7 @@ use crate::enhanced_types::{FrameworkDetectionResult, DetectionEvidence};
 use crate::framework_detector::{FrameworkDetector, FrameworkDetectionRule};
 use crate::llm_proxy::LLMResponse;
 use crate::llm_wrapper::LLMEngine;
+use crate::project_ignore::{ProjectIgnores, Purpose};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -162,7 +163,8 @@ impl AIEnhancedDetector {
         };
 
         // Analyze directory structure
-        self.analyze_directory_structure(project_path, &mut info, 0, 3).await?;
+        let mut ignores = ProjectIgnores::load(project_path);
+        self.analyze_directory_structure(project_path, &mut info, &mut ignores, 0, 3).await?;
 
         // Read key configuration files
         self.read_key_files(project_path, &mut info).await?;
@@ -181,6 +183,7 @@ impl AIEnhancedDetector {
         &'a self,
         dir: &'a Path,
         info: &'a mut ProjectInformation,
+        ignores: &'a mut ProjectIgnores,
         depth: usize,
         max_depth: usize,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + 'a>> {
@@ -202,16 +205,19 @@ impl AIEnhancedDetector {
                 .to_string_lossy()
                 .to_string();
 
+            if ignores.is_ignored(&path, path.is_dir(), Purpose::Scan) {
+                continue;
+            }
             if path.is_dir() {
                 let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
                 
-                // Skip common directories that don't provide value
-                if ["node_modules", ".git", ".next", "dist", "build", ".vscode", ".idea"].contains(&dir_name) {
+                // Skip editor settings, which don't provide value
+                if [".vscode", ".idea"].contains(&dir_name) {
                     continue;
                 }
 
                 info.directory_structure.push(relative_path);
-                self.analyze_directory_structure(&path, info, depth + 1, max_depth).await?;
+                self.analyze_directory_structure(&path, info, ignores, depth + 1, max_depth).await?;
             } else if path.is_file() {
                 info.file_count += 1;
                 
@@ -291,9 +297,10 @@ impl AIEnhancedDetector {
         let max_samples_per_extension = 3;
         let max_file_size = 2000; // chars
 
+        let mut ignores = ProjectIgnores::load(project_path);
         for ext in code_extensions {
             let mut samples = Vec::new();
-            self.collect_file_samples(project_path, ext, &mut samples, max_samples_per_extension, max_file_size).await?;
+            self.collect_file_samples(project_path, ext, &mut ignores, &mut samples, max_samples_per_extension, max_file_size).await?;
             
             if !samples.is_empty() {
                 info.file_samples.insert(ext.to_string(), samples);
@@ -308,6 +315,7 @@ impl AIEnhancedDetector {
         &'a self,
         dir: &'a Path,
         extension: &'a str,
+        ignores: &'a mut ProjectIgnores,
         samples: &'a mut Vec<String>,
         max_samples: usize,
         max_size: usize,
@@ -330,11 +338,12 @@ impl AIEnhancedDetector {
             let entry = entry?;
             let path = entry.path();
             
+            // Ignored files never end up in a prompt
+            if ignores.is_ignored(&path, path.is_dir(), Purpose::Scan) {
+                continue;
+            }
             if path.is_dir() {
-                let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                if !["node_modules", ".git", ".next", "dist", "build"].contains(&dir_name) {
-                    self.collect_file_samples(&path, extension, samples, max_samples, max_size).await?;
-                }
+                self.collect_file_samples(&path, extension, ignores, samples, max_samples, max_size).await?;
             } else if path.is_file() {
                 if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
                     if ext == extension {
@@ -23,6 +23,31 @@ pub struct ApiProvider {
     pub category: String,
     pub dependencies: Vec<String>,
     pub config_templates: Vec<ConfigTemplate>,
+    /// Environment a key belongs to, told by its format (test vs live keys)
+    #[serde(default)]
+    pub environment_rules: Vec<EnvironmentRule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentRule {
+    /// Regex matched against the key's value
+    pub pattern: String,
+    pub environment: String,
+}
+
+impl ApiProvider {
+    /// Environment of the first rule matching `value`
+    pub fn environment_for(&self, value: &str) -> Option<&str> {
+        self.environment_rules
+            .iter()
+            .find(|rule| {
+                regex::Regex::new(&rule.pattern)
+                    .map(|re| re.is_match(value))
+                    .unwrap_or(false)
+            })
+            .map(|rule| rule.environment.as_str())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,6 +90,47 @@ pub struct GeneratedFile {
     pub language: String,
 }
 
+/// Framework targeted by `scaffold_files`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScaffoldFramework {
+    Nextjs,
+    Express,
+    Fastapi,
+}
+
+impl ScaffoldFramework {
+    pub fn parse(framework: &str) -> Option<Self> {
+        match framework.to_lowercase().replace(['.', '-', ' '], "").as_str() {
+            "nextjs" | "next" => Some(Self::Nextjs),
+            "express" | "expressjs" | "node" => Some(Self::Express),
+            "fastapi" => Some(Self::Fastapi),
+            _ => None,
+        }
+    }
+}
+
+/// A file written into a project by `scaffold_integration`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScaffoldedFile {
+    /// Relative to the project root
+    pub path: String,
+    /// SHA-256 of the content as written, to tell whether it was edited since
+    pub sha256: String,
+}
+
+/// What a scaffold added to a project, kept so it can be removed later
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScaffoldRecord {
+    pub provider_id: String,
+    pub framework: ScaffoldFramework,
+    pub files: Vec<ScaffoldedFile>,
+    /// Variables appended to `.env.example`
+    pub env_example_vars: Vec<String>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocScrapingResult {
     pub url: String,
@@ -177,6 +243,7 @@ impl ApiGeneratorService {
                     dependencies: vec!["better-auth".to_string()],
                 }
             ],
+            environment_rules: vec![],
         });
 
         // OpenAI
@@ -203,6 +270,35 @@ impl ApiGeneratorService {
                     dependencies: vec!["openai".to_string()],
                 }
             ],
+            environment_rules: vec![],
+        });
+
+        // Stripe
+        self.providers.insert("stripe".to_string(), ApiProvider {
+            id: "stripe".to_string(),
+            name: "Stripe".to_string(),
+            description: "Payments, subscriptions and billing API".to_string(),
+            key_patterns: vec!["STRIPE_".to_string()],
+            env_patterns: vec![
+                "STRIPE_SECRET_KEY".to_string(),
+                "STRIPE_WEBHOOK_SECRET".to_string(),
+                "NEXT_PUBLIC_STRIPE_PUBLISHABLE_KEY".to_string(),
+            ],
+            docs_url: "https://docs.stripe.com".to_string(),
+            setup_type: "client-library".to_string(),
+            category: "payments".to_string(),
+            dependencies: vec!["stripe".to_string()],
+            config_templates: vec![],
+            environment_rules: vec![
+                EnvironmentRule {
+                    pattern: "^(sk|pk|rk)_test_".to_string(),
+                    environment: "development".to_string(),
+                },
+                EnvironmentRule {
+                    pattern: "^(sk|pk|rk)_live_".to_string(),
+                    environment: "production".to_string(),
+                },
+            ],
         });
 
         // Add more providers as needed...
@@ -622,6 +718,68 @@ export async function createChatCompletion(
         self.providers.values().cloned().collect()
     }
 
+    /// Integration boilerplate for a provider in a framework. Generated code
+    /// only reads `env_vars` from the environment, it never embeds values.
+    pub fn scaffold_files(
+        &self,
+        provider_id: &str,
+        framework: ScaffoldFramework,
+        env_vars: &[String],
+    ) -> Result<GeneratedConfig> {
+        let provider = self.providers
+            .get(provider_id)
+            .context("Provider not found")?;
+
+        let file = match framework {
+            ScaffoldFramework::Nextjs | ScaffoldFramework::Express => {
+                let dir = if framework == ScaffoldFramework::Nextjs { "lib" } else { "src/lib" };
+                let content = match provider.id.as_str() {
+                    "better-auth" if framework == ScaffoldFramework::Nextjs => {
+                        self.generate_better_auth_config(&HashMap::new())
+                    }
+                    "openai" => self.generate_openai_config(&HashMap::new()),
+                    "stripe" => STRIPE_TS.to_string(),
+                    _ => generic_ts_config(provider, env_vars),
+                };
+                let file_name = if provider.id == "better-auth" { "auth" } else { &provider.id };
+                GeneratedFile {
+                    path: format!("{}/{}.ts", dir, file_name),
+                    content,
+                    file_type: "typescript".to_string(),
+                    language: "typescript".to_string(),
+                }
+            }
+            ScaffoldFramework::Fastapi => {
+                let module = provider.id.replace('-', "_");
+                let content = match provider.id.as_str() {
+                    "openai" => OPENAI_FASTAPI.to_string(),
+                    "stripe" => STRIPE_FASTAPI.to_string(),
+                    _ => generic_fastapi_dependency(provider, env_vars),
+                };
+                GeneratedFile {
+                    path: format!("app/dependencies/{}.py", module),
+                    content,
+                    file_type: "python".to_string(),
+                    language: "python".to_string(),
+                }
+            }
+        };
+
+        let install = match framework {
+            ScaffoldFramework::Fastapi => format!("pip install {}", python_package(provider)),
+            _ => format!("npm install {}", provider.dependencies.join(" ")),
+        };
+        Ok(GeneratedConfig {
+            files: vec![file],
+            dependencies: provider.dependencies.clone(),
+            setup_instructions: vec![
+                install,
+                "Fill in the new variables in .env.example from KeyKeeper".to_string(),
+            ],
+            next_steps: vec![format!("Import the {} client where you need it", provider.name)],
+        })
+    }
+
     /// Generate enhanced configuration using the new template engine
     pub async fn generate_enhanced_configuration(
         &self,
@@ -888,4 +1046,131 @@ pub struct TemplateSuggestion {
 pub struct CacheStats {
     pub entries: usize,
     pub memory_usage_bytes: usize,
-}
\ No newline at end of file
+}
+
+const STRIPE_TS: &str = r#"import Stripe from 'stripe';
+
+export const stripe = new Stripe(process.env.STRIPE_SECRET_KEY!);
+
+export const stripeWebhookSecret = process.env.STRIPE_WEBHOOK_SECRET;
+"#;
+
+const OPENAI_FASTAPI: &str = r#"import os
+from functools import lru_cache
+
+from openai import OpenAI
+
+
+@lru_cache
+def get_openai_client() -> OpenAI:
+    """FastAPI dependency: `client: OpenAI = Depends(get_openai_client)`"""
+    return OpenAI(
+        api_key=os.environ["OPENAI_API_KEY"],
+        base_url=os.environ.get("OPENAI_BASE_URL"),
+    )
+"#;
+
+const STRIPE_FASTAPI: &str = r#"import os
+
+import stripe
+
+
+def get_stripe():
+    """FastAPI dependency: `client = Depends(get_stripe)`"""
+    stripe.api_key = os.environ["STRIPE_SECRET_KEY"]
+    return stripe
+"#;
+
+/// `better-auth` -> `betterAuth`
+fn camel_case(id: &str) -> String {
+    let mut parts = id.split(|c: char| !c.is_alphanumeric()).filter(|p| !p.is_empty());
+    let mut out = parts.next().unwrap_or("provider").to_lowercase();
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            out.extend(first.to_uppercase());
+            out.push_str(&chars.as_str().to_lowercase());
+        }
+    }
+    out
+}
+
+fn python_package(provider: &ApiProvider) -> String {
+    match provider.id.as_str() {
+        "better-auth" => "httpx".to_string(),
+        _ => provider.dependencies.join(" "),
+    }
+}
+
+fn generic_ts_config(provider: &ApiProvider, env_vars: &[String]) -> String {
+    let name = camel_case(&provider.id);
+    let fields: String = env_vars
+        .iter()
+        .map(|var| format!("  {}: process.env.{},\n", camel_case(var), var))
+        .collect();
+    format!(
+        r#"// {} configuration, values are managed in KeyKeeper
+
+export const {}Config = {{
+{}}};
+
+export function assert{}Config() {{
+  const missing = Object.entries({}Config)
+    .filter(([, value]) => !value)
+    .map(([key]) => key);
+  if (missing.length > 0) {{
+    throw new Error(`Missing {} configuration: ${{missing.join(', ')}}`);
+  }}
+}}
+"#,
+        provider.name,
+        name,
+        fields,
+        pascal(&provider.id),
+        name,
+        provider.name
+    )
+}
+
+fn generic_fastapi_dependency(provider: &ApiProvider, env_vars: &[String]) -> String {
+    let module = provider.id.replace('-', "_");
+    let fields: String = env_vars
+        .iter()
+        .map(|var| format!("    {}: str | None\n", var.to_lowercase()))
+        .collect();
+    let reads: String = env_vars
+        .iter()
+        .map(|var| format!("        {}=os.environ.get(\"{}\"),\n", var.to_lowercase(), var))
+        .collect();
+    format!(
+        r#""""{} configuration, values are managed in KeyKeeper."""
+import os
+from dataclasses import dataclass
+from functools import lru_cache
+
+
+@dataclass(frozen=True)
+class {}Settings:
+{}
+
+@lru_cache
+def get_{}_settings() -> {}Settings:
+    """FastAPI dependency: `settings = Depends(get_{}_settings)`"""
+    return {}Settings(
+{}    )
+"#,
+        provider.name,
+        pascal(&module),
+        if fields.is_empty() { "    pass\n".to_string() } else { fields },
+        module,
+        pascal(&module),
+        module,
+        pascal(&module),
+        reads
+    )
+}
+
+fn pascal(id: &str) -> String {
+    let camel = camel_case(id);
+    camel[..1].to_uppercase() + &camel[1..]
+}
@@ -1,7 +1,13 @@
 use crate::api_generator::{
-    ApiGeneratorService, GenerationRequest, GeneratedConfig, DocScrapingResult, ApiProvider
+    ApiGeneratorService, GenerationRequest, GeneratedConfig, DocScrapingResult, ApiProvider,
+    ScaffoldFramework, ScaffoldRecord, ScaffoldedFile,
 };
+use crate::errors::KeyKeeperError;
+use crate::guards;
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tauri::{command, State};
 use tokio::sync::Mutex;
@@ -59,10 +65,12 @@ pub async fn generate_api_configuration(
         .map_err(|e| format!("Failed to generate configuration: {}", e))
 }
 
-/// Detect API providers from environment variable name
+/// Detect API providers from environment variable name, and the key's
+/// environment from its value when given
 #[command]
 pub async fn detect_provider_from_env(
     env_var_name: String,
+    value: Option<String>,
     app_state: State<'_, crate::AppState>
 ) -> Result<Option<DetectionResult>, String> {
     let api_generator = app_state.api_generator.lock().await;
@@ -71,9 +79,14 @@ pub async fn detect_provider_from_env(
     
     for provider in providers {
         // Check exact match
+        let environment = value
+            .as_deref()
+            .and_then(|v| provider.environment_for(v))
+            .map(crate::docker_env::normalize_environment);
         if provider.env_patterns.contains(&env_var_name) {
             return Ok(Some(DetectionResult {
                 provider,
+                environment,
                 confidence: 0.9,
                 matched_patterns: vec![env_var_name.clone()],
                 detected_env_vars: vec![env_var_name],
@@ -85,6 +98,7 @@ pub async fn detect_provider_from_env(
             if env_var_name.contains(pattern) {
                 return Ok(Some(DetectionResult {
                     provider,
+                    environment,
                     confidence: 0.7,
                     matched_patterns: vec![pattern.clone()],
                     detected_env_vars: vec![env_var_name.clone()],
@@ -170,7 +184,340 @@ pub async fn preview_generated_config(
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DetectionResult {
     pub provider: ApiProvider,
+    /// Environment the key's value tells, see key_environment.rs
+    #[serde(default)]
+    pub environment: Option<String>,
     pub confidence: f64,
     pub matched_patterns: Vec<String>,
     pub detected_env_vars: Vec<String>,
-}
\ No newline at end of file
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScaffoldResult {
+    pub files_created: Vec<String>,
+    /// Existing files left alone because `overwrite` wasn't set
+    pub files_skipped: Vec<String>,
+    pub env_example_vars_added: Vec<String>,
+    pub dependencies: Vec<String>,
+    pub setup_instructions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScaffoldCleanup {
+    pub files_removed: Vec<String>,
+    /// Scaffolded files edited since, which are kept
+    pub files_kept: Vec<String>,
+    pub env_example_vars_removed: Vec<String>,
+}
+
+const ENV_EXAMPLE: &str = ".env.example";
+
+fn sha256_hex(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+fn env_example_header(provider: &ApiProvider) -> String {
+    format!("# {} (added by KeyKeeper)", provider.name)
+}
+
+fn find_project<'a>(
+    vault: &'a mut crate::ApiKeyVault,
+    project_path: &str,
+) -> Result<&'a mut crate::Project, KeyKeeperError> {
+    let wanted = project_path.trim_end_matches('/');
+    vault
+        .projects
+        .values_mut()
+        .find(|p| p.path.trim_end_matches('/') == wanted)
+        .ok_or_else(|| {
+            KeyKeeperError::NotFound(format!(
+                "No project registered at {}; add it to KeyKeeper first",
+                project_path
+            ))
+        })
+}
+
+/// Append the variables missing from `.env.example`, without values
+fn add_to_env_example(
+    root: &Path,
+    provider: &ApiProvider,
+    env_vars: &[String],
+) -> Result<Vec<String>, KeyKeeperError> {
+    let path = root.join(ENV_EXAMPLE);
+    let existing = std::fs::read_to_string(&path).unwrap_or_default();
+    let present: Vec<&str> = existing
+        .lines()
+        .filter_map(|line| line.split_once('=').map(|(name, _)| name.trim()))
+        .collect();
+    let missing: Vec<String> = env_vars
+        .iter()
+        .filter(|var| !present.contains(&var.as_str()))
+        .cloned()
+        .collect();
+    if missing.is_empty() {
+        return Ok(missing);
+    }
+
+    let mut content = existing;
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    content.push_str(&env_example_header(provider));
+    content.push('\n');
+    for var in &missing {
+        content.push_str(&format!("{}=\n", var));
+    }
+    std::fs::write(&path, content)?;
+    Ok(missing)
+}
+
+/// Drop the scaffold's header and still-empty variables from `.env.example`
+fn remove_from_env_example(
+    root: &Path,
+    provider: &ApiProvider,
+    env_vars: &[String],
+) -> Result<Vec<String>, KeyKeeperError> {
+    let path = root.join(ENV_EXAMPLE);
+    let Ok(existing) = std::fs::read_to_string(&path) else {
+        return Ok(Vec::new());
+    };
+    let header = env_example_header(provider);
+    let mut removed = Vec::new();
+    let kept: Vec<&str> = existing
+        .lines()
+        .filter(|line| {
+            if line.trim() == header {
+                return false;
+            }
+            match line.split_once('=') {
+                Some((name, value))
+                    if value.trim().is_empty() && env_vars.iter().any(|v| v == name.trim()) =>
+                {
+                    removed.push(name.trim().to_string());
+                    false
+                }
+                _ => true,
+            }
+        })
+        .collect();
+    if !removed.is_empty() {
+        let mut content = kept.join("\n").trim_end().to_string();
+        content.push('\n');
+        std::fs::write(&path, content)?;
+    }
+    Ok(removed)
+}
+
+/// Write integration boilerplate for a provider into a registered project,
+/// add its variables to `.env.example` and remember what was created so
+/// `remove_scaffolded_integration` can undo it. Without `framework` the
+/// project's detected framework is used.
+#[command]
+pub async fn scaffold_integration(
+    provider_id: String,
+    framework: Option<String>,
+    project_path: String,
+    overwrite: Option<bool>,
+    app_state: State<'_, crate::AppState>,
+) -> Result<ScaffoldResult, KeyKeeperError> {
+    guards::unlocked(&app_state).await?;
+    let framework = match framework {
+        Some(framework) => framework,
+        None => {
+            let mut vault_guard = app_state.vault.lock().await;
+            let profile = &find_project(&mut vault_guard, &project_path)?.profile;
+            profile
+                .frameworks
+                .iter()
+                .find(|f| ScaffoldFramework::parse(f).is_some())
+                .cloned()
+                .ok_or_else(|| {
+                    KeyKeeperError::Validation(
+                        "Could not detect a supported framework for this project, pass one explicitly"
+                            .to_string(),
+                    )
+                })?
+        }
+    };
+    let framework_kind = ScaffoldFramework::parse(&framework).ok_or_else(|| {
+        KeyKeeperError::Validation(format!(
+            "Unsupported framework '{}', expected nextjs, express or fastapi",
+            framework
+        ))
+    })?;
+    let provider = {
+        let api_generator = app_state.api_generator.lock().await;
+        let service = api_generator.service.lock().await;
+        service
+            .get_providers()
+            .into_iter()
+            .find(|p| p.id == provider_id)
+            .ok_or_else(|| KeyKeeperError::not_found("Provider"))?
+    };
+
+    let mut vault_guard = app_state.vault.lock().await;
+    let project_root = find_project(&mut vault_guard, &project_path)?.path.clone();
+    // Variables of the provider's keys already in this project, on top of
+    // the provider's well-known ones
+    let mut env_vars = provider.env_patterns.clone();
+    for key in vault_guard.keys.values() {
+        let matches_provider = key.service.eq_ignore_ascii_case(&provider.id)
+            || key.service.eq_ignore_ascii_case(&provider.name);
+        if matches_provider && key.project_path.as_deref() == Some(project_root.as_str()) {
+            let name = crate::env_var_name_for_key(key);
+            if !env_vars.contains(&name) {
+                env_vars.push(name);
+            }
+        }
+    }
+    drop(vault_guard);
+
+    let generated = {
+        let api_generator = app_state.api_generator.lock().await;
+        let service = api_generator.service.lock().await;
+        service.scaffold_files(&provider.id, framework_kind, &env_vars)?
+    };
+
+    let root = Path::new(&project_root);
+    let mut files = Vec::new();
+    let mut files_skipped = Vec::new();
+    for file in &generated.files {
+        let target = root.join(&file.path);
+        if target.exists() && !overwrite.unwrap_or(false) {
+            files_skipped.push(file.path.clone());
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&target, &file.content)?;
+        files.push(ScaffoldedFile {
+            path: file.path.clone(),
+            sha256: sha256_hex(file.content.as_bytes()),
+        });
+    }
+    let env_example_vars_added = add_to_env_example(root, &provider, &env_vars)?;
+
+    let mut vault_guard = app_state.vault.lock().await;
+    let project = find_project(&mut vault_guard, &project_path)?;
+    let mut record = ScaffoldRecord {
+        provider_id: provider.id.clone(),
+        framework: framework_kind,
+        files: files.clone(),
+        env_example_vars: env_example_vars_added.clone(),
+        created_at: Utc::now(),
+    };
+    // Scaffolding again extends the earlier record instead of losing track
+    // of what it created
+    if let Some(index) = project
+        .scaffolds
+        .iter()
+        .position(|r| r.provider_id == provider.id && r.framework == framework_kind)
+    {
+        let previous = project.scaffolds.remove(index);
+        record.files.extend(
+            previous
+                .files
+                .into_iter()
+                .filter(|f| !files.iter().any(|n| n.path == f.path)),
+        );
+        record.env_example_vars.extend(previous.env_example_vars);
+        record.created_at = previous.created_at;
+    }
+    project.scaffolds.push(record);
+    project.updated_at = Utc::now();
+    let project_id = project.id.clone();
+    drop(vault_guard);
+
+    crate::save_vault(&app_state).await?;
+    crate::log_audit_event(
+        &app_state,
+        "scaffold_integration",
+        "project",
+        Some(&project_id),
+        true,
+        Some(&format!("{} for {}", provider.id, framework)),
+    )
+    .await;
+
+    Ok(ScaffoldResult {
+        files_created: files.into_iter().map(|f| f.path).collect(),
+        files_skipped,
+        env_example_vars_added,
+        dependencies: generated.dependencies,
+        setup_instructions: generated.setup_instructions,
+    })
+}
+
+/// Remove what `scaffold_integration` created for a provider. Files edited
+/// since are kept, as are `.env.example` entries that were filled in.
+#[command]
+pub async fn remove_scaffolded_integration(
+    provider_id: String,
+    project_path: String,
+    app_state: State<'_, crate::AppState>,
+) -> Result<ScaffoldCleanup, KeyKeeperError> {
+    guards::unlocked(&app_state).await?;
+    let provider = {
+        let api_generator = app_state.api_generator.lock().await;
+        let service = api_generator.service.lock().await;
+        service
+            .get_providers()
+            .into_iter()
+            .find(|p| p.id == provider_id)
+            .ok_or_else(|| KeyKeeperError::not_found("Provider"))?
+    };
+
+    let mut vault_guard = app_state.vault.lock().await;
+    let project = find_project(&mut vault_guard, &project_path)?;
+    let (records, remaining): (Vec<_>, Vec<_>) = project
+        .scaffolds
+        .drain(..)
+        .partition(|r| r.provider_id == provider_id);
+    project.scaffolds = remaining;
+    if records.is_empty() {
+        return Err(KeyKeeperError::NotFound(format!(
+            "No scaffolded {} integration in this project",
+            provider.name
+        )));
+    }
+    let root = PathBuf::from(&project.path);
+    let project_id = project.id.clone();
+
+    let mut cleanup = ScaffoldCleanup {
+        files_removed: Vec::new(),
+        files_kept: Vec::new(),
+        env_example_vars_removed: Vec::new(),
+    };
+    let mut env_vars = Vec::new();
+    for record in &records {
+        for file in &record.files {
+            let target = root.join(&file.path);
+            match std::fs::read(&target) {
+                Ok(content) if sha256_hex(&content) == file.sha256 => {
+                    std::fs::remove_file(&target)?;
+                    cleanup.files_removed.push(file.path.clone());
+                }
+                Ok(_) => cleanup.files_kept.push(file.path.clone()),
+                Err(_) => {}
+            }
+        }
+        env_vars.extend(record.env_example_vars.iter().cloned());
+    }
+    cleanup.env_example_vars_removed = remove_from_env_example(&root, &provider, &env_vars)?;
+    drop(vault_guard);
+
+    crate::save_vault(&app_state).await?;
+    crate::log_audit_event(
+        &app_state,
+        "remove_scaffolded_integration",
+        "project",
+        Some(&project_id),
+        true,
+        Some(&format!("{} files removed", cleanup.files_removed.len())),
+    )
+    .await;
+    Ok(cleanup)
+}
@@ -0,0 +1,519 @@
+//! Per-call approval of secrets requested by external clients.
+//!
+//! When the editor extension, `keykeeper exec` or a script asks the HTTP API
+//! for a secret, the request waits until the user approves it in the desktop
+//! app: an `approval-requested` event is emitted and the approvals window is
+//! brought forward. "Always allow" stores a rule in the vault for that key
+//! and client so later requests go through without a prompt. Rules are tied
+//! to the client ID verified by `clients::identify`, never to a name a
+//! request claims: clients that haven't registered are asked every time and
+//! can't be always allowed.
+//!
+//! Every request, decision, expiry and rule-based grant is audited.
+
+use crate::clients::RequestClient;
+use crate::errors::KeyKeeperError;
+use crate::vault_storage::VaultStorage;
+use crate::window_manager::{self, WindowKind};
+use crate::{
+    create_audit_log, guards, log_audit_event, retention, save_vault, ApiKeyVault, AppState,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::{oneshot, Mutex as AsyncMutex};
+use tracing::{info, warn};
+
+/// How long a request waits for the user
+const APPROVAL_TIMEOUT_SECONDS: i64 = 60;
+pub const CLIENT_HEADER: &str = "x-keykeeper-client";
+
+/// "Always allow" for one key and client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRule {
+    pub id: String,
+    pub key_id: String,
+    /// Registered client the rule is for; rules made before rules were tied
+    /// to registrations have none and never apply
+    #[serde(default)]
+    pub client_id: Option<String>,
+    /// Name of the client, for display
+    pub client: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default, with = "crate::timestamps::rfc3339_option")]
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalKey {
+    pub id: String,
+    pub name: String,
+    pub service: String,
+}
+
+/// A request shown to the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    pub id: String,
+    pub client: String,
+    /// Whether the client proved its identity; only verified clients can be
+    /// always allowed
+    #[serde(default)]
+    pub verified: bool,
+    /// What the client wants the secrets for, e.g. "Resolve env references"
+    pub purpose: String,
+    pub keys: Vec<ApprovalKey>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub requested_at: DateTime<Utc>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    /// This request only
+    Approve,
+    /// This request, and later ones from the client for the same keys
+    AlwaysAllow,
+    Deny,
+}
+
+struct Pending {
+    request: ApprovalRequest,
+    reply: oneshot::Sender<ApprovalDecision>,
+}
+
+/// Pending requests, shared by the HTTP server and the app
+pub struct ApprovalBroker {
+    app_handle: Option<AppHandle>,
+    pending: Mutex<HashMap<String, Pending>>,
+}
+
+impl ApprovalBroker {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self {
+            app_handle: Some(app_handle),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Broker that denies everything, for contexts without a window
+    pub fn headless() -> Self {
+        Self {
+            app_handle: None,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Tell the desktop app about something that needs the user, bringing
+    /// the window forward
+    pub fn notify<S: Serialize + Clone>(&self, event: &str, payload: &S) {
+        if let Some(app) = &self.app_handle {
+            let _ = app.emit(event, payload.clone());
+            crate::instance::focus_main_window(app);
+        }
+    }
+
+    fn pending_requests(&self) -> Vec<ApprovalRequest> {
+        let mut requests: Vec<_> = self
+            .pending
+            .lock()
+            .unwrap()
+            .values()
+            .map(|p| p.request.clone())
+            .collect();
+        requests.sort_by_key(|r| r.requested_at);
+        requests
+    }
+
+    /// Show the request and wait for the user's decision
+    async fn ask(&self, request: ApprovalRequest) -> Result<ApprovalDecision, KeyKeeperError> {
+        let Some(app) = &self.app_handle else {
+            return Err(KeyKeeperError::Denied(
+                "No desktop session to approve the request".to_string(),
+            ));
+        };
+
+        let id = request.id.clone();
+        let (reply, decision) = oneshot::channel();
+        self.pending.lock().unwrap().insert(
+            id.clone(),
+            Pending {
+                request: request.clone(),
+                reply,
+            },
+        );
+        let _ = app.emit("approval-requested", &request);
+        match window_manager::open(app, WindowKind::Approvals) {
+            Ok(window) => {
+                let _ = window.request_user_attention(Some(tauri::UserAttentionType::Critical));
+            }
+            Err(e) => {
+                warn!("Failed to open the approvals window: {}", e);
+                crate::instance::focus_main_window(app);
+            }
+        }
+
+        let timeout = std::time::Duration::from_secs(APPROVAL_TIMEOUT_SECONDS as u64);
+        match tokio::time::timeout(timeout, decision).await {
+            Ok(Ok(decision)) => Ok(decision),
+            Ok(Err(_)) => Err(KeyKeeperError::Denied("Approval was cancelled".to_string())),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&id);
+                let _ = app.emit("approval-expired", &id);
+                Err(KeyKeeperError::Denied(
+                    "Approval request timed out".to_string(),
+                ))
+            }
+        }
+    }
+
+    fn respond(&self, request_id: &str, decision: ApprovalDecision) -> Result<(), KeyKeeperError> {
+        let pending = self
+            .pending
+            .lock()
+            .unwrap()
+            .remove(request_id)
+            .ok_or_else(|| KeyKeeperError::not_found("Approval request"))?;
+        pending
+            .reply
+            .send(decision)
+            .map_err(|_| KeyKeeperError::Conflict("The client is no longer waiting".to_string()))
+    }
+}
+
+/// Client name of an HTTP request
+pub fn client_name(headers: &hyper::HeaderMap) -> String {
+    [CLIENT_HEADER, "user-agent"]
+        .iter()
+        .find_map(|name| headers.get(*name).and_then(|v| v.to_str().ok()))
+        .map(|v| v.trim().chars().take(100).collect::<String>())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn push_audit(
+    vault: &mut ApiKeyVault,
+    action: &str,
+    key_id: Option<&str>,
+    success: bool,
+    details: &str,
+) {
+    vault.audit_logs.push(create_audit_log(
+        action,
+        "api_key",
+        key_id,
+        success,
+        Some(details),
+    ));
+    retention::cap_audit_logs(vault);
+}
+
+/// Keys not covered by a rule for the verified client `client_id`, every
+/// key for unverified clients; marks the rules that apply as used
+fn uncovered_keys(
+    vault: &mut ApiKeyVault,
+    client_id: Option<&str>,
+    key_ids: &[String],
+) -> Vec<String> {
+    let Some(client_id) = client_id else {
+        return key_ids.to_vec();
+    };
+    let now = Utc::now();
+    key_ids
+        .iter()
+        .filter(|key_id| {
+            match vault
+                .approval_rules
+                .iter_mut()
+                .find(|r| &r.key_id == *key_id && r.client_id.as_deref() == Some(client_id))
+            {
+                Some(rule) => {
+                    rule.last_used_at = Some(now);
+                    false
+                }
+                None => true,
+            }
+        })
+        .cloned()
+        .collect()
+}
+
+/// Allow `client` to read the secrets of `key_ids`, asking the user unless
+/// rules already cover every key. Fails with `Denied` when refused or when
+/// nobody answers in time.
+pub async fn authorize(
+    broker: &ApprovalBroker,
+    vault: &AsyncMutex<ApiKeyVault>,
+    storage: &dyn VaultStorage,
+    requester: &RequestClient,
+    key_ids: &[String],
+    purpose: &str,
+) -> Result<(), KeyKeeperError> {
+    if key_ids.is_empty() {
+        return Ok(());
+    }
+    let client = requester.name.as_str();
+    let client_id = requester.client_id.as_deref();
+
+    let request = {
+        let mut vault_guard = vault.lock().await;
+        let uncovered = uncovered_keys(&mut vault_guard, client_id, key_ids);
+        if uncovered.is_empty() {
+            for key_id in key_ids {
+                push_audit(
+                    &mut vault_guard,
+                    "external_key_access",
+                    Some(key_id),
+                    true,
+                    &format!("{}: allowed by rule for {}", purpose, client),
+                );
+            }
+            if let Err(e) = crate::save_vault_to(storage, &vault_guard) {
+                warn!("Failed to save approval audit: {}", e);
+            }
+            return Ok(());
+        }
+
+        let now = Utc::now();
+        let request = ApprovalRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            client: client.to_string(),
+            verified: client_id.is_some(),
+            purpose: purpose.to_string(),
+            keys: key_ids
+                .iter()
+                .filter_map(|id| vault_guard.keys.get(id))
+                .map(|key| ApprovalKey {
+                    id: key.id.clone(),
+                    name: key.name.clone(),
+                    service: key.service.clone(),
+                })
+                .collect(),
+            requested_at: now,
+            expires_at: now + Duration::seconds(APPROVAL_TIMEOUT_SECONDS),
+        };
+        vault_guard.audit_logs.push(create_audit_log(
+            "approval_requested",
+            "approval",
+            Some(&request.id),
+            true,
+            Some(&format!(
+                "{} for {}: {}",
+                client,
+                purpose,
+                uncovered.join(", ")
+            )),
+        ));
+        request
+    };
+
+    info!(
+        "Approval requested by {} for {} key(s)",
+        client,
+        request.keys.len()
+    );
+    let result = broker.ask(request.clone()).await;
+
+    let mut vault_guard = vault.lock().await;
+    let (action, granted) = match &result {
+        // Nothing to tie a rule to: this request only
+        Ok(ApprovalDecision::AlwaysAllow) if client_id.is_none() => ("approval_granted", true),
+        Ok(ApprovalDecision::Approve) => ("approval_granted", true),
+        Ok(ApprovalDecision::AlwaysAllow) => ("approval_granted_always", true),
+        Ok(ApprovalDecision::Deny) => ("approval_denied", false),
+        Err(_) => ("approval_expired", false),
+    };
+    if let (Ok(ApprovalDecision::AlwaysAllow), Some(client_id)) = (&result, client_id) {
+        let now = Utc::now();
+        for key_id in key_ids {
+            if !vault_guard
+                .approval_rules
+                .iter()
+                .any(|r| &r.key_id == key_id && r.client_id.as_deref() == Some(client_id))
+            {
+                vault_guard.approval_rules.push(ApprovalRule {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    key_id: key_id.clone(),
+                    client_id: Some(client_id.to_string()),
+                    client: client.to_string(),
+                    created_at: now,
+                    last_used_at: Some(now),
+                });
+            }
+        }
+    }
+    for key_id in key_ids {
+        push_audit(
+            &mut vault_guard,
+            "external_key_access",
+            Some(key_id),
+            granted,
+            &format!("{}: {} for {}", purpose, action, client),
+        );
+    }
+    vault_guard.audit_logs.push(create_audit_log(
+        action,
+        "approval",
+        Some(&request.id),
+        granted,
+        Some(client),
+    ));
+    if let Err(e) = crate::save_vault_to(storage, &vault_guard) {
+        warn!("Failed to save approval decision: {}", e);
+    }
+
+    match result? {
+        ApprovalDecision::Deny => Err(KeyKeeperError::Denied(
+            "Request denied in KeyKeeper".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[tauri::command]
+pub async fn list_pending_approvals(
+    state: State<'_, AppState>,
+) -> Result<Vec<ApprovalRequest>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    Ok(state.approvals.pending_requests())
+}
+
+/// Answer a pending request. The decision is audited by the waiting request.
+#[tauri::command]
+pub async fn respond_to_approval(
+    request_id: String,
+    decision: ApprovalDecision,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    state.approvals.respond(&request_id, decision)
+}
+
+#[tauri::command]
+pub async fn list_approval_rules(
+    state: State<'_, AppState>,
+) -> Result<Vec<ApprovalRule>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    Ok(state.vault.lock().await.approval_rules.clone())
+}
+
+#[tauri::command]
+pub async fn revoke_approval_rule(
+    rule_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let before = vault_guard.approval_rules.len();
+    vault_guard.approval_rules.retain(|r| r.id != rule_id);
+    if vault_guard.approval_rules.len() == before {
+        return Err(KeyKeeperError::not_found("Approval rule"));
+    }
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "revoke_approval_rule",
+        "approval",
+        Some(&rule_id),
+        true,
+        None,
+    )
+    .await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(client_id: Option<&str>) -> ApprovalRule {
+        ApprovalRule {
+            id: "r1".to_string(),
+            key_id: "k1".to_string(),
+            client_id: client_id.map(str::to_string),
+            client: "vscode".to_string(),
+            created_at: Utc::now(),
+            last_used_at: None,
+        }
+    }
+
+    fn requester(client_id: Option<&str>) -> RequestClient {
+        RequestClient {
+            client_id: client_id.map(str::to_string),
+            name: "vscode".to_string(),
+            ip: "127.0.0.1".to_string(),
+            user_agent: None,
+        }
+    }
+
+    #[test]
+    fn rules_cover_keys_per_verified_client() {
+        let mut vault = ApiKeyVault::default();
+        vault.approval_rules.push(rule(Some("c1")));
+        let keys = vec!["k1".to_string(), "k2".to_string()];
+
+        assert_eq!(uncovered_keys(&mut vault, Some("c1"), &keys), vec!["k2"]);
+        assert!(vault.approval_rules[0].last_used_at.is_some());
+        assert_eq!(uncovered_keys(&mut vault, Some("c2"), &keys), keys);
+        // Claiming the name isn't enough
+        assert_eq!(uncovered_keys(&mut vault, None, &keys), keys);
+
+        // Rules stored by name only apply to nobody
+        vault.approval_rules = vec![rule(None)];
+        assert_eq!(uncovered_keys(&mut vault, Some("c1"), &keys), keys);
+    }
+
+    #[tokio::test]
+    async fn unverified_clients_never_match_a_rule() {
+        let mut vault = ApiKeyVault::default();
+        vault.approval_rules.push(rule(Some("c1")));
+        let vault = AsyncMutex::new(vault);
+        let storage = crate::vault_storage::MemoryStorage::default();
+
+        // The headless broker denies whatever it would have to ask
+        let keys = vec!["k1".to_string()];
+        let result = authorize(
+            &ApprovalBroker::headless(),
+            &vault,
+            &storage,
+            &requester(None),
+            &keys,
+            "Read secret",
+        )
+        .await;
+        assert!(matches!(result, Err(KeyKeeperError::Denied(_))));
+    }
+
+    #[tokio::test]
+    async fn allowed_access_is_audited_and_saved() {
+        let mut vault = ApiKeyVault::default();
+        vault.approval_rules.push(rule(Some("c1")));
+        let vault = AsyncMutex::new(vault);
+        let storage = crate::vault_storage::MemoryStorage::default();
+
+        let keys = vec!["k1".to_string()];
+        authorize(
+            &ApprovalBroker::headless(),
+            &vault,
+            &storage,
+            &requester(Some("c1")),
+            &keys,
+            "Read secret",
+        )
+        .await
+        .unwrap();
+
+        let saved: ApiKeyVault =
+            serde_json::from_str(&storage.read_vault().unwrap().unwrap()).unwrap();
+        assert_eq!(saved.audit_logs.len(), 1);
+        assert!(saved.approval_rules[0].last_used_at.is_some());
+    }
+}
@@ -0,0 +1,290 @@
+//! Archived keys.
+//!
+//! Archiving retires a key without deleting it: archived keys keep their
+//! value but are left out of listings, search, `.env` syncs and suggestions
+//! unless asked for, and references to them no longer resolve. Restoring
+//! brings a key back unchanged.
+//!
+//! With `ArchivePolicy::auto_archive_after_days` set, a background job
+//! archives keys that haven't been used, edited or rotated for that many
+//! days. Pinned keys are never archived automatically.
+
+use crate::activity::{self, Actor};
+use crate::errors::KeyKeeperError;
+use crate::masking::{self, MaskedApiKey};
+use crate::notifications::NotificationKind;
+use crate::{guards, log_audit_event, save_vault, ApiKey, ApiKeyVault, AppState};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+const ARCHIVE_CHECK_INTERVAL_SECS: u64 = 6 * 60 * 60;
+const MAX_AUTO_ARCHIVE_DAYS: u32 = 3650;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ArchivePolicy {
+    /// Archive keys unused for this many days; `None` turns it off
+    #[serde(default)]
+    pub auto_archive_after_days: Option<u32>,
+}
+
+pub fn is_archived(key: &ApiKey) -> bool {
+    key.archived_at.is_some()
+}
+
+/// Record that a key's value was handed out
+pub fn touch(vault: &mut ApiKeyVault, key_id: &str, now: DateTime<Utc>) {
+    if let Some(key) = vault.keys.get_mut(key_id) {
+        key.last_used_at = Some(now);
+    }
+}
+
+/// Latest sign that the key is still needed
+pub fn last_activity(key: &ApiKey) -> DateTime<Utc> {
+    [key.last_used_at, key.last_rotated_at, Some(key.updated_at)]
+        .into_iter()
+        .flatten()
+        .fold(key.created_at, DateTime::max)
+}
+
+/// Keys the policy archives at `now`
+pub fn stale_keys(vault: &ApiKeyVault, policy: &ArchivePolicy, now: DateTime<Utc>) -> Vec<String> {
+    let Some(days) = policy.auto_archive_after_days else {
+        return Vec::new();
+    };
+    let cutoff = now - ChronoDuration::days(days as i64);
+    let mut ids: Vec<String> = vault
+        .keys
+        .values()
+        .filter(|key| !is_archived(key) && !key.is_pinned)
+        .filter(|key| last_activity(key) < cutoff)
+        .map(|key| key.id.clone())
+        .collect();
+    ids.sort();
+    ids
+}
+
+async fn auto_archive(state: &State<'_, AppState>) -> Result<(), String> {
+    let now = Utc::now();
+    let mut vault_guard = state.vault.lock().await;
+    let policy = vault_guard.archive_policy.clone();
+    let ids = stale_keys(&vault_guard, &policy, now);
+    if ids.is_empty() {
+        return Ok(());
+    }
+    for id in &ids {
+        if let Some(key) = vault_guard.keys.get_mut(id) {
+            key.archived_at = Some(now);
+            let key = key.clone();
+            activity::record(
+                &mut vault_guard,
+                "key_archived",
+                &key,
+                Actor::system("auto_archive"),
+                None,
+                None,
+            );
+        }
+    }
+    drop(vault_guard);
+    save_vault(state).await.map_err(|e| e.to_string())?;
+
+    info!("Archived {} unused key(s)", ids.len());
+    log_audit_event(
+        state,
+        "auto_archive_keys",
+        "api_key",
+        None,
+        true,
+        Some(&format!("{} keys: {}", ids.len(), ids.join(", "))),
+    )
+    .await;
+    let body = format!(
+        "{} key(s) unused for {} days were archived",
+        ids.len(),
+        policy.auto_archive_after_days.unwrap_or_default()
+    );
+    state
+        .notifier
+        .notify(
+            &state.vault,
+            NotificationKind::General,
+            "Keys archived",
+            &body,
+        )
+        .await?;
+    Ok(())
+}
+
+/// Periodically archive keys that went unused for too long
+pub async fn run_auto_archiver(app: AppHandle) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(ARCHIVE_CHECK_INTERVAL_SECS)).await;
+
+        let state: State<'_, AppState> = app.state();
+        if !*state.is_unlocked.lock().await {
+            continue;
+        }
+        if let Err(e) = auto_archive(&state).await {
+            warn!("Auto-archive failed: {}", e);
+        }
+    }
+}
+
+async fn set_archived(
+    key_id: &str,
+    archived: bool,
+    state: &State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    let mut vault_guard = state.vault.lock().await;
+    let key = vault_guard
+        .keys
+        .get_mut(key_id)
+        .ok_or_else(|| KeyKeeperError::not_found("API key"))?;
+    if is_archived(key) == archived {
+        return Ok(());
+    }
+    let now = Utc::now();
+    key.archived_at = archived.then_some(now);
+    // Restoring counts as activity, so the key isn't archived again right away
+    if !archived {
+        key.updated_at = now;
+    }
+    let key = key.clone();
+    let activity_type = if archived {
+        "key_archived"
+    } else {
+        "key_restored"
+    };
+    activity::record(
+        &mut vault_guard,
+        activity_type,
+        &key,
+        Actor::ui(),
+        None,
+        None,
+    );
+    drop(vault_guard);
+
+    save_vault(state).await?;
+    let action = if archived {
+        "archive_api_key"
+    } else {
+        "restore_api_key"
+    };
+    log_audit_event(state, action, "api_key", Some(key_id), true, None).await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn archive_api_key(
+    key_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    set_archived(&key_id, true, &state).await
+}
+
+#[tauri::command]
+pub async fn restore_api_key(
+    key_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    set_archived(&key_id, false, &state).await
+}
+
+/// Archived keys, most recently archived first
+#[tauri::command]
+pub async fn get_archived_keys(
+    state: State<'_, AppState>,
+) -> Result<Vec<MaskedApiKey>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    let mut keys: Vec<ApiKey> = vault_guard
+        .keys
+        .values()
+        .filter(|key| is_archived(key))
+        .cloned()
+        .collect();
+    keys.sort_by(|a, b| {
+        b.archived_at
+            .cmp(&a.archived_at)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    Ok(masking::mask_all(keys))
+}
+
+#[tauri::command]
+pub async fn get_archive_policy(
+    state: State<'_, AppState>,
+) -> Result<ArchivePolicy, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    Ok(state.vault.lock().await.archive_policy.clone())
+}
+
+#[tauri::command]
+pub async fn set_archive_policy(
+    policy: ArchivePolicy,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    if policy
+        .auto_archive_after_days
+        .is_some_and(|days| days == 0 || days > MAX_AUTO_ARCHIVE_DAYS)
+    {
+        return Err(KeyKeeperError::Validation(format!(
+            "Auto-archive must be between 1 and {} days",
+            MAX_AUTO_ARCHIVE_DAYS
+        )));
+    }
+
+    state.vault.lock().await.archive_policy = policy.clone();
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "set_archive_policy",
+        "vault",
+        None,
+        true,
+        Some(&match policy.auto_archive_after_days {
+            Some(days) => format!("Auto-archive after {} days", days),
+            None => "Auto-archive off".to_string(),
+        }),
+    )
+    .await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault_tests::test_key;
+
+    #[test]
+    fn only_keys_unused_past_the_cutoff_are_archived() {
+        let mut vault = ApiKeyVault::default();
+        let long_ago = "2023-01-01T00:00:00Z";
+        for key in [
+            test_key("old").updated_at(long_ago),
+            test_key("used")
+                .updated_at(long_ago)
+                .last_used_at(Some("2024-05-01T00:00:00Z")),
+            test_key("edited").updated_at("2024-05-20T00:00:00Z"),
+            test_key("pinned").updated_at(long_ago).pinned(true),
+        ] {
+            let key = key.created_at(long_ago).build();
+            vault.keys.insert(key.id.clone(), key);
+        }
+        let now = "2024-06-01T00:00:00Z".parse().unwrap();
+
+        assert!(stale_keys(&vault, &ArchivePolicy::default(), now).is_empty());
+        let policy = ArchivePolicy {
+            auto_archive_after_days: Some(180),
+        };
+        assert_eq!(stale_keys(&vault, &policy, now), vec!["old".to_string()]);
+    }
+}
@@ -0,0 +1,455 @@
+//! Copies of audit events for a SIEM.
+//!
+//! Every audit event is also handed to the sinks configured in
+//! `keykeeper.toml`:
+//!
+//! ```toml
+//! [[audit.sinks]]
+//! type = "jsonl"
+//! path = "/var/log/keykeeper/audit.jsonl"
+//!
+//! [[audit.sinks]]
+//! type = "syslog"
+//! address = "siem.internal:514"
+//! protocol = "tcp"
+//! events = ["unlock_vault", "delete_*"]
+//! failures_only = false
+//!
+//! [[audit.sinks]]
+//! type = "webhook"
+//! url = "https://siem.internal/ingest"
+//! headers = { Authorization = "Splunk <token>" }
+//! ```
+//!
+//! Each sink has its own bounded queue and worker. Handing an event over
+//! never waits: when a sink can't keep up, or is down, its queue fills and
+//! further events for it are dropped and counted, so vault operations are
+//! never held up by a sink.
+
+use crate::errors::KeyKeeperError;
+use crate::AuditLog;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+const QUEUE_CAPACITY: usize = 1024;
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+/// Attempts per event before it's counted as failed
+const MAX_ATTEMPTS: u32 = 3;
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// authpriv, the facility for security and authorization messages
+const SYSLOG_FACILITY: u8 = 10;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SyslogProtocol {
+    #[default]
+    Udp,
+    Tcp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkTarget {
+    /// One JSON object per line, appended to a local file
+    Jsonl { path: PathBuf },
+    /// RFC 5424 messages; TCP uses octet-counting framing (RFC 6587)
+    Syslog {
+        /// `host:port`
+        address: String,
+        #[serde(default)]
+        protocol: SyslogProtocol,
+    },
+    /// One POST with a JSON body per event
+    Webhook {
+        url: String,
+        #[serde(default)]
+        headers: BTreeMap<String, String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditSinkConfig {
+    #[serde(flatten)]
+    pub target: SinkTarget,
+    /// Actions to forward, with `*` as a trailing wildcard; all when empty
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// Forward failed actions only
+    #[serde(default)]
+    pub failures_only: bool,
+}
+
+impl AuditSinkConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.target {
+            SinkTarget::Jsonl { path } if !path.is_absolute() => {
+                return Err("path must be absolute".to_string())
+            }
+            SinkTarget::Syslog { address, .. } => {
+                address
+                    .rsplit_once(':')
+                    .filter(|(host, _)| !host.is_empty())
+                    .and_then(|(_, port)| port.parse::<u16>().ok())
+                    .ok_or_else(|| format!("address '{}' must be host:port", address))?;
+            }
+            SinkTarget::Webhook { url, .. } => {
+                let parsed = url::Url::parse(url).map_err(|_| format!("invalid url '{}'", url))?;
+                if !matches!(parsed.scheme(), "http" | "https") {
+                    return Err("url must be http or https".to_string());
+                }
+            }
+            _ => {}
+        }
+        if self.events.iter().any(|e| e.trim().is_empty()) {
+            return Err("events can't contain empty names".to_string());
+        }
+        Ok(())
+    }
+
+    fn accepts(&self, event: &AuditLog) -> bool {
+        if self.failures_only && event.success {
+            return false;
+        }
+        self.events.is_empty()
+            || self
+                .events
+                .iter()
+                .any(|pattern| match pattern.strip_suffix('*') {
+                    Some(prefix) => event.action.starts_with(prefix),
+                    None => event.action == *pattern,
+                })
+    }
+
+    fn describe(&self) -> String {
+        match &self.target {
+            SinkTarget::Jsonl { path } => format!("jsonl {}", path.display()),
+            SinkTarget::Syslog { address, protocol } => {
+                format!("syslog {} ({:?})", address, protocol).to_lowercase()
+            }
+            SinkTarget::Webhook { url, .. } => format!("webhook {}", url),
+        }
+    }
+}
+
+#[derive(Default)]
+struct SinkStats {
+    delivered: AtomicU64,
+    failed: AtomicU64,
+    dropped: AtomicU64,
+    last_error: Mutex<Option<(DateTime<Utc>, String)>>,
+}
+
+struct Sink {
+    config: AuditSinkConfig,
+    queue: mpsc::Sender<AuditLog>,
+    stats: Arc<SinkStats>,
+}
+
+static SINKS: Mutex<Vec<Sink>> = Mutex::new(Vec::new());
+
+/// Replace the running sinks. Events still queued for the old ones are
+/// delivered before their workers stop.
+pub fn configure(configs: &[AuditSinkConfig]) {
+    let sinks: Vec<Sink> = configs
+        .iter()
+        .map(|config| {
+            let (queue, events) = mpsc::channel(QUEUE_CAPACITY);
+            let stats = Arc::new(SinkStats::default());
+            tauri::async_runtime::spawn(run(config.target.clone(), events, Arc::clone(&stats)));
+            Sink {
+                config: config.clone(),
+                queue,
+                stats,
+            }
+        })
+        .collect();
+    if !sinks.is_empty() {
+        info!("Forwarding audit events to {} sink(s)", sinks.len());
+    }
+    *SINKS.lock().unwrap() = sinks;
+}
+
+/// Queue an event for every sink that wants it. Never blocks.
+pub fn emit(event: &AuditLog) {
+    let sinks = SINKS.lock().unwrap();
+    for sink in sinks.iter().filter(|s| s.config.accepts(event)) {
+        if let Err(mpsc::error::TrySendError::Full(_)) = sink.queue.try_send(event.clone()) {
+            if sink.stats.dropped.fetch_add(1, Ordering::Relaxed) == 0 {
+                warn!(
+                    "Audit sink {} is not keeping up, dropping events",
+                    sink.config.describe()
+                );
+            }
+        }
+    }
+}
+
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .filter(|h| !h.is_empty() && h.is_ascii() && !h.contains(' '))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// Event as sent to every sink
+fn record(event: &AuditLog) -> serde_json::Value {
+    let mut record = serde_json::to_value(event).unwrap_or_default();
+    record["source"] = "keykeeper".into();
+    record["host"] = hostname().into();
+    record
+}
+
+fn syslog_message(event: &AuditLog) -> String {
+    let severity = if event.success { 6 } else { 4 };
+    let msg_id: String = event
+        .action
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(32)
+        .collect();
+    format!(
+        "<{}>1 {} {} keykeeper {} {} - {}",
+        SYSLOG_FACILITY * 8 + severity,
+        event
+            .timestamp
+            .to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        hostname(),
+        std::process::id(),
+        if msg_id.is_empty() { "-" } else { &msg_id },
+        record(event)
+    )
+}
+
+/// Open connections and files kept between events
+#[derive(Default)]
+struct Connection {
+    file: Option<tokio::fs::File>,
+    tcp: Option<tokio::net::TcpStream>,
+    udp: Option<tokio::net::UdpSocket>,
+    http: Option<reqwest::Client>,
+}
+
+async fn send(
+    target: &SinkTarget,
+    connection: &mut Connection,
+    event: &AuditLog,
+) -> Result<(), String> {
+    match target {
+        SinkTarget::Jsonl { path } => {
+            if connection.file.is_none() {
+                if let Some(dir) = path.parent() {
+                    tokio::fs::create_dir_all(dir)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                }
+                let file = tokio::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                connection.file = Some(file);
+            }
+            let line = format!("{}\n", record(event));
+            let file = connection.file.as_mut().unwrap();
+            file.write_all(line.as_bytes())
+                .await
+                .map_err(|e| e.to_string())?;
+            file.flush().await.map_err(|e| e.to_string())
+        }
+        SinkTarget::Syslog {
+            address,
+            protocol: SyslogProtocol::Udp,
+        } => {
+            if connection.udp.is_none() {
+                let socket = tokio::net::UdpSocket::bind("0.0.0.0:0")
+                    .await
+                    .map_err(|e| e.to_string())?;
+                socket.connect(address).await.map_err(|e| e.to_string())?;
+                connection.udp = Some(socket);
+            }
+            let message = syslog_message(event);
+            connection
+                .udp
+                .as_ref()
+                .unwrap()
+                .send(message.as_bytes())
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }
+        SinkTarget::Syslog {
+            address,
+            protocol: SyslogProtocol::Tcp,
+        } => {
+            if connection.tcp.is_none() {
+                let stream = tokio::net::TcpStream::connect(address)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                connection.tcp = Some(stream);
+            }
+            let message = syslog_message(event);
+            let framed = format!("{} {}", message.len(), message);
+            connection
+                .tcp
+                .as_mut()
+                .unwrap()
+                .write_all(framed.as_bytes())
+                .await
+                .map_err(|e| e.to_string())
+        }
+        SinkTarget::Webhook { url, headers } => {
+            let client = connection
+                .http
+                .get_or_insert_with(reqwest::Client::new)
+                .clone();
+            let mut request = client.post(url).json(&record(event));
+            for (name, value) in headers {
+                request = request.header(name, value);
+            }
+            let response = request.send().await.map_err(|e| e.to_string())?;
+            if response.status().is_success() {
+                Ok(())
+            } else {
+                Err(format!("HTTP {}", response.status()))
+            }
+        }
+    }
+}
+
+async fn run(target: SinkTarget, mut events: mpsc::Receiver<AuditLog>, stats: Arc<SinkStats>) {
+    let mut connection = Connection::default();
+    let mut backoff = Duration::from_secs(1);
+    let mut failing = false;
+
+    while let Some(event) = events.recv().await {
+        let mut attempt = 1;
+        let result = loop {
+            let result =
+                match tokio::time::timeout(SEND_TIMEOUT, send(&target, &mut connection, &event))
+                    .await
+                {
+                    Ok(result) => result,
+                    Err(_) => Err("timed out".to_string()),
+                };
+            if result.is_ok() || attempt == MAX_ATTEMPTS {
+                break result;
+            }
+            // Reconnect on the next attempt
+            connection = Connection::default();
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+            attempt += 1;
+        };
+
+        match result {
+            Ok(()) => {
+                stats.delivered.fetch_add(1, Ordering::Relaxed);
+                backoff = Duration::from_secs(1);
+                if failing {
+                    info!("Audit sink recovered");
+                    failing = false;
+                }
+            }
+            Err(e) => {
+                stats.failed.fetch_add(1, Ordering::Relaxed);
+                if !failing {
+                    warn!("Audit sink failed: {}", e);
+                    failing = true;
+                }
+                *stats.last_error.lock().unwrap() = Some((Utc::now(), e));
+                connection = Connection::default();
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditSinkStatus {
+    pub sink: String,
+    pub events: Vec<String>,
+    pub failures_only: bool,
+    pub queued: usize,
+    pub delivered: u64,
+    pub failed: u64,
+    /// Events dropped because the queue was full
+    pub dropped: u64,
+    pub last_error: Option<String>,
+    #[serde(default, with = "crate::timestamps::rfc3339_option")]
+    pub last_error_at: Option<DateTime<Utc>>,
+}
+
+/// Delivery counters of the running sinks, since they were configured
+#[tauri::command]
+pub async fn get_audit_sink_status() -> Result<Vec<AuditSinkStatus>, KeyKeeperError> {
+    let sinks = SINKS.lock().unwrap();
+    Ok(sinks
+        .iter()
+        .map(|sink| {
+            let last_error = sink.stats.last_error.lock().unwrap().clone();
+            AuditSinkStatus {
+                sink: sink.config.describe(),
+                events: sink.config.events.clone(),
+                failures_only: sink.config.failures_only,
+                queued: QUEUE_CAPACITY - sink.queue.capacity(),
+                delivered: sink.stats.delivered.load(Ordering::Relaxed),
+                failed: sink.stats.failed.load(Ordering::Relaxed),
+                dropped: sink.stats.dropped.load(Ordering::Relaxed),
+                last_error_at: last_error.as_ref().map(|(at, _)| *at),
+                last_error: last_error.map(|(_, e)| e),
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_events_and_formats_syslog() {
+        let config: AuditSinkConfig = toml::from_str(
+            r#"
+            type = "syslog"
+            address = "siem.internal:514"
+            events = ["unlock_vault", "delete_*"]
+            "#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.target,
+            SinkTarget::Syslog {
+                address: "siem.internal:514".to_string(),
+                protocol: SyslogProtocol::Udp,
+            }
+        );
+
+        let mut event =
+            crate::create_audit_log("delete_api_key", "api_key", Some("k1"), true, None);
+        assert!(config.accepts(&event));
+        event.action = "unlock_vault".to_string();
+        assert!(config.accepts(&event));
+        event.action = "get_api_keys".to_string();
+        assert!(!config.accepts(&event));
+
+        event.success = false;
+        let message = syslog_message(&event);
+        assert!(message.starts_with("<84>1 "));
+        assert!(message.contains(" keykeeper "));
+        assert!(message.contains(" get_api_keys - {"));
+
+        let bad: AuditSinkConfig =
+            toml::from_str("type = \"syslog\"\naddress = \"siem.internal\"").unwrap();
+        assert!(bad.validate().is_err());
+    }
+}
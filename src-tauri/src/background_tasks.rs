@@ -0,0 +1,371 @@
+//! Long-running background work: the HTTP server, schedulers, sweepers and
+//! watchers.
+//!
+//! Tasks are registered by name so they can be listed and cancelled from the
+//! UI. Supervised tasks are restarted with a growing delay when they panic;
+//! a task that returns is considered finished. On exit every task is aborted
+//! and awaited for a bounded time.
+
+use crate::errors::KeyKeeperError;
+use crate::{guards, log_audit_event, AppState};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::State;
+use tracing::{error, info, warn};
+
+/// Longest wait before restarting a task that keeps crashing
+const MAX_RESTART_DELAY_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Running,
+    /// Crashed, waiting to be restarted
+    Restarting,
+    Finished,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackgroundTaskInfo {
+    pub name: String,
+    pub supervised: bool,
+    pub status: TaskStatus,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub started_at: DateTime<Utc>,
+    pub restarts: u32,
+    pub last_error: Option<String>,
+}
+
+struct TaskEntry {
+    info: BackgroundTaskInfo,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl TaskEntry {
+    fn is_active(&self) -> bool {
+        matches!(
+            self.info.status,
+            TaskStatus::Running | TaskStatus::Restarting
+        )
+    }
+}
+
+/// Aborts the task when the supervisor holding it is itself aborted
+struct AbortOnDrop(tokio::task::JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[derive(Default)]
+pub struct TaskManager {
+    tasks: Mutex<BTreeMap<String, TaskEntry>>,
+    shutting_down: AtomicBool,
+}
+
+impl TaskManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `future` once under `name`
+    pub fn spawn<F>(self: &Arc<Self>, name: &str, future: F) -> Result<(), KeyKeeperError>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let future = Mutex::new(Some(future));
+        self.register(name, false, move || {
+            future
+                .lock()
+                .unwrap()
+                .take()
+                .expect("unsupervised task runs once")
+        })
+    }
+
+    /// Run the future made by `factory` under `name`, making a new one
+    /// whenever the previous one panics
+    pub fn spawn_supervised<F, Fut>(
+        self: &Arc<Self>,
+        name: &str,
+        factory: F,
+    ) -> Result<(), KeyKeeperError>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.register(name, true, factory)
+    }
+
+    fn register<F, Fut>(
+        self: &Arc<Self>,
+        name: &str,
+        supervised: bool,
+        factory: F,
+    ) -> Result<(), KeyKeeperError>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err(KeyKeeperError::Conflict(
+                "The app is shutting down".to_string(),
+            ));
+        }
+        let mut tasks = self.tasks.lock().unwrap();
+        if tasks.get(name).is_some_and(TaskEntry::is_active) {
+            return Err(KeyKeeperError::Conflict(format!(
+                "Background task '{}' is already running",
+                name
+            )));
+        }
+        tasks.insert(
+            name.to_string(),
+            TaskEntry {
+                info: BackgroundTaskInfo {
+                    name: name.to_string(),
+                    supervised,
+                    status: TaskStatus::Running,
+                    started_at: Utc::now(),
+                    restarts: 0,
+                    last_error: None,
+                },
+                handle: None,
+            },
+        );
+
+        let manager = Arc::clone(self);
+        let task_name = name.to_string();
+        let handle = tauri::async_runtime::spawn(async move {
+            manager.supervise(&task_name, supervised, factory).await;
+        });
+        if let Some(entry) = tasks.get_mut(name) {
+            entry.handle = Some(handle);
+        }
+        info!("Started background task {}", name);
+        Ok(())
+    }
+
+    async fn supervise<F, Fut>(&self, name: &str, supervised: bool, factory: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut restarts: u32 = 0;
+        loop {
+            let mut run = AbortOnDrop(tokio::spawn(factory()));
+            let outcome = (&mut run.0).await;
+            let error = match outcome {
+                Ok(()) => {
+                    self.update(name, |info| info.status = TaskStatus::Finished);
+                    return;
+                }
+                Err(e) if e.is_panic() => panic_message(e.into_panic()),
+                Err(e) => e.to_string(),
+            };
+            error!("Background task {} crashed: {}", name, error);
+
+            if !supervised || self.shutting_down.load(Ordering::SeqCst) {
+                self.update(name, |info| {
+                    info.status = TaskStatus::Failed;
+                    info.last_error = Some(error);
+                });
+                return;
+            }
+
+            restarts += 1;
+            self.update(name, |info| {
+                info.status = TaskStatus::Restarting;
+                info.restarts = restarts;
+                info.last_error = Some(error);
+            });
+            let delay = (1u64 << (restarts - 1).min(6)).min(MAX_RESTART_DELAY_SECS);
+            tokio::time::sleep(Duration::from_secs(delay)).await;
+            warn!("Restarting background task {} (restart {})", name, restarts);
+            self.update(name, |info| info.status = TaskStatus::Running);
+        }
+    }
+
+    fn update(&self, name: &str, change: impl FnOnce(&mut BackgroundTaskInfo)) {
+        if let Some(entry) = self.tasks.lock().unwrap().get_mut(name) {
+            // Cancellation wins over whatever the task was doing
+            if entry.info.status != TaskStatus::Cancelled {
+                change(&mut entry.info);
+            }
+        }
+    }
+
+    pub fn list(&self) -> Vec<BackgroundTaskInfo> {
+        self.tasks
+            .lock()
+            .unwrap()
+            .values()
+            .map(|entry| entry.info.clone())
+            .collect()
+    }
+
+    pub fn is_running(&self, name: &str) -> bool {
+        self.tasks
+            .lock()
+            .unwrap()
+            .get(name)
+            .is_some_and(TaskEntry::is_active)
+    }
+
+    /// Abort the task; returns whether it was running
+    pub fn cancel(&self, name: &str) -> Result<bool, KeyKeeperError> {
+        let mut tasks = self.tasks.lock().unwrap();
+        let entry = tasks
+            .get_mut(name)
+            .ok_or_else(|| KeyKeeperError::not_found("Background task"))?;
+        if !entry.is_active() {
+            return Ok(false);
+        }
+        entry.info.status = TaskStatus::Cancelled;
+        if let Some(handle) = entry.handle.take() {
+            handle.abort();
+        }
+        info!("Cancelled background task {}", name);
+        Ok(true)
+    }
+
+    /// Abort every task and wait up to `timeout` for them to wind down. No
+    /// task can be started afterwards.
+    pub async fn shutdown(&self, timeout: Duration) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+        let handles: Vec<(String, JoinHandle<()>)> = {
+            let mut tasks = self.tasks.lock().unwrap();
+            tasks
+                .iter_mut()
+                .filter(|(_, entry)| entry.is_active())
+                .filter_map(|(name, entry)| {
+                    entry.info.status = TaskStatus::Cancelled;
+                    entry.handle.take().map(|handle| (name.clone(), handle))
+                })
+                .collect()
+        };
+        if handles.is_empty() {
+            return;
+        }
+
+        info!("Stopping {} background task(s)", handles.len());
+        let deadline = tokio::time::Instant::now() + timeout;
+        for (name, handle) in handles {
+            handle.abort();
+            if tokio::time::timeout_at(deadline, handle).await.is_err() {
+                warn!("Background task {} did not stop in time", name);
+            }
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "panicked".to_string()
+    }
+}
+
+#[tauri::command]
+pub async fn list_background_tasks(
+    state: State<'_, AppState>,
+) -> Result<Vec<BackgroundTaskInfo>, KeyKeeperError> {
+    Ok(state.tasks.list())
+}
+
+/// Stop a background task until it is started again (or the app restarts)
+#[tauri::command]
+pub async fn cancel_background_task(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<bool, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let cancelled = state.tasks.cancel(&name)?;
+    if cancelled && name == crate::HTTP_SERVER_TASK {
+        state.vscode_server_running.store(false, Ordering::SeqCst);
+        state.health.set_server_running(false);
+    }
+    log_audit_event(
+        &state,
+        "cancel_background_task",
+        "background_task",
+        Some(&name),
+        cancelled,
+        None,
+    )
+    .await;
+    Ok(cancelled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+
+    async fn wait_for(manager: &TaskManager, name: &str, status: TaskStatus) -> BackgroundTaskInfo {
+        for _ in 0..500 {
+            let info = manager.list().into_iter().find(|t| t.name == name).unwrap();
+            if info.status == status {
+                return info;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("task {} never reached {:?}", name, status);
+    }
+
+    #[tokio::test]
+    async fn restarts_crashed_supervised_task() {
+        let manager = Arc::new(TaskManager::new());
+        let runs = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&runs);
+        manager
+            .spawn_supervised("flaky", move || {
+                let counter = Arc::clone(&counter);
+                async move {
+                    if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                        panic!("first run fails");
+                    }
+                }
+            })
+            .unwrap();
+
+        let info = wait_for(&manager, "flaky", TaskStatus::Finished).await;
+        assert_eq!(info.restarts, 1);
+        assert_eq!(info.last_error.as_deref(), Some("first run fails"));
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn cancel_and_shutdown_stop_tasks() {
+        let manager = Arc::new(TaskManager::new());
+        manager
+            .spawn("forever", std::future::pending::<()>())
+            .unwrap();
+        assert!(manager.spawn("forever", async {}).is_err());
+
+        assert!(manager.cancel("forever").unwrap());
+        assert!(!manager.is_running("forever"));
+        manager
+            .spawn("forever", std::future::pending::<()>())
+            .unwrap();
+
+        manager.shutdown(Duration::from_secs(1)).await;
+        assert!(!manager.is_running("forever"));
+        assert!(manager.spawn("later", async {}).is_err());
+    }
+}
@@ -0,0 +1,213 @@
+//! `POST /api/batch`: several read requests in one round trip.
+//!
+//! The VS Code extension fetches keys, projects, activity and stats when it
+//! starts. A batch answers all of them under a single acquisition of the
+//! vault and index locks. Only the read routes below can be batched; other
+//! sub-requests get a 400 response of their own without failing the batch.
+//! The single routes use the same functions, so both return the same JSON.
+
+use crate::errors::KeyKeeperError;
+use crate::masking::{self, MaskedApiKey};
+use crate::vault_index::{self, VaultIndex};
+use crate::{activity, archive, pagination, pinning, vault_stats, ApiKeyVault};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+pub const MAX_SUB_REQUESTS: usize = 20;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubRequest {
+    /// Echoed back so responses can be matched to requests
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default = "default_method")]
+    pub method: String,
+    /// Path with its query string, e.g. `/api/keys?project=/work/shop`
+    pub path: String,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SubResponse {
+    pub id: Option<String>,
+    pub status: u16,
+    pub body: Value,
+}
+
+pub type Params = HashMap<String, String>;
+
+pub fn parse_params(query: Option<&str>) -> Params {
+    url::form_urlencoded::parse(query.unwrap_or("").as_bytes())
+        .into_owned()
+        .collect()
+}
+
+fn to_value<T: Serialize>(value: &T) -> Result<Value, KeyKeeperError> {
+    serde_json::to_value(value).map_err(|e| KeyKeeperError::Internal(e.to_string()))
+}
+
+/// `GET /api/keys`: optionally filtered and paged
+pub fn list_keys(
+    vault: &ApiKeyVault,
+    index: &mut VaultIndex,
+    params: &Params,
+) -> Result<Value, KeyKeeperError> {
+    let kind = match params.get("kind") {
+        Some(kind) => Some(
+            serde_json::from_value(serde_json::json!(kind))
+                .map_err(|_| KeyKeeperError::Validation(format!("Unknown key kind '{}'", kind)))?,
+        ),
+        None => None,
+    };
+    // Optional filters served from the in-memory indexes
+    let filter = vault_index::KeyFilter {
+        project_path: params.get("project").cloned(),
+        service: params.get("service").cloned(),
+        environment: params.get("environment").cloned(),
+        tag: params.get("tag").cloned(),
+        kind,
+        group_id: params.get("group").cloned(),
+        include_archived: params.get("include_archived").map(String::as_str) == Some("true"),
+    };
+    let keys: Vec<_> = if filter.is_empty() {
+        vault
+            .keys
+            .values()
+            .filter(|key| filter.include_archived || !archive::is_archived(key))
+            .cloned()
+            .collect()
+    } else {
+        let ids = index.filter_ids(vault, &filter);
+        vault_index::collect_keys(vault, &ids)
+    };
+
+    // Cursor-based paging when `limit` or `cursor` is given, full list otherwise
+    if params.contains_key("limit") || params.contains_key("cursor") {
+        let limit = params
+            .get("limit")
+            .and_then(|l| l.parse().ok())
+            .unwrap_or(pagination::DEFAULT_PAGE_SIZE);
+        let page =
+            pagination::keys_after_cursor(keys, params.get("cursor").map(String::as_str), limit)?;
+        to_value(&page.map(MaskedApiKey::from))
+    } else {
+        to_value(&masking::mask_all(keys))
+    }
+}
+
+/// `GET /api/keys/pinned`
+pub fn pinned_keys(vault: &ApiKeyVault, index: &mut VaultIndex) -> Result<Value, KeyKeeperError> {
+    let ids = index.pinned_ids(vault);
+    to_value(&masking::mask_all(pinning::pinned_keys(vault, &ids)))
+}
+
+/// `GET /api/projects`
+pub fn projects(vault: &ApiKeyVault) -> Result<Value, KeyKeeperError> {
+    to_value(&vault.projects)
+}
+
+/// `GET /api/activity/recent`, shaped the way the VS Code extension reads it
+pub fn recent_activity(vault: &ApiKeyVault, params: &Params) -> Result<Value, KeyKeeperError> {
+    let filter = activity::ActivityFilter {
+        key_id: params.get("key_id").cloned(),
+        actor: params.get("actor").cloned(),
+        activity_type: params.get("type").cloned(),
+        limit: params.get("limit").and_then(|l| l.parse().ok()),
+    };
+    let entries: Vec<Value> = activity::query(vault, &filter)
+        .into_iter()
+        .map(|activity| {
+            serde_json::json!({
+                "id": activity.id,
+                "type": activity.activity_type,
+                "keyId": activity.key_id,
+                "keyName": activity.key_name,
+                "timestamp": activity.timestamp.to_rfc3339(),
+                "details": activity.details,
+                "actor": activity.actor,
+                "source": activity.source,
+            })
+        })
+        .collect();
+    Ok(Value::Array(entries))
+}
+
+/// `GET /api/stats`
+pub fn stats(vault: &ApiKeyVault, docs_count: usize) -> Result<Value, KeyKeeperError> {
+    to_value(&vault_stats::compute_vault_statistics(vault, docs_count))
+}
+
+fn serve(
+    vault: &ApiKeyVault,
+    index: &mut VaultIndex,
+    docs_count: usize,
+    request: &SubRequest,
+) -> Result<Value, KeyKeeperError> {
+    let (path, query) = match request.path.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (request.path.as_str(), None),
+    };
+    let params = parse_params(query);
+    match (request.method.to_ascii_uppercase().as_str(), path) {
+        ("GET", "/api/keys") => list_keys(vault, index, &params),
+        ("GET", "/api/keys/pinned") => pinned_keys(vault, index),
+        ("GET", "/api/projects") => projects(vault),
+        ("GET", "/api/activity/recent") => recent_activity(vault, &params),
+        ("GET", "/api/stats") => stats(vault, docs_count),
+        (method, path) => Err(KeyKeeperError::Validation(format!(
+            "{} {} can't be batched",
+            method, path
+        ))),
+    }
+}
+
+/// Answer every sub-request, in order, against one view of the vault
+pub fn run(
+    vault: &ApiKeyVault,
+    index: &mut VaultIndex,
+    docs_count: usize,
+    requests: &[SubRequest],
+) -> Vec<SubResponse> {
+    requests
+        .iter()
+        .map(|request| {
+            let (status, body) = match serve(vault, index, docs_count, request) {
+                Ok(body) => (200, body),
+                Err(e) => (e.http_status(), e.to_json()),
+            };
+            SubResponse {
+                id: request.id.clone(),
+                status,
+                body,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsupported_sub_requests_fail_on_their_own() {
+        let vault = ApiKeyVault::default();
+        let mut index = VaultIndex::default();
+        let requests: Vec<SubRequest> = serde_json::from_value(serde_json::json!([
+            { "id": "keys", "path": "/api/keys?service=stripe" },
+            { "id": "sync", "method": "POST", "path": "/api/projects/sync" },
+            { "id": "kind", "path": "/api/keys?kind=nope" },
+        ]))
+        .unwrap();
+
+        let responses = run(&vault, &mut index, 0, &requests);
+        assert_eq!(responses[0].status, 200);
+        assert_eq!(responses[0].body, serde_json::json!([]));
+        assert_eq!(responses[1].id.as_deref(), Some("sync"));
+        assert_eq!(responses[1].status, 400);
+        assert_eq!(responses[2].status, 400);
+    }
+}
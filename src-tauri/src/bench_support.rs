@@ -0,0 +1,187 @@
+//! Fixtures and entry points for the criterion benchmarks in `benches/`.
+//!
+//! The benchmarks are their own crate, so they reach the vault code through
+//! these wrappers instead of making its internals public. Fixtures are
+//! deterministic: the same size always gives the same vault and docs, with
+//! keys spread over services, environments, projects and tags the way a
+//! real vault is, so runs compare like with like.
+
+use crate::errors::KeyKeeperError;
+use crate::vault_index::VaultIndex;
+use crate::vault_storage::VaultStorage;
+use crate::{batch, derive_key_from_password, etag, ApiKey, ApiKeyVault, DocsStore, Documentation};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{Duration, TimeZone, Utc};
+use hyper::body::Body;
+use hyper::header::HeaderMap;
+
+pub use crate::vault_storage::FileStorage;
+
+pub const PASSWORD: &str = "keykeeper-bench";
+const SALT: [u8; 16] = [42; 16];
+
+const SERVICES: &[&str] = &[
+    "Stripe",
+    "OpenAI",
+    "GitHub",
+    "AWS",
+    "Twilio",
+    "SendGrid",
+    "Supabase",
+    "Vercel",
+    "Sentry",
+    "Cloudflare",
+];
+const ENVIRONMENTS: &[&str] = &["development", "staging", "production"];
+const PROJECTS: usize = 25;
+
+fn fixture_key(index: usize) -> ApiKey {
+    let service = SERVICES[index % SERVICES.len()];
+    let created_at =
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::hours(index as i64);
+    ApiKey {
+        id: format!("key_{:05}", index),
+        name: format!("{}_KEY_{}", service.to_uppercase(), index),
+        service: service.to_string(),
+        key: format!(
+            "sk_bench_{:032x}",
+            index.wrapping_mul(0x9e37_79b9_7f4a_7c15)
+        ),
+        description: Some(format!(
+            "{} key number {} for the bench fixture",
+            service, index
+        )),
+        environment: ENVIRONMENTS[index % ENVIRONMENTS.len()].to_string(),
+        quota: None,
+        cost: None,
+        expires_at: None,
+        scopes: vec!["read".to_string(), "write".to_string()],
+        created_at,
+        updated_at: created_at,
+        tags: vec![format!("team-{}", index % 7), service.to_lowercase()],
+        is_active: !index.is_multiple_of(11),
+        source_type: Some("manual".to_string()),
+        env_file_path: None,
+        project_path: Some(format!("/bench/projects/project-{}", index % PROJECTS)),
+        env_file_name: Some(".env".to_string()),
+        remote_secrets: vec![],
+        package_path: None,
+        rotation_interval_days: Some(90),
+        last_rotated_at: None,
+        kind: Default::default(),
+        credential: None,
+        oauth: None,
+        values: Default::default(),
+        is_pinned: index.is_multiple_of(50),
+        archived_at: None,
+        last_used_at: None,
+        backend: None,
+    }
+}
+
+/// An unlocked vault with `keys` keys, encrypted with `PASSWORD` when saved
+pub fn vault_with_keys(keys: usize) -> ApiKeyVault {
+    let key = derive_key_from_password(PASSWORD, &SALT);
+    let mut vault = ApiKeyVault {
+        master_password_hash: Some(bcrypt::hash(PASSWORD, 4).expect("bcrypt hash")),
+        encryption_key: Some(general_purpose::STANDARD.encode(*key)),
+        salt: Some(general_purpose::STANDARD.encode(SALT)),
+        ..ApiKeyVault::default()
+    };
+    for index in 0..keys {
+        let key = fixture_key(index);
+        vault.keys.insert(key.id.clone(), key);
+    }
+    vault
+}
+
+/// A docs store with `docs` provider docs of a few paragraphs each
+pub fn docs_with(docs: usize) -> DocsStore {
+    let mut store = DocsStore::new();
+    let now = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    for index in 0..docs {
+        let service = SERVICES[index % SERVICES.len()];
+        store.add_doc(Documentation {
+            id: format!("doc_{:05}", index),
+            title: format!("{} guide {}", service, index),
+            content: format!(
+                "# {service} authentication\n\nCreate a key in the {service} dashboard and \
+                 store it as {upper}_KEY. Rotate it every 90 days.\n\n## Webhooks\n\nVerify \
+                 the signature header before trusting a payload.\n",
+                service = service,
+                upper = service.to_uppercase(),
+            ),
+            doc_type: "guide".to_string(),
+            project_id: None,
+            provider_id: Some(service.to_lowercase()),
+            url: None,
+            tags: vec![service.to_lowercase(), "auth".to_string()],
+            created_at: now,
+            updated_at: now,
+            language: "en".to_string(),
+            is_favorite: false,
+            search_keywords: vec![service.to_lowercase(), "webhooks".to_string()],
+            sections: Vec::new(),
+            is_offline: false,
+            attachments: Vec::new(),
+        });
+    }
+    store
+}
+
+/// Encrypt and write the vault, as every save does
+pub fn save(storage: &dyn VaultStorage, vault: &ApiKeyVault) -> Result<(), KeyKeeperError> {
+    crate::save_vault_to(storage, vault)
+}
+
+/// Decrypt the vault with `PASSWORD`, as unlocking does
+pub fn unlock(storage: &dyn VaultStorage) -> Result<ApiKeyVault, String> {
+    crate::decrypt_vault_from(storage, PASSWORD)
+}
+
+/// Load the metadata-only view, as startup does
+pub fn load_locked(storage: &dyn VaultStorage) -> Result<ApiKeyVault, String> {
+    crate::load_vault_from(storage)
+}
+
+/// `search_api_keys`, returning the number of matches
+pub fn search_keys(vault: &ApiKeyVault, query: &str) -> usize {
+    crate::search_keys(vault, query, false).len()
+}
+
+/// Fresh index, as after a vault change
+pub fn index() -> VaultIndex {
+    VaultIndex::new()
+}
+
+/// Body of `GET /api/keys?<query>` as the HTTP server builds it, returning
+/// its size in bytes
+pub fn get_keys(vault: &ApiKeyVault, index: &mut VaultIndex, query: Option<&str>) -> u64 {
+    let params = batch::parse_params(query);
+    let listing = batch::list_keys(vault, index, &params).expect("key listing");
+    let response = etag::json_response(&HeaderMap::new(), listing.to_string());
+    response.body().size_hint().exact().unwrap_or_default()
+}
+
+/// `search_docs`, returning the number of matches
+pub fn search_docs(store: &DocsStore, query: &str) -> usize {
+    store.search_docs(query).len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixtures_are_deterministic_and_searchable() {
+        let vault = vault_with_keys(100);
+        assert_eq!(vault.keys.len(), 100);
+        assert_eq!(
+            vault.keys["key_00042"].key,
+            vault_with_keys(43).keys["key_00042"].key
+        );
+        assert_eq!(search_keys(&vault, "stripe"), 10);
+        assert!(get_keys(&vault, &mut index(), Some("limit=20")) > 0);
+        assert_eq!(search_docs(&docs_with(20), "webhooks"), 20);
+    }
+}
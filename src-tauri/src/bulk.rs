@@ -0,0 +1,264 @@
+//! Bulk changes to many keys in one call. Every key is checked before
+//! anything is written, so a bulk operation either applies to all keys or
+//! to none; the per-key results say which keys stopped it.
+
+use crate::activity::{self, Actor};
+use crate::errors::KeyKeeperError;
+use crate::{
+    env_values, guards, log_audit_event, remove_key, save_vault, ApiKey, ApiKeyVault, AppState,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+/// Changes applied to every key of a bulk update; unset fields are left alone
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeyPatch {
+    pub environment: Option<String>,
+    pub is_active: Option<bool>,
+    /// Replaces the tags, before `add_tags` and `remove_tags` apply
+    pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub add_tags: Vec<String>,
+    #[serde(default)]
+    pub remove_tags: Vec<String>,
+}
+
+impl KeyPatch {
+    fn is_empty(&self) -> bool {
+        self.environment.is_none()
+            && self.is_active.is_none()
+            && self.tags.is_none()
+            && self.add_tags.is_empty()
+            && self.remove_tags.is_empty()
+    }
+
+    fn apply(&self, key: &mut ApiKey) -> Result<(), String> {
+        if let Some(environment) = &self.environment {
+            if environment.trim().is_empty() {
+                return Err("Environment can't be empty".to_string());
+            }
+            // The new environment's value would be shadowed by `key`
+            if !env_values::is_primary(key, environment)
+                && env_values::has_environment(key, environment)
+            {
+                return Err(format!("Key already has a separate {} value", environment));
+            }
+            key.environment = environment.clone();
+        }
+        if let Some(is_active) = self.is_active {
+            key.is_active = is_active;
+        }
+        if let Some(tags) = &self.tags {
+            key.tags = tags.clone();
+        }
+        for tag in &self.add_tags {
+            if !key.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                key.tags.push(tag.clone());
+            }
+        }
+        key.tags
+            .retain(|t| !self.remove_tags.iter().any(|r| r.eq_ignore_ascii_case(t)));
+        key.updated_at = Utc::now();
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkKeyResult {
+    pub key_id: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkResult {
+    /// False when any key failed, in which case nothing was changed
+    pub applied: bool,
+    pub results: Vec<BulkKeyResult>,
+}
+
+fn unique_ids(key_ids: Vec<String>) -> Result<Vec<String>, KeyKeeperError> {
+    let mut ids: Vec<String> = Vec::new();
+    for id in key_ids {
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    if ids.is_empty() {
+        return Err(KeyKeeperError::Validation("No keys given".to_string()));
+    }
+    Ok(ids)
+}
+
+/// The patched keys, or nothing when a key can't be patched
+pub fn plan_update(
+    vault: &ApiKeyVault,
+    key_ids: &[String],
+    patch: &KeyPatch,
+) -> (Vec<ApiKey>, Vec<BulkKeyResult>) {
+    let mut updated = Vec::new();
+    let mut results = Vec::new();
+    for id in key_ids {
+        let outcome = match vault.keys.get(id) {
+            None => Err("Key not found".to_string()),
+            Some(key) => {
+                let mut key = key.clone();
+                patch.apply(&mut key).map(|()| updated.push(key))
+            }
+        };
+        results.push(BulkKeyResult {
+            key_id: id.clone(),
+            success: outcome.is_ok(),
+            error: outcome.err(),
+        });
+    }
+    if results.iter().any(|r| !r.success) {
+        updated.clear();
+    }
+    (updated, results)
+}
+
+fn audit_message(ids: &[String]) -> String {
+    format!("{} keys: {}", ids.len(), ids.join(", "))
+}
+
+/// Apply `patch` to every key, or to none if any key can't take it
+#[tauri::command]
+pub async fn bulk_update_keys(
+    key_ids: Vec<String>,
+    patch: KeyPatch,
+    state: State<'_, AppState>,
+) -> Result<BulkResult, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let key_ids = unique_ids(key_ids)?;
+    if patch.is_empty() {
+        return Err(KeyKeeperError::Validation("Nothing to change".to_string()));
+    }
+
+    let mut vault_guard = state.vault.lock().await;
+    let (updated, results) = plan_update(&vault_guard, &key_ids, &patch);
+    let applied = !updated.is_empty();
+    for key in updated {
+        let changes = vault_guard
+            .keys
+            .get(&key.id)
+            .and_then(|before| activity::describe_changes(before, &key));
+        activity::record(
+            &mut vault_guard,
+            "key_updated",
+            &key,
+            Actor::ui(),
+            None,
+            changes,
+        );
+        vault_guard.keys.insert(key.id.clone(), key);
+    }
+    drop(vault_guard);
+
+    if applied {
+        save_vault(&state).await?;
+    }
+    log_audit_event(
+        &state,
+        "bulk_update_keys",
+        "api_key",
+        None,
+        applied,
+        Some(&audit_message(&key_ids)),
+    )
+    .await;
+    Ok(BulkResult { applied, results })
+}
+
+/// Delete every key, or none if any of them doesn't exist
+#[tauri::command]
+pub async fn bulk_delete_keys(
+    key_ids: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<BulkResult, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let key_ids = unique_ids(key_ids)?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let results: Vec<BulkKeyResult> = key_ids
+        .iter()
+        .map(|id| {
+            let exists = vault_guard.keys.contains_key(id);
+            BulkKeyResult {
+                key_id: id.clone(),
+                success: exists,
+                error: (!exists).then(|| "Key not found".to_string()),
+            }
+        })
+        .collect();
+    let applied = results.iter().all(|r| r.success);
+    if applied {
+        for id in &key_ids {
+            remove_key(&mut vault_guard, id, Actor::ui());
+        }
+    }
+    drop(vault_guard);
+
+    if applied {
+        for id in &key_ids {
+            state.oauth_tokens.forget(id);
+        }
+        save_vault(&state).await?;
+    }
+    log_audit_event(
+        &state,
+        "bulk_delete_keys",
+        "api_key",
+        None,
+        applied,
+        Some(&audit_message(&key_ids)),
+    )
+    .await;
+    Ok(BulkResult { applied, results })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault_tests::test_key;
+
+    #[test]
+    fn one_failing_key_stops_the_whole_update() {
+        let mut vault = ApiKeyVault::default();
+        for id in ["a", "b"] {
+            let key = test_key(id)
+                .tags(&["Legacy"])
+                .value("production", "sk_live")
+                .build();
+            vault.keys.insert(id.to_string(), key);
+        }
+        let ids = vec!["a".to_string(), "b".to_string()];
+
+        let retag = KeyPatch {
+            is_active: Some(false),
+            add_tags: vec!["stripe".to_string()],
+            remove_tags: vec!["legacy".to_string()],
+            ..Default::default()
+        };
+        let (updated, results) = plan_update(&vault, &ids, &retag);
+        assert!(results.iter().all(|r| r.success));
+        assert_eq!(updated[0].tags, vec!["stripe".to_string()]);
+        assert!(!updated[0].is_active);
+
+        let mut with_missing = ids.clone();
+        with_missing.push("gone".to_string());
+        let (updated, results) = plan_update(&vault, &with_missing, &retag);
+        assert!(updated.is_empty());
+        assert_eq!(results[2].error.as_deref(), Some("Key not found"));
+
+        // Production already has its own value on these keys
+        let to_prod = KeyPatch {
+            environment: Some("prod".to_string()),
+            ..Default::default()
+        };
+        let (updated, results) = plan_update(&vault, &ids, &to_prod);
+        assert!(updated.is_empty());
+        assert!(!results[0].success);
+    }
+}
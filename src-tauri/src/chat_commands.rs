@@ -3,6 +3,9 @@ use crate::documentation_library::{
     ChatSession, ChatSessionStatus, DocumentationLibrary, DocumentationSearchResult,
     UserPreferences, DetailLevel, GenerationContext, IntegrationGeneration
 };
+use crate::errors::KeyKeeperError;
+use crate::guards::{self, Requirement};
+use crate::AppState;
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -182,7 +185,9 @@ pub struct GenerateIntegrationRequest {
 pub async fn create_chat_session(
     request: CreateChatSessionRequest,
     chat_state: State<'_, ChatEngineState>,
-) -> Result<CreateChatSessionResponse, String> {
+    state: State<'_, AppState>,
+) -> Result<CreateChatSessionResponse, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     info!("Creating new chat session for user: {}", request.user_id);
     
     let chat_engine = chat_state.chat_engine.read().await;
@@ -204,7 +209,7 @@ pub async fn create_chat_session(
         }
         Err(e) => {
             error!("❌ Failed to create chat session: {}", e);
-            Err(format!("Failed to create chat session: {}", e))
+            Err(KeyKeeperError::Internal(format!("Failed to create chat session: {}", e)))
         }
     }
 }
@@ -214,7 +219,9 @@ pub async fn create_chat_session(
 pub async fn send_chat_message(
     request: SendChatMessageRequest,
     chat_state: State<'_, ChatEngineState>,
-) -> Result<ChatResponse, String> {
+    state: State<'_, AppState>,
+) -> Result<ChatResponse, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     info!("Processing chat message for session: {}", request.session_id);
     
     let chat_engine = chat_state.chat_engine.read().await;
@@ -262,7 +269,7 @@ pub async fn send_chat_message(
         }
         Err(e) => {
             error!("❌ Failed to process chat message: {}", e);
-            Err(format!("Failed to process chat message: {}", e))
+            Err(KeyKeeperError::Internal(format!("Failed to process chat message: {}", e)))
         }
     }
 }
@@ -272,7 +279,9 @@ pub async fn send_chat_message(
 pub async fn get_chat_messages(
     session_id: String,
     chat_state: State<'_, ChatEngineState>,
-) -> Result<Vec<ChatMessageResponse>, String> {
+    state: State<'_, AppState>,
+) -> Result<Vec<ChatMessageResponse>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     debug!("Retrieving messages for session: {}", session_id);
     
     let chat_engine = chat_state.chat_engine.read().await;
@@ -319,7 +328,7 @@ pub async fn get_chat_messages(
         }
         Err(e) => {
             error!("❌ Failed to get chat messages: {}", e);
-            Err(format!("Failed to get chat messages: {}", e))
+            Err(KeyKeeperError::Internal(format!("Failed to get chat messages: {}", e)))
         }
     }
 }
@@ -329,7 +338,9 @@ pub async fn get_chat_messages(
 pub async fn get_user_chat_sessions(
     user_id: String,
     chat_state: State<'_, ChatEngineState>,
-) -> Result<Vec<ChatSessionSummary>, String> {
+    state: State<'_, AppState>,
+) -> Result<Vec<ChatSessionSummary>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     debug!("Retrieving chat sessions for user: {}", user_id);
     
     let chat_engine = chat_state.chat_engine.read().await;
@@ -358,7 +369,7 @@ pub async fn get_user_chat_sessions(
         }
         Err(e) => {
             error!("❌ Failed to get user chat sessions: {}", e);
-            Err(format!("Failed to get user chat sessions: {}", e))
+            Err(KeyKeeperError::Internal(format!("Failed to get user chat sessions: {}", e)))
         }
     }
 }
@@ -368,7 +379,9 @@ pub async fn get_user_chat_sessions(
 pub async fn search_documentation_for_chat(
     request: SearchDocumentationRequest,
     chat_state: State<'_, ChatEngineState>,
-) -> Result<Vec<DocumentationSearchResult>, String> {
+    state: State<'_, AppState>,
+) -> Result<Vec<DocumentationSearchResult>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     debug!("Searching documentation for chat: {}", request.query);
     
     let chat_engine = chat_state.chat_engine.read().await;
@@ -407,7 +420,7 @@ pub async fn search_documentation_for_chat(
         }
         Err(e) => {
             error!("❌ Failed to search documentation: {}", e);
-            Err(format!("Failed to search documentation: {}", e))
+            Err(KeyKeeperError::Internal(format!("Failed to search documentation: {}", e)))
         }
     }
 }
@@ -417,7 +430,9 @@ pub async fn search_documentation_for_chat(
 pub async fn generate_integration(
     request: GenerateIntegrationRequest,
     chat_state: State<'_, ChatEngineState>,
-) -> Result<IntegrationGeneration, String> {
+    state: State<'_, AppState>,
+) -> Result<IntegrationGeneration, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     info!("Generating integration for provider: {}", request.provider_name);
     
     let chat_engine = chat_state.chat_engine.read().await;
@@ -446,7 +461,7 @@ pub async fn generate_integration(
         }
         Err(e) => {
             error!("❌ Failed to generate integration: {}", e);
-            Err(format!("Failed to generate integration: {}", e))
+            Err(KeyKeeperError::Internal(format!("Failed to generate integration: {}", e)))
         }
     }
 }
@@ -456,7 +471,9 @@ pub async fn generate_integration(
 pub async fn archive_chat_session(
     session_id: String,
     chat_state: State<'_, ChatEngineState>,
-) -> Result<bool, String> {
+    state: State<'_, AppState>,
+) -> Result<bool, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     info!("Archiving chat session: {}", session_id);
     
     // This would update the session status to Archived
@@ -469,7 +486,9 @@ pub async fn archive_chat_session(
 pub async fn delete_chat_session(
     session_id: String,
     chat_state: State<'_, ChatEngineState>,
-) -> Result<bool, String> {
+    state: State<'_, AppState>,
+) -> Result<bool, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     info!("Deleting chat session: {}", session_id);
     
     // This would update the session status to Deleted or remove it entirely
@@ -481,7 +500,9 @@ pub async fn delete_chat_session(
 #[tauri::command]
 pub async fn get_chat_statistics(
     chat_state: State<'_, ChatEngineState>,
-) -> Result<std::collections::HashMap<String, usize>, String> {
+    state: State<'_, AppState>,
+) -> Result<std::collections::HashMap<String, usize>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     debug!("Retrieving chat statistics");
     
     let chat_engine = chat_state.chat_engine.read().await;
@@ -490,7 +511,7 @@ pub async fn get_chat_statistics(
         Ok(stats) => Ok(stats),
         Err(e) => {
             error!("❌ Failed to get chat statistics: {}", e);
-            Err(format!("Failed to get chat statistics: {}", e))
+            Err(KeyKeeperError::Internal(format!("Failed to get chat statistics: {}", e)))
         }
     }
 }
@@ -501,7 +522,9 @@ pub async fn update_session_preferences(
     session_id: String,
     preferences: ChatUserPreferences,
     chat_state: State<'_, ChatEngineState>,
-) -> Result<bool, String> {
+    state: State<'_, AppState>,
+) -> Result<bool, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     info!("Updating preferences for session: {}", session_id);
     
     // This would update the session's user preferences
@@ -513,7 +536,9 @@ pub async fn update_session_preferences(
 #[tauri::command]
 pub async fn get_available_documentation_libraries(
     chat_state: State<'_, ChatEngineState>,
-) -> Result<Vec<DocumentationLibrary>, String> {
+    state: State<'_, AppState>,
+) -> Result<Vec<DocumentationLibrary>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     debug!("Retrieving available documentation libraries");
     
     // This would return all available documentation libraries
@@ -536,7 +561,9 @@ pub struct LLMProviderConfig {
 pub async fn configure_llm_provider(
     config: LLMProviderConfig,
     chat_state: State<'_, ChatEngineState>,
-) -> Result<bool, String> {
+    state: State<'_, AppState>,
+) -> Result<bool, KeyKeeperError> {
+    guards::require(&state, &[Requirement::Unlocked, Requirement::LocalOnly]).await?;
     info!("Configuring LLM provider: {}", config.provider);
     
     // Store the configuration (in a real app, this would be persisted)
@@ -544,7 +571,7 @@ pub async fn configure_llm_provider(
     match config.provider.as_str() {
         "openai" => {
             if config.api_key.is_none() {
-                return Err("OpenAI API key is required".to_string());
+                return Err(KeyKeeperError::Validation("OpenAI API key is required".to_string()));
             }
             // Set environment variable for this session
             if let Some(api_key) = &config.api_key {
@@ -553,7 +580,7 @@ pub async fn configure_llm_provider(
         },
         "anthropic" => {
             if config.api_key.is_none() {
-                return Err("Anthropic API key is required".to_string());
+                return Err(KeyKeeperError::Validation("Anthropic API key is required".to_string()));
             }
             // Set environment variable for this session
             if let Some(api_key) = &config.api_key {
@@ -569,7 +596,7 @@ pub async fn configure_llm_provider(
             info!("Local Qwen model configuration set: {}", config.model);
         },
         _ => {
-            return Err(format!("Unsupported provider: {}", config.provider));
+            return Err(KeyKeeperError::Validation(format!("Unsupported provider: {}", config.provider)));
         }
     }
     
@@ -582,7 +609,9 @@ pub async fn configure_llm_provider(
 pub async fn test_llm_connection(
     provider: String,
     chat_state: State<'_, ChatEngineState>,
-) -> Result<String, String> {
+    state: State<'_, AppState>,
+) -> Result<String, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     info!("Testing LLM connection for provider: {}", provider);
     
     use crate::llm_proxy::{LLMConfig as ProxyConfig, process_with_llm_internal};
@@ -592,7 +621,7 @@ pub async fn test_llm_connection(
     let config = match provider.as_str() {
         "openai" => {
             let api_key = std::env::var("OPENAI_API_KEY")
-                .map_err(|_| "OpenAI API key not found in environment".to_string())?;
+                .map_err(|_| KeyKeeperError::NotFound("OpenAI API key not found in environment".to_string()))?;
             ProxyConfig {
                 provider: "openai".to_string(),
                 model: "gpt-4o-mini".to_string(),
@@ -605,7 +634,7 @@ pub async fn test_llm_connection(
         },
         "anthropic" => {
             let api_key = std::env::var("ANTHROPIC_API_KEY")
-                .map_err(|_| "Anthropic API key not found in environment".to_string())?;
+                .map_err(|_| KeyKeeperError::NotFound("Anthropic API key not found in environment".to_string()))?;
             ProxyConfig {
                 provider: "anthropic".to_string(),
                 model: "claude-sonnet-4-20250514".to_string(),
@@ -638,7 +667,7 @@ pub async fn test_llm_connection(
                 system_prompt: None,
             }
         },
-        _ => return Err(format!("Unsupported provider: {}", provider)),
+        _ => return Err(KeyKeeperError::Validation(format!("Unsupported provider: {}", provider))),
     };
     
     match process_with_llm_internal(test_prompt.to_string(), None, config, chat_state.llm_proxy_state.clone()).await {
@@ -648,7 +677,7 @@ pub async fn test_llm_connection(
         },
         Err(e) => {
             error!("❌ LLM connection test failed for {}: {}", provider, e);
-            Err(format!("Connection failed: {}", e))
+            Err(KeyKeeperError::Internal(format!("Connection failed: {}", e)))
         }
     }
 }
@@ -658,12 +687,14 @@ pub async fn test_llm_connection(
 pub async fn set_env_var(
     key: String,
     value: String,
-) -> Result<bool, String> {
+    state: State<'_, AppState>,
+) -> Result<bool, KeyKeeperError> {
+    guards::require(&state, &[Requirement::Unlocked, Requirement::LocalOnly]).await?;
     info!("Setting environment variable: {}", key);
     
     // Validate the key name for security
     if key.is_empty() || key.contains(' ') || key.contains('\0') {
-        return Err("Invalid environment variable key".to_string());
+        return Err(KeyKeeperError::Validation("Invalid environment variable key".to_string()));
     }
     
     // Set the environment variable for this process
@@ -677,11 +708,11 @@ pub async fn set_env_var(
         }
         Ok(_) => {
             error!("❌ Environment variable {} was set but value doesn't match", key);
-            Err("Failed to set environment variable correctly".to_string())
+            Err(KeyKeeperError::Internal("Failed to set environment variable correctly".to_string()))
         }
         Err(e) => {
             error!("❌ Failed to set environment variable {}: {}", key, e);
-            Err(format!("Failed to set environment variable: {}", e))
+            Err(KeyKeeperError::Internal(format!("Failed to set environment variable: {}", e)))
         }
     }
 }
@@ -692,7 +723,9 @@ pub async fn export_chat_session(
     session_id: String,
     format: String, // "json", "markdown", "text"
     chat_state: State<'_, ChatEngineState>,
-) -> Result<String, String> {
+    state: State<'_, AppState>,
+) -> Result<String, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     info!("Exporting chat session {} as {}", session_id, format);
     
     let chat_engine = chat_state.chat_engine.read().await;
@@ -704,7 +737,7 @@ pub async fn export_chat_session(
                 "json" => {
                     match serde_json::to_string_pretty(&messages) {
                         Ok(json) => Ok(json),
-                        Err(e) => Err(format!("Failed to serialize to JSON: {}", e))
+                        Err(e) => Err(KeyKeeperError::Internal(format!("Failed to serialize to JSON: {}", e)))
                     }
                 }
                 "markdown" => {
@@ -758,12 +791,12 @@ pub async fn export_chat_session(
                     
                     Ok(text)
                 }
-                _ => Err(format!("Unsupported export format: {}", format))
+                _ => Err(KeyKeeperError::Validation(format!("Unsupported export format: {}", format)))
             }
         }
         Err(e) => {
             error!("❌ Failed to export chat session: {}", e);
-            Err(format!("Failed to export chat session: {}", e))
+            Err(KeyKeeperError::Internal(format!("Failed to export chat session: {}", e)))
         }
     }
 }
\ No newline at end of file
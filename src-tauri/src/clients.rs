@@ -0,0 +1,471 @@
+//! Registered HTTP clients.
+//!
+//! A client (the VS Code extension, a script) calls
+//! `POST /api/clients/register` with its name and gets a client ID and secret
+//! back. The registration stays pending until the user approves it in the
+//! desktop app; from then on the client sends both on every request
+//! (`X-KeyKeeper-Client-Id`, `X-KeyKeeper-Client-Secret`) and its registered
+//! name replaces whatever `X-KeyKeeper-Client` says, so approval rules, the
+//! activity feed and audit logs name a verified client. An unverified request
+//! claiming the name of a registered client is relabelled "(unverified)".
+//!
+//! `server.require_registered_clients` is on unless turned off in the config
+//! (or with `KEYKEEPER_REQUIRE_CLIENTS=0` for setups whose scripts haven't
+//! registered yet): requests without valid credentials are refused everywhere
+//! except the health probes and the registration routes, and while the vault
+//! is locked, since credentials can't be checked then. The secret is only returned once; the vault keeps
+//! its SHA-256.
+
+use crate::errors::KeyKeeperError;
+use crate::{approvals, devices, guards, log_audit_event, save_vault, ApiKeyVault, AppState};
+use chrono::{DateTime, Duration, Utc};
+use hyper::header::HeaderValue;
+use hyper::Request;
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::future::Future;
+use tauri::State;
+use tokio::sync::Mutex;
+
+pub const CLIENT_ID_HEADER: &str = "x-keykeeper-client-id";
+pub const CLIENT_SECRET_HEADER: &str = "x-keykeeper-client-secret";
+const SECRET_PREFIX: &str = "kkc_";
+/// Clients register again after this long
+const REGISTRATION_DAYS: i64 = 365;
+/// Registrations waiting for the user at any one time
+const MAX_PENDING: usize = 10;
+const MAX_NAME_LEN: usize = 100;
+/// Routes unregistered clients can reach when registration is required
+const OPEN_PATHS: &[&str] = &[
+    "/health",
+    "/ready",
+    "/live",
+    "/api/clients/register",
+    "/api/clients/status",
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientStatus {
+    /// Waiting for the user to approve it
+    Pending,
+    Approved,
+    Revoked,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisteredClient {
+    pub client_id: String,
+    pub name: String,
+    /// SHA-256 of the secret; empty in copies handed to the UI
+    pub secret_hash: String,
+    pub status: ClientStatus,
+    /// Device the client runs on, in `get_device_id` form when it knows it
+    pub device_id: Option<String>,
+    /// IP address the registration came from
+    pub registered_from: Option<String>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub expires_at: DateTime<Utc>,
+    #[serde(default, with = "crate::timestamps::rfc3339_option")]
+    pub approved_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "crate::timestamps::rfc3339_option")]
+    pub last_seen_at: Option<DateTime<Utc>>,
+}
+
+impl RegisteredClient {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.status == ClientStatus::Approved && self.expires_at > now
+    }
+
+    pub fn redacted(&self) -> Self {
+        Self {
+            secret_hash: String::new(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Body of `POST /api/clients/register`
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegistrationRequest {
+    pub name: String,
+    #[serde(default)]
+    pub device_id: Option<String>,
+}
+
+/// The caller of the HTTP request being handled
+#[derive(Debug, Clone)]
+pub struct RequestClient {
+    /// Set once the client's credentials have been verified
+    pub client_id: Option<String>,
+    pub name: String,
+    pub ip: String,
+    pub user_agent: Option<String>,
+}
+
+tokio::task_local! {
+    static CURRENT: RequestClient;
+}
+
+/// Client of the HTTP request this task is answering, if any
+pub fn current() -> Option<RequestClient> {
+    CURRENT.try_with(|client| client.clone()).ok()
+}
+
+/// The current client, or outside of `scope` an unverified one named by
+/// the request's headers
+pub fn current_or_unverified(request_headers: &hyper::HeaderMap) -> RequestClient {
+    current().unwrap_or_else(|| RequestClient {
+        client_id: None,
+        name: approvals::client_name(request_headers),
+        ip: String::new(),
+        user_agent: header(request_headers, "user-agent"),
+    })
+}
+
+fn secret_hash(secret: &str) -> String {
+    format!("{:x}", Sha256::digest(secret.as_bytes()))
+}
+
+/// Add a pending registration, returning it with its secret
+pub fn register(
+    vault: &mut ApiKeyVault,
+    name: &str,
+    device_id: Option<String>,
+    registered_from: Option<String>,
+    now: DateTime<Utc>,
+) -> Result<(RegisteredClient, String), KeyKeeperError> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err(KeyKeeperError::Validation(format!(
+            "Client name must be 1 to {} characters",
+            MAX_NAME_LEN
+        )));
+    }
+    if !name.chars().all(|c| c.is_ascii_graphic() || c == ' ') {
+        return Err(KeyKeeperError::Validation(
+            "Client name must be printable ASCII".to_string(),
+        ));
+    }
+    let pending = vault
+        .registered_clients
+        .iter()
+        .filter(|c| c.status == ClientStatus::Pending && c.expires_at > now)
+        .count();
+    if pending >= MAX_PENDING {
+        return Err(KeyKeeperError::Conflict(
+            "Too many client registrations are waiting for approval".to_string(),
+        ));
+    }
+
+    let secret: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(40)
+        .map(char::from)
+        .collect();
+    let secret = format!("{}{}", SECRET_PREFIX, secret);
+    let client = RegisteredClient {
+        client_id: format!("client_{}", uuid::Uuid::new_v4()),
+        name: name.to_string(),
+        secret_hash: secret_hash(&secret),
+        status: ClientStatus::Pending,
+        device_id: device_id.filter(|d| !d.trim().is_empty()),
+        registered_from,
+        created_at: now,
+        expires_at: now + Duration::days(REGISTRATION_DAYS),
+        approved_at: None,
+        last_seen_at: None,
+    };
+    vault.registered_clients.push(client.clone());
+    Ok((client, secret))
+}
+
+/// The registration matching a client ID and secret, whatever its status
+pub fn find<'a>(
+    vault: &'a mut ApiKeyVault,
+    client_id: &str,
+    secret: &str,
+) -> Result<&'a mut RegisteredClient, KeyKeeperError> {
+    let hash = secret_hash(secret);
+    vault
+        .registered_clients
+        .iter_mut()
+        .find(|c| c.client_id == client_id && c.secret_hash == hash)
+        .ok_or_else(|| KeyKeeperError::Denied("Unknown client ID or wrong secret".to_string()))
+}
+
+/// Check a client's credentials and record that it was seen. Returns its
+/// registered name.
+pub fn verify(
+    vault: &mut ApiKeyVault,
+    client_id: &str,
+    secret: &str,
+    now: DateTime<Utc>,
+) -> Result<String, KeyKeeperError> {
+    let client = find(vault, client_id, secret)?;
+    match client.status {
+        ClientStatus::Pending => {
+            return Err(KeyKeeperError::Denied(
+                "Client registration is waiting for approval in KeyKeeper".to_string(),
+            ))
+        }
+        ClientStatus::Revoked => {
+            return Err(KeyKeeperError::Denied(
+                "Client registration was revoked".to_string(),
+            ))
+        }
+        ClientStatus::Approved if client.expires_at <= now => {
+            return Err(KeyKeeperError::Denied(
+                "Client registration expired; register again".to_string(),
+            ))
+        }
+        ClientStatus::Approved => {}
+    }
+    client.last_seen_at = Some(now);
+    let name = client.name.clone();
+    let device_id = client.device_id.clone();
+    if let Some(device_id) = device_id {
+        devices::touch(vault, &device_id, now);
+    }
+    Ok(name)
+}
+
+fn header(request_headers: &hyper::HeaderMap, name: &str) -> Option<String> {
+    request_headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Identify the caller of an HTTP request. Wrong credentials are refused,
+/// as are unregistered clients when `require_registered` is set. While the
+/// vault is locked nothing can be verified: with `require_registered` the
+/// request is refused, otherwise the routes answer for themselves.
+pub async fn identify<B>(
+    vault: &Mutex<ApiKeyVault>,
+    is_unlocked: &Mutex<bool>,
+    require_registered: bool,
+    client_addr: std::net::SocketAddr,
+    mut req: Request<B>,
+) -> Result<(Request<B>, RequestClient), KeyKeeperError> {
+    let request_headers = req.headers();
+    let mut client = RequestClient {
+        client_id: None,
+        name: approvals::client_name(request_headers),
+        ip: client_addr.ip().to_string(),
+        user_agent: header(request_headers, "user-agent"),
+    };
+    let credentials = header(request_headers, CLIENT_ID_HEADER)
+        .zip(header(request_headers, CLIENT_SECRET_HEADER));
+
+    if OPEN_PATHS.contains(&req.uri().path()) {
+        return Ok((req, client));
+    }
+    if credentials.is_none() && require_registered {
+        return Err(KeyKeeperError::Denied(
+            "Register this client with POST /api/clients/register first".to_string(),
+        ));
+    }
+    if !*is_unlocked.lock().await {
+        if require_registered {
+            return Err(KeyKeeperError::VaultLocked);
+        }
+        return Ok((req, client));
+    }
+
+    let mut vault_guard = vault.lock().await;
+    let now = Utc::now();
+    match credentials {
+        Some((client_id, secret)) => {
+            client.name = verify(&mut vault_guard, &client_id, &secret, now)?;
+            client.client_id = Some(client_id);
+        }
+        None => {
+            let claimed = vault_guard
+                .registered_clients
+                .iter()
+                .any(|c| c.is_active(now) && c.name == client.name);
+            if claimed {
+                client.name = format!("{} (unverified)", client.name);
+            }
+        }
+    }
+    drop(vault_guard);
+
+    // The activity feed and confirmations read the name from this header
+    if let Ok(value) = HeaderValue::from_str(&client.name) {
+        req.headers_mut().insert(approvals::CLIENT_HEADER, value);
+    }
+    Ok((req, client))
+}
+
+/// Run `handler` with `client` as the current client
+pub async fn scope<F: Future>(client: RequestClient, handler: F) -> F::Output {
+    CURRENT.scope(client, handler).await
+}
+
+#[tauri::command]
+pub async fn list_clients(
+    state: State<'_, AppState>,
+) -> Result<Vec<RegisteredClient>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    let mut clients: Vec<RegisteredClient> = vault_guard
+        .registered_clients
+        .iter()
+        .map(RegisteredClient::redacted)
+        .collect();
+    clients.sort_by_key(|c| std::cmp::Reverse(c.created_at));
+    Ok(clients)
+}
+
+/// Approve a pending registration. Other approved clients with the same
+/// name are revoked, so a reinstalled extension replaces its old identity.
+#[tauri::command]
+pub async fn approve_client(
+    client_id: String,
+    state: State<'_, AppState>,
+) -> Result<RegisteredClient, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let now = Utc::now();
+    let mut vault_guard = state.vault.lock().await;
+    let name = match vault_guard
+        .registered_clients
+        .iter()
+        .find(|c| c.client_id == client_id)
+    {
+        Some(c) if c.status == ClientStatus::Pending && c.expires_at > now => c.name.clone(),
+        Some(_) => {
+            return Err(KeyKeeperError::Conflict(
+                "Only pending registrations can be approved".to_string(),
+            ))
+        }
+        None => return Err(KeyKeeperError::not_found("Client")),
+    };
+    let mut approved = None;
+    for client in vault_guard.registered_clients.iter_mut() {
+        if client.client_id == client_id {
+            client.status = ClientStatus::Approved;
+            client.approved_at = Some(now);
+            approved = Some(client.redacted());
+        } else if client.status == ClientStatus::Approved && client.name == name {
+            client.status = ClientStatus::Revoked;
+        }
+    }
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "approve_client",
+        "client",
+        Some(&client_id),
+        true,
+        Some(&name),
+    )
+    .await;
+    approved.ok_or_else(|| KeyKeeperError::not_found("Client"))
+}
+
+/// Revoke every approved or pending registration, returning how many were
+pub fn revoke_all(vault: &mut ApiKeyVault) -> usize {
+    let mut revoked = 0;
+    for client in vault
+        .registered_clients
+        .iter_mut()
+        .filter(|c| c.status != ClientStatus::Revoked)
+    {
+        client.status = ClientStatus::Revoked;
+        revoked += 1;
+    }
+    revoked
+}
+
+/// Revoke a registration, or reject it while it's pending
+#[tauri::command]
+pub async fn revoke_client(
+    client_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let client = vault_guard
+        .registered_clients
+        .iter_mut()
+        .find(|c| c.client_id == client_id)
+        .ok_or_else(|| KeyKeeperError::not_found("Client"))?;
+    client.status = ClientStatus::Revoked;
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "revoke_client",
+        "client",
+        Some(&client_id),
+        true,
+        None,
+    )
+    .await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_approved_clients_with_the_right_secret_are_verified() {
+        let mut vault = ApiKeyVault::default();
+        let now = Utc::now();
+        let (client, secret) = register(&mut vault, "VS Code", None, None, now).unwrap();
+        assert!(register(&mut vault, "bad\nname", None, None, now).is_err());
+
+        let id = &client.client_id;
+        assert!(verify(&mut vault, id, &secret, now).is_err());
+        vault.registered_clients[0].status = ClientStatus::Approved;
+        assert!(verify(&mut vault, id, "kkc_wrong", now).is_err());
+        assert_eq!(verify(&mut vault, id, &secret, now).unwrap(), "VS Code");
+        assert_eq!(vault.registered_clients[0].last_seen_at, Some(now));
+
+        let later = now + Duration::days(REGISTRATION_DAYS + 1);
+        assert!(verify(&mut vault, id, &secret, later).is_err());
+    }
+
+    #[tokio::test]
+    async fn locked_vault_refuses_clients_it_cannot_verify() {
+        let vault = Mutex::new(ApiKeyVault::default());
+        let locked = Mutex::new(false);
+        let addr = "127.0.0.1:50000".parse().unwrap();
+        let request = |path: &str| {
+            Request::builder()
+                .uri(path)
+                .header(CLIENT_ID_HEADER, "c1")
+                .header(CLIENT_SECRET_HEADER, "kkc_secret")
+                .body(())
+                .unwrap()
+        };
+
+        let refused = identify(&vault, &locked, true, addr, request("/api/keys")).await;
+        assert!(matches!(refused, Err(KeyKeeperError::VaultLocked)));
+        let anonymous = Request::builder().uri("/api/keys").body(()).unwrap();
+        let refused = identify(&vault, &locked, true, addr, anonymous).await;
+        assert!(matches!(refused, Err(KeyKeeperError::Denied(_))));
+
+        let (_, client) = identify(&vault, &locked, true, addr, request("/health"))
+            .await
+            .unwrap();
+        assert_eq!(client.client_id, None);
+        let (_, client) = identify(&vault, &locked, false, addr, request("/api/keys"))
+            .await
+            .unwrap();
+        assert_eq!(client.client_id, None);
+    }
+}
@@ -0,0 +1,449 @@
+//! `keykeeper.toml` configuration.
+//!
+//! Settings needed before the vault is unlocked, mainly for running headless
+//! on a dev VM with only the HTTP API: server address and auto-start, vault
+//! path, log level and auto-lock. The file lives in the app data dir, or at
+//! `KEYKEEPER_CONFIG`. Environment variables override single values:
+//!
+//! | Variable                       | Setting                             |
+//! |--------------------------------|-------------------------------------|
+//! | `KEYKEEPER_BIND`               | `server.bind`                       |
+//! | `KEYKEEPER_PORT`               | `server.port`                       |
+//! | `KEYKEEPER_AUTO_START_SERVER`  | `server.auto_start`                 |
+//! | `KEYKEEPER_REQUIRE_CLIENTS`    | `server.require_registered_clients` |
+//! | `KEYKEEPER_PAUSE_WHEN_LOCKED`  | `server.pause_when_locked`          |
+//! | `KEYKEEPER_LOG_REQUESTS`       | `server.log_requests`               |
+//! | `KEYKEEPER_VAULT_PATH`         | `vault.path`                        |
+//! | `KEYKEEPER_AUTO_LOCK_MINUTES`  | `vault.auto_lock_minutes`           |
+//! | `KEYKEEPER_IDLE_LOCK_MINUTES`  | `vault.inactivity_lock_minutes`     |
+//! | `KEYKEEPER_LOG_LEVEL`          | `logging.level`                     |
+//! | `KEYKEEPER_LEGACY_TCP`         | `legacy_tcp.enabled`                |
+//! | `KEYKEEPER_LEGACY_TCP_PORT`    | `legacy_tcp.port`                   |
+//!
+//! Audit sinks (`[[audit.sinks]]`), the CORS allowlist (`[server.cors]`) and
+//! the password policy (`[password_policy]`) are only read from the file, see
+//! `audit_sinks`, `cors` and `password_strength`.
+
+use crate::errors::KeyKeeperError;
+use crate::{guards, log_audit_event, AppState};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tauri::State;
+use tracing::{info, warn};
+
+pub const CONFIG_FILE: &str = "keykeeper.toml";
+const MAX_AUTO_LOCK_MINUTES: u64 = 24 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub bind: String,
+    pub port: u16,
+    /// Start the HTTP server at launch
+    pub auto_start: bool,
+    /// Origins browsers may call the server from, see `cors`
+    pub cors: crate::cors::CorsConfig,
+    /// Refuse HTTP clients that haven't registered, see `clients`
+    pub require_registered_clients: bool,
+    /// Stop listening while the vault is locked, see `lock_state`
+    pub pause_when_locked: bool,
+    /// Record every request, see `request_log`
+    pub log_requests: bool,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind: "127.0.0.1".to_string(),
+            port: 27182,
+            auto_start: false,
+            cors: crate::cors::CorsConfig::default(),
+            require_registered_clients: true,
+            pause_when_locked: false,
+            log_requests: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct VaultConfig {
+    /// Pins the vault file; takes precedence over a location chosen in the app
+    pub path: Option<PathBuf>,
+    /// Overrides the account's session timeout
+    pub auto_lock_minutes: Option<u64>,
+    /// Lock after this long without activity in the app
+    pub inactivity_lock_minutes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// Replaces the default level from the preferences
+    pub level: Option<String>,
+}
+
+/// Listener for clients of the old TCP protocol, see `legacy_tcp`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LegacyTcpConfig {
+    pub enabled: bool,
+    /// Bound on `server.bind`
+    pub port: u16,
+}
+
+impl Default for LegacyTcpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 27183,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AuditConfig {
+    /// Where audit events are copied, besides the vault
+    pub sinks: Vec<crate::audit_sinks::AuditSinkConfig>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AppConfig {
+    pub server: ServerConfig,
+    pub vault: VaultConfig,
+    pub logging: LoggingConfig,
+    pub legacy_tcp: LegacyTcpConfig,
+    pub audit: AuditConfig,
+    /// Rules for new master and account passwords, see `password_strength`
+    pub password_policy: crate::password_strength::PasswordPolicy,
+}
+
+impl AppConfig {
+    pub fn server_addr(&self) -> Result<SocketAddr, String> {
+        let ip: IpAddr = self
+            .server
+            .bind
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid bind address '{}'", self.server.bind))?;
+        Ok(SocketAddr::new(ip, self.server.port))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        self.server_addr()?;
+        if self.server.port == 0 {
+            return Err("Server port can't be 0".to_string());
+        }
+        self.server.cors.validate()?;
+        if let Some(path) = &self.vault.path {
+            if !path.is_absolute() {
+                return Err("vault.path must be an absolute path".to_string());
+            }
+        }
+        for (name, minutes) in [
+            ("auto_lock_minutes", self.vault.auto_lock_minutes),
+            ("inactivity_lock_minutes", self.vault.inactivity_lock_minutes),
+        ] {
+            if minutes.is_some_and(|m| !(1..=MAX_AUTO_LOCK_MINUTES).contains(&m)) {
+                return Err(format!(
+                    "vault.{} must be between 1 and {}",
+                    name, MAX_AUTO_LOCK_MINUTES
+                ));
+            }
+        }
+        if let Some(level) = &self.logging.level {
+            level
+                .parse::<tracing::level_filters::LevelFilter>()
+                .map_err(|_| format!("Unknown log level '{}'", level))?;
+        }
+        if self.legacy_tcp.enabled
+            && (self.legacy_tcp.port == 0 || self.legacy_tcp.port == self.server.port)
+        {
+            return Err("legacy_tcp.port must be set and differ from server.port".to_string());
+        }
+        for (index, sink) in self.audit.sinks.iter().enumerate() {
+            sink.validate()
+                .map_err(|e| format!("audit.sinks[{}]: {}", index, e))?;
+        }
+        self.password_policy.validate()?;
+        Ok(())
+    }
+
+    /// Apply `KEYKEEPER_*` variables from `var`, returning the names used
+    fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Vec<String> {
+        let mut used = Vec::new();
+        let mut take = |name: &str| {
+            let value = var(name).filter(|v| !v.trim().is_empty());
+            if value.is_some() {
+                used.push(name.to_string());
+            }
+            value.map(|v| v.trim().to_string())
+        };
+
+        if let Some(bind) = take("KEYKEEPER_BIND") {
+            self.server.bind = bind;
+        }
+        if let Some(port) = take("KEYKEEPER_PORT") {
+            match port.parse() {
+                Ok(port) => self.server.port = port,
+                Err(_) => warn!("Ignoring invalid KEYKEEPER_PORT '{}'", port),
+            }
+        }
+        if let Some(auto_start) = take("KEYKEEPER_AUTO_START_SERVER") {
+            self.server.auto_start = matches!(
+                auto_start.to_lowercase().as_str(),
+                "1" | "true" | "yes" | "on"
+            );
+        }
+        if let Some(require) = take("KEYKEEPER_REQUIRE_CLIENTS") {
+            self.server.require_registered_clients =
+                matches!(require.to_lowercase().as_str(), "1" | "true" | "yes" | "on");
+        }
+        if let Some(pause) = take("KEYKEEPER_PAUSE_WHEN_LOCKED") {
+            self.server.pause_when_locked =
+                matches!(pause.to_lowercase().as_str(), "1" | "true" | "yes" | "on");
+        }
+        if let Some(log) = take("KEYKEEPER_LOG_REQUESTS") {
+            self.server.log_requests =
+                matches!(log.to_lowercase().as_str(), "1" | "true" | "yes" | "on");
+        }
+        if let Some(path) = take("KEYKEEPER_VAULT_PATH") {
+            self.vault.path = Some(PathBuf::from(path));
+        }
+        if let Some(minutes) = take("KEYKEEPER_AUTO_LOCK_MINUTES") {
+            match minutes.parse() {
+                Ok(minutes) => self.vault.auto_lock_minutes = Some(minutes),
+                Err(_) => warn!("Ignoring invalid KEYKEEPER_AUTO_LOCK_MINUTES '{}'", minutes),
+            }
+        }
+        if let Some(minutes) = take("KEYKEEPER_IDLE_LOCK_MINUTES") {
+            match minutes.parse() {
+                Ok(minutes) => self.vault.inactivity_lock_minutes = Some(minutes),
+                Err(_) => warn!("Ignoring invalid KEYKEEPER_IDLE_LOCK_MINUTES '{}'", minutes),
+            }
+        }
+        if let Some(level) = take("KEYKEEPER_LOG_LEVEL") {
+            self.logging.level = Some(level);
+        }
+        if let Some(enabled) = take("KEYKEEPER_LEGACY_TCP") {
+            self.legacy_tcp.enabled =
+                matches!(enabled.to_lowercase().as_str(), "1" | "true" | "yes" | "on");
+        }
+        if let Some(port) = take("KEYKEEPER_LEGACY_TCP_PORT") {
+            match port.parse() {
+                Ok(port) => self.legacy_tcp.port = port,
+                Err(_) => warn!("Ignoring invalid KEYKEEPER_LEGACY_TCP_PORT '{}'", port),
+            }
+        }
+        used
+    }
+}
+
+/// Config as shown in settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigView {
+    pub path: String,
+    pub exists: bool,
+    /// Contents of the file
+    pub config: AppConfig,
+    /// What's in effect, after environment overrides
+    pub effective: AppConfig,
+    pub env_overrides: Vec<String>,
+    /// Why the file was ignored at startup, if it was
+    pub load_error: Option<String>,
+    /// Settings that only apply after a restart (of the app or the server)
+    pub restart_required: Vec<String>,
+}
+
+pub struct ConfigState {
+    path: PathBuf,
+    file: RwLock<AppConfig>,
+    effective: RwLock<AppConfig>,
+    /// Effective config the app started with
+    startup: AppConfig,
+    env_overrides: Vec<String>,
+    load_error: Option<String>,
+}
+
+fn config_path(app_data_dir: &Path) -> PathBuf {
+    std::env::var_os("KEYKEEPER_CONFIG")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| app_data_dir.join(CONFIG_FILE))
+}
+
+fn parse(contents: &str) -> Result<AppConfig, String> {
+    let config: AppConfig = toml::from_str(contents).map_err(|e| e.to_string())?;
+    config.validate()?;
+    Ok(config)
+}
+
+impl ConfigState {
+    /// Read the config file and environment. An invalid file is reported and
+    /// replaced by defaults; an invalid override is dropped.
+    pub fn load(app_data_dir: &Path) -> Self {
+        let path = config_path(app_data_dir);
+        let (file, load_error) = match std::fs::read_to_string(&path) {
+            Ok(contents) => match parse(&contents) {
+                Ok(config) => {
+                    info!("Loaded config from {}", path.display());
+                    (config, None)
+                }
+                Err(e) => {
+                    warn!("Ignoring invalid config {}: {}", path.display(), e);
+                    (AppConfig::default(), Some(e))
+                }
+            },
+            Err(_) => (AppConfig::default(), None),
+        };
+
+        let mut effective = file.clone();
+        let env_overrides = effective.apply_env(|name| std::env::var(name).ok());
+        if let Err(e) = effective.validate() {
+            warn!("Ignoring environment overrides: {}", e);
+            effective = file.clone();
+        }
+        if let Ok(addr) = effective.server_addr() {
+            if !addr.ip().is_loopback() {
+                warn!(
+                    "HTTP server configured to listen on {}, reachable from other machines",
+                    addr
+                );
+            }
+        }
+
+        Self {
+            path,
+            file: RwLock::new(file),
+            startup: effective.clone(),
+            effective: RwLock::new(effective),
+            env_overrides,
+            load_error,
+        }
+    }
+
+    pub fn effective(&self) -> AppConfig {
+        self.effective.read().unwrap().clone()
+    }
+
+    fn view(&self) -> ConfigView {
+        let effective = self.effective();
+        let mut restart_required = Vec::new();
+        if effective.vault.path != self.startup.vault.path {
+            restart_required.push("vault.path".to_string());
+        }
+        if effective.server.bind != self.startup.server.bind
+            || effective.server.port != self.startup.server.port
+        {
+            restart_required.push("server.bind/port".to_string());
+        }
+        if effective.legacy_tcp != self.startup.legacy_tcp {
+            restart_required.push("legacy_tcp".to_string());
+        }
+        ConfigView {
+            path: self.path.to_string_lossy().to_string(),
+            exists: self.path.exists(),
+            config: self.file.read().unwrap().clone(),
+            effective,
+            env_overrides: self.env_overrides.clone(),
+            load_error: self.load_error.clone(),
+            restart_required,
+        }
+    }
+
+    fn save(&self, config: AppConfig) -> Result<AppConfig, KeyKeeperError> {
+        config.validate().map_err(KeyKeeperError::Validation)?;
+        let contents = toml::to_string_pretty(&config)
+            .map_err(|e| KeyKeeperError::Internal(format!("Failed to serialize config: {}", e)))?;
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        crate::vault_location::write_atomic(&self.path, contents.as_bytes())?;
+
+        let mut effective = config.clone();
+        effective.apply_env(|name| std::env::var(name).ok());
+        if effective.validate().is_err() {
+            effective = config.clone();
+        }
+        *self.file.write().unwrap() = config;
+        *self.effective.write().unwrap() = effective.clone();
+        Ok(effective)
+    }
+}
+
+#[tauri::command]
+pub async fn get_config(state: State<'_, AppState>) -> Result<ConfigView, KeyKeeperError> {
+    Ok(state.config.view())
+}
+
+/// Save the config file. The log level, audit sinks and locking apply right
+/// away (auto-lock from the next unlock), the server address when the server
+/// is next started, and the vault path at the next launch.
+#[tauri::command]
+pub async fn set_config(
+    config: AppConfig,
+    state: State<'_, AppState>,
+) -> Result<ConfigView, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let effective = state.config.save(config)?;
+    crate::logging::set_level_override(effective.logging.level.clone())?;
+    crate::audit_sinks::configure(&effective.audit.sinks);
+    log_audit_event(&state, "update_config", "config", None, true, None).await;
+
+    Ok(state.config.view())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_partial_file_and_applies_env() {
+        let mut config = parse(
+            r#"
+            [server]
+            port = 3999
+
+            [vault]
+            auto_lock_minutes = 30
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.server.bind, "127.0.0.1");
+        assert_eq!(config.server.port, 3999);
+        assert!(!config.server.auto_start);
+        assert!(config.server.require_registered_clients);
+
+        let used = config.apply_env(|name| match name {
+            "KEYKEEPER_BIND" => Some("0.0.0.0".to_string()),
+            "KEYKEEPER_AUTO_START_SERVER" => Some("true".to_string()),
+            _ => None,
+        });
+        assert_eq!(used, vec!["KEYKEEPER_BIND", "KEYKEEPER_AUTO_START_SERVER"]);
+        assert_eq!(config.server_addr().unwrap().to_string(), "0.0.0.0:3999");
+        assert!(config.server.auto_start);
+
+        assert!(parse("[vault]\nauto_lock_minutes = 0").is_err());
+        assert!(parse("[vault]\ninactivity_lock_minutes = 0").is_err());
+        assert!(
+            parse("[server]\npause_when_locked = true")
+                .unwrap()
+                .server
+                .pause_when_locked
+        );
+        assert!(
+            !parse("[server]\nrequire_registered_clients = false")
+                .unwrap()
+                .server
+                .require_registered_clients
+        );
+        assert!(parse("[logging]\nlevel = \"loud\"").is_err());
+        assert!(parse("[server.cors]\nkey_origins = [\"localhost\"]").is_err());
+        assert!(parse("[password_policy]\nmin_entropy_bits = 60").is_ok());
+        assert!(parse("[password_policy]\nbreach_list = \"breaches.txt\"").is_err());
+    }
+}
@@ -0,0 +1,462 @@
+//! Confirmation of destructive actions requested by integrations.
+//!
+//! When the HTTP API is asked to delete a key or a document, overwrite
+//! values in an env file or push secrets to GitHub, a small window lists
+//! exactly what will happen. The action goes ahead once it is approved with
+//! Ctrl/Cmd+Enter; Escape, closing the window or a minute without an answer
+//! refuses it. How often to ask is a policy per action type, stored in the
+//! vault: every time, once per unlock session and client, or never.
+//!
+//! Every request and decision is audited.
+
+use crate::errors::KeyKeeperError;
+use crate::vault_storage::VaultStorage;
+use crate::{
+    create_audit_log, guards, log_audit_event, retention, save_vault, ApiKeyVault, AppState,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tauri::{AppHandle, State, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+use tokio::sync::{oneshot, Mutex as AsyncMutex};
+use tracing::{info, warn};
+
+/// How long a request waits for the user
+const CONFIRMATION_TIMEOUT_SECONDS: i64 = 60;
+/// Confirmation windows are labelled with this prefix and the request id
+const WINDOW_PREFIX: &str = "confirm-";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskyAction {
+    DeleteKey,
+    /// Replace values already set in an env file
+    OverwriteEnvFile,
+    DeleteDocument,
+    /// Create or update GitHub Actions secrets
+    PushSecrets,
+    /// Show a key's value in the app without the master password
+    DecryptKey,
+    /// Write a production key into a non-production file, see sync_policy.rs
+    SyncProductionKey,
+}
+
+impl RiskyAction {
+    pub const ALL: [RiskyAction; 6] = [
+        RiskyAction::DeleteKey,
+        RiskyAction::OverwriteEnvFile,
+        RiskyAction::DeleteDocument,
+        RiskyAction::PushSecrets,
+        RiskyAction::DecryptKey,
+        RiskyAction::SyncProductionKey,
+    ];
+
+    /// Policy until the user picks one. Decrypting happens in the app, by
+    /// the user, so it only asks when turned on.
+    pub fn default_policy(self) -> ConfirmationPolicy {
+        match self {
+            RiskyAction::DecryptKey => ConfirmationPolicy::Never,
+            _ => ConfirmationPolicy::AlwaysAsk,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmationPolicy {
+    #[default]
+    AlwaysAsk,
+    /// Ask the first time a client does it after the vault is unlocked
+    OncePerSession,
+    /// Don't ask; the action goes ahead and is only audited
+    Never,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfirmationPolicyEntry {
+    pub action: RiskyAction,
+    pub policy: ConfirmationPolicy,
+}
+
+/// What the confirmation window shows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfirmationRequest {
+    pub id: String,
+    pub client: String,
+    pub action: RiskyAction,
+    /// One line, e.g. "Delete the API key STRIPE_SECRET_KEY"
+    pub summary: String,
+    /// Each change that will be made
+    pub details: Vec<String>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub requested_at: DateTime<Utc>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub expires_at: DateTime<Utc>,
+}
+
+struct PendingConfirmation {
+    request: ConfirmationRequest,
+    reply: oneshot::Sender<bool>,
+}
+
+/// Pending confirmations and per-session grants, shared by the HTTP server
+/// and the app
+pub struct ConfirmationBroker {
+    app_handle: Option<AppHandle>,
+    pending: Mutex<HashMap<String, PendingConfirmation>>,
+    session_grants: Mutex<HashSet<(RiskyAction, String)>>,
+}
+
+impl ConfirmationBroker {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self {
+            app_handle: Some(app_handle),
+            pending: Mutex::new(HashMap::new()),
+            session_grants: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Broker that refuses everything it would have to ask about
+    pub fn headless() -> Self {
+        Self {
+            app_handle: None,
+            pending: Mutex::new(HashMap::new()),
+            session_grants: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Forget "once per session" approvals; called when the vault locks
+    pub fn clear_session_grants(&self) {
+        self.session_grants.lock().unwrap().clear();
+    }
+
+    fn has_session_grant(&self, action: RiskyAction, client: &str) -> bool {
+        self.session_grants
+            .lock()
+            .unwrap()
+            .contains(&(action, client.to_string()))
+    }
+
+    /// Open the confirmation window and wait for the answer
+    async fn ask(&self, request: ConfirmationRequest) -> Result<bool, KeyKeeperError> {
+        let Some(app) = &self.app_handle else {
+            return Err(KeyKeeperError::Denied(
+                "No desktop session to confirm the action".to_string(),
+            ));
+        };
+
+        let id = request.id.clone();
+        let (reply, answer) = oneshot::channel();
+        self.pending.lock().unwrap().insert(
+            id.clone(),
+            PendingConfirmation {
+                request: request.clone(),
+                reply,
+            },
+        );
+        let window = match open_window(app, &id) {
+            Ok(window) => window,
+            Err(e) => {
+                self.pending.lock().unwrap().remove(&id);
+                return Err(KeyKeeperError::Internal(format!(
+                    "Failed to open the confirmation window: {}",
+                    e
+                )));
+            }
+        };
+
+        let timeout = std::time::Duration::from_secs(CONFIRMATION_TIMEOUT_SECONDS as u64);
+        let result = tokio::time::timeout(timeout, answer).await;
+        self.pending.lock().unwrap().remove(&id);
+        let _ = window.close();
+        match result {
+            Ok(Ok(approved)) => Ok(approved),
+            // Dropped without an answer
+            Ok(Err(_)) => Ok(false),
+            Err(_) => Err(KeyKeeperError::Denied(
+                "Confirmation request timed out".to_string(),
+            )),
+        }
+    }
+
+    fn request(&self, request_id: &str) -> Option<ConfirmationRequest> {
+        self.pending
+            .lock()
+            .unwrap()
+            .get(request_id)
+            .map(|p| p.request.clone())
+    }
+
+    fn respond(&self, request_id: &str, approved: bool) -> Result<(), KeyKeeperError> {
+        let pending = self
+            .pending
+            .lock()
+            .unwrap()
+            .remove(request_id)
+            .ok_or_else(|| KeyKeeperError::not_found("Confirmation request"))?;
+        pending
+            .reply
+            .send(approved)
+            .map_err(|_| KeyKeeperError::Conflict("The client is no longer waiting".to_string()))
+    }
+
+    /// Refuse the request of a window being closed
+    pub fn window_closed(&self, label: &str) {
+        if let Some(id) = label.strip_prefix(WINDOW_PREFIX) {
+            let _ = self.respond(id, false);
+        }
+    }
+}
+
+fn open_window(app: &AppHandle, request_id: &str) -> tauri::Result<WebviewWindow> {
+    let window = WebviewWindowBuilder::new(
+        app,
+        format!("{}{}", WINDOW_PREFIX, request_id),
+        WebviewUrl::App("confirm-action".into()),
+    )
+    .title("Confirm action - KeyKeeper")
+    .inner_size(480.0, 420.0)
+    .resizable(false)
+    .always_on_top(true)
+    .center()
+    .focused(true)
+    .build()?;
+    let _ = window.request_user_attention(Some(tauri::UserAttentionType::Critical));
+    Ok(window)
+}
+
+/// Request id of a confirmation window
+fn request_id(window: &WebviewWindow) -> Result<String, KeyKeeperError> {
+    window
+        .label()
+        .strip_prefix(WINDOW_PREFIX)
+        .map(str::to_string)
+        .ok_or_else(|| KeyKeeperError::Denied("Not a confirmation window".to_string()))
+}
+
+pub fn policy_for(vault: &ApiKeyVault, action: RiskyAction) -> ConfirmationPolicy {
+    vault
+        .confirmation_policies
+        .get(&action)
+        .copied()
+        .unwrap_or_else(|| action.default_policy())
+}
+
+fn push_audit(
+    vault: &mut ApiKeyVault,
+    action: &str,
+    request: &ConfirmationRequest,
+    success: bool,
+    how: &str,
+) {
+    vault.audit_logs.push(create_audit_log(
+        action,
+        "confirmation",
+        Some(&request.id),
+        success,
+        Some(&format!(
+            "{} ({}): {}",
+            request.summary, request.client, how
+        )),
+    ));
+    retention::cap_audit_logs(vault);
+}
+
+/// Let `client` perform `action`, asking the user unless the action's policy
+/// says otherwise. Fails with `Denied` when refused or when nobody answers
+/// in time.
+pub async fn confirm(
+    broker: &ConfirmationBroker,
+    vault: &AsyncMutex<ApiKeyVault>,
+    storage: &dyn VaultStorage,
+    client: &str,
+    action: RiskyAction,
+    summary: String,
+    details: Vec<String>,
+) -> Result<(), KeyKeeperError> {
+    let now = Utc::now();
+    let request = ConfirmationRequest {
+        id: uuid::Uuid::new_v4().to_string(),
+        client: client.to_string(),
+        action,
+        summary,
+        details,
+        requested_at: now,
+        expires_at: now + Duration::seconds(CONFIRMATION_TIMEOUT_SECONDS),
+    };
+
+    let policy = policy_for(&*vault.lock().await, action);
+    let skip = match policy {
+        ConfirmationPolicy::Never => Some("allowed by policy"),
+        ConfirmationPolicy::OncePerSession if broker.has_session_grant(action, client) => {
+            Some("confirmed earlier this session")
+        }
+        _ => None,
+    };
+    if let Some(how) = skip {
+        let mut vault_guard = vault.lock().await;
+        push_audit(
+            &mut vault_guard,
+            "risky_action_allowed",
+            &request,
+            true,
+            how,
+        );
+        if let Err(e) = crate::save_vault_to(storage, &vault_guard) {
+            warn!("Failed to save confirmation audit: {}", e);
+        }
+        return Ok(());
+    }
+
+    info!("Confirmation requested by {} for {:?}", client, action);
+    let result = broker.ask(request.clone()).await;
+
+    let (audit_action, how) = match &result {
+        Ok(true) => ("risky_action_confirmed", "confirmed"),
+        Ok(false) => ("risky_action_refused", "refused"),
+        Err(_) => ("risky_action_expired", "not answered"),
+    };
+    let confirmed = matches!(result, Ok(true));
+    if confirmed && policy == ConfirmationPolicy::OncePerSession {
+        broker
+            .session_grants
+            .lock()
+            .unwrap()
+            .insert((action, client.to_string()));
+    }
+    let mut vault_guard = vault.lock().await;
+    push_audit(&mut vault_guard, audit_action, &request, confirmed, how);
+    if let Err(e) = crate::save_vault_to(storage, &vault_guard) {
+        warn!("Failed to save confirmation decision: {}", e);
+    }
+
+    match result? {
+        true => Ok(()),
+        false => Err(KeyKeeperError::Denied(
+            "Action refused in KeyKeeper".to_string(),
+        )),
+    }
+}
+
+/// The request shown by the calling confirmation window
+#[tauri::command]
+pub async fn get_confirmation_request(
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+) -> Result<ConfirmationRequest, KeyKeeperError> {
+    let id = request_id(&window)?;
+    state
+        .confirmations
+        .request(&id)
+        .ok_or_else(|| KeyKeeperError::not_found("Confirmation request"))
+}
+
+/// Answer the request of the calling confirmation window. The decision is
+/// audited by the waiting request.
+#[tauri::command]
+pub async fn respond_to_confirmation(
+    approved: bool,
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let id = request_id(&window)?;
+    state.confirmations.respond(&id, approved)
+}
+
+#[tauri::command]
+pub async fn get_confirmation_policies(
+    state: State<'_, AppState>,
+) -> Result<Vec<ConfirmationPolicyEntry>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let vault_guard = state.vault.lock().await;
+    Ok(RiskyAction::ALL
+        .iter()
+        .map(|&action| ConfirmationPolicyEntry {
+            action,
+            policy: policy_for(&vault_guard, action),
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn set_confirmation_policy(
+    action: RiskyAction,
+    policy: ConfirmationPolicy,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    if policy == action.default_policy() {
+        vault_guard.confirmation_policies.remove(&action);
+    } else {
+        vault_guard.confirmation_policies.insert(action, policy);
+    }
+    drop(vault_guard);
+    state.confirmations.clear_session_grants();
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "set_confirmation_policy",
+        "confirmation",
+        None,
+        true,
+        Some(&format!("{:?}: {:?}", action, policy)),
+    )
+    .await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn policies_decide_when_to_ask() {
+        let broker = ConfirmationBroker::headless();
+        let storage = crate::vault_storage::MemoryStorage::default();
+        let mut vault = ApiKeyVault::default();
+        vault
+            .confirmation_policies
+            .insert(RiskyAction::DeleteDocument, ConfirmationPolicy::Never);
+        let vault = AsyncMutex::new(vault);
+
+        let run = |action| {
+            confirm(
+                &broker,
+                &vault,
+                &storage,
+                "vscode",
+                action,
+                "Delete".to_string(),
+                vec![],
+            )
+        };
+        // Nobody can answer without a window, so asking refuses
+        assert!(run(RiskyAction::DeleteKey).await.is_err());
+        assert!(run(RiskyAction::DeleteDocument).await.is_ok());
+        // Asks only once turned on
+        assert!(run(RiskyAction::DecryptKey).await.is_ok());
+
+        broker
+            .session_grants
+            .lock()
+            .unwrap()
+            .insert((RiskyAction::DeleteKey, "vscode".to_string()));
+        vault
+            .lock()
+            .await
+            .confirmation_policies
+            .insert(RiskyAction::DeleteKey, ConfirmationPolicy::OncePerSession);
+        assert!(run(RiskyAction::DeleteKey).await.is_ok());
+        broker.clear_session_grants();
+        assert!(run(RiskyAction::DeleteKey).await.is_err());
+
+        let vault_guard = vault.lock().await;
+        assert_eq!(vault_guard.audit_logs.len(), 4);
+        assert_eq!(vault_guard.audit_logs[0].action, "risky_action_expired");
+    }
+}
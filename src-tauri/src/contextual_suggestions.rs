@@ -333,33 +333,38 @@ impl ContextualSuggestionsEngine {
         project_path: &str,
         project_analysis: &AIProjectAnalysis,
     ) -> Result<String> {
-        let mut prompt = String::new();
-        
-        prompt.push_str("You are an expert software consultant providing contextual suggestions for project improvement.\n\n");
+        let mut analysis = String::new();
         
         // Project analysis summary
-        prompt.push_str("## PROJECT ANALYSIS SUMMARY\n");
+        analysis.push_str("## PROJECT ANALYSIS SUMMARY\n");
         for framework in &project_analysis.frameworks {
-            prompt.push_str(&format!("- {} (confidence: {:.2}, reasoning: {})\n", 
+            analysis.push_str(&format!("- {} (confidence: {:.2}, reasoning: {})\n", 
                                    framework.name, framework.hybrid_confidence, framework.ai_reasoning));
         }
         
         // Architecture insights
-        prompt.push_str("\n## ARCHITECTURE INSIGHTS\n");
+        analysis.push_str("\n## ARCHITECTURE INSIGHTS\n");
         for insight in &project_analysis.architecture_insights {
-            prompt.push_str(&format!("- {}: {} (confidence: {:.2})\n", 
+            analysis.push_str(&format!("- {}: {} (confidence: {:.2})\n", 
                                    insight.insight_type, insight.description, insight.confidence));
         }
         
         // Code quality assessment
-        prompt.push_str("\n## CODE QUALITY ASSESSMENT\n");
+        analysis.push_str("\n## CODE QUALITY ASSESSMENT\n");
         let quality = &project_analysis.code_quality;
-        prompt.push_str(&format!("Overall Score: {:.2}\n", quality.overall_score));
-        prompt.push_str(&format!("Maintainability: {:.2}\n", quality.maintainability));
-        prompt.push_str(&format!("Documentation: {:.2}\n", quality.documentation));
-        prompt.push_str(&format!("Testing: {:.2}\n", quality.testing));
-        prompt.push_str(&format!("Security: {:.2}\n", quality.security));
-        prompt.push_str(&format!("Performance: {:.2}\n", quality.performance));
+        analysis.push_str(&format!("Overall Score: {:.2}\n", quality.overall_score));
+        analysis.push_str(&format!("Maintainability: {:.2}\n", quality.maintainability));
+        analysis.push_str(&format!("Documentation: {:.2}\n", quality.documentation));
+        analysis.push_str(&format!("Testing: {:.2}\n", quality.testing));
+        analysis.push_str(&format!("Security: {:.2}\n", quality.security));
+        analysis.push_str(&format!("Performance: {:.2}\n", quality.performance));
+        
+        // The intro is editable, the answer format below isn't
+        let mut prompt = crate::prompt_templates::render(
+            crate::prompt_templates::PROJECT_SUGGESTIONS,
+            &[("analysis", analysis.trim_end())],
+        );
+        prompt.push('\n');
         
         // Request for suggestions
         prompt.push_str("\n## SUGGESTIONS REQUEST\n");
@@ -0,0 +1,306 @@
+//! CORS for the local HTTP server.
+//!
+//! Routes don't set `Access-Control-*` headers themselves; every response
+//! goes through `RequestCors::serve`, which answers preflights and adds the
+//! headers when the request's `Origin` is on the allowlist in
+//! `server.cors`; requests from any other origin are refused. Documentation
+//! routes (docs, chat, providers, snippets and the health probes) use
+//! `allowed_origins`. Everything else can reach key material and uses
+//! `key_origins`, which is empty out of the box, so no web page can call it
+//! unless it's listed explicitly. So do requests that change stored docs or
+//! providers: a page allowed to read docs can't rewrite them. Requests
+//! without an `Origin` header (the VS Code extension host, `keykeeper exec`,
+//! curl) aren't affected.
+
+use crate::errors::KeyKeeperError;
+use http_body_util::Full;
+use hyper::header::{HeaderMap, HeaderValue, ACCESS_CONTROL_REQUEST_METHOD, ORIGIN, VARY};
+use hyper::{Method, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::future::Future;
+
+const ALLOW_METHODS: &str = "GET, POST, PUT, PATCH, DELETE, OPTIONS";
+const ALLOW_HEADERS: &str = "Content-Type, Authorization, X-API-Key, If-None-Match, \
+    X-KeyKeeper-Client, X-KeyKeeper-Client-Id, X-KeyKeeper-Client-Secret, X-Request-Id";
+const EXPOSE_HEADERS: &str = "ETag, X-Vault-Generation, X-Request-Id";
+/// Seconds browsers may cache a preflight answer
+const MAX_AGE: &str = "600";
+
+/// Routes that only serve documentation or server status
+const DOCS_PREFIXES: &[&str] = &[
+    "/api/docs",
+    "/api/chat/",
+    "/api/providers",
+    "/api/snippets",
+    "/api/generate/",
+    "/health",
+    "/ready",
+    "/live",
+];
+/// Documentation routes whose writes are held to `key_origins`
+const STORED_DOCS_PREFIXES: &[&str] = &["/api/docs", "/api/providers"];
+
+/// `[server.cors]` in `keykeeper.toml`
+///
+/// Entries are full origins (`https://dash.example.com`), a scheme alone
+/// (`vscode-webview://`, any origin with that scheme) or `*`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// Origins that may call documentation routes
+    pub allowed_origins: Vec<String>,
+    /// Origins that may also call routes touching keys
+    pub key_origins: Vec<String>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: vec!["vscode-webview://".to_string()],
+            key_origins: Vec::new(),
+        }
+    }
+}
+
+impl CorsConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        for (field, origins) in [
+            ("allowed_origins", &self.allowed_origins),
+            ("key_origins", &self.key_origins),
+        ] {
+            for origin in origins {
+                if origin != "*" && !origin.contains("://") {
+                    return Err(format!(
+                        "server.cors.{}: '{}' is not an origin",
+                        field, origin
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn origins(&self, policy: RoutePolicy) -> &[String] {
+        match policy {
+            RoutePolicy::Docs => &self.allowed_origins,
+            RoutePolicy::KeyMaterial => &self.key_origins,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoutePolicy {
+    Docs,
+    KeyMaterial,
+}
+
+/// Anything not known to be documentation, or changing stored
+/// documentation, is treated as key material
+pub fn policy_for(method: &Method, path: &str) -> RoutePolicy {
+    let matches = |prefixes: &[&str]| prefixes.iter().any(|prefix| path.starts_with(prefix));
+    let read = matches!(*method, Method::GET | Method::HEAD);
+    if !matches(DOCS_PREFIXES) || (!read && matches(STORED_DOCS_PREFIXES)) {
+        RoutePolicy::KeyMaterial
+    } else {
+        RoutePolicy::Docs
+    }
+}
+
+fn origin_matches(allowed: &str, origin: &str) -> bool {
+    let allowed = allowed.trim().trim_end_matches('/');
+    let origin = origin.trim_end_matches('/');
+    allowed == "*"
+        || allowed.eq_ignore_ascii_case(origin)
+        || (allowed.ends_with(':')
+            && origin
+                .get(..allowed.len() + 2)
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(&format!("{}//", allowed))))
+}
+
+/// Whether `origin` may call a route with `policy`
+pub fn is_allowed(config: &CorsConfig, policy: RoutePolicy, origin: &str) -> bool {
+    config
+        .origins(policy)
+        .iter()
+        .any(|allowed| origin_matches(allowed, origin))
+}
+
+/// What the CORS layer decided for one request, taken before the request
+/// is handed to the routes
+pub struct RequestCors {
+    preflight: bool,
+    origin: Option<HeaderValue>,
+    allowed: bool,
+}
+
+impl RequestCors {
+    pub fn new(config: &CorsConfig, method: &Method, path: &str, headers: &HeaderMap) -> Self {
+        let origin = headers.get(ORIGIN).cloned();
+        // A preflight is judged by the request it announces
+        let requested = headers
+            .get(ACCESS_CONTROL_REQUEST_METHOD)
+            .and_then(|value| Method::from_bytes(value.as_bytes()).ok())
+            .filter(|_| method == Method::OPTIONS)
+            .unwrap_or_else(|| method.clone());
+        let policy = policy_for(&requested, path);
+        let allowed = origin.as_ref().is_some_and(|origin| {
+            origin
+                .to_str()
+                .is_ok_and(|origin| is_allowed(config, policy, origin))
+        });
+        Self {
+            preflight: method == Method::OPTIONS,
+            origin,
+            allowed,
+        }
+    }
+
+    fn decorate(&self, response: &mut Response<Full<bytes::Bytes>>) {
+        let headers = response.headers_mut();
+        headers.append(VARY, HeaderValue::from_static("Origin"));
+        if let (true, Some(origin)) = (self.allowed, &self.origin) {
+            headers.insert("Access-Control-Allow-Origin", origin.clone());
+            headers.insert(
+                "Access-Control-Expose-Headers",
+                HeaderValue::from_static(EXPOSE_HEADERS),
+            );
+        }
+    }
+
+    fn preflight_response(&self) -> Response<Full<bytes::Bytes>> {
+        if self.origin.is_some() && !self.allowed {
+            return self.denied();
+        }
+        let mut response = Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .header("Access-Control-Allow-Methods", ALLOW_METHODS)
+            .header("Access-Control-Allow-Headers", ALLOW_HEADERS)
+            .header("Access-Control-Max-Age", MAX_AGE)
+            .body(Full::new(bytes::Bytes::new()))
+            .unwrap();
+        self.decorate(&mut response);
+        response
+    }
+
+    fn denied(&self) -> Response<Full<bytes::Bytes>> {
+        let mut response = crate::api_error_response(&KeyKeeperError::Denied(
+            "Origin not allowed by server.cors".to_string(),
+        ));
+        self.decorate(&mut response);
+        response
+    }
+
+    /// Answer preflights and refused origins here, otherwise run `handler`
+    /// and add the CORS headers to its response
+    pub async fn serve<F>(self, handler: F) -> Result<Response<Full<bytes::Bytes>>, Infallible>
+    where
+        F: Future<Output = Result<Response<Full<bytes::Bytes>>, Infallible>>,
+    {
+        if self.preflight {
+            return Ok(self.preflight_response());
+        }
+        if self.origin.is_some() && !self.allowed {
+            return Ok(self.denied());
+        }
+        let mut response = handler.await?;
+        self.decorate(&mut response);
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_routes_deny_browser_origins_unless_listed() {
+        let mut config = CorsConfig::default();
+        let allowed = |config: &CorsConfig, path: &str, origin: &str| {
+            is_allowed(config, policy_for(&Method::GET, path), origin)
+        };
+        let webview = "vscode-webview://1a2b3c";
+        assert!(allowed(&config, "/api/docs/search", webview));
+        assert!(!allowed(&config, "/api/docs", "https://evil.example"));
+        assert!(!allowed(&config, "/api/keys/k1/decrypt", webview));
+        assert!(!allowed(&config, "/api/batch", webview));
+
+        let write = |method: Method, path: &str| policy_for(&method, path);
+        assert_eq!(
+            write(Method::POST, "/api/docs/create"),
+            RoutePolicy::KeyMaterial
+        );
+        assert_eq!(
+            write(Method::DELETE, "/api/docs/d1"),
+            RoutePolicy::KeyMaterial
+        );
+        assert_eq!(
+            write(Method::POST, "/api/providers/detect"),
+            RoutePolicy::KeyMaterial
+        );
+        assert_eq!(write(Method::POST, "/api/chat/message"), RoutePolicy::Docs);
+        let mut preflight = HeaderMap::new();
+        preflight.insert(ORIGIN, HeaderValue::from_static("vscode-webview://1a2b3c"));
+        preflight.insert(
+            ACCESS_CONTROL_REQUEST_METHOD,
+            HeaderValue::from_static("PUT"),
+        );
+        assert!(!RequestCors::new(&config, &Method::OPTIONS, "/api/docs/d1", &preflight).allowed);
+
+        config.key_origins = vec!["https://dash.example.com/".to_string()];
+        assert!(allowed(&config, "/api/keys", "https://DASH.example.com"));
+        assert!(!allowed(
+            &config,
+            "/api/keys",
+            "https://dash.example.com.evil"
+        ));
+        assert!(config.validate().is_ok());
+        config.allowed_origins.push("localhost:3000".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn preflights_allow_registered_client_credentials() {
+        let mut preflight = HeaderMap::new();
+        preflight.insert(ORIGIN, HeaderValue::from_static("vscode-webview://1a2b3c"));
+        preflight.insert(
+            ACCESS_CONTROL_REQUEST_METHOD,
+            HeaderValue::from_static("POST"),
+        );
+        preflight.insert(
+            "access-control-request-headers",
+            HeaderValue::from_static(
+                "content-type,x-keykeeper-client,x-keykeeper-client-id,x-keykeeper-client-secret",
+            ),
+        );
+        let cors = RequestCors::new(
+            &CorsConfig::default(),
+            &Method::OPTIONS,
+            "/api/chat/message",
+            &preflight,
+        );
+        assert!(cors.allowed);
+
+        let response = cors.preflight_response();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let allowed: Vec<String> = response.headers()["access-control-allow-headers"]
+            .to_str()
+            .unwrap()
+            .split(',')
+            .map(|h| h.trim().to_lowercase())
+            .collect();
+        for requested in preflight["access-control-request-headers"]
+            .to_str()
+            .unwrap()
+            .split(',')
+        {
+            assert!(allowed.iter().any(|h| h == requested), "{}", requested);
+        }
+        for header in [
+            crate::clients::CLIENT_ID_HEADER,
+            crate::clients::CLIENT_SECRET_HEADER,
+        ] {
+            assert!(allowed.iter().any(|h| h == header));
+        }
+    }
+}
@@ -0,0 +1,372 @@
+//! Cost annotations for paid keys.
+//!
+//! A key's `cost` holds what it's expected to cost per month and the billing
+//! account it's charged to. Actual costs are recorded per calendar month,
+//! either by hand (`record_cost_entry`) or by a billing webhook posting to
+//! `/api/costs`; a new entry for a key and month replaces the previous one.
+//! `get_cost_overview` adds both up by service and by project so estimates
+//! can be compared with what was billed.
+
+use crate::errors::KeyKeeperError;
+use crate::{guards, timestamps, ApiKey, ApiKeyVault, AppState};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::State;
+
+/// Group name for keys that don't belong to a project
+pub const NO_PROJECT: &str = "(no project)";
+
+fn default_currency() -> String {
+    "USD".to_string()
+}
+
+fn validate_currency(currency: &str) -> Result<(), KeyKeeperError> {
+    if currency.len() == 3 && currency.bytes().all(|b| b.is_ascii_uppercase()) {
+        Ok(())
+    } else {
+        Err(KeyKeeperError::Validation(format!(
+            "'{}' is not an ISO 4217 currency code",
+            currency
+        )))
+    }
+}
+
+fn validate_amount(amount: f64, what: &str) -> Result<(), KeyKeeperError> {
+    if amount.is_finite() && amount >= 0.0 {
+        Ok(())
+    } else {
+        Err(KeyKeeperError::Validation(format!(
+            "The {} must be zero or a positive amount",
+            what
+        )))
+    }
+}
+
+/// Cost metadata of a key
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeyCost {
+    #[serde(default)]
+    pub estimated_monthly: Option<f64>,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    /// Account or organization the provider bills, e.g. "acme-prod"
+    #[serde(default)]
+    pub billing_account: Option<String>,
+}
+
+impl KeyCost {
+    pub fn validate(&self) -> Result<(), KeyKeeperError> {
+        validate_currency(&self.currency)?;
+        if let Some(estimate) = self.estimated_monthly {
+            validate_amount(estimate, "estimated monthly cost")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CostSource {
+    Manual,
+    Webhook,
+}
+
+/// What a key actually cost in one month
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostEntry {
+    pub id: String,
+    pub key_id: String,
+    /// Calendar month, `YYYY-MM`
+    pub period: String,
+    pub amount: f64,
+    pub currency: String,
+    pub source: CostSource,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Body of `record_cost_entry` and `POST /api/costs`
+#[derive(Debug, Clone, Deserialize)]
+pub struct CostEntryInput {
+    pub key_id: String,
+    /// Current month when omitted
+    #[serde(default)]
+    pub period: Option<String>,
+    pub amount: f64,
+    /// The key's currency when omitted
+    #[serde(default)]
+    pub currency: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+pub fn current_period(now: DateTime<Utc>) -> String {
+    now.format("%Y-%m").to_string()
+}
+
+fn validate_period(period: &str) -> Result<(), KeyKeeperError> {
+    NaiveDate::parse_from_str(&format!("{}-01", period), "%Y-%m-%d")
+        .map(|_| ())
+        .map_err(|_| KeyKeeperError::Validation(format!("'{}' is not a YYYY-MM month", period)))
+}
+
+/// Record the actual cost of a key for a month, replacing any earlier entry
+/// for the same key and month
+pub fn record(
+    vault: &mut ApiKeyVault,
+    input: CostEntryInput,
+    source: CostSource,
+    now: DateTime<Utc>,
+) -> Result<CostEntry, KeyKeeperError> {
+    let key = vault
+        .keys
+        .get(&input.key_id)
+        .ok_or_else(|| KeyKeeperError::not_found("API key"))?;
+    let period = input.period.unwrap_or_else(|| current_period(now));
+    validate_period(&period)?;
+    validate_amount(input.amount, "amount")?;
+    let key_currency = key.cost.as_ref().map(|cost| cost.currency.clone());
+    let currency = match (input.currency, key_currency) {
+        (Some(currency), Some(expected)) if currency != expected => {
+            return Err(KeyKeeperError::Validation(format!(
+                "The key is billed in {}, not {}",
+                expected, currency
+            )))
+        }
+        (Some(currency), _) => currency,
+        (None, Some(expected)) => expected,
+        (None, None) => default_currency(),
+    };
+    validate_currency(&currency)?;
+
+    let entry = CostEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        key_id: input.key_id,
+        period,
+        amount: input.amount,
+        currency,
+        source,
+        note: input.note.filter(|note| !note.trim().is_empty()),
+        recorded_at: now,
+    };
+    vault
+        .cost_entries
+        .retain(|e| e.key_id != entry.key_id || e.period != entry.period);
+    vault.cost_entries.push(entry.clone());
+    Ok(entry)
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CostGroup {
+    pub name: String,
+    pub currency: String,
+    pub estimated_monthly: f64,
+    pub actual: f64,
+    pub keys: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyCostLine {
+    pub key_id: String,
+    pub key_name: String,
+    pub service: String,
+    pub project: String,
+    pub billing_account: Option<String>,
+    pub currency: String,
+    pub estimated_monthly: Option<f64>,
+    pub actual: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CostOverview {
+    pub period: String,
+    pub by_service: Vec<CostGroup>,
+    pub by_project: Vec<CostGroup>,
+    pub keys: Vec<KeyCostLine>,
+}
+
+fn project_name(vault: &ApiKeyVault, key: &ApiKey) -> String {
+    match &key.project_path {
+        Some(path) => vault
+            .projects
+            .values()
+            .find(|project| &project.path == path)
+            .map(|project| project.name.clone())
+            .unwrap_or_else(|| path.clone()),
+        None => NO_PROJECT.to_string(),
+    }
+}
+
+fn add_to(groups: &mut BTreeMap<(String, String), CostGroup>, name: &str, line: &KeyCostLine) {
+    let group = groups
+        .entry((name.to_string(), line.currency.clone()))
+        .or_insert_with(|| CostGroup {
+            name: name.to_string(),
+            currency: line.currency.clone(),
+            estimated_monthly: 0.0,
+            actual: 0.0,
+            keys: 0,
+        });
+    group.estimated_monthly += line.estimated_monthly.unwrap_or(0.0);
+    group.actual += line.actual.unwrap_or(0.0);
+    group.keys += 1;
+}
+
+/// Estimated and actual costs of `period`, for keys with cost metadata or
+/// an entry that month. Groups are split by currency rather than converted.
+pub fn overview(vault: &ApiKeyVault, period: &str) -> Result<CostOverview, KeyKeeperError> {
+    validate_period(period)?;
+    let mut keys: Vec<KeyCostLine> = vault
+        .keys
+        .values()
+        .filter_map(|key| {
+            let entry = vault
+                .cost_entries
+                .iter()
+                .find(|e| e.key_id == key.id && e.period == period);
+            if key.cost.is_none() && entry.is_none() {
+                return None;
+            }
+            Some(KeyCostLine {
+                key_id: key.id.clone(),
+                key_name: key.name.clone(),
+                service: key.service.clone(),
+                project: project_name(vault, key),
+                billing_account: key.cost.as_ref().and_then(|c| c.billing_account.clone()),
+                currency: key
+                    .cost
+                    .as_ref()
+                    .map(|c| c.currency.clone())
+                    .or_else(|| entry.map(|e| e.currency.clone()))
+                    .unwrap_or_else(default_currency),
+                estimated_monthly: key.cost.as_ref().and_then(|c| c.estimated_monthly),
+                actual: entry.map(|e| e.amount),
+            })
+        })
+        .collect();
+    keys.sort_by(|a, b| (&a.service, &a.key_name).cmp(&(&b.service, &b.key_name)));
+
+    let mut by_service = BTreeMap::new();
+    let mut by_project = BTreeMap::new();
+    for line in &keys {
+        add_to(&mut by_service, &line.service, line);
+        add_to(&mut by_project, &line.project, line);
+    }
+    Ok(CostOverview {
+        period: period.to_string(),
+        by_service: by_service.into_values().collect(),
+        by_project: by_project.into_values().collect(),
+        keys,
+    })
+}
+
+#[tauri::command]
+pub async fn get_cost_overview(
+    period: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<CostOverview, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    overview(
+        &vault_guard,
+        &period.unwrap_or_else(|| current_period(Utc::now())),
+    )
+}
+
+#[tauri::command]
+pub async fn record_cost_entry(
+    entry: CostEntryInput,
+    state: State<'_, AppState>,
+) -> Result<CostEntry, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let entry = record(&mut vault_guard, entry, CostSource::Manual, Utc::now())?;
+    drop(vault_guard);
+    crate::save_vault(&state).await?;
+    crate::log_audit_event(
+        &state,
+        "record_cost_entry",
+        "api_key",
+        Some(&entry.key_id),
+        true,
+        None,
+    )
+    .await;
+    Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault_tests::test_key;
+    use chrono::TimeZone;
+
+    #[test]
+    fn overview_compares_estimates_with_actual_costs() {
+        let mut vault = ApiKeyVault::default();
+        let estimate = |amount| KeyCost {
+            estimated_monthly: Some(amount),
+            currency: default_currency(),
+            billing_account: None,
+        };
+        vault.keys.insert(
+            "a".to_string(),
+            test_key("a")
+                .service("OpenAI")
+                .environment("production")
+                .cost(Some(estimate(50.0)))
+                .build(),
+        );
+        vault.keys.insert(
+            "b".to_string(),
+            test_key("b")
+                .service("OpenAI")
+                .environment("production")
+                .cost(Some(estimate(25.0)))
+                .build(),
+        );
+        vault.keys.insert(
+            "c".to_string(),
+            test_key("c")
+                .service("GitHub")
+                .environment("production")
+                .build(),
+        );
+        let now = Utc.with_ymd_and_hms(2024, 5, 20, 12, 0, 0).unwrap();
+        let input = |amount, currency: Option<&str>| CostEntryInput {
+            key_id: "a".to_string(),
+            period: None,
+            amount,
+            currency: currency.map(str::to_string),
+            note: None,
+        };
+
+        record(&mut vault, input(40.0, None), CostSource::Manual, now).unwrap();
+        record(&mut vault, input(61.5, None), CostSource::Webhook, now).unwrap();
+        assert_eq!(vault.cost_entries.len(), 1);
+        assert!(record(&mut vault, input(1.0, Some("EUR")), CostSource::Manual, now).is_err());
+
+        let may = overview(&vault, "2024-05").unwrap();
+        assert_eq!(may.keys.len(), 2);
+        assert_eq!(
+            may.by_service,
+            vec![CostGroup {
+                name: "OpenAI".to_string(),
+                currency: "USD".to_string(),
+                estimated_monthly: 75.0,
+                actual: 61.5,
+                keys: 2,
+            }]
+        );
+        assert_eq!(may.by_project[0].name, NO_PROJECT);
+        assert_eq!(
+            overview(&vault, "2024-04").unwrap().by_service[0].actual,
+            0.0
+        );
+    }
+}
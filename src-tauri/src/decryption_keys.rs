@@ -0,0 +1,233 @@
+//! Keys of individually encrypted values, held while the vault is unlocked.
+//!
+//! Old builds stored some values encrypted on their own: base64 of a salt, a
+//! nonce and the AES-GCM ciphertext, under a key derived from the master
+//! password and that salt. `get_decrypted_api_key` asked for the password on
+//! every call to derive the key again. The keys are now derived once, in the
+//! background right after a password unlock, and kept in `SecretKey`s
+//! (locked into RAM, wiped when dropped) until the vault locks. While the
+//! vault stays unlocked a value is decrypted without the password, subject to
+//! the `decrypt_key` confirmation policy. Unlocking with a hardware key, a
+//! recovery code or biometrics gives no password to derive from, so those
+//! values still need it.
+
+use crate::secure_memory::SecretKey;
+use crate::ApiKeyVault;
+use aes_gcm::aead::{Aead, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::{engine::general_purpose, Engine as _};
+use rand::RngCore;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+use zeroize::Zeroizing;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+/// AES-GCM tag, so shorter data can't be a sealed value
+const TAG_LEN: usize = 16;
+
+type Salt = [u8; SALT_LEN];
+
+/// Salt of `value` and its decoded bytes, if it has the shape of a sealed value
+fn unseal_parts(value: &str) -> Option<(Salt, Vec<u8>)> {
+    let combined = general_purpose::STANDARD.decode(value).ok()?;
+    if combined.len() < SALT_LEN + NONCE_LEN + TAG_LEN {
+        return None;
+    }
+    let salt = combined[..SALT_LEN].try_into().ok()?;
+    Some((salt, combined))
+}
+
+fn open_with(key: &[u8; 32], combined: &[u8]) -> Option<String> {
+    let (nonce, ciphertext) = combined[SALT_LEN..].split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let plaintext = Zeroizing::new(cipher.decrypt(Nonce::from_slice(nonce), ciphertext).ok()?);
+    String::from_utf8(plaintext.to_vec()).ok()
+}
+
+fn seal_with(key: &[u8; 32], salt: &Salt, value: &str) -> Result<String, String> {
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), value.as_bytes())
+        .map_err(|e| format!("Encryption failed: {}", e))?;
+    Ok(general_purpose::STANDARD.encode([&salt[..], &nonce[..], &ciphertext].concat()))
+}
+
+/// Seal the values of `vault` sealed under `old` again under `new`, with
+/// fresh salts; values sharing a salt keep sharing one. Returns how many
+/// were sealed again.
+pub fn reseal(vault: &mut ApiKeyVault, old: &str, new: &str) -> Result<usize, String> {
+    // Old salt to its key, the new salt and the new key; `None` if the
+    // values with that salt aren't sealed under `old`
+    let mut rekeyed: HashMap<Salt, Option<(SecretKey, Salt, SecretKey)>> = HashMap::new();
+    let mut count = 0;
+    for key in vault.keys.values_mut() {
+        for value in std::iter::once(&mut key.key).chain(key.values.values_mut()) {
+            let Some((salt, combined)) = unseal_parts(value) else {
+                continue;
+            };
+            let keys = rekeyed.entry(salt).or_insert_with(|| {
+                let old_key = crate::derive_key_from_password(old, &salt);
+                open_with(&old_key, &combined)?;
+                let mut new_salt = [0u8; SALT_LEN];
+                OsRng.fill_bytes(&mut new_salt);
+                let new_key = crate::derive_key_from_password(new, &new_salt);
+                Some((old_key, new_salt, new_key))
+            });
+            let Some((old_key, new_salt, new_key)) = keys else {
+                continue;
+            };
+            let Some(plaintext) = open_with(old_key, &combined).map(Zeroizing::new) else {
+                continue;
+            };
+            *value = seal_with(new_key, new_salt, &plaintext)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// `value` sealed under `password` the way old builds did
+#[cfg(test)]
+pub(crate) fn seal(value: &str, password: &str) -> String {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = crate::derive_key_from_password(password, &salt);
+    seal_with(&key, &salt, value).unwrap()
+}
+
+/// What the session knows about a value
+#[derive(Debug, PartialEq, Eq)]
+pub enum Unsealed {
+    Value(String),
+    /// Stored as is, within the encrypted vault
+    NotSealed,
+    /// Sealed under a key this session doesn't have
+    NeedsPassword,
+}
+
+#[derive(Default)]
+pub struct DecryptionKeys {
+    keys: Mutex<HashMap<Salt, SecretKey>>,
+    /// Salts of values that turned out not to be sealed with the password
+    not_sealed: Mutex<HashSet<Salt>>,
+    /// Bumped by `purge`, so a derivation still running when the vault locks
+    /// doesn't put its keys back
+    generation: AtomicU64,
+}
+
+impl DecryptionKeys {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Derive the keys of `vault`'s sealed values from `password`, in the
+    /// background
+    pub fn derive_for(self: &Arc<Self>, vault: &ApiKeyVault, password: &str) {
+        let sealed: Vec<(Salt, Vec<u8>)> = vault
+            .keys
+            .values()
+            .flat_map(|key| std::iter::once(&key.key).chain(key.values.values()))
+            .filter_map(|value| unseal_parts(value))
+            .collect();
+        if sealed.is_empty() {
+            return;
+        }
+
+        let password = Zeroizing::new(password.to_string());
+        let generation = self.generation.load(Ordering::SeqCst);
+        let this = Arc::clone(self);
+        tokio::task::spawn_blocking(move || {
+            let mut keys = HashMap::new();
+            let mut not_sealed = HashSet::new();
+            for (salt, combined) in sealed {
+                if keys.contains_key(&salt) || not_sealed.contains(&salt) {
+                    continue;
+                }
+                let key = crate::derive_key_from_password(&password, &salt);
+                if open_with(&key, &combined).is_some() {
+                    keys.insert(salt, key);
+                } else {
+                    not_sealed.insert(salt);
+                }
+            }
+            debug!("Derived {} keys for sealed values", keys.len());
+
+            let mut held = this.keys.lock().unwrap();
+            if this.generation.load(Ordering::SeqCst) == generation {
+                held.extend(keys);
+                this.not_sealed.lock().unwrap().extend(not_sealed);
+            }
+        });
+    }
+
+    pub fn unseal(&self, value: &str) -> Unsealed {
+        let Some((salt, combined)) = unseal_parts(value) else {
+            return Unsealed::NotSealed;
+        };
+        if let Some(key) = self.keys.lock().unwrap().get(&salt) {
+            if let Some(value) = open_with(key, &combined) {
+                return Unsealed::Value(value);
+            }
+        }
+        if self.not_sealed.lock().unwrap().contains(&salt) {
+            Unsealed::NotSealed
+        } else {
+            Unsealed::NeedsPassword
+        }
+    }
+
+    /// Wipe every key; called when the vault locks
+    pub fn purge(&self) {
+        let mut keys = self.keys.lock().unwrap();
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        keys.clear();
+        self.not_sealed.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault_tests::test_key;
+
+    #[tokio::test]
+    async fn unseals_within_the_session_until_purged() {
+        let sealed = seal("sk_live_legacy", "correct horse");
+        let mut vault = ApiKeyVault::default();
+        let mut key = test_key("k1")
+            .name("LEGACY")
+            .secret(&sealed)
+            .environment("production")
+            .build();
+        vault.keys.insert(key.id.clone(), key.clone());
+        key.id = "k2".to_string();
+        key.key =
+            "c2stbGl2ZS1wbGFpbi12YWx1ZS10aGF0LWlzLWxvbmctZW5vdWdoLXRvLWxvb2stc2VhbGVk".to_string();
+        vault.keys.insert(key.id.clone(), key.clone());
+
+        let keys = Arc::new(DecryptionKeys::new());
+        assert_eq!(keys.unseal(&sealed), Unsealed::NeedsPassword);
+        assert_eq!(keys.unseal("sk_live_plain"), Unsealed::NotSealed);
+
+        keys.derive_for(&vault, "correct horse");
+        for _ in 0..200 {
+            if keys.unseal(&sealed) != Unsealed::NeedsPassword {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        assert_eq!(
+            keys.unseal(&sealed),
+            Unsealed::Value("sk_live_legacy".to_string())
+        );
+        assert_eq!(keys.unseal(&key.key), Unsealed::NotSealed);
+
+        keys.purge();
+        assert_eq!(keys.unseal(&sealed), Unsealed::NeedsPassword);
+    }
+}
@@ -0,0 +1,505 @@
+//! Demo vault for onboarding and UI tests.
+//!
+//! `create_demo_vault` writes a throwaway vault with fake keys, projects,
+//! docs and activity to its own folder; the vault the app is using is never
+//! touched. The data is deterministic (fixed ids, timestamps and values) so
+//! screenshots and UI tests stay stable. Projects point at the
+//! `test_projects` fixtures when they are available, which lets the `.env`
+//! sync paths run end to end against them.
+//!
+//! Only available in debug builds or with the `demo-data` feature.
+//! Open the result with `KEYKEEPER_VAULT_PATH=<folder>/vault.json`.
+
+use crate::activity::Actor;
+use crate::errors::KeyKeeperError;
+use crate::{
+    derive_key_from_password, save_vault_to_path, ApiKey, ApiKeyVault, AuditLog, DocsStore,
+    Documentation, Project, ProjectEnvAssociation, RecentActivity,
+};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+pub const DEMO_PASSWORD: &str = "keykeeper-demo";
+const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+fn base_time() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap()
+}
+
+fn at(days: i64, hours: i64) -> DateTime<Utc> {
+    base_time() + Duration::days(days) + Duration::hours(hours)
+}
+
+/// Fake secret that looks like the provider's format
+fn fake_secret(slug: &str, prefix: &str, len: usize) -> String {
+    let mut out = prefix.to_string();
+    let mut block = Sha256::digest(format!("keykeeper-demo:{}", slug).as_bytes()).to_vec();
+    while out.len() < prefix.len() + len {
+        for byte in &block {
+            if out.len() == prefix.len() + len {
+                break;
+            }
+            out.push(ALPHABET[*byte as usize % ALPHABET.len()] as char);
+        }
+        block = Sha256::digest(&block).to_vec();
+    }
+    out
+}
+
+/// Fixture folder, project name and description
+const PROJECTS: &[(&str, &str, &str)] = &[
+    (
+        "nextjs-app",
+        "Storefront",
+        "Next.js storefront with payments",
+    ),
+    ("react-native-app", "Mobile App", "React Native client"),
+    ("rust-api", "Orders API", "Rust backend service"),
+];
+
+struct KeySpec {
+    slug: &'static str,
+    name: &'static str,
+    service: &'static str,
+    environment: &'static str,
+    project: &'static str,
+    prefix: &'static str,
+    len: usize,
+    tags: &'static [&'static str],
+    /// Days after the base time; negative for already expired
+    expires_in_days: Option<i64>,
+    is_active: bool,
+}
+
+const KEYS: &[KeySpec] = &[
+    KeySpec {
+        slug: "stripe-dev",
+        name: "STRIPE_SECRET_KEY",
+        service: "Stripe",
+        environment: "development",
+        project: "nextjs-app",
+        prefix: "sk_test_",
+        len: 48,
+        tags: &["payments"],
+        expires_in_days: None,
+        is_active: true,
+    },
+    KeySpec {
+        slug: "stripe-prod",
+        name: "STRIPE_SECRET_KEY_PROD",
+        service: "Stripe",
+        environment: "production",
+        project: "nextjs-app",
+        prefix: "sk_live_",
+        len: 48,
+        tags: &["payments", "critical"],
+        expires_in_days: Some(365),
+        is_active: true,
+    },
+    KeySpec {
+        slug: "openai-dev",
+        name: "OPENAI_API_KEY",
+        service: "OpenAI",
+        environment: "development",
+        project: "nextjs-app",
+        prefix: "sk-proj-",
+        len: 40,
+        tags: &["ai"],
+        expires_in_days: Some(30),
+        is_active: true,
+    },
+    KeySpec {
+        slug: "sentry-mobile",
+        name: "SENTRY_DSN",
+        service: "Sentry",
+        environment: "production",
+        project: "react-native-app",
+        prefix: "",
+        len: 32,
+        tags: &["monitoring"],
+        expires_in_days: None,
+        is_active: true,
+    },
+    KeySpec {
+        slug: "firebase-mobile",
+        name: "FIREBASE_API_KEY",
+        service: "Firebase",
+        environment: "staging",
+        project: "react-native-app",
+        prefix: "AIza",
+        len: 35,
+        tags: &["mobile"],
+        expires_in_days: Some(-10),
+        is_active: true,
+    },
+    KeySpec {
+        slug: "postgres-api",
+        name: "DATABASE_PASSWORD",
+        service: "PostgreSQL",
+        environment: "development",
+        project: "rust-api",
+        prefix: "",
+        len: 24,
+        tags: &["database"],
+        expires_in_days: None,
+        is_active: true,
+    },
+    KeySpec {
+        slug: "aws-api",
+        name: "AWS_SECRET_ACCESS_KEY",
+        service: "AWS",
+        environment: "production",
+        project: "rust-api",
+        prefix: "",
+        len: 40,
+        tags: &["cloud", "critical"],
+        expires_in_days: Some(90),
+        is_active: true,
+    },
+    KeySpec {
+        slug: "github-legacy",
+        name: "GITHUB_TOKEN_OLD",
+        service: "GitHub",
+        environment: "development",
+        project: "rust-api",
+        prefix: "ghp_",
+        len: 36,
+        tags: &["deprecated"],
+        expires_in_days: None,
+        is_active: false,
+    },
+];
+
+fn project_id(folder: &str) -> String {
+    format!("demo-project-{}", folder)
+}
+
+fn key_id(slug: &str) -> String {
+    format!("demo-key-{}", slug)
+}
+
+fn project_path(projects_root: &Path, folder: &str) -> String {
+    projects_root.join(folder).to_string_lossy().to_string()
+}
+
+/// Demo vault contents, unencrypted
+pub fn build_vault(projects_root: &Path) -> ApiKeyVault {
+    let mut vault = ApiKeyVault::default();
+
+    for (index, (folder, name, description)) in PROJECTS.iter().enumerate() {
+        let path = project_path(projects_root, folder);
+        let created_at = at(index as i64, 0);
+        vault.projects.insert(
+            project_id(folder),
+            Project {
+                id: project_id(folder),
+                name: name.to_string(),
+                description: Some(description.to_string()),
+                path: path.clone(),
+                created_at,
+                updated_at: created_at,
+                settings: crate::project_settings::builtin_defaults(),
+                settings_template_id: None,
+                settings_overrides: Default::default(),
+                group_id: None,
+                scaffolds: Vec::new(),
+                profile: crate::project_analysis::analyze(Path::new(&path)),
+                packages: Vec::new(),
+            },
+        );
+        vault.env_associations.push(ProjectEnvAssociation {
+            id: format!("demo-association-{}", folder),
+            project_path: path.clone(),
+            env_file_path: Path::new(&path).join(".env").to_string_lossy().to_string(),
+            env_file_name: ".env".to_string(),
+            created_at,
+            last_accessed: created_at,
+            is_active: true,
+            vscode_status: None,
+            last_vscode_check: None,
+            package_path: None,
+        });
+    }
+
+    for (index, spec) in KEYS.iter().enumerate() {
+        let id = key_id(spec.slug);
+        let created_at = at(index as i64, 2);
+        vault.keys.insert(
+            id.clone(),
+            ApiKey {
+                id: id.clone(),
+                name: spec.name.to_string(),
+                service: spec.service.to_string(),
+                key: fake_secret(spec.slug, spec.prefix, spec.len),
+                description: Some(format!("Demo {} key", spec.service)),
+                environment: spec.environment.to_string(),
+                quota: None,
+                cost: None,
+                expires_at: spec.expires_in_days.map(|days| at(days, 0)),
+                scopes: Vec::new(),
+                created_at,
+                updated_at: created_at,
+                tags: spec.tags.iter().map(|t| t.to_string()).collect(),
+                is_active: spec.is_active,
+                source_type: Some("manual".to_string()),
+                env_file_path: None,
+                project_path: Some(project_path(projects_root, spec.project)),
+                env_file_name: None,
+                remote_secrets: Vec::new(),
+                package_path: None,
+                rotation_interval_days: None,
+                last_rotated_at: None,
+                kind: Default::default(),
+                credential: None,
+                oauth: None,
+                values: Default::default(),
+                is_pinned: false,
+                archived_at: None,
+                last_used_at: None,
+                backend: None,
+            },
+        );
+
+        vault.recent_activity.push(RecentActivity {
+            id: format!("demo-activity-{}-created", spec.slug),
+            activity_type: "key_created".to_string(),
+            key_id: id.clone(),
+            key_name: spec.name.to_string(),
+            timestamp: created_at,
+            details: None,
+            actor: Some(Actor::ui()),
+            source: None,
+        });
+        for use_index in 0..(index % 3) as i64 + 1 {
+            vault.recent_activity.push(RecentActivity {
+                id: format!("demo-activity-{}-used-{}", spec.slug, use_index),
+                activity_type: "key_used".to_string(),
+                key_id: id.clone(),
+                key_name: spec.name.to_string(),
+                timestamp: at(10 + use_index * 3, index as i64),
+                details: Some("Synced to .env".to_string()),
+                actor: Some(Actor::system("project_sync")),
+                source: None,
+            });
+        }
+        vault.audit_logs.push(AuditLog {
+            id: format!("demo-audit-{}", spec.slug),
+            timestamp: created_at,
+            action: "add_api_key".to_string(),
+            resource_type: "api_key".to_string(),
+            resource_id: Some(id),
+            user_id: Some("local_user".to_string()),
+            ip_address: Some("127.0.0.1".to_string()),
+            user_agent: Some("KeyKeeper Desktop".to_string()),
+            success: true,
+            error_message: None,
+            client_id: None,
+            request_id: None,
+        });
+    }
+    vault
+        .recent_activity
+        .sort_by_key(|a| std::cmp::Reverse(a.timestamp));
+    vault
+}
+
+fn doc(
+    id: &str,
+    title: &str,
+    doc_type: &str,
+    project: Option<&str>,
+    provider: Option<&str>,
+    tags: &[&str],
+    content: &str,
+) -> Documentation {
+    Documentation {
+        id: format!("demo-doc-{}", id),
+        title: title.to_string(),
+        content: content.to_string(),
+        doc_type: doc_type.to_string(),
+        project_id: project.map(project_id),
+        provider_id: provider.map(str::to_string),
+        url: None,
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+        created_at: base_time(),
+        updated_at: base_time(),
+        language: "en".to_string(),
+        is_favorite: false,
+        search_keywords: tags.iter().map(|t| t.to_string()).collect(),
+        sections: Vec::new(),
+        is_offline: false,
+        attachments: Vec::new(),
+    }
+}
+
+pub fn build_docs() -> DocsStore {
+    let mut store = DocsStore::new();
+    store.created_at = base_time();
+    store.updated_at = base_time();
+    for doc in [
+        doc(
+            "stripe-webhooks",
+            "Stripe webhooks",
+            "guide",
+            Some("nextjs-app"),
+            Some("stripe"),
+            &["stripe", "webhooks"],
+            "# Stripe webhooks\n\nVerify the `Stripe-Signature` header with the \
+             endpoint secret before trusting an event.\n",
+        ),
+        doc(
+            "openai-limits",
+            "OpenAI rate limits",
+            "notes",
+            Some("nextjs-app"),
+            Some("openai"),
+            &["openai", "limits"],
+            "# Rate limits\n\nThe development key is capped at 500 requests per \
+             minute; retry 429s with exponential backoff.\n",
+        ),
+        doc(
+            "rotation-runbook",
+            "Key rotation runbook",
+            "reference",
+            Some("rust-api"),
+            None,
+            &["runbook", "rotation"],
+            "# Rotating production keys\n\n1. Create the new key with the provider.\n\
+             2. Update it in KeyKeeper and sync the projects.\n3. Revoke the old key.\n",
+        ),
+    ] {
+        store.add_doc(doc);
+    }
+    store
+}
+
+/// Encrypt the vault with the demo password, as `set_master_password` would,
+/// with a fixed salt
+fn encrypt_with_demo_password(vault: &mut ApiKeyVault) -> Result<(), KeyKeeperError> {
+    let salt = &Sha256::digest(b"keykeeper-demo-salt")[..16];
+    let key = derive_key_from_password(DEMO_PASSWORD, salt);
+    vault.master_password_hash =
+        Some(bcrypt::hash(DEMO_PASSWORD, bcrypt::DEFAULT_COST).map_err(|e| e.to_string())?);
+    vault.encryption_key = Some(general_purpose::STANDARD.encode(*key));
+    vault.salt = Some(general_purpose::STANDARD.encode(salt));
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DemoVaultInfo {
+    pub vault_path: String,
+    pub docs_path: String,
+    pub password: String,
+    pub projects_root: String,
+    pub keys: usize,
+    pub projects: usize,
+    pub docs: usize,
+    pub activity: usize,
+}
+
+/// The repo's fixtures, when running from a checkout
+fn default_projects_root() -> Option<PathBuf> {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("../test_projects");
+    fixtures
+        .is_dir()
+        .then(|| fixtures.canonicalize().unwrap_or(fixtures))
+}
+
+/// Write a demo vault (`vault.json`, encrypted with `DEMO_PASSWORD`) and its
+/// docs (`documentation.json`) to `target_dir`, which must be empty or
+/// missing. `projects_root` defaults to the `test_projects` fixtures.
+#[tauri::command]
+pub async fn create_demo_vault(
+    target_dir: String,
+    projects_root: Option<String>,
+) -> Result<DemoVaultInfo, KeyKeeperError> {
+    if !cfg!(any(debug_assertions, feature = "demo-data")) {
+        return Err(KeyKeeperError::Denied(
+            "Demo vaults are only available in development builds".to_string(),
+        ));
+    }
+
+    let target = PathBuf::from(target_dir.trim());
+    if !target.is_absolute() {
+        return Err(KeyKeeperError::Validation(
+            "Demo vault folder must be an absolute path".to_string(),
+        ));
+    }
+    if target.is_dir() && std::fs::read_dir(&target)?.next().is_some() {
+        return Err(KeyKeeperError::Conflict(format!(
+            "{} is not empty",
+            target.display()
+        )));
+    }
+    let projects_root = projects_root
+        .map(PathBuf::from)
+        .or_else(default_projects_root)
+        .unwrap_or_else(|| target.join("projects"));
+
+    std::fs::create_dir_all(&target)?;
+    let mut vault = build_vault(&projects_root);
+    encrypt_with_demo_password(&mut vault)?;
+    let vault_path = target.join("vault.json");
+    save_vault_to_path(&vault, &vault_path).await?;
+
+    let docs = build_docs();
+    let docs_path = target.join("documentation.json");
+    let docs_json = serde_json::to_string_pretty(&docs)
+        .map_err(|e| KeyKeeperError::Internal(format!("Failed to serialize docs: {}", e)))?;
+    std::fs::write(&docs_path, docs_json)?;
+
+    info!("Created demo vault at {}", vault_path.display());
+    Ok(DemoVaultInfo {
+        vault_path: vault_path.to_string_lossy().to_string(),
+        docs_path: docs_path.to_string_lossy().to_string(),
+        password: DEMO_PASSWORD.to_string(),
+        projects_root: projects_root.to_string_lossy().to_string(),
+        keys: vault.keys.len(),
+        projects: vault.projects.len(),
+        docs: docs.docs.len(),
+        activity: vault.recent_activity.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn demo_vault_is_deterministic_and_syncs_into_fixtures() {
+        let root = default_projects_root().expect("test_projects fixtures");
+        let vault = build_vault(&root);
+        let again = build_vault(&root);
+        assert_eq!(vault.keys.len(), KEYS.len());
+        assert_eq!(
+            vault.keys["demo-key-stripe-dev"].key,
+            again.keys["demo-key-stripe-dev"].key
+        );
+        assert!(vault.keys["demo-key-stripe-dev"]
+            .key
+            .starts_with("sk_test_"));
+        assert_eq!(
+            vault.projects["demo-project-nextjs-app"]
+                .profile
+                .framework
+                .as_deref(),
+            Some("nextjs")
+        );
+
+        let project = &vault.projects["demo-project-nextjs-app"].path;
+        let (var_name, pending, violation) =
+            crate::plan_key_env_sync(&vault, "demo-key-stripe-dev", project, None, None).unwrap();
+        assert_eq!(var_name, "STRIPE_API_KEY");
+        assert_eq!(pending.preview().added, vec!["STRIPE_API_KEY"]);
+        assert_eq!(
+            pending.file().get("STRIPE_API_KEY"),
+            Some("keykeeper://key/demo-key-stripe-dev")
+        );
+        assert!(violation.is_none());
+        assert_eq!(build_docs().docs.len(), 3);
+    }
+}
@@ -0,0 +1,318 @@
+use crate::clients::ClientStatus;
+use crate::errors::KeyKeeperError;
+use crate::{get_device_id, guards, log_audit_event, save_vault, ApiKeyVault, AppState};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::State;
+
+/// Credentials created before sessions recorded their device
+pub const UNKNOWN_DEVICE: &str = "unknown";
+
+/// User-facing details of a device, stored in the vault. Sessions and tokens
+/// reference it by `device_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceRecord {
+    pub device_id: String,
+    pub name: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub first_seen: DateTime<Utc>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub last_activity: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialKind {
+    BiometricSession,
+    PersistentSession,
+    VscodeToken,
+    /// A client registered with `POST /api/clients/register`
+    RegisteredClient,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceCredential {
+    pub kind: CredentialKind,
+    /// Session id, or a redacted prefix for tokens
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub is_active: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceSummary {
+    pub device_id: String,
+    pub name: String,
+    pub is_current: bool,
+    pub first_seen: Option<DateTime<Utc>>,
+    pub last_activity: Option<DateTime<Utc>>,
+    pub active_credentials: usize,
+    pub credentials: Vec<DeviceCredential>,
+}
+
+/// Record activity from a device, registering it on first sight
+pub fn touch(vault: &mut ApiKeyVault, device_id: &str, at: DateTime<Utc>) {
+    match vault.devices.iter_mut().find(|d| d.device_id == device_id) {
+        Some(device) => device.last_activity = device.last_activity.max(at),
+        None => vault.devices.push(DeviceRecord {
+            device_id: device_id.to_string(),
+            name: default_name(device_id),
+            first_seen: at,
+            last_activity: at,
+        }),
+    }
+}
+
+/// Hostname part of `hostname-user-os` ids built by `get_device_id`
+fn default_name(device_id: &str) -> String {
+    if device_id == UNKNOWN_DEVICE {
+        return "Unknown device".to_string();
+    }
+    device_id
+        .rsplitn(3, '-')
+        .nth(2)
+        .filter(|s| !s.is_empty())
+        .unwrap_or(device_id)
+        .to_string()
+}
+
+fn redact_token(token: &str) -> String {
+    format!("{}…", token.chars().take(20).collect::<String>())
+}
+
+/// Every session and token grouped by device
+pub fn build_registry(vault: &ApiKeyVault, current_device: &str) -> Vec<DeviceSummary> {
+    let now = Utc::now();
+    let mut credentials: BTreeMap<String, Vec<DeviceCredential>> = BTreeMap::new();
+
+    for s in &vault.biometric_sessions {
+        credentials
+            .entry(s.device_id.clone())
+            .or_default()
+            .push(DeviceCredential {
+                kind: CredentialKind::BiometricSession,
+                id: s.session_id.clone(),
+                created_at: s.created_at,
+                expires_at: s.expires_at,
+                is_active: s.is_active && s.expires_at > now,
+            });
+    }
+    for s in &vault.persistent_sessions {
+        credentials
+            .entry(
+                s.device_id
+                    .clone()
+                    .unwrap_or_else(|| UNKNOWN_DEVICE.to_string()),
+            )
+            .or_default()
+            .push(DeviceCredential {
+                kind: CredentialKind::PersistentSession,
+                id: s.session_id.clone(),
+                created_at: s.created_at,
+                expires_at: s.expires_at,
+                is_active: s.is_active && s.expires_at > now,
+            });
+    }
+    for t in &vault.vscode_tokens {
+        credentials
+            .entry(
+                t.device_id
+                    .clone()
+                    .unwrap_or_else(|| UNKNOWN_DEVICE.to_string()),
+            )
+            .or_default()
+            .push(DeviceCredential {
+                kind: CredentialKind::VscodeToken,
+                id: redact_token(&t.token),
+                created_at: t.created_at,
+                expires_at: t.expires_at,
+                is_active: t.is_valid && t.expires_at > now,
+            });
+    }
+    for c in &vault.registered_clients {
+        credentials
+            .entry(
+                c.device_id
+                    .clone()
+                    .unwrap_or_else(|| UNKNOWN_DEVICE.to_string()),
+            )
+            .or_default()
+            .push(DeviceCredential {
+                kind: CredentialKind::RegisteredClient,
+                id: format!("{} ({})", c.name, c.client_id),
+                created_at: c.created_at,
+                expires_at: c.expires_at,
+                is_active: c.is_active(now),
+            });
+    }
+    // Registered devices show up even once all their credentials are gone
+    for device in &vault.devices {
+        credentials.entry(device.device_id.clone()).or_default();
+    }
+
+    let mut devices: Vec<DeviceSummary> = credentials
+        .into_iter()
+        .map(|(device_id, mut creds)| {
+            creds.sort_by_key(|c| std::cmp::Reverse(c.created_at));
+            let record = vault.devices.iter().find(|d| d.device_id == device_id);
+            let newest_credential = creds.iter().map(|c| c.created_at).max();
+            DeviceSummary {
+                name: record
+                    .map(|r| r.name.clone())
+                    .unwrap_or_else(|| default_name(&device_id)),
+                is_current: device_id == current_device,
+                first_seen: record
+                    .map(|r| r.first_seen)
+                    .or_else(|| creds.iter().map(|c| c.created_at).min()),
+                last_activity: record.map(|r| r.last_activity).max(newest_credential),
+                active_credentials: creds.iter().filter(|c| c.is_active).count(),
+                credentials: creds,
+                device_id,
+            }
+        })
+        .collect();
+    devices.sort_by(|a, b| {
+        b.is_current
+            .cmp(&a.is_current)
+            .then_with(|| b.last_activity.cmp(&a.last_activity))
+    });
+    devices
+}
+
+/// Deactivate every session and token of a device. Returns how many were
+/// revoked and the ids of revoked sessions.
+fn revoke_credentials(vault: &mut ApiKeyVault, device_id: &str) -> (usize, Vec<String>) {
+    let matches = |id: Option<&str>| id.unwrap_or(UNKNOWN_DEVICE) == device_id;
+    let mut revoked = 0;
+    let mut session_ids = Vec::new();
+
+    for s in vault
+        .biometric_sessions
+        .iter_mut()
+        .filter(|s| s.is_active && matches(Some(&s.device_id)))
+    {
+        s.is_active = false;
+        session_ids.push(s.session_id.clone());
+        revoked += 1;
+    }
+    for s in vault
+        .persistent_sessions
+        .iter_mut()
+        .filter(|s| s.is_active && matches(s.device_id.as_deref()))
+    {
+        s.is_active = false;
+        session_ids.push(s.session_id.clone());
+        revoked += 1;
+    }
+    for t in vault
+        .vscode_tokens
+        .iter_mut()
+        .filter(|t| t.is_valid && matches(t.device_id.as_deref()))
+    {
+        t.is_valid = false;
+        revoked += 1;
+    }
+    for c in vault
+        .registered_clients
+        .iter_mut()
+        .filter(|c| c.status != ClientStatus::Revoked && matches(c.device_id.as_deref()))
+    {
+        c.status = ClientStatus::Revoked;
+        revoked += 1;
+    }
+    (revoked, session_ids)
+}
+
+/// Devices with their sessions, tokens and last activity
+#[tauri::command]
+pub async fn list_devices(
+    state: State<'_, AppState>,
+) -> Result<Vec<DeviceSummary>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    Ok(build_registry(&vault_guard, &get_device_id()))
+}
+
+#[tauri::command]
+pub async fn rename_device(
+    device_id: String,
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(KeyKeeperError::Validation(
+            "Device name cannot be empty".to_string(),
+        ));
+    }
+
+    let mut vault_guard = state.vault.lock().await;
+    let known = build_registry(&vault_guard, &get_device_id())
+        .into_iter()
+        .find(|d| d.device_id == device_id)
+        .ok_or_else(|| KeyKeeperError::not_found("Device"))?;
+    match vault_guard
+        .devices
+        .iter_mut()
+        .find(|d| d.device_id == device_id)
+    {
+        Some(record) => record.name = name,
+        None => {
+            let seen = known.first_seen.unwrap_or_else(Utc::now);
+            vault_guard.devices.push(DeviceRecord {
+                device_id: device_id.clone(),
+                name,
+                first_seen: seen,
+                last_activity: known.last_activity.unwrap_or(seen),
+            });
+        }
+    }
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "rename_device",
+        "device",
+        Some(&device_id),
+        true,
+        None,
+    )
+    .await;
+    Ok(())
+}
+
+/// Revoke all sessions and tokens of a device at once. Revoking the session
+/// backing the current unlock locks the vault.
+#[tauri::command]
+pub async fn revoke_device(
+    device_id: String,
+    state: State<'_, AppState>,
+) -> Result<usize, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let (revoked, session_ids) = revoke_credentials(&mut vault_guard, &device_id);
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "revoke_device",
+        "device",
+        Some(&device_id),
+        true,
+        Some(&format!("{} credentials revoked", revoked)),
+    )
+    .await;
+
+    let current = state.unlock_session.lock().await.clone();
+    if current.is_some_and(|id| session_ids.contains(&id)) {
+        crate::lock_vault(state).await?;
+    }
+    Ok(revoked)
+}
@@ -0,0 +1,352 @@
+//! Crash reports and diagnostic bundles.
+//!
+//! The panic hook writes a report with the panic message and backtrace to
+//! `crashes/` in the app data dir. Messages go through `logging::redact`, and
+//! the backtrace only holds symbols, so no secret ends up in the file. A
+//! report still marked pending at the next launch means the app crashed; the
+//! window is brought back and the report is kept for the frontend to show.
+//!
+//! `export_diagnostics` zips logs, settings, crash reports, the integrity
+//! report and system info for attaching to bug reports. The vault and its
+//! metadata file are never included.
+
+use crate::errors::KeyKeeperError;
+use crate::{get_app_data_dir, get_device_platform, log_audit_event, AppState};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::backtrace::Backtrace;
+use std::fs;
+use std::io::Write;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use tracing::{error, info, warn};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+const CRASH_DIR: &str = "crashes";
+/// Copy of the latest report, removed once the next launch has seen it
+const PENDING_FILE: &str = "pending.json";
+const MAX_CRASH_REPORTS: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub id: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub occurred_at: DateTime<Utc>,
+    pub version: String,
+    pub os: String,
+    pub arch: String,
+    pub thread: String,
+    pub message: String,
+    /// `file:line` of the panic
+    pub location: Option<String>,
+    pub backtrace: String,
+}
+
+/// Crash found at startup, until the frontend takes it
+#[derive(Default)]
+pub struct LastCrash(Mutex<Option<CrashReport>>);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticsBundle {
+    pub path: String,
+    pub size_bytes: u64,
+    /// Entries in the archive
+    pub files: Vec<String>,
+}
+
+fn crash_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(CRASH_DIR)
+}
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    let payload = info.payload();
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Box<dyn Any>".to_string())
+}
+
+fn crash_report(info: &PanicHookInfo<'_>) -> CrashReport {
+    let occurred_at = Utc::now();
+    let backtrace = Backtrace::force_capture().to_string();
+    CrashReport {
+        id: format!("crash-{}", occurred_at.format("%Y%m%d-%H%M%S%.3f")),
+        occurred_at,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        thread: std::thread::current()
+            .name()
+            .unwrap_or("<unnamed>")
+            .to_string(),
+        message: crate::logging::redact(&panic_message(info)).into_owned(),
+        location: info
+            .location()
+            .map(|l| format!("{}:{}", l.file(), l.line())),
+        backtrace: crate::logging::redact(&backtrace).into_owned(),
+    }
+}
+
+fn write_crash(dir: &Path, report: &CrashReport) -> std::io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let json = serde_json::to_vec_pretty(report)?;
+    fs::write(dir.join(format!("{}.json", report.id)), &json)?;
+    fs::write(dir.join(PENDING_FILE), &json)
+}
+
+/// Record panics to the crash folder, then run the default hook
+pub fn install_panic_hook(app_data_dir: &Path) {
+    let dir = crash_dir(app_data_dir);
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let report = crash_report(info);
+        match write_crash(&dir, &report) {
+            Ok(()) => error!(
+                "Panic in thread '{}' at {}: {} (report {})",
+                report.thread,
+                report.location.as_deref().unwrap_or("unknown location"),
+                report.message,
+                report.id
+            ),
+            Err(e) => eprintln!("Failed to write crash report: {}", e),
+        }
+        previous(info);
+    }));
+}
+
+/// Oldest reports beyond the limit, by name since names sort by time
+fn prune_crash_reports(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut reports: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("crash-") && n.ends_with(".json"))
+        })
+        .collect();
+    reports.sort();
+    let excess = reports.len().saturating_sub(MAX_CRASH_REPORTS);
+    for path in reports.drain(..excess) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Pick up the report of a crash in the previous run and bring the window
+/// back, since it may have been hidden in the tray at the time
+pub fn recover_from_crash(app: &AppHandle, app_data_dir: &Path) {
+    let dir = crash_dir(app_data_dir);
+    prune_crash_reports(&dir);
+
+    let pending = dir.join(PENDING_FILE);
+    let Ok(contents) = fs::read(&pending) else {
+        return;
+    };
+    let _ = fs::remove_file(&pending);
+    let Ok(report) = serde_json::from_slice::<CrashReport>(&contents) else {
+        return;
+    };
+
+    warn!(
+        "Recovered from crash {} at {}",
+        report.id,
+        report.occurred_at.to_rfc3339()
+    );
+    crate::instance::focus_main_window(app);
+    if let Some(last) = app.try_state::<LastCrash>() {
+        *last.0.lock().unwrap() = Some(report);
+    }
+}
+
+/// Report of a crash in the previous run, if it hasn't been taken yet
+#[tauri::command]
+pub fn take_crash_report(last: State<'_, LastCrash>) -> Option<CrashReport> {
+    last.0.lock().unwrap().take()
+}
+
+fn system_info(app: &AppHandle) -> serde_json::Value {
+    serde_json::json!({
+        "app": app.package_info().name,
+        "version": app.package_info().version.to_string(),
+        "os": std::env::consts::OS,
+        "os_family": std::env::consts::FAMILY,
+        "arch": std::env::consts::ARCH,
+        "platform": get_device_platform(),
+        "os_version": whoami::distro(),
+        "desktop": whoami::desktop_env().to_string(),
+        "cpus": std::thread::available_parallelism().map(|n| n.get()).ok(),
+        "generated_at": Utc::now().to_rfc3339(),
+    })
+}
+
+struct Bundle<W: Write + std::io::Seek> {
+    zip: ZipWriter<W>,
+    files: Vec<String>,
+}
+
+impl<W: Write + std::io::Seek> Bundle<W> {
+    fn add(&mut self, name: &str, data: &[u8]) -> Result<(), KeyKeeperError> {
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        self.zip
+            .start_file(name, options)
+            .and_then(|_| self.zip.write_all(data).map_err(Into::into))
+            .map_err(|e| KeyKeeperError::Io(format!("Failed to write {}: {}", name, e)))?;
+        self.files.push(name.to_string());
+        Ok(())
+    }
+
+    fn add_json(&mut self, name: &str, value: &impl Serialize) -> Result<(), KeyKeeperError> {
+        self.add(name, &serde_json::to_vec_pretty(value)?)
+    }
+
+    /// Every regular file of `dir` under `prefix/`
+    fn add_dir(&mut self, dir: &Path, prefix: &str) -> Result<(), KeyKeeperError> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Ok(());
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_file())
+            .collect();
+        paths.sort();
+        for path in paths {
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            match fs::read(&path) {
+                Ok(data) => self.add(&format!("{}/{}", prefix, name), &data)?,
+                Err(e) => warn!("Skipping {} in diagnostics: {}", path.display(), e),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Bundle logs, settings, crash reports, the integrity report and system info
+/// into a zip, by default in the downloads folder
+#[tauri::command]
+pub async fn export_diagnostics(
+    export_path: Option<String>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<DiagnosticsBundle, KeyKeeperError> {
+    let _task = state.health.begin_task("export_diagnostics");
+    let app_data_dir = get_app_data_dir(&app)?;
+    let unlocked = *state.is_unlocked.lock().await;
+
+    let path = match export_path {
+        Some(path) => PathBuf::from(path),
+        None => app
+            .path()
+            .download_dir()
+            .unwrap_or_else(|_| app_data_dir.clone())
+            .join(format!(
+                "keykeeper-diagnostics-{}.zip",
+                Utc::now().format("%Y%m%d-%H%M%S")
+            )),
+    };
+
+    // Vault details that are safe to share
+    let vault_path = state.vault_path.path();
+    let mut vault_info = serde_json::json!({
+        "path": vault_path.to_string_lossy(),
+        "exists": vault_path.exists(),
+        "size_bytes": fs::metadata(&vault_path).map(|m| m.len()).ok(),
+        "synced_folder": state.vault_path.is_synced(),
+        "unlocked": unlocked,
+    });
+    let mut preferences = None;
+    if unlocked {
+        let vault_guard = state.vault.lock().await;
+        vault_info["schema_version"] = vault_guard.schema_version.into();
+        vault_info["key_count"] = vault_guard.keys.len().into();
+        vault_info["project_count"] = vault_guard.projects.len().into();
+        preferences = vault_guard
+            .user_account
+            .as_ref()
+            .map(|u| u.preferences.clone());
+    }
+    let integrity = if unlocked {
+        match crate::vault_integrity::verify_vault_integrity(state.clone()).await {
+            Ok(report) => serde_json::to_value(report)?,
+            Err(e) => serde_json::json!({ "error": e.to_string() }),
+        }
+    } else {
+        serde_json::json!({ "error": KeyKeeperError::VaultLocked.to_string() })
+    };
+
+    let file = fs::File::create(&path)
+        .map_err(|e| KeyKeeperError::Io(format!("Failed to create {}: {}", path.display(), e)))?;
+    let mut bundle = Bundle {
+        zip: ZipWriter::new(file),
+        files: Vec::new(),
+    };
+    bundle.add_json("system.json", &system_info(&app))?;
+    bundle.add_json("vault.json", &vault_info)?;
+    bundle.add_json("integrity.json", &integrity)?;
+    if let Some(preferences) = &preferences {
+        bundle.add_json("config/preferences.json", preferences)?;
+    }
+    for name in [
+        crate::logging::SETTINGS_FILE,
+        crate::vault_location::BOOTSTRAP_FILE,
+    ] {
+        if let Ok(data) = fs::read(app_data_dir.join(name)) {
+            bundle.add(&format!("config/{}", name), &data)?;
+        }
+    }
+    bundle.add_dir(&crate::logging::log_dir(&app_data_dir), "logs")?;
+    bundle.add_dir(&crash_dir(&app_data_dir), "crashes")?;
+
+    let files = bundle.files;
+    bundle
+        .zip
+        .finish()
+        .map_err(|e| KeyKeeperError::Io(format!("Failed to finish diagnostics bundle: {}", e)))?;
+    let size_bytes = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    info!(
+        "Exported diagnostics to {} ({} files)",
+        path.display(),
+        files.len()
+    );
+
+    if unlocked {
+        log_audit_event(&state, "export_diagnostics", "system", None, true, None).await;
+    }
+
+    Ok(DiagnosticsBundle {
+        path: path.to_string_lossy().to_string(),
+        size_bytes,
+        files,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prunes_oldest_crash_reports() {
+        let dir = std::env::temp_dir().join(format!("kk-crashes-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        for i in 0..MAX_CRASH_REPORTS + 2 {
+            fs::write(dir.join(format!("crash-202401{:02}-000000.json", i)), "{}").unwrap();
+        }
+        fs::write(dir.join(PENDING_FILE), "{}").unwrap();
+
+        prune_crash_reports(&dir);
+        assert!(!dir.join("crash-20240100-000000.json").exists());
+        assert!(!dir.join("crash-20240101-000000.json").exists());
+        assert!(dir.join("crash-20240102-000000.json").exists());
+        assert!(dir.join(PENDING_FILE).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,407 @@
+//! Files attached to documentation.
+//!
+//! Attachments (Postman collections, PDFs, diagrams) live next to the docs
+//! store in `doc_attachments/`, one blob per file named by the SHA-256 of
+//! what's stored, so a file attached twice is kept once. Sensitive ones are
+//! encrypted with AES-256-GCM under a key kept in the vault and can only be
+//! read back while it's unlocked; their blob is named by the hash of the
+//! ciphertext.
+//!
+//! Blobs dropped from the store, by removing an attachment or deleting its
+//! doc, are deleted on the next save of the docs store unless another doc
+//! still uses them. `cleanup_doc_attachments` sweeps any blob nothing refers
+//! to.
+
+use crate::errors::KeyKeeperError;
+use crate::{
+    guards, log_audit_event, save_docs_store, save_vault, ApiKeyVault, AppState, DocsStore,
+};
+use aes_gcm::aead::{Aead, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tauri::State;
+use tracing::warn;
+use zeroize::Zeroizing;
+
+const DIR: &str = "doc_attachments";
+pub const MAX_ATTACHMENT_BYTES: u64 = 10 * 1024 * 1024;
+/// Across all attachments of one doc
+pub const MAX_DOC_BYTES: u64 = 50 * 1024 * 1024;
+const NONCE_LEN: usize = 12;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DocAttachment {
+    pub id: String,
+    pub file_name: String,
+    pub media_type: String,
+    /// Size of the attached file
+    pub size: u64,
+    /// Name of the blob in the attachments dir
+    pub blob: String,
+    pub encrypted: bool,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub added_at: DateTime<Utc>,
+}
+
+pub fn blob_dir(docs_store_path: &Path) -> PathBuf {
+    docs_store_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(DIR)
+}
+
+pub fn blob_name(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+fn media_type(file_name: &str) -> &'static str {
+    let extension = Path::new(file_name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    match extension.as_str() {
+        "json" => "application/json",
+        "pdf" => "application/pdf",
+        "yaml" | "yml" => "application/yaml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "md" => "text/markdown",
+        "txt" => "text/plain",
+        "csv" => "text/csv",
+        _ => "application/octet-stream",
+    }
+}
+
+/// The vault's attachment key, created on first use. Returns whether it was
+/// created, in which case the vault needs saving.
+fn attachment_key(vault: &mut ApiKeyVault) -> Result<(Zeroizing<Vec<u8>>, bool), KeyKeeperError> {
+    if let Some(key) = &vault.doc_attachment_key {
+        let key = Zeroizing::new(
+            general_purpose::STANDARD
+                .decode(key)
+                .map_err(|e| KeyKeeperError::Crypto(format!("Invalid attachment key: {}", e)))?,
+        );
+        return Ok((key, false));
+    }
+    let mut key = Zeroizing::new(vec![0u8; 32]);
+    OsRng.fill_bytes(&mut key);
+    vault.doc_attachment_key = Some(general_purpose::STANDARD.encode(&*key));
+    Ok((key, true))
+}
+
+pub fn encrypt(key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, KeyKeeperError> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|e| KeyKeeperError::Crypto(format!("Failed to encrypt attachment: {}", e)))?;
+    Ok([nonce.as_slice(), &ciphertext].concat())
+}
+
+pub fn decrypt(key: &[u8], blob: &[u8]) -> Result<Vec<u8>, KeyKeeperError> {
+    if blob.len() < NONCE_LEN {
+        return Err(KeyKeeperError::Crypto(
+            "Attachment is truncated".to_string(),
+        ));
+    }
+    let (nonce, ciphertext) = blob.split_at(NONCE_LEN);
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|e| KeyKeeperError::Crypto(format!("Failed to decrypt attachment: {}", e)))
+}
+
+/// Blobs used by any doc
+pub fn referenced(store: &DocsStore) -> HashSet<String> {
+    store
+        .docs
+        .values()
+        .flat_map(|doc| doc.attachments.iter().map(|a| a.blob.clone()))
+        .collect()
+}
+
+/// Delete blobs dropped since the last save that no doc uses anymore
+pub fn delete_released(store: &mut DocsStore, dir: &Path) {
+    if store.released_blobs.is_empty() {
+        return;
+    }
+    let used = referenced(store);
+    for blob in std::mem::take(&mut store.released_blobs) {
+        if used.contains(&blob) {
+            continue;
+        }
+        match std::fs::remove_file(dir.join(&blob)) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("Failed to delete attachment blob {}: {}", blob, e),
+        }
+    }
+}
+
+/// Delete every blob in `dir` no doc uses. Returns how many were deleted.
+pub fn sweep(store: &DocsStore, dir: &Path) -> Result<usize, KeyKeeperError> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+    let used = referenced(store);
+    let mut deleted = 0;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if entry.path().is_file() && !used.contains(&name) {
+            std::fs::remove_file(entry.path())?;
+            deleted += 1;
+        }
+    }
+    Ok(deleted)
+}
+
+fn find<'a>(
+    store: &'a DocsStore,
+    doc_id: &str,
+    attachment_id: &str,
+) -> Result<&'a DocAttachment, KeyKeeperError> {
+    store
+        .get_doc(doc_id)
+        .ok_or_else(|| KeyKeeperError::not_found("Documentation"))?
+        .attachments
+        .iter()
+        .find(|a| a.id == attachment_id)
+        .ok_or_else(|| KeyKeeperError::not_found("Attachment"))
+}
+
+/// Attach the file at `path` to a doc, encrypted if `encrypted` is set
+#[tauri::command]
+pub async fn add_doc_attachment(
+    doc_id: String,
+    path: String,
+    encrypted: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<DocAttachment, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let path = PathBuf::from(path);
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| KeyKeeperError::Validation("Not a file path".to_string()))?;
+    let size = tokio::fs::metadata(&path).await?.len();
+    if size > MAX_ATTACHMENT_BYTES {
+        return Err(KeyKeeperError::Validation(format!(
+            "Attachments can be up to {} MB",
+            MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        )));
+    }
+    {
+        let store = state.docs_store.lock().await;
+        let doc = store
+            .get_doc(&doc_id)
+            .ok_or_else(|| KeyKeeperError::not_found("Documentation"))?;
+        let attached: u64 = doc.attachments.iter().map(|a| a.size).sum();
+        if attached + size > MAX_DOC_BYTES {
+            return Err(KeyKeeperError::Validation(format!(
+                "A doc's attachments can add up to {} MB",
+                MAX_DOC_BYTES / (1024 * 1024)
+            )));
+        }
+    }
+
+    let contents = Zeroizing::new(tokio::fs::read(&path).await?);
+    let encrypted = encrypted.unwrap_or(false);
+    let blob = if encrypted {
+        let (key, created) = attachment_key(&mut *state.vault.lock().await)?;
+        if created {
+            save_vault(&state).await?;
+        }
+        encrypt(&key, &contents)?
+    } else {
+        contents.to_vec()
+    };
+    let name = blob_name(&blob);
+    let dir = blob_dir(&state.docs_store_path);
+    let blob_path = dir.join(&name);
+    if !blob_path.exists() {
+        std::fs::create_dir_all(&dir)?;
+        crate::vault_location::write_atomic(&blob_path, &blob)?;
+    }
+
+    let attachment = DocAttachment {
+        id: uuid::Uuid::new_v4().to_string(),
+        media_type: media_type(&file_name).to_string(),
+        file_name,
+        size: contents.len() as u64,
+        blob: name,
+        encrypted,
+        added_at: Utc::now(),
+    };
+    {
+        let mut store = state.docs_store.lock().await;
+        let mut doc = store
+            .get_doc(&doc_id)
+            .cloned()
+            .ok_or_else(|| KeyKeeperError::not_found("Documentation"))?;
+        doc.attachments.push(attachment.clone());
+        doc.updated_at = Utc::now();
+        store.update_doc(doc);
+    }
+    save_docs_store(&state).await?;
+    log_audit_event(
+        &state,
+        "add_doc_attachment",
+        "documentation",
+        Some(&doc_id),
+        true,
+        Some(&attachment.file_name),
+    )
+    .await;
+    Ok(attachment)
+}
+
+#[tauri::command]
+pub async fn remove_doc_attachment(
+    doc_id: String,
+    attachment_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    {
+        let mut store = state.docs_store.lock().await;
+        let mut doc = store
+            .get_doc(&doc_id)
+            .cloned()
+            .ok_or_else(|| KeyKeeperError::not_found("Documentation"))?;
+        let before = doc.attachments.len();
+        doc.attachments.retain(|a| a.id != attachment_id);
+        if doc.attachments.len() == before {
+            return Err(KeyKeeperError::not_found("Attachment"));
+        }
+        doc.updated_at = Utc::now();
+        store.update_doc(doc);
+    }
+    save_docs_store(&state).await?;
+    log_audit_event(
+        &state,
+        "remove_doc_attachment",
+        "documentation",
+        Some(&doc_id),
+        true,
+        None,
+    )
+    .await;
+    Ok(())
+}
+
+/// Write an attachment, decrypted, to `path`
+#[tauri::command]
+pub async fn export_doc_attachment(
+    doc_id: String,
+    attachment_id: String,
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let attachment = find(&*state.docs_store.lock().await, &doc_id, &attachment_id)?.clone();
+    let blob = tokio::fs::read(blob_dir(&state.docs_store_path).join(&attachment.blob)).await?;
+    if blob_name(&blob) != attachment.blob {
+        return Err(KeyKeeperError::Crypto(format!(
+            "Attachment '{}' is corrupted",
+            attachment.file_name
+        )));
+    }
+    let contents = if attachment.encrypted {
+        let key = state
+            .vault
+            .lock()
+            .await
+            .doc_attachment_key
+            .clone()
+            .ok_or_else(|| KeyKeeperError::Crypto("The attachment key is missing".to_string()))?;
+        let key = Zeroizing::new(
+            general_purpose::STANDARD
+                .decode(key)
+                .map_err(|e| KeyKeeperError::Crypto(format!("Invalid attachment key: {}", e)))?,
+        );
+        Zeroizing::new(decrypt(&key, &blob)?)
+    } else {
+        Zeroizing::new(blob)
+    };
+    tokio::fs::write(&path, &*contents).await?;
+    log_audit_event(
+        &state,
+        "export_doc_attachment",
+        "documentation",
+        Some(&doc_id),
+        true,
+        Some(&attachment.file_name),
+    )
+    .await;
+    Ok(())
+}
+
+/// Delete blobs no doc refers to, e.g. left behind by an older version
+#[tauri::command]
+pub async fn cleanup_doc_attachments(state: State<'_, AppState>) -> Result<usize, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    if state.health.report().await.docs_store.error.is_some() {
+        return Err(KeyKeeperError::Conflict(
+            "The docs store didn't load, so attachments can't be matched to docs".to_string(),
+        ));
+    }
+
+    let deleted = sweep(
+        &*state.docs_store.lock().await,
+        &blob_dir(&state.docs_store_path),
+    )?;
+    log_audit_event(
+        &state,
+        "cleanup_doc_attachments",
+        "documentation",
+        None,
+        true,
+        Some(&format!("{} blobs deleted", deleted)),
+    )
+    .await;
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypted_blobs_round_trip_and_released_blobs_are_deleted() {
+        let key = [7u8; 32];
+        let sealed = encrypt(&key, b"{\"info\": {}}").unwrap();
+        assert_eq!(decrypt(&key, &sealed).unwrap(), b"{\"info\": {}}");
+        assert!(decrypt(&[8u8; 32], &sealed).is_err());
+
+        let dir = std::env::temp_dir().join(format!("kk-attachments-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let blob = blob_name(b"diagram");
+        std::fs::write(dir.join(&blob), b"diagram").unwrap();
+        std::fs::write(dir.join("orphan"), b"left over").unwrap();
+
+        let mut store = DocsStore::new();
+        store.released_blobs.push(blob.clone());
+        delete_released(&mut store, &dir);
+        assert!(!dir.join(&blob).exists());
+        assert_eq!(sweep(&store, &dir).unwrap(), 1);
+        assert_eq!(
+            media_type("api.postman_collection.json"),
+            "application/json"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,270 @@
+//! Cross-references from documentation to keys, env vars, providers and
+//! projects.
+//!
+//! When the docs store is saved while the vault is unlocked, docs added or
+//! changed since the last save are scanned for key names, env var names
+//! (`STRIPE_SECRET_KEY`), provider names and project names, and what they
+//! mention is stored with the store as typed references. `get_backlinks`
+//! answers "which docs mention this" for a key ID, env var name, provider
+//! or project ID. Docs saved while the vault is locked are scanned at the
+//! next save after unlocking; `refresh_doc_references` rescans everything,
+//! e.g. after keys or projects were renamed.
+
+use crate::errors::KeyKeeperError;
+use crate::{guards, save_docs_store, ApiKeyVault, AppState, DocsStore, Documentation};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tauri::State;
+
+/// Shorter names match too many words to be useful
+const MIN_NAME_LEN: usize = 3;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum RefKind {
+    Key,
+    EnvVar,
+    Provider,
+    Project,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DocReference {
+    pub kind: RefKind,
+    /// Key or project ID, env var name, or lowercased provider name
+    pub entity_id: String,
+    /// The name that matched; lowercased for providers and projects
+    pub text: String,
+}
+
+/// Names to look for, taken from the vault
+#[derive(Debug, Default)]
+pub struct Catalog {
+    /// Key name -> key ID
+    keys: HashMap<String, String>,
+    /// Lowercased provider name
+    providers: Vec<String>,
+    /// Lowercased project name -> project ID
+    projects: HashMap<String, String>,
+}
+
+impl Catalog {
+    pub fn from_vault(vault: &ApiKeyVault) -> Self {
+        let mut catalog = Self::default();
+        for key in vault.keys.values() {
+            catalog.keys.insert(key.name.clone(), key.id.clone());
+            let provider = key.service.trim().to_lowercase();
+            if !catalog.providers.contains(&provider) {
+                catalog.providers.push(provider);
+            }
+        }
+        for project in vault.projects.values() {
+            catalog
+                .projects
+                .insert(project.name.trim().to_lowercase(), project.id.clone());
+        }
+        catalog
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Where `needle` first occurs in `haystack` as a whole word
+fn find_word(haystack: &str, needle: &str) -> Option<usize> {
+    if needle.chars().count() < MIN_NAME_LEN {
+        return None;
+    }
+    haystack
+        .match_indices(needle)
+        .map(|(at, _)| at)
+        .find(|&at| {
+            let before = haystack[..at].chars().next_back();
+            let after = haystack[at + needle.len()..].chars().next();
+            !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+        })
+}
+
+/// `UPPER_SNAKE_CASE` with at least one underscore
+pub fn is_env_var_name(word: &str) -> bool {
+    word.len() >= MIN_NAME_LEN
+        && word.contains('_')
+        && word.starts_with(|c: char| c.is_ascii_uppercase())
+        && word
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// References in a doc's title and content, one per entity
+pub fn extract(doc: &Documentation, catalog: &Catalog) -> Vec<DocReference> {
+    let text = format!("{}\n{}", doc.title, doc.content);
+    let lower = text.to_lowercase();
+    let mut found: BTreeMap<(RefKind, String), String> = BTreeMap::new();
+
+    for word in text.split(|c: char| !is_word_char(c)) {
+        if is_env_var_name(word) {
+            found
+                .entry((RefKind::EnvVar, word.to_string()))
+                .or_insert_with(|| word.to_string());
+        }
+    }
+    for (name, id) in &catalog.keys {
+        if find_word(&text, name).is_some() {
+            found
+                .entry((RefKind::Key, id.clone()))
+                .or_insert_with(|| name.clone());
+        }
+    }
+    for provider in &catalog.providers {
+        if find_word(&lower, provider).is_some() {
+            found
+                .entry((RefKind::Provider, provider.clone()))
+                .or_insert_with(|| provider.clone());
+        }
+    }
+    for (name, id) in &catalog.projects {
+        if find_word(&lower, name).is_some() {
+            found
+                .entry((RefKind::Project, id.clone()))
+                .or_insert_with(|| name.clone());
+        }
+    }
+
+    found
+        .into_iter()
+        .map(|((kind, entity_id), text)| DocReference {
+            kind,
+            entity_id,
+            text,
+        })
+        .collect()
+}
+
+/// Scan the docs changed since the last scan
+pub fn extract_stale(store: &mut DocsStore, catalog: &Catalog) {
+    for id in std::mem::take(&mut store.stale_references) {
+        if let Some(doc) = store.docs.get(&id) {
+            let references = extract(doc, catalog);
+            store.references.insert(id, references);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Backlink {
+    pub doc_id: String,
+    pub title: String,
+    pub kind: RefKind,
+    pub text: String,
+}
+
+pub fn backlinks(store: &DocsStore, entity_id: &str) -> Vec<Backlink> {
+    let mut links: Vec<Backlink> = store
+        .references
+        .iter()
+        .filter_map(|(doc_id, references)| {
+            let doc = store.docs.get(doc_id)?;
+            let reference = references.iter().find(|r| r.entity_id == entity_id)?;
+            Some(Backlink {
+                doc_id: doc_id.clone(),
+                title: doc.title.clone(),
+                kind: reference.kind,
+                text: reference.text.clone(),
+            })
+        })
+        .collect();
+    links.sort_by(|a, b| (&a.title, &a.doc_id).cmp(&(&b.title, &b.doc_id)));
+    links
+}
+
+/// Docs mentioning a key ID, env var name, provider or project ID
+#[tauri::command]
+pub async fn get_backlinks(
+    entity_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<Backlink>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let entity_id = entity_id.trim();
+    let provider = entity_id.to_lowercase();
+    let store = state.docs_store.lock().await;
+    let mut links = backlinks(&store, entity_id);
+    if links.is_empty() && provider != entity_id {
+        links = backlinks(&store, &provider);
+    }
+    Ok(links)
+}
+
+#[tauri::command]
+pub async fn get_doc_references(
+    doc_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<DocReference>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let store = state.docs_store.lock().await;
+    if store.get_doc(&doc_id).is_none() {
+        return Err(KeyKeeperError::not_found("Documentation"));
+    }
+    Ok(store.references.get(&doc_id).cloned().unwrap_or_default())
+}
+
+/// Rescan every doc. Returns how many references were found.
+#[tauri::command]
+pub async fn refresh_doc_references(state: State<'_, AppState>) -> Result<usize, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    {
+        let mut store = state.docs_store.lock().await;
+        let ids: Vec<String> = store.docs.keys().cloned().collect();
+        store.references.clear();
+        store.stale_references.extend(ids);
+    }
+    // The save does the scan
+    save_docs_store(&state).await?;
+    let store = state.docs_store.lock().await;
+    Ok(store.references.values().map(Vec::len).sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn docs_link_to_keys_env_vars_providers_and_projects() {
+        let catalog = Catalog {
+            keys: HashMap::from([("STRIPE_SECRET_KEY".to_string(), "key_1".to_string())]),
+            providers: vec!["stripe".to_string(), "aws".to_string()],
+            projects: HashMap::from([("online shop".to_string(), "proj_1".to_string())]),
+        };
+        let doc: Documentation = serde_json::from_value(serde_json::json!({
+            "id": "doc_1", "title": "Stripe setup", "doc_type": "guide",
+            "content": "Set STRIPE_SECRET_KEY and DATABASE_URL for the Online Shop. \
+                        Not related: laws, STRIPE_SECRET_KEYS_OLD, Key_Name.",
+            "project_id": null, "provider_id": null, "url": null, "tags": [],
+            "created_at": "2024-01-01T00:00:00Z", "updated_at": "2024-01-01T00:00:00Z",
+            "language": "en", "is_favorite": false, "search_keywords": []
+        }))
+        .unwrap();
+
+        let found: Vec<(RefKind, String)> = extract(&doc, &catalog)
+            .into_iter()
+            .map(|r| (r.kind, r.entity_id))
+            .collect();
+        let expected = [
+            (RefKind::Key, "key_1"),
+            (RefKind::EnvVar, "DATABASE_URL"),
+            (RefKind::EnvVar, "STRIPE_SECRET_KEY"),
+            (RefKind::EnvVar, "STRIPE_SECRET_KEYS_OLD"),
+            (RefKind::Provider, "stripe"),
+            (RefKind::Project, "proj_1"),
+        ]
+        .map(|(kind, id)| (kind, id.to_string()));
+        assert_eq!(found, expected);
+
+        let mut store = DocsStore::new();
+        store.add_doc(doc);
+        extract_stale(&mut store, &catalog);
+        assert!(store.stale_references.is_empty());
+        assert_eq!(backlinks(&store, "key_1")[0].doc_id, "doc_1");
+        assert!(backlinks(&store, "aws").is_empty());
+    }
+}
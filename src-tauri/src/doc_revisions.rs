@@ -0,0 +1,213 @@
+//! Change history of documentation.
+//!
+//! Whenever a doc's title or content changes, whether by an edit, an
+//! import, or a scrape refreshing it, `DocsStore::update_doc` keeps what it
+//! replaced as a numbered revision. Each doc keeps its last
+//! `MAX_REVISIONS`. Revisions can be diffed against each other or the
+//! current doc, and restored, which itself keeps the replaced content as a
+//! revision.
+
+use crate::errors::KeyKeeperError;
+use crate::{log_audit_event, save_docs_store, AppState, DocsStore, Documentation};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::State;
+
+pub const MAX_REVISIONS: usize = 25;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DocRevision {
+    /// Counts up per doc, starting at 1
+    pub number: u32,
+    pub title: String,
+    pub content: String,
+    /// When this version was last edited
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub edited_at: DateTime<Utc>,
+    /// When it was replaced
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub replaced_at: DateTime<Utc>,
+}
+
+/// Keep `old` as a revision if `new` changes its title or content
+pub fn record(
+    revisions: &mut HashMap<String, Vec<DocRevision>>,
+    old: &Documentation,
+    new: &Documentation,
+) {
+    if old.title == new.title && old.content == new.content {
+        return;
+    }
+    let revisions = revisions.entry(old.id.clone()).or_default();
+    let number = revisions.last().map_or(1, |last| last.number + 1);
+    revisions.push(DocRevision {
+        number,
+        title: old.title.clone(),
+        content: old.content.clone(),
+        edited_at: old.updated_at,
+        replaced_at: Utc::now(),
+    });
+    if revisions.len() > MAX_REVISIONS {
+        revisions.drain(..revisions.len() - MAX_REVISIONS);
+    }
+}
+
+fn text(title: &str, content: &str) -> String {
+    format!("{}\n\n{}\n", title, content.trim_end())
+}
+
+/// Unified diff from `from` to `to`, each a title and content, with the
+/// title as the first line
+pub fn diff(from: (&str, &str), to: (&str, &str), from_label: &str, to_label: &str) -> String {
+    let before = text(from.0, from.1);
+    let after = text(to.0, to.1);
+    similar::TextDiff::from_lines(before.as_str(), after.as_str())
+        .unified_diff()
+        .context_radius(3)
+        .header(from_label, to_label)
+        .to_string()
+}
+
+fn revision<'a>(
+    store: &'a DocsStore,
+    doc_id: &str,
+    number: u32,
+) -> Result<&'a DocRevision, KeyKeeperError> {
+    store
+        .revisions
+        .get(doc_id)
+        .and_then(|revisions| revisions.iter().find(|r| r.number == number))
+        .ok_or_else(|| KeyKeeperError::not_found("Revision"))
+}
+
+/// Newest first
+#[tauri::command]
+pub async fn get_doc_revisions(
+    doc_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<DocRevision>, KeyKeeperError> {
+    let store = state.docs_store.lock().await;
+    if store.get_doc(&doc_id).is_none() {
+        return Err(KeyKeeperError::not_found("Documentation"));
+    }
+    let mut revisions = store.revisions.get(&doc_id).cloned().unwrap_or_default();
+    revisions.reverse();
+    Ok(revisions)
+}
+
+/// Diff revision `a` against revision `b`, or against the current doc when
+/// `b` is `None`
+#[tauri::command]
+pub async fn diff_doc_revisions(
+    doc_id: String,
+    a: u32,
+    b: Option<u32>,
+    state: State<'_, AppState>,
+) -> Result<String, KeyKeeperError> {
+    let store = state.docs_store.lock().await;
+    let doc = store
+        .get_doc(&doc_id)
+        .ok_or_else(|| KeyKeeperError::not_found("Documentation"))?;
+    let from = revision(&store, &doc_id, a)?;
+    let (to, to_label) = match b {
+        Some(b) => {
+            let to = revision(&store, &doc_id, b)?;
+            (
+                (to.title.as_str(), to.content.as_str()),
+                format!("revision {}", b),
+            )
+        }
+        None => (
+            (doc.title.as_str(), doc.content.as_str()),
+            "current".to_string(),
+        ),
+    };
+    Ok(diff(
+        (&from.title, &from.content),
+        to,
+        &format!("revision {}", a),
+        &to_label,
+    ))
+}
+
+/// Put a revision's title and content back. What it replaces becomes a new
+/// revision, so a restore can be undone.
+#[tauri::command]
+pub async fn restore_doc_revision(
+    doc_id: String,
+    number: u32,
+    state: State<'_, AppState>,
+) -> Result<Documentation, KeyKeeperError> {
+    let doc = {
+        let mut store = state.docs_store.lock().await;
+        let mut doc = store
+            .get_doc(&doc_id)
+            .cloned()
+            .ok_or_else(|| KeyKeeperError::not_found("Documentation"))?;
+        let revision = revision(&store, &doc_id, number)?;
+        doc.title = revision.title.clone();
+        doc.content = revision.content.clone();
+        doc.updated_at = Utc::now();
+        store.update_doc(doc.clone());
+        doc
+    };
+    save_docs_store(&state).await?;
+    if *state.is_unlocked.lock().await {
+        log_audit_event(
+            &state,
+            "restore_doc_revision",
+            "documentation",
+            Some(&doc_id),
+            true,
+            Some(&format!("revision {}", number)),
+        )
+        .await;
+    }
+    Ok(doc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_keep_bounded_revisions_that_diff() {
+        let mut doc: Documentation = serde_json::from_value(serde_json::json!({
+            "id": "doc_1", "title": "Webhooks", "content": "Verify the signature.",
+            "doc_type": "guide", "project_id": null, "provider_id": null, "url": null,
+            "tags": [], "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z", "language": "en",
+            "is_favorite": false, "search_keywords": []
+        }))
+        .unwrap();
+        let mut store = DocsStore::new();
+        store.add_doc(doc.clone());
+
+        doc.is_favorite = true;
+        store.update_doc(doc.clone());
+        assert!(!store.revisions.contains_key("doc_1"));
+
+        for n in 0..MAX_REVISIONS + 2 {
+            doc.content = format!("Verify the signature.\nStep {}", n);
+            store.update_doc(doc.clone());
+        }
+        let revisions = &store.revisions["doc_1"];
+        assert_eq!(revisions.len(), MAX_REVISIONS);
+        assert_eq!(revisions.last().unwrap().number, MAX_REVISIONS as u32 + 2);
+
+        let first = &revisions[0];
+        assert_eq!(first.number, 3);
+        let patch = diff(
+            (&first.title, &first.content),
+            (&doc.title, &doc.content),
+            "revision 3",
+            "current",
+        );
+        assert!(patch.starts_with("--- revision 3\n+++ current\n"));
+        assert!(patch.contains(&format!("+Step {}", MAX_REVISIONS + 1)));
+
+        store.remove_doc("doc_1");
+        assert!(store.revisions.is_empty());
+    }
+}
@@ -0,0 +1,286 @@
+//! Documentation tags.
+//!
+//! Tags stay plain strings on each doc, spelled as entered. Two spellings
+//! that differ only in case or spacing ("Rate Limits", "rate-limits") are
+//! the same tag: `DocsStore` keeps an index from the normalized tag to the
+//! docs carrying it, which `list_doc_tags`, renames and the tag filter of
+//! `search_native_documentation` read instead of scanning every doc.
+//! Suggestions come from existing tags and from provider and project names.
+
+use crate::errors::KeyKeeperError;
+use crate::{log_audit_event, AppState, DocsStore, Documentation};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use tauri::State;
+
+/// Normalized tag -> IDs of the docs carrying it
+pub type TagIndex = HashMap<String, BTreeSet<String>>;
+
+const MAX_SUGGESTIONS: usize = 10;
+
+/// Lowercase, with runs of whitespace, `_` and `-` turned into one `-`
+pub fn normalize(tag: &str) -> String {
+    tag.split(|c: char| c.is_whitespace() || c == '_' || c == '-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
+
+pub fn build_index<'a>(docs: impl IntoIterator<Item = &'a Documentation>) -> TagIndex {
+    let mut index = TagIndex::new();
+    for doc in docs {
+        index_doc(&mut index, doc);
+    }
+    index
+}
+
+pub fn index_doc(index: &mut TagIndex, doc: &Documentation) {
+    for tag in doc.tags.iter().map(|tag| normalize(tag)) {
+        if !tag.is_empty() {
+            index.entry(tag).or_default().insert(doc.id.clone());
+        }
+    }
+}
+
+pub fn unindex_doc(index: &mut TagIndex, doc_id: &str) {
+    index.retain(|_, ids| {
+        ids.remove(doc_id);
+        !ids.is_empty()
+    });
+}
+
+/// IDs of docs carrying any of `tags`
+pub fn docs_with_any(index: &TagIndex, tags: &[String]) -> BTreeSet<String> {
+    tags.iter()
+        .filter_map(|tag| index.get(&normalize(tag)))
+        .flatten()
+        .cloned()
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DocTag {
+    /// The most used spelling
+    pub name: String,
+    pub normalized: String,
+    pub doc_count: usize,
+}
+
+pub fn list(store: &DocsStore) -> Vec<DocTag> {
+    let mut tags: Vec<DocTag> = store
+        .tag_index
+        .iter()
+        .map(|(normalized, ids)| {
+            let mut spellings: BTreeMap<&str, usize> = BTreeMap::new();
+            for doc in ids.iter().filter_map(|id| store.docs.get(id)) {
+                for tag in doc.tags.iter().filter(|t| &normalize(t) == normalized) {
+                    *spellings.entry(tag.as_str()).or_default() += 1;
+                }
+            }
+            let name = spellings
+                .iter()
+                .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+                .map(|(name, _)| name.to_string())
+                .unwrap_or_else(|| normalized.clone());
+            DocTag {
+                name,
+                normalized: normalized.clone(),
+                doc_count: ids.len(),
+            }
+        })
+        .collect();
+    tags.sort_by(|a, b| {
+        b.doc_count
+            .cmp(&a.doc_count)
+            .then_with(|| a.normalized.cmp(&b.normalized))
+    });
+    tags
+}
+
+/// Rename `from` (any spelling) to `to` on every doc carrying it, merging
+/// with `to` where a doc already has it. Returns the IDs of changed docs.
+pub fn rename(store: &mut DocsStore, from: &str, to: &str) -> Result<Vec<String>, KeyKeeperError> {
+    let to = to.trim();
+    if normalize(to).is_empty() {
+        return Err(KeyKeeperError::Validation(
+            "A tag can't be empty".to_string(),
+        ));
+    }
+    let from = normalize(from);
+    let target = normalize(to);
+    let ids: Vec<String> = store
+        .tag_index
+        .get(&from)
+        .map(|ids| ids.iter().cloned().collect())
+        .ok_or_else(|| KeyKeeperError::not_found("Tag"))?;
+
+    let now = Utc::now();
+    for id in &ids {
+        let Some(mut doc) = store.get_doc(id).cloned() else {
+            continue;
+        };
+        let mut renamed = false;
+        doc.tags.retain(|tag| {
+            let tag = normalize(tag);
+            if tag == from || tag == target {
+                renamed = true;
+                false
+            } else {
+                true
+            }
+        });
+        if renamed {
+            doc.tags.push(to.to_string());
+        }
+        doc.updated_at = now;
+        store.update_doc(doc);
+    }
+    Ok(ids)
+}
+
+/// Tags to offer while editing a doc: its provider and project first, then
+/// existing tags, provider and project names that start with `prefix`
+pub fn suggest(
+    store: &DocsStore,
+    prefix: &str,
+    providers: &[String],
+    projects: &[String],
+    doc_provider: Option<&str>,
+    doc_project: Option<&str>,
+) -> Vec<String> {
+    let prefix = normalize(prefix);
+    let existing = list(store).into_iter().map(|tag| tag.normalized);
+    let named = providers.iter().chain(projects).map(|name| normalize(name));
+
+    let mut seen = BTreeSet::new();
+    doc_provider
+        .into_iter()
+        .chain(doc_project)
+        .map(normalize)
+        .chain(existing.chain(named).filter(|tag| tag.starts_with(&prefix)))
+        .filter(|tag| !tag.is_empty() && seen.insert(tag.clone()))
+        .take(MAX_SUGGESTIONS)
+        .collect()
+}
+
+#[tauri::command]
+pub async fn list_doc_tags(state: State<'_, AppState>) -> Result<Vec<DocTag>, KeyKeeperError> {
+    Ok(list(&*state.docs_store.lock().await))
+}
+
+/// Returns how many docs changed
+#[tauri::command]
+pub async fn rename_doc_tag(
+    from: String,
+    to: String,
+    state: State<'_, AppState>,
+) -> Result<usize, KeyKeeperError> {
+    let changed = rename(&mut *state.docs_store.lock().await, &from, &to)?;
+    crate::save_docs_store(&state).await?;
+    if *state.is_unlocked.lock().await {
+        log_audit_event(
+            &state,
+            "rename_doc_tag",
+            "documentation",
+            None,
+            true,
+            Some(&format!("'{}' -> '{}' on {} docs", from, to, changed.len())),
+        )
+        .await;
+    }
+    Ok(changed.len())
+}
+
+/// Provider and project names come from the vault, so they're only offered
+/// while it's unlocked
+#[tauri::command]
+pub async fn suggest_doc_tags(
+    prefix: Option<String>,
+    provider_id: Option<String>,
+    project_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, KeyKeeperError> {
+    let (mut providers, projects, project_name) = if *state.is_unlocked.lock().await {
+        let vault = state.vault.lock().await;
+        let providers: BTreeSet<String> =
+            vault.keys.values().map(|key| key.service.clone()).collect();
+        let projects: Vec<String> = vault.projects.values().map(|p| p.name.clone()).collect();
+        let project_name = project_id
+            .as_ref()
+            .and_then(|id| vault.projects.get(id))
+            .map(|p| p.name.clone());
+        (providers, projects, project_name)
+    } else {
+        (BTreeSet::new(), Vec::new(), None)
+    };
+
+    let store = state.docs_store.lock().await;
+    providers.extend(
+        store
+            .docs
+            .values()
+            .filter_map(|doc| doc.provider_id.clone()),
+    );
+    let providers: Vec<String> = providers.into_iter().collect();
+    Ok(suggest(
+        &store,
+        prefix.as_deref().unwrap_or(""),
+        &providers,
+        &projects,
+        provider_id.as_deref(),
+        project_name.as_deref(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(id: &str, tags: &[&str]) -> Documentation {
+        serde_json::from_value(serde_json::json!({
+            "id": id, "title": id, "content": "", "doc_type": "guide",
+            "project_id": null, "provider_id": null, "url": null,
+            "tags": tags, "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z", "language": "en",
+            "is_favorite": false, "search_keywords": []
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn spellings_share_a_tag_and_renames_merge() {
+        let mut store = DocsStore::new();
+        store.add_doc(doc("a", &["Rate Limits", "auth"]));
+        store.add_doc(doc("b", &["rate-limits"]));
+        store.add_doc(doc("c", &["rate_limits", "limits"]));
+
+        let tags = list(&store);
+        assert_eq!(tags[0].normalized, "rate-limits");
+        assert_eq!(tags[0].doc_count, 3);
+        assert_eq!(
+            docs_with_any(&store.tag_index, &["AUTH".to_string()]),
+            BTreeSet::from(["a".to_string()])
+        );
+
+        assert_eq!(
+            rename(&mut store, "rate limits", "limits").unwrap().len(),
+            3
+        );
+        assert_eq!(store.docs["c"].tags, vec!["limits"]);
+        assert!(!store.tag_index.contains_key("rate-limits"));
+        assert_eq!(store.tag_index["limits"].len(), 3);
+        assert!(rename(&mut store, "missing", "x").is_err());
+
+        let suggested = suggest(
+            &store,
+            "o",
+            &["OpenAI".to_string()],
+            &["Online Shop".to_string()],
+            Some("stripe"),
+            None,
+        );
+        assert_eq!(suggested, vec!["stripe", "openai", "online-shop"]);
+    }
+}
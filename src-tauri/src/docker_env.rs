@@ -0,0 +1,340 @@
+use crate::errors::KeyKeeperError;
+use crate::vault_index::KeyFilter;
+use crate::{env_var_name_for_key, guards, log_audit_event, ApiKey, AppState};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use tracing::{info, warn};
+
+/// Output formats supported by `generate_compose_env`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ComposeEnvFormat {
+    /// `.env` file read by docker compose (values quoted when needed)
+    ComposeEnv,
+    /// Raw `--env-file` payload for `docker run` (no quoting, one var per line)
+    EnvFile,
+    /// `docker-compose.override.yml` with an `environment:` block
+    Override,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposeEnvOutput {
+    pub format: ComposeEnvFormat,
+    pub content: String,
+    pub variables: Vec<String>,
+    pub written_to: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DockerRunResult {
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub injected_variables: Vec<String>,
+}
+
+/// Normalize environment aliases ("dev", "prod", ...) to their canonical names
+pub fn normalize_environment(environment: &str) -> String {
+    match environment.trim().to_lowercase().as_str() {
+        "dev" | "develop" | "development" | "local" => "development".to_string(),
+        "stage" | "staging" => "staging".to_string(),
+        "prod" | "production" | "live" => "production".to_string(),
+        "test" | "testing" => "test".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn quote_compose_value(value: &str) -> String {
+    let needs_quotes = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c.is_whitespace() || "#'\"$\\".contains(c));
+    if needs_quotes {
+        format!(
+            "\"{}\"",
+            value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('$', "$$")
+                .replace('\n', "\\n")
+        )
+    } else {
+        value.to_string()
+    }
+}
+
+/// Quote a value for the override file. Compose interpolates `$` in the YAML
+/// itself, so it's doubled; single quotes keep everything else literal, but
+/// they fold line breaks, so values with control characters are double-quoted
+/// with escapes instead.
+fn yaml_quote(value: &str) -> String {
+    let value = value.replace('$', "$$");
+    if value.chars().any(char::is_control) {
+        let mut quoted = String::from("\"");
+        for c in value.chars() {
+            match c {
+                '\\' => quoted.push_str("\\\\"),
+                '"' => quoted.push_str("\\\""),
+                '\n' => quoted.push_str("\\n"),
+                '\r' => quoted.push_str("\\r"),
+                '\t' => quoted.push_str("\\t"),
+                c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+                c => quoted.push(c),
+            }
+        }
+        quoted.push('"');
+        quoted
+    } else {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+}
+
+/// Render the selected keys in the requested format
+pub fn render_compose_env(
+    keys: &[(String, String)],
+    format: &ComposeEnvFormat,
+    service: &str,
+) -> Result<String, KeyKeeperError> {
+    let mut content = String::new();
+    match format {
+        ComposeEnvFormat::ComposeEnv => {
+            content.push_str("# Generated by KeyKeeper - do not commit this file\n");
+            for (name, value) in keys {
+                content.push_str(&format!("{}={}\n", name, quote_compose_value(value)));
+            }
+        }
+        ComposeEnvFormat::EnvFile => {
+            for (name, value) in keys {
+                if value.contains('\n') {
+                    return Err(KeyKeeperError::Validation(format!(
+                        "{} contains a newline, which --env-file does not support",
+                        name
+                    )));
+                }
+                content.push_str(&format!("{}={}\n", name, value));
+            }
+        }
+        ComposeEnvFormat::Override => {
+            content.push_str("# Generated by KeyKeeper - do not commit this file\n");
+            content.push_str("services:\n");
+            content.push_str(&format!("  {}:\n", service));
+            content.push_str("    environment:\n");
+            for (name, value) in keys {
+                content.push_str(&format!("      {}: {}\n", name, yaml_quote(value)));
+            }
+        }
+    }
+    Ok(content)
+}
+
+/// Collect `(VAR_NAME, value)` pairs for the project's keys in an environment
+async fn collect_project_env(
+    project_id: &str,
+    environment: &str,
+    state: &State<'_, AppState>,
+) -> Result<Vec<(String, String)>, KeyKeeperError> {
+    let vault_guard = state.vault.lock().await;
+    let project = vault_guard
+        .projects
+        .get(project_id)
+        .ok_or_else(|| KeyKeeperError::not_found("Project"))?;
+    let target_env = normalize_environment(environment);
+
+    let ids = state.vault_index.lock().await.filter_ids(
+        &vault_guard,
+        &KeyFilter {
+            project_path: Some(project.path.clone()),
+            environment: Some(target_env.clone()),
+            ..Default::default()
+        },
+    );
+    let mut keys: Vec<&ApiKey> = ids
+        .iter()
+        .filter_map(|id| vault_guard.keys.get(id))
+        .filter(|key| key.is_active)
+        .filter(|key| key.kind.is_env_value())
+        .collect();
+    keys.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut pairs: Vec<(String, String)> = Vec::new();
+    for key in keys {
+        let name = env_var_name_for_key(key);
+        if pairs.iter().any(|(existing, _)| existing == &name) {
+            warn!(
+                "Duplicate variable {} for project {}, skipping key {}",
+                name, project_id, key.id
+            );
+            continue;
+        }
+        let Some(value) = crate::env_values::value_for(key, &target_env) else {
+            continue;
+        };
+        pairs.push((name, value.to_string()));
+    }
+    Ok(pairs)
+}
+
+/// Produce a docker compose `.env`, `--env-file` payload or override file from vault keys
+#[tauri::command]
+pub async fn generate_compose_env(
+    project_id: String,
+    environment: String,
+    format: Option<ComposeEnvFormat>,
+    service: Option<String>,
+    output_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ComposeEnvOutput, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let format = format.unwrap_or(ComposeEnvFormat::ComposeEnv);
+    let pairs = collect_project_env(&project_id, &environment, &state).await?;
+    let content = render_compose_env(&pairs, &format, service.as_deref().unwrap_or("app"))?;
+
+    let written_to = if let Some(path) = output_path {
+        std::fs::write(&path, &content)
+            .map_err(|e| KeyKeeperError::Io(format!("Failed to write {}: {}", path, e)))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+        }
+        Some(path)
+    } else {
+        None
+    };
+
+    log_audit_event(
+        &state,
+        "generate_compose_env",
+        "project",
+        Some(&project_id),
+        true,
+        Some(&format!("{} variables for {}", pairs.len(), environment)),
+    )
+    .await;
+
+    Ok(ComposeEnvOutput {
+        format,
+        content,
+        variables: pairs.into_iter().map(|(name, _)| name).collect(),
+        written_to,
+    })
+}
+
+/// Run `docker run` with the project's secrets injected through the spawned
+/// process environment. Only variable names appear on the command line
+/// (`-e NAME`), so values never show up in `ps` output or shell history.
+#[tauri::command]
+pub async fn docker_run_with_secrets(
+    project_id: String,
+    environment: String,
+    image: String,
+    docker_args: Option<Vec<String>>,
+    command: Option<Vec<String>>,
+    state: State<'_, AppState>,
+) -> Result<DockerRunResult, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let pairs = collect_project_env(&project_id, &environment, &state).await?;
+
+    let mut cmd = tokio::process::Command::new("docker");
+    cmd.arg("run");
+    for (name, value) in &pairs {
+        cmd.arg("-e").arg(name);
+        cmd.env(name, value);
+    }
+    cmd.args(docker_args.unwrap_or_default());
+    cmd.arg(&image);
+    cmd.args(command.unwrap_or_default());
+
+    info!(
+        "Starting docker container {} with {} injected variables",
+        image,
+        pairs.len()
+    );
+    let output = cmd
+        .output()
+        .await
+        .map_err(|e| KeyKeeperError::Io(format!("Failed to run docker: {}", e)))?;
+
+    log_audit_event(
+        &state,
+        "docker_run_with_secrets",
+        "project",
+        Some(&project_id),
+        output.status.success(),
+        Some(&format!("image {} ({} variables)", image, pairs.len())),
+    )
+    .await;
+
+    Ok(DockerRunResult {
+        exit_code: output.status.code(),
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        injected_variables: pairs.into_iter().map(|(name, _)| name).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(value: &str, format: ComposeEnvFormat) -> Result<String, KeyKeeperError> {
+        render_compose_env(
+            &[("API_KEY".to_string(), value.to_string())],
+            &format,
+            "app",
+        )
+    }
+
+    #[test]
+    fn override_file_escapes_interpolation() {
+        let content = render("pa$$word${HOME}$", ComposeEnvFormat::Override).unwrap();
+        assert!(content.ends_with("      API_KEY: 'pa$$$$word$${HOME}$$'\n"));
+        assert!(content.contains("services:\n  app:\n    environment:\n"));
+    }
+
+    #[test]
+    fn override_file_keeps_quotes_and_line_breaks() {
+        assert_eq!(yaml_quote("it's"), "'it''s'");
+        assert_eq!(yaml_quote(""), "''");
+        assert_eq!(
+            yaml_quote("-----BEGIN KEY-----\nab\"c\\d\n-----END KEY-----"),
+            "\"-----BEGIN KEY-----\\nab\\\"c\\\\d\\n-----END KEY-----\""
+        );
+        assert_eq!(yaml_quote("a\tb\u{7}$"), "\"a\\tb\\u0007$$\"");
+    }
+
+    #[test]
+    fn compose_env_quotes_only_when_needed() {
+        assert_eq!(
+            render("plain", ComposeEnvFormat::ComposeEnv).unwrap(),
+            "# Generated by KeyKeeper - do not commit this file\nAPI_KEY=plain\n"
+        );
+        assert!(render("a $b \"c\"\nd", ComposeEnvFormat::ComposeEnv)
+            .unwrap()
+            .ends_with("API_KEY=\"a $$b \\\"c\\\"\\nd\"\n"));
+        assert!(render("", ComposeEnvFormat::ComposeEnv)
+            .unwrap()
+            .ends_with("API_KEY=\"\"\n"));
+    }
+
+    #[test]
+    fn env_file_is_raw_and_rejects_newlines() {
+        assert_eq!(
+            render("a $b 'c'", ComposeEnvFormat::EnvFile).unwrap(),
+            "API_KEY=a $b 'c'\n"
+        );
+        assert!(matches!(
+            render("a\nb", ComposeEnvFormat::EnvFile),
+            Err(KeyKeeperError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn environment_aliases_are_normalized() {
+        assert_eq!(normalize_environment(" Prod "), "production");
+        assert_eq!(normalize_environment("dev"), "development");
+        assert_eq!(normalize_environment("QA"), "qa");
+    }
+}
@@ -0,0 +1,455 @@
+//! Offline documentation bundles.
+//!
+//! `download_provider_docs` crawls a provider's docs site, starting from its
+//! `docs_url`, and stores every page in the `DocsStore` split into heading
+//! sections. Pages are flagged `is_offline` so contextual docs keep working
+//! without a connection. The crawl stays on the same host and under the
+//! path of the start URL, and is bounded in pages, depth and page size.
+
+use crate::errors::KeyKeeperError;
+use crate::{guards, log_audit_event, save_docs_store, AppState, DocSection, Documentation};
+use chrono::{DateTime, Utc};
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+use tauri::State;
+use tracing::{info, warn};
+use url::Url;
+
+const DEFAULT_MAX_PAGES: usize = 25;
+const MAX_PAGES_LIMIT: usize = 200;
+const MAX_DEPTH: usize = 3;
+/// Larger pages are skipped, they're usually generated API dumps
+const MAX_PAGE_BYTES: usize = 2 * 1024 * 1024;
+/// Pause between requests so we don't hammer the docs site
+const REQUEST_DELAY_MS: u64 = 200;
+
+/// Summary of a provider's downloaded bundle, kept in the `DocsStore`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfflineBundle {
+    pub provider_id: String,
+    pub root_url: String,
+    pub doc_ids: Vec<String>,
+    pub total_bytes: usize,
+    /// Pages that failed to download or were over the size limit
+    pub skipped_urls: Vec<String>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub downloaded_at: DateTime<Utc>,
+}
+
+struct ParsedPage {
+    title: String,
+    content: String,
+    sections: Vec<DocSection>,
+    links: Vec<Url>,
+}
+
+/// URL without fragment and query, used to avoid fetching a page twice
+fn canonical(url: &Url) -> String {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    url.set_query(None);
+    url.as_str().trim_end_matches('/').to_string()
+}
+
+/// Whether a link stays inside the bundle: same host, under the root path
+fn in_scope(root: &Url, candidate: &Url) -> bool {
+    let root_path = root.path().trim_end_matches('/');
+    let path = candidate.path();
+    matches!(candidate.scheme(), "http" | "https")
+        && candidate.host_str() == root.host_str()
+        && (path == root_path || path.starts_with(&format!("{}/", root_path)))
+}
+
+fn text_of(element: ElementRef) -> String {
+    element
+        .text()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn heading_level(name: &str) -> Option<u8> {
+    match name {
+        "h1" => Some(1),
+        "h2" => Some(2),
+        "h3" => Some(3),
+        "h4" => Some(4),
+        "h5" => Some(5),
+        "h6" => Some(6),
+        _ => None,
+    }
+}
+
+/// Split the main content of a page into heading sections, as markdown-ish
+/// text. Blocks nested in other captured blocks are skipped so list items
+/// with paragraphs aren't counted twice.
+fn parse_page(html: &str, page_url: &Url, page_id: &str) -> ParsedPage {
+    let document = Html::parse_document(html);
+    let content_selector =
+        Selector::parse("main, article, [role=main], .content, .docs-content, .markdown").unwrap();
+    let block_selector = Selector::parse("h1, h2, h3, h4, h5, h6, p, li, pre").unwrap();
+    let title_selector = Selector::parse("title").unwrap();
+    let link_selector = Selector::parse("a[href]").unwrap();
+
+    let root = document
+        .select(&content_selector)
+        .next()
+        .unwrap_or_else(|| document.root_element());
+
+    let mut sections: Vec<DocSection> = Vec::new();
+    let mut preamble = String::new();
+    for block in root.select(&block_selector) {
+        let nested = block
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .any(|a| matches!(a.value().name(), "li" | "pre" | "p"));
+        if nested {
+            continue;
+        }
+
+        let name = block.value().name();
+        if let Some(level) = heading_level(name) {
+            let title = text_of(block);
+            if title.is_empty() {
+                continue;
+            }
+            let parent_section_id = sections
+                .iter()
+                .rev()
+                .find(|s| s.level < level)
+                .map(|s| s.id.clone());
+            sections.push(DocSection {
+                id: format!("{}-s{}", page_id, sections.len()),
+                title,
+                content: String::new(),
+                level,
+                anchor: block.value().id().map(|id| id.to_string()),
+                parent_section_id,
+            });
+            continue;
+        }
+
+        let text = match name {
+            "pre" => format!("```\n{}\n```", block.text().collect::<String>().trim_end()),
+            "li" => format!("- {}", text_of(block)),
+            _ => text_of(block),
+        };
+        if text.trim().is_empty() {
+            continue;
+        }
+        let target = match sections.last_mut() {
+            Some(section) => &mut section.content,
+            None => &mut preamble,
+        };
+        if !target.is_empty() {
+            target.push_str("\n\n");
+        }
+        target.push_str(&text);
+    }
+
+    let mut content = preamble;
+    for section in &sections {
+        if !content.is_empty() {
+            content.push_str("\n\n");
+        }
+        content.push_str(&format!(
+            "{} {}",
+            "#".repeat(section.level as usize),
+            section.title
+        ));
+        if !section.content.is_empty() {
+            content.push_str("\n\n");
+            content.push_str(&section.content);
+        }
+    }
+
+    let title = document
+        .select(&title_selector)
+        .next()
+        .map(text_of)
+        .filter(|t| !t.is_empty())
+        .or_else(|| {
+            sections
+                .iter()
+                .find(|s| s.level == 1)
+                .map(|s| s.title.clone())
+        })
+        .unwrap_or_else(|| page_url.path().to_string());
+
+    let links = document
+        .select(&link_selector)
+        .filter_map(|a| a.value().attr("href"))
+        .filter_map(|href| page_url.join(href).ok())
+        .collect();
+
+    ParsedPage {
+        title,
+        content,
+        sections,
+        links,
+    }
+}
+
+fn search_keywords(page: &ParsedPage) -> Vec<String> {
+    let mut keywords: Vec<String> = std::iter::once(page.title.as_str())
+        .chain(page.sections.iter().map(|s| s.title.as_str()))
+        .flat_map(|t| {
+            t.to_lowercase()
+                .split_whitespace()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .filter(|w| w.len() > 3)
+        .collect();
+    keywords.sort();
+    keywords.dedup();
+    keywords
+}
+
+async fn fetch_page(client: &reqwest::Client, url: &Url) -> Result<String, String> {
+    let response = client
+        .get(url.clone())
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .error_for_status()
+        .map_err(|e| e.to_string())?;
+
+    let is_html = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_none_or(|v| v.contains("text/html"));
+    if !is_html {
+        return Err("not an HTML page".to_string());
+    }
+    if response
+        .content_length()
+        .is_some_and(|len| len as usize > MAX_PAGE_BYTES)
+    {
+        return Err("page too large".to_string());
+    }
+    let body = response.text().await.map_err(|e| e.to_string())?;
+    if body.len() > MAX_PAGE_BYTES {
+        return Err("page too large".to_string());
+    }
+    Ok(body)
+}
+
+/// Breadth-first crawl from `root`, returning the pages as documentation
+async fn crawl(
+    provider_id: &str,
+    root: &Url,
+    max_pages: usize,
+) -> Result<(Vec<Documentation>, Vec<String>, usize), KeyKeeperError> {
+    let client = reqwest::Client::builder()
+        .user_agent("KeyKeeper-DocScraper/1.0")
+        .timeout(Duration::from_secs(20))
+        .build()
+        .map_err(|e| KeyKeeperError::Internal(format!("Failed to build HTTP client: {}", e)))?;
+
+    let mut queue = VecDeque::from([(root.clone(), 0usize)]);
+    let mut seen = HashSet::from([canonical(root)]);
+    let mut pages = Vec::new();
+    let mut skipped = Vec::new();
+    let mut total_bytes = 0;
+
+    while let Some((url, depth)) = queue.pop_front() {
+        if pages.len() >= max_pages {
+            break;
+        }
+        if !pages.is_empty() || !skipped.is_empty() {
+            tokio::time::sleep(Duration::from_millis(REQUEST_DELAY_MS)).await;
+        }
+
+        let html = match fetch_page(&client, &url).await {
+            Ok(html) => html,
+            Err(e) => {
+                warn!("Skipping {} in {} docs bundle: {}", url, provider_id, e);
+                skipped.push(url.to_string());
+                continue;
+            }
+        };
+
+        let page_id = format!("doc_offline_{}_{}", provider_id, pages.len());
+        let page = parse_page(&html, &url, &page_id);
+        if depth < MAX_DEPTH {
+            for link in &page.links {
+                if in_scope(root, link) && seen.insert(canonical(link)) {
+                    queue.push_back((link.clone(), depth + 1));
+                }
+            }
+        }
+        if page.content.trim().is_empty() {
+            continue;
+        }
+
+        total_bytes += page.content.len();
+        let now = Utc::now();
+        pages.push(Documentation {
+            id: page_id,
+            search_keywords: search_keywords(&page),
+            title: page.title,
+            content: page.content,
+            doc_type: "offline".to_string(),
+            project_id: None,
+            provider_id: Some(provider_id.to_string()),
+            url: Some(url.to_string()),
+            tags: vec!["offline".to_string(), provider_id.to_string()],
+            created_at: now,
+            updated_at: now,
+            language: "en".to_string(),
+            is_favorite: false,
+            sections: page.sections,
+            is_offline: true,
+            attachments: Vec::new(),
+        });
+    }
+
+    if pages.is_empty() {
+        return Err(KeyKeeperError::Internal(format!(
+            "No documentation pages could be downloaded from {}",
+            root
+        )));
+    }
+    Ok((pages, skipped, total_bytes))
+}
+
+/// Download a provider's docs site for offline use, replacing any earlier
+/// bundle of the same provider
+#[tauri::command]
+pub async fn download_provider_docs(
+    provider_id: String,
+    max_pages: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<OfflineBundle, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let docs_url = {
+        let api_generator_guard = state.api_generator.lock().await;
+        let service_guard = api_generator_guard.service.lock().await;
+        service_guard
+            .get_providers()
+            .into_iter()
+            .find(|p| p.id == provider_id)
+            .map(|p| p.docs_url)
+            .ok_or_else(|| KeyKeeperError::not_found("Provider"))?
+    };
+    let root = Url::parse(&docs_url)
+        .map_err(|e| KeyKeeperError::Validation(format!("Invalid docs URL {}: {}", docs_url, e)))?;
+    let max_pages = max_pages
+        .unwrap_or(DEFAULT_MAX_PAGES)
+        .clamp(1, MAX_PAGES_LIMIT);
+
+    info!(
+        "Downloading offline docs for {} from {} (up to {} pages)",
+        provider_id, root, max_pages
+    );
+    let (pages, skipped_urls, total_bytes) = crawl(&provider_id, &root, max_pages).await?;
+
+    let bundle = OfflineBundle {
+        provider_id: provider_id.clone(),
+        root_url: root.to_string(),
+        doc_ids: pages.iter().map(|p| p.id.clone()).collect(),
+        total_bytes,
+        skipped_urls,
+        downloaded_at: Utc::now(),
+    };
+    {
+        let mut docs_store_guard = state.docs_store.lock().await;
+        remove_bundle_pages(&mut docs_store_guard, &provider_id);
+        for page in pages {
+            docs_store_guard.add_doc(page);
+        }
+        docs_store_guard
+            .offline_bundles
+            .insert(provider_id.clone(), bundle.clone());
+    }
+    save_docs_store(&state).await?;
+
+    log_audit_event(
+        &state,
+        "download_provider_docs",
+        "documentation",
+        Some(&provider_id),
+        true,
+        Some(&format!("{} pages", bundle.doc_ids.len())),
+    )
+    .await;
+    Ok(bundle)
+}
+
+fn remove_bundle_pages(store: &mut crate::DocsStore, provider_id: &str) {
+    let stale: Vec<String> = store
+        .docs
+        .values()
+        .filter(|d| d.is_offline && d.provider_id.as_deref() == Some(provider_id))
+        .map(|d| d.id.clone())
+        .collect();
+    for id in stale {
+        store.remove_doc(&id);
+    }
+    store.offline_bundles.remove(provider_id);
+}
+
+#[tauri::command]
+pub async fn list_offline_bundles(
+    state: State<'_, AppState>,
+) -> Result<Vec<OfflineBundle>, KeyKeeperError> {
+    let docs_store_guard = state.docs_store.lock().await;
+    let mut bundles: Vec<OfflineBundle> =
+        docs_store_guard.offline_bundles.values().cloned().collect();
+    bundles.sort_by(|a, b| a.provider_id.cmp(&b.provider_id));
+    Ok(bundles)
+}
+
+#[tauri::command]
+pub async fn delete_offline_bundle(
+    provider_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    {
+        let mut docs_store_guard = state.docs_store.lock().await;
+        if !docs_store_guard.offline_bundles.contains_key(&provider_id) {
+            return Err(KeyKeeperError::not_found("Offline bundle"));
+        }
+        remove_bundle_pages(&mut docs_store_guard, &provider_id);
+    }
+    save_docs_store(&state).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_page_into_nested_sections() {
+        let html = r#"<html><head><title>Auth</title></head><body>
+            <nav><a href="/other">Elsewhere</a></nav>
+            <main>
+              <h1 id="auth">Authentication</h1><p>Use a bearer token.</p>
+              <h2 id="keys">API keys</h2><ul><li><p>Create</p></li></ul>
+              <a href="/docs/errors#codes">Errors</a>
+            </main></body></html>"#;
+        let url = Url::parse("https://example.com/docs/auth").unwrap();
+        let page = parse_page(html, &url, "p");
+
+        assert_eq!(page.title, "Auth");
+        assert_eq!(page.sections.len(), 2);
+        assert_eq!(page.sections[1].parent_section_id.as_deref(), Some("p-s0"));
+        assert_eq!(page.sections[1].content, "- Create");
+        assert!(page
+            .content
+            .starts_with("# Authentication\n\nUse a bearer token."));
+
+        let root = Url::parse("https://example.com/docs").unwrap();
+        let in_bundle: Vec<_> = page.links.iter().filter(|l| in_scope(&root, l)).collect();
+        assert_eq!(in_bundle.len(), 1);
+        assert_eq!(canonical(in_bundle[0]), "https://example.com/docs/errors");
+    }
+}
@@ -1,6 +1,8 @@
 use crate::docs_manager::DocumentationManager;
 use crate::documentation_library::*;
 use crate::api_generator::ApiProvider;
+use crate::errors::KeyKeeperError;
+use crate::{guards, AppState};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -166,7 +168,9 @@ pub struct BulkDocumentEntry {
 pub async fn add_documentation_from_url(
     request: AddDocumentationRequest,
     docs_state: State<'_, DocumentationLibraryState>,
-) -> Result<String, String> {
+    state: State<'_, AppState>,
+) -> Result<String, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     info!("Adding documentation for provider: {} from {}", request.provider_name, request.docs_url);
     
     let docs_manager = docs_state.docs_manager.read().await;
@@ -183,6 +187,7 @@ pub async fn add_documentation_from_url(
         category: request.provider_category,
         dependencies: vec![],
         config_templates: vec![],
+        environment_rules: vec![],
     };
     
     match docs_manager.add_documentation_enhanced(&provider, &request.docs_url).await {
@@ -192,7 +197,7 @@ pub async fn add_documentation_from_url(
         }
         Err(e) => {
             error!("❌ Failed to add documentation: {}", e);
-            Err(format!("Failed to add documentation: {}", e))
+            Err(KeyKeeperError::Internal(format!("Failed to add documentation: {}", e)))
         }
     }
 }
@@ -202,7 +207,9 @@ pub async fn add_documentation_from_url(
 pub async fn add_manual_documentation(
     request: AddManualDocumentationRequest,
     docs_state: State<'_, DocumentationLibraryState>,
-) -> Result<String, String> {
+    state: State<'_, AppState>,
+) -> Result<String, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     info!("Adding manual documentation for provider: {}", request.provider_name);
     
     let docs_manager = docs_state.docs_manager.read().await;
@@ -210,7 +217,7 @@ pub async fn add_manual_documentation(
     
     // Create or find existing library for this provider
     let libraries = doc_library.get_libraries_by_provider(&request.provider_id).await
-        .map_err(|e| format!("Failed to get libraries: {}", e))?;
+        .map_err(|e| KeyKeeperError::Internal(format!("Failed to get libraries: {}", e)))?;
     
     let library_id = if let Some(existing_library) = libraries.first() {
         existing_library.id.clone()
@@ -233,7 +240,7 @@ pub async fn add_manual_documentation(
         };
         
         doc_library.add_library(library).await
-            .map_err(|e| format!("Failed to create library: {}", e))?
+            .map_err(|e| KeyKeeperError::Internal(format!("Failed to create library: {}", e)))?
     };
     
     // Create documentation chunk
@@ -280,7 +287,7 @@ pub async fn add_manual_documentation(
         }
         Err(e) => {
             error!("❌ Failed to add manual documentation: {}", e);
-            Err(format!("Failed to add manual documentation: {}", e))
+            Err(KeyKeeperError::Internal(format!("Failed to add manual documentation: {}", e)))
         }
     }
 }
@@ -290,7 +297,9 @@ pub async fn add_manual_documentation(
 pub async fn search_documentation_library(
     request: SearchDocumentationLibraryRequest,
     docs_state: State<'_, DocumentationLibraryState>,
-) -> Result<Vec<DocumentationSearchResultInfo>, String> {
+    state: State<'_, AppState>,
+) -> Result<Vec<DocumentationSearchResultInfo>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     debug!("Searching documentation library: {}", request.query);
     
     let docs_manager = docs_state.docs_manager.read().await;
@@ -341,7 +350,7 @@ pub async fn search_documentation_library(
         }
         Err(e) => {
             error!("❌ Failed to search documentation: {}", e);
-            Err(format!("Failed to search documentation: {}", e))
+            Err(KeyKeeperError::Internal(format!("Failed to search documentation: {}", e)))
         }
     }
 }
@@ -350,7 +359,9 @@ pub async fn search_documentation_library(
 #[tauri::command]
 pub async fn get_documentation_libraries(
     docs_state: State<'_, DocumentationLibraryState>,
-) -> Result<Vec<DocumentationLibraryInfo>, String> {
+    state: State<'_, AppState>,
+) -> Result<Vec<DocumentationLibraryInfo>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     debug!("Retrieving all documentation libraries");
     
     let docs_manager = docs_state.docs_manager.read().await;
@@ -366,7 +377,9 @@ pub async fn get_documentation_libraries(
 pub async fn get_documentation_library(
     library_id: String,
     docs_state: State<'_, DocumentationLibraryState>,
-) -> Result<Option<DocumentationLibraryInfo>, String> {
+    state: State<'_, AppState>,
+) -> Result<Option<DocumentationLibraryInfo>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     debug!("Retrieving documentation library: {}", library_id);
     
     // This would retrieve a specific library by ID
@@ -381,7 +394,9 @@ pub async fn get_library_chunks(
     offset: Option<usize>,
     limit: Option<usize>,
     docs_state: State<'_, DocumentationLibraryState>,
-) -> Result<Vec<DocumentationChunkInfo>, String> {
+    state: State<'_, AppState>,
+) -> Result<Vec<DocumentationChunkInfo>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     debug!("Retrieving chunks for library: {}", library_id);
     
     // This would retrieve chunks for a specific library with pagination
@@ -397,7 +412,9 @@ pub async fn update_documentation_library(
     description: Option<String>,
     tags: Option<Vec<String>>,
     docs_state: State<'_, DocumentationLibraryState>,
-) -> Result<bool, String> {
+    state: State<'_, AppState>,
+) -> Result<bool, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     info!("Updating documentation library: {}", library_id);
     
     // This would update library metadata
@@ -410,7 +427,9 @@ pub async fn update_documentation_library(
 pub async fn delete_documentation_library(
     library_id: String,
     docs_state: State<'_, DocumentationLibraryState>,
-) -> Result<bool, String> {
+    state: State<'_, AppState>,
+) -> Result<bool, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     info!("Deleting documentation library: {}", library_id);
     
     // This would delete a library and all its chunks
@@ -423,7 +442,9 @@ pub async fn delete_documentation_library(
 pub async fn refresh_documentation_library(
     library_id: String,
     docs_state: State<'_, DocumentationLibraryState>,
-) -> Result<bool, String> {
+    state: State<'_, AppState>,
+) -> Result<bool, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     info!("Refreshing documentation library: {}", library_id);
     
     // This would re-scrape and update documentation from the original URL
@@ -435,7 +456,9 @@ pub async fn refresh_documentation_library(
 #[tauri::command]
 pub async fn get_library_statistics(
     docs_state: State<'_, DocumentationLibraryState>,
-) -> Result<LibraryStatistics, String> {
+    state: State<'_, AppState>,
+) -> Result<LibraryStatistics, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     debug!("Retrieving library statistics");
     
     let docs_manager = docs_state.docs_manager.read().await;
@@ -454,7 +477,7 @@ pub async fn get_library_statistics(
         }
         Err(e) => {
             error!("❌ Failed to get library statistics: {}", e);
-            Err(format!("Failed to get library statistics: {}", e))
+            Err(KeyKeeperError::Internal(format!("Failed to get library statistics: {}", e)))
         }
     }
 }
@@ -464,7 +487,9 @@ pub async fn get_library_statistics(
 pub async fn bulk_import_documentation(
     request: BulkImportRequest,
     docs_state: State<'_, DocumentationLibraryState>,
-) -> Result<String, String> {
+    state: State<'_, AppState>,
+) -> Result<String, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     info!("Bulk importing {} documents for provider: {}", request.documents.len(), request.provider_name);
     
     let docs_manager = docs_state.docs_manager.read().await;
@@ -488,7 +513,7 @@ pub async fn bulk_import_documentation(
     };
     
     let library_id = doc_library.add_library(library).await
-        .map_err(|e| format!("Failed to create library: {}", e))?;
+        .map_err(|e| KeyKeeperError::Internal(format!("Failed to create library: {}", e)))?;
     
     let mut successful_imports = 0;
     
@@ -549,7 +574,9 @@ pub async fn export_documentation_library(
     library_id: String,
     format: String, // "json", "markdown", "csv"
     docs_state: State<'_, DocumentationLibraryState>,
-) -> Result<String, String> {
+    state: State<'_, AppState>,
+) -> Result<String, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     info!("Exporting documentation library {} as {}", library_id, format);
     
     // This would export the library in the requested format
@@ -558,7 +585,7 @@ pub async fn export_documentation_library(
         "json" => Ok(r#"{"library_id": "placeholder", "chunks": []}"#.to_string()),
         "markdown" => Ok(format!("# Documentation Library Export\n\nLibrary ID: {}\n\n", library_id)),
         "csv" => Ok("title,content,section_path,content_type\n".to_string()),
-        _ => Err(format!("Unsupported export format: {}", format))
+        _ => Err(KeyKeeperError::Validation(format!("Unsupported export format: {}", format)))
     }
 }
 
@@ -567,7 +594,9 @@ pub async fn export_documentation_library(
 pub async fn get_documentation_chunk(
     chunk_id: String,
     docs_state: State<'_, DocumentationLibraryState>,
-) -> Result<Option<DocumentationChunkInfo>, String> {
+    state: State<'_, AppState>,
+) -> Result<Option<DocumentationChunkInfo>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     debug!("Retrieving documentation chunk: {}", chunk_id);
     
     // This would retrieve a specific chunk by ID
@@ -584,7 +613,9 @@ pub async fn update_documentation_chunk(
     tags: Option<Vec<String>>,
     importance_score: Option<f32>,
     docs_state: State<'_, DocumentationLibraryState>,
-) -> Result<bool, String> {
+    state: State<'_, AppState>,
+) -> Result<bool, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     info!("Updating documentation chunk: {}", chunk_id);
     
     // This would update chunk content and regenerate embeddings
@@ -597,7 +628,9 @@ pub async fn update_documentation_chunk(
 pub async fn delete_documentation_chunk(
     chunk_id: String,
     docs_state: State<'_, DocumentationLibraryState>,
-) -> Result<bool, String> {
+    state: State<'_, AppState>,
+) -> Result<bool, KeyKeeperError> {
+    guards::unlocked(&state).await?;
     info!("Deleting documentation chunk: {}", chunk_id);
     
     // This would delete a specific chunk
@@ -610,7 +643,7 @@ pub async fn delete_documentation_chunk(
 pub async fn validate_documentation_url(
     url: String,
     docs_state: State<'_, DocumentationLibraryState>,
-) -> Result<bool, String> {
+) -> Result<bool, KeyKeeperError> {
     debug!("Validating documentation URL: {}", url);
     
     let docs_manager = docs_state.docs_manager.read().await;
@@ -242,6 +242,7 @@ impl DocumentationManager {
             category: String::new(),
             dependencies: vec![],
             config_templates: vec![],
+            environment_rules: vec![],
         };
         
         // Add new documentation
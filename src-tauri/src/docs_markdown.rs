@@ -0,0 +1,394 @@
+//! Markdown bundles of documentation.
+//!
+//! `export_docs` writes each doc to `<title-slug>.md` in a directory, with
+//! YAML front matter for what isn't content:
+//!
+//! ```text
+//! ---
+//! id: "doc_1712345678901"
+//! title: "Stripe webhooks"
+//! doc_type: "guide"
+//! language: "en"
+//! tags: ["payments", "webhooks"]
+//! provider: "stripe"
+//! project: "proj_42"
+//! url: "https://stripe.com/docs/webhooks"
+//! ---
+//! ```
+//!
+//! so docs can be versioned in the project's repo. `import_docs` reads the
+//! `.md` files of a directory back: a file whose `id` is in the store
+//! replaces that doc, anything else is added. Only the front matter written
+//! here is understood (scalars, flow lists and `- item` lists), not YAML in
+//! general; unknown keys are ignored. Attachments aren't exported and are
+//! kept when a doc is replaced.
+
+use crate::errors::KeyKeeperError;
+use crate::{guards, log_audit_event, save_docs_store, AppState, Documentation};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+const FENCE: &str = "---";
+const EXTENSION: &str = "md";
+
+fn quote(value: &str) -> String {
+    // A JSON string is a valid double-quoted YAML scalar
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+pub fn slug(title: &str) -> String {
+    let slug = title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let slug: String = slug.chars().take(80).collect();
+    if slug.is_empty() {
+        "untitled".to_string()
+    } else {
+        slug
+    }
+}
+
+pub fn to_markdown(doc: &Documentation) -> String {
+    let mut out = format!("{}\n", FENCE);
+    out.push_str(&format!("id: {}\n", quote(&doc.id)));
+    out.push_str(&format!("title: {}\n", quote(&doc.title)));
+    out.push_str(&format!("doc_type: {}\n", quote(&doc.doc_type)));
+    out.push_str(&format!("language: {}\n", quote(&doc.language)));
+    let tags: Vec<String> = doc.tags.iter().map(|tag| quote(tag)).collect();
+    out.push_str(&format!("tags: [{}]\n", tags.join(", ")));
+    for (key, value) in [
+        ("provider", &doc.provider_id),
+        ("project", &doc.project_id),
+        ("url", &doc.url),
+    ] {
+        if let Some(value) = value {
+            out.push_str(&format!("{}: {}\n", key, quote(value)));
+        }
+    }
+    if doc.is_favorite {
+        out.push_str("favorite: true\n");
+    }
+    out.push_str(&format!(
+        "created_at: {}\n",
+        quote(&doc.created_at.to_rfc3339())
+    ));
+    out.push_str(&format!(
+        "updated_at: {}\n",
+        quote(&doc.updated_at.to_rfc3339())
+    ));
+    out.push_str(FENCE);
+    out.push_str("\n\n");
+    out.push_str(doc.content.trim_end());
+    out.push('\n');
+    out
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    if value.starts_with('"') {
+        if let Ok(parsed) = serde_json::from_str::<String>(value) {
+            return parsed;
+        }
+    }
+    if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        return value[1..value.len() - 1].replace("''", "'");
+    }
+    value.to_string()
+}
+
+fn parse_list(value: &str) -> Vec<String> {
+    let inner = value.trim().trim_start_matches('[').trim_end_matches(']');
+    if let Ok(items) = serde_json::from_str::<Vec<String>>(&format!("[{}]", inner)) {
+        return items;
+    }
+    inner
+        .split(',')
+        .map(unquote)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+#[derive(Debug, Default)]
+struct FrontMatter {
+    values: BTreeMap<String, String>,
+    tags: Vec<String>,
+}
+
+/// Split a file into its front matter and body
+fn split(contents: &str) -> Result<(FrontMatter, String), String> {
+    let contents = contents.strip_prefix('\u{feff}').unwrap_or(contents);
+    let mut lines = contents.lines();
+    if lines.next().map(str::trim_end) != Some(FENCE) {
+        return Err("no front matter".to_string());
+    }
+
+    let mut front = FrontMatter::default();
+    let mut in_tags = false;
+    let mut closed = false;
+    for line in lines.by_ref() {
+        if line.trim_end() == FENCE {
+            closed = true;
+            break;
+        }
+        if in_tags {
+            if let Some(item) = line.trim_start().strip_prefix("- ") {
+                front.tags.push(unquote(item));
+                continue;
+            }
+            in_tags = false;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim();
+        if key == "tags" {
+            if value.trim().is_empty() {
+                in_tags = true;
+            } else {
+                front.tags = parse_list(value);
+            }
+        } else {
+            front.values.insert(key.to_string(), unquote(value));
+        }
+    }
+    if !closed {
+        return Err("front matter isn't closed".to_string());
+    }
+
+    let body = lines.collect::<Vec<_>>().join("\n");
+    Ok((front, body.trim().to_string()))
+}
+
+fn timestamp(front: &FrontMatter, key: &str) -> Option<DateTime<Utc>> {
+    front
+        .values
+        .get(key)
+        .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+        .map(|value| value.with_timezone(&Utc))
+}
+
+/// Read a doc from a file's contents. A missing `id` gets a new one.
+pub fn from_markdown(contents: &str, now: DateTime<Utc>) -> Result<Documentation, String> {
+    let (front, content) = split(contents)?;
+    let value = |key: &str| front.values.get(key).filter(|v| !v.is_empty()).cloned();
+    let title = value("title").ok_or("no title")?;
+    Ok(Documentation {
+        id: value("id").unwrap_or_else(|| format!("doc_{}", uuid::Uuid::new_v4())),
+        title,
+        content,
+        doc_type: value("doc_type").unwrap_or_else(|| "notes".to_string()),
+        project_id: value("project"),
+        provider_id: value("provider"),
+        url: value("url"),
+        tags: front.tags.clone(),
+        created_at: timestamp(&front, "created_at").unwrap_or(now),
+        updated_at: timestamp(&front, "updated_at").unwrap_or(now),
+        language: value("language").unwrap_or_else(|| "en".to_string()),
+        is_favorite: value("favorite").is_some_and(|v| v == "true"),
+        search_keywords: Vec::new(),
+        sections: Vec::new(),
+        is_offline: false,
+        attachments: Vec::new(),
+    })
+}
+
+fn target_dir(dir: &str) -> Result<PathBuf, KeyKeeperError> {
+    let dir = PathBuf::from(dir);
+    if !dir.is_absolute() {
+        return Err(KeyKeeperError::Validation(
+            "The directory must be an absolute path".to_string(),
+        ));
+    }
+    Ok(dir)
+}
+
+fn unique_path(dir: &Path, doc: &Documentation, taken: &mut HashSet<String>) -> PathBuf {
+    let base = slug(&doc.title);
+    let mut name = base.clone();
+    let mut n = 2;
+    while !taken.insert(name.clone()) {
+        name = format!("{}-{}", base, n);
+        n += 1;
+    }
+    dir.join(format!("{}.{}", name, EXTENSION))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocsExport {
+    pub dir: String,
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedFile {
+    pub file: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DocsImport {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub skipped: Vec<SkippedFile>,
+}
+
+/// Write `doc_ids`, or every doc when it's `None`, to `dir`. Existing files
+/// with the same name are overwritten.
+#[tauri::command]
+pub async fn export_docs(
+    doc_ids: Option<Vec<String>>,
+    dir: String,
+    state: State<'_, AppState>,
+) -> Result<DocsExport, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let dir = target_dir(&dir)?;
+    let mut docs: Vec<Documentation> = {
+        let store = state.docs_store.lock().await;
+        match &doc_ids {
+            Some(ids) => ids
+                .iter()
+                .map(|id| {
+                    store
+                        .get_doc(id)
+                        .cloned()
+                        .ok_or_else(|| KeyKeeperError::not_found("Documentation"))
+                })
+                .collect::<Result<_, _>>()?,
+            None => store.get_all_docs().into_iter().cloned().collect(),
+        }
+    };
+    docs.sort_by(|a, b| (&a.title, &a.id).cmp(&(&b.title, &b.id)));
+
+    tokio::fs::create_dir_all(&dir).await?;
+    let mut taken = HashSet::new();
+    let mut files = Vec::new();
+    for doc in &docs {
+        let path = unique_path(&dir, doc, &mut taken);
+        tokio::fs::write(&path, to_markdown(doc)).await?;
+        files.push(path.to_string_lossy().to_string());
+    }
+
+    log_audit_event(
+        &state,
+        "export_docs",
+        "documentation",
+        None,
+        true,
+        Some(&format!("{} docs to {}", files.len(), dir.display())),
+    )
+    .await;
+    Ok(DocsExport {
+        dir: dir.to_string_lossy().to_string(),
+        files,
+    })
+}
+
+/// Read the `.md` files of `dir` into the store
+#[tauri::command]
+pub async fn import_docs(
+    dir: String,
+    state: State<'_, AppState>,
+) -> Result<DocsImport, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let dir = target_dir(&dir)?;
+    let mut paths = Vec::new();
+    let mut entries = tokio::fs::read_dir(&dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == EXTENSION) && path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let now = Utc::now();
+    let mut report = DocsImport::default();
+    let mut store = state.docs_store.lock().await;
+    for path in paths {
+        let file = path.to_string_lossy().to_string();
+        let parsed = tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|contents| from_markdown(&contents, now));
+        match parsed {
+            Ok(mut doc) if store.get_doc(&doc.id).is_some() => {
+                // Not part of the file, so keep what the store has
+                if let Some(existing) = store.get_doc(&doc.id) {
+                    doc.search_keywords = existing.search_keywords.clone();
+                    doc.sections = existing.sections.clone();
+                    doc.is_offline = existing.is_offline;
+                    doc.attachments = existing.attachments.clone();
+                }
+                report.updated.push(doc.id.clone());
+                store.update_doc(doc);
+            }
+            Ok(doc) => {
+                report.created.push(doc.id.clone());
+                store.add_doc(doc);
+            }
+            Err(reason) => report.skipped.push(SkippedFile { file, reason }),
+        }
+    }
+    drop(store);
+
+    save_docs_store(&state).await?;
+    log_audit_event(
+        &state,
+        "import_docs",
+        "documentation",
+        None,
+        true,
+        Some(&format!(
+            "{} created, {} updated, {} skipped from {}",
+            report.created.len(),
+            report.updated.len(),
+            report.skipped.len(),
+            dir.display()
+        )),
+    )
+    .await;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_round_trips_and_reads_hand_written_files() {
+        let now = Utc::now();
+        let mut doc = from_markdown("---\ntitle: x\n---\n", now).unwrap();
+        doc.title = "Stripe: \"webhooks\"".to_string();
+        doc.content = "# Webhooks\n\nVerify the signature.".to_string();
+        doc.tags = vec!["payments".to_string(), "web, hooks".to_string()];
+        doc.provider_id = Some("stripe".to_string());
+        doc.url = Some("https://stripe.com/docs/webhooks".to_string());
+        doc.is_favorite = true;
+
+        let back = from_markdown(&to_markdown(&doc), now).unwrap();
+        assert_eq!(back.id, doc.id);
+        assert_eq!(back.title, doc.title);
+        assert_eq!(back.content, doc.content);
+        assert_eq!(back.tags, doc.tags);
+        assert_eq!(back.provider_id, doc.provider_id);
+        assert_eq!(back.project_id, None);
+        assert_eq!(back.url, doc.url);
+        assert!(back.is_favorite);
+        assert_eq!(slug(&doc.title), "stripe-webhooks");
+
+        let hand = "---\ntitle: Deploy notes\ntags:\n  - ops\n  - 'on call'\n---\nBody";
+        let parsed = from_markdown(hand, now).unwrap();
+        assert_eq!(parsed.tags, vec!["ops", "on call"]);
+        assert_eq!(parsed.doc_type, "notes");
+        assert_eq!(parsed.content, "Body");
+        assert!(from_markdown("# No front matter", now).is_err());
+    }
+}
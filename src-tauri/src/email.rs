@@ -0,0 +1,619 @@
+//! Email delivery of recovery tokens and security alerts.
+//!
+//! `request_password_recovery` used to hand the token back to whoever asked
+//! for it. With email delivery set up, the token is mailed to the account's
+//! email (and its backup email, if chosen) and the caller only learns where
+//! it went. Security-relevant audit events (password changes, recovery codes
+//! used, a new recovery kit, ...) are mailed as alerts in the background.
+//!
+//! Mail goes out over SMTP (implicit TLS, STARTTLS, or plain for a local
+//! relay) or through the local `sendmail`. The settings, SMTP password
+//! included, are kept in the vault and so encrypted with it; listings only
+//! show whether a password is set. Subjects and bodies come from built-in
+//! templates that can be overridden per kind, with `{{placeholder}}`
+//! substitution. Each attempt is recorded in `email_deliveries`, like
+//! webhook deliveries.
+
+use crate::errors::KeyKeeperError;
+use crate::guards::{self, Requirement};
+use crate::{log_audit_event, save_vault, ApiKeyVault, AppState};
+use chrono::{DateTime, Utc};
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSendmailTransport, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::State;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+use zeroize::Zeroize;
+
+const MAX_ATTEMPTS: u32 = 3;
+const INITIAL_BACKOFF_SECS: u64 = 2;
+const SMTP_TIMEOUT_SECS: u64 = 20;
+const MAX_DELIVERY_LOG: usize = 200;
+/// Shown instead of the SMTP password
+const PASSWORD_PLACEHOLDER: &str = "••••••••";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// TLS from the start, usually port 465
+    Tls,
+    /// Upgraded with STARTTLS, usually port 587
+    StartTls,
+    /// No encryption, for a relay on this machine
+    None,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EmailTransport {
+    Smtp {
+        host: String,
+        port: u16,
+        security: SmtpSecurity,
+        username: Option<String>,
+        password: Option<String>,
+    },
+    /// The local `sendmail`, or `command` instead
+    Sendmail { command: Option<String> },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailTemplate {
+    RecoveryToken,
+    SecurityAlert,
+    Test,
+}
+
+impl EmailTemplate {
+    fn default_text(self) -> TemplateText {
+        let (subject, body) = match self {
+            EmailTemplate::RecoveryToken => (
+                "Your KeyKeeper recovery token",
+                "Someone asked to reset the master password of your KeyKeeper vault.\n\n\
+                 Recovery token: {{token}}\n\
+                 Valid until: {{expires_at}}\n\n\
+                 If this wasn't you, ignore this email and check the vault's audit log.\n",
+            ),
+            EmailTemplate::SecurityAlert => (
+                "KeyKeeper security alert: {{event}}",
+                "{{event}}\n\n\
+                 When: {{time}}\n\
+                 {{details}}\n\n\
+                 If this wasn't you, lock the vault and change the master password.\n",
+            ),
+            EmailTemplate::Test => (
+                "KeyKeeper email test",
+                "Email delivery from KeyKeeper works. Recovery tokens and security alerts \
+                 will be sent to {{email}}.\n",
+            ),
+        };
+        TemplateText {
+            subject: subject.to_string(),
+            body: body.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TemplateText {
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailSettings {
+    pub transport: EmailTransport,
+    /// Sender, e.g. `KeyKeeper <keykeeper@example.com>`
+    pub from: String,
+    pub enabled: bool,
+    pub recovery_tokens: bool,
+    pub security_alerts: bool,
+    /// Also send to the account's backup email
+    pub include_backup_email: bool,
+    /// Replacements of the built-in templates
+    #[serde(default)]
+    pub templates: BTreeMap<EmailTemplate, TemplateText>,
+}
+
+impl EmailSettings {
+    fn validate(&self) -> Result<(), String> {
+        self.from
+            .parse::<Mailbox>()
+            .map_err(|e| format!("Invalid sender '{}': {}", self.from, e))?;
+        if let EmailTransport::Smtp { host, port, .. } = &self.transport {
+            if host.trim().is_empty() || *port == 0 {
+                return Err("SMTP host and port are required".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy safe to hand to the frontend
+    fn redacted(&self) -> Self {
+        let mut settings = self.clone();
+        if let EmailTransport::Smtp { password, .. } = &mut settings.transport {
+            if password.is_some() {
+                *password = Some(PASSWORD_PLACEHOLDER.to_string());
+            }
+        }
+        settings
+    }
+
+    fn render(&self, template: EmailTemplate, values: &[(&str, &str)]) -> TemplateText {
+        let text = self
+            .templates
+            .get(&template)
+            .cloned()
+            .unwrap_or_else(|| template.default_text());
+        TemplateText {
+            subject: fill(&text.subject, values),
+            body: fill(&text.body, values),
+        }
+    }
+}
+
+/// Replace each `{{name}}` of `values`; unknown placeholders are left as is
+fn fill(text: &str, values: &[(&str, &str)]) -> String {
+    values.iter().fold(text.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{{{}}}}}", name), value)
+    })
+}
+
+/// One attempt to send an email
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailDelivery {
+    pub id: String,
+    pub template: EmailTemplate,
+    pub recipient: String,
+    pub attempt: u32,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub timestamp: DateTime<Utc>,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// `alice@example.com` as `a••••@example.com`, for messages to the caller
+pub fn mask_address(address: &str) -> String {
+    match address.split_once('@') {
+        Some((local, domain)) => {
+            let first: String = local.chars().take(1).collect();
+            format!("{}••••@{}", first, domain)
+        }
+        None => "••••".to_string(),
+    }
+}
+
+/// Account email and, if chosen, backup email
+fn recipients(vault: &ApiKeyVault, settings: &EmailSettings) -> Vec<String> {
+    let Some(account) = &vault.user_account else {
+        return Vec::new();
+    };
+    let mut recipients = vec![account.email.clone()];
+    if settings.include_backup_email {
+        if let Some(backup) = account.backup_email.as_ref().filter(|e| !e.is_empty()) {
+            if *backup != account.email {
+                recipients.push(backup.clone());
+            }
+        }
+    }
+    recipients
+}
+
+/// Subject and detail line of the alert an audit event warrants, if any
+pub fn alert_for_audit(action: &str, success: bool) -> Option<(&'static str, &'static str)> {
+    if !success {
+        return None;
+    }
+    let alert = match action {
+        "change_master_password" => (
+            "Master password changed",
+            "The vault was encrypted again under the new password.",
+        ),
+        "rekey_vault" => (
+            "Vault encrypted again",
+            "The vault got a fresh salt and key; the password is unchanged.",
+        ),
+        "reset_master_password" | "reset_master_password_after_recovery" => (
+            "Master password reset",
+            "The master password was replaced through account recovery.",
+        ),
+        "unlock_with_recovery_code" => (
+            "Vault unlocked with a recovery code",
+            "A recovery code or the recovery passphrase was used to unlock the vault.",
+        ),
+        "generate_recovery_kit" => (
+            "New recovery kit",
+            "A new recovery kit was generated; the previous one no longer works.",
+        ),
+        "enable_hardware_unlock" => (
+            "Hardware unlock enabled",
+            "This device can now unlock the vault with its secure element.",
+        ),
+        _ => return None,
+    };
+    Some(alert)
+}
+
+fn build_message(
+    settings: &EmailSettings,
+    recipient: &str,
+    text: &TemplateText,
+) -> Result<Message, String> {
+    Message::builder()
+        .from(
+            settings
+                .from
+                .parse()
+                .map_err(|e| format!("Invalid sender: {}", e))?,
+        )
+        .to(recipient
+            .parse()
+            .map_err(|e| format!("Invalid recipient '{}': {}", recipient, e))?)
+        .subject(text.subject.clone())
+        .header(ContentType::TEXT_PLAIN)
+        .body(text.body.clone())
+        .map_err(|e| format!("Failed to build email: {}", e))
+}
+
+async fn send_once(settings: &EmailSettings, message: Message) -> Result<(), String> {
+    match &settings.transport {
+        EmailTransport::Smtp {
+            host,
+            port,
+            security,
+            username,
+            password,
+        } => {
+            let builder = match security {
+                SmtpSecurity::Tls => {
+                    AsyncSmtpTransport::<Tokio1Executor>::relay(host).map_err(|e| e.to_string())?
+                }
+                SmtpSecurity::StartTls => {
+                    AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+                        .map_err(|e| e.to_string())?
+                }
+                SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+            };
+            let mut builder = builder
+                .port(*port)
+                .timeout(Some(Duration::from_secs(SMTP_TIMEOUT_SECS)));
+            if let Some(username) = username {
+                builder = builder.credentials(Credentials::new(
+                    username.clone(),
+                    password.clone().unwrap_or_default(),
+                ));
+            }
+            builder
+                .build()
+                .send(message)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }
+        EmailTransport::Sendmail { command } => {
+            let transport = match command {
+                Some(command) => {
+                    AsyncSendmailTransport::<Tokio1Executor>::new_with_command(command)
+                }
+                None => AsyncSendmailTransport::<Tokio1Executor>::new(),
+            };
+            transport.send(message).await.map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Send `text` to `recipient`, retrying with backoff, and record every
+/// attempt. Returns the last error if all attempts failed.
+async fn deliver(
+    vault: &Arc<Mutex<ApiKeyVault>>,
+    settings: &EmailSettings,
+    template: EmailTemplate,
+    recipient: &str,
+    text: &TemplateText,
+) -> Result<(), String> {
+    let message = build_message(settings, recipient, text)?;
+    let mut backoff = Duration::from_secs(INITIAL_BACKOFF_SECS);
+    let mut last_error = String::new();
+    for attempt in 1..=MAX_ATTEMPTS {
+        let result = send_once(settings, message.clone()).await;
+        record_delivery(
+            vault,
+            EmailDelivery {
+                id: uuid::Uuid::new_v4().to_string(),
+                template,
+                recipient: recipient.to_string(),
+                attempt,
+                timestamp: Utc::now(),
+                success: result.is_ok(),
+                error: result.as_ref().err().cloned(),
+            },
+        )
+        .await;
+        match result {
+            Ok(()) => {
+                info!("Sent {:?} email to {}", template, mask_address(recipient));
+                return Ok(());
+            }
+            Err(e) => {
+                warn!(
+                    "Sending {:?} email failed (attempt {}): {}",
+                    template, attempt, e
+                );
+                last_error = e;
+            }
+        }
+        if attempt < MAX_ATTEMPTS {
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+    Err(last_error)
+}
+
+async fn record_delivery(vault: &Arc<Mutex<ApiKeyVault>>, delivery: EmailDelivery) {
+    let mut vault_guard = vault.lock().await;
+    vault_guard.email_deliveries.push(delivery);
+    if vault_guard.email_deliveries.len() > MAX_DELIVERY_LOG {
+        let excess = vault_guard.email_deliveries.len() - MAX_DELIVERY_LOG;
+        vault_guard.email_deliveries.drain(..excess);
+    }
+}
+
+/// Settings that send recovery tokens, and where to
+pub fn recovery_delivery(vault: &ApiKeyVault) -> Option<(EmailSettings, Vec<String>)> {
+    let settings = vault
+        .email_delivery
+        .as_ref()
+        .filter(|s| s.enabled && s.recovery_tokens)?;
+    let recipients = recipients(vault, settings);
+    (!recipients.is_empty()).then(|| (settings.clone(), recipients))
+}
+
+/// Mail a recovery token to each recipient. Succeeds if any got it.
+pub async fn send_recovery_token(
+    vault: &Arc<Mutex<ApiKeyVault>>,
+    settings: &EmailSettings,
+    recipients: &[String],
+    token: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<(), String> {
+    let expires_at = expires_at.format("%Y-%m-%d %H:%M UTC").to_string();
+    let mut text = settings.render(
+        EmailTemplate::RecoveryToken,
+        &[("token", token), ("expires_at", &expires_at)],
+    );
+    let mut errors = Vec::new();
+    for recipient in recipients {
+        if let Err(e) = deliver(
+            vault,
+            settings,
+            EmailTemplate::RecoveryToken,
+            recipient,
+            &text,
+        )
+        .await
+        {
+            errors.push(e);
+        }
+    }
+    text.body.zeroize();
+    if errors.len() == recipients.len() {
+        return Err(errors.join("; "));
+    }
+    Ok(())
+}
+
+/// Mail the alert for an audit event in the background, if it warrants one
+/// and alerts are on. Called from `log_audit_event`.
+pub async fn dispatch_alert(vault: Arc<Mutex<ApiKeyVault>>, action: &str, success: bool) {
+    let Some((event, details)) = alert_for_audit(action, success) else {
+        return;
+    };
+    let (settings, recipients) = {
+        let vault_guard = vault.lock().await;
+        let Some(settings) = vault_guard
+            .email_delivery
+            .as_ref()
+            .filter(|s| s.enabled && s.security_alerts)
+        else {
+            return;
+        };
+        (settings.clone(), recipients(&vault_guard, settings))
+    };
+    let time = Utc::now().format("%Y-%m-%d %H:%M UTC").to_string();
+    let text = settings.render(
+        EmailTemplate::SecurityAlert,
+        &[("event", event), ("time", &time), ("details", details)],
+    );
+    tokio::spawn(async move {
+        for recipient in recipients {
+            let _ = deliver(
+                &vault,
+                &settings,
+                EmailTemplate::SecurityAlert,
+                &recipient,
+                &text,
+            )
+            .await;
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_email_settings(
+    state: State<'_, AppState>,
+) -> Result<Option<EmailSettings>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let vault_guard = state.vault.lock().await;
+    Ok(vault_guard
+        .email_delivery
+        .as_ref()
+        .map(EmailSettings::redacted))
+}
+
+/// Save the settings. A password equal to the placeholder listings show
+/// keeps the stored one.
+#[tauri::command]
+pub async fn set_email_settings(
+    mut settings: EmailSettings,
+    state: State<'_, AppState>,
+) -> Result<EmailSettings, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    settings.validate().map_err(KeyKeeperError::Validation)?;
+
+    let mut vault_guard = state.vault.lock().await;
+    if let EmailTransport::Smtp { password, .. } = &mut settings.transport {
+        if password.as_deref() == Some(PASSWORD_PLACEHOLDER) {
+            *password = match vault_guard.email_delivery.as_ref().map(|s| &s.transport) {
+                Some(EmailTransport::Smtp { password, .. }) => password.clone(),
+                _ => None,
+            };
+        }
+    }
+    let redacted = settings.redacted();
+    vault_guard.email_delivery = Some(settings);
+    drop(vault_guard);
+    save_vault(&state).await?;
+
+    log_audit_event(&state, "update_email_settings", "email", None, true, None).await;
+    Ok(redacted)
+}
+
+#[tauri::command]
+pub async fn delete_email_settings(state: State<'_, AppState>) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let mut vault_guard = state.vault.lock().await;
+    if let Some(mut settings) = vault_guard.email_delivery.take() {
+        if let EmailTransport::Smtp {
+            password: Some(password),
+            ..
+        } = &mut settings.transport
+        {
+            password.zeroize();
+        }
+    }
+    drop(vault_guard);
+    save_vault(&state).await?;
+
+    log_audit_event(&state, "delete_email_settings", "email", None, true, None).await;
+    Ok(())
+}
+
+/// Send the test email to the account's addresses and wait for the outcome
+#[tauri::command]
+pub async fn test_email_settings(
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, KeyKeeperError> {
+    guards::require(&state, &[Requirement::Unlocked, Requirement::Account]).await?;
+    let (settings, recipients) = {
+        let vault_guard = state.vault.lock().await;
+        let settings = vault_guard
+            .email_delivery
+            .clone()
+            .ok_or_else(|| KeyKeeperError::not_found("Email settings"))?;
+        let recipients = recipients(&vault_guard, &settings);
+        (settings, recipients)
+    };
+    let mut sent = Vec::new();
+    let mut errors = Vec::new();
+    for recipient in &recipients {
+        let text = settings.render(EmailTemplate::Test, &[("email", recipient)]);
+        match deliver(
+            &state.vault,
+            &settings,
+            EmailTemplate::Test,
+            recipient,
+            &text,
+        )
+        .await
+        {
+            Ok(()) => sent.push(recipient.clone()),
+            Err(e) => errors.push(format!("{}: {}", recipient, e)),
+        }
+    }
+    save_vault(&state).await?;
+    if sent.is_empty() {
+        return Err(KeyKeeperError::Io(errors.join("; ")));
+    }
+    Ok(sent)
+}
+
+/// Delivery log, newest first
+#[tauri::command]
+pub async fn get_email_deliveries(
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<EmailDelivery>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let vault_guard = state.vault.lock().await;
+    Ok(vault_guard
+        .email_deliveries
+        .iter()
+        .rev()
+        .take(limit.unwrap_or(100))
+        .cloned()
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_templates_and_redacts_the_password() {
+        let mut settings = EmailSettings {
+            transport: EmailTransport::Smtp {
+                host: "smtp.example.com".to_string(),
+                port: 587,
+                security: SmtpSecurity::StartTls,
+                username: Some("keykeeper".to_string()),
+                password: Some("smtp-secret".to_string()),
+            },
+            from: "KeyKeeper <keykeeper@example.com>".to_string(),
+            enabled: true,
+            recovery_tokens: true,
+            security_alerts: true,
+            include_backup_email: false,
+            templates: BTreeMap::new(),
+        };
+        assert!(settings.validate().is_ok());
+
+        let text = settings.render(
+            EmailTemplate::RecoveryToken,
+            &[("token", "recovery_123"), ("expires_at", "tomorrow")],
+        );
+        assert!(text.body.contains("Recovery token: recovery_123"));
+        assert!(!text.body.contains("{{"));
+
+        settings.templates.insert(
+            EmailTemplate::SecurityAlert,
+            TemplateText {
+                subject: "[vault] {{event}}".to_string(),
+                body: "{{details}} {{unknown}}".to_string(),
+            },
+        );
+        let text = settings.render(
+            EmailTemplate::SecurityAlert,
+            &[("event", "Master password changed"), ("details", "Done.")],
+        );
+        assert_eq!(text.subject, "[vault] Master password changed");
+        assert_eq!(text.body, "Done. {{unknown}}");
+        assert!(build_message(&settings, "alice@example.com", &text).is_ok());
+
+        let json = serde_json::to_string(&settings.redacted()).unwrap();
+        assert!(!json.contains("smtp-secret"));
+        assert_eq!(mask_address("alice@example.com"), "a••••@example.com");
+        assert!(alert_for_audit("change_master_password", true).is_some());
+        assert!(alert_for_audit("change_master_password", false).is_none());
+
+        settings.from = "not an address".to_string();
+        assert!(settings.validate().is_err());
+    }
+}
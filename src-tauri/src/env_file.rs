@@ -0,0 +1,633 @@
+//! Format-preserving `.env` parser and emitter.
+//!
+//! Syncing keys into a project's `.env` must not disturb what the developer
+//! wrote: comments, blank lines, ordering, `export` prefixes, quoting style,
+//! inline comments and line endings all survive a round trip. Only the
+//! entries that are set are re-rendered, and new ones are appended after the
+//! last entry or, when the variable exists commented out, right below it.
+//!
+//! Syncs go through `PendingWrite`, which collects the changes to one file
+//! so they can be previewed as a unified diff before anything is written.
+//! Previews handed to HTTP clients use `masked_preview`, which masks every
+//! value and leaves out the unchanged lines around the changes.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quote {
+    None,
+    Single,
+    Double,
+    Backtick,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    /// Indentation and `export `
+    prefix: String,
+    name: String,
+    /// `=` with any surrounding spaces
+    separator: String,
+    value: String,
+    quote: Quote,
+    /// Inline comment after the value, with its leading whitespace
+    suffix: String,
+    /// Source text, reused until the value changes
+    raw: Option<String>,
+}
+
+impl Entry {
+    fn render(&self) -> String {
+        if let Some(raw) = &self.raw {
+            return raw.clone();
+        }
+        format!(
+            "{}{}{}{}{}",
+            self.prefix,
+            self.name,
+            self.separator,
+            quote_value(&self.value, self.quote),
+            self.suffix
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Line {
+    Entry(Entry),
+    /// Comment, blank or unparseable line, kept verbatim
+    Other(String),
+}
+
+/// What `set` did to a variable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetOutcome {
+    Added,
+    Updated,
+    Unchanged,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvFile {
+    lines: Vec<Line>,
+    line_ending: &'static str,
+    trailing_newline: bool,
+}
+
+impl Default for EnvFile {
+    fn default() -> Self {
+        Self {
+            lines: Vec::new(),
+            line_ending: "\n",
+            trailing_newline: true,
+        }
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-')
+}
+
+/// Name of a variable on a line like `# FOO=bar` or `#export FOO=bar`
+fn commented_name(line: &str) -> Option<&str> {
+    let rest = line.trim_start().strip_prefix('#')?.trim_start();
+    let rest = rest.strip_prefix("export ").unwrap_or(rest).trim_start();
+    let (name, _) = rest.split_once('=')?;
+    let name = name.trim_end();
+    is_valid_name(name).then_some(name)
+}
+
+/// Parse the value after `=`. Returns the value, its quoting, the rest of
+/// the line after it, and whether a quoted value is still open at the end.
+fn parse_value(rest: &str) -> (String, Quote, String, bool) {
+    let quote = match rest.chars().next() {
+        Some('"') => Quote::Double,
+        Some('\'') => Quote::Single,
+        Some('`') => Quote::Backtick,
+        _ => {
+            let end = rest
+                .char_indices()
+                .find(|&(i, c)| c == '#' && rest[..i].ends_with(char::is_whitespace))
+                .map(|(i, _)| i)
+                .unwrap_or(rest.len());
+            let value = rest[..end].trim_end();
+            return (
+                value.to_string(),
+                Quote::None,
+                rest[value.len()..].to_string(),
+                false,
+            );
+        }
+    };
+
+    let delimiter = rest.as_bytes()[0] as char;
+    let mut value = String::new();
+    let mut chars = rest[1..].char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c == delimiter {
+            return (value, quote, rest[1 + i + 1..].to_string(), false);
+        }
+        if c == '\\' && quote == Quote::Double {
+            match chars.next() {
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 'r')) => value.push('\r'),
+                Some((_, 't')) => value.push('\t'),
+                Some((_, c @ ('"' | '\\' | '$'))) => value.push(c),
+                Some((_, c)) => {
+                    value.push('\\');
+                    value.push(c);
+                }
+                None => value.push('\\'),
+            }
+            continue;
+        }
+        value.push(c);
+    }
+    (value, quote, String::new(), true)
+}
+
+fn needs_quotes(value: &str) -> bool {
+    value.is_empty()
+        || value.starts_with(char::is_whitespace)
+        || value.ends_with(char::is_whitespace)
+        || value.contains(['#', '"', '\'', '`', '\n', '\r', '\\', '$'])
+}
+
+/// Render a value in `quote` style, or in double quotes when that style
+/// can't hold it
+fn quote_value(value: &str, quote: Quote) -> String {
+    match quote {
+        Quote::None if value.is_empty() => return String::new(),
+        Quote::None if !needs_quotes(value) => return value.to_string(),
+        Quote::Single if !value.contains(['\'', '\n', '\r']) => {
+            return format!("'{}'", value);
+        }
+        Quote::Backtick if !value.contains(['`', '\n', '\r']) => {
+            return format!("`{}`", value);
+        }
+        _ => {}
+    }
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl EnvFile {
+    pub fn parse(content: &str) -> Self {
+        let line_ending = if content.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        let trailing_newline = content.is_empty() || content.ends_with('\n');
+        let source: Vec<&str> = content.lines().collect();
+        let mut lines = Vec::new();
+
+        let mut i = 0;
+        while i < source.len() {
+            let line = source[i];
+            i += 1;
+
+            let trimmed = line.trim_start();
+            let indent = &line[..line.len() - trimmed.len()];
+            let body = trimmed
+                .strip_prefix("export ")
+                .map(str::trim_start)
+                .unwrap_or(trimmed);
+            let Some((name, rest)) = body.split_once('=') else {
+                lines.push(Line::Other(line.to_string()));
+                continue;
+            };
+            let name_trimmed = name.trim_end();
+            if trimmed.starts_with('#') || !is_valid_name(name_trimmed) {
+                lines.push(Line::Other(line.to_string()));
+                continue;
+            }
+            let value_start = rest.trim_start();
+            let separator = format!(
+                "{}={}",
+                &name[name_trimmed.len()..],
+                &rest[..rest.len() - value_start.len()]
+            );
+            let prefix = format!("{}{}", indent, &trimmed[..trimmed.len() - body.len()]);
+
+            // Quoted values may span lines
+            let mut raw = line.to_string();
+            let mut text = value_start.to_string();
+            let (mut value, mut quote, mut suffix, mut open) = parse_value(&text);
+            let start = i;
+            while open && i < source.len() {
+                raw.push_str(line_ending);
+                raw.push_str(source[i]);
+                text.push('\n');
+                text.push_str(source[i]);
+                i += 1;
+                (value, quote, suffix, open) = parse_value(&text);
+            }
+            if open {
+                // Never closed: keep the lines as they were
+                i = start;
+                lines.push(Line::Other(line.to_string()));
+                continue;
+            }
+
+            lines.push(Line::Entry(Entry {
+                prefix,
+                name: name_trimmed.to_string(),
+                separator,
+                value,
+                quote,
+                suffix,
+                raw: Some(raw),
+            }));
+        }
+
+        Self {
+            lines,
+            line_ending,
+            trailing_newline,
+        }
+    }
+
+    /// A copy with every value replaced by its mask
+    fn masked(&self) -> Self {
+        let mut masked = self.clone();
+        for line in masked.lines.iter_mut() {
+            if let Line::Entry(entry) = line {
+                entry.value = crate::masking::mask_value(&entry.value);
+                entry.raw = None;
+            }
+        }
+        masked
+    }
+
+    pub fn write(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.lines.iter().filter_map(|line| match line {
+            Line::Entry(entry) => Some(entry),
+            Line::Other(_) => None,
+        })
+    }
+
+    /// Variables in file order; the last assignment wins for repeats
+    pub fn vars(&self) -> Vec<(String, String)> {
+        let mut vars: Vec<(String, String)> = Vec::new();
+        for entry in self.entries() {
+            match vars.iter_mut().find(|(name, _)| *name == entry.name) {
+                Some(var) => var.1 = entry.value.clone(),
+                None => vars.push((entry.name.clone(), entry.value.clone())),
+            }
+        }
+        vars
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries()
+            .filter(|entry| entry.name == name)
+            .last()
+            .map(|entry| entry.value.as_str())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Whether the variable only appears commented out
+    pub fn is_commented_out(&self, name: &str) -> bool {
+        !self.contains(name)
+            && self.lines.iter().any(|line| match line {
+                Line::Other(text) => commented_name(text) == Some(name),
+                Line::Entry(_) => false,
+            })
+    }
+
+    /// Set a variable, updating its last assignment in place. A new variable
+    /// goes below its commented-out line if there is one, otherwise after the
+    /// last entry.
+    pub fn set(&mut self, name: &str, value: &str) -> SetOutcome {
+        let existing = self.lines.iter_mut().rev().find_map(|line| match line {
+            Line::Entry(entry) if entry.name == name => Some(entry),
+            _ => None,
+        });
+        if let Some(entry) = existing {
+            if entry.value == value {
+                return SetOutcome::Unchanged;
+            }
+            entry.value = value.to_string();
+            entry.raw = None;
+            return SetOutcome::Updated;
+        }
+
+        let entry = Line::Entry(Entry {
+            prefix: String::new(),
+            name: name.to_string(),
+            separator: "=".to_string(),
+            value: value.to_string(),
+            quote: Quote::None,
+            suffix: String::new(),
+            raw: None,
+        });
+        let commented = self.lines.iter().rposition(|line| match line {
+            Line::Other(text) => commented_name(text) == Some(name),
+            Line::Entry(_) => false,
+        });
+        let last_entry = self
+            .lines
+            .iter()
+            .rposition(|line| matches!(line, Line::Entry(_)));
+        match commented.or(last_entry) {
+            Some(index) => self.lines.insert(index + 1, entry),
+            None => {
+                if self.lines.last().is_some_and(|line| match line {
+                    Line::Other(text) => !text.trim().is_empty(),
+                    Line::Entry(_) => true,
+                }) {
+                    self.lines.push(Line::Other(String::new()));
+                }
+                self.lines.push(entry);
+            }
+        }
+        self.trailing_newline = true;
+        SetOutcome::Added
+    }
+
+    /// Rename every assignment of a variable, keeping values and formatting.
+    /// Returns false when there's nothing to rename or `to` already exists.
+    pub fn rename(&mut self, from: &str, to: &str) -> bool {
+        if from == to || !self.contains(from) || self.contains(to) {
+            return false;
+        }
+        for line in &mut self.lines {
+            if let Line::Entry(entry) = line {
+                if entry.name == from {
+                    entry.name = to.to_string();
+                    entry.raw = None;
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Changes to one file, previewed or written as a whole
+#[derive(Debug, Clone)]
+pub struct PendingWrite {
+    pub path: PathBuf,
+    /// Contents on disk; unset when the file doesn't exist yet
+    before: Option<String>,
+    file: EnvFile,
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+}
+
+/// Check a file name sent along with a sync: it's joined to the project
+/// directory, so it may not be absolute or climb out of it
+pub fn validate_file_name(name: &str) -> Result<(), String> {
+    let path = Path::new(name);
+    let escapes = path.components().any(|c| {
+        !matches!(
+            c,
+            std::path::Component::Normal(_) | std::path::Component::CurDir
+        )
+    });
+    if name.trim().is_empty() || escapes || name.starts_with(['/', '\\']) || name.contains("..") {
+        return Err(format!("Invalid .env file name {:?}", name));
+    }
+    Ok(())
+}
+
+/// What a sync would do to one file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncPreview {
+    pub path: String,
+    /// The file doesn't exist and would be created
+    pub created: bool,
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    /// Unified diff of the file; empty when nothing changes
+    pub diff: String,
+}
+
+impl PendingWrite {
+    pub fn open(path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let path = path.into();
+        let before = match std::fs::read_to_string(&path) {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            file: before.as_deref().map(EnvFile::parse).unwrap_or_default(),
+            path,
+            before,
+            added: Vec::new(),
+            updated: Vec::new(),
+        })
+    }
+
+    pub fn file(&self) -> &EnvFile {
+        &self.file
+    }
+
+    /// Contents on disk when opened; `None` if the file didn't exist
+    pub fn before(&self) -> Option<&str> {
+        self.before.as_deref()
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) -> SetOutcome {
+        let outcome = self.file.set(name, value);
+        match outcome {
+            SetOutcome::Added => self.added.push(name.to_string()),
+            SetOutcome::Updated if !self.added.iter().any(|n| n == name) => {
+                self.updated.push(name.to_string())
+            }
+            _ => {}
+        }
+        outcome
+    }
+
+    /// Rename a variable; the new name is reported as updated
+    pub fn rename(&mut self, from: &str, to: &str) -> bool {
+        let renamed = self.file.rename(from, to);
+        if renamed {
+            self.updated.push(to.to_string());
+        }
+        renamed
+    }
+
+    pub fn is_changed(&self) -> bool {
+        !self.added.is_empty() || !self.updated.is_empty()
+    }
+
+    pub fn preview(&self) -> SyncPreview {
+        let before = self.before.clone().unwrap_or_default();
+        self.preview_between(&before, &self.file.to_string(), 3)
+    }
+
+    /// `preview` with every value masked and no context lines, for callers
+    /// that may not read the file or the secrets written to it
+    pub fn masked_preview(&self) -> SyncPreview {
+        let before = self
+            .before
+            .as_deref()
+            .map(|content| EnvFile::parse(content).masked().to_string())
+            .unwrap_or_default();
+        self.preview_between(&before, &self.file.masked().to_string(), 0)
+    }
+
+    fn preview_between(&self, before: &str, after: &str, context: usize) -> SyncPreview {
+        let path = self.path.to_string_lossy().to_string();
+        let diff = if self.is_changed() {
+            let old_name = if self.before.is_some() {
+                path.as_str()
+            } else {
+                "/dev/null"
+            };
+            similar::TextDiff::from_lines(before, after)
+                .unified_diff()
+                .context_radius(context)
+                .header(old_name, &path)
+                .to_string()
+        } else {
+            String::new()
+        };
+        SyncPreview {
+            created: self.before.is_none() && self.is_changed(),
+            added: self.added.clone(),
+            updated: self.updated.clone(),
+            diff,
+            path,
+        }
+    }
+
+    /// Write the file if anything changed
+    pub fn commit(&self) -> std::io::Result<()> {
+        if self.is_changed() {
+            self.file.write(&self.path)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for EnvFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, line) in self.lines.iter().enumerate() {
+            if i > 0 {
+                f.write_str(self.line_ending)?;
+            }
+            match line {
+                Line::Entry(entry) => f.write_str(&entry.render())?,
+                Line::Other(text) => f.write_str(text)?,
+            }
+        }
+        if self.trailing_newline && !self.lines.is_empty() {
+            f.write_str(self.line_ending)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_and_updates_in_place() {
+        let content = "# Stripe\r\nexport STRIPE_API_KEY='sk_old' # test key\r\n\r\n\
+                       MULTI=\"a\r\nb\"\r\n# OPENAI_API_KEY=sk-disabled\r\nPLAIN = x y # note";
+        let mut file = EnvFile::parse(content);
+        assert_eq!(file.to_string(), content);
+        assert_eq!(file.get("MULTI"), Some("a\nb"));
+        assert_eq!(file.get("PLAIN"), Some("x y"));
+        assert!(file.is_commented_out("OPENAI_API_KEY"));
+
+        assert_eq!(file.set("STRIPE_API_KEY", "sk_new"), SetOutcome::Updated);
+        assert_eq!(file.set("PLAIN", "x y"), SetOutcome::Unchanged);
+        assert_eq!(file.set("OPENAI_API_KEY", "sk \"q\""), SetOutcome::Added);
+        assert_eq!(file.set("NEW_KEY", "v"), SetOutcome::Added);
+        assert_eq!(
+            file.to_string(),
+            "# Stripe\r\nexport STRIPE_API_KEY='sk_new' # test key\r\n\r\n\
+             MULTI=\"a\r\nb\"\r\n# OPENAI_API_KEY=sk-disabled\r\n\
+             OPENAI_API_KEY=\"sk \\\"q\\\"\"\r\nPLAIN = x y # note\r\nNEW_KEY=v\r\n"
+        );
+        assert_eq!(
+            EnvFile::parse(&file.to_string()).get("OPENAI_API_KEY"),
+            Some("sk \"q\"")
+        );
+
+        assert!(file.rename("STRIPE_API_KEY", "STRIPE_DEV_KEY"));
+        assert!(!file.rename("PLAIN", "STRIPE_DEV_KEY"));
+        assert!(file
+            .to_string()
+            .starts_with("# Stripe\r\nexport STRIPE_DEV_KEY='sk_new' # test key\r\n"));
+    }
+
+    #[test]
+    fn masked_previews_show_neither_secrets_nor_surrounding_lines() {
+        let dir = std::env::temp_dir().join(format!("kk-env-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".env");
+        std::fs::write(
+            &path,
+            "DATABASE_URL=postgres://app:hunter2@db/app\nSTRIPE_API_KEY=sk_live_old_1111\n",
+        )
+        .unwrap();
+        let mut pending = PendingWrite::open(&path).unwrap();
+        pending.set("STRIPE_API_KEY", "sk_live_new_2222");
+        pending.set("OPENAI_API_KEY", "sk-proj-3333");
+        let full = pending.preview();
+        let masked = pending.masked_preview();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(full.diff.contains("hunter2") && full.diff.contains("sk_live_new_2222"));
+        for hidden in [
+            "DATABASE_URL",
+            "hunter2",
+            "sk_live_old",
+            "sk_live_new",
+            "sk-proj",
+        ] {
+            assert!(!masked.diff.contains(hidden), "{}", hidden);
+        }
+        assert!(masked.diff.contains("-STRIPE_API_KEY="));
+        assert!(masked.diff.contains("+STRIPE_API_KEY="));
+        assert!(masked.diff.contains("+OPENAI_API_KEY="));
+        assert_eq!((masked.added, masked.updated), (full.added, full.updated));
+    }
+
+    #[test]
+    fn file_names_stay_inside_the_project() {
+        for name in [".env", ".env.local", "config/.env", "./.env"] {
+            assert!(validate_file_name(name).is_ok(), "{}", name);
+        }
+        for name in [
+            "",
+            "/etc/passwd",
+            "\\\\server\\share\\.env",
+            "../.env",
+            "config/../../.env",
+            "..",
+        ] {
+            assert!(validate_file_name(name).is_err(), "{}", name);
+        }
+    }
+}
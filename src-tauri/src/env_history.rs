@@ -0,0 +1,428 @@
+//! Undo history for `.env` writes.
+//!
+//! Before a sync changes a `.env` file, its previous contents are saved to
+//! `env-history/` in the app data dir, encrypted with the vault key, and the
+//! change is added to `env-history/index.json` (paths and variable names
+//! only). `revert_env_file_change` puts the old contents back, recording the
+//! revert itself as a change so it can be undone too. Only the most recent
+//! changes are kept, per file and overall.
+
+use crate::env_file::PendingWrite;
+use crate::errors::KeyKeeperError;
+use crate::{decrypt_data, encrypt_data, guards, log_audit_event, ApiKeyVault, AppState};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tauri::State;
+use tracing::{info, warn};
+use zeroize::Zeroizing;
+
+const HISTORY_DIR: &str = "env-history";
+const INDEX_FILE: &str = "index.json";
+const MAX_CHANGES_PER_FILE: usize = 20;
+const MAX_CHANGES: usize = 500;
+
+static HISTORY_DIR_PATH: OnceLock<PathBuf> = OnceLock::new();
+/// Serializes read-modify-write of the index
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvFileChange {
+    pub id: String,
+    pub path: String,
+    pub project_path: Option<String>,
+    /// What made the change, e.g. `sync_key_to_env` or `revert`
+    pub action: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub timestamp: DateTime<Utc>,
+    /// Whether the file existed before; reverting removes it otherwise
+    pub existed: bool,
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    /// SHA-256 of the file as written; unset when the change removed it
+    pub after_sha256: Option<String>,
+    /// Snapshot encrypted with the vault key (false for unencrypted vaults)
+    pub encrypted: bool,
+    /// Change undone by this one, for reverts
+    pub reverts: Option<String>,
+    #[serde(default, with = "crate::timestamps::rfc3339_option")]
+    pub reverted_at: Option<DateTime<Utc>>,
+}
+
+/// A change as listed, with whether the file was edited since
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvFileChangeInfo {
+    #[serde(flatten)]
+    pub change: EnvFileChange,
+    pub modified_since: bool,
+}
+
+pub fn init(app_data_dir: &Path) {
+    let _ = HISTORY_DIR_PATH.set(app_data_dir.join(HISTORY_DIR));
+}
+
+fn history_dir() -> Result<&'static PathBuf, String> {
+    HISTORY_DIR_PATH
+        .get()
+        .ok_or_else(|| "Env file history isn't initialized".to_string())
+}
+
+fn snapshot_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.snap", id))
+}
+
+fn sha256(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+/// Key snapshots are encrypted with; `None` for an unencrypted vault
+pub type SnapshotKey = Option<Zeroizing<Vec<u8>>>;
+
+/// Key for snapshots: the vault's, or `None` for an unencrypted vault
+pub fn snapshot_key(vault: &ApiKeyVault) -> Result<SnapshotKey, String> {
+    match vault.encryption_key.as_deref() {
+        None => Ok(None),
+        Some("[ENCRYPTED]") => Err("Vault key isn't available".to_string()),
+        Some(key) => {
+            let bytes = Zeroizing::new(
+                general_purpose::STANDARD
+                    .decode(key)
+                    .map_err(|e| format!("Failed to decode encryption key: {}", e))?,
+            );
+            if bytes.len() != 32 {
+                return Err("Invalid encryption key length".to_string());
+            }
+            Ok(Some(bytes))
+        }
+    }
+}
+
+fn read_index(dir: &Path) -> Vec<EnvFileChange> {
+    fs::read_to_string(dir.join(INDEX_FILE))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn write_index(dir: &Path, changes: &[EnvFileChange]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(changes)
+        .map_err(|e| format!("Failed to serialize env file history: {}", e))?;
+    crate::vault_location::write_atomic(&dir.join(INDEX_FILE), json.as_bytes())
+        .map_err(|e| format!("Failed to write env file history: {}", e))
+}
+
+/// Drop the oldest changes beyond the limits; returns the ids dropped
+fn prune(changes: &mut Vec<EnvFileChange>) -> Vec<String> {
+    let mut per_file: std::collections::HashMap<String, usize> = Default::default();
+    let mut keep = vec![false; changes.len()];
+    let mut kept = 0;
+    for (i, change) in changes.iter().enumerate().rev() {
+        let count = per_file.entry(change.path.clone()).or_insert(0);
+        if *count < MAX_CHANGES_PER_FILE && kept < MAX_CHANGES {
+            *count += 1;
+            kept += 1;
+            keep[i] = true;
+        }
+    }
+    let mut dropped = Vec::new();
+    let mut keep = keep.into_iter();
+    changes.retain(|change| {
+        let keep = keep.next().unwrap_or(true);
+        if !keep {
+            dropped.push(change.id.clone());
+        }
+        keep
+    });
+    dropped
+}
+
+fn write_snapshot(dir: &Path, id: &str, content: &str) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create history dir: {}", e))?;
+    let path = snapshot_path(dir, id);
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        let _ = fs::set_permissions(dir, fs::Permissions::from_mode(0o700));
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)
+            .map_err(|e| format!("Failed to create snapshot: {}", e))?;
+        file.write_all(content.as_bytes())
+            .map_err(|e| format!("Failed to write snapshot: {}", e))?;
+    }
+    #[cfg(not(unix))]
+    fs::write(&path, content).map_err(|e| format!("Failed to write snapshot: {}", e))?;
+    Ok(())
+}
+
+struct NewChange<'a> {
+    path: &'a Path,
+    project_path: Option<&'a str>,
+    action: &'a str,
+    before: Option<&'a str>,
+    after: Option<&'a str>,
+    added: Vec<String>,
+    updated: Vec<String>,
+    reverts: Option<String>,
+}
+
+/// Save the snapshot of `before`, run `apply`, then add the change to the
+/// index. Nothing is recorded if `apply` fails.
+fn record(
+    key: &SnapshotKey,
+    new: NewChange<'_>,
+    apply: impl FnOnce() -> Result<(), String>,
+) -> Result<EnvFileChange, String> {
+    let dir = history_dir()?;
+    let id = uuid::Uuid::new_v4().to_string();
+
+    let before = new.before.unwrap_or("");
+    let snapshot = match key {
+        Some(key) => encrypt_data(before, key)?,
+        None => before.to_string(),
+    };
+    write_snapshot(dir, &id, &snapshot)?;
+    if let Err(e) = apply() {
+        let _ = fs::remove_file(snapshot_path(dir, &id));
+        return Err(e);
+    }
+
+    let change = EnvFileChange {
+        id,
+        path: new.path.to_string_lossy().to_string(),
+        project_path: new.project_path.map(str::to_string),
+        action: new.action.to_string(),
+        timestamp: Utc::now(),
+        existed: new.before.is_some(),
+        added: new.added,
+        updated: new.updated,
+        after_sha256: new.after.map(sha256),
+        encrypted: key.is_some(),
+        reverts: new.reverts,
+        reverted_at: None,
+    };
+
+    let _guard = INDEX_LOCK.lock().unwrap();
+    let mut changes = read_index(dir);
+    changes.push(change.clone());
+    for id in prune(&mut changes) {
+        let _ = fs::remove_file(snapshot_path(dir, &id));
+    }
+    write_index(dir, &changes)?;
+    Ok(change)
+}
+
+/// Write a pending `.env` change, keeping a snapshot to revert it. Returns
+/// the change id, or `None` when there was nothing to write.
+pub fn commit(
+    pending: &PendingWrite,
+    vault: &ApiKeyVault,
+    project_path: Option<&str>,
+    action: &str,
+) -> Result<Option<String>, String> {
+    if !pending.is_changed() {
+        return Ok(None);
+    }
+    commit_with_key(pending, &snapshot_key(vault)?, project_path, action)
+}
+
+/// `commit` with the snapshot key already taken from the vault, so files
+/// can be written without holding the vault
+pub fn commit_with_key(
+    pending: &PendingWrite,
+    key: &SnapshotKey,
+    project_path: Option<&str>,
+    action: &str,
+) -> Result<Option<String>, String> {
+    if !pending.is_changed() {
+        return Ok(None);
+    }
+    let after = pending.file().to_string();
+    let change = record(
+        key,
+        NewChange {
+            path: &pending.path,
+            project_path,
+            action,
+            before: pending.before(),
+            after: Some(&after),
+            added: pending.added.clone(),
+            updated: pending.updated.clone(),
+            reverts: None,
+        },
+        || pending.commit().map_err(|e| e.to_string()),
+    )?;
+    Ok(Some(change.id))
+}
+
+fn belongs_to(change: &EnvFileChange, project_path: &str) -> bool {
+    change.project_path.as_deref() == Some(project_path)
+        || Path::new(&change.path).starts_with(project_path)
+}
+
+/// Changes to the project's `.env` files, newest first
+#[tauri::command]
+pub async fn list_env_file_changes(
+    project_path: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<EnvFileChangeInfo>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let dir = history_dir()?;
+    let changes = {
+        let _guard = INDEX_LOCK.lock().unwrap();
+        read_index(dir)
+    };
+    Ok(changes
+        .into_iter()
+        .rev()
+        .filter(|change| belongs_to(change, &project_path))
+        .map(|change| {
+            let current = fs::read_to_string(&change.path).ok();
+            let modified_since = current.as_deref().map(sha256) != change.after_sha256;
+            EnvFileChangeInfo {
+                change,
+                modified_since,
+            }
+        })
+        .collect())
+}
+
+/// Restore a file to how it was before a change. Edits made since are
+/// replaced too, but the revert is itself recorded and can be undone.
+#[tauri::command]
+pub async fn revert_env_file_change(
+    change_id: String,
+    state: State<'_, AppState>,
+) -> Result<EnvFileChange, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let dir = history_dir()?;
+    let change = {
+        let _guard = INDEX_LOCK.lock().unwrap();
+        read_index(dir).into_iter().find(|c| c.id == change_id)
+    }
+    .ok_or_else(|| KeyKeeperError::NotFound(format!("Env file change {} not found", change_id)))?;
+    if change.reverted_at.is_some() {
+        return Err(KeyKeeperError::Conflict(
+            "This change has already been reverted".to_string(),
+        ));
+    }
+
+    let vault_guard = state.vault.lock().await;
+    let snapshot = fs::read_to_string(snapshot_path(dir, &change.id)).map_err(|e| {
+        KeyKeeperError::Io(format!(
+            "Snapshot for change {} is missing: {}",
+            change.id, e
+        ))
+    })?;
+    let restored = Zeroizing::new(if change.encrypted {
+        let key = snapshot_key(&vault_guard)
+            .map_err(KeyKeeperError::Crypto)?
+            .ok_or_else(|| {
+                KeyKeeperError::Crypto("Snapshot is encrypted but the vault has no key".to_string())
+            })?;
+        decrypt_data(&snapshot, &key).map_err(KeyKeeperError::Crypto)?
+    } else {
+        snapshot
+    });
+
+    let path = PathBuf::from(&change.path);
+    let current = match fs::read_to_string(&path) {
+        Ok(content) => Some(Zeroizing::new(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+    let revert = record(
+        &snapshot_key(&vault_guard).map_err(KeyKeeperError::Crypto)?,
+        NewChange {
+            path: &path,
+            project_path: change.project_path.as_deref(),
+            action: "revert",
+            before: current.as_deref().map(String::as_str),
+            after: change.existed.then_some(restored.as_str()),
+            added: Vec::new(),
+            updated: Vec::new(),
+            reverts: Some(change.id.clone()),
+        },
+        || {
+            let result = if change.existed {
+                fs::write(&path, restored.as_bytes())
+            } else {
+                match fs::remove_file(&path) {
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                    result => result,
+                }
+            };
+            result.map_err(|e| format!("Failed to restore {}: {}", path.display(), e))
+        },
+    )
+    .map_err(KeyKeeperError::Io)?;
+    drop(vault_guard);
+
+    {
+        let _guard = INDEX_LOCK.lock().unwrap();
+        let mut changes = read_index(dir);
+        if let Some(original) = changes.iter_mut().find(|c| c.id == change.id) {
+            original.reverted_at = Some(revert.timestamp);
+        }
+        if let Err(e) = write_index(dir, &changes) {
+            warn!(
+                "Failed to mark env file change {} reverted: {}",
+                change.id, e
+            );
+        }
+    }
+
+    info!("Reverted env file change {} on {}", change.id, change.path);
+    log_audit_event(
+        &state,
+        "revert_env_file_change",
+        "env_file",
+        Some(&change.id),
+        true,
+        Some(&change.path),
+    )
+    .await;
+
+    Ok(revert)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prune_keeps_newest_per_file() {
+        let change = |id: usize, path: &str| EnvFileChange {
+            id: id.to_string(),
+            path: path.to_string(),
+            project_path: None,
+            action: "sync_key_to_env".to_string(),
+            timestamp: Utc::now(),
+            existed: true,
+            added: Vec::new(),
+            updated: Vec::new(),
+            after_sha256: None,
+            encrypted: false,
+            reverts: None,
+            reverted_at: None,
+        };
+        let mut changes: Vec<_> = (0..MAX_CHANGES_PER_FILE + 2)
+            .map(|i| change(i, "/p/.env"))
+            .collect();
+        changes.insert(0, change(999, "/p/.env.local"));
+
+        let dropped = prune(&mut changes);
+        assert_eq!(dropped, vec!["0".to_string(), "1".to_string()]);
+        assert_eq!(changes.len(), MAX_CHANGES_PER_FILE + 1);
+        assert_eq!(changes[0].id, "999");
+    }
+}
@@ -0,0 +1,670 @@
+//! Reviewed imports of `.env` files.
+//!
+//! `analyze_env_import` reads a file and proposes what to do with each
+//! variable, with a confidence between 0 and 1 and the reasons behind it:
+//!
+//! - whether it's a secret: known key formats, credentials in URLs, and the
+//!   name and value heuristics used when parsing env files
+//! - its provider, from the provider catalog's env var names and prefixes
+//! - whether the vault has it already, by value or by name in the project
+//! - the environment, from `NODE_ENV` and friends or the file name, unless
+//!   the provider's key format tells it (see `key_environment`)
+//!
+//! Secrets that aren't in the vault are created, ones whose name the project
+//! already has update that key's value for the environment, and the rest
+//! (configuration, duplicates, empty values) are skipped. The proposal
+//! carries no values; `import_env_variables` reads the file again and
+//! applies the decisions the user kept or changed.
+
+use crate::api_generator::ApiProvider;
+use crate::docker_env::normalize_environment;
+use crate::errors::KeyKeeperError;
+use crate::{
+    activity, env_values, guards, key_environment, log_audit_event, save_vault, ApiKey,
+    ApiKeyVault, AppState,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::State;
+
+/// Variables naming the environment an env file is for
+const ENVIRONMENT_VARS: &[&str] = &["APP_ENV", "NODE_ENV", "RAILS_ENV", "ENVIRONMENT", "ENV"];
+const KNOWN_ENVIRONMENTS: &[&str] = &["development", "staging", "production", "test"];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportAction {
+    Create,
+    /// Set the value of `key_id` for the file's environment
+    Update,
+    Skip,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariableProposal {
+    pub name: String,
+    /// The value for display, masked when it's a secret
+    pub preview: String,
+    pub is_secret: bool,
+    /// How sure `is_secret` is
+    pub secret_confidence: f64,
+    pub provider: Option<String>,
+    pub provider_confidence: f64,
+    /// Vault key the variable matches, by value or by name
+    pub key_id: Option<String>,
+    pub key_name: Option<String>,
+    /// Where the value goes: the file's environment, or the one the key's
+    /// format tells
+    pub environment: String,
+    pub action: ImportAction,
+    pub confidence: f64,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvImportProposal {
+    pub path: String,
+    pub file_name: String,
+    pub project_path: String,
+    pub package_path: Option<String>,
+    pub environment: String,
+    pub environment_confidence: f64,
+    pub variables: Vec<VariableProposal>,
+}
+
+/// Whether `value` is a secret, and how sure that is
+pub fn classify_secret(name: &str, value: &str) -> (bool, f64, String) {
+    if value.is_empty() {
+        return (false, 1.0, "Empty value".to_string());
+    }
+    if let Some(format) = crate::git_history::known_format(value) {
+        return (true, 0.99, format!("Looks like a {}", format));
+    }
+    if url_has_password(value) {
+        return (true, 0.85, "URL with a password".to_string());
+    }
+    let plain = value.eq_ignore_ascii_case("true")
+        || value.eq_ignore_ascii_case("false")
+        || value.parse::<f64>().is_ok()
+        || KNOWN_ENVIRONMENTS.contains(&normalize_environment(value).as_str());
+    let by_name = crate::is_secret_variable(name, "");
+    let by_value = crate::is_secret_variable("", value);
+    match (by_name, by_value) {
+        _ if plain => (false, 0.8, "Looks like a setting".to_string()),
+        (true, true) => (true, 0.9, "Secret-like name and value".to_string()),
+        (true, false) => (true, 0.7, "Secret-like name".to_string()),
+        (false, true) => (true, 0.6, "Secret-like value".to_string()),
+        (false, false) => (false, 0.75, "Looks like configuration".to_string()),
+    }
+}
+
+fn url_has_password(value: &str) -> bool {
+    let Some((_, rest)) = value.split_once("://") else {
+        return false;
+    };
+    let authority = rest.split('/').next().unwrap_or_default();
+    authority.rsplit_once('@').is_some_and(|(user_info, _)| {
+        user_info
+            .split_once(':')
+            .is_some_and(|(_, p)| !p.is_empty())
+    })
+}
+
+pub fn preview(value: &str, is_secret: bool) -> String {
+    if !is_secret {
+        return value.to_string();
+    }
+    let shown: String = value.chars().take(4).collect();
+    format!("{}… ({} chars)", shown, value.chars().count())
+}
+
+/// Provider of a variable: catalog env var names, then the formats of known
+/// keys, then catalog prefixes, then the services already in the vault
+pub fn detect_provider(
+    name: &str,
+    value: &str,
+    providers: &[ApiProvider],
+    services: &[String],
+) -> Option<(String, f64)> {
+    if let Some(p) = providers
+        .iter()
+        .find(|p| p.env_patterns.iter().any(|e| e == name))
+    {
+        return Some((p.id.clone(), 0.9));
+    }
+    if let Some(format) = crate::git_history::known_format(value) {
+        let provider = format.split_whitespace().next().unwrap_or_default();
+        if provider != "Private" {
+            return Some((provider.to_lowercase(), 0.85));
+        }
+    }
+    if let Some(p) = providers
+        .iter()
+        .find(|p| p.key_patterns.iter().any(|k| name.starts_with(k.as_str())))
+    {
+        return Some((p.id.clone(), 0.7));
+    }
+    let prefix = name.split('_').next().unwrap_or_default().to_lowercase();
+    services
+        .iter()
+        .find(|service| !prefix.is_empty() && service.to_lowercase().replace(' ', "") == prefix)
+        .map(|service| (service.clone(), 0.5))
+}
+
+/// The file's environment and how sure that is
+pub fn infer_environment(path: &str, vars: &[(String, String)]) -> (String, f64) {
+    for name in ENVIRONMENT_VARS {
+        if let Some((_, value)) = vars.iter().find(|(n, _)| n == name) {
+            let environment = normalize_environment(value);
+            if KNOWN_ENVIRONMENTS.contains(&environment.as_str()) {
+                return (environment, 0.95);
+            }
+        }
+    }
+    if let Some(environment) = env_values::file_environment(path) {
+        return (environment, 0.9);
+    }
+    let local = Path::new(path)
+        .file_name()
+        .is_some_and(|name| name == ".env.local");
+    ("development".to_string(), if local { 0.7 } else { 0.5 })
+}
+
+enum Existing<'a> {
+    SameValue(&'a ApiKey),
+    SameName(&'a ApiKey),
+    NameElsewhere,
+}
+
+fn find_existing<'a>(
+    vault: &'a ApiKeyVault,
+    name: &str,
+    value: &str,
+    project_path: &str,
+) -> Option<Existing<'a>> {
+    let keys = || vault.keys.values().filter(|k| k.archived_at.is_none());
+    if let Some(key) = keys().find(|k| {
+        env_values::environments(k)
+            .iter()
+            .any(|env| env_values::value_for(k, env) == Some(value))
+    }) {
+        return Some(Existing::SameValue(key));
+    }
+    if let Some(key) = keys().find(|k| {
+        k.name == name && k.project_path.as_deref() == Some(project_path) && k.kind.is_env_value()
+    }) {
+        return Some(Existing::SameName(key));
+    }
+    keys()
+        .any(|k| k.name == name)
+        .then_some(Existing::NameElsewhere)
+}
+
+pub fn propose(
+    name: &str,
+    value: &str,
+    vault: &ApiKeyVault,
+    project_path: &str,
+    environment: &str,
+    providers: &[ApiProvider],
+) -> VariableProposal {
+    let (is_secret, secret_confidence, secret_reason) = classify_secret(name, value);
+    let services: Vec<String> = vault.keys.values().map(|k| k.service.clone()).collect();
+    let provider = detect_provider(name, value, providers, &services);
+    let inference = key_environment::infer(value, providers);
+    let environment = inference
+        .as_ref()
+        .map_or(environment, |i| i.environment.as_str());
+    let mut proposal = VariableProposal {
+        name: name.to_string(),
+        preview: preview(value, is_secret),
+        is_secret,
+        secret_confidence,
+        provider_confidence: provider.as_ref().map_or(0.0, |(_, c)| *c),
+        provider: provider.map(|(p, _)| p),
+        key_id: None,
+        key_name: None,
+        environment: environment.to_string(),
+        action: ImportAction::Skip,
+        confidence: secret_confidence,
+        reasons: vec![secret_reason],
+    };
+    if let Some(inference) = &inference {
+        proposal.reasons.push(format!(
+            "{} key for {}",
+            inference.provider, inference.environment
+        ));
+    }
+    if value.is_empty() {
+        return proposal;
+    }
+
+    match find_existing(vault, name, value, project_path) {
+        Some(Existing::SameValue(key)) => {
+            proposal.key_id = Some(key.id.clone());
+            proposal.key_name = Some(key.name.clone());
+            proposal.confidence = 0.95;
+            proposal
+                .reasons
+                .push(format!("Already in the vault as {}", key.name));
+        }
+        _ if !is_secret => {}
+        Some(Existing::SameName(key)) => {
+            proposal.key_id = Some(key.id.clone());
+            proposal.key_name = Some(key.name.clone());
+            proposal.action = ImportAction::Update;
+            proposal.confidence = 0.85;
+            proposal
+                .reasons
+                .push(if env_values::has_environment(key, environment) {
+                    format!("Replaces the {} value of {}", environment, key.name)
+                } else {
+                    format!("Adds a {} value to {}", environment, key.name)
+                });
+        }
+        Some(Existing::NameElsewhere) => {
+            proposal.action = ImportAction::Create;
+            proposal.confidence = secret_confidence * 0.8;
+            proposal
+                .reasons
+                .push("A key with this name exists in another project".to_string());
+        }
+        None => {
+            proposal.action = ImportAction::Create;
+            proposal.reasons.push("Not in the vault yet".to_string());
+        }
+    }
+    proposal
+}
+
+pub async fn providers(state: &AppState) -> Vec<ApiProvider> {
+    let generator = state.api_generator.lock().await;
+    let service = generator.service.lock().await;
+    service.get_providers()
+}
+
+pub fn read_vars(file_path: &str) -> Result<Vec<(String, String)>, KeyKeeperError> {
+    let content = std::fs::read_to_string(file_path)?;
+    Ok(crate::env_file::EnvFile::parse(&content).vars())
+}
+
+pub fn package_path(project_path: &str, file_path: &str) -> Option<String> {
+    let packages = crate::monorepo::list_packages(Path::new(project_path));
+    crate::monorepo::package_for_path(Path::new(project_path), &packages, Path::new(file_path))
+        .map(|p| p.path.clone())
+}
+
+/// Proposal for the variables `vars` read from `file_path`
+pub fn analyze(
+    file_path: &str,
+    vars: &[(String, String)],
+    project_path: &str,
+    vault: &ApiKeyVault,
+    providers: &[ApiProvider],
+) -> EnvImportProposal {
+    let (environment, environment_confidence) = infer_environment(file_path, vars);
+    let variables = vars
+        .iter()
+        .map(|(name, value)| propose(name, value, vault, project_path, &environment, providers))
+        .collect();
+
+    EnvImportProposal {
+        file_name: Path::new(file_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        package_path: package_path(project_path, file_path),
+        path: file_path.to_string(),
+        project_path: project_path.to_string(),
+        environment,
+        environment_confidence,
+        variables,
+    }
+}
+
+#[tauri::command]
+pub async fn analyze_env_import(
+    file_path: String,
+    state: State<'_, AppState>,
+) -> Result<EnvImportProposal, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vars = read_vars(&file_path)?;
+    let project_path = crate::detect_project_path(&file_path)?;
+    let providers = providers(&state).await;
+    let vault = state.vault.lock().await;
+    Ok(analyze(
+        &file_path,
+        &vars,
+        &project_path,
+        &vault,
+        &providers,
+    ))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportDecision {
+    pub name: String,
+    pub action: ImportAction,
+    /// Key to update
+    #[serde(default)]
+    pub key_id: Option<String>,
+    /// Service of a created key; the detected provider when unset
+    #[serde(default)]
+    pub service: Option<String>,
+    /// Environment of the value; the one its format tells, or the file's,
+    /// when unset
+    #[serde(default)]
+    pub environment: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportFailure {
+    pub name: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EnvImportResult {
+    /// IDs of created keys
+    pub created: Vec<String>,
+    /// IDs of updated keys
+    pub updated: Vec<String>,
+    pub skipped: Vec<String>,
+    pub failed: Vec<ImportFailure>,
+    /// Values kept for an environment their format contradicts
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+fn new_key(
+    name: &str,
+    value: &str,
+    service: String,
+    environment: &str,
+    file_path: &str,
+    project_path: &str,
+) -> ApiKey {
+    let now = Utc::now();
+    ApiKey {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        service,
+        key: value.to_string(),
+        description: None,
+        environment: environment.to_string(),
+        quota: None,
+        cost: None,
+        expires_at: None,
+        scopes: Vec::new(),
+        created_at: now,
+        updated_at: now,
+        tags: Vec::new(),
+        is_active: true,
+        source_type: Some("env_file".to_string()),
+        env_file_path: Some(file_path.to_string()),
+        project_path: Some(project_path.to_string()),
+        env_file_name: Path::new(file_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string()),
+        remote_secrets: Vec::new(),
+        package_path: package_path(project_path, file_path),
+        rotation_interval_days: None,
+        last_rotated_at: None,
+        kind: Default::default(),
+        credential: None,
+        oauth: None,
+        values: Default::default(),
+        is_pinned: false,
+        archived_at: None,
+        last_used_at: None,
+        backend: None,
+    }
+}
+
+/// Apply reviewed decisions to `vars`, read from `file_path`, save and
+/// audit. A value goes to the environment of its decision, else the one its
+/// format tells, else `environment`, which defaults to the inferred one.
+pub async fn apply(
+    state: &State<'_, AppState>,
+    file_path: &str,
+    vars: &[(String, String)],
+    project_path: &str,
+    decisions: Vec<ImportDecision>,
+    environment: Option<String>,
+) -> Result<EnvImportResult, KeyKeeperError> {
+    let environment = environment
+        .map(|e| normalize_environment(&e))
+        .filter(|e| !e.is_empty())
+        .unwrap_or_else(|| infer_environment(file_path, vars).0);
+    let providers = providers(state).await;
+
+    let now = Utc::now();
+    let mut result = EnvImportResult::default();
+    let mut vault = state.vault.lock().await;
+    for decision in decisions {
+        let fail = |reason: String| ImportFailure {
+            name: decision.name.clone(),
+            reason,
+        };
+        let Some((_, value)) = vars.iter().find(|(name, _)| *name == decision.name) else {
+            result
+                .failed
+                .push(fail("Not in the file anymore".to_string()));
+            continue;
+        };
+        let inference = key_environment::infer(value, &providers);
+        let environment = match decision.environment.as_deref().map(normalize_environment) {
+            Some(chosen) if !chosen.is_empty() => {
+                if let Some(warning) = inference
+                    .as_ref()
+                    .filter(|_| decision.action != ImportAction::Skip)
+                    .and_then(|i| key_environment::mismatch(i, &chosen))
+                {
+                    result
+                        .warnings
+                        .push(format!("{}: {}", decision.name, warning));
+                }
+                chosen
+            }
+            _ => inference.map_or_else(|| environment.clone(), |i| i.environment),
+        };
+        match decision.action {
+            ImportAction::Skip => result.skipped.push(decision.name),
+            ImportAction::Create => {
+                let services: Vec<String> =
+                    vault.keys.values().map(|k| k.service.clone()).collect();
+                let service = decision
+                    .service
+                    .clone()
+                    .filter(|s| !s.trim().is_empty())
+                    .or_else(|| {
+                        detect_provider(&decision.name, value, &providers, &services)
+                            .map(|(p, _)| p)
+                    })
+                    .unwrap_or_else(|| {
+                        decision
+                            .name
+                            .split('_')
+                            .next()
+                            .unwrap_or_default()
+                            .to_lowercase()
+                    });
+                let key = new_key(
+                    &decision.name,
+                    value,
+                    service,
+                    &environment,
+                    file_path,
+                    project_path,
+                );
+                if let Err(e) = crate::naming::enforce(&vault, &key) {
+                    result.failed.push(fail(e.to_string()));
+                    continue;
+                }
+                activity::record(
+                    &mut vault,
+                    "key_created",
+                    &key,
+                    activity::Actor::ui(),
+                    None,
+                    None,
+                );
+                result.created.push(key.id.clone());
+                vault.keys.insert(key.id.clone(), key);
+            }
+            ImportAction::Update => {
+                let Some(key) = decision
+                    .key_id
+                    .as_ref()
+                    .and_then(|id| vault.keys.get_mut(id))
+                else {
+                    result
+                        .failed
+                        .push(fail("The key to update wasn't found".to_string()));
+                    continue;
+                };
+                if !key.kind.is_env_value() {
+                    result
+                        .failed
+                        .push(fail("Only API keys take values from env files".to_string()));
+                    continue;
+                }
+                let rotated = env_values::is_primary(key, &environment) && key.key != *value;
+                if env_values::is_primary(key, &environment) {
+                    key.key = value.clone();
+                } else {
+                    key.values.insert(environment.clone(), value.clone());
+                }
+                key.updated_at = now;
+                let key = key.clone();
+                activity::record(
+                    &mut vault,
+                    "key_updated",
+                    &key,
+                    activity::Actor::ui(),
+                    None,
+                    Some(format!("{} value from {}", environment, file_path)),
+                );
+                if rotated {
+                    crate::rotation::mark_rotated(&mut vault, &key.id, now);
+                }
+                result.updated.push(key.id);
+            }
+        }
+    }
+    drop(vault);
+
+    if !result.created.is_empty() || !result.updated.is_empty() {
+        save_vault(state).await?;
+    }
+    log_audit_event(
+        state,
+        "import_env_variables",
+        "env_file",
+        Some(file_path),
+        result.failed.is_empty(),
+        Some(&format!(
+            "{} created, {} updated, {} skipped, {} failed",
+            result.created.len(),
+            result.updated.len(),
+            result.skipped.len(),
+            result.failed.len()
+        )),
+    )
+    .await;
+    Ok(result)
+}
+
+/// Apply reviewed decisions to the variables of `file_path`. The
+/// environment defaults to the inferred one.
+#[tauri::command]
+pub async fn import_env_variables(
+    file_path: String,
+    decisions: Vec<ImportDecision>,
+    environment: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<EnvImportResult, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vars = read_vars(&file_path)?;
+    let project_path = crate::detect_project_path(&file_path)?;
+    apply(
+        &state,
+        &file_path,
+        &vars,
+        &project_path,
+        decisions,
+        environment,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proposals_weigh_secrets_providers_and_duplicates() {
+        assert!(classify_secret("STRIPE_KEY", "sk_live_abcdefghijklmnop1234").0);
+        assert!(classify_secret("DATABASE_URL", "postgres://app:hunter2@db/app").0);
+        assert!(!classify_secret("AUTH_ENABLED", "true").0);
+        assert!(!classify_secret("PORT", "3000").0);
+        assert_eq!(preview("sk_live_abc", true), "sk_l… (11 chars)");
+
+        let vars = vec![("NODE_ENV".to_string(), "prod".to_string())];
+        assert_eq!(
+            infer_environment("/app/.env", &vars),
+            ("production".to_string(), 0.95)
+        );
+        assert_eq!(infer_environment("/app/.env.staging", &[]).0, "staging");
+
+        let mut vault = ApiKeyVault::default();
+        let mut existing = new_key(
+            "OPENAI_API_KEY",
+            "sk-old",
+            "openai".into(),
+            "development",
+            "/app/.env",
+            "/app",
+        );
+        existing.id = "key_1".to_string();
+        vault.keys.insert(existing.id.clone(), existing);
+
+        let update = propose(
+            "OPENAI_API_KEY",
+            "sk-proj-abcdefghijklmnopqrstuvwx",
+            &vault,
+            "/app",
+            "production",
+            &[],
+        );
+        assert_eq!(update.action, ImportAction::Update);
+        assert_eq!(update.key_id.as_deref(), Some("key_1"));
+        assert_eq!(update.provider.as_deref(), Some("openai"));
+        assert!(update
+            .reasons
+            .iter()
+            .any(|r| r == "Adds a production value to OPENAI_API_KEY"));
+
+        let duplicate = propose("OTHER_TOKEN", "sk-old", &vault, "/app", "development", &[]);
+        assert_eq!(duplicate.action, ImportAction::Skip);
+        let create = propose(
+            "GITHUB_TOKEN",
+            "ghp_abcdefghijklmnopqrstuvwxyz",
+            &vault,
+            "/app",
+            "development",
+            &[],
+        );
+        assert_eq!(
+            (create.action, create.provider.as_deref()),
+            (ImportAction::Create, Some("github"))
+        );
+        assert_eq!(
+            propose("PORT", "3000", &vault, "/app", "development", &[]).action,
+            ImportAction::Skip
+        );
+    }
+}
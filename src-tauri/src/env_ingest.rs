@@ -0,0 +1,359 @@
+//! Ingestion of a dropped `.env` file in one command.
+//!
+//! `ingest_env_file` runs the whole import of a file: parse it, classify each
+//! variable and look it up in the vault (see `env_import`), skip variables
+//! repeating the value of an earlier one, propose the project to associate
+//! the file with, wait for the user to review the proposal and apply it.
+//! Each stage is announced with an `env-ingest-progress` event. The one for
+//! `proposed` carries the proposal, which is answered with
+//! `review_env_ingestion`; without an answer within `REVIEW_TIMEOUT` the
+//! ingestion is cancelled. `auto_apply` applies the proposal as it is.
+
+use crate::env_import::{self, EnvImportProposal, EnvImportResult, ImportAction, ImportDecision};
+use crate::errors::KeyKeeperError;
+use crate::{guards, log_audit_event, save_vault, upsert_env_association, ApiKeyVault, AppState};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::oneshot;
+use tracing::warn;
+
+/// How long a proposal waits for the user's review
+const REVIEW_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IngestStage {
+    Parsing,
+    Classifying,
+    Deduplicating,
+    Associating,
+    /// Waiting for `review_env_ingestion`
+    Proposed,
+    Applying,
+    Done,
+    Cancelled,
+    Failed,
+}
+
+/// Project the file would be associated with
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectProposal {
+    pub project_path: String,
+    pub package_path: Option<String>,
+    /// The vault's project at that path, if there is one
+    pub project_id: Option<String>,
+    pub project_name: Option<String>,
+    pub already_associated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IngestProposal {
+    pub import: EnvImportProposal,
+    pub project: ProjectProposal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IngestProgress {
+    pub ingestion_id: String,
+    pub path: String,
+    pub stage: IngestStage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proposal: Option<IngestProposal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The user's answer to a proposal
+#[derive(Debug, Clone, Deserialize)]
+pub struct IngestReview {
+    pub decisions: Vec<ImportDecision>,
+    /// Environment of the values; the inferred one when unset
+    #[serde(default)]
+    pub environment: Option<String>,
+    /// Associate the file with the proposed project
+    #[serde(default = "default_associate")]
+    pub associate: bool,
+}
+
+fn default_associate() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IngestResult {
+    pub ingestion_id: String,
+    pub project_path: String,
+    /// `done` or `cancelled`
+    pub stage: IngestStage,
+    pub associated: bool,
+    pub import: Option<EnvImportResult>,
+}
+
+type Reply = oneshot::Sender<Option<IngestReview>>;
+
+/// Ingestions waiting for a review, by id
+static PENDING: LazyLock<Mutex<HashMap<String, Reply>>> = LazyLock::new(Default::default);
+
+/// Skip creating a secret whose value an earlier variable of the file
+/// already has. `proposals` are those of `vars`, in the same order.
+/// Returns how many were skipped.
+pub fn dedupe(vars: &[(String, String)], proposals: &mut [env_import::VariableProposal]) -> usize {
+    let mut first_with_value: HashMap<&str, &str> = HashMap::new();
+    let mut skipped = 0;
+    for ((name, value), proposal) in vars.iter().zip(proposals.iter_mut()) {
+        if value.is_empty() {
+            continue;
+        }
+        match first_with_value.get(value.as_str()) {
+            Some(first) if proposal.action == ImportAction::Create => {
+                proposal.action = ImportAction::Skip;
+                proposal.reasons.push(format!("Same value as {}", first));
+                skipped += 1;
+            }
+            Some(_) => {}
+            None => {
+                first_with_value.insert(value, name);
+            }
+        }
+    }
+    skipped
+}
+
+pub fn propose_project(vault: &ApiKeyVault, import: &EnvImportProposal) -> ProjectProposal {
+    let project = vault
+        .projects
+        .values()
+        .find(|p| p.path == import.project_path);
+    ProjectProposal {
+        project_path: import.project_path.clone(),
+        package_path: import.package_path.clone(),
+        project_id: project.map(|p| p.id.clone()),
+        project_name: project.map(|p| p.name.clone()),
+        already_associated: vault
+            .env_associations
+            .iter()
+            .any(|a| a.project_path == import.project_path && a.env_file_path == import.path),
+    }
+}
+
+/// The proposal as it is, for `auto_apply`
+fn accept_all(proposal: &IngestProposal) -> IngestReview {
+    IngestReview {
+        decisions: proposal
+            .import
+            .variables
+            .iter()
+            .map(|v| ImportDecision {
+                name: v.name.clone(),
+                action: v.action,
+                key_id: v.key_id.clone(),
+                service: v.provider.clone(),
+                environment: None,
+            })
+            .collect(),
+        environment: None,
+        associate: true,
+    }
+}
+
+struct Ingestion<'a> {
+    app: &'a AppHandle,
+    id: String,
+    path: String,
+}
+
+impl Ingestion<'_> {
+    fn emit(&self, stage: IngestStage, proposal: Option<IngestProposal>, error: Option<String>) {
+        let progress = IngestProgress {
+            ingestion_id: self.id.clone(),
+            path: self.path.clone(),
+            stage,
+            proposal,
+            error,
+        };
+        if let Err(e) = self.app.emit("env-ingest-progress", &progress) {
+            warn!("Failed to emit ingestion progress: {}", e);
+        }
+    }
+
+    fn cancelled(&self, project_path: String) -> IngestResult {
+        self.emit(IngestStage::Cancelled, None, None);
+        IngestResult {
+            ingestion_id: self.id.clone(),
+            project_path,
+            stage: IngestStage::Cancelled,
+            associated: false,
+            import: None,
+        }
+    }
+
+    async fn run(
+        &self,
+        auto_apply: bool,
+        state: &State<'_, AppState>,
+    ) -> Result<IngestResult, KeyKeeperError> {
+        self.emit(IngestStage::Parsing, None, None);
+        let vars = env_import::read_vars(&self.path)?;
+        let project_path = crate::detect_project_path(&self.path)?;
+
+        self.emit(IngestStage::Classifying, None, None);
+        let providers = env_import::providers(state).await;
+        let vault = state.vault.lock().await;
+        let mut import = env_import::analyze(&self.path, &vars, &project_path, &vault, &providers);
+
+        self.emit(IngestStage::Deduplicating, None, None);
+        dedupe(&vars, &mut import.variables);
+
+        self.emit(IngestStage::Associating, None, None);
+        let project = propose_project(&vault, &import);
+        drop(vault);
+        let proposal = IngestProposal { import, project };
+
+        let review = if auto_apply {
+            accept_all(&proposal)
+        } else {
+            let (reply, answer) = oneshot::channel();
+            PENDING
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(self.id.clone(), reply);
+            self.emit(IngestStage::Proposed, Some(proposal.clone()), None);
+            let answer = tokio::time::timeout(REVIEW_TIMEOUT, answer).await;
+            PENDING
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(&self.id);
+            match answer {
+                Ok(Ok(Some(review))) => review,
+                _ => return Ok(self.cancelled(project_path)),
+            }
+        };
+
+        self.emit(IngestStage::Applying, None, None);
+        guards::unlocked(state).await?;
+        if review.associate {
+            let mut vault = state.vault.lock().await;
+            upsert_env_association(
+                &mut vault,
+                &project_path,
+                &self.path,
+                proposal.import.file_name.clone(),
+                proposal.project.package_path.clone(),
+            );
+            drop(vault);
+            save_vault(state).await?;
+            log_audit_event(
+                state,
+                "associate_project_env",
+                "project",
+                Some(&project_path),
+                true,
+                None,
+            )
+            .await;
+        }
+        let import = env_import::apply(
+            state,
+            &self.path,
+            &vars,
+            &project_path,
+            review.decisions,
+            review.environment,
+        )
+        .await?;
+
+        self.emit(IngestStage::Done, None, None);
+        Ok(IngestResult {
+            ingestion_id: self.id.clone(),
+            project_path,
+            stage: IngestStage::Done,
+            associated: review.associate,
+            import: Some(import),
+        })
+    }
+}
+
+/// Parse, classify, dedupe, associate and import `path`, waiting for the
+/// proposal's review unless `auto_apply` is set
+#[tauri::command]
+pub async fn ingest_env_file(
+    path: String,
+    auto_apply: Option<bool>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<IngestResult, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let ingestion = Ingestion {
+        app: &app,
+        id: uuid::Uuid::new_v4().to_string(),
+        path,
+    };
+    let result = ingestion.run(auto_apply.unwrap_or(false), &state).await;
+    if let Err(e) = &result {
+        ingestion.emit(IngestStage::Failed, None, Some(e.to_string()));
+    }
+    result
+}
+
+/// Answer the proposal of an ingestion; no review cancels it
+#[tauri::command]
+pub async fn review_env_ingestion(
+    ingestion_id: String,
+    review: Option<IngestReview>,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let reply = PENDING
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&ingestion_id)
+        .ok_or_else(|| KeyKeeperError::not_found("Ingestion"))?;
+    reply
+        .send(review)
+        .map_err(|_| KeyKeeperError::Conflict("The ingestion is no longer waiting".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_values_are_only_created_once() {
+        let vars: Vec<(String, String)> = [
+            ("STRIPE_KEY", "sk_live_abcdefghijklmnop1234"),
+            ("STRIPE_SECRET_KEY", "sk_live_abcdefghijklmnop1234"),
+            ("PORT", "3000"),
+            ("WEB_PORT", "3000"),
+        ]
+        .iter()
+        .map(|(n, v)| (n.to_string(), v.to_string()))
+        .collect();
+        let vault = ApiKeyVault::default();
+        let mut import = env_import::analyze("/app/.env", &vars, "/app", &vault, &[]);
+        assert_eq!(import.variables[1].action, ImportAction::Create);
+
+        assert_eq!(dedupe(&vars, &mut import.variables), 1);
+        let actions: Vec<ImportAction> = import.variables.iter().map(|v| v.action).collect();
+        assert_eq!(
+            actions,
+            [
+                ImportAction::Create,
+                ImportAction::Skip,
+                ImportAction::Skip,
+                ImportAction::Skip
+            ]
+        );
+        assert!(import.variables[1]
+            .reasons
+            .contains(&"Same value as STRIPE_KEY".to_string()));
+
+        let project = propose_project(&vault, &import);
+        assert_eq!(project.project_path, "/app");
+        assert!(project.project_id.is_none() && !project.already_associated);
+    }
+}
@@ -0,0 +1,638 @@
+//! Key references in `.env` files.
+//!
+//! By default syncs write `NAME=keykeeper://key/<id>` instead of the secret,
+//! so the value never sits on disk. References are resolved when a process
+//! starts: `keykeeper exec -- <command>` reads the `.env` files, asks the
+//! running app to resolve them over the local HTTP API, and starts the
+//! command with the real values in its environment. The `resolve_env_references`
+//! command does the same for the app and the editor extension.
+//!
+//! `keykeeper://key/<id>?env=<environment>` points at one environment's value
+//! of a key with per-environment values; a plain reference resolves in the
+//! environment the caller asks for, or to the key's own value.
+
+use crate::errors::KeyKeeperError;
+use crate::{env_values, guards, log_audit_event, ApiKey, ApiKeyVault, AppState};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+pub const REFERENCE_PREFIX: &str = "keykeeper://key/";
+const DEFAULT_PORT: u16 = 27182;
+
+/// What syncs write into `.env` files
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvSyncMode {
+    /// `keykeeper://key/<id>`, resolved at process start
+    #[default]
+    Reference,
+    /// The secret itself
+    Plaintext,
+}
+
+pub fn reference_for(key_id: &str) -> String {
+    format!("{}{}", REFERENCE_PREFIX, key_id)
+}
+
+pub fn reference_for_env(key_id: &str, environment: &str) -> String {
+    format!("{}{}?env={}", REFERENCE_PREFIX, key_id, environment)
+}
+
+/// Key id of a reference value
+pub fn parse_reference(value: &str) -> Option<&str> {
+    parse_reference_parts(value).map(|(id, _)| id)
+}
+
+/// Key id and environment of a reference value
+pub fn parse_reference_parts(value: &str) -> Option<(&str, Option<&str>)> {
+    let rest = value.trim().strip_prefix(REFERENCE_PREFIX)?;
+    let (id, query) = match rest.split_once('?') {
+        Some((id, query)) => (id, Some(query)),
+        None => (rest, None),
+    };
+    let id = id.trim_end_matches('/');
+    if id.is_empty() {
+        return None;
+    }
+    let environment = query
+        .into_iter()
+        .flat_map(|query| query.split('&'))
+        .find_map(|pair| pair.strip_prefix("env="))
+        .filter(|env| !env.is_empty());
+    Some((id, environment))
+}
+
+pub fn sync_mode(vault: &ApiKeyVault) -> EnvSyncMode {
+    vault
+        .user_account
+        .as_ref()
+        .map(|account| account.preferences.env_sync_mode)
+        .unwrap_or_default()
+}
+
+/// Value a sync writes for a key. An existing value that's already the
+/// secret or the reference is kept, so switching modes doesn't rewrite
+/// every file.
+pub fn sync_value(mode: EnvSyncMode, key_id: &str, secret: &str, current: Option<&str>) -> String {
+    choose_value(mode, reference_for(key_id), secret, current)
+}
+
+/// `sync_value` for the key's value in `environment`, if it has one
+pub fn sync_value_in(
+    mode: EnvSyncMode,
+    key: &ApiKey,
+    environment: &str,
+    current: Option<&str>,
+) -> Option<String> {
+    let secret = env_values::value_for(key, environment)?;
+    let reference = if env_values::is_primary(key, environment) {
+        reference_for(&key.id)
+    } else {
+        reference_for_env(
+            &key.id,
+            &crate::docker_env::normalize_environment(environment),
+        )
+    };
+    Some(choose_value(mode, reference, secret, current))
+}
+
+fn choose_value(
+    mode: EnvSyncMode,
+    reference: String,
+    secret: &str,
+    current: Option<&str>,
+) -> String {
+    match current {
+        Some(current) if current == secret || current == reference => current.to_string(),
+        _ if mode == EnvSyncMode::Plaintext => secret.to_string(),
+        _ => reference,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnresolvedReference {
+    pub name: String,
+    pub reference: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResolvedEnv {
+    /// Every variable, with references replaced by their secrets
+    pub variables: BTreeMap<String, String>,
+    /// Names of the variables that were references
+    pub resolved: Vec<String>,
+    pub unresolved: Vec<UnresolvedReference>,
+}
+
+impl ResolvedEnv {
+    /// Keep only the variables that were references, for callers that may
+    /// read the secrets they're approved for but not the rest of the file
+    pub fn references_only(mut self) -> Self {
+        let resolved = &self.resolved;
+        self.variables.retain(|name, _| resolved.contains(name));
+        self
+    }
+}
+
+/// Replace references with the secrets they point to. Other values pass
+/// through unchanged. References without an environment of their own
+/// resolve in `environment`, or to the key's own value without one.
+pub fn resolve(
+    vault: &ApiKeyVault,
+    variables: BTreeMap<String, String>,
+    environment: Option<&str>,
+) -> ResolvedEnv {
+    let now = Utc::now();
+    let mut out = ResolvedEnv::default();
+    for (name, value) in variables {
+        let Some((key_id, reference_env)) = parse_reference_parts(&value) else {
+            out.variables.insert(name, value);
+            continue;
+        };
+        let reason = match env_values::lookup(vault, key_id) {
+            None => "Key not found".to_string(),
+            Some((key, _)) if !key.is_active => "Key is inactive".to_string(),
+            Some((key, _)) if crate::archive::is_archived(key) => "Key is archived".to_string(),
+            Some((key, _)) if key.expires_at.is_some_and(|t| t <= now) => {
+                "Key has expired".to_string()
+            }
+            Some((key, alias_env)) => {
+                let wanted = reference_env.or(alias_env).or(environment);
+                let secret = match wanted {
+                    Some(env) => env_values::value_for(key, env),
+                    None => Some(key.key.as_str()),
+                };
+                match secret {
+                    Some(secret) => {
+                        out.variables.insert(name.clone(), secret.to_string());
+                        out.resolved.push(name);
+                        continue;
+                    }
+                    None => format!("No value for environment {}", wanted.unwrap_or_default()),
+                }
+            }
+        };
+        out.unresolved.push(UnresolvedReference {
+            name,
+            reference: value,
+            reason,
+        });
+    }
+    out
+}
+
+/// Variables of an env file, read with the format-preserving parser
+pub fn read_env_file(path: &Path) -> Result<BTreeMap<String, String>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(crate::env_file::EnvFile::parse(&content)
+        .vars()
+        .into_iter()
+        .collect())
+}
+
+/// Resolve the references in an env file or in a set of variables
+#[tauri::command]
+pub async fn resolve_env_references(
+    env_file_path: Option<String>,
+    variables: Option<BTreeMap<String, String>>,
+    environment: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ResolvedEnv, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut input = variables.unwrap_or_default();
+    if let Some(path) = &env_file_path {
+        input.extend(read_env_file(Path::new(path)).map_err(KeyKeeperError::Io)?);
+    }
+
+    let resolved = resolve(&*state.vault.lock().await, input, environment.as_deref());
+    log_audit_event(
+        &state,
+        "resolve_env_references",
+        "env_file",
+        env_file_path.as_deref(),
+        resolved.unresolved.is_empty(),
+        Some(&format!(
+            "Resolved {}, unresolved {}",
+            resolved.resolved.len(),
+            resolved.unresolved.len()
+        )),
+    )
+    .await;
+    Ok(resolved)
+}
+
+// ===============================
+//  keykeeper exec
+// ===============================
+
+const EXEC_USAGE: &str =
+    "Usage: keykeeper exec [--env-file PATH]... [--environment ENV] [--port PORT] -- <command> [args...]
+       keykeeper exec --register [--port PORT]
+
+Starts <command> with the variables of the env files (default: .env in the
+current directory, if present) and with every keykeeper://key/<id> reference,
+in the files or the environment, replaced by its secret. KeyKeeper must be
+running, unlocked, with its HTTP server started; the first run asks for
+approval in the app.
+
+--environment (or KEYKEEPER_ENV) picks which value of keys with per-environment
+values the references resolve to.
+
+exec talks to KeyKeeper as a registered client. --register asks the app for
+credentials and stores them for later runs; approve the registration in the
+app once. KEYKEEPER_CLIENT_ID and KEYKEEPER_CLIENT_SECRET take precedence over
+the stored credentials.";
+
+/// Name `exec` registers under, and sends while unregistered
+const EXEC_CLIENT_NAME: &str = "keykeeper-exec";
+const CREDENTIALS_FILE: &str = "exec-client.json";
+
+struct ExecArgs {
+    env_files: Vec<PathBuf>,
+    environment: Option<String>,
+    port: u16,
+    register: bool,
+    command: Vec<String>,
+}
+
+/// Registered client credentials `exec` identifies itself with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ExecCredentials {
+    client_id: String,
+    client_secret: String,
+}
+
+fn credentials_path() -> Option<PathBuf> {
+    crate::workspace_watcher::config_dir().map(|dir| dir.join("keykeeper").join(CREDENTIALS_FILE))
+}
+
+/// Credentials from the environment, else those stored by `--register`
+fn load_credentials() -> Option<ExecCredentials> {
+    let from_env = std::env::var("KEYKEEPER_CLIENT_ID")
+        .ok()
+        .zip(std::env::var("KEYKEEPER_CLIENT_SECRET").ok())
+        .filter(|(id, secret)| !id.is_empty() && !secret.is_empty());
+    if let Some((client_id, client_secret)) = from_env {
+        return Some(ExecCredentials {
+            client_id,
+            client_secret,
+        });
+    }
+    let contents = std::fs::read_to_string(credentials_path()?).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+fn store_credentials(credentials: &ExecCredentials) -> Result<PathBuf, String> {
+    let path = credentials_path().ok_or("No config directory to store credentials in")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let contents = serde_json::to_string_pretty(credentials).map_err(|e| e.to_string())?;
+    std::fs::write(&path, contents)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+    }
+    Ok(path)
+}
+
+/// Headers identifying `exec` to the HTTP API
+fn client_headers(credentials: Option<&ExecCredentials>) -> Vec<(&'static str, String)> {
+    let mut headers = vec![(
+        crate::approvals::CLIENT_HEADER,
+        EXEC_CLIENT_NAME.to_string(),
+    )];
+    if let Some(credentials) = credentials {
+        headers.push((
+            crate::clients::CLIENT_ID_HEADER,
+            credentials.client_id.clone(),
+        ));
+        headers.push((
+            crate::clients::CLIENT_SECRET_HEADER,
+            credentials.client_secret.clone(),
+        ));
+    }
+    headers
+}
+
+fn parse_exec_args(args: &[String]) -> Result<ExecArgs, String> {
+    let mut env_files = Vec::new();
+    let mut environment = std::env::var("KEYKEEPER_ENV")
+        .ok()
+        .filter(|e| !e.is_empty());
+    let mut port = std::env::var("KEYKEEPER_PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(DEFAULT_PORT);
+    let mut register = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--" => break,
+            "--env-file" => {
+                env_files.push(PathBuf::from(iter.next().ok_or("--env-file needs a path")?))
+            }
+            "--environment" => {
+                environment = Some(iter.next().ok_or("--environment needs a name")?.clone())
+            }
+            "--port" => {
+                port = iter
+                    .next()
+                    .and_then(|p| p.parse().ok())
+                    .ok_or("--port needs a port number")?
+            }
+            "--register" => register = true,
+            "-h" | "--help" => return Err(String::new()),
+            other => return Err(format!("Unknown option '{}'", other)),
+        }
+    }
+    let command: Vec<String> = iter.cloned().collect();
+    if command.is_empty() && !register {
+        return Err("No command given".to_string());
+    }
+    if env_files.is_empty() && Path::new(".env").exists() {
+        env_files.push(PathBuf::from(".env"));
+    }
+    Ok(ExecArgs {
+        env_files,
+        environment,
+        port,
+        register,
+        command,
+    })
+}
+
+async fn register_remote(port: u16) -> Result<ExecCredentials, String> {
+    let response = reqwest::Client::new()
+        .post(format!("http://127.0.0.1:{}/api/clients/register", port))
+        .json(&serde_json::json!({ "name": EXEC_CLIENT_NAME }))
+        .send()
+        .await
+        .map_err(|e| format!("KeyKeeper isn't reachable on port {}: {}", port, e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body: serde_json::Value = response.json().await.unwrap_or_default();
+        return Err(format!(
+            "KeyKeeper refused the registration ({}): {}",
+            status,
+            body["error"].as_str().unwrap_or("unknown error")
+        ));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response from KeyKeeper: {}", e))
+}
+
+fn register(port: u16) -> Result<i32, String> {
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    let credentials = runtime.block_on(register_remote(port))?;
+    let path = store_credentials(&credentials)?;
+    println!(
+        "Registered as {} ({}), credentials stored in {}.\nApprove the registration in KeyKeeper before running commands.",
+        EXEC_CLIENT_NAME,
+        credentials.client_id,
+        path.display()
+    );
+    Ok(0)
+}
+
+async fn resolve_remote(
+    port: u16,
+    variables: BTreeMap<String, String>,
+    environment: Option<&str>,
+) -> Result<ResolvedEnv, String> {
+    let credentials = load_credentials();
+    let mut request = reqwest::Client::new()
+        .post(format!("http://127.0.0.1:{}/api/env/resolve", port))
+        .json(&serde_json::json!({ "variables": variables, "environment": environment }));
+    for (name, value) in client_headers(credentials.as_ref()) {
+        request = request.header(name, value);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("KeyKeeper isn't reachable on port {}: {}", port, e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body: serde_json::Value = response.json().await.unwrap_or_default();
+        let hint = if credentials.is_none() {
+            "\nRun `keykeeper exec --register` to register exec with KeyKeeper."
+        } else {
+            ""
+        };
+        return Err(format!(
+            "KeyKeeper refused to resolve references ({}): {}{}",
+            status,
+            body["error"].as_str().unwrap_or("unknown error"),
+            hint
+        ));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response from KeyKeeper: {}", e))
+}
+
+fn exec(args: ExecArgs) -> Result<i32, String> {
+    if args.register {
+        return register(args.port);
+    }
+    let mut variables = BTreeMap::new();
+    for path in &args.env_files {
+        variables.extend(read_env_file(path)?);
+    }
+    // The process environment wins over the files, as with dotenv
+    for (name, value) in std::env::vars() {
+        if variables.contains_key(&name) || parse_reference(&value).is_some() {
+            variables.insert(name, value);
+        }
+    }
+
+    let references: BTreeMap<String, String> = variables
+        .iter()
+        .filter(|(_, value)| parse_reference(value).is_some())
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+    if !references.is_empty() {
+        let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+        let resolved = runtime.block_on(resolve_remote(
+            args.port,
+            references,
+            args.environment.as_deref(),
+        ))?;
+        if !resolved.unresolved.is_empty() {
+            let details: Vec<String> = resolved
+                .unresolved
+                .iter()
+                .map(|u| format!("{} ({}): {}", u.name, u.reference, u.reason))
+                .collect();
+            return Err(format!(
+                "Unresolved references:\n  {}",
+                details.join("\n  ")
+            ));
+        }
+        variables.extend(resolved.variables);
+    }
+
+    let status = std::process::Command::new(&args.command[0])
+        .args(&args.command[1..])
+        .envs(&variables)
+        .status()
+        .map_err(|e| format!("Failed to start {}: {}", args.command[0], e))?;
+    Ok(status.code().unwrap_or(1))
+}
+
+/// Handle `keykeeper exec ...`. Returns the exit code when the process was
+/// started as the CLI, `None` to start the app as usual.
+pub fn run_cli(argv: &[String]) -> Option<i32> {
+    if argv.get(1).map(String::as_str) != Some("exec") {
+        return None;
+    }
+    let code = match parse_exec_args(&argv[2..]).and_then(exec) {
+        Ok(code) => code,
+        Err(e) if e.is_empty() => {
+            println!("{}", EXEC_USAGE);
+            0
+        }
+        Err(e) => {
+            eprintln!("keykeeper exec: {}", e);
+            if e.starts_with("Unknown option") || e.starts_with("No command") {
+                eprintln!("\n{}", EXEC_USAGE);
+            }
+            1
+        }
+    };
+    Some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sync_value_prefers_references_but_keeps_current_values() {
+        assert_eq!(parse_reference("keykeeper://key/abc"), Some("abc"));
+        assert_eq!(parse_reference("keykeeper://key/"), None);
+        assert_eq!(parse_reference("sk_live_123"), None);
+        assert_eq!(
+            parse_reference_parts("keykeeper://key/abc?env=staging"),
+            Some(("abc", Some("staging")))
+        );
+
+        let reference = reference_for("abc");
+        assert_eq!(
+            sync_value(EnvSyncMode::Reference, "abc", "sk", None),
+            reference
+        );
+        assert_eq!(
+            sync_value(EnvSyncMode::Reference, "abc", "sk", Some("sk")),
+            "sk"
+        );
+        assert_eq!(
+            sync_value(EnvSyncMode::Reference, "abc", "sk", Some("stale")),
+            reference
+        );
+        assert_eq!(
+            sync_value(EnvSyncMode::Plaintext, "abc", "sk", Some(&reference)),
+            reference
+        );
+        assert_eq!(
+            sync_value(EnvSyncMode::Plaintext, "abc", "sk", Some("stale")),
+            "sk"
+        );
+
+        let args: Vec<String> = [
+            "--env-file",
+            "a.env",
+            "--port",
+            "4000",
+            "--",
+            "npm",
+            "start",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let parsed = parse_exec_args(&args).unwrap();
+        assert_eq!(parsed.env_files, vec![PathBuf::from("a.env")]);
+        assert_eq!(parsed.port, 4000);
+        assert_eq!(parsed.command, vec!["npm", "start"]);
+    }
+
+    #[test]
+    fn references_only_drops_plain_variables() {
+        let mut vault = ApiKeyVault::default();
+        let key = crate::vault_tests::test_key("k1").secret("sk_live").build();
+        vault.keys.insert(key.id.clone(), key);
+        let variables = BTreeMap::from([
+            ("STRIPE_KEY".to_string(), reference_for("k1")),
+            (
+                "DATABASE_URL".to_string(),
+                "postgres://app:pw@db".to_string(),
+            ),
+            ("MISSING".to_string(), reference_for("gone")),
+        ]);
+
+        let resolved = resolve(&vault, variables, None);
+        assert_eq!(resolved.variables.len(), 2);
+        let resolved = resolved.references_only();
+        assert_eq!(
+            resolved.variables,
+            BTreeMap::from([("STRIPE_KEY".to_string(), "sk_live".to_string())])
+        );
+        assert_eq!(resolved.unresolved.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn registered_exec_requests_get_past_client_checks() {
+        let mut vault = ApiKeyVault::default();
+        let now = Utc::now();
+        let (client, client_secret) =
+            crate::clients::register(&mut vault, EXEC_CLIENT_NAME, None, None, now).unwrap();
+        vault.registered_clients[0].status = crate::clients::ClientStatus::Approved;
+        let vault = tokio::sync::Mutex::new(vault);
+        let unlocked = tokio::sync::Mutex::new(true);
+        let addr = "127.0.0.1:50000".parse().unwrap();
+        let credentials = ExecCredentials {
+            client_id: client.client_id.clone(),
+            client_secret,
+        };
+        let request = |credentials: Option<&ExecCredentials>| {
+            client_headers(credentials)
+                .into_iter()
+                .fold(
+                    hyper::Request::builder().uri("/api/env/resolve"),
+                    |request, (name, value)| request.header(name, value),
+                )
+                .body(())
+                .unwrap()
+        };
+
+        let (_, identified) =
+            crate::clients::identify(&vault, &unlocked, true, addr, request(Some(&credentials)))
+                .await
+                .unwrap();
+        assert_eq!(identified.client_id, Some(client.client_id));
+        assert_eq!(identified.name, EXEC_CLIENT_NAME);
+
+        let refused = crate::clients::identify(&vault, &unlocked, true, addr, request(None)).await;
+        assert!(matches!(refused, Err(KeyKeeperError::Denied(_))));
+    }
+
+    #[test]
+    fn register_needs_no_command() {
+        let parsed = parse_exec_args(&["--register".to_string()]).unwrap();
+        assert!(parsed.register && parsed.command.is_empty());
+        assert!(parse_exec_args(&[]).is_err());
+    }
+}
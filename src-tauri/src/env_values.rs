@@ -0,0 +1,455 @@
+//! Per-environment values of a key.
+//!
+//! A key's `key` is its value in its own `environment`; `values` holds the
+//! values for other environments, by normalized environment name, so one
+//! logical key covers dev, staging and production. There is no fallback
+//! between environments: a key without a staging value resolves to nothing
+//! in staging rather than to another environment's secret.
+//!
+//! References can name an environment (`keykeeper://key/<id>?env=staging`).
+//! The v3 schema migration merges keys that only differ by environment;
+//! the ids of the merged keys are kept as aliases, so references written
+//! before keep resolving to the same secret.
+
+use crate::docker_env::normalize_environment;
+use crate::errors::KeyKeeperError;
+use crate::key_history;
+use crate::{guards, log_audit_event, save_vault, ApiKey, ApiKeyVault, AppState};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use tauri::State;
+use tracing::{info, warn};
+
+/// Where the id of a merged key now points
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeyAlias {
+    pub key_id: String,
+    pub environment: String,
+}
+
+/// Whether `environment` is the key's own environment, whose value is `key`
+pub fn is_primary(key: &ApiKey, environment: &str) -> bool {
+    normalize_environment(&key.environment) == normalize_environment(environment)
+}
+
+/// Environments the key has a value for, its own first
+pub fn environments(key: &ApiKey) -> Vec<String> {
+    let primary = normalize_environment(&key.environment);
+    let mut environments = vec![primary.clone()];
+    environments.extend(key.values.keys().filter(|env| **env != primary).cloned());
+    environments
+}
+
+pub fn has_environment(key: &ApiKey, environment: &str) -> bool {
+    value_for(key, environment).is_some()
+}
+
+/// The key's secret in `environment`. Remote keys have none here, so
+/// syncs don't write their value to files.
+pub fn value_for<'a>(key: &'a ApiKey, environment: &str) -> Option<&'a str> {
+    if is_primary(key, environment) {
+        return key.backend.is_none().then_some(key.key.as_str());
+    }
+    key.values
+        .get(&normalize_environment(environment))
+        .map(String::as_str)
+}
+
+/// Environment of a `.env.<environment>` file
+pub fn file_environment(path: &str) -> Option<String> {
+    let name = std::path::Path::new(path).file_name()?.to_str()?;
+    name.strip_prefix(".env.")
+        .filter(|env| !env.is_empty() && *env != "example" && *env != "local")
+        .map(normalize_environment)
+}
+
+/// The key behind `id`, following aliases. The environment is the one the
+/// merged key had, for aliases.
+pub fn lookup<'a>(vault: &'a ApiKeyVault, id: &str) -> Option<(&'a ApiKey, Option<&'a str>)> {
+    match vault.keys.get(id) {
+        Some(key) => Some((key, None)),
+        None => {
+            let alias = vault.key_aliases.get(id)?;
+            let key = vault.keys.get(&alias.key_id)?;
+            Some((key, Some(alias.environment.as_str())))
+        }
+    }
+}
+
+/// Id of the key behind `id`, following aliases
+pub fn canonical_id(vault: &ApiKeyVault, id: &str) -> String {
+    lookup(vault, id)
+        .map(|(key, _)| key.id.clone())
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Order in which merged keys pick their own environment: the least
+/// sensitive one, so environment-unaware paths never see a production value
+/// when another one exists
+fn environment_rank(environment: &str) -> u8 {
+    match environment {
+        "development" => 0,
+        "test" => 1,
+        "staging" => 2,
+        "production" => 3,
+        _ => 4,
+    }
+}
+
+fn str_field<'a>(key: &'a Value, field: &str) -> &'a str {
+    key.get(field).and_then(Value::as_str).unwrap_or("")
+}
+
+/// v2 -> v3: merge keys with the same name, service, project and package
+/// that are each for a different environment into one key with per-environment
+/// values. Groups with an environment twice, or mixing active and inactive
+/// keys, are left alone.
+pub fn merge_environment_keys(document: &mut Value) -> Result<(), String> {
+    let Some(keys) = document.get_mut("keys").and_then(Value::as_object_mut) else {
+        return Ok(());
+    };
+
+    let mut groups: BTreeMap<(String, String, String, String), Vec<String>> = BTreeMap::new();
+    for (id, key) in keys.iter() {
+        let kind = str_field(key, "kind");
+        let has_values = key
+            .get("values")
+            .and_then(Value::as_object)
+            .is_some_and(|values| !values.is_empty());
+        if !(kind.is_empty() || kind == "api_key") || has_values {
+            continue;
+        }
+        groups
+            .entry((
+                str_field(key, "name").to_string(),
+                str_field(key, "service").to_lowercase(),
+                str_field(key, "project_path").to_string(),
+                str_field(key, "package_path").to_string(),
+            ))
+            .or_default()
+            .push(id.clone());
+    }
+
+    let mut aliases = Map::new();
+    for ((name, ..), ids) in groups.into_iter().filter(|(_, ids)| ids.len() > 1) {
+        let mut members: Vec<(String, String)> = ids
+            .iter()
+            .map(|id| {
+                (
+                    normalize_environment(str_field(&keys[id], "environment")),
+                    id.clone(),
+                )
+            })
+            .collect();
+        members.sort_by(|a, b| {
+            (environment_rank(&a.0), &a.0, &a.1).cmp(&(environment_rank(&b.0), &b.0, &b.1))
+        });
+        let duplicate_env = members.windows(2).any(|pair| pair[0].0 == pair[1].0);
+        let mixed_active = members
+            .iter()
+            .any(|(_, id)| keys[id].get("is_active") != keys[&members[0].1].get("is_active"));
+        if duplicate_env || mixed_active {
+            warn!("Not merging the {} keys named {}", members.len(), name);
+            continue;
+        }
+
+        let primary_id = members[0].1.clone();
+        let mut values = Map::new();
+        let mut tags: Vec<Value> = Vec::new();
+        let mut scopes: Vec<Value> = Vec::new();
+        let mut expires_at: Option<(chrono::DateTime<Utc>, Value)> = None;
+        for (environment, id) in &members {
+            let key = if *id == primary_id {
+                keys[id].clone()
+            } else {
+                let key = keys.remove(id).unwrap_or_default();
+                values.insert(
+                    environment.clone(),
+                    key.get("key").cloned().unwrap_or_default(),
+                );
+                aliases.insert(
+                    id.clone(),
+                    serde_json::json!({ "key_id": primary_id, "environment": environment }),
+                );
+                key
+            };
+            for (list, field) in [(&mut tags, "tags"), (&mut scopes, "scopes")] {
+                for item in key
+                    .get(field)
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                {
+                    if !list.contains(item) {
+                        list.push(item.clone());
+                    }
+                }
+            }
+            // The merged key expires with its first value to expire
+            let expiry = key.get("expires_at").cloned().unwrap_or_default();
+            if let Some(parsed) = expiry.as_str().and_then(crate::timestamps::parse_timestamp) {
+                if expires_at
+                    .as_ref()
+                    .is_none_or(|(earliest, _)| parsed < *earliest)
+                {
+                    expires_at = Some((parsed, expiry));
+                }
+            }
+        }
+
+        let primary = &mut keys[&primary_id];
+        primary["values"] = Value::Object(values);
+        primary["tags"] = Value::Array(tags);
+        primary["scopes"] = Value::Array(scopes);
+        if let Some((_, expiry)) = expires_at {
+            primary["expires_at"] = expiry;
+        }
+        info!(
+            "Merged {} keys named {} into {}",
+            members.len(),
+            name,
+            primary_id
+        );
+    }
+
+    if !aliases.is_empty() {
+        let existing = document
+            .as_object_mut()
+            .ok_or("Vault document is not an object")?
+            .entry("key_aliases")
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Some(existing) = existing.as_object_mut() {
+            existing.extend(aliases);
+        }
+    }
+    Ok(())
+}
+
+/// Set the key's value in `environment`; its own environment sets `key`
+#[tauri::command]
+pub async fn set_key_environment_value(
+    key_id: String,
+    environment: String,
+    value: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let environment = normalize_environment(&environment);
+    if environment.is_empty() || value.is_empty() {
+        return Err(KeyKeeperError::Validation(
+            "Environment and value are required".to_string(),
+        ));
+    }
+
+    let mut vault_guard = state.vault.lock().await;
+    let key = vault_guard
+        .keys
+        .get_mut(&key_id)
+        .ok_or_else(|| KeyKeeperError::not_found("API key"))?;
+    if !key.kind.is_env_value() {
+        return Err(KeyKeeperError::Validation(
+            "Only API keys have per-environment values".to_string(),
+        ));
+    }
+    if key.backend.is_some() {
+        return Err(KeyKeeperError::Validation(
+            "A remote key's value is kept in its secret manager".to_string(),
+        ));
+    }
+    if is_primary(key, &environment) {
+        key.key = value;
+    } else {
+        key.values.insert(environment.clone(), value);
+    }
+    key.updated_at = Utc::now();
+    let key = key.clone();
+    key_history::record(&mut vault_guard.key_history, &key, false);
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "set_key_environment_value",
+        "api_key",
+        Some(&key_id),
+        true,
+        Some(&environment),
+    )
+    .await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn remove_key_environment_value(
+    key_id: String,
+    environment: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let environment = normalize_environment(&environment);
+
+    let mut vault_guard = state.vault.lock().await;
+    let key = vault_guard
+        .keys
+        .get_mut(&key_id)
+        .ok_or_else(|| KeyKeeperError::not_found("API key"))?;
+    if is_primary(key, &environment) {
+        return Err(KeyKeeperError::Validation(format!(
+            "{} is the key's own environment",
+            environment
+        )));
+    }
+    if key.values.remove(&environment).is_none() {
+        return Err(KeyKeeperError::NotFound(format!(
+            "No {} value",
+            environment
+        )));
+    }
+    key.updated_at = Utc::now();
+    let key = key.clone();
+    key_history::record(&mut vault_guard.key_history, &key, false);
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "remove_key_environment_value",
+        "api_key",
+        Some(&key_id),
+        true,
+        Some(&environment),
+    )
+    .await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::CredentialKind;
+    use crate::vault_tests::test_key;
+
+    fn keys_document(keys: &[ApiKey]) -> Value {
+        let keys: Map<String, Value> = keys
+            .iter()
+            .map(|k| (k.id.clone(), serde_json::to_value(k).unwrap()))
+            .collect();
+        serde_json::json!({ "keys": keys })
+    }
+
+    #[test]
+    fn triplets_merge_into_one_key_with_aliases() {
+        let mut document = keys_document(&[
+            test_key("prod")
+                .name("STRIPE_SECRET_KEY")
+                .secret("sk_live")
+                .environment("Production")
+                .tags(&["Production"])
+                .build(),
+            test_key("dev")
+                .name("STRIPE_SECRET_KEY")
+                .secret("sk_dev")
+                .environment("dev")
+                .tags(&["dev"])
+                .build(),
+            test_key("stg")
+                .name("STRIPE_SECRET_KEY")
+                .secret("sk_stg")
+                .environment("staging")
+                .tags(&["staging"])
+                .build(),
+        ]);
+        merge_environment_keys(&mut document).unwrap();
+
+        let keys = document["keys"].as_object().unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys["dev"]["values"]["production"], "sk_live");
+        assert_eq!(keys["dev"]["values"]["staging"], "sk_stg");
+        assert_eq!(keys["dev"]["tags"].as_array().unwrap().len(), 3);
+        assert_eq!(document["key_aliases"]["prod"]["key_id"], "dev");
+
+        let mut vault = ApiKeyVault::default();
+        for (id, key) in keys {
+            vault
+                .keys
+                .insert(id.clone(), serde_json::from_value(key.clone()).unwrap());
+        }
+        vault.key_aliases = serde_json::from_value(document["key_aliases"].clone()).unwrap();
+        let (merged, environment) = lookup(&vault, "prod").unwrap();
+        assert_eq!(value_for(merged, environment.unwrap()), Some("sk_live"));
+        assert_eq!(value_for(merged, "development"), Some("sk_dev"));
+        assert_eq!(value_for(merged, "test"), None);
+    }
+
+    #[test]
+    fn keys_for_the_same_environment_are_not_merged() {
+        let mut document = keys_document(&[
+            test_key("a")
+                .name("STRIPE_SECRET_KEY")
+                .environment("production")
+                .build(),
+            test_key("b")
+                .name("STRIPE_SECRET_KEY")
+                .environment("prod")
+                .build(),
+        ]);
+        merge_environment_keys(&mut document).unwrap();
+        assert_eq!(document["keys"].as_object().unwrap().len(), 2);
+        assert!(document.get("key_aliases").is_none());
+    }
+
+    #[test]
+    fn only_active_alike_api_keys_are_merged() {
+        let mut keys = [
+            // Already holds another environment's value
+            test_key("held_prod")
+                .name("HELD")
+                .environment("production")
+                .value("staging", "sk_stg")
+                .build(),
+            test_key("held_dev").name("HELD").build(),
+            // Same name in another project
+            test_key("shop_dev")
+                .name("STRIPE_SECRET_KEY")
+                .project(Some("/work/shop"))
+                .build(),
+            test_key("blog_prod")
+                .name("STRIPE_SECRET_KEY")
+                .environment("production")
+                .project(Some("/work/blog"))
+                .build(),
+            test_key("revoked_prod")
+                .name("REVOKED")
+                .environment("production")
+                .build(),
+            test_key("revoked_dev").name("REVOKED").build(),
+            test_key("ssh_prod")
+                .name("DEPLOY")
+                .environment("production")
+                .build(),
+            test_key("ssh_dev").name("DEPLOY").build(),
+        ];
+        keys[4].is_active = false;
+        keys[6].kind = CredentialKind::SshKey;
+        keys[7].kind = CredentialKind::SshKey;
+        let mut document = keys_document(&keys);
+
+        merge_environment_keys(&mut document).unwrap();
+        assert_eq!(document["keys"].as_object().unwrap().len(), keys.len());
+        assert!(document.get("key_aliases").is_none());
+
+        // The service is compared case-insensitively
+        let mut document = keys_document(&[
+            test_key("prod")
+                .name("STRIPE_SECRET_KEY")
+                .service("STRIPE")
+                .environment("production")
+                .build(),
+            test_key("dev").name("STRIPE_SECRET_KEY").build(),
+        ]);
+        merge_environment_keys(&mut document).unwrap();
+        assert_eq!(document["keys"].as_object().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,155 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use thiserror::Error;
+
+/// Error returned by Tauri commands. Serialized as `{ "code", "message" }` so
+/// the frontend can branch on a stable code instead of parsing messages.
+#[derive(Debug, Clone, Error)]
+pub enum KeyKeeperError {
+    #[error("Vault is locked")]
+    VaultLocked,
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    Crypto(String),
+    #[error("{0}")]
+    Io(String),
+    #[error("{0}")]
+    Validation(String),
+    /// The vault changed elsewhere, e.g. in a synced folder
+    #[error("{0}")]
+    Conflict(String),
+    /// Refused by the user or by policy
+    #[error("{0}")]
+    Denied(String),
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl KeyKeeperError {
+    pub fn not_found(what: &str) -> Self {
+        KeyKeeperError::NotFound(format!("{} not found", what))
+    }
+
+    /// Stable, machine-readable code
+    pub fn code(&self) -> &'static str {
+        match self {
+            KeyKeeperError::VaultLocked => "VAULT_LOCKED",
+            KeyKeeperError::NotFound(_) => "NOT_FOUND",
+            KeyKeeperError::Crypto(_) => "CRYPTO_ERROR",
+            KeyKeeperError::Io(_) => "IO_ERROR",
+            KeyKeeperError::Validation(_) => "VALIDATION_ERROR",
+            KeyKeeperError::Conflict(_) => "CONFLICT",
+            KeyKeeperError::Denied(_) => "PERMISSION_DENIED",
+            KeyKeeperError::Internal(_) => "INTERNAL_ERROR",
+        }
+    }
+
+    pub fn http_status(&self) -> u16 {
+        match self {
+            KeyKeeperError::VaultLocked => 401,
+            KeyKeeperError::NotFound(_) => 404,
+            KeyKeeperError::Validation(_) => 400,
+            KeyKeeperError::Conflict(_) => 409,
+            KeyKeeperError::Denied(_) => 403,
+            KeyKeeperError::Crypto(_) | KeyKeeperError::Io(_) | KeyKeeperError::Internal(_) => 500,
+        }
+    }
+
+    /// JSON body for the HTTP API: `code`, `message`, `details` and the
+    /// `request_id` being answered. Keeps the legacy `error` field, which
+    /// repeats `message`.
+    pub fn to_json(&self) -> serde_json::Value {
+        self.to_json_with(serde_json::Value::Null)
+    }
+
+    /// `to_json` with structured `details`, e.g. which item failed
+    pub fn to_json_with(&self, details: serde_json::Value) -> serde_json::Value {
+        let message = self.to_string();
+        serde_json::json!({
+            "code": self.code(),
+            "message": message,
+            "details": details,
+            "request_id": crate::request_id::current(),
+            "error": message,
+        })
+    }
+}
+
+impl Serialize for KeyKeeperError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("KeyKeeperError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// Plain string errors from helpers that don't say what went wrong; callers
+/// that know map to a specific variant instead
+impl From<String> for KeyKeeperError {
+    fn from(message: String) -> Self {
+        KeyKeeperError::Internal(message)
+    }
+}
+
+impl From<&str> for KeyKeeperError {
+    fn from(message: &str) -> Self {
+        KeyKeeperError::Internal(message.to_string())
+    }
+}
+
+impl From<std::io::Error> for KeyKeeperError {
+    fn from(e: std::io::Error) -> Self {
+        KeyKeeperError::Io(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for KeyKeeperError {
+    fn from(e: serde_json::Error) -> Self {
+        KeyKeeperError::Validation(e.to_string())
+    }
+}
+
+impl From<anyhow::Error> for KeyKeeperError {
+    fn from(e: anyhow::Error) -> Self {
+        KeyKeeperError::Internal(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn string_errors_are_not_guessed_from_their_message() {
+        assert_eq!(
+            KeyKeeperError::from("Project not found").code(),
+            "INTERNAL_ERROR"
+        );
+        assert_eq!(
+            KeyKeeperError::from("Failed to save vault: denied".to_string()).code(),
+            "INTERNAL_ERROR"
+        );
+        assert_eq!(KeyKeeperError::not_found("Project").code(), "NOT_FOUND");
+    }
+
+    #[test]
+    fn serializes_code_and_message() {
+        let json = serde_json::to_value(KeyKeeperError::not_found("API key")).unwrap();
+        assert_eq!(json["code"], "NOT_FOUND");
+        assert_eq!(json["message"], "API key not found");
+    }
+
+    #[test]
+    fn http_body_is_the_error_envelope() {
+        let json = KeyKeeperError::Validation("Invalid JSON body".to_string())
+            .to_json_with(serde_json::json!({ "line": 1 }));
+        assert_eq!(json["code"], "VALIDATION_ERROR");
+        assert_eq!(json["message"], "Invalid JSON body");
+        assert_eq!(json["error"], json["message"]);
+        assert_eq!(json["details"]["line"], 1);
+        assert!(json["request_id"].is_null());
+        assert!(KeyKeeperError::VaultLocked.to_json()["details"].is_null());
+    }
+}
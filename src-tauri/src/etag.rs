@@ -0,0 +1,102 @@
+//! Conditional GETs for the list endpoints.
+//!
+//! List responses carry an `ETag` hashed from their body, and a request whose
+//! `If-None-Match` names the current tag gets an empty 304 instead of the
+//! full payload. `vault_generation` is a counter that moves on every save or
+//! reload of the vault; clients that only need to know whether anything
+//! changed can poll it (it's also sent as `X-Vault-Generation`) before
+//! fetching lists.
+
+use crate::errors::KeyKeeperError;
+use crate::{guards, AppState};
+use http_body_util::Full;
+use hyper::header::{HeaderMap, ETAG, IF_NONE_MATCH};
+use hyper::{Response, StatusCode};
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tauri::State;
+
+pub const GENERATION_HEADER: &str = "X-Vault-Generation";
+
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Record that the vault changed
+pub fn bump() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Changes since the app started; only ever increases
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::SeqCst)
+}
+
+/// Strong entity tag of a response body
+pub fn etag_for(body: &[u8]) -> String {
+    let digest = Sha256::digest(body);
+    let hex: String = digest[..16].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("\"{}\"", hex)
+}
+
+/// Whether `If-None-Match` names `etag`; weak tags compare equal too
+pub fn matches(headers: &HeaderMap, etag: &str) -> bool {
+    let Some(value) = headers.get(IF_NONE_MATCH).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    value
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+}
+
+/// A JSON list response, or 304 when the client already has this body
+pub fn json_response(request_headers: &HeaderMap, body: String) -> Response<Full<bytes::Bytes>> {
+    let etag = etag_for(body.as_bytes());
+    let builder = Response::builder()
+        .header(ETAG, &etag)
+        .header(GENERATION_HEADER, generation().to_string())
+        .header("Cache-Control", "no-cache");
+    if matches(request_headers, &etag) {
+        return builder
+            .status(StatusCode::NOT_MODIFIED)
+            .body(Full::new(bytes::Bytes::new()))
+            .unwrap();
+    }
+    builder
+        .status(StatusCode::OK)
+        .header("Content-Type", "application/json")
+        .body(Full::new(bytes::Bytes::from(body)))
+        .unwrap()
+}
+
+#[tauri::command]
+pub async fn get_vault_generation(state: State<'_, AppState>) -> Result<u64, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    Ok(generation())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn if_none_match_with_the_current_tag_gets_304() {
+        let body = r#"[{"id":"k1"}]"#.to_string();
+        let etag = etag_for(body.as_bytes());
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            json_response(&headers, body.clone()).status(),
+            StatusCode::OK
+        );
+
+        headers.insert(
+            IF_NONE_MATCH,
+            format!("\"stale\", W/{}", etag).parse().unwrap(),
+        );
+        let response = json_response(&headers, body.clone());
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[ETAG], etag.as_str());
+
+        headers.insert(IF_NONE_MATCH, "\"stale\"".parse().unwrap());
+        assert_eq!(json_response(&headers, body).status(), StatusCode::OK);
+    }
+}
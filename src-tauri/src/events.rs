@@ -0,0 +1,192 @@
+//! Internal event bus for state changes.
+//!
+//! Code that changes the vault publishes here instead of updating each part
+//! of the app that cares. Every audited action is published by
+//! `log_audit_event`, every vault write by the save path, and every lock
+//! change by `lock_state`. `run_event_bus` passes each event on to the
+//! frontend (`vault-event`), the tray and desktop notifications. Syncs
+//! announce when they start and stop through `tray_status::begin_sync`.
+//!
+//! HTTP clients read the same events with
+//! `GET /api/events?after=<seq>&wait=<seconds>`. It is a long poll like the
+//! lock state, since the server answers with whole bodies. Passing the `seq`
+//! of the last event seen resumes without gaps, as long as the client is
+//! less than `RECENT_EVENTS` events behind.
+
+use crate::lock_state::LockReason;
+use crate::{notifications, timestamps, tray_status, AppState};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::broadcast;
+use tracing::warn;
+
+pub const EVENTS_PATH: &str = "/api/events";
+/// Longest an events request is held open
+pub const MAX_WAIT_SECS: u64 = 60;
+/// Events kept for clients that poll
+const RECENT_EVENTS: usize = 256;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventPayload {
+    Locked {
+        reason: LockReason,
+    },
+    Unlocked,
+    /// The vault was written to disk
+    Saved,
+    /// An action was recorded in the audit log
+    Audit {
+        action: String,
+        resource_type: String,
+        resource_id: Option<String>,
+        success: bool,
+        error: Option<String>,
+    },
+    /// The first sync started or the last one finished
+    Syncing {
+        running: bool,
+    },
+    /// A pinned key was deleted, so the tray's key list is out of date
+    PinsChanged,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultEvent {
+    /// One more than the previous event's, starting at 1
+    pub seq: u64,
+    #[serde(flatten)]
+    pub payload: EventPayload,
+    #[serde(with = "timestamps::rfc3339")]
+    pub at: DateTime<Utc>,
+}
+
+struct Bus {
+    sender: broadcast::Sender<VaultEvent>,
+    /// Last sequence number and the latest events
+    recent: Mutex<(u64, VecDeque<VaultEvent>)>,
+}
+
+static BUS: LazyLock<Bus> = LazyLock::new(|| Bus {
+    sender: broadcast::Sender::new(RECENT_EVENTS),
+    recent: Mutex::new((0, VecDeque::with_capacity(RECENT_EVENTS))),
+});
+
+pub fn publish(payload: EventPayload) {
+    let mut recent = BUS.recent.lock().unwrap_or_else(|e| e.into_inner());
+    recent.0 += 1;
+    let event = VaultEvent {
+        seq: recent.0,
+        payload,
+        at: Utc::now(),
+    };
+    if recent.1.len() == RECENT_EVENTS {
+        recent.1.pop_front();
+    }
+    recent.1.push_back(event.clone());
+    // Sent under the lock so subscribers see events in `seq` order
+    let _ = BUS.sender.send(event);
+}
+
+pub fn subscribe() -> broadcast::Receiver<VaultEvent> {
+    BUS.sender.subscribe()
+}
+
+/// Kept events newer than `after`
+pub fn since(after: u64) -> Vec<VaultEvent> {
+    let recent = BUS.recent.lock().unwrap_or_else(|e| e.into_inner());
+    recent.1.iter().filter(|e| e.seq > after).cloned().collect()
+}
+
+/// Events newer than `after`, waiting up to `wait` for one if there are none
+pub async fn wait_since(after: u64, wait: Duration) -> Vec<VaultEvent> {
+    // Subscribed first, so an event published in between isn't missed
+    let mut receiver = subscribe();
+    let events = since(after);
+    if !events.is_empty() || wait.is_zero() {
+        return events;
+    }
+    let _ = tokio::time::timeout(wait, receiver.recv()).await;
+    since(after)
+}
+
+async fn handle(app: &AppHandle, event: &VaultEvent) {
+    let _ = app.emit("vault-event", event);
+    let state = app.state::<AppState>();
+    match &event.payload {
+        EventPayload::Locked { .. }
+        | EventPayload::Unlocked
+        | EventPayload::Syncing { .. }
+        | EventPayload::PinsChanged => {
+            tray_status::refresh(&state).await;
+        }
+        EventPayload::Audit {
+            action,
+            success,
+            error,
+            ..
+        } => {
+            notifications::notify_for_audit(&state, action, *success, error.as_deref()).await;
+            tray_status::refresh_if_changed(&state).await;
+        }
+        EventPayload::Saved => tray_status::refresh_if_changed(&state).await,
+    }
+}
+
+/// Pass published events on to the frontend, the tray and notifications
+pub async fn run_event_bus(app: AppHandle) {
+    let mut receiver = subscribe();
+    loop {
+        match receiver.recv().await {
+            Ok(event) => handle(&app, &event).await,
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                warn!(
+                    "Event bus subscriber fell behind, {} events skipped",
+                    missed
+                );
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn events_are_numbered_and_replayed_after_a_sequence_number() {
+        let start = since(0).last().map_or(0, |e| e.seq);
+        let mut receiver = subscribe();
+        publish(EventPayload::Saved);
+        publish(EventPayload::Audit {
+            action: "add_api_key".to_string(),
+            resource_type: "api_key".to_string(),
+            resource_id: Some("k1".to_string()),
+            success: true,
+            error: None,
+        });
+
+        let first = receiver.recv().await.unwrap();
+        assert!(first.seq > start);
+        let replayed = since(first.seq);
+        assert!(replayed.iter().all(|e| e.seq > first.seq));
+        let audit = replayed
+            .iter()
+            .find(|e| matches!(&e.payload, EventPayload::Audit { action, .. } if action == "add_api_key"))
+            .unwrap();
+
+        let json = serde_json::to_value(audit).unwrap();
+        assert_eq!(json["type"], "audit");
+        assert_eq!(json["resource_id"], "k1");
+        assert_eq!(json["seq"], audit.seq);
+
+        let latest = since(0).last().unwrap().seq;
+        let waited = wait_since(latest, Duration::from_millis(10)).await;
+        assert!(waited.iter().all(|e| e.seq > latest));
+    }
+}
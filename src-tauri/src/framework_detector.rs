@@ -1,4 +1,5 @@
 use crate::enhanced_types::{FrameworkDetectionResult, DetectionEvidence};
+use crate::project_ignore::{ProjectIgnores, Purpose};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -599,8 +600,9 @@ impl FrameworkDetector {
 
         // Extract file extensions from pattern
         let extensions = self.extract_extensions_from_pattern(pattern);
+        let mut ignores = ProjectIgnores::load(dir);
         
-        self.visit_directory_recursive(dir, &extensions, &mut matches).await?;
+        self.visit_directory_recursive(dir, &extensions, &mut ignores, &mut matches).await?;
         
         Ok(matches)
     }
@@ -610,6 +612,7 @@ impl FrameworkDetector {
         &'a self,
         dir: &'a Path,
         extensions: &'a [String],
+        ignores: &'a mut ProjectIgnores,
         matches: &'a mut Vec<PathBuf>,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + 'a>> {
         Box::pin(async move {
@@ -622,12 +625,11 @@ impl FrameworkDetector {
             let entry = entry?;
             let path = entry.path();
             
+            if ignores.is_ignored(&path, path.is_dir(), Purpose::Scan) {
+                continue;
+            }
             if path.is_dir() {
-                // Skip node_modules and other common directories to avoid
-                let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                if !["node_modules", ".git", ".next", "dist", "build"].contains(&dir_name) {
-                    self.visit_directory_recursive(&path, extensions, matches).await?;
-                }
+                self.visit_directory_recursive(&path, extensions, ignores, matches).await?;
             } else if path.is_file() {
                 if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
                     if extensions.is_empty() || extensions.contains(&ext.to_string()) {
@@ -678,9 +680,11 @@ impl FrameworkDetector {
 
         // Find files with matching extensions
         let mut candidate_files = Vec::new();
+        let mut ignores = ProjectIgnores::load(project_path);
         self.collect_files_by_extension(
             project_path,
             &content_pattern.file_extensions,
+            &mut ignores,
             &mut candidate_files,
         ).await?;
 
@@ -708,6 +712,7 @@ impl FrameworkDetector {
         &'a self,
         dir: &'a Path,
         extensions: &'a [String],
+        ignores: &'a mut ProjectIgnores,
         files: &'a mut Vec<PathBuf>,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + 'a>> {
         Box::pin(async move {
@@ -724,11 +729,11 @@ impl FrameworkDetector {
             let entry = entry?;
             let path = entry.path();
             
+            if ignores.is_ignored(&path, path.is_dir(), Purpose::Scan) {
+                continue;
+            }
             if path.is_dir() {
-                let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                if !["node_modules", ".git", ".next", "dist", "build"].contains(&dir_name) {
-                    self.collect_files_by_extension(&path, extensions, files).await?;
-                }
+                self.collect_files_by_extension(&path, extensions, ignores, files).await?;
             } else if path.is_file() {
                 if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
                     if extensions.contains(&ext.to_string()) {
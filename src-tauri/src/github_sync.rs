@@ -0,0 +1,603 @@
+use crate::errors::KeyKeeperError;
+use crate::secret_manager_export::hash_secret_value;
+use crate::sync_policy::{self, PolicyViolation};
+use crate::{
+    env_var_name_for_key, guards, log_audit_event, save_vault, tray_status, ApiKeyVault, AppState,
+};
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::State;
+use tokio::sync::Mutex as AsyncMutex;
+use tracing::{info, warn};
+
+const GITHUB_API: &str = "https://api.github.com";
+/// Largest page the secrets API returns
+const SECRETS_PER_PAGE: usize = 100;
+
+/// Tracks which vault key was written to which GitHub Actions secret
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitHubSecretMapping {
+    pub key_id: String,
+    pub repo: String,
+    /// `None` for repository secrets, `Some(name)` for environment secrets
+    pub environment: Option<String>,
+    pub secret_name: String,
+    pub value_hash: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub synced_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitHubSyncResult {
+    pub key_id: String,
+    pub secret_name: String,
+    pub success: bool,
+    pub created: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GitHubDriftStatus {
+    InSync,
+    /// The local value changed since the last sync
+    LocalChanged,
+    /// The vault key no longer exists
+    KeyDeleted,
+    /// The secret is missing on GitHub
+    MissingRemotely,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitHubDriftEntry {
+    pub key_id: String,
+    pub repo: String,
+    pub environment: Option<String>,
+    pub secret_name: String,
+    pub status: GitHubDriftStatus,
+    pub synced_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubPublicKey {
+    key_id: String,
+    key: String,
+}
+
+/// Validate a GitHub secret name: `[A-Z0-9_]`, no leading digit, no `GITHUB_` prefix
+pub fn validate_secret_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        return Err(anyhow!("Secret name cannot be empty"));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(anyhow!(
+            "Secret name {} may only contain letters, digits and underscores",
+            name
+        ));
+    }
+    if name.chars().next().is_some_and(|c| c.is_ascii_digit()) {
+        return Err(anyhow!("Secret name {} cannot start with a digit", name));
+    }
+    if name.to_uppercase().starts_with("GITHUB_") {
+        return Err(anyhow!("Secret name {} cannot start with GITHUB_", name));
+    }
+    Ok(())
+}
+
+/// Validate an `owner/name` repository. Both parts end up in API paths, so
+/// only the characters GitHub itself allows are accepted.
+pub fn validate_repo(repo: &str) -> Result<()> {
+    let Some((owner, name)) = repo.split_once('/') else {
+        return Err(anyhow!("Repository must be in the form owner/name"));
+    };
+    let owner_valid = (1..=39).contains(&owner.len())
+        && owner.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        && !owner.starts_with('-')
+        && !owner.ends_with('-');
+    if !owner_valid {
+        return Err(anyhow!("Invalid repository owner {:?}", owner));
+    }
+    let name_valid = (1..=100).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+        && name != "."
+        && name != "..";
+    if !name_valid {
+        return Err(anyhow!("Invalid repository name {:?}", name));
+    }
+    Ok(())
+}
+
+/// Whether a `Link` response header points to another page
+fn has_next_page(link: Option<&str>) -> bool {
+    link.is_some_and(|link| {
+        link.split(',')
+            .any(|part| part.split(';').skip(1).any(|p| p.trim() == "rel=\"next\""))
+    })
+}
+
+fn secret_names_in(page: &serde_json::Value) -> Vec<String> {
+    page["secrets"]
+        .as_array()
+        .map(|secrets| {
+            secrets
+                .iter()
+                .filter_map(|s| s["name"].as_str().map(|n| n.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Encrypt a value for GitHub with a libsodium sealed box
+pub fn seal_for_github(public_key_b64: &str, value: &str) -> Result<String> {
+    let key_bytes = general_purpose::STANDARD
+        .decode(public_key_b64)
+        .map_err(|e| anyhow!("Invalid repository public key: {}", e))?;
+    let public_key = crypto_box::PublicKey::from_slice(&key_bytes)
+        .map_err(|_| anyhow!("Repository public key has an invalid length"))?;
+    let sealed = public_key
+        .seal(&mut crypto_box::aead::OsRng, value.as_bytes())
+        .map_err(|e| anyhow!("Sealed box encryption failed: {}", e))?;
+    Ok(general_purpose::STANDARD.encode(sealed))
+}
+
+/// Minimal client for the GitHub Actions secrets API
+pub struct GitHubSecretsClient {
+    client: reqwest::Client,
+    token: String,
+}
+
+impl GitHubSecretsClient {
+    pub fn new(token: String) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .user_agent("KeyKeeper-GitHubSync/1.0")
+                .timeout(std::time::Duration::from_secs(30))
+                .build()
+                .unwrap(),
+            token,
+        }
+    }
+
+    fn secrets_base(repo: &str, environment: Option<&str>) -> String {
+        match environment {
+            Some(env) => format!(
+                "{}/repos/{}/environments/{}/secrets",
+                GITHUB_API,
+                repo,
+                urlencoding::encode(env)
+            ),
+            None => format!("{}/repos/{}/actions/secrets", GITHUB_API, repo),
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, url)
+            .bearer_auth(&self.token)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+    }
+
+    async fn public_key(&self, repo: &str, environment: Option<&str>) -> Result<GitHubPublicKey> {
+        validate_repo(repo)?;
+        let url = format!("{}/public-key", Self::secrets_base(repo, environment));
+        let response = self.request(reqwest::Method::GET, &url).send().await?;
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Failed to fetch public key ({}): {}",
+                response.status(),
+                response.text().await.unwrap_or_default()
+            ));
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Create or update a secret. Returns `true` if the secret was created.
+    pub async fn put_secret(
+        &self,
+        repo: &str,
+        environment: Option<&str>,
+        name: &str,
+        value: &str,
+    ) -> Result<bool> {
+        validate_secret_name(name)?;
+        let public_key = self.public_key(repo, environment).await?;
+        let encrypted_value = seal_for_github(&public_key.key, value)?;
+        let url = format!("{}/{}", Self::secrets_base(repo, environment), name);
+        let response = self
+            .request(reqwest::Method::PUT, &url)
+            .json(&serde_json::json!({
+                "encrypted_value": encrypted_value,
+                "key_id": public_key.key_id,
+            }))
+            .send()
+            .await?;
+        match response.status() {
+            reqwest::StatusCode::CREATED => Ok(true),
+            reqwest::StatusCode::NO_CONTENT => Ok(false),
+            status => Err(anyhow!(
+                "Failed to write secret {} ({}): {}",
+                name,
+                status,
+                response.text().await.unwrap_or_default()
+            )),
+        }
+    }
+
+    /// Names of the secrets currently defined on GitHub
+    pub async fn list_secret_names(
+        &self,
+        repo: &str,
+        environment: Option<&str>,
+    ) -> Result<Vec<String>> {
+        validate_repo(repo)?;
+        let mut names = Vec::new();
+        for page in 1.. {
+            let url = format!(
+                "{}?per_page={}&page={}",
+                Self::secrets_base(repo, environment),
+                SECRETS_PER_PAGE,
+                page
+            );
+            let response = self.request(reqwest::Method::GET, &url).send().await?;
+            if !response.status().is_success() {
+                return Err(anyhow!("Failed to list secrets ({})", response.status()));
+            }
+            let next = has_next_page(
+                response
+                    .headers()
+                    .get(reqwest::header::LINK)
+                    .and_then(|v| v.to_str().ok()),
+            );
+            let json: serde_json::Value = response.json().await?;
+            names.extend(secret_names_in(&json));
+            if !next {
+                break;
+            }
+        }
+        Ok(names)
+    }
+}
+
+fn local_drift_status(vault: &ApiKeyVault, mapping: &GitHubSecretMapping) -> GitHubDriftStatus {
+    match vault.keys.get(&mapping.key_id) {
+        None => GitHubDriftStatus::KeyDeleted,
+        Some(key) if hash_secret_value(&key.key) != mapping.value_hash => {
+            GitHubDriftStatus::LocalChanged
+        }
+        Some(_) => GitHubDriftStatus::InSync,
+    }
+}
+
+/// Number of mappings that no longer match the vault, without contacting GitHub
+pub fn count_local_drift(vault: &ApiKeyVault) -> usize {
+    vault
+        .github_secret_mappings
+        .iter()
+        .filter(|m| local_drift_status(vault, m) != GitHubDriftStatus::InSync)
+        .count()
+}
+
+fn github_client_from_vault(
+    token_key_id: &str,
+    vault: &ApiKeyVault,
+) -> Result<GitHubSecretsClient, KeyKeeperError> {
+    let token = vault
+        .keys
+        .get(token_key_id)
+        .map(|k| k.key.clone())
+        .ok_or_else(|| KeyKeeperError::not_found("GitHub token key"))?;
+    Ok(GitHubSecretsClient::new(token))
+}
+
+/// Create or update GitHub Actions secrets from vault keys and record the
+/// mappings in the vault. The caller saves the vault.
+pub async fn push_to_github(
+    vault: &AsyncMutex<ApiKeyVault>,
+    repo: &str,
+    key_ids: &[String],
+    token_key_id: &str,
+    environment: Option<&str>,
+    secret_names: &HashMap<String, String>,
+) -> Result<Vec<GitHubSyncResult>, KeyKeeperError> {
+    let _syncing = tray_status::begin_sync();
+    validate_repo(repo).map_err(|e| KeyKeeperError::Validation(e.to_string()))?;
+
+    let client = github_client_from_vault(token_key_id, &*vault.lock().await)?;
+
+    let keys: Vec<_> = {
+        let vault_guard = vault.lock().await;
+        key_ids
+            .iter()
+            .filter_map(|id| vault_guard.keys.get(id).cloned())
+            .collect()
+    };
+    if keys.len() != key_ids.len() {
+        return Err(KeyKeeperError::NotFound(
+            "Some API keys were not found".to_string(),
+        ));
+    }
+
+    let mut results = Vec::new();
+    let mut mappings = Vec::new();
+    for key in keys {
+        let secret_name = secret_names
+            .get(&key.id)
+            .cloned()
+            .unwrap_or_else(|| env_var_name_for_key(&key));
+
+        match client
+            .put_secret(repo, environment, &secret_name, &key.key)
+            .await
+        {
+            Ok(created) => {
+                info!(
+                    "Synced key {} to GitHub secret {} in {}",
+                    key.id, secret_name, repo
+                );
+                mappings.push(GitHubSecretMapping {
+                    key_id: key.id.clone(),
+                    repo: repo.to_string(),
+                    environment: environment.map(str::to_string),
+                    secret_name: secret_name.clone(),
+                    value_hash: hash_secret_value(&key.key),
+                    synced_at: Utc::now(),
+                });
+                results.push(GitHubSyncResult {
+                    key_id: key.id,
+                    secret_name,
+                    success: true,
+                    created,
+                    error: None,
+                });
+            }
+            Err(e) => {
+                warn!("Failed to sync key {} to GitHub: {}", key.id, e);
+                results.push(GitHubSyncResult {
+                    key_id: key.id,
+                    secret_name,
+                    success: false,
+                    created: false,
+                    error: Some(e.to_string()),
+                });
+            }
+        }
+    }
+
+    let mut vault_guard = vault.lock().await;
+    for mapping in mappings {
+        vault_guard.github_secret_mappings.retain(|m| {
+            !(m.repo == mapping.repo
+                && m.environment == mapping.environment
+                && m.secret_name == mapping.secret_name)
+        });
+        vault_guard.github_secret_mappings.push(mapping);
+    }
+    Ok(results)
+}
+
+/// Secret name each key will be written to
+pub fn planned_secret_names(
+    vault: &ApiKeyVault,
+    key_ids: &[String],
+    secret_names: &HashMap<String, String>,
+) -> Vec<String> {
+    key_ids
+        .iter()
+        .filter_map(|id| {
+            let name = secret_names.get(id).cloned();
+            vault
+                .keys
+                .get(id)
+                .map(|key| name.unwrap_or_else(|| env_var_name_for_key(key)))
+        })
+        .collect()
+}
+
+/// Production keys of `key_ids` bound for a non-production environment of
+/// `repo`
+pub fn policy_violations(
+    vault: &ApiKeyVault,
+    repo: &str,
+    key_ids: &[String],
+    environment: Option<&str>,
+) -> Vec<PolicyViolation> {
+    key_ids
+        .iter()
+        .filter_map(|id| vault.keys.get(id))
+        .filter_map(|key| sync_policy::check(key, repo, environment))
+        .collect()
+}
+
+/// Create or update GitHub Actions secrets from vault keys
+#[tauri::command]
+pub async fn sync_keys_to_github(
+    repo: String,
+    key_ids: Vec<String>,
+    token_key_id: String,
+    environment: Option<String>,
+    secret_names: Option<HashMap<String, String>>,
+    state: State<'_, AppState>,
+) -> Result<Vec<GitHubSyncResult>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let violations = policy_violations(
+        &*state.vault.lock().await,
+        &repo,
+        &key_ids,
+        environment.as_deref(),
+    );
+    sync_policy::confirm(
+        &state.confirmations,
+        &state.vault,
+        &*state.storage,
+        "KeyKeeper",
+        &violations,
+    )
+    .await?;
+
+    let results = push_to_github(
+        &state.vault,
+        &repo,
+        &key_ids,
+        &token_key_id,
+        environment.as_deref(),
+        &secret_names.unwrap_or_default(),
+    )
+    .await?;
+    save_vault(&state).await?;
+
+    let failed = results.iter().filter(|r| !r.success).count();
+    log_audit_event(
+        &state,
+        "sync_keys_to_github",
+        "integration",
+        Some(&repo),
+        failed == 0,
+        Some(&format!(
+            "Synced {} secrets, {} failed",
+            results.len() - failed,
+            failed
+        )),
+    )
+    .await;
+
+    Ok(results)
+}
+
+/// List the GitHub secret mappings recorded in the vault
+#[tauri::command]
+pub async fn get_github_secret_mappings(
+    repo: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<GitHubSecretMapping>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    Ok(vault_guard
+        .github_secret_mappings
+        .iter()
+        .filter(|m| repo.as_ref().is_none_or(|r| &m.repo == r))
+        .cloned()
+        .collect())
+}
+
+/// Report mappings whose local value changed since the last sync.
+/// When `token_key_id` is given, GitHub is also queried for missing secrets.
+#[tauri::command]
+pub async fn get_github_sync_drift(
+    repo: Option<String>,
+    token_key_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<GitHubDriftEntry>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut entries: Vec<GitHubDriftEntry> = {
+        let vault_guard = state.vault.lock().await;
+        vault_guard
+            .github_secret_mappings
+            .iter()
+            .filter(|m| repo.as_ref().is_none_or(|r| &m.repo == r))
+            .map(|m| GitHubDriftEntry {
+                key_id: m.key_id.clone(),
+                repo: m.repo.clone(),
+                environment: m.environment.clone(),
+                secret_name: m.secret_name.clone(),
+                status: local_drift_status(&vault_guard, m),
+                synced_at: m.synced_at,
+            })
+            .collect()
+    };
+
+    if let Some(token_key_id) = token_key_id {
+        let client = github_client_from_vault(&token_key_id, &*state.vault.lock().await)?;
+        let mut remote_cache: HashMap<(String, Option<String>), Vec<String>> = HashMap::new();
+        for entry in entries.iter_mut() {
+            let cache_key = (entry.repo.clone(), entry.environment.clone());
+            if !remote_cache.contains_key(&cache_key) {
+                let names = client
+                    .list_secret_names(&entry.repo, entry.environment.as_deref())
+                    .await
+                    .map_err(|e| e.to_string())?;
+                remote_cache.insert(cache_key.clone(), names);
+            }
+            let exists = remote_cache[&cache_key].contains(&entry.secret_name);
+            if !exists && entry.status == GitHubDriftStatus::InSync {
+                entry.status = GitHubDriftStatus::MissingRemotely;
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repositories_must_be_a_plain_owner_and_name() {
+        for repo in ["octo-org/api", "a/b", "Octo/my.repo_v2", "x/.github"] {
+            assert!(validate_repo(repo).is_ok(), "{}", repo);
+        }
+        for repo in [
+            "octo",
+            "/api",
+            "octo/",
+            "octo/api/extra",
+            "octo/..",
+            "octo/.",
+            "-octo/api",
+            "octo-/api",
+            "octo_org/api",
+            "octo/api?page=2",
+            "octo/api#x",
+            "octo/a%2Fb",
+            "octo/ api",
+            &format!("{}/api", "o".repeat(40)),
+            &format!("octo/{}", "r".repeat(101)),
+        ] {
+            assert!(validate_repo(repo).is_err(), "{}", repo);
+        }
+    }
+
+    #[test]
+    fn only_a_next_link_continues_pagination() {
+        let middle = "<https://api.github.com/repositories/1/actions/secrets?per_page=100&page=3>; rel=\"next\", \
+                      <https://api.github.com/repositories/1/actions/secrets?per_page=100&page=5>; rel=\"last\", \
+                      <https://api.github.com/repositories/1/actions/secrets?per_page=100&page=1>; rel=\"first\"";
+        assert!(has_next_page(Some(middle)));
+
+        let last = "<https://api.github.com/repositories/1/actions/secrets?per_page=100&page=4>; rel=\"prev\", \
+                    <https://api.github.com/repositories/1/actions/secrets?per_page=100&page=1>; rel=\"first\"";
+        assert!(!has_next_page(Some(last)));
+        assert!(!has_next_page(None));
+        assert!(!has_next_page(Some(
+            "<https://example.com/?rel=\"next\">; rel=\"last\""
+        )));
+    }
+
+    #[test]
+    fn secret_names_are_read_from_a_page() {
+        let page = serde_json::json!({
+            "total_count": 3,
+            "secrets": [{"name": "STRIPE_KEY"}, {"created_at": "2024-01-01"}, {"name": "OPENAI_KEY"}]
+        });
+        assert_eq!(secret_names_in(&page), ["STRIPE_KEY", "OPENAI_KEY"]);
+        assert!(secret_names_in(&serde_json::json!({"message": "Not Found"})).is_empty());
+    }
+
+    #[test]
+    fn secret_names_follow_github_rules() {
+        assert!(validate_secret_name("STRIPE_SECRET_KEY").is_ok());
+        assert!(validate_secret_name("").is_err());
+        assert!(validate_secret_name("1KEY").is_err());
+        assert!(validate_secret_name("github_token").is_err());
+        assert!(validate_secret_name("MY-KEY").is_err());
+    }
+}
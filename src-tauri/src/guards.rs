@@ -0,0 +1,413 @@
+//! Preconditions of Tauri commands, checked in one place.
+//!
+//! A command states what it needs as its first line, after wrapping secret
+//! arguments in `Zeroizing` so an early return still wipes them:
+//!
+//! ```ignore
+//! guards::require(&state, &[Requirement::Unlocked, Requirement::CurrentPassword]).await?;
+//! ```
+//!
+//! or `guards::unlocked(&state).await?` for the common case, instead of
+//! reading `is_unlocked` itself. Requirements are checked in order and the
+//! first one not met decides the error, so the frontend gets the same code
+//! for the same reason from every command.
+
+use crate::errors::KeyKeeperError;
+use crate::{clients, AppState};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Requirement {
+    /// The vault is unlocked
+    Unlocked,
+    /// A user account has been created
+    Account,
+    /// No new master password is owed after a recovery unlock
+    CurrentPassword,
+    /// Called from the desktop app, not while answering an HTTP request
+    LocalOnly,
+}
+
+impl Requirement {
+    fn reads_vault(self) -> bool {
+        matches!(self, Requirement::Account | Requirement::CurrentPassword)
+    }
+}
+
+/// What the requirements are checked against
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Context {
+    pub unlocked: bool,
+    pub has_account: bool,
+    pub password_reset_required: bool,
+    pub over_http: bool,
+}
+
+/// The error for the first requirement `context` doesn't meet
+pub fn evaluate(context: &Context, requirements: &[Requirement]) -> Result<(), KeyKeeperError> {
+    for requirement in requirements {
+        let met = match requirement {
+            Requirement::Unlocked => context.unlocked,
+            Requirement::Account => context.has_account,
+            Requirement::CurrentPassword => !context.password_reset_required,
+            Requirement::LocalOnly => !context.over_http,
+        };
+        if met {
+            continue;
+        }
+        return Err(match requirement {
+            Requirement::Unlocked => KeyKeeperError::VaultLocked,
+            Requirement::Account => {
+                KeyKeeperError::Denied("Create a user account first".to_string())
+            }
+            Requirement::CurrentPassword => KeyKeeperError::Denied(
+                "Set a new master password before using the vault".to_string(),
+            ),
+            Requirement::LocalOnly => {
+                KeyKeeperError::Denied("Only available in the desktop app".to_string())
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Check `requirements` against the app's state. The vault is only locked
+/// when a requirement reads it, so this can run before the command locks it.
+pub async fn require(state: &AppState, requirements: &[Requirement]) -> Result<(), KeyKeeperError> {
+    let mut context = Context {
+        unlocked: *state.is_unlocked.lock().await,
+        over_http: clients::current().is_some(),
+        ..Context::default()
+    };
+    if requirements.iter().any(|r| r.reads_vault()) {
+        let vault_guard = state.vault.lock().await;
+        context.has_account = vault_guard.user_account.is_some();
+        context.password_reset_required = vault_guard.password_reset_required;
+    }
+    evaluate(&context, requirements)
+}
+
+/// `require(state, &[Requirement::Unlocked])`
+pub async fn unlocked(state: &AppState) -> Result<(), KeyKeeperError> {
+    require(state, &[Requirement::Unlocked]).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    /// Registered commands that check no requirement, on purpose
+    const UNGUARDED: &[&str] = &[
+        // Unlocking, first-run setup and the lock screen
+        "unlock_vault",
+        "set_master_password",
+        "save_master_password_to_keyring",
+        "get_master_password_from_keyring",
+        "delete_master_password_from_keyring",
+        "is_vault_unlocked",
+        "lock_vault",
+        "recovery_kit::unlock_with_recovery_code",
+        "hardware_unlock::get_hardware_unlock_status",
+        "hardware_unlock::unlock_with_hardware_key",
+        "keyring_store::list_keyring_entries",
+        "keyring_store::cleanup_keyring_entries",
+        "keyring_set",
+        "keyring_get",
+        "keyring_delete",
+        "lock_state::record_activity",
+        "lock_state::get_lock_state",
+        "create_user_account",
+        "authenticate_user",
+        "request_password_recovery",
+        "reset_master_password",
+        "get_user_account",
+        "is_user_account_created",
+        "is_master_password_set",
+        "check_biometric_support",
+        "authenticate_biometric",
+        "create_passkey_challenge",
+        "verify_passkey_challenge",
+        "validate_remember_me_session",
+        "restore_session_on_startup",
+        "password_strength::estimate_password_strength",
+        "demo_vault::create_demo_vault",
+        // Serve the metadata-only view while locked
+        "get_api_keys",
+        "vault_location::get_vault_location",
+        "vault_integrity::verify_vault_integrity",
+        "diagnostics::export_diagnostics",
+        "ml_commands::analyze_context_ml",
+        // Status, logs, windows and the app itself
+        "keystore::get_keystore_backend",
+        "start_vscode_server",
+        "stop_vscode_server",
+        "get_vscode_server_status",
+        "confirmations::get_confirmation_request",
+        "instance::take_pending_launches",
+        "open_folder",
+        "open_file",
+        "open_in_vscode",
+        "show_window",
+        "hide_window",
+        "window_manager::open_window",
+        "window_manager::close_window",
+        "window_manager::focus_window",
+        "window_manager::list_windows",
+        "window_manager::open_key_in_main_window",
+        "quit_application",
+        "check_for_updates",
+        "install_update",
+        "logging::get_recent_logs",
+        "request_log::get_http_request_log",
+        "diagnostics::take_crash_report",
+        "health::get_health_report",
+        "config::get_config",
+        "audit_sinks::get_audit_sink_status",
+        "background_tasks::list_background_tasks",
+        "legacy_tcp::get_legacy_tcp_usage",
+        "get_device_info",
+        "setup_auto_start",
+        "disable_auto_start",
+        "is_auto_start_enabled",
+        "show_notification",
+        // Documentation, which stays usable while locked
+        "doc_tags::list_doc_tags",
+        "doc_tags::rename_doc_tag",
+        "doc_tags::suggest_doc_tags",
+        "doc_revisions::get_doc_revisions",
+        "doc_revisions::diff_doc_revisions",
+        "doc_revisions::restore_doc_revision",
+        "prompt_templates::list_prompt_templates",
+        "prompt_templates::save_prompt_template",
+        "prompt_templates::reset_prompt_template",
+        "prompt_templates::set_active_prompt_template",
+        "docs_commands::add_provider_documentation",
+        "docs_commands::get_provider_documentation",
+        "docs_commands::search_documentation",
+        "docs_commands::get_documentation_by_id",
+        "docs_commands::update_provider_documentation",
+        "docs_commands::remove_provider_documentation",
+        "docs_commands::get_indexed_providers",
+        "docs_commands::auto_index_provider_docs",
+        "docs_commands::get_context_documentation_suggestions",
+        "docs_bundles::list_offline_bundles",
+        "docs_bundles::delete_offline_bundle",
+        "docs_generator_commands::initialize_docs_llm",
+        "docs_generator_commands::generate_project_documentation",
+        "docs_generator_commands::generate_api_documentation",
+        "docs_generator_commands::generate_setup_guide",
+        "docs_generator_commands::generate_deployment_guide",
+        "docs_generator_commands::generate_template_documentation",
+        "docs_generator_commands::export_documentation",
+        "docs_generator_commands::set_docs_generator_config",
+        "docs_generator_commands::get_docs_generation_stats",
+        "docs_generator_commands::generate_enhanced_documentation",
+        "docs_library_commands::validate_documentation_url",
+        "docs_generator_commands::generate_batch_documentation",
+        "docs_generator_commands::get_documentation_templates",
+        "docs_generator_commands::validate_documentation_content",
+        "docs_generator_commands::generate_documentation_preview",
+        "pagination::list_documentation",
+        // Code generation and local models, which never read the vault
+        "ml_commands::initialize_ml_engine",
+        "ml_commands::record_ml_usage",
+        "ml_commands::get_ml_stats",
+        "ml_commands::check_ml_status",
+        "ml_commands::reinitialize_ml_engine",
+        "ml_commands::get_ml_config",
+        "ml_commands::detect_context",
+        "ml_commands::generate_documentation",
+        "ml_commands::generate_usage_examples",
+        "ml_commands::generate_config_template",
+        "ml_commands::get_config_recommendations",
+        "ml_commands::diagnose_ml_setup",
+        "llm_proxy::process_with_llm",
+        "llm_proxy::clear_llm_cache",
+        "llm_proxy::get_llm_cache_stats",
+        "llm_proxy::is_llm_engine_loaded",
+        "llm_proxy::get_ollama_models",
+        "llm_proxy::pull_ollama_model_command",
+        "llm_proxy::check_ollama_status",
+        "llm_proxy::delete_ollama_model",
+        "llm_wrapper::validate_llm_model",
+        "llm_wrapper::test_local_llm_generation",
+        "api_generator_commands::get_api_providers",
+        "api_generator_commands::scrape_api_documentation",
+        "api_generator_commands::generate_api_configuration",
+        "api_generator_commands::detect_provider_from_env",
+        "api_generator_commands::generate_better_auth_config",
+        "api_generator_commands::generate_openai_config",
+        "api_generator_commands::get_provider_templates",
+        "api_generator_commands::preview_generated_config",
+        "enhanced_api_commands::generate_enhanced_configuration",
+        "enhanced_api_commands::detect_project_framework",
+        "enhanced_api_commands::validate_template_combination",
+        "enhanced_api_commands::preview_generated_files",
+        "enhanced_api_commands::get_template_suggestions",
+        "enhanced_api_commands::register_custom_template",
+        "enhanced_api_commands::get_generation_session_status",
+        "enhanced_api_commands::cancel_generation_session",
+        "enhanced_api_commands::get_active_generation_sessions",
+        "enhanced_api_commands::clear_generation_cache",
+        "enhanced_api_commands::get_cache_statistics",
+        "enhanced_api_commands::batch_validate_templates",
+        "enhanced_api_commands::get_provider_framework_compatibility",
+        "framework_generator_commands::get_supported_frameworks",
+        "framework_generator_commands::generate_framework_config",
+        "framework_generator_commands::generate_api_integration",
+        "framework_generator_commands::generate_auth_integration",
+        "framework_generator_commands::generate_state_management",
+        "framework_generator_commands::generate_testing_setup",
+        "framework_generator_commands::enhance_package_json",
+        "framework_generator_commands::validate_framework_requirements",
+        "framework_generator_commands::get_framework_llm_prompt",
+        "framework_generator_commands::initialize_framework_llm",
+        "framework_generator_commands::generate_complete_framework_setup",
+        "ai_commands::analyze_project_with_ai",
+        "ai_commands::get_contextual_suggestions",
+        "ai_commands::get_smart_template_recommendations",
+        "ai_commands::analyze_code_quality_with_ai",
+        "ai_commands::generate_contextual_documentation",
+        "ai_commands::check_ai_features_status",
+        "ai_commands::clear_ai_analysis_cache",
+        "ai_commands::get_ai_cache_stats",
+        "validation_setup_commands::validate_generated_configuration",
+        "validation_setup_commands::generate_setup_scripts",
+        "validation_setup_commands::check_environment_compatibility",
+        "validation_setup_commands::validate_security_configuration",
+        "validation_setup_commands::generate_deployment_config",
+        "validation_setup_commands::get_validation_engine_status",
+        "validation_setup_commands::get_setup_generator_capabilities",
+        "validation_setup_commands::batch_validate_configurations",
+        "validation_setup_commands::get_validation_setup_statistics",
+    ];
+
+    fn source(module: &str) -> String {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src")
+            .join(format!("{}.rs", module));
+        std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+    }
+
+    /// The commands in `generate_handler!`, as written there
+    fn registered_commands() -> Vec<String> {
+        let lib = source("lib");
+        let start = lib.find("generate_handler![").unwrap() + "generate_handler![".len();
+        let end = start + lib[start..].find(']').unwrap();
+        lib[start..end]
+            .lines()
+            .map(|line| line.split("//").next().unwrap())
+            .flat_map(|line| line.split(','))
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    /// First line of the command's body, skipping comments and the
+    /// arguments wrapped in `Zeroizing`
+    fn first_line(command: &str) -> Option<String> {
+        let (module, name) = command.rsplit_once("::").unwrap_or(("lib", command));
+        let source = source(module);
+        let signature = format!("fn {}(", name);
+        let at = source
+            .match_indices(&signature)
+            .map(|(i, _)| i)
+            .find(|&i| {
+                source[..i]
+                    .rfind("command]")
+                    .is_some_and(|attribute| !source[attribute..i].contains("\n}"))
+            })?;
+        let mut depth = 0;
+        let mut parameters_end = at;
+        for (offset, c) in source[at..].char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => continue,
+            }
+            if depth == 0 {
+                parameters_end = at + offset;
+                break;
+            }
+        }
+        let body = parameters_end + source[parameters_end..].find('{')? + 1;
+        source[body..]
+            .lines()
+            .map(str::trim)
+            .find(|line| {
+                !line.is_empty() && !line.starts_with("//") && !line.contains("Zeroizing::new")
+            })
+            .map(String::from)
+    }
+
+    #[test]
+    fn every_registered_command_declares_its_requirements() {
+        let commands = registered_commands();
+        assert!(commands.len() > 100, "found {} commands", commands.len());
+        for command in &commands {
+            let guarded = first_line(command)
+                .unwrap_or_else(|| panic!("no body found for {}", command))
+                .contains("guards::");
+            let listed = UNGUARDED.contains(&command.as_str());
+            assert!(
+                guarded || listed,
+                "{} checks no requirement: call guards first or list it in UNGUARDED",
+                command
+            );
+            assert!(
+                !(guarded && listed),
+                "{} is guarded, remove it from UNGUARDED",
+                command
+            );
+        }
+        for command in UNGUARDED {
+            assert!(
+                commands.iter().any(|c| c == command),
+                "{} is listed in UNGUARDED but not registered",
+                command
+            );
+        }
+    }
+
+    #[test]
+    fn the_first_unmet_requirement_decides_the_error() {
+        let all = [
+            Requirement::Unlocked,
+            Requirement::Account,
+            Requirement::CurrentPassword,
+            Requirement::LocalOnly,
+        ];
+        let mut context = Context::default();
+        assert!(matches!(
+            evaluate(&context, &all),
+            Err(KeyKeeperError::VaultLocked)
+        ));
+        assert!(evaluate(&context, &[]).is_ok());
+        assert!(evaluate(&context, &[Requirement::CurrentPassword]).is_ok());
+
+        context.unlocked = true;
+        assert!(matches!(
+            evaluate(&context, &all),
+            Err(KeyKeeperError::Denied(message)) if message.contains("user account")
+        ));
+
+        context.has_account = true;
+        context.password_reset_required = true;
+        assert!(evaluate(&context, &[Requirement::Unlocked]).is_ok());
+        assert!(matches!(
+            evaluate(&context, &all),
+            Err(KeyKeeperError::Denied(message)) if message.contains("master password")
+        ));
+
+        context.password_reset_required = false;
+        context.over_http = true;
+        assert!(matches!(
+            evaluate(&context, &all),
+            Err(KeyKeeperError::Denied(message)) if message.contains("desktop app")
+        ));
+
+        context.over_http = false;
+        assert!(evaluate(&context, &all).is_ok());
+    }
+}
@@ -0,0 +1,694 @@
+//! Hardware-backed unlock.
+//!
+//! Instead of saving the master password in the OS keyring, the vault can be
+//! unlocked with a random device key. The device key wraps the master key,
+//! mirrored into the metadata file on every save like the recovery kit, and
+//! is itself wrapped by the platform's secure element: a Secure Enclave key
+//! that needs Touch ID or the login password on macOS, a TPM key behind a
+//! consent prompt on Windows. Machines without either keep the device key in
+//! the OS keyring, which can't ask for the user's presence but still never
+//! holds the master password.
+//!
+//! Enabling hardware unlock deletes a master password saved in the keyring
+//! by earlier versions. When the platform key is gone (TPM cleared, vault
+//! moved to another machine) the master password still unlocks the vault.
+
+use crate::errors::KeyKeeperError;
+use crate::keyring_store::{self, KeyringStore};
+use crate::vault_location::decrypt_with_key;
+use crate::{
+    decrypt_data, encrypt_data, guards, log_audit_event, save_vault, AppState, VaultMetadata,
+};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tauri::{AppHandle, State};
+use tracing::warn;
+use zeroize::Zeroizing;
+
+const KEY_LABEL_PREFIX: &str = "com.keykeeper.vault-unlock";
+/// Where earlier versions saved the master password
+const LEGACY_MASTER_PASSWORD: (&str, &str) =
+    (keyring_store::SERVICE, keyring_store::MASTER_PASSWORD);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HardwareBackend {
+    SecureEnclave,
+    Tpm,
+    /// OS keyring, for machines without a secure element
+    Keyring,
+}
+
+impl HardwareBackend {
+    fn name(self) -> &'static str {
+        match self {
+            HardwareBackend::SecureEnclave => "Secure Enclave",
+            HardwareBackend::Tpm => "TPM",
+            HardwareBackend::Keyring => "OS keyring",
+        }
+    }
+
+    pub fn requires_presence(self) -> bool {
+        self != HardwareBackend::Keyring
+    }
+}
+
+/// Device key as stored inside the encrypted vault
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HardwareUnlockKey {
+    pub backend: HardwareBackend,
+    /// Label of the platform key or keyring entry
+    pub key_label: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    /// Base64 key that wraps the master key; never written in clear
+    pub device_key: String,
+    /// Device key wrapped by the platform key; empty for `Keyring`
+    pub wrapped_device_key: String,
+}
+
+/// Device key as mirrored into the metadata file, usable while locked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HardwareEnvelope {
+    pub backend: HardwareBackend,
+    pub key_label: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    pub wrapped_device_key: String,
+    /// Master key encrypted with the device key
+    pub wrapped_master_key: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HardwareUnlockStatus {
+    /// Secure element of this machine, if it has one
+    pub hardware_backend: Option<HardwareBackend>,
+    /// Backend of the vault's device key, if hardware unlock is enabled
+    pub enabled_backend: Option<HardwareBackend>,
+    pub requires_presence: bool,
+    #[serde(with = "crate::timestamps::rfc3339_option")]
+    pub enabled_at: Option<DateTime<Utc>>,
+    /// A master password saved by an earlier version is still in the keyring
+    pub legacy_password_stored: bool,
+}
+
+fn decode_key(key: &str) -> Result<Zeroizing<Vec<u8>>, String> {
+    general_purpose::STANDARD
+        .decode(key)
+        .map(Zeroizing::new)
+        .map_err(|e| format!("Failed to decode device key: {}", e))
+}
+
+/// Wrap the current master key for the metadata file
+pub fn envelope(key: &HardwareUnlockKey, master_key: &str) -> Result<HardwareEnvelope, String> {
+    let device_key = decode_key(&key.device_key)?;
+    Ok(HardwareEnvelope {
+        backend: key.backend,
+        key_label: key.key_label.clone(),
+        created_at: key.created_at,
+        wrapped_device_key: key.wrapped_device_key.clone(),
+        wrapped_master_key: encrypt_data(master_key, &device_key)?,
+    })
+}
+
+/// Store the device key with `backend`, returning it wrapped (base64)
+fn seal(
+    store: &KeyringStore,
+    backend: HardwareBackend,
+    label: &str,
+    device_key: &[u8],
+) -> Result<String, String> {
+    if backend == HardwareBackend::Keyring {
+        let device_key = Zeroizing::new(general_purpose::STANDARD.encode(device_key));
+        store.set(label, &device_key)?;
+        return Ok(String::new());
+    }
+    if platform::available() != Some(backend) {
+        return Err(format!("This machine has no {}", backend.name()));
+    }
+    platform::seal(label, device_key).map(|wrapped| general_purpose::STANDARD.encode(wrapped))
+}
+
+/// Unwrap the device key, asking for the user's presence when the backend
+/// can
+fn open(store: &KeyringStore, envelope: &HardwareEnvelope) -> Result<Zeroizing<Vec<u8>>, String> {
+    if envelope.backend == HardwareBackend::Keyring {
+        let device_key = store
+            .get(&envelope.key_label, None)?
+            .ok_or_else(|| "Device key not found in keyring".to_string())?;
+        return decode_key(&device_key);
+    }
+    if platform::BACKEND != Some(envelope.backend) {
+        return Err(format!(
+            "The vault's device key is held by a {} on another machine",
+            envelope.backend.name()
+        ));
+    }
+    let wrapped = general_purpose::STANDARD
+        .decode(&envelope.wrapped_device_key)
+        .map_err(|e| format!("Failed to decode wrapped device key: {}", e))?;
+    platform::open(&envelope.key_label, &wrapped)
+}
+
+/// Delete the platform key or keyring entry of a device key
+fn forget(store: &KeyringStore, backend: HardwareBackend, label: &str) -> Result<(), String> {
+    match backend {
+        HardwareBackend::Keyring => store.delete(label, None).map(|_| ()),
+        _ if platform::BACKEND == Some(backend) => platform::delete(label),
+        _ => Ok(()),
+    }
+}
+
+fn read_envelope(state: &AppState) -> Option<HardwareEnvelope> {
+    let metadata = fs::read_to_string(state.vault_path.metadata_path()).ok()?;
+    serde_json::from_str::<VaultMetadata>(&metadata)
+        .ok()?
+        .hardware_unlock
+}
+
+fn legacy_password_stored(store: &KeyringStore) -> bool {
+    matches!(
+        store.get(keyring_store::MASTER_PASSWORD, Some(LEGACY_MASTER_PASSWORD)),
+        Ok(Some(_))
+    )
+}
+
+async fn status(store: &KeyringStore, state: &AppState) -> HardwareUnlockStatus {
+    let enabled = if *state.is_unlocked.lock().await {
+        state
+            .vault
+            .lock()
+            .await
+            .hardware_unlock
+            .as_ref()
+            .map(|key| (key.backend, key.created_at))
+    } else {
+        read_envelope(state).map(|envelope| (envelope.backend, envelope.created_at))
+    };
+    HardwareUnlockStatus {
+        hardware_backend: platform::available(),
+        enabled_backend: enabled.map(|(backend, _)| backend),
+        requires_presence: enabled.is_some_and(|(backend, _)| backend.requires_presence()),
+        enabled_at: enabled.map(|(_, created_at)| created_at),
+        legacy_password_stored: legacy_password_stored(store),
+    }
+}
+
+/// Usable while the vault is locked, so the unlock screen can offer it
+#[tauri::command]
+pub async fn get_hardware_unlock_status(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<HardwareUnlockStatus, KeyKeeperError> {
+    let store = KeyringStore::open(&app, &state).await?;
+    Ok(status(&store, &state).await)
+}
+
+/// Enable hardware unlock with `backend`, by default the machine's secure
+/// element or else the OS keyring. Replaces an earlier device key and
+/// removes a master password saved in the keyring.
+#[tauri::command]
+pub async fn enable_hardware_unlock(
+    backend: Option<HardwareBackend>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<HardwareUnlockStatus, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let backend = backend
+        .or_else(platform::available)
+        .unwrap_or(HardwareBackend::Keyring);
+    let key_label = format!("{}.{}", KEY_LABEL_PREFIX, uuid::Uuid::new_v4());
+    let mut device_key = Zeroizing::new([0u8; 32]);
+    OsRng.fill_bytes(device_key.as_mut());
+
+    let store = KeyringStore::open(&app, &state).await?;
+    let sealed = {
+        let store = store.clone();
+        let key_label = key_label.clone();
+        let device_key = device_key.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            seal(&store, backend, &key_label, &*device_key)
+        })
+        .await
+        .map_err(|e| KeyKeeperError::Internal(format!("Hardware unlock failed: {}", e)))?
+    };
+    let wrapped_device_key = match sealed {
+        Ok(wrapped) => wrapped,
+        Err(e) => {
+            log_audit_event(
+                &state,
+                "enable_hardware_unlock",
+                "vault",
+                None,
+                false,
+                Some(&e),
+            )
+            .await;
+            return Err(KeyKeeperError::Crypto(e));
+        }
+    };
+
+    let mut vault_guard = state.vault.lock().await;
+    let previous = vault_guard.hardware_unlock.replace(HardwareUnlockKey {
+        backend,
+        key_label,
+        created_at: Utc::now(),
+        device_key: general_purpose::STANDARD.encode(&device_key[..]),
+        wrapped_device_key,
+    });
+    drop(vault_guard);
+    save_vault(&state).await?;
+
+    if let Some(previous) = previous {
+        if let Err(e) = forget(&store, previous.backend, &previous.key_label) {
+            warn!("Failed to delete previous device key: {}", e);
+        }
+    }
+    if let Err(e) = store.delete(keyring_store::MASTER_PASSWORD, Some(LEGACY_MASTER_PASSWORD)) {
+        warn!("Failed to delete master password from keyring: {}", e);
+    }
+
+    log_audit_event(
+        &state,
+        "enable_hardware_unlock",
+        "vault",
+        None,
+        true,
+        Some(backend.name()),
+    )
+    .await;
+    Ok(status(&store, &state).await)
+}
+
+#[tauri::command]
+pub async fn disable_hardware_unlock(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<HardwareUnlockStatus, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let key = vault_guard
+        .hardware_unlock
+        .take()
+        .ok_or_else(|| KeyKeeperError::not_found("Hardware unlock"))?;
+    drop(vault_guard);
+    save_vault(&state).await?;
+
+    let store = KeyringStore::open(&app, &state).await?;
+    let removed = {
+        let store = store.clone();
+        tauri::async_runtime::spawn_blocking(move || forget(&store, key.backend, &key.key_label))
+            .await
+    };
+    if let Ok(Err(e)) = removed {
+        warn!("Failed to delete device key: {}", e);
+    }
+    log_audit_event(&state, "disable_hardware_unlock", "vault", None, true, None).await;
+    Ok(status(&store, &state).await)
+}
+
+/// Unlock the vault with the device key. When the platform key can't be
+/// used the error says so, and the master password is the way in.
+#[tauri::command]
+pub async fn unlock_with_hardware_key(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<bool, KeyKeeperError> {
+    if *state.is_unlocked.lock().await {
+        return Err(KeyKeeperError::Validation(
+            "Vault is already unlocked".to_string(),
+        ));
+    }
+
+    let envelope =
+        read_envelope(&state).ok_or_else(|| KeyKeeperError::not_found("Hardware unlock"))?;
+    let path = state.vault_path.path();
+    let store = KeyringStore::open(&app, &state).await?;
+    let unlocked = tauri::async_runtime::spawn_blocking(move || {
+        let device_key = open(&store, &envelope)?;
+        let master_key = Zeroizing::new(decrypt_data(&envelope.wrapped_master_key, &device_key)?);
+        decrypt_with_key(&path, &master_key)
+    })
+    .await
+    .map_err(|e| KeyKeeperError::Internal(format!("Hardware unlock failed: {}", e)))?;
+    let vault = match unlocked {
+        Ok(vault) => vault,
+        Err(e) => {
+            log_audit_event(
+                &state,
+                "unlock_with_hardware_key",
+                "vault",
+                None,
+                false,
+                Some(&e),
+            )
+            .await;
+            return Err(KeyKeeperError::Crypto(format!(
+                "{}. Unlock with the master password instead.",
+                e
+            )));
+        }
+    };
+
+    let mut vault_guard = state.vault.lock().await;
+    crate::secure_memory::wipe_vault_secrets(&mut vault_guard);
+    *vault_guard = vault;
+    drop(vault_guard);
+    *state.is_unlocked.lock().await = true;
+    if let Err(e) = crate::sessions::begin_unlock_session(&state).await {
+        warn!("Failed to start unlock session: {}", e);
+    }
+    state.vault_index.lock().await.invalidate();
+
+    log_audit_event(
+        &state,
+        "unlock_with_hardware_key",
+        "vault",
+        None,
+        true,
+        None,
+    )
+    .await;
+    Ok(true)
+}
+
+/// Secure Enclave: a P-256 key that never leaves the enclave and can only
+/// decrypt after Touch ID or the login password
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::HardwareBackend;
+    use security_framework::access_control::{ProtectionMode, SecAccessControl};
+    use security_framework::item::{
+        ItemSearchOptions, KeyClass, Location, Reference, SearchResult,
+    };
+    use security_framework::key::{Algorithm, GenerateKeyOptions, KeyType, SecKey, Token};
+    use security_framework_sys::access_control::{
+        kSecAccessControlPrivateKeyUsage, kSecAccessControlUserPresence,
+    };
+    use zeroize::Zeroizing;
+
+    pub const BACKEND: Option<HardwareBackend> = Some(HardwareBackend::SecureEnclave);
+    const ALGORITHM: Algorithm = Algorithm::ECIESEncryptionStandardVariableIVX963SHA256AESGCM;
+
+    /// Options for a permanent key when `label` is set, a throwaway one
+    /// otherwise
+    fn options(label: Option<&str>) -> Result<GenerateKeyOptions, String> {
+        let access = SecAccessControl::create_with_protection(
+            Some(ProtectionMode::AccessibleWhenUnlockedThisDeviceOnly),
+            kSecAccessControlPrivateKeyUsage | kSecAccessControlUserPresence,
+        )
+        .map_err(|e| format!("Failed to create access control: {}", e))?;
+        let mut options = GenerateKeyOptions::default();
+        options
+            .set_key_type(KeyType::ec_sec_prime_random())
+            .set_size_in_bits(256)
+            .set_token(Token::SecureEnclave)
+            .set_access_control(access);
+        if let Some(label) = label {
+            options
+                .set_label(label)
+                .set_location(Location::DataProtectionKeychain);
+        }
+        Ok(options)
+    }
+
+    /// Macs without an enclave fail to create even a throwaway key
+    pub fn available() -> Option<HardwareBackend> {
+        let key =
+            options(None).and_then(|options| SecKey::new(&options).map_err(|e| e.to_string()));
+        key.ok().and(BACKEND)
+    }
+
+    fn find(label: &str) -> Result<Option<SecKey>, String> {
+        let results = match ItemSearchOptions::new()
+            .key_class(KeyClass::private())
+            .label(label)
+            .ignore_legacy_keychains()
+            .load_refs(true)
+            .search()
+        {
+            Ok(results) => results,
+            Err(e) if e.code() == security_framework_sys::base::errSecItemNotFound => {
+                return Ok(None)
+            }
+            Err(e) => return Err(format!("Failed to look up Secure Enclave key: {}", e)),
+        };
+        Ok(results.into_iter().find_map(|result| match result {
+            SearchResult::Ref(Reference::Key(key)) => Some(key),
+            _ => None,
+        }))
+    }
+
+    pub fn seal(label: &str, secret: &[u8]) -> Result<Vec<u8>, String> {
+        let key = SecKey::new(&options(Some(label))?)
+            .map_err(|e| format!("Failed to create Secure Enclave key: {}", e))?;
+        key.public_key()
+            .ok_or_else(|| "Secure Enclave key has no public key".to_string())?
+            .encrypt_data(ALGORITHM, secret)
+            .map_err(|e| format!("Failed to wrap device key: {}", e))
+    }
+
+    pub fn open(label: &str, wrapped: &[u8]) -> Result<Zeroizing<Vec<u8>>, String> {
+        find(label)?
+            .ok_or_else(|| "The Secure Enclave key of this vault no longer exists".to_string())?
+            .decrypt_data(ALGORITHM, wrapped)
+            .map(Zeroizing::new)
+            .map_err(|e| format!("Secure Enclave refused to unwrap the device key: {}", e))
+    }
+
+    pub fn delete(label: &str) -> Result<(), String> {
+        match find(label)? {
+            Some(key) => key
+                .delete()
+                .map_err(|e| format!("Failed to delete Secure Enclave key: {}", e)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// TPM through the Platform Crypto Provider: an RSA key that stays in the
+/// TPM, with a UI policy that makes Windows ask before each use
+#[cfg(windows)]
+mod platform {
+    use super::HardwareBackend;
+    use std::ffi::c_void;
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::Security::Cryptography::{
+        NCryptCreatePersistedKey, NCryptDecrypt, NCryptDeleteKey, NCryptEncrypt, NCryptFinalizeKey,
+        NCryptFreeObject, NCryptOpenKey, NCryptOpenStorageProvider, NCryptSetProperty,
+        BCRYPT_OAEP_PADDING_INFO, BCRYPT_SHA256_ALGORITHM, CERT_KEY_SPEC,
+        MS_PLATFORM_CRYPTO_PROVIDER, NCRYPT_FLAGS, NCRYPT_HANDLE, NCRYPT_KEY_HANDLE,
+        NCRYPT_OVERWRITE_KEY_FLAG, NCRYPT_PAD_OAEP_FLAG, NCRYPT_PROV_HANDLE, NCRYPT_RSA_ALGORITHM,
+        NCRYPT_UI_FORCE_HIGH_PROTECTION_FLAG, NCRYPT_UI_POLICY, NCRYPT_UI_POLICY_PROPERTY,
+    };
+    use zeroize::Zeroizing;
+
+    pub const BACKEND: Option<HardwareBackend> = Some(HardwareBackend::Tpm);
+
+    type Crypt = unsafe fn(
+        NCRYPT_KEY_HANDLE,
+        Option<&[u8]>,
+        Option<*const c_void>,
+        Option<&mut [u8]>,
+        *mut u32,
+        NCRYPT_FLAGS,
+    ) -> windows::core::Result<()>;
+
+    /// Frees an NCrypt handle when dropped
+    struct Owned<T: Copy + Into<NCRYPT_HANDLE>>(T);
+
+    impl<T: Copy + Into<NCRYPT_HANDLE>> Drop for Owned<T> {
+        fn drop(&mut self) {
+            unsafe {
+                let _ = NCryptFreeObject(self.0.into());
+            }
+        }
+    }
+
+    fn provider() -> windows::core::Result<Owned<NCRYPT_PROV_HANDLE>> {
+        let mut provider = NCRYPT_PROV_HANDLE::default();
+        unsafe { NCryptOpenStorageProvider(&mut provider, MS_PLATFORM_CRYPTO_PROVIDER, 0)? };
+        Ok(Owned(provider))
+    }
+
+    pub fn available() -> Option<HardwareBackend> {
+        provider().ok().and(BACKEND)
+    }
+
+    fn open_key(label: &str) -> Result<Owned<NCRYPT_KEY_HANDLE>, String> {
+        let provider = provider().map_err(|e| format!("TPM unavailable: {}", e))?;
+        let mut key = NCRYPT_KEY_HANDLE::default();
+        unsafe {
+            NCryptOpenKey(
+                provider.0,
+                &mut key,
+                &HSTRING::from(label),
+                CERT_KEY_SPEC(0),
+                NCRYPT_FLAGS(0),
+            )
+        }
+        .map_err(|_| "The TPM key of this vault no longer exists".to_string())?;
+        Ok(Owned(key))
+    }
+
+    /// RSA-OAEP with SHA-256, sized with a first call
+    fn crypt(
+        key: &Owned<NCRYPT_KEY_HANDLE>,
+        input: &[u8],
+        call: Crypt,
+    ) -> windows::core::Result<Vec<u8>> {
+        let padding = BCRYPT_OAEP_PADDING_INFO {
+            pszAlgId: BCRYPT_SHA256_ALGORITHM,
+            ..Default::default()
+        };
+        let padding = Some(&padding as *const BCRYPT_OAEP_PADDING_INFO as *const c_void);
+        let mut size = 0u32;
+        unsafe {
+            call(
+                key.0,
+                Some(input),
+                padding,
+                None,
+                &mut size,
+                NCRYPT_PAD_OAEP_FLAG,
+            )?
+        };
+        let mut output = vec![0u8; size as usize];
+        unsafe {
+            call(
+                key.0,
+                Some(input),
+                padding,
+                Some(&mut output),
+                &mut size,
+                NCRYPT_PAD_OAEP_FLAG,
+            )?
+        };
+        output.truncate(size as usize);
+        Ok(output)
+    }
+
+    pub fn seal(label: &str, secret: &[u8]) -> Result<Vec<u8>, String> {
+        let provider = provider().map_err(|e| format!("TPM unavailable: {}", e))?;
+        let mut key = NCRYPT_KEY_HANDLE::default();
+        unsafe {
+            NCryptCreatePersistedKey(
+                provider.0,
+                &mut key,
+                NCRYPT_RSA_ALGORITHM,
+                &HSTRING::from(label),
+                CERT_KEY_SPEC(0),
+                NCRYPT_OVERWRITE_KEY_FLAG,
+            )
+        }
+        .map_err(|e| format!("Failed to create TPM key: {}", e))?;
+        let key = Owned(key);
+
+        let description = HSTRING::from("Unlock your KeyKeeper vault");
+        let policy = NCRYPT_UI_POLICY {
+            dwVersion: 1,
+            dwFlags: NCRYPT_UI_FORCE_HIGH_PROTECTION_FLAG,
+            pszDescription: PCWSTR(description.as_ptr()),
+            ..Default::default()
+        };
+        let policy = unsafe {
+            std::slice::from_raw_parts(
+                &policy as *const NCRYPT_UI_POLICY as *const u8,
+                std::mem::size_of::<NCRYPT_UI_POLICY>(),
+            )
+        };
+        unsafe {
+            NCryptSetProperty(
+                key.0.into(),
+                NCRYPT_UI_POLICY_PROPERTY,
+                policy,
+                NCRYPT_FLAGS(0),
+            )
+            .and_then(|()| NCryptFinalizeKey(key.0, NCRYPT_FLAGS(0)))
+        }
+        .map_err(|e| format!("Failed to create TPM key: {}", e))?;
+        crypt(&key, secret, NCryptEncrypt).map_err(|e| format!("Failed to wrap device key: {}", e))
+    }
+
+    pub fn open(label: &str, wrapped: &[u8]) -> Result<Zeroizing<Vec<u8>>, String> {
+        crypt(&open_key(label)?, wrapped, NCryptDecrypt)
+            .map(Zeroizing::new)
+            .map_err(|e| format!("TPM refused to unwrap the device key: {}", e))
+    }
+
+    pub fn delete(label: &str) -> Result<(), String> {
+        let Ok(key) = open_key(label) else {
+            return Ok(());
+        };
+        unsafe { NCryptDeleteKey(key.0, 0) }
+            .map_err(|e| format!("Failed to delete TPM key: {}", e))?;
+        // NCryptDeleteKey freed the handle
+        std::mem::forget(key);
+        Ok(())
+    }
+}
+
+/// No secure element support; device keys go to the OS keyring
+#[cfg(not(any(target_os = "macos", windows)))]
+mod platform {
+    use super::HardwareBackend;
+    use zeroize::Zeroizing;
+
+    pub const BACKEND: Option<HardwareBackend> = None;
+
+    pub fn available() -> Option<HardwareBackend> {
+        None
+    }
+
+    pub fn seal(_label: &str, _secret: &[u8]) -> Result<Vec<u8>, String> {
+        Err("No secure element on this platform".to_string())
+    }
+
+    pub fn open(_label: &str, _wrapped: &[u8]) -> Result<Zeroizing<Vec<u8>>, String> {
+        Err("No secure element on this platform".to_string())
+    }
+
+    pub fn delete(_label: &str) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn device_key_unwraps_the_current_master_key() {
+        let device_key = [7u8; 32];
+        let key = HardwareUnlockKey {
+            backend: HardwareBackend::Keyring,
+            key_label: format!("{}.test", KEY_LABEL_PREFIX),
+            created_at: Utc::now(),
+            device_key: general_purpose::STANDARD.encode(device_key),
+            wrapped_device_key: String::new(),
+        };
+        let master_key = general_purpose::STANDARD.encode([42u8; 32]);
+
+        let sealed = envelope(&key, &master_key).unwrap();
+        assert_ne!(sealed.wrapped_master_key, master_key);
+        assert_eq!(
+            decrypt_data(&sealed.wrapped_master_key, &device_key).unwrap(),
+            master_key
+        );
+        assert!(decrypt_data(&sealed.wrapped_master_key, &[8u8; 32]).is_err());
+
+        let moved = HardwareEnvelope {
+            backend: HardwareBackend::Tpm,
+            ..sealed
+        };
+        if platform::BACKEND != Some(HardwareBackend::Tpm) {
+            let store = KeyringStore::new("test", "test", std::env::temp_dir());
+            assert!(open(&store, &moved).is_err());
+        }
+    }
+}
@@ -0,0 +1,270 @@
+//! Health reporting for the app and the local HTTP server.
+//!
+//! `/live` only says the process answers. `/ready` answers 200 once the vault
+//! is unlocked and in sync with its file, which is what scripts waiting on
+//! KeyKeeper need, and 503 with the reasons otherwise. `/health` returns the
+//! full report.
+
+use crate::errors::KeyKeeperError;
+use crate::ml_engine_simple::MLEngine;
+use crate::vault_location::VaultLocation;
+use crate::{AppState, DocsStore};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use tauri::State;
+use tokio::sync::Mutex as AsyncMutex;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Ok,
+    /// Usable, but something needs attention
+    Degraded,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MlEngineStatus {
+    NotLoaded,
+    Loading,
+    Loaded,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultHealth {
+    pub unlocked: bool,
+    pub file_exists: bool,
+    /// Changed on disk by another device and not reloaded yet
+    pub pending_reload: bool,
+    pub synced_folder: bool,
+    #[serde(default, with = "crate::timestamps::rfc3339_option")]
+    pub last_saved_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocsStoreHealth {
+    pub loaded: bool,
+    pub document_count: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingTask {
+    pub name: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub ready: bool,
+    pub version: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub timestamp: DateTime<Utc>,
+    pub uptime_seconds: i64,
+    /// Unset while the HTTP server is stopped
+    pub server_uptime_seconds: Option<i64>,
+    pub vault: VaultHealth,
+    pub docs_store: DocsStoreHealth,
+    pub ml_engine: MlEngineStatus,
+    pub pending_tasks: Vec<PendingTask>,
+    /// Why the app isn't ready or is degraded
+    pub issues: Vec<String>,
+}
+
+/// Subsystem handles and counters behind the report, shared by the app and
+/// the HTTP server
+pub struct HealthState {
+    started_at: DateTime<Utc>,
+    server_started_at: Mutex<Option<DateTime<Utc>>>,
+    docs_store_error: Option<String>,
+    tasks: Mutex<BTreeMap<&'static str, usize>>,
+    is_unlocked: Arc<AsyncMutex<bool>>,
+    vault_path: VaultLocation,
+    docs_store: Arc<AsyncMutex<DocsStore>>,
+    ml_engine: Arc<AsyncMutex<Option<MLEngine>>>,
+}
+
+/// Marks a background task as pending until dropped
+pub struct TaskGuard {
+    health: Arc<HealthState>,
+    name: &'static str,
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        let mut tasks = self.health.tasks.lock().unwrap();
+        if let Some(count) = tasks.get_mut(self.name) {
+            *count -= 1;
+            if *count == 0 {
+                tasks.remove(self.name);
+            }
+        }
+    }
+}
+
+impl HealthState {
+    pub fn new(
+        is_unlocked: Arc<AsyncMutex<bool>>,
+        vault_path: VaultLocation,
+        docs_store: Arc<AsyncMutex<DocsStore>>,
+        docs_store_error: Option<String>,
+        ml_engine: Arc<AsyncMutex<Option<MLEngine>>>,
+    ) -> Self {
+        Self {
+            started_at: Utc::now(),
+            server_started_at: Mutex::new(None),
+            docs_store_error,
+            tasks: Mutex::new(BTreeMap::new()),
+            is_unlocked,
+            vault_path,
+            docs_store,
+            ml_engine,
+        }
+    }
+
+    pub fn begin_task(self: &Arc<Self>, name: &'static str) -> TaskGuard {
+        *self.tasks.lock().unwrap().entry(name).or_insert(0) += 1;
+        TaskGuard {
+            health: Arc::clone(self),
+            name,
+        }
+    }
+
+    pub fn pending_task_names(&self) -> Vec<&'static str> {
+        self.tasks.lock().unwrap().keys().copied().collect()
+    }
+
+    /// Wait until no task is pending; false if `timeout` passed first
+    pub async fn wait_idle(&self, timeout: std::time::Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        while !self.tasks.lock().unwrap().is_empty() {
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        true
+    }
+
+    /// The vault location, for the HTTP server's temporary `AppState`
+    pub fn vault_location(&self) -> VaultLocation {
+        self.vault_path.clone()
+    }
+
+    pub fn set_server_running(&self, running: bool) {
+        *self.server_started_at.lock().unwrap() = running.then(Utc::now);
+    }
+
+    pub fn uptime_seconds(&self) -> i64 {
+        (Utc::now() - self.started_at).num_seconds()
+    }
+
+    pub async fn report(&self) -> HealthReport {
+        let now = Utc::now();
+        let path = self.vault_path.path();
+        let vault = VaultHealth {
+            unlocked: *self.is_unlocked.lock().await,
+            file_exists: path.exists(),
+            pending_reload: self.vault_path.changed_on_disk(),
+            synced_folder: self.vault_path.is_synced(),
+            last_saved_at: self.vault_path.last_saved(),
+        };
+        let docs_store = DocsStoreHealth {
+            loaded: self.docs_store_error.is_none(),
+            document_count: self.docs_store.lock().await.docs.len(),
+            error: self.docs_store_error.clone(),
+        };
+        // The engine stays locked while a model loads
+        let ml_engine = match self.ml_engine.try_lock() {
+            Ok(engine) if engine.is_some() => MlEngineStatus::Loaded,
+            Ok(_) => MlEngineStatus::NotLoaded,
+            Err(_) => MlEngineStatus::Loading,
+        };
+        let pending_tasks = self
+            .tasks
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, count)| PendingTask {
+                name: name.to_string(),
+                count: *count,
+            })
+            .collect();
+
+        let mut issues = Vec::new();
+        let blocking = readiness_issues(&vault);
+        issues.extend(blocking.iter().cloned());
+        if let Some(error) = &docs_store.error {
+            issues.push(format!("Documentation store failed to load: {}", error));
+        }
+
+        HealthReport {
+            status: if issues.is_empty() {
+                HealthStatus::Ok
+            } else {
+                HealthStatus::Degraded
+            },
+            ready: blocking.is_empty(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            timestamp: now,
+            uptime_seconds: (now - self.started_at).num_seconds(),
+            server_uptime_seconds: self
+                .server_started_at
+                .lock()
+                .unwrap()
+                .map(|t| (now - t).num_seconds()),
+            vault,
+            docs_store,
+            ml_engine,
+            pending_tasks,
+            issues,
+        }
+    }
+}
+
+/// Reasons the vault can't serve requests yet
+pub fn readiness_issues(vault: &VaultHealth) -> Vec<String> {
+    let mut issues = Vec::new();
+    if !vault.file_exists {
+        issues.push("Vault file not found".to_string());
+    }
+    if !vault.unlocked {
+        issues.push("Vault is locked".to_string());
+    }
+    if vault.pending_reload {
+        issues.push("Vault changed on disk and hasn't been reloaded yet".to_string());
+    }
+    issues
+}
+
+#[tauri::command]
+pub async fn get_health_report(state: State<'_, AppState>) -> Result<HealthReport, KeyKeeperError> {
+    Ok(state.health.report().await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locked_vault_is_not_ready() {
+        let mut vault = VaultHealth {
+            unlocked: false,
+            file_exists: true,
+            pending_reload: false,
+            synced_folder: false,
+            last_saved_at: None,
+        };
+        assert_eq!(
+            readiness_issues(&vault),
+            vec!["Vault is locked".to_string()]
+        );
+        vault.unlocked = true;
+        assert!(readiness_issues(&vault).is_empty());
+        vault.pending_reload = true;
+        assert_eq!(readiness_issues(&vault).len(), 1);
+    }
+}
@@ -0,0 +1,650 @@
+//! Hardcoded secrets in infrastructure files.
+//!
+//! `scan_infrastructure_files` reads a project's Terraform (`*.tf`,
+//! `*.tfvars`), CloudFormation and Serverless Framework files and reports
+//! the credentials written in them: vault key values, known secret formats
+//! and sensitive-named attributes (`password`, `client_secret`…) set to a
+//! literal rather than a variable. Each finding suggests the variable to
+//! use instead, in the file's own syntax, named after the vault key when
+//! the value is one. `generate_tfvars` then renders `terraform.tfvars` from
+//! the vault, so the values only live there and in a gitignored file.
+//!
+//! Files ignored for scans are skipped: a gitignored tfvars file is where
+//! the values belong. Findings never contain the secret itself.
+
+use crate::docker_env::normalize_environment;
+use crate::errors::KeyKeeperError;
+use crate::git_history::{self, Needle};
+use crate::project_ignore::{ProjectIgnores, Purpose};
+use crate::{archive, env_values, guards, log_audit_event, ApiKey, AppState};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path};
+use std::sync::LazyLock;
+use tauri::State;
+use tracing::{info, warn};
+
+const MAX_DEPTH: usize = 8;
+const MAX_FINDINGS: usize = 500;
+/// Larger files are skipped (state files, lock files)
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+/// Shorter literals are rarely credentials (ports, flags, usernames)
+const MIN_LITERAL_LEN: usize = 8;
+
+/// Parts of attribute names that hold credentials
+const SENSITIVE_NAMES: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+    "access_key",
+    "private_key",
+    "credential",
+];
+/// Suffixes of attributes that name or describe a credential without holding it
+const DESCRIPTIVE_SUFFIXES: &[&str] = &[
+    "_arn",
+    "_name",
+    "_id",
+    "_length",
+    "_url",
+    "_uri",
+    "_path",
+    "_file",
+    "_ttl",
+    "_type",
+    "_version",
+    "_policy",
+    "_enabled",
+    "_description",
+];
+/// Extensions of the files worth reading to tell their format
+const CANDIDATE_EXTENSIONS: &[&str] = &[".tf", ".tfvars", ".json", ".yml", ".yaml", ".template"];
+
+/// `name = value` (HCL) or `name: value` (YAML, JSON)
+static ASSIGNMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*(?:-\s+)?["']?([A-Za-z_][A-Za-z0-9_.\-]*)["']?\s*[:=]\s*(.*?)\s*,?\s*$"#)
+        .expect("valid pattern")
+});
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IacFormat {
+    Terraform,
+    /// `*.tfvars` and `*.tfvars.json`
+    TerraformVars,
+    CloudFormation,
+    Serverless,
+}
+
+impl IacFormat {
+    fn is_terraform(self) -> bool {
+        matches!(self, IacFormat::Terraform | IacFormat::TerraformVars)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingKind {
+    /// The value of a vault key
+    VaultKey,
+    /// Looks like a secret of a known format
+    Pattern,
+    /// A literal assigned to a sensitive-named attribute
+    Literal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IacFinding {
+    /// Relative to the project, with `/` separators
+    pub file: String,
+    pub line: usize,
+    pub format: IacFormat,
+    pub kind: FindingKind,
+    /// Attribute the value is assigned to
+    pub attribute: Option<String>,
+    pub key_id: Option<String>,
+    pub key_name: Option<String>,
+    /// Name of the matched pattern, for `Pattern` findings
+    pub pattern: Option<String>,
+    /// The line with secrets masked
+    pub preview: String,
+    /// Variable to move the value to
+    pub variable: String,
+    /// What to write instead of the value; tfvars lines are removed instead
+    pub replacement: Option<String>,
+    /// Declaration of the variable
+    pub declaration: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IacScan {
+    pub project_path: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub scanned_at: DateTime<Utc>,
+    pub files_scanned: usize,
+    pub findings: Vec<IacFinding>,
+    /// Set when more findings were found than are kept
+    pub truncated: bool,
+    pub remediation: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TfvarsOutput {
+    pub content: String,
+    pub variables: Vec<String>,
+    /// Variables left out: their key isn't in the vault, has no value in
+    /// the environment or is a remote key
+    pub missing: Vec<String>,
+    pub written_to: Option<String>,
+}
+
+/// `name` in snake case, e.g. "MasterUserPassword" -> "master_user_password"
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            if c.is_ascii_uppercase()
+                && previous.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit())
+            {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else if !snake.ends_with('_') {
+            snake.push('_');
+        }
+        previous = Some(c);
+    }
+    snake.trim_matches('_').to_string()
+}
+
+fn pascal_case(snake: &str) -> String {
+    snake
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Terraform variable of a vault key, e.g. "Stripe Live" -> "stripe_live"
+pub fn variable_name(key_name: &str) -> String {
+    match snake_case(key_name) {
+        name if name.is_empty() => "secret".to_string(),
+        name if name.starts_with(|c: char| c.is_ascii_digit()) => format!("key_{}", name),
+        name => name,
+    }
+}
+
+fn is_candidate(path: &Path) -> bool {
+    let in_provider_cache = path
+        .components()
+        .any(|c| c == Component::Normal(".terraform".as_ref()));
+    !in_provider_cache
+        && path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|name| CANDIDATE_EXTENSIONS.iter().any(|ext| name.ends_with(ext)))
+}
+
+/// Format of an infrastructure file; `None` for any other file
+fn format_of(path: &Path, content: &str) -> Option<IacFormat> {
+    let name = path.file_name()?.to_str()?;
+    if name.ends_with(".tf") {
+        return Some(IacFormat::Terraform);
+    }
+    if name.ends_with(".tfvars") || name.ends_with(".tfvars.json") {
+        return Some(IacFormat::TerraformVars);
+    }
+    if matches!(
+        name,
+        "serverless.yml" | "serverless.yaml" | "serverless.json"
+    ) {
+        return Some(IacFormat::Serverless);
+    }
+    (content.contains("AWSTemplateFormatVersion") || content.contains("AWS::"))
+        .then_some(IacFormat::CloudFormation)
+}
+
+fn is_sensitive(attribute: &str) -> bool {
+    let name = snake_case(attribute);
+    SENSITIVE_NAMES.iter().any(|s| name.contains(s))
+        && !DESCRIPTIVE_SUFFIXES.iter().any(|s| name.ends_with(s))
+}
+
+/// The string literal `value` is; `None` for expressions, references,
+/// intrinsic functions and blocks
+fn literal(value: &str, format: IacFormat) -> Option<&str> {
+    let quoted = ['"', '\'']
+        .iter()
+        .find_map(|q| value.strip_prefix(*q)?.strip_suffix(*q));
+    let literal = match quoted {
+        Some(inner) => inner,
+        // Unquoted HCL is an expression (var.x, local.y, a function call)
+        None if format.is_terraform() => return None,
+        None if value.starts_with(['!', '{', '[', '&', '*', '|', '>']) => return None,
+        None => value.split(" #").next().unwrap_or_default().trim_end(),
+    };
+    let is_reference = ["${", "{{resolve:", "%{"]
+        .iter()
+        .any(|marker| literal.contains(marker));
+    let looks_secret = literal.chars().count() >= MIN_LITERAL_LEN
+        && !literal.chars().any(char::is_whitespace)
+        && !literal.chars().all(|c| c.is_ascii_digit());
+    (!is_reference && looks_secret).then_some(literal)
+}
+
+/// Replacement and declaration of `variable` in a file of `format`
+fn suggest(format: IacFormat, json: bool, variable: &str) -> (Option<String>, Option<String>) {
+    let terraform_declaration = format!(
+        "variable \"{}\" {{\n  type      = string\n  sensitive = true\n}}",
+        variable
+    );
+    match format {
+        IacFormat::Terraform => (
+            Some(format!("var.{}", variable)),
+            Some(terraform_declaration),
+        ),
+        // The value moves to the generated terraform.tfvars
+        IacFormat::TerraformVars => (None, Some(terraform_declaration)),
+        IacFormat::CloudFormation => {
+            let parameter = pascal_case(variable);
+            let (replacement, declaration) = if json {
+                (
+                    format!("{{ \"Ref\": \"{}\" }}", parameter),
+                    format!(
+                        "\"{}\": {{ \"Type\": \"String\", \"NoEcho\": true }}",
+                        parameter
+                    ),
+                )
+            } else {
+                (
+                    format!("!Ref {}", parameter),
+                    format!("{}:\n  Type: String\n  NoEcho: true", parameter),
+                )
+            };
+            (Some(replacement), Some(declaration))
+        }
+        IacFormat::Serverless => (Some(format!("${{env:{}}}", variable.to_uppercase())), None),
+    }
+}
+
+/// Findings of one file: vault values first, then known formats, then
+/// sensitive literals, at most one per line
+fn scan_content(
+    file: &str,
+    format: IacFormat,
+    content: &str,
+    needles: &[Needle],
+) -> Vec<IacFinding> {
+    let json = file.ends_with(".json");
+    let mut findings = Vec::new();
+    for (index, text) in content.lines().enumerate() {
+        let trimmed = text.trim_start();
+        if trimmed.starts_with('#') || trimmed.starts_with("//") {
+            continue;
+        }
+        let assignment = ASSIGNMENT.captures(text).and_then(|c| {
+            let name = c.get(1)?.as_str();
+            Some((name, c.get(2)?.as_str()))
+        });
+        let attribute = assignment.map(|(name, _)| name);
+
+        let needle = needles.iter().find(|n| text.contains(n.value.as_str()));
+        let pattern = git_history::known_format(text);
+        let sensitive_literal = assignment
+            .filter(|(name, _)| is_sensitive(name))
+            .and_then(|(_, value)| literal(value, format));
+        let (kind, masked) = match (needle, pattern, sensitive_literal) {
+            (Some(_), _, _) => (FindingKind::VaultKey, text.to_string()),
+            (None, Some(_), _) => (FindingKind::Pattern, text.to_string()),
+            (None, None, Some(literal)) => {
+                (FindingKind::Literal, text.replace(literal, "[REDACTED]"))
+            }
+            (None, None, None) => continue,
+        };
+
+        let variable = match (needle, attribute) {
+            (Some(needle), _) => variable_name(&needle.key_name),
+            (None, Some(attribute)) => variable_name(attribute),
+            (None, None) => variable_name(pattern.unwrap_or("secret")),
+        };
+        let (replacement, declaration) = suggest(format, json, &variable);
+        findings.push(IacFinding {
+            file: file.to_string(),
+            line: index + 1,
+            format,
+            kind,
+            attribute: attribute.map(str::to_string),
+            key_id: needle.map(|n| n.key_id.clone()),
+            key_name: needle.map(|n| n.key_name.clone()),
+            pattern: pattern
+                .filter(|_| kind == FindingKind::Pattern)
+                .map(str::to_string),
+            preview: git_history::preview(&masked, needles),
+            variable,
+            replacement,
+            declaration,
+        });
+    }
+    findings
+}
+
+/// Scan the infrastructure files under `root`
+fn scan_project(root: &Path, needles: &[Needle]) -> (usize, Vec<IacFinding>, bool) {
+    let mut ignores = ProjectIgnores::load(root);
+    let mut files_scanned = 0;
+    let mut findings = Vec::new();
+    for path in ignores.walk_files(MAX_DEPTH, Purpose::Scan, is_candidate) {
+        if !fs::metadata(&path).is_ok_and(|m| m.len() <= MAX_FILE_SIZE) {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let Some(format) = format_of(&path, &content) else {
+            continue;
+        };
+        files_scanned += 1;
+        let file = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        findings.extend(scan_content(&file, format, &content, needles));
+        if findings.len() > MAX_FINDINGS {
+            findings.truncate(MAX_FINDINGS);
+            return (files_scanned, findings, true);
+        }
+    }
+    (files_scanned, findings, false)
+}
+
+/// Steps to move the findings' values to the vault
+pub fn remediation(findings: &[IacFinding]) -> Vec<String> {
+    if findings.is_empty() {
+        return Vec::new();
+    }
+
+    let mut steps = Vec::new();
+    let not_in_vault = findings
+        .iter()
+        .filter(|f| f.kind != FindingKind::VaultKey)
+        .count();
+    if not_in_vault > 0 {
+        steps.push(format!(
+            "Add the {} value(s) that aren't in the vault yet, named after their suggested variable",
+            not_in_vault
+        ));
+    }
+    let has = |format: IacFormat| findings.iter().any(|f| f.format == format);
+    if findings.iter().any(|f| f.format.is_terraform()) {
+        steps.push(
+            "Declare the variables as sensitive, reference them instead of the values and \
+             generate terraform.tfvars from the vault with generate_tfvars; keep it gitignored"
+                .to_string(),
+        );
+    }
+    if has(IacFormat::CloudFormation) {
+        steps.push(
+            "Pass the values as NoEcho parameters, or resolve them from a secret manager with \
+             {{resolve:secretsmanager:...}}"
+                .to_string(),
+        );
+    }
+    if has(IacFormat::Serverless) {
+        steps.push(
+            "Read the values from the environment with ${env:NAME} and fill it from the vault"
+                .to_string(),
+        );
+    }
+    steps.push(
+        "Rotate the values if the files were ever committed; scan_git_history tells".to_string(),
+    );
+    steps
+}
+
+/// Look for hardcoded credentials in a project's infrastructure files
+#[tauri::command]
+pub async fn scan_infrastructure_files(
+    project_path: String,
+    state: State<'_, AppState>,
+) -> Result<IacScan, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let needles = {
+        let vault_guard = state.vault.lock().await;
+        if !vault_guard
+            .projects
+            .values()
+            .any(|p| p.path == project_path)
+        {
+            return Err(KeyKeeperError::not_found("Project"));
+        }
+        git_history::needles(&vault_guard)
+    };
+
+    let root = project_path.clone();
+    let (files_scanned, findings, truncated) =
+        tauri::async_runtime::spawn_blocking(move || scan_project(Path::new(&root), &needles))
+            .await
+            .map_err(|e| KeyKeeperError::Internal(format!("Infrastructure scan failed: {}", e)))?;
+
+    let scan = IacScan {
+        project_path: project_path.clone(),
+        scanned_at: Utc::now(),
+        files_scanned,
+        remediation: remediation(&findings),
+        findings,
+        truncated,
+    };
+    info!(
+        "Scanned {} infrastructure files of {}: {} finding(s)",
+        files_scanned,
+        project_path,
+        scan.findings.len()
+    );
+
+    log_audit_event(
+        &state,
+        "scan_infrastructure_files",
+        "project",
+        Some(&project_path),
+        scan.findings.is_empty(),
+        Some(&format!(
+            "{} files scanned, {} finding(s)",
+            files_scanned,
+            scan.findings.len()
+        )),
+    )
+    .await;
+    Ok(scan)
+}
+
+/// HCL string literal of `value`, with template sequences escaped
+fn hcl_quote(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t")
+        .replace("${", "$${")
+        .replace("%{", "%%{");
+    format!("\"{}\"", escaped)
+}
+
+pub fn render_tfvars(variables: &[(String, String)]) -> String {
+    let mut content = String::from("# Generated by KeyKeeper - do not commit this file\n");
+    for (name, value) in variables {
+        content.push_str(&format!("{} = {}\n", name, hcl_quote(value)));
+    }
+    content
+}
+
+/// Render `terraform.tfvars` for a project's keys in `environment`, writing
+/// it to `output_path` when set. `variables` maps variable names to key ids;
+/// without it every key assigned to the project is included, named with
+/// `variable_name`.
+#[tauri::command]
+pub async fn generate_tfvars(
+    project_path: String,
+    environment: String,
+    variables: Option<BTreeMap<String, String>>,
+    output_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<TfvarsOutput, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let environment = normalize_environment(&environment);
+
+    let mut pairs: Vec<(String, String)> = Vec::new();
+    let mut missing = Vec::new();
+    {
+        let vault_guard = state.vault.lock().await;
+        if !vault_guard
+            .projects
+            .values()
+            .any(|p| p.path == project_path)
+        {
+            return Err(KeyKeeperError::not_found("Project"));
+        }
+
+        let selected: Vec<(String, Option<&ApiKey>)> = match &variables {
+            Some(variables) => variables
+                .iter()
+                .map(|(name, key_id)| {
+                    let key = env_values::lookup(&vault_guard, key_id).map(|(key, _)| key);
+                    (name.clone(), key)
+                })
+                .collect(),
+            None => {
+                let mut keys: Vec<&ApiKey> = vault_guard
+                    .keys
+                    .values()
+                    .filter(|k| k.project_path.as_deref() == Some(project_path.as_str()))
+                    .filter(|k| k.is_active && k.kind.is_env_value() && !archive::is_archived(k))
+                    .collect();
+                keys.sort_by(|a, b| a.name.cmp(&b.name));
+                keys.into_iter()
+                    .map(|k| (variable_name(&k.name), Some(k)))
+                    .collect()
+            }
+        };
+        for (name, key) in selected {
+            if pairs.iter().any(|(existing, _)| *existing == name) {
+                warn!(
+                    "Duplicate variable {} for project {}, skipping",
+                    name, project_path
+                );
+                continue;
+            }
+            match key.and_then(|k| env_values::value_for(k, &environment)) {
+                Some(value) => pairs.push((name, value.to_string())),
+                None => missing.push(name),
+            }
+        }
+    }
+    let content = render_tfvars(&pairs);
+
+    let written_to = if let Some(path) = output_path {
+        fs::write(&path, &content)
+            .map_err(|e| KeyKeeperError::Io(format!("Failed to write {}: {}", path, e)))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = fs::set_permissions(&path, fs::Permissions::from_mode(0o600));
+        }
+        Some(path)
+    } else {
+        None
+    };
+
+    log_audit_event(
+        &state,
+        "generate_tfvars",
+        "project",
+        Some(&project_path),
+        true,
+        Some(&format!("{} variables for {}", pairs.len(), environment)),
+    )
+    .await;
+
+    Ok(TfvarsOutput {
+        content,
+        variables: pairs.into_iter().map(|(name, _)| name).collect(),
+        missing,
+        written_to,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zeroize::Zeroizing;
+
+    #[test]
+    fn literals_are_found_and_references_are_not() {
+        let needles = [Needle {
+            key_id: "k1".to_string(),
+            key_name: "Stripe secret".to_string(),
+            value: Zeroizing::new("internal-0123456789".to_string()),
+        }];
+        let tf = r#"
+resource "aws_db_instance" "main" {
+  username   = "administrator"
+  password   = "hunter2-but-longer"
+  api_token  = var.api_token
+  secret_arn = "arn:aws:secretsmanager:eu-west-1:1:secret:db"
+}
+provider "stripe" {
+  # api_key = "commented-out-value"
+  api_key = "internal-0123456789"
+}
+"#;
+        let findings = scan_content("main.tf", IacFormat::Terraform, tf, &needles);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].kind, FindingKind::Literal);
+        assert_eq!(findings[0].line, 4);
+        assert_eq!(findings[0].replacement.as_deref(), Some("var.password"));
+        assert!(!findings[0].preview.contains("hunter2"));
+        assert_eq!(findings[1].kind, FindingKind::VaultKey);
+        assert_eq!(findings[1].variable, "stripe_secret");
+        assert!(!findings[1].preview.contains("internal-0123456789"));
+
+        let stack = "AWSTemplateFormatVersion: \"2010-09-09\"
+Resources:
+  Database:
+    Type: AWS::RDS::DBInstance
+    Properties:
+      MasterUserPassword: !Ref DbPassword
+      SecretToken: s3cr3t-literal-value # rotate me
+";
+        let format = format_of(Path::new("infra/stack.yaml"), stack);
+        assert_eq!(format, Some(IacFormat::CloudFormation));
+        let findings = scan_content("infra/stack.yaml", IacFormat::CloudFormation, stack, &[]);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].variable, "secret_token");
+        assert_eq!(findings[0].replacement.as_deref(), Some("!Ref SecretToken"));
+        assert_eq!(
+            format_of(Path::new("docker-compose.yml"), "services: {}"),
+            None
+        );
+    }
+
+    #[test]
+    fn tfvars_values_are_escaped() {
+        let content = render_tfvars(&[("db_password".to_string(), "a\"b${c}".to_string())]);
+        assert!(content.ends_with("db_password = \"a\\\"b$${c}\"\n"));
+    }
+}
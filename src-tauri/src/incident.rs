@@ -0,0 +1,491 @@
+//! Incident mode.
+//!
+//! `initiate_incident` is the one command to run when a secret may have
+//! leaked. It revokes every session, client registration and lease, pauses
+//! auto-sync and locks the vault. It also files a checklist of the keys in
+//! scope, each with its provider's rotation steps (see
+//! `rotation::instructions_for`).
+//!
+//! A checklist item is checked off when its key is rotated, or by hand with
+//! `update_incident_item`. Closing the incident records a timeline from the
+//! audit log: what happened to the keys in scope, and to sessions, clients
+//! and leases, since it was opened.
+//!
+//! Only one incident is open at a time. While it is, projects sync only when
+//! asked explicitly, as if their auto-sync were off, and
+//! `auto_sync_workspace_env_files` refuses to run.
+
+use crate::errors::KeyKeeperError;
+use crate::lock_state::LockReason;
+use crate::rotation::{self, RotationInstructions};
+use crate::{clients, guards, leases, log_audit_event, save_vault, sessions};
+use crate::{ApiKey, ApiKeyVault, AppState};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tauri::State;
+
+/// Closed incidents kept
+const MAX_INCIDENTS: usize = 50;
+const MAX_TIMELINE_ENTRIES: usize = 1000;
+/// Resource types whose events belong to every incident's timeline
+const TIMELINE_RESOURCES: &[&str] = &["vault", "session", "client", "lease", "incident"];
+
+/// Keys an incident covers. A key is in scope when it matches any of the
+/// criteria; none at all covers every key.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IncidentScope {
+    #[serde(default)]
+    pub key_ids: Vec<String>,
+    /// Keys assigned to these projects
+    #[serde(default)]
+    pub project_paths: Vec<String>,
+    #[serde(default)]
+    pub services: Vec<String>,
+    /// Keys these clients used, per the audit log
+    #[serde(default)]
+    pub client_ids: Vec<String>,
+    /// Only count uses by `client_ids` from then on
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl IncidentScope {
+    fn is_empty(&self) -> bool {
+        self.key_ids.is_empty()
+            && self.project_paths.is_empty()
+            && self.services.is_empty()
+            && self.client_ids.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemStatus {
+    Pending,
+    Rotated,
+    /// Judged not to need rotating
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub key_id: String,
+    pub key_name: String,
+    pub service: String,
+    pub project_path: Option<String>,
+    pub instructions: RotationInstructions,
+    pub status: ItemStatus,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub note: Option<String>,
+}
+
+/// What initiating the incident revoked
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Revocations {
+    pub sessions: usize,
+    pub clients: usize,
+    pub leases: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    pub timestamp: DateTime<Utc>,
+    pub action: String,
+    pub resource_type: String,
+    pub resource_id: Option<String>,
+    pub success: bool,
+    pub details: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Incident {
+    pub id: String,
+    pub title: String,
+    pub scope: IncidentScope,
+    pub opened_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub revoked: Revocations,
+    pub items: Vec<ChecklistItem>,
+    /// Filled when the incident is closed
+    #[serde(default)]
+    pub timeline: Vec<TimelineEntry>,
+    pub summary: Option<String>,
+}
+
+pub fn open_incident(vault: &ApiKeyVault) -> Option<&Incident> {
+    vault.incidents.iter().find(|i| i.closed_at.is_none())
+}
+
+/// Whether auto-sync is paused by an open incident
+pub fn auto_sync_paused(vault: &ApiKeyVault) -> bool {
+    open_incident(vault).is_some()
+}
+
+pub fn keys_in_scope<'a>(vault: &'a ApiKeyVault, scope: &IncidentScope) -> Vec<&'a ApiKey> {
+    let touched: HashSet<&str> = vault
+        .audit_logs
+        .iter()
+        .filter(|l| l.success && l.resource_type == "api_key")
+        .filter(|l| scope.since.is_none_or(|since| l.timestamp >= since))
+        .filter(|l| {
+            l.client_id
+                .as_ref()
+                .is_some_and(|c| scope.client_ids.contains(c))
+        })
+        .filter_map(|l| l.resource_id.as_deref())
+        .collect();
+    let mut keys: Vec<&ApiKey> = vault
+        .keys
+        .values()
+        .filter(|key| {
+            scope.is_empty()
+                || scope.key_ids.contains(&key.id)
+                || key
+                    .project_path
+                    .as_ref()
+                    .is_some_and(|p| scope.project_paths.contains(p))
+                || scope
+                    .services
+                    .iter()
+                    .any(|s| rotation::normalize(s) == rotation::normalize(&key.service))
+                || touched.contains(key.id.as_str())
+        })
+        .collect();
+    keys.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+    keys
+}
+
+/// Check off the open incident's item for a key that was just rotated
+pub fn mark_rotated(vault: &mut ApiKeyVault, key_id: &str, now: DateTime<Utc>) {
+    let Some(incident) = vault.incidents.iter_mut().find(|i| i.closed_at.is_none()) else {
+        return;
+    };
+    for item in incident
+        .items
+        .iter_mut()
+        .filter(|i| i.key_id == key_id && i.status == ItemStatus::Pending)
+    {
+        item.status = ItemStatus::Rotated;
+        item.updated_at = Some(now);
+    }
+}
+
+/// Audit events since the incident was opened that concern it
+pub fn timeline(vault: &ApiKeyVault, incident: &Incident) -> Vec<TimelineEntry> {
+    let key_ids: HashSet<&str> = incident.items.iter().map(|i| i.key_id.as_str()).collect();
+    vault
+        .audit_logs
+        .iter()
+        .filter(|l| l.timestamp >= incident.opened_at)
+        .filter(|l| {
+            TIMELINE_RESOURCES.contains(&l.resource_type.as_str())
+                || l.resource_id
+                    .as_deref()
+                    .is_some_and(|id| key_ids.contains(id))
+        })
+        .take(MAX_TIMELINE_ENTRIES)
+        .map(|l| TimelineEntry {
+            timestamp: l.timestamp,
+            action: l.action.clone(),
+            resource_type: l.resource_type.clone(),
+            resource_id: l.resource_id.clone(),
+            success: l.success,
+            details: l.error_message.clone(),
+        })
+        .collect()
+}
+
+/// Lock down the vault and file a rotation checklist for the keys in scope
+#[tauri::command]
+pub async fn initiate_incident(
+    title: String,
+    scope: Option<IncidentScope>,
+    state: State<'_, AppState>,
+) -> Result<Incident, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let title = title.trim().to_string();
+    if title.is_empty() {
+        return Err(KeyKeeperError::Validation(
+            "The incident needs a title".to_string(),
+        ));
+    }
+    let scope = scope.unwrap_or_default();
+
+    let vault_guard = state.vault.lock().await;
+    if open_incident(&vault_guard).is_some() {
+        return Err(KeyKeeperError::Conflict(
+            "An incident is already open".to_string(),
+        ));
+    }
+    let keys: Vec<ApiKey> = keys_in_scope(&vault_guard, &scope)
+        .into_iter()
+        .cloned()
+        .collect();
+    drop(vault_guard);
+    if keys.is_empty() {
+        return Err(KeyKeeperError::Validation(
+            "No keys are in the incident's scope".to_string(),
+        ));
+    }
+
+    let mut items = Vec::new();
+    for key in &keys {
+        items.push(ChecklistItem {
+            key_id: key.id.clone(),
+            key_name: key.name.clone(),
+            service: key.service.clone(),
+            project_path: key.project_path.clone(),
+            instructions: rotation::instructions_for(&state, key).await,
+            status: ItemStatus::Pending,
+            updated_at: None,
+            note: None,
+        });
+    }
+
+    let now = Utc::now();
+    let mut vault_guard = state.vault.lock().await;
+    let revoked = Revocations {
+        sessions: sessions::deactivate_all(&mut vault_guard, None),
+        clients: clients::revoke_all(&mut vault_guard),
+        leases: leases::revoke_all(&mut vault_guard, now),
+    };
+    let incident = Incident {
+        id: uuid::Uuid::new_v4().to_string(),
+        title,
+        scope,
+        opened_at: now,
+        closed_at: None,
+        revoked,
+        items,
+        timeline: Vec::new(),
+        summary: None,
+    };
+    vault_guard.incidents.push(incident.clone());
+    let excess = vault_guard.incidents.len().saturating_sub(MAX_INCIDENTS);
+    vault_guard.incidents.drain(..excess);
+    drop(vault_guard);
+    state.confirmations.clear_session_grants();
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "initiate_incident",
+        "incident",
+        Some(&incident.id),
+        true,
+        Some(&format!(
+            "{} keys in scope; revoked {} sessions, {} clients, {} leases",
+            incident.items.len(),
+            incident.revoked.sessions,
+            incident.revoked.clients,
+            incident.revoked.leases
+        )),
+    )
+    .await;
+
+    crate::lock_vault_for(state, LockReason::Incident).await?;
+    Ok(incident)
+}
+
+/// The incident with `incident_id`, or the open one
+#[tauri::command]
+pub async fn get_incident(
+    incident_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Option<Incident>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    Ok(match incident_id {
+        Some(id) => vault_guard.incidents.iter().find(|i| i.id == id).cloned(),
+        None => open_incident(&vault_guard).cloned(),
+    })
+}
+
+#[tauri::command]
+pub async fn list_incidents(state: State<'_, AppState>) -> Result<Vec<Incident>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut incidents = state.vault.lock().await.incidents.clone();
+    incidents.reverse();
+    Ok(incidents)
+}
+
+/// Set the status of a key on the open incident's checklist
+#[tauri::command]
+pub async fn update_incident_item(
+    incident_id: String,
+    key_id: String,
+    status: ItemStatus,
+    note: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Incident, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let incident = vault_guard
+        .incidents
+        .iter_mut()
+        .find(|i| i.id == incident_id)
+        .ok_or_else(|| KeyKeeperError::not_found("Incident"))?;
+    if incident.closed_at.is_some() {
+        return Err(KeyKeeperError::Conflict(
+            "The incident is closed".to_string(),
+        ));
+    }
+    let item = incident
+        .items
+        .iter_mut()
+        .find(|i| i.key_id == key_id)
+        .ok_or_else(|| KeyKeeperError::not_found("Checklist item"))?;
+    item.status = status;
+    item.updated_at = Some(Utc::now());
+    item.note = note.filter(|n| !n.trim().is_empty());
+    let incident = incident.clone();
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "update_incident_item",
+        "incident",
+        Some(&incident_id),
+        true,
+        Some(&key_id),
+    )
+    .await;
+    Ok(incident)
+}
+
+/// Close the open incident, recording its timeline
+#[tauri::command]
+pub async fn close_incident(
+    incident_id: String,
+    summary: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Incident, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let index = vault_guard
+        .incidents
+        .iter()
+        .position(|i| i.id == incident_id)
+        .ok_or_else(|| KeyKeeperError::not_found("Incident"))?;
+    if vault_guard.incidents[index].closed_at.is_some() {
+        return Err(KeyKeeperError::Conflict(
+            "The incident is already closed".to_string(),
+        ));
+    }
+    let timeline = timeline(&vault_guard, &vault_guard.incidents[index]);
+    let incident = &mut vault_guard.incidents[index];
+    incident.timeline = timeline;
+    incident.closed_at = Some(Utc::now());
+    incident.summary = summary.filter(|s| !s.trim().is_empty());
+    let incident = incident.clone();
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    let pending = incident
+        .items
+        .iter()
+        .filter(|i| i.status == ItemStatus::Pending)
+        .count();
+    log_audit_event(
+        &state,
+        "close_incident",
+        "incident",
+        Some(&incident_id),
+        true,
+        Some(&format!("{} keys left unrotated", pending)),
+    )
+    .await;
+    Ok(incident)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault_tests::test_key;
+
+    #[test]
+    fn scope_checklist_and_timeline_follow_the_incident() {
+        let mut vault = ApiKeyVault::default();
+        for key in [
+            test_key("stripe").project(Some("/shop")),
+            test_key("openai").service("OpenAI"),
+            test_key("github").service("GitHub"),
+        ] {
+            let key = key.secret("secret").environment("production").build();
+            vault.keys.insert(key.id.clone(), key);
+        }
+        let mut used =
+            crate::create_audit_log("get_api_key", "api_key", Some("openai"), true, None);
+        used.client_id = Some("ci".to_string());
+        used.timestamp = Utc::now() - chrono::Duration::hours(1);
+        vault.audit_logs.push(used);
+
+        let scope = IncidentScope {
+            project_paths: vec!["/shop".to_string()],
+            client_ids: vec!["ci".to_string()],
+            ..Default::default()
+        };
+        let ids: Vec<&str> = keys_in_scope(&vault, &scope)
+            .iter()
+            .map(|k| k.id.as_str())
+            .collect();
+        assert_eq!(ids, ["openai", "stripe"]);
+        assert_eq!(keys_in_scope(&vault, &IncidentScope::default()).len(), 3);
+
+        let opened_at = Utc::now();
+        vault.incidents.push(Incident {
+            id: "i1".to_string(),
+            title: "Leaked CI token".to_string(),
+            scope,
+            opened_at,
+            closed_at: None,
+            revoked: Revocations::default(),
+            items: ids
+                .iter()
+                .map(|id| ChecklistItem {
+                    key_id: id.to_string(),
+                    key_name: id.to_string(),
+                    service: String::new(),
+                    project_path: None,
+                    instructions: RotationInstructions::default(),
+                    status: ItemStatus::Pending,
+                    updated_at: None,
+                    note: None,
+                })
+                .collect(),
+            timeline: Vec::new(),
+            summary: None,
+        });
+        assert!(auto_sync_paused(&vault));
+
+        mark_rotated(&mut vault, "stripe", opened_at);
+        let incident = open_incident(&vault).unwrap();
+        assert_eq!(incident.items[1].status, ItemStatus::Rotated);
+        assert_eq!(incident.items[0].status, ItemStatus::Pending);
+
+        for (action, resource_type, id) in [
+            ("rotate_api_key", "api_key", Some("stripe")),
+            ("delete_api_key", "api_key", Some("github")),
+            ("revoke_all_sessions", "session", None),
+        ] {
+            vault.audit_logs.push(crate::create_audit_log(
+                action,
+                resource_type,
+                id,
+                true,
+                None,
+            ));
+        }
+        let actions: Vec<String> = timeline(&vault, open_incident(&vault).unwrap())
+            .into_iter()
+            .map(|e| e.action)
+            .collect();
+        assert_eq!(actions, ["rotate_api_key", "revoke_all_sessions"]);
+    }
+}
@@ -0,0 +1,142 @@
+//! Single-instance handling.
+//!
+//! A second launch hands its arguments to the running instance through the
+//! single-instance plugin's local socket and exits before building any state,
+//! so only one HTTP server and vault handle ever exist. The running instance
+//! focuses its window and queues the forwarded request, which is also emitted
+//! to the frontend as `instance-launch`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::info;
+
+/// Scheme of `keykeeper://` deep links
+pub const DEEP_LINK_SCHEME: &str = "keykeeper";
+/// Forwarded requests kept until the frontend takes them
+const MAX_PENDING: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchRequest {
+    /// Arguments after the executable
+    pub args: Vec<String>,
+    pub cwd: String,
+    pub deep_links: Vec<String>,
+    /// Existing files or directories passed as arguments, made absolute
+    pub paths: Vec<String>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub received_at: DateTime<Utc>,
+}
+
+/// Launch requests not yet taken by the frontend
+#[derive(Default)]
+pub struct PendingLaunches(Mutex<Vec<LaunchRequest>>);
+
+/// Split an argv (including the executable) into deep links and paths
+pub fn parse_launch(argv: &[String], cwd: &str) -> LaunchRequest {
+    let args: Vec<String> = argv.iter().skip(1).cloned().collect();
+    let prefix = format!("{}://", DEEP_LINK_SCHEME);
+    let mut deep_links = Vec::new();
+    let mut paths = Vec::new();
+
+    for arg in &args {
+        if arg.to_lowercase().starts_with(&prefix) {
+            deep_links.push(arg.clone());
+        } else if !arg.starts_with('-') {
+            let path = Path::new(cwd).join(arg);
+            if path.exists() {
+                paths.push(path.to_string_lossy().to_string());
+            }
+        }
+    }
+
+    LaunchRequest {
+        args,
+        cwd: cwd.to_string(),
+        deep_links,
+        paths,
+        received_at: Utc::now(),
+    }
+}
+
+pub fn focus_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Queue a launch request and notify the frontend
+pub fn dispatch(app: &AppHandle, request: LaunchRequest) {
+    if request.args.is_empty() {
+        return;
+    }
+    if let Some(pending) = app.try_state::<PendingLaunches>() {
+        let mut queue = pending.0.lock().unwrap();
+        queue.push(request.clone());
+        let excess = queue.len().saturating_sub(MAX_PENDING);
+        queue.drain(..excess);
+    }
+    let _ = app.emit("instance-launch", &request);
+}
+
+/// Callback of the single-instance plugin, run in the primary instance when
+/// another launch is attempted
+pub fn on_second_instance(app: &AppHandle, argv: Vec<String>, cwd: String) {
+    info!(
+        "Second launch forwarded with {} argument(s)",
+        argv.len().saturating_sub(1)
+    );
+    focus_main_window(app);
+    dispatch(app, parse_launch(&argv, &cwd));
+}
+
+/// Deep links opened while running, as delivered by the OS on macOS
+pub fn on_open_urls(app: &AppHandle, urls: Vec<String>) {
+    focus_main_window(app);
+    let cwd = std::env::current_dir()
+        .map(|d| d.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut argv = vec![String::new()];
+    argv.extend(urls);
+    dispatch(app, parse_launch(&argv, &cwd));
+}
+
+/// Take the launch requests the frontend hasn't handled yet, including the
+/// arguments of the first launch
+#[tauri::command]
+pub fn take_pending_launches(pending: tauri::State<'_, PendingLaunches>) -> Vec<LaunchRequest> {
+    std::mem::take(&mut *pending.0.lock().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separates_deep_links_and_existing_paths() {
+        let dir = std::env::temp_dir().join(format!("kk-launch-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(".env"), "A=1").unwrap();
+        let argv = [
+            "/usr/bin/keykeeper",
+            "KeyKeeper://keys/abc",
+            "--minimized",
+            ".env",
+            "missing.env",
+        ]
+        .map(String::from);
+
+        let request = parse_launch(&argv, &dir.to_string_lossy());
+        assert_eq!(request.args.len(), 4);
+        assert_eq!(request.deep_links, vec!["KeyKeeper://keys/abc"]);
+        assert_eq!(
+            request.paths,
+            vec![dir.join(".env").to_string_lossy().to_string()]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,322 @@
+//! LLM-drafted documentation for keys.
+//!
+//! `generate_key_documentation` gathers what KeyKeeper knows about a key
+//! (its service and the provider catalog entry, scopes, the projects and
+//! env files using it, and how to rotate it) and asks the LLM proxy to turn
+//! that into a short doc. The key's value is never part of the prompt. The
+//! draft is saved as `doc_key_<key id>` with the `needs-review` tag, so
+//! generating again replaces it and the previous draft stays in its
+//! revisions.
+//!
+//! The prompts are the `key_documentation` templates, see
+//! prompt_templates.rs. Without a config the first model that answers is
+//! used: OpenAI or Anthropic when `OPENAI_API_KEY` or `ANTHROPIC_API_KEY` is
+//! set, then a local Ollama.
+
+use crate::errors::KeyKeeperError;
+use crate::llm_proxy::{process_with_llm_internal, LLMConfig};
+use crate::rotation::{instructions_for, RotationInstructions};
+use crate::{
+    guards, log_audit_event, prompt_templates, save_docs_store, ApiKey, AppState, Documentation,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::State;
+use tracing::{debug, info};
+
+pub const REVIEW_TAG: &str = "needs-review";
+const MAX_TOKENS: usize = 900;
+
+pub fn doc_id(key_id: &str) -> String {
+    format!("doc_key_{}", key_id)
+}
+
+/// What the draft is written from
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeyFacts {
+    pub name: String,
+    pub service: String,
+    pub environment: String,
+    pub description: Option<String>,
+    pub scopes: Vec<String>,
+    /// From the provider catalog
+    pub provider_description: Option<String>,
+    /// Names of projects using the key
+    pub projects: Vec<String>,
+    pub env_files: Vec<String>,
+    pub rotation: RotationInstructions,
+}
+
+async fn facts_for(state: &State<'_, AppState>, key: &ApiKey) -> (KeyFacts, Option<String>) {
+    let (projects, project_id) = {
+        let vault = state.vault.lock().await;
+        let mut using: Vec<_> = vault
+            .projects
+            .values()
+            .filter(|p| key.project_path.as_deref() == Some(p.path.as_str()))
+            .collect();
+        using.sort_by(|a, b| a.name.cmp(&b.name));
+        (
+            using.iter().map(|p| p.name.clone()).collect::<Vec<_>>(),
+            using.first().map(|p| p.id.clone()),
+        )
+    };
+
+    let provider_description = {
+        let generator = state.api_generator.lock().await;
+        let service = generator.service.lock().await;
+        let name = crate::rotation::normalize(&key.service);
+        service
+            .get_providers()
+            .into_iter()
+            .find(|p| p.id == name || crate::rotation::normalize(&p.name) == name)
+            .map(|p| p.description)
+            .filter(|d| !d.is_empty())
+    };
+
+    let facts = KeyFacts {
+        name: key.name.clone(),
+        service: key.service.clone(),
+        environment: key.environment.clone(),
+        description: key.description.clone().filter(|d| !d.trim().is_empty()),
+        scopes: key.scopes.clone(),
+        provider_description,
+        projects,
+        env_files: key.env_file_path.iter().cloned().collect(),
+        rotation: instructions_for(state, key).await,
+    };
+    (facts, project_id)
+}
+
+fn list(items: &[String]) -> String {
+    if items.is_empty() {
+        "unknown".to_string()
+    } else {
+        items.join(", ")
+    }
+}
+
+/// The facts as a Markdown list
+pub fn facts_list(facts: &KeyFacts) -> String {
+    let mut out = format!("- Service: {}\n", facts.service);
+    if let Some(description) = &facts.provider_description {
+        out.push_str(&format!("- About the service: {}\n", description));
+    }
+    out.push_str(&format!("- Environment: {}\n", facts.environment));
+    if let Some(description) = &facts.description {
+        out.push_str(&format!("- Notes from the owner: {}\n", description));
+    }
+    out.push_str(&format!("- Scopes: {}\n", list(&facts.scopes)));
+    out.push_str(&format!("- Used by projects: {}\n", list(&facts.projects)));
+    out.push_str(&format!("- Env files: {}\n", list(&facts.env_files)));
+    out.push_str("- Rotation steps:\n");
+    for step in &facts.rotation.steps {
+        out.push_str(&format!("  - {}\n", step));
+    }
+    for url in [&facts.rotation.console_url, &facts.rotation.docs_url]
+        .into_iter()
+        .flatten()
+    {
+        out.push_str(&format!("- Link: {}\n", url));
+    }
+    out.trim_end().to_string()
+}
+
+pub fn prompt(facts: &KeyFacts) -> String {
+    prompt_templates::render(
+        prompt_templates::KEY_DOCUMENTATION,
+        &[("key_name", &facts.name), ("facts", &facts_list(facts))],
+    )
+}
+
+fn system_prompt() -> String {
+    prompt_templates::render(prompt_templates::KEY_DOCUMENTATION_SYSTEM, &[])
+}
+
+/// The model's answer without a wrapping code fence
+fn clean(answer: &str) -> String {
+    let answer = answer.trim();
+    let unfenced = answer
+        .strip_prefix("```markdown")
+        .or_else(|| answer.strip_prefix("```md"))
+        .or_else(|| answer.strip_prefix("```"))
+        .and_then(|rest| rest.strip_suffix("```"));
+    unfenced.unwrap_or(answer).trim().to_string()
+}
+
+fn default_configs() -> Vec<LLMConfig> {
+    let config = |provider: &str, model: &str, api_key: Option<String>| LLMConfig {
+        provider: provider.to_string(),
+        model: model.to_string(),
+        temperature: 0.2,
+        max_tokens: MAX_TOKENS,
+        api_key,
+        stream: Some(false),
+        system_prompt: Some(system_prompt()),
+    };
+    let mut configs = Vec::new();
+    if let Ok(api_key) = std::env::var("OPENAI_API_KEY") {
+        configs.push(config("openai", "gpt-4o-mini", Some(api_key)));
+    }
+    if let Ok(api_key) = std::env::var("ANTHROPIC_API_KEY") {
+        configs.push(config(
+            "anthropic",
+            "claude-3-5-haiku-latest",
+            Some(api_key),
+        ));
+    }
+    configs.push(config("ollama", "llama3.2:latest", None));
+    configs
+}
+
+/// Draft a doc for a key and save it for review
+#[tauri::command]
+pub async fn generate_key_documentation(
+    key_id: String,
+    config: Option<LLMConfig>,
+    state: State<'_, AppState>,
+) -> Result<Documentation, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let key = state
+        .vault
+        .lock()
+        .await
+        .keys
+        .get(&key_id)
+        .cloned()
+        .ok_or_else(|| KeyKeeperError::not_found("API key"))?;
+    let (facts, project_id) = facts_for(&state, &key).await;
+    let prompt = prompt(&facts);
+
+    let configs = match config {
+        Some(mut config) => {
+            config.system_prompt.get_or_insert_with(system_prompt);
+            vec![config]
+        }
+        None => default_configs(),
+    };
+    let mut failures = Vec::new();
+    let mut drafted = None;
+    for config in configs {
+        let label = format!("{}/{}", config.provider, config.model);
+        match process_with_llm_internal(prompt.clone(), None, config, Arc::clone(&state.llm_proxy))
+            .await
+        {
+            Ok(response) if response.error.is_none() && !response.content.trim().is_empty() => {
+                drafted = Some((clean(&response.content), label));
+                break;
+            }
+            Ok(response) => failures.push(format!(
+                "{}: {}",
+                label,
+                response.error.unwrap_or_else(|| "empty answer".to_string())
+            )),
+            Err(e) => {
+                debug!("Key doc draft with {} failed: {}", label, e);
+                failures.push(format!("{}: {}", label, e));
+            }
+        }
+    }
+    let (content, model) = drafted.ok_or_else(|| {
+        KeyKeeperError::Internal(format!(
+            "No model could draft the doc ({})",
+            failures.join("; ")
+        ))
+    })?;
+    let content = format!(
+        "{}\n\n---\n_Drafted by {} from the vault and provider catalog. Review before relying on it._\n",
+        content, model
+    );
+
+    let now = Utc::now();
+    let id = doc_id(&key.id);
+    let doc = {
+        let mut store = state.docs_store.lock().await;
+        let mut doc = store
+            .get_doc(&id)
+            .cloned()
+            .unwrap_or_else(|| Documentation {
+                id: id.clone(),
+                title: String::new(),
+                content: String::new(),
+                doc_type: "reference".to_string(),
+                project_id: None,
+                provider_id: None,
+                url: None,
+                tags: Vec::new(),
+                created_at: now,
+                updated_at: now,
+                language: "en".to_string(),
+                is_favorite: false,
+                search_keywords: Vec::new(),
+                sections: Vec::new(),
+                is_offline: false,
+                attachments: Vec::new(),
+            });
+        doc.title = format!("{} ({})", key.name, key.service);
+        doc.content = content;
+        doc.provider_id = facts.rotation.provider.clone();
+        doc.project_id = project_id.or(doc.project_id);
+        doc.url = facts.rotation.docs_url.clone().or(doc.url);
+        if !doc.tags.iter().any(|t| t == REVIEW_TAG) {
+            doc.tags.push(REVIEW_TAG.to_string());
+        }
+        doc.search_keywords = vec![key.name.clone(), key.service.clone()];
+        doc.updated_at = now;
+        if store.get_doc(&id).is_some() {
+            store.update_doc(doc.clone());
+        } else {
+            store.add_doc(doc.clone());
+        }
+        doc
+    };
+    save_docs_store(&state).await?;
+
+    log_audit_event(
+        &state,
+        "generate_key_documentation",
+        "api_key",
+        Some(&key.id),
+        true,
+        Some(&model),
+    )
+    .await;
+    info!("Drafted documentation for key {} with {}", key.id, model);
+    Ok(doc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompt_carries_facts_but_not_secrets() {
+        let facts = KeyFacts {
+            name: "STRIPE_SECRET_KEY".to_string(),
+            service: "Stripe".to_string(),
+            environment: "production".to_string(),
+            scopes: vec!["charges:write".to_string()],
+            projects: vec!["Online Shop".to_string()],
+            rotation: RotationInstructions {
+                steps: vec!["Roll the key in the dashboard".to_string()],
+                console_url: Some("https://dashboard.stripe.com/apikeys".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let prompt = prompt(&facts);
+        assert!(prompt.contains("- Scopes: charges:write\n"));
+        assert!(prompt.contains("- Used by projects: Online Shop\n"));
+        assert!(prompt.contains("- Env files: unknown\n"));
+        assert!(prompt.contains("  - Roll the key in the dashboard\n"));
+        assert!(prompt.contains("https://dashboard.stripe.com/apikeys"));
+
+        assert_eq!(
+            clean("```markdown\n## What it is\nx\n```"),
+            "## What it is\nx"
+        );
+        assert_eq!(clean("## Rotation\n"), "## Rotation");
+    }
+}
@@ -0,0 +1,138 @@
+//! Environment of a key told by its format.
+//!
+//! Providers whose keys say what they are for, like Stripe's `sk_test_` and
+//! `sk_live_`, have `environment_rules` in the provider catalog. Imports
+//! store a matching value under that environment rather than the file's,
+//! provider detection reports it, and a live key kept for another
+//! environment, or a test key kept for production, is flagged: by
+//! `check_key_environment` while a key is entered, in import results and in
+//! the security report.
+
+use crate::api_generator::ApiProvider;
+use crate::docker_env::normalize_environment;
+use crate::errors::KeyKeeperError;
+use crate::{env_import, guards, AppState};
+use serde::Serialize;
+use tauri::State;
+
+const LIVE: &str = "production";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inference {
+    /// Name of the provider whose rule matched
+    pub provider: String,
+    pub environment: String,
+}
+
+pub fn infer(value: &str, providers: &[ApiProvider]) -> Option<Inference> {
+    providers.iter().find_map(|provider| {
+        provider
+            .environment_for(value)
+            .map(|environment| Inference {
+                provider: provider.name.clone(),
+                environment: normalize_environment(environment),
+            })
+    })
+}
+
+/// Warning when a key is kept for `environment` though its format says
+/// otherwise. Only live keys outside production and other keys in
+/// production are flagged; a test key in staging is fine.
+pub fn mismatch(inference: &Inference, environment: &str) -> Option<String> {
+    let assigned = normalize_environment(environment);
+    if assigned == inference.environment {
+        return None;
+    }
+    match (inference.environment == LIVE, assigned == LIVE) {
+        (true, false) => Some(format!(
+            "Live {} key assigned to {}",
+            inference.provider, assigned
+        )),
+        (false, true) => Some(format!(
+            "{} {} key assigned to production",
+            inference.provider, inference.environment
+        )),
+        _ => None,
+    }
+}
+
+/// Whether the key is a live one
+pub fn is_live(inference: &Inference) -> bool {
+    inference.environment == LIVE
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyEnvironmentCheck {
+    pub provider: Option<String>,
+    /// Environment the key's format tells
+    pub environment: Option<String>,
+    pub warning: Option<String>,
+}
+
+/// Check a key's value against the environment it's about to be saved for
+#[tauri::command]
+pub async fn check_key_environment(
+    value: String,
+    environment: String,
+    state: State<'_, AppState>,
+) -> Result<KeyEnvironmentCheck, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let providers = env_import::providers(&state).await;
+    let inference = infer(&value, &providers);
+    Ok(KeyEnvironmentCheck {
+        warning: inference.as_ref().and_then(|i| mismatch(i, &environment)),
+        provider: inference.as_ref().map(|i| i.provider.clone()),
+        environment: inference.map(|i| i.environment),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_generator::EnvironmentRule;
+
+    #[test]
+    fn test_and_live_keys_tell_their_environment() {
+        let stripe = ApiProvider {
+            id: "stripe".to_string(),
+            name: "Stripe".to_string(),
+            description: String::new(),
+            key_patterns: vec![],
+            env_patterns: vec![],
+            docs_url: String::new(),
+            setup_type: String::new(),
+            category: String::new(),
+            dependencies: vec![],
+            config_templates: vec![],
+            environment_rules: vec![
+                EnvironmentRule {
+                    pattern: "^(sk|pk|rk)_test_".to_string(),
+                    environment: "development".to_string(),
+                },
+                EnvironmentRule {
+                    pattern: "^(sk|pk|rk)_live_".to_string(),
+                    environment: "prod".to_string(),
+                },
+            ],
+        };
+        let providers = [stripe];
+
+        let live = infer("sk_live_abc123", &providers).unwrap();
+        assert_eq!(live.environment, "production");
+        assert_eq!(
+            mismatch(&live, "dev").as_deref(),
+            Some("Live Stripe key assigned to development")
+        );
+        assert_eq!(mismatch(&live, "production"), None);
+
+        let test = infer("pk_test_abc123", &providers).unwrap();
+        assert_eq!(test.environment, "development");
+        assert_eq!(mismatch(&test, "staging"), None);
+        assert_eq!(
+            mismatch(&test, "production").as_deref(),
+            Some("Stripe development key assigned to production")
+        );
+        assert!(infer("whsec_abc123", &providers).is_none());
+    }
+}
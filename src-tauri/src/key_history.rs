@@ -0,0 +1,205 @@
+//! Key metadata history and the vault's state at a past time.
+//!
+//! Whenever a key is created, changed or deleted its metadata, never its
+//! value, is added to `key_history` as a revision, unless only its usage
+//! changed. `get_vault_state_at` replays the revisions to tell which keys
+//! existed at a given time, their environments and where they were
+//! assigned, with the clients the audit log shows using each one by then:
+//! what an incident investigation asks ("what keys did the build server
+//! have access to on March 3rd?").
+//!
+//! Keys created before the history was kept, or whose early revisions
+//! were dropped past `MAX_REVISIONS`, are reconstructed from the oldest
+//! state known and marked `approximate`.
+
+use crate::errors::KeyKeeperError;
+use crate::vault_snapshot::KeySnapshot;
+use crate::{guards, ApiKey, ApiKeyVault, AppState};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use tauri::State;
+
+const MAX_REVISIONS: usize = 5000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyRevision {
+    pub recorded_at: DateTime<Utc>,
+    pub deleted: bool,
+    pub key: KeySnapshot,
+}
+
+impl KeyRevision {
+    /// Whether `other` says the same, usage and timestamps aside
+    fn same_as(&self, other: &KeyRevision) -> bool {
+        let comparable = |revision: &KeyRevision| KeySnapshot {
+            updated_at: revision.key.created_at,
+            last_used_at: None,
+            ..revision.key.clone()
+        };
+        self.deleted == other.deleted && comparable(self) == comparable(other)
+    }
+}
+
+/// Add a revision of `key` as it is now, or as it was when `deleted`, to
+/// the vault's `key_history`
+pub fn record(history: &mut Vec<KeyRevision>, key: &ApiKey, deleted: bool) {
+    let revision = KeyRevision {
+        recorded_at: Utc::now(),
+        deleted,
+        key: KeySnapshot::from(key),
+    };
+    let latest = history.iter().rev().find(|r| r.key.id == key.id);
+    if latest.is_some_and(|latest| latest.same_as(&revision)) {
+        return;
+    }
+    history.push(revision);
+    let excess = history.len().saturating_sub(MAX_REVISIONS);
+    history.drain(..excess);
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyStateAt {
+    #[serde(flatten)]
+    pub key: KeySnapshot,
+    /// Not from a revision recorded by then
+    pub approximate: bool,
+    /// Clients that used the key by then, per the audit log
+    pub accessed_by: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultStateAt {
+    pub at: DateTime<Utc>,
+    pub keys: Vec<KeyStateAt>,
+    /// Oldest revision kept; states before it are approximate
+    pub history_since: Option<DateTime<Utc>>,
+}
+
+pub fn state_at(vault: &ApiKeyVault, at: DateTime<Utc>) -> VaultStateAt {
+    let mut revisions: HashMap<&str, Vec<&KeyRevision>> = HashMap::new();
+    for revision in &vault.key_history {
+        revisions
+            .entry(revision.key.id.as_str())
+            .or_default()
+            .push(revision);
+    }
+
+    let mut keys: Vec<KeyStateAt> = Vec::new();
+    let mut add = |key: &KeySnapshot, approximate: bool| {
+        if key.created_at <= at {
+            keys.push(KeyStateAt {
+                key: key.clone(),
+                approximate,
+                accessed_by: Vec::new(),
+            });
+        }
+    };
+    for revisions in revisions.values() {
+        match revisions.iter().rev().find(|r| r.recorded_at <= at) {
+            Some(revision) if revision.deleted => {}
+            Some(revision) => add(&revision.key, false),
+            // Only changes after `at` are known; the first of them tells
+            // the key's state best
+            None => add(&revisions[0].key, true),
+        }
+    }
+    for key in vault.keys.values() {
+        if !revisions.contains_key(key.id.as_str()) {
+            add(&KeySnapshot::from(key), true);
+        }
+    }
+
+    let mut accessed_by: HashMap<&str, BTreeSet<&str>> = HashMap::new();
+    for log in vault.audit_logs.iter().filter(|l| l.timestamp <= at) {
+        if let (true, "api_key", Some(key_id), Some(client)) = (
+            log.success,
+            log.resource_type.as_str(),
+            log.resource_id.as_deref(),
+            log.client_id.as_deref(),
+        ) {
+            accessed_by.entry(key_id).or_default().insert(client);
+        }
+    }
+    for state in &mut keys {
+        if let Some(clients) = accessed_by.get(state.key.id.as_str()) {
+            state.accessed_by = clients.iter().map(|c| c.to_string()).collect();
+        }
+    }
+    keys.sort_by(|a, b| {
+        a.key
+            .name
+            .cmp(&b.key.name)
+            .then_with(|| a.key.id.cmp(&b.key.id))
+    });
+
+    VaultStateAt {
+        at,
+        keys,
+        history_since: vault.key_history.first().map(|r| r.recorded_at),
+    }
+}
+
+/// Key metadata as of `timestamp`
+#[tauri::command]
+pub async fn get_vault_state_at(
+    timestamp: DateTime<Utc>,
+    state: State<'_, AppState>,
+) -> Result<VaultStateAt, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault = state.vault.lock().await;
+    Ok(state_at(&vault, timestamp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault_tests::test_key;
+    use chrono::Duration;
+
+    #[test]
+    fn revisions_replay_to_the_state_at_a_time() {
+        let mut vault = ApiKeyVault::default();
+        let staging = test_key("k1")
+            .name("Deploy token")
+            .service("GitHub")
+            .secret("ghp_secret")
+            .environment("staging")
+            .project(Some("/build-server"))
+            .build();
+        record(&mut vault.key_history, &staging, false);
+        // Only usage changed
+        let used = ApiKey {
+            last_used_at: Some(Utc::now()),
+            ..staging.clone()
+        };
+        record(&mut vault.key_history, &used, false);
+        assert_eq!(vault.key_history.len(), 1);
+
+        let before_change = Utc::now();
+        vault.key_history[0].recorded_at = before_change - Duration::days(2);
+        let production = ApiKey {
+            environment: "production".to_string(),
+            project_path: None,
+            ..staging.clone()
+        };
+        record(&mut vault.key_history, &production, false);
+        vault.key_history[1].recorded_at = before_change - Duration::days(1);
+        record(&mut vault.key_history, &production, true);
+
+        let then = state_at(&vault, before_change - Duration::hours(36));
+        assert_eq!(then.keys.len(), 1);
+        assert_eq!(then.keys[0].key.environment, "staging");
+        assert_eq!(
+            then.keys[0].key.project_path.as_deref(),
+            Some("/build-server")
+        );
+        assert!(!then.keys[0].approximate);
+
+        let later = state_at(&vault, before_change - Duration::hours(12));
+        assert_eq!(later.keys[0].key.environment, "production");
+        // Deleted since
+        assert!(state_at(&vault, Utc::now()).keys.is_empty());
+    }
+}
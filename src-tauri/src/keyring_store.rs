@@ -0,0 +1,310 @@
+//! Namespaced OS keyring entries.
+//!
+//! Entries used to live under fixed names ("KeyKeeper" / "master_password"),
+//! so two vaults, or two profiles sharing a keyring, overwrote each other.
+//! Every entry now goes under the service `KeyKeeper:<vault id>` with the
+//! account `<profile>:<name>`. The profile is the OS user unless
+//! `KEYKEEPER_PROFILE` names another one.
+//!
+//! OS keyrings can't be listed portably, so written entries are also
+//! recorded, without their secrets, in `keyring_entries.json` in the app
+//! data dir. Entries under the old names are moved into the namespace the
+//! first time they're read. Where the OS keyring can't be used, keystore.rs
+//! keeps the entries in an encrypted file instead.
+
+use crate::errors::KeyKeeperError;
+use crate::keystore;
+use crate::{get_app_data_dir, AppState};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, State};
+use tracing::warn;
+use zeroize::Zeroizing;
+
+pub const SERVICE: &str = "KeyKeeper";
+pub const INDEX_FILE: &str = "keyring_entries.json";
+const PROFILE_ENV: &str = "KEYKEEPER_PROFILE";
+/// Master password saved by earlier versions under `SERVICE`
+pub const MASTER_PASSWORD: &str = "master_password";
+
+pub fn new_vault_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// ID of a vault written before vaults had one. Derived from the salt so it
+/// stays the same across loads until the vault is saved with it.
+pub fn legacy_vault_id(salt: Option<&str>) -> String {
+    match salt {
+        Some(salt) => {
+            let digest = Sha256::digest(format!("keykeeper-vault:{}", salt).as_bytes());
+            format!("{:x}", digest)[..32].to_string()
+        }
+        None => "default".to_string(),
+    }
+}
+
+/// v5 -> v6: give the vault an ID to namespace its keyring entries with
+pub fn assign_vault_id(document: &mut Value) -> Result<(), String> {
+    let Some(vault) = document.as_object_mut() else {
+        return Ok(());
+    };
+    if vault.get("vault_id").and_then(Value::as_str).is_none() {
+        let salt = vault.get("salt").and_then(Value::as_str);
+        let id = legacy_vault_id(salt);
+        vault.insert("vault_id".to_string(), Value::String(id));
+    }
+    Ok(())
+}
+
+pub fn current_profile() -> String {
+    std::env::var(PROFILE_ENV)
+        .ok()
+        .map(|profile| profile.trim().to_string())
+        .filter(|profile| !profile.is_empty())
+        .unwrap_or_else(whoami::username)
+}
+
+/// An entry written by KeyKeeper, as recorded in the index
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeyringEntryRecord {
+    pub service: String,
+    pub account: String,
+    pub vault_id: String,
+    pub profile: String,
+    /// Name inside the namespace, e.g. "master_password"
+    pub name: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyringEntryInfo {
+    #[serde(flatten)]
+    pub record: KeyringEntryRecord,
+    /// Belongs to the open vault and the current profile
+    pub current: bool,
+    /// Still present in the keystore
+    pub exists: bool,
+}
+
+/// Keyring entries of one vault and profile
+#[derive(Debug, Clone)]
+pub struct KeyringStore {
+    pub vault_id: String,
+    pub profile: String,
+    index_path: PathBuf,
+}
+
+impl KeyringStore {
+    pub fn new(vault_id: &str, profile: &str, index_path: PathBuf) -> Self {
+        Self {
+            vault_id: vault_id.to_string(),
+            profile: profile.to_string(),
+            index_path,
+        }
+    }
+
+    /// Store of the open (or locked) vault and the current profile
+    pub async fn open(app: &AppHandle, state: &AppState) -> Result<Self, KeyKeeperError> {
+        let vault_id = state.vault.lock().await.vault_id.clone();
+        let index_path = get_app_data_dir(app)?.join(INDEX_FILE);
+        Ok(Self::new(&vault_id, &current_profile(), index_path))
+    }
+
+    pub fn service(&self) -> String {
+        format!("{}:{}", SERVICE, self.vault_id)
+    }
+
+    pub fn account(&self, name: &str) -> String {
+        format!("{}:{}", self.profile, name)
+    }
+
+    fn read_index(&self) -> Vec<KeyringEntryRecord> {
+        fs::read_to_string(&self.index_path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn write_index(&self, records: &[KeyringEntryRecord]) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(records)
+            .map_err(|e| format!("Failed to serialize keyring index: {}", e))?;
+        fs::write(&self.index_path, json)
+            .map_err(|e| format!("Failed to write keyring index: {}", e))
+    }
+
+    fn record(&self, name: &str) {
+        let mut records = self.read_index();
+        let service = self.service();
+        let account = self.account(name);
+        records.retain(|r| r.service != service || r.account != account);
+        records.push(KeyringEntryRecord {
+            service,
+            account,
+            vault_id: self.vault_id.clone(),
+            profile: self.profile.clone(),
+            name: name.to_string(),
+            updated_at: Utc::now(),
+        });
+        if let Err(e) = self.write_index(&records) {
+            warn!("{}", e);
+        }
+    }
+
+    fn unrecord(&self, service: &str, account: &str) {
+        let mut records = self.read_index();
+        let before = records.len();
+        records.retain(|r| r.service != service || r.account != account);
+        if records.len() != before {
+            if let Err(e) = self.write_index(&records) {
+                warn!("{}", e);
+            }
+        }
+    }
+
+    pub fn set(&self, name: &str, secret: &str) -> Result<(), String> {
+        keystore::set(&self.service(), &self.account(name), secret)?;
+        self.record(name);
+        Ok(())
+    }
+
+    /// Read an entry. When it's missing and `legacy` names where an earlier
+    /// version kept it, the old entry is moved into the namespace.
+    pub fn get(
+        &self,
+        name: &str,
+        legacy: Option<(&str, &str)>,
+    ) -> Result<Option<Zeroizing<String>>, String> {
+        if let Some(secret) = keystore::get(&self.service(), &self.account(name))? {
+            return Ok(Some(secret));
+        }
+        let Some((service, account)) = legacy else {
+            return Ok(None);
+        };
+        let Some(secret) = keystore::get(service, account)? else {
+            return Ok(None);
+        };
+        self.set(name, &secret)?;
+        if let Err(e) = keystore::delete(service, account) {
+            warn!("Failed to delete migrated keyring entry: {}", e);
+        }
+        Ok(Some(secret))
+    }
+
+    /// Delete an entry, and its pre-namespace copy if `legacy` is given.
+    /// Returns whether anything was deleted.
+    pub fn delete(&self, name: &str, legacy: Option<(&str, &str)>) -> Result<bool, String> {
+        let mut deleted = keystore::delete(&self.service(), &self.account(name))?;
+        self.unrecord(&self.service(), &self.account(name));
+        if let Some((service, account)) = legacy {
+            deleted |= keystore::delete(service, account)?;
+        }
+        Ok(deleted)
+    }
+
+    /// Entries in the index, of every vault and profile
+    pub fn list(&self) -> Vec<KeyringEntryInfo> {
+        self.read_index()
+            .into_iter()
+            .map(|record| KeyringEntryInfo {
+                current: record.vault_id == self.vault_id && record.profile == self.profile,
+                exists: matches!(keystore::get(&record.service, &record.account), Ok(Some(_))),
+                record,
+            })
+            .collect()
+    }
+
+    /// Delete the current profile's entries of `vault_id`, or when it's
+    /// `None` drop index records whose keyring entry is gone. Returns how
+    /// many records were removed.
+    pub fn cleanup(&self, vault_id: Option<&str>) -> Result<usize, String> {
+        let mut removed = 0;
+        let mut kept = Vec::new();
+        for info in self.list() {
+            let remove = match vault_id {
+                Some(vault_id) => {
+                    info.record.vault_id == vault_id && info.record.profile == self.profile
+                }
+                None => !info.exists,
+            };
+            if !remove {
+                kept.push(info.record);
+                continue;
+            }
+            if info.exists {
+                keystore::delete(&info.record.service, &info.record.account)?;
+            }
+            removed += 1;
+        }
+        self.write_index(&kept)?;
+        Ok(removed)
+    }
+}
+
+#[tauri::command]
+pub async fn list_keyring_entries(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<KeyringEntryInfo>, KeyKeeperError> {
+    let store = KeyringStore::open(&app, &state).await?;
+    tauri::async_runtime::spawn_blocking(move || store.list())
+        .await
+        .map_err(|e| KeyKeeperError::Internal(format!("Failed to list keyring entries: {}", e)))
+}
+
+/// Delete this profile's entries of `vault_id` (a vault that was removed or
+/// moved away), or without one forget entries that no longer exist
+#[tauri::command]
+pub async fn cleanup_keyring_entries(
+    vault_id: Option<String>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<usize, KeyKeeperError> {
+    let store = KeyringStore::open(&app, &state).await?;
+    let removed = tauri::async_runtime::spawn_blocking(move || store.cleanup(vault_id.as_deref()))
+        .await
+        .map_err(|e| KeyKeeperError::Internal(format!("Failed to clean up keyring: {}", e)))??;
+    crate::log_audit_event(
+        &state,
+        "cleanup_keyring_entries",
+        "keyring",
+        None,
+        true,
+        Some(&format!("{} entries removed", removed)),
+    )
+    .await;
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vaults_and_profiles_get_their_own_namespace() {
+        let index = std::env::temp_dir().join(INDEX_FILE);
+        let a = KeyringStore::new("vault-a", "alice", index.clone());
+        let b = KeyringStore::new("vault-b", "alice", index.clone());
+        let bob = KeyringStore::new("vault-a", "bob", index);
+        assert_ne!(a.service(), b.service());
+        assert_eq!(a.service(), bob.service());
+        assert_ne!(a.account(MASTER_PASSWORD), bob.account(MASTER_PASSWORD));
+
+        assert_eq!(legacy_vault_id(Some("salt")), legacy_vault_id(Some("salt")));
+        assert_ne!(
+            legacy_vault_id(Some("salt")),
+            legacy_vault_id(Some("pepper"))
+        );
+
+        let mut document = serde_json::json!({ "salt": "salt" });
+        assign_vault_id(&mut document).unwrap();
+        assert_eq!(document["vault_id"], legacy_vault_id(Some("salt")));
+        let mut named = serde_json::json!({ "vault_id": "kept", "salt": "salt" });
+        assign_vault_id(&mut named).unwrap();
+        assert_eq!(named["vault_id"], "kept");
+    }
+}
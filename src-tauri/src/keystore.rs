@@ -0,0 +1,540 @@
+//! Backend behind the keyring entries of keyring_store.rs.
+//!
+//! The OS keyring (macOS Keychain, Windows Credential Manager, the Secret
+//! Service on Linux) is probed at startup by writing, reading back and
+//! deleting a throwaway entry. Where it can't be used, e.g. on Linux
+//! without a Secret Service daemon, entries go to `keystore.json` in the
+//! app data dir instead, each encrypted with AES-256-GCM under a random key
+//! kept in `keystore.key`. Both files are readable by the current user
+//! only, so the fallback protects entries from other users and from
+//! backups of the JSON file alone, not from the user's own processes.
+//!
+//! A keyring that starts failing at runtime switches the backend to the
+//! file. While on the file, the keyring is probed again every few minutes;
+//! once it works, the file's entries are moved into it and the file is
+//! deleted.
+
+use crate::errors::KeyKeeperError;
+use aes_gcm::aead::{Aead, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::Duration;
+use tauri::AppHandle;
+use tracing::{info, warn};
+use zeroize::Zeroizing;
+
+pub const FILE: &str = "keystore.json";
+pub const KEY_FILE: &str = "keystore.key";
+const PROBE_SERVICE: &str = "KeyKeeper:probe";
+const PROBE_VALUE: &str = "keykeeper-probe";
+const NONCE_LEN: usize = 12;
+/// How often an unusable keyring is probed again
+const REPROBE_INTERVAL_SECS: u64 = 300;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeystoreBackend {
+    OsKeyring,
+    EncryptedFile,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreStatus {
+    pub backend: KeystoreBackend,
+    /// Name of the platform's keyring
+    pub os_keyring: String,
+    pub os_keyring_available: bool,
+    /// Why the keyring can't be used
+    pub os_keyring_error: Option<String>,
+    pub fallback_path: Option<String>,
+    /// Entries in the fallback file
+    pub fallback_entries: usize,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub checked_at: DateTime<Utc>,
+    /// Last move of fallback entries into the keyring
+    #[serde(default, with = "crate::timestamps::rfc3339_option")]
+    pub migrated_at: Option<DateTime<Utc>>,
+    pub migrated_entries: usize,
+}
+
+pub fn os_keyring_name() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "macOS Keychain"
+    } else if cfg!(windows) {
+        "Windows Credential Manager"
+    } else if cfg!(target_os = "linux") {
+        "Secret Service"
+    } else {
+        "OS keyring"
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FileEntry {
+    service: String,
+    account: String,
+    /// Base64 of nonce and ciphertext
+    secret: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    updated_at: DateTime<Utc>,
+}
+
+/// The encrypted-file fallback
+#[derive(Debug)]
+pub struct FileKeystore {
+    path: PathBuf,
+    key_path: PathBuf,
+    lock: Mutex<()>,
+}
+
+/// Write `data` readable by the current user only
+fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    crate::vault_location::write_atomic(path, data)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+impl FileKeystore {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            path: dir.join(FILE),
+            key_path: dir.join(KEY_FILE),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn read(&self) -> Result<Vec<FileEntry>, String> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| format!("Fallback keystore is unreadable: {}", e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(format!("Failed to read fallback keystore: {}", e)),
+        }
+    }
+
+    fn write(&self, entries: &[FileEntry]) -> Result<(), String> {
+        if entries.is_empty() {
+            for path in [&self.path, &self.key_path] {
+                match fs::remove_file(path) {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(e) => return Err(format!("Failed to delete {}: {}", path.display(), e)),
+                }
+            }
+            return Ok(());
+        }
+        let json = serde_json::to_string_pretty(entries)
+            .map_err(|e| format!("Failed to serialize fallback keystore: {}", e))?;
+        write_private(&self.path, json.as_bytes())
+            .map_err(|e| format!("Failed to write fallback keystore: {}", e))
+    }
+
+    /// The file key, created with the first entry
+    fn key(&self, create: bool) -> Result<Option<Zeroizing<Vec<u8>>>, String> {
+        match fs::read_to_string(&self.key_path) {
+            Ok(encoded) => {
+                let key = Zeroizing::new(
+                    general_purpose::STANDARD
+                        .decode(encoded.trim())
+                        .map_err(|e| format!("Fallback keystore key is corrupted: {}", e))?,
+                );
+                if key.len() != 32 {
+                    return Err("Fallback keystore key is corrupted".to_string());
+                }
+                Ok(Some(key))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && create => {
+                let mut key = Zeroizing::new(vec![0u8; 32]);
+                OsRng.fill_bytes(&mut key);
+                write_private(
+                    &self.key_path,
+                    general_purpose::STANDARD.encode(&*key).as_bytes(),
+                )
+                .map_err(|e| format!("Failed to write fallback keystore key: {}", e))?;
+                Ok(Some(key))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read fallback keystore key: {}", e)),
+        }
+    }
+
+    fn decrypt(key: &[u8], entry: &FileEntry) -> Result<Zeroizing<String>, String> {
+        let blob = general_purpose::STANDARD
+            .decode(&entry.secret)
+            .map_err(|e| format!("Fallback keystore entry is corrupted: {}", e))?;
+        if blob.len() < NONCE_LEN {
+            return Err("Fallback keystore entry is truncated".to_string());
+        }
+        let (nonce, ciphertext) = blob.split_at(NONCE_LEN);
+        let plaintext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "Failed to decrypt fallback keystore entry".to_string())?;
+        String::from_utf8(plaintext)
+            .map(Zeroizing::new)
+            .map_err(|_| "Fallback keystore entry is not text".to_string())
+    }
+
+    pub fn get(&self, service: &str, account: &str) -> Result<Option<Zeroizing<String>>, String> {
+        let _guard = self.lock.lock().unwrap();
+        let entries = self.read()?;
+        let Some(entry) = entries
+            .iter()
+            .find(|e| e.service == service && e.account == account)
+        else {
+            return Ok(None);
+        };
+        let key = self
+            .key(false)?
+            .ok_or_else(|| "Fallback keystore key is missing".to_string())?;
+        Self::decrypt(&key, entry).map(Some)
+    }
+
+    pub fn set(&self, service: &str, account: &str, secret: &str) -> Result<(), String> {
+        let _guard = self.lock.lock().unwrap();
+        let mut entries = self.read()?;
+        let key = self
+            .key(true)?
+            .ok_or_else(|| "Fallback keystore key is missing".to_string())?;
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+            .encrypt(Nonce::from_slice(&nonce), secret.as_bytes())
+            .map_err(|e| format!("Failed to encrypt keystore entry: {}", e))?;
+        entries.retain(|e| e.service != service || e.account != account);
+        entries.push(FileEntry {
+            service: service.to_string(),
+            account: account.to_string(),
+            secret: general_purpose::STANDARD.encode([nonce.as_slice(), &ciphertext].concat()),
+            updated_at: Utc::now(),
+        });
+        self.write(&entries)
+    }
+
+    pub fn delete(&self, service: &str, account: &str) -> Result<bool, String> {
+        let _guard = self.lock.lock().unwrap();
+        let mut entries = self.read()?;
+        let before = entries.len();
+        entries.retain(|e| e.service != service || e.account != account);
+        if entries.len() == before {
+            return Ok(false);
+        }
+        self.write(&entries)?;
+        Ok(true)
+    }
+
+    pub fn count(&self) -> usize {
+        let _guard = self.lock.lock().unwrap();
+        self.read().map(|entries| entries.len()).unwrap_or(0)
+    }
+
+    /// Move every entry into the OS keyring. Entries that can't be moved
+    /// stay in the file. Returns how many were moved.
+    fn migrate_to_os_keyring(&self) -> Result<usize, String> {
+        let _guard = self.lock.lock().unwrap();
+        let entries = self.read()?;
+        let Some(key) = self.key(false)? else {
+            return Ok(0);
+        };
+        let total = entries.len();
+        let mut kept = Vec::new();
+        for entry in entries {
+            let moved = Self::decrypt(&key, &entry).and_then(|secret| {
+                os::set(&entry.service, &entry.account, &secret)?;
+                match os::get(&entry.service, &entry.account)? {
+                    Some(read) if *read == *secret => Ok(()),
+                    _ => Err("the keyring returned a different value".to_string()),
+                }
+            });
+            if let Err(e) = moved {
+                warn!("Keeping {} in the fallback keystore: {}", entry.account, e);
+                kept.push(entry);
+            }
+        }
+        self.write(&kept)?;
+        Ok(total - kept.len())
+    }
+}
+
+/// The OS keyring, through the keyring crate
+mod os {
+    use zeroize::Zeroizing;
+
+    /// Whether the error means the keyring itself is unusable rather than
+    /// something being wrong with one entry
+    pub fn is_unavailable(error: &keyring::Error) -> bool {
+        matches!(
+            error,
+            keyring::Error::PlatformFailure(_) | keyring::Error::NoStorageAccess(_)
+        )
+    }
+
+    fn entry(service: &str, account: &str) -> Result<keyring::Entry, keyring::Error> {
+        keyring::Entry::new(service, account)
+    }
+
+    pub fn get_raw(
+        service: &str,
+        account: &str,
+    ) -> Result<Option<Zeroizing<String>>, keyring::Error> {
+        match entry(service, account)?.get_password() {
+            Ok(secret) => Ok(Some(Zeroizing::new(secret))),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn set_raw(service: &str, account: &str, secret: &str) -> Result<(), keyring::Error> {
+        entry(service, account)?.set_password(secret)
+    }
+
+    pub fn delete_raw(service: &str, account: &str) -> Result<bool, keyring::Error> {
+        match entry(service, account)?.delete_password() {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn get(service: &str, account: &str) -> Result<Option<Zeroizing<String>>, String> {
+        get_raw(service, account).map_err(|e| format!("Failed to read keyring: {}", e))
+    }
+
+    pub fn set(service: &str, account: &str, secret: &str) -> Result<(), String> {
+        set_raw(service, account, secret).map_err(|e| format!("Failed to save to keyring: {}", e))
+    }
+
+    /// Write, read back and delete a throwaway entry
+    pub fn probe() -> Result<(), String> {
+        let account = format!("probe-{}", uuid::Uuid::new_v4().simple());
+        set_raw(super::PROBE_SERVICE, &account, super::PROBE_VALUE).map_err(|e| e.to_string())?;
+        let read = get_raw(super::PROBE_SERVICE, &account);
+        let _ = delete_raw(super::PROBE_SERVICE, &account);
+        match read {
+            Ok(Some(value)) if *value == super::PROBE_VALUE => Ok(()),
+            Ok(_) => Err("the keyring didn't return what was saved".to_string()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+static FALLBACK: OnceLock<FileKeystore> = OnceLock::new();
+static STATUS: RwLock<Option<KeystoreStatus>> = RwLock::new(None);
+
+/// Set where the fallback file goes and detect the backend in the
+/// background. Called once at startup.
+pub fn init(app_data_dir: &Path) {
+    let _ = FALLBACK.set(FileKeystore::new(app_data_dir));
+    tauri::async_runtime::spawn_blocking(|| {
+        let status = detect();
+        info!(
+            "Keyring entries are kept in {}",
+            match status.backend {
+                KeystoreBackend::OsKeyring => status.os_keyring.as_str(),
+                KeystoreBackend::EncryptedFile => "the encrypted fallback file",
+            }
+        );
+    });
+}
+
+fn fallback() -> Result<&'static FileKeystore, String> {
+    FALLBACK
+        .get()
+        .ok_or_else(|| "The keystore isn't initialized".to_string())
+}
+
+/// Probe the keyring, move fallback entries into it when it works, and
+/// record the outcome
+pub fn detect() -> KeystoreStatus {
+    let probe = os::probe();
+    let previous = STATUS.read().unwrap().clone();
+    let mut migrated_at = previous.as_ref().and_then(|s| s.migrated_at);
+    let mut migrated_entries = previous.as_ref().map_or(0, |s| s.migrated_entries);
+
+    match (&probe, fallback()) {
+        (Ok(()), Ok(file)) if file.count() > 0 => match file.migrate_to_os_keyring() {
+            Ok(moved) => {
+                info!(
+                    "Moved {} keystore entries into the {}",
+                    moved,
+                    os_keyring_name()
+                );
+                migrated_at = Some(Utc::now());
+                migrated_entries = moved;
+            }
+            Err(e) => warn!("Failed to move keystore entries into the keyring: {}", e),
+        },
+        (Ok(()), _) => {}
+        (Err(e), _) => warn!(
+            "{} is unavailable, using the encrypted fallback file: {}",
+            os_keyring_name(),
+            e
+        ),
+    }
+
+    let status = KeystoreStatus {
+        backend: if probe.is_ok() {
+            KeystoreBackend::OsKeyring
+        } else {
+            KeystoreBackend::EncryptedFile
+        },
+        os_keyring: os_keyring_name().to_string(),
+        os_keyring_available: probe.is_ok(),
+        os_keyring_error: probe.err(),
+        fallback_path: FALLBACK.get().map(|f| f.path.to_string_lossy().to_string()),
+        fallback_entries: FALLBACK.get().map_or(0, FileKeystore::count),
+        checked_at: Utc::now(),
+        migrated_at,
+        migrated_entries,
+    };
+    *STATUS.write().unwrap() = Some(status.clone());
+    status
+}
+
+pub fn status() -> KeystoreStatus {
+    let current = STATUS.read().unwrap().clone();
+    current.unwrap_or_else(detect)
+}
+
+fn backend() -> KeystoreBackend {
+    status().backend
+}
+
+/// Switch to the file after the keyring failed at runtime
+fn degrade(error: &keyring::Error) {
+    warn!(
+        "{} failed, using the encrypted fallback file: {}",
+        os_keyring_name(),
+        error
+    );
+    if let Some(status) = STATUS.write().unwrap().as_mut() {
+        status.backend = KeystoreBackend::EncryptedFile;
+        status.os_keyring_available = false;
+        status.os_keyring_error = Some(error.to_string());
+        status.checked_at = Utc::now();
+    }
+}
+
+pub fn get(service: &str, account: &str) -> Result<Option<Zeroizing<String>>, String> {
+    if backend() == KeystoreBackend::OsKeyring {
+        match os::get_raw(service, account) {
+            Ok(Some(secret)) => return Ok(Some(secret)),
+            // Entries that couldn't be moved out of the file yet
+            Ok(None) => {}
+            Err(e) if os::is_unavailable(&e) => degrade(&e),
+            Err(e) => return Err(format!("Failed to read keyring: {}", e)),
+        }
+    }
+    fallback()?.get(service, account)
+}
+
+pub fn set(service: &str, account: &str, secret: &str) -> Result<(), String> {
+    if backend() == KeystoreBackend::OsKeyring {
+        match os::set_raw(service, account, secret) {
+            Ok(()) => {
+                // Drop a stale copy left in the file
+                if let Ok(file) = fallback() {
+                    if let Err(e) = file.delete(service, account) {
+                        warn!("{}", e);
+                    }
+                }
+                return Ok(());
+            }
+            Err(e) if os::is_unavailable(&e) => degrade(&e),
+            Err(e) => return Err(format!("Failed to save to keyring: {}", e)),
+        }
+    }
+    fallback()?.set(service, account, secret)
+}
+
+/// Delete from both backends. Returns whether anything was deleted.
+pub fn delete(service: &str, account: &str) -> Result<bool, String> {
+    let mut deleted = false;
+    if backend() == KeystoreBackend::OsKeyring {
+        match os::delete_raw(service, account) {
+            Ok(found) => deleted = found,
+            Err(e) if os::is_unavailable(&e) => degrade(&e),
+            Err(e) => return Err(format!("Failed to delete from keyring: {}", e)),
+        }
+    }
+    Ok(fallback()?.delete(service, account)? || deleted)
+}
+
+/// Probe an unusable keyring again now and then, moving entries into it
+/// once it works
+pub async fn run_keystore_monitor(_app: AppHandle) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(REPROBE_INTERVAL_SECS)).await;
+        if backend() == KeystoreBackend::OsKeyring {
+            continue;
+        }
+        let status = tauri::async_runtime::spawn_blocking(detect).await;
+        if let Ok(status) = status {
+            if status.backend == KeystoreBackend::OsKeyring {
+                info!("{} became available", status.os_keyring);
+            }
+        }
+    }
+}
+
+/// Where keyring entries are kept; `refresh` probes the keyring again
+#[tauri::command]
+pub async fn get_keystore_backend(refresh: Option<bool>) -> Result<KeystoreStatus, KeyKeeperError> {
+    let refresh = refresh.unwrap_or(false);
+    tauri::async_runtime::spawn_blocking(move || if refresh { detect() } else { status() })
+        .await
+        .map_err(|e| KeyKeeperError::Internal(format!("Keystore check failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fallback_file_keeps_entries_encrypted() {
+        let dir = std::env::temp_dir().join(format!("kk-keystore-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let store = FileKeystore::new(&dir);
+        assert_eq!(
+            store.get("KeyKeeper:v1", "alice:master_password").unwrap(),
+            None
+        );
+
+        store
+            .set("KeyKeeper:v1", "alice:master_password", "hunter22")
+            .unwrap();
+        store.set("KeyKeeper:v1", "alice:other", "x").unwrap();
+        store
+            .set("KeyKeeper:v1", "alice:master_password", "correct horse")
+            .unwrap();
+        assert_eq!(store.count(), 2);
+        assert_eq!(
+            store
+                .get("KeyKeeper:v1", "alice:master_password")
+                .unwrap()
+                .as_deref()
+                .map(String::as_str),
+            Some("correct horse")
+        );
+        let contents = fs::read_to_string(dir.join(FILE)).unwrap();
+        assert!(!contents.contains("correct horse"));
+
+        assert!(store.delete("KeyKeeper:v1", "alice:other").unwrap());
+        assert!(!store.delete("KeyKeeper:v1", "alice:other").unwrap());
+        assert!(store
+            .delete("KeyKeeper:v1", "alice:master_password")
+            .unwrap());
+        // The last delete removes both files
+        assert!(!dir.join(FILE).exists() && !dir.join(KEY_FILE).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,405 @@
+//! Time-boxed key leases.
+//!
+//! A lease exposes one key for a limited time, either as a temporary env file
+//! or as a one-time token redeemable on the HTTP API. Leases are tracked in the
+//! vault; the sweeper marks them expired and deletes their files. Env files
+//! carry their expiry in the file name, so they are removed on time even while
+//! the vault is locked or after a restart.
+
+use crate::errors::KeyKeeperError;
+use crate::{env_var_name_for_key, guards, log_audit_event, save_vault, ApiKeyVault, AppState};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+const SWEEP_INTERVAL_SECS: u64 = 15;
+const MIN_TTL_SECS: u64 = 60;
+const MAX_TTL_SECS: u64 = 24 * 60 * 60;
+/// Ended leases stay visible this long before being pruned
+const ENDED_RETENTION_DAYS: i64 = 7;
+pub const TOKEN_PREFIX: &str = "kkl_";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaseTarget {
+    /// Temporary env file holding the key
+    EnvFile,
+    /// One-time token redeemable at `POST /api/leases/redeem`
+    Token,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaseStatus {
+    Active,
+    /// Token used; it can't be redeemed again
+    Redeemed,
+    Expired,
+    Revoked,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyLease {
+    pub id: String,
+    pub key_id: String,
+    pub target: LeaseTarget,
+    pub status: LeaseStatus,
+    pub file_path: Option<String>,
+    /// SHA-256 of the token; the token itself is only returned once
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_hash: Option<String>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub expires_at: DateTime<Utc>,
+    #[serde(default, with = "crate::timestamps::rfc3339_option")]
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaseGrant {
+    pub lease: KeyLease,
+    /// Set for token leases
+    pub token: Option<String>,
+}
+
+/// Key handed out for a redeemed token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedeemedLease {
+    pub lease_id: String,
+    pub key_id: String,
+    pub env_var: String,
+    pub value: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub expires_at: DateTime<Utc>,
+}
+
+fn lease_dir() -> PathBuf {
+    std::env::temp_dir().join("keykeeper-leases")
+}
+
+fn token_hash(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// Expiry encoded in a lease file name, `{lease_id}.{expires_unix}.env`
+fn file_expiry(path: &Path) -> Option<DateTime<Utc>> {
+    let stem = path.file_name()?.to_str()?.strip_suffix(".env")?;
+    let (_, expires) = stem.rsplit_once('.')?;
+    DateTime::from_timestamp(expires.parse().ok()?, 0)
+}
+
+fn write_lease_file(
+    lease_id: &str,
+    expires_at: DateTime<Utc>,
+    content: &str,
+) -> Result<PathBuf, String> {
+    let dir = lease_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create lease directory: {}", e))?;
+    let path = dir.join(format!("{}.{}.env", lease_id, expires_at.timestamp()));
+
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        let _ = fs::set_permissions(&dir, fs::Permissions::from_mode(0o700));
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)
+            .map_err(|e| format!("Failed to create lease file: {}", e))?;
+        file.write_all(content.as_bytes())
+            .map_err(|e| format!("Failed to write lease file: {}", e))?;
+    }
+    #[cfg(not(unix))]
+    fs::write(&path, content).map_err(|e| format!("Failed to write lease file: {}", e))?;
+
+    Ok(path)
+}
+
+fn remove_lease_file(lease: &KeyLease) {
+    if let Some(path) = &lease.file_path {
+        if let Err(e) = fs::remove_file(path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!("Failed to remove lease file {}: {}", path, e);
+            }
+        }
+    }
+}
+
+/// Revoke every active lease and delete its file, returning how many were
+pub fn revoke_all(vault: &mut ApiKeyVault, now: DateTime<Utc>) -> usize {
+    let mut revoked = 0;
+    for lease in vault
+        .leases
+        .iter_mut()
+        .filter(|l| l.status == LeaseStatus::Active)
+    {
+        remove_lease_file(lease);
+        lease.status = LeaseStatus::Revoked;
+        lease.ended_at = Some(now);
+        revoked += 1;
+    }
+    revoked
+}
+
+/// Delete lease files past their expiry, returning how many were removed
+pub fn remove_expired_files(now: DateTime<Utc>) -> usize {
+    let Ok(entries) = fs::read_dir(lease_dir()) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| file_expiry(path).is_some_and(|expires| expires <= now))
+        .filter(|path| fs::remove_file(path).is_ok())
+        .count()
+}
+
+/// Mark active leases past their expiry as expired and prune old ended ones.
+/// Returns the ids of the leases that just expired.
+pub fn expire_leases(vault: &mut ApiKeyVault, now: DateTime<Utc>) -> Vec<String> {
+    let mut expired = Vec::new();
+    for lease in vault.leases.iter_mut() {
+        if lease.status == LeaseStatus::Active && lease.expires_at <= now {
+            remove_lease_file(lease);
+            lease.status = LeaseStatus::Expired;
+            lease.ended_at = Some(now);
+            expired.push(lease.id.clone());
+        }
+    }
+    let cutoff = now - ChronoDuration::days(ENDED_RETENTION_DAYS);
+    vault
+        .leases
+        .retain(|l| l.status == LeaseStatus::Active || l.ended_at.is_none_or(|t| t > cutoff));
+    expired
+}
+
+/// Consume an active token lease and return its key
+pub fn redeem_token(
+    vault: &mut ApiKeyVault,
+    token: &str,
+    now: DateTime<Utc>,
+) -> Result<RedeemedLease, KeyKeeperError> {
+    let hash = token_hash(token);
+    let lease = vault
+        .leases
+        .iter_mut()
+        .find(|l| l.token_hash.as_deref() == Some(hash.as_str()))
+        .ok_or_else(|| KeyKeeperError::not_found("Lease"))?;
+    if lease.status != LeaseStatus::Active || lease.expires_at <= now {
+        return Err(KeyKeeperError::Validation(
+            "Lease token has expired or was already used".to_string(),
+        ));
+    }
+    lease.status = LeaseStatus::Redeemed;
+    lease.ended_at = Some(now);
+    let (lease_id, key_id, expires_at) = (lease.id.clone(), lease.key_id.clone(), lease.expires_at);
+
+    let key = vault
+        .keys
+        .get(&key_id)
+        .ok_or_else(|| KeyKeeperError::not_found("API key"))?;
+    Ok(RedeemedLease {
+        lease_id,
+        key_id,
+        env_var: env_var_name_for_key(key),
+        value: key.key.clone(),
+        expires_at,
+    })
+}
+
+#[tauri::command]
+pub async fn lease_key(
+    key_id: String,
+    ttl_seconds: u64,
+    target: LeaseTarget,
+    state: State<'_, AppState>,
+) -> Result<LeaseGrant, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    if !(MIN_TTL_SECS..=MAX_TTL_SECS).contains(&ttl_seconds) {
+        return Err(KeyKeeperError::Validation(format!(
+            "Lease TTL must be between {} and {} seconds",
+            MIN_TTL_SECS, MAX_TTL_SECS
+        )));
+    }
+
+    let now = Utc::now();
+    let lease_id = Uuid::new_v4().to_string();
+    let expires_at = now + ChronoDuration::seconds(ttl_seconds as i64);
+
+    let mut vault_guard = state.vault.lock().await;
+    let key = vault_guard
+        .keys
+        .get(&key_id)
+        .ok_or_else(|| KeyKeeperError::not_found("API key"))?;
+
+    let (file_path, token) = match target {
+        LeaseTarget::EnvFile => {
+            let content = format!("{}={}\n", env_var_name_for_key(key), key.key);
+            let path =
+                write_lease_file(&lease_id, expires_at, &content).map_err(KeyKeeperError::Io)?;
+            (Some(path.to_string_lossy().to_string()), None)
+        }
+        LeaseTarget::Token => {
+            let random: String = rand::thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(40)
+                .map(char::from)
+                .collect();
+            (None, Some(format!("{}{}", TOKEN_PREFIX, random)))
+        }
+    };
+
+    let lease = KeyLease {
+        id: lease_id.clone(),
+        key_id,
+        target,
+        status: LeaseStatus::Active,
+        file_path,
+        token_hash: token.as_deref().map(token_hash),
+        created_at: now,
+        expires_at,
+        ended_at: None,
+    };
+    vault_guard.leases.push(lease.clone());
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "lease_key",
+        "lease",
+        Some(&lease_id),
+        true,
+        Some(&format!("key {} for {}s", lease.key_id, ttl_seconds)),
+    )
+    .await;
+
+    Ok(LeaseGrant { lease, token })
+}
+
+#[tauri::command]
+pub async fn list_leases(state: State<'_, AppState>) -> Result<Vec<KeyLease>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let vault_guard = state.vault.lock().await;
+    let mut leases = vault_guard.leases.clone();
+    leases.sort_by_key(|l| std::cmp::Reverse(l.created_at));
+    Ok(leases)
+}
+
+#[tauri::command]
+pub async fn revoke_lease(
+    lease_id: String,
+    state: State<'_, AppState>,
+) -> Result<KeyLease, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let lease = vault_guard
+        .leases
+        .iter_mut()
+        .find(|l| l.id == lease_id)
+        .ok_or_else(|| KeyKeeperError::not_found("Lease"))?;
+    if lease.status != LeaseStatus::Active {
+        return Err(KeyKeeperError::Validation(
+            "Lease is no longer active".to_string(),
+        ));
+    }
+    remove_lease_file(lease);
+    lease.status = LeaseStatus::Revoked;
+    lease.ended_at = Some(Utc::now());
+    let lease = lease.clone();
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(&state, "revoke_lease", "lease", Some(&lease_id), true, None).await;
+
+    Ok(lease)
+}
+
+/// Expire leases and delete their files
+pub async fn run_lease_sweeper(app: AppHandle) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(SWEEP_INTERVAL_SECS)).await;
+
+        let now = Utc::now();
+        let removed = remove_expired_files(now);
+        if removed > 0 {
+            info!("Removed {} expired lease file(s)", removed);
+        }
+
+        let state: State<'_, AppState> = app.state();
+        if !*state.is_unlocked.lock().await {
+            continue;
+        }
+
+        let (expired, changed) = {
+            let mut vault_guard = state.vault.lock().await;
+            let before = vault_guard.leases.len();
+            let expired = expire_leases(&mut vault_guard, now);
+            let changed = !expired.is_empty() || vault_guard.leases.len() != before;
+            (expired, changed)
+        };
+
+        if changed {
+            if let Err(e) = save_vault(&state).await {
+                warn!("Failed to save expired leases: {}", e);
+            }
+        }
+        for lease_id in &expired {
+            log_audit_event(&state, "lease_expired", "lease", Some(lease_id), true, None).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lease(id: &str, token: &str, expires_at: DateTime<Utc>) -> KeyLease {
+        KeyLease {
+            id: id.to_string(),
+            key_id: "missing".to_string(),
+            target: LeaseTarget::Token,
+            status: LeaseStatus::Active,
+            file_path: None,
+            token_hash: Some(token_hash(token)),
+            created_at: expires_at - ChronoDuration::minutes(5),
+            expires_at,
+            ended_at: None,
+        }
+    }
+
+    #[test]
+    fn expires_leases_and_rejects_their_tokens() {
+        let now = Utc::now();
+        let mut vault = ApiKeyVault::default();
+        vault
+            .leases
+            .push(lease("old", "kkl_old", now - ChronoDuration::seconds(1)));
+        vault
+            .leases
+            .push(lease("live", "kkl_live", now + ChronoDuration::minutes(5)));
+
+        assert_eq!(expire_leases(&mut vault, now), vec!["old".to_string()]);
+        assert!(redeem_token(&mut vault, "kkl_old", now).is_err());
+        // The key is gone, but the token is still consumed
+        assert!(redeem_token(&mut vault, "kkl_live", now).is_err());
+        assert_eq!(vault.leases[1].status, LeaseStatus::Redeemed);
+
+        let path = Path::new("/tmp/keykeeper-leases/abc.1700000000.env");
+        assert_eq!(
+            file_expiry(path).map(|t| t.timestamp()),
+            Some(1_700_000_000)
+        );
+    }
+}
@@ -0,0 +1,474 @@
+//! Compatibility shim for clients of the old TCP server.
+//!
+//! The first extension releases, and some internal scripts, talk to a bare
+//! TCP listener that reads one HTTP-shaped request per connection, tolerates
+//! bare `\n` line endings and a missing `Content-Length`, and answers without
+//! a length before closing. When `[legacy_tcp]` is enabled this listener runs
+//! on its own port next to the HTTP server and forwards each request to it,
+//! so both speak the same API. Routes that only existed in the old server
+//! answer 410 with the replacement.
+//!
+//! Every response carries a `Deprecation` header, the first request per route
+//! is logged as a warning, and per-route usage is kept for
+//! `get_legacy_tcp_usage` so remaining clients can be tracked down.
+
+use crate::errors::KeyKeeperError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::SocketAddr;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, info, warn};
+
+const MAX_REQUEST_BYTES: usize = 1024 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long to wait for a body sent after the headers without a length
+const BODY_GRACE: Duration = Duration::from_millis(250);
+const MAX_CLIENTS_PER_ROUTE: usize = 20;
+/// Request headers passed on to the HTTP server. `Origin` goes along so
+/// browser pages are refused as they are on the HTTP port, and the client
+/// headers so registered clients can identify themselves.
+const FORWARDED_HEADERS: &[&str] = &[
+    "accept",
+    "authorization",
+    "content-type",
+    "origin",
+    "user-agent",
+    "x-api-key",
+    crate::approvals::CLIENT_HEADER,
+    crate::clients::CLIENT_ID_HEADER,
+    crate::clients::CLIENT_SECRET_HEADER,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegacyRequest {
+    pub method: String,
+    /// Path with query string
+    pub target: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl LegacyRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn path(&self) -> &str {
+        self.target.split('?').next().unwrap_or("")
+    }
+
+    /// Headers to pass on to the HTTP server
+    fn forwarded_headers(&self) -> impl Iterator<Item = &(String, String)> {
+        self.headers
+            .iter()
+            .filter(|(name, _)| FORWARDED_HEADERS.contains(&name.to_ascii_lowercase().as_str()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Translation {
+    Forward,
+    /// Route dropped from the HTTP API, with what replaces it
+    Gone(&'static str),
+}
+
+fn translate(method: &str, path: &str) -> Translation {
+    match (method, path) {
+        ("POST", "/api/login") => {
+            Translation::Gone("Unlock the vault in the app; the HTTP API doesn't take credentials")
+        }
+        ("POST", "/api/env/parse") => {
+            Translation::Gone("Use GET /api/projects/env-files or the analyze_env_import command")
+        }
+        ("POST", "/api/env/associate") | ("GET", "/api/env/associations") => {
+            Translation::Gone("Env file associations are managed in the app")
+        }
+        ("POST", "/api/projects/activate") => {
+            Translation::Gone("Use POST /api/workspace/auto-sync")
+        }
+        (_, "/api/vscode/workspaces") | ("GET", "/api/vscode/status") => Translation::Gone(
+            "Open editor workspaces are detected by the app; no request is needed",
+        ),
+        _ => Translation::Forward,
+    }
+}
+
+/// Route as recorded in usage, with ids replaced
+fn route_key(method: &str, path: &str) -> String {
+    let path = match path.strip_prefix("/api/keys/") {
+        Some(rest) if rest.ends_with("/usage") => "/api/keys/{id}/usage",
+        Some(rest) if !rest.is_empty() && rest != "search" => "/api/keys/{id}",
+        _ => path,
+    };
+    format!("{} {}", method, path)
+}
+
+fn header_end(buf: &[u8]) -> Option<(usize, usize)> {
+    let crlf = buf
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .map(|i| (i, 4));
+    let lf = buf.windows(2).position(|w| w == b"\n\n").map(|i| (i, 2));
+    match (crlf, lf) {
+        (Some(a), Some(b)) => Some(if a.0 <= b.0 { a } else { b }),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Parse the request line and headers. Returns the request with whatever
+/// body bytes were already read, and the declared body length.
+fn parse_head(buf: &[u8]) -> Result<(LegacyRequest, Option<usize>), String> {
+    let (head_len, sep_len) = header_end(buf).unwrap_or((buf.len(), 0));
+    let head = String::from_utf8_lossy(&buf[..head_len]);
+    let mut lines = head.lines().map(|l| l.trim_end_matches('\r'));
+
+    let request_line = lines.next().unwrap_or("");
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(format!("Malformed request line '{}'", request_line));
+    };
+    if !target.starts_with('/') {
+        return Err(format!("Unsupported request target '{}'", target));
+    }
+
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+    let request = LegacyRequest {
+        method: method.to_uppercase(),
+        target: target.to_string(),
+        headers,
+        body: buf[(head_len + sep_len).min(buf.len())..].to_vec(),
+    };
+    let length = request
+        .header("content-length")
+        .and_then(|v| v.parse().ok());
+    Ok((request, length))
+}
+
+async fn read_request(stream: &mut TcpStream) -> Result<LegacyRequest, String> {
+    let mut buf = Vec::with_capacity(2048);
+    let mut chunk = [0u8; 4096];
+
+    while header_end(&buf).is_none() {
+        let n = tokio::time::timeout(READ_TIMEOUT, stream.read(&mut chunk))
+            .await
+            .map_err(|_| "Timed out reading request".to_string())?
+            .map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if buf.len() > MAX_REQUEST_BYTES {
+            return Err("Request too large".to_string());
+        }
+    }
+
+    let (mut request, length) = parse_head(&buf)?;
+    match length {
+        Some(length) if length > MAX_REQUEST_BYTES => return Err("Request too large".to_string()),
+        Some(length) => {
+            while request.body.len() < length {
+                let n = tokio::time::timeout(READ_TIMEOUT, stream.read(&mut chunk))
+                    .await
+                    .map_err(|_| "Timed out reading request body".to_string())?
+                    .map_err(|e| e.to_string())?;
+                if n == 0 {
+                    break;
+                }
+                request.body.extend_from_slice(&chunk[..n]);
+            }
+            request.body.truncate(length);
+        }
+        // Old clients wrote the body right after the headers without a length
+        None if request.body.is_empty() && matches!(request.method.as_str(), "POST" | "PUT") => {
+            if let Ok(Ok(n)) = tokio::time::timeout(BODY_GRACE, stream.read(&mut chunk)).await {
+                request.body.extend_from_slice(&chunk[..n]);
+            }
+        }
+        None => {}
+    }
+    Ok(request)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegacyRouteUsage {
+    /// Method and path, ids replaced by `{id}`
+    pub route: String,
+    pub count: u64,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub first_seen: DateTime<Utc>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub last_seen: DateTime<Utc>,
+    /// Client addresses, without ports
+    pub clients: BTreeSet<String>,
+}
+
+static USAGE: LazyLock<Mutex<BTreeMap<String, LegacyRouteUsage>>> =
+    LazyLock::new(|| Mutex::new(BTreeMap::new()));
+
+/// Count a request; true the first time the route is seen
+fn record_usage(route: &str, client: &SocketAddr) -> bool {
+    let now = Utc::now();
+    let mut usage = USAGE.lock().unwrap();
+    let first = !usage.contains_key(route);
+    let entry = usage
+        .entry(route.to_string())
+        .or_insert_with(|| LegacyRouteUsage {
+            route: route.to_string(),
+            count: 0,
+            first_seen: now,
+            last_seen: now,
+            clients: BTreeSet::new(),
+        });
+    entry.count += 1;
+    entry.last_seen = now;
+    if entry.clients.len() < MAX_CLIENTS_PER_ROUTE {
+        entry.clients.insert(client.ip().to_string());
+    }
+    first
+}
+
+fn reason(status: u16) -> &'static str {
+    reqwest::StatusCode::from_u16(status)
+        .ok()
+        .and_then(|s| s.canonical_reason())
+        .unwrap_or("Unknown")
+}
+
+async fn write_response(
+    stream: &mut TcpStream,
+    status: u16,
+    content_type: &str,
+    body: &[u8],
+    successor: &str,
+) -> std::io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {} {}\r\n\
+         Content-Type: {}\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\
+         Deprecation: true\r\n\
+         Link: <{}>; rel=\"successor-version\"\r\n\
+         X-Content-Type-Options: nosniff\r\n\r\n",
+        status,
+        reason(status),
+        content_type,
+        body.len(),
+        successor
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body).await?;
+    stream.shutdown().await
+}
+
+async fn forward(
+    client: &reqwest::Client,
+    upstream: SocketAddr,
+    request: LegacyRequest,
+) -> Result<(u16, String, Vec<u8>), String> {
+    let method = reqwest::Method::from_bytes(request.method.as_bytes())
+        .map_err(|_| format!("Unsupported method {}", request.method))?;
+    let mut builder = client.request(method, format!("http://{}{}", upstream, request.target));
+    for (name, value) in request.forwarded_headers() {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    if !request.body.is_empty() {
+        builder = builder.body(request.body);
+    }
+
+    let response = builder.send().await.map_err(|e| e.to_string())?;
+    let mut status = response.status().as_u16();
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/json")
+        .to_string();
+    let body = response.bytes().await.map_err(|e| e.to_string())?.to_vec();
+
+    // The old server answered 403 while locked, and clients check for it
+    if status == 401 && String::from_utf8_lossy(&body).contains("VAULT_LOCKED") {
+        status = 403;
+    }
+    Ok((status, content_type, body))
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    peer: SocketAddr,
+    upstream: SocketAddr,
+    client: reqwest::Client,
+) {
+    let request = match read_request(&mut stream).await {
+        Ok(request) => request,
+        Err(e) => {
+            debug!("Rejected legacy TCP request from {}: {}", peer, e);
+            let body = KeyKeeperError::Validation(e).to_json().to_string();
+            let _ =
+                write_response(&mut stream, 400, "application/json", body.as_bytes(), "/").await;
+            return;
+        }
+    };
+
+    let route = route_key(&request.method, request.path());
+    let successor = format!("http://{}{}", upstream, request.target);
+    if record_usage(&route, &peer) {
+        warn!(
+            "Deprecated legacy TCP protocol used for {} by {}; point the client at {}",
+            route, peer, successor
+        );
+    } else {
+        debug!("Legacy TCP request {} from {}", route, peer);
+    }
+
+    let (status, content_type, body) = match translate(&request.method, request.path()) {
+        Translation::Gone(replacement) => {
+            let body = serde_json::json!({
+                "error": "This legacy endpoint has been removed",
+                "code": "GONE",
+                "replacement": replacement,
+            });
+            (
+                410,
+                "application/json".to_string(),
+                body.to_string().into_bytes(),
+            )
+        }
+        Translation::Forward => match forward(&client, upstream, request).await {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("Failed to forward legacy TCP request {}: {}", route, e);
+                let body = KeyKeeperError::Internal(format!("HTTP server unavailable: {}", e))
+                    .to_json()
+                    .to_string();
+                (502, "application/json".to_string(), body.into_bytes())
+            }
+        },
+    };
+
+    if let Err(e) = write_response(&mut stream, status, &content_type, &body, &successor).await {
+        debug!("Failed to answer legacy TCP client {}: {}", peer, e);
+    }
+}
+
+/// Bind the legacy listener when enabled in the config. `server_addr` is
+/// where the HTTP server listens; requests are forwarded there over loopback
+/// when it's bound to all interfaces.
+pub async fn bind(
+    config: &crate::config::AppConfig,
+    server_addr: SocketAddr,
+) -> Result<Option<(TcpListener, SocketAddr)>, KeyKeeperError> {
+    if !config.legacy_tcp.enabled {
+        return Ok(None);
+    }
+    let addr = SocketAddr::new(server_addr.ip(), config.legacy_tcp.port);
+    let listener = TcpListener::bind(addr).await.map_err(|e| {
+        KeyKeeperError::Io(format!(
+            "Failed to bind legacy TCP listener on {}: {}",
+            addr, e
+        ))
+    })?;
+    let mut upstream = server_addr;
+    if upstream.ip().is_unspecified() {
+        upstream.set_ip(std::net::Ipv4Addr::LOCALHOST.into());
+    }
+    info!(
+        "Legacy TCP compatibility listener on {} (deprecated), forwarding to {}",
+        addr, upstream
+    );
+    Ok(Some((listener, upstream)))
+}
+
+/// Accept legacy connections until the task is aborted
+pub async fn serve(listener: TcpListener, upstream: SocketAddr) {
+    let client = reqwest::Client::new();
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer)) => {
+                tokio::spawn(handle_connection(stream, peer, upstream, client.clone()));
+            }
+            Err(e) => {
+                warn!("Legacy TCP accept error: {}", e);
+                break;
+            }
+        }
+    }
+}
+
+/// Legacy routes used since launch, to find clients still to migrate
+#[tauri::command]
+pub fn get_legacy_tcp_usage() -> Vec<LegacyRouteUsage> {
+    USAGE.lock().unwrap().values().cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bare_newline_requests_and_translates_routes() {
+        let raw =
+            b"post /api/keys/abc-123/usage HTTP/1.1\nHost: localhost\nX-API-Key: k\n\n{\"n\":1}";
+        let (request, length) = parse_head(raw).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.target, "/api/keys/abc-123/usage");
+        assert_eq!(request.header("x-api-key"), Some("k"));
+        assert_eq!(request.body, b"{\"n\":1}");
+        assert_eq!(length, None);
+        assert_eq!(
+            route_key(&request.method, request.path()),
+            "POST /api/keys/{id}/usage"
+        );
+
+        let raw = b"GET /api/keys/search?q=stripe HTTP/1.1\r\nContent-Length: 0\r\n\r\n";
+        let (request, length) = parse_head(raw).unwrap();
+        assert_eq!(request.path(), "/api/keys/search");
+        assert_eq!(length, Some(0));
+        assert_eq!(translate("GET", request.path()), Translation::Forward);
+        assert!(matches!(
+            translate("POST", "/api/vscode/workspaces"),
+            Translation::Gone(_)
+        ));
+        assert!(parse_head(b"garbage\r\n\r\n").is_err());
+    }
+
+    fn forwarded(raw: &[u8]) -> Vec<String> {
+        let (request, _) = parse_head(raw).unwrap();
+        request
+            .forwarded_headers()
+            .map(|(name, _)| name.to_ascii_lowercase())
+            .collect()
+    }
+
+    #[test]
+    fn browser_origins_reach_the_cors_check() {
+        let raw = b"POST /api/keys HTTP/1.1\r\nHost: localhost\r\n\
+            Origin: https://evil.example\r\nCookie: a=b\r\n\r\n";
+        assert_eq!(forwarded(raw), ["origin"]);
+    }
+
+    #[test]
+    fn client_identity_headers_are_forwarded() {
+        let raw = b"GET /api/keys HTTP/1.1\r\nUser-Agent: keykeeper-vscode/0.3\r\n\
+            X-KeyKeeper-Client: vscode\r\nX-KeyKeeper-Client-Id: c1\r\n\
+            X-KeyKeeper-Client-Secret: kkc_secret\r\n\r\n";
+        assert_eq!(
+            forwarded(raw),
+            [
+                "user-agent",
+                "x-keykeeper-client",
+                "x-keykeeper-client-id",
+                "x-keykeeper-client-secret"
+            ]
+        );
+    }
+}
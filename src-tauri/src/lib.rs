@@ -0,0 +1,10005 @@
+use aes_gcm::{
+    aead::{Aead, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use base64::{engine::general_purpose, Engine as _};
+use bcrypt::{hash, verify, DEFAULT_COST};
+use chrono::{DateTime, Utc};
+use tracing::{error, info, warn};
+use pbkdf2::pbkdf2_hmac;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use http_body_util::{BodyExt, Full};
+use hyper::body::Incoming;
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use sha2::Sha256;
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::fs;
+use std::io::Error;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+// use tauri::menu::{MenuBuilder, MenuItemBuilder, PredefinedMenuItem};
+use tauri::tray::TrayIcon;
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_updater::UpdaterExt;
+use tokio::net::TcpListener;
+use tokio::sync::{Mutex, RwLock};
+use uuid::Uuid;
+use zeroize::{Zeroize, Zeroizing};
+use errors::KeyKeeperError;
+extern crate keyring;
+extern crate whoami;
+
+// ML Engine modules
+mod ml_engine_simple;
+mod ml_engine;
+mod ml_commands;
+mod llm_wrapper;
+mod llm_proxy;
+mod api_generator;
+mod api_generator_commands;
+// Enhanced template system modules
+mod enhanced_types;
+mod framework_detector;
+mod template_inheritance;
+// AI-enhanced modules
+mod ai_enhanced_detector;
+mod contextual_suggestions;
+mod ai_commands;
+mod template_engine;
+mod enhanced_api_commands;
+// Framework generators modules
+mod framework_generators;
+mod framework_generator_commands;
+// Documentation modules
+mod docs_manager;
+mod docs_commands;
+mod docs_generator;
+mod docs_generator_commands;
+// Documentation Library + LLM Chat System modules
+mod documentation_library;
+mod chat_engine;
+mod chat_commands;
+mod docs_library_commands;
+mod docs_bundles;
+mod snippets;
+// Validation and setup modules
+mod validation_engine;
+mod setup_generator;
+mod validation_setup_commands;
+// Secret manager integrations
+mod secret_manager_export;
+mod secret_manager_commands;
+mod github_sync;
+// Container integrations
+mod docker_env;
+// Outgoing notifications
+mod webhooks;
+mod notifications;
+// Memory hardening for decrypted material
+mod secure_memory;
+// Structured command errors
+mod errors;
+// Tracing setup and the in-app log viewer
+mod logging;
+// Crash reports and diagnostic bundles
+mod diagnostics;
+// Health, readiness and liveness reporting
+mod health;
+// keykeeper.toml and environment overrides
+mod config;
+// Typed timestamps in the vault model
+mod timestamps;
+mod migrations;
+// Vault file location and synced folders
+mod vault_location;
+mod vault_integrity;
+mod recovery_kit;
+// Session expiry and unlock sessions
+mod sessions;
+mod devices;
+// Time-boxed key leases
+mod leases;
+// Dashboard
+mod vault_stats;
+mod vault_index;
+mod pagination;
+// Project tooling
+mod project_analysis;
+mod monorepo;
+mod workspace_watcher;
+// Format-preserving .env reading and writing
+mod env_file;
+// Snapshots of .env files before sync writes, for undo
+mod env_history;
+// keykeeper://key/<id> references and `keykeeper exec`
+mod env_refs;
+// Old TCP protocol, forwarded to the HTTP server
+mod legacy_tcp;
+// Single-instance launches and deep links
+mod instance;
+// User approval of secrets requested by external clients
+mod approvals;
+// Audit events forwarded to files, syslog and webhooks
+mod audit_sinks;
+// Key naming policies and bulk renames
+mod naming;
+// Deterministic demo data for onboarding and UI tests
+mod demo_vault;
+// Backends holding the vault document and its metadata
+mod vault_storage;
+// Named, supervised background tasks
+mod background_tasks;
+// Orderly exit: drain the server, lock the vault, stop tasks
+mod shutdown;
+// Confirmation window for destructive actions requested over HTTP
+mod confirmations;
+// Rotation schedules and reminders
+mod rotation;
+// Secrets committed to project git history
+mod git_history;
+// Issues needing attention across the vault and its projects
+mod security_report;
+// Confirms production keys synced into non-production files
+mod sync_policy;
+// Periodic attestation of who uses which keys
+mod access_review;
+// Syncs every registered project's .env files in parallel
+mod project_sync;
+// SSH keys and TLS certificates stored alongside API keys
+mod credentials;
+// OAuth2 client credentials and their cached access tokens
+mod oauth;
+// Per-environment values of a single key
+mod env_values;
+// Pinned keys and the ordering of key listings
+mod pinning;
+// All-or-nothing updates and deletes of many keys
+mod bulk;
+// Archived keys and the auto-archive job
+mod archive;
+// Recent activity feed with actors and change summaries
+mod activity;
+// Retention policy for audit logs, activity, sessions and other history
+mod retention;
+// Batched read requests for the HTTP API
+mod batch;
+// ETags for list endpoints and the vault generation counter
+mod etag;
+// Origin allowlist and preflights for the HTTP server
+mod cors;
+// Client registration and identification for the HTTP API
+mod clients;
+// Request and spend quotas per key
+mod quotas;
+// Estimated and billed costs of paid keys
+mod costs;
+// Organization defaults, settings templates and per-project overrides
+mod project_settings;
+// Client, team and area groups of projects
+mod project_groups;
+// Secure Enclave / TPM wrapped device keys
+mod hardware_unlock;
+// OS keyring entries namespaced per vault and profile
+mod keyring_store;
+// Lock gate for the HTTP server, inactivity lock and lock events
+mod lock_state;
+// Documentation tag index, listing, renames and suggestions
+mod doc_tags;
+// Markdown export and import of documentation
+mod docs_markdown;
+// Files attached to documentation
+mod doc_attachments;
+// Cross-references from docs to keys, env vars, providers and projects
+mod doc_refs;
+// Revision history of documentation
+mod doc_revisions;
+// LLM-drafted documentation for keys
+mod key_docs;
+// Editable prompts for the LLM features
+mod prompt_templates;
+// Reviewed .env imports with per-variable proposals
+mod env_import;
+// Dropped .env files ingested in one command, with progress events
+mod env_ingest;
+// .gitignore and .keykeeperignore rules for project scans
+mod project_ignore;
+// OS keyring with an encrypted-file fallback
+mod keystore;
+// Correlation ids for HTTP requests
+mod request_id;
+// Optional log of HTTP requests
+mod request_log;
+// Coalesced vault saves
+mod save_queue;
+// Masked views of keys for listings
+mod masking;
+// Keys of individually encrypted values, for the unlocked session
+mod decryption_keys;
+// Changing the master password, with the vault encrypted again
+mod master_password;
+// Strength estimate and policy for new passwords
+mod password_strength;
+// Preconditions of commands: unlocked vault, account, ...
+mod guards;
+// Internal event bus for state changes
+mod events;
+// Recovery tokens and security alerts sent by email
+mod email;
+// Hashed, device-bound recovery tokens
+mod recovery_tokens;
+// Tray icon and menu showing lock, sync and pending issues
+mod tray_status;
+// Quick palette and approvals windows, and the geometry of each
+mod window_manager;
+// Environment of keys told by their format (test vs live)
+mod key_environment;
+// Read-only, secrets-free snapshots of the vault for reporting tools
+mod vault_snapshot;
+// Key metadata history and the vault's state at a past time
+mod key_history;
+// Incident mode: lock down and track rotating the keys in scope
+mod incident;
+// Keys read through from cloud secret managers instead of stored
+mod secret_backends;
+// Hardcoded secrets in Terraform, CloudFormation and Serverless files
+mod iac_scan;
+// Fixtures and wrappers for the benchmarks in benches/
+#[doc(hidden)]
+pub mod bench_support;
+#[cfg(test)]
+mod vault_tests;
+use ml_engine_simple::MLEngine;
+
+// ===============================
+//  API HTTP ↔️ Tauri Command Map
+// ===============================
+// | HTTP Endpoint                | Metodo | Comando Tauri                |
+// |-----------------------------|--------|------------------------------|
+// | /api/keys                   | GET    | get_api_keys                 |
+//   (?group=<id> limita la lista ai progetti di un gruppo, vedi project_groups.rs)
+// | /api/keys/search?q=...      | GET    | search_api_keys_by_query     |
+// | /api/keys/pinned            | GET    | get_pinned_keys              |
+// | /api/projects               | GET    | get_projects                 |
+// | /api/activity/recent        | GET    | get_recent_activity          |
+// | /api/batch                  | POST   | (più GET in una richiesta)   |
+//   (solo keys, keys/pinned, projects, activity/recent e stats, vedi batch.rs)
+// | /api/vault/generation       | GET    | get_vault_generation         |
+// | /api/vault/lock-state       | GET    | get_lock_state               |
+//   (?locked=&wait= attende un cambio; aperto anche a vault bloccato, le
+//    altre rotte tranne /health, /ready e /live danno 401, vedi lock_state.rs)
+// | /api/clients/register       | POST   | (approvato con approve_client)|
+// | /api/clients/status         | GET    | list_clients                 |
+//   (i client registrati inviano X-KeyKeeper-Client-Id/-Secret, vedi clients.rs)
+//   (/api/keys, /api/keys/pinned e /api/projects rispondono 304 se
+//    If-None-Match corrisponde all'ETag, vedi etag.rs)
+// | /api/keys/{id}/usage        | POST   | record_key_usage             |
+//   (body opzionale {requests, cost} contato sulla quota, vedi quotas.rs)
+// | /api/costs                  | POST   | record_cost_entry (webhook)  |
+// | /api/snippets?provider=...  | GET    | list_snippets                |
+// | /api/snippets/{id}/render   | POST   | render_snippet               |
+// | /api/projects/sync          | POST   | sync_project                 |
+// | /api/leases/redeem          | POST   | lease_key (token lease)      |
+// | /api/env/resolve            | POST   | resolve_env_references       |
+//   (chiede l'approvazione dell'utente, vedi approvals.rs)
+// | /api/keys/{id}/public       | GET    | (chiavi SSH e certificati)   |
+// | /api/keys/{id}/decrypt      | POST   | (approvazione, no comando)   |
+// | /api/oauth/{id}/token       | POST   | get_access_token             |
+// | /api/keys/sync-to-env       | POST   | sync_key_to_env_file         |
+//   (sovrascritture, eliminazioni e push su GitHub chiedono conferma,
+//    vedi confirmations.rs)
+// | /api/github/sync            | POST   | sync_keys_to_github          |
+// | /api/docs/{id}              | DELETE | (conferma, no comando)       |
+//   (docs e providers: stesso sblocco e client registrati delle chiavi; le
+//    scritture seguono server.cors.key_origins, vedi cors.rs)
+// | /api/workspace/auto-sync    | POST   | auto_sync_workspace_env_files|
+//   ("preview": true nel body → preview_* , restituisce il diff)
+// | /health                     | GET    | get_health_report            |
+// | /ready                      | GET    | (503 se il vault è bloccato) |
+// | /live                       | GET    | (interno, no comando)        |
+// | /api/keys                   | POST   | add_api_key                  |
+// | /api/keys/{id}              | DELETE | delete_api_key               |
+// | /api/keys                   | PUT    | update_api_key               |
+// | /api/keys                   | GET    | get_api_keys                 |
+// | /api/keys                   | PATCH  | update_api_key               |
+// | /api/keys                   | OPTIONS| (CORS preflight, no comando) |
+//   (preflight e Access-Control-* per tutte le rotte in cors.rs; le rotte
+//    delle chiavi rifiutano le origini fuori da server.cors.key_origins)
+// | /api/keys                   | HEAD   | (non usato)                  |
+//
+// NB: Alcuni endpoint sono solo per uso interno o legacy.
+// ===============================
+
+// Helper function for consistent UTC timestamp formatting
+fn get_utc_timestamp() -> String {
+    Utc::now().to_rfc3339()
+}
+
+fn get_utc_timestamp_millis() -> i64 {
+    Utc::now().timestamp_millis()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuditLog {
+    pub id: String,
+    #[serde(with = "timestamps::rfc3339")]
+    pub timestamp: DateTime<Utc>,
+    pub action: String,
+    pub resource_type: String,
+    pub resource_id: Option<String>,
+    pub user_id: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub success: bool,
+    pub error_message: Option<String>,
+    /// Registered HTTP client that performed the action
+    #[serde(default)]
+    pub client_id: Option<String>,
+    /// HTTP request the action was taken for, as in its `X-Request-Id`
+    #[serde(default)]
+    pub request_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiKey {
+    pub id: String,
+    pub name: String,
+    pub service: String,
+    pub key: String,
+    pub description: Option<String>,
+    pub environment: String, // dev, staging, production
+    /// Request and spend limits, see quotas.rs
+    #[serde(default)]
+    pub quota: Option<quotas::KeyQuota>,
+    /// Estimated monthly cost and billing account, see costs.rs
+    #[serde(default)]
+    pub cost: Option<costs::KeyCost>,
+    #[serde(default, with = "timestamps::rfc3339_option")]
+    pub expires_at: Option<DateTime<Utc>>,
+    pub scopes: Vec<String>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub updated_at: DateTime<Utc>,
+    pub tags: Vec<String>,
+    pub is_active: bool,
+    // Informazioni per chiavi importate da .env
+    pub source_type: Option<String>, // "manual" | "env_file"
+    pub env_file_path: Option<String>,
+    pub project_path: Option<String>,
+    pub env_file_name: Option<String>,
+    // Copie della chiave pubblicate su secret manager esterni
+    #[serde(default)]
+    pub remote_secrets: Vec<secret_manager_export::RemoteSecretRef>,
+    /// Package of a monorepo project the key is scoped to, relative to `project_path`
+    #[serde(default)]
+    pub package_path: Option<String>,
+    /// Rotate every N days; reminders are filed when due (see rotation.rs)
+    #[serde(default)]
+    pub rotation_interval_days: Option<u32>,
+    #[serde(default, with = "timestamps::rfc3339_option")]
+    pub last_rotated_at: Option<DateTime<Utc>>,
+    /// API key, or SSH key/certificate whose private key is `key`
+    #[serde(default)]
+    pub kind: credentials::CredentialKind,
+    /// Public half, passphrase and fingerprint of SSH keys and certificates
+    #[serde(default)]
+    pub credential: Option<credentials::CredentialParts>,
+    /// Client id and token endpoint of OAuth clients
+    #[serde(default)]
+    pub oauth: Option<oauth::OAuthClientConfig>,
+    /// Values in environments other than `environment`, by normalized
+    /// environment (see env_values.rs)
+    #[serde(default)]
+    pub values: std::collections::BTreeMap<String, String>,
+    /// Listed first and in the tray menu (see pinning.rs)
+    #[serde(default)]
+    pub is_pinned: bool,
+    /// Set while the key is archived (see archive.rs)
+    #[serde(default, with = "timestamps::rfc3339_option")]
+    pub archived_at: Option<DateTime<Utc>>,
+    /// Last time the key's value was handed out
+    #[serde(default, with = "timestamps::rfc3339_option")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// Read from a secret manager instead of stored (see secret_backends.rs)
+    #[serde(default)]
+    pub backend: Option<secret_backends::BackendRef>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UserAccount {
+    pub id: String,
+    pub email: String,
+    pub username: String,
+    pub password_hash: String, // bcrypt hash for account password
+    #[serde(with = "timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub updated_at: DateTime<Utc>,
+    pub verified: bool,
+    pub recovery_codes: Vec<String>, // Encrypted recovery codes
+    pub two_factor_enabled: bool,
+    pub backup_email: Option<String>,
+    pub biometric_enabled: bool,
+    pub passkey_credentials: Vec<PasskeyCredential>,
+    pub session_timeout: u64, // in minutes
+    pub login_attempts: u32,
+    #[serde(default, with = "timestamps::rfc3339_option")]
+    pub locked_until: Option<DateTime<Utc>>,
+    pub preferences: UserPreferences,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PasskeyCredential {
+    pub id: String,
+    pub name: String, // User-friendly name like "MacBook Touch ID"
+    pub credential_id: Vec<u8>,
+    pub public_key: Vec<u8>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default, with = "timestamps::rfc3339_option")]
+    pub last_used: Option<DateTime<Utc>>,
+    pub device_info: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UserPreferences {
+    pub theme: String, // "light", "dark", "system"
+    pub language: String,
+    pub auto_lock_timeout: u64,       // in minutes
+    pub clipboard_clear_timeout: u64, // in seconds
+    pub show_notifications: bool,
+    pub audit_logging: bool,
+    pub biometric_unlock: bool,
+    pub auto_backup: bool,
+    pub encryption_level: String, // "standard", "enhanced", "maximum"
+    #[serde(default)]
+    pub notifications: notifications::NotificationSettings,
+    #[serde(default)]
+    pub logging: logging::LogSettings,
+    /// Whether syncs write references or secrets into .env files
+    #[serde(default)]
+    pub env_sync_mode: env_refs::EnvSyncMode,
+    /// Order of key listings and of the pinned keys
+    #[serde(default)]
+    pub key_ordering: pinning::KeyOrdering,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BiometricSession {
+    pub user_id: String,
+    pub session_id: String,
+    #[serde(with = "timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub expires_at: DateTime<Utc>,
+    pub device_id: String,
+    pub is_active: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WebAuthnChallenge {
+    pub challenge: String,
+    pub user_id: String,
+    #[serde(with = "timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub expires_at: DateTime<Utc>,
+    pub challenge_type: String, // "registration" or "authentication"
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PasswordRecovery {
+    /// SHA-256 of the token, see recovery_tokens.rs
+    #[serde(alias = "token")]
+    pub token_hash: String,
+    /// Device and client the token was requested from, hashed
+    #[serde(default)]
+    pub fingerprint: String,
+    pub email: String,
+    #[serde(with = "timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub expires_at: DateTime<Utc>,
+    pub used: bool,
+    pub attempts: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Project {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub path: String,
+    #[serde(with = "timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub updated_at: DateTime<Utc>,
+    /// Resolved settings, see project_settings.rs
+    pub settings: ProjectSettings,
+    #[serde(default)]
+    pub settings_template_id: Option<String>,
+    /// Settings that differ from the organization defaults and template
+    #[serde(default)]
+    pub settings_overrides: project_settings::SettingsOverrides,
+    #[serde(default)]
+    pub group_id: Option<String>,
+    /// Integration boilerplate written by `scaffold_integration`
+    #[serde(default)]
+    pub scaffolds: Vec<api_generator::ScaffoldRecord>,
+    /// Language, framework and package manager detected from the manifests
+    #[serde(flatten)]
+    pub profile: project_analysis::ProjectProfile,
+    /// Member packages when the project is a monorepo
+    #[serde(default)]
+    pub packages: Vec<monorepo::WorkspacePackage>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectSettings {
+    pub default_environment: String,
+    pub auto_sync: bool,
+    pub vscode_integration: bool,
+    pub cursor_integration: bool,
+    pub notifications: bool,
+    /// `.env` file (relative to the project or package) -> environment
+    /// whose keys it receives. Empty keeps the default files.
+    #[serde(default)]
+    pub env_mapping: std::collections::BTreeMap<String, String>,
+    /// Variable name template with `<SERVICE>`, `<ENV>` and `<NAME>`;
+    /// `None` uses `<SERVICE>_API_KEY`
+    #[serde(default)]
+    pub var_name_template: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Documentation {
+    pub id: String,
+    pub title: String,
+    pub content: String,
+    pub doc_type: String, // "api", "guide", "reference", "notes", "snippet"
+    pub project_id: Option<String>, // Associazione con progetti
+    pub provider_id: Option<String>, // Associazione con provider API
+    pub url: Option<String>, // URL di riferimento originale
+    pub tags: Vec<String>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub updated_at: DateTime<Utc>,
+    pub language: String, // "en", "it", "es", etc.
+    pub is_favorite: bool,
+    pub search_keywords: Vec<String>, // Keywords per migliorare la ricerca
+    #[serde(default)]
+    pub sections: Vec<DocSection>,
+    /// Part of a downloaded offline bundle
+    #[serde(default)]
+    pub is_offline: bool,
+    #[serde(default)]
+    pub attachments: Vec<doc_attachments::DocAttachment>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DocSection {
+    pub id: String,
+    pub title: String,
+    pub content: String,
+    pub level: u8, // 1-6 per heading levels
+    pub anchor: Option<String>,
+    pub parent_section_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecentActivity {
+    pub id: String,
+    pub activity_type: String, // "key_used", "key_created", "key_updated"
+    pub key_id: String,
+    pub key_name: String,
+    #[serde(with = "timestamps::rfc3339")]
+    pub timestamp: DateTime<Utc>,
+    /// What changed, for updates
+    pub details: Option<String>,
+    /// Missing on entries recorded before actors were tracked
+    #[serde(default)]
+    pub actor: Option<activity::Actor>,
+    /// Set for actions that came in over HTTP
+    #[serde(default)]
+    pub source: Option<activity::ActivitySource>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectEnvAssociation {
+    pub id: String,
+    pub project_path: String,
+    pub env_file_path: String,
+    pub env_file_name: String,
+    #[serde(with = "timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub last_accessed: DateTime<Utc>,
+    pub is_active: bool,
+    pub vscode_status: Option<String>, // "open", "closed", "unknown"
+    #[serde(default, with = "timestamps::rfc3339_option")]
+    pub last_vscode_check: Option<DateTime<Utc>>,
+    /// Monorepo package the env file belongs to, relative to `project_path`
+    #[serde(default)]
+    pub package_path: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VSCodeWorkspace {
+    pub path: String,
+    pub name: String,
+    pub is_open: bool,
+    #[serde(with = "timestamps::rfc3339")]
+    pub last_updated: DateTime<Utc>,
+    #[serde(default)]
+    pub source: workspace_watcher::WorkspaceSource,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PersistentSession {
+    pub session_id: String,
+    pub user_id: String,
+    #[serde(with = "timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub expires_at: DateTime<Utc>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub last_accessed: DateTime<Utc>,
+    pub device_info: String,
+    #[serde(default)]
+    pub device_id: Option<String>,
+    pub is_remember_me: bool,
+    pub is_active: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VSCodeToken {
+    pub token: String,
+    pub user_id: String,
+    #[serde(default)]
+    pub device_id: Option<String>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub expires_at: DateTime<Utc>,
+    pub is_valid: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EnvVariable {
+    pub name: String,
+    pub value: String,
+    pub is_secret: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DroppedEnvFile {
+    pub path: String,
+    pub project_path: String,
+    pub package_path: Option<String>,
+    pub file_name: String,
+    pub keys: Vec<EnvVariable>,
+}
+
+// Metadata structure for encrypted vaults
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VaultMetadata {
+    pub master_password_hash: Option<String>,
+    pub salt: Option<String>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    /// App version that wrote the file
+    pub version: String,
+    #[serde(default = "migrations::legacy_schema_version")]
+    pub schema_version: u32,
+    pub api_keys_metadata: Vec<ApiKeyMetadata>,
+    /// SHA-256 of the encrypted vault file written alongside this metadata
+    #[serde(default)]
+    pub vault_checksum: Option<String>,
+    /// Recovery kit slots, readable while the vault is locked
+    #[serde(default)]
+    pub recovery: Option<recovery_kit::RecoveryEnvelope>,
+    /// Device key wrapped by the secure element, readable while locked
+    #[serde(default)]
+    pub hardware_unlock: Option<hardware_unlock::HardwareEnvelope>,
+    /// Namespace of the vault's keyring entries
+    #[serde(default)]
+    pub vault_id: Option<String>,
+    /// Webhooks notified of failed unlocks while locked
+    #[serde(default)]
+    pub webhooks: Vec<webhooks::WebhookEndpoint>,
+    /// Failed unlocks counted while locked, see `webhooks::events_for_audit`
+    #[serde(default)]
+    pub failed_unlocks: Vec<DateTime<Utc>>,
+    /// Notification preferences, for the notifications raised while locked
+    #[serde(default)]
+    pub notifications: Option<notifications::NotificationPreferences>,
+}
+
+// Non-sensitive metadata for API keys (stored in clear text)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiKeyMetadata {
+    pub id: String,
+    pub name: String,
+    pub key: String,
+    pub service: String,
+    pub description: Option<String>,
+    pub environment: String,
+    #[serde(default)]
+    pub quota: Option<quotas::KeyQuota>,
+    #[serde(default)]
+    pub cost: Option<costs::KeyCost>,
+    #[serde(default, with = "timestamps::rfc3339_option")]
+    pub expires_at: Option<DateTime<Utc>>,
+    pub scopes: Vec<String>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub updated_at: DateTime<Utc>,
+    pub tags: Vec<String>,
+    pub is_active: bool,
+    pub source_type: Option<String>,
+    pub env_file_path: Option<String>,
+    pub project_path: Option<String>,
+    pub env_file_name: Option<String>,
+    // No `remote_secrets`: their value hashes would let anyone with this
+    // file confirm a guessed secret, so they stay in the encrypted vault
+    #[serde(default)]
+    pub package_path: Option<String>,
+    #[serde(default)]
+    pub rotation_interval_days: Option<u32>,
+    #[serde(default, with = "timestamps::rfc3339_option")]
+    pub last_rotated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub kind: credentials::CredentialKind,
+    #[serde(default)]
+    pub credential: Option<credentials::CredentialParts>,
+    #[serde(default)]
+    pub oauth: Option<oauth::OAuthClientConfig>,
+    #[serde(default)]
+    pub values: std::collections::BTreeMap<String, String>,
+    #[serde(default)]
+    pub is_pinned: bool,
+    #[serde(default, with = "timestamps::rfc3339_option")]
+    pub archived_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "timestamps::rfc3339_option")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub backend: Option<secret_backends::BackendRef>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyVault {
+    #[serde(default = "migrations::legacy_schema_version")]
+    pub schema_version: u32,
+    /// Namespace of the vault's keyring entries
+    #[serde(default = "keyring_store::new_vault_id")]
+    pub vault_id: String,
+    pub keys: HashMap<String, ApiKey>,
+    pub master_password_hash: Option<String>,
+    #[serde(default, with = "timestamps::rfc3339_option")]
+    pub last_backup: Option<DateTime<Utc>>,
+    pub encryption_key: Option<String>, // Base64 encoded encryption key
+    pub salt: Option<String>,           // Base64 encoded salt for key derivation
+    pub audit_logs: Vec<AuditLog>,
+    pub user_account: Option<UserAccount>,
+    pub recovery_tokens: Vec<PasswordRecovery>,
+    pub projects: HashMap<String, Project>,
+    pub recent_activity: Vec<RecentActivity>,
+    pub env_associations: Vec<ProjectEnvAssociation>,
+    pub biometric_sessions: Vec<BiometricSession>,
+    pub webauthn_challenges: Vec<WebAuthnChallenge>,
+    pub vscode_workspaces: Vec<VSCodeWorkspace>,
+    pub persistent_sessions: Vec<PersistentSession>,
+    pub vscode_tokens: Vec<VSCodeToken>,
+    #[serde(default)]
+    pub github_secret_mappings: Vec<github_sync::GitHubSecretMapping>,
+    #[serde(default)]
+    pub webhooks: Vec<webhooks::WebhookEndpoint>,
+    #[serde(default)]
+    pub webhook_deliveries: Vec<webhooks::WebhookDelivery>,
+    #[serde(default)]
+    pub devices: Vec<devices::DeviceRecord>,
+    #[serde(default)]
+    pub snippets: HashMap<String, snippets::Snippet>,
+    #[serde(default)]
+    pub recovery_kit: Option<recovery_kit::RecoveryKit>,
+    #[serde(default)]
+    pub hardware_unlock: Option<hardware_unlock::HardwareUnlockKey>,
+    /// Set after a recovery unlock until a new master password is chosen
+    #[serde(default)]
+    pub password_reset_required: bool,
+    #[serde(default)]
+    pub leases: Vec<leases::KeyLease>,
+    #[serde(default)]
+    pub approval_rules: Vec<approvals::ApprovalRule>,
+    #[serde(default)]
+    pub naming_policies: Vec<naming::NamingPolicy>,
+    /// Only actions that don't use the default (always ask)
+    #[serde(default)]
+    pub confirmation_policies:
+        std::collections::BTreeMap<confirmations::RiskyAction, confirmations::ConfirmationPolicy>,
+    #[serde(default)]
+    pub rotation_reminders: Vec<rotation::RotationReminder>,
+    /// Latest scan of each project's git history
+    #[serde(default)]
+    pub git_history_scans: Vec<git_history::GitHistoryScan>,
+    /// Ids of keys merged into another key by environment
+    #[serde(default)]
+    pub key_aliases: std::collections::BTreeMap<String, env_values::KeyAlias>,
+    #[serde(default)]
+    pub archive_policy: archive::ArchivePolicy,
+    #[serde(default)]
+    pub retention_policy: retention::RetentionPolicy,
+    #[serde(default)]
+    pub retention_report: Option<retention::RetentionReport>,
+    #[serde(default)]
+    pub registered_clients: Vec<clients::RegisteredClient>,
+    /// Consumption counted against each key's quota
+    #[serde(default)]
+    pub quota_usage: std::collections::BTreeMap<String, quotas::QuotaUsage>,
+    /// Actual costs per key and month
+    #[serde(default)]
+    pub cost_entries: Vec<costs::CostEntry>,
+    /// Settings every project inherits
+    #[serde(default = "project_settings::builtin_defaults")]
+    pub organization_settings: ProjectSettings,
+    #[serde(default)]
+    pub settings_templates: Vec<project_settings::SettingsTemplate>,
+    #[serde(default)]
+    pub project_groups: Vec<project_groups::ProjectGroup>,
+    /// Base64 key for encrypted doc attachments, see doc_attachments.rs
+    #[serde(default)]
+    pub doc_attachment_key: Option<String>,
+    /// Sending recovery tokens and security alerts by email
+    #[serde(default)]
+    pub email_delivery: Option<email::EmailSettings>,
+    #[serde(default)]
+    pub email_deliveries: Vec<email::EmailDelivery>,
+    #[serde(default)]
+    pub access_reviews: Vec<access_review::AccessReview>,
+    /// Revisions of the keys' metadata, oldest first
+    #[serde(default)]
+    pub key_history: Vec<key_history::KeyRevision>,
+    #[serde(default)]
+    pub incidents: Vec<incident::Incident>,
+    /// Failed unlocks in the anomaly window. Kept in the metadata rather
+    /// than here, so a successful unlock starts the count over.
+    #[serde(skip)]
+    pub failed_unlocks: Vec<DateTime<Utc>>,
+    /// Notification preferences of the metadata-only view, which has no
+    /// user account to read them from
+    #[serde(skip)]
+    pub locked_notifications: Option<notifications::NotificationPreferences>,
+}
+
+impl Default for ApiKeyVault {
+    fn default() -> Self {
+        Self {
+            schema_version: migrations::CURRENT_SCHEMA_VERSION,
+            vault_id: keyring_store::new_vault_id(),
+            keys: HashMap::new(),
+            master_password_hash: None,
+            last_backup: None,
+            encryption_key: None,
+            salt: None,
+            audit_logs: Vec::new(),
+            user_account: None,
+            recovery_tokens: Vec::new(),
+            projects: HashMap::new(),
+            recent_activity: Vec::new(),
+            env_associations: Vec::new(),
+            biometric_sessions: Vec::new(),
+            webauthn_challenges: Vec::new(),
+            vscode_workspaces: Vec::new(),
+            persistent_sessions: Vec::new(),
+            vscode_tokens: Vec::new(),
+            github_secret_mappings: Vec::new(),
+            webhooks: Vec::new(),
+            webhook_deliveries: Vec::new(),
+            devices: Vec::new(),
+            snippets: HashMap::new(),
+            recovery_kit: None,
+            hardware_unlock: None,
+            password_reset_required: false,
+            leases: Vec::new(),
+            approval_rules: Vec::new(),
+            naming_policies: Vec::new(),
+            confirmation_policies: std::collections::BTreeMap::new(),
+            rotation_reminders: Vec::new(),
+            git_history_scans: Vec::new(),
+            key_aliases: std::collections::BTreeMap::new(),
+            archive_policy: archive::ArchivePolicy::default(),
+            retention_policy: retention::RetentionPolicy::default(),
+            retention_report: None,
+            registered_clients: Vec::new(),
+            quota_usage: std::collections::BTreeMap::new(),
+            cost_entries: Vec::new(),
+            organization_settings: project_settings::builtin_defaults(),
+            settings_templates: Vec::new(),
+            project_groups: Vec::new(),
+            doc_attachment_key: None,
+            email_delivery: None,
+            email_deliveries: Vec::new(),
+            access_reviews: Vec::new(),
+            key_history: Vec::new(),
+            incidents: Vec::new(),
+            failed_unlocks: Vec::new(),
+            locked_notifications: None,
+        }
+    }
+}
+
+// ================================
+// Documentation Storage System
+// ================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DocsStore {
+    pub docs: HashMap<String, Documentation>,
+    pub version: String,
+    #[serde(with = "timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub updated_at: DateTime<Utc>,
+    pub index: HashMap<String, Vec<String>>, // keyword -> doc_ids mapping for search
+    #[serde(default)]
+    pub offline_bundles: HashMap<String, docs_bundles::OfflineBundle>,
+    /// Rebuilt from the docs on load, see doc_tags.rs
+    #[serde(skip)]
+    pub tag_index: doc_tags::TagIndex,
+    /// Attachment blobs dropped since the last save
+    #[serde(skip)]
+    pub released_blobs: Vec<String>,
+    /// Doc ID -> what the doc mentions, see doc_refs.rs
+    #[serde(default)]
+    pub references: HashMap<String, Vec<doc_refs::DocReference>>,
+    /// Docs to scan for references at the next save
+    #[serde(skip)]
+    pub stale_references: HashSet<String>,
+    /// Doc ID -> replaced versions, oldest first, see doc_revisions.rs
+    #[serde(default)]
+    pub revisions: HashMap<String, Vec<doc_revisions::DocRevision>>,
+}
+
+impl Default for DocsStore {
+    fn default() -> Self {
+        Self {
+            docs: HashMap::new(),
+            version: "1.0.0".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            index: HashMap::new(),
+            offline_bundles: HashMap::new(),
+            tag_index: doc_tags::TagIndex::new(),
+            released_blobs: Vec::new(),
+            references: HashMap::new(),
+            stale_references: HashSet::new(),
+            revisions: HashMap::new(),
+        }
+    }
+}
+
+impl DocsStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn rebuild_tag_index(&mut self) {
+        self.tag_index = doc_tags::build_index(self.docs.values());
+    }
+
+    pub fn add_doc(&mut self, doc: Documentation) {
+        // Update index
+        self.update_index(&doc);
+        self.stale_references.insert(doc.id.clone());
+        
+        // Add to store
+        self.docs.insert(doc.id.clone(), doc);
+        self.updated_at = Utc::now();
+    }
+
+    pub fn update_doc(&mut self, doc: Documentation) {
+        // Remove old index entries
+        if let Some(old_doc) = self.docs.get(&doc.id).cloned() {
+            self.remove_from_index(&old_doc);
+            self.released_blobs.extend(
+                old_doc.attachments.iter()
+                    .filter(|a| !doc.attachments.iter().any(|kept| kept.blob == a.blob))
+                    .map(|a| a.blob.clone()),
+            );
+            doc_revisions::record(&mut self.revisions, &old_doc, &doc);
+        }
+        
+        // Update index with new doc
+        self.update_index(&doc);
+        self.stale_references.insert(doc.id.clone());
+        
+        // Update doc
+        self.docs.insert(doc.id.clone(), doc);
+        self.updated_at = Utc::now();
+    }
+
+    pub fn remove_doc(&mut self, doc_id: &str) -> Option<Documentation> {
+        if let Some(doc) = self.docs.remove(doc_id) {
+            self.remove_from_index(&doc);
+            self.released_blobs.extend(doc.attachments.iter().map(|a| a.blob.clone()));
+            self.references.remove(doc_id);
+            self.stale_references.remove(doc_id);
+            self.revisions.remove(doc_id);
+            self.updated_at = Utc::now();
+            Some(doc)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_doc(&self, doc_id: &str) -> Option<&Documentation> {
+        self.docs.get(doc_id)
+    }
+
+    pub fn get_all_docs(&self) -> Vec<&Documentation> {
+        self.docs.values().collect()
+    }
+
+    pub fn search_docs(&self, query: &str) -> Vec<&Documentation> {
+        let query_lower = query.to_lowercase();
+        let mut results = Vec::new();
+        
+        // Search in index first
+        for (keyword, doc_ids) in &self.index {
+            if keyword.contains(&query_lower) {
+                for doc_id in doc_ids {
+                    if let Some(doc) = self.docs.get(doc_id) {
+                        if !results.iter().any(|d: &&Documentation| d.id == doc.id) {
+                            results.push(doc);
+                        }
+                    }
+                }
+            }
+        }
+        
+        // Also search directly in content if not found in index
+        if results.is_empty() {
+            for doc in self.docs.values() {
+                if doc.title.to_lowercase().contains(&query_lower) ||
+                   doc.content.to_lowercase().contains(&query_lower) {
+                    results.push(doc);
+                }
+            }
+        }
+        
+        results
+    }
+
+    fn update_index(&mut self, doc: &Documentation) {
+        doc_tags::index_doc(&mut self.tag_index, doc);
+
+        // Index title words
+        for word in doc.title.to_lowercase().split_whitespace() {
+            if word.len() > 2 {
+                self.index.entry(word.to_string())
+                    .or_insert_with(Vec::new)
+                    .push(doc.id.clone());
+            }
+        }
+        
+        // Index search keywords
+        for keyword in &doc.search_keywords {
+            self.index.entry(keyword.to_lowercase())
+                .or_insert_with(Vec::new)
+                .push(doc.id.clone());
+        }
+        
+        // Index tags
+        for tag in &doc.tags {
+            self.index.entry(tag.to_lowercase())
+                .or_insert_with(Vec::new)
+                .push(doc.id.clone());
+        }
+    }
+
+    fn remove_from_index(&mut self, doc: &Documentation) {
+        doc_tags::unindex_doc(&mut self.tag_index, &doc.id);
+
+        // Remove from all index entries
+        for doc_ids in self.index.values_mut() {
+            doc_ids.retain(|id| id != &doc.id);
+        }
+        
+        // Remove empty index entries
+        self.index.retain(|_, doc_ids| !doc_ids.is_empty());
+    }
+}
+
+pub struct AppState {
+    pub vault: Arc<Mutex<ApiKeyVault>>,
+    pub is_unlocked: Arc<Mutex<bool>>,
+    pub vault_path: vault_location::VaultLocation,
+    /// Where the vault is read from and saved to; the `VaultLocation` above
+    /// outside of tests
+    pub storage: Arc<dyn vault_storage::VaultStorage>,
+    /// Saves of `vault` to `storage`, coalesced
+    pub saves: Arc<save_queue::SaveQueue>,
+    pub ml_engine: Arc<tokio::sync::Mutex<Option<MLEngine>>>,
+    pub llm_proxy: std::sync::Arc<llm_proxy::LLMProxyState>,
+
+    pub tasks: Arc<background_tasks::TaskManager>,
+    pub vscode_server_running: Arc<AtomicBool>,
+    
+    pub tray_handle: Arc<Mutex<Option<TrayIcon>>>,
+    pub api_generator: Arc<Mutex<api_generator_commands::ApiGeneratorState>>,
+    /// Enhanced API generator with template engine
+    pub enhanced_api: Arc<enhanced_api_commands::EnhancedApiState>,
+    /// Framework generators for framework-specific code generation
+    pub framework_generators: Arc<framework_generator_commands::FrameworkGeneratorState>,
+    // Documentation Manager
+    pub docs_manager: Arc<Mutex<docs_commands::DocsManagerState>>,
+    // Documentation Generator
+    pub docs_generator: Arc<docs_generator_commands::DocsGeneratorState>,
+    // Separate Documentation Store
+    pub docs_store: Arc<Mutex<DocsStore>>,
+    pub docs_store_path: PathBuf,
+    /// AI-enhanced features state
+    pub ai_state: Arc<ai_commands::AIState>,
+    /// Documentation Library + LLM Chat System
+    pub chat_engine_state: Arc<chat_commands::ChatEngineState>,
+    pub docs_library_state: Arc<docs_library_commands::DocumentationLibraryState>,
+    /// Desktop notifications filtered by user preferences
+    pub notifier: Arc<notifications::NotificationDispatcher>,
+    /// In-memory key orderings used by paginated listings
+    pub vault_index: Arc<Mutex<vault_index::VaultIndex>>,
+    /// Session backing the current unlock; the vault locks when it ends
+    pub unlock_session: Arc<Mutex<Option<String>>>,
+    /// Subsystem status behind `/health` and `/ready`
+    pub health: Arc<health::HealthState>,
+    /// Settings from keykeeper.toml and the environment
+    pub config: Arc<config::ConfigState>,
+    /// Pending approvals of secrets requested over HTTP
+    pub approvals: Arc<approvals::ApprovalBroker>,
+    pub confirmations: Arc<confirmations::ConfirmationBroker>,
+    /// Access tokens of OAuth clients, in memory only
+    pub oauth_tokens: Arc<oauth::TokenCache>,
+    /// Keys derived at unlock for values encrypted on their own
+    pub decryption_keys: Arc<decryption_keys::DecryptionKeys>,
+    /// Values of remote keys, in memory only
+    pub remote_values: Arc<secret_backends::ValueCache>,
+}
+
+fn decrypt_api_key(encrypted: &str, password: &str) -> Result<String, String> {
+    let combined = general_purpose::STANDARD
+        .decode(encrypted)
+        .map_err(|e| format!("Base64 decode failed: {}", e))?;
+
+    if combined.len() < 28 {
+        // 16 (salt) + 12 (nonce)
+        return Err("Invalid encrypted data".to_string());
+    }
+
+    let (salt, rest) = combined.split_at(16);
+    let (nonce_bytes, ciphertext) = rest.split_at(12);
+
+    let key_bytes = derive_key_from_password(password, salt);
+    let key = Key::<Aes256Gcm>::from_slice(&*key_bytes);
+    let cipher = Aes256Gcm::new(key);
+    let nonce = Nonce::from_slice(nonce_bytes);
+
+    let plaintext = cipher
+        .decrypt(nonce, ciphertext)
+        .map_err(|e| format!("Decryption failed: {}", e))?;
+
+    String::from_utf8(plaintext).map_err(|e| {
+        let message = format!("UTF-8 decode failed: {}", e);
+        e.into_bytes().zeroize();
+        message
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PasswordCheck {
+    Valid,
+    /// Matches a master password stored in plain text by old builds; the
+    /// caller should replace it with a bcrypt hash
+    LegacyPlaintext,
+    Invalid,
+}
+
+/// Verify the master password against the stored bcrypt hash, accepting the
+/// plaintext passwords written by old builds
+fn check_master_password(stored_hash: &str, password: &str) -> PasswordCheck {
+    match verify(password, stored_hash) {
+        Ok(true) => PasswordCheck::Valid,
+        Ok(false) => PasswordCheck::Invalid,
+        Err(e) => {
+            error!("Bcrypt verification failed: {}", e);
+            error!("This usually means the stored hash is not a valid bcrypt hash");
+            // Fallback: check if password was stored in plain text (legacy support)
+            if stored_hash == password {
+                warn!("Password was stored in plain text! Will migrate to bcrypt hash...");
+                PasswordCheck::LegacyPlaintext
+            } else {
+                PasswordCheck::Invalid
+            }
+        }
+    }
+}
+
+#[tauri::command]
+async fn unlock_vault(password: String, state: State<'_, AppState>) -> Result<bool, KeyKeeperError> {
+    let password = Zeroizing::new(password);
+    let vault_guard = state.vault.lock().await;
+
+    if let Some(stored_hash) = &vault_guard.master_password_hash {
+        info!("Attempting to verify password against stored hash");
+        info!(
+            "Stored hash length: {}, starts with: {}",
+            stored_hash.len(),
+            &stored_hash[..std::cmp::min(10, stored_hash.len())]
+        );
+        info!("Password length: {}", password.len());
+
+        let check = check_master_password(stored_hash, &password);
+        info!("Final password verification result: {:?}", check);
+        if check != PasswordCheck::Invalid {
+            // Check if we need to migrate plain text password to bcrypt hash
+            let needs_migration = check == PasswordCheck::LegacyPlaintext;
+
+            // Check if vault is encrypted (has placeholder encryption key)
+            let needs_decryption = vault_guard
+                .encryption_key
+                .as_ref()
+                .map_or(false, |key| key == "[ENCRYPTED]");
+
+            if needs_decryption {
+                // Drop the guard to avoid deadlock before calling decrypt function
+                drop(vault_guard);
+
+                // Decrypt and reload the actual vault data
+                match decrypt_vault_from(&*state.storage, &password) {
+                    Ok(decrypted_vault) => {
+                        // Replace the vault in memory with the decrypted version
+                        let mut vault_guard = state.vault.lock().await;
+                        *vault_guard = decrypted_vault;
+                        state.decryption_keys.derive_for(&vault_guard, &password);
+                        drop(vault_guard);
+                        state.vault_index.lock().await.invalidate();
+
+                        *state.is_unlocked.lock().await = true;
+                        if let Err(e) = sessions::begin_unlock_session(&state).await {
+                            warn!("Failed to start unlock session: {}", e);
+                        }
+
+                        // Migrate password hash if needed
+                        if needs_migration {
+                            match hash(&password, DEFAULT_COST) {
+                                Ok(new_hash) => {
+                                    let mut vault_guard = state.vault.lock().await;
+                                    vault_guard.master_password_hash = Some(new_hash);
+                                    drop(vault_guard);
+                                    if let Err(e) = save_vault(&state).await {
+                                        warn!("Failed to save migrated password hash: {}", e);
+                                    }
+                                    log_audit_event(
+                                        &state,
+                                        "unlock_vault",
+                                        "vault",
+                                        None,
+                                        true,
+                                        Some("Vault decrypted, unlocked and password migrated"),
+                                    )
+                                    .await;
+                                }
+                                Err(e) => {
+                                    warn!("Failed to create bcrypt hash during migration: {}", e);
+                                    log_audit_event(
+                                        &state,
+                                        "unlock_vault",
+                                        "vault",
+                                        None,
+                                        true,
+                                        Some("Vault decrypted and unlocked (migration failed)"),
+                                    )
+                                    .await;
+                                }
+                            }
+                        } else {
+                            log_audit_event(
+                                &state,
+                                "unlock_vault",
+                                "vault",
+                                None,
+                                true,
+                                Some("Vault decrypted and unlocked"),
+                            )
+                            .await;
+                        }
+                        Ok(true)
+                    }
+                    Err(e) => {
+                        log_audit_event(
+                            &state,
+                            "unlock_vault",
+                            "vault",
+                            None,
+                            false,
+                            Some(&format!("Failed to decrypt vault: {}", e)),
+                        )
+                        .await;
+                        Err(KeyKeeperError::Crypto(format!("Failed to decrypt vault: {}", e)))
+                    }
+                }
+            } else {
+                // Vault is not encrypted, just unlock it
+                state.decryption_keys.derive_for(&vault_guard, &password);
+                drop(vault_guard);
+                *state.is_unlocked.lock().await = true;
+                if let Err(e) = sessions::begin_unlock_session(&state).await {
+                    warn!("Failed to start unlock session: {}", e);
+                }
+
+                // Migrate password hash if needed
+                if needs_migration {
+                    match hash(&password, DEFAULT_COST) {
+                        Ok(new_hash) => {
+                            let mut vault_guard = state.vault.lock().await;
+                            vault_guard.master_password_hash = Some(new_hash);
+                            drop(vault_guard);
+                            if let Err(e) = save_vault(&state).await {
+                                warn!("Failed to save migrated password hash: {}", e);
+                            }
+                            log_audit_event(
+                                &state,
+                                "unlock_vault",
+                                "vault",
+                                None,
+                                true,
+                                Some("Vault unlocked and password migrated"),
+                            )
+                            .await;
+                        }
+                        Err(e) => {
+                            warn!("Failed to create bcrypt hash during migration: {}", e);
+                            log_audit_event(
+                                &state,
+                                "unlock_vault",
+                                "vault",
+                                None,
+                                true,
+                                Some("Vault unlocked (migration failed)"),
+                            )
+                            .await;
+                        }
+                    }
+                } else {
+                    log_audit_event(&state, "unlock_vault", "vault", None, true, None).await;
+                }
+                Ok(true)
+            }
+        } else {
+            drop(vault_guard);
+            log_audit_event(
+                &state,
+                "unlock_vault",
+                "vault",
+                None,
+                false,
+                Some("Invalid password"),
+            )
+            .await;
+            Ok(false)
+        }
+    } else {
+        // Master password not set, check if this is a legacy vault that needs migration
+        info!("No master password hash found, checking for legacy vault");
+
+        // For legacy vaults without bcrypt hash, try to decrypt directly with password
+        if vault_guard.encryption_key.is_some()
+            && vault_guard.encryption_key.as_ref().unwrap() != "[ENCRYPTED]"
+        {
+            // This might be a legacy encrypted vault, try to decrypt
+            drop(vault_guard);
+            match decrypt_vault_from(&*state.storage, &password) {
+                Ok(decrypted_vault) => {
+                    // Migration: set proper bcrypt hash for future unlocks
+                    let password_hash = hash(&password, DEFAULT_COST).map_err(|e| KeyKeeperError::Crypto(e.to_string()))?;
+
+                    let mut vault_guard = state.vault.lock().await;
+                    *vault_guard = decrypted_vault;
+                    vault_guard.master_password_hash = Some(password_hash);
+                    state.decryption_keys.derive_for(&vault_guard, &password);
+                    drop(vault_guard);
+
+                    // Save the migrated vault
+                    save_vault(&state).await?;
+
+                    *state.is_unlocked.lock().await = true;
+                    if let Err(e) = sessions::begin_unlock_session(&state).await {
+                        warn!("Failed to start unlock session: {}", e);
+                    }
+                    log_audit_event(
+                        &state,
+                        "unlock_vault",
+                        "vault",
+                        None,
+                        true,
+                        Some("Legacy vault migrated and unlocked"),
+                    )
+                    .await;
+                    Ok(true)
+                }
+                Err(_) => {
+                    // Not a legacy vault or wrong password
+                    log_audit_event(
+                        &state,
+                        "unlock_vault",
+                        "vault",
+                        None,
+                        false,
+                        Some("Master password not set"),
+                    )
+                    .await;
+                    Err(KeyKeeperError::Validation("Master password not set. Please set it first.".to_string()))
+                }
+            }
+        } else {
+            // No encryption key at all, master password not set
+            log_audit_event(
+                &state,
+                "unlock_vault",
+                "vault",
+                None,
+                false,
+                Some("Master password not set"),
+            )
+            .await;
+            Err(KeyKeeperError::Validation("Master password not set. Please set it first.".to_string()))
+        }
+    }
+}
+
+// Keyring functions for master password, namespaced per vault and profile
+const LEGACY_MASTER_PASSWORD: (&str, &str) =
+    (keyring_store::SERVICE, keyring_store::MASTER_PASSWORD);
+
+#[tauri::command]
+async fn save_master_password_to_keyring(
+    app: AppHandle,
+    password: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    let password = Zeroizing::new(password);
+    let store = keyring_store::KeyringStore::open(&app, &state).await?;
+
+    match store.set(keyring_store::MASTER_PASSWORD, &password) {
+        Ok(_) => {
+            info!("Master password saved to keyring successfully");
+            Ok(())
+        }
+        Err(e) => {
+            error!("Failed to save master password to keyring: {}", e);
+            Err(KeyKeeperError::Io(format!("Failed to save master password to keyring: {}", e)))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_master_password_from_keyring(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Option<String>, KeyKeeperError> {
+    let store = keyring_store::KeyringStore::open(&app, &state).await?;
+
+    match store.get(keyring_store::MASTER_PASSWORD, Some(LEGACY_MASTER_PASSWORD)) {
+        Ok(Some(password)) => {
+            info!("Master password retrieved from keyring successfully");
+            Ok(Some(password.to_string()))
+        }
+        Ok(None) => {
+            info!("No master password found in keyring");
+            Ok(None)
+        }
+        Err(e) => {
+            error!("Failed to retrieve master password from keyring: {}", e);
+            Err(KeyKeeperError::Internal(format!(
+                "Failed to retrieve master password from keyring: {}",
+                e
+            )))
+        }
+    }
+}
+
+#[tauri::command]
+async fn delete_master_password_from_keyring(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    let store = keyring_store::KeyringStore::open(&app, &state).await?;
+
+    match store.delete(keyring_store::MASTER_PASSWORD, Some(LEGACY_MASTER_PASSWORD)) {
+        Ok(_) => {
+            info!("Master password deleted from keyring successfully");
+            Ok(())
+        }
+        Err(e) => {
+            error!("Failed to delete master password from keyring: {}", e);
+            Err(KeyKeeperError::Internal(format!(
+                "Failed to delete master password from keyring: {}",
+                e
+            )))
+        }
+    }
+}
+
+#[tauri::command]
+async fn set_master_password(password: String, state: State<'_, AppState>) -> Result<bool, KeyKeeperError> {
+    let password = Zeroizing::new(password);
+    let user_inputs = password_strength::account_inputs(&*state.vault.lock().await);
+    password_strength::enforce(&state, &password, &user_inputs)?;
+    // Use bcrypt for secure password hashing
+    let password_hash = hash(&password, DEFAULT_COST).map_err(|e| KeyKeeperError::Crypto(e.to_string()))?;
+
+    let mut vault_guard = state.vault.lock().await;
+    // A locked encrypted vault is only loaded as metadata; re-keying it here
+    // would overwrite the real contents with an empty vault
+    if vault_guard.encryption_key.as_deref() == Some("[ENCRYPTED]") {
+        return Err(KeyKeeperError::Conflict(
+            "An encrypted vault already exists; unlock it instead".to_string(),
+        ));
+    }
+    vault_guard.master_password_hash = Some(password_hash);
+
+    // Generate salt for key derivation
+    let mut salt_bytes = [0u8; 16]; // 128 bits
+
+    OsRng.fill_bytes(&mut salt_bytes);
+
+    // Derive encryption key from password and salt using PBKDF2
+    let key_bytes = derive_key_from_password(&password, &salt_bytes);
+
+    vault_guard.encryption_key = Some(general_purpose::STANDARD.encode(&*key_bytes));
+    vault_guard.salt = Some(general_purpose::STANDARD.encode(&salt_bytes));
+
+    drop(vault_guard);
+
+    *state.is_unlocked.lock().await = true;
+    if let Err(e) = sessions::begin_unlock_session(&state).await {
+        warn!("Failed to start unlock session: {}", e);
+    }
+    save_vault(&state).await?;
+    Ok(true)
+}
+
+#[tauri::command]
+async fn is_vault_unlocked(state: State<'_, AppState>) -> Result<bool, KeyKeeperError> {
+    Ok(*state.is_unlocked.lock().await)
+}
+
+#[tauri::command]
+async fn lock_vault(state: State<'_, AppState>) -> Result<(), KeyKeeperError> {
+    lock_vault_for(state, lock_state::LockReason::Manual).await
+}
+
+async fn lock_vault_for(
+    state: State<'_, AppState>,
+    reason: lock_state::LockReason,
+) -> Result<(), KeyKeeperError> {
+    *state.is_unlocked.lock().await = false;
+    lock_state::set_locked(reason);
+    sessions::end_unlock_session(&state).await;
+    state.confirmations.clear_session_grants();
+    state.oauth_tokens.clear();
+    state.remote_values.clear();
+    state.decryption_keys.purge();
+    log_audit_event(&state, "lock_vault", "vault", None, true, None).await;
+
+    // Persist pending changes, including saves still waiting to be
+    // coalesced, then wipe decrypted secrets and fall back to the
+    // metadata-only view; the next unlock decrypts from disk again
+    let is_encrypted = state
+        .vault
+        .lock()
+        .await
+        .encryption_key
+        .as_ref()
+        .map_or(false, |key| key != "[ENCRYPTED]");
+    if is_encrypted {
+        state.saves.flush().await?;
+        let mut vault_guard = state.vault.lock().await;
+        secure_memory::wipe_vault_secrets(&mut vault_guard);
+        *vault_guard = load_vault_from(&*state.storage).map_err(KeyKeeperError::Io)?;
+    }
+    state.vault_index.lock().await.invalidate();
+    Ok(())
+}
+
+// Keyring management commands. Entries go into the vault's namespace as
+// "<service>/<account>"; ones written before namespacing are moved on read.
+#[tauri::command]
+async fn keyring_set(
+    service: String,
+    account: String,
+    password: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    let password = Zeroizing::new(password);
+    let store = keyring_store::KeyringStore::open(&app, &state).await?;
+    store.set(&format!("{}/{}", service, account), &password)?;
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn keyring_get(
+    service: String,
+    account: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, KeyKeeperError> {
+    let store = keyring_store::KeyringStore::open(&app, &state).await?;
+    let password = store
+        .get(&format!("{}/{}", service, account), Some((&service, &account)))?
+        .ok_or_else(|| KeyKeeperError::not_found("Keyring entry"))?;
+
+    Ok(password.to_string())
+}
+
+#[tauri::command]
+async fn keyring_delete(
+    service: String,
+    account: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    let store = keyring_store::KeyringStore::open(&app, &state).await?;
+    store.delete(&format!("{}/{}", service, account), Some((&service, &account)))?;
+
+    Ok(())
+}
+
+// Device information command
+#[tauri::command]
+async fn get_device_info() -> Result<serde_json::Value, KeyKeeperError> {
+    let hostname = whoami::fallible::hostname().unwrap_or_else(|_| "unknown".to_string());
+    let device_info = serde_json::json!({
+        "platform": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "hostname": hostname,
+        "username": whoami::username(),
+        "device_id": get_device_id()
+    });
+
+    Ok(device_info)
+}
+
+// Auto-start management with proper implementation
+#[tauri::command]
+async fn setup_auto_start(app: AppHandle, enabled: bool) -> Result<(), KeyKeeperError> {
+    use tauri_plugin_autostart::ManagerExt;
+
+    let autostart_manager = app.autolaunch();
+
+    if enabled {
+        autostart_manager
+            .enable()
+            .map_err(|e| format!("Failed to enable auto-start: {}", e))?;
+        info!("Auto-start enabled");
+    } else {
+        autostart_manager
+            .disable()
+            .map_err(|e| format!("Failed to disable auto-start: {}", e))?;
+        info!("Auto-start disabled");
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn disable_auto_start(app: AppHandle) -> Result<(), KeyKeeperError> {
+    use tauri_plugin_autostart::ManagerExt;
+
+    app.autolaunch()
+        .disable()
+        .map_err(|e| format!("Failed to disable auto-start: {}", e))?;
+
+    info!("Auto-start disabled");
+    Ok(())
+}
+
+#[tauri::command]
+async fn is_auto_start_enabled(app: AppHandle) -> Result<bool, KeyKeeperError> {
+    use tauri_plugin_autostart::ManagerExt;
+
+    app.autolaunch()
+        .is_enabled()
+        .map_err(|e| KeyKeeperError::Internal(format!("Failed to check auto-start status: {}", e)))
+}
+
+// Notification command, routed through the dispatcher so preferences apply
+#[tauri::command]
+async fn show_notification(
+    title: String,
+    body: String,
+    kind: Option<notifications::NotificationKind>,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    let kind = kind.unwrap_or(notifications::NotificationKind::General);
+    if state.notifier.notify(&state.vault, kind, &title, &body).await? {
+        info!("Notification shown successfully");
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_api_keys(
+    state: State<'_, AppState>,
+) -> Result<Vec<masking::MaskedApiKey>, KeyKeeperError> {
+    let vault_guard = state.vault.lock().await;
+    let is_unlocked = *state.is_unlocked.lock().await;
+
+    // If vault is locked but we have metadata, return keys with encrypted placeholders
+    let has_metadata = vault_guard
+        .encryption_key
+        .as_ref()
+        .map_or(false, |key| key == "[ENCRYPTED]");
+    if !is_unlocked && !has_metadata {
+        // No metadata available, vault is completely locked
+        return Err(KeyKeeperError::VaultLocked);
+    }
+
+    let mut keys: Vec<ApiKey> = vault_guard
+        .keys
+        .values()
+        .filter(|key| !archive::is_archived(key))
+        .cloned()
+        .collect();
+    pinning::order_keys(&vault_guard, &mut keys);
+    Ok(masking::mask_all(keys))
+}
+
+#[tauri::command]
+async fn add_api_key(api_key: ApiKey, state: State<'_, AppState>) -> Result<(), KeyKeeperError> {
+    if let Err(e) = guards::unlocked(&state).await {
+        log_audit_event(
+            &state,
+            "add_api_key",
+            "api_key",
+            Some(&api_key.id),
+            false,
+            Some(&e.to_string()),
+        )
+        .await;
+        return Err(e);
+    }
+    if let Some(quota) = &api_key.quota {
+        quota.validate()?;
+    }
+    if let Some(cost) = &api_key.cost {
+        cost.validate()?;
+    }
+
+    let mut vault_guard = state.vault.lock().await;
+    if let Err(e) = naming::enforce(&vault_guard, &api_key) {
+        drop(vault_guard);
+        log_audit_event(
+            &state,
+            "add_api_key",
+            "api_key",
+            Some(&api_key.id),
+            false,
+            Some(&e.to_string()),
+        )
+        .await;
+        return Err(e);
+    }
+    vault_guard.keys.insert(api_key.id.clone(), api_key.clone());
+    let actor = activity::Actor::ui();
+    activity::record(&mut vault_guard, "key_created", &api_key, actor, None, None);
+    drop(vault_guard);
+
+    match save_vault(&state).await {
+        Ok(_) => {
+            log_audit_event(
+                &state,
+                "add_api_key",
+                "api_key",
+                Some(&api_key.id),
+                true,
+                None,
+            )
+            .await;
+            Ok(())
+        }
+        Err(e) => {
+            log_audit_event(
+                &state,
+                "add_api_key",
+                "api_key",
+                Some(&api_key.id),
+                false,
+                Some(&e.to_string()),
+            )
+            .await;
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+async fn update_api_key(api_key: ApiKey, state: State<'_, AppState>) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    if let Some(quota) = &api_key.quota {
+        quota.validate()?;
+    }
+    if let Some(cost) = &api_key.cost {
+        cost.validate()?;
+    }
+
+    let mut api_key = api_key;
+    let mut vault_guard = state.vault.lock().await;
+    let existing = vault_guard.keys.get(&api_key.id);
+    // Keys come masked from the listings; their masked value means unchanged
+    if let Some(existing) = existing.filter(|e| masking::mask_value(&e.key) == api_key.key) {
+        api_key.key = existing.key.clone();
+    }
+    let rotated = existing.is_some_and(|existing| existing.key != api_key.key);
+    // Keys named before a policy existed can still be edited
+    if existing.is_none_or(|existing| existing.name != api_key.name) {
+        naming::enforce(&vault_guard, &api_key)?;
+    }
+    // The schedule is set with set_key_rotation_schedule
+    if let Some(existing) = existing {
+        api_key.rotation_interval_days = existing.rotation_interval_days;
+        api_key.last_rotated_at = existing.last_rotated_at;
+        // The kind and its parts have their own commands. A new private
+        // key needs its public half too, so SSH keys and certificates are
+        // re-imported instead.
+        if existing.credential.is_some() && rotated {
+            return Err(KeyKeeperError::Validation(
+                "Import the new key files with import_credential_files".to_string(),
+            ));
+        }
+        // A remote key's value is changed in its secret manager
+        if existing.backend.is_some() && rotated {
+            return Err(KeyKeeperError::Validation(
+                "Change the value of a remote key in its secret manager".to_string(),
+            ));
+        }
+        api_key.backend = existing.backend.clone();
+        api_key.kind = existing.kind;
+        api_key.credential = existing.credential.clone();
+        api_key.oauth = existing.oauth.clone();
+        api_key.values = existing.values.clone();
+        api_key.is_pinned = existing.is_pinned;
+        api_key.archived_at = existing.archived_at;
+        api_key.last_used_at = existing.last_used_at;
+    }
+    let key_id = api_key.id.clone();
+    let changes = existing.and_then(|existing| activity::describe_changes(existing, &api_key));
+    let actor = activity::Actor::ui();
+    activity::record(&mut vault_guard, "key_updated", &api_key, actor, None, changes);
+    vault_guard.keys.insert(api_key.id.clone(), api_key);
+    if rotated {
+        rotation::mark_rotated(&mut vault_guard, &key_id, Utc::now());
+    }
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+
+    if rotated {
+        log_audit_event(&state, "rotate_api_key", "api_key", Some(&key_id), true, None).await;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn delete_api_key(id: String, state: State<'_, AppState>) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    remove_key(&mut vault_guard, &id, activity::Actor::ui());
+    drop(vault_guard);
+    state.oauth_tokens.forget(&id);
+
+    save_vault(&state).await
+}
+
+/// Remove a key along with its quota usage and cost entries, recording the
+/// deletion in the activity feed and the audit log. Pinned keys also leave
+/// the tray. Cached OAuth tokens live outside the vault, so callers forget
+/// those.
+pub(crate) fn remove_key(
+    vault: &mut ApiKeyVault,
+    id: &str,
+    actor: activity::Actor,
+) -> Option<ApiKey> {
+    let key = vault.keys.remove(id)?;
+    vault.quota_usage.remove(id);
+    vault.cost_entries.retain(|entry| entry.key_id != id);
+
+    let details = match &actor.name {
+        Some(client) => format!("Deleted {} via {}", key.name, client),
+        None => format!("Deleted {}", key.name),
+    };
+    activity::record(vault, "key_deleted", &key, actor, None, None);
+    vault.audit_logs.push(create_audit_log(
+        "delete_api_key",
+        "api_key",
+        Some(id),
+        true,
+        Some(&details),
+    ));
+    retention::cap_audit_logs(vault);
+
+    if key.is_pinned {
+        events::publish(events::EventPayload::PinsChanged);
+    }
+    Some(key)
+}
+
+#[tauri::command]
+async fn search_api_keys(
+    query: String,
+    include_archived: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Vec<masking::MaskedApiKey>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    Ok(masking::mask_all(search_keys(
+        &vault_guard,
+        &query,
+        include_archived.unwrap_or(false),
+    )))
+}
+
+/// Keys whose name, service, tags or credential fields contain `query`,
+/// pinned ones first
+fn search_keys(vault: &ApiKeyVault, query: &str, include_archived: bool) -> Vec<ApiKey> {
+    let query = query.to_lowercase();
+    let mut filtered_keys: Vec<ApiKey> = vault
+        .keys
+        .values()
+        .filter(|key| include_archived || !archive::is_archived(key))
+        .filter(|key| {
+            key.name.to_lowercase().contains(&query)
+                || key.service.to_lowercase().contains(&query)
+                || key.tags.iter().any(|tag| tag.to_lowercase().contains(&query))
+                || credentials::matches_query(key, &query)
+        })
+        .cloned()
+        .collect();
+    pinning::order_keys(vault, &mut filtered_keys);
+    filtered_keys
+}
+
+/// Value of a key. Within the unlocked session no password is needed; the
+/// `decrypt_key` confirmation policy decides whether to ask first. Values
+/// encrypted on their own that the session has no key for need
+/// `master_password`.
+#[tauri::command]
+async fn get_decrypted_api_key(
+    key_id: String,
+    master_password: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, KeyKeeperError> {
+    let master_password = master_password.map(Zeroizing::new);
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+
+    // First, verify the master password is correct
+    if let Some(master_password) = &master_password {
+        if let Some(stored_hash) = &vault_guard.master_password_hash {
+            let is_valid = verify(master_password.as_str(), stored_hash)
+                .map_err(|e| KeyKeeperError::Crypto(format!("Password verification failed: {}", e)))?;
+
+            if !is_valid {
+                return Err(KeyKeeperError::Validation("Invalid master password".to_string()));
+            }
+        } else {
+            return Err(KeyKeeperError::Validation("No master password set".to_string()));
+        }
+    }
+
+    // Find the API key by ID
+    let api_key = vault_guard
+        .keys
+        .get(&key_id)
+        .cloned()
+        .ok_or_else(|| KeyKeeperError::not_found("API key"))?;
+    drop(vault_guard);
+
+    // Check if the key is a placeholder
+    if api_key.key == "[ENCRYPTED]" {
+        return Err(KeyKeeperError::Validation("API key is not properly encrypted yet".to_string()));
+    }
+
+    let Some(master_password) = master_password else {
+        confirmations::confirm(
+            &state.confirmations,
+            &state.vault,
+            &*state.storage,
+            "KeyKeeper",
+            confirmations::RiskyAction::DecryptKey,
+            format!("Show the value of {}", api_key.name),
+            vec![],
+        )
+        .await?;
+        let value = if api_key.backend.is_some() {
+            secret_backends::value(&state.remote_values, &state.vault, &api_key).await?
+        } else {
+            match state.decryption_keys.unseal(&api_key.key) {
+                decryption_keys::Unsealed::Value(value) => value,
+                decryption_keys::Unsealed::NotSealed => api_key.key,
+                decryption_keys::Unsealed::NeedsPassword => {
+                    return Err(KeyKeeperError::Validation(
+                        "Enter the master password to decrypt this key".to_string(),
+                    ));
+                }
+            }
+        };
+        log_audit_event(&state, "decrypt_api_key", "api_key", Some(&key_id), true, None).await;
+        return Ok(value);
+    };
+
+    if api_key.backend.is_some() {
+        let value = secret_backends::value(&state.remote_values, &state.vault, &api_key).await?;
+        log_audit_event(&state, "decrypt_api_key", "api_key", Some(&key_id), true, None).await;
+        return Ok(value);
+    }
+
+    // Try to decrypt the key with the verified master password
+    match decrypt_api_key(&api_key.key, &master_password) {
+        Ok(decrypted) => {
+            info!("API key {} successfully decrypted", key_id);
+            log_audit_event(&state, "decrypt_api_key", "api_key", Some(&key_id), true, None).await;
+            Ok(decrypted)
+        }
+        Err(e) => {
+            error!("Failed to decrypt API key {}: {}", key_id, e);
+            Err(KeyKeeperError::Crypto(format!("Failed to decrypt API key: {}", e)))
+        }
+    }
+}
+
+#[tauri::command]
+async fn export_vault(state: State<'_, AppState>) -> Result<String, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    serde_json::to_string_pretty(&*vault_guard)
+        .map_err(|e| KeyKeeperError::Internal(format!("Failed to export vault: {}", e)))
+}
+
+/// JSON error response for the HTTP API, status and body derived from the error
+fn api_error_response(err: &KeyKeeperError) -> Response<Full<bytes::Bytes>> {
+    api_error_response_with(err, serde_json::Value::Null)
+}
+
+/// `api_error_response` with structured `details` in the body
+fn api_error_response_with(err: &KeyKeeperError, details: serde_json::Value) -> Response<Full<bytes::Bytes>> {
+    Response::builder()
+        .status(StatusCode::from_u16(err.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR))
+        .header("Content-Type", "application/json")
+        .body(Full::new(bytes::Bytes::from(err.to_json_with(details).to_string())))
+        .unwrap()
+}
+
+// Hyper HTTP request handler
+async fn handle_hyper_request(
+    req: Request<Incoming>,
+    vault: Arc<Mutex<ApiKeyVault>>,
+    is_unlocked: Arc<Mutex<bool>>,
+    storage: Arc<dyn vault_storage::VaultStorage>,
+    saves: Arc<save_queue::SaveQueue>,
+    llm_proxy: Arc<llm_proxy::LLMProxyState>,
+    vault_index: Arc<Mutex<vault_index::VaultIndex>>,
+    health: Arc<health::HealthState>,
+    config: Arc<config::ConfigState>,
+    approvals: Arc<approvals::ApprovalBroker>,
+    confirmations: Arc<confirmations::ConfirmationBroker>,
+    oauth_tokens: Arc<oauth::TokenCache>,
+    remote_values: Arc<secret_backends::ValueCache>,
+    notifier: Arc<notifications::NotificationDispatcher>,
+    client_addr: std::net::SocketAddr,
+) -> Result<Response<Full<bytes::Bytes>>, Infallible> {
+    // Counted so shutdown can wait for requests being answered
+    let _request = health.begin_task("http_request");
+    let method = req.method();
+    let path = req.uri().path();
+    let _query = req.uri().query().unwrap_or("");
+
+    // Everything but the health checks needs the vault unlocked
+    if !lock_state::is_open_path(path) && !*is_unlocked.lock().await {
+        return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+    }
+
+    // Create temporary AppState for Tauri command calls
+    // Initialize minimal AI state for VSCode server
+    let llm_engine = Arc::new(RwLock::new(None));
+    let template_engine = Arc::new(template_engine::EnhancedTemplateEngine::new(
+        template_engine::TemplateEngineConfig::default()
+    ));
+    let ai_state = Arc::new(ai_commands::AIState::new(llm_engine.clone(), template_engine.clone()));
+
+    // Initialize documentation library manager for chat and docs integration
+    let docs_manager_enhanced = crate::docs_manager::DocumentationManager::new_enhanced(
+        Some(llm_proxy.clone()),
+        None, // ML engine will be initialized later
+    );
+    let doc_library = docs_manager_enhanced.get_doc_library();
+    let chat_engine = chat_engine::ChatEngine::new(
+        None, // LLM engine - will be set later
+        None, // ML engine - will be set later
+        doc_library,
+        Some(llm_proxy.clone()), // ✅ Inject LLM proxy state for dynamic responses
+        chat_engine::ChatEngineConfig::default(),
+    );
+    
+    let _app_state = AppState {
+        vault: vault.clone(),
+        is_unlocked: is_unlocked.clone(),
+        vault_path: health.vault_location(),
+        storage: storage.clone(),
+        saves: saves.clone(),
+        tasks: Arc::new(background_tasks::TaskManager::new()),
+        vscode_server_running: Arc::new(AtomicBool::new(false)),
+        tray_handle: Arc::new(Mutex::new(None)),
+        ml_engine: Arc::new(tokio::sync::Mutex::new(None)),
+        api_generator: Arc::new(Mutex::new(api_generator_commands::ApiGeneratorState::new())),
+        enhanced_api: Arc::new(enhanced_api_commands::EnhancedApiState::new()),
+        framework_generators: Arc::new(framework_generator_commands::FrameworkGeneratorState::new()),
+        docs_manager: Arc::new(Mutex::new(docs_commands::DocsManagerState::new())),
+        docs_generator: Arc::new(docs_generator_commands::DocsGeneratorState::new()),
+        llm_proxy: llm_proxy,
+        docs_store: Arc::new(Mutex::new(DocsStore::new())),
+        docs_store_path: PathBuf::from("/tmp/temp_docs.json"),
+        ai_state,
+        chat_engine_state: Arc::new(chat_commands::ChatEngineState::new_with_default_proxy(chat_engine)),
+        docs_library_state: Arc::new(docs_library_commands::DocumentationLibraryState::new(docs_manager_enhanced)),
+        notifier: notifier.clone(),
+        vault_index: vault_index.clone(),
+        unlock_session: Arc::new(Mutex::new(None)),
+        health: health.clone(),
+        config: config.clone(),
+        approvals: approvals.clone(),
+        confirmations: confirmations.clone(),
+        oauth_tokens: oauth_tokens.clone(),
+        decryption_keys: Arc::new(decryption_keys::DecryptionKeys::new()),
+        remote_values: remote_values.clone(),
+    };
+
+    // Get headers
+    let headers = req.headers();
+    let _auth_header = headers.get("authorization").and_then(|h| h.to_str().ok());
+    let _user_agent = headers
+        .get("user-agent")
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("");
+
+    match (method, path) {
+        (&Method::GET, "/health") => {
+            let report = health.report().await;
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(serde_json::to_string(&report).unwrap_or_default())))
+                .unwrap())
+        }
+
+        (&Method::GET, "/live") => {
+            let response = serde_json::json!({
+                "status": "alive",
+                "timestamp": get_utc_timestamp(),
+                "uptime_seconds": health.uptime_seconds()
+            });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::GET, "/ready") => {
+            let report = health.report().await;
+            let status = if report.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+            let response = serde_json::json!({
+                "ready": report.ready,
+                "timestamp": get_utc_timestamp(),
+                "reasons": health::readiness_issues(&report.vault)
+            });
+            Ok(Response::builder()
+                .status(status)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::GET, lock_state::LOCK_STATE_PATH) => {
+            let params = batch::parse_params(req.uri().query());
+            let seen = params.get("locked").and_then(|v| v.parse::<bool>().ok());
+            let wait = params
+                .get("wait")
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(0)
+                .min(lock_state::MAX_WAIT_SECS);
+            let event =
+                lock_state::wait_for_change(seen, std::time::Duration::from_secs(wait)).await;
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(
+                    serde_json::to_string(&event).unwrap_or_default(),
+                )))
+                .unwrap())
+        }
+
+        (&Method::GET, events::EVENTS_PATH) => {
+            let params = batch::parse_params(req.uri().query());
+            let after = params
+                .get("after")
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(0);
+            let wait = params
+                .get("wait")
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(0)
+                .min(events::MAX_WAIT_SECS);
+            let events = events::wait_since(after, std::time::Duration::from_secs(wait)).await;
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(
+                    serde_json::to_string(&events).unwrap_or_default(),
+                )))
+                .unwrap())
+        }
+
+        (&Method::POST, "/api/auth/master-password") => {
+            // Quick fix per VSCode - sempre ritorna successo per la password corretta
+            let response = serde_json::json!({
+                "success": true,
+                "token": "vscode_session_fixed"
+            });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::GET, "/api/keys") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let params = batch::parse_params(req.uri().query());
+            let vault_guard = vault.lock().await;
+            let listing = batch::list_keys(&vault_guard, &mut *vault_index.lock().await, &params);
+            drop(vault_guard);
+            match listing {
+                Ok(listing) => Ok(etag::json_response(req.headers(), listing.to_string())),
+                Err(e) => Ok(api_error_response(&e)),
+            }
+        }
+
+        (&Method::GET, "/api/keys/pinned") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let vault_guard = vault.lock().await;
+            let pinned = batch::pinned_keys(&vault_guard, &mut *vault_index.lock().await);
+            drop(vault_guard);
+            match pinned {
+                Ok(pinned) => Ok(etag::json_response(req.headers(), pinned.to_string())),
+                Err(e) => Ok(api_error_response(&e)),
+            }
+        }
+
+        (&Method::GET, path) if path.starts_with("/api/keys/search") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            // Extract search query from URL
+            let search_term = if let Some(query) = req.uri().query() {
+                query
+                    .split('&')
+                    .find_map(|param| {
+                        let mut parts = param.split('=');
+                        if let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+                            if key == "q" {
+                                Some(urlencoding::decode(value).unwrap_or_default().to_string())
+                            } else {
+                                None
+                            }
+                        } else {
+                            None
+                        }
+                    })
+                    .unwrap_or_default()
+            } else {
+                String::new()
+            };
+
+            let vault_guard = vault.lock().await;
+            // Archived keys only with ?include_archived=true
+            let include_archived = req
+                .uri()
+                .query()
+                .is_some_and(|q| q.split('&').any(|param| param == "include_archived=true"));
+            let mut matching_keys: Vec<_> = vault_guard
+                .keys
+                .values()
+                .filter(|key| include_archived || !archive::is_archived(key))
+                .filter(|key| {
+                    key.name
+                        .to_lowercase()
+                        .contains(&search_term.to_lowercase())
+                        || key
+                            .service
+                            .to_lowercase()
+                            .contains(&search_term.to_lowercase())
+                        || key
+                            .description
+                            .as_ref()
+                            .map(|d| d.to_lowercase().contains(&search_term.to_lowercase()))
+                            .unwrap_or(false)
+                        || credentials::matches_query(key, &search_term.to_lowercase())
+                })
+                .cloned()
+                .collect();
+            pinning::order_keys(&vault_guard, &mut matching_keys);
+            drop(vault_guard);
+
+            let response =
+                serde_json::to_string(&masking::mask_all(matching_keys)).unwrap_or_default();
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response)))
+                .unwrap())
+        }
+
+        (&Method::GET, "/api/projects") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            match batch::projects(&*vault.lock().await) {
+                Ok(projects) => Ok(etag::json_response(req.headers(), projects.to_string())),
+                Err(e) => Ok(api_error_response(&e)),
+            }
+        }
+
+        (&Method::GET, "/api/activity/recent") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let params = batch::parse_params(req.uri().query());
+            match batch::recent_activity(&*vault.lock().await, &params) {
+                Ok(activity) => Ok(Response::builder()
+                    .status(StatusCode::OK)
+                    .header("Content-Type", "application/json")
+                    .body(Full::new(bytes::Bytes::from(activity.to_string())))
+                    .unwrap()),
+                Err(e) => Ok(api_error_response(&e)),
+            }
+        }
+
+        (&Method::GET, "/api/stats") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let docs_count = _app_state.docs_store.lock().await.docs.len();
+            match batch::stats(&*vault.lock().await, docs_count) {
+                Ok(stats) => Ok(Response::builder()
+                    .status(StatusCode::OK)
+                    .header("Content-Type", "application/json")
+                    .body(Full::new(bytes::Bytes::from(stats.to_string())))
+                    .unwrap()),
+                Err(e) => Ok(api_error_response(&e)),
+            }
+        }
+
+        (&Method::GET, "/api/vault/snapshot") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let params = batch::parse_params(req.uri().query());
+            let options = vault_snapshot::SnapshotOptions::from_params(&params);
+            let docs_count = _app_state.docs_store.lock().await.docs.len();
+            match vault_snapshot::build(&*vault.lock().await, docs_count, &options, Utc::now()) {
+                Ok(snapshot) => Ok(Response::builder()
+                    .status(StatusCode::OK)
+                    .header("Content-Type", "application/json")
+                    .body(Full::new(bytes::Bytes::from(
+                        serde_json::to_string(&snapshot).unwrap_or_default(),
+                    )))
+                    .unwrap()),
+                Err(e) => Ok(api_error_response(&e)),
+            }
+        }
+
+        (&Method::GET, "/api/vault/generation") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let response = serde_json::json!({ "generation": etag::generation() });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .header(etag::GENERATION_HEADER, etag::generation().to_string())
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::POST, "/api/clients/register") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let body_bytes = match req.into_body().collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(_) => {
+                    return Ok(api_error_response(&KeyKeeperError::Validation(
+                        "Cannot read request body".to_string(),
+                    )))
+                }
+            };
+            let Ok(registration) = serde_json::from_slice::<clients::RegistrationRequest>(&body_bytes) else {
+                return Ok(api_error_response(&KeyKeeperError::Validation(
+                    "Expected {name, device_id?}".to_string(),
+                )));
+            };
+
+            let mut vault_guard = vault.lock().await;
+            let registered = clients::register(
+                &mut vault_guard,
+                &registration.name,
+                registration.device_id,
+                Some(client_addr.ip().to_string()),
+                Utc::now(),
+            );
+            let (client, secret) = match registered {
+                Ok(registered) => registered,
+                Err(e) => return Ok(api_error_response(&e)),
+            };
+            vault_guard.audit_logs.push(create_audit_log(
+                "client_registration_requested",
+                "client",
+                Some(&client.client_id),
+                true,
+                Some(&client.name),
+            ));
+            retention::cap_audit_logs(&mut vault_guard);
+            drop(vault_guard);
+            if let Err(e) = saves.save().await {
+                // The secret is only ever returned here; don't hand out an unsaved one
+                return Ok(api_error_response(&KeyKeeperError::Internal(format!(
+                    "Failed to save registration: {}",
+                    e
+                ))));
+            }
+            approvals.notify("client-registration-requested", &client.redacted());
+
+            let response = serde_json::json!({
+                "client_id": client.client_id,
+                "client_secret": secret,
+                "status": client.status,
+                "expires_at": client.expires_at.to_rfc3339(),
+            });
+            Ok(Response::builder()
+                .status(StatusCode::ACCEPTED)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::GET, "/api/clients/status") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let header = |name: &str| {
+                req.headers()
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let client_id = header(clients::CLIENT_ID_HEADER);
+            let secret = header(clients::CLIENT_SECRET_HEADER);
+            let mut vault_guard = vault.lock().await;
+            match clients::find(&mut vault_guard, &client_id, &secret) {
+                Ok(client) => Ok(Response::builder()
+                    .status(StatusCode::OK)
+                    .header("Content-Type", "application/json")
+                    .body(Full::new(bytes::Bytes::from(
+                        serde_json::to_string(&client.redacted()).unwrap_or_default(),
+                    )))
+                    .unwrap()),
+                Err(e) => Ok(api_error_response(&e)),
+            }
+        }
+
+        (&Method::POST, "/api/batch") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let body_bytes = match req.into_body().collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(_) => {
+                    return Ok(api_error_response(&KeyKeeperError::Validation(
+                        "Cannot read request body".to_string(),
+                    )))
+                }
+            };
+            let Ok(requests) = serde_json::from_slice::<Vec<batch::SubRequest>>(&body_bytes) else {
+                return Ok(api_error_response(&KeyKeeperError::Validation(
+                    "Expected an array of {method, path} requests".to_string(),
+                )));
+            };
+            if requests.is_empty() || requests.len() > batch::MAX_SUB_REQUESTS {
+                return Ok(api_error_response(&KeyKeeperError::Validation(format!(
+                    "A batch holds 1 to {} requests",
+                    batch::MAX_SUB_REQUESTS
+                ))));
+            }
+
+            let docs_count = _app_state.docs_store.lock().await.docs.len();
+            // One acquisition of each lock for the whole batch
+            let vault_guard = vault.lock().await;
+            let mut index_guard = vault_index.lock().await;
+            let responses = batch::run(&vault_guard, &mut index_guard, docs_count, &requests);
+            drop(index_guard);
+            drop(vault_guard);
+
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(serde_json::to_string(&responses).unwrap_or_default())))
+                .unwrap())
+        }
+
+        (&Method::POST, path) if path.starts_with("/api/keys/") && path.ends_with("/decrypt") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let key_id = path
+                .strip_prefix("/api/keys/")
+                .and_then(|s| s.strip_suffix("/decrypt"))
+                .unwrap_or_default()
+                .to_string();
+            if !vault.lock().await.keys.contains_key(&key_id) {
+                return Ok(api_error_response(&KeyKeeperError::not_found("API key")));
+            }
+
+            if let Err(e) = approvals::authorize(
+                &approvals,
+                &vault,
+                &*storage,
+                &clients::current_or_unverified(req.headers()),
+                std::slice::from_ref(&key_id),
+                "Read secret",
+            )
+            .await
+            {
+                return Ok(api_error_response(&e));
+            }
+
+            let mut vault_guard = vault.lock().await;
+            // Saved along with the next vault write
+            archive::touch(&mut vault_guard, &key_id, Utc::now());
+            let Some(key) = vault_guard.keys.get(&key_id).cloned() else {
+                return Ok(api_error_response(&KeyKeeperError::not_found("API key")));
+            };
+            activity::record(
+                &mut vault_guard,
+                "key_used",
+                &key,
+                activity::Actor::http(req.headers()),
+                Some(activity::ActivitySource::http(req.headers(), client_addr)),
+                Some("Read secret".to_string()),
+            );
+            drop(vault_guard);
+            let value = match secret_backends::value(&remote_values, &vault, &key).await {
+                Ok(value) => value,
+                Err(e) => return Ok(api_error_response(&e)),
+            };
+            let response = serde_json::json!({
+                "id": key.id,
+                "name": key.name,
+                "kind": key.kind,
+                "value": value,
+                "passphrase": key.credential.as_ref().and_then(|c| c.passphrase.as_deref()),
+            });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .header("Cache-Control", "no-store")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::GET, path) if path.starts_with("/api/keys/") && path.ends_with("/public") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            // Public halves aren't secret, so no approval is asked for
+            let key_id = path
+                .strip_prefix("/api/keys/")
+                .and_then(|s| s.strip_suffix("/public"))
+                .unwrap_or_default();
+            let vault_guard = vault.lock().await;
+            let Some(key) = vault_guard.keys.get(key_id) else {
+                return Ok(api_error_response(&KeyKeeperError::not_found("API key")));
+            };
+            let Some(parts) = &key.credential else {
+                return Ok(api_error_response(&KeyKeeperError::Validation(
+                    "Only SSH keys and certificates have a public part".to_string(),
+                )));
+            };
+            let response = serde_json::json!({
+                "id": key.id,
+                "name": key.name,
+                "kind": key.kind,
+                "public_part": parts.public_part,
+                "fingerprint": parts.fingerprint,
+                "expires_at": key.expires_at.map(|e| e.to_rfc3339()),
+            });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::POST, path) if path.starts_with("/api/oauth/") && path.ends_with("/token") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let key_id = path
+                .strip_prefix("/api/oauth/")
+                .and_then(|s| s.strip_suffix("/token"))
+                .unwrap_or_default()
+                .to_string();
+            let force_refresh = req
+                .uri()
+                .query()
+                .is_some_and(|q| q.split('&').any(|p| p == "refresh=true"));
+            if !vault.lock().await.keys.contains_key(&key_id) {
+                return Ok(api_error_response(&KeyKeeperError::not_found("OAuth client")));
+            }
+
+            let client = approvals::client_name(req.headers());
+            if let Err(e) = approvals::authorize(
+                &approvals,
+                &vault,
+                &*storage,
+                &clients::current_or_unverified(req.headers()),
+                std::slice::from_ref(&key_id),
+                "Fetch an access token",
+            )
+            .await
+            {
+                return Ok(api_error_response(&e));
+            }
+
+            let result = oauth::access_token(&oauth_tokens, &vault, &key_id, force_refresh).await;
+            // Tokens served from the cache aren't audited; the entry is
+            // written with the next save
+            if !matches!(&result, Ok(token) if token.cached) {
+                let mut vault_guard = vault.lock().await;
+                let error = result.as_ref().err().map(|e| e.to_string());
+                vault_guard.audit_logs.push(create_audit_log(
+                    "get_access_token",
+                    "api_key",
+                    Some(&key_id),
+                    error.is_none(),
+                    Some(error.as_deref().unwrap_or(&format!("via {}", client))),
+                ));
+                retention::cap_audit_logs(&mut vault_guard);
+            }
+
+            match result {
+                Ok(token) => Ok(Response::builder()
+                    .status(StatusCode::OK)
+                    .header("Content-Type", "application/json")
+                    .header("Cache-Control", "no-store")
+                    .body(Full::new(bytes::Bytes::from(
+                        serde_json::to_string(&token).unwrap_or_default(),
+                    )))
+                    .unwrap()),
+                Err(e) => Ok(api_error_response(&e)),
+            }
+        }
+
+        (&Method::POST, path) if path.starts_with("/api/keys/") && path.ends_with("/usage") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let key_id = path
+                .strip_prefix("/api/keys/")
+                .and_then(|s| s.strip_suffix("/usage"))
+                .unwrap_or_default()
+                .to_string();
+            let actor = activity::Actor::http(req.headers());
+            let source = activity::ActivitySource::http(req.headers(), client_addr);
+            let body_bytes = match req.into_body().collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(_) => {
+                    return Ok(api_error_response(&KeyKeeperError::Validation(
+                        "Cannot read request body".to_string(),
+                    )))
+                }
+            };
+            // An empty body counts as one request
+            let usage = if body_bytes.iter().all(u8::is_ascii_whitespace) {
+                quotas::UsageMetadata::default()
+            } else {
+                match serde_json::from_slice::<quotas::UsageMetadata>(&body_bytes) {
+                    Ok(usage) => usage,
+                    Err(_) => {
+                        return Ok(api_error_response(&KeyKeeperError::Validation(
+                            "Expected {requests?, cost?}".to_string(),
+                        )))
+                    }
+                }
+            };
+
+            let now = Utc::now();
+            let mut vault_guard = vault.lock().await;
+            let Some(key) = vault_guard.keys.get(&key_id).cloned() else {
+                return Ok(api_error_response(&KeyKeeperError::not_found("API key")));
+            };
+            archive::touch(&mut vault_guard, &key_id, now);
+            activity::record(&mut vault_guard, "key_used", &key, actor, Some(source), None);
+            let alerts = quotas::consume(&mut vault_guard, &key_id, &usage, now);
+            let status = quotas::status(&vault_guard, &key_id, now);
+            vault_guard.audit_logs.push(create_audit_log(
+                "record_key_usage",
+                "api_key",
+                Some(&key_id),
+                true,
+                None,
+            ));
+            retention::cap_audit_logs(&mut vault_guard);
+            drop(vault_guard);
+            saves.save_later(health.begin_task("vault_save")).await;
+            quotas::notify(&notifier, &vault, &alerts).await;
+
+            match status {
+                Ok(status) => Ok(Response::builder()
+                    .status(StatusCode::OK)
+                    .header("Content-Type", "application/json")
+                    .body(Full::new(bytes::Bytes::from(
+                        serde_json::json!({ "success": true, "quota": status }).to_string(),
+                    )))
+                    .unwrap()),
+                Err(e) => Ok(api_error_response(&e)),
+            }
+        }
+
+        (&Method::POST, "/api/costs") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let body_bytes = match req.into_body().collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(_) => {
+                    return Ok(api_error_response(&KeyKeeperError::Validation(
+                        "Cannot read request body".to_string(),
+                    )))
+                }
+            };
+            let input = match serde_json::from_slice::<costs::CostEntryInput>(&body_bytes) {
+                Ok(input) => input,
+                Err(_) => {
+                    return Ok(api_error_response(&KeyKeeperError::Validation(
+                        "Expected {key_id, amount, period?, currency?, note?}".to_string(),
+                    )))
+                }
+            };
+
+            let mut vault_guard = vault.lock().await;
+            let entry = match costs::record(
+                &mut vault_guard,
+                input,
+                costs::CostSource::Webhook,
+                Utc::now(),
+            ) {
+                Ok(entry) => entry,
+                Err(e) => return Ok(api_error_response(&e)),
+            };
+            vault_guard.audit_logs.push(create_audit_log(
+                "record_cost_entry",
+                "api_key",
+                Some(&entry.key_id),
+                true,
+                None,
+            ));
+            retention::cap_audit_logs(&mut vault_guard);
+            drop(vault_guard);
+            if let Err(e) = saves.save().await {
+                return Ok(api_error_response(&KeyKeeperError::Internal(format!(
+                    "Failed to save cost entry: {}",
+                    e
+                ))));
+            }
+
+            Ok(Response::builder()
+                .status(StatusCode::CREATED)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(
+                    serde_json::to_string(&entry).unwrap_or_default(),
+                )))
+                .unwrap())
+        }
+
+        (&Method::POST, "/api/keys") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            // Read request body for creating new key
+            let body = req.into_body();
+            let body_bytes = match body.collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(_) => {
+                    return Ok(api_error_response(&KeyKeeperError::Validation("Cannot read request body".to_string())));
+                }
+            };
+
+            let body_str = String::from_utf8_lossy(&body_bytes);
+            if let Ok(key_data) = serde_json::from_str::<serde_json::Value>(&body_str) {
+                // Create mock response for VSCode extension
+                let new_key = serde_json::json!({
+                    "id": format!("mock_key_{}", uuid::Uuid::new_v4()),
+                    "name": key_data["name"].as_str().unwrap_or("Unknown"),
+                    "service": key_data["service"].as_str().unwrap_or("Unknown"),
+                    "key": masking::mask_value(key_data["key"].as_str().unwrap_or("")),
+                    "environment": key_data["environment"].as_str().unwrap_or("development"),
+                    "description": key_data["description"].as_str(),
+                    "created_at": get_utc_timestamp(),
+                    "updated_at": get_utc_timestamp(),
+                    "scopes": [],
+                    "tags": [],
+                    "is_active": true
+                });
+
+                Ok(Response::builder()
+                    .status(StatusCode::CREATED)
+                    .header("Content-Type", "application/json")
+                    .body(Full::new(bytes::Bytes::from(new_key.to_string())))
+                    .unwrap())
+            } else {
+                Ok(api_error_response(&KeyKeeperError::Validation("Invalid JSON body".to_string())))
+            }
+        }
+
+        (&Method::POST, "/api/projects/sync") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let response = serde_json::json!({"success": true, "message": "Project synced"});
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::POST, "/api/leases/redeem") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let token = req
+                .headers()
+                .get("Authorization")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("Bearer "))
+                .filter(|t| t.starts_with(leases::TOKEN_PREFIX))
+                .map(|t| t.trim().to_string());
+            let Some(token) = token else {
+                return Ok(api_error_response(&KeyKeeperError::Validation(
+                    "Missing lease token".to_string(),
+                )));
+            };
+
+            let mut vault_guard = vault.lock().await;
+            let result = leases::redeem_token(&mut vault_guard, &token, Utc::now());
+            let audit = match &result {
+                Ok(redeemed) => create_audit_log("redeem_lease", "lease", Some(&redeemed.lease_id), true, None),
+                Err(e) => create_audit_log("redeem_lease", "lease", None, false, Some(&e.to_string())),
+            };
+            vault_guard.audit_logs.push(audit);
+            retention::cap_audit_logs(&mut vault_guard);
+            drop(vault_guard);
+            saves.save_later(health.begin_task("vault_save")).await;
+
+            match result {
+                Ok(redeemed) => Ok(Response::builder()
+                    .status(StatusCode::OK)
+                    .header("Content-Type", "application/json")
+                    .header("Cache-Control", "no-store")
+                    .body(Full::new(bytes::Bytes::from(serde_json::to_string(&redeemed).unwrap_or_default())))
+                    .unwrap()),
+                Err(e) => Ok(api_error_response(&e)),
+            }
+        }
+
+        (&Method::POST, "/api/env/resolve") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let requester = clients::current_or_unverified(req.headers());
+            let actor = activity::Actor::http(req.headers());
+            let source = activity::ActivitySource::http(req.headers(), client_addr);
+            let body_bytes = match req.into_body().collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(_) => {
+                    return Ok(api_error_response(&KeyKeeperError::Validation(
+                        "Cannot read request body".to_string(),
+                    )))
+                }
+            };
+            let Ok(request_data) = serde_json::from_slice::<serde_json::Value>(&body_bytes) else {
+                return Ok(api_error_response(&KeyKeeperError::Validation(
+                    "Invalid JSON body".to_string(),
+                )));
+            };
+
+            let mut variables: std::collections::BTreeMap<String, String> =
+                serde_json::from_value(request_data["variables"].clone()).unwrap_or_default();
+            let env_file_path = request_data["envFilePath"].as_str();
+            let environment = request_data["environment"].as_str();
+            if let Some(path) = env_file_path {
+                match env_refs::read_env_file(std::path::Path::new(path)) {
+                    Ok(vars) => variables.extend(vars),
+                    Err(e) => return Ok(api_error_response(&KeyKeeperError::Io(e))),
+                }
+            }
+
+            // Approval is for the keys ids of merged keys now point to
+            let mut key_ids: Vec<String> = {
+                let vault_guard = vault.lock().await;
+                variables
+                    .values()
+                    .filter_map(|value| env_refs::parse_reference(value))
+                    .map(|id| env_values::canonical_id(&vault_guard, id))
+                    .collect()
+            };
+            key_ids.sort();
+            key_ids.dedup();
+            if let Err(e) = approvals::authorize(
+                &approvals,
+                &vault,
+                &*storage,
+                &requester,
+                &key_ids,
+                "Resolve env references",
+            )
+            .await
+            {
+                return Ok(api_error_response(&e));
+            }
+
+            let mut vault_guard = vault.lock().await;
+            // Only the secrets asked for go back, not the rest of the file
+            let resolved = env_refs::resolve(&vault_guard, variables, environment).references_only();
+            let now = Utc::now();
+            for id in &key_ids {
+                archive::touch(&mut vault_guard, id, now);
+                if let Some(key) = vault_guard.keys.get(id).cloned() {
+                    activity::record(
+                        &mut vault_guard,
+                        "key_used",
+                        &key,
+                        actor.clone(),
+                        Some(source.clone()),
+                        Some("Resolved env reference".to_string()),
+                    );
+                }
+            }
+            vault_guard.audit_logs.push(create_audit_log(
+                "resolve_env_references",
+                "env_file",
+                env_file_path,
+                resolved.unresolved.is_empty(),
+                Some(&format!(
+                    "Resolved {}, unresolved {}",
+                    resolved.resolved.len(),
+                    resolved.unresolved.len()
+                )),
+            ));
+            retention::cap_audit_logs(&mut vault_guard);
+            drop(vault_guard);
+            saves.save_later(health.begin_task("vault_save")).await;
+
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .header("Cache-Control", "no-store")
+                .body(Full::new(bytes::Bytes::from(serde_json::to_string(&resolved).unwrap_or_default())))
+                .unwrap())
+        }
+
+        (&Method::POST, "/api/github/sync") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let client = approvals::client_name(req.headers());
+            let body_bytes = match req.into_body().collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(_) => {
+                    return Ok(api_error_response(&KeyKeeperError::Validation(
+                        "Cannot read request body".to_string(),
+                    )))
+                }
+            };
+            let Ok(request_data) = serde_json::from_slice::<serde_json::Value>(&body_bytes) else {
+                return Ok(api_error_response(&KeyKeeperError::Validation(
+                    "Invalid JSON body".to_string(),
+                )));
+            };
+
+            let repo = request_data["repo"].as_str().unwrap_or("").to_string();
+            let token_key_id = request_data["tokenKeyId"].as_str().unwrap_or("").to_string();
+            let environment = request_data["environment"].as_str().map(str::to_string);
+            let key_ids: Vec<String> =
+                serde_json::from_value(request_data["keyIds"].clone()).unwrap_or_default();
+            let secret_names: HashMap<String, String> =
+                serde_json::from_value(request_data["secretNames"].clone()).unwrap_or_default();
+            if key_ids.is_empty() {
+                return Ok(api_error_response(&KeyKeeperError::Validation(
+                    "keyIds is required".to_string(),
+                )));
+            }
+
+            let names = github_sync::planned_secret_names(&*vault.lock().await, &key_ids, &secret_names);
+            let target = match &environment {
+                Some(env) => format!("{} (environment {})", repo, env),
+                None => repo.clone(),
+            };
+            let details = names
+                .iter()
+                .map(|name| format!("Create or replace the secret {} in {}", name, target))
+                .collect();
+            if let Err(e) = confirmations::confirm(
+                &confirmations,
+                &vault,
+                &*storage,
+                &client,
+                confirmations::RiskyAction::PushSecrets,
+                format!("Push {} secret(s) to {}", names.len(), target),
+                details,
+            )
+            .await
+            {
+                return Ok(api_error_response(&e));
+            }
+            let violations = github_sync::policy_violations(
+                &*vault.lock().await,
+                &repo,
+                &key_ids,
+                environment.as_deref(),
+            );
+            if let Err(e) =
+                sync_policy::confirm(&confirmations, &vault, &*storage, &client, &violations).await
+            {
+                return Ok(api_error_response(&e));
+            }
+
+            let result = github_sync::push_to_github(
+                &vault,
+                &repo,
+                &key_ids,
+                &token_key_id,
+                environment.as_deref(),
+                &secret_names,
+            )
+            .await;
+            let mut vault_guard = vault.lock().await;
+            let audit = match &result {
+                Ok(results) => {
+                    let failed = results.iter().filter(|r| !r.success).count();
+                    create_audit_log(
+                        "sync_keys_to_github",
+                        "integration",
+                        Some(&repo),
+                        failed == 0,
+                        Some(&format!(
+                            "Synced {} secrets, {} failed (via {})",
+                            results.len() - failed,
+                            failed,
+                            client
+                        )),
+                    )
+                }
+                Err(e) => create_audit_log("sync_keys_to_github", "integration", Some(&repo), false, Some(&e.to_string())),
+            };
+            vault_guard.audit_logs.push(audit);
+            retention::cap_audit_logs(&mut vault_guard);
+            drop(vault_guard);
+            saves.save_later(health.begin_task("vault_save")).await;
+
+            match result {
+                Ok(results) => Ok(Response::builder()
+                    .status(StatusCode::OK)
+                    .header("Content-Type", "application/json")
+                    .body(Full::new(bytes::Bytes::from(serde_json::to_string(&results).unwrap_or_default())))
+                    .unwrap()),
+                Err(e) => Ok(api_error_response(&e)),
+            }
+        }
+
+        // ===============================
+        //  VSCODE AUTO-SYNC HTTP ENDPOINTS
+        // ===============================
+        (&Method::POST, "/api/keys/sync-to-env") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let client = approvals::client_name(req.headers());
+            let requester = clients::current_or_unverified(req.headers());
+            let body = req.into_body();
+            let body_bytes = match body.collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(_) => {
+                    return Ok(api_error_response(&KeyKeeperError::Validation("Cannot read request body".to_string())));
+                }
+            };
+
+            let body_str = String::from_utf8_lossy(&body_bytes);
+            if let Ok(request_data) = serde_json::from_str::<serde_json::Value>(&body_str) {
+                let key_id = request_data["keyId"].as_str().unwrap_or("");
+                let project_path = request_data["projectPath"].as_str().unwrap_or("");
+                let env_file_name = request_data["envFileName"].as_str();
+
+                let preview = request_data["preview"].as_bool().unwrap_or(false);
+
+                // A plaintext sync hands the secret to the file, so it needs
+                // the same approval as reading the key
+                if !preview {
+                    let plaintext = {
+                        let vault_guard = vault.lock().await;
+                        vault_guard.keys.contains_key(key_id)
+                            && env_refs::sync_mode(&vault_guard) == env_refs::EnvSyncMode::Plaintext
+                    };
+                    if plaintext {
+                        if let Err(e) = approvals::authorize(
+                            &approvals,
+                            &vault,
+                            &*storage,
+                            &requester,
+                            &[key_id.to_string()],
+                            "Write to .env file",
+                        )
+                        .await
+                        {
+                            return Ok(api_error_response(&e));
+                        }
+                    }
+                }
+
+                let mut vault_guard = vault.lock().await;
+                let mut planned = plan_key_env_sync(
+                    &vault_guard,
+                    key_id,
+                    project_path,
+                    env_file_name.map(str::to_string),
+                    None,
+                );
+
+                // Dry run: return the diff without writing
+                if preview {
+                    return Ok(match planned {
+                        Ok((_, pending, _)) => {
+                            let response = serde_json::json!({
+                                "success": true,
+                                "preview": true,
+                                "files": [pending.masked_preview()],
+                            });
+                            Response::builder()
+                                .status(StatusCode::OK)
+                                .header("Content-Type", "application/json")
+                                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                                .unwrap()
+                        }
+                        Err(e) => api_error_response(&e),
+                    });
+                }
+
+                // Replacing values already in the file needs confirmation. Only
+                // names are shown, never the values.
+                let overwrite = match &planned {
+                    Ok((var_name, pending, _)) if !pending.updated.is_empty() => {
+                        let env_file = pending.path.display().to_string();
+                        let details: Vec<String> = pending
+                            .updated
+                            .iter()
+                            .map(|name| format!("Overwrite {} in {}", name, env_file))
+                            .collect();
+                        Some((format!("Overwrite {} in {}", var_name, env_file), details))
+                    }
+                    _ => None,
+                };
+                // So does writing a production key into a non-production file
+                let violation = planned.as_ref().ok().and_then(|(_, _, v)| v.clone());
+                if overwrite.is_some() || violation.is_some() {
+                    drop(vault_guard);
+                    if let Some((summary, details)) = overwrite {
+                        if let Err(e) = confirmations::confirm(
+                            &confirmations,
+                            &vault,
+                            &*storage,
+                            &client,
+                            confirmations::RiskyAction::OverwriteEnvFile,
+                            summary,
+                            details,
+                        )
+                        .await
+                        {
+                            return Ok(api_error_response(&e));
+                        }
+                    }
+                    if let Err(e) = sync_policy::confirm(
+                        &confirmations,
+                        &vault,
+                        &*storage,
+                        &client,
+                        violation.as_slice(),
+                    )
+                    .await
+                    {
+                        return Ok(api_error_response(&e));
+                    }
+                    // The key or the file may have changed while waiting
+                    vault_guard = vault.lock().await;
+                    planned = plan_key_env_sync(
+                        &vault_guard,
+                        key_id,
+                        project_path,
+                        env_file_name.map(str::to_string),
+                        None,
+                    );
+                }
+
+                let sync_result = planned.map_err(|e| e.to_string()).and_then(|(var_name, pending, _)| {
+                    let env_file = pending.path.to_string_lossy().to_string();
+                    if !pending.is_changed() {
+                        return Ok(format!("Key {} already up to date in {}", var_name, env_file));
+                    }
+                    env_history::commit(&pending, &vault_guard, Some(project_path), "sync_key_to_env")
+                        .map_err(|e| format!("Failed to write to .env file: {}", e))?;
+                    let verb = if pending.updated.is_empty() { "added" } else { "updated" };
+                    Ok(format!("Successfully {} {} in {}", verb, var_name, env_file))
+                });
+                drop(vault_guard);
+
+                match sync_result {
+                    Ok(message) => {
+                        let response = serde_json::json!({"success": true, "message": message});
+                        Ok(Response::builder()
+                            .status(StatusCode::OK)
+                            .header("Content-Type", "application/json")
+                            .body(Full::new(bytes::Bytes::from(response.to_string())))
+                            .unwrap())
+                    }
+                    Err(error) => Ok(api_error_response(&KeyKeeperError::Internal(error))),
+                }
+            } else {
+                Ok(api_error_response(&KeyKeeperError::Validation("Invalid JSON body".to_string())))
+            }
+        }
+
+        (&Method::DELETE, path) if path.starts_with("/api/keys/") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let key_id = path.strip_prefix("/api/keys/").unwrap_or_default().to_string();
+            let (summary, details) = {
+                let vault_guard = vault.lock().await;
+                let Some(key) = vault_guard.keys.get(&key_id) else {
+                    return Ok(api_error_response(&KeyKeeperError::not_found("API key")));
+                };
+                let mut details = vec![format!(
+                    "Delete {} ({}, {})",
+                    key.name, key.service, key.environment
+                )];
+                if let Some(env_file) = &key.env_file_path {
+                    details.push(format!("{} keeps its current value", env_file));
+                }
+                (format!("Delete the API key {}", key.name), details)
+            };
+
+            let client = approvals::client_name(req.headers());
+            if let Err(e) = confirmations::confirm(
+                &confirmations,
+                &vault,
+                &*storage,
+                &client,
+                confirmations::RiskyAction::DeleteKey,
+                summary,
+                details,
+            )
+            .await
+            {
+                return Ok(api_error_response(&e));
+            }
+
+            let mut vault_guard = vault.lock().await;
+            let actor = activity::Actor::http(req.headers());
+            if remove_key(&mut vault_guard, &key_id, actor).is_none() {
+                return Ok(api_error_response(&KeyKeeperError::not_found("API key")));
+            }
+            drop(vault_guard);
+            oauth_tokens.forget(&key_id);
+            if let Err(e) = saves.save().await {
+                return Ok(api_error_response(&e));
+            }
+
+            let response = serde_json::json!({"success": true});
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::GET, "/api/keys/check-in-env") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let url = req.uri();
+            let query = url.query().unwrap_or("");
+            let mut key_id = "";
+            let mut project_path = "";
+            let mut env_file_name: Option<String> = None;
+
+            for param in query.split('&') {
+                let parts: Vec<&str> = param.split('=').collect();
+                if parts.len() == 2 {
+                    match parts[0] {
+                        "keyId" => key_id = parts[1],
+                        "projectPath" => project_path = parts[1],
+                        "envFileName" => env_file_name = Some(parts[1].to_string()),
+                        _ => {}
+                    }
+                }
+            }
+
+            // Implement check_key_in_env_file logic inline
+            let check_result = {
+                let vault_guard = vault.lock().await;
+
+                // Find the API key
+                if let Some(api_key) = vault_guard.keys.get(key_id) {
+                    // Determine the .env file path
+                    let env_file = env_file_name.unwrap_or(".env".to_string());
+                    let env_file_path = format!("{}/{}", project_path, env_file);
+
+                    // Check if .env file exists and contains the key
+                    match std::fs::read_to_string(&env_file_path) {
+                        Ok(env_content) => {
+                            let var_name = format!(
+                                "{}_API_KEY",
+                                api_key.service.to_uppercase().replace(" ", "_")
+                            );
+                            Ok(env_content.contains(&var_name))
+                        }
+                        Err(_) => Ok(false), // File doesn't exist
+                    }
+                } else {
+                    Err("API key not found".to_string())
+                }
+            };
+
+            match check_result {
+                Ok(exists) => {
+                    let response = serde_json::json!({"exists": exists});
+                    Ok(Response::builder()
+                        .status(StatusCode::OK)
+                        .header("Content-Type", "application/json")
+                        .body(Full::new(bytes::Bytes::from(response.to_string())))
+                        .unwrap())
+                }
+                Err(error) => Ok(api_error_response(&KeyKeeperError::Internal(error))),
+            }
+        }
+
+        (&Method::GET, "/api/projects/env-files") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let url = req.uri();
+            let query = url.query().unwrap_or("");
+            let mut project_path = "";
+
+            for param in query.split('&') {
+                let parts: Vec<&str> = param.split('=').collect();
+                if parts.len() == 2 && parts[0] == "projectPath" {
+                    project_path = parts[1];
+                }
+            }
+
+            // Same as get_env_file_suggestions
+            let suggestions_result: Result<Vec<String>, String> =
+                Ok(project_ignore::env_files(std::path::Path::new(project_path)));
+
+            match suggestions_result {
+                Ok(env_files) => {
+                    let response = serde_json::json!({"envFiles": env_files});
+                    Ok(Response::builder()
+                        .status(StatusCode::OK)
+                        .header("Content-Type", "application/json")
+                        .body(Full::new(bytes::Bytes::from(response.to_string())))
+                        .unwrap())
+                }
+                Err(error) => Ok(api_error_response(&KeyKeeperError::Internal(error))),
+            }
+        }
+
+        (&Method::POST, "/api/workspace/auto-sync") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let client = approvals::client_name(req.headers());
+            let requester = clients::current_or_unverified(req.headers());
+            let body = req.into_body();
+            let body_bytes = match body.collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(_) => {
+                    return Ok(api_error_response(&KeyKeeperError::Validation("Cannot read request body".to_string())));
+                }
+            };
+
+            let body_str = String::from_utf8_lossy(&body_bytes);
+            if let Ok(request_data) = serde_json::from_str::<serde_json::Value>(&body_str) {
+                let workspace_path = request_data["workspacePath"].as_str().unwrap_or("");
+
+                let preview = request_data["preview"].as_bool().unwrap_or(false);
+
+                let mut errors = Vec::new();
+                let mut vault_guard = vault.lock().await;
+                if !preview && incident::auto_sync_paused(&vault_guard) {
+                    return Ok(api_error_response(&KeyKeeperError::Conflict(
+                        "Auto sync is paused while an incident is open".to_string(),
+                    )));
+                }
+                let (mut writes, violations, key_ids) =
+                    plan_workspace_files_sync(&vault_guard, workspace_path, &mut errors);
+
+                // Dry run: return the diffs without writing
+                if preview {
+                    let files: Vec<_> = writes
+                        .iter()
+                        .map(|pending| pending.masked_preview())
+                        .collect();
+                    let response = serde_json::json!({
+                        "success": true,
+                        "preview": true,
+                        "files": files,
+                        "violations": violations,
+                        "errors": errors,
+                    });
+                    return Ok(Response::builder()
+                        .status(StatusCode::OK)
+                        .header("Content-Type", "application/json")
+                        .body(Full::new(bytes::Bytes::from(response.to_string())))
+                        .unwrap());
+                }
+
+                // Plaintext syncs hand the secrets to the files, so they need
+                // the same approval as reading the keys
+                let approve = env_refs::sync_mode(&vault_guard) == env_refs::EnvSyncMode::Plaintext
+                    && !key_ids.is_empty();
+                if approve || !violations.is_empty() {
+                    drop(vault_guard);
+                    if approve {
+                        if let Err(e) = approvals::authorize(
+                            &approvals,
+                            &vault,
+                            &*storage,
+                            &requester,
+                            &key_ids,
+                            "Write to .env files",
+                        )
+                        .await
+                        {
+                            return Ok(api_error_response(&e));
+                        }
+                    }
+                    // Production keys bound for non-production files need confirmation
+                    if let Err(e) = sync_policy::confirm(
+                        &confirmations,
+                        &vault,
+                        &*storage,
+                        &client,
+                        &violations,
+                    )
+                    .await
+                    {
+                        return Ok(api_error_response(&e));
+                    }
+                    // The keys or the files may have changed while waiting
+                    vault_guard = vault.lock().await;
+                    errors.clear();
+                    let (replanned, _, replanned_ids) =
+                        plan_workspace_files_sync(&vault_guard, workspace_path, &mut errors);
+                    writes = replanned;
+                    let unapproved = env_refs::sync_mode(&vault_guard) == env_refs::EnvSyncMode::Plaintext
+                        && replanned_ids.iter().any(|id| !approve || !key_ids.contains(id));
+                    if unapproved {
+                        return Ok(api_error_response(&KeyKeeperError::Conflict(
+                            "The keys to sync changed while waiting; try again".to_string(),
+                        )));
+                    }
+                }
+
+                let sync_result = {
+                    let mut synced_count = 0;
+                    for pending in writes.iter().filter(|pending| pending.is_changed()) {
+                        match env_history::commit(
+                            pending,
+                            &vault_guard,
+                            Some(workspace_path),
+                            "auto_sync_workspace",
+                        ) {
+                            Ok(_) => synced_count += pending.added.len(),
+                            Err(e) => errors.push(format!(
+                                "Failed to write to {}: {}",
+                                pending.path.display(),
+                                e
+                            )),
+                        }
+                    }
+
+                    if errors.is_empty() {
+                        Ok(format!(
+                            "Successfully synced {} API keys to workspace .env files",
+                            synced_count
+                        ))
+                    } else if synced_count > 0 {
+                        Ok(format!(
+                            "Synced {} API keys with {} errors: {}",
+                            synced_count,
+                            errors.len(),
+                            errors.join(", ")
+                        ))
+                    } else {
+                        Err(format!("Failed to sync workspace: {}", errors.join(", ")))
+                    }
+                };
+
+                match sync_result {
+                    Ok(message) => {
+                        let response = serde_json::json!({"success": true, "message": message});
+                        Ok(Response::builder()
+                            .status(StatusCode::OK)
+                            .header("Content-Type", "application/json")
+                            .body(Full::new(bytes::Bytes::from(response.to_string())))
+                            .unwrap())
+                    }
+                    Err(error) => Ok(api_error_response(&KeyKeeperError::Internal(error))),
+                }
+            } else {
+                Ok(api_error_response(&KeyKeeperError::Validation("Invalid JSON body".to_string())))
+            }
+        }
+
+        // ===============================
+        //  DOCUMENTATION HTTP ENDPOINTS
+        // ===============================
+        
+        (&Method::GET, "/api/docs") => {
+            let docs_store_guard = _app_state.docs_store.lock().await;
+            let docs: Vec<Documentation> = docs_store_guard.get_all_docs().into_iter().cloned().collect();
+            let response = serde_json::json!({
+                "success": true,
+                "data": docs
+            });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::POST, "/api/docs/create") => {
+            if let Ok(body_bytes) = req.into_body().collect().await.map(|collected| collected.to_bytes()) {
+                if let Ok(create_request) = serde_json::from_slice::<serde_json::Value>(&body_bytes) {
+                    let title = create_request["title"].as_str().unwrap_or("").to_string();
+                    let content = create_request["content"].as_str().unwrap_or("").to_string();
+                    let doc_type = create_request["doc_type"].as_str().unwrap_or("api").to_string();
+                    let project_id = create_request["project_id"].as_str().map(|s| s.to_string());
+                    let provider_id = create_request["provider_id"].as_str().map(|s| s.to_string());
+                    let url = create_request["url"].as_str().map(|s| s.to_string());
+                    let tags = create_request["tags"].as_array()
+                        .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+                        .unwrap_or_default();
+                    let language = create_request["language"].as_str().unwrap_or("en").to_string();
+
+                    // Generate unique documentation ID
+                    let doc_id = format!("doc_{}", chrono::Utc::now().timestamp_millis());
+
+                    let documentation = Documentation {
+                        id: doc_id.clone(),
+                        title: title.clone(),
+                        content,
+                        doc_type,
+                        project_id,
+                        provider_id,
+                        url,
+                        tags,
+                        created_at: Utc::now(),
+                        updated_at: Utc::now(),
+                        language,
+                        is_favorite: false,
+                        search_keywords: vec![],
+                        sections: Vec::new(),
+                        is_offline: false,
+                        attachments: Vec::new(),
+                    };
+
+                    let mut docs_store_guard = _app_state.docs_store.lock().await;
+                    docs_store_guard.add_doc(documentation.clone());
+                    drop(docs_store_guard);
+
+                    // Save docs store
+                    let docs_store_guard = _app_state.docs_store.lock().await;
+                    let docs_path = &_app_state.docs_store_path;
+                    if let Some(parent) = docs_path.parent() {
+                        if let Err(e) = tokio::fs::create_dir_all(parent).await {
+                            warn!("Failed to create docs store directory: {}", e);
+                        }
+                    }
+                    let serialized = serde_json::to_string_pretty(&*docs_store_guard).unwrap_or_default();
+                    drop(docs_store_guard);
+                    if let Err(e) = tokio::fs::write(docs_path, serialized).await {
+                        warn!("Failed to save docs store: {}", e);
+                    }
+
+                    let response = serde_json::json!({
+                        "success": true,
+                        "data": documentation
+                    });
+                    Ok(Response::builder()
+                        .status(StatusCode::CREATED)
+                        .header("Content-Type", "application/json")
+                        .body(Full::new(bytes::Bytes::from(response.to_string())))
+                        .unwrap())
+                } else {
+                    Ok(api_error_response(&KeyKeeperError::Validation("Invalid JSON body".to_string())))
+                }
+            } else {
+                Ok(api_error_response(&KeyKeeperError::Validation("Failed to read request body".to_string())))
+            }
+        }
+
+        (&Method::GET, "/api/docs/search") => {
+            if !_query.is_empty() {
+                let query_str = _query;
+                let query_params: std::collections::HashMap<_, _> = url::form_urlencoded::parse(query_str.as_bytes()).collect();
+                let search_query = query_params.get("query").map(|s| s.to_string()).unwrap_or_default();
+                let project_id = query_params.get("project_id").map(|s| s.to_string());
+                let doc_type = query_params.get("doc_type").map(|s| s.to_string());
+
+                let docs_store_guard = _app_state.docs_store.lock().await;
+                let mut results: Vec<Documentation> = docs_store_guard
+                    .search_docs(&search_query)
+                    .into_iter()
+                    .filter(|doc| {
+                        let matches_project = project_id.as_ref()
+                            .map(|pid| doc.project_id.as_ref() == Some(pid))
+                            .unwrap_or(true);
+                        let matches_type = doc_type.as_ref()
+                            .map(|dt| &doc.doc_type == dt)
+                            .unwrap_or(true);
+                        matches_project && matches_type
+                    })
+                    .cloned()
+                    .collect();
+
+                let response = serde_json::json!({
+                    "success": true,
+                    "data": results
+                });
+                Ok(Response::builder()
+                    .status(StatusCode::OK)
+                    .header("Content-Type", "application/json")
+                    .body(Full::new(bytes::Bytes::from(response.to_string())))
+                    .unwrap())
+            } else {
+                // Return empty results if no query provided
+                let response = serde_json::json!({
+                    "success": true,
+                    "data": {
+                        "results": Vec::<Documentation>::new(),
+                        "total": 0
+                    }
+                });
+                Ok(Response::builder()
+                    .status(StatusCode::OK)
+                    .header("Content-Type", "application/json")
+                    .body(Full::new(bytes::Bytes::from(response.to_string())))
+                    .unwrap())
+            }
+        }
+
+        (&Method::GET, path) if path.starts_with("/api/docs/project/") => {
+            let project_id = path.strip_prefix("/api/docs/project/").unwrap_or("").to_string();
+            let docs_store_guard = _app_state.docs_store.lock().await;
+            let docs: Vec<Documentation> = docs_store_guard
+                .get_all_docs()
+                .into_iter()
+                .filter(|doc| doc.project_id.as_ref() == Some(&project_id))
+                .cloned()
+                .collect();
+
+            let response = serde_json::json!({
+                "success": true,
+                "data": docs
+            });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::POST, "/api/docs/scrape") => {
+            if let Ok(body_bytes) = req.into_body().collect().await.map(|collected| collected.to_bytes()) {
+                if let Ok(scrape_request) = serde_json::from_slice::<serde_json::Value>(&body_bytes) {
+                    let url = scrape_request["url"].as_str().unwrap_or("").to_string();
+                    let title = scrape_request["title"].as_str().map(|s| s.to_string());
+                    let project_id = scrape_request["project_id"].as_str().map(|s| s.to_string());
+                    let tags = scrape_request["tags"].as_array()
+                        .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+                        .unwrap_or_default();
+
+                    // Use documentation manager to scrape content
+                    let mut docs_manager = docs_manager::DocumentationManager::new_with_llm_proxy(
+                        Arc::clone(&_app_state.llm_proxy)
+                    );
+
+                    match docs_manager.scrape_documentation_content(&url).await {
+                        Ok(scraped_content) => {
+                            let doc_id = format!("doc_{}", chrono::Utc::now().timestamp_millis());
+                            let doc_title = title.unwrap_or_else(|| {
+                                let url_parts: Vec<&str> = url.split('/').collect();
+                                if let Some(domain) = url_parts.get(2) {
+                                    format!("Documentation from {}", domain)
+                                } else {
+                                    "Scraped Documentation".to_string()
+                                }
+                            });
+
+                            let documentation = Documentation {
+                                id: doc_id.clone(),
+                                title: doc_title,
+                                content: scraped_content,
+                                doc_type: "scraped".to_string(),
+                                project_id,
+                                provider_id: None,
+                                url: Some(url),
+                                tags,
+                                created_at: Utc::now(),
+                                updated_at: Utc::now(),
+                                language: "en".to_string(),
+                                is_favorite: false,
+                                search_keywords: vec![],
+                                sections: Vec::new(),
+                                is_offline: false,
+                                attachments: Vec::new(),
+                            };
+
+                            let mut docs_store_guard = _app_state.docs_store.lock().await;
+                            docs_store_guard.add_doc(documentation.clone());
+                            drop(docs_store_guard);
+
+                            let docs_store_guard = _app_state.docs_store.lock().await;
+                        let docs_path = &_app_state.docs_store_path;
+                        if let Some(parent) = docs_path.parent() {
+                            if let Err(e) = tokio::fs::create_dir_all(parent).await {
+                                warn!("Failed to create docs store directory: {}", e);
+                            }
+                        }
+                        let serialized = serde_json::to_string_pretty(&*docs_store_guard).unwrap_or_default();
+                        drop(docs_store_guard);
+                        if let Err(e) = tokio::fs::write(docs_path, serialized).await {
+                                warn!("Failed to save docs store: {}", e);
+                            }
+
+                            let response = serde_json::json!({
+                                "success": true,
+                                "data": documentation
+                            });
+                            Ok(Response::builder()
+                                .status(StatusCode::CREATED)
+                                .header("Content-Type", "application/json")
+                                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                                .unwrap())
+                        }
+                        Err(e) => {
+                            Ok(api_error_response(&KeyKeeperError::Internal(format!("Failed to scrape: {}", e))))
+                        }
+                    }
+                } else {
+                    Ok(api_error_response(&KeyKeeperError::Validation("Invalid JSON body".to_string())))
+                }
+            } else {
+                Ok(api_error_response(&KeyKeeperError::Validation("Failed to read request body".to_string())))
+            }
+        }
+
+        (&Method::GET, "/api/snippets") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let provider = req.uri().query().and_then(|query| {
+                query.split('&').find_map(|param| {
+                    param
+                        .strip_prefix("provider=")
+                        .map(|value| urlencoding::decode(value).unwrap_or_default().to_string())
+                })
+            });
+            let vault_guard = vault.lock().await;
+            let snippets = snippets::list(&vault_guard, provider.as_deref());
+            drop(vault_guard);
+
+            let response = serde_json::json!({
+                "success": true,
+                "data": snippets
+            });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::POST, path) if path.starts_with("/api/snippets/") && path.ends_with("/render") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let snippet_id = path
+                .strip_prefix("/api/snippets/")
+                .and_then(|s| s.strip_suffix("/render"))
+                .unwrap_or("")
+                .to_string();
+            let body = match req.into_body().collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(e) => {
+                    return Ok(api_error_response(&KeyKeeperError::Validation(format!(
+                        "Failed to read request body: {}",
+                        e
+                    ))))
+                }
+            };
+            let request: serde_json::Value = match serde_json::from_slice(&body) {
+                Ok(value) => value,
+                Err(e) => return Ok(api_error_response(&KeyKeeperError::from(e))),
+            };
+            let Some(key_id) = request["key_id"].as_str() else {
+                return Ok(api_error_response(&KeyKeeperError::Validation(
+                    "key_id is required".to_string(),
+                )));
+            };
+            let values: HashMap<String, String> =
+                serde_json::from_value(request["values"].clone()).unwrap_or_default();
+
+            let vault_guard = vault.lock().await;
+            let rendered = snippets::render_stored(&vault_guard, &snippet_id, key_id, &values);
+            drop(vault_guard);
+
+            match rendered {
+                Ok(rendered) => {
+                    let response = serde_json::json!({
+                        "success": true,
+                        "data": rendered
+                    });
+                    Ok(Response::builder()
+                        .status(StatusCode::OK)
+                        .header("Content-Type", "application/json")
+                        .body(Full::new(bytes::Bytes::from(response.to_string())))
+                        .unwrap())
+                }
+                Err(e) => Ok(api_error_response(&e)),
+            }
+        }
+
+        (&Method::GET, "/api/providers") => {
+            // Get API providers using the existing command
+            let api_generator_guard = _app_state.api_generator.lock().await;
+            let service_guard = api_generator_guard.service.lock().await;
+            let providers = service_guard.get_providers();
+            drop(service_guard);
+            drop(api_generator_guard);
+            
+            let response = serde_json::json!({
+                "success": true,
+                "data": providers
+            });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::POST, "/api/providers/detect") => {
+            if let Ok(body_bytes) = req.into_body().collect().await.map(|collected| collected.to_bytes()) {
+                if let Ok(detect_request) = serde_json::from_slice::<serde_json::Value>(&body_bytes) {
+                    let file_path = detect_request["file_path"].as_str().unwrap_or("").to_string();
+                    
+                    // Simple detection based on file content
+                    let api_generator_guard = _app_state.api_generator.lock().await;
+                    let service_guard = api_generator_guard.service.lock().await;
+                    let providers = service_guard.get_providers();
+                    drop(service_guard);
+                    drop(api_generator_guard);
+                    
+                    let mut detection_results = Vec::new();
+                    
+                    // Read file content if possible and check for patterns
+                    if let Ok(content) = tokio::fs::read_to_string(&file_path).await {
+                        for provider in providers {
+                            let mut matched_patterns = Vec::new();
+                            let mut confidence = 0.0f64;
+                            
+                            // Check env patterns
+                            for pattern in &provider.env_patterns {
+                                if content.contains(pattern) {
+                                    matched_patterns.push(pattern.clone());
+                                    confidence += 0.3;
+                                }
+                            }
+                            
+                            // Check key patterns  
+                            for pattern in &provider.key_patterns {
+                                if content.contains(pattern) {
+                                    matched_patterns.push(pattern.clone());
+                                    confidence += 0.3;
+                                }
+                            }
+                            
+                            if confidence > 0.0 {
+                                detection_results.push(serde_json::json!({
+                                    "provider": provider,
+                                    "confidence": confidence.min(1.0f64),
+                                    "matched_patterns": matched_patterns,
+                                    "detected_env_vars": Vec::<String>::new()
+                                }));
+                            }
+                        }
+                    }
+                    
+                    let result = if detection_results.is_empty() {
+                        None::<serde_json::Value>
+                    } else {
+                        detection_results.first().cloned()
+                    };
+                    
+                    let response = serde_json::json!({
+                        "success": true,
+                        "data": result
+                    });
+                    Ok(Response::builder()
+                        .status(StatusCode::OK)
+                        .header("Content-Type", "application/json")
+                        .body(Full::new(bytes::Bytes::from(response.to_string())))
+                        .unwrap())
+                } else {
+                    Ok(api_error_response(&KeyKeeperError::Validation("Invalid JSON body".to_string())))
+                }
+            } else {
+                Ok(api_error_response(&KeyKeeperError::Validation("Failed to read request body".to_string())))
+            }
+        }
+
+        (&Method::POST, "/api/generate/config") => {
+            if let Ok(body_bytes) = req.into_body().collect().await.map(|collected| collected.to_bytes()) {
+                if let Ok(generate_request) = serde_json::from_slice::<api_generator::GenerationRequest>(&body_bytes) {
+                    // Use existing API generator service directly
+                    let api_generator_guard = _app_state.api_generator.lock().await;
+                    let service_guard = api_generator_guard.service.lock().await;
+                    match service_guard.generate_configuration(generate_request).await {
+                        Ok(config) => {
+                            let response = serde_json::json!({
+                                "success": true,
+                                "data": config
+                            });
+                            Ok(Response::builder()
+                                .status(StatusCode::OK)
+                                .header("Content-Type", "application/json")
+                                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                                .unwrap())
+                        }
+                        Err(e) => {
+                            Ok(api_error_response(&KeyKeeperError::Internal(format!("Config generation failed: {}", e))))
+                        }
+                    }
+                } else {
+                    Ok(api_error_response(&KeyKeeperError::Validation("Invalid JSON body".to_string())))
+                }
+            } else {
+                Ok(api_error_response(&KeyKeeperError::Validation("Failed to read request body".to_string())))
+            }
+        }
+
+        (&Method::POST, "/api/generate/preview") => {
+            if let Ok(body_bytes) = req.into_body().collect().await.map(|collected| collected.to_bytes()) {
+                if let Ok(generate_request) = serde_json::from_slice::<api_generator::GenerationRequest>(&body_bytes) {
+                    // Use existing API generator service directly
+                    let api_generator_guard = _app_state.api_generator.lock().await;
+                    let service_guard = api_generator_guard.service.lock().await;
+                    match service_guard.generate_configuration(generate_request).await {
+                        Ok(preview) => {
+                            let response = serde_json::json!({
+                                "success": true,
+                                "data": preview
+                            });
+                            Ok(Response::builder()
+                                .status(StatusCode::OK)
+                                .header("Content-Type", "application/json")
+                                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                                .unwrap())
+                        }
+                        Err(e) => {
+                            Ok(api_error_response(&KeyKeeperError::Internal(format!("Preview generation failed: {}", e))))
+                        }
+                    }
+                } else {
+                    Ok(api_error_response(&KeyKeeperError::Validation("Invalid JSON body".to_string())))
+                }
+            } else {
+                Ok(api_error_response(&KeyKeeperError::Validation("Failed to read request body".to_string())))
+            }
+        }
+
+        // DELETE, PUT endpoint for documentation
+        (&Method::PUT, path) if path.starts_with("/api/docs/") => {
+            let doc_id = path.strip_prefix("/api/docs/").unwrap_or("").to_string();
+            if doc_id.is_empty() {
+                return Ok(api_error_response(&KeyKeeperError::Validation("Missing document ID".to_string())));
+            }
+
+            if let Ok(body_bytes) = req.into_body().collect().await.map(|collected| collected.to_bytes()) {
+                if let Ok(update_request) = serde_json::from_slice::<serde_json::Value>(&body_bytes) {
+                    let mut docs_store_guard = _app_state.docs_store.lock().await;
+                    
+                    if let Some(mut doc) = docs_store_guard.get_doc(&doc_id).cloned() {
+                        // Update fields if provided
+                        if let Some(title) = update_request["title"].as_str() {
+                            doc.title = title.to_string();
+                        }
+                        if let Some(content) = update_request["content"].as_str() {
+                            doc.content = content.to_string();
+                        }
+                        if let Some(doc_type) = update_request["doc_type"].as_str() {
+                            doc.doc_type = doc_type.to_string();
+                        }
+                        if let Some(is_favorite) = update_request["is_favorite"].as_bool() {
+                            doc.is_favorite = is_favorite;
+                        }
+                        doc.updated_at = Utc::now();
+
+                        docs_store_guard.update_doc(doc.clone());
+                        drop(docs_store_guard);
+
+                        let docs_store_guard = _app_state.docs_store.lock().await;
+                        let docs_path = &_app_state.docs_store_path;
+                        if let Some(parent) = docs_path.parent() {
+                            if let Err(e) = tokio::fs::create_dir_all(parent).await {
+                                warn!("Failed to create docs store directory: {}", e);
+                            }
+                        }
+                        let serialized = serde_json::to_string_pretty(&*docs_store_guard).unwrap_or_default();
+                        drop(docs_store_guard);
+                        if let Err(e) = tokio::fs::write(docs_path, serialized).await {
+                            warn!("Failed to save docs store: {}", e);
+                        }
+
+                        let response = serde_json::json!({
+                            "success": true,
+                            "data": doc
+                        });
+                        Ok(Response::builder()
+                            .status(StatusCode::OK)
+                            .header("Content-Type", "application/json")
+                            .body(Full::new(bytes::Bytes::from(response.to_string())))
+                            .unwrap())
+                    } else {
+                        Ok(api_error_response(&KeyKeeperError::not_found("Documentation")))
+                    }
+                } else {
+                    Ok(api_error_response(&KeyKeeperError::Validation("Invalid JSON body".to_string())))
+                }
+            } else {
+                Ok(api_error_response(&KeyKeeperError::Validation("Failed to read request body".to_string())))
+            }
+        }
+
+        (&Method::DELETE, path) if path.starts_with("/api/docs/") => {
+            let doc_id = path.strip_prefix("/api/docs/").unwrap_or("").to_string();
+            if doc_id.is_empty() {
+                return Ok(api_error_response(&KeyKeeperError::Validation("Missing document ID".to_string())));
+            }
+
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let title = _app_state
+                .docs_store
+                .lock()
+                .await
+                .get_doc(&doc_id)
+                .map(|doc| doc.title.clone());
+            if let Some(title) = title {
+                let client = approvals::client_name(req.headers());
+                if let Err(e) = confirmations::confirm(
+                    &confirmations,
+                    &vault,
+                    &*storage,
+                    &client,
+                    confirmations::RiskyAction::DeleteDocument,
+                    format!("Delete the document \"{}\"", title),
+                    vec![format!("Remove \"{}\" from the documentation library", title)],
+                )
+                .await
+                {
+                    return Ok(api_error_response(&e));
+                }
+            }
+
+            let mut docs_store_guard = _app_state.docs_store.lock().await;
+            match docs_store_guard.remove_doc(&doc_id) {
+                Some(_) => {
+                    drop(docs_store_guard);
+                    
+                    let docs_store_guard = _app_state.docs_store.lock().await;
+                    let docs_path = &_app_state.docs_store_path;
+                    if let Some(parent) = docs_path.parent() {
+                        if let Err(e) = tokio::fs::create_dir_all(parent).await {
+                            warn!("Failed to create docs store directory: {}", e);
+                        }
+                    }
+                    let serialized = serde_json::to_string_pretty(&*docs_store_guard).unwrap_or_default();
+                    drop(docs_store_guard);
+                    if let Err(e) = tokio::fs::write(docs_path, serialized).await {
+                        warn!("Failed to save docs store: {}", e);
+                    }
+
+                    let response = serde_json::json!({
+                        "success": true,
+                        "message": "Documentation deleted successfully"
+                    });
+                    Ok(Response::builder()
+                        .status(StatusCode::OK)
+                        .header("Content-Type", "application/json")
+                        .body(Full::new(bytes::Bytes::from(response.to_string())))
+                        .unwrap())
+                }
+                None => {
+                    Ok(api_error_response(&KeyKeeperError::not_found("Documentation")))
+                }
+            }
+        }
+
+        (&Method::POST, path) if path.starts_with("/api/docs/") && path.ends_with("/toggle-favorite") => {
+            let doc_id = path.strip_prefix("/api/docs/")
+                .and_then(|s| s.strip_suffix("/toggle-favorite"))
+                .unwrap_or("")
+                .to_string();
+            
+            if doc_id.is_empty() {
+                return Ok(api_error_response(&KeyKeeperError::Validation("Missing document ID".to_string())));
+            }
+
+            let mut docs_store_guard = _app_state.docs_store.lock().await;
+            
+            if let Some(mut doc) = docs_store_guard.get_doc(&doc_id).cloned() {
+                doc.is_favorite = !doc.is_favorite;
+                doc.updated_at = Utc::now();
+
+                docs_store_guard.update_doc(doc.clone());
+                drop(docs_store_guard);
+
+                let docs_store_guard = _app_state.docs_store.lock().await;
+                let docs_path = &_app_state.docs_store_path;
+                if let Some(parent) = docs_path.parent() {
+                    if let Err(e) = tokio::fs::create_dir_all(parent).await {
+                        warn!("Failed to create docs store directory: {}", e);
+                    }
+                }
+                let serialized = serde_json::to_string_pretty(&*docs_store_guard).unwrap_or_default();
+                drop(docs_store_guard);
+                if let Err(e) = tokio::fs::write(docs_path, serialized).await {
+                    warn!("Failed to save docs store: {}", e);
+                }
+
+                let response = serde_json::json!({
+                    "success": true,
+                    "data": doc
+                });
+                Ok(Response::builder()
+                    .status(StatusCode::OK)
+                    .header("Content-Type", "application/json")
+                    .body(Full::new(bytes::Bytes::from(response.to_string())))
+                    .unwrap())
+            } else {
+                Ok(api_error_response(&KeyKeeperError::not_found("Documentation")))
+            }
+        }
+
+        // ===============================
+        //  CHAT SYSTEM HTTP ENDPOINTS
+        // ===============================
+
+        (&Method::POST, "/api/chat/sessions") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            if let Ok(body_bytes) = req.into_body().collect().await.map(|collected| collected.to_bytes()) {
+                if let Ok(session_request) = serde_json::from_slice::<serde_json::Value>(&body_bytes) {
+                    let session_name = session_request["name"].as_str().unwrap_or("New Chat Session").to_string();
+                    let user_id = session_request["user_id"].as_str().unwrap_or("default_user").to_string();
+                    let llm_provider = session_request["llm_provider"].as_str().unwrap_or("default").to_string();
+                    let system_prompt = session_request["system_prompt"].as_str().map(|s| s.to_string());
+
+                    // Create new chat session using chat_engine
+                    let session_id = format!("chat_{}", chrono::Utc::now().timestamp_millis());
+                    
+                    let session_data = serde_json::json!({
+                        "id": session_id,
+                        "name": session_name,
+                        "user_id": user_id,
+                        "llm_provider": llm_provider,
+                        "system_prompt": system_prompt,
+                        "created_at": chrono::Utc::now().to_rfc3339(),
+                        "updated_at": chrono::Utc::now().to_rfc3339(),
+                        "message_count": 0,
+                        "is_active": true
+                    });
+
+                    let response = serde_json::json!({
+                        "success": true,
+                        "data": session_data
+                    });
+                    Ok(Response::builder()
+                        .status(StatusCode::CREATED)
+                        .header("Content-Type", "application/json")
+                        .body(Full::new(bytes::Bytes::from(response.to_string())))
+                        .unwrap())
+                } else {
+                    Ok(api_error_response(&KeyKeeperError::Validation("Invalid JSON body".to_string())))
+                }
+            } else {
+                Ok(api_error_response(&KeyKeeperError::Validation("Failed to read request body".to_string())))
+            }
+        }
+
+        (&Method::GET, "/api/chat/sessions") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let url = req.uri();
+            let query = url.query().unwrap_or("");
+            let query_params: std::collections::HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes()).collect();
+            let user_id = query_params.get("user_id").map(|s| s.to_string()).unwrap_or_else(|| "default_user".to_string());
+            let limit = query_params.get("limit").and_then(|s| s.parse::<usize>().ok()).unwrap_or(50);
+
+            // Mock data - in real implementation, this would query from a database
+            let sessions = vec![
+                serde_json::json!({
+                    "id": "chat_1",
+                    "name": "API Documentation Chat",
+                    "user_id": user_id,
+                    "created_at": chrono::Utc::now().to_rfc3339(),
+                    "updated_at": chrono::Utc::now().to_rfc3339(),
+                    "message_count": 5,
+                    "is_active": true
+                }),
+                serde_json::json!({
+                    "id": "chat_2",
+                    "name": "Project Setup Help",
+                    "user_id": user_id,
+                    "created_at": chrono::Utc::now().to_rfc3339(),
+                    "updated_at": chrono::Utc::now().to_rfc3339(),
+                    "message_count": 12,
+                    "is_active": true
+                })
+            ];
+
+            let response = serde_json::json!({
+                "success": true,
+                "data": sessions.into_iter().take(limit).collect::<Vec<_>>()
+            });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::POST, path) if path.starts_with("/api/chat/sessions/") && path.ends_with("/messages") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let session_id = path.strip_prefix("/api/chat/sessions/")
+                .and_then(|s| s.strip_suffix("/messages"))
+                .unwrap_or("").to_string();
+
+            if session_id.is_empty() {
+                return Ok(api_error_response(&KeyKeeperError::Validation("Missing session ID".to_string())));
+            }
+
+            if let Ok(body_bytes) = req.into_body().collect().await.map(|collected| collected.to_bytes()) {
+                if let Ok(message_request) = serde_json::from_slice::<serde_json::Value>(&body_bytes) {
+                    let message_content = message_request["message"].as_str().unwrap_or("").to_string();
+                    let user_id = message_request["user_id"].as_str().unwrap_or("default_user").to_string();
+                    let include_docs = message_request["include_docs"].as_bool().unwrap_or(false);
+
+                    // Process message with chat engine
+                    let message_id = format!("msg_{}", chrono::Utc::now().timestamp_millis());
+                    
+                    // Mock response - in real implementation, this would use the actual chat engine
+                    let ai_response = if include_docs {
+                        format!("Based on the documentation, here's what I found about '{}': This is a comprehensive response that incorporates relevant documentation...", message_content)
+                    } else {
+                        format!("I understand you're asking about '{}'. Here's my response...", message_content)
+                    };
+
+                    let user_message = serde_json::json!({
+                        "id": message_id.clone(),
+                        "session_id": session_id,
+                        "role": "user",
+                        "content": message_content,
+                        "user_id": user_id,
+                        "timestamp": chrono::Utc::now().to_rfc3339(),
+                        "include_docs": include_docs
+                    });
+
+                    let ai_message = serde_json::json!({
+                        "id": format!("msg_{}", chrono::Utc::now().timestamp_millis() + 1),
+                        "session_id": session_id,
+                        "role": "assistant",
+                        "content": ai_response,
+                        "user_id": user_id,
+                        "timestamp": chrono::Utc::now().to_rfc3339(),
+                        "sources": if include_docs { vec!["Documentation Library"] } else { vec![] }
+                    });
+
+                    let response = serde_json::json!({
+                        "success": true,
+                        "data": {
+                            "user_message": user_message,
+                            "ai_response": ai_message
+                        }
+                    });
+                    Ok(Response::builder()
+                        .status(StatusCode::CREATED)
+                        .header("Content-Type", "application/json")
+                        .body(Full::new(bytes::Bytes::from(response.to_string())))
+                        .unwrap())
+                } else {
+                    Ok(api_error_response(&KeyKeeperError::Validation("Invalid JSON body".to_string())))
+                }
+            } else {
+                Ok(api_error_response(&KeyKeeperError::Validation("Failed to read request body".to_string())))
+            }
+        }
+
+        (&Method::GET, path) if path.starts_with("/api/chat/sessions/") && path.ends_with("/messages") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let session_id = path.strip_prefix("/api/chat/sessions/")
+                .and_then(|s| s.strip_suffix("/messages"))
+                .unwrap_or("").to_string();
+
+            if session_id.is_empty() {
+                return Ok(api_error_response(&KeyKeeperError::Validation("Missing session ID".to_string())));
+            }
+
+            let url = req.uri();
+            let query = url.query().unwrap_or("");
+            let query_params: std::collections::HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes()).collect();
+            let limit = query_params.get("limit").and_then(|s| s.parse::<usize>().ok()).unwrap_or(50);
+            let offset = query_params.get("offset").and_then(|s| s.parse::<usize>().ok()).unwrap_or(0);
+
+            // Mock messages - in real implementation, this would query from a database
+            let messages = vec![
+                serde_json::json!({
+                    "id": "msg_1",
+                    "session_id": session_id,
+                    "role": "user",
+                    "content": "How do I set up API keys for my project?",
+                    "timestamp": chrono::Utc::now().to_rfc3339(),
+                    "user_id": "default_user"
+                }),
+                serde_json::json!({
+                    "id": "msg_2",
+                    "session_id": session_id,
+                    "role": "assistant",
+                    "content": "To set up API keys for your project, you can use KeyKeeper to manage them securely. Here's how to get started...",
+                    "timestamp": chrono::Utc::now().to_rfc3339(),
+                    "sources": ["KeyKeeper Documentation"]
+                })
+            ];
+
+            let paginated_messages = messages.into_iter().skip(offset).take(limit).collect::<Vec<_>>();
+
+            let response = serde_json::json!({
+                "success": true,
+                "data": {
+                    "messages": paginated_messages,
+                    "total": 2,
+                    "offset": offset,
+                    "limit": limit
+                }
+            });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::DELETE, path) if path.starts_with("/api/chat/sessions/") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let session_id = path.strip_prefix("/api/chat/sessions/").unwrap_or("").to_string();
+            if session_id.is_empty() {
+                return Ok(api_error_response(&KeyKeeperError::Validation("Missing session ID".to_string())));
+            }
+
+            // Mock deletion - in real implementation, this would delete from database
+            let response = serde_json::json!({
+                "success": true,
+                "message": format!("Chat session {} deleted successfully", session_id)
+            });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::POST, "/api/chat/search-docs") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            if let Ok(body_bytes) = req.into_body().collect().await.map(|collected| collected.to_bytes()) {
+                if let Ok(search_request) = serde_json::from_slice::<serde_json::Value>(&body_bytes) {
+                    let query = search_request["query"].as_str().unwrap_or("").to_string();
+                    let limit = search_request["limit"].as_u64().unwrap_or(10) as usize;
+                    let doc_types = search_request["doc_types"].as_array()
+                        .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect::<Vec<_>>())
+                        .unwrap_or_default();
+
+                    if query.is_empty() {
+                        return Ok(api_error_response(&KeyKeeperError::Validation("Search query is required".to_string())));
+                    }
+
+                    // Search documentation using existing docs store
+                    let docs_store_guard = _app_state.docs_store.lock().await;
+                    let results = docs_store_guard.search_docs(&query);
+                    
+                    // Filter by doc types if specified and collect into owned Vec
+                    let filtered_results: Vec<_> = if !doc_types.is_empty() {
+                        results.into_iter()
+                            .filter(|doc| doc_types.contains(&doc.doc_type))
+                            .cloned()
+                            .collect()
+                    } else {
+                        results.into_iter().cloned().collect()
+                    };
+                    
+                    let limited_results = filtered_results.into_iter().take(limit).collect::<Vec<_>>();
+                    let total_found = limited_results.len();
+                    drop(docs_store_guard);
+
+                    let response = serde_json::json!({
+                        "success": true,
+                        "data": {
+                            "results": limited_results,
+                            "query": query,
+                            "total_found": total_found
+                        }
+                    });
+                    Ok(Response::builder()
+                        .status(StatusCode::OK)
+                        .header("Content-Type", "application/json")
+                        .body(Full::new(bytes::Bytes::from(response.to_string())))
+                        .unwrap())
+                } else {
+                    Ok(api_error_response(&KeyKeeperError::Validation("Invalid JSON body".to_string())))
+                }
+            } else {
+                Ok(api_error_response(&KeyKeeperError::Validation("Failed to read request body".to_string())))
+            }
+        }
+
+        // ===============================
+        //  ML ENGINE HTTP ENDPOINTS
+        // ===============================
+
+        (&Method::GET, "/api/ml/models") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let models = vec![
+                serde_json::json!({
+                    "id": "qwen3-7b",
+                    "name": "Qwen3 7B",
+                    "provider": "Qwen",
+                    "capabilities": ["chat", "code", "docs"],
+                    "license": "Apache 2.0",
+                    "available": true
+                }),
+                serde_json::json!({
+                    "id": "qwen3-32b",
+                    "name": "Qwen3 32B",
+                    "provider": "Qwen",
+                    "capabilities": ["chat", "code", "docs", "analysis"],
+                    "license": "Apache 2.0",
+                    "available": true
+                })
+            ];
+
+            let response = serde_json::json!({
+                "success": true,
+                "data": models
+            });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::POST, "/api/ml/configure") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            if let Ok(body_bytes) = req.into_body().collect().await.map(|collected| collected.to_bytes()) {
+                if let Ok(config_request) = serde_json::from_slice::<serde_json::Value>(&body_bytes) {
+                    let model_id = config_request["model_id"].as_str().unwrap_or("qwen3-7b");
+                    let provider = config_request["provider"].as_str().unwrap_or("local");
+                    let api_key = config_request["api_key"].as_str();
+                    let endpoint = config_request["endpoint"].as_str();
+                    
+                    let config = serde_json::json!({
+                        "model_id": model_id,
+                        "provider": provider,
+                        "api_key": api_key,
+                        "endpoint": endpoint,
+                        "configured_at": chrono::Utc::now().to_rfc3339(),
+                        "status": "active"
+                    });
+
+                    let response = serde_json::json!({
+                        "success": true,
+                        "data": config
+                    });
+                    Ok(Response::builder()
+                        .status(StatusCode::OK)
+                        .header("Content-Type", "application/json")
+                        .body(Full::new(bytes::Bytes::from(response.to_string())))
+                        .unwrap())
+                } else {
+                    Ok(api_error_response(&KeyKeeperError::Validation("Invalid JSON".to_string())))
+                }
+            } else {
+                Ok(api_error_response(&KeyKeeperError::Validation("Failed to read body".to_string())))
+            }
+        }
+
+        (&Method::POST, "/api/ml/generate/docs") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            if let Ok(body_bytes) = req.into_body().collect().await.map(|collected| collected.to_bytes()) {
+                if let Ok(request) = serde_json::from_slice::<serde_json::Value>(&body_bytes) {
+                    let project_path = request["project_path"].as_str().unwrap_or("");
+                    let doc_type = request["doc_type"].as_str().unwrap_or("api");
+                    let language = request["language"].as_str().unwrap_or("en");
+                    
+                    let generated_docs = serde_json::json!({
+                        "title": format!("Generated {} Documentation", doc_type),
+                        "content": format!("# Generated Documentation\n\nThis is AI-generated documentation for project at {}\n\n## Overview\n\nThis documentation was automatically generated using ML analysis...", project_path),
+                        "sections": [
+                            {"title": "Getting Started", "content": "Quick start guide..."},
+                            {"title": "API Reference", "content": "Complete API reference..."}
+                        ],
+                        "generated_at": chrono::Utc::now().to_rfc3339(),
+                        "language": language
+                    });
+
+                    let response = serde_json::json!({
+                        "success": true,
+                        "data": generated_docs
+                    });
+                    Ok(Response::builder()
+                        .status(StatusCode::OK)
+                        .header("Content-Type", "application/json")
+                        .body(Full::new(bytes::Bytes::from(response.to_string())))
+                        .unwrap())
+                } else {
+                    Ok(api_error_response(&KeyKeeperError::Validation("Invalid JSON".to_string())))
+                }
+            } else {
+                Ok(api_error_response(&KeyKeeperError::Validation("Failed to read body".to_string())))
+            }
+        }
+
+        (&Method::POST, "/api/ml/generate/code") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            if let Ok(body_bytes) = req.into_body().collect().await.map(|collected| collected.to_bytes()) {
+                if let Ok(request) = serde_json::from_slice::<serde_json::Value>(&body_bytes) {
+                    let prompt = request["prompt"].as_str().unwrap_or("");
+                    let language = request["language"].as_str().unwrap_or("javascript");
+                    let framework = request["framework"].as_str();
+                    
+                    let generated_code = match language {
+                        "javascript" => "// Generated JavaScript code\nfunction exampleFunction() {\n    console.log('Hello from AI!');\n}\n\nexport default exampleFunction;",
+                        "python" => "# Generated Python code\ndef example_function():\n    print('Hello from AI!')\n\nif __name__ == '__main__':\n    example_function()",
+                        "rust" => "// Generated Rust code\nfn example_function() {\n    println!(\"Hello from AI!\");\n}\n\nfn main() {\n    example_function();\n}",
+                        _ => "// Generated code\nconsole.log('Hello from AI!');"
+                    };
+                    
+                    let result = serde_json::json!({
+                        "code": generated_code,
+                        "language": language,
+                        "framework": framework,
+                        "prompt": prompt,
+                        "generated_at": chrono::Utc::now().to_rfc3339(),
+                        "suggestions": ["Add error handling", "Consider unit tests", "Add type annotations"]
+                    });
+
+                    let response = serde_json::json!({
+                        "success": true,
+                        "data": result
+                    });
+                    Ok(Response::builder()
+                        .status(StatusCode::OK)
+                        .header("Content-Type", "application/json")
+                        .body(Full::new(bytes::Bytes::from(response.to_string())))
+                        .unwrap())
+                } else {
+                    Ok(api_error_response(&KeyKeeperError::Validation("Invalid JSON".to_string())))
+                }
+            } else {
+                Ok(api_error_response(&KeyKeeperError::Validation("Failed to read body".to_string())))
+            }
+        }
+
+        (&Method::POST, "/api/ml/analyze") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            if let Ok(body_bytes) = req.into_body().collect().await.map(|collected| collected.to_bytes()) {
+                if let Ok(request) = serde_json::from_slice::<serde_json::Value>(&body_bytes) {
+                    let project_path = request["project_path"].as_str().unwrap_or("");
+                    let analysis_type = request["analysis_type"].as_str().unwrap_or("comprehensive");
+                    
+                    let analysis = serde_json::json!({
+                        "project_path": project_path,
+                        "analysis_type": analysis_type,
+                        "summary": "This project uses modern JavaScript/TypeScript with React framework.",
+                        "technologies": ["React", "TypeScript", "Tailwind CSS", "Node.js"],
+                        "recommendations": [
+                            "Add more comprehensive error handling",
+                            "Consider implementing unit tests",
+                            "Optimize bundle size"
+                        ],
+                        "security_issues": [],
+                        "performance_insights": {
+                            "bundle_size": "Large",
+                            "loading_time": "Good",
+                            "suggestions": ["Code splitting", "Lazy loading"]
+                        },
+                        "analyzed_at": chrono::Utc::now().to_rfc3339()
+                    });
+
+                    let response = serde_json::json!({
+                        "success": true,
+                        "data": analysis
+                    });
+                    Ok(Response::builder()
+                        .status(StatusCode::OK)
+                        .header("Content-Type", "application/json")
+                        .body(Full::new(bytes::Bytes::from(response.to_string())))
+                        .unwrap())
+                } else {
+                    Ok(api_error_response(&KeyKeeperError::Validation("Invalid JSON".to_string())))
+                }
+            } else {
+                Ok(api_error_response(&KeyKeeperError::Validation("Failed to read body".to_string())))
+            }
+        }
+
+        (&Method::GET, "/api/ml/status") => {
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let status = serde_json::json!({
+                "ml_engine": "active",
+                "current_model": "qwen3-7b",
+                "provider": "local",
+                "capabilities": ["chat", "code_generation", "documentation", "analysis"],
+                "last_used": chrono::Utc::now().to_rfc3339(),
+                "performance": {
+                    "avg_response_time": "1.2s",
+                    "success_rate": "98.5%",
+                    "total_requests": 1247
+                },
+                "license": "Apache 2.0",
+                "version": "1.0.0"
+            });
+
+            let response = serde_json::json!({
+                "success": true,
+                "data": status
+            });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        // Missing ML endpoints called by VSCode extension
+        (&Method::POST, "/api/ml/initialize") => {
+            // Vault lock check
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let response = serde_json::json!({
+                "success": true,
+                "data": {
+                    "status": "initialized",
+                    "model": "qwen3-7b",
+                    "provider": "local",
+                    "initialized_at": chrono::Utc::now().to_rfc3339()
+                }
+            });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::POST, "/api/ml/record-usage") => {
+            // Vault lock check
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let response = serde_json::json!({
+                "success": true,
+                "data": {
+                    "recorded": true,
+                    "recorded_at": chrono::Utc::now().to_rfc3339()
+                }
+            });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::GET, "/api/ml/stats") => {
+            // Vault lock check
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let stats = serde_json::json!({
+                "total_requests": 1247,
+                "successful_requests": 1229,
+                "failed_requests": 18,
+                "average_response_time": 1.2,
+                "models_used": {
+                    "qwen3-7b": 856,
+                    "qwen3-32b": 391
+                },
+                "usage_by_type": {
+                    "code_generation": 523,
+                    "documentation": 401,
+                    "analysis": 323
+                },
+                "last_updated": chrono::Utc::now().to_rfc3339()
+            });
+
+            let response = serde_json::json!({
+                "success": true,
+                "data": stats
+            });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::POST, "/api/ml/reinitialize") => {
+            // Vault lock check
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let response = serde_json::json!({
+                "success": true,
+                "data": {
+                    "status": "reinitialized",
+                    "model": "qwen3-7b",
+                    "provider": "local",
+                    "reinitialized_at": chrono::Utc::now().to_rfc3339()
+                }
+            });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::GET, "/api/ml/export") => {
+            // Vault lock check
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let export_data = serde_json::json!({
+                "metadata": {
+                    "exported_at": chrono::Utc::now().to_rfc3339(),
+                    "version": "1.0.0",
+                    "total_records": 1247
+                },
+                "usage_stats": {
+                    "total_requests": 1247,
+                    "success_rate": 0.985,
+                    "avg_response_time": 1.2
+                },
+                "model_usage": {
+                    "qwen3-7b": 856,
+                    "qwen3-32b": 391
+                }
+            });
+
+            let response = serde_json::json!({
+                "success": true,
+                "data": export_data
+            });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::POST, "/api/ml/reset") => {
+            // Vault lock check
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let response = serde_json::json!({
+                "success": true,
+                "data": {
+                    "status": "reset",
+                    "reset_at": chrono::Utc::now().to_rfc3339(),
+                    "records_cleared": 1247
+                }
+            });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        (&Method::GET, "/api/ml/config") => {
+            // Vault lock check
+            if !*is_unlocked.lock().await {
+                return Ok(api_error_response(&KeyKeeperError::VaultLocked));
+            }
+
+            let config = serde_json::json!({
+                "provider": "local",
+                "model": "qwen3-7b",
+                "temperature": 0.7,
+                "max_tokens": 2048,
+                "stream": false,
+                "timeout": 30,
+                "capabilities": ["chat", "code", "docs", "analysis"],
+                "license": "Apache 2.0",
+                "version": "1.0.0",
+                "last_updated": chrono::Utc::now().to_rfc3339()
+            });
+
+            let response = serde_json::json!({
+                "success": true,
+                "data": config
+            });
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(bytes::Bytes::from(response.to_string())))
+                .unwrap())
+        }
+
+        _ => Ok(api_error_response_with(
+            &KeyKeeperError::NotFound(format!("No route for {} {}", method, path)),
+            serde_json::json!({"method": method.as_str(), "path": path}),
+        )),
+    }
+}
+
+/// Background task running the HTTP server (and the legacy TCP listener)
+const HTTP_SERVER_TASK: &str = "http_server";
+
+#[tauri::command]
+async fn start_vscode_server(state: State<'_, AppState>) -> Result<String, KeyKeeperError> {
+    // Check if already running
+    if state.vscode_server_running.load(Ordering::SeqCst) {
+        return Ok("VSCode server is already running".to_string());
+    }
+    if lock_state::defer_server_start(&state).await {
+        return Ok("VSCode server will start when the vault is unlocked".to_string());
+    }
+
+    let vault = Arc::clone(&state.vault);
+    let is_unlocked = Arc::clone(&state.is_unlocked);
+    let storage = Arc::clone(&state.storage);
+    let saves = Arc::clone(&state.saves);
+    let running_flag = Arc::clone(&state.vscode_server_running);
+    let llm_proxy = Arc::clone(&state.llm_proxy);
+    let vault_index = Arc::clone(&state.vault_index);
+    let health = Arc::clone(&state.health);
+    let config = Arc::clone(&state.config);
+    let approvals = Arc::clone(&state.approvals);
+    let confirmations = Arc::clone(&state.confirmations);
+    let oauth_tokens = Arc::clone(&state.oauth_tokens);
+    let remote_values = Arc::clone(&state.remote_values);
+    let notifier = Arc::clone(&state.notifier);
+    let effective_config = config.effective();
+    let addr = effective_config.server_addr().map_err(KeyKeeperError::Validation)?;
+
+    running_flag.store(true, Ordering::SeqCst);
+
+    log_audit_event(
+        &state,
+        "start_vscode_server",
+        "integration",
+        Some("vscode"),
+        true,
+        None,
+    )
+    .await;
+
+    // Start Hyper server with manual connection handling
+    let listener = match TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            running_flag.store(false, Ordering::SeqCst);
+            return Err(KeyKeeperError::Io(format!("Failed to bind server on {}: {}", addr, e)));
+        }
+    };
+    let legacy = match legacy_tcp::bind(&effective_config, addr).await {
+        Ok(legacy) => legacy,
+        Err(e) => {
+            running_flag.store(false, Ordering::SeqCst);
+            return Err(e);
+        }
+    };
+
+    let server = async move {
+        while running_flag.load(Ordering::SeqCst) {
+            match listener.accept().await {
+                Ok((stream, client_addr)) => {
+                    let vault = Arc::clone(&vault);
+                    let is_unlocked = Arc::clone(&is_unlocked);
+                    let storage = Arc::clone(&storage);
+                    let saves = Arc::clone(&saves);
+                    let llm_proxy = Arc::clone(&llm_proxy);
+                    let vault_index = Arc::clone(&vault_index);
+                    let health = Arc::clone(&health);
+                    let config = Arc::clone(&config);
+                    let approvals = Arc::clone(&approvals);
+                    let confirmations = Arc::clone(&confirmations);
+                    let oauth_tokens = Arc::clone(&oauth_tokens);
+                    let remote_values = Arc::clone(&remote_values);
+                    let notifier = Arc::clone(&notifier);
+
+                    tokio::spawn(async move {
+                        let io = TokioIo::new(stream);
+                        let service = service_fn(move |req: Request<Incoming>| {
+                            let server_config = config.effective().server;
+                            let cors = cors::RequestCors::new(
+                                &server_config.cors,
+                                req.method(),
+                                req.uri().path(),
+                                req.headers(),
+                            );
+                            let vault = Arc::clone(&vault);
+                            let is_unlocked = Arc::clone(&is_unlocked);
+                            let storage = Arc::clone(&storage);
+                            let saves = Arc::clone(&saves);
+                            let llm_proxy = Arc::clone(&llm_proxy);
+                            let vault_index = Arc::clone(&vault_index);
+                            let health = Arc::clone(&health);
+                            let config = Arc::clone(&config);
+                            let approvals = Arc::clone(&approvals);
+                            let confirmations = Arc::clone(&confirmations);
+                            let oauth_tokens = Arc::clone(&oauth_tokens);
+                            let remote_values = Arc::clone(&remote_values);
+                            let notifier = Arc::clone(&notifier);
+                            let request_id = request_id::from_headers(req.headers());
+                            let pending = request_log::begin(&req, server_config.log_requests);
+                            request_id::serve(request_id, request_log::serve(pending, cors.serve(async move {
+                                let (req, client) = match clients::identify(
+                                    &vault,
+                                    &is_unlocked,
+                                    server_config.require_registered_clients,
+                                    client_addr,
+                                    req,
+                                )
+                                .await
+                                {
+                                    Ok(identified) => identified,
+                                    Err(e) => return Ok(api_error_response(&e)),
+                                };
+                                // Left on the response for `request_log`
+                                let logged_client = client.clone();
+                                let mut response = clients::scope(
+                                    client,
+                                    handle_hyper_request(
+                                        req,
+                                        vault,
+                                        is_unlocked,
+                                        storage,
+                                        saves,
+                                        llm_proxy,
+                                        vault_index,
+                                        health,
+                                        config,
+                                        approvals,
+                                        confirmations,
+                                        oauth_tokens,
+                                        remote_values,
+                                        notifier,
+                                        client_addr,
+                                    ),
+                                )
+                                .await?;
+                                response.extensions_mut().insert(logged_client);
+                                Ok(response)
+                            })))
+                        });
+
+                        if let Err(e) = http1::Builder::new().serve_connection(io, service).await {
+                            warn!("HTTP connection error: {}", e);
+                        }
+                    });
+                }
+                Err(e) => {
+                    warn!("Accept error: {}", e);
+                    break;
+                }
+            }
+        }
+    };
+    // Both listeners live in one task so stopping the server stops both
+    let spawned = state.tasks.spawn(HTTP_SERVER_TASK, async move {
+        match legacy {
+            Some((listener, upstream)) => {
+                tokio::select! {
+                    _ = server => {}
+                    _ = legacy_tcp::serve(listener, upstream) => {}
+                }
+            }
+            None => server.await,
+        }
+    });
+    if let Err(e) = spawned {
+        state.vscode_server_running.store(false, Ordering::SeqCst);
+        return Err(e);
+    }
+
+    state.health.set_server_running(true);
+    Ok(format!("Enterprise VSCode server started on {} with Hyper", addr))
+}
+
+#[tauri::command]
+async fn stop_vscode_server(state: State<'_, AppState>) -> Result<String, KeyKeeperError> {
+    lock_state::clear_server_pause();
+    if state.tasks.cancel(HTTP_SERVER_TASK).unwrap_or(false) {
+        state
+            .vscode_server_running
+            .store(false, std::sync::atomic::Ordering::SeqCst);
+        state.health.set_server_running(false);
+        log_audit_event(
+            &state,
+            "stop_vscode_server",
+            "integration",
+            Some("vscode"),
+            true,
+            None,
+        )
+        .await;
+        Ok("VSCode server stopped".to_string())
+    } else {
+        Ok("VSCode server was not running".to_string())
+    }
+}
+
+#[tauri::command]
+async fn get_vscode_server_status(state: State<'_, AppState>) -> Result<bool, KeyKeeperError> {
+    Ok(state.vscode_server_running.load(Ordering::SeqCst))
+}
+
+#[tauri::command]
+async fn get_audit_logs(state: State<'_, AppState>) -> Result<Vec<AuditLog>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    Ok(vault_guard.audit_logs.clone())
+}
+
+#[tauri::command]
+async fn create_user_account(
+    email: String,
+    password: String,
+    state: State<'_, AppState>,
+) -> Result<String, KeyKeeperError> {
+    // Simple validation
+    if email.is_empty() || !email.contains('@') {
+        return Err(KeyKeeperError::Validation("Invalid email address".to_string()));
+    }
+
+    password_strength::enforce(&state, &password, &password_strength::email_inputs(&email))?;
+
+    let mut vault_guard = state.vault.lock().await;
+
+    // Check if user already exists
+    if vault_guard.user_account.is_some() {
+        return Err(KeyKeeperError::Validation("User account already exists".to_string()));
+    }
+
+    // Hash the password
+    let password_hash = hash(&password, DEFAULT_COST).map_err(|e| KeyKeeperError::Crypto(e.to_string()))?;
+
+    // Create user account
+    let user_account = UserAccount {
+        id: format!("user_{}", get_utc_timestamp_millis()),
+        email: email.clone(),
+        username: email.split('@').next().unwrap_or("user").to_string(),
+        password_hash,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        verified: false,
+        recovery_codes: Vec::new(),
+        two_factor_enabled: false,
+        backup_email: None,
+        biometric_enabled: false,
+        passkey_credentials: Vec::new(),
+        session_timeout: 60, // 1 hour default
+        login_attempts: 0,
+        locked_until: None,
+        preferences: UserPreferences {
+            theme: "system".to_string(),
+            language: "en".to_string(),
+            auto_lock_timeout: 15,
+            clipboard_clear_timeout: 30,
+            show_notifications: true,
+            audit_logging: true,
+            biometric_unlock: false,
+            auto_backup: true,
+            encryption_level: "enhanced".to_string(),
+            notifications: notifications::NotificationSettings::default(),
+            logging: logging::LogSettings::default(),
+            env_sync_mode: env_refs::EnvSyncMode::default(),
+            key_ordering: pinning::KeyOrdering::default(),
+        },
+    };
+
+    vault_guard.user_account = Some(user_account.clone());
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "create_user_account",
+        "user",
+        Some(&user_account.id),
+        true,
+        None,
+    )
+    .await;
+
+    Ok(user_account.id)
+}
+
+#[tauri::command]
+async fn authenticate_user(
+    email: String,
+    password: String,
+    state: State<'_, AppState>,
+) -> Result<bool, KeyKeeperError> {
+    let vault_guard = state.vault.lock().await;
+
+    if let Some(user_account) = &vault_guard.user_account {
+        if user_account.email == email {
+            let password_hash = user_account.password_hash.clone();
+            let user_id = user_account.id.clone();
+            drop(vault_guard);
+
+            let is_valid = verify(&password, &password_hash).map_err(|e| KeyKeeperError::Crypto(e.to_string()))?;
+
+            if is_valid {
+                log_audit_event(
+                    &state,
+                    "authenticate_user",
+                    "user",
+                    Some(&user_id),
+                    true,
+                    None,
+                )
+                .await;
+                Ok(true)
+            } else {
+                log_audit_event(
+                    &state,
+                    "authenticate_user",
+                    "user",
+                    Some(&user_id),
+                    false,
+                    Some("Invalid password"),
+                )
+                .await;
+                Ok(false)
+            }
+        } else {
+            drop(vault_guard);
+            log_audit_event(
+                &state,
+                "authenticate_user",
+                "user",
+                None,
+                false,
+                Some("Email not found"),
+            )
+            .await;
+            Ok(false)
+        }
+    } else {
+        drop(vault_guard);
+        log_audit_event(
+            &state,
+            "authenticate_user",
+            "user",
+            None,
+            false,
+            Some("No user account"),
+        )
+        .await;
+        Ok(false)
+    }
+}
+
+#[tauri::command]
+async fn request_password_recovery(
+    email: String,
+    state: State<'_, AppState>,
+) -> Result<String, KeyKeeperError> {
+    let mut vault_guard = state.vault.lock().await;
+
+    if let Some(user_account) = &vault_guard.user_account {
+        if user_account.email == email {
+            let user_id = user_account.id.clone();
+
+            let (token, expires_at) = recovery_tokens::issue(
+                &mut vault_guard,
+                &email,
+                &recovery_tokens::request_fingerprint(),
+                Utc::now(),
+            );
+            // With email delivery set up, the token only goes out by email
+            let delivery = email::recovery_delivery(&vault_guard);
+            drop(vault_guard);
+
+            let sent = match &delivery {
+                Some((settings, recipients)) => email::send_recovery_token(
+                    &state.vault,
+                    settings,
+                    recipients,
+                    &token,
+                    expires_at,
+                )
+                .await
+                .map(|()| {
+                    let masked: Vec<String> =
+                        recipients.iter().map(|r| email::mask_address(r)).collect();
+                    format!("Recovery token sent to {}", masked.join(", "))
+                }),
+                None => Ok(token.to_string()),
+            };
+            let reply = match sent {
+                Ok(reply) => reply,
+                Err(e) => {
+                    state
+                        .vault
+                        .lock()
+                        .await
+                        .recovery_tokens
+                        .retain(|r| r.email != email);
+                    save_vault(&state).await?;
+                    log_audit_event(
+                        &state,
+                        "request_password_recovery",
+                        "user",
+                        Some(&user_id),
+                        false,
+                        Some(&e),
+                    )
+                    .await;
+                    return Err(KeyKeeperError::Io(format!(
+                        "Failed to email the recovery token: {}",
+                        e
+                    )));
+                }
+            };
+
+            save_vault(&state).await?;
+            log_audit_event(
+                &state,
+                "request_password_recovery",
+                "user",
+                Some(&user_id),
+                true,
+                None,
+            )
+            .await;
+
+            Ok(reply)
+        } else {
+            drop(vault_guard);
+            log_audit_event(
+                &state,
+                "request_password_recovery",
+                "user",
+                None,
+                false,
+                Some("Email not found"),
+            )
+            .await;
+            Err(KeyKeeperError::not_found("Email"))
+        }
+    } else {
+        drop(vault_guard);
+        log_audit_event(
+            &state,
+            "request_password_recovery",
+            "user",
+            None,
+            false,
+            Some("No user account"),
+        )
+        .await;
+        Err(KeyKeeperError::Validation("No user account exists".to_string()))
+    }
+}
+
+#[tauri::command]
+async fn reset_master_password(
+    token: String,
+    new_password: String,
+    state: State<'_, AppState>,
+) -> Result<bool, KeyKeeperError> {
+    let user_inputs = password_strength::account_inputs(&*state.vault.lock().await);
+    password_strength::enforce(&state, &new_password, &user_inputs)?;
+
+    let mut vault_guard = state.vault.lock().await;
+
+    // Validate and use up the recovery token
+    let redeemed = recovery_tokens::redeem(
+        &mut vault_guard,
+        &token,
+        &recovery_tokens::request_fingerprint(),
+        Utc::now(),
+    );
+    if let Err(rejection) = redeemed {
+        drop(vault_guard);
+        // Keep the failed attempt and the cleanup
+        save_vault(&state).await?;
+        log_audit_event(
+            &state,
+            "reset_master_password",
+            "user",
+            None,
+            false,
+            Some(rejection.reason()),
+        )
+        .await;
+        return Err(KeyKeeperError::Validation(rejection.message().to_string()));
+    }
+
+    // Reset master password
+    let password_hash = hash(&new_password, DEFAULT_COST).map_err(|e| KeyKeeperError::Crypto(e.to_string()))?;
+    vault_guard.master_password_hash = Some(password_hash);
+
+    // Generate new encryption key
+    let mut key_bytes = [0u8; 32];
+    let mut salt_bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut key_bytes);
+    OsRng.fill_bytes(&mut salt_bytes);
+
+    vault_guard.encryption_key = Some(general_purpose::STANDARD.encode(&key_bytes));
+    key_bytes.zeroize();
+    vault_guard.salt = Some(general_purpose::STANDARD.encode(&salt_bytes));
+
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(&state, "reset_master_password", "user", None, true, None).await;
+
+    Ok(true)
+}
+
+#[tauri::command]
+async fn get_user_account(state: State<'_, AppState>) -> Result<Option<UserAccount>, KeyKeeperError> {
+    let vault_guard = state.vault.lock().await;
+
+    if let Some(user_account) = &vault_guard.user_account {
+        // Return user account without password hash for security
+        let mut safe_account = user_account.clone();
+        safe_account.password_hash = "***HIDDEN***".to_string();
+        Ok(Some(safe_account))
+    } else {
+        Ok(None)
+    }
+}
+
+#[tauri::command]
+async fn is_user_account_created(state: State<'_, AppState>) -> Result<bool, KeyKeeperError> {
+    let vault_guard = state.vault.lock().await;
+    Ok(vault_guard.user_account.is_some())
+}
+
+#[tauri::command]
+async fn is_master_password_set(state: State<'_, AppState>) -> Result<bool, KeyKeeperError> {
+    let vault_guard = state.vault.lock().await;
+    let has_password_in_memory = vault_guard.master_password_hash.is_some();
+    info!(
+        "Checking master password status in memory: {}",
+        has_password_in_memory
+    );
+
+    // If not found in memory, check metadata file as fallback
+    if !has_password_in_memory {
+        let metadata_path = state.vault_path.metadata_path();
+        if metadata_path.exists() {
+            if let Ok(metadata_contents) = fs::read_to_string(&metadata_path) {
+                if let Ok(metadata) = serde_json::from_str::<VaultMetadata>(&metadata_contents) {
+                    let has_password_in_file = metadata.master_password_hash.is_some();
+                    info!(
+                        "Master password found in metadata file: {}",
+                        has_password_in_file
+                    );
+                    return Ok(has_password_in_file);
+                }
+            }
+        }
+        info!("No master password hash found anywhere");
+        return Ok(false);
+    }
+
+    if let Some(ref hash) = vault_guard.master_password_hash {
+        info!(
+            "Master password hash exists in memory, length: {}",
+            hash.len()
+        );
+    }
+    Ok(has_password_in_memory)
+}
+
+#[tauri::command]
+async fn search_api_keys_by_query(
+    query: String,
+    include_archived: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Vec<masking::MaskedApiKey>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    let mut filtered_keys: Vec<ApiKey> = vault_guard
+        .keys
+        .values()
+        .filter(|key| include_archived.unwrap_or(false) || !archive::is_archived(key))
+        .filter(|key| {
+            key.name.to_lowercase().contains(&query.to_lowercase())
+                || key.service.to_lowercase().contains(&query.to_lowercase())
+                || key
+                    .tags
+                    .iter()
+                    .any(|tag| tag.to_lowercase().contains(&query.to_lowercase()))
+                || key.description.as_ref().map_or(false, |desc| {
+                    desc.to_lowercase().contains(&query.to_lowercase())
+                })
+                || credentials::matches_query(key, &query.to_lowercase())
+        })
+        .cloned()
+        .collect();
+    pinning::order_keys(&vault_guard, &mut filtered_keys);
+
+    Ok(masking::mask_all(filtered_keys))
+}
+
+#[tauri::command]
+async fn get_projects(state: State<'_, AppState>) -> Result<Vec<Project>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    Ok(vault_guard.projects.values().cloned().collect())
+}
+
+/// Record that a key was used. `usage` says what the use consumed, counted
+/// against the key's quota; without it the use counts as one request.
+#[tauri::command]
+async fn record_key_usage(
+    key_id: String,
+    usage: Option<quotas::UsageMetadata>,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let now = Utc::now();
+
+    archive::touch(&mut vault_guard, &key_id, now);
+
+    if let Some(key) = vault_guard.keys.get(&key_id).cloned() {
+        activity::record(
+            &mut vault_guard,
+            "key_used",
+            &key,
+            activity::Actor::ui(),
+            None,
+            Some("Used via VSCode extension".to_string()),
+        );
+        let alerts = quotas::consume(&mut vault_guard, &key_id, &usage.unwrap_or_default(), now);
+        drop(vault_guard);
+        save_vault(&state).await?;
+        log_audit_event(
+            &state,
+            "record_key_usage",
+            "api_key",
+            Some(&key_id),
+            true,
+            None,
+        )
+        .await;
+        quotas::notify(&state.notifier, &state.vault, &alerts).await;
+        Ok(())
+    } else {
+        Err(KeyKeeperError::not_found("API key"))
+    }
+}
+
+#[tauri::command]
+async fn sync_project(project_path: String, state: State<'_, AppState>) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let _syncing = tray_status::begin_sync();
+
+    let mut vault_guard = state.vault.lock().await;
+
+    // Extract project name from path
+    let project_name = std::path::Path::new(&project_path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("Unknown Project")
+        .to_string();
+
+    // Check if project already exists
+    let project_id = format!("project_{}", get_utc_timestamp_millis());
+    let profile = project_analysis::analyze(std::path::Path::new(&project_path));
+    let packages = monorepo::list_packages(std::path::Path::new(&project_path));
+
+    let mut project = Project {
+        id: project_id.clone(),
+        name: project_name,
+        description: Some("Synced from VSCode".to_string()),
+        path: project_path.clone(),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        settings: project_settings::builtin_defaults(),
+        settings_template_id: None,
+        settings_overrides: Default::default(),
+        group_id: None,
+        scaffolds: Vec::new(),
+        profile,
+        packages,
+    };
+    project_settings::for_new_project(&vault_guard, &mut project, None)?;
+
+    vault_guard.projects.insert(project_id.clone(), project);
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "sync_project",
+        "project",
+        Some(&project_id),
+        true,
+        None,
+    )
+    .await;
+
+    Ok(())
+}
+
+// ===============================
+//  ENHANCED PROJECT CRUD OPERATIONS
+// ===============================
+
+#[tauri::command]
+async fn create_project(
+    name: String,
+    description: Option<String>,
+    path: Option<String>,
+    template_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Project, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+
+    // Generate unique project ID
+    let project_id = format!("project_{}", get_utc_timestamp_millis());
+
+    // Use provided path or generate default
+    let project_path = path.unwrap_or_else(|| format!("/projects/{}", &name));
+    let profile = project_analysis::analyze(std::path::Path::new(&project_path));
+    let packages = monorepo::list_packages(std::path::Path::new(&project_path));
+
+    let mut project = Project {
+        id: project_id.clone(),
+        name: name.clone(),
+        description,
+        path: project_path,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        settings: project_settings::builtin_defaults(),
+        settings_template_id: None,
+        settings_overrides: Default::default(),
+        group_id: None,
+        scaffolds: Vec::new(),
+        profile,
+        packages,
+    };
+    project_settings::for_new_project(&vault_guard, &mut project, template_id)?;
+
+    vault_guard
+        .projects
+        .insert(project_id.clone(), project.clone());
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "create_project",
+        "project",
+        Some(&project_id),
+        true,
+        None,
+    )
+    .await;
+
+    info!("Project created successfully: {} ({})", name, project_id);
+    Ok(project)
+}
+
+#[tauri::command]
+async fn update_project(
+    id: String,
+    name: Option<String>,
+    description: Option<String>,
+    settings: Option<ProjectSettings>,
+    state: State<'_, AppState>,
+) -> Result<Project, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+
+    if let Some(mut project) = vault_guard.projects.get(&id).cloned() {
+        // Update fields if provided
+        if let Some(new_name) = name {
+            project.name = new_name;
+        }
+        if let Some(new_description) = description {
+            project.description = Some(new_description);
+        }
+        if let Some(new_settings) = settings {
+            project_settings::set_project_settings(&vault_guard, &mut project, new_settings)?;
+        }
+
+        project.updated_at = Utc::now();
+
+        vault_guard.projects.insert(id.clone(), project.clone());
+        drop(vault_guard);
+
+        save_vault(&state).await?;
+        log_audit_event(&state, "update_project", "project", Some(&id), true, None).await;
+
+        info!("Project updated successfully: {}", id);
+        Ok(project)
+    } else {
+        Err(KeyKeeperError::not_found("Project"))
+    }
+}
+
+#[tauri::command]
+async fn delete_project(
+    id: String,
+    reassign_keys_to: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+
+    // Check if project exists
+    if !vault_guard.projects.contains_key(&id) {
+        return Err(KeyKeeperError::not_found("Project"));
+    }
+
+    // Get the project's path for finding associated keys
+    let project_path = vault_guard.projects.get(&id).unwrap().path.clone();
+
+    // Get the new project path if reassigning
+    let new_project_path = if let Some(new_project_id) = &reassign_keys_to {
+        vault_guard
+            .projects
+            .get(new_project_id)
+            .map(|p| p.path.clone())
+    } else {
+        None
+    };
+
+    // Handle keys associated with this project
+    let vault = &mut *vault_guard;
+    for (key_id, key) in vault.keys.iter_mut() {
+        if key.project_path.as_ref() == Some(&project_path) {
+            if let Some(ref new_path) = new_project_path {
+                // Reassign to another project
+                key.project_path = Some(new_path.clone());
+                info!(
+                    "Reassigned key {} to project {}",
+                    key_id,
+                    reassign_keys_to.as_ref().unwrap()
+                );
+            } else {
+                // Remove project association
+                key.project_path = None;
+                key.env_file_path = None;
+                key.env_file_name = None;
+                info!("Removed project association from key {}", key_id);
+            }
+            key_history::record(&mut vault.key_history, key, false);
+        }
+    }
+
+    // Remove project-env associations
+    vault_guard
+        .env_associations
+        .retain(|assoc| assoc.project_path != project_path);
+
+    // Remove the project
+    vault_guard.projects.remove(&id);
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(&state, "delete_project", "project", Some(&id), true, None).await;
+
+    info!("Project deleted successfully: {}", id);
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_project_by_id(id: String, state: State<'_, AppState>) -> Result<Project, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+
+    if let Some(project) = vault_guard.projects.get(&id) {
+        Ok(project.clone())
+    } else {
+        Err(KeyKeeperError::not_found("Project"))
+    }
+}
+
+#[tauri::command]
+async fn assign_keys_to_project(
+    project_id: String,
+    key_ids: Vec<String>,
+    package_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+
+    // Verify project exists
+    let project_path = if let Some(project) = vault_guard.projects.get(&project_id) {
+        if let Some(package) = &package_path {
+            if !project.packages.iter().any(|p| &p.path == package) {
+                return Err(KeyKeeperError::NotFound(format!(
+                    "Package {} not found in project",
+                    package
+                )));
+            }
+        }
+        project.path.clone()
+    } else {
+        return Err(KeyKeeperError::not_found("Project"));
+    };
+
+    let mut assigned_count = 0;
+    let mut not_found_keys = Vec::new();
+
+    // Assign keys to project
+    let vault = &mut *vault_guard;
+    for key_id in key_ids {
+        if let Some(key) = vault.keys.get_mut(&key_id) {
+            key.project_path = Some(project_path.clone());
+            key.package_path = package_path.clone();
+            key_history::record(&mut vault.key_history, key, false);
+            assigned_count += 1;
+        } else {
+            not_found_keys.push(key_id);
+        }
+    }
+
+    drop(vault_guard);
+
+    if assigned_count > 0 {
+        save_vault(&state).await?;
+        log_audit_event(
+            &state,
+            "assign_keys_to_project",
+            "project",
+            Some(&project_id),
+            true,
+            Some(&format!("Assigned {} keys", assigned_count)),
+        )
+        .await;
+    }
+
+    if !not_found_keys.is_empty() {
+        warn!(
+            "Some keys not found during assignment: {:?}",
+            not_found_keys
+        );
+        return Err(KeyKeeperError::NotFound(format!("Some keys not found: {:?}", not_found_keys)));
+    }
+
+    info!(
+        "Successfully assigned {} keys to project {}",
+        assigned_count, project_id
+    );
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_keys_by_project(
+    project_id: Option<String>,
+    package_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<masking::MaskedApiKey>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+
+    let result = if let Some(project_id) = project_id {
+        // Get keys for specific project
+        if let Some(project) = vault_guard.projects.get(&project_id) {
+            let ids = state
+                .vault_index
+                .lock()
+                .await
+                .ids_by_project(&vault_guard, &project.path);
+            let mut keys = vault_index::collect_keys(&vault_guard, &ids);
+            keys.retain(|key| !archive::is_archived(key));
+            if package_path.is_some() {
+                keys.retain(|key| key.package_path == package_path);
+            }
+            keys
+        } else {
+            return Err(KeyKeeperError::not_found("Project"));
+        }
+    } else {
+        // Get all keys regardless of project
+        vault_guard
+            .keys
+            .values()
+            .filter(|key| !archive::is_archived(key))
+            .cloned()
+            .collect()
+    };
+
+    Ok(masking::mask_all(result))
+}
+
+#[tauri::command]
+async fn get_unassigned_keys(
+    state: State<'_, AppState>,
+) -> Result<Vec<masking::MaskedApiKey>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+
+    let ids = state.vault_index.lock().await.unassigned_ids(&vault_guard);
+    let unassigned_keys = vault_index::collect_keys(&vault_guard, &ids);
+
+    Ok(masking::mask_all(unassigned_keys))
+}
+
+#[tauri::command]
+async fn search_keys_in_project(
+    project_id: String,
+    query: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<masking::MaskedApiKey>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+
+    // Verify project exists
+    let project_path = if let Some(project) = vault_guard.projects.get(&project_id) {
+        project.path.clone()
+    } else {
+        return Err(KeyKeeperError::not_found("Project"));
+    };
+
+    let query_lower = query.to_lowercase();
+    let project_key_ids = state
+        .vault_index
+        .lock()
+        .await
+        .ids_by_project(&vault_guard, &project_path);
+    let matching_keys: Vec<masking::MaskedApiKey> = project_key_ids
+        .iter()
+        .filter_map(|id| vault_guard.keys.get(id))
+        .filter(|key| {
+            // Key must match search query
+            key.name.to_lowercase().contains(&query_lower) ||
+            key.service.to_lowercase().contains(&query_lower) ||
+            key.description.as_ref().map_or(false, |d| d.to_lowercase().contains(&query_lower)) ||
+            key.tags.iter().any(|tag| tag.to_lowercase().contains(&query_lower))
+        })
+        .map(masking::MaskedApiKey::from)
+        .collect();
+
+    Ok(matching_keys)
+}
+
+// ===============================
+//  VSCODE AUTO-SYNC FUNCTIONALITY
+// ===============================
+
+/// Work out what syncing a key into a project's `.env` changes, without
+/// writing. Returns the variable name, the pending write and, when it puts a
+/// production key into a non-production file, the policy violation.
+fn plan_key_env_sync(
+    vault: &ApiKeyVault,
+    key_id: &str,
+    project_path: &str,
+    env_file_name: Option<String>,
+    package_path: Option<String>,
+) -> Result<(String, env_file::PendingWrite, Option<sync_policy::PolicyViolation>), KeyKeeperError> {
+    // Find the API key
+    let api_key = vault
+        .keys
+        .get(key_id)
+        .ok_or_else(|| KeyKeeperError::not_found("API key"))?;
+    if !api_key.kind.is_env_value() {
+        return Err(KeyKeeperError::Validation(
+            "SSH keys and certificates are exported as files, not .env values".to_string(),
+        ));
+    }
+    if archive::is_archived(api_key) {
+        return Err(KeyKeeperError::Validation(
+            "The key is archived; restore it before syncing".to_string(),
+        ));
+    }
+
+    // Keys scoped to a package of this project go to that package's .env
+    let package_path = package_path.or_else(|| {
+        api_key
+            .package_path
+            .clone()
+            .filter(|_| api_key.project_path.as_deref() == Some(project_path))
+    });
+
+    // Determine the .env file path
+    let env_file = env_file_name.unwrap_or_else(|| ".env".to_string());
+    env_file::validate_file_name(&env_file).map_err(KeyKeeperError::Validation)?;
+    let env_file_path = monorepo::package_dir(project_path, package_path.as_deref()).join(&env_file);
+
+    // Generate environment variable name
+    let var_name = format!(
+        "{}_API_KEY",
+        api_key.service.to_uppercase().replace(" ", "_")
+    );
+
+    let mut pending = env_file::PendingWrite::open(env_file_path)
+        .map_err(|e| KeyKeeperError::Io(format!("Failed to read .env file: {}", e)))?;
+    let mode = env_refs::sync_mode(vault);
+    let current = pending.file().get(&var_name);
+    // `.env.<environment>` files of keys with per-environment values get
+    // that environment's value
+    let file_environment =
+        env_values::file_environment(&env_file).filter(|_| !api_key.values.is_empty());
+    let own_value = file_environment.is_none();
+    let value = match file_environment {
+        Some(environment) => env_refs::sync_value_in(mode, api_key, &environment, current)
+            .ok_or_else(|| {
+                KeyKeeperError::Validation(format!("{} has no {} value", api_key.name, environment))
+            })?,
+        None => env_refs::sync_value(mode, &api_key.id, &api_key.key, current),
+    };
+    let changed = pending.set(&var_name, &value) != env_file::SetOutcome::Unchanged;
+    let violation = if changed && own_value {
+        let project = vault.projects.values().find(|p| p.path == project_path);
+        let target = sync_policy::target_environment(project, &pending.path);
+        sync_policy::check(api_key, &pending.path.to_string_lossy(), target.as_deref())
+    } else {
+        None
+    };
+    Ok((var_name, pending, violation))
+}
+
+#[tauri::command]
+async fn sync_key_to_env_file(
+    key_id: String,
+    project_path: String,
+    env_file_name: Option<String>,
+    package_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let mut planned = plan_key_env_sync(
+        &vault_guard,
+        &key_id,
+        &project_path,
+        env_file_name.clone(),
+        package_path.clone(),
+    )?;
+    if let Some(violation) = planned.2.clone() {
+        drop(vault_guard);
+        sync_policy::confirm(
+            &state.confirmations,
+            &state.vault,
+            &*state.storage,
+            "KeyKeeper",
+            &[violation],
+        )
+        .await?;
+        // The key or the file may have changed while waiting
+        vault_guard = state.vault.lock().await;
+        planned =
+            plan_key_env_sync(&vault_guard, &key_id, &project_path, env_file_name, package_path)?;
+    }
+    let (var_name, pending, _) = planned;
+
+    let env_file_path = pending.path.to_string_lossy().to_string();
+    if !pending.is_changed() {
+        return Ok(format!("Key {} already up to date in {}", var_name, env_file_path));
+    }
+    env_history::commit(&pending, &vault_guard, Some(&project_path), "sync_key_to_env")
+        .map_err(|e| KeyKeeperError::Io(format!("Failed to write to .env file: {}", e)))?;
+    drop(vault_guard);
+
+    let verb = if pending.updated.is_empty() { "Added" } else { "Updated" };
+    info!("{} {} in {}", verb, var_name, env_file_path);
+    log_audit_event(
+        &state,
+        "sync_key_to_env",
+        "api_key",
+        Some(&key_id),
+        true,
+        Some(&format!("{} in {}", verb, env_file_path)),
+    )
+    .await;
+
+    Ok(format!("Successfully {} {} in {}", verb.to_lowercase(), var_name, env_file_path))
+}
+
+/// Diff of what `sync_key_to_env_file` would write, without touching the file
+#[tauri::command]
+async fn preview_sync_key_to_env_file(
+    key_id: String,
+    project_path: String,
+    env_file_name: Option<String>,
+    package_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<env_file::SyncPreview, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    let (_, pending, _) =
+        plan_key_env_sync(&vault_guard, &key_id, &project_path, env_file_name, package_path)?;
+    Ok(pending.preview())
+}
+
+#[tauri::command]
+async fn check_key_in_env_file(
+    key_id: String,
+    project_path: String,
+    env_file_name: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<bool, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+
+    // Find the API key
+    let api_key = vault_guard
+        .keys
+        .get(&key_id)
+        .ok_or_else(|| KeyKeeperError::not_found("API key"))?;
+
+    // Determine the .env file path
+    let env_file = env_file_name.unwrap_or_else(|| ".env".to_string());
+    env_file::validate_file_name(&env_file).map_err(KeyKeeperError::Validation)?;
+    let env_file_path = format!("{}/{}", project_path, env_file);
+
+    // Check if .env file exists and contains the key
+    match std::fs::read_to_string(&env_file_path) {
+        Ok(env_content) => {
+            let var_name = format!(
+                "{}_API_KEY",
+                api_key.service.to_uppercase().replace(" ", "_")
+            );
+            Ok(env_content.contains(&var_name))
+        }
+        Err(_) => Ok(false), // File doesn't exist
+    }
+}
+
+#[tauri::command]
+async fn get_env_file_suggestions(
+    project_path: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    Ok(project_ignore::env_files(std::path::Path::new(&project_path)))
+}
+
+/// Work out the `.env` changes for the project open at `workspace_path`:
+/// each of its keys is added to, or updated in, the existing `.env` files of
+/// its package or of the project root. Returns the project id and the
+/// production keys put into non-production files, or `None` when no project
+/// is registered for the workspace.
+fn plan_workspace_env_sync(
+    vault: &ApiKeyVault,
+    workspace_path: &str,
+) -> Option<(String, Vec<env_file::PendingWrite>, Vec<sync_policy::PolicyViolation>)> {
+    // Find project associated with this workspace. A monorepo package opened
+    // on its own limits the sync to that package.
+    let mut scope_package = None;
+    let project = vault
+        .projects
+        .values()
+        .find(|p| p.path == workspace_path)
+        .or_else(|| {
+            vault.projects.values().find(|p| {
+                scope_package = monorepo::package_for_path(
+                    std::path::Path::new(&p.path),
+                    &p.packages,
+                    std::path::Path::new(workspace_path),
+                )
+                .filter(|package| {
+                    monorepo::package_dir(&p.path, Some(&package.path))
+                        == std::path::Path::new(workspace_path)
+                })
+                .map(|package| package.path.clone());
+                scope_package.is_some()
+            })
+        })?;
+
+    // Get all keys for this project
+    let project_keys: Vec<_> = vault
+        .keys
+        .values()
+        .filter(|key| key.project_path.as_ref() == Some(&project.path))
+        .filter(|key| scope_package.is_none() || key.package_path == scope_package)
+        .collect();
+
+    let (writes, violations) =
+        project_sync::plan_env_writes(project, &project_keys, env_refs::sync_mode(vault));
+    Some((project.id.clone(), writes, violations))
+}
+
+/// Work out the `.env` changes of a workspace sync over HTTP: every key of
+/// the vault is added to the workspace's `.env` files that don't have it
+/// yet. Returns the writes, the production keys put into non-production
+/// files and the ids of the keys written; files that can't be read are
+/// reported in `errors`.
+fn plan_workspace_files_sync(
+    vault: &ApiKeyVault,
+    workspace_path: &str,
+    errors: &mut Vec<String>,
+) -> (Vec<env_file::PendingWrite>, Vec<sync_policy::PolicyViolation>, Vec<String>) {
+    let mut writes = Vec::new();
+    let mut violations = Vec::new();
+    let mut key_ids: Vec<String> = Vec::new();
+    let project = vault.projects.values().find(|p| p.path == workspace_path);
+
+    // Find all .env files in the workspace
+    let env_files = vec![
+        format!("{}/.env", workspace_path),
+        format!("{}/.env.local", workspace_path),
+        format!("{}/.env.development", workspace_path),
+        format!("{}/.env.staging", workspace_path),
+        format!("{}/.env.production", workspace_path),
+    ];
+
+    for env_file_path in env_files {
+        if !std::path::Path::new(&env_file_path).exists() {
+            continue;
+        }
+        let mut pending = match env_file::PendingWrite::open(&env_file_path) {
+            Ok(pending) => pending,
+            Err(e) => {
+                errors.push(format!("Failed to read {}: {}", env_file_path, e));
+                continue;
+            }
+        };
+
+        let file_environment = env_values::file_environment(&env_file_path);
+        for api_key in vault
+            .keys
+            .values()
+            .filter(|k| k.kind.is_env_value() && !archive::is_archived(k))
+        {
+            let var_name = format!(
+                "{}_API_KEY",
+                api_key.service.to_uppercase().replace(" ", "_")
+            );
+
+            // Add keys that aren't in the file, leaving commented-out ones alone
+            let file = pending.file();
+            if !file.contains(&var_name) && !file.is_commented_out(&var_name) {
+                let mode = env_refs::sync_mode(vault);
+                let own_value = file_environment.is_none() || api_key.values.is_empty();
+                let value = match &file_environment {
+                    Some(env) if !api_key.values.is_empty() => {
+                        env_refs::sync_value_in(mode, api_key, env, None)
+                    }
+                    _ => Some(env_refs::sync_value(
+                        mode,
+                        &api_key.id,
+                        &api_key.key,
+                        None,
+                    )),
+                };
+                let Some(value) = value else {
+                    continue;
+                };
+                if pending.set(&var_name, &value) == env_file::SetOutcome::Unchanged {
+                    continue;
+                }
+                if !key_ids.contains(&api_key.id) {
+                    key_ids.push(api_key.id.clone());
+                }
+                if own_value {
+                    let target = sync_policy::target_environment(project, &pending.path);
+                    violations.extend(sync_policy::check(
+                        api_key,
+                        &pending.path.to_string_lossy(),
+                        target.as_deref(),
+                    ));
+                }
+            }
+        }
+        writes.push(pending);
+    }
+    (writes, violations, key_ids)
+}
+
+#[tauri::command]
+async fn auto_sync_workspace_env_files(
+    workspace_path: String,
+    state: State<'_, AppState>,
+) -> Result<String, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let _syncing = tray_status::begin_sync();
+
+    let no_project = || Ok("No project associated with this workspace".to_string());
+    let mut vault_guard = state.vault.lock().await;
+    if incident::auto_sync_paused(&vault_guard) {
+        return Err(KeyKeeperError::Conflict(
+            "Auto sync is paused while an incident is open".to_string(),
+        ));
+    }
+    let Some(mut planned) = plan_workspace_env_sync(&vault_guard, &workspace_path) else {
+        return no_project();
+    };
+    if !planned.2.is_empty() {
+        drop(vault_guard);
+        sync_policy::confirm(
+            &state.confirmations,
+            &state.vault,
+            &*state.storage,
+            "KeyKeeper",
+            &planned.2,
+        )
+        .await?;
+        // The keys or the files may have changed while waiting
+        vault_guard = state.vault.lock().await;
+        let Some(replanned) = plan_workspace_env_sync(&vault_guard, &workspace_path) else {
+            return no_project();
+        };
+        planned = replanned;
+    }
+    let (project_id, writes, _) = planned;
+
+    let mut synced_count = 0;
+    let mut updated_count = 0;
+    for pending in writes.iter().filter(|pending| pending.is_changed()) {
+        match env_history::commit(
+            pending,
+            &vault_guard,
+            Some(&workspace_path),
+            "auto_sync_workspace",
+        ) {
+            Ok(_) => {
+                synced_count += pending.added.len();
+                updated_count += pending.updated.len();
+            }
+            Err(e) => warn!("Failed to write to {}: {}", pending.path.display(), e),
+        }
+    }
+    drop(vault_guard);
+
+    log_audit_event(
+        &state,
+        "auto_sync_workspace",
+        "project",
+        Some(&project_id),
+        true,
+        Some(&format!(
+            "Synced {} keys, updated {}",
+            synced_count, updated_count
+        )),
+    )
+    .await;
+
+    Ok(format!(
+        "Auto-synced {} keys to workspace .env files ({} updated)",
+        synced_count, updated_count
+    ))
+}
+
+/// Diffs of what `auto_sync_workspace_env_files` would write, one per
+/// `.env` file it looks at, without touching them
+#[tauri::command]
+async fn preview_auto_sync_workspace_env_files(
+    workspace_path: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<env_file::SyncPreview>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    let (_, writes, _) = plan_workspace_env_sync(&vault_guard, &workspace_path).ok_or_else(|| {
+        KeyKeeperError::NotFound("No project associated with this workspace".to_string())
+    })?;
+    Ok(writes.iter().map(|pending| pending.preview()).collect())
+}
+
+/// Save the vault, coalesced with other saves, see `save_queue`
+async fn save_vault(state: &State<'_, AppState>) -> Result<(), KeyKeeperError> {
+    let _task = state.health.begin_task("vault_save");
+    state.saves.save().await
+}
+
+async fn save_vault_to_path(
+    vault: &ApiKeyVault,
+    vault_path: &PathBuf,
+) -> Result<(), KeyKeeperError> {
+    save_vault_to(&vault_storage::FileStorage::new(vault_path), vault)
+}
+
+/// Encrypt the vault with its key, if it has one, and write it with its
+/// metadata. Metadata-only vaults (key `[ENCRYPTED]`) are never written.
+fn save_vault_to(
+    storage: &dyn vault_storage::VaultStorage,
+    vault: &ApiKeyVault,
+) -> Result<(), KeyKeeperError> {
+    if vault.encryption_key.as_deref() == Some("[ENCRYPTED]") {
+        return Ok(());
+    }
+    storage.begin_save()?;
+    let result = write_vault_documents(storage, vault);
+    storage.end_save(result.is_ok());
+    if result.is_ok() {
+        etag::bump();
+        events::publish(events::EventPayload::Saved);
+    }
+    result
+}
+
+fn write_vault_documents(
+    storage: &dyn vault_storage::VaultStorage,
+    vault: &ApiKeyVault,
+) -> Result<(), KeyKeeperError> {
+    // Serialize the vault to JSON
+    let json = Zeroizing::new(serde_json::to_string_pretty(vault).map_err(|e| {
+        KeyKeeperError::Internal(format!("Failed to serialize vault: {}", e))
+    })?);
+
+    // Encrypt the vault data if encryption key is available
+    let final_data = if let Some(key_str) = &vault.encryption_key {
+        let key_bytes = Zeroizing::new(
+            general_purpose::STANDARD
+                .decode(key_str)
+                .map_err(|e| {
+                    KeyKeeperError::Crypto(format!("Failed to decode encryption key: {}", e))
+                })?,
+        );
+
+        if key_bytes.len() != 32 {
+            return Err(KeyKeeperError::Crypto(
+                "Invalid encryption key length".to_string(),
+            ));
+        }
+
+        let mut key_array = secure_memory::SecretKey::zeroed();
+        key_array.as_mut_bytes().copy_from_slice(&key_bytes);
+        let encrypted = encrypt_data(&json, &*key_array).map_err(KeyKeeperError::Crypto)?;
+
+        // Save metadata file for encrypted vaults
+        let api_keys_metadata: Vec<ApiKeyMetadata> = vault
+            .keys
+            .values()
+            .map(|api_key| {
+                ApiKeyMetadata {
+                    id: api_key.id.clone(),
+                    name: api_key.name.clone(),
+                    key: "[ENCRYPTED]".to_string(), // Never store actual keys in metadata
+                    service: api_key.service.clone(),
+                    description: api_key.description.clone(),
+                    environment: api_key.environment.clone(),
+                    quota: api_key.quota.clone(),
+                    cost: api_key.cost.clone(),
+                    expires_at: api_key.expires_at,
+                    scopes: api_key.scopes.clone(),
+                    created_at: api_key.created_at,
+                    updated_at: api_key.updated_at,
+                    tags: api_key.tags.clone(),
+                    is_active: api_key.is_active,
+                    source_type: api_key.source_type.clone(),
+                    env_file_path: api_key.env_file_path.clone(),
+                    project_path: api_key.project_path.clone(),
+                    env_file_name: api_key.env_file_name.clone(),
+                    package_path: api_key.package_path.clone(),
+                    rotation_interval_days: api_key.rotation_interval_days,
+                    last_rotated_at: api_key.last_rotated_at,
+                    kind: api_key.kind,
+                    credential: api_key
+                        .credential
+                        .as_ref()
+                        .map(credentials::CredentialParts::without_secrets),
+                    oauth: api_key.oauth.clone(),
+                    values: api_key
+                        .values
+                        .keys()
+                        .map(|env| (env.clone(), "[ENCRYPTED]".to_string()))
+                        .collect(),
+                    is_pinned: api_key.is_pinned,
+                    archived_at: api_key.archived_at,
+                    last_used_at: api_key.last_used_at,
+                    backend: api_key.backend.clone(),
+                }
+            })
+            .collect();
+
+        // Create complete vault metadata structure
+        let vault_metadata = VaultMetadata {
+            master_password_hash: vault.master_password_hash.clone(),
+            salt: vault.salt.clone(),
+            created_at: Utc::now(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            schema_version: migrations::CURRENT_SCHEMA_VERSION,
+            api_keys_metadata,
+            vault_checksum: Some(vault_integrity::checksum(encrypted.as_bytes())),
+            recovery: vault
+                .recovery_kit
+                .as_ref()
+                .map(|kit| recovery_kit::envelope(kit, key_str))
+                .transpose()
+                .map_err(KeyKeeperError::Crypto)?,
+            hardware_unlock: vault
+                .hardware_unlock
+                .as_ref()
+                .map(|key| hardware_unlock::envelope(key, key_str))
+                .transpose()
+                .map_err(KeyKeeperError::Crypto)?,
+            vault_id: Some(vault.vault_id.clone()),
+            webhooks: webhooks::locked_endpoints(&vault.webhooks),
+            failed_unlocks: vault.failed_unlocks.clone(),
+            notifications: notifications::account_preferences(vault),
+        };
+
+        let metadata_json = serde_json::to_string_pretty(&vault_metadata).map_err(|e| {
+            KeyKeeperError::Internal(format!("Failed to serialize metadata: {}", e))
+        })?;
+        storage
+            .write_metadata(&metadata_json)
+            .map_err(KeyKeeperError::Io)?;
+
+        encrypted
+    } else {
+        // No encryption key yet, save as plaintext (first-time setup)
+        json.to_string()
+    };
+
+    storage.write_vault(&final_data).map_err(KeyKeeperError::Io)
+}
+
+fn load_vault(vault_path: &PathBuf) -> Result<ApiKeyVault, String> {
+    load_vault_from(&vault_storage::FileStorage::new(vault_path))
+}
+
+/// Load the vault as far as possible without the password: plaintext vaults
+/// completely, encrypted ones as the metadata-only view
+fn load_vault_from(storage: &dyn vault_storage::VaultStorage) -> Result<ApiKeyVault, String> {
+    let Some(contents) = storage.read_vault()? else {
+        return Ok(ApiKeyVault::default());
+    };
+
+    // Try to parse as JSON first (for backwards compatibility or new vaults)
+    if is_plaintext_vault(&contents) {
+        return migrations::load_vault_document(&contents, storage);
+    }
+
+    // If JSON parsing fails, it might be encrypted
+    // Check if we have a metadata file with essential info
+    if let Ok(Some(metadata_contents)) = storage.read_metadata() {
+        if let Ok(metadata) = serde_json::from_str::<VaultMetadata>(&metadata_contents) {
+            migrations::ensure_supported(metadata.schema_version, Some(&metadata.version))?;
+
+            // Create a vault with metadata but no sensitive data
+            let mut vault = ApiKeyVault::default();
+            vault.master_password_hash = metadata.master_password_hash.clone();
+            vault.salt = metadata.salt.clone();
+            vault.vault_id = metadata.vault_id.clone().unwrap_or_else(|| {
+                keyring_store::legacy_vault_id(metadata.salt.as_deref())
+            });
+            info!(
+                "Loading vault from metadata, master_password_hash: {:?}",
+                metadata.master_password_hash.is_some()
+            );
+            vault.encryption_key = Some("[ENCRYPTED]".to_string()); // Placeholder to indicate encryption
+            vault.webhooks = metadata.webhooks;
+            vault.failed_unlocks = metadata.failed_unlocks;
+            vault.locked_notifications = metadata.notifications;
+
+            // Load API keys metadata (with encrypted key data)
+            for api_key_meta in metadata.api_keys_metadata {
+                let api_key = ApiKey {
+                    id: api_key_meta.id.clone(),
+                    name: api_key_meta.name,
+                    service: api_key_meta.service,
+                    key: api_key_meta.key.clone(), // Placeholder for encrypted key
+                    description: api_key_meta.description,
+                    environment: api_key_meta.environment,
+                    quota: api_key_meta.quota,
+                    cost: api_key_meta.cost,
+                    expires_at: api_key_meta.expires_at,
+                    scopes: api_key_meta.scopes,
+                    created_at: api_key_meta.created_at,
+                    updated_at: api_key_meta.updated_at,
+                    tags: api_key_meta.tags,
+                    is_active: api_key_meta.is_active,
+                    source_type: api_key_meta.source_type,
+                    env_file_path: api_key_meta.env_file_path,
+                    project_path: api_key_meta.project_path,
+                    env_file_name: api_key_meta.env_file_name,
+                    remote_secrets: Vec::new(),
+                    package_path: api_key_meta.package_path,
+                    rotation_interval_days: api_key_meta.rotation_interval_days,
+                    last_rotated_at: api_key_meta.last_rotated_at,
+                    kind: api_key_meta.kind,
+                    credential: api_key_meta.credential,
+                    oauth: api_key_meta.oauth,
+                    values: api_key_meta.values,
+                    is_pinned: api_key_meta.is_pinned,
+                    archived_at: api_key_meta.archived_at,
+                    last_used_at: api_key_meta.last_used_at,
+                    backend: api_key_meta.backend,
+                };
+                vault.keys.insert(api_key_meta.id, api_key);
+            }
+
+            return Ok(vault);
+        }
+    }
+
+    // Without readable metadata there's no salt to unlock with. Keep the
+    // placeholder key so the encrypted file is never overwritten.
+    warn!("Vault is encrypted but its metadata is missing or unreadable");
+    Ok(ApiKeyVault {
+        encryption_key: Some("[ENCRYPTED]".to_string()),
+        ..ApiKeyVault::default()
+    })
+}
+
+// Decrypt vault file using password
+fn decrypt_vault_from(
+    storage: &dyn vault_storage::VaultStorage,
+    password: &str,
+) -> Result<ApiKeyVault, String> {
+    let encrypted_contents = storage
+        .read_vault()?
+        .ok_or_else(|| "Vault file not found".to_string())?;
+
+    // Try to parse as JSON first (unencrypted vault)
+    if is_plaintext_vault(&encrypted_contents) {
+        return migrations::load_vault_document(&encrypted_contents, storage);
+    }
+
+    // File is encrypted, need to decrypt it
+    // Get salt from metadata file
+    let metadata_contents = storage
+        .read_metadata()?
+        .ok_or_else(|| "Metadata file not found for encrypted vault".to_string())?;
+    let metadata: VaultMetadata = serde_json::from_str(&metadata_contents)
+        .map_err(|e| format!("Failed to parse metadata: {}", e))?;
+    migrations::ensure_supported(metadata.schema_version, Some(&metadata.version))?;
+
+    let salt = metadata.salt.ok_or("No salt found in metadata")?;
+    let salt_bytes = general_purpose::STANDARD
+        .decode(&salt)
+        .map_err(|e| format!("Failed to decode salt: {}", e))?;
+
+    // Derive key from password and salt
+    let key = derive_key_from_password(password, &salt_bytes);
+
+    // Decrypt the vault data
+    let decrypted_json = Zeroizing::new(decrypt_data(&encrypted_contents, &*key)?);
+
+    // Parse decrypted JSON, upgrading older schemas
+    migrations::load_vault_document(&decrypted_json, storage)
+}
+
+/// Encrypted vaults are stored as an opaque string, plaintext ones as a JSON object
+fn is_plaintext_vault(contents: &str) -> bool {
+    contents.trim_start().starts_with('{')
+}
+
+// Encryption/Decryption utilities for vault data
+fn encrypt_data(data: &str, key: &[u8]) -> Result<String, String> {
+    if key.len() != 32 {
+        return Err("Invalid encryption key length".to_string());
+    }
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let mut nonce_bytes = [0u8; 12]; // 96 bits for AES-GCM
+    OsRng.fill_bytes(&mut nonce_bytes);
+    let nonce = Nonce::from_slice(&nonce_bytes);
+
+    let ciphertext = cipher
+        .encrypt(nonce, data.as_bytes())
+        .map_err(|e| format!("Encryption failed: {}", e))?;
+
+    // Combine nonce and ciphertext
+    let mut result = nonce_bytes.to_vec();
+    result.extend_from_slice(&ciphertext);
+
+    Ok(general_purpose::STANDARD.encode(result))
+}
+
+fn decrypt_data(encrypted_data: &str, key: &[u8]) -> Result<String, String> {
+    let data = general_purpose::STANDARD
+        .decode(encrypted_data)
+        .map_err(|e| format!("Base64 decode failed: {}", e))?;
+
+    if data.len() < 12 {
+        return Err("Invalid encrypted data length".to_string());
+    }
+    if key.len() != 32 {
+        return Err("Invalid encryption key length".to_string());
+    }
+
+    let (nonce_bytes, ciphertext) = data.split_at(12);
+    let nonce = Nonce::from_slice(nonce_bytes);
+
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let plaintext = cipher
+        .decrypt(nonce, ciphertext)
+        .map_err(|e| format!("Decryption failed: {}", e))?;
+
+    String::from_utf8(plaintext).map_err(|e| {
+        let message = format!("UTF-8 conversion failed: {}", e);
+        e.into_bytes().zeroize();
+        message
+    })
+}
+
+fn derive_key_from_password(password: &str, salt: &[u8]) -> secure_memory::SecretKey {
+    // Enterprise-grade key derivation using PBKDF2 with SHA-256
+    let mut key = secure_memory::SecretKey::zeroed();
+    let iterations = 100_000; // OWASP recommended minimum
+
+    pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, iterations, key.as_mut_bytes());
+
+    key
+}
+
+// ===============================
+//  ENV FILE PARSING AND PROJECT ASSOCIATION
+// ===============================
+
+fn parse_env_file(file_path: &str) -> Result<Vec<EnvVariable>, String> {
+    let content =
+        fs::read_to_string(file_path).map_err(|e| format!("Failed to read .env file: {}", e))?;
+
+    let variables = env_file::EnvFile::parse(&content)
+        .vars()
+        .into_iter()
+        .map(|(name, value)| {
+            // Determine if it's a secret based on common patterns
+            let is_secret = is_secret_variable(&name, &value);
+            EnvVariable {
+                name,
+                value,
+                is_secret,
+            }
+        })
+        .collect();
+
+    Ok(variables)
+}
+
+fn is_secret_variable(name: &str, value: &str) -> bool {
+    let name_lower = name.to_lowercase();
+
+    // Common secret patterns
+    let secret_patterns = [
+        "key",
+        "secret",
+        "token",
+        "password",
+        "pass",
+        "pwd",
+        "auth",
+        "api_key",
+        "private",
+        "credential",
+        "cert",
+        "signature",
+        "access_token",
+        "refresh_token",
+        "client_secret",
+        "webhook_secret",
+    ];
+
+    // Check if name contains secret patterns
+    let name_is_secret = secret_patterns
+        .iter()
+        .any(|pattern| name_lower.contains(pattern));
+
+    // Check value patterns (long base64-like strings, UUIDs, etc.)
+    let value_looks_secret = value.len() > 20
+        && (value.chars().all(|c| c.is_alphanumeric() || "=+/".contains(c)) || // base64-like
+         value.contains('-') && value.len() > 30); // UUID-like
+
+    name_is_secret || value_looks_secret
+}
+
+// Environment variable name used when a key is written to .env files or injected into processes
+fn env_var_name_for_key(api_key: &ApiKey) -> String {
+    format!(
+        "{}_API_KEY",
+        api_key.service.to_uppercase().replace(" ", "_")
+    )
+}
+
+fn detect_project_path(env_file_path: &str) -> Result<String, String> {
+    let path = std::path::Path::new(env_file_path);
+
+    if let Some(parent) = path.parent() {
+        // In a monorepo the project is the whole workspace, not the package
+        // whose manifest happens to be closest
+        if let Some(root) = monorepo::find_workspace_root(parent) {
+            return Ok(root.to_string_lossy().to_string());
+        }
+
+        // Look for common project indicators
+        let project_indicators = [
+            "package.json",
+            "Cargo.toml",
+            ".git",
+            "composer.json",
+            "requirements.txt",
+            "go.mod",
+            "pom.xml",
+            "build.gradle",
+        ];
+
+        let mut current_dir = parent;
+
+        // Walk up the directory tree looking for project indicators
+        loop {
+            for indicator in &project_indicators {
+                if current_dir.join(indicator).exists() {
+                    return Ok(current_dir.to_string_lossy().to_string());
+                }
+            }
+
+            // Move up one directory
+            if let Some(parent_dir) = current_dir.parent() {
+                current_dir = parent_dir;
+            } else {
+                // Reached root, use the .env file's directory
+                break;
+            }
+        }
+
+        // If no project indicators found, use the .env file's directory
+        Ok(parent.to_string_lossy().to_string())
+    } else {
+        Err("Cannot determine project path".to_string())
+    }
+}
+
+#[tauri::command]
+async fn parse_and_register_env_file(
+    file_path: String,
+    state: State<'_, AppState>,
+) -> Result<DroppedEnvFile, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    info!("Parsing .env file: {}", file_path);
+
+    // Parse the .env file
+    let variables = parse_env_file(&file_path).map_err(KeyKeeperError::Io)?;
+
+    // Detect project path
+    let project_path = detect_project_path(&file_path)?;
+    let packages = monorepo::list_packages(std::path::Path::new(&project_path));
+    let package_path = monorepo::package_for_path(
+        std::path::Path::new(&project_path),
+        &packages,
+        std::path::Path::new(&file_path),
+    )
+    .map(|p| p.path.clone());
+
+    // Extract file name
+    let file_name = std::path::Path::new(&file_path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("unknown.env")
+        .to_string();
+
+    log_audit_event(
+        &state,
+        "parse_env_file",
+        "env_file",
+        Some(&file_path),
+        true,
+        None,
+    )
+    .await;
+
+    Ok(DroppedEnvFile {
+        path: file_path,
+        project_path,
+        package_path,
+        file_name,
+        keys: variables,
+    })
+}
+
+/// Add or replace the association of `env_path` with `project_path`
+fn upsert_env_association(
+    vault_guard: &mut ApiKeyVault,
+    project_path: &str,
+    env_path: &str,
+    file_name: String,
+    package_path: Option<String>,
+) {
+    // Without an explicit package, infer it from where the env file lives
+    let package_path = package_path.or_else(|| {
+        let packages = vault_guard
+            .projects
+            .values()
+            .find(|p| p.path == project_path)
+            .map(|p| p.packages.clone())
+            .unwrap_or_else(|| monorepo::list_packages(std::path::Path::new(project_path)));
+        monorepo::package_for_path(
+            std::path::Path::new(project_path),
+            &packages,
+            std::path::Path::new(env_path),
+        )
+        .map(|p| p.path.clone())
+    });
+
+    // Check if association already exists
+    let existing_index = vault_guard
+        .env_associations
+        .iter()
+        .position(|assoc| assoc.project_path == project_path && assoc.env_file_path == env_path);
+
+    let association = ProjectEnvAssociation {
+        id: format!("env_assoc_{}", get_utc_timestamp_millis()),
+        project_path: project_path.to_string(),
+        env_file_path: env_path.to_string(),
+        env_file_name: file_name,
+        created_at: Utc::now(),
+        last_accessed: Utc::now(),
+        is_active: true,
+        vscode_status: Some("unknown".to_string()),
+        last_vscode_check: None,
+        package_path,
+    };
+
+    if let Some(index) = existing_index {
+        // Update existing association
+        vault_guard.env_associations[index] = association;
+    } else {
+        // Add new association
+        vault_guard.env_associations.push(association);
+    }
+}
+
+#[tauri::command]
+async fn associate_project_with_env(
+    project_path: String,
+    env_path: String,
+    file_name: String,
+    package_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    upsert_env_association(&mut vault_guard, &project_path, &env_path, file_name, package_path);
+    drop(vault_guard);
+    save_vault(&state).await?;
+
+    log_audit_event(
+        &state,
+        "associate_project_env",
+        "project",
+        Some(&project_path),
+        true,
+        None,
+    )
+    .await;
+
+    info!(
+        "Associated project {} with env file {}",
+        project_path, env_path
+    );
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_project_env_associations(
+    project_path: Option<String>,
+    package_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ProjectEnvAssociation>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+
+    let associations = if let Some(path) = project_path {
+        vault_guard
+            .env_associations
+            .iter()
+            .filter(|assoc| assoc.project_path == path && assoc.is_active)
+            .filter(|assoc| package_path.is_none() || assoc.package_path == package_path)
+            .cloned()
+            .collect()
+    } else {
+        vault_guard.env_associations.clone()
+    };
+
+    Ok(associations)
+}
+
+#[tauri::command]
+async fn activate_project_context(
+    project_path: String,
+    state: State<'_, AppState>,
+) -> Result<bool, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+
+    // Update last_accessed for matching associations
+    let mut found = false;
+    for association in &mut vault_guard.env_associations {
+        if association.project_path == project_path && association.is_active {
+            association.last_accessed = Utc::now();
+            found = true;
+        }
+    }
+
+    if found {
+        drop(vault_guard);
+        save_vault(&state).await?;
+        log_audit_event(
+            &state,
+            "activate_project_context",
+            "project",
+            Some(&project_path),
+            true,
+            None,
+        )
+        .await;
+    }
+
+    Ok(found)
+}
+
+// ===============================
+//  BIOMETRIC & USER MANAGEMENT
+// ===============================
+
+#[tauri::command]
+async fn check_biometric_support() -> Result<bool, KeyKeeperError> {
+    // Check if device supports biometric authentication
+    #[cfg(target_os = "macos")]
+    {
+        // On macOS, check for Touch ID or Face ID availability
+        Ok(true) // Simplified - in reality would check LAContext.canEvaluatePolicy
+    }
+    #[cfg(target_os = "windows")]
+    {
+        // On Windows, check for Windows Hello availability
+        Ok(true) // Simplified - in reality would check Windows Hello API
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        Ok(false)
+    }
+}
+
+#[tauri::command]
+async fn enable_biometric_auth(
+    user_id: String,
+    credential_name: String,
+    state: State<'_, AppState>,
+) -> Result<String, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+
+    if let Some(ref mut user_account) = vault_guard.user_account {
+        if user_account.id == user_id {
+            // Generate a new passkey credential
+            let credential_id = Uuid::new_v4().to_string();
+            let passkey = PasskeyCredential {
+                id: credential_id.clone(),
+                name: credential_name,
+                credential_id: credential_id.as_bytes().to_vec(),
+                public_key: Vec::new(), // Would be populated by WebAuthn
+                created_at: Utc::now(),
+                last_used: None,
+                device_info: get_device_platform(),
+            };
+
+            user_account.passkey_credentials.push(passkey);
+            user_account.biometric_enabled = true;
+            user_account.updated_at = Utc::now();
+
+            drop(vault_guard);
+            save_vault(&state).await?;
+            log_audit_event(
+                &state,
+                "enable_biometric",
+                "user",
+                Some(&user_id),
+                true,
+                None,
+            )
+            .await;
+
+            Ok(credential_id)
+        } else {
+            Err(KeyKeeperError::Validation("User ID mismatch".to_string()))
+        }
+    } else {
+        Err(KeyKeeperError::not_found("User account"))
+    }
+}
+
+#[tauri::command]
+async fn authenticate_biometric(
+    credential_id: String,
+    state: State<'_, AppState>,
+) -> Result<String, KeyKeeperError> {
+    let mut vault_guard = state.vault.lock().await;
+
+    if let Some(ref mut user_account) = vault_guard.user_account {
+        // Find the credential
+        if let Some(credential) = user_account
+            .passkey_credentials
+            .iter_mut()
+            .find(|c| c.id == credential_id)
+        {
+            // Update last used timestamp
+            credential.last_used = Some(Utc::now());
+
+            // Create a biometric session
+            let session_id = Uuid::new_v4().to_string();
+            let user_id = user_account.id.clone();
+            let session_timeout = user_account.session_timeout;
+
+            let session = BiometricSession {
+                user_id: user_id.clone(),
+                session_id: session_id.clone(),
+                created_at: Utc::now(),
+                expires_at: get_future_timestamp(session_timeout),
+                device_id: get_device_id(),
+                is_active: true,
+            };
+
+            vault_guard.biometric_sessions.push(session);
+            devices::touch(&mut vault_guard, &get_device_id(), Utc::now());
+
+            // Unlock the vault
+            *state.is_unlocked.lock().await = true;
+
+            drop(vault_guard);
+            sessions::set_unlock_session(&state, &session_id).await;
+            save_vault(&state).await?;
+            log_audit_event(&state, "biometric_auth", "user", Some(&user_id), true, None).await;
+
+            Ok(session_id)
+        } else {
+            drop(vault_guard);
+            log_audit_event(
+                &state,
+                "biometric_auth",
+                "user",
+                None,
+                false,
+                Some("Invalid credential"),
+            )
+            .await;
+            Err(KeyKeeperError::Validation("Invalid credential".to_string()))
+        }
+    } else {
+        drop(vault_guard);
+        Err(KeyKeeperError::not_found("User account"))
+    }
+}
+
+#[tauri::command]
+async fn update_user_preferences(
+    preferences: UserPreferences,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    logging::apply(&preferences.logging)?;
+
+    let mut vault_guard = state.vault.lock().await;
+
+    if let Some(ref mut user_account) = vault_guard.user_account {
+        let user_id = user_account.id.clone();
+        user_account.preferences = preferences;
+        user_account.updated_at = Utc::now();
+
+        drop(vault_guard);
+        save_vault(&state).await?;
+        tray_status::refresh(&state).await;
+        log_audit_event(
+            &state,
+            "update_preferences",
+            "user",
+            Some(&user_id),
+            true,
+            None,
+        )
+        .await;
+
+        Ok(())
+    } else {
+        drop(vault_guard);
+        Err(KeyKeeperError::not_found("User account"))
+    }
+}
+
+#[tauri::command]
+async fn get_user_preferences(state: State<'_, AppState>) -> Result<UserPreferences, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+
+    if let Some(ref user_account) = vault_guard.user_account {
+        Ok(user_account.preferences.clone())
+    } else {
+        // Return default preferences if no user account
+        Ok(UserPreferences {
+            theme: "system".to_string(),
+            language: "en".to_string(),
+            auto_lock_timeout: 15,
+            clipboard_clear_timeout: 30,
+            show_notifications: true,
+            audit_logging: true,
+            biometric_unlock: false,
+            auto_backup: true,
+            encryption_level: "enhanced".to_string(),
+            notifications: notifications::NotificationSettings::default(),
+            logging: logging::LogSettings::default(),
+            env_sync_mode: env_refs::EnvSyncMode::default(),
+            key_ordering: pinning::KeyOrdering::default(),
+        })
+    }
+}
+
+#[tauri::command]
+async fn create_passkey_challenge(
+    user_id: String,
+    challenge_type: String,
+    state: State<'_, AppState>,
+) -> Result<String, KeyKeeperError> {
+    let mut vault_guard = state.vault.lock().await;
+
+    // Generate a new challenge
+    let challenge_id = Uuid::new_v4().to_string();
+    let challenge = WebAuthnChallenge {
+        challenge: challenge_id.clone(),
+        user_id: user_id.clone(),
+        created_at: Utc::now(),
+        expires_at: get_future_timestamp(5), // 5 minutes
+        challenge_type,
+    };
+
+    vault_guard.webauthn_challenges.push(challenge);
+
+    drop(vault_guard);
+    save_vault(&state).await?;
+
+    Ok(challenge_id)
+}
+
+#[tauri::command]
+async fn verify_passkey_challenge(
+    challenge_id: String,
+    response: String,
+    state: State<'_, AppState>,
+) -> Result<bool, KeyKeeperError> {
+    let mut vault_guard = state.vault.lock().await;
+
+    // Find and validate the challenge
+    if let Some(challenge_index) = vault_guard
+        .webauthn_challenges
+        .iter()
+        .position(|c| c.challenge == challenge_id)
+    {
+        let challenge = vault_guard.webauthn_challenges.remove(challenge_index);
+
+        // Check if challenge is still valid (not expired)
+        if Utc::now() > challenge.expires_at {
+            return Err(KeyKeeperError::Validation("Challenge expired".to_string()));
+        }
+
+        // In a real implementation, you would verify the WebAuthn response here
+        // For now, we'll assume the response is valid if it's not empty
+        if !response.is_empty() {
+            log_audit_event(
+                &state,
+                "verify_passkey",
+                "user",
+                Some(&challenge.user_id),
+                true,
+                None,
+            )
+            .await;
+            Ok(true)
+        } else {
+            log_audit_event(
+                &state,
+                "verify_passkey",
+                "user",
+                Some(&challenge.user_id),
+                false,
+                Some("Invalid response"),
+            )
+            .await;
+            Err(KeyKeeperError::Validation("Invalid passkey response".to_string()))
+        }
+    } else {
+        Err(KeyKeeperError::not_found("Challenge"))
+    }
+}
+
+#[tauri::command]
+async fn invalidate_biometric_sessions(
+    user_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let mut vault_guard = state.vault.lock().await;
+
+    // Mark all sessions as inactive
+    for session in &mut vault_guard.biometric_sessions {
+        if session.user_id == user_id {
+            session.is_active = false;
+        }
+    }
+
+    drop(vault_guard);
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "invalidate_sessions",
+        "user",
+        Some(&user_id),
+        true,
+        None,
+    )
+    .await;
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn update_vscode_workspaces(
+    workspaces: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+
+    // Replace the pushed workspaces and update project association statuses
+    workspace_watcher::apply_pushed(&mut vault_guard, workspaces, Utc::now());
+
+    drop(vault_guard);
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "update_vscode_workspaces",
+        "workspace",
+        None,
+        true,
+        None,
+    )
+    .await;
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_vscode_workspaces(state: State<'_, AppState>) -> Result<Vec<VSCodeWorkspace>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    Ok(vault_guard.vscode_workspaces.clone())
+}
+
+#[tauri::command]
+async fn get_project_vscode_status(
+    project_path: String,
+    state: State<'_, AppState>,
+) -> Result<Option<String>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+
+    // Check if project is in VSCode workspaces
+    let is_open = vault_guard
+        .vscode_workspaces
+        .iter()
+        .any(|ws| ws.path == project_path || project_path.starts_with(&ws.path));
+
+    if is_open {
+        Ok(Some("open".to_string()))
+    } else {
+        // Check if we have any VSCode workspaces at all
+        if vault_guard.vscode_workspaces.is_empty() {
+            Ok(Some("unknown".to_string()))
+        } else {
+            Ok(Some("closed".to_string()))
+        }
+    }
+}
+
+#[tauri::command]
+async fn open_folder(path: String) -> Result<(), KeyKeeperError> {
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .arg(&path)
+            .spawn()
+            .map_err(|e| KeyKeeperError::Io(format!("Failed to open folder: {}", e)))?;
+    }
+    #[cfg(target_os = "windows")]
+    {
+        std::process::Command::new("explorer")
+            .arg(&path)
+            .spawn()
+            .map_err(|e| KeyKeeperError::Io(format!("Failed to open folder: {}", e)))?;
+    }
+    #[cfg(target_os = "linux")]
+    {
+        std::process::Command::new("xdg-open")
+            .arg(&path)
+            .spawn()
+            .map_err(|e| KeyKeeperError::Io(format!("Failed to open folder: {}", e)))?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn open_file(path: String) -> Result<(), KeyKeeperError> {
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .arg(&path)
+            .spawn()
+            .map_err(|e| KeyKeeperError::Io(format!("Failed to open file: {}", e)))?;
+    }
+    #[cfg(target_os = "windows")]
+    {
+        std::process::Command::new("cmd")
+            .args(["/C", "start", "", &path])
+            .spawn()
+            .map_err(|e| KeyKeeperError::Io(format!("Failed to open file: {}", e)))?;
+    }
+    #[cfg(target_os = "linux")]
+    {
+        std::process::Command::new("xdg-open")
+            .arg(&path)
+            .spawn()
+            .map_err(|e| KeyKeeperError::Io(format!("Failed to open file: {}", e)))?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn open_in_vscode(path: String) -> Result<(), KeyKeeperError> {
+    std::process::Command::new("code")
+        .arg(&path)
+        .spawn()
+        .map_err(|e| KeyKeeperError::Io(format!("Failed to open in VSCode: {}", e)))?;
+    Ok(())
+}
+
+// Session management for persistent login
+#[tauri::command]
+async fn restore_session_on_startup(state: State<'_, AppState>) -> Result<bool, KeyKeeperError> {
+    // Check if user account exists and if vault is already unlocked
+    let vault_guard = state.vault.lock().await;
+    let has_user = vault_guard.user_account.is_some();
+    drop(vault_guard);
+
+    if has_user {
+        // Check if vault is already unlocked from a previous session
+        let is_unlocked = {
+            let unlocked_guard = state.is_unlocked.lock().await;
+            *unlocked_guard
+        };
+
+        if is_unlocked {
+            log_audit_event(
+                &state,
+                "restore_session",
+                "session",
+                None,
+                true,
+                Some("Vault already unlocked"),
+            )
+            .await;
+            info!("Session restored with vault already unlocked");
+        } else {
+            log_audit_event(
+                &state,
+                "restore_session",
+                "session",
+                None,
+                true,
+                Some("User account found, vault locked"),
+            )
+            .await;
+            info!("Session restored but vault requires unlock");
+        }
+
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+// Window Management Commands
+#[tauri::command]
+async fn show_window(app: AppHandle) -> Result<(), KeyKeeperError> {
+    if let Some(window) = app.get_webview_window("main") {
+        window.show().map_err(|e| e.to_string())?;
+        window.set_focus().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn hide_window(app: AppHandle) -> Result<(), KeyKeeperError> {
+    if let Some(window) = app.get_webview_window("main") {
+        window.hide().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn quit_application(app: AppHandle) -> Result<(), KeyKeeperError> {
+    shutdown::run(&app).await;
+    app.exit(0);
+    Ok(())
+}
+
+// Update Commands with proper implementation
+#[tauri::command]
+async fn check_for_updates(app: AppHandle) -> Result<serde_json::Value, KeyKeeperError> {
+    match app.updater() {
+        Ok(updater) => match updater.check().await {
+            Ok(Some(update)) => Ok(serde_json::json!({
+                "available": true,
+                "version": update.version,
+                "date": update.date,
+                "body": update.body
+            })),
+            Ok(None) => Ok(serde_json::json!({
+                "available": false,
+                "message": "No updates available"
+            })),
+            Err(e) => Err(KeyKeeperError::Internal(format!("Failed to check for updates: {}", e))),
+        },
+        Err(e) => Err(KeyKeeperError::Internal(format!("Updater not available: {}", e))),
+    }
+}
+
+#[tauri::command]
+async fn install_update(app: AppHandle) -> Result<(), KeyKeeperError> {
+    match app.updater() {
+        Ok(updater) => {
+            // First check for updates
+            match updater.check().await {
+                Ok(Some(update)) => {
+                    // Download and install the update
+                    match update
+                        .download_and_install(
+                            |_chunk_length, _content_length| {
+                                // Progress callback - could be used for progress reporting
+                            },
+                            || {
+                                // Download finished callback
+                                info!("Update download completed");
+                            },
+                        )
+                        .await
+                    {
+                        Ok(_) => {
+                            info!("Update installed successfully");
+                            Ok(())
+                        }
+                        Err(e) => Err(KeyKeeperError::Internal(format!("Failed to install update: {}", e))),
+                    }
+                }
+                Ok(None) => Err(KeyKeeperError::Validation("No updates available to install".to_string())),
+                Err(e) => Err(KeyKeeperError::Internal(format!("Failed to check for updates: {}", e))),
+            }
+        }
+        Err(e) => Err(KeyKeeperError::Internal(format!("Updater not available: {}", e))),
+    }
+}
+
+// Additional Session Management Commands
+#[tauri::command]
+async fn create_remember_me_session(
+    user_id: String,
+    timeout_minutes: u64,
+    state: State<'_, AppState>,
+) -> Result<String, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let session_id = format!("session_{}", Uuid::new_v4());
+    let expires_at = get_future_timestamp(timeout_minutes);
+
+    let session = PersistentSession {
+        session_id: session_id.clone(),
+        user_id,
+        created_at: Utc::now(),
+        expires_at,
+        last_accessed: Utc::now(),
+        device_info: get_device_platform(),
+        device_id: Some(get_device_id()),
+        is_remember_me: true,
+        is_active: true,
+    };
+
+    let mut vault_guard = state.vault.lock().await;
+    vault_guard.persistent_sessions.push(session);
+    devices::touch(&mut vault_guard, &get_device_id(), Utc::now());
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "create_remember_session",
+        "session",
+        Some(&session_id),
+        true,
+        None,
+    )
+    .await;
+
+    Ok(session_id)
+}
+
+#[tauri::command]
+async fn validate_remember_me_session(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<bool, KeyKeeperError> {
+    let vault_guard = state.vault.lock().await;
+
+    if let Some(session) = vault_guard
+        .persistent_sessions
+        .iter()
+        .find(|s| s.session_id == session_id && s.is_active)
+    {
+        // Check if session is not expired
+        if Utc::now() < session.expires_at {
+            drop(vault_guard);
+            log_audit_event(
+                &state,
+                "validate_remember_session",
+                "session",
+                Some(&session_id),
+                true,
+                None,
+            )
+            .await;
+            return Ok(true);
+        }
+    }
+
+    drop(vault_guard);
+    log_audit_event(
+        &state,
+        "validate_remember_session",
+        "session",
+        Some(&session_id),
+        false,
+        Some("Session invalid or expired"),
+    )
+    .await;
+    Ok(false)
+}
+
+#[tauri::command]
+async fn get_persistent_sessions(
+    state: State<'_, AppState>,
+) -> Result<Vec<PersistentSession>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let vault_guard = state.vault.lock().await;
+    Ok(vault_guard.persistent_sessions.clone())
+}
+
+#[tauri::command]
+async fn revoke_persistent_session(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let mut vault_guard = state.vault.lock().await;
+
+    if let Some(session) = vault_guard
+        .persistent_sessions
+        .iter_mut()
+        .find(|s| s.session_id == session_id)
+    {
+        session.is_active = false;
+    }
+
+    drop(vault_guard);
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "revoke_persistent_session",
+        "session",
+        Some(&session_id),
+        true,
+        None,
+    )
+    .await;
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn cleanup_all_sessions(state: State<'_, AppState>) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let mut vault_guard = state.vault.lock().await;
+    vault_guard.persistent_sessions.clear();
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(&state, "cleanup_all_sessions", "session", None, true, None).await;
+
+    Ok(())
+}
+
+// User Management Commands
+#[tauri::command]
+async fn update_username(new_username: String, state: State<'_, AppState>) -> Result<(), KeyKeeperError> {
+    guards::require(&state, &[guards::Requirement::Unlocked, guards::Requirement::Account]).await?;
+    let mut vault_guard = state.vault.lock().await;
+
+    if let Some(user_account) = &mut vault_guard.user_account {
+        user_account.username = new_username.clone();
+        user_account.updated_at = Utc::now();
+    } else {
+        return Err(KeyKeeperError::not_found("User account"));
+    }
+
+    drop(vault_guard);
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "update_username",
+        "user",
+        Some(&new_username),
+        true,
+        None,
+    )
+    .await;
+
+    Ok(())
+}
+
+// ================================
+// Documentation Store File Operations
+// ================================
+
+async fn save_docs_store(state: &State<'_, AppState>) -> Result<(), KeyKeeperError> {
+    let _task = state.health.begin_task("docs_save");
+    // Names to link docs to are only known while the vault is unlocked
+    let catalog = if *state.is_unlocked.lock().await {
+        Some(doc_refs::Catalog::from_vault(&*state.vault.lock().await))
+    } else {
+        None
+    };
+    let mut docs_store_guard = state.docs_store.lock().await;
+    if let Some(catalog) = &catalog {
+        doc_refs::extract_stale(&mut docs_store_guard, catalog);
+    }
+    let docs_path = &state.docs_store_path;
+    
+    // Create parent directory if it doesn't exist
+    if let Some(parent) = docs_path.parent() {
+        tokio::fs::create_dir_all(parent).await
+            .map_err(|e| KeyKeeperError::Io(format!("Failed to create docs directory: {}", e)))?;
+    }
+    
+    // Serialize docs store to JSON
+    let docs_json = serde_json::to_string_pretty(&*docs_store_guard)
+        .map_err(|e| KeyKeeperError::Internal(format!("Failed to serialize docs store: {}", e)))?;
+    
+    // Write to file
+    tokio::fs::write(docs_path, docs_json).await
+        .map_err(|e| KeyKeeperError::Io(format!("Failed to write docs store: {}", e)))?;
+    doc_attachments::delete_released(&mut docs_store_guard, &doc_attachments::blob_dir(docs_path));
+    
+    info!("Documentation store saved to: {:?}", docs_path);
+    Ok(())
+}
+
+/// Load the docs store, falling back to an empty one. The error, if any, is
+/// returned alongside for the health report.
+async fn load_docs_store(docs_store_path: &PathBuf) -> (DocsStore, Option<String>) {
+    if !docs_store_path.exists() {
+        info!("Documentation store file not found, creating new one: {:?}", docs_store_path);
+        return (DocsStore::new(), None);
+    }
+    
+    match tokio::fs::read_to_string(docs_store_path).await {
+        Ok(content) => {
+            match serde_json::from_str::<DocsStore>(&content) {
+                Ok(mut docs_store) => {
+                    docs_store.rebuild_tag_index();
+                    let unscanned: Vec<String> = docs_store.docs.keys()
+                        .filter(|id| !docs_store.references.contains_key(*id))
+                        .cloned()
+                        .collect();
+                    docs_store.stale_references.extend(unscanned);
+                    info!("Loaded documentation store with {} documents", docs_store.docs.len());
+                    (docs_store, None)
+                }
+                Err(e) => {
+                    warn!("Failed to parse docs store, creating new one: {}", e);
+                    (DocsStore::new(), Some(format!("Failed to parse docs store: {}", e)))
+                }
+            }
+        }
+        Err(e) => {
+            warn!("Failed to read docs store file, creating new one: {}", e);
+            (DocsStore::new(), Some(format!("Failed to read docs store: {}", e)))
+        }
+    }
+}
+
+// ================================
+// Documentation CRUD Commands
+// ================================
+
+#[tauri::command]
+async fn get_documentation(state: State<'_, AppState>) -> Result<Vec<Documentation>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let docs_store_guard = state.docs_store.lock().await;
+    Ok(docs_store_guard.get_all_docs().into_iter().cloned().collect())
+}
+
+#[tauri::command]
+async fn create_documentation(
+    title: String,
+    content: String,
+    doc_type: String,
+    project_id: Option<String>,
+    provider_id: Option<String>,
+    url: Option<String>,
+    tags: Option<Vec<String>>,
+    language: Option<String>,
+    search_keywords: Option<Vec<String>>,
+    state: State<'_, AppState>,
+) -> Result<Documentation, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let mut docs_store_guard = state.docs_store.lock().await;
+
+    // Generate unique documentation ID
+    let doc_id = format!("doc_{}", get_utc_timestamp_millis());
+
+    let documentation = Documentation {
+        id: doc_id.clone(),
+        title: title.clone(),
+        content,
+        doc_type,
+        project_id,
+        provider_id,
+        url,
+        tags: tags.unwrap_or_default(),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        language: language.unwrap_or_else(|| "en".to_string()),
+        is_favorite: false,
+        search_keywords: search_keywords.unwrap_or_default(),
+        sections: Vec::new(),
+        is_offline: false,
+        attachments: Vec::new(),
+    };
+
+    docs_store_guard.add_doc(documentation.clone());
+    drop(docs_store_guard);
+
+    save_docs_store(&state).await?;
+    
+    log_audit_event(&state, "create_documentation", "documentation", Some(&doc_id), true, None).await;
+
+    info!("Documentation created successfully: {} ({})", title, doc_id);
+    Ok(documentation)
+}
+
+#[tauri::command]
+async fn update_documentation(
+    id: String,
+    title: Option<String>,
+    content: Option<String>,
+    doc_type: Option<String>,
+    project_id: Option<String>,
+    provider_id: Option<String>,
+    url: Option<String>,
+    tags: Option<Vec<String>>,
+    language: Option<String>,
+    is_favorite: Option<bool>,
+    search_keywords: Option<Vec<String>>,
+    state: State<'_, AppState>,
+) -> Result<Documentation, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let mut docs_store_guard = state.docs_store.lock().await;
+
+    if let Some(mut documentation) = docs_store_guard.get_doc(&id).cloned() {
+        // Update fields if provided
+        if let Some(new_title) = title {
+            documentation.title = new_title;
+        }
+        if let Some(new_content) = content {
+            documentation.content = new_content;
+        }
+        if let Some(new_doc_type) = doc_type {
+            documentation.doc_type = new_doc_type;
+        }
+        if let Some(new_project_id) = project_id {
+            documentation.project_id = Some(new_project_id);
+        }
+        if let Some(new_provider_id) = provider_id {
+            documentation.provider_id = Some(new_provider_id);
+        }
+        if let Some(new_url) = url {
+            documentation.url = Some(new_url);
+        }
+        if let Some(new_tags) = tags {
+            documentation.tags = new_tags;
+        }
+        if let Some(new_language) = language {
+            documentation.language = new_language;
+        }
+        if let Some(new_is_favorite) = is_favorite {
+            documentation.is_favorite = new_is_favorite;
+        }
+        if let Some(new_keywords) = search_keywords {
+            documentation.search_keywords = new_keywords;
+        }
+
+        documentation.updated_at = Utc::now();
+
+        docs_store_guard.update_doc(documentation.clone());
+        drop(docs_store_guard);
+
+        save_docs_store(&state).await?;
+        
+        log_audit_event(&state, "update_documentation", "documentation", Some(&id), true, None).await;
+
+        info!("Documentation updated successfully: {}", id);
+        Ok(documentation)
+    } else {
+        Err(KeyKeeperError::not_found("Documentation"))
+    }
+}
+
+#[tauri::command]
+async fn delete_documentation(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let mut docs_store_guard = state.docs_store.lock().await;
+
+    // Remove the documentation
+    let doc = docs_store_guard.remove_doc(&id)
+        .ok_or_else(|| KeyKeeperError::not_found("Documentation"))?;
+    drop(docs_store_guard);
+
+    save_docs_store(&state).await?;
+    
+    log_audit_event(&state, "delete_documentation", "documentation", Some(&id), true, None).await;
+
+    info!("Documentation deleted successfully: {} ({})", doc.title, id);
+    Ok(())
+}
+
+#[tauri::command]
+async fn search_native_documentation(
+    query: String,
+    project_id: Option<String>,
+    provider_id: Option<String>,
+    doc_type: Option<String>,
+    tags: Option<Vec<String>>,
+    favorites_only: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Vec<Documentation>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let docs_store_guard = state.docs_store.lock().await;
+    let query_lower = query.to_lowercase();
+
+    // Tag filters are looked up in the tag index; without a query they pick
+    // the candidates on their own
+    let tagged = tags
+        .as_ref()
+        .map(|tags| doc_tags::docs_with_any(&docs_store_guard.tag_index, tags));
+    let candidates = match &tagged {
+        Some(ids) if query.trim().is_empty() => ids
+            .iter()
+            .filter_map(|id| docs_store_guard.get_doc(id))
+            .collect(),
+        _ => docs_store_guard.search_docs(&query),
+    };
+
+    let mut results: Vec<Documentation> = candidates
+        .into_iter()
+        .filter(|doc| {
+            // Filter by project_id if specified
+            let matches_project = project_id.as_ref()
+                .map(|pid| doc.project_id.as_ref() == Some(pid))
+                .unwrap_or(true);
+            
+            // Filter by provider_id if specified
+            let matches_provider = provider_id.as_ref()
+                .map(|pid| doc.provider_id.as_ref() == Some(pid))
+                .unwrap_or(true);
+            
+            // Filter by doc_type if specified
+            let matches_type = doc_type.as_ref()
+                .map(|dt| &doc.doc_type == dt)
+                .unwrap_or(true);
+            
+            // Filter by tags if specified
+            let matches_tags = tagged.as_ref()
+                .map(|ids| ids.contains(&doc.id))
+                .unwrap_or(true);
+            
+            // Filter by favorites if specified
+            let matches_favorites = favorites_only
+                .map(|fav| fav == false || doc.is_favorite)
+                .unwrap_or(true);
+            
+            matches_project && matches_provider && matches_type && matches_tags && matches_favorites
+        })
+        .cloned()
+        .collect();
+    
+    // Sort by relevance (simple scoring: title matches get higher priority)
+    results.sort_by(|a, b| {
+        let a_title_match = a.title.to_lowercase().contains(&query_lower);
+        let b_title_match = b.title.to_lowercase().contains(&query_lower);
+        
+        match (a_title_match, b_title_match) {
+            (true, false) => std::cmp::Ordering::Less,
+            (false, true) => std::cmp::Ordering::Greater,
+            _ => b.updated_at.cmp(&a.updated_at), // Then sort by most recent
+        }
+    });
+    
+    Ok(results)
+}
+
+#[tauri::command]
+async fn get_documentation_by_project(
+    project_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<Documentation>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let docs_store_guard = state.docs_store.lock().await;
+    let docs: Vec<Documentation> = docs_store_guard
+        .get_all_docs()
+        .into_iter()
+        .filter(|doc| doc.project_id.as_ref() == Some(&project_id))
+        .cloned()
+        .collect();
+    
+    Ok(docs)
+}
+
+#[tauri::command]
+async fn get_documentation_by_provider(
+    provider_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<Documentation>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let docs_store_guard = state.docs_store.lock().await;
+    let docs: Vec<Documentation> = docs_store_guard
+        .get_all_docs()
+        .into_iter()
+        .filter(|doc| doc.provider_id.as_ref() == Some(&provider_id))
+        .cloned()
+        .collect();
+    
+    Ok(docs)
+}
+
+#[tauri::command]
+async fn toggle_documentation_favorite(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<Documentation, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let mut docs_store_guard = state.docs_store.lock().await;
+
+    if let Some(mut documentation) = docs_store_guard.get_doc(&id).cloned() {
+        documentation.is_favorite = !documentation.is_favorite;
+        documentation.updated_at = Utc::now();
+
+        docs_store_guard.update_doc(documentation.clone());
+        drop(docs_store_guard);
+
+        save_docs_store(&state).await?;
+        
+        log_audit_event(&state, "toggle_documentation_favorite", "documentation", Some(&id), true, None).await;
+
+        info!("Documentation favorite toggled: {} -> {}", id, documentation.is_favorite);
+        Ok(documentation)
+    } else {
+        Err(KeyKeeperError::not_found("Documentation"))
+    }
+}
+
+#[tauri::command]
+async fn scrape_and_save_documentation(
+    url: String,
+    title: Option<String>,
+    doc_type: Option<String>,
+    project_id: Option<String>,
+    provider_id: Option<String>,
+    tags: Option<Vec<String>>,
+    state: State<'_, AppState>,
+) -> Result<Documentation, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    // Create documentation manager with LLM proxy
+    let mut docs_manager = docs_manager::DocumentationManager::new_with_llm_proxy(
+        Arc::clone(&state.llm_proxy)
+    );
+
+    // Scrape content from URL
+    let scraped_content = docs_manager.scrape_documentation_content(&url).await
+        .map_err(|e| format!("Failed to scrape documentation: {}", e))?;
+
+    // Generate unique documentation ID
+    let doc_id = format!("doc_{}", get_utc_timestamp_millis());
+
+    // Determine title
+    let doc_title = title.unwrap_or_else(|| {
+        // Extract title from URL if not provided
+        let url_parts: Vec<&str> = url.split('/').collect();
+        if let Some(domain) = url_parts.get(2) {
+            format!("Documentation from {}", domain)
+        } else {
+            "Scraped Documentation".to_string()
+        }
+    });
+
+    // Create search keywords from content and title
+    let mut search_keywords = Vec::new();
+    search_keywords.extend(
+        doc_title
+            .to_lowercase()
+            .split_whitespace()
+            .map(|s| s.to_string())
+    );
+
+    // Extract keywords from first 500 characters of content
+    let content_preview = scraped_content.chars().take(500).collect::<String>();
+    let content_words: Vec<String> = content_preview
+        .to_lowercase()
+        .split_whitespace()
+        .filter(|word| word.len() > 3) // Only words longer than 3 chars
+        .take(20) // Limit to 20 keywords
+        .map(|s| s.to_string())
+        .collect();
+    search_keywords.extend(content_words);
+
+    // Remove duplicates
+    search_keywords.sort();
+    search_keywords.dedup();
+
+    let documentation = Documentation {
+        id: doc_id.clone(),
+        title: doc_title.clone(),
+        content: scraped_content,
+        doc_type: doc_type.unwrap_or_else(|| "scraped".to_string()),
+        project_id,
+        provider_id,
+        url: Some(url.clone()),
+        tags: tags.unwrap_or_default(),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        language: "en".to_string(),
+        is_favorite: false,
+        search_keywords,
+        sections: Vec::new(),
+        is_offline: false,
+        attachments: Vec::new(),
+    };
+
+
+    info!("Documentation scraped and saved: {} from {}", doc_title, url);
+    Ok(documentation)
+}
+
+// Helper functions for biometric authentication
+fn get_device_platform() -> String {
+    #[cfg(target_os = "macos")]
+    return "macOS Device".to_string();
+    #[cfg(target_os = "windows")]
+    return "Windows Device".to_string();
+    #[cfg(target_os = "linux")]
+    return "Linux Device".to_string();
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    return "Unknown Device".to_string();
+}
+
+/// Stable identifier of this machine and OS user, used to group sessions
+/// and tokens per device
+fn get_device_id() -> String {
+    let hostname = whoami::fallible::hostname().unwrap_or_else(|_| "unknown".to_string());
+    format!("{}-{}-{}", hostname, whoami::username(), std::env::consts::OS)
+}
+
+fn get_future_timestamp(minutes: u64) -> DateTime<Utc> {
+    Utc::now() + chrono::Duration::minutes(minutes as i64)
+}
+
+fn create_audit_log(
+    action: &str,
+    resource_type: &str,
+    resource_id: Option<&str>,
+    success: bool,
+    error_message: Option<&str>,
+) -> AuditLog {
+    // Actions taken while answering an HTTP request name its client
+    let client = clients::current();
+    let log = AuditLog {
+        id: format!("audit_{}", get_utc_timestamp_millis()),
+        timestamp: Utc::now(),
+        action: action.to_string(),
+        resource_type: resource_type.to_string(),
+        resource_id: resource_id.map(|s| s.to_string()),
+        user_id: Some("local_user".to_string()), // In multi-user scenario, this would be dynamic
+        ip_address: Some(client.as_ref().map_or("127.0.0.1".to_string(), |c| c.ip.clone())),
+        user_agent: Some(match &client {
+            Some(c) => c.user_agent.clone().unwrap_or_else(|| c.name.clone()),
+            None => "KeyKeeper Desktop".to_string(),
+        }),
+        success,
+        error_message: error_message.map(|s| s.to_string()),
+        client_id: client.and_then(|c| c.client_id),
+        request_id: request_id::current(),
+    };
+    audit_sinks::emit(&log);
+    log
+}
+
+async fn log_audit_event(
+    state: &State<'_, AppState>,
+    action: &str,
+    resource_type: &str,
+    resource_id: Option<&str>,
+    success: bool,
+    error_message: Option<&str>,
+) {
+    let audit_log = create_audit_log(action, resource_type, resource_id, success, error_message);
+
+    // Log to console for immediate debugging
+    if success {
+        info!(
+            "AUDIT: {} {} {:?} - SUCCESS",
+            action, resource_type, resource_id
+        );
+    } else {
+        warn!(
+            "AUDIT: {} {} {:?} - FAILED: {:?}",
+            action, resource_type, resource_id, error_message
+        );
+    }
+
+    // Store in vault
+    let mut vault_guard = state.vault.lock().await;
+    vault_guard.audit_logs.push(audit_log);
+
+    retention::cap_audit_logs(&mut vault_guard);
+
+    // Fan out to registered webhooks. While locked, the failed unlocks are
+    // only kept if they're written to the metadata now.
+    let webhook_events = webhooks::events_for_audit(&mut vault_guard, action, success, Utc::now());
+    let failed_unlocks = vault_guard.failed_unlocks.clone();
+    drop(vault_guard);
+    if action == "unlock_vault" && !success && !*state.is_unlocked.lock().await {
+        if let Err(e) = webhooks::persist_failed_unlocks(&*state.storage, &failed_unlocks) {
+            warn!("Failed to record the failed unlock: {}", e);
+        }
+    }
+    for event in webhook_events {
+        let data = serde_json::json!({
+            "action": action,
+            "resource_type": resource_type,
+            "resource_id": resource_id,
+        });
+        webhooks::dispatch(state.vault.clone(), event, data).await;
+    }
+
+    email::dispatch_alert(state.vault.clone(), action, success).await;
+
+    // Frontend, tray and notifications follow through the event bus
+    events::publish(events::EventPayload::Audit {
+        action: action.to_string(),
+        resource_type: resource_type.to_string(),
+        resource_id: resource_id.map(|s| s.to_string()),
+        success,
+        error: error_message.map(|s| s.to_string()),
+    });
+}
+
+fn get_app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+
+    // Ensure the directory exists
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir)
+}
+
+// Removed get_request_body - replaced with hyper HTTP server
+
+// Helper function to validate VSCode tokens
+
+// Old TCP server implementation removed - now using Hyper HTTP server
+/*
+async fn handle_vscode_connection_enterprise(
+    mut stream: tokio::net::TcpStream,
+    vault: Arc<Mutex<ApiKeyVault>>,
+    is_unlocked: Arc<Mutex<bool>>,
+    vault_path: PathBuf,
+    client_addr: std::net::SocketAddr,
+) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    let mut buffer = [0; 2048];
+    if let Ok(n) = stream.read(&mut buffer).await {
+        let request = String::from_utf8_lossy(&buffer[..n]);
+        // Parse HTTP request with enhanced security
+        let lines: Vec<&str> = request.lines().collect();
+        if lines.is_empty() {
+            return;
+        }
+        let request_line = lines[0];
+        let parts: Vec<&str> = request_line.split_whitespace().collect();
+        if parts.len() < 3 {
+            return;
+        }
+        let method = parts[0];
+        let path = parts[1];
+        let _version = parts[2];
+        // Enhanced security headers
+        let security_headers = "Content-Type: application/json\r\n\
+            X-Content-Type-Options: nosniff\r\n\
+            X-Frame-Options: DENY\r\n\
+            X-XSS-Protection: 1; mode=block\r\n\
+            Strict-Transport-Security: max-age=31536000; includeSubDomains\r\n\
+            Content-Security-Policy: default-src 'none'; frame-ancestors 'none'\r\n\
+            Access-Control-Allow-Origin: vscode-webview://\r\n\
+            Access-Control-Allow-Methods: GET, POST, OPTIONS\r\n\
+            Access-Control-Allow-Headers: Content-Type, Authorization, X-API-Key\r\n";
+        // Rate limiting check (simplified)
+        let rate_limit_exceeded = false;
+        // TODO: Implement proper rate limiting
+        if rate_limit_exceeded {
+            let response = format!(
+                "HTTP/1.1 429 Too Many Requests\r\n{}\r\n{{\"error\":\"Rate limit exceeded\",\"client\":\"{}\"}}",
+                security_headers,
+                client_addr
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+            return;
+        }
+        // Parse path and query parameters
+        let (path_only, query_params) = if let Some(pos) = path.find('?') {
+            (&path[..pos], Some(&path[pos + 1..]))
+        } else {
+            (path, None)
+        };
+        // Route handling with audit logging
+        match (method, path_only) {
+            ("GET", "/health") => {
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\n{}\r\n{{\"status\":\"ok\",\"timestamp\":\"{}\",\"version\":\"2.0.0-enterprise\"}}",
+                    security_headers,
+                    get_utc_timestamp()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+            ("GET", "/api/keys") => {
+                if *is_unlocked.lock().await {
+                    let vault_guard = vault.lock().await;
+                    // Filter sensitive data for VSCode
+                    let filtered_keys: Vec<_> = vault_guard
+                        .keys
+                        .values()
+                        .map(|key| {
+                            let mut filtered = key.clone();
+                            // Mask the actual key for security
+                            filtered.key = masking::mask_value(&key.key);
+                            filtered
+                        })
+                        .collect();
+                    let json_keys = serde_json::to_string(&filtered_keys).unwrap_or_default();
+                    let response =
+                        format!("HTTP/1.1 200 OK\r\n{}\r\n{}", security_headers, json_keys);
+                    let _ = stream.write_all(response.as_bytes()).await;
+                } else {
+                    let response = format!(
+                        "HTTP/1.1 403 Forbidden\r\n{}\r\n{{\"error\":\"Vault is locked\",\"timestamp\":\"{}\"}}",
+                        security_headers,
+                        get_utc_timestamp()
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                }
+            }
+            ("GET", "/api/projects") => {
+                if *is_unlocked.lock().await {
+                    let response = format!("HTTP/1.1 200 OK\r\n{}\r\n[]", security_headers);
+                    let _ = stream.write_all(response.as_bytes()).await;
+                } else {
+                    let response = format!(
+                        "HTTP/1.1 403 Forbidden\r\n{}\r\n{{\"error\":\"Vault is locked\"}}",
+                        security_headers
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                }
+            }
+            ("GET", "/api/keys/search") => {
+                if *is_unlocked.lock().await {
+                    if let Some(query_str) = query_params {
+                        // Parse query parameter
+                        if let Some(q_start) = query_str.find("q=") {
+                            let q_value = &query_str[q_start + 2..];
+                            let query = q_value.split('&').next().unwrap_or("");
+                            // Usa la logica reale di ricerca
+                            let vault_guard = vault.lock().await;
+                            let filtered_keys: Vec<_> = vault_guard
+                                .keys
+                                .values()
+                                .filter(|key| {
+                                    key.name.to_lowercase().contains(&query.to_lowercase())
+                                        || key
+                                            .service
+                                            .to_lowercase()
+                                            .contains(&query.to_lowercase())
+                                        || key.tags.iter().any(|tag| {
+                                            tag.to_lowercase().contains(&query.to_lowercase())
+                                        })
+                                        || key.description.as_ref().map_or(false, |desc| {
+                                            desc.to_lowercase().contains(&query.to_lowercase())
+                                        })
+                                })
+                                .map(|key| {
+                                    let mut filtered = key.clone();
+                                    filtered.key = masking::mask_value(&key.key);
+                                    filtered
+                                })
+                                .collect();
+                            let json_keys =
+                                serde_json::to_string(&filtered_keys).unwrap_or_default();
+                            let response =
+                                format!("HTTP/1.1 200 OK\r\n{}\r\n{}", security_headers, json_keys);
+                            let _ = stream.write_all(response.as_bytes()).await;
+                        } else {
+                            let response = format!(
+                                "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"error\":\"Missing query parameter\"}}",
+                                security_headers
+                            );
+                            let _ = stream.write_all(response.as_bytes()).await;
+                        }
+                    } else {
+                        let response = format!(
+                            "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"error\":\"Missing query parameter\"}}",
+                            security_headers
+                        );
+                        let _ = stream.write_all(response.as_bytes()).await;
+                    }
+                } else {
+                    let response = format!(
+                        "HTTP/1.1 403 Forbidden\r\n{}\r\n{{\"error\":\"Vault is locked\"}}",
+                        security_headers
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                }
+            }
+            ("GET", "/api/activity/recent") => {
+                if *is_unlocked.lock().await {
+                    let vault_guard = vault.lock().await;
+                    let mut activities = vault_guard.recent_activity.clone();
+                    // Sort by timestamp, most recent first
+                    activities.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+                    activities.truncate(50);
+                    let json_activities = serde_json::to_string(&activities).unwrap_or_default();
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\n{}\r\n{}",
+                        security_headers, json_activities
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                } else {
+                    let response = format!(
+                        "HTTP/1.1 403 Forbidden\r\n{}\r\n{{\"error\":\"Vault is locked\"}}",
+                        security_headers
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                }
+            }
+            ("POST", path_only)
+                if path_only.starts_with("/api/keys/") && path_only.ends_with("/usage") =>
+            {
+                if *is_unlocked.lock().await {
+                    // Extract key ID from path
+                    if let Some(_key_id) = path_only
+                        .strip_prefix("/api/keys/")
+                        .and_then(|s| s.strip_suffix("/usage"))
+                    {
+                        // For now, just return success - real implementation would call record_key_usage
+                        let response = format!(
+                            "HTTP/1.1 200 OK\r\n{}\r\n{{\"success\":true}}",
+                            security_headers
+                        );
+                        let _ = stream.write_all(response.as_bytes()).await;
+                    } else {
+                        let response = format!(
+                            "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"error\":\"Invalid key ID\"}}",
+                            security_headers
+                        );
+                        let _ = stream.write_all(response.as_bytes()).await;
+                    }
+                } else {
+                    let response = format!(
+                        "HTTP/1.1 403 Forbidden\r\n{}\r\n{{\"error\":\"Vault is locked\"}}",
+                        security_headers
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                }
+            }
+            ("POST", "/api/projects/sync") => {
+                if *is_unlocked.lock().await {
+                    // For now, just return success - real implementation would call sync_project
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\n{}\r\n{{\"success\":true}}",
+                        security_headers
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                } else {
+                    let response = format!(
+                        "HTTP/1.1 403 Forbidden\r\n{}\r\n{{\"error\":\"Vault is locked\"}}",
+                        security_headers
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                }
+            }
+            ("POST", "/api/login") => {
+                // Parse request body to get credentials
+                if let Ok(body) = get_request_body(&mut stream).await {
+                    if let Ok(parsed_body) = serde_json::from_str::<serde_json::Value>(&body) {
+                        if let (Some(account), Some(master_pass)) = (
+                            parsed_body["account"].as_str(),
+                            parsed_body["masterPass"].as_str(),
+                        ) {
+                            // Check if user account exists and password matches master password
+                            let vault_guard = vault.lock().await;
+                            if let Some(user_account) = &vault_guard.user_account {
+                                if user_account.email == account || user_account.username == account
+                                {
+                                    // For VSCode integration, we check against master password, not user password
+                                    if let Some(master_hash) = &vault_guard.master_password_hash {
+                                        let master_hash_clone = master_hash.clone();
+                                        let user_id_clone = user_account.id.clone();
+                                        drop(vault_guard);
+                                        if let Ok(is_valid) =
+                                            bcrypt::verify(master_pass, &master_hash_clone)
+                                        {
+                                            if is_valid {
+                                                // ✅ FIX: Create secure token with expiry
+                                                let token = format!(
+                                                    "vscode_session_{}",
+                                                    uuid::Uuid::new_v4().to_string()
+                                                );
+                                                let now = get_utc_timestamp();
+                                                let expires_at = get_future_timestamp(480); // 8 hours
+
+                                                let vscode_token = VSCodeToken {
+                                                    token: token.clone(),
+                                                    user_id: user_id_clone,
+                                                    created_at: now,
+                                                    expires_at,
+                                                    is_valid: true,
+                                                };
+
+                                                // Store token in vault
+                                                let mut vault_guard_for_token = vault.lock().await;
+                                                vault_guard_for_token
+                                                    .vscode_tokens
+                                                    .push(vscode_token);
+                                                drop(vault_guard_for_token);
+
+                                                let response = format!(
+                                                    "HTTP/1.1 200 OK\r\n{}\r\n{{\"success\":true,\"token\":\"{}\"}}",
+                                                    security_headers,
+                                                    token
+                                                );
+                                                let _ = stream.write_all(response.as_bytes()).await;
+                                                return;
+                                            }
+                                        }
+                                    } else {
+                                        drop(vault_guard);
+                                    }
+                                } else {
+                                    drop(vault_guard);
+                                }
+                            } else {
+                                drop(vault_guard);
+                            }
+                            let response = format!(
+                                "HTTP/1.1 401 Unauthorized\r\n{}\r\n{{\"success\":false,\"message\":\"Invalid credentials\"}}",
+                                security_headers
+                            );
+                            let _ = stream.write_all(response.as_bytes()).await;
+                        } else {
+                            let response = format!(
+                                "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"success\":false,\"message\":\"Missing account or masterPass\"}}",
+                                security_headers
+                            );
+                            let _ = stream.write_all(response.as_bytes()).await;
+                        }
+                    } else {
+                        let response = format!(
+                            "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"success\":false,\"message\":\"Invalid JSON body\"}}",
+                            security_headers
+                        );
+                        let _ = stream.write_all(response.as_bytes()).await;
+                    }
+                } else {
+                    let response = format!(
+                        "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"success\":false,\"message\":\"Cannot read request body\"}}",
+                        security_headers
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                }
+            }
+            ("POST", "/api/auth/master-password") => {
+                // Simplified authentication endpoint for VSCode extension - only requires master password
+                if let Ok(body) = get_request_body(&mut stream).await {
+                    if let Ok(parsed_body) = serde_json::from_str::<serde_json::Value>(&body) {
+                        if let Some(master_pass) = parsed_body["masterPass"].as_str() {
+                            // Check if master password is valid
+                            let vault_guard = vault.lock().await;
+                            if let Some(master_hash) = &vault_guard.master_password_hash {
+                                let master_hash_clone = master_hash.clone();
+                                // For simplified auth, we'll use a default user_id if no user account exists
+                                let user_id = if let Some(user_account) = &vault_guard.user_account {
+                                    user_account.id.clone()
+                                } else {
+                                    "default_user".to_string()
+                                };
+                                drop(vault_guard);
+
+                                if let Ok(is_valid) = bcrypt::verify(master_pass, &master_hash_clone) {
+                                    if is_valid {
+                                        // Create secure token with expiry
+                                        let token = format!(
+                                            "vscode_session_{}",
+                                            uuid::Uuid::new_v4().to_string()
+                                        );
+                                        let now = get_utc_timestamp();
+                                        let expires_at = get_future_timestamp(480); // 8 hours
+
+                                        let vscode_token = VSCodeToken {
+                                            token: token.clone(),
+                                            user_id,
+                                            created_at: now,
+                                            expires_at,
+                                            is_valid: true,
+                                        };
+
+                                        // Store token in vault
+                                        let mut vault_guard_for_token = vault.lock().await;
+                                        vault_guard_for_token.vscode_tokens.push(vscode_token);
+                                        drop(vault_guard_for_token);
+
+                                        let response = format!(
+                                            "HTTP/1.1 200 OK\r\n{}\r\n{{\"success\":true,\"token\":\"{}\"}}",
+                                            security_headers,
+                                            token
+                                        );
+                                        let _ = stream.write_all(response.as_bytes()).await;
+                                        return;
+                                    }
+                                }
+                            } else {
+                                drop(vault_guard);
+                            }
+
+                            let response = format!(
+                                "HTTP/1.1 401 Unauthorized\r\n{}\r\n{{\"success\":false,\"message\":\"Invalid master password\"}}",
+                                security_headers
+                            );
+                            let _ = stream.write_all(response.as_bytes()).await;
+                        } else {
+                            let response = format!(
+                                "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"success\":false,\"message\":\"Missing masterPass\"}}",
+                                security_headers
+                            );
+                            let _ = stream.write_all(response.as_bytes()).await;
+                        }
+                    } else {
+                        let response = format!(
+                            "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"success\":false,\"message\":\"Invalid JSON body\"}}",
+                            security_headers
+                        );
+                        let _ = stream.write_all(response.as_bytes()).await;
+                    }
+                } else {
+                    let response = format!(
+                        "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"success\":false,\"message\":\"Cannot read request body\"}}",
+                        security_headers
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                }
+            }
+            ("POST", "/api/keys") => {
+                if *is_unlocked.lock().await {
+                    // Parse request body to create new API key
+                    if let Ok(body) = get_request_body(&mut stream).await {
+                        if let Ok(mut parsed_body) =
+                            serde_json::from_str::<serde_json::Value>(&body)
+                        {
+                            // Generate ID and timestamps for new key
+                            let now = get_utc_timestamp();
+                            parsed_body["id"] = serde_json::Value::String(format!(
+                                "key_{}",
+                                get_utc_timestamp_millis()
+                            ));
+                            parsed_body["created_at"] = serde_json::Value::String(now.clone());
+                            parsed_body["updated_at"] = serde_json::Value::String(now);
+
+                            if let Ok(api_key) = serde_json::from_value::<ApiKey>(parsed_body) {
+                                let mut vault_guard = vault.lock().await;
+                                vault_guard.keys.insert(api_key.id.clone(), api_key.clone());
+
+                                // ✅ FIXED: Save vault to persist keys created via HTTP API
+                                if let Err(e) = save_vault_to_path(&*vault_guard, &vault_path).await
+                                {
+                                    drop(vault_guard);
+                                    let response = format!(
+                                        "HTTP/1.1 500 Internal Server Error\r\n{}\r\n{{\"error\":\"Failed to save key: {}\"}}",
+                                        security_headers,
+                                        e
+                                    );
+                                    let _ = stream.write_all(response.as_bytes()).await;
+                                    return;
+                                }
+                                drop(vault_guard);
+
+                                let json_response =
+                                    serde_json::to_string(&api_key).unwrap_or_default();
+                                let response = format!(
+                                    "HTTP/1.1 201 Created\r\n{}\r\n{}",
+                                    security_headers, json_response
+                                );
+                                let _ = stream.write_all(response.as_bytes()).await;
+                            } else {
+                                let response = format!(
+                                    "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"error\":\"Invalid API key data\"}}",
+                                    security_headers
+                                );
+                                let _ = stream.write_all(response.as_bytes()).await;
+                            }
+                        } else {
+                            let response = format!(
+                                "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"error\":\"Invalid JSON body\"}}",
+                                security_headers
+                            );
+                            let _ = stream.write_all(response.as_bytes()).await;
+                        }
+                    } else {
+                        let response = format!(
+                            "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"error\":\"Cannot read request body\"}}",
+                            security_headers
+                        );
+                        let _ = stream.write_all(response.as_bytes()).await;
+                    }
+                } else {
+                    let response = format!(
+                        "HTTP/1.1 403 Forbidden\r\n{}\r\n{{\"error\":\"Vault is locked\"}}",
+                        security_headers
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                }
+            }
+            ("POST", "/api/env/parse") => {
+                if *is_unlocked.lock().await {
+                    // Parse body to get file path
+                    if let Ok(body) = get_request_body(&mut stream).await {
+                        if let Ok(parsed_body) = serde_json::from_str::<serde_json::Value>(&body) {
+                            if let Some(file_path) = parsed_body["filePath"].as_str() {
+                                // Call parse_and_register_env_file function
+                                match parse_env_file(file_path) {
+                                    Ok(variables) => {
+                                        if let Ok(project_path) = detect_project_path(file_path) {
+                                            let file_name = std::path::Path::new(file_path)
+                                                .file_name()
+                                                .and_then(|name| name.to_str())
+                                                .unwrap_or("unknown.env");
+
+                                            let result = DroppedEnvFile {
+                                                path: file_path.to_string(),
+                                                project_path,
+                                                file_name: file_name.to_string(),
+                                                keys: variables,
+                                            };
+
+                                            let json_result =
+                                                serde_json::to_string(&result).unwrap_or_default();
+                                            let response = format!(
+                                                "HTTP/1.1 200 OK\r\n{}\r\n{}",
+                                                security_headers, json_result
+                                            );
+                                            let _ = stream.write_all(response.as_bytes()).await;
+                                        } else {
+                                            let response = format!(
+                                                "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"error\":\"Cannot detect project path\"}}",
+                                                security_headers
+                                            );
+                                            let _ = stream.write_all(response.as_bytes()).await;
+                                        }
+                                    }
+                                    Err(e) => {
+                                        let response = format!(
+                                            "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"error\":\"{}\"}}",
+                                            security_headers,
+                                            e
+                                        );
+                                        let _ = stream.write_all(response.as_bytes()).await;
+                                    }
+                                }
+                            } else {
+                                let response = format!(
+                                    "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"error\":\"Missing filePath\"}}",
+                                    security_headers
+                                );
+                                let _ = stream.write_all(response.as_bytes()).await;
+                            }
+                        } else {
+                            let response = format!(
+                                "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"error\":\"Invalid JSON body\"}}",
+                                security_headers
+                            );
+                            let _ = stream.write_all(response.as_bytes()).await;
+                        }
+                    } else {
+                        let response = format!(
+                            "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"error\":\"Cannot read request body\"}}",
+                            security_headers
+                        );
+                        let _ = stream.write_all(response.as_bytes()).await;
+                    }
+                } else {
+                    let response = format!(
+                        "HTTP/1.1 403 Forbidden\r\n{}\r\n{{\"error\":\"Vault is locked\"}}",
+                        security_headers
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                }
+            }
+            ("POST", "/api/env/associate") => {
+                if *is_unlocked.lock().await {
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\n{}\r\n{{\"success\":true}}",
+                        security_headers
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                } else {
+                    let response = format!(
+                        "HTTP/1.1 403 Forbidden\r\n{}\r\n{{\"error\":\"Vault is locked\"}}",
+                        security_headers
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                }
+            }
+            ("GET", "/api/env/associations") => {
+                if *is_unlocked.lock().await {
+                    let response = format!("HTTP/1.1 200 OK\r\n{}\r\n[]", security_headers);
+                    let _ = stream.write_all(response.as_bytes()).await;
+                } else {
+                    let response = format!(
+                        "HTTP/1.1 403 Forbidden\r\n{}\r\n{{\"error\":\"Vault is locked\"}}",
+                        security_headers
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                }
+            }
+            ("POST", "/api/projects/activate") => {
+                if *is_unlocked.lock().await {
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\n{}\r\n{{\"success\":true}}",
+                        security_headers
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                } else {
+                    let response = format!(
+                        "HTTP/1.1 403 Forbidden\r\n{}\r\n{{\"error\":\"Vault is locked\"}}",
+                        security_headers
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                }
+            }
+            ("OPTIONS", _) => {
+                let response = format!("HTTP/1.1 200 OK\r\n{}\r\n", security_headers);
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+            ("POST", "/api/vscode/workspaces") => {
+                if *is_unlocked.lock().await {
+                    // Parse request body
+                    let body_start = request.find("\r\n\r\n").unwrap_or(request.len());
+                    let body = if body_start + 4 < request.len() {
+                        &request[body_start + 4..]
+                    } else {
+                        ""
+                    };
+
+                    if let Ok(request_data) = serde_json::from_str::<serde_json::Value>(body) {
+                        if let Some(workspaces) = request_data["workspaces"].as_array() {
+                            let workspace_paths: Vec<String> = workspaces
+                                .iter()
+                                .filter_map(|w| w.as_str().map(|s| s.to_string()))
+                                .collect();
+
+                            let mut vault_guard = vault.lock().await;
+
+                            // Replace the pushed workspaces and update project association statuses
+                            let timestamp = Utc::now();
+                            workspace_watcher::apply_pushed(
+                                &mut vault_guard,
+                                workspace_paths,
+                                timestamp,
+                            );
+
+                            let response = format!(
+                                "HTTP/1.1 200 OK\r\n{}\r\n{{\"success\":true,\"updated\":{},\"timestamp\":\"{}\"}}",
+                                security_headers,
+                                vault_guard.vscode_workspaces.len(),
+                                timestamp
+                            );
+                            let _ = stream.write_all(response.as_bytes()).await;
+                        } else {
+                            let response = format!(
+                                "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"error\":\"Invalid workspaces format\"}}",
+                                security_headers
+                            );
+                            let _ = stream.write_all(response.as_bytes()).await;
+                        }
+                    } else {
+                        let response = format!(
+                            "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"error\":\"Invalid JSON body\"}}",
+                            security_headers
+                        );
+                        let _ = stream.write_all(response.as_bytes()).await;
+                    }
+                } else {
+                    let response = format!(
+                        "HTTP/1.1 403 Forbidden\r\n{}\r\n{{\"error\":\"Vault is locked\"}}",
+                        security_headers
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                }
+            }
+            ("GET", "/api/vscode/workspaces") => {
+                if *is_unlocked.lock().await {
+                    let vault_guard = vault.lock().await;
+                    let json_workspaces =
+                        serde_json::to_string(&vault_guard.vscode_workspaces).unwrap_or_default();
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\n{}\r\n{}",
+                        security_headers, json_workspaces
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                } else {
+                    let response = format!(
+                        "HTTP/1.1 403 Forbidden\r\n{}\r\n{{\"error\":\"Vault is locked\"}}",
+                        security_headers
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                }
+            }
+            ("GET", "/api/vscode/status") => {
+                if *is_unlocked.lock().await {
+                    if let Some(query_str) = query_params {
+                        if let Some(project_start) = query_str.find("projectPath=") {
+                            let project_path = &query_str[project_start + 12..];
+                            let project_path = project_path.split('&').next().unwrap_or("");
+                            let project_path =
+                                urlencoding::decode(project_path).unwrap_or_default();
+
+                            let vault_guard = vault.lock().await;
+                            let is_open = vault_guard.vscode_workspaces.iter().any(|ws| {
+                                ws.path == project_path || project_path.starts_with(&ws.path)
+                            });
+
+                            let status = if is_open {
+                                "open"
+                            } else if vault_guard.vscode_workspaces.is_empty() {
+                                "unknown"
+                            } else {
+                                "closed"
+                            };
+
+                            let response = format!(
+                                "HTTP/1.1 200 OK\r\n{}\r\n{{\"status\":\"{}\"}}",
+                                security_headers, status
+                            );
+                            let _ = stream.write_all(response.as_bytes()).await;
+                        } else {
+                            let response = format!(
+                                "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"error\":\"Missing projectPath parameter\"}}",
+                                security_headers
+                            );
+                            let _ = stream.write_all(response.as_bytes()).await;
+                        }
+                    } else {
+                        let response = format!(
+                            "HTTP/1.1 400 Bad Request\r\n{}\r\n{{\"error\":\"Missing query parameters\"}}",
+                            security_headers
+                        );
+                        let _ = stream.write_all(response.as_bytes()).await;
+                    }
+                } else {
+                    let response = format!(
+                        "HTTP/1.1 403 Forbidden\r\n{}\r\n{{\"error\":\"Vault is locked\"}}",
+                        security_headers
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                }
+            }
+            _ => {
+                let response = format!(
+                    "HTTP/1.1 404 Not Found\r\n{}\r\n{{\"error\":\"Not found\",\"method\":\"{}\",\"path\":\"{}\"}}",
+                    security_headers,
+                    method,
+                    path
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        }
+    }
+}
+*/
+
+const TRAY_ID: &str = "main";
+
+/// Tray menu; `status` are disabled lines shown first, `pinned` the (id, name)
+/// of the pinned keys
+fn build_tray_menu(
+    app: &AppHandle,
+    status: &[String],
+    pinned: &[(String, String)],
+) -> tauri::Result<tauri::menu::Menu<tauri::Wry>> {
+    use tauri::menu::{MenuBuilder, MenuItemBuilder, PredefinedMenuItem, SubmenuBuilder};
+
+    // Create menu items (copying exact pattern from working app)
+    let show_item = MenuItemBuilder::with_id("show", "Show KeyKeeper").build(app)?;
+    let hide_item = MenuItemBuilder::with_id("hide", "Hide").build(app)?;
+    let separator1 = PredefinedMenuItem::separator(app)?;
+    let add_api_key = MenuItemBuilder::with_id("add_api_key", "Add API Key").build(app)?;
+    let sync_vscode = MenuItemBuilder::with_id("sync_vscode", "Sync with VS Code").build(app)?;
+    let open_project = MenuItemBuilder::with_id("open_project", "Open Project").build(app)?;
+    let separator2 = PredefinedMenuItem::separator(app)?;
+    let quit_item = MenuItemBuilder::with_id("quit", "Quit").build(app)?;
+
+    let mut tray_menu = MenuBuilder::new(app);
+    if !status.is_empty() {
+        for (i, line) in status.iter().enumerate() {
+            tray_menu = tray_menu.item(
+                &MenuItemBuilder::with_id(format!("status:{}", i), line)
+                    .enabled(false)
+                    .build(app)?,
+            );
+        }
+        tray_menu = tray_menu.item(&PredefinedMenuItem::separator(app)?);
+    }
+    tray_menu = tray_menu
+        .item(&show_item)
+        .item(&hide_item)
+        .item(&separator1);
+    if !pinned.is_empty() {
+        let mut submenu = SubmenuBuilder::new(app, "Pinned Keys");
+        for (id, name) in pinned {
+            submenu = submenu.item(&MenuItemBuilder::with_id(format!("pinned:{}", id), name).build(app)?);
+        }
+        tray_menu = tray_menu.item(&submenu.build()?);
+    }
+    tray_menu
+        .item(&add_api_key)
+        .item(&sync_vscode)
+        .item(&open_project)
+        .item(&separator2)
+        .item(&quit_item)
+        .build()
+}
+
+fn setup_system_tray(app: &AppHandle) -> tauri::Result<TrayIcon> {
+    use tauri::tray::{TrayIconBuilder, TrayIconEvent, MouseButton, MouseButtonState};
+
+    let tray_menu = build_tray_menu(app, &[], &[])?;
+
+    let icon = app.default_window_icon().unwrap().clone();
+
+    let tray = TrayIconBuilder::with_id(TRAY_ID)
+        .icon(icon)
+        .tooltip("KeyKeeper - Secure API Key Manager")
+        .menu(&tray_menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(move |app, event| {
+            if let Some(window) = app.get_webview_window("main") {
+                match event.id().as_ref() {
+                    "show" => {
+                        let _ = window.show();
+                        let _ = window.set_focus();
+                    }
+                    "hide" => {
+                        let _ = window.hide();
+                    }
+                    "add_api_key" => {
+                        let _ = window.show();
+                        let _ = window.set_focus();
+                        let _ = window.eval("window.dispatchEvent(new CustomEvent('tauri-add-api-key'))");
+                    }
+                    "sync_vscode" => {
+                        let _ = window.show();
+                        let _ = window.set_focus();
+                        let _ = window.eval("window.dispatchEvent(new CustomEvent('tauri-sync-vscode'))");
+                    }
+                    "open_project" => {
+                        let _ = window.show();
+                        let _ = window.set_focus();
+                        let _ = window.eval("window.dispatchEvent(new CustomEvent('tauri-open-project'))");
+                    }
+                    id if id.starts_with("pinned:") => {
+                        let _ = window_manager::open_key(app, &id["pinned:".len()..]);
+                    }
+                    "quit" => {
+                        let app = app.clone();
+                        tauri::async_runtime::spawn(async move {
+                            shutdown::run(&app).await;
+                            app.exit(0);
+                        });
+                    }
+                    _ => {}
+                }
+            }
+        })
+        .on_tray_icon_event(|tray, event| {
+            match event {
+                TrayIconEvent::Click {
+                    button: MouseButton::Left,
+                    button_state: MouseButtonState::Up,
+                    ..
+                } => {
+                    let app = tray.app_handle();
+                    if let Some(window) = app.get_webview_window("main") {
+                        if window.is_visible().unwrap_or(false) {
+                            let _ = window.hide();
+                        } else {
+                            let _ = window.show();
+                            let _ = window.set_focus();
+                        }
+                    }
+                }
+                _ => {}
+            }
+        })
+        .build(app)?;
+
+    Ok(tray)
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    // `keykeeper exec` runs as a plain CLI, without the app or its window
+    let argv: Vec<String> = std::env::args().collect();
+    if let Some(code) = env_refs::run_cli(&argv) {
+        std::process::exit(code);
+    }
+
+    tauri::Builder::default()
+        // Must stay the first plugin so a second launch exits before any setup
+        .plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+            instance::on_second_instance(app, argv, cwd);
+        }))
+        .plugin(tauri_plugin_deep_link::init())
+        .setup(|app| {
+            let handle = app.handle().clone();
+
+            // Logging first, so everything after it is captured
+            if let Ok(dir) = get_app_data_dir(&handle) {
+                logging::init(&dir, None);
+                prompt_templates::init(&dir);
+                diagnostics::install_panic_hook(&dir);
+                app.manage(diagnostics::LastCrash::default());
+                diagnostics::recover_from_crash(&handle, &dir);
+            }
+
+            // Arguments of this launch and deep links received while running
+            app.manage(instance::PendingLaunches::default());
+            let cwd = std::env::current_dir()
+                .map(|d| d.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let argv: Vec<String> = std::env::args().collect();
+            instance::dispatch(&handle, instance::parse_launch(&argv, &cwd));
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+                let deep_link_handle = handle.clone();
+                app.deep_link().on_open_url(move |event| {
+                    let urls = event.urls().iter().map(|u| u.to_string()).collect();
+                    instance::on_open_urls(&deep_link_handle, urls);
+                });
+                #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+                if let Err(e) = app.deep_link().register_all() {
+                    warn!("Failed to register deep link scheme: {}", e);
+                }
+            }
+
+            // Initialize app state synchronously without block_on
+            let app_data_dir = get_app_data_dir(&handle)?;
+            let config = Arc::new(config::ConfigState::load(&app_data_dir));
+            let startup_config = config.effective();
+            if let Err(e) = logging::set_level_override(startup_config.logging.level.clone()) {
+                warn!("Failed to apply configured log level: {}", e);
+            }
+            audit_sinks::configure(&startup_config.audit.sinks);
+            let vault_path = startup_config
+                .vault
+                .path
+                .clone()
+                .unwrap_or_else(|| vault_location::resolve_vault_path(&app_data_dir));
+            let docs_store_path = app_data_dir.join("documentation.json");
+            env_history::init(&app_data_dir);
+            keystore::init(&app_data_dir);
+
+            // Load vault synchronously
+            let vault_location = vault_location::VaultLocation::new(vault_path);
+            let storage: Arc<dyn vault_storage::VaultStorage> = Arc::new(vault_location.clone());
+            let vault =
+                load_vault_from(&*storage).map_err(|e| Box::new(Error::new(ErrorKind::Other, e)))?;
+            
+            // Load docs store synchronously
+            let runtime = tokio::runtime::Runtime::new().map_err(|e| Box::new(Error::new(ErrorKind::Other, e)))?;
+            let (docs_store, docs_store_error) = runtime.block_on(load_docs_store(&docs_store_path));
+
+            // Initialize AI state with shared LLM engine
+            let llm_engine = Arc::new(RwLock::new(None));
+            let template_engine = Arc::new(template_engine::EnhancedTemplateEngine::new(
+                template_engine::TemplateEngineConfig::default()
+            ));
+            let ai_state = Arc::new(ai_commands::AIState::new(llm_engine.clone(), template_engine.clone()));
+
+            // Initialize Documentation Library + LLM Chat System with shared LLM proxy state
+            let shared_llm_proxy_state = Arc::new(llm_proxy::LLMProxyState::default());
+            
+            let docs_manager_enhanced = docs_manager::DocumentationManager::new_enhanced(
+                Some(shared_llm_proxy_state.clone()),
+                None, // ML engine will be initialized later
+            );
+            let doc_library = docs_manager_enhanced.get_doc_library();
+            let docs_manager_arc = Arc::new(RwLock::new(docs_manager_enhanced));
+            
+            // Create individual states for command access - sharing the same DocumentationManager
+            let docs_library_state = docs_library_commands::DocumentationLibraryState::new_with_shared_manager(docs_manager_arc.clone());
+            
+            // Create chat engine with proper LLM proxy state injection for dynamic responses
+            let chat_engine = chat_engine::ChatEngine::new(
+                None, // LLM engine will be initialized later
+                None, // ML engine will be initialized later
+                doc_library.clone(),
+                Some(shared_llm_proxy_state.clone()), // ✅ Inject LLM proxy state for dynamic responses
+                chat_engine::ChatEngineConfig::default(),
+            );
+            let chat_engine_state = chat_commands::ChatEngineState::new(chat_engine, shared_llm_proxy_state.clone());
+
+            // Create separate instance for AppState with same LLM proxy state
+            let chat_engine_for_appstate = chat_engine::ChatEngine::new(
+                None, 
+                None, 
+                doc_library, 
+                Some(shared_llm_proxy_state.clone()), // ✅ Inject LLM proxy state for dynamic responses
+                chat_engine::ChatEngineConfig::default(),
+            );
+
+            let is_unlocked = Arc::new(Mutex::new(false));
+            let docs_store = Arc::new(Mutex::new(docs_store));
+            let ml_engine = Arc::new(tokio::sync::Mutex::new(None));
+            let health = Arc::new(health::HealthState::new(
+                is_unlocked.clone(),
+                vault_location.clone(),
+                docs_store.clone(),
+                docs_store_error,
+                ml_engine.clone(),
+            ));
+
+            let vault = Arc::new(Mutex::new(vault));
+            let vault_index = Arc::new(Mutex::new(vault_index::VaultIndex::new()));
+            let saves = Arc::new(save_queue::SaveQueue::new(
+                vault.clone(),
+                storage.clone(),
+                vault_index.clone(),
+            ));
+
+            let app_state = AppState {
+                vault,
+                vault_path: vault_location,
+                storage,
+                saves,
+                is_unlocked,
+                tasks: Arc::new(background_tasks::TaskManager::new()),
+                vscode_server_running: Arc::new(AtomicBool::new(false)),
+                tray_handle: Arc::new(Mutex::new(None)),
+                ml_engine,
+                api_generator: Arc::new(Mutex::new(api_generator_commands::ApiGeneratorState::new())),
+                enhanced_api: Arc::new(enhanced_api_commands::EnhancedApiState::new()),
+                framework_generators: Arc::new(framework_generator_commands::FrameworkGeneratorState::new()),
+                docs_manager: Arc::new(Mutex::new(docs_commands::DocsManagerState::new())),
+                docs_generator: Arc::new(docs_generator_commands::DocsGeneratorState::new()),
+                llm_proxy: shared_llm_proxy_state.clone(),
+                docs_store,
+                docs_store_path,
+                ai_state,
+                chat_engine_state: Arc::new(chat_commands::ChatEngineState::new(chat_engine_for_appstate, shared_llm_proxy_state.clone())),
+                docs_library_state: Arc::new(docs_library_commands::DocumentationLibraryState::new_with_shared_manager(docs_manager_arc)),
+                notifier: Arc::new(notifications::NotificationDispatcher::new(handle.clone())),
+                vault_index,
+                unlock_session: Arc::new(Mutex::new(None)),
+                health,
+                config,
+                approvals: Arc::new(approvals::ApprovalBroker::new(handle.clone())),
+                confirmations: Arc::new(confirmations::ConfirmationBroker::new(handle.clone())),
+                oauth_tokens: Arc::new(oauth::TokenCache::new()),
+                decryption_keys: Arc::new(decryption_keys::DecryptionKeys::new()),
+                remote_values: Arc::new(secret_backends::ValueCache::new()),
+            };
+
+            app.manage(app_state);
+
+            // Headless setups only use the HTTP API, so start it right away
+            if startup_config.server.auto_start {
+                let server_handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    match start_vscode_server(server_handle.state()).await {
+                        Ok(message) => info!("{}", message),
+                        Err(e) => error!("Failed to auto-start HTTP server: {}", e),
+                    }
+                });
+            }
+
+            // Long-running loops are supervised so a crash doesn't silently
+            // stop them
+            let tasks = Arc::clone(&app.state::<AppState>().tasks);
+
+            // Daily digest and reminder notifications
+            let scheduler_handle = app.handle().clone();
+            // Expire sessions and lock the vault when its session ends
+            let sweeper_handle = app.handle().clone();
+            // Emit lock changes and pause the HTTP server while locked
+            let lock_events_handle = app.handle().clone();
+            // Pass bus events on to the frontend, tray and notifications
+            let event_bus_handle = app.handle().clone();
+            // Expire key leases and delete their files
+            let lease_handle = app.handle().clone();
+            // Reload the vault when its file is changed from outside
+            let vault_watcher_handle = app.handle().clone();
+            // Track open editor workspaces without relying on the extension
+            let watcher_handle = app.handle().clone();
+            // File reminders for keys due for rotation
+            let rotation_handle = app.handle().clone();
+            // Archive keys unused for longer than the archive policy allows
+            let archive_handle = app.handle().clone();
+            // Purge history past the retention policy and compact the vault
+            let retention_handle = app.handle().clone();
+            // Move fallback keystore entries into the OS keyring once it works
+            let keystore_handle = app.handle().clone();
+
+            let started = [
+                tasks.spawn_supervised("notification_scheduler", move || {
+                    notifications::run_scheduler(scheduler_handle.clone())
+                }),
+                tasks.spawn_supervised("session_sweeper", move || {
+                    sessions::run_session_sweeper(sweeper_handle.clone())
+                }),
+                tasks.spawn_supervised("lock_events", move || {
+                    lock_state::run_lock_events(lock_events_handle.clone())
+                }),
+                tasks.spawn_supervised("event_bus", move || {
+                    events::run_event_bus(event_bus_handle.clone())
+                }),
+                tasks.spawn_supervised("lease_sweeper", move || {
+                    leases::run_lease_sweeper(lease_handle.clone())
+                }),
+                tasks.spawn_supervised("vault_file_watcher", move || {
+                    vault_location::run_vault_file_watcher(vault_watcher_handle.clone())
+                }),
+                tasks.spawn_supervised("workspace_watcher", move || {
+                    workspace_watcher::run_workspace_watcher(watcher_handle.clone())
+                }),
+                tasks.spawn_supervised("rotation_scheduler", move || {
+                    rotation::run_scheduler(rotation_handle.clone())
+                }),
+                tasks.spawn_supervised("auto_archiver", move || {
+                    archive::run_auto_archiver(archive_handle.clone())
+                }),
+                tasks.spawn_supervised("retention", move || {
+                    retention::run_retention(retention_handle.clone())
+                }),
+                tasks.spawn_supervised("keystore_monitor", move || {
+                    keystore::run_keystore_monitor(keystore_handle.clone())
+                }),
+            ];
+            for e in started.into_iter().filter_map(Result::err) {
+                warn!("Failed to start background task: {}", e);
+            }
+            
+            // Manage individual states for direct command access
+            app.manage(docs_library_state);
+            app.manage(chat_engine_state);
+
+            // Setup system tray after a small delay to ensure app is fully initialized
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                match setup_system_tray(&app_handle) {
+                    Ok(tray) => {
+                        let state = app_handle.state::<AppState>();
+                        *state.tray_handle.lock().await = Some(tray);
+                        tray_status::refresh(&state).await;
+                        println!("✅ System tray setup successfully");
+                    }
+                    Err(e) => eprintln!("❌ Failed to setup tray: {}", e),
+                }
+            });
+
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            window_manager::on_window_event(window, event);
+            match event {
+                tauri::WindowEvent::CloseRequested { .. } => {
+                    let Some(state) = window.app_handle().try_state::<AppState>() else {
+                        return;
+                    };
+                    if window.label() == "main" {
+                        // Lock vault when window is closed, the same way an
+                        // explicit lock does
+                        let app = window.app_handle().clone();
+                        tauri::async_runtime::spawn(async move {
+                            let reason = lock_state::LockReason::WindowClosed;
+                            if let Err(e) = lock_vault_for(app.state(), reason).await {
+                                error!("Failed to lock vault on window close: {}", e);
+                            }
+                        });
+                    } else {
+                        // Closing a confirmation window refuses the action
+                        state.confirmations.window_closed(window.label());
+                    }
+                }
+                _ => {}
+            }
+        })
+        .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_persisted_scope::init())
+        .plugin(tauri_plugin_store::Builder::default().build())
+        .plugin(
+            tauri_plugin_window_state::Builder::default()
+                .with_filter(window_manager::persists_geometry)
+                .build(),
+        )
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_autostart::init(
+            tauri_plugin_autostart::MacosLauncher::LaunchAgent,
+            Some(vec!["--start-hidden"]),
+        ))
+        .plugin(tauri_plugin_keyring::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .invoke_handler(tauri::generate_handler![
+            unlock_vault,
+            set_master_password,
+            save_master_password_to_keyring,
+            get_master_password_from_keyring,
+            delete_master_password_from_keyring,
+            is_vault_unlocked,
+            vault_location::get_vault_location,
+            vault_location::set_vault_location,
+            vault_integrity::verify_vault_integrity,
+            recovery_kit::generate_recovery_kit,
+            recovery_kit::get_recovery_kit_status,
+            recovery_kit::unlock_with_recovery_code,
+            recovery_kit::reset_master_password_after_recovery,
+            hardware_unlock::get_hardware_unlock_status,
+            hardware_unlock::enable_hardware_unlock,
+            hardware_unlock::disable_hardware_unlock,
+            hardware_unlock::unlock_with_hardware_key,
+            keyring_store::list_keyring_entries,
+            keyring_store::cleanup_keyring_entries,
+            lock_state::record_activity,
+            lock_state::get_lock_state,
+            doc_tags::list_doc_tags,
+            doc_tags::rename_doc_tag,
+            doc_tags::suggest_doc_tags,
+            docs_markdown::export_docs,
+            docs_markdown::import_docs,
+            doc_attachments::add_doc_attachment,
+            doc_attachments::remove_doc_attachment,
+            doc_attachments::export_doc_attachment,
+            doc_attachments::cleanup_doc_attachments,
+            doc_refs::get_backlinks,
+            doc_refs::get_doc_references,
+            doc_refs::refresh_doc_references,
+            doc_revisions::get_doc_revisions,
+            doc_revisions::diff_doc_revisions,
+            doc_revisions::restore_doc_revision,
+            key_docs::generate_key_documentation,
+            prompt_templates::list_prompt_templates,
+            prompt_templates::save_prompt_template,
+            prompt_templates::reset_prompt_template,
+            prompt_templates::set_active_prompt_template,
+            env_import::analyze_env_import,
+            env_import::import_env_variables,
+            env_ingest::ingest_env_file,
+            env_ingest::review_env_ingestion,
+            project_ignore::get_effective_ignores,
+            keystore::get_keystore_backend,
+            lock_vault,
+            get_api_keys,
+            add_api_key,
+            update_api_key,
+            delete_api_key,
+            search_api_keys,
+            get_decrypted_api_key,
+            search_api_keys_by_query,
+            export_vault,
+            start_vscode_server,
+            stop_vscode_server,
+            get_vscode_server_status,
+            get_audit_logs,
+            create_user_account,
+            authenticate_user,
+            request_password_recovery,
+            reset_master_password,
+            get_user_account,
+            is_user_account_created,
+            is_master_password_set,
+            get_projects,
+            activity::get_recent_activity,
+            record_key_usage,
+            sync_project,
+            // ML commands
+            ml_commands::initialize_ml_engine,
+            ml_commands::analyze_context_ml,
+            ml_commands::record_ml_usage,
+            ml_commands::get_ml_stats,
+            ml_commands::check_ml_status,
+            ml_commands::reinitialize_ml_engine,
+            ml_commands::get_ml_config,
+            ml_commands::detect_context,
+            // ML LLM-enhanced commands
+            ml_commands::generate_documentation,
+            ml_commands::generate_usage_examples,
+            ml_commands::generate_config_template,
+            ml_commands::get_config_recommendations,
+            ml_commands::diagnose_ml_setup,
+            // LLM Proxy commands
+            llm_proxy::process_with_llm,
+            llm_proxy::clear_llm_cache,
+            llm_proxy::get_llm_cache_stats,
+            llm_proxy::is_llm_engine_loaded,
+            // Enhanced LLM Management commands
+            llm_proxy::get_ollama_models,
+            llm_proxy::pull_ollama_model_command,
+            llm_proxy::check_ollama_status,
+            llm_proxy::delete_ollama_model,
+            // Local LLM Wrapper commands
+            llm_wrapper::validate_llm_model,
+            llm_wrapper::test_local_llm_generation,
+            // API Generator Commands
+            api_generator_commands::get_api_providers,
+            api_generator_commands::scrape_api_documentation,
+            api_generator_commands::generate_api_configuration,
+            api_generator_commands::detect_provider_from_env,
+            api_generator_commands::generate_better_auth_config,
+            api_generator_commands::generate_openai_config,
+            api_generator_commands::get_provider_templates,
+            api_generator_commands::preview_generated_config,
+            api_generator_commands::scaffold_integration,
+            api_generator_commands::remove_scaffolded_integration,
+            project_analysis::analyze_project,
+            // Enhanced API Generator Commands
+            enhanced_api_commands::generate_enhanced_configuration,
+            enhanced_api_commands::detect_project_framework,
+            enhanced_api_commands::validate_template_combination,
+            enhanced_api_commands::preview_generated_files,
+            enhanced_api_commands::get_template_suggestions,
+            enhanced_api_commands::register_custom_template,
+            enhanced_api_commands::get_generation_session_status,
+            enhanced_api_commands::cancel_generation_session,
+            enhanced_api_commands::get_active_generation_sessions,
+            enhanced_api_commands::clear_generation_cache,
+            enhanced_api_commands::get_cache_statistics,
+            enhanced_api_commands::batch_validate_templates,
+            enhanced_api_commands::get_provider_framework_compatibility,
+            // Framework Generator Commands
+            framework_generator_commands::get_supported_frameworks,
+            framework_generator_commands::generate_framework_config,
+            framework_generator_commands::generate_api_integration,
+            framework_generator_commands::generate_auth_integration,
+            framework_generator_commands::generate_state_management,
+            framework_generator_commands::generate_testing_setup,
+            framework_generator_commands::enhance_package_json,
+            framework_generator_commands::validate_framework_requirements,
+            framework_generator_commands::get_framework_llm_prompt,
+            framework_generator_commands::initialize_framework_llm,
+            framework_generator_commands::generate_complete_framework_setup,
+            // Documentation Commands
+            docs_commands::add_provider_documentation,
+            docs_commands::get_provider_documentation,
+            docs_commands::search_documentation,
+            docs_commands::get_documentation_by_id,
+            docs_commands::update_provider_documentation,
+            docs_commands::remove_provider_documentation,
+            docs_commands::get_indexed_providers,
+            docs_commands::auto_index_provider_docs,
+            docs_commands::get_context_documentation_suggestions,
+            // Native Documentation Store Commands
+            get_documentation,
+            create_documentation,
+            update_documentation,
+            delete_documentation,
+            search_native_documentation,
+            get_documentation_by_project,
+            get_documentation_by_provider,
+            toggle_documentation_favorite,
+            scrape_and_save_documentation,
+            docs_bundles::download_provider_docs,
+            docs_bundles::list_offline_bundles,
+            docs_bundles::delete_offline_bundle,
+            snippets::create_snippet,
+            snippets::update_snippet,
+            snippets::delete_snippet,
+            snippets::list_snippets,
+            snippets::render_snippet,
+            create_project,
+            update_project,
+            delete_project,
+            get_project_by_id,
+            assign_keys_to_project,
+            get_keys_by_project,
+            get_unassigned_keys,
+            search_keys_in_project,
+            sync_key_to_env_file,
+            preview_sync_key_to_env_file,
+            check_key_in_env_file,
+            get_env_file_suggestions,
+            auto_sync_workspace_env_files,
+            preview_auto_sync_workspace_env_files,
+            env_history::list_env_file_changes,
+            env_history::revert_env_file_change,
+            env_refs::resolve_env_references,
+            demo_vault::create_demo_vault,
+            naming::list_naming_policies,
+            naming::save_naming_policy,
+            naming::delete_naming_policy,
+            naming::lint_vault_naming,
+            naming::bulk_rename_keys,
+            approvals::list_pending_approvals,
+            approvals::respond_to_approval,
+            approvals::list_approval_rules,
+            approvals::revoke_approval_rule,
+            confirmations::get_confirmation_request,
+            confirmations::respond_to_confirmation,
+            confirmations::get_confirmation_policies,
+            confirmations::set_confirmation_policy,
+            rotation::set_key_rotation_schedule,
+            rotation::mark_key_rotated,
+            rotation::get_key_rotation_status,
+            rotation::list_rotation_reminders,
+            rotation::dismiss_rotation_reminder,
+            git_history::scan_git_history,
+            security_report::get_security_report,
+            project_sync::sync_all_projects,
+            credentials::import_credential_files,
+            credentials::export_credential_files,
+            oauth::add_oauth_client,
+            oauth::update_oauth_client,
+            oauth::get_access_token,
+            env_values::set_key_environment_value,
+            env_values::remove_key_environment_value,
+            pinning::pin_api_key,
+            pinning::unpin_api_key,
+            pinning::get_pinned_keys,
+            pinning::reorder_pinned_keys,
+            bulk::bulk_update_keys,
+            bulk::bulk_delete_keys,
+            archive::archive_api_key,
+            archive::restore_api_key,
+            archive::get_archived_keys,
+            archive::get_archive_policy,
+            archive::set_archive_policy,
+            retention::get_retention_policy,
+            retention::set_retention_policy,
+            retention::run_retention_now,
+            retention::get_retention_report,
+            etag::get_vault_generation,
+            quotas::get_quota_status,
+            costs::get_cost_overview,
+            costs::record_cost_entry,
+            project_settings::get_organization_settings,
+            project_settings::update_organization_settings,
+            project_settings::list_settings_templates,
+            project_settings::save_settings_template,
+            project_settings::delete_settings_template,
+            project_settings::apply_settings_template,
+            project_settings::get_effective_project_settings,
+            project_groups::list_project_groups,
+            project_groups::create_project_group,
+            project_groups::rename_project_group,
+            project_groups::delete_project_group,
+            project_groups::move_project_to_group,
+            clients::list_clients,
+            clients::approve_client,
+            clients::revoke_client,
+            parse_and_register_env_file,
+            associate_project_with_env,
+            get_project_env_associations,
+            activate_project_context,
+            check_biometric_support,
+            enable_biometric_auth,
+            authenticate_biometric,
+            update_user_preferences,
+            get_user_preferences,
+            create_passkey_challenge,
+            verify_passkey_challenge,
+            invalidate_biometric_sessions,
+            update_vscode_workspaces,
+            get_vscode_workspaces,
+            instance::take_pending_launches,
+            workspace_watcher::refresh_vscode_workspaces,
+            get_project_vscode_status,
+            open_folder,
+            open_file,
+            open_in_vscode,
+            restore_session_on_startup,
+            show_window,
+            hide_window,
+            window_manager::open_window,
+            window_manager::close_window,
+            window_manager::focus_window,
+            window_manager::list_windows,
+            window_manager::open_key_in_main_window,
+            key_environment::check_key_environment,
+            access_review::start_access_review,
+            access_review::get_access_review,
+            access_review::list_access_reviews,
+            access_review::get_access_review_status,
+            access_review::attest_access,
+            vault_snapshot::create_vault_snapshot,
+            key_history::get_vault_state_at,
+            incident::initiate_incident,
+            incident::get_incident,
+            incident::list_incidents,
+            incident::update_incident_item,
+            incident::close_incident,
+            secret_backends::add_remote_key,
+            secret_backends::test_remote_key,
+            iac_scan::scan_infrastructure_files,
+            iac_scan::generate_tfvars,
+            quit_application,
+            // AI-Enhanced Commands
+            ai_commands::analyze_project_with_ai,
+            ai_commands::get_contextual_suggestions,
+            ai_commands::get_smart_template_recommendations,
+            ai_commands::analyze_code_quality_with_ai,
+            ai_commands::generate_contextual_documentation,
+            ai_commands::check_ai_features_status,
+            ai_commands::clear_ai_analysis_cache,
+            ai_commands::get_ai_cache_stats,
+            check_for_updates,
+            install_update,
+            create_remember_me_session,
+            validate_remember_me_session,
+            get_persistent_sessions,
+            revoke_persistent_session,
+            cleanup_all_sessions,
+            sessions::list_active_sessions,
+            sessions::revoke_all_sessions,
+            leases::lease_key,
+            leases::list_leases,
+            leases::revoke_lease,
+            logging::get_recent_logs,
+            request_log::get_http_request_log,
+            masking::reveal_api_key,
+            master_password::change_master_password,
+            master_password::rekey_vault,
+            password_strength::estimate_password_strength,
+            email::get_email_settings,
+            email::set_email_settings,
+            email::delete_email_settings,
+            email::test_email_settings,
+            email::get_email_deliveries,
+            diagnostics::take_crash_report,
+            diagnostics::export_diagnostics,
+            health::get_health_report,
+            config::get_config,
+            config::set_config,
+            audit_sinks::get_audit_sink_status,
+            background_tasks::list_background_tasks,
+            background_tasks::cancel_background_task,
+            legacy_tcp::get_legacy_tcp_usage,
+            devices::list_devices,
+            devices::rename_device,
+            devices::revoke_device,
+            update_username,
+            keyring_set,
+            keyring_get,
+            keyring_delete,
+            get_device_info,
+            setup_auto_start,
+            disable_auto_start,
+            is_auto_start_enabled,
+            show_notification,
+            // Validation and Setup Commands
+            validation_setup_commands::validate_generated_configuration,
+            validation_setup_commands::generate_setup_scripts,
+            validation_setup_commands::check_environment_compatibility,
+            validation_setup_commands::validate_security_configuration,
+            validation_setup_commands::generate_deployment_config,
+            validation_setup_commands::get_validation_engine_status,
+            validation_setup_commands::get_setup_generator_capabilities,
+            validation_setup_commands::batch_validate_configurations,
+            validation_setup_commands::get_validation_setup_statistics,
+            // Documentation Generator Commands
+            docs_generator_commands::initialize_docs_llm,
+            docs_generator_commands::generate_project_documentation,
+            docs_generator_commands::generate_api_documentation,
+            docs_generator_commands::generate_setup_guide,
+            docs_generator_commands::generate_deployment_guide,
+            docs_generator_commands::generate_template_documentation,
+            docs_generator_commands::export_documentation,
+            docs_generator_commands::set_docs_generator_config,
+            docs_generator_commands::get_docs_generation_stats,
+            docs_generator_commands::generate_enhanced_documentation,
+            // Documentation Library + LLM Chat System Commands
+            chat_commands::create_chat_session,
+            chat_commands::send_chat_message,
+            chat_commands::get_chat_messages,
+            chat_commands::get_user_chat_sessions,
+            chat_commands::search_documentation_for_chat,
+            chat_commands::generate_integration,
+            chat_commands::archive_chat_session,
+            chat_commands::delete_chat_session,
+            chat_commands::get_chat_statistics,
+            chat_commands::update_session_preferences,
+            chat_commands::get_available_documentation_libraries,
+            chat_commands::export_chat_session,
+            chat_commands::configure_llm_provider,
+            chat_commands::test_llm_connection,
+            chat_commands::set_env_var,
+            // Documentation Library Management Commands
+            docs_library_commands::add_documentation_from_url,
+            docs_library_commands::add_manual_documentation,
+            docs_library_commands::search_documentation_library,
+            docs_library_commands::get_documentation_libraries,
+            docs_library_commands::get_documentation_library,
+            docs_library_commands::get_library_chunks,
+            docs_library_commands::update_documentation_library,
+            docs_library_commands::delete_documentation_library,
+            docs_library_commands::refresh_documentation_library,
+            docs_library_commands::get_library_statistics,
+            docs_library_commands::bulk_import_documentation,
+            docs_library_commands::export_documentation_library,
+            docs_library_commands::get_documentation_chunk,
+            docs_library_commands::update_documentation_chunk,
+            docs_library_commands::delete_documentation_chunk,
+            docs_library_commands::validate_documentation_url,
+            docs_generator_commands::generate_batch_documentation,
+            docs_generator_commands::get_documentation_templates,
+            docs_generator_commands::validate_documentation_content,
+            docs_generator_commands::generate_documentation_preview,
+            // Secret Manager Commands
+            secret_manager_commands::plan_secret_manager_push,
+            secret_manager_commands::push_keys_to_secret_manager,
+            // Docker Commands
+            docker_env::generate_compose_env,
+            docker_env::docker_run_with_secrets,
+            // GitHub Sync Commands
+            github_sync::sync_keys_to_github,
+            github_sync::get_github_secret_mappings,
+            github_sync::get_github_sync_drift,
+            // Webhook Commands
+            webhooks::create_webhook,
+            webhooks::get_webhooks,
+            webhooks::update_webhook,
+            webhooks::delete_webhook,
+            webhooks::test_webhook,
+            webhooks::get_webhook_deliveries,
+            // Notification Commands
+            notifications::get_notification_settings,
+            notifications::update_notification_settings,
+            notifications::get_notification_digest,
+            // Dashboard Commands
+            vault_stats::get_vault_statistics,
+            // Pagination Commands
+            pagination::list_api_keys,
+            pagination::list_documentation,
+            pagination::list_audit_logs,
+            vault_index::find_api_keys
+        ])
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| match event {
+            // Exits not started by quit_application or the tray (e.g. Cmd+Q)
+            // wait for the shutdown sequence too
+            tauri::RunEvent::ExitRequested { api, .. } if !shutdown::is_complete() => {
+                api.prevent_exit();
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    shutdown::run(&app).await;
+                    app.exit(0);
+                });
+            }
+            tauri::RunEvent::Exit => {
+                // Give background tasks a moment to wind down
+                let tasks = Arc::clone(&app.state::<AppState>().tasks);
+                tauri::async_runtime::block_on(tasks.shutdown(std::time::Duration::from_secs(5)));
+            }
+            _ => {}
+        });
+}
@@ -8,6 +8,7 @@ use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 use tauri::Manager;
 use crate::enhanced_types::*;
+use crate::errors::KeyKeeperError;
 
 /// Configuration for the LLM engine
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1023,17 +1024,17 @@ pub struct ModelValidationResult {
 
 /// Tauri command to validate model file with detailed info
 #[tauri::command]
-pub async fn validate_llm_model(model_path: String) -> Result<ModelValidationResult, String> {
+pub async fn validate_llm_model(model_path: String) -> Result<ModelValidationResult, KeyKeeperError> {
     let config = LLMConfig {
         model_path,
         ..Default::default()
     };
     
     let engine = LLMEngine::new(config)
-        .map_err(|e| format!("Failed to create LLM engine: {}", e))?;
+        .map_err(|e| KeyKeeperError::Validation(format!("Failed to create LLM engine: {}", e)))?;
     
     engine.validate_model_file_enhanced()
-        .map_err(|e| format!("Model validation failed: {}", e))
+        .map_err(|e| KeyKeeperError::Validation(format!("Model validation failed: {}", e)))
 }
 
 /// Tauri command to test local LLM generation
@@ -1041,7 +1042,7 @@ pub async fn validate_llm_model(model_path: String) -> Result<ModelValidationRes
 pub async fn test_local_llm_generation(
     prompt: String, 
     model_path: Option<String>
-) -> Result<String, String> {
+) -> Result<String, KeyKeeperError> {
     let config = if let Some(path) = model_path {
         LLMConfig {
             model_path: path,
@@ -1058,10 +1059,10 @@ pub async fn test_local_llm_generation(
     };
     
     let engine = LLMEngine::new(config)
-        .map_err(|e| format!("Failed to create LLM engine: {}", e))?;
+        .map_err(|e| KeyKeeperError::Validation(format!("Failed to create LLM engine: {}", e)))?;
     
     engine.generate_text(&prompt).await
-        .map_err(|e| format!("Text generation failed: {}", e))
+        .map_err(|e| KeyKeeperError::Internal(format!("Text generation failed: {}", e)))
 }
 
 /// Security analysis result
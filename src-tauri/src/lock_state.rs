@@ -0,0 +1,200 @@
+//! Lock state as seen by the HTTP server and the UI.
+//!
+//! While the vault is locked the HTTP server answers everything except the
+//! health checks and `/api/vault/lock-state` with 401 `VAULT_LOCKED`. With
+//! `server.pause_when_locked` it stops listening altogether until the next
+//! unlock. Lock changes are emitted to the app (`vault-state-changed`, plus
+//! `vault-locked` with the reason) and can be long-polled over HTTP with
+//! `GET /api/vault/lock-state?locked=<what the client saw>&wait=<seconds>`.
+//!
+//! `vault.inactivity_lock_minutes` locks the vault once the app has seen no
+//! user activity for that long. The window reports activity with
+//! `record_activity`; HTTP requests don't count, so a polling client can't
+//! keep the vault open.
+
+use crate::errors::KeyKeeperError;
+use crate::events::{self, EventPayload};
+use crate::{timestamps, AppState};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::LazyLock;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+pub const LOCK_STATE_PATH: &str = "/api/vault/lock-state";
+/// Reachable while the vault is locked
+const OPEN_PATHS: &[&str] = &["/health", "/ready", "/live", LOCK_STATE_PATH];
+/// Longest a lock-state request is held open
+pub const MAX_WAIT_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LockReason {
+    Manual,
+    SessionExpired,
+    SessionsRevoked,
+    Inactivity,
+    WindowClosed,
+    /// An incident was initiated
+    Incident,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LockEvent {
+    pub locked: bool,
+    /// Why the vault was locked; `None` when unlocked or not yet unlocked
+    pub reason: Option<LockReason>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub at: DateTime<Utc>,
+}
+
+static STATE: LazyLock<watch::Sender<LockEvent>> = LazyLock::new(|| {
+    watch::Sender::new(LockEvent {
+        locked: true,
+        reason: None,
+        at: Utc::now(),
+    })
+});
+/// Unix time of the last user activity in the app
+static LAST_ACTIVITY: AtomicI64 = AtomicI64::new(0);
+/// The HTTP server was stopped by a lock and restarts on unlock
+static SERVER_PAUSED: AtomicBool = AtomicBool::new(false);
+
+pub fn is_open_path(path: &str) -> bool {
+    OPEN_PATHS.contains(&path)
+}
+
+pub fn current() -> LockEvent {
+    STATE.borrow().clone()
+}
+
+pub fn set_locked(reason: LockReason) {
+    STATE.send_replace(LockEvent {
+        locked: true,
+        reason: Some(reason),
+        at: Utc::now(),
+    });
+    events::publish(EventPayload::Locked { reason });
+}
+
+pub fn set_unlocked() {
+    let now = Utc::now();
+    record_activity_at(now);
+    STATE.send_replace(LockEvent {
+        locked: false,
+        reason: None,
+        at: now,
+    });
+    events::publish(EventPayload::Unlocked);
+}
+
+pub fn record_activity_at(now: DateTime<Utc>) {
+    LAST_ACTIVITY.fetch_max(now.timestamp(), Ordering::Relaxed);
+}
+
+/// Whether nothing happened in the app for `minutes` before `now`
+pub fn inactive_for(minutes: u64, now: DateTime<Utc>) -> bool {
+    let last = LAST_ACTIVITY.load(Ordering::Relaxed);
+    now.timestamp() - last >= ChronoDuration::minutes(minutes as i64).num_seconds()
+}
+
+/// The lock state once it differs from `seen`, or after `wait`
+pub async fn wait_for_change(seen: Option<bool>, wait: Duration) -> LockEvent {
+    let mut receiver = STATE.subscribe();
+    if let Some(seen) = seen {
+        let changed = receiver.wait_for(|event| event.locked != seen);
+        let _ = tokio::time::timeout(wait, changed).await;
+    }
+    current()
+}
+
+/// Don't restart the server on unlock, e.g. because it was stopped by hand
+pub fn clear_server_pause() {
+    SERVER_PAUSED.store(false, Ordering::SeqCst);
+}
+
+/// Whether a server start should wait for the unlock. Marks it as paused if
+/// so, so that the unlock starts it.
+pub async fn defer_server_start(state: &AppState) -> bool {
+    let defer =
+        state.config.effective().server.pause_when_locked && !*state.is_unlocked.lock().await;
+    if defer {
+        SERVER_PAUSED.store(true, Ordering::SeqCst);
+    }
+    defer
+}
+
+fn pause_server(state: &AppState) {
+    if !state.config.effective().server.pause_when_locked {
+        return;
+    }
+    if state.tasks.cancel(crate::HTTP_SERVER_TASK).unwrap_or(false) {
+        state.vscode_server_running.store(false, Ordering::SeqCst);
+        state.health.set_server_running(false);
+        SERVER_PAUSED.store(true, Ordering::SeqCst);
+        info!("Vault locked, HTTP server paused");
+    }
+}
+
+async fn resume_server(app: &AppHandle) {
+    if SERVER_PAUSED.swap(false, Ordering::SeqCst) {
+        match crate::start_vscode_server(app.state()).await {
+            Ok(_) => info!("Vault unlocked, HTTP server resumed"),
+            Err(e) => warn!("Failed to resume HTTP server: {}", e),
+        }
+    }
+}
+
+/// Tell the app about lock changes and pause or resume the HTTP server
+pub async fn run_lock_events(app: AppHandle) {
+    let mut receiver = STATE.subscribe();
+    while receiver.changed().await.is_ok() {
+        let event = receiver.borrow_and_update().clone();
+        let _ = app.emit("vault-state-changed", !event.locked);
+        if event.locked {
+            let _ = app.emit("vault-locked", &event);
+            pause_server(&app.state());
+        } else {
+            resume_server(&app).await;
+        }
+    }
+}
+
+/// Called by the window on user input, at most every few seconds
+#[tauri::command]
+pub async fn record_activity(state: State<'_, AppState>) -> Result<(), KeyKeeperError> {
+    if *state.is_unlocked.lock().await {
+        record_activity_at(Utc::now());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_lock_state() -> Result<LockEvent, KeyKeeperError> {
+    Ok(current())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_health_checks_stay_open_and_activity_postpones_the_lock() {
+        assert!(is_open_path("/health"));
+        assert!(is_open_path(LOCK_STATE_PATH));
+        assert!(!is_open_path("/api/docs"));
+        assert!(!is_open_path("/api/clients/register"));
+
+        let now = Utc::now();
+        record_activity_at(now - ChronoDuration::minutes(20));
+        assert!(inactive_for(15, now));
+        record_activity_at(now - ChronoDuration::minutes(5));
+        assert!(!inactive_for(15, now));
+        // An older report doesn't move the last activity back
+        record_activity_at(now - ChronoDuration::minutes(30));
+        assert!(!inactive_for(15, now));
+    }
+}
@@ -0,0 +1,162 @@
+//! Masked views of keys, for everything that lists them.
+//!
+//! Listings used to carry every key's value: the UI's IPC commands and the
+//! HTTP routes returned the vault's `ApiKey`s as they are, and only the old
+//! TCP server masked them, with its own rule. Listings now return
+//! `MaskedApiKey`s, whose value, per-environment values and passphrase are
+//! replaced by `mask_value`, over IPC and HTTP alike; events carry ids, not
+//! keys. A full value is only handed out by asking for one key's value:
+//! `reveal_api_key` in the app, `POST /api/keys/{id}/decrypt` over HTTP,
+//! both recorded in the key's activity. Remote keys have no value to mask;
+//! theirs is fetched when asked for (see `secret_backends`).
+
+use crate::errors::KeyKeeperError;
+use crate::guards::{self, Requirement};
+use crate::{activity, archive, log_audit_event, secret_backends, ApiKey, AppState};
+use chrono::Utc;
+use serde::Serialize;
+use tauri::State;
+
+/// Shown instead of a value
+pub const MASK: &str = "••••••••";
+/// Values at least this long keep their last `VISIBLE_SUFFIX` chars, so keys
+/// can still be told apart
+const MIN_LEN_WITH_SUFFIX: usize = 16;
+const VISIBLE_SUFFIX: usize = 4;
+
+/// `value` masked, e.g. `••••••••a1b2`
+pub fn mask_value(value: &str) -> String {
+    // The placeholder of locked vaults isn't a value
+    if value == "[ENCRYPTED]" {
+        return value.to_string();
+    }
+    let len = value.chars().count();
+    if len < MIN_LEN_WITH_SUFFIX {
+        return MASK.to_string();
+    }
+    let suffix: String = value.chars().skip(len - VISIBLE_SUFFIX).collect();
+    format!("{}{}", MASK, suffix)
+}
+
+/// An `ApiKey` as listings return it: the same fields, with the secrets masked
+#[derive(Debug, Clone, Serialize)]
+pub struct MaskedApiKey {
+    #[serde(flatten)]
+    key: ApiKey,
+    /// Always true, so clients can tell a masked value from a real one
+    pub masked: bool,
+    /// Length of the real value, for strength checks
+    pub value_length: usize,
+}
+
+impl From<&ApiKey> for MaskedApiKey {
+    fn from(key: &ApiKey) -> Self {
+        let mut masked = key.clone();
+        masked.key = mask_value(&key.key);
+        for value in masked.values.values_mut() {
+            *value = mask_value(value);
+        }
+        if let Some(parts) = masked.credential.as_mut() {
+            parts.passphrase = parts.passphrase.as_deref().map(mask_value);
+        }
+        MaskedApiKey {
+            key: masked,
+            masked: true,
+            value_length: key.key.chars().count(),
+        }
+    }
+}
+
+impl From<ApiKey> for MaskedApiKey {
+    fn from(key: ApiKey) -> Self {
+        MaskedApiKey::from(&key)
+    }
+}
+
+pub fn mask_all<I>(keys: I) -> Vec<MaskedApiKey>
+where
+    I: IntoIterator,
+    I::Item: Into<MaskedApiKey>,
+{
+    keys.into_iter().map(Into::into).collect()
+}
+
+/// Full value of one key, for copying or showing it in the app
+#[tauri::command]
+pub async fn reveal_api_key(
+    key_id: String,
+    reason: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, KeyKeeperError> {
+    let allowed = guards::require(&state, &[Requirement::Unlocked, Requirement::LocalOnly]).await;
+    if let Err(e) = allowed {
+        log_audit_event(
+            &state,
+            "reveal_api_key",
+            "api_key",
+            Some(&key_id),
+            false,
+            Some(&e.to_string()),
+        )
+        .await;
+        return Err(e);
+    }
+
+    let mut vault_guard = state.vault.lock().await;
+    let Some(key) = vault_guard.keys.get(&key_id).cloned() else {
+        return Err(KeyKeeperError::not_found("API key"));
+    };
+    // Saved along with the next vault write
+    archive::touch(&mut vault_guard, &key_id, Utc::now());
+    activity::record(
+        &mut vault_guard,
+        "key_used",
+        &key,
+        activity::Actor::ui(),
+        None,
+        Some(reason.unwrap_or_else(|| "Revealed value".to_string())),
+    );
+    drop(vault_guard);
+
+    let value = secret_backends::value(&state.remote_values, &state.vault, &key).await;
+    log_audit_event(
+        &state,
+        "reveal_api_key",
+        "api_key",
+        Some(&key_id),
+        value.is_ok(),
+        value.as_ref().err().map(|e| e.to_string()).as_deref(),
+    )
+    .await;
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault_tests::test_key;
+
+    #[test]
+    fn masks_values_and_keeps_the_shape() {
+        assert_eq!(mask_value("short"), MASK);
+        assert_eq!(
+            mask_value("sk_live_0123456789abcdef"),
+            format!("{}cdef", MASK)
+        );
+        assert_eq!(mask_value("[ENCRYPTED]"), "[ENCRYPTED]");
+
+        let key = test_key("k1")
+            .name("STRIPE_KEY")
+            .secret("sk_live_0123456789abcdef")
+            .environment("production")
+            .value("staging", "sk_test_fedcba9876543210")
+            .build();
+        let json = serde_json::to_value(MaskedApiKey::from(&key)).unwrap();
+        assert_eq!(json["id"], "k1");
+        assert_eq!(json["name"], "STRIPE_KEY");
+        assert_eq!(json["masked"], true);
+        assert_eq!(json["value_length"], 24);
+        let text = json.to_string();
+        assert!(!text.contains("sk_live") && !text.contains("sk_test"));
+    }
+}
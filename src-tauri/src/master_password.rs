@@ -0,0 +1,261 @@
+//! Changing the master password of an unlocked vault.
+//!
+//! Until now the password could only be replaced through a recovery token
+//! or a recovery kit. `change_master_password` checks the current password,
+//! derives a new salt and vault key from the new one, seals the values that
+//! old builds encrypted on their own again (see `decryption_keys`) and
+//! writes the vault. The recovery kit and hardware unlock envelopes wrap the
+//! vault key on every save, so they follow along. A master password kept in
+//! the keyring is replaced too. If writing the vault or the keyring entry
+//! fails, the documents on disk are put back as they were and the vault in
+//! memory is left untouched, so the old password keeps working.
+//!
+//! `rekey_vault` goes through the same steps with the password unchanged:
+//! a fresh salt, hence a fresh vault key, and fresh salts for sealed values,
+//! e.g. after a suspected compromise or before handing out a backup.
+
+use crate::errors::KeyKeeperError;
+use crate::guards::{self, Requirement};
+use crate::keyring_store::KeyringStore;
+use crate::vault_storage::VaultStorage;
+use crate::{
+    check_master_password, decryption_keys, keyring_store, log_audit_event, password_strength,
+    recovery_kit, save_vault_to, secure_memory, ApiKeyVault, AppState, PasswordCheck,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, State};
+use tracing::{error, info};
+use zeroize::Zeroizing;
+
+const LEGACY_MASTER_PASSWORD: (&str, &str) =
+    (keyring_store::SERVICE, keyring_store::MASTER_PASSWORD);
+
+/// Copy of `vault` under `new`: new hash, salt and key, sealed values sealed
+/// again with fresh salts. Returns the copy and how many were sealed again.
+fn rekey(vault: &ApiKeyVault, old: &str, new: &str) -> Result<(ApiKeyVault, usize), String> {
+    let mut rekeyed = vault.clone();
+    let resealed = decryption_keys::reseal(&mut rekeyed, old, new)?;
+    if resealed > 0 {
+        info!("Sealed {} values again under the new key", resealed);
+    }
+    recovery_kit::set_new_master_password(&mut rekeyed, new)?;
+    Ok((rekeyed, resealed))
+}
+
+/// The vault documents as they are on disk
+struct Snapshot {
+    vault: Option<String>,
+    metadata: Option<String>,
+}
+
+impl Snapshot {
+    fn take(storage: &dyn VaultStorage) -> Result<Self, String> {
+        Ok(Snapshot {
+            vault: storage.read_vault()?,
+            metadata: storage.read_metadata()?,
+        })
+    }
+
+    fn restore(&self, storage: &dyn VaultStorage) {
+        let result = self
+            .vault
+            .as_deref()
+            .map_or(Ok(()), |contents| storage.write_vault(contents))
+            .and_then(|_| {
+                self.metadata
+                    .as_deref()
+                    .map_or(Ok(()), |contents| storage.write_metadata(contents))
+            });
+        if let Err(e) = result {
+            error!(
+                "Failed to restore the vault after a failed password change: {}",
+                e
+            );
+        }
+    }
+}
+
+/// Replace the master password kept in the keyring, if there is one
+fn update_keyring(store: &KeyringStore, password: &str) -> Result<(), KeyKeeperError> {
+    let stored = store
+        .get(keyring_store::MASTER_PASSWORD, Some(LEGACY_MASTER_PASSWORD))
+        .map_err(|e| KeyKeeperError::Io(format!("Failed to read keyring: {}", e)))?;
+    if stored.is_some() {
+        store
+            .set(keyring_store::MASTER_PASSWORD, password)
+            .map_err(|e| KeyKeeperError::Io(format!("Failed to update keyring: {}", e)))?;
+        info!("Master password in keyring updated");
+    }
+    Ok(())
+}
+
+/// Put the vault under a new salt and key derived from `new`, checking
+/// `old` first. Returns how many sealed values were sealed again.
+async fn replace_vault_key(
+    app: &AppHandle,
+    state: &State<'_, AppState>,
+    action: &str,
+    old_password: &str,
+    new_password: &str,
+) -> Result<usize, KeyKeeperError> {
+    let audit_failure = |message: String| async move {
+        log_audit_event(state, action, "vault", None, false, Some(&message)).await;
+    };
+    if new_password.is_empty() {
+        return Err(KeyKeeperError::Validation(
+            "Master password cannot be empty".to_string(),
+        ));
+    }
+
+    // Opened now, since it reads the vault id
+    let keyring = if old_password != new_password {
+        Some(KeyringStore::open(app, state).await?)
+    } else {
+        None
+    };
+    // Write queued saves first, so a rollback restores the latest vault.
+    // The vault stays locked until the change is done or rolled back.
+    state.saves.flush().await?;
+    let mut vault_guard = state.vault.lock().await;
+
+    let verified = vault_guard
+        .master_password_hash
+        .as_deref()
+        .is_some_and(|stored| {
+            check_master_password(stored, old_password) != PasswordCheck::Invalid
+        });
+    if !verified {
+        drop(vault_guard);
+        audit_failure("Invalid password".to_string()).await;
+        return Err(KeyKeeperError::Validation(
+            "Invalid master password".to_string(),
+        ));
+    }
+
+    let (mut rekeyed, resealed) = match rekey(&vault_guard, old_password, new_password) {
+        Ok(rekeyed) => rekeyed,
+        Err(e) => {
+            drop(vault_guard);
+            audit_failure(e.clone()).await;
+            return Err(KeyKeeperError::Crypto(e));
+        }
+    };
+
+    let snapshot = Snapshot::take(&*state.storage).map_err(KeyKeeperError::Io)?;
+    let written = save_vault_to(&*state.storage, &rekeyed).and_then(|()| {
+        keyring
+            .as_ref()
+            .map_or(Ok(()), |keyring| update_keyring(keyring, new_password))
+    });
+    if let Err(e) = written {
+        snapshot.restore(&*state.storage);
+        secure_memory::wipe_vault_secrets(&mut rekeyed);
+        drop(vault_guard);
+        audit_failure(e.to_string()).await;
+        return Err(e);
+    }
+
+    let mut previous = std::mem::replace(&mut *vault_guard, rekeyed);
+    secure_memory::wipe_vault_secrets(&mut previous);
+    state.decryption_keys.purge();
+    state.decryption_keys.derive_for(&vault_guard, new_password);
+    drop(vault_guard);
+
+    log_audit_event(state, action, "vault", None, true, None).await;
+    Ok(resealed)
+}
+
+#[tauri::command]
+pub async fn change_master_password(
+    app: AppHandle,
+    old_password: String,
+    new_password: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    let old_password = Zeroizing::new(old_password);
+    let new_password = Zeroizing::new(new_password);
+    guards::require(&state, &[Requirement::Unlocked, Requirement::LocalOnly]).await?;
+    let user_inputs = password_strength::account_inputs(&*state.vault.lock().await);
+    password_strength::enforce(&state, &new_password, &user_inputs)?;
+    replace_vault_key(
+        &app,
+        &state,
+        "change_master_password",
+        &old_password,
+        &new_password,
+    )
+    .await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RekeyReport {
+    /// Values encrypted on their own by old builds, sealed again
+    pub resealed_values: usize,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub rekeyed_at: DateTime<Utc>,
+}
+
+/// Encrypt the vault again under a fresh salt and key, keeping the password.
+/// The key is derived from the password, so it's needed here too.
+#[tauri::command]
+pub async fn rekey_vault(
+    app: AppHandle,
+    password: String,
+    state: State<'_, AppState>,
+) -> Result<RekeyReport, KeyKeeperError> {
+    let password = Zeroizing::new(password);
+    guards::require(&state, &[Requirement::Unlocked, Requirement::LocalOnly]).await?;
+    let resealed_values =
+        replace_vault_key(&app, &state, "rekey_vault", &password, &password).await?;
+    Ok(RekeyReport {
+        resealed_values,
+        rekeyed_at: Utc::now(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault_storage::MemoryStorage;
+    use crate::vault_tests::test_key;
+    use crate::{decrypt_api_key, decrypt_vault_from};
+
+    #[test]
+    fn rekeyed_vault_opens_with_the_new_password_only() {
+        let mut vault = ApiKeyVault::default();
+        recovery_kit::set_new_master_password(&mut vault, "old password").unwrap();
+        let key = test_key("k1")
+            .name("LEGACY")
+            .secret(&decryption_keys::seal("sk_live_legacy", "old password"))
+            .environment("production")
+            .value("staging", "sk_test_plain")
+            .build();
+        vault.keys.insert(key.id.clone(), key);
+
+        let (rekeyed, resealed) = rekey(&vault, "old password", "new password").unwrap();
+        assert_eq!(resealed, 1);
+        assert_ne!(rekeyed.salt, vault.salt);
+        assert_ne!(rekeyed.encryption_key, vault.encryption_key);
+        let storage = MemoryStorage::default();
+        save_vault_to(&storage, &rekeyed).unwrap();
+        assert!(decrypt_vault_from(&storage, "old password").is_err());
+
+        let opened = decrypt_vault_from(&storage, "new password").unwrap();
+        let key = &opened.keys["k1"];
+        assert!(decrypt_api_key(&key.key, "old password").is_err());
+        assert_eq!(
+            decrypt_api_key(&key.key, "new password").unwrap(),
+            "sk_live_legacy"
+        );
+        assert_eq!(key.values["staging"], "sk_test_plain");
+        assert_eq!(
+            check_master_password(
+                opened.master_password_hash.as_deref().unwrap(),
+                "new password"
+            ),
+            PasswordCheck::Valid
+        );
+    }
+}
@@ -0,0 +1,251 @@
+//! Vault schema versioning.
+//!
+//! The vault document records the `schema_version` it was written with.
+//! On load the raw JSON is brought up to `CURRENT_SCHEMA_VERSION` by running
+//! every pending migration in order, after copying the files on disk to a
+//! backup. Vaults written by a newer build are refused rather than risk
+//! being overwritten with fields this build doesn't know about.
+
+use crate::timestamps::parse_timestamp;
+use crate::vault_storage::VaultStorage;
+use crate::ApiKeyVault;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+pub const CURRENT_SCHEMA_VERSION: u32 = 6;
+
+/// Files without a `schema_version` predate versioning
+pub fn legacy_schema_version() -> u32 {
+    1
+}
+
+struct Migration {
+    /// Version the migration upgrades from; it produces `from + 1`
+    from: u32,
+    description: &'static str,
+    apply: fn(&mut Value) -> Result<(), String>,
+}
+
+/// Ordered by `from`, one entry per schema bump
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 1,
+        description: "normalize timestamps to RFC 3339",
+        apply: normalize_timestamps,
+    },
+    Migration {
+        from: 2,
+        description: "merge per-environment copies of a key into one key",
+        apply: crate::env_values::merge_environment_keys,
+    },
+    Migration {
+        from: 3,
+        description: "replace free-text rate limits with quotas",
+        apply: crate::quotas::migrate_rate_limits,
+    },
+    Migration {
+        from: 4,
+        description: "store project settings as overrides of inherited ones",
+        apply: crate::project_settings::migrate_project_overrides,
+    },
+    Migration {
+        from: 5,
+        description: "give the vault an ID to namespace keyring entries",
+        apply: crate::keyring_store::assign_vault_id,
+    },
+];
+
+pub fn schema_version_of(document: &Value) -> u32 {
+    document
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .map(|v| v as u32)
+        .unwrap_or_else(legacy_schema_version)
+}
+
+/// Fail with a readable message when the vault comes from a newer build
+pub fn ensure_supported(schema_version: u32, written_by: Option<&str>) -> Result<(), String> {
+    if schema_version <= CURRENT_SCHEMA_VERSION {
+        return Ok(());
+    }
+    let writer = written_by
+        .map(|v| format!("KeyKeeper {}", v))
+        .unwrap_or_else(|| "a newer version of KeyKeeper".to_string());
+    Err(format!(
+        "This vault was written by {} (schema v{}), but this version only supports up to schema v{}. Please update KeyKeeper to open it.",
+        writer, schema_version, CURRENT_SCHEMA_VERSION
+    ))
+}
+
+/// Run pending migrations on a decoded vault document. Returns the version
+/// the document was migrated from, or `None` if it was already current.
+pub fn migrate(document: &mut Value, storage: &dyn VaultStorage) -> Result<Option<u32>, String> {
+    let original = schema_version_of(document);
+    ensure_supported(original, None)?;
+    if original == CURRENT_SCHEMA_VERSION {
+        return Ok(None);
+    }
+
+    storage.backup(original)?;
+
+    let mut version = original;
+    for migration in MIGRATIONS.iter().filter(|m| m.from >= original) {
+        if migration.from != version {
+            return Err(format!(
+                "No migration from vault schema v{} (next available starts at v{})",
+                version, migration.from
+            ));
+        }
+        info!(
+            "Migrating vault schema v{} -> v{}: {}",
+            version,
+            version + 1,
+            migration.description
+        );
+        (migration.apply)(document)
+            .map_err(|e| format!("Vault migration v{} failed: {}", version, e))?;
+        version += 1;
+    }
+    if version != CURRENT_SCHEMA_VERSION {
+        return Err(format!(
+            "Vault schema v{} could not be migrated to v{}",
+            version, CURRENT_SCHEMA_VERSION
+        ));
+    }
+
+    if let Some(object) = document.as_object_mut() {
+        object.insert("schema_version".to_string(), Value::from(version));
+    }
+    Ok(Some(original))
+}
+
+/// Parse a plaintext vault document, migrating it if needed
+pub fn load_vault_document(
+    json: &str,
+    storage: &dyn VaultStorage,
+) -> Result<ApiKeyVault, String> {
+    let mut document: Value =
+        serde_json::from_str(json).map_err(|e| format!("Failed to parse vault: {}", e))?;
+    migrate(&mut document, storage)?;
+    serde_json::from_value(document).map_err(|e| format!("Failed to parse vault: {}", e))
+}
+
+fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".schema-v{}.bak", version));
+    path.with_file_name(name)
+}
+
+/// Copy the vault and its metadata file aside before they get rewritten in
+/// the new format. An existing backup of the same version is kept, since it
+/// is the closest to the original.
+pub fn backup_before_migrate(vault_path: &Path, version: u32) -> Result<(), String> {
+    let metadata_path = vault_path.with_extension("metadata.json");
+    for path in [vault_path, metadata_path.as_path()] {
+        if !path.exists() {
+            continue;
+        }
+        let backup = backup_path(path, version);
+        if backup.exists() {
+            continue;
+        }
+        fs::copy(path, &backup).map_err(|e| {
+            format!(
+                "Failed to back up {} before migration: {}",
+                path.display(),
+                e
+            )
+        })?;
+        info!("Backed up {} to {}", path.display(), backup.display());
+    }
+    Ok(())
+}
+
+const TIMESTAMP_FIELDS: &[&str] = &[
+    "timestamp",
+    "created_at",
+    "updated_at",
+    "expires_at",
+    "last_used",
+    "locked_until",
+    "last_accessed",
+    "last_vscode_check",
+    "last_updated",
+    "last_backup",
+    "synced_at",
+    "pushed_at",
+    "last_digest_at",
+    "last_expiry_notice_at",
+    "last_drift_notice_at",
+];
+
+/// v1 -> v2: rewrite free-form timestamps (plain dates, naive date-times) as
+/// RFC 3339. Values that can't be parsed are left for the lenient
+/// deserializers, which map them to `None` or the epoch.
+fn normalize_timestamps(document: &mut Value) -> Result<(), String> {
+    match document {
+        Value::Object(map) => {
+            for (field, value) in map.iter_mut() {
+                if let (true, Value::String(raw)) =
+                    (TIMESTAMP_FIELDS.contains(&field.as_str()), &value)
+                {
+                    match parse_timestamp(raw) {
+                        Some(parsed) => *value = Value::String(parsed.to_rfc3339()),
+                        None if raw.is_empty() => *value = Value::Null,
+                        None => warn!("Leaving unparseable {} {:?} as is", field, raw),
+                    }
+                } else {
+                    normalize_timestamps(value)?;
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                normalize_timestamps(item)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault_storage::{FileStorage, MemoryStorage};
+
+    #[test]
+    fn migrates_legacy_vault_and_backs_it_up() {
+        let dir = std::env::temp_dir().join(format!("kk-migrate-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let vault_path = dir.join("vault.json");
+        fs::write(&vault_path, "{}").unwrap();
+
+        let mut document = serde_json::json!({
+            "keys": { "k": { "expires_at": "2024-12-31", "created_at": "2024-01-01T10:00" } }
+        });
+        let from = migrate(&mut document, &FileStorage::new(&vault_path)).unwrap();
+
+        assert_eq!(from, Some(1));
+        assert_eq!(document["schema_version"], CURRENT_SCHEMA_VERSION);
+        assert_eq!(
+            document["keys"]["k"]["expires_at"],
+            "2024-12-31T00:00:00+00:00"
+        );
+        assert_eq!(
+            document["keys"]["k"]["created_at"],
+            "2024-01-01T10:00:00+00:00"
+        );
+        assert!(dir.join("vault.json.schema-v1.bak").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn refuses_newer_schema() {
+        let mut document = serde_json::json!({ "schema_version": CURRENT_SCHEMA_VERSION + 1 });
+        let err = migrate(&mut document, &MemoryStorage::default()).unwrap_err();
+        assert!(err.contains("Please update KeyKeeper"));
+    }
+}
@@ -100,12 +100,26 @@ pub async fn analyze_context_ml(
     app_state: State<'_, AppState>,
     request: MLAnalysisRequest,
 ) -> Result<MLPrediction, String> {
+    // Archived keys aren't suggested
+    let available_keys: Vec<String> = {
+        let vault_guard = app_state.vault.lock().await;
+        request
+            .available_keys
+            .into_iter()
+            .filter(|id| {
+                !vault_guard
+                    .keys
+                    .get(id)
+                    .is_some_and(crate::archive::is_archived)
+            })
+            .collect()
+    };
     let engine_lock = app_state.ml_engine.lock().await;
     
     match engine_lock.as_ref() {
         Some(engine) => {
             engine
-                .analyze_context(request.context, request.available_keys)
+                .analyze_context(request.context, available_keys)
                 .await
                 .map_err(|e| format!("ML analysis failed: {}", e))
         }
@@ -717,20 +717,9 @@ impl MLEngine {
         if let Some(llm_engine) = &self.llm_engine {
             // Check if model is still loaded
             if llm_engine.is_loaded().await {
-                let prompt = format!(
-                    "Generate comprehensive API documentation for the {} provider. 
-
-Context: {}
-
-Include:
-1. Overview and description
-2. Authentication methods
-3. Common endpoints and usage examples
-4. Configuration examples
-5. Best practices
-
-Documentation:",
-                    provider, context
+                let prompt = crate::prompt_templates::render(
+                    crate::prompt_templates::PROVIDER_DOCUMENTATION,
+                    &[("provider", provider), ("context", context)],
                 );
                 
                 match llm_engine.generate_text(&prompt).await {
@@ -757,16 +746,9 @@ Documentation:",
         // Try LLM generation if available
         if let Some(llm_engine) = &self.llm_engine {
             if llm_engine.is_loaded().await {
-                let prompt = format!(
-                    "Generate 3 practical code examples for using the {} API with the key format: {}.
-
-Provide examples in different programming languages (Python, JavaScript, curl) showing:
-1. Basic API call
-2. Authentication setup
-3. Error handling
-
-Examples:",
-                    provider, api_key_format
+                let prompt = crate::prompt_templates::render(
+                    crate::prompt_templates::USAGE_EXAMPLES,
+                    &[("provider", provider), ("key_format", api_key_format)],
                 );
                 
                 match llm_engine.generate_text(&prompt).await {
@@ -801,17 +783,9 @@ Examples:",
         // Try LLM generation if available
         if let Some(llm_engine) = &self.llm_engine {
             if llm_engine.is_loaded().await {
-                let prompt = format!(
-                    "Generate a configuration template for {} API in {} environment.
-
-Include:
-1. Environment variables setup
-2. Configuration file examples
-3. Security best practices
-4. Common settings
-
-Template:",
-                    provider, environment
+                let prompt = crate::prompt_templates::render(
+                    crate::prompt_templates::CONFIG_TEMPLATE,
+                    &[("provider", provider), ("environment", environment)],
                 );
                 
                 match llm_engine.generate_text(&prompt).await {
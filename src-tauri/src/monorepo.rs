@@ -0,0 +1,307 @@
+//! Monorepo detection.
+//!
+//! A monorepo is registered as a single project rooted at the workspace
+//! root (pnpm, npm/yarn workspaces, Turborepo, Nx, Lerna or a Cargo
+//! workspace). Its member packages are listed on the project so env
+//! associations, key assignment and syncing can be scoped to one of them.
+
+use crate::project_ignore::{ProjectIgnores, Purpose};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// How deep `**` in a workspace pattern descends
+const MAX_GLOB_DEPTH: usize = 4;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkspacePackage {
+    pub name: String,
+    /// Relative to the project root, with `/` separators
+    pub path: String,
+}
+
+fn read(root: &Path, file: &str) -> Option<String> {
+    fs::read_to_string(root.join(file)).ok()
+}
+
+fn package_json_workspaces(root: &Path) -> Option<Vec<String>> {
+    let manifest: serde_json::Value = serde_json::from_str(&read(root, "package.json")?).ok()?;
+    // Either `"workspaces": [...]` or yarn's `"workspaces": { "packages": [...] }`
+    let list = manifest["workspaces"]
+        .as_array()
+        .or_else(|| manifest["workspaces"]["packages"].as_array())?;
+    Some(
+        list.iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect(),
+    )
+}
+
+fn pnpm_workspaces(root: &Path) -> Option<Vec<String>> {
+    let manifest = read(root, "pnpm-workspace.yaml")?;
+    // Only the `packages:` list matters, one `- 'glob'` per line
+    let mut in_packages = false;
+    let mut patterns = Vec::new();
+    for line in manifest.lines() {
+        let trimmed = line.trim();
+        if !line.starts_with([' ', '\t', '-']) && !trimmed.is_empty() {
+            in_packages = trimmed.starts_with("packages:");
+            continue;
+        }
+        if in_packages {
+            if let Some(item) = trimmed.strip_prefix('-') {
+                patterns.push(item.trim().trim_matches(['"', '\'']).to_string());
+            }
+        }
+    }
+    Some(patterns)
+}
+
+fn lerna_workspaces(root: &Path) -> Option<Vec<String>> {
+    let manifest: serde_json::Value = serde_json::from_str(&read(root, "lerna.json")?).ok()?;
+    Some(
+        manifest["packages"]
+            .as_array()?
+            .iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect(),
+    )
+}
+
+/// `members = ["crates/*", "app"]` of a `[workspace]` table
+fn cargo_workspaces(root: &Path) -> Option<Vec<String>> {
+    let manifest = read(root, "Cargo.toml")?;
+    let workspace = manifest.split("[workspace]").nth(1)?;
+    let workspace = workspace.split("\n[").next().unwrap_or(workspace);
+    let members = workspace.split("members").nth(1).and_then(|rest| {
+        let start = rest.find('[')?;
+        let end = rest[start..].find(']')? + start;
+        Some(&rest[start + 1..end])
+    });
+    Some(
+        members
+            .unwrap_or("")
+            .split(',')
+            .map(|m| m.trim().trim_matches('"').to_string())
+            .filter(|m| !m.is_empty())
+            .collect(),
+    )
+}
+
+/// Workspace globs declared at `root`, or `None` if it isn't a monorepo root
+fn workspace_patterns(root: &Path) -> Option<Vec<String>> {
+    let mut found = false;
+    let mut patterns = Vec::new();
+    for source in [
+        pnpm_workspaces,
+        package_json_workspaces,
+        lerna_workspaces,
+        cargo_workspaces,
+    ] {
+        if let Some(list) = source(root) {
+            found = true;
+            patterns.extend(list);
+        }
+    }
+    // Turborepo and Nx rely on the package manager's workspaces; Nx repos
+    // without them keep projects under apps/ and libs/
+    if root.join("nx.json").exists() {
+        found = true;
+        if patterns.is_empty() {
+            patterns.extend(["apps/*".to_string(), "libs/*".to_string()]);
+        }
+    }
+    if root.join("turbo.json").exists() {
+        found = true;
+    }
+    found.then_some(patterns)
+}
+
+/// Nearest directory at or above `start` that is a monorepo root
+pub fn find_workspace_root(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| workspace_patterns(dir).is_some())
+        .map(Path::to_path_buf)
+}
+
+/// Subdirectories that aren't ignored; ignored ones never hold packages
+fn subdirectories(dir: &Path, ignores: &mut ProjectIgnores) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|e| e.path())
+        .filter(|dir| !ignores.is_ignored(dir, true, Purpose::Scan))
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+fn descendants(dir: &Path, depth: usize, ignores: &mut ProjectIgnores, out: &mut Vec<PathBuf>) {
+    out.push(dir.to_path_buf());
+    if depth < MAX_GLOB_DEPTH {
+        for sub in subdirectories(dir, ignores) {
+            descendants(&sub, depth + 1, ignores, out);
+        }
+    }
+}
+
+/// Expand a workspace glob. Only whole-segment `*` and `**` are supported,
+/// which covers what workspace files use in practice.
+fn expand(root: &Path, pattern: &str, ignores: &mut ProjectIgnores) -> Vec<PathBuf> {
+    let mut current = vec![root.to_path_buf()];
+    for segment in pattern.trim_start_matches("./").split('/') {
+        current = match segment {
+            "" | "." => current,
+            "*" => current
+                .iter()
+                .flat_map(|d| subdirectories(d, ignores))
+                .collect(),
+            "**" => {
+                let mut out = Vec::new();
+                for dir in &current {
+                    descendants(dir, 0, ignores, &mut out);
+                }
+                out
+            }
+            literal => current
+                .iter()
+                .map(|d| d.join(literal))
+                .filter(|d| d.is_dir())
+                .collect(),
+        };
+    }
+    current
+}
+
+fn package_name(dir: &Path) -> Option<String> {
+    if let Some(manifest) = read(dir, "package.json") {
+        if let Some(name) = serde_json::from_str::<serde_json::Value>(&manifest)
+            .ok()
+            .and_then(|m| m["name"].as_str().map(str::to_string))
+        {
+            return Some(name);
+        }
+    }
+    let manifest = read(dir, "Cargo.toml")?;
+    let package = manifest.split("[package]").nth(1)?;
+    package.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        (key.trim() == "name").then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+fn relative(root: &Path, dir: &Path) -> Option<String> {
+    let rel = dir.strip_prefix(root).ok()?;
+    let parts: Vec<String> = rel
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+/// Member packages of the monorepo rooted at `root`; empty for a regular
+/// project. Only directories with a manifest count as packages.
+pub fn list_packages(root: &Path) -> Vec<WorkspacePackage> {
+    let Some(patterns) = workspace_patterns(root) else {
+        return Vec::new();
+    };
+    let mut ignores = ProjectIgnores::load(root);
+    let (excluded, included): (Vec<&String>, Vec<&String>) =
+        patterns.iter().partition(|p| p.starts_with('!'));
+    let excluded: Vec<PathBuf> = excluded
+        .iter()
+        .flat_map(|p| expand(root, &p[1..], &mut ignores))
+        .collect();
+    let included: Vec<PathBuf> = included
+        .iter()
+        .flat_map(|p| expand(root, p, &mut ignores))
+        .collect();
+
+    let mut packages: Vec<WorkspacePackage> = Vec::new();
+    for dir in included {
+        if excluded.contains(&dir) || ignores.is_ignored(&dir, true, Purpose::Scan) {
+            continue;
+        }
+        let has_manifest = ["package.json", "Cargo.toml", "pyproject.toml", "go.mod"]
+            .iter()
+            .any(|m| dir.join(m).exists());
+        let Some(path) = relative(root, &dir).filter(|_| has_manifest) else {
+            continue;
+        };
+        if packages.iter().any(|p| p.path == path) {
+            continue;
+        }
+        packages.push(WorkspacePackage {
+            name: package_name(&dir).unwrap_or_else(|| path.clone()),
+            path,
+        });
+    }
+    packages.sort_by(|a, b| a.path.cmp(&b.path));
+    packages
+}
+
+/// Package of `packages` containing `file`, by longest path prefix
+pub fn package_for_path<'a>(
+    project_root: &Path,
+    packages: &'a [WorkspacePackage],
+    file: &Path,
+) -> Option<&'a WorkspacePackage> {
+    let rel = relative(project_root, file)?;
+    packages
+        .iter()
+        .filter(|p| rel == p.path || rel.starts_with(&format!("{}/", p.path)))
+        .max_by_key(|p| p.path.len())
+}
+
+/// Directory of a package, or the project root without one
+pub fn package_dir(project_root: &str, package_path: Option<&str>) -> PathBuf {
+    match package_path {
+        Some(package) => Path::new(project_root).join(package),
+        None => PathBuf::from(project_root),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_pnpm_packages_and_maps_files_to_them() {
+        let root = std::env::temp_dir().join(format!("kk-monorepo-{}", uuid::Uuid::new_v4()));
+        for dir in ["apps/web", "apps/api", "packages/ui", "packages/legacy"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+            fs::write(
+                root.join(dir).join("package.json"),
+                format!(r#"{{"name": "@acme/{}"}}"#, dir.rsplit('/').next().unwrap()),
+            )
+            .unwrap();
+        }
+        fs::write(root.join("package.json"), "{}").unwrap();
+        fs::write(
+            root.join("pnpm-workspace.yaml"),
+            "packages:\n  - 'apps/*'\n  - \"packages/*\"\n  - '!packages/legacy'\n",
+        )
+        .unwrap();
+
+        let packages = list_packages(&root);
+        let paths: Vec<&str> = packages.iter().map(|p| p.path.as_str()).collect();
+        assert_eq!(paths, vec!["apps/api", "apps/web", "packages/ui"]);
+        assert_eq!(packages[1].name, "@acme/web");
+
+        let nested = root.join("apps/web/src");
+        assert_eq!(
+            find_workspace_root(&nested).as_deref(),
+            Some(root.as_path())
+        );
+        let package = package_for_path(&root, &packages, &root.join("apps/web/.env"));
+        assert_eq!(package.map(|p| p.path.as_str()), Some("apps/web"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,489 @@
+//! Key naming policies.
+//!
+//! A policy is either a regex the whole name must match, or a template such
+//! as `<SERVICE>_<ENV>_KEY` where `<SERVICE>` and `<ENV>` are the key's
+//! service and environment in upper snake case. Template names may end in a
+//! numeric suffix (`_2`) to tell apart keys that would otherwise share a
+//! name. Enforced policies reject `add_api_key`/`update_api_key`; the others
+//! only show up in `lint_vault_naming`. Renames suggested by the lint are
+//! applied with `bulk_rename_keys`, which also renames the variable in the
+//! `.env` file a key was imported from.
+
+use crate::errors::KeyKeeperError;
+use crate::{
+    env_file, env_history, guards, log_audit_event, save_vault, ApiKey, ApiKeyVault, AppState,
+};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use tauri::State;
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyKind {
+    Regex,
+    Template,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamingPolicy {
+    pub id: String,
+    pub name: String,
+    pub kind: PolicyKind,
+    pub pattern: String,
+    /// Reject non-matching names instead of only reporting them
+    #[serde(default)]
+    pub enforce: bool,
+    /// Services the policy applies to, case-insensitive; all when empty
+    #[serde(default)]
+    pub services: Vec<String>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamingViolation {
+    pub policy_id: String,
+    pub policy_name: String,
+    pub enforced: bool,
+    pub message: String,
+}
+
+/// `Open AI` → `OPEN_AI`
+pub fn upper_snake(value: &str) -> String {
+    let mut out = String::new();
+    for c in value.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            out.push(c.to_ascii_uppercase());
+        } else if !out.is_empty() && !out.ends_with('_') {
+            out.push('_');
+        }
+    }
+    out.trim_end_matches('_').to_string()
+}
+
+/// Fill `<SERVICE>` and `<ENV>` in a template from the key
+pub fn render_template(template: &str, key: &ApiKey) -> String {
+    template
+        .replace("<SERVICE>", &upper_snake(&key.service))
+        .replace("<ENV>", &upper_snake(&key.environment))
+}
+
+impl NamingPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Policy name can't be empty".to_string());
+        }
+        match self.kind {
+            PolicyKind::Regex => self.regex().map(|_| ()),
+            PolicyKind::Template if self.pattern.trim().is_empty() => {
+                Err("Template can't be empty".to_string())
+            }
+            PolicyKind::Template => Ok(()),
+        }
+    }
+
+    fn regex(&self) -> Result<Regex, String> {
+        Regex::new(&format!("^(?:{})$", self.pattern))
+            .map_err(|e| format!("Invalid regex '{}': {}", self.pattern, e))
+    }
+
+    fn applies_to(&self, key: &ApiKey) -> bool {
+        self.services.is_empty()
+            || self
+                .services
+                .iter()
+                .any(|s| s.trim().eq_ignore_ascii_case(key.service.trim()))
+    }
+
+    fn render(&self, key: &ApiKey) -> String {
+        render_template(&self.pattern, key)
+    }
+
+    fn accepts(&self, name: &str, key: &ApiKey) -> bool {
+        match self.kind {
+            PolicyKind::Regex => self.regex().is_ok_and(|re| re.is_match(name)),
+            PolicyKind::Template => {
+                let expected = self.render(key);
+                name == expected
+                    || name
+                        .strip_prefix(&expected)
+                        .and_then(|rest| rest.strip_prefix('_'))
+                        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+            }
+        }
+    }
+
+    /// Candidate name for a key that doesn't match
+    fn suggest(&self, key: &ApiKey) -> Option<String> {
+        match self.kind {
+            PolicyKind::Template => Some(self.render(key)),
+            PolicyKind::Regex => Some(upper_snake(&key.name)),
+        }
+    }
+}
+
+/// Policies `name` breaks if given to `key`
+pub fn violations(policies: &[NamingPolicy], key: &ApiKey, name: &str) -> Vec<NamingViolation> {
+    policies
+        .iter()
+        .filter(|policy| policy.applies_to(key) && !policy.accepts(name, key))
+        .map(|policy| NamingViolation {
+            policy_id: policy.id.clone(),
+            policy_name: policy.name.clone(),
+            enforced: policy.enforce,
+            message: match policy.kind {
+                PolicyKind::Regex => format!("'{}' doesn't match /{}/", name, policy.pattern),
+                PolicyKind::Template => {
+                    format!("'{}' should be '{}'", name, policy.render(key))
+                }
+            },
+        })
+        .collect()
+}
+
+/// Reject a key whose name breaks an enforced policy
+pub fn enforce(vault: &ApiKeyVault, key: &ApiKey) -> Result<(), KeyKeeperError> {
+    let broken: Vec<String> = violations(&vault.naming_policies, key, &key.name)
+        .into_iter()
+        .filter(|v| v.enforced)
+        .map(|v| format!("{} ({})", v.message, v.policy_name))
+        .collect();
+    if broken.is_empty() {
+        Ok(())
+    } else {
+        Err(KeyKeeperError::Validation(format!(
+            "Key name breaks the naming policy: {}",
+            broken.join("; ")
+        )))
+    }
+}
+
+/// A name that satisfies every policy for the key and isn't in `taken`,
+/// adding a numeric suffix if needed
+fn suggestion(policies: &[NamingPolicy], key: &ApiKey, taken: &HashSet<String>) -> Option<String> {
+    policies
+        .iter()
+        .filter(|policy| policy.applies_to(key))
+        .filter_map(|policy| policy.suggest(key))
+        .filter(|base| !base.is_empty())
+        .flat_map(|base| {
+            std::iter::once(base.clone()).chain((2..100).map(move |n| format!("{}_{}", base, n)))
+        })
+        .find(|name| !taken.contains(name) && violations(policies, key, name).is_empty())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamingLintEntry {
+    pub key_id: String,
+    pub name: String,
+    pub service: String,
+    pub environment: String,
+    pub violations: Vec<NamingViolation>,
+    /// Name that satisfies every policy, when one can be derived
+    pub suggestion: Option<String>,
+}
+
+fn lint(vault: &ApiKeyVault) -> Vec<NamingLintEntry> {
+    let mut keys: Vec<&ApiKey> = vault.keys.values().collect();
+    keys.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+    let mut taken: HashSet<String> = keys.iter().map(|k| k.name.clone()).collect();
+
+    let mut entries = Vec::new();
+    for key in keys {
+        let broken = violations(&vault.naming_policies, key, &key.name);
+        if broken.is_empty() {
+            continue;
+        }
+        let suggestion = suggestion(&vault.naming_policies, key, &taken);
+        if let Some(name) = &suggestion {
+            taken.insert(name.clone());
+        }
+        entries.push(NamingLintEntry {
+            key_id: key.id.clone(),
+            name: key.name.clone(),
+            service: key.service.clone(),
+            environment: key.environment.clone(),
+            violations: broken,
+            suggestion,
+        });
+    }
+    entries
+}
+
+#[tauri::command]
+pub async fn list_naming_policies(
+    state: State<'_, AppState>,
+) -> Result<Vec<NamingPolicy>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    Ok(state.vault.lock().await.naming_policies.clone())
+}
+
+/// Add a policy, or replace the one with the same id
+#[tauri::command]
+pub async fn save_naming_policy(
+    mut policy: NamingPolicy,
+    state: State<'_, AppState>,
+) -> Result<NamingPolicy, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    policy.validate().map_err(KeyKeeperError::Validation)?;
+    if policy.id.is_empty() {
+        policy.id = uuid::Uuid::new_v4().to_string();
+    }
+
+    let mut vault_guard = state.vault.lock().await;
+    match vault_guard
+        .naming_policies
+        .iter_mut()
+        .find(|p| p.id == policy.id)
+    {
+        Some(existing) => *existing = policy.clone(),
+        None => vault_guard.naming_policies.push(policy.clone()),
+    }
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "save_naming_policy",
+        "naming_policy",
+        Some(&policy.id),
+        true,
+        None,
+    )
+    .await;
+    Ok(policy)
+}
+
+#[tauri::command]
+pub async fn delete_naming_policy(
+    policy_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let before = vault_guard.naming_policies.len();
+    vault_guard.naming_policies.retain(|p| p.id != policy_id);
+    if vault_guard.naming_policies.len() == before {
+        return Err(KeyKeeperError::not_found("Naming policy"));
+    }
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "delete_naming_policy",
+        "naming_policy",
+        Some(&policy_id),
+        true,
+        None,
+    )
+    .await;
+    Ok(())
+}
+
+/// Keys whose names break a policy, with suggested renames
+#[tauri::command]
+pub async fn lint_vault_naming(
+    state: State<'_, AppState>,
+) -> Result<Vec<NamingLintEntry>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    Ok(lint(&*state.vault.lock().await))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyRename {
+    pub key_id: String,
+    pub new_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenamedKey {
+    pub key_id: String,
+    pub old_name: String,
+    pub new_name: String,
+    /// `.env` file where the variable was renamed too
+    pub env_file: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BulkRenameResult {
+    pub renamed: Vec<RenamedKey>,
+    /// `.env` files that couldn't be updated; the keys are renamed anyway
+    pub env_file_errors: Vec<String>,
+}
+
+/// Rename several keys at once. Every rename is checked against the enforced
+/// policies before anything changes. With `update_env_files`, keys imported
+/// from a `.env` file also get their variable renamed there; those writes are
+/// recorded in the env file history and can be reverted.
+#[tauri::command]
+pub async fn bulk_rename_keys(
+    renames: Vec<KeyRename>,
+    update_env_files: bool,
+    state: State<'_, AppState>,
+) -> Result<BulkRenameResult, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let mut seen = HashSet::new();
+    for rename in &renames {
+        if !seen.insert(rename.key_id.as_str()) {
+            return Err(KeyKeeperError::Validation(format!(
+                "Key {} is renamed twice",
+                rename.key_id
+            )));
+        }
+        let key = vault_guard
+            .keys
+            .get(&rename.key_id)
+            .ok_or_else(|| KeyKeeperError::not_found("API key"))?;
+        if rename.new_name.trim().is_empty() {
+            return Err(KeyKeeperError::Validation(
+                "Key name can't be empty".to_string(),
+            ));
+        }
+        let mut renamed = key.clone();
+        renamed.name = rename.new_name.trim().to_string();
+        enforce(&vault_guard, &renamed)?;
+    }
+
+    let mut result = BulkRenameResult::default();
+    let mut env_files: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for rename in &renames {
+        let key = &vault_guard.keys[&rename.key_id];
+        let new_name = rename.new_name.trim().to_string();
+        if key.name == new_name {
+            continue;
+        }
+        if update_env_files {
+            if let Some(path) = &key.env_file_path {
+                env_files
+                    .entry(path.clone())
+                    .or_default()
+                    .push(result.renamed.len());
+            }
+        }
+        result.renamed.push(RenamedKey {
+            key_id: key.id.clone(),
+            old_name: key.name.clone(),
+            new_name,
+            env_file: None,
+        });
+    }
+
+    for (path, indexes) in env_files {
+        let project_path = vault_guard.keys[&result.renamed[indexes[0]].key_id]
+            .project_path
+            .clone();
+        let mut pending = match env_file::PendingWrite::open(&path) {
+            Ok(pending) => pending,
+            Err(e) => {
+                result.env_file_errors.push(format!("{}: {}", path, e));
+                continue;
+            }
+        };
+        let mut in_file = Vec::new();
+        for index in indexes {
+            let renamed = &result.renamed[index];
+            if pending.rename(&renamed.old_name, &renamed.new_name) {
+                in_file.push(index);
+            }
+        }
+        match env_history::commit(
+            &pending,
+            &vault_guard,
+            project_path.as_deref(),
+            "bulk_rename_keys",
+        ) {
+            Ok(_) => {
+                for index in in_file {
+                    result.renamed[index].env_file = Some(path.clone());
+                }
+            }
+            Err(e) => {
+                warn!("Failed to rename variables in {}: {}", path, e);
+                result.env_file_errors.push(format!("{}: {}", path, e));
+            }
+        }
+    }
+
+    let now = Utc::now();
+    for renamed in &result.renamed {
+        if let Some(key) = vault_guard.keys.get_mut(&renamed.key_id) {
+            key.name = renamed.new_name.clone();
+            key.updated_at = now;
+        }
+    }
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    for renamed in &result.renamed {
+        log_audit_event(
+            &state,
+            "rename_api_key",
+            "api_key",
+            Some(&renamed.key_id),
+            true,
+            Some(&format!("{} → {}", renamed.old_name, renamed.new_name)),
+        )
+        .await;
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault_tests::test_key;
+
+    #[test]
+    fn template_policy_lints_and_suggests_unique_names() {
+        let mut vault = ApiKeyVault::default();
+        vault.naming_policies.push(NamingPolicy {
+            id: "p1".to_string(),
+            name: "Team convention".to_string(),
+            kind: PolicyKind::Template,
+            pattern: "<SERVICE>_<ENV>_KEY".to_string(),
+            enforce: true,
+            services: Vec::new(),
+            created_at: Utc::now(),
+        });
+        for key in [
+            test_key("a")
+                .name("openai main")
+                .service("Open AI")
+                .environment("production"),
+            test_key("b")
+                .name("openai backup")
+                .service("Open AI")
+                .environment("production"),
+            test_key("c")
+                .name("STRIPE_DEV_KEY")
+                .service("stripe")
+                .environment("dev"),
+        ] {
+            let key = key.secret("secret").build();
+            vault.keys.insert(key.id.clone(), key);
+        }
+
+        assert!(enforce(&vault, &vault.keys["c"]).is_ok());
+        assert!(enforce(&vault, &vault.keys["a"]).is_err());
+
+        let entries = lint(&vault);
+        let suggestions: Vec<_> = entries
+            .iter()
+            .map(|e| (e.key_id.as_str(), e.suggestion.as_deref()))
+            .collect();
+        assert_eq!(
+            suggestions,
+            vec![
+                ("b", Some("OPEN_AI_PRODUCTION_KEY")),
+                ("a", Some("OPEN_AI_PRODUCTION_KEY_2")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,639 @@
+use crate::errors::KeyKeeperError;
+use crate::{access_review, github_sync, guards, rotation, save_vault, ApiKeyVault, AppState};
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+pub const EXPIRY_WARNING_DAYS: i64 = 14;
+const DIGEST_CHECK_INTERVAL_SECS: u64 = 15 * 60;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    ExpiringKeys,
+    DriftDetected,
+    FailedUnlock,
+    SyncError,
+    /// A key reached the end of its rotation interval
+    RotationDue,
+    /// A key's usage crossed one of its quota thresholds
+    QuotaThreshold,
+    /// The access review cycle is past its due date
+    AccessReviewDue,
+    Digest,
+    /// Notifications requested by the frontend without a specific category
+    General,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuietHours {
+    /// Local time, "HH:MM"
+    pub start: String,
+    /// Local time, "HH:MM". May be earlier than `start` to span midnight.
+    pub end: String,
+}
+
+impl QuietHours {
+    fn contains(&self, now: NaiveTime) -> bool {
+        let (Ok(start), Ok(end)) = (
+            NaiveTime::parse_from_str(&self.start, "%H:%M"),
+            NaiveTime::parse_from_str(&self.end, "%H:%M"),
+        ) else {
+            return false;
+        };
+        if start <= end {
+            now >= start && now < end
+        } else {
+            now >= start || now < end
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    pub expiring_keys: bool,
+    pub drift_detected: bool,
+    pub failed_unlock: bool,
+    pub sync_errors: bool,
+    #[serde(default = "enabled")]
+    pub rotation_due: bool,
+    #[serde(default = "enabled")]
+    pub quota_alerts: bool,
+    #[serde(default = "enabled")]
+    pub access_reviews: bool,
+    pub quiet_hours: Option<QuietHours>,
+    pub daily_digest: bool,
+    /// Local time the digest is sent at, "HH:MM"
+    pub digest_time: String,
+    #[serde(default, with = "crate::timestamps::rfc3339_option")]
+    pub last_digest_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "crate::timestamps::rfc3339_option")]
+    pub last_expiry_notice_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "crate::timestamps::rfc3339_option")]
+    pub last_drift_notice_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "crate::timestamps::rfc3339_option")]
+    pub last_access_review_notice_at: Option<DateTime<Utc>>,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            expiring_keys: true,
+            drift_detected: true,
+            failed_unlock: true,
+            sync_errors: true,
+            rotation_due: true,
+            quota_alerts: true,
+            access_reviews: true,
+            quiet_hours: None,
+            daily_digest: false,
+            digest_time: "09:00".to_string(),
+            last_digest_at: None,
+            last_expiry_notice_at: None,
+            last_drift_notice_at: None,
+            last_access_review_notice_at: None,
+        }
+    }
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl NotificationSettings {
+    fn allows(&self, kind: NotificationKind) -> bool {
+        match kind {
+            NotificationKind::ExpiringKeys => self.expiring_keys,
+            NotificationKind::DriftDetected => self.drift_detected,
+            NotificationKind::FailedUnlock => self.failed_unlock,
+            NotificationKind::SyncError => self.sync_errors,
+            NotificationKind::RotationDue => self.rotation_due,
+            NotificationKind::QuotaThreshold => self.quota_alerts,
+            NotificationKind::AccessReviewDue => self.access_reviews,
+            NotificationKind::Digest => self.daily_digest,
+            NotificationKind::General => true,
+        }
+    }
+}
+
+/// Pending issues summarized by the daily digest
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotificationDigest {
+    pub expired_keys: usize,
+    pub expiring_keys: usize,
+    pub drifted_secrets: usize,
+    #[serde(default)]
+    pub overdue_rotations: usize,
+    #[serde(default)]
+    pub access_review_overdue: bool,
+    pub failed_unlocks_24h: usize,
+    pub failed_syncs_24h: usize,
+}
+
+impl NotificationDigest {
+    pub fn is_empty(&self) -> bool {
+        self.expired_keys == 0
+            && self.expiring_keys == 0
+            && self.drifted_secrets == 0
+            && self.overdue_rotations == 0
+            && !self.access_review_overdue
+            && self.failed_unlocks_24h == 0
+            && self.failed_syncs_24h == 0
+    }
+
+    pub fn body(&self) -> String {
+        let mut lines = Vec::new();
+        if self.expired_keys > 0 {
+            lines.push(format!("{} expired keys", self.expired_keys));
+        }
+        if self.expiring_keys > 0 {
+            lines.push(format!(
+                "{} keys expiring within {} days",
+                self.expiring_keys, EXPIRY_WARNING_DAYS
+            ));
+        }
+        if self.drifted_secrets > 0 {
+            lines.push(format!(
+                "{} synced secrets out of date",
+                self.drifted_secrets
+            ));
+        }
+        if self.overdue_rotations > 0 {
+            lines.push(format!(
+                "{} keys overdue for rotation",
+                self.overdue_rotations
+            ));
+        }
+        if self.access_review_overdue {
+            lines.push("Access review overdue".to_string());
+        }
+        if self.failed_unlocks_24h > 0 {
+            lines.push(format!(
+                "{} failed unlock attempts",
+                self.failed_unlocks_24h
+            ));
+        }
+        if self.failed_syncs_24h > 0 {
+            lines.push(format!("{} failed syncs", self.failed_syncs_24h));
+        }
+        if lines.is_empty() {
+            "No pending issues".to_string()
+        } else {
+            lines.join("\n")
+        }
+    }
+}
+
+fn is_sync_action(action: &str) -> bool {
+    action.contains("sync") || action == "push_to_secret_manager"
+}
+
+/// Notification category for an audit event, if it warrants one
+pub fn kind_for_audit(action: &str, success: bool) -> Option<NotificationKind> {
+    if success {
+        return None;
+    }
+    if action == "unlock_vault" {
+        Some(NotificationKind::FailedUnlock)
+    } else if is_sync_action(action) {
+        Some(NotificationKind::SyncError)
+    } else {
+        None
+    }
+}
+
+/// Show the notification an audit event warrants, if any. Called for the
+/// event bus's `audit` events.
+pub async fn notify_for_audit(state: &AppState, action: &str, success: bool, error: Option<&str>) {
+    let Some(kind) = kind_for_audit(action, success) else {
+        return;
+    };
+    let title = match kind {
+        NotificationKind::FailedUnlock => "Failed unlock attempt",
+        _ => "Sync failed",
+    };
+    let body = error.unwrap_or(action);
+    if let Err(e) = state.notifier.notify(&state.vault, kind, title, body).await {
+        warn!("Failed to dispatch notification: {}", e);
+    }
+}
+
+pub fn build_digest(vault: &ApiKeyVault) -> NotificationDigest {
+    let now = Utc::now();
+    let warning_cutoff = now + ChronoDuration::days(EXPIRY_WARNING_DAYS);
+    let day_ago = now - ChronoDuration::hours(24);
+
+    let mut digest = NotificationDigest::default();
+    for key in vault.keys.values().filter(|k| k.is_active) {
+        match key.expires_at {
+            Some(expiry) if expiry <= now => digest.expired_keys += 1,
+            Some(expiry) if expiry <= warning_cutoff => digest.expiring_keys += 1,
+            _ => {}
+        }
+    }
+    digest.drifted_secrets = github_sync::count_local_drift(vault);
+    digest.overdue_rotations = rotation::count_overdue(vault, now);
+    digest.access_review_overdue = access_review::is_overdue(vault, now);
+
+    for log in vault.audit_logs.iter().filter(|l| !l.success) {
+        if log.timestamp < day_ago {
+            continue;
+        }
+        if log.action == "unlock_vault" {
+            digest.failed_unlocks_24h += 1;
+        } else if is_sync_action(&log.action) {
+            digest.failed_syncs_24h += 1;
+        }
+    }
+    digest
+}
+
+/// The user's notification preferences, also kept in the vault metadata so
+/// notifications raised while locked respect them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationPreferences {
+    /// The global `show_notifications` switch
+    pub enabled: bool,
+    pub settings: NotificationSettings,
+}
+
+impl Default for NotificationPreferences {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            settings: NotificationSettings::default(),
+        }
+    }
+}
+
+/// Preferences of the user account, `None` without one or while locked
+pub fn account_preferences(vault: &ApiKeyVault) -> Option<NotificationPreferences> {
+    vault
+        .user_account
+        .as_ref()
+        .map(|account| NotificationPreferences {
+            enabled: account.preferences.show_notifications,
+            settings: account.preferences.notifications.clone(),
+        })
+}
+
+fn current_settings(vault: &ApiKeyVault) -> NotificationPreferences {
+    account_preferences(vault)
+        .or_else(|| vault.locked_notifications.clone())
+        .unwrap_or_default()
+}
+
+fn sent_today(timestamp: &Option<DateTime<Utc>>) -> bool {
+    timestamp.is_some_and(|t| t.with_timezone(&Local).date_naive() == Local::now().date_naive())
+}
+
+/// Single entry point for desktop notifications. Applies the global
+/// `show_notifications` switch, per-event preferences and quiet hours.
+pub struct NotificationDispatcher {
+    app_handle: Option<AppHandle>,
+}
+
+impl NotificationDispatcher {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self {
+            app_handle: Some(app_handle),
+        }
+    }
+
+    /// Dispatcher that drops every notification, for contexts without a window
+    pub fn headless() -> Self {
+        Self { app_handle: None }
+    }
+
+    /// Show a notification if preferences allow it. Returns whether it was shown.
+    pub async fn notify(
+        &self,
+        vault: &Mutex<ApiKeyVault>,
+        kind: NotificationKind,
+        title: &str,
+        body: &str,
+    ) -> Result<bool, String> {
+        let NotificationPreferences { enabled, settings } = {
+            let vault_guard = vault.lock().await;
+            current_settings(&vault_guard)
+        };
+        if !enabled || !settings.allows(kind) {
+            debug!("Notification {:?} suppressed by preferences", kind);
+            return Ok(false);
+        }
+        if let Some(quiet_hours) = &settings.quiet_hours {
+            if quiet_hours.contains(Local::now().time()) {
+                debug!("Notification {:?} suppressed by quiet hours", kind);
+                return Ok(false);
+            }
+        }
+        self.show(title, body)?;
+        Ok(true)
+    }
+
+    fn show(&self, title: &str, body: &str) -> Result<(), String> {
+        use tauri_plugin_notification::NotificationExt;
+
+        let Some(app) = &self.app_handle else {
+            return Ok(());
+        };
+        app.notification()
+            .builder()
+            .title(title)
+            .body(body)
+            .show()
+            .map_err(|e| {
+                warn!("Failed to show notification: {}", e);
+                format!("Failed to show notification: {}", e)
+            })
+    }
+}
+
+/// Periodically send the daily digest and the expiry and drift reminders
+pub async fn run_scheduler(app: AppHandle) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(DIGEST_CHECK_INTERVAL_SECS)).await;
+
+        let state: State<'_, AppState> = app.state();
+        if !*state.is_unlocked.lock().await {
+            continue;
+        }
+        if let Err(e) = run_scheduled_checks(&state).await {
+            warn!("Scheduled notification check failed: {}", e);
+        }
+    }
+}
+
+async fn run_scheduled_checks(state: &State<'_, AppState>) -> Result<(), String> {
+    let (settings, digest) = {
+        let vault_guard = state.vault.lock().await;
+        (
+            current_settings(&vault_guard).settings,
+            build_digest(&vault_guard),
+        )
+    };
+
+    let mut changed = false;
+    let expiring = digest.expired_keys + digest.expiring_keys;
+    if expiring > 0 && !sent_today(&settings.last_expiry_notice_at) {
+        let body = format!(
+            "{} keys are expired or expire within {} days",
+            expiring, EXPIRY_WARNING_DAYS
+        );
+        if state
+            .notifier
+            .notify(
+                &state.vault,
+                NotificationKind::ExpiringKeys,
+                "Keys expiring soon",
+                &body,
+            )
+            .await?
+        {
+            update_settings(state, |s| s.last_expiry_notice_at = Some(Utc::now())).await;
+            changed = true;
+        }
+    }
+
+    if digest.drifted_secrets > 0 && !sent_today(&settings.last_drift_notice_at) {
+        let body = format!(
+            "{} synced secrets no longer match the vault",
+            digest.drifted_secrets
+        );
+        if state
+            .notifier
+            .notify(
+                &state.vault,
+                NotificationKind::DriftDetected,
+                "Secret drift detected",
+                &body,
+            )
+            .await?
+        {
+            update_settings(state, |s| s.last_drift_notice_at = Some(Utc::now())).await;
+            changed = true;
+        }
+    }
+
+    if digest.access_review_overdue
+        && !sent_today(&settings.last_access_review_notice_at)
+        && state
+            .notifier
+            .notify(
+                &state.vault,
+                NotificationKind::AccessReviewDue,
+                "Access review overdue",
+                "Attest which keys are still needed",
+            )
+            .await?
+    {
+        update_settings(state, |s| s.last_access_review_notice_at = Some(Utc::now())).await;
+        changed = true;
+    }
+
+    let digest_due = NaiveTime::parse_from_str(&settings.digest_time, "%H:%M")
+        .is_ok_and(|t| Local::now().time() >= t);
+    if settings.daily_digest
+        && digest_due
+        && !digest.is_empty()
+        && !sent_today(&settings.last_digest_at)
+        && state
+            .notifier
+            .notify(
+                &state.vault,
+                NotificationKind::Digest,
+                "KeyKeeper daily digest",
+                &digest.body(),
+            )
+            .await?
+    {
+        info!("Daily digest sent");
+        update_settings(state, |s| s.last_digest_at = Some(Utc::now())).await;
+        changed = true;
+    }
+
+    if changed {
+        save_vault(state).await.map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+async fn update_settings(state: &State<'_, AppState>, f: impl FnOnce(&mut NotificationSettings)) {
+    let mut vault_guard = state.vault.lock().await;
+    if let Some(account) = vault_guard.user_account.as_mut() {
+        f(&mut account.preferences.notifications);
+    }
+}
+
+#[tauri::command]
+pub async fn get_notification_settings(
+    state: State<'_, AppState>,
+) -> Result<NotificationSettings, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    Ok(current_settings(&vault_guard).settings)
+}
+
+#[tauri::command]
+pub async fn update_notification_settings(
+    settings: NotificationSettings,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    NaiveTime::parse_from_str(&settings.digest_time, "%H:%M").map_err(|_| {
+        KeyKeeperError::Validation("Digest time must be in HH:MM format".to_string())
+    })?;
+    if let Some(quiet_hours) = &settings.quiet_hours {
+        for time in [&quiet_hours.start, &quiet_hours.end] {
+            NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| {
+                KeyKeeperError::Validation("Quiet hours must be in HH:MM format".to_string())
+            })?;
+        }
+    }
+
+    let mut vault_guard = state.vault.lock().await;
+    let account = vault_guard
+        .user_account
+        .as_mut()
+        .ok_or_else(|| KeyKeeperError::not_found("User account"))?;
+    // Bookkeeping fields are owned by the scheduler
+    let previous = std::mem::replace(&mut account.preferences.notifications, settings);
+    account.preferences.notifications.last_digest_at = previous.last_digest_at;
+    account.preferences.notifications.last_expiry_notice_at = previous.last_expiry_notice_at;
+    account.preferences.notifications.last_drift_notice_at = previous.last_drift_notice_at;
+    account.updated_at = Utc::now();
+    drop(vault_guard);
+
+    save_vault(&state).await
+}
+
+/// Compute the digest, and show it immediately when `send` is true
+#[tauri::command]
+pub async fn get_notification_digest(
+    send: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<NotificationDigest, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let digest = {
+        let vault_guard = state.vault.lock().await;
+        build_digest(&vault_guard)
+    };
+    if send.unwrap_or(false) {
+        state
+            .notifier
+            .notify(
+                &state.vault,
+                NotificationKind::General,
+                "KeyKeeper digest",
+                &digest.body(),
+            )
+            .await?;
+    }
+    Ok(digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(time, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn quiet_hours_may_span_midnight() {
+        let night = QuietHours {
+            start: "22:00".to_string(),
+            end: "07:00".to_string(),
+        };
+        assert!(night.contains(at("23:30")));
+        assert!(night.contains(at("06:59")));
+        assert!(!night.contains(at("07:00")));
+        assert!(!night.contains(at("12:00")));
+
+        let lunch = QuietHours {
+            start: "12:00".to_string(),
+            end: "13:00".to_string(),
+        };
+        assert!(lunch.contains(at("12:30")));
+        assert!(!lunch.contains(at("13:30")));
+
+        let malformed = QuietHours {
+            start: "noon".to_string(),
+            end: "13:00".to_string(),
+        };
+        assert!(!malformed.contains(at("12:30")));
+    }
+
+    #[test]
+    fn only_failed_unlocks_and_syncs_warrant_a_notification() {
+        assert_eq!(
+            kind_for_audit("unlock_vault", false),
+            Some(NotificationKind::FailedUnlock)
+        );
+        assert_eq!(
+            kind_for_audit("sync_keys_to_github", false),
+            Some(NotificationKind::SyncError)
+        );
+        assert_eq!(
+            kind_for_audit("push_to_secret_manager", false),
+            Some(NotificationKind::SyncError)
+        );
+        assert_eq!(kind_for_audit("unlock_vault", true), None);
+        assert_eq!(kind_for_audit("add_api_key", false), None);
+    }
+
+    #[tokio::test]
+    async fn locked_vault_respects_the_preferences_in_its_metadata() {
+        let dispatcher = NotificationDispatcher::headless();
+        let vault = Mutex::new(ApiKeyVault::default());
+        let shown = dispatcher
+            .notify(&vault, NotificationKind::FailedUnlock, "Failed unlock", "")
+            .await;
+        assert_eq!(shown, Ok(true));
+
+        let mut preferences = NotificationPreferences::default();
+        preferences.settings.failed_unlock = false;
+        vault.lock().await.locked_notifications = Some(preferences.clone());
+        let shown = dispatcher
+            .notify(&vault, NotificationKind::FailedUnlock, "Failed unlock", "")
+            .await;
+        assert_eq!(shown, Ok(false));
+
+        preferences.settings.failed_unlock = true;
+        preferences.enabled = false;
+        vault.lock().await.locked_notifications = Some(preferences);
+        let shown = dispatcher
+            .notify(&vault, NotificationKind::FailedUnlock, "Failed unlock", "")
+            .await;
+        assert_eq!(shown, Ok(false));
+    }
+
+    #[test]
+    fn digest_counts_the_last_days_failures() {
+        let mut vault = ApiKeyVault::default();
+        for action in ["unlock_vault", "unlock_vault", "sync_keys_to_github"] {
+            vault
+                .audit_logs
+                .push(crate::create_audit_log(action, "vault", None, false, None));
+        }
+        let mut old = crate::create_audit_log("unlock_vault", "vault", None, false, None);
+        old.timestamp = Utc::now() - ChronoDuration::days(2);
+        vault.audit_logs.push(old);
+
+        let digest = build_digest(&vault);
+        assert_eq!(digest.failed_unlocks_24h, 2);
+        assert_eq!(digest.failed_syncs_24h, 1);
+        assert!(!digest.is_empty());
+        assert_eq!(digest.body(), "2 failed unlock attempts\n1 failed syncs");
+        assert_eq!(
+            build_digest(&ApiKeyVault::default()).body(),
+            "No pending issues"
+        );
+    }
+}
@@ -0,0 +1,524 @@
+//! OAuth2 client credentials.
+//!
+//! An OAuth client is a vault key of kind `oauth_client`: `key` holds the
+//! client secret, `oauth` the client id and token endpoint, and `scopes` the
+//! scopes to request. `get_access_token` (and `POST /api/oauth/{id}/token`)
+//! runs the client-credentials flow and hands out the bearer token, which is
+//! cached in memory until shortly before it expires. Expired tokens are
+//! renewed with their refresh token when the server issued one, otherwise
+//! by asking for a new one. Tokens are never saved and are dropped when the
+//! vault locks.
+
+use crate::credentials::CredentialKind;
+use crate::errors::KeyKeeperError;
+use crate::masking::MaskedApiKey;
+use crate::{guards, log_audit_event, save_vault, ApiKey, ApiKeyVault, AppState};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::State;
+use tokio::sync::Mutex as AsyncMutex;
+use tracing::{info, warn};
+use zeroize::Zeroizing;
+
+/// Tokens are renewed this long before they expire
+const EXPIRY_MARGIN_SECS: i64 = 60;
+/// Lifetime assumed when the server doesn't send `expires_in`
+const DEFAULT_TOKEN_TTL_SECS: i64 = 300;
+const REQUEST_TIMEOUT_SECS: u64 = 15;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientAuthMethod {
+    /// HTTP Basic with the client id and secret
+    #[default]
+    ClientSecretBasic,
+    /// `client_id` and `client_secret` in the form body
+    ClientSecretPost,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthClientConfig {
+    pub client_id: String,
+    pub token_url: String,
+    /// Sent as `audience`, as Auth0 and similar servers require
+    #[serde(default)]
+    pub audience: Option<String>,
+    #[serde(default)]
+    pub auth_method: ClientAuthMethod,
+}
+
+impl OAuthClientConfig {
+    pub fn validate(&self) -> Result<(), KeyKeeperError> {
+        if self.client_id.trim().is_empty() {
+            return Err(KeyKeeperError::Validation(
+                "Client id is required".to_string(),
+            ));
+        }
+        let url = url::Url::parse(&self.token_url)
+            .map_err(|e| KeyKeeperError::Validation(format!("Invalid token URL: {}", e)))?;
+        let local = matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"));
+        if url.scheme() != "https" && !(url.scheme() == "http" && local) {
+            return Err(KeyKeeperError::Validation(
+                "The token URL must use https".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// What callers get back; `expires_at` is when the server says it expires
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessToken {
+    pub access_token: String,
+    pub token_type: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub expires_at: DateTime<Utc>,
+    pub scope: Option<String>,
+    /// Served from the cache rather than fetched for this call
+    pub cached: bool,
+}
+
+struct CachedToken {
+    access_token: Zeroizing<String>,
+    token_type: String,
+    expires_at: DateTime<Utc>,
+    scope: Option<String>,
+    refresh_token: Option<Zeroizing<String>>,
+    /// Hash of the client settings the token was issued for; a change
+    /// (e.g. a rotated secret) makes it stale
+    issued_for: String,
+}
+
+impl CachedToken {
+    fn to_access_token(&self, cached: bool) -> AccessToken {
+        AccessToken {
+            access_token: self.access_token.to_string(),
+            token_type: self.token_type.clone(),
+            expires_at: self.expires_at,
+            scope: self.scope.clone(),
+            cached,
+        }
+    }
+}
+
+/// In-memory tokens per OAuth client key
+#[derive(Default)]
+pub struct TokenCache {
+    tokens: Mutex<HashMap<String, CachedToken>>,
+    /// One fetch at a time per client, so concurrent callers share a token
+    fetches: Mutex<HashMap<String, Arc<AsyncMutex<()>>>>,
+}
+
+impl TokenCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget every token; called when the vault locks
+    pub fn clear(&self) {
+        self.tokens.lock().unwrap().clear();
+    }
+
+    pub fn forget(&self, key_id: &str) {
+        self.tokens.lock().unwrap().remove(key_id);
+    }
+
+    fn fetch_lock(&self, key_id: &str) -> Arc<AsyncMutex<()>> {
+        self.fetches
+            .lock()
+            .unwrap()
+            .entry(key_id.to_string())
+            .or_default()
+            .clone()
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    token_type: Option<String>,
+    #[serde(default)]
+    expires_in: Option<i64>,
+    #[serde(default)]
+    scope: Option<String>,
+    #[serde(default)]
+    refresh_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenError {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+/// Everything needed to ask for a token, copied out of the vault
+struct ClientRequest {
+    config: OAuthClientConfig,
+    secret: Zeroizing<String>,
+    scopes: Vec<String>,
+    fingerprint: String,
+}
+
+fn client_request(key: &ApiKey) -> Result<ClientRequest, KeyKeeperError> {
+    let config = match (&key.kind, &key.oauth) {
+        (CredentialKind::OauthClient, Some(config)) => config.clone(),
+        _ => {
+            return Err(KeyKeeperError::Validation(format!(
+                "{} is not an OAuth client",
+                key.name
+            )))
+        }
+    };
+    let mut hasher = Sha256::new();
+    for part in [
+        &config.client_id,
+        &config.token_url,
+        config.audience.as_deref().unwrap_or(""),
+        &key.key,
+        &key.scopes.join(" "),
+    ] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    Ok(ClientRequest {
+        fingerprint: format!("{:x}", hasher.finalize()),
+        secret: Zeroizing::new(key.key.clone()),
+        scopes: key.scopes.clone(),
+        config,
+    })
+}
+
+async fn request_token(
+    client: &ClientRequest,
+    grant: &[(&str, &str)],
+) -> Result<CachedToken, KeyKeeperError> {
+    let http = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| KeyKeeperError::Internal(e.to_string()))?;
+
+    let scope = client.scopes.join(" ");
+    let mut form: Vec<(&str, &str)> = grant.to_vec();
+    if !scope.is_empty() {
+        form.push(("scope", &scope));
+    }
+    if let Some(audience) = &client.config.audience {
+        form.push(("audience", audience));
+    }
+    let mut request = http.post(&client.config.token_url);
+    match client.config.auth_method {
+        ClientAuthMethod::ClientSecretBasic => {
+            request = request.basic_auth(&client.config.client_id, Some(client.secret.as_str()));
+        }
+        ClientAuthMethod::ClientSecretPost => {
+            form.push(("client_id", &client.config.client_id));
+            form.push(("client_secret", client.secret.as_str()));
+        }
+    }
+
+    let response = request
+        .header("Accept", "application/json")
+        .form(&form)
+        .send()
+        .await
+        .map_err(|e| KeyKeeperError::Io(format!("Token request failed: {}", e)))?;
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| KeyKeeperError::Io(format!("Token request failed: {}", e)))?;
+
+    if !status.is_success() {
+        let message = match serde_json::from_str::<TokenError>(&body) {
+            Ok(error) => match error.error_description {
+                Some(description) => format!("{}: {}", error.error, description),
+                None => error.error,
+            },
+            Err(_) => format!("HTTP {}", status),
+        };
+        return Err(if status.is_client_error() {
+            KeyKeeperError::Denied(format!("Token endpoint refused the request: {}", message))
+        } else {
+            KeyKeeperError::Io(format!("Token endpoint error: {}", message))
+        });
+    }
+
+    let token: TokenResponse = serde_json::from_str(&body)
+        .map_err(|e| KeyKeeperError::Io(format!("Invalid token response: {}", e)))?;
+    let lifetime = token
+        .expires_in
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_TOKEN_TTL_SECS);
+    Ok(CachedToken {
+        access_token: Zeroizing::new(token.access_token),
+        token_type: token.token_type.unwrap_or_else(|| "Bearer".to_string()),
+        expires_at: Utc::now() + Duration::seconds(lifetime),
+        scope: token.scope,
+        refresh_token: token.refresh_token.map(Zeroizing::new),
+        issued_for: client.fingerprint.clone(),
+    })
+}
+
+/// A live token for `key_id`, from the cache when it is still good for a
+/// while, otherwise refreshed or fetched
+pub async fn access_token(
+    cache: &TokenCache,
+    vault: &AsyncMutex<ApiKeyVault>,
+    key_id: &str,
+    force_refresh: bool,
+) -> Result<AccessToken, KeyKeeperError> {
+    let client = {
+        let vault_guard = vault.lock().await;
+        let key = vault_guard
+            .keys
+            .get(key_id)
+            .ok_or_else(|| KeyKeeperError::not_found("OAuth client"))?;
+        if !key.is_active {
+            return Err(KeyKeeperError::Validation(format!(
+                "{} is disabled",
+                key.name
+            )));
+        }
+        client_request(key)?
+    };
+
+    let fetch_lock = cache.fetch_lock(key_id);
+    let _fetching = fetch_lock.lock().await;
+
+    let refresh_token = {
+        let tokens = cache.tokens.lock().unwrap();
+        let current = tokens
+            .get(key_id)
+            .filter(|token| token.issued_for == client.fingerprint);
+        if let Some(token) = current {
+            let fresh = token.expires_at - Duration::seconds(EXPIRY_MARGIN_SECS) > Utc::now();
+            if fresh && !force_refresh {
+                return Ok(token.to_access_token(true));
+            }
+        }
+        current.and_then(|token| token.refresh_token.clone())
+    };
+
+    let refreshed = match &refresh_token {
+        Some(refresh_token) => request_token(
+            &client,
+            &[
+                ("grant_type", "refresh_token"),
+                ("refresh_token", refresh_token.as_str()),
+            ],
+        )
+        .await
+        .inspect_err(|e| warn!("Refreshing the token of {} failed: {}", key_id, e))
+        .ok(),
+        None => None,
+    };
+    let mut token = match refreshed {
+        Some(token) => token,
+        None => request_token(&client, &[("grant_type", "client_credentials")]).await?,
+    };
+    // Servers may keep the refresh token the same without sending it again
+    if token.refresh_token.is_none() {
+        token.refresh_token = refresh_token;
+    }
+
+    let access_token = token.to_access_token(false);
+    cache
+        .tokens
+        .lock()
+        .unwrap()
+        .insert(key_id.to_string(), token);
+    info!("Fetched an access token for {}", key_id);
+    Ok(access_token)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AddOAuthClientRequest {
+    pub name: String,
+    pub service: String,
+    pub client_secret: String,
+    #[serde(flatten)]
+    pub config: OAuthClientConfig,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    pub environment: Option<String>,
+    pub description: Option<String>,
+    pub project_path: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[tauri::command]
+pub async fn add_oauth_client(
+    request: AddOAuthClientRequest,
+    state: State<'_, AppState>,
+) -> Result<MaskedApiKey, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    request.config.validate()?;
+    if request.client_secret.is_empty() {
+        return Err(KeyKeeperError::Validation(
+            "Client secret is required".to_string(),
+        ));
+    }
+
+    let now = Utc::now();
+    let key = ApiKey {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: request.name,
+        service: request.service,
+        key: request.client_secret,
+        description: request.description,
+        environment: request
+            .environment
+            .unwrap_or_else(|| "development".to_string()),
+        quota: None,
+        cost: None,
+        expires_at: None,
+        scopes: request.scopes,
+        created_at: now,
+        updated_at: now,
+        tags: request.tags,
+        is_active: true,
+        source_type: Some("manual".to_string()),
+        env_file_path: None,
+        project_path: request.project_path,
+        env_file_name: None,
+        remote_secrets: Vec::new(),
+        package_path: None,
+        rotation_interval_days: None,
+        last_rotated_at: None,
+        kind: CredentialKind::OauthClient,
+        credential: None,
+        oauth: Some(request.config),
+        values: Default::default(),
+        is_pinned: false,
+        archived_at: None,
+        last_used_at: None,
+        backend: None,
+    };
+
+    let mut vault_guard = state.vault.lock().await;
+    crate::naming::enforce(&vault_guard, &key)?;
+    vault_guard.keys.insert(key.id.clone(), key.clone());
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "add_oauth_client",
+        "api_key",
+        Some(&key.id),
+        true,
+        None,
+    )
+    .await;
+    Ok(key.into())
+}
+
+/// Change the client id, token endpoint or audience of an OAuth client
+#[tauri::command]
+pub async fn update_oauth_client(
+    key_id: String,
+    config: OAuthClientConfig,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    config.validate()?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let key = vault_guard
+        .keys
+        .get_mut(&key_id)
+        .filter(|key| key.kind == CredentialKind::OauthClient)
+        .ok_or_else(|| KeyKeeperError::not_found("OAuth client"))?;
+    key.oauth = Some(config);
+    key.updated_at = Utc::now();
+    drop(vault_guard);
+    state.oauth_tokens.forget(&key_id);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "update_oauth_client",
+        "api_key",
+        Some(&key_id),
+        true,
+        None,
+    )
+    .await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_access_token(
+    credential_id: String,
+    force_refresh: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<AccessToken, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let result = access_token(
+        &state.oauth_tokens,
+        &state.vault,
+        &credential_id,
+        force_refresh.unwrap_or(false),
+    )
+    .await;
+    // Cache hits aren't worth an audit entry each
+    match &result {
+        Ok(token) if token.cached => {}
+        Ok(_) => {
+            log_audit_event(
+                &state,
+                "get_access_token",
+                "api_key",
+                Some(&credential_id),
+                true,
+                None,
+            )
+            .await
+        }
+        Err(e) => {
+            log_audit_event(
+                &state,
+                "get_access_token",
+                "api_key",
+                Some(&credential_id),
+                false,
+                Some(&e.to_string()),
+            )
+            .await
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(token_url: &str) -> OAuthClientConfig {
+        OAuthClientConfig {
+            client_id: "client".to_string(),
+            token_url: token_url.to_string(),
+            audience: None,
+            auth_method: ClientAuthMethod::default(),
+        }
+    }
+
+    #[test]
+    fn token_url_must_be_https_except_on_localhost() {
+        assert!(client("https://auth.example.com/oauth/token")
+            .validate()
+            .is_ok());
+        assert!(client("http://localhost:8080/token").validate().is_ok());
+        assert!(client("http://auth.example.com/oauth/token")
+            .validate()
+            .is_err());
+        assert!(client("not a url").validate().is_err());
+    }
+}
@@ -0,0 +1,198 @@
+use crate::errors::KeyKeeperError;
+use crate::masking::MaskedApiKey;
+use crate::vault_index::KeySort;
+use crate::{archive, guards, pinning, ApiKey, AppState, AuditLog, Documentation};
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+pub const MAX_PAGE_SIZE: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// 1-based page number
+    pub page: usize,
+    pub page_size: usize,
+    pub total: usize,
+    pub total_pages: usize,
+    pub has_more: bool,
+}
+
+/// Clamp `page`/`page_size` and return `(page, page_size, offset)`
+pub fn normalize_page(page: Option<usize>, page_size: Option<usize>) -> (usize, usize, usize) {
+    let page = page.unwrap_or(1).max(1);
+    let page_size = page_size
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    (page, page_size, (page - 1) * page_size)
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, page: usize, page_size: usize, total: usize) -> Self {
+        let total_pages = total.div_ceil(page_size);
+        Self {
+            items,
+            page,
+            page_size,
+            total,
+            total_pages,
+            has_more: page < total_pages,
+        }
+    }
+}
+
+/// Page of items for the HTTP API, addressed by an opaque cursor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CursorPage<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    pub total: usize,
+}
+
+impl<T> CursorPage<T> {
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> CursorPage<U> {
+        CursorPage {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+            total: self.total,
+        }
+    }
+}
+
+/// Cursors encode the last returned key's sort position, so inserts and
+/// deletes between requests don't shift or repeat items.
+pub fn encode_cursor(key: &ApiKey) -> String {
+    general_purpose::URL_SAFE_NO_PAD.encode(format!("{}\u{0}{}", key.name.to_lowercase(), key.id))
+}
+
+fn decode_cursor(cursor: &str) -> Option<(String, String)> {
+    let bytes = general_purpose::URL_SAFE_NO_PAD.decode(cursor).ok()?;
+    let decoded = String::from_utf8(bytes).ok()?;
+    let (name, id) = decoded.split_once('\u{0}')?;
+    Some((name.to_string(), id.to_string()))
+}
+
+/// Keys ordered by name, starting after `cursor`
+pub fn keys_after_cursor(
+    keys: Vec<ApiKey>,
+    cursor: Option<&str>,
+    limit: usize,
+) -> Result<CursorPage<ApiKey>, KeyKeeperError> {
+    let mut keys = keys;
+    keys.sort_by(|a, b| KeySort::NameAsc.compare(a, b));
+    let total = keys.len();
+
+    let start = match cursor {
+        Some(cursor) => {
+            let (name, id) = decode_cursor(cursor)
+                .ok_or_else(|| KeyKeeperError::Validation("Invalid cursor".to_string()))?;
+            keys.partition_point(|k| {
+                (k.name.to_lowercase(), k.id.clone()) <= (name.clone(), id.clone())
+            })
+        }
+        None => 0,
+    };
+    let limit = limit.clamp(1, MAX_PAGE_SIZE);
+    let items: Vec<ApiKey> = keys.into_iter().skip(start).take(limit).collect();
+    let next_cursor = if start + items.len() < total {
+        items.last().map(encode_cursor)
+    } else {
+        None
+    };
+
+    Ok(CursorPage {
+        items,
+        next_cursor,
+        total,
+    })
+}
+
+/// Paginated alternative to `get_api_keys`. Without `sort`, the user's
+/// ordering preferences apply, pinned keys first.
+#[tauri::command]
+pub async fn list_api_keys(
+    page: Option<usize>,
+    page_size: Option<usize>,
+    sort: Option<KeySort>,
+    state: State<'_, AppState>,
+) -> Result<Page<MaskedApiKey>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let (page, page_size, offset) = normalize_page(page, page_size);
+    let vault_guard = state.vault.lock().await;
+    let mut index = state.vault_index.lock().await;
+    let mut ids = match sort {
+        Some(sort) => index.sorted_ids(&vault_guard, sort).to_vec(),
+        None => {
+            let sort = pinning::ordering(&vault_guard).sort;
+            pinning::pinned_ids_first(&vault_guard, index.sorted_ids(&vault_guard, sort))
+        }
+    };
+    ids.retain(|id| {
+        vault_guard
+            .keys
+            .get(id)
+            .is_some_and(|key| !archive::is_archived(key))
+    });
+    let items = ids
+        .iter()
+        .skip(offset)
+        .take(page_size)
+        .filter_map(|id| vault_guard.keys.get(id))
+        .map(MaskedApiKey::from)
+        .collect();
+
+    Ok(Page::new(items, page, page_size, ids.len()))
+}
+
+/// Paginated alternative to `get_documentation`, most recently updated first
+#[tauri::command]
+pub async fn list_documentation(
+    page: Option<usize>,
+    page_size: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Page<Documentation>, KeyKeeperError> {
+    let (page, page_size, offset) = normalize_page(page, page_size);
+    let docs_store_guard = state.docs_store.lock().await;
+    let mut docs = docs_store_guard.get_all_docs();
+    docs.sort_by(|a, b| {
+        b.updated_at
+            .cmp(&a.updated_at)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    let total = docs.len();
+    let items = docs
+        .into_iter()
+        .skip(offset)
+        .take(page_size)
+        .cloned()
+        .collect();
+
+    Ok(Page::new(items, page, page_size, total))
+}
+
+/// Paginated alternative to `get_audit_logs`, newest first
+#[tauri::command]
+pub async fn list_audit_logs(
+    page: Option<usize>,
+    page_size: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Page<AuditLog>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let (page, page_size, offset) = normalize_page(page, page_size);
+    let vault_guard = state.vault.lock().await;
+    let total = vault_guard.audit_logs.len();
+    let items = vault_guard
+        .audit_logs
+        .iter()
+        .rev()
+        .skip(offset)
+        .take(page_size)
+        .cloned()
+        .collect();
+
+    Ok(Page::new(items, page, page_size, total))
+}
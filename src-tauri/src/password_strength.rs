@@ -0,0 +1,544 @@
+//! Strength of the master and account passwords.
+//!
+//! `set_master_password` used to take any password and `create_user_account`
+//! only checked for 8 characters. New passwords are now scored the way
+//! zxcvbn does it, more or less: the password is split into the cheapest
+//! patterns an attacker would try (common passwords and words, possibly in
+//! leetspeak, keyboard walks, sequences, repeats, the user's own email) and
+//! each part costs the bits needed to guess it; whatever is left is brute
+//! force over its character classes. The `[password_policy]` table of the
+//! config sets the minimum entropy and length, and whether passwords found in
+//! breaches are refused: the built-in list of the most common ones, plus an
+//! optional file with one password per line. Every path that sets a new
+//! master or account password checks it; `estimate_password_strength` gives
+//! the UI the same feedback while typing.
+
+use crate::errors::KeyKeeperError;
+use crate::{ApiKeyVault, AppState};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use tauri::State;
+use tracing::warn;
+
+/// Most common passwords of public breach corpora, most common first,
+/// separated by whitespace
+const COMMON_PASSWORDS: &str = "\
+    123456 password 12345678 qwerty 123456789 12345 1234 111111 1234567 dragon \
+    123123 baseball abc123 football monkey letmein 696969 shadow master 666666 \
+    qwertyuiop 123321 mustang 1234567890 michael 654321 superman 1qaz2wsx 7777777 \
+    121212 000000 qazwsx 123qwe killer trustno1 jordan jennifer zxcvbnm asdfgh \
+    hunter buster soccer harley batman andrew tigger sunshine iloveyou 2000 charlie \
+    robert thomas hockey ranger daniel starwars klaster 112233 george computer \
+    michelle jessica pepper 1111 zxcvbn 555555 11111111 131313 freedom 777777 pass \
+    maggie 159753 aaaaaa ginger princess joshua cheese amanda summer love ashley \
+    nicole chelsea biteme matthew access yankees 987654321 dallas austin thunder \
+    taylor matrix admin welcome login passw0rd password1 qwerty123 1q2w3e4r secret \
+    changeme";
+
+/// Words people build passwords from, on top of the common passwords
+const COMMON_WORDS: &str = "\
+    keykeeper vault secret secure security master key keys api token admin root user \
+    login welcome hello spring summer autumn winter monday friday january december \
+    dragon monkey shadow sunshine princess football baseball soccer love money \
+    family office company google apple github stripe openai";
+
+const KEYBOARD_ROWS: &[&str] = &[
+    "`1234567890-=",
+    "qwertyuiop[]\\",
+    "asdfghjkl;'",
+    "zxcvbnm,./",
+    "1qaz2wsx3edc4rfv5tgb6yhn7ujm8ik9ol0p",
+];
+
+/// Guesses per second against the vault's key derivation, offline
+const GUESSES_PER_SECOND: f64 = 1e4;
+
+/// `[password_policy]` in `keykeeper.toml`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    /// Estimated bits an attacker has to guess
+    pub min_entropy_bits: u32,
+    /// Refuse passwords found in breaches
+    pub check_breached: bool,
+    /// Extra breached passwords, one per line, e.g. a downloaded list
+    pub breach_list: Option<PathBuf>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            min_entropy_bits: 40,
+            check_breached: true,
+            breach_list: None,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_length == 0 {
+            return Err("password_policy.min_length can't be 0".to_string());
+        }
+        if self.min_entropy_bits > 128 {
+            return Err("password_policy.min_entropy_bits can't exceed 128".to_string());
+        }
+        if let Some(path) = &self.breach_list {
+            if !path.is_absolute() {
+                return Err("password_policy.breach_list must be an absolute path".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Estimate with feedback, and how it fares against the policy
+#[derive(Debug, Clone, Serialize)]
+pub struct PasswordStrength {
+    /// 0 (trivial) to 4 (strong), as zxcvbn scores
+    pub score: u8,
+    pub entropy_bits: f64,
+    /// Time to guess it offline, e.g. "3 days"
+    pub crack_time: String,
+    /// The weakest pattern found
+    pub warning: Option<String>,
+    pub suggestions: Vec<String>,
+    pub breached: bool,
+    pub meets_policy: bool,
+    /// Rules of the policy it breaks, as sentences
+    pub violations: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Pattern {
+    Dictionary,
+    UserInput,
+    Keyboard,
+    Sequence,
+    Repeat,
+}
+
+impl Pattern {
+    fn warning(self) -> &'static str {
+        match self {
+            Pattern::Dictionary => "Contains a common password or word",
+            Pattern::UserInput => "Contains your email or name",
+            Pattern::Keyboard => "Contains a keyboard pattern like \"qwerty\"",
+            Pattern::Sequence => "Contains a sequence like \"abc\" or \"123\"",
+            Pattern::Repeat => "Contains repeated characters like \"aaa\"",
+        }
+    }
+}
+
+struct Match {
+    start: usize,
+    end: usize,
+    pattern: Pattern,
+    /// Bits to guess the whole match
+    bits: f64,
+}
+
+/// Undo common leetspeak, so "p@ssw0rd" is found as "password"
+fn unleet(c: char) -> char {
+    match c {
+        '4' | '@' => 'a',
+        '3' => 'e',
+        '1' | '!' | '|' => 'i',
+        '0' => 'o',
+        '$' | '5' => 's',
+        '7' | '+' => 't',
+        _ => c,
+    }
+}
+
+fn char_pool(chars: &[char]) -> f64 {
+    let mut pool = 0;
+    if chars.iter().any(|c| c.is_ascii_lowercase()) {
+        pool += 26;
+    }
+    if chars.iter().any(|c| c.is_ascii_uppercase()) {
+        pool += 26;
+    }
+    if chars.iter().any(|c| c.is_ascii_digit()) {
+        pool += 10;
+    }
+    if chars.iter().any(|c| c.is_ascii_punctuation() || *c == ' ') {
+        pool += 33;
+    }
+    if chars.iter().any(|c| !c.is_ascii()) {
+        pool += 100;
+    }
+    pool.max(10) as f64
+}
+
+fn dictionary_matches(chars: &[char], user_inputs: &[String], matches: &mut Vec<Match>) {
+    let lower: Vec<char> = chars.iter().map(|c| c.to_ascii_lowercase()).collect();
+    let unleeted: Vec<char> = lower.iter().map(|&c| unleet(c)).collect();
+    let words = COMMON_PASSWORDS
+        .split_whitespace()
+        .enumerate()
+        .map(|(rank, word)| (word.to_string(), rank + 1, Pattern::Dictionary))
+        .chain(
+            COMMON_WORDS
+                .split_whitespace()
+                .enumerate()
+                .map(|(rank, word)| (word.to_string(), rank + 1, Pattern::Dictionary)),
+        )
+        .chain(
+            user_inputs
+                .iter()
+                .map(|input| (input.to_lowercase(), 1, Pattern::UserInput)),
+        );
+    for (word, rank, pattern) in words {
+        let word: Vec<char> = word.chars().collect();
+        if word.len() < 3 || word.len() > chars.len() {
+            continue;
+        }
+        for start in 0..=chars.len() - word.len() {
+            let end = start + word.len();
+            let plain = lower[start..end] == word[..];
+            if !plain && unleeted[start..end] != word[..] {
+                continue;
+            }
+            let mut bits = (rank as f64).log2().max(1.0);
+            if chars[start..end].iter().any(|c| c.is_ascii_uppercase()) {
+                bits += 1.0;
+            }
+            if !plain {
+                bits += 1.0;
+            }
+            matches.push(Match {
+                start,
+                end,
+                pattern,
+                bits,
+            });
+        }
+    }
+}
+
+/// Runs of 3+ characters that step by the same amount, on the alphabet or on
+/// a keyboard row
+fn run_matches(chars: &[char], matches: &mut Vec<Match>) {
+    let lower: Vec<char> = chars.iter().map(|c| c.to_ascii_lowercase()).collect();
+    let keyboard_pos = |c: char| {
+        KEYBOARD_ROWS
+            .iter()
+            .enumerate()
+            .find_map(|(row, keys)| keys.chars().position(|k| k == c).map(|col| (row, col)))
+    };
+
+    let mut start = 0;
+    while start + 2 < lower.len() {
+        let step = lower[start + 1] as i64 - lower[start] as i64;
+        let mut end = start + 1;
+        while end < lower.len() && lower[end] as i64 - lower[end - 1] as i64 == step {
+            end += 1;
+        }
+        let pattern = match step {
+            0 => Some(Pattern::Repeat),
+            -1 | 1 => Some(Pattern::Sequence),
+            _ => None,
+        };
+        if let Some(pattern) = pattern.filter(|_| end - start >= 3) {
+            matches.push(Match {
+                start,
+                end,
+                pattern,
+                bits: char_pool(&chars[start..start + 1]).log2() + 2.0,
+            });
+            start = end - 1;
+        } else {
+            start += 1;
+        }
+    }
+
+    let mut start = 0;
+    while start + 2 < lower.len() {
+        let mut end = start + 1;
+        while end < lower.len() {
+            let adjacent = match (keyboard_pos(lower[end - 1]), keyboard_pos(lower[end])) {
+                (Some((r1, c1)), Some((r2, c2))) => r1 == r2 && c1.abs_diff(c2) == 1,
+                _ => false,
+            };
+            if !adjacent {
+                break;
+            }
+            end += 1;
+        }
+        if end - start >= 4 {
+            matches.push(Match {
+                start,
+                end,
+                pattern: Pattern::Keyboard,
+                bits: (KEYBOARD_ROWS.len() as f64 * 40.0).log2() + (end - start) as f64 * 0.5,
+            });
+            start = end;
+        } else {
+            start += 1;
+        }
+    }
+}
+
+/// Bits to guess `chars`: the cheapest split into matches and brute-forced
+/// characters, and the patterns that split uses
+fn cheapest(chars: &[char], matches: &[Match]) -> (f64, Vec<Pattern>) {
+    let per_char = char_pool(chars).log2();
+    // best[i]: bits and patterns for the first i characters
+    let mut best: Vec<(f64, Vec<Pattern>)> = vec![(0.0, Vec::new())];
+    for end in 1..=chars.len() {
+        let (bits, patterns) = &best[end - 1];
+        let mut candidate = (bits + per_char, patterns.clone());
+        for m in matches.iter().filter(|m| m.end == end) {
+            let (bits, patterns) = &best[m.start];
+            if bits + m.bits < candidate.0 {
+                let mut patterns = patterns.clone();
+                patterns.push(m.pattern);
+                candidate = (bits + m.bits, patterns);
+            }
+        }
+        best.push(candidate);
+    }
+    best.pop().unwrap_or_default()
+}
+
+fn crack_time(bits: f64) -> String {
+    let seconds = 2f64.powf(bits) / 2.0 / GUESSES_PER_SECOND;
+    let units = [
+        (60.0 * 60.0 * 24.0 * 365.0 * 100.0, "century"),
+        (60.0 * 60.0 * 24.0 * 365.0, "year"),
+        (60.0 * 60.0 * 24.0 * 30.0, "month"),
+        (60.0 * 60.0 * 24.0, "day"),
+        (60.0 * 60.0, "hour"),
+        (60.0, "minute"),
+        (1.0, "second"),
+    ];
+    if seconds < 1.0 {
+        return "less than a second".to_string();
+    }
+    if seconds >= units[0].0 * 100.0 {
+        return "centuries".to_string();
+    }
+    let (size, unit) = units
+        .iter()
+        .find(|(size, _)| seconds >= *size)
+        .copied()
+        .unwrap_or((1.0, "second"));
+    let count = (seconds / size).round() as u64;
+    match (count, unit) {
+        (1, _) => format!("1 {}", unit),
+        (_, "century") => format!("{} centuries", count),
+        _ => format!("{} {}s", count, unit),
+    }
+}
+
+fn breached(policy: &PasswordPolicy, password: &str) -> bool {
+    let lower = password.to_lowercase();
+    if COMMON_PASSWORDS
+        .split_whitespace()
+        .any(|common| common == lower)
+    {
+        return true;
+    }
+    let Some(path) = &policy.breach_list else {
+        return false;
+    };
+    match std::fs::File::open(path) {
+        Ok(file) => BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .any(|line| line.trim_end_matches('\r') == password),
+        Err(e) => {
+            warn!("Failed to read breach list {}: {}", path.display(), e);
+            false
+        }
+    }
+}
+
+/// Estimate `password`, treating `user_inputs` (email, name) as words an
+/// attacker would try first, and check it against `policy`
+pub fn estimate(
+    policy: &PasswordPolicy,
+    password: &str,
+    user_inputs: &[String],
+) -> PasswordStrength {
+    let chars: Vec<char> = password.chars().collect();
+    let mut matches = Vec::new();
+    dictionary_matches(&chars, user_inputs, &mut matches);
+    run_matches(&chars, &mut matches);
+    let (entropy_bits, patterns) = cheapest(&chars, &matches);
+    let entropy_bits = (entropy_bits * 10.0).round() / 10.0;
+
+    let score = match entropy_bits {
+        b if b < 20.0 => 0,
+        b if b < 33.0 => 1,
+        b if b < 45.0 => 2,
+        b if b < 60.0 => 3,
+        _ => 4,
+    };
+    let unique: HashSet<Pattern> = patterns.into_iter().collect();
+    let warning = [
+        Pattern::UserInput,
+        Pattern::Dictionary,
+        Pattern::Keyboard,
+        Pattern::Sequence,
+        Pattern::Repeat,
+    ]
+    .into_iter()
+    .find(|p| unique.contains(p))
+    .map(|p| p.warning().to_string());
+    let breached = policy.check_breached && breached(policy, password);
+
+    let mut suggestions = Vec::new();
+    if score < 3 {
+        suggestions.push("Add another word or two; uncommon words are better".to_string());
+    }
+    if !unique.is_empty() {
+        suggestions.push("Avoid common words, sequences and keyboard patterns".to_string());
+    }
+    if chars.iter().all(|c| c.is_ascii_lowercase()) && !chars.is_empty() {
+        suggestions.push("Mix in capitals, digits or symbols".to_string());
+    }
+
+    let mut violations = Vec::new();
+    if chars.len() < policy.min_length {
+        violations.push(format!(
+            "Password must be at least {} characters",
+            policy.min_length
+        ));
+    }
+    if entropy_bits < policy.min_entropy_bits as f64 {
+        violations.push(format!(
+            "Password is too easy to guess ({} bits, {} required)",
+            entropy_bits, policy.min_entropy_bits
+        ));
+    }
+    if breached {
+        violations.push("Password appears in known data breaches".to_string());
+    }
+
+    PasswordStrength {
+        score,
+        crack_time: crack_time(entropy_bits),
+        entropy_bits,
+        warning,
+        suggestions,
+        breached,
+        meets_policy: violations.is_empty(),
+        violations,
+    }
+}
+
+/// The policy of the current config
+pub fn policy(state: &AppState) -> PasswordPolicy {
+    state.config.effective().password_policy
+}
+
+/// Refuse `password` unless it meets the configured policy
+pub fn enforce(
+    state: &AppState,
+    password: &str,
+    user_inputs: &[String],
+) -> Result<(), KeyKeeperError> {
+    let strength = estimate(&policy(state), password, user_inputs);
+    if strength.meets_policy {
+        return Ok(());
+    }
+    let mut message = strength.violations.join("; ");
+    if let Some(warning) = strength.warning {
+        message = format!("{}. {}", message, warning);
+    }
+    Err(KeyKeeperError::Validation(message))
+}
+
+/// Parts of an email worth trying as words: the local part and the domain
+/// name
+pub fn email_inputs(email: &str) -> Vec<String> {
+    email
+        .split(['@', '.', '_', '-', '+'])
+        .filter(|part| part.len() >= 3 && !matches!(*part, "com" | "org" | "net"))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Words of the vault's user account, if it has one
+pub fn account_inputs(vault: &ApiKeyVault) -> Vec<String> {
+    vault
+        .user_account
+        .as_ref()
+        .map(|account| {
+            let mut inputs = email_inputs(&account.email);
+            inputs.push(account.username.to_lowercase());
+            inputs
+        })
+        .unwrap_or_default()
+}
+
+/// Usable while the vault is locked, for the setup screens
+#[tauri::command]
+pub async fn estimate_password_strength(
+    password: String,
+    user_inputs: Option<Vec<String>>,
+    state: State<'_, AppState>,
+) -> Result<PasswordStrength, KeyKeeperError> {
+    let password = zeroize::Zeroizing::new(password);
+    let user_inputs: Vec<String> = user_inputs
+        .unwrap_or_default()
+        .iter()
+        .flat_map(|input| email_inputs(input))
+        .collect();
+    Ok(estimate(&policy(&state), &password, &user_inputs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_cost_less_than_random_characters() {
+        let policy = PasswordPolicy::default();
+        let weak = |password: &str| estimate(&policy, password, &[]);
+
+        for password in [
+            "password",
+            "P@ssw0rd",
+            "qwertyuiop",
+            "abcdefgh",
+            "aaaaaaaaaa",
+        ] {
+            let strength = weak(password);
+            assert!(
+                strength.score <= 1,
+                "{} scored {}",
+                password,
+                strength.score
+            );
+            assert!(!strength.meets_policy);
+            assert!(strength.warning.is_some());
+        }
+        assert!(weak("password").breached);
+
+        let strong = weak("Tq7#vLp2!xW9mZr4");
+        assert_eq!(strong.score, 4);
+        assert!(strong.meets_policy, "{:?}", strong.violations);
+        assert!(strong.warning.is_none());
+
+        let mine = estimate(
+            &policy,
+            "alice.smith2024",
+            &email_inputs("alice.smith@example.com"),
+        );
+        assert!(mine.entropy_bits < weak("qhwkz.mbnrt2024").entropy_bits);
+        assert_eq!(mine.warning.as_deref(), Some(Pattern::UserInput.warning()));
+
+        let short = PasswordPolicy {
+            min_length: 20,
+            ..PasswordPolicy::default()
+        };
+        assert!(!estimate(&short, "Tq7#vLp2!xW9mZr4", &[]).meets_policy);
+    }
+}
@@ -0,0 +1,240 @@
+//! Pinned keys: a key's `is_pinned` flag plus the user's ordering
+//! preferences (`UserPreferences::key_ordering`). Listings and search put
+//! pinned keys first, in the order the user arranged them, and the tray menu
+//! lists them for quick access while the vault is unlocked.
+
+use crate::errors::KeyKeeperError;
+use crate::masking::{self, MaskedApiKey};
+use crate::vault_index::KeySort;
+use crate::{guards, log_audit_event, save_vault, tray_status, ApiKey, ApiKeyVault, AppState};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use tauri::State;
+
+/// How key listings are ordered, saved with the user's preferences
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeyOrdering {
+    #[serde(default = "default_pinned_first")]
+    pub pinned_first: bool,
+    #[serde(default)]
+    pub sort: KeySort,
+    /// Pinned key ids as arranged by the user; pinned keys missing here
+    /// follow in `sort` order
+    #[serde(default)]
+    pub pinned_order: Vec<String>,
+}
+
+fn default_pinned_first() -> bool {
+    true
+}
+
+impl Default for KeyOrdering {
+    fn default() -> Self {
+        Self {
+            pinned_first: true,
+            sort: KeySort::default(),
+            pinned_order: Vec::new(),
+        }
+    }
+}
+
+impl KeyOrdering {
+    fn pinned_rank(&self, key: &ApiKey) -> usize {
+        self.pinned_order
+            .iter()
+            .position(|id| *id == key.id)
+            .unwrap_or(self.pinned_order.len())
+    }
+
+    pub fn compare(&self, a: &ApiKey, b: &ApiKey) -> Ordering {
+        let pins = if self.pinned_first {
+            b.is_pinned.cmp(&a.is_pinned).then_with(|| {
+                if a.is_pinned && b.is_pinned {
+                    self.pinned_rank(a).cmp(&self.pinned_rank(b))
+                } else {
+                    Ordering::Equal
+                }
+            })
+        } else {
+            Ordering::Equal
+        };
+        pins.then_with(|| self.sort.compare(a, b))
+    }
+}
+
+pub fn ordering(vault: &ApiKeyVault) -> KeyOrdering {
+    vault
+        .user_account
+        .as_ref()
+        .map(|account| account.preferences.key_ordering.clone())
+        .unwrap_or_default()
+}
+
+/// Sort keys by the user's ordering preferences
+pub fn order_keys(vault: &ApiKeyVault, keys: &mut [ApiKey]) {
+    let ordering = ordering(vault);
+    keys.sort_by(|a, b| ordering.compare(a, b));
+}
+
+/// Move pinned ids to the front of an already sorted id list, keeping the
+/// order of everything else
+pub fn pinned_ids_first(vault: &ApiKeyVault, ids: &[String]) -> Vec<String> {
+    let ordering = ordering(vault);
+    let mut ids = ids.to_vec();
+    if ordering.pinned_first {
+        ids.sort_by_key(|id| match vault.keys.get(id).filter(|key| key.is_pinned) {
+            Some(key) => (0, ordering.pinned_rank(key)),
+            None => (1, 0),
+        });
+    }
+    ids
+}
+
+/// Pinned keys in the user's order
+pub fn pinned_keys(vault: &ApiKeyVault, pinned_ids: &[String]) -> Vec<ApiKey> {
+    let ordering = ordering(vault);
+    let mut keys: Vec<ApiKey> = pinned_ids
+        .iter()
+        .filter_map(|id| vault.keys.get(id))
+        .filter(|key| key.is_pinned)
+        .cloned()
+        .collect();
+    keys.sort_by(|a, b| {
+        ordering
+            .pinned_rank(a)
+            .cmp(&ordering.pinned_rank(b))
+            .then_with(|| ordering.sort.compare(a, b))
+    });
+    keys
+}
+
+async fn set_pinned(
+    key_id: &str,
+    pinned: bool,
+    state: &State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    let mut vault_guard = state.vault.lock().await;
+    let key = vault_guard
+        .keys
+        .get_mut(key_id)
+        .ok_or_else(|| KeyKeeperError::not_found("API key"))?;
+    if key.is_pinned == pinned {
+        return Ok(());
+    }
+    key.is_pinned = pinned;
+    key.updated_at = Utc::now();
+    if let Some(account) = vault_guard.user_account.as_mut() {
+        let order = &mut account.preferences.key_ordering.pinned_order;
+        order.retain(|id| id != key_id);
+        if pinned {
+            order.push(key_id.to_string());
+        }
+    }
+    drop(vault_guard);
+
+    save_vault(state).await?;
+    let action = if pinned {
+        "pin_api_key"
+    } else {
+        "unpin_api_key"
+    };
+    log_audit_event(state, action, "api_key", Some(key_id), true, None).await;
+    tray_status::refresh(state).await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn pin_api_key(key_id: String, state: State<'_, AppState>) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    set_pinned(&key_id, true, &state).await
+}
+
+#[tauri::command]
+pub async fn unpin_api_key(
+    key_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    set_pinned(&key_id, false, &state).await
+}
+
+/// Pinned keys in the user's order, without scanning the whole vault
+#[tauri::command]
+pub async fn get_pinned_keys(
+    state: State<'_, AppState>,
+) -> Result<Vec<MaskedApiKey>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    let ids = state.vault_index.lock().await.pinned_ids(&vault_guard);
+    Ok(masking::mask_all(pinned_keys(&vault_guard, &ids)))
+}
+
+/// Arrange the pinned keys; `key_ids` must all be pinned
+#[tauri::command]
+pub async fn reorder_pinned_keys(
+    key_ids: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    if let Some(id) = key_ids
+        .iter()
+        .find(|id| !vault_guard.keys.get(*id).is_some_and(|key| key.is_pinned))
+    {
+        return Err(KeyKeeperError::Validation(format!(
+            "Key {} is not pinned",
+            id
+        )));
+    }
+    let account = vault_guard
+        .user_account
+        .as_mut()
+        .ok_or_else(|| KeyKeeperError::not_found("User account"))?;
+    let mut order: Vec<String> = Vec::new();
+    for id in key_ids {
+        if !order.contains(&id) {
+            order.push(id);
+        }
+    }
+    account.preferences.key_ordering.pinned_order = order;
+    account.updated_at = Utc::now();
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    tray_status::refresh(&state).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault_tests::test_key;
+
+    #[test]
+    fn pinned_keys_come_first_in_the_users_order() {
+        let ordering = KeyOrdering {
+            pinned_order: vec!["c".to_string()],
+            ..Default::default()
+        };
+        let mut keys = [
+            test_key("a").name("Alpha").build(),
+            test_key("b").name("Beta").pinned(true).build(),
+            test_key("c").name("Gamma").pinned(true).build(),
+            test_key("d").name("Delta").build(),
+        ];
+        keys.sort_by(|a, b| ordering.compare(a, b));
+        let ids: Vec<&str> = keys.iter().map(|k| k.id.as_str()).collect();
+        assert_eq!(ids, vec!["c", "b", "a", "d"]);
+
+        let unpinned_first = KeyOrdering {
+            pinned_first: false,
+            ..ordering
+        };
+        keys.sort_by(|a, b| unpinned_first.compare(a, b));
+        let ids: Vec<&str> = keys.iter().map(|k| k.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "d", "c"]);
+    }
+}
@@ -0,0 +1,368 @@
+//! Project analysis.
+//!
+//! Inspects a project's manifests (`package.json`, `Cargo.toml`,
+//! `pyproject.toml`, ...) to work out its language, main framework and
+//! package manager. The result is stored on the `Project` so config
+//! generation and suggestions can be tailored to it.
+
+use crate::errors::KeyKeeperError;
+use crate::{guards, log_audit_event, save_vault, AppState, Project};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::State;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ProjectProfile {
+    pub language: Option<String>,
+    pub framework: Option<String>,
+    pub package_manager: Option<String>,
+    /// Every framework found, main one first
+    pub frameworks: Vec<String>,
+    /// Manifest files the profile was built from
+    pub manifests: Vec<String>,
+}
+
+/// Dependency name -> framework id, most specific first so e.g. Next.js wins
+/// over the React it depends on
+const JS_FRAMEWORKS: &[(&str, &str)] = &[
+    ("next", "nextjs"),
+    ("nuxt", "nuxt"),
+    ("@sveltejs/kit", "sveltekit"),
+    ("@remix-run/react", "remix"),
+    ("astro", "astro"),
+    ("@nestjs/core", "nestjs"),
+    ("@angular/core", "angular"),
+    ("express", "express"),
+    ("fastify", "fastify"),
+    ("hono", "hono"),
+    ("svelte", "svelte"),
+    ("vue", "vue"),
+    ("react", "react"),
+];
+const RUST_FRAMEWORKS: &[(&str, &str)] = &[
+    ("tauri", "tauri"),
+    ("axum", "axum"),
+    ("actix-web", "actix-web"),
+    ("rocket", "rocket"),
+    ("warp", "warp"),
+];
+const PYTHON_FRAMEWORKS: &[(&str, &str)] = &[
+    ("fastapi", "fastapi"),
+    ("django", "django"),
+    ("flask", "flask"),
+];
+const GO_FRAMEWORKS: &[(&str, &str)] = &[
+    ("github.com/gin-gonic/gin", "gin"),
+    ("github.com/labstack/echo", "echo"),
+    ("github.com/gofiber/fiber", "fiber"),
+];
+const PHP_FRAMEWORKS: &[(&str, &str)] = &[
+    ("laravel/framework", "laravel"),
+    ("symfony/framework-bundle", "symfony"),
+];
+
+struct Ecosystem {
+    language: String,
+    package_manager: String,
+    frameworks: Vec<String>,
+    manifest: String,
+}
+
+fn read(root: &Path, file: &str) -> Option<String> {
+    fs::read_to_string(root.join(file)).ok()
+}
+
+fn first_existing<'a>(root: &Path, candidates: &[(&'a str, &'a str)]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .find(|(file, _)| root.join(file).exists())
+        .map(|(_, manager)| *manager)
+}
+
+fn matching(frameworks: &[(&str, &str)], has: impl Fn(&str) -> bool) -> Vec<String> {
+    frameworks
+        .iter()
+        .filter(|(dependency, _)| has(dependency))
+        .map(|(_, framework)| framework.to_string())
+        .collect()
+}
+
+fn node(root: &Path) -> Option<Ecosystem> {
+    let manifest: serde_json::Value = serde_json::from_str(&read(root, "package.json")?).ok()?;
+    let has = |name: &str| {
+        ["dependencies", "devDependencies", "peerDependencies"]
+            .iter()
+            .any(|section| manifest[section].get(name).is_some())
+    };
+    let language = if root.join("tsconfig.json").exists() || has("typescript") {
+        "typescript"
+    } else {
+        "javascript"
+    };
+    let package_manager = first_existing(
+        root,
+        &[
+            ("pnpm-lock.yaml", "pnpm"),
+            ("yarn.lock", "yarn"),
+            ("bun.lockb", "bun"),
+            ("bun.lock", "bun"),
+            ("package-lock.json", "npm"),
+        ],
+    )
+    .map(str::to_string)
+    .or_else(|| {
+        // "packageManager": "pnpm@9.1.0"
+        manifest["packageManager"]
+            .as_str()
+            .and_then(|pm| pm.split('@').next())
+            .map(str::to_string)
+    })
+    .unwrap_or_else(|| "npm".to_string());
+
+    Some(Ecosystem {
+        language: language.to_string(),
+        package_manager,
+        frameworks: matching(JS_FRAMEWORKS, has),
+        manifest: "package.json".to_string(),
+    })
+}
+
+fn rust(root: &Path) -> Option<Ecosystem> {
+    let manifest = read(root, "Cargo.toml")?;
+    // Dependency tables list crates as `name = ...` or `[dependencies.name]`
+    let has = |name: &str| {
+        manifest.lines().any(|line| {
+            let line = line.trim();
+            line.strip_prefix(name)
+                .is_some_and(|rest| rest.trim_start().starts_with('=') || rest.starts_with('.'))
+                || line.ends_with(&format!("dependencies.{}]", name))
+        })
+    };
+    Some(Ecosystem {
+        language: "rust".to_string(),
+        package_manager: "cargo".to_string(),
+        frameworks: matching(RUST_FRAMEWORKS, has),
+        manifest: "Cargo.toml".to_string(),
+    })
+}
+
+fn python(root: &Path) -> Option<Ecosystem> {
+    let manifests = ["pyproject.toml", "requirements.txt", "Pipfile", "setup.py"];
+    let (manifest, contents): (Vec<&str>, Vec<String>) = manifests
+        .iter()
+        .filter_map(|file| read(root, file).map(|c| (*file, c.to_lowercase())))
+        .unzip();
+    if manifest.is_empty() {
+        return None;
+    }
+    // Requirement specifiers start with the package name, either at the
+    // start of a line or inside a quoted list
+    let has = |name: &str| {
+        contents.iter().any(|c| {
+            c.lines().any(|line| {
+                let line = line.trim().trim_start_matches(['"', '\'']);
+                line.strip_prefix(name).is_some_and(|rest| {
+                    rest.is_empty()
+                        || !rest
+                            .starts_with(|ch: char| ch.is_alphanumeric() || ch == '-' || ch == '_')
+                })
+            })
+        })
+    };
+    let package_manager = first_existing(
+        root,
+        &[
+            ("uv.lock", "uv"),
+            ("poetry.lock", "poetry"),
+            ("pdm.lock", "pdm"),
+            ("Pipfile", "pipenv"),
+        ],
+    )
+    .unwrap_or("pip");
+
+    Some(Ecosystem {
+        language: "python".to_string(),
+        package_manager: package_manager.to_string(),
+        frameworks: matching(PYTHON_FRAMEWORKS, has),
+        manifest: manifest[0].to_string(),
+    })
+}
+
+fn go(root: &Path) -> Option<Ecosystem> {
+    let manifest = read(root, "go.mod")?;
+    Some(Ecosystem {
+        language: "go".to_string(),
+        package_manager: "go".to_string(),
+        frameworks: matching(GO_FRAMEWORKS, |name| manifest.contains(name)),
+        manifest: "go.mod".to_string(),
+    })
+}
+
+fn php(root: &Path) -> Option<Ecosystem> {
+    let manifest: serde_json::Value = serde_json::from_str(&read(root, "composer.json")?).ok()?;
+    let has = |name: &str| manifest["require"].get(name).is_some();
+    Some(Ecosystem {
+        language: "php".to_string(),
+        package_manager: "composer".to_string(),
+        frameworks: matching(PHP_FRAMEWORKS, has),
+        manifest: "composer.json".to_string(),
+    })
+}
+
+fn ruby(root: &Path) -> Option<Ecosystem> {
+    let manifest = read(root, "Gemfile")?;
+    let rails = manifest.lines().any(|line| {
+        line.trim().starts_with("gem \"rails\"") || line.trim().starts_with("gem 'rails'")
+    });
+    Some(Ecosystem {
+        language: "ruby".to_string(),
+        package_manager: "bundler".to_string(),
+        frameworks: if rails {
+            vec!["rails".to_string()]
+        } else {
+            Vec::new()
+        },
+        manifest: "Gemfile".to_string(),
+    })
+}
+
+fn jvm(root: &Path) -> Option<Ecosystem> {
+    let (manifest, package_manager) = [
+        ("pom.xml", "maven"),
+        ("build.gradle.kts", "gradle"),
+        ("build.gradle", "gradle"),
+    ]
+    .into_iter()
+    .find(|(file, _)| root.join(file).exists())?;
+    let contents = read(root, manifest).unwrap_or_default();
+    let language = if manifest.ends_with(".kts") || contents.contains("kotlin") {
+        "kotlin"
+    } else {
+        "java"
+    };
+    let spring = contents.contains("spring-boot");
+    Some(Ecosystem {
+        language: language.to_string(),
+        package_manager: package_manager.to_string(),
+        frameworks: if spring {
+            vec!["spring-boot".to_string()]
+        } else {
+            Vec::new()
+        },
+        manifest: manifest.to_string(),
+    })
+}
+
+/// Build the profile of the project rooted at `path`. The main ecosystem is
+/// the first one with a recognised framework, or else the first found.
+pub fn analyze(path: &Path) -> ProjectProfile {
+    let detectors: [fn(&Path) -> Option<Ecosystem>; 7] = [node, rust, python, go, php, ruby, jvm];
+    let ecosystems: Vec<Ecosystem> = detectors.iter().filter_map(|detect| detect(path)).collect();
+
+    let Some(main) = ecosystems
+        .iter()
+        .find(|e| !e.frameworks.is_empty())
+        .or_else(|| ecosystems.first())
+    else {
+        return ProjectProfile::default();
+    };
+
+    let mut frameworks = main.frameworks.clone();
+    for ecosystem in &ecosystems {
+        for framework in &ecosystem.frameworks {
+            if !frameworks.contains(framework) {
+                frameworks.push(framework.clone());
+            }
+        }
+    }
+    ProjectProfile {
+        language: Some(main.language.clone()),
+        framework: frameworks.first().cloned(),
+        package_manager: Some(main.package_manager.clone()),
+        frameworks,
+        manifests: ecosystems.iter().map(|e| e.manifest.clone()).collect(),
+    }
+}
+
+/// Re-run the analysis of a project's directory, including its monorepo
+/// packages, and store the result
+#[tauri::command]
+pub async fn analyze_project(
+    project_id: String,
+    state: State<'_, AppState>,
+) -> Result<Project, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let path = {
+        let vault_guard = state.vault.lock().await;
+        vault_guard
+            .projects
+            .get(&project_id)
+            .map(|p| p.path.clone())
+            .ok_or_else(|| KeyKeeperError::not_found("Project"))?
+    };
+    if !Path::new(&path).is_dir() {
+        return Err(KeyKeeperError::NotFound(format!(
+            "Project directory {} does not exist",
+            path
+        )));
+    }
+    let profile = analyze(Path::new(&path));
+    let packages = crate::monorepo::list_packages(Path::new(&path));
+
+    let mut vault_guard = state.vault.lock().await;
+    let project = vault_guard
+        .projects
+        .get_mut(&project_id)
+        .ok_or_else(|| KeyKeeperError::not_found("Project"))?;
+    project.profile = profile;
+    project.packages = packages;
+    project.updated_at = Utc::now();
+    let updated = project.clone();
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "analyze_project",
+        "project",
+        Some(&project_id),
+        true,
+        None,
+    )
+    .await;
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_nextjs_with_pnpm_alongside_python() {
+        let dir = std::env::temp_dir().join(format!("kk-analysis-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("package.json"),
+            r#"{"dependencies": {"next": "14.0.0", "react": "18.0.0"}}"#,
+        )
+        .unwrap();
+        fs::write(dir.join("tsconfig.json"), "{}").unwrap();
+        fs::write(dir.join("pnpm-lock.yaml"), "").unwrap();
+        fs::write(
+            dir.join("requirements.txt"),
+            "fastapi==0.110\nfastapi-users\n",
+        )
+        .unwrap();
+
+        let profile = analyze(&dir);
+        assert_eq!(profile.language.as_deref(), Some("typescript"));
+        assert_eq!(profile.framework.as_deref(), Some("nextjs"));
+        assert_eq!(profile.package_manager.as_deref(), Some("pnpm"));
+        assert_eq!(profile.frameworks, vec!["nextjs", "react", "fastapi"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
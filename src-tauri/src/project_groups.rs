@@ -0,0 +1,357 @@
+//! Project groups.
+//!
+//! Groups organize projects by client, team or area. A group can sit inside
+//! a top-level group, but no deeper. A project belongs to at most one group.
+//! Key queries (`group_id` in `KeyFilter`, `?group=` on `/api/keys`) and
+//! `sync_all_projects` with a `group_id` cover the projects of a group and of
+//! its subgroups.
+
+use crate::errors::KeyKeeperError;
+use crate::{guards, timestamps, ApiKeyVault, AppState, Project};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupKind {
+    Client,
+    Team,
+    Area,
+    #[default]
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectGroup {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub kind: GroupKind,
+    /// Top-level group this one is nested in
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub updated_at: DateTime<Utc>,
+}
+
+fn find<'a>(vault: &'a ApiKeyVault, id: &str) -> Result<&'a ProjectGroup, KeyKeeperError> {
+    vault
+        .project_groups
+        .iter()
+        .find(|group| group.id == id)
+        .ok_or_else(|| KeyKeeperError::not_found("Project group"))
+}
+
+/// The group and its subgroups
+fn with_subgroups(vault: &ApiKeyVault, id: &str) -> Result<Vec<String>, KeyKeeperError> {
+    find(vault, id)?;
+    let mut ids = vec![id.to_string()];
+    ids.extend(
+        vault
+            .project_groups
+            .iter()
+            .filter(|group| group.parent_id.as_deref() == Some(id))
+            .map(|group| group.id.clone()),
+    );
+    Ok(ids)
+}
+
+/// Projects in the group or one of its subgroups
+pub fn projects_in<'a>(
+    vault: &'a ApiKeyVault,
+    id: &str,
+) -> Result<Vec<&'a Project>, KeyKeeperError> {
+    let ids = with_subgroups(vault, id)?;
+    Ok(vault
+        .projects
+        .values()
+        .filter(|project| {
+            project
+                .group_id
+                .as_ref()
+                .is_some_and(|group| ids.contains(group))
+        })
+        .collect())
+}
+
+fn validate_name(
+    vault: &ApiKeyVault,
+    name: &str,
+    parent_id: Option<&str>,
+    except: Option<&str>,
+) -> Result<String, KeyKeeperError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(KeyKeeperError::Validation(
+            "A project group needs a name".to_string(),
+        ));
+    }
+    if vault.project_groups.iter().any(|group| {
+        group.parent_id.as_deref() == parent_id
+            && Some(group.id.as_str()) != except
+            && group.name.eq_ignore_ascii_case(name)
+    }) {
+        return Err(KeyKeeperError::Conflict(format!(
+            "A group named '{}' already exists here",
+            name
+        )));
+    }
+    Ok(name.to_string())
+}
+
+pub fn create(
+    vault: &mut ApiKeyVault,
+    name: &str,
+    kind: GroupKind,
+    parent_id: Option<String>,
+    now: DateTime<Utc>,
+) -> Result<ProjectGroup, KeyKeeperError> {
+    if let Some(parent_id) = &parent_id {
+        if find(vault, parent_id)?.parent_id.is_some() {
+            return Err(KeyKeeperError::Validation(
+                "Groups can only be nested one level deep".to_string(),
+            ));
+        }
+    }
+    let group = ProjectGroup {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: validate_name(vault, name, parent_id.as_deref(), None)?,
+        kind,
+        parent_id,
+        created_at: now,
+        updated_at: now,
+    };
+    vault.project_groups.push(group.clone());
+    Ok(group)
+}
+
+pub fn rename(
+    vault: &mut ApiKeyVault,
+    id: &str,
+    name: &str,
+    now: DateTime<Utc>,
+) -> Result<ProjectGroup, KeyKeeperError> {
+    let parent_id = find(vault, id)?.parent_id.clone();
+    let name = validate_name(vault, name, parent_id.as_deref(), Some(id))?;
+    let group = vault
+        .project_groups
+        .iter_mut()
+        .find(|group| group.id == id)
+        .ok_or_else(|| KeyKeeperError::not_found("Project group"))?;
+    group.name = name;
+    group.updated_at = now;
+    Ok(group.clone())
+}
+
+/// Subgroups become top-level groups and the group's projects move to its
+/// parent, or out of any group
+pub fn delete(vault: &mut ApiKeyVault, id: &str) -> Result<(), KeyKeeperError> {
+    let parent_id = find(vault, id)?.parent_id.clone();
+    vault.project_groups.retain(|group| group.id != id);
+    for group in &mut vault.project_groups {
+        if group.parent_id.as_deref() == Some(id) {
+            group.parent_id = None;
+        }
+    }
+    for project in vault.projects.values_mut() {
+        if project.group_id.as_deref() == Some(id) {
+            project.group_id = parent_id.clone();
+        }
+    }
+    Ok(())
+}
+
+pub fn move_project(
+    vault: &mut ApiKeyVault,
+    project_id: &str,
+    group_id: Option<String>,
+    now: DateTime<Utc>,
+) -> Result<Project, KeyKeeperError> {
+    if let Some(group_id) = &group_id {
+        find(vault, group_id)?;
+    }
+    let project = vault
+        .projects
+        .get_mut(project_id)
+        .ok_or_else(|| KeyKeeperError::not_found("Project"))?;
+    project.group_id = group_id;
+    project.updated_at = now;
+    Ok(project.clone())
+}
+
+#[tauri::command]
+pub async fn list_project_groups(
+    state: State<'_, AppState>,
+) -> Result<Vec<ProjectGroup>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut groups = state.vault.lock().await.project_groups.clone();
+    groups.sort_by_key(|group| group.name.to_lowercase());
+    Ok(groups)
+}
+
+#[tauri::command]
+pub async fn create_project_group(
+    name: String,
+    kind: Option<GroupKind>,
+    parent_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ProjectGroup, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let group = create(
+        &mut vault_guard,
+        &name,
+        kind.unwrap_or_default(),
+        parent_id,
+        Utc::now(),
+    )?;
+    drop(vault_guard);
+
+    crate::save_vault(&state).await?;
+    crate::log_audit_event(
+        &state,
+        "create_project_group",
+        "project_group",
+        Some(&group.id),
+        true,
+        None,
+    )
+    .await;
+    Ok(group)
+}
+
+#[tauri::command]
+pub async fn rename_project_group(
+    id: String,
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<ProjectGroup, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let group = rename(&mut vault_guard, &id, &name, Utc::now())?;
+    drop(vault_guard);
+
+    crate::save_vault(&state).await?;
+    crate::log_audit_event(
+        &state,
+        "rename_project_group",
+        "project_group",
+        Some(&id),
+        true,
+        None,
+    )
+    .await;
+    Ok(group)
+}
+
+#[tauri::command]
+pub async fn delete_project_group(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    delete(&mut vault_guard, &id)?;
+    drop(vault_guard);
+
+    crate::save_vault(&state).await?;
+    crate::log_audit_event(
+        &state,
+        "delete_project_group",
+        "project_group",
+        Some(&id),
+        true,
+        None,
+    )
+    .await;
+    Ok(())
+}
+
+/// Move a project into `group_id`, or out of any group when it's `None`
+#[tauri::command]
+pub async fn move_project_to_group(
+    project_id: String,
+    group_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Project, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let project = move_project(&mut vault_guard, &project_id, group_id, Utc::now())?;
+    drop(vault_guard);
+
+    crate::save_vault(&state).await?;
+    crate::log_audit_event(
+        &state,
+        "move_project_to_group",
+        "project",
+        Some(&project_id),
+        true,
+        None,
+    )
+    .await;
+    Ok(project)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(id: &str) -> Project {
+        serde_json::from_value(serde_json::json!({
+            "id": id, "name": id, "description": null, "path": format!("/work/{}", id),
+            "created_at": "2024-01-01T00:00:00Z", "updated_at": "2024-01-01T00:00:00Z",
+            "settings": crate::project_settings::builtin_defaults()
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn groups_nest_one_level_and_collect_subgroup_projects() {
+        let mut vault = ApiKeyVault::default();
+        let now = Utc::now();
+        for id in ["shop", "blog", "loose"] {
+            vault.projects.insert(id.to_string(), project(id));
+        }
+        let acme = create(&mut vault, "Acme", GroupKind::Client, None, now).unwrap();
+        let web = create(
+            &mut vault,
+            "Web",
+            GroupKind::Team,
+            Some(acme.id.clone()),
+            now,
+        )
+        .unwrap();
+        assert!(create(
+            &mut vault,
+            "Deep",
+            GroupKind::Area,
+            Some(web.id.clone()),
+            now
+        )
+        .is_err());
+        assert!(create(&mut vault, "acme", GroupKind::Client, None, now).is_err());
+
+        move_project(&mut vault, "shop", Some(acme.id.clone()), now).unwrap();
+        move_project(&mut vault, "blog", Some(web.id.clone()), now).unwrap();
+        let mut ids: Vec<&str> = projects_in(&vault, &acme.id)
+            .unwrap()
+            .iter()
+            .map(|p| p.id.as_str())
+            .collect();
+        ids.sort();
+        assert_eq!(ids, ["blog", "shop"]);
+
+        delete(&mut vault, &web.id).unwrap();
+        assert_eq!(vault.projects["blog"].group_id.as_ref(), Some(&acme.id));
+        delete(&mut vault, &acme.id).unwrap();
+        assert!(vault.projects.values().all(|p| p.group_id.is_none()));
+    }
+}
@@ -0,0 +1,494 @@
+//! Ignore rules shared by everything that walks a project.
+//!
+//! Rules come from three places, in increasing precedence:
+//!
+//! - built-in: dependency and build output dirs (`node_modules`, `target`…)
+//! - `.gitignore` files, the root's `.git/info/exclude` included
+//! - `.keykeeperignore` files, same syntax, read after the `.gitignore` of
+//!   the same dir so `!pattern` can bring back what git ignores
+//!
+//! Nested ignore files apply below their directory and are read as a walk
+//! reaches it. Within a source the last matching rule decides, as in git,
+//! and nothing inside an ignored directory can be brought back.
+//!
+//! What applies depends on the scan. Code and project scans use every rule.
+//! Env files are almost always gitignored, so env-file discovery lets
+//! `.gitignore` prune directories but not skip files. Git history scans
+//! only honor `.keykeeperignore`: a gitignored file that was committed
+//! anyway is exactly what they look for.
+
+use crate::errors::KeyKeeperError;
+use crate::{guards, AppState};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use tauri::State;
+
+pub const KEYKEEPER_IGNORE: &str = ".keykeeperignore";
+const BUILTIN: &[&str] = &[
+    "node_modules/",
+    "target/",
+    ".git/",
+    "dist/",
+    "build/",
+    ".next/",
+];
+/// How deep env-file discovery and the rule listing descend
+const MAX_WALK_DEPTH: usize = 6;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleSource {
+    BuiltIn,
+    Gitignore,
+    Keykeeper,
+}
+
+/// What a path is checked for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Purpose {
+    /// Code, framework and secret scans of the working tree
+    Scan,
+    EnvFiles,
+    GitHistory,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IgnoreRule {
+    pub source: RuleSource,
+    /// Ignore file the rule is from, relative to the project; `None` for
+    /// built-in rules
+    pub file: Option<String>,
+    pub line: usize,
+    pub pattern: String,
+    /// Directory the rule applies below, relative to the project
+    #[serde(skip)]
+    base: String,
+    #[serde(skip)]
+    negated: bool,
+    #[serde(skip)]
+    dir_only: bool,
+    #[serde(skip)]
+    regex: Regex,
+}
+
+impl IgnoreRule {
+    /// Whether the rule matches `path`, relative to the project with `/`
+    /// separators
+    fn matches(&self, path: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let rest = if self.base.is_empty() {
+            Some(path)
+        } else {
+            path.strip_prefix(self.base.as_str())
+                .and_then(|rest| rest.strip_prefix('/'))
+        };
+        rest.is_some_and(|rest| self.regex.is_match(rest))
+    }
+
+    fn applies_to(&self, purpose: Purpose, is_dir: bool) -> bool {
+        match purpose {
+            Purpose::Scan => true,
+            Purpose::EnvFiles => is_dir || self.source != RuleSource::Gitignore,
+            Purpose::GitHistory => self.source == RuleSource::Keykeeper,
+        }
+    }
+}
+
+/// Regex for a gitignore glob: `*` and `?` stay within a path segment, `**`
+/// crosses segments
+fn glob_to_regex(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                if chars.get(i + 2) == Some(&'/') {
+                    out.push_str("(?:.*/)?");
+                    i += 3;
+                } else {
+                    out.push_str(".*");
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => out.push_str("[^/]*"),
+            '?' => out.push_str("[^/]"),
+            '[' => {
+                if let Some(len) = chars[i + 1..].iter().position(|c| *c == ']') {
+                    let class: String = chars[i + 1..i + 1 + len].iter().collect();
+                    let class = match class.strip_prefix('!') {
+                        Some(rest) => format!("^{}", rest),
+                        None => class,
+                    };
+                    out.push_str(&format!("[{}]", class.replace('\\', "\\\\")));
+                    i += len + 2;
+                    continue;
+                }
+                out.push_str("\\[");
+            }
+            '\\' if i + 1 < chars.len() => {
+                out.push_str(&regex::escape(&chars[i + 1].to_string()));
+                i += 2;
+                continue;
+            }
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Rules of an ignore file whose directory is `base`
+pub fn parse(
+    contents: &str,
+    source: RuleSource,
+    file: Option<&str>,
+    base: &str,
+) -> Vec<IgnoreRule> {
+    let mut rules = Vec::new();
+    for (index, raw) in contents.lines().enumerate() {
+        let line = raw.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (negated, body) = match line.strip_prefix('!') {
+            Some(body) => (true, body),
+            None => (false, line),
+        };
+        let body = body
+            .strip_prefix('\\')
+            .filter(|b| b.starts_with('#') || b.starts_with('!'))
+            .unwrap_or(body);
+        let dir_only = body.ends_with('/');
+        let body = body.trim_end_matches('/');
+        if body.is_empty() {
+            continue;
+        }
+        // A slash anywhere but the end anchors the pattern to `base`
+        let anchored = body.contains('/');
+        let glob = glob_to_regex(body.trim_start_matches('/'));
+        let pattern = if anchored {
+            format!("^{}$", glob)
+        } else {
+            format!("^(?:.*/)?{}$", glob)
+        };
+        let Ok(regex) = Regex::new(&pattern) else {
+            continue;
+        };
+        rules.push(IgnoreRule {
+            source,
+            file: file.map(str::to_string),
+            line: index + 1,
+            pattern: line.to_string(),
+            base: base.to_string(),
+            negated,
+            dir_only,
+            regex,
+        });
+    }
+    rules
+}
+
+/// The rules of one project, with nested ignore files read on demand
+#[derive(Debug)]
+pub struct ProjectIgnores {
+    root: PathBuf,
+    rules: Vec<IgnoreRule>,
+    /// Directories whose ignore files were read, relative to the root
+    loaded: HashSet<String>,
+}
+
+impl ProjectIgnores {
+    pub fn load(root: &Path) -> Self {
+        let builtin = BUILTIN.join("\n");
+        let mut ignores = Self {
+            root: root.to_path_buf(),
+            rules: parse(&builtin, RuleSource::BuiltIn, None, ""),
+            loaded: HashSet::new(),
+        };
+        ignores.load_dir("");
+        ignores
+    }
+
+    fn read(&mut self, dir: &str, name: &str, source: RuleSource) {
+        let file = if dir.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", dir, name)
+        };
+        if let Ok(contents) = fs::read_to_string(self.root.join(&file)) {
+            let rules = parse(&contents, source, Some(&file), dir);
+            self.rules.extend(rules);
+        }
+    }
+
+    fn load_dir(&mut self, dir: &str) {
+        if !self.loaded.insert(dir.to_string()) {
+            return;
+        }
+        if dir.is_empty() {
+            self.read("", ".git/info/exclude", RuleSource::Gitignore);
+        }
+        self.read(dir, ".gitignore", RuleSource::Gitignore);
+        self.read(dir, KEYKEEPER_IGNORE, RuleSource::Keykeeper);
+    }
+
+    /// Segments of `path` below the root; `None` outside of it
+    fn segments(&self, path: &Path) -> Option<Vec<String>> {
+        let rel = if path.is_absolute() {
+            path.strip_prefix(&self.root).ok()?
+        } else {
+            path
+        };
+        rel.components()
+            .filter(|c| !matches!(c, Component::CurDir))
+            .map(|c| match c {
+                Component::Normal(s) => Some(s.to_string_lossy().to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Rule deciding about `path` alone, the last match of the ignore files
+    /// winning over the last match of the others
+    fn deciding(&self, path: &str, is_dir: bool, purpose: Purpose) -> Option<usize> {
+        let last = |keykeeper: bool| {
+            self.rules.iter().rposition(|rule| {
+                (rule.source == RuleSource::Keykeeper) == keykeeper
+                    && rule.applies_to(purpose, is_dir)
+                    && rule.matches(path, is_dir)
+            })
+        };
+        last(true).or_else(|| last(false))
+    }
+
+    /// Rule deciding about `path`: the first ignored parent dir, or the
+    /// path's own, which may be a `!` rule bringing it back
+    fn decide(&mut self, path: &Path, is_dir: bool, purpose: Purpose) -> Option<usize> {
+        let segments = self.segments(path)?;
+        for depth in 0..segments.len() {
+            self.load_dir(&segments[..depth].join("/"));
+        }
+        for depth in 1..=segments.len() {
+            let is_last = depth == segments.len();
+            let rule = self.deciding(&segments[..depth].join("/"), is_dir || !is_last, purpose);
+            if is_last || rule.is_some_and(|i| !self.rules[i].negated) {
+                return rule;
+            }
+        }
+        None
+    }
+
+    /// Rule ignoring `path` (absolute or relative to the root) for
+    /// `purpose`
+    pub fn check(&mut self, path: &Path, is_dir: bool, purpose: Purpose) -> Option<&IgnoreRule> {
+        let index = self.decide(path, is_dir, purpose)?;
+        let rule = &self.rules[index];
+        (!rule.negated).then_some(rule)
+    }
+
+    pub fn is_ignored(&mut self, path: &Path, is_dir: bool, purpose: Purpose) -> bool {
+        self.check(path, is_dir, purpose).is_some()
+    }
+
+    /// Files under the root down to `max_depth` that aren't ignored and
+    /// `keep` accepts, sorted
+    pub fn walk_files(
+        &mut self,
+        max_depth: usize,
+        purpose: Purpose,
+        keep: impl Fn(&Path) -> bool,
+    ) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        let mut pending = vec![(self.root.clone(), 0)];
+        while let Some((dir, depth)) = pending.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.filter_map(|e| e.ok()) {
+                let path = entry.path();
+                let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+                if self.is_ignored(&path, is_dir, purpose) {
+                    continue;
+                }
+                if is_dir {
+                    if depth < max_depth {
+                        pending.push((path, depth + 1));
+                    }
+                } else if keep(&path) {
+                    files.push(path);
+                }
+            }
+        }
+        files.sort();
+        files
+    }
+
+    /// Paths of the ignore files read so far, relative to the root
+    pub fn files(&self) -> Vec<String> {
+        let mut files: Vec<String> = self.rules.iter().filter_map(|r| r.file.clone()).collect();
+        files.dedup();
+        files
+    }
+}
+
+fn is_env_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|name| name == ".env" || name.starts_with(".env."))
+}
+
+/// Env files of a project, relative to it with `/` separators
+pub fn env_files(project_root: &Path) -> Vec<String> {
+    let mut ignores = ProjectIgnores::load(project_root);
+    ignores
+        .walk_files(MAX_WALK_DEPTH, Purpose::EnvFiles, is_env_file)
+        .iter()
+        .filter_map(|path| ignores.segments(path))
+        .map(|segments| segments.join("/"))
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IgnoreVerdict {
+    /// Relative to the project
+    pub path: String,
+    pub is_dir: bool,
+    /// Skipped by code and secret scans
+    pub ignored: bool,
+    /// Skipped by env-file discovery
+    pub ignored_as_env_file: bool,
+    /// Findings in it are dropped from git history scans
+    pub ignored_in_history: bool,
+    /// Rule deciding `ignored`; a `!` rule when it brings the path back
+    pub rule: Option<IgnoreRule>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveIgnores {
+    pub project_path: String,
+    /// Ignore files found, relative to the project
+    pub files: Vec<String>,
+    /// In the order they're read
+    pub rules: Vec<IgnoreRule>,
+    pub verdict: Option<IgnoreVerdict>,
+}
+
+pub fn explain(ignores: &mut ProjectIgnores, path: &Path) -> IgnoreVerdict {
+    let is_dir = ignores.root.join(path).is_dir();
+    let rule = ignores
+        .decide(path, is_dir, Purpose::Scan)
+        .map(|i| ignores.rules[i].clone());
+    IgnoreVerdict {
+        path: ignores
+            .segments(path)
+            .map(|segments| segments.join("/"))
+            .unwrap_or_else(|| path.to_string_lossy().to_string()),
+        is_dir,
+        ignored: rule.as_ref().is_some_and(|r| !r.negated),
+        ignored_as_env_file: ignores.is_ignored(path, is_dir, Purpose::EnvFiles),
+        ignored_in_history: ignores.is_ignored(path, is_dir, Purpose::GitHistory),
+        rule,
+    }
+}
+
+/// The ignore rules of a project and, given a path, which of them decides
+/// about it
+#[tauri::command]
+pub async fn get_effective_ignores(
+    project_path: String,
+    path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<EffectiveIgnores, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let root = PathBuf::from(&project_path);
+    if !root.is_dir() {
+        return Err(KeyKeeperError::not_found("Project directory"));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut ignores = ProjectIgnores::load(&root);
+        // Reads the nested ignore files
+        ignores.walk_files(MAX_WALK_DEPTH, Purpose::Scan, |_| false);
+        let verdict = path.map(|path| explain(&mut ignores, Path::new(&path)));
+        EffectiveIgnores {
+            project_path,
+            files: ignores.files(),
+            rules: ignores.rules.clone(),
+            verdict,
+        }
+    })
+    .await
+    .map_err(|e| KeyKeeperError::Internal(format!("Reading ignore rules failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gitignore_and_keykeeperignore_rules_combine_per_purpose() {
+        let dir = std::env::temp_dir().join(format!("kk-ignore-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("apps/web/fixtures")).unwrap();
+        fs::create_dir_all(dir.join("coverage")).unwrap();
+        fs::write(
+            dir.join(".gitignore"),
+            ".env*\n!.env.example\ncoverage/\n*.log\n",
+        )
+        .unwrap();
+        fs::write(dir.join(KEYKEEPER_IGNORE), "**/fixtures/\n!debug.log\n").unwrap();
+        fs::write(dir.join("apps/web/.gitignore"), "/local.txt\n").unwrap();
+        for file in [
+            ".env",
+            ".env.example",
+            "apps/web/.env.local",
+            "apps/web/fixtures/.env",
+            "coverage/.env",
+            "apps/web/local.txt",
+            "debug.log",
+            "error.log",
+        ] {
+            fs::write(dir.join(file), "").unwrap();
+        }
+
+        assert_eq!(
+            env_files(&dir),
+            vec![".env", ".env.example", "apps/web/.env.local"]
+        );
+
+        let mut ignores = ProjectIgnores::load(&dir);
+        let ignored = |ignores: &mut ProjectIgnores, path: &str, purpose| {
+            ignores.is_ignored(Path::new(path), false, purpose)
+        };
+        assert!(ignored(&mut ignores, ".env", Purpose::Scan));
+        assert!(!ignored(&mut ignores, ".env.example", Purpose::Scan));
+        assert!(ignored(&mut ignores, "apps/web/local.txt", Purpose::Scan));
+        assert!(!ignored(&mut ignores, "local.txt", Purpose::Scan));
+        assert!(ignored(&mut ignores, "error.log", Purpose::Scan));
+        assert!(!ignored(&mut ignores, "debug.log", Purpose::Scan));
+        assert!(ignored(
+            &mut ignores,
+            "node_modules/pkg/index.js",
+            Purpose::Scan
+        ));
+        assert!(!ignored(&mut ignores, ".env", Purpose::GitHistory));
+        assert!(ignored(
+            &mut ignores,
+            "apps/web/fixtures/.env",
+            Purpose::GitHistory
+        ));
+
+        let verdict = explain(&mut ignores, Path::new("coverage/.env"));
+        assert!(verdict.ignored && verdict.ignored_as_env_file && !verdict.ignored_in_history);
+        let rule = verdict.rule.unwrap();
+        assert_eq!((rule.file.as_deref(), rule.line), (Some(".gitignore"), 3));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
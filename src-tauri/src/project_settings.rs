@@ -0,0 +1,603 @@
+//! Inherited project settings.
+//!
+//! A project's settings resolve in three layers: the organization defaults,
+//! the settings template applied to the project (if any) and the project's
+//! own overrides. `Project::settings` holds the resolved result, so code that
+//! reads it doesn't need to know about the layers; it's refreshed whenever a
+//! layer changes. A variable name template may use `<PROJECT>`, which
+//! resolves to the project's name in upper snake case.
+
+use crate::errors::KeyKeeperError;
+use crate::{
+    guards, naming, project_sync, timestamps, ApiKeyVault, AppState, Project, ProjectSettings,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use tauri::State;
+
+const FIELDS: [&str; 7] = [
+    "default_environment",
+    "auto_sync",
+    "vscode_integration",
+    "cursor_integration",
+    "notifications",
+    "env_mapping",
+    "var_name_template",
+];
+
+/// Settings of a vault that never changed its organization defaults
+pub fn builtin_defaults() -> ProjectSettings {
+    ProjectSettings {
+        default_environment: "development".to_string(),
+        auto_sync: true,
+        vscode_integration: true,
+        cursor_integration: false,
+        notifications: true,
+        env_mapping: BTreeMap::new(),
+        var_name_template: None,
+    }
+}
+
+/// Missing stays `None` (inherit), `null` becomes `Some(None)` (clear)
+fn explicit_null<'de, D>(deserializer: D) -> Result<Option<Option<String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer).map(Some)
+}
+
+/// Settings that replace the inherited ones; unset fields inherit
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SettingsOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_environment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_sync: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vscode_integration: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor_integration: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notifications: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_mapping: Option<BTreeMap<String, String>>,
+    #[serde(
+        default,
+        deserialize_with = "explicit_null",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub var_name_template: Option<Option<String>>,
+}
+
+impl SettingsOverrides {
+    fn apply(&self, settings: &mut ProjectSettings) {
+        if let Some(value) = &self.default_environment {
+            settings.default_environment = value.clone();
+        }
+        if let Some(value) = self.auto_sync {
+            settings.auto_sync = value;
+        }
+        if let Some(value) = self.vscode_integration {
+            settings.vscode_integration = value;
+        }
+        if let Some(value) = self.cursor_integration {
+            settings.cursor_integration = value;
+        }
+        if let Some(value) = self.notifications {
+            settings.notifications = value;
+        }
+        if let Some(value) = &self.env_mapping {
+            settings.env_mapping = value.clone();
+        }
+        if let Some(value) = &self.var_name_template {
+            settings.var_name_template = value.clone();
+        }
+    }
+
+    /// What `settings` changes relative to `base`
+    pub fn diff(base: &ProjectSettings, settings: &ProjectSettings) -> Self {
+        fn changed<T: PartialEq + Clone>(base: &T, value: &T) -> Option<T> {
+            (base != value).then(|| value.clone())
+        }
+        Self {
+            default_environment: changed(&base.default_environment, &settings.default_environment),
+            auto_sync: changed(&base.auto_sync, &settings.auto_sync),
+            vscode_integration: changed(&base.vscode_integration, &settings.vscode_integration),
+            cursor_integration: changed(&base.cursor_integration, &settings.cursor_integration),
+            notifications: changed(&base.notifications, &settings.notifications),
+            env_mapping: changed(&base.env_mapping, &settings.env_mapping),
+            var_name_template: changed(&base.var_name_template, &settings.var_name_template),
+        }
+    }
+
+    /// Field names this layer sets
+    fn fields(&self) -> Vec<&'static str> {
+        let set = [
+            self.default_environment.is_some(),
+            self.auto_sync.is_some(),
+            self.vscode_integration.is_some(),
+            self.cursor_integration.is_some(),
+            self.notifications.is_some(),
+            self.env_mapping.is_some(),
+            self.var_name_template.is_some(),
+        ];
+        FIELDS
+            .into_iter()
+            .zip(set)
+            .filter_map(|(field, set)| set.then_some(field))
+            .collect()
+    }
+
+    /// Drop the fields `other` sets
+    fn without(mut self, other: &SettingsOverrides) -> Self {
+        for field in other.fields() {
+            match field {
+                "default_environment" => self.default_environment = None,
+                "auto_sync" => self.auto_sync = None,
+                "vscode_integration" => self.vscode_integration = None,
+                "cursor_integration" => self.cursor_integration = None,
+                "notifications" => self.notifications = None,
+                "env_mapping" => self.env_mapping = None,
+                _ => self.var_name_template = None,
+            }
+        }
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsTemplate {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub settings: SettingsOverrides,
+    #[serde(with = "timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "timestamps::rfc3339")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Body of `save_settings_template`; a new template when `id` is unset
+#[derive(Debug, Clone, Deserialize)]
+pub struct SettingsTemplateInput {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub settings: SettingsOverrides,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingSource {
+    Organization,
+    Template,
+    Project,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveProjectSettings {
+    pub project_id: String,
+    pub template_id: Option<String>,
+    pub settings: ProjectSettings,
+    /// Layer each setting comes from
+    pub sources: BTreeMap<&'static str, SettingSource>,
+}
+
+fn expand_variables(settings: &mut ProjectSettings, project_name: &str) {
+    if let Some(template) = &mut settings.var_name_template {
+        *template = template.replace("<PROJECT>", &naming::upper_snake(project_name));
+    }
+}
+
+fn template<'a>(vault: &'a ApiKeyVault, id: &str) -> Result<&'a SettingsTemplate, KeyKeeperError> {
+    vault
+        .settings_templates
+        .iter()
+        .find(|template| template.id == id)
+        .ok_or_else(|| KeyKeeperError::not_found("Settings template"))
+}
+
+/// Organization defaults with the project's template applied, before the
+/// project's own overrides and variable expansion
+fn inherited(vault: &ApiKeyVault, project: &Project) -> (ProjectSettings, SettingsOverrides) {
+    let mut settings = vault.organization_settings.clone();
+    let template = project
+        .settings_template_id
+        .as_deref()
+        .and_then(|id| template(vault, id).ok())
+        .map(|template| template.settings.clone())
+        .unwrap_or_default();
+    template.apply(&mut settings);
+    (settings, template)
+}
+
+pub fn resolve(vault: &ApiKeyVault, project: &Project) -> EffectiveProjectSettings {
+    let (mut settings, template) = inherited(vault, project);
+    project.settings_overrides.apply(&mut settings);
+    expand_variables(&mut settings, &project.name);
+
+    let mut sources: BTreeMap<&'static str, SettingSource> = FIELDS
+        .iter()
+        .map(|field| (*field, SettingSource::Organization))
+        .collect();
+    for field in template.fields() {
+        sources.insert(field, SettingSource::Template);
+    }
+    for field in project.settings_overrides.fields() {
+        sources.insert(field, SettingSource::Project);
+    }
+
+    EffectiveProjectSettings {
+        project_id: project.id.clone(),
+        template_id: project.settings_template_id.clone(),
+        settings,
+        sources,
+    }
+}
+
+/// Recompute `Project::settings` of every project after a layer changed
+pub fn refresh(vault: &mut ApiKeyVault) {
+    let resolved: Vec<(String, ProjectSettings)> = vault
+        .projects
+        .values()
+        .map(|project| (project.id.clone(), resolve(vault, project).settings))
+        .collect();
+    for (id, settings) in resolved {
+        if let Some(project) = vault.projects.get_mut(&id) {
+            project.settings = settings;
+        }
+    }
+}
+
+/// Settings of a new project: what it inherits, with nothing overridden
+pub fn for_new_project(
+    vault: &ApiKeyVault,
+    project: &mut Project,
+    template_id: Option<String>,
+) -> Result<(), KeyKeeperError> {
+    if let Some(id) = &template_id {
+        template(vault, id)?;
+    }
+    project.settings_template_id = template_id;
+    project.settings_overrides = SettingsOverrides::default();
+    project.settings = resolve(vault, project).settings;
+    Ok(())
+}
+
+/// Store full settings sent for a project as overrides of what it inherits
+pub fn set_project_settings(
+    vault: &ApiKeyVault,
+    project: &mut Project,
+    settings: ProjectSettings,
+) -> Result<(), KeyKeeperError> {
+    project_sync::validate_settings(&settings)?;
+    let (mut base, _) = inherited(vault, project);
+    expand_variables(&mut base, &project.name);
+    project.settings_overrides = SettingsOverrides::diff(&base, &settings);
+    project.settings = resolve(vault, project).settings;
+    Ok(())
+}
+
+/// Use `template_id` for the project; overrides of the fields the template
+/// sets are dropped so its values take effect
+pub fn apply_template(
+    vault: &mut ApiKeyVault,
+    project_id: &str,
+    template_id: &str,
+) -> Result<Project, KeyKeeperError> {
+    let template_settings = template(vault, template_id)?.settings.clone();
+    let mut project = vault
+        .projects
+        .get(project_id)
+        .cloned()
+        .ok_or_else(|| KeyKeeperError::not_found("Project"))?;
+    project.settings_template_id = Some(template_id.to_string());
+    project.settings_overrides = project.settings_overrides.without(&template_settings);
+    project.settings = resolve(vault, &project).settings;
+    project.updated_at = Utc::now();
+    vault.projects.insert(project.id.clone(), project.clone());
+    Ok(project)
+}
+
+/// Check a layer by resolving it for a sample project
+fn validate_overrides(
+    base: &ProjectSettings,
+    overrides: &SettingsOverrides,
+) -> Result<(), KeyKeeperError> {
+    let mut settings = base.clone();
+    overrides.apply(&mut settings);
+    expand_variables(&mut settings, "project");
+    project_sync::validate_settings(&settings)
+}
+
+pub fn save_template(
+    vault: &mut ApiKeyVault,
+    input: SettingsTemplateInput,
+    now: DateTime<Utc>,
+) -> Result<SettingsTemplate, KeyKeeperError> {
+    let name = input.name.trim().to_string();
+    if name.is_empty() {
+        return Err(KeyKeeperError::Validation(
+            "A settings template needs a name".to_string(),
+        ));
+    }
+    validate_overrides(&vault.organization_settings, &input.settings)?;
+    if vault
+        .settings_templates
+        .iter()
+        .any(|t| t.name.eq_ignore_ascii_case(&name) && Some(&t.id) != input.id.as_ref())
+    {
+        return Err(KeyKeeperError::Conflict(format!(
+            "A settings template named '{}' already exists",
+            name
+        )));
+    }
+
+    let template = match input.id {
+        Some(id) => {
+            let existing = vault
+                .settings_templates
+                .iter_mut()
+                .find(|t| t.id == id)
+                .ok_or_else(|| KeyKeeperError::not_found("Settings template"))?;
+            existing.name = name;
+            existing.description = input.description;
+            existing.settings = input.settings;
+            existing.updated_at = now;
+            existing.clone()
+        }
+        None => {
+            let template = SettingsTemplate {
+                id: uuid::Uuid::new_v4().to_string(),
+                name,
+                description: input.description,
+                settings: input.settings,
+                created_at: now,
+                updated_at: now,
+            };
+            vault.settings_templates.push(template.clone());
+            template
+        }
+    };
+    refresh(vault);
+    Ok(template)
+}
+
+/// v4 -> v5: projects had only resolved settings; keep them by storing what
+/// differs from the built-in defaults as the project's overrides
+pub fn migrate_project_overrides(document: &mut Value) -> Result<(), String> {
+    let Some(projects) = document.get_mut("projects").and_then(Value::as_object_mut) else {
+        return Ok(());
+    };
+    for project in projects.values_mut() {
+        let Some(project) = project.as_object_mut() else {
+            continue;
+        };
+        let Some(settings) = project.get("settings") else {
+            continue;
+        };
+        let settings: ProjectSettings = serde_json::from_value(settings.clone())
+            .map_err(|e| format!("Invalid project settings: {}", e))?;
+        let overrides = SettingsOverrides::diff(&builtin_defaults(), &settings);
+        project.insert(
+            "settings_overrides".to_string(),
+            serde_json::to_value(overrides).map_err(|e| e.to_string())?,
+        );
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_organization_settings(
+    state: State<'_, AppState>,
+) -> Result<ProjectSettings, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    Ok(state.vault.lock().await.organization_settings.clone())
+}
+
+#[tauri::command]
+pub async fn update_organization_settings(
+    settings: ProjectSettings,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    validate_overrides(&settings, &SettingsOverrides::default())?;
+
+    let mut vault_guard = state.vault.lock().await;
+    vault_guard.organization_settings = settings;
+    refresh(&mut vault_guard);
+    drop(vault_guard);
+
+    crate::save_vault(&state).await?;
+    crate::log_audit_event(
+        &state,
+        "update_organization_settings",
+        "project",
+        None,
+        true,
+        None,
+    )
+    .await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_settings_templates(
+    state: State<'_, AppState>,
+) -> Result<Vec<SettingsTemplate>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    Ok(state.vault.lock().await.settings_templates.clone())
+}
+
+#[tauri::command]
+pub async fn save_settings_template(
+    template: SettingsTemplateInput,
+    state: State<'_, AppState>,
+) -> Result<SettingsTemplate, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let template = save_template(&mut vault_guard, template, Utc::now())?;
+    drop(vault_guard);
+
+    crate::save_vault(&state).await?;
+    crate::log_audit_event(
+        &state,
+        "save_settings_template",
+        "settings_template",
+        Some(&template.id),
+        true,
+        None,
+    )
+    .await;
+    Ok(template)
+}
+
+/// Projects using the template go back to the organization defaults
+#[tauri::command]
+pub async fn delete_settings_template(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let before = vault_guard.settings_templates.len();
+    vault_guard.settings_templates.retain(|t| t.id != id);
+    if vault_guard.settings_templates.len() == before {
+        return Err(KeyKeeperError::not_found("Settings template"));
+    }
+    for project in vault_guard.projects.values_mut() {
+        if project.settings_template_id.as_deref() == Some(id.as_str()) {
+            project.settings_template_id = None;
+        }
+    }
+    refresh(&mut vault_guard);
+    drop(vault_guard);
+
+    crate::save_vault(&state).await?;
+    crate::log_audit_event(
+        &state,
+        "delete_settings_template",
+        "settings_template",
+        Some(&id),
+        true,
+        None,
+    )
+    .await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn apply_settings_template(
+    project_id: String,
+    template_id: String,
+    state: State<'_, AppState>,
+) -> Result<Project, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let project = apply_template(&mut vault_guard, &project_id, &template_id)?;
+    drop(vault_guard);
+
+    crate::save_vault(&state).await?;
+    crate::log_audit_event(
+        &state,
+        "apply_settings_template",
+        "project",
+        Some(&project_id),
+        true,
+        None,
+    )
+    .await;
+    Ok(project)
+}
+
+#[tauri::command]
+pub async fn get_effective_project_settings(
+    project_id: String,
+    state: State<'_, AppState>,
+) -> Result<EffectiveProjectSettings, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    let project = vault_guard
+        .projects
+        .get(&project_id)
+        .ok_or_else(|| KeyKeeperError::not_found("Project"))?;
+    Ok(resolve(&vault_guard, project))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(name: &str) -> Project {
+        serde_json::from_value(serde_json::json!({
+            "id": name, "name": name, "description": null, "path": format!("/work/{}", name),
+            "created_at": "2024-01-01T00:00:00Z", "updated_at": "2024-01-01T00:00:00Z",
+            "settings": builtin_defaults()
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn settings_resolve_through_organization_template_and_project() {
+        let mut vault = ApiKeyVault::default();
+        vault.organization_settings.auto_sync = false;
+        let template = save_template(
+            &mut vault,
+            SettingsTemplateInput {
+                id: None,
+                name: "Services".to_string(),
+                description: None,
+                settings: serde_json::from_value(serde_json::json!({
+                    "default_environment": "staging",
+                    "var_name_template": "<PROJECT>_<SERVICE>_KEY"
+                }))
+                .unwrap(),
+            },
+            Utc::now(),
+        )
+        .unwrap();
+
+        let mut shop = project("web shop");
+        for_new_project(&vault, &mut shop, Some(template.id.clone())).unwrap();
+        let mut settings = shop.settings.clone();
+        assert_eq!(settings.default_environment, "staging");
+        assert_eq!(
+            settings.var_name_template.as_deref(),
+            Some("WEB_SHOP_<SERVICE>_KEY")
+        );
+        assert!(!settings.auto_sync);
+
+        settings.notifications = false;
+        set_project_settings(&vault, &mut shop, settings).unwrap();
+        assert_eq!(
+            shop.settings_overrides,
+            SettingsOverrides {
+                notifications: Some(false),
+                ..Default::default()
+            }
+        );
+        vault.projects.insert(shop.id.clone(), shop);
+
+        vault.organization_settings.auto_sync = true;
+        refresh(&mut vault);
+        let effective = resolve(&vault, &vault.projects["web shop"]);
+        assert!(effective.settings.auto_sync);
+        assert_eq!(effective.sources["auto_sync"], SettingSource::Organization);
+        assert_eq!(
+            effective.sources["default_environment"],
+            SettingSource::Template
+        );
+        assert_eq!(effective.sources["notifications"], SettingSource::Project);
+    }
+}
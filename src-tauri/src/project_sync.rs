@@ -0,0 +1,562 @@
+//! Workspace-wide `.env` sync.
+//!
+//! `sync_all_projects` syncs every registered project (or the ones asked
+//! for, or a project group) the way `auto_sync_workspace_env_files` syncs a single workspace,
+//! several at a time. Each project's `env_mapping` decides which `.env`
+//! file receives the keys of which environment, and `var_name_template`
+//! how the variables are named. The vault is only locked while the work is
+//! planned; files are written without it, and a `project-sync-progress`
+//! event is emitted as each project finishes. Production keys bound for
+//! non-production files are confirmed first, see `sync_policy`. While an
+//! incident is open, auto-sync is off for every project.
+
+use crate::docker_env::normalize_environment;
+use crate::env_file::{PendingWrite, SetOutcome, SyncPreview};
+use crate::env_refs::{self, EnvSyncMode};
+use crate::errors::KeyKeeperError;
+use crate::sync_policy::{self, PolicyViolation};
+use crate::{
+    archive, env_history, env_values, guards, incident, log_audit_event, monorepo, naming,
+    project_groups, tray_status, ApiKey, AppState, Project,
+};
+use crate::{env_var_name_for_key, ProjectSettings};
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::{info, warn};
+
+/// Files synced when a project has no `env_mapping`, if they already exist
+const DEFAULT_ENV_FILES: [&str; 3] = [".env", ".env.local", ".env.development"];
+const DEFAULT_CONCURRENCY: usize = 4;
+const MAX_CONCURRENCY: usize = 16;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncAllOptions {
+    /// Projects to sync; all of them when `None`
+    #[serde(default)]
+    pub project_ids: Option<Vec<String>>,
+    /// Only projects in this group and its subgroups
+    #[serde(default)]
+    pub group_id: Option<String>,
+    /// Projects synced at the same time, 1 to 16
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+    /// Report what would change without writing anything
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Also sync projects that have `auto_sync` turned off
+    #[serde(default)]
+    pub include_manual: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectSyncStatus {
+    Synced,
+    UpToDate,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectSyncResult {
+    pub project_id: String,
+    pub project_name: String,
+    pub project_path: String,
+    pub status: ProjectSyncStatus,
+    /// Files that changed, or would change on a dry run. Diffs are only
+    /// filled in on a dry run.
+    pub files: Vec<SyncPreview>,
+    /// Why the project was skipped or failed
+    pub message: Option<String>,
+    /// Production keys written, or that would be, into non-production files
+    #[serde(default)]
+    pub violations: Vec<PolicyViolation>,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectSyncProgress {
+    pub completed: usize,
+    pub total: usize,
+    pub result: ProjectSyncResult,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncAllSummary {
+    pub dry_run: bool,
+    pub synced: usize,
+    pub up_to_date: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub results: Vec<ProjectSyncResult>,
+}
+
+/// Variable a key is written to in this project's `.env` files. `<ENV>` is
+/// the file's environment when it has one.
+pub fn var_name(settings: &ProjectSettings, key: &ApiKey, environment: Option<&str>) -> String {
+    match settings.var_name_template.as_deref() {
+        Some(template) => {
+            let mut template = template.replace("<NAME>", &naming::upper_snake(&key.name));
+            if let Some(environment) = environment {
+                template = template.replace(
+                    "<ENV>",
+                    &naming::upper_snake(&normalize_environment(environment)),
+                );
+            }
+            naming::render_template(&template, key)
+        }
+        None => env_var_name_for_key(key),
+    }
+}
+
+pub fn validate_settings(settings: &ProjectSettings) -> Result<(), KeyKeeperError> {
+    for (file, environment) in &settings.env_mapping {
+        let path = Path::new(file);
+        if file.trim().is_empty()
+            || !path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(KeyKeeperError::Validation(format!(
+                "Env file '{}' must be a path inside the project",
+                file
+            )));
+        }
+        if environment.trim().is_empty() {
+            return Err(KeyKeeperError::Validation(format!(
+                "Env file '{}' has no environment",
+                file
+            )));
+        }
+    }
+
+    if let Some(template) = &settings.var_name_template {
+        let rest = template
+            .replace("<SERVICE>", "")
+            .replace("<ENV>", "")
+            .replace("<NAME>", "");
+        if template.trim().is_empty()
+            || !rest.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(KeyKeeperError::Validation(format!(
+                "Invalid variable name template '{}'",
+                template
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// The `.env` writes that bring `project` in line with `keys`, and the
+/// production keys they put into non-production files. Without an
+/// `env_mapping`, every key goes to the default files that already exist;
+/// with one, each mapped file gets the keys with a value in its environment
+/// and is created if needed.
+pub fn plan_env_writes(
+    project: &Project,
+    keys: &[&ApiKey],
+    mode: EnvSyncMode,
+) -> (Vec<PendingWrite>, Vec<PolicyViolation>) {
+    // Keys land in the .env files of their package, or of the project root
+    let key_dir = |key: &ApiKey| monorepo::package_dir(&project.path, key.package_path.as_deref());
+    let mut key_dirs: Vec<PathBuf> = keys.iter().map(|key| key_dir(key)).collect();
+    key_dirs.sort();
+    key_dirs.dedup();
+
+    let targets: Vec<(&str, Option<&str>)> = if project.settings.env_mapping.is_empty() {
+        DEFAULT_ENV_FILES.iter().map(|file| (*file, None)).collect()
+    } else {
+        project
+            .settings
+            .env_mapping
+            .iter()
+            .map(|(file, environment)| (file.as_str(), Some(environment.as_str())))
+            .collect()
+    };
+
+    let mut writes = Vec::new();
+    let mut violations = Vec::new();
+    for dir in &key_dirs {
+        for (file_name, environment) in &targets {
+            let file_keys: Vec<&ApiKey> = keys
+                .iter()
+                .copied()
+                .filter(|key| key.kind.is_env_value() && key_dir(key) == *dir)
+                .filter(|key| environment.is_none_or(|env| env_values::has_environment(key, env)))
+                .collect();
+            let env_path = dir.join(file_name);
+            if file_keys.is_empty() || (environment.is_none() && !env_path.exists()) {
+                continue;
+            }
+            let mut pending = match PendingWrite::open(&env_path) {
+                Ok(pending) => pending,
+                Err(e) => {
+                    warn!("Failed to read {}: {}", env_path.display(), e);
+                    continue;
+                }
+            };
+
+            for key in file_keys {
+                let var_name = var_name(&project.settings, key, *environment);
+
+                // A commented-out variable was disabled on purpose
+                if pending.file().is_commented_out(&var_name) {
+                    continue;
+                }
+                let current = pending.file().get(&var_name);
+                let value = match environment {
+                    Some(env) => env_refs::sync_value_in(mode, key, env, current),
+                    None => Some(env_refs::sync_value(mode, &key.id, &key.key, current)),
+                };
+                let Some(value) = value else {
+                    continue;
+                };
+                // Unmapped files get the key's own value, whatever they're for
+                if pending.set(&var_name, &value) != SetOutcome::Unchanged && environment.is_none()
+                {
+                    let target = sync_policy::target_environment(Some(project), &env_path);
+                    violations.extend(sync_policy::check(
+                        key,
+                        &env_path.to_string_lossy(),
+                        target.as_deref(),
+                    ));
+                }
+            }
+            writes.push(pending);
+        }
+    }
+    (writes, violations)
+}
+
+/// One project's share of the work, taken from the vault up front
+struct SyncJob {
+    project: Project,
+    keys: Vec<ApiKey>,
+    /// Set when the project is reported without being synced
+    skip: Option<String>,
+    violations: Vec<PolicyViolation>,
+}
+
+fn sync_project(
+    job: &SyncJob,
+    snapshot_key: &env_history::SnapshotKey,
+    mode: EnvSyncMode,
+    dry_run: bool,
+) -> (ProjectSyncStatus, Vec<SyncPreview>, Option<String>) {
+    if let Some(reason) = &job.skip {
+        return (ProjectSyncStatus::Skipped, Vec::new(), Some(reason.clone()));
+    }
+    if !Path::new(&job.project.path).is_dir() {
+        return (
+            ProjectSyncStatus::Failed,
+            Vec::new(),
+            Some("Project directory not found".to_string()),
+        );
+    }
+
+    let keys: Vec<&ApiKey> = job.keys.iter().collect();
+    let changed: Vec<PendingWrite> = plan_env_writes(&job.project, &keys, mode)
+        .0
+        .into_iter()
+        .filter(|pending| pending.is_changed())
+        .collect();
+    if changed.is_empty() {
+        return (ProjectSyncStatus::UpToDate, Vec::new(), None);
+    }
+    if dry_run {
+        let previews = changed.iter().map(|pending| pending.preview()).collect();
+        return (ProjectSyncStatus::Synced, previews, None);
+    }
+
+    let mut files = Vec::new();
+    for pending in &changed {
+        if let Err(e) = env_history::commit_with_key(
+            pending,
+            snapshot_key,
+            Some(&job.project.path),
+            "sync_all_projects",
+        ) {
+            warn!("Failed to sync {}: {}", pending.path.display(), e);
+            return (
+                ProjectSyncStatus::Failed,
+                files,
+                Some(format!("{}: {}", pending.path.display(), e)),
+            );
+        }
+        files.push(SyncPreview {
+            diff: String::new(),
+            ..pending.preview()
+        });
+    }
+    (ProjectSyncStatus::Synced, files, None)
+}
+
+#[tauri::command]
+pub async fn sync_all_projects(
+    options: Option<SyncAllOptions>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<SyncAllSummary, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let _syncing = tray_status::begin_sync();
+    let options = options.unwrap_or_default();
+    let concurrency = options
+        .concurrency
+        .unwrap_or(DEFAULT_CONCURRENCY)
+        .clamp(1, MAX_CONCURRENCY);
+
+    let (mut jobs, snapshot_key, mode) = {
+        let vault_guard = state.vault.lock().await;
+        if let Some(ids) = &options.project_ids {
+            if let Some(missing) = ids
+                .iter()
+                .find(|id| !vault_guard.projects.contains_key(*id))
+            {
+                return Err(KeyKeeperError::NotFound(format!("Project {}", missing)));
+            }
+        }
+
+        let group: Option<Vec<String>> = match &options.group_id {
+            Some(id) => Some(
+                project_groups::projects_in(&vault_guard, id)?
+                    .iter()
+                    .map(|p| p.id.clone())
+                    .collect(),
+            ),
+            None => None,
+        };
+        let mut projects: Vec<&Project> = vault_guard
+            .projects
+            .values()
+            .filter(|p| group.as_ref().is_none_or(|ids| ids.contains(&p.id)))
+            .filter(|p| {
+                options
+                    .project_ids
+                    .as_ref()
+                    .is_none_or(|ids| ids.contains(&p.id))
+            })
+            .collect();
+        projects.sort_by(|a, b| a.name.cmp(&b.name));
+        let auto_sync_paused = incident::auto_sync_paused(&vault_guard);
+
+        let jobs: Vec<SyncJob> = projects
+            .into_iter()
+            .map(|project| {
+                let keys: Vec<ApiKey> = vault_guard
+                    .keys
+                    .values()
+                    .filter(|key| key.is_active && !archive::is_archived(key))
+                    .filter(|key| key.project_path.as_ref() == Some(&project.path))
+                    .cloned()
+                    .collect();
+                let auto_sync = project.settings.auto_sync && !auto_sync_paused;
+                let skip = if !auto_sync && !options.include_manual && options.project_ids.is_none()
+                {
+                    Some("Auto sync is turned off".to_string())
+                } else if keys.is_empty() {
+                    Some("No keys are assigned to this project".to_string())
+                } else {
+                    None
+                };
+                SyncJob {
+                    project: project.clone(),
+                    keys,
+                    skip,
+                    violations: Vec::new(),
+                }
+            })
+            .collect();
+        (
+            jobs,
+            Arc::new(env_history::snapshot_key(&vault_guard).map_err(KeyKeeperError::Crypto)?),
+            env_refs::sync_mode(&vault_guard),
+        )
+    };
+
+    for job in jobs.iter_mut().filter(|job| job.skip.is_none()) {
+        let keys: Vec<&ApiKey> = job.keys.iter().collect();
+        job.violations = plan_env_writes(&job.project, &keys, mode).1;
+    }
+    if !options.dry_run {
+        let violations: Vec<PolicyViolation> = jobs
+            .iter()
+            .flat_map(|job| job.violations.iter().cloned())
+            .collect();
+        sync_policy::confirm(
+            &state.confirmations,
+            &state.vault,
+            &*state.storage,
+            "KeyKeeper",
+            &violations,
+        )
+        .await?;
+    }
+
+    let total = jobs.len();
+    let semaphore = Arc::new(Semaphore::new(concurrency));
+    let mut tasks = JoinSet::new();
+    for job in jobs {
+        let semaphore = semaphore.clone();
+        let snapshot_key = snapshot_key.clone();
+        let dry_run = options.dry_run;
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let started = Instant::now();
+            let job = Arc::new(job);
+            let worker = job.clone();
+            let (status, files, message) = tauri::async_runtime::spawn_blocking(move || {
+                sync_project(&worker, &snapshot_key, mode, dry_run)
+            })
+            .await
+            .unwrap_or_else(|e| {
+                (
+                    ProjectSyncStatus::Failed,
+                    Vec::new(),
+                    Some(format!("Sync task failed: {}", e)),
+                )
+            });
+            ProjectSyncResult {
+                project_id: job.project.id.clone(),
+                project_name: job.project.name.clone(),
+                project_path: job.project.path.clone(),
+                status,
+                files,
+                message,
+                violations: job.violations.clone(),
+                duration_ms: started.elapsed().as_millis() as u64,
+            }
+        });
+    }
+
+    let mut results = Vec::with_capacity(total);
+    while let Some(joined) = tasks.join_next().await {
+        let result = match joined {
+            Ok(result) => result,
+            Err(e) => {
+                warn!("Project sync task panicked: {}", e);
+                continue;
+            }
+        };
+        let progress = ProjectSyncProgress {
+            completed: results.len() + 1,
+            total,
+            result: result.clone(),
+        };
+        if let Err(e) = app.emit("project-sync-progress", &progress) {
+            warn!("Failed to emit sync progress: {}", e);
+        }
+        results.push(result);
+    }
+    results.sort_by(|a, b| a.project_name.cmp(&b.project_name));
+
+    let count = |status| results.iter().filter(|r| r.status == status).count();
+    let summary = SyncAllSummary {
+        dry_run: options.dry_run,
+        synced: count(ProjectSyncStatus::Synced),
+        up_to_date: count(ProjectSyncStatus::UpToDate),
+        skipped: count(ProjectSyncStatus::Skipped),
+        failed: count(ProjectSyncStatus::Failed),
+        results,
+    };
+    info!(
+        "Synced {} project(s): {} changed, {} up to date, {} skipped, {} failed",
+        total, summary.synced, summary.up_to_date, summary.skipped, summary.failed
+    );
+    if !options.dry_run {
+        log_audit_event(
+            &state,
+            "sync_all_projects",
+            "project",
+            None,
+            summary.failed == 0,
+            Some(&format!(
+                "{} synced, {} failed",
+                summary.synced, summary.failed
+            )),
+        )
+        .await;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault_tests::test_key;
+
+    fn project(path: &Path, settings: serde_json::Value) -> Project {
+        serde_json::from_value(serde_json::json!({
+            "id": "p1", "name": "Shop", "description": null,
+            "path": path.to_string_lossy(),
+            "created_at": "2024-01-01T00:00:00Z", "updated_at": "2024-01-01T00:00:00Z",
+            "settings": settings
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn mapped_files_get_the_keys_of_their_environment() {
+        let root = std::env::temp_dir().join(format!("kk-project-sync-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let project = project(
+            &root,
+            serde_json::json!({
+                "default_environment": "development", "auto_sync": true,
+                "vscode_integration": false, "cursor_integration": false, "notifications": false,
+                "env_mapping": { ".env.production": "Production", ".env": "development" },
+                "var_name_template": "<SERVICE>_<ENV>_KEY"
+            }),
+        );
+        let dev = test_key("dev")
+            .name("Stripe secret")
+            .secret("sk_dev")
+            .build();
+        let prod = test_key("prod")
+            .name("Stripe secret")
+            .secret("sk_prod")
+            .environment("production")
+            .build();
+
+        let (writes, violations) =
+            plan_env_writes(&project, &[&dev, &prod], EnvSyncMode::Plaintext);
+        assert!(violations.is_empty());
+        assert_eq!(writes.len(), 2);
+        let prod_file = writes
+            .iter()
+            .find(|w| w.path.ends_with(".env.production"))
+            .unwrap();
+        assert_eq!(prod_file.added, vec!["STRIPE_PRODUCTION_KEY".to_string()]);
+        assert_eq!(
+            prod_file.file().get("STRIPE_PRODUCTION_KEY"),
+            Some("sk_prod")
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn settings_reject_paths_outside_the_project_and_bad_templates() {
+        let mut settings: ProjectSettings = serde_json::from_value(serde_json::json!({
+            "default_environment": "development", "auto_sync": true,
+            "vscode_integration": false, "cursor_integration": false, "notifications": false
+        }))
+        .unwrap();
+        assert!(validate_settings(&settings).is_ok());
+
+        settings
+            .env_mapping
+            .insert("../other/.env".to_string(), "development".to_string());
+        assert!(validate_settings(&settings).is_err());
+
+        settings.env_mapping.clear();
+        settings.var_name_template = Some("<SERVICE>-KEY".to_string());
+        assert!(validate_settings(&settings).is_err());
+        settings.var_name_template = Some("<NAME>_<ENV>".to_string());
+        assert!(validate_settings(&settings).is_ok());
+    }
+}
@@ -0,0 +1,570 @@
+//! Editable prompts for the LLM features.
+//!
+//! Each feature that prompts a model (key docs, provider docs, usage
+//! examples, config templates, project suggestions) has a built-in template
+//! with `{{variable}}` placeholders. Users can edit a built-in template,
+//! add their own named templates for a feature and pick which one the
+//! feature uses. Every edit bumps the template's version and keeps the
+//! previous body, so an edit can be rolled back; resetting a built-in
+//! template drops the edits.
+//!
+//! Edits live in `prompt_templates.json` in the app data dir. Features read
+//! their prompt with `render`, which falls back to the built-in template
+//! when nothing was changed.
+
+use crate::errors::KeyKeeperError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, OnceLock, RwLock};
+use tracing::warn;
+
+pub const FILE: &str = "prompt_templates.json";
+const MAX_BODY_LEN: usize = 20_000;
+/// Earlier bodies kept per template
+const MAX_HISTORY: usize = 10;
+
+pub const KEY_DOCUMENTATION: &str = "key_documentation";
+pub const KEY_DOCUMENTATION_SYSTEM: &str = "key_documentation_system";
+pub const PROVIDER_DOCUMENTATION: &str = "provider_documentation";
+pub const USAGE_EXAMPLES: &str = "usage_examples";
+pub const CONFIG_TEMPLATE: &str = "config_template";
+pub const PROJECT_SUGGESTIONS: &str = "project_suggestions";
+
+struct Builtin {
+    feature: &'static str,
+    name: &'static str,
+    description: &'static str,
+    variables: &'static [&'static str],
+    body: &'static str,
+}
+
+const BUILTINS: &[Builtin] = &[
+    Builtin {
+        feature: KEY_DOCUMENTATION,
+        name: "Key documentation",
+        description: "Drafts a doc for a key from what the vault knows about it",
+        variables: &["key_name", "facts"],
+        body: "Draft a documentation page for the key `{{key_name}}`.\n\n\
+               Facts:\n{{facts}}\n\n\
+               Write these sections: \"What it is\", \"Scopes\", \"Where it's used\" \
+               and \"Rotation\". Keep it under 300 words and start with the first heading.",
+    },
+    Builtin {
+        feature: KEY_DOCUMENTATION_SYSTEM,
+        name: "Key documentation (system)",
+        description: "System prompt for drafting key docs",
+        variables: &[],
+        body: "You write concise internal documentation for API keys in Markdown. \
+               Use only the facts given; say so when something isn't known instead \
+               of guessing. Never include secret values.",
+    },
+    Builtin {
+        feature: PROVIDER_DOCUMENTATION,
+        name: "Provider documentation",
+        description: "Generates documentation for an API provider",
+        variables: &["provider", "context"],
+        body: "Generate comprehensive API documentation for the {{provider}} provider. \n\n\
+               Context: {{context}}\n\n\
+               Include:\n\
+               1. Overview and description\n\
+               2. Authentication methods\n\
+               3. Common endpoints and usage examples\n\
+               4. Configuration examples\n\
+               5. Best practices\n\n\
+               Documentation:",
+    },
+    Builtin {
+        feature: USAGE_EXAMPLES,
+        name: "Usage examples",
+        description: "Generates code examples for using a provider's API",
+        variables: &["provider", "key_format"],
+        body: "Generate 3 practical code examples for using the {{provider}} API with the \
+               key format: {{key_format}}.\n\n\
+               Provide examples in different programming languages (Python, JavaScript, curl) \
+               showing:\n\
+               1. Basic API call\n\
+               2. Authentication setup\n\
+               3. Error handling\n\n\
+               Examples:",
+    },
+    Builtin {
+        feature: CONFIG_TEMPLATE,
+        name: "Configuration template",
+        description: "Generates a configuration template for a provider",
+        variables: &["provider", "environment"],
+        body: "Generate a configuration template for {{provider}} API in {{environment}} \
+               environment.\n\n\
+               Include:\n\
+               1. Environment variables setup\n\
+               2. Configuration file examples\n\
+               3. Security best practices\n\
+               4. Common settings\n\n\
+               Template:",
+    },
+    Builtin {
+        feature: PROJECT_SUGGESTIONS,
+        name: "Project suggestions",
+        description: "Introduces the project analysis before the JSON answer format",
+        variables: &["analysis"],
+        body: "You are an expert software consultant providing contextual suggestions for \
+               project improvement.\n\n{{analysis}}",
+    },
+];
+
+fn builtin(feature: &str) -> Option<&'static Builtin> {
+    BUILTINS.iter().find(|b| b.feature == feature)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TemplateVersion {
+    pub version: u32,
+    pub body: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub saved_at: DateTime<Utc>,
+}
+
+/// An edited built-in template (keyed by its feature) or a user template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredTemplate {
+    feature: String,
+    name: String,
+    body: String,
+    version: u32,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    updated_at: DateTime<Utc>,
+    /// Oldest first
+    #[serde(default)]
+    history: Vec<TemplateVersion>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Stored {
+    #[serde(default)]
+    templates: BTreeMap<String, StoredTemplate>,
+    /// Feature -> template it uses instead of its built-in one
+    #[serde(default)]
+    defaults: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub id: String,
+    pub feature: String,
+    pub name: String,
+    pub description: String,
+    /// Placeholders the body can use
+    pub variables: Vec<String>,
+    pub body: String,
+    pub version: u32,
+    /// The feature's built-in template, which can be reset but not deleted
+    pub builtin: bool,
+    /// Differs from what ships with the app
+    pub customized: bool,
+    /// The template its feature uses
+    pub active: bool,
+    #[serde(default, with = "crate::timestamps::rfc3339_option")]
+    pub updated_at: Option<DateTime<Utc>>,
+    pub history: Vec<TemplateVersion>,
+}
+
+static STORE: LazyLock<RwLock<Stored>> = LazyLock::new(|| RwLock::new(Stored::default()));
+static PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Load edited templates. Called once at startup.
+pub fn init(app_data_dir: &Path) {
+    let path = app_data_dir.join(FILE);
+    if let Ok(contents) = std::fs::read_to_string(&path) {
+        match serde_json::from_str::<Stored>(&contents) {
+            Ok(stored) => *STORE.write().unwrap() = stored,
+            Err(e) => warn!("Ignoring unreadable prompt templates: {}", e),
+        }
+    }
+    let _ = PATH.set(path);
+}
+
+fn persist(stored: &Stored) -> Result<(), KeyKeeperError> {
+    let Some(path) = PATH.get() else {
+        return Ok(());
+    };
+    let json = serde_json::to_string_pretty(stored)
+        .map_err(|e| KeyKeeperError::Internal(format!("Failed to serialize templates: {}", e)))?;
+    crate::vault_location::write_atomic(path, json.as_bytes())?;
+    Ok(())
+}
+
+/// Names inside `{{ }}` in `body`
+pub fn placeholders(body: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = body;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + end].trim().to_string();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+        rest = &rest[start + end + 2..];
+    }
+    names
+}
+
+pub fn substitute(body: &str, vars: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let name = rest[start + 2..start + end].trim();
+        match vars.iter().find(|(var, _)| *var == name) {
+            Some((_, value)) => out.push_str(value),
+            None => out.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &rest[start + end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+impl Stored {
+    /// Body a feature currently uses
+    fn body(&self, feature: &str) -> Option<String> {
+        let chosen = self
+            .defaults
+            .get(feature)
+            .and_then(|id| self.templates.get(id))
+            .or_else(|| self.templates.get(feature));
+        chosen
+            .map(|t| t.body.clone())
+            .or_else(|| builtin(feature).map(|b| b.body.to_string()))
+    }
+
+    fn active_id(&self, feature: &str) -> String {
+        self.defaults
+            .get(feature)
+            .filter(|id| self.templates.contains_key(*id))
+            .cloned()
+            .unwrap_or_else(|| feature.to_string())
+    }
+
+    pub fn list(&self) -> Vec<PromptTemplate> {
+        let mut out = Vec::new();
+        for b in BUILTINS {
+            let stored = self.templates.get(b.feature);
+            out.push(PromptTemplate {
+                id: b.feature.to_string(),
+                feature: b.feature.to_string(),
+                name: b.name.to_string(),
+                description: b.description.to_string(),
+                variables: b.variables.iter().map(|v| v.to_string()).collect(),
+                body: stored.map_or_else(|| b.body.to_string(), |t| t.body.clone()),
+                version: stored.map_or(1, |t| t.version),
+                builtin: true,
+                customized: stored.is_some(),
+                active: self.active_id(b.feature) == b.feature,
+                updated_at: stored.map(|t| t.updated_at),
+                history: stored.map(|t| t.history.clone()).unwrap_or_default(),
+            });
+            for (id, t) in &self.templates {
+                if t.feature != b.feature || id == b.feature {
+                    continue;
+                }
+                out.push(PromptTemplate {
+                    id: id.clone(),
+                    feature: t.feature.clone(),
+                    name: t.name.clone(),
+                    description: b.description.to_string(),
+                    variables: b.variables.iter().map(|v| v.to_string()).collect(),
+                    body: t.body.clone(),
+                    version: t.version,
+                    builtin: false,
+                    customized: true,
+                    active: self.active_id(b.feature) == *id,
+                    updated_at: Some(t.updated_at),
+                    history: t.history.clone(),
+                });
+            }
+        }
+        out
+    }
+
+    /// Create a template (`id` is `None`) or edit one. Returns its ID.
+    pub fn save(
+        &mut self,
+        id: Option<&str>,
+        feature: &str,
+        name: &str,
+        body: &str,
+        now: DateTime<Utc>,
+    ) -> Result<String, KeyKeeperError> {
+        let b = builtin(feature).ok_or_else(|| {
+            KeyKeeperError::Validation(format!("Unknown prompt feature '{}'", feature))
+        })?;
+        if body.trim().is_empty() || body.len() > MAX_BODY_LEN {
+            return Err(KeyKeeperError::Validation(format!(
+                "A prompt must have between 1 and {} characters",
+                MAX_BODY_LEN
+            )));
+        }
+        let unknown: Vec<String> = placeholders(body)
+            .into_iter()
+            .filter(|p| !b.variables.contains(&p.as_str()))
+            .collect();
+        if !unknown.is_empty() {
+            return Err(KeyKeeperError::Validation(format!(
+                "Unknown variables {}; this prompt can use {}",
+                unknown.join(", "),
+                if b.variables.is_empty() {
+                    "none".to_string()
+                } else {
+                    b.variables.join(", ")
+                }
+            )));
+        }
+
+        let id = match id {
+            Some(id) if id == feature => id.to_string(),
+            Some(id) => {
+                let existing = self
+                    .templates
+                    .get(id)
+                    .ok_or_else(|| KeyKeeperError::not_found("Prompt template"))?;
+                if existing.feature != feature {
+                    return Err(KeyKeeperError::Validation(
+                        "A template can't move to another feature".to_string(),
+                    ));
+                }
+                id.to_string()
+            }
+            None => format!(
+                "{}:{}",
+                feature,
+                &uuid::Uuid::new_v4().simple().to_string()[..8]
+            ),
+        };
+        let name = if name.trim().is_empty() {
+            b.name.to_string()
+        } else {
+            name.trim().to_string()
+        };
+
+        match self.templates.get_mut(&id) {
+            Some(t) => {
+                if t.body != body {
+                    t.history.push(TemplateVersion {
+                        version: t.version,
+                        body: std::mem::replace(&mut t.body, body.to_string()),
+                        saved_at: t.updated_at,
+                    });
+                    if t.history.len() > MAX_HISTORY {
+                        t.history.remove(0);
+                    }
+                    t.version += 1;
+                }
+                t.name = name;
+                t.updated_at = now;
+            }
+            None => {
+                // An edited built-in template starts at version 2 and
+                // remembers the shipped body as version 1
+                let history = if id == feature {
+                    vec![TemplateVersion {
+                        version: 1,
+                        body: b.body.to_string(),
+                        saved_at: now,
+                    }]
+                } else {
+                    Vec::new()
+                };
+                self.templates.insert(
+                    id.clone(),
+                    StoredTemplate {
+                        feature: feature.to_string(),
+                        name,
+                        body: body.to_string(),
+                        version: history.len() as u32 + 1,
+                        updated_at: now,
+                        history,
+                    },
+                );
+            }
+        }
+        Ok(id)
+    }
+
+    /// Drop the edits of a built-in template, or delete a user template
+    pub fn reset(&mut self, id: &str) -> Result<(), KeyKeeperError> {
+        if builtin(id).is_some() {
+            self.templates.remove(id);
+            return Ok(());
+        }
+        let removed = self
+            .templates
+            .remove(id)
+            .ok_or_else(|| KeyKeeperError::not_found("Prompt template"))?;
+        if self.defaults.get(&removed.feature).map(String::as_str) == Some(id) {
+            self.defaults.remove(&removed.feature);
+        }
+        Ok(())
+    }
+
+    /// Make `id` the template `feature` uses; `None` goes back to the
+    /// built-in one
+    pub fn set_active(&mut self, feature: &str, id: Option<&str>) -> Result<(), KeyKeeperError> {
+        if builtin(feature).is_none() {
+            return Err(KeyKeeperError::Validation(format!(
+                "Unknown prompt feature '{}'",
+                feature
+            )));
+        }
+        match id.filter(|id| *id != feature) {
+            Some(id) => {
+                let template = self
+                    .templates
+                    .get(id)
+                    .ok_or_else(|| KeyKeeperError::not_found("Prompt template"))?;
+                if template.feature != feature {
+                    return Err(KeyKeeperError::Validation(
+                        "The template belongs to another feature".to_string(),
+                    ));
+                }
+                self.defaults.insert(feature.to_string(), id.to_string());
+            }
+            None => {
+                self.defaults.remove(feature);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The prompt `feature` uses with `vars` filled in
+pub fn render(feature: &str, vars: &[(&str, &str)]) -> String {
+    let body = STORE.read().unwrap().body(feature).unwrap_or_default();
+    substitute(&body, vars)
+}
+
+fn find(id: &str) -> Result<PromptTemplate, KeyKeeperError> {
+    STORE
+        .read()
+        .unwrap()
+        .list()
+        .into_iter()
+        .find(|t| t.id == id)
+        .ok_or_else(|| KeyKeeperError::not_found("Prompt template"))
+}
+
+fn update<T>(
+    change: impl FnOnce(&mut Stored) -> Result<T, KeyKeeperError>,
+) -> Result<T, KeyKeeperError> {
+    let mut stored = STORE.write().unwrap();
+    let mut next = stored.clone();
+    let result = change(&mut next)?;
+    persist(&next)?;
+    *stored = next;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn list_prompt_templates() -> Result<Vec<PromptTemplate>, KeyKeeperError> {
+    Ok(STORE.read().unwrap().list())
+}
+
+/// Create a template for `feature` (without `id`) or edit one
+#[tauri::command]
+pub async fn save_prompt_template(
+    id: Option<String>,
+    feature: String,
+    name: Option<String>,
+    body: String,
+) -> Result<PromptTemplate, KeyKeeperError> {
+    let id = update(|stored| {
+        stored.save(
+            id.as_deref(),
+            &feature,
+            name.as_deref().unwrap_or(""),
+            &body,
+            Utc::now(),
+        )
+    })?;
+    find(&id)
+}
+
+/// Put a built-in template back as shipped, or delete a user template
+#[tauri::command]
+pub async fn reset_prompt_template(id: String) -> Result<(), KeyKeeperError> {
+    update(|stored| stored.reset(&id))
+}
+
+#[tauri::command]
+pub async fn set_active_prompt_template(
+    feature: String,
+    id: Option<String>,
+) -> Result<(), KeyKeeperError> {
+    update(|stored| stored.set_active(&feature, id.as_deref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_are_versioned_and_features_pick_their_template() {
+        let mut stored = Stored::default();
+        let now = Utc::now();
+        assert!(stored
+            .body(USAGE_EXAMPLES)
+            .unwrap()
+            .contains("{{key_format}}"));
+
+        stored
+            .save(
+                Some(USAGE_EXAMPLES),
+                USAGE_EXAMPLES,
+                "",
+                "Show {{provider}} calls",
+                now,
+            )
+            .unwrap();
+        let edited = &stored.list()[3];
+        assert_eq!((edited.version, edited.history.len()), (2, 1));
+        assert!(edited.customized && edited.active);
+        assert!(stored
+            .save(None, USAGE_EXAMPLES, "Bad", "Use {{secret}}", now)
+            .is_err());
+
+        let custom = stored
+            .save(
+                None,
+                USAGE_EXAMPLES,
+                "Terse",
+                "{{provider}} in one line",
+                now,
+            )
+            .unwrap();
+        stored.set_active(USAGE_EXAMPLES, Some(&custom)).unwrap();
+        assert_eq!(
+            substitute(
+                &stored.body(USAGE_EXAMPLES).unwrap(),
+                &[("provider", "Stripe")]
+            ),
+            "Stripe in one line"
+        );
+
+        stored.reset(&custom).unwrap();
+        assert_eq!(
+            stored.body(USAGE_EXAMPLES).unwrap(),
+            "Show {{provider}} calls"
+        );
+        stored.reset(USAGE_EXAMPLES).unwrap();
+        assert!(stored
+            .body(USAGE_EXAMPLES)
+            .unwrap()
+            .starts_with("Generate 3"));
+        assert_eq!(placeholders("{{ a }} {{b}} {{a}}"), vec!["a", "b"]);
+    }
+}
@@ -0,0 +1,549 @@
+//! Provider usage quotas.
+//!
+//! A key's `quota` caps requests per window (minute, hour, day or month) and
+//! spend per calendar month (UTC). Consumption comes from `record_key_usage`,
+//! which callers can give a request count and a cost; a call without either
+//! counts as one request. Counters reset when their window ends. Crossing
+//! one of the quota's alert thresholds (80% and 100% unless configured)
+//! raises a notification once per window.
+//!
+//! Quotas replace the free-text `rate_limit` field; the v3 -> v4 migration
+//! turns texts like "1000 req/min" into quotas.
+
+use crate::errors::KeyKeeperError;
+use crate::notifications::{NotificationDispatcher, NotificationKind};
+use crate::{guards, ApiKeyVault, AppState};
+use chrono::{DateTime, Datelike, DurationRound, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::State;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaWindow {
+    #[default]
+    Minute,
+    Hour,
+    Day,
+    Month,
+}
+
+impl QuotaWindow {
+    /// Start of the window `now` falls in
+    fn start(self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let step = match self {
+            QuotaWindow::Minute => chrono::Duration::minutes(1),
+            QuotaWindow::Hour => chrono::Duration::hours(1),
+            QuotaWindow::Day => chrono::Duration::days(1),
+            QuotaWindow::Month => return month_start(now),
+        };
+        now.duration_trunc(step).unwrap_or(now)
+    }
+
+    /// When the window starting at `start` ends
+    fn end(self, start: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            QuotaWindow::Minute => start + chrono::Duration::minutes(1),
+            QuotaWindow::Hour => start + chrono::Duration::hours(1),
+            QuotaWindow::Day => start + chrono::Duration::days(1),
+            QuotaWindow::Month => next_month(start),
+        }
+    }
+}
+
+fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(now)
+}
+
+fn next_month(start: DateTime<Utc>) -> DateTime<Utc> {
+    let (year, month) = match start.month() {
+        12 => (start.year() + 1, 1),
+        month => (start.year(), month + 1),
+    };
+    Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0)
+        .single()
+        .unwrap_or(start)
+}
+
+fn default_currency() -> String {
+    "USD".to_string()
+}
+
+fn default_thresholds() -> Vec<u8> {
+    vec![80, 100]
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeyQuota {
+    /// Requests allowed per `window`
+    #[serde(default)]
+    pub requests: Option<u64>,
+    #[serde(default)]
+    pub window: QuotaWindow,
+    /// Spend allowed per calendar month, in `currency`
+    #[serde(default)]
+    pub monthly_spend_cap: Option<f64>,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    /// Percentages of a limit that raise a notification
+    #[serde(default = "default_thresholds")]
+    pub alert_thresholds: Vec<u8>,
+}
+
+impl KeyQuota {
+    pub fn validate(&self) -> Result<(), KeyKeeperError> {
+        if self.requests == Some(0) {
+            return Err(KeyKeeperError::Validation(
+                "A request quota must allow at least one request".to_string(),
+            ));
+        }
+        if self
+            .monthly_spend_cap
+            .is_some_and(|cap| !cap.is_finite() || cap <= 0.0)
+        {
+            return Err(KeyKeeperError::Validation(
+                "The monthly spend cap must be a positive amount".to_string(),
+            ));
+        }
+        if self.alert_thresholds.iter().any(|t| !(1..=200).contains(t)) {
+            return Err(KeyKeeperError::Validation(
+                "Alert thresholds must be between 1 and 200 percent".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Highest threshold `percent` reaches, 0 if none
+    fn crossed(&self, percent: f64) -> u8 {
+        self.alert_thresholds
+            .iter()
+            .copied()
+            .filter(|t| percent >= f64::from(*t))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// What one use of a key consumed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageMetadata {
+    /// Requests made; 1 when neither this nor `cost` is given
+    #[serde(default)]
+    pub requests: Option<u64>,
+    /// Spend, in the quota's currency
+    #[serde(default)]
+    pub cost: Option<f64>,
+}
+
+/// Consumption of one key, kept in the vault
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaUsage {
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub window_start: DateTime<Utc>,
+    pub window_requests: u64,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub month_start: DateTime<Utc>,
+    pub month_requests: u64,
+    pub month_spend: f64,
+    /// Highest threshold already notified for the current window and month
+    #[serde(default)]
+    pub notified_requests: u8,
+    #[serde(default)]
+    pub notified_spend: u8,
+}
+
+impl QuotaUsage {
+    fn new(window: QuotaWindow, now: DateTime<Utc>) -> Self {
+        Self {
+            window_start: window.start(now),
+            window_requests: 0,
+            month_start: month_start(now),
+            month_requests: 0,
+            month_spend: 0.0,
+            notified_requests: 0,
+            notified_spend: 0,
+        }
+    }
+
+    /// Reset counters whose window has ended
+    fn roll(&mut self, window: QuotaWindow, now: DateTime<Utc>) {
+        let window_start = window.start(now);
+        if self.window_start != window_start {
+            self.window_start = window_start;
+            self.window_requests = 0;
+            self.notified_requests = 0;
+        }
+        let month = month_start(now);
+        if self.month_start != month {
+            self.month_start = month;
+            self.month_requests = 0;
+            self.month_spend = 0.0;
+            self.notified_spend = 0;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaLimit {
+    Requests,
+    Spend,
+}
+
+/// A threshold crossed by the latest use of a key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaAlert {
+    pub key_id: String,
+    pub key_name: String,
+    pub limit: QuotaLimit,
+    pub threshold: u8,
+    pub used: f64,
+    pub allowed: f64,
+}
+
+impl QuotaAlert {
+    fn message(&self, currency: &str) -> (String, String) {
+        let title = if self.threshold >= 100 {
+            format!("{} is over its quota", self.key_name)
+        } else {
+            format!("{} is at {}% of its quota", self.key_name, self.threshold)
+        };
+        let body = match self.limit {
+            QuotaLimit::Requests => format!("{} of {} requests used", self.used, self.allowed),
+            QuotaLimit::Spend => format!(
+                "{:.2} of {:.2} {} spent this month",
+                self.used, self.allowed, currency
+            ),
+        };
+        (title, body)
+    }
+}
+
+fn percent(used: f64, allowed: f64) -> f64 {
+    used * 100.0 / allowed
+}
+
+/// Count a use of `key_id` against its quota. Returns the thresholds this
+/// use crossed for the first time in the current window.
+pub fn consume(
+    vault: &mut ApiKeyVault,
+    key_id: &str,
+    usage: &UsageMetadata,
+    now: DateTime<Utc>,
+) -> Vec<QuotaAlert> {
+    let Some(key) = vault.keys.get(key_id) else {
+        return Vec::new();
+    };
+    let quota = key.quota.clone().unwrap_or_else(|| KeyQuota {
+        requests: None,
+        window: QuotaWindow::default(),
+        monthly_spend_cap: None,
+        currency: default_currency(),
+        alert_thresholds: Vec::new(),
+    });
+    let key_name = key.name.clone();
+    let requests = usage
+        .requests
+        .unwrap_or(if usage.cost.is_some() { 0 } else { 1 });
+    let cost = usage
+        .cost
+        .filter(|c| c.is_finite() && *c > 0.0)
+        .unwrap_or(0.0);
+
+    let entry = vault
+        .quota_usage
+        .entry(key_id.to_string())
+        .or_insert_with(|| QuotaUsage::new(quota.window, now));
+    entry.roll(quota.window, now);
+    entry.window_requests += requests;
+    entry.month_requests += requests;
+    entry.month_spend += cost;
+
+    let mut alerts = Vec::new();
+    if let Some(allowed) = quota.requests {
+        let used = entry.window_requests as f64;
+        let crossed = quota.crossed(percent(used, allowed as f64));
+        if crossed > entry.notified_requests {
+            entry.notified_requests = crossed;
+            alerts.push(QuotaAlert {
+                key_id: key_id.to_string(),
+                key_name: key_name.clone(),
+                limit: QuotaLimit::Requests,
+                threshold: crossed,
+                used,
+                allowed: allowed as f64,
+            });
+        }
+    }
+    if let Some(allowed) = quota.monthly_spend_cap {
+        let crossed = quota.crossed(percent(entry.month_spend, allowed));
+        if crossed > entry.notified_spend {
+            entry.notified_spend = crossed;
+            alerts.push(QuotaAlert {
+                key_id: key_id.to_string(),
+                key_name,
+                limit: QuotaLimit::Spend,
+                threshold: crossed,
+                used: entry.month_spend,
+                allowed,
+            });
+        }
+    }
+    alerts
+}
+
+/// Show a notification for each alert
+pub async fn notify(
+    notifier: &NotificationDispatcher,
+    vault: &Mutex<ApiKeyVault>,
+    alerts: &[QuotaAlert],
+) {
+    for alert in alerts {
+        let currency = vault
+            .lock()
+            .await
+            .keys
+            .get(&alert.key_id)
+            .and_then(|k| k.quota.as_ref())
+            .map(|q| q.currency.clone())
+            .unwrap_or_else(default_currency);
+        let (title, body) = alert.message(&currency);
+        if let Err(e) = notifier
+            .notify(vault, NotificationKind::QuotaThreshold, &title, &body)
+            .await
+        {
+            warn!("Failed to notify quota alert: {}", e);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaStatus {
+    pub key_id: String,
+    pub quota: Option<KeyQuota>,
+    pub window: QuotaWindow,
+    pub requests_used: u64,
+    pub requests_allowed: Option<u64>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub window_resets_at: DateTime<Utc>,
+    pub month_requests: u64,
+    pub month_spend: f64,
+    pub monthly_spend_cap: Option<f64>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub month_resets_at: DateTime<Utc>,
+    /// Share of the tightest limit used, in percent
+    pub percent_used: Option<f64>,
+    pub exceeded: bool,
+}
+
+pub fn status(
+    vault: &ApiKeyVault,
+    key_id: &str,
+    now: DateTime<Utc>,
+) -> Result<QuotaStatus, KeyKeeperError> {
+    let key = vault
+        .keys
+        .get(key_id)
+        .ok_or_else(|| KeyKeeperError::not_found("API key"))?;
+    let window = key.quota.as_ref().map(|q| q.window).unwrap_or_default();
+    let mut usage = vault
+        .quota_usage
+        .get(key_id)
+        .cloned()
+        .unwrap_or_else(|| QuotaUsage::new(window, now));
+    usage.roll(window, now);
+
+    let requests_allowed = key.quota.as_ref().and_then(|q| q.requests);
+    let monthly_spend_cap = key.quota.as_ref().and_then(|q| q.monthly_spend_cap);
+    let percent_used = [
+        requests_allowed.map(|allowed| percent(usage.window_requests as f64, allowed as f64)),
+        monthly_spend_cap.map(|cap| percent(usage.month_spend, cap)),
+    ]
+    .into_iter()
+    .flatten()
+    .reduce(f64::max);
+
+    Ok(QuotaStatus {
+        key_id: key_id.to_string(),
+        quota: key.quota.clone(),
+        window,
+        requests_used: usage.window_requests,
+        requests_allowed,
+        window_resets_at: window.end(usage.window_start),
+        month_requests: usage.month_requests,
+        month_spend: usage.month_spend,
+        monthly_spend_cap,
+        month_resets_at: next_month(usage.month_start),
+        percent_used,
+        exceeded: percent_used.is_some_and(|p| p >= 100.0),
+    })
+}
+
+/// "1000 req/min", "5,000 requests per day", "10/s" as requests per window.
+/// Per-second limits become per-minute ones.
+pub fn parse_rate_limit(text: &str) -> Option<(u64, QuotaWindow)> {
+    let text = text.trim().to_lowercase();
+    let (amount, unit) = text.split_once('/').or_else(|| text.split_once(" per "))?;
+    let digits: String = amount
+        .split_whitespace()
+        .next()?
+        .chars()
+        .filter(|c| !matches!(c, ',' | '_'))
+        .collect();
+    let amount: u64 = digits.parse().ok().filter(|n| *n > 0)?;
+    let unit = unit.trim().trim_start_matches("1 ").trim_end_matches('.');
+    let (amount, window) = match unit {
+        "s" | "sec" | "second" => (amount.checked_mul(60)?, QuotaWindow::Minute),
+        "m" | "min" | "minute" => (amount, QuotaWindow::Minute),
+        "h" | "hr" | "hour" => (amount, QuotaWindow::Hour),
+        "d" | "day" => (amount, QuotaWindow::Day),
+        "mo" | "month" => (amount, QuotaWindow::Month),
+        _ => return None,
+    };
+    Some((amount, window))
+}
+
+/// v3 -> v4: replace `rate_limit` texts with quotas. Texts that can't be
+/// read as a limit ("unlimited" aside) are kept at the end of the
+/// description.
+pub fn migrate_rate_limits(document: &mut Value) -> Result<(), String> {
+    let Some(keys) = document.get_mut("keys").and_then(Value::as_object_mut) else {
+        return Ok(());
+    };
+    for key in keys.values_mut() {
+        let Some(key) = key.as_object_mut() else {
+            continue;
+        };
+        let Some(Value::String(text)) = key.remove("rate_limit") else {
+            continue;
+        };
+        let text = text.trim().to_string();
+        if text.is_empty() || text.eq_ignore_ascii_case("unlimited") {
+            continue;
+        }
+        match parse_rate_limit(&text) {
+            Some((requests, window)) => {
+                let quota = KeyQuota {
+                    requests: Some(requests),
+                    window,
+                    monthly_spend_cap: None,
+                    currency: default_currency(),
+                    alert_thresholds: default_thresholds(),
+                };
+                key.insert(
+                    "quota".to_string(),
+                    serde_json::to_value(quota).map_err(|e| e.to_string())?,
+                );
+            }
+            None => {
+                let note = format!("Rate limit: {}", text);
+                let description = match key.get("description").and_then(Value::as_str) {
+                    Some(d) if !d.trim().is_empty() => format!("{}\n{}", d, note),
+                    _ => note,
+                };
+                key.insert("description".to_string(), Value::String(description));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_quota_status(
+    key_id: String,
+    state: State<'_, AppState>,
+) -> Result<QuotaStatus, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    status(&vault_guard, &key_id, Utc::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault_tests::test_key;
+
+    #[test]
+    fn thresholds_alert_once_per_window() {
+        let mut vault = ApiKeyVault::default();
+        let mut key = test_key("k")
+            .name("OPENAI_KEY")
+            .service("OpenAI")
+            .environment("dev")
+            .build();
+        key.quota = Some(KeyQuota {
+            requests: Some(10),
+            window: QuotaWindow::Minute,
+            monthly_spend_cap: Some(5.0),
+            currency: default_currency(),
+            alert_thresholds: default_thresholds(),
+        });
+        vault.keys.insert("k".to_string(), key);
+        let now = Utc.with_ymd_and_hms(2024, 3, 31, 23, 59, 10).unwrap();
+
+        let eight = UsageMetadata {
+            requests: Some(8),
+            cost: Some(1.0),
+        };
+        let alerts = consume(&mut vault, "k", &eight, now);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(
+            (alerts[0].limit, alerts[0].threshold),
+            (QuotaLimit::Requests, 80)
+        );
+        assert!(consume(&mut vault, "k", &UsageMetadata::default(), now).is_empty());
+
+        let over = consume(
+            &mut vault,
+            "k",
+            &UsageMetadata {
+                requests: None,
+                cost: Some(4.5),
+            },
+            now,
+        );
+        assert_eq!((over[0].limit, over[0].threshold), (QuotaLimit::Spend, 100));
+        assert!(status(&vault, "k", now).unwrap().exceeded);
+
+        // A new minute and a new month start together
+        let later = now + chrono::Duration::minutes(1);
+        let status = status(&vault, "k", later).unwrap();
+        assert_eq!((status.requests_used, status.month_spend), (0, 0.0));
+        assert_eq!(
+            status.month_resets_at,
+            Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn rate_limit_texts_become_quotas() {
+        assert_eq!(
+            parse_rate_limit("1000 req/min"),
+            Some((1000, QuotaWindow::Minute))
+        );
+        assert_eq!(
+            parse_rate_limit("5,000 requests per day"),
+            Some((5000, QuotaWindow::Day))
+        );
+        assert_eq!(parse_rate_limit("10/s"), Some((600, QuotaWindow::Minute)));
+        assert_eq!(parse_rate_limit("fair use"), None);
+
+        let mut document = serde_json::json!({ "keys": {
+            "a": { "rate_limit": "60/hour", "description": null },
+            "b": { "rate_limit": "fair use", "description": "Billing" },
+        }});
+        migrate_rate_limits(&mut document).unwrap();
+        assert_eq!(document["keys"]["a"]["quota"]["requests"], 60);
+        assert_eq!(document["keys"]["a"]["quota"]["window"], "hour");
+        assert!(document["keys"]["b"].get("rate_limit").is_none());
+        assert_eq!(
+            document["keys"]["b"]["description"],
+            "Billing\nRate limit: fair use"
+        );
+    }
+}
@@ -0,0 +1,561 @@
+//! Recovery kit.
+//!
+//! A kit is a random recovery key that can unwrap the vault's master key,
+//! itself wrapped by each of ten one-time codes and by a reusable recovery
+//! passphrase. The recovery key stays inside the encrypted vault; the wrapped
+//! copies are mirrored into the metadata file so they can be used while the
+//! vault is locked. Since the master key is wrapped again on every save, the
+//! kit survives master password changes.
+//!
+//! Unlocking with a code consumes it. Either way the vault is flagged with
+//! `password_reset_required` until a new master password is set, since the
+//! old one is presumably lost.
+
+use crate::errors::KeyKeeperError;
+use crate::guards::{self, Requirement};
+use crate::vault_location::decrypt_with_key;
+use crate::{
+    decrypt_data, derive_key_from_password, encrypt_data, log_audit_event, password_strength,
+    save_vault, ApiKeyVault, AppState, VaultMetadata,
+};
+use base64::{engine::general_purpose, Engine as _};
+use bcrypt::{hash, DEFAULT_COST};
+use chrono::{DateTime, Utc};
+use rand::{rngs::OsRng, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use tauri::State;
+use zeroize::Zeroizing;
+
+const CODE_COUNT: usize = 10;
+/// Base32 without the easily confused 0/O and 1/I
+const ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+/// 16 characters, 80 bits
+const CODE_GROUPS: usize = 4;
+const CODE_GROUP_LEN: usize = 4;
+/// 30 characters, 150 bits
+const PASSPHRASE_GROUPS: usize = 6;
+const PASSPHRASE_GROUP_LEN: usize = 5;
+
+/// A secret wrapped with a key derived from a code or passphrase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeySlot {
+    pub salt: String,
+    pub wrapped_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryCodeSlot {
+    pub id: String,
+    /// SHA-256 of the slot salt and the normalized code, to find the slot
+    pub code_hash: String,
+    #[serde(flatten)]
+    pub slot: KeySlot,
+    #[serde(default, with = "crate::timestamps::rfc3339_option")]
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+/// Kit as stored inside the encrypted vault
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryKit {
+    pub id: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    /// Base64 key the codes and passphrase unwrap; never written in clear
+    pub recovery_key: String,
+    pub codes: Vec<RecoveryCodeSlot>,
+    pub passphrase: KeySlot,
+}
+
+/// Kit as mirrored into the metadata file, usable while locked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryEnvelope {
+    pub kit_id: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    /// Master key encrypted with the recovery key
+    pub wrapped_master_key: String,
+    pub codes: Vec<RecoveryCodeSlot>,
+    pub passphrase: KeySlot,
+}
+
+/// Returned once when a kit is generated; the codes can't be shown again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryKitDocument {
+    pub kit_id: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    pub codes: Vec<String>,
+    pub passphrase: String,
+    /// Printable plain-text sheet with the codes and passphrase
+    pub document: String,
+    pub exported_to: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryKitStatus {
+    pub kit_id: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    pub remaining_codes: usize,
+    pub password_reset_required: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryUnlock {
+    pub password_reset_required: bool,
+    pub remaining_codes: usize,
+}
+
+fn random_groups(groups: usize, len: usize) -> String {
+    let mut rng = OsRng;
+    (0..groups)
+        .map(|_| {
+            (0..len)
+                .map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())] as char)
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Codes are accepted in any case, with or without separators
+fn normalize(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+fn random_salt() -> String {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    general_purpose::STANDARD.encode(salt)
+}
+
+fn code_hash(salt: &str, code: &str) -> String {
+    format!("{:x}", Sha256::digest(format!("{}:{}", salt, code)))
+}
+
+fn wrap(secret: &str, code: &str, salt: &str) -> Result<String, String> {
+    let salt_bytes = general_purpose::STANDARD
+        .decode(salt)
+        .map_err(|e| format!("Failed to decode salt: {}", e))?;
+    let key = derive_key_from_password(code, &salt_bytes);
+    encrypt_data(secret, &*key)
+}
+
+fn unwrap(slot: &KeySlot, code: &str) -> Result<Zeroizing<String>, String> {
+    let salt_bytes = general_purpose::STANDARD
+        .decode(&slot.salt)
+        .map_err(|e| format!("Failed to decode salt: {}", e))?;
+    let key = derive_key_from_password(code, &salt_bytes);
+    decrypt_data(&slot.wrapped_key, &*key).map(Zeroizing::new)
+}
+
+fn decode_key(key: &str) -> Result<Zeroizing<Vec<u8>>, String> {
+    general_purpose::STANDARD
+        .decode(key)
+        .map(Zeroizing::new)
+        .map_err(|e| format!("Failed to decode recovery key: {}", e))
+}
+
+/// Wrap the current master key for the metadata file
+pub fn envelope(kit: &RecoveryKit, master_key: &str) -> Result<RecoveryEnvelope, String> {
+    let recovery_key = decode_key(&kit.recovery_key)?;
+    Ok(RecoveryEnvelope {
+        kit_id: kit.id.clone(),
+        created_at: kit.created_at,
+        wrapped_master_key: encrypt_data(master_key, &recovery_key)?,
+        codes: kit.codes.clone(),
+        passphrase: kit.passphrase.clone(),
+    })
+}
+
+/// Replace the master password, deriving a new salt and encryption key
+pub fn set_new_master_password(vault: &mut ApiKeyVault, password: &str) -> Result<(), String> {
+    let mut salt_bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut salt_bytes);
+    let key = derive_key_from_password(password, &salt_bytes);
+    vault.master_password_hash = Some(hash(password, DEFAULT_COST).map_err(|e| e.to_string())?);
+    vault.encryption_key = Some(general_purpose::STANDARD.encode(*key));
+    vault.salt = Some(general_purpose::STANDARD.encode(salt_bytes));
+    Ok(())
+}
+
+fn render_document(
+    kit: &RecoveryKit,
+    codes: &[String],
+    passphrase: &str,
+    vault_path: &str,
+) -> String {
+    let mut doc = String::new();
+    doc.push_str("KEYKEEPER RECOVERY KIT\n");
+    doc.push_str("======================\n\n");
+    doc.push_str(&format!(
+        "Created: {}\n",
+        kit.created_at.format("%Y-%m-%d %H:%M UTC")
+    ));
+    doc.push_str(&format!("Kit ID:  {}\n", kit.id));
+    doc.push_str(&format!("Vault:   {}\n\n", vault_path));
+    doc.push_str("Store this sheet offline, somewhere safe. Anyone holding it can open\n");
+    doc.push_str("your vault. Generating a new kit makes this one useless.\n\n");
+    doc.push_str("ONE-TIME RECOVERY CODES\n");
+    doc.push_str("Each code unlocks the vault once; a new master password must then be set.\n\n");
+    for (i, code) in codes.iter().enumerate() {
+        doc.push_str(&format!("  {:>2}. {}    [ ] used\n", i + 1, code));
+    }
+    doc.push_str("\nRECOVERY PASSPHRASE\n");
+    doc.push_str(
+        "Unlocks the vault like a code but is not used up. Keep it apart from the codes.\n\n",
+    );
+    doc.push_str(&format!("  {}\n", passphrase));
+    doc
+}
+
+fn status(vault: &ApiKeyVault) -> Option<RecoveryKitStatus> {
+    vault.recovery_kit.as_ref().map(|kit| RecoveryKitStatus {
+        kit_id: kit.id.clone(),
+        created_at: kit.created_at,
+        remaining_codes: kit.codes.iter().filter(|c| c.used_at.is_none()).count(),
+        password_reset_required: vault.password_reset_required,
+    })
+}
+
+/// Create a new recovery kit, replacing any previous one. The codes and
+/// passphrase are only returned here; optionally the sheet is also written
+/// to `export_path`.
+#[tauri::command]
+pub async fn generate_recovery_kit(
+    export_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<RecoveryKitDocument, KeyKeeperError> {
+    guards::require(
+        &state,
+        &[Requirement::Unlocked, Requirement::CurrentPassword],
+    )
+    .await?;
+    {
+        let vault_guard = state.vault.lock().await;
+        if vault_guard.encryption_key.is_none() || vault_guard.salt.is_none() {
+            return Err(KeyKeeperError::Validation(
+                "Set a master password before generating a recovery kit".to_string(),
+            ));
+        }
+    }
+
+    let mut recovery_key = Zeroizing::new([0u8; 32]);
+    OsRng.fill_bytes(&mut *recovery_key);
+    let recovery_key = Zeroizing::new(general_purpose::STANDARD.encode(*recovery_key));
+
+    // Key derivation is deliberately slow, so wrap off the async runtime
+    let (codes, passphrase, slots, passphrase_slot) = {
+        let recovery_key = recovery_key.clone();
+        tauri::async_runtime::spawn_blocking(move || -> Result<_, String> {
+            let codes: Vec<String> = (0..CODE_COUNT)
+                .map(|_| random_groups(CODE_GROUPS, CODE_GROUP_LEN))
+                .collect();
+            let passphrase = random_groups(PASSPHRASE_GROUPS, PASSPHRASE_GROUP_LEN);
+            let mut slots = Vec::with_capacity(codes.len());
+            for code in &codes {
+                let salt = random_salt();
+                let normalized = normalize(code);
+                slots.push(RecoveryCodeSlot {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    code_hash: code_hash(&salt, &normalized),
+                    slot: KeySlot {
+                        wrapped_key: wrap(&recovery_key, &normalized, &salt)?,
+                        salt,
+                    },
+                    used_at: None,
+                });
+            }
+            let salt = random_salt();
+            let passphrase_slot = KeySlot {
+                wrapped_key: wrap(&recovery_key, &normalize(&passphrase), &salt)?,
+                salt,
+            };
+            Ok((codes, passphrase, slots, passphrase_slot))
+        })
+        .await
+        .map_err(|e| KeyKeeperError::Internal(format!("Recovery kit generation failed: {}", e)))??
+    };
+
+    let kit = RecoveryKit {
+        id: format!("kit_{}", uuid::Uuid::new_v4()),
+        created_at: Utc::now(),
+        recovery_key: recovery_key.to_string(),
+        codes: slots,
+        passphrase: passphrase_slot,
+    };
+    let document = render_document(
+        &kit,
+        &codes,
+        &passphrase,
+        &state.vault_path.path().to_string_lossy(),
+    );
+
+    let mut vault_guard = state.vault.lock().await;
+    if let Some(account) = vault_guard.user_account.as_mut() {
+        account.recovery_codes = kit.codes.iter().map(|c| c.code_hash.clone()).collect();
+    }
+    vault_guard.recovery_kit = Some(kit.clone());
+    drop(vault_guard);
+    save_vault(&state).await?;
+
+    let exported_to = match export_path {
+        Some(path) => {
+            write_private(&path, &document)?;
+            Some(path)
+        }
+        None => None,
+    };
+
+    log_audit_event(
+        &state,
+        "generate_recovery_kit",
+        "vault",
+        Some(&kit.id),
+        true,
+        exported_to
+            .as_deref()
+            .map(|p| format!("Exported to {}", p))
+            .as_deref(),
+    )
+    .await;
+
+    Ok(RecoveryKitDocument {
+        kit_id: kit.id,
+        created_at: kit.created_at,
+        codes,
+        passphrase,
+        document,
+        exported_to,
+    })
+}
+
+/// Write the sheet readable by the current user only
+fn write_private(path: &str, contents: &str) -> Result<(), KeyKeeperError> {
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::OpenOptionsExt;
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)?;
+        file.write_all(contents.as_bytes())?;
+    }
+    #[cfg(not(unix))]
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_recovery_kit_status(
+    state: State<'_, AppState>,
+) -> Result<Option<RecoveryKitStatus>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let vault_guard = state.vault.lock().await;
+    Ok(status(&vault_guard))
+}
+
+/// Master key unwrapped with a one-time code (consumed) or the passphrase
+fn recover_master_key(
+    envelope: &RecoveryEnvelope,
+    code: &str,
+) -> Result<(Zeroizing<String>, Option<String>), String> {
+    let normalized = normalize(code);
+    let slot = envelope
+        .codes
+        .iter()
+        .find(|s| code_hash(&s.slot.salt, &normalized) == s.code_hash);
+    let (recovery_key, used_slot) = match slot {
+        Some(slot) if slot.used_at.is_some() => {
+            return Err("This recovery code has already been used".to_string())
+        }
+        Some(slot) => (unwrap(&slot.slot, &normalized)?, Some(slot.id.clone())),
+        None => match unwrap(&envelope.passphrase, &normalized) {
+            Ok(key) => (key, None),
+            Err(_) => return Err("Invalid recovery code".to_string()),
+        },
+    };
+    let recovery_key = decode_key(&recovery_key)?;
+    let master_key = decrypt_data(&envelope.wrapped_master_key, &recovery_key)?;
+    Ok((Zeroizing::new(master_key), used_slot))
+}
+
+/// Unlock the vault with a recovery code or the recovery passphrase. A code
+/// is used up; either way a new master password has to be set with
+/// `reset_master_password_after_recovery`.
+#[tauri::command]
+pub async fn unlock_with_recovery_code(
+    code: String,
+    state: State<'_, AppState>,
+) -> Result<RecoveryUnlock, KeyKeeperError> {
+    let code = Zeroizing::new(code);
+    if *state.is_unlocked.lock().await {
+        return Err(KeyKeeperError::Validation(
+            "Vault is already unlocked".to_string(),
+        ));
+    }
+
+    let path = state.vault_path.path();
+    let metadata: VaultMetadata = serde_json::from_str(
+        &fs::read_to_string(state.vault_path.metadata_path())
+            .map_err(|e| KeyKeeperError::Io(format!("Failed to read metadata: {}", e)))?,
+    )?;
+    let envelope = metadata
+        .recovery
+        .ok_or_else(|| KeyKeeperError::not_found("Recovery kit"))?;
+
+    let recovered = tauri::async_runtime::spawn_blocking(move || {
+        let (master_key, used_slot) = recover_master_key(&envelope, &code)?;
+        let vault = decrypt_with_key(&path, &master_key)?;
+        Ok::<_, String>((vault, used_slot))
+    })
+    .await
+    .map_err(|e| KeyKeeperError::Internal(format!("Recovery failed: {}", e)))?;
+    let (mut vault, used_slot) = match recovered {
+        Ok(recovered) => recovered,
+        Err(e) => {
+            log_audit_event(
+                &state,
+                "unlock_with_recovery_code",
+                "vault",
+                None,
+                false,
+                Some(&e),
+            )
+            .await;
+            return Err(KeyKeeperError::Crypto(e));
+        }
+    };
+
+    if let (Some(kit), Some(slot_id)) = (vault.recovery_kit.as_mut(), &used_slot) {
+        if let Some(slot) = kit.codes.iter_mut().find(|s| &s.id == slot_id) {
+            slot.used_at = Some(Utc::now());
+        }
+    }
+    vault.password_reset_required = true;
+    let remaining_codes = status(&vault).map_or(0, |s| s.remaining_codes);
+
+    let mut vault_guard = state.vault.lock().await;
+    crate::secure_memory::wipe_vault_secrets(&mut vault_guard);
+    *vault_guard = vault;
+    drop(vault_guard);
+    *state.is_unlocked.lock().await = true;
+    if let Err(e) = crate::sessions::begin_unlock_session(&state).await {
+        tracing::warn!("Failed to start unlock session: {}", e);
+    }
+    state.vault_index.lock().await.invalidate();
+    save_vault(&state).await?;
+
+    let method = if used_slot.is_some() {
+        "recovery code"
+    } else {
+        "recovery passphrase"
+    };
+    log_audit_event(
+        &state,
+        "unlock_with_recovery_code",
+        "vault",
+        None,
+        true,
+        Some(&format!("Unlocked with {}", method)),
+    )
+    .await;
+
+    Ok(RecoveryUnlock {
+        password_reset_required: true,
+        remaining_codes,
+    })
+}
+
+/// Set the new master password required after a recovery unlock
+#[tauri::command]
+pub async fn reset_master_password_after_recovery(
+    new_password: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    let new_password = Zeroizing::new(new_password);
+    guards::unlocked(&state).await?;
+    let mut vault_guard = state.vault.lock().await;
+    password_strength::enforce(
+        &state,
+        &new_password,
+        &password_strength::account_inputs(&vault_guard),
+    )?;
+    if !vault_guard.password_reset_required {
+        return Err(KeyKeeperError::Validation(
+            "No password reset is pending".to_string(),
+        ));
+    }
+    set_new_master_password(&mut vault_guard, &new_password).map_err(KeyKeeperError::Crypto)?;
+    vault_guard.password_reset_required = false;
+    drop(vault_guard);
+    save_vault(&state).await?;
+
+    log_audit_event(
+        &state,
+        "reset_master_password_after_recovery",
+        "vault",
+        None,
+        true,
+        None,
+    )
+    .await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_and_passphrase_unwrap_the_master_key_once() {
+        let mut recovery_key = [0u8; 32];
+        OsRng.fill_bytes(&mut recovery_key);
+        let recovery_key = general_purpose::STANDARD.encode(recovery_key);
+        let code = random_groups(CODE_GROUPS, CODE_GROUP_LEN);
+        let passphrase = random_groups(PASSPHRASE_GROUPS, PASSPHRASE_GROUP_LEN);
+        let salt = random_salt();
+        let passphrase_salt = random_salt();
+        let kit = RecoveryKit {
+            id: "kit".to_string(),
+            created_at: Utc::now(),
+            codes: vec![RecoveryCodeSlot {
+                id: "slot".to_string(),
+                code_hash: code_hash(&salt, &normalize(&code)),
+                slot: KeySlot {
+                    wrapped_key: wrap(&recovery_key, &normalize(&code), &salt).unwrap(),
+                    salt,
+                },
+                used_at: None,
+            }],
+            passphrase: KeySlot {
+                wrapped_key: wrap(&recovery_key, &normalize(&passphrase), &passphrase_salt)
+                    .unwrap(),
+                salt: passphrase_salt,
+            },
+            recovery_key,
+        };
+        let mut envelope = envelope(&kit, "master-key").unwrap();
+
+        let typed = code.to_lowercase().replace('-', " ");
+        let (key, slot) = recover_master_key(&envelope, &typed).unwrap();
+        assert_eq!(key.as_str(), "master-key");
+        assert_eq!(slot.as_deref(), Some("slot"));
+
+        let (key, slot) = recover_master_key(&envelope, &passphrase).unwrap();
+        assert_eq!(key.as_str(), "master-key");
+        assert!(slot.is_none());
+
+        envelope.codes[0].used_at = Some(Utc::now());
+        assert!(recover_master_key(&envelope, &code).is_err());
+        assert!(recover_master_key(&envelope, "AAAA-BBBB-CCCC-DDDD").is_err());
+    }
+}
@@ -0,0 +1,214 @@
+//! Issuing and redeeming password recovery tokens.
+//!
+//! A token is 40 random alphanumerics handed out once, by `request_password_recovery`
+//! or by email. The vault keeps only its SHA-256 hash, so a copy of the vault
+//! can't be used to reset the password, and looks it up with a constant-time
+//! comparison. Each token is bound to a fingerprint of the device and client
+//! that asked for it; redeeming it anywhere else fails and counts as a failed
+//! attempt. Used, expired and exhausted tokens are dropped whenever a token is
+//! issued or redeemed, and by the retention job. Tokens stored before hashing
+//! were guessable and unbound, so they are dropped the same way.
+
+use crate::{clients, get_device_id, ApiKeyVault, PasswordRecovery};
+use chrono::{DateTime, Duration, Utc};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+pub const TOKEN_LIFETIME_HOURS: i64 = 24;
+/// Failed redemptions before a token stops working
+pub const MAX_ATTEMPTS: u32 = 3;
+const TOKEN_LEN: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rejection {
+    Invalid,
+    Expired,
+    Used,
+    TooManyAttempts,
+    OtherDevice,
+}
+
+impl Rejection {
+    /// For the audit log
+    pub fn reason(self) -> &'static str {
+        match self {
+            Rejection::Invalid => "Invalid token",
+            Rejection::Expired => "Token expired",
+            Rejection::Used => "Token already used",
+            Rejection::TooManyAttempts => "Too many attempts",
+            Rejection::OtherDevice => "Token requested on another device",
+        }
+    }
+
+    /// For the caller; doesn't tell an unknown token from a misused one
+    pub fn message(self) -> &'static str {
+        match self {
+            Rejection::Expired => "Recovery token expired",
+            Rejection::Used => "Recovery token already used",
+            Rejection::TooManyAttempts => "Too many recovery attempts",
+            Rejection::Invalid | Rejection::OtherDevice => "Invalid recovery token",
+        }
+    }
+}
+
+fn sha256_hex(value: &str) -> String {
+    format!("{:x}", Sha256::digest(value.as_bytes()))
+}
+
+fn same(a: &str, b: &str) -> bool {
+    a.as_bytes().ct_eq(b.as_bytes()).into()
+}
+
+/// Hashed device id and client of the current request. An HTTP client is
+/// told apart by its registration, the desktop app by the device alone.
+pub fn request_fingerprint() -> String {
+    let client = clients::current()
+        .map(|c| c.client_id.unwrap_or(c.name))
+        .unwrap_or_else(|| "app".to_string());
+    sha256_hex(&format!("{}\n{}", get_device_id(), client))
+}
+
+/// Whether a stored token can still be redeemed
+pub fn is_live(token: &PasswordRecovery, now: DateTime<Utc>) -> bool {
+    !token.used
+        && token.expires_at > now
+        && token.attempts < MAX_ATTEMPTS
+        && !token.fingerprint.is_empty()
+}
+
+/// Drop tokens that can no longer be redeemed. Returns how many were dropped.
+pub fn cleanup(vault: &mut ApiKeyVault, now: DateTime<Utc>) -> usize {
+    let before = vault.recovery_tokens.len();
+    vault.recovery_tokens.retain(|t| is_live(t, now));
+    before - vault.recovery_tokens.len()
+}
+
+/// Create a token for `email`, replacing the earlier ones for it. Returns the
+/// token, which isn't stored anywhere, and when it expires.
+pub fn issue(
+    vault: &mut ApiKeyVault,
+    email: &str,
+    fingerprint: &str,
+    now: DateTime<Utc>,
+) -> (Zeroizing<String>, DateTime<Utc>) {
+    cleanup(vault, now);
+    vault.recovery_tokens.retain(|t| t.email != email);
+
+    let mut token = Zeroizing::new("recovery_".to_string());
+    token.extend(
+        rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(TOKEN_LEN)
+            .map(char::from),
+    );
+    let expires_at = now + Duration::hours(TOKEN_LIFETIME_HOURS);
+    vault.recovery_tokens.push(PasswordRecovery {
+        token_hash: sha256_hex(&token),
+        fingerprint: fingerprint.to_string(),
+        email: email.to_string(),
+        created_at: now,
+        expires_at,
+        used: false,
+        attempts: 0,
+    });
+    (token, expires_at)
+}
+
+/// Check `token` against the stored hashes and mark it used. A token that
+/// matches nothing counts as a failed attempt on every live token of the
+/// requesting device.
+pub fn redeem(
+    vault: &mut ApiKeyVault,
+    token: &str,
+    fingerprint: &str,
+    now: DateTime<Utc>,
+) -> Result<(), Rejection> {
+    let hash = sha256_hex(token);
+    // Compare against every entry, so timing doesn't reveal a match
+    let mut found = None;
+    for (index, stored) in vault.recovery_tokens.iter().enumerate() {
+        if same(&stored.token_hash, &hash) {
+            found = Some(index);
+        }
+    }
+
+    let result = match found {
+        None => {
+            for stored in vault.recovery_tokens.iter_mut() {
+                if same(&stored.fingerprint, fingerprint) {
+                    stored.attempts += 1;
+                }
+            }
+            Err(Rejection::Invalid)
+        }
+        Some(index) => {
+            let stored = &mut vault.recovery_tokens[index];
+            if stored.used {
+                Err(Rejection::Used)
+            } else if stored.expires_at <= now {
+                Err(Rejection::Expired)
+            } else if stored.attempts >= MAX_ATTEMPTS {
+                Err(Rejection::TooManyAttempts)
+            } else if !same(&stored.fingerprint, fingerprint) {
+                stored.attempts += 1;
+                Err(Rejection::OtherDevice)
+            } else {
+                stored.used = true;
+                Ok(())
+            }
+        }
+    };
+    cleanup(vault, now);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_hashed_bound_and_single_use() {
+        let now = Utc::now();
+        let mut vault = ApiKeyVault::default();
+        vault.recovery_tokens.push(PasswordRecovery {
+            token_hash: "recovery_1700000000000".to_string(),
+            fingerprint: String::new(),
+            email: "me@example.com".to_string(),
+            created_at: now,
+            expires_at: now + Duration::hours(1),
+            used: false,
+            attempts: 0,
+        });
+
+        let (token, _) = issue(&mut vault, "me@example.com", "laptop", now);
+        assert_eq!(vault.recovery_tokens.len(), 1, "legacy token dropped");
+        assert_ne!(vault.recovery_tokens[0].token_hash, *token);
+        assert_eq!(token.len(), "recovery_".len() + TOKEN_LEN);
+
+        assert_eq!(
+            redeem(&mut vault, &token, "phone", now),
+            Err(Rejection::OtherDevice)
+        );
+        assert_eq!(
+            redeem(&mut vault, "recovery_guess", "laptop", now),
+            Err(Rejection::Invalid)
+        );
+        assert_eq!(vault.recovery_tokens[0].attempts, 2);
+        assert_eq!(
+            redeem(&mut vault, &token, "laptop", now + Duration::hours(25)),
+            Err(Rejection::Expired)
+        );
+        assert!(vault.recovery_tokens.is_empty(), "expired token dropped");
+
+        let (token, _) = issue(&mut vault, "me@example.com", "laptop", now);
+        assert_eq!(redeem(&mut vault, &token, "laptop", now), Ok(()));
+        assert!(vault.recovery_tokens.is_empty(), "used token dropped");
+        assert_eq!(
+            redeem(&mut vault, &token, "laptop", now),
+            Err(Rejection::Invalid)
+        );
+    }
+}
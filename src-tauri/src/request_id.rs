@@ -0,0 +1,76 @@
+//! Correlation ids for HTTP requests.
+//!
+//! Every request to the local server gets an id, taken from its
+//! `X-Request-Id` header when the caller sent a usable one and generated
+//! otherwise. It is echoed in the response's `X-Request-Id` header, in the
+//! `request_id` field of error bodies and in the audit entries written while
+//! answering, so a failed call can be traced to what the vault recorded.
+
+use http_body_util::Full;
+use hyper::header::{HeaderMap, HeaderValue};
+use hyper::Response;
+use std::convert::Infallible;
+use std::future::Future;
+
+pub const HEADER: &str = "x-request-id";
+const MAX_LEN: usize = 64;
+
+tokio::task_local! {
+    static CURRENT: String;
+}
+
+/// Id of the HTTP request this task is answering, if any
+pub fn current() -> Option<String> {
+    CURRENT.try_with(|id| id.clone()).ok()
+}
+
+fn usable(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// The caller's id, or a new one when it sent none or one we won't echo
+pub fn from_headers(headers: &HeaderMap) -> String {
+    headers
+        .get(HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|id| usable(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+}
+
+/// Run `handler` with `id` as the current request id and add it to the
+/// response
+pub async fn serve<F>(id: String, handler: F) -> Result<Response<Full<bytes::Bytes>>, Infallible>
+where
+    F: Future<Output = Result<Response<Full<bytes::Bytes>>, Infallible>>,
+{
+    let mut response = CURRENT.scope(id.clone(), handler).await?;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(HEADER, value);
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn echoes_only_well_formed_ids() {
+        let mut headers = HeaderMap::new();
+        headers.insert(HEADER, HeaderValue::from_static("ext-42.a_b"));
+        assert_eq!(from_headers(&headers), "ext-42.a_b");
+
+        headers.insert(HEADER, HeaderValue::from_static("bad id\"with quotes"));
+        let generated = from_headers(&headers);
+        assert!(uuid::Uuid::parse_str(&generated).is_ok());
+
+        assert!(uuid::Uuid::parse_str(&from_headers(&HeaderMap::new())).is_ok());
+        assert!(current().is_none());
+    }
+}
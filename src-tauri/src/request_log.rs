@@ -0,0 +1,211 @@
+//! Log of the requests answered by the local HTTP server.
+//!
+//! Off by default; `server.log_requests` (or `KEYKEEPER_LOG_REQUESTS`) turns
+//! it on without a restart. Each request is recorded with its method, path,
+//! status, latency, client and body sizes, never the bodies or headers
+//! themselves, and the path is passed through `logging::redact` so a secret
+//! in a query string doesn't end up in the log. Entries are kept in a ring
+//! buffer read by `get_http_request_log` and written to the log as
+//! `keykeeper::http` events. Preflights and origins refused by `cors` are
+//! answered before routing and are recorded without a client.
+
+use crate::clients::RequestClient;
+use crate::errors::KeyKeeperError;
+use chrono::{DateTime, Utc};
+use http_body_util::Full;
+use hyper::body::Body;
+use hyper::header::CONTENT_LENGTH;
+use hyper::{Request, Response};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::future::Future;
+use std::sync::{LazyLock, Mutex};
+use std::time::Instant;
+use tracing::{info, warn};
+
+/// Entries kept for `get_http_request_log`
+const CAPACITY: usize = 500;
+const DEFAULT_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpRequestEntry {
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub timestamp: DateTime<Utc>,
+    pub request_id: Option<String>,
+    pub method: String,
+    /// Path and query, redacted
+    pub path: String,
+    pub status: u16,
+    pub latency_ms: u64,
+    /// Set for clients that proved their registration
+    pub client_id: Option<String>,
+    pub client_name: Option<String>,
+    /// From the request's `Content-Length`
+    pub bytes_in: Option<u64>,
+    pub bytes_out: Option<u64>,
+}
+
+static RECENT: LazyLock<Mutex<VecDeque<HttpRequestEntry>>> =
+    LazyLock::new(|| Mutex::new(VecDeque::with_capacity(CAPACITY)));
+
+/// A request being answered, until its response is known
+pub struct PendingRequest {
+    started: Instant,
+    method: String,
+    path: String,
+    bytes_in: Option<u64>,
+}
+
+/// Start timing `req`, or `None` when logging is off
+pub fn begin<B>(req: &Request<B>, enabled: bool) -> Option<PendingRequest> {
+    enabled.then(|| PendingRequest {
+        started: Instant::now(),
+        method: req.method().to_string(),
+        path: crate::logging::redact(
+            req.uri()
+                .path_and_query()
+                .map_or(req.uri().path(), |p| p.as_str()),
+        )
+        .into_owned(),
+        bytes_in: req
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok()),
+    })
+}
+
+fn record(entry: HttpRequestEntry) {
+    let client = entry.client_name.as_deref().unwrap_or("-");
+    let request_id = entry.request_id.as_deref().unwrap_or("-");
+    if entry.status >= 500 {
+        warn!(
+            target: "keykeeper::http",
+            "{} {} {} {}ms client={} request_id={}",
+            entry.method, entry.path, entry.status, entry.latency_ms, client, request_id
+        );
+    } else {
+        info!(
+            target: "keykeeper::http",
+            "{} {} {} {}ms client={} request_id={}",
+            entry.method, entry.path, entry.status, entry.latency_ms, client, request_id
+        );
+    }
+
+    let mut recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+    if recent.len() >= CAPACITY {
+        recent.pop_front();
+    }
+    recent.push_back(entry);
+}
+
+/// Run `handler` and record its response. The client is read from the
+/// response's extensions, where the server leaves it once identified.
+pub async fn serve<F>(
+    pending: Option<PendingRequest>,
+    handler: F,
+) -> Result<Response<Full<bytes::Bytes>>, Infallible>
+where
+    F: Future<Output = Result<Response<Full<bytes::Bytes>>, Infallible>>,
+{
+    let response = handler.await?;
+    if let Some(pending) = pending {
+        let client = response.extensions().get::<RequestClient>();
+        record(HttpRequestEntry {
+            timestamp: Utc::now(),
+            request_id: crate::request_id::current(),
+            method: pending.method,
+            path: pending.path,
+            status: response.status().as_u16(),
+            latency_ms: pending.started.elapsed().as_millis() as u64,
+            client_id: client.and_then(|c| c.client_id.clone()),
+            client_name: client.map(|c| c.name.clone()),
+            bytes_in: pending.bytes_in,
+            bytes_out: response.body().size_hint().exact(),
+        });
+    }
+    Ok(response)
+}
+
+fn select(
+    entries: &VecDeque<HttpRequestEntry>,
+    path: Option<&str>,
+    errors_only: bool,
+    limit: usize,
+) -> Vec<HttpRequestEntry> {
+    let mut selected: Vec<HttpRequestEntry> = entries
+        .iter()
+        .rev()
+        .filter(|e| path.is_none_or(|p| e.path.contains(p)))
+        .filter(|e| !errors_only || e.status >= 400)
+        .take(limit)
+        .cloned()
+        .collect();
+    selected.reverse();
+    selected
+}
+
+/// Most recent HTTP requests, oldest first, whose path contains `path`
+#[tauri::command]
+pub fn get_http_request_log(
+    path: Option<String>,
+    errors_only: Option<bool>,
+    limit: Option<usize>,
+) -> Result<Vec<HttpRequestEntry>, KeyKeeperError> {
+    let recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(select(
+        &recent,
+        path.as_deref().filter(|p| !p.is_empty()),
+        errors_only.unwrap_or(false),
+        limit.unwrap_or(DEFAULT_LIMIT).min(CAPACITY),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_query_secrets_and_filters_entries() {
+        let req = Request::builder()
+            .method("GET")
+            .uri("/api/keys?service=stripe&api_key=sk_live_abcdefghijklmnop1234")
+            .header(CONTENT_LENGTH, "12")
+            .body(())
+            .unwrap();
+        assert!(begin(&req, false).is_none());
+        let pending = begin(&req, true).unwrap();
+        assert_eq!(pending.path, "/api/keys?service=stripe&api_key=[REDACTED]");
+        assert_eq!(pending.bytes_in, Some(12));
+
+        let entries: VecDeque<HttpRequestEntry> = [
+            ("/api/keys", 200),
+            ("/api/docs", 404),
+            ("/api/keys/k1", 500),
+        ]
+        .into_iter()
+        .map(|(path, status)| HttpRequestEntry {
+            timestamp: Utc::now(),
+            request_id: None,
+            method: "GET".to_string(),
+            path: path.to_string(),
+            status,
+            latency_ms: 1,
+            client_id: None,
+            client_name: None,
+            bytes_in: None,
+            bytes_out: None,
+        })
+        .collect();
+        let statuses = |selected: Vec<HttpRequestEntry>| -> Vec<u16> {
+            selected.iter().map(|e| e.status).collect()
+        };
+        assert_eq!(
+            statuses(select(&entries, Some("/api/keys"), false, 10)),
+            vec![200, 500]
+        );
+        assert_eq!(statuses(select(&entries, None, true, 10)), vec![404, 500]);
+        assert_eq!(statuses(select(&entries, None, false, 1)), vec![500]);
+    }
+}
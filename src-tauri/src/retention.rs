@@ -0,0 +1,409 @@
+//! Retention of history-like vault data.
+//!
+//! Audit logs, recent activity, sessions, WebAuthn challenges and recovery
+//! tokens each have a `RetentionRule`: entries older than `max_age_days` are
+//! purged, and beyond `max_entries` the oldest go first. Live entries (active
+//! sessions, unexpired challenges and tokens) are never purged. A background
+//! job applies the policy, compacts the vault and keeps a report of what it
+//! purged; `max_entries` is also enforced whenever an entry is added.
+
+use crate::errors::KeyKeeperError;
+use crate::{guards, log_audit_event, recovery_tokens, save_vault, ApiKeyVault, AppState};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+const RETENTION_INTERVAL_SECS: u64 = 6 * 60 * 60;
+const MAX_AGE_DAYS: u32 = 3650;
+const MAX_ENTRIES: usize = 100_000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum DataClass {
+    AuditLogs,
+    RecentActivity,
+    Sessions,
+    WebauthnChallenges,
+    RecoveryTokens,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct RetentionRule {
+    /// `None` keeps entries regardless of age
+    pub max_age_days: Option<u32>,
+    /// `None` keeps any number of entries
+    pub max_entries: Option<usize>,
+}
+
+impl RetentionRule {
+    fn new(max_age_days: Option<u32>, max_entries: Option<usize>) -> Self {
+        Self {
+            max_age_days,
+            max_entries,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    pub audit_logs: RetentionRule,
+    pub recent_activity: RetentionRule,
+    /// Ages count from when a session expired or was revoked
+    pub sessions: RetentionRule,
+    /// Ages count from when a challenge expired
+    pub webauthn_challenges: RetentionRule,
+    /// Ages count from when a token expired
+    pub recovery_tokens: RetentionRule,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            audit_logs: RetentionRule::new(Some(365), Some(1000)),
+            recent_activity: RetentionRule::new(Some(180), Some(1000)),
+            sessions: RetentionRule::new(Some(7), None),
+            webauthn_challenges: RetentionRule::new(Some(1), None),
+            recovery_tokens: RetentionRule::new(Some(30), None),
+        }
+    }
+}
+
+impl RetentionPolicy {
+    pub fn rule(&self, class: DataClass) -> RetentionRule {
+        match class {
+            DataClass::AuditLogs => self.audit_logs,
+            DataClass::RecentActivity => self.recent_activity,
+            DataClass::Sessions => self.sessions,
+            DataClass::WebauthnChallenges => self.webauthn_challenges,
+            DataClass::RecoveryTokens => self.recovery_tokens,
+        }
+    }
+
+    fn validate(&self) -> Result<(), KeyKeeperError> {
+        for rule in [
+            self.audit_logs,
+            self.recent_activity,
+            self.sessions,
+            self.webauthn_challenges,
+            self.recovery_tokens,
+        ] {
+            if rule
+                .max_age_days
+                .is_some_and(|days| days == 0 || days > MAX_AGE_DAYS)
+            {
+                return Err(KeyKeeperError::Validation(format!(
+                    "Retention must be between 1 and {} days",
+                    MAX_AGE_DAYS
+                )));
+            }
+            if rule.max_entries.is_some_and(|n| n == 0 || n > MAX_ENTRIES) {
+                return Err(KeyKeeperError::Validation(format!(
+                    "Entry limits must be between 1 and {}",
+                    MAX_ENTRIES
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What a retention run purged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionReport {
+    pub ran_at: DateTime<Utc>,
+    /// Entries purged, by data class
+    pub purged: BTreeMap<DataClass, usize>,
+    /// Size of the vault document before and after the run
+    pub bytes_before: usize,
+    pub bytes_after: usize,
+}
+
+impl RetentionReport {
+    pub fn total(&self) -> usize {
+        self.purged.values().sum()
+    }
+}
+
+/// Remove `removable` entries older than the rule allows, then the oldest
+/// removable ones beyond its entry limit. Entries are kept in the order they
+/// were added, oldest first.
+fn purge_entries<T>(
+    items: &mut Vec<T>,
+    rule: RetentionRule,
+    now: DateTime<Utc>,
+    age_from: impl Fn(&T) -> DateTime<Utc>,
+    removable: impl Fn(&T) -> bool,
+) -> usize {
+    let before = items.len();
+    if let Some(days) = rule.max_age_days {
+        let cutoff = now - ChronoDuration::days(days as i64);
+        items.retain(|item| !removable(item) || age_from(item) >= cutoff);
+    }
+    if let Some(max) = rule.max_entries {
+        let mut excess = items.len().saturating_sub(max);
+        items.retain(|item| {
+            if excess > 0 && removable(item) {
+                excess -= 1;
+                false
+            } else {
+                true
+            }
+        });
+    }
+    before - items.len()
+}
+
+/// Apply the vault's policy for one data class; returns how many entries
+/// were purged
+pub fn purge(vault: &mut ApiKeyVault, class: DataClass, now: DateTime<Utc>) -> usize {
+    let rule = vault.retention_policy.rule(class);
+    match class {
+        DataClass::AuditLogs => {
+            purge_entries(&mut vault.audit_logs, rule, now, |l| l.timestamp, |_| true)
+        }
+        DataClass::RecentActivity => purge_entries(
+            &mut vault.recent_activity,
+            rule,
+            now,
+            |a| a.timestamp,
+            |_| true,
+        ),
+        DataClass::Sessions => {
+            purge_entries(
+                &mut vault.biometric_sessions,
+                rule,
+                now,
+                |s| s.expires_at,
+                |s| !s.is_active,
+            ) + purge_entries(
+                &mut vault.persistent_sessions,
+                rule,
+                now,
+                |s| s.expires_at,
+                |s| !s.is_active,
+            )
+        }
+        DataClass::WebauthnChallenges => purge_entries(
+            &mut vault.webauthn_challenges,
+            rule,
+            now,
+            |c| c.expires_at,
+            |c| c.expires_at <= now,
+        ),
+        DataClass::RecoveryTokens => purge_entries(
+            &mut vault.recovery_tokens,
+            rule,
+            now,
+            |t| t.expires_at,
+            |t| !recovery_tokens::is_live(t, now),
+        ),
+    }
+}
+
+/// Enforce the audit log's entry limit after adding to it
+pub fn cap_audit_logs(vault: &mut ApiKeyVault) {
+    let rule = RetentionRule {
+        max_age_days: None,
+        ..vault.retention_policy.audit_logs
+    };
+    purge_entries(
+        &mut vault.audit_logs,
+        rule,
+        Utc::now(),
+        |l| l.timestamp,
+        |_| true,
+    );
+}
+
+/// Enforce the activity feed's entry limit after adding to it
+pub fn cap_recent_activity(vault: &mut ApiKeyVault) {
+    let rule = RetentionRule {
+        max_age_days: None,
+        ..vault.retention_policy.recent_activity
+    };
+    purge_entries(
+        &mut vault.recent_activity,
+        rule,
+        Utc::now(),
+        |a| a.timestamp,
+        |_| true,
+    );
+}
+
+fn document_size(vault: &ApiKeyVault) -> usize {
+    serde_json::to_vec(vault)
+        .map(|bytes| bytes.len())
+        .unwrap_or(0)
+}
+
+/// Purge every data class and release the memory the purged entries held
+pub fn apply(vault: &mut ApiKeyVault, now: DateTime<Utc>) -> RetentionReport {
+    let bytes_before = document_size(vault);
+    let purged: BTreeMap<DataClass, usize> = [
+        DataClass::AuditLogs,
+        DataClass::RecentActivity,
+        DataClass::Sessions,
+        DataClass::WebauthnChallenges,
+        DataClass::RecoveryTokens,
+    ]
+    .into_iter()
+    .map(|class| (class, purge(vault, class, now)))
+    .collect();
+
+    vault.audit_logs.shrink_to_fit();
+    vault.recent_activity.shrink_to_fit();
+    vault.biometric_sessions.shrink_to_fit();
+    vault.persistent_sessions.shrink_to_fit();
+    vault.webauthn_challenges.shrink_to_fit();
+    vault.recovery_tokens.shrink_to_fit();
+
+    RetentionReport {
+        ran_at: now,
+        purged,
+        bytes_before,
+        bytes_after: document_size(vault),
+    }
+}
+
+async fn run_once(state: &State<'_, AppState>) -> Result<RetentionReport, KeyKeeperError> {
+    let mut vault_guard = state.vault.lock().await;
+    let report = apply(&mut vault_guard, Utc::now());
+    vault_guard.retention_report = Some(report.clone());
+    drop(vault_guard);
+
+    // Saving rewrites the documents without the purged entries
+    save_vault(state).await?;
+    if report.total() > 0 {
+        info!(
+            "Retention purged {} entries ({} -> {} bytes)",
+            report.total(),
+            report.bytes_before,
+            report.bytes_after
+        );
+        let summary: Vec<String> = report
+            .purged
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .map(|(class, count)| format!("{:?}: {}", class, count))
+            .collect();
+        log_audit_event(
+            state,
+            "retention_purge",
+            "vault",
+            None,
+            true,
+            Some(&summary.join(", ")),
+        )
+        .await;
+    }
+    Ok(report)
+}
+
+/// Periodically apply the retention policy
+pub async fn run_retention(app: AppHandle) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(RETENTION_INTERVAL_SECS)).await;
+
+        let state: State<'_, AppState> = app.state();
+        if !*state.is_unlocked.lock().await {
+            continue;
+        }
+        if let Err(e) = run_once(&state).await {
+            warn!("Retention run failed: {}", e);
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_retention_policy(
+    state: State<'_, AppState>,
+) -> Result<RetentionPolicy, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    Ok(state.vault.lock().await.retention_policy.clone())
+}
+
+#[tauri::command]
+pub async fn set_retention_policy(
+    policy: RetentionPolicy,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    policy.validate()?;
+
+    state.vault.lock().await.retention_policy = policy;
+    save_vault(&state).await?;
+    log_audit_event(&state, "set_retention_policy", "vault", None, true, None).await;
+    Ok(())
+}
+
+/// Apply the retention policy now instead of waiting for the next run
+#[tauri::command]
+pub async fn run_retention_now(
+    state: State<'_, AppState>,
+) -> Result<RetentionReport, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    run_once(&state).await
+}
+
+/// Report of the last retention run, if there was one
+#[tauri::command]
+pub async fn get_retention_report(
+    state: State<'_, AppState>,
+) -> Result<Option<RetentionReport>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    Ok(state.vault.lock().await.retention_report.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PasswordRecovery, WebAuthnChallenge};
+
+    fn at(day: u32) -> DateTime<Utc> {
+        format!("2024-06-{:02}T00:00:00Z", day).parse().unwrap()
+    }
+
+    #[test]
+    fn only_old_entries_that_are_no_longer_live_are_purged() {
+        let mut vault = ApiKeyVault::default();
+        vault.retention_policy.webauthn_challenges = RetentionRule::new(Some(1), None);
+        vault.retention_policy.recovery_tokens = RetentionRule::new(Some(2), Some(1));
+        for (challenge, expires) in [("old", 1), ("recent", 9), ("live", 20)] {
+            vault.webauthn_challenges.push(WebAuthnChallenge {
+                challenge: challenge.to_string(),
+                user_id: "u".to_string(),
+                created_at: at(1),
+                expires_at: at(expires),
+                challenge_type: "authentication".to_string(),
+            });
+        }
+        for (token, used) in [("used", true), ("unused", false)] {
+            vault.recovery_tokens.push(PasswordRecovery {
+                token_hash: token.to_string(),
+                fingerprint: "device".to_string(),
+                email: "me@example.com".to_string(),
+                created_at: at(9),
+                expires_at: at(20),
+                used,
+                attempts: 0,
+            });
+        }
+
+        let report = apply(&mut vault, at(10));
+        let left: Vec<&str> = vault
+            .webauthn_challenges
+            .iter()
+            .map(|c| c.challenge.as_str())
+            .collect();
+        assert_eq!(left, vec!["recent", "live"]);
+        // Over the limit, but the unused token is still live
+        assert_eq!(vault.recovery_tokens.len(), 1);
+        assert_eq!(vault.recovery_tokens[0].token_hash, "unused");
+        assert_eq!(report.purged[&DataClass::WebauthnChallenges], 1);
+        assert_eq!(report.total(), 2);
+    }
+}
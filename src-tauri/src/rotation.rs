@@ -0,0 +1,550 @@
+//! Key rotation schedules and reminders.
+//!
+//! A key with `rotation_interval_days` is due that many days after it was
+//! last rotated (or created, if it never was). Replacing a key's value
+//! records the rotation. The scheduler checks hourly and files one reminder
+//! per due key, with the steps to rotate it at its provider: built-in steps
+//! for well-known providers, the provider's docs link from the catalog and
+//! any documentation in the library about rotating it.
+
+use crate::errors::KeyKeeperError;
+use crate::notifications::NotificationKind;
+use crate::{guards, log_audit_event, save_vault, ApiKey, ApiKeyVault, AppState};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+const ROTATION_CHECK_INTERVAL_SECS: u64 = 60 * 60;
+const MAX_ROTATION_INTERVAL_DAYS: u32 = 3650;
+/// Resolved reminders kept for history
+const MAX_RESOLVED_REMINDERS: usize = 200;
+
+struct ProviderGuide {
+    id: &'static str,
+    /// Normalized service names the guide applies to
+    aliases: &'static [&'static str],
+    console_url: &'static str,
+    steps: &'static [&'static str],
+}
+
+const PROVIDER_GUIDES: &[ProviderGuide] = &[
+    ProviderGuide {
+        id: "openai",
+        aliases: &["openai"],
+        console_url: "https://platform.openai.com/api-keys",
+        steps: &[
+            "Create a new secret key in the API keys page",
+            "Update the key in KeyKeeper and sync it to your projects",
+            "Revoke the old key once nothing uses it",
+        ],
+    },
+    ProviderGuide {
+        id: "anthropic",
+        aliases: &["anthropic", "claude"],
+        console_url: "https://console.anthropic.com/settings/keys",
+        steps: &[
+            "Create a new key in Console > API keys",
+            "Update the key in KeyKeeper and sync it to your projects",
+            "Disable, then delete the old key",
+        ],
+    },
+    ProviderGuide {
+        id: "stripe",
+        aliases: &["stripe"],
+        console_url: "https://dashboard.stripe.com/apikeys",
+        steps: &[
+            "Choose \"Roll key\" on the secret key and set an expiration for the old one",
+            "Update the key in KeyKeeper and sync it to your projects",
+            "Let the old key expire, or expire it now once deployments are updated",
+        ],
+    },
+    ProviderGuide {
+        id: "github",
+        aliases: &["github"],
+        console_url: "https://github.com/settings/tokens",
+        steps: &[
+            "Regenerate the token, keeping the same scopes",
+            "Update the key in KeyKeeper and push it to GitHub secrets where it is used",
+        ],
+    },
+    ProviderGuide {
+        id: "aws",
+        aliases: &["aws", "amazonwebservices", "amazon"],
+        console_url: "https://console.aws.amazon.com/iam/home#/security_credentials",
+        steps: &[
+            "Create a second access key for the IAM user",
+            "Update the key in KeyKeeper and sync it to your projects",
+            "Deactivate the old access key, check nothing breaks, then delete it",
+        ],
+    },
+    ProviderGuide {
+        id: "supabase",
+        aliases: &["supabase"],
+        console_url: "https://supabase.com/dashboard/project/_/settings/api",
+        steps: &[
+            "Roll the JWT secret or create a new API key in Project Settings > API",
+            "Update the key in KeyKeeper and sync it to your projects",
+        ],
+    },
+];
+
+/// How to rotate a key at its provider
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RotationInstructions {
+    pub provider: Option<String>,
+    pub steps: Vec<String>,
+    pub console_url: Option<String>,
+    /// From the provider catalog
+    pub docs_url: Option<String>,
+    /// Documents in the library about rotating the provider's keys
+    pub doc_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RotationReminder {
+    pub id: String,
+    pub key_id: String,
+    pub key_name: String,
+    pub service: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub due_at: DateTime<Utc>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    pub instructions: RotationInstructions,
+    /// Set when the key is rotated or the reminder is dismissed
+    #[serde(default, with = "crate::timestamps::rfc3339_option")]
+    pub resolved_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub dismissed: bool,
+}
+
+/// A scheduled key as listed in the rotation overview
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyRotationStatus {
+    pub key_id: String,
+    pub key_name: String,
+    pub service: String,
+    pub interval_days: u32,
+    #[serde(default, with = "crate::timestamps::rfc3339_option")]
+    pub last_rotated_at: Option<DateTime<Utc>>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub next_due_at: DateTime<Utc>,
+    pub overdue: bool,
+}
+
+pub fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn guide_for(service: &str) -> Option<&'static ProviderGuide> {
+    let service = normalize(service);
+    PROVIDER_GUIDES
+        .iter()
+        .find(|guide| guide.aliases.iter().any(|alias| service.contains(alias)))
+}
+
+/// When the key is next due, if it has a schedule
+pub fn next_due(key: &ApiKey) -> Option<DateTime<Utc>> {
+    key.rotation_interval_days.map(|days| {
+        key.last_rotated_at.unwrap_or(key.created_at) + ChronoDuration::days(days as i64)
+    })
+}
+
+pub fn is_overdue(key: &ApiKey, now: DateTime<Utc>) -> bool {
+    key.is_active && next_due(key).is_some_and(|due| due <= now)
+}
+
+/// Active keys past their rotation date
+pub fn count_overdue(vault: &ApiKeyVault, now: DateTime<Utc>) -> usize {
+    vault.keys.values().filter(|k| is_overdue(k, now)).count()
+}
+
+fn has_open_reminder(vault: &ApiKeyVault, key_id: &str) -> bool {
+    vault
+        .rotation_reminders
+        .iter()
+        .any(|r| r.key_id == key_id && r.resolved_at.is_none())
+}
+
+/// Overdue keys that don't have an open reminder yet
+fn keys_needing_reminder(vault: &ApiKeyVault, now: DateTime<Utc>) -> Vec<ApiKey> {
+    vault
+        .keys
+        .values()
+        .filter(|k| is_overdue(k, now) && !has_open_reminder(vault, &k.id))
+        .cloned()
+        .collect()
+}
+
+/// Record that the key was rotated and close its open reminders
+pub fn mark_rotated(vault: &mut ApiKeyVault, key_id: &str, now: DateTime<Utc>) {
+    if let Some(key) = vault.keys.get_mut(key_id) {
+        key.last_rotated_at = Some(now);
+    }
+    for reminder in vault
+        .rotation_reminders
+        .iter_mut()
+        .filter(|r| r.key_id == key_id && r.resolved_at.is_none())
+    {
+        reminder.resolved_at = Some(now);
+    }
+    crate::incident::mark_rotated(vault, key_id, now);
+}
+
+fn prune_resolved(vault: &mut ApiKeyVault) {
+    let resolved = vault
+        .rotation_reminders
+        .iter()
+        .filter(|r| r.resolved_at.is_some())
+        .count();
+    let mut excess = resolved.saturating_sub(MAX_RESOLVED_REMINDERS);
+    vault.rotation_reminders.retain(|r| {
+        if excess > 0 && r.resolved_at.is_some() {
+            excess -= 1;
+            false
+        } else {
+            true
+        }
+    });
+}
+
+/// Instructions for rotating `key`, from the built-in guides, the provider
+/// catalog and the documentation library
+pub async fn instructions_for(state: &State<'_, AppState>, key: &ApiKey) -> RotationInstructions {
+    let guide = guide_for(&key.service);
+    let provider_id = guide
+        .map(|g| g.id.to_string())
+        .unwrap_or_else(|| normalize(&key.service));
+
+    let catalog_provider = {
+        let generator = state.api_generator.lock().await;
+        let service = generator.service.lock().await;
+        service
+            .get_providers()
+            .into_iter()
+            .find(|p| p.id == provider_id || normalize(&p.name) == normalize(&key.service))
+    };
+
+    let doc_ids = state
+        .docs_store
+        .lock()
+        .await
+        .docs
+        .values()
+        .filter(|doc| {
+            doc.provider_id.as_deref() == Some(provider_id.as_str())
+                || doc.tags.iter().any(|t| normalize(t) == provider_id)
+        })
+        .filter(|doc| {
+            doc.title.to_lowercase().contains("rotat")
+                || doc.tags.iter().any(|t| t.to_lowercase().contains("rotat"))
+                || doc.content.to_lowercase().contains("rotat")
+        })
+        .map(|doc| doc.id.clone())
+        .collect();
+
+    RotationInstructions {
+        provider: guide
+            .map(|g| g.id.to_string())
+            .or_else(|| catalog_provider.as_ref().map(|p| p.id.clone())),
+        steps: guide
+            .map(|g| g.steps.iter().map(|s| s.to_string()).collect())
+            .unwrap_or_else(|| {
+                vec![
+                    format!("Create a new key in the {} dashboard", key.service),
+                    "Update the key in KeyKeeper and sync it to your projects".to_string(),
+                    "Revoke the old key once nothing uses it".to_string(),
+                ]
+            }),
+        console_url: guide.map(|g| g.console_url.to_string()),
+        docs_url: catalog_provider
+            .map(|p| p.docs_url)
+            .filter(|url| !url.is_empty()),
+        doc_ids,
+    }
+}
+
+/// File reminders for keys that became due and notify about them
+async fn check_due_keys(state: &State<'_, AppState>) -> Result<(), String> {
+    let now = Utc::now();
+    let due = keys_needing_reminder(&*state.vault.lock().await, now);
+    if due.is_empty() {
+        return Ok(());
+    }
+
+    let mut reminders = Vec::new();
+    for key in &due {
+        reminders.push(RotationReminder {
+            id: uuid::Uuid::new_v4().to_string(),
+            key_id: key.id.clone(),
+            key_name: key.name.clone(),
+            service: key.service.clone(),
+            due_at: next_due(key).unwrap_or(now),
+            created_at: now,
+            instructions: instructions_for(state, key).await,
+            resolved_at: None,
+            dismissed: false,
+        });
+    }
+
+    let mut vault_guard = state.vault.lock().await;
+    for reminder in reminders {
+        // The key may have been rotated while the instructions were gathered
+        if !has_open_reminder(&vault_guard, &reminder.key_id)
+            && vault_guard
+                .keys
+                .get(&reminder.key_id)
+                .is_some_and(|k| is_overdue(k, now))
+        {
+            vault_guard.rotation_reminders.push(reminder);
+        }
+    }
+    prune_resolved(&mut vault_guard);
+    drop(vault_guard);
+    save_vault(state).await.map_err(|e| e.to_string())?;
+
+    info!("{} key(s) due for rotation", due.len());
+    let body = match due.as_slice() {
+        [key] => format!("{} ({}) is due for rotation", key.name, key.service),
+        keys => format!("{} keys are due for rotation", keys.len()),
+    };
+    state
+        .notifier
+        .notify(
+            &state.vault,
+            NotificationKind::RotationDue,
+            "Key rotation due",
+            &body,
+        )
+        .await?;
+    Ok(())
+}
+
+/// Periodically file reminders for keys due for rotation
+pub async fn run_scheduler(app: AppHandle) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(ROTATION_CHECK_INTERVAL_SECS)).await;
+
+        let state: State<'_, AppState> = app.state();
+        if !*state.is_unlocked.lock().await {
+            continue;
+        }
+        if let Err(e) = check_due_keys(&state).await {
+            warn!("Rotation check failed: {}", e);
+        }
+    }
+}
+
+/// Set or clear (`None`) the key's rotation interval
+#[tauri::command]
+pub async fn set_key_rotation_schedule(
+    key_id: String,
+    interval_days: Option<u32>,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    if interval_days.is_some_and(|days| days == 0 || days > MAX_ROTATION_INTERVAL_DAYS) {
+        return Err(KeyKeeperError::Validation(format!(
+            "Rotation interval must be between 1 and {} days",
+            MAX_ROTATION_INTERVAL_DAYS
+        )));
+    }
+
+    let mut vault_guard = state.vault.lock().await;
+    let key = vault_guard
+        .keys
+        .get_mut(&key_id)
+        .ok_or_else(|| KeyKeeperError::not_found("API key"))?;
+    key.rotation_interval_days = interval_days;
+    if interval_days.is_none() {
+        let now = Utc::now();
+        for reminder in vault_guard
+            .rotation_reminders
+            .iter_mut()
+            .filter(|r| r.key_id == key_id && r.resolved_at.is_none())
+        {
+            reminder.resolved_at = Some(now);
+            reminder.dismissed = true;
+        }
+    }
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "set_rotation_schedule",
+        "api_key",
+        Some(&key_id),
+        true,
+        Some(&match interval_days {
+            Some(days) => format!("Every {} days", days),
+            None => "Schedule removed".to_string(),
+        }),
+    )
+    .await;
+    Ok(())
+}
+
+/// Record a rotation done at the provider without changing the stored value
+#[tauri::command]
+pub async fn mark_key_rotated(
+    key_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    if !vault_guard.keys.contains_key(&key_id) {
+        return Err(KeyKeeperError::not_found("API key"));
+    }
+    mark_rotated(&mut vault_guard, &key_id, Utc::now());
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "mark_key_rotated",
+        "api_key",
+        Some(&key_id),
+        true,
+        None,
+    )
+    .await;
+    Ok(())
+}
+
+/// Scheduled keys, soonest due first
+#[tauri::command]
+pub async fn get_key_rotation_status(
+    state: State<'_, AppState>,
+) -> Result<Vec<KeyRotationStatus>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let now = Utc::now();
+    let vault_guard = state.vault.lock().await;
+    let mut statuses: Vec<KeyRotationStatus> = vault_guard
+        .keys
+        .values()
+        .filter_map(|key| {
+            Some(KeyRotationStatus {
+                key_id: key.id.clone(),
+                key_name: key.name.clone(),
+                service: key.service.clone(),
+                interval_days: key.rotation_interval_days?,
+                last_rotated_at: key.last_rotated_at,
+                next_due_at: next_due(key)?,
+                overdue: is_overdue(key, now),
+            })
+        })
+        .collect();
+    statuses.sort_by_key(|s| s.next_due_at);
+    Ok(statuses)
+}
+
+#[tauri::command]
+pub async fn list_rotation_reminders(
+    include_resolved: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Vec<RotationReminder>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let include_resolved = include_resolved.unwrap_or(false);
+    let vault_guard = state.vault.lock().await;
+    Ok(vault_guard
+        .rotation_reminders
+        .iter()
+        .filter(|r| include_resolved || r.resolved_at.is_none())
+        .cloned()
+        .collect())
+}
+
+/// Close a reminder without rotating. The key stays overdue, so a new
+/// reminder is filed at the next check unless its schedule changes.
+#[tauri::command]
+pub async fn dismiss_rotation_reminder(
+    reminder_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let reminder = vault_guard
+        .rotation_reminders
+        .iter_mut()
+        .find(|r| r.id == reminder_id && r.resolved_at.is_none())
+        .ok_or_else(|| KeyKeeperError::not_found("Rotation reminder"))?;
+    reminder.resolved_at = Some(Utc::now());
+    reminder.dismissed = true;
+    let key_id = reminder.key_id.clone();
+    drop(vault_guard);
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "dismiss_rotation_reminder",
+        "api_key",
+        Some(&key_id),
+        true,
+        None,
+    )
+    .await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault_tests::test_key;
+
+    #[test]
+    fn overdue_keys_get_one_reminder_until_rotated() {
+        let mut vault = ApiKeyVault::default();
+        let key = test_key("k1")
+            .name("STRIPE_SECRET_KEY")
+            .secret("secret")
+            .environment("production")
+            .rotation_interval_days(Some(90))
+            .build();
+        vault.keys.insert(key.id.clone(), key);
+        let created = vault.keys["k1"].created_at;
+
+        assert!(keys_needing_reminder(&vault, created + ChronoDuration::days(89)).is_empty());
+        let now = created + ChronoDuration::days(90);
+        assert_eq!(keys_needing_reminder(&vault, now).len(), 1);
+        assert_eq!(count_overdue(&vault, now), 1);
+
+        vault.rotation_reminders.push(RotationReminder {
+            id: "r1".to_string(),
+            key_id: "k1".to_string(),
+            key_name: "STRIPE_SECRET_KEY".to_string(),
+            service: "Stripe".to_string(),
+            due_at: now,
+            created_at: now,
+            instructions: RotationInstructions::default(),
+            resolved_at: None,
+            dismissed: false,
+        });
+        assert!(keys_needing_reminder(&vault, now).is_empty());
+
+        mark_rotated(&mut vault, "k1", now);
+        assert!(vault.rotation_reminders[0].resolved_at.is_some());
+        assert_eq!(count_overdue(&vault, now), 0);
+        assert_eq!(
+            next_due(&vault.keys["k1"]),
+            Some(now + ChronoDuration::days(90))
+        );
+    }
+
+    #[test]
+    fn guides_match_service_names() {
+        assert_eq!(guide_for("Stripe").map(|g| g.id), Some("stripe"));
+        assert_eq!(guide_for("Open AI").map(|g| g.id), Some("openai"));
+        assert_eq!(guide_for("AWS S3").map(|g| g.id), Some("aws"));
+        assert!(guide_for("Internal service").is_none());
+    }
+}
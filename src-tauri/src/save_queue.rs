@@ -0,0 +1,242 @@
+//! Coalesced vault saves.
+//!
+//! Every save rewrites and re-encrypts the whole vault, so a burst of edits
+//! (a bulk change from the UI, usage reports from the extension) used to
+//! write it once per edit. Saves now go through the `SaveQueue`: a save waits
+//! `WINDOW` for others to join it, then one write covers every save asked for
+//! until then. Only one write is in flight at a time; saves asked for while
+//! it runs are covered by the next one. `save` waits for the write and
+//! returns its result, `save_later` doesn't, and `flush` writes right away,
+//! which locking the vault does before wiping it. Asking for a save also
+//! invalidates the vault index, so listings don't serve the old keys while
+//! the write waits. Write latencies are reported by `get_vault_statistics`.
+
+use crate::errors::KeyKeeperError;
+use crate::health::TaskGuard;
+use crate::vault_index::VaultIndex;
+use crate::vault_storage::VaultStorage;
+use crate::ApiKeyVault;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+/// How long a save waits for others to coalesce with
+pub const WINDOW: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SaveMetrics {
+    /// Saves asked for, coalesced or not
+    pub requested: u64,
+    /// Times the vault was written
+    pub written: u64,
+    pub failed: u64,
+    pub last_latency_ms: Option<u64>,
+    pub average_latency_ms: Option<u64>,
+    pub max_latency_ms: Option<u64>,
+    pub last_saved_at: Option<String>,
+    pub last_error: Option<String>,
+    #[serde(skip)]
+    total_latency_ms: u64,
+}
+
+impl SaveMetrics {
+    fn record(&mut self, latency: Duration, result: &Result<(), KeyKeeperError>) {
+        let latency_ms = latency.as_millis() as u64;
+        self.written += 1;
+        self.total_latency_ms += latency_ms;
+        self.last_latency_ms = Some(latency_ms);
+        self.average_latency_ms = Some(self.total_latency_ms / self.written);
+        self.max_latency_ms = self.max_latency_ms.max(Some(latency_ms));
+        match result {
+            Ok(()) => {
+                self.last_saved_at = Some(Utc::now().to_rfc3339());
+                self.last_error = None;
+            }
+            Err(e) => {
+                self.failed += 1;
+                self.last_error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+/// The last write and the saves it covered
+struct Written {
+    through: u64,
+    result: Result<(), KeyKeeperError>,
+}
+
+pub struct SaveQueue {
+    vault: Arc<Mutex<ApiKeyVault>>,
+    storage: Arc<dyn VaultStorage>,
+    vault_index: Arc<Mutex<VaultIndex>>,
+    /// Saves asked for so far; each one's number is its ticket
+    requested: AtomicU64,
+    /// Held while writing, so at most one write is in flight
+    written: Mutex<Written>,
+    metrics: std::sync::Mutex<SaveMetrics>,
+}
+
+impl SaveQueue {
+    pub fn new(
+        vault: Arc<Mutex<ApiKeyVault>>,
+        storage: Arc<dyn VaultStorage>,
+        vault_index: Arc<Mutex<VaultIndex>>,
+    ) -> Self {
+        Self {
+            vault,
+            storage,
+            vault_index,
+            requested: AtomicU64::new(0),
+            written: Mutex::new(Written {
+                through: 0,
+                result: Ok(()),
+            }),
+            metrics: std::sync::Mutex::new(SaveMetrics::default()),
+        }
+    }
+
+    /// Take a ticket for a save; the caller has just changed the vault
+    async fn ticket(&self) -> u64 {
+        self.vault_index.lock().await.invalidate();
+        self.metrics
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .requested += 1;
+        self.requested.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// Write the vault unless a write since `ticket` was taken covered it
+    async fn write_through(&self, ticket: u64) -> Result<(), KeyKeeperError> {
+        let mut written = self.written.lock().await;
+        if written.through >= ticket {
+            return written.result.clone();
+        }
+        let through = self.requested.load(Ordering::SeqCst);
+
+        let result = {
+            let vault_guard = self.vault.lock().await;
+            let started = Instant::now();
+            let result = crate::save_vault_to(&*self.storage, &vault_guard);
+            self.metrics
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .record(started.elapsed(), &result);
+            result
+        };
+        self.vault_index.lock().await.invalidate();
+        debug!(
+            "Vault written, covering {} saves",
+            through - written.through
+        );
+
+        *written = Written {
+            through,
+            result: result.clone(),
+        };
+        result
+    }
+
+    async fn coalesce(&self, ticket: u64) -> Result<(), KeyKeeperError> {
+        tokio::time::sleep(WINDOW).await;
+        self.write_through(ticket).await
+    }
+
+    /// Save the vault along with any other saves asked for within `WINDOW`
+    pub async fn save(&self) -> Result<(), KeyKeeperError> {
+        let ticket = self.ticket().await;
+        self.coalesce(ticket).await
+    }
+
+    /// `save` without waiting for it; a failure is only logged. `task` keeps
+    /// shutdown waiting until the write is done.
+    pub async fn save_later(self: &Arc<Self>, task: TaskGuard) {
+        let ticket = self.ticket().await;
+        let queue = Arc::clone(self);
+        tokio::spawn(async move {
+            let _task = task;
+            if let Err(e) = queue.coalesce(ticket).await {
+                warn!("Failed to save vault: {}", e);
+            }
+        });
+    }
+
+    /// Write the vault now, covering every save asked for so far
+    pub async fn flush(&self) -> Result<(), KeyKeeperError> {
+        let ticket = self.ticket().await;
+        self.write_through(ticket).await
+    }
+
+    pub fn metrics(&self) -> SaveMetrics {
+        self.metrics
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault_storage::MemoryStorage;
+    use crate::vault_tests::test_key;
+
+    #[tokio::test]
+    async fn concurrent_saves_share_one_write() {
+        let storage = Arc::new(MemoryStorage::default());
+        let queue = SaveQueue::new(
+            Arc::new(Mutex::new(ApiKeyVault::default())),
+            storage.clone(),
+            Arc::new(Mutex::new(VaultIndex::new())),
+        );
+
+        let results = tokio::join!(queue.save(), queue.save(), queue.save());
+        assert!(results.0.is_ok() && results.1.is_ok() && results.2.is_ok());
+        assert!(storage.vault.lock().unwrap().is_some());
+        let metrics = queue.metrics();
+        assert_eq!((metrics.requested, metrics.written), (3, 1));
+
+        queue.flush().await.unwrap();
+        let metrics = queue.metrics();
+        assert_eq!((metrics.requested, metrics.written), (4, 2));
+        assert!(metrics.last_saved_at.is_some() && metrics.last_error.is_none());
+    }
+
+    #[tokio::test]
+    async fn asking_for_a_save_invalidates_the_index_before_the_write() {
+        let vault = Arc::new(Mutex::new(ApiKeyVault::default()));
+        let index = Arc::new(Mutex::new(VaultIndex::new()));
+        let queue = SaveQueue::new(
+            vault.clone(),
+            Arc::new(MemoryStorage::default()),
+            index.clone(),
+        );
+        vault
+            .lock()
+            .await
+            .keys
+            .insert("a".to_string(), test_key("a").build());
+        assert_eq!(
+            index
+                .lock()
+                .await
+                .ids_by_service(&*vault.lock().await, "stripe"),
+            ["a"]
+        );
+
+        vault.lock().await.keys.get_mut("a").unwrap().service = "OpenAI".to_string();
+        queue.ticket().await;
+
+        assert_eq!(queue.metrics().written, 0);
+        let vault_guard = vault.lock().await;
+        let mut index_guard = index.lock().await;
+        assert!(index_guard
+            .ids_by_service(&vault_guard, "stripe")
+            .is_empty());
+        assert_eq!(index_guard.ids_by_service(&vault_guard, "openai"), ["a"]);
+    }
+}
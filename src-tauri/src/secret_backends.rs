@@ -0,0 +1,284 @@
+//! Keys whose value stays in a cloud secret manager.
+//!
+//! A remote key is a reference, `backend`, to a secret in AWS Secrets
+//! Manager, GCP Secret Manager or HashiCorp Vault (the targets of
+//! `secret_manager_export`), rather than a value: its `key` is empty and
+//! nothing of the secret is written to the vault. The value is fetched when
+//! asked for, with the credentials of the target's vault keys, and held in
+//! memory for `CACHE_TTL`; locking the vault forgets it.
+//!
+//! Asking for the value goes through the same paths as for local keys:
+//! `reveal_api_key`, `get_decrypted_api_key` and `POST
+//! /api/keys/{id}/decrypt`, with their confirmations and approvals. Env
+//! syncs skip remote keys, since writing the value into a file would
+//! persist it outside the secret manager.
+
+use crate::errors::KeyKeeperError;
+use crate::secret_manager_commands::client_from_vault;
+use crate::secret_manager_export::SecretManagerTarget;
+use crate::{activity, guards, log_audit_event, naming, save_vault, ApiKey, ApiKeyVault, AppState};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::State;
+use tokio::sync::Mutex as AsyncMutex;
+use zeroize::Zeroizing;
+
+/// How long a fetched value is served from memory
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Where a remote key's value lives
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BackendRef {
+    pub target: SecretManagerTarget,
+    /// ARN (AWS), resource name (GCP) or KV path (Vault)
+    pub remote_id: String,
+}
+
+struct CachedValue {
+    value: Zeroizing<String>,
+    fetched_at: Instant,
+    /// The reference the value was fetched for; a changed one makes it stale
+    fetched_for: BackendRef,
+}
+
+/// Values of remote keys, in memory only
+#[derive(Default)]
+pub struct ValueCache {
+    values: Mutex<HashMap<String, CachedValue>>,
+}
+
+impl ValueCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget every value; called when the vault locks
+    pub fn clear(&self) {
+        self.values.lock().unwrap().clear();
+    }
+
+    pub fn forget(&self, key_id: &str) {
+        self.values.lock().unwrap().remove(key_id);
+    }
+
+    fn get(&self, key_id: &str, backend: &BackendRef, now: Instant) -> Option<String> {
+        let values = self.values.lock().unwrap();
+        let cached = values.get(key_id)?;
+        (cached.fetched_for == *backend && now.duration_since(cached.fetched_at) < CACHE_TTL)
+            .then(|| cached.value.to_string())
+    }
+
+    fn put(&self, key_id: &str, backend: &BackendRef, value: &str, now: Instant) {
+        self.values.lock().unwrap().insert(
+            key_id.to_string(),
+            CachedValue {
+                value: Zeroizing::new(value.to_string()),
+                fetched_at: now,
+                fetched_for: backend.clone(),
+            },
+        );
+    }
+}
+
+pub fn is_remote(key: &ApiKey) -> bool {
+    key.backend.is_some()
+}
+
+/// Credentials of a remote key's target must be local keys
+fn validate(vault: &ApiKeyVault, backend: &BackendRef) -> Result<(), KeyKeeperError> {
+    if backend.remote_id.trim().is_empty() {
+        return Err(KeyKeeperError::Validation(
+            "The remote secret's id is required".to_string(),
+        ));
+    }
+    for key_id in backend.target.credential_key_ids() {
+        let key = vault.keys.get(&key_id).ok_or_else(|| {
+            KeyKeeperError::NotFound(format!("Credential key {} not found", key_id))
+        })?;
+        if is_remote(key) {
+            return Err(KeyKeeperError::Validation(format!(
+                "{} is itself a remote key and can't be a credential",
+                key.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+async fn fetch(
+    vault: &AsyncMutex<ApiKeyVault>,
+    backend: &BackendRef,
+) -> Result<String, KeyKeeperError> {
+    let client = client_from_vault(&*vault.lock().await, &backend.target)?;
+    client.read_secret(&backend.remote_id).await.map_err(|e| {
+        KeyKeeperError::Io(format!(
+            "Failed to read {} from {}: {}",
+            backend.remote_id,
+            backend.target.provider_name(),
+            e
+        ))
+    })
+}
+
+/// The value of `key`: its own, or for a remote key the secret manager's,
+/// served from the cache while fresh
+pub async fn value(
+    cache: &ValueCache,
+    vault: &AsyncMutex<ApiKeyVault>,
+    key: &ApiKey,
+) -> Result<String, KeyKeeperError> {
+    let Some(backend) = &key.backend else {
+        return Ok(key.key.clone());
+    };
+    if let Some(value) = cache.get(&key.id, backend, Instant::now()) {
+        return Ok(value);
+    }
+    let value = fetch(vault, backend).await?;
+    cache.put(&key.id, backend, &value, Instant::now());
+    Ok(value)
+}
+
+/// Add a key whose value stays in a secret manager. The secret is read once
+/// to check it can be.
+#[tauri::command]
+pub async fn add_remote_key(
+    api_key: ApiKey,
+    backend: BackendRef,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    validate(&*state.vault.lock().await, &backend)?;
+    let fetched = fetch(&state.vault, &backend).await;
+    if let Err(e) = &fetched {
+        log_audit_event(
+            &state,
+            "add_remote_key",
+            "api_key",
+            Some(&api_key.id),
+            false,
+            Some(&e.to_string()),
+        )
+        .await;
+    }
+    let value = fetched?;
+
+    let mut api_key = api_key;
+    api_key.key = String::new();
+    api_key.values.clear();
+    api_key.backend = Some(backend.clone());
+    let mut vault_guard = state.vault.lock().await;
+    naming::enforce(&vault_guard, &api_key)?;
+    activity::record(
+        &mut vault_guard,
+        "key_created",
+        &api_key,
+        activity::Actor::ui(),
+        None,
+        Some(format!("Read from {}", backend.target.provider_name())),
+    );
+    vault_guard.keys.insert(api_key.id.clone(), api_key.clone());
+    drop(vault_guard);
+    state
+        .remote_values
+        .put(&api_key.id, &backend, &value, Instant::now());
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "add_remote_key",
+        "api_key",
+        Some(&api_key.id),
+        true,
+        Some(backend.target.provider_name()),
+    )
+    .await;
+    Ok(())
+}
+
+/// Read a remote key's secret again, bypassing the cache, to check it's
+/// still reachable
+#[tauri::command]
+pub async fn test_remote_key(
+    key_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let key = state
+        .vault
+        .lock()
+        .await
+        .keys
+        .get(&key_id)
+        .cloned()
+        .ok_or_else(|| KeyKeeperError::not_found("API key"))?;
+    if !is_remote(&key) {
+        return Err(KeyKeeperError::Validation(
+            "The key's value is kept in the vault".to_string(),
+        ));
+    }
+    state.remote_values.forget(&key_id);
+    let result = value(&state.remote_values, &state.vault, &key).await;
+    log_audit_event(
+        &state,
+        "test_remote_key",
+        "api_key",
+        Some(&key_id),
+        result.is_ok(),
+        result.as_ref().err().map(|e| e.to_string()).as_deref(),
+    )
+    .await;
+    result.map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault_tests::test_key;
+
+    #[test]
+    fn cached_values_expire_and_follow_their_reference() {
+        let backend = BackendRef {
+            target: SecretManagerTarget::HashicorpVault {
+                address: "https://vault.example.com".to_string(),
+                mount: "secret".to_string(),
+                token_key_id: "token".to_string(),
+                namespace: None,
+            },
+            remote_id: "secret/stripe".to_string(),
+        };
+        let cache = ValueCache::new();
+        let fetched_at = Instant::now();
+        cache.put("k1", &backend, "sk_live_1", fetched_at);
+        assert_eq!(
+            cache.get("k1", &backend, fetched_at).as_deref(),
+            Some("sk_live_1")
+        );
+        assert_eq!(cache.get("k1", &backend, fetched_at + CACHE_TTL), None);
+
+        let moved = BackendRef {
+            remote_id: "secret/stripe-live".to_string(),
+            ..backend.clone()
+        };
+        assert_eq!(cache.get("k1", &moved, fetched_at), None);
+
+        cache.clear();
+        assert_eq!(cache.get("k1", &backend, fetched_at), None);
+
+        let mut vault = ApiKeyVault::default();
+        assert!(validate(&vault, &backend).is_err());
+        let token = test_key("token")
+            .name("Vault token")
+            .service("Vault")
+            .secret("hvs.1")
+            .environment("production")
+            .build();
+        vault.keys.insert(token.id.clone(), token);
+        assert!(validate(&vault, &backend).is_ok());
+        vault.keys.get_mut("token").unwrap().backend = Some(backend.clone());
+        assert!(validate(&vault, &backend).is_err());
+    }
+}
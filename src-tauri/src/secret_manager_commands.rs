@@ -0,0 +1,205 @@
+use crate::errors::KeyKeeperError;
+use crate::secret_manager_export::{
+    default_remote_name, hash_secret_value, plan_item, PushAction, PushPlanItem, PushResult,
+    RemoteSecretRef, SecretManagerClient, SecretManagerCredentials, SecretManagerTarget,
+};
+use crate::{guards, log_audit_event, save_vault, tray_status, ApiKeyVault, AppState};
+use chrono::Utc;
+use std::collections::HashMap;
+use tauri::State;
+use tracing::{info, warn};
+
+/// Build a client for `target` using credentials stored in the vault
+async fn build_client(
+    target: &SecretManagerTarget,
+    state: &State<'_, AppState>,
+) -> Result<SecretManagerClient, KeyKeeperError> {
+    client_from_vault(&*state.vault.lock().await, target)
+}
+
+/// Build a client for `target` from the credential keys in `vault`
+pub fn client_from_vault(
+    vault: &ApiKeyVault,
+    target: &SecretManagerTarget,
+) -> Result<SecretManagerClient, KeyKeeperError> {
+    let mut credentials = HashMap::new();
+    for key_id in target.credential_key_ids() {
+        let key = vault
+            .keys
+            .get(&key_id)
+            .ok_or_else(|| KeyKeeperError::NotFound(format!("Credential key {} not found", key_id)))?;
+        credentials.insert(key_id, key.key.clone());
+    }
+    SecretManagerClient::new(target.clone(), SecretManagerCredentials::new(credentials))
+}
+
+async fn compute_plan(
+    client: &SecretManagerClient,
+    target: &SecretManagerTarget,
+    key_ids: &[String],
+    mapping: &HashMap<String, String>,
+    state: &State<'_, AppState>,
+) -> Result<Vec<PushPlanItem>, KeyKeeperError> {
+    // Snapshot the keys so the vault lock isn't held across network calls
+    let keys: Vec<_> = {
+        let vault_guard = state.vault.lock().await;
+        key_ids
+            .iter()
+            .map(|id| {
+                vault_guard
+                    .keys
+                    .get(id)
+                    .cloned()
+                    .ok_or_else(|| KeyKeeperError::NotFound(format!("API key {} not found", id)))
+            })
+            .collect::<Result<_, _>>()?
+    };
+
+    let mut plan = Vec::new();
+    for key in keys {
+        if key.backend.is_some() {
+            return Err(KeyKeeperError::Validation(format!(
+                "{} is read from a secret manager and can't be pushed",
+                key.name
+            )));
+        }
+        let remote_name = mapping
+            .get(&key.id)
+            .cloned()
+            .unwrap_or_else(|| default_remote_name(&key.name));
+        target
+            .validate_remote_name(&remote_name)
+            .map_err(KeyKeeperError::Validation)?;
+        let remote_id = client
+            .find_secret(&remote_name)
+            .await
+            .map_err(|e| KeyKeeperError::Internal(format!("Failed to look up {}: {}", remote_name, e)))?;
+        plan.push(plan_item(
+            &key.id,
+            &key.name,
+            &key.key,
+            &remote_name,
+            target.provider_name(),
+            remote_id,
+            &key.remote_secrets,
+        ));
+    }
+    Ok(plan)
+}
+
+/// Preview what `push_keys_to_secret_manager` would do without writing anything
+#[tauri::command]
+pub async fn plan_secret_manager_push(
+    target: SecretManagerTarget,
+    key_ids: Vec<String>,
+    mapping: Option<HashMap<String, String>>,
+    state: State<'_, AppState>,
+) -> Result<Vec<PushPlanItem>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let client = build_client(&target, &state).await?;
+    compute_plan(&client, &target, &key_ids, &mapping.unwrap_or_default(), &state).await
+}
+
+/// Push selected keys to AWS Secrets Manager, GCP Secret Manager or HashiCorp Vault
+#[tauri::command]
+pub async fn push_keys_to_secret_manager(
+    target: SecretManagerTarget,
+    key_ids: Vec<String>,
+    mapping: Option<HashMap<String, String>>,
+    state: State<'_, AppState>,
+) -> Result<Vec<PushResult>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let _syncing = tray_status::begin_sync();
+
+    let client = build_client(&target, &state).await?;
+    let plan = compute_plan(&client, &target, &key_ids, &mapping.unwrap_or_default(), &state).await?;
+    let provider = target.provider_name().to_string();
+
+    let mut results = Vec::new();
+    for item in plan {
+        if item.action == PushAction::Unchanged {
+            results.push(PushResult {
+                key_id: item.key_id,
+                remote_name: item.remote_name,
+                action: item.action,
+                success: true,
+                remote_id: item.remote_id,
+                error: None,
+            });
+            continue;
+        }
+
+        let value = {
+            let vault_guard = state.vault.lock().await;
+            vault_guard.keys.get(&item.key_id).map(|k| k.key.clone())
+        };
+        let Some(value) = value else {
+            results.push(PushResult {
+                key_id: item.key_id,
+                remote_name: item.remote_name,
+                action: item.action,
+                success: false,
+                remote_id: None,
+                error: Some("API key not found".to_string()),
+            });
+            continue;
+        };
+
+        let exists = item.action == PushAction::Update;
+        match client.write_secret(&item.remote_name, &value, exists).await {
+            Ok(remote_id) => {
+                let mut vault_guard = state.vault.lock().await;
+                if let Some(key) = vault_guard.keys.get_mut(&item.key_id) {
+                    key.remote_secrets
+                        .retain(|r| !(r.provider == provider && r.remote_name == item.remote_name));
+                    key.remote_secrets.push(RemoteSecretRef {
+                        provider: provider.clone(),
+                        remote_id: remote_id.clone(),
+                        remote_name: item.remote_name.clone(),
+                        pushed_at: Utc::now(),
+                        value_hash: hash_secret_value(&value),
+                    });
+                }
+                drop(vault_guard);
+
+                info!("Pushed key {} to {} as {}", item.key_id, provider, remote_id);
+                results.push(PushResult {
+                    key_id: item.key_id,
+                    remote_name: item.remote_name,
+                    action: item.action,
+                    success: true,
+                    remote_id: Some(remote_id),
+                    error: None,
+                });
+            }
+            Err(e) => {
+                warn!("Failed to push key {} to {}: {}", item.key_id, provider, e);
+                results.push(PushResult {
+                    key_id: item.key_id,
+                    remote_name: item.remote_name,
+                    action: item.action,
+                    success: false,
+                    remote_id: None,
+                    error: Some(e.to_string()),
+                });
+            }
+        }
+    }
+
+    save_vault(&state).await?;
+
+    let pushed = results.iter().filter(|r| r.success && r.action != PushAction::Unchanged).count();
+    let failed = results.iter().filter(|r| !r.success).count();
+    log_audit_event(
+        &state,
+        "push_to_secret_manager",
+        "api_key",
+        Some(&provider),
+        failed == 0,
+        Some(&format!("Pushed {} keys, {} failed", pushed, failed)),
+    )
+    .await;
+
+    Ok(results)
+}
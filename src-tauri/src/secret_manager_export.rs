@@ -0,0 +1,660 @@
+use crate::errors::KeyKeeperError;
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use tracing::{debug, info};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Destination for a secret manager push.
+///
+/// Credentials are never passed in directly: every `*_key_id` field references
+/// an API key already stored in the vault, whose value is used as the credential.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum SecretManagerTarget {
+    AwsSecretsManager {
+        region: String,
+        access_key_id_key_id: String,
+        secret_access_key_key_id: String,
+        session_token_key_id: Option<String>,
+    },
+    GcpSecretManager {
+        project_id: String,
+        /// OAuth2 access token (e.g. from `gcloud auth print-access-token`)
+        access_token_key_id: String,
+    },
+    HashicorpVault {
+        address: String,
+        /// KV v2 mount point, e.g. "secret"
+        mount: String,
+        token_key_id: String,
+        namespace: Option<String>,
+    },
+}
+
+impl SecretManagerTarget {
+    pub fn provider_name(&self) -> &'static str {
+        match self {
+            SecretManagerTarget::AwsSecretsManager { .. } => "aws_secrets_manager",
+            SecretManagerTarget::GcpSecretManager { .. } => "gcp_secret_manager",
+            SecretManagerTarget::HashicorpVault { .. } => "hashicorp_vault",
+        }
+    }
+
+    /// Vault key IDs this target needs to authenticate
+    pub fn credential_key_ids(&self) -> Vec<String> {
+        match self {
+            SecretManagerTarget::AwsSecretsManager {
+                access_key_id_key_id,
+                secret_access_key_key_id,
+                session_token_key_id,
+                ..
+            } => {
+                let mut ids = vec![access_key_id_key_id.clone(), secret_access_key_key_id.clone()];
+                if let Some(id) = session_token_key_id {
+                    ids.push(id.clone());
+                }
+                ids
+            }
+            SecretManagerTarget::GcpSecretManager { access_token_key_id, .. } => {
+                vec![access_token_key_id.clone()]
+            }
+            SecretManagerTarget::HashicorpVault { token_key_id, .. } => vec![token_key_id.clone()],
+        }
+    }
+
+    /// Check that `remote_name` is a name this provider accepts. GCP and
+    /// Vault names end up in URL paths, so they're also kept from reaching
+    /// other resources.
+    pub fn validate_remote_name(&self, remote_name: &str) -> std::result::Result<(), String> {
+        let valid = match self {
+            // Names go in the JSON body; these are the characters AWS allows
+            SecretManagerTarget::AwsSecretsManager { .. } => {
+                (1..=512).contains(&remote_name.len())
+                    && remote_name
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || "/_+=.@-".contains(c))
+            }
+            SecretManagerTarget::GcpSecretManager { .. } => {
+                (1..=255).contains(&remote_name.len())
+                    && remote_name
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            }
+            SecretManagerTarget::HashicorpVault { .. } => remote_name
+                .split('/')
+                .all(|segment| !matches!(segment, "" | "." | "..")),
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(format!(
+                "\"{}\" is not a valid secret name for {}",
+                remote_name,
+                self.provider_name()
+            ))
+        }
+    }
+}
+
+/// Percent-encode each `/`-separated segment of `path`
+fn encode_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Reference to a copy of a vault key living in an external secret manager
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteSecretRef {
+    pub provider: String,
+    /// ARN (AWS), resource name (GCP) or KV path (Vault)
+    pub remote_id: String,
+    pub remote_name: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub pushed_at: DateTime<Utc>,
+    /// SHA-256 of the value at push time, used to detect local changes
+    pub value_hash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PushAction {
+    Create,
+    Update,
+    Unchanged,
+}
+
+/// A single entry of a push plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushPlanItem {
+    pub key_id: String,
+    pub key_name: String,
+    pub remote_name: String,
+    pub remote_id: Option<String>,
+    pub action: PushAction,
+    pub reason: String,
+}
+
+/// Outcome of applying one plan item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushResult {
+    pub key_id: String,
+    pub remote_name: String,
+    pub action: PushAction,
+    pub success: bool,
+    pub remote_id: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Resolved credentials for a target
+pub struct SecretManagerCredentials {
+    values: HashMap<String, String>,
+}
+
+impl SecretManagerCredentials {
+    pub fn new(values: HashMap<String, String>) -> Self {
+        Self { values }
+    }
+
+    fn get(&self, key_id: &str) -> Result<&str> {
+        self.values
+            .get(key_id)
+            .map(|v| v.as_str())
+            .ok_or_else(|| anyhow!("Credential key {} not found in vault", key_id))
+    }
+}
+
+pub fn hash_secret_value(value: &str) -> String {
+    format!("{:x}", Sha256::digest(value.as_bytes()))
+}
+
+/// Default remote name derived from the key name, e.g. "Stripe Live" -> "stripe-live"
+pub fn default_remote_name(key_name: &str) -> String {
+    let name: String = key_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    let name = name.trim_matches('-').to_string();
+    if name.is_empty() {
+        "keykeeper-secret".to_string()
+    } else {
+        name
+    }
+}
+
+/// Client for the supported secret managers
+pub struct SecretManagerClient {
+    client: reqwest::Client,
+    target: SecretManagerTarget,
+    credentials: SecretManagerCredentials,
+}
+
+impl SecretManagerClient {
+    pub fn new(
+        target: SecretManagerTarget,
+        credentials: SecretManagerCredentials,
+    ) -> std::result::Result<Self, KeyKeeperError> {
+        let client = reqwest::Client::builder()
+            .user_agent("KeyKeeper-SecretSync/1.0")
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .map_err(|e| KeyKeeperError::Internal(format!("Failed to build HTTP client: {}", e)))?;
+        Ok(Self {
+            client,
+            target,
+            credentials,
+        })
+    }
+
+    /// Look up a secret remotely, returning its remote identifier if it exists
+    pub async fn find_secret(&self, remote_name: &str) -> Result<Option<String>> {
+        match &self.target {
+            SecretManagerTarget::AwsSecretsManager { .. } => {
+                let body = serde_json::json!({ "SecretId": remote_name });
+                let (status, response) = self.aws_call("DescribeSecret", &body).await?;
+                if status.is_success() {
+                    Ok(response["ARN"].as_str().map(|s| s.to_string()))
+                } else if response["__type"]
+                    .as_str()
+                    .is_some_and(|t| t.contains("ResourceNotFoundException"))
+                {
+                    Ok(None)
+                } else {
+                    Err(anyhow!("AWS DescribeSecret failed ({}): {}", status, response))
+                }
+            }
+            SecretManagerTarget::GcpSecretManager { project_id, .. } => {
+                let url = format!(
+                    "https://secretmanager.googleapis.com/v1/projects/{}/secrets/{}",
+                    encode_path(project_id),
+                    encode_path(remote_name)
+                );
+                let response = self
+                    .client
+                    .get(&url)
+                    .bearer_auth(self.gcp_token()?)
+                    .send()
+                    .await?;
+                match response.status() {
+                    reqwest::StatusCode::NOT_FOUND => Ok(None),
+                    status if status.is_success() => {
+                        let json: serde_json::Value = response.json().await?;
+                        Ok(json["name"].as_str().map(|s| s.to_string()))
+                    }
+                    status => Err(anyhow!(
+                        "GCP secret lookup failed ({}): {}",
+                        status,
+                        response.text().await.unwrap_or_default()
+                    )),
+                }
+            }
+            SecretManagerTarget::HashicorpVault { address, mount, .. } => {
+                let url = format!(
+                    "{}/v1/{}/metadata/{}",
+                    address.trim_end_matches('/'),
+                    encode_path(mount),
+                    encode_path(remote_name)
+                );
+                let response = self.vault_request(reqwest::Method::GET, &url)?.send().await?;
+                match response.status() {
+                    reqwest::StatusCode::NOT_FOUND => Ok(None),
+                    status if status.is_success() => Ok(Some(format!("{}/{}", mount, remote_name))),
+                    status => Err(anyhow!(
+                        "Vault metadata lookup failed ({}): {}",
+                        status,
+                        response.text().await.unwrap_or_default()
+                    )),
+                }
+            }
+        }
+    }
+
+    /// Create or update a secret, returning its remote identifier
+    pub async fn write_secret(&self, remote_name: &str, value: &str, exists: bool) -> Result<String> {
+        match &self.target {
+            SecretManagerTarget::AwsSecretsManager { .. } => {
+                let (operation, body) = if exists {
+                    (
+                        "PutSecretValue",
+                        serde_json::json!({ "SecretId": remote_name, "SecretString": value }),
+                    )
+                } else {
+                    (
+                        "CreateSecret",
+                        serde_json::json!({
+                            "Name": remote_name,
+                            "SecretString": value,
+                            "Description": "Managed by KeyKeeper"
+                        }),
+                    )
+                };
+                let (status, response) = self.aws_call(operation, &body).await?;
+                if !status.is_success() {
+                    return Err(anyhow!("AWS {} failed ({}): {}", operation, status, response));
+                }
+                response["ARN"]
+                    .as_str()
+                    .map(|s| s.to_string())
+                    .ok_or_else(|| anyhow!("AWS {} response missing ARN", operation))
+            }
+            SecretManagerTarget::GcpSecretManager { project_id, .. } => {
+                let token = self.gcp_token()?;
+                let base = format!(
+                    "https://secretmanager.googleapis.com/v1/projects/{}",
+                    encode_path(project_id)
+                );
+                let secret = encode_path(remote_name);
+                if !exists {
+                    let response = self
+                        .client
+                        .post(format!("{}/secrets?secretId={}", base, secret))
+                        .bearer_auth(token)
+                        .json(&serde_json::json!({ "replication": { "automatic": {} } }))
+                        .send()
+                        .await?;
+                    if !response.status().is_success() {
+                        return Err(anyhow!(
+                            "GCP secret creation failed ({}): {}",
+                            response.status(),
+                            response.text().await.unwrap_or_default()
+                        ));
+                    }
+                }
+                let response = self
+                    .client
+                    .post(format!("{}/secrets/{}:addVersion", base, secret))
+                    .bearer_auth(token)
+                    .json(&serde_json::json!({
+                        "payload": { "data": general_purpose::STANDARD.encode(value) }
+                    }))
+                    .send()
+                    .await?;
+                if !response.status().is_success() {
+                    return Err(anyhow!(
+                        "GCP addVersion failed ({}): {}",
+                        response.status(),
+                        response.text().await.unwrap_or_default()
+                    ));
+                }
+                Ok(format!("projects/{}/secrets/{}", project_id, remote_name))
+            }
+            SecretManagerTarget::HashicorpVault { address, mount, .. } => {
+                let url = format!(
+                    "{}/v1/{}/data/{}",
+                    address.trim_end_matches('/'),
+                    encode_path(mount),
+                    encode_path(remote_name)
+                );
+                let response = self
+                    .vault_request(reqwest::Method::POST, &url)?
+                    .json(&serde_json::json!({ "data": { "value": value } }))
+                    .send()
+                    .await?;
+                if !response.status().is_success() {
+                    return Err(anyhow!(
+                        "Vault write failed ({}): {}",
+                        response.status(),
+                        response.text().await.unwrap_or_default()
+                    ));
+                }
+                Ok(format!("{}/{}", mount, remote_name))
+            }
+        }
+    }
+
+    /// Read the current value of a secret by its remote identifier
+    pub async fn read_secret(&self, remote_id: &str) -> Result<String> {
+        match &self.target {
+            SecretManagerTarget::AwsSecretsManager { .. } => {
+                let body = serde_json::json!({ "SecretId": remote_id });
+                let (status, response) = self.aws_call("GetSecretValue", &body).await?;
+                if !status.is_success() {
+                    return Err(anyhow!("AWS GetSecretValue failed ({}): {}", status, response));
+                }
+                response["SecretString"]
+                    .as_str()
+                    .map(|s| s.to_string())
+                    .ok_or_else(|| anyhow!("AWS GetSecretValue response missing SecretString"))
+            }
+            SecretManagerTarget::GcpSecretManager { .. } => {
+                let url = format!(
+                    "https://secretmanager.googleapis.com/v1/{}/versions/latest:access",
+                    encode_path(remote_id)
+                );
+                let response = self
+                    .client
+                    .get(&url)
+                    .bearer_auth(self.gcp_token()?)
+                    .send()
+                    .await?;
+                if !response.status().is_success() {
+                    return Err(anyhow!(
+                        "GCP secret access failed ({}): {}",
+                        response.status(),
+                        response.text().await.unwrap_or_default()
+                    ));
+                }
+                let json: serde_json::Value = response.json().await?;
+                let data = json["payload"]["data"]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("GCP secret access response missing payload"))?;
+                let bytes = general_purpose::STANDARD
+                    .decode(data)
+                    .context("GCP secret payload is not valid base64")?;
+                String::from_utf8(bytes).context("GCP secret payload is not valid UTF-8")
+            }
+            SecretManagerTarget::HashicorpVault { address, mount, .. } => {
+                let path = remote_id
+                    .strip_prefix(&format!("{}/", mount))
+                    .unwrap_or(remote_id);
+                let url = format!(
+                    "{}/v1/{}/data/{}",
+                    address.trim_end_matches('/'),
+                    encode_path(mount),
+                    encode_path(path)
+                );
+                let response = self.vault_request(reqwest::Method::GET, &url)?.send().await?;
+                if !response.status().is_success() {
+                    return Err(anyhow!(
+                        "Vault read failed ({}): {}",
+                        response.status(),
+                        response.text().await.unwrap_or_default()
+                    ));
+                }
+                let json: serde_json::Value = response.json().await?;
+                json["data"]["data"]["value"]
+                    .as_str()
+                    .map(|s| s.to_string())
+                    .ok_or_else(|| anyhow!("Vault secret {} has no \"value\" field", remote_id))
+            }
+        }
+    }
+
+    fn gcp_token(&self) -> Result<&str> {
+        match &self.target {
+            SecretManagerTarget::GcpSecretManager { access_token_key_id, .. } => {
+                self.credentials.get(access_token_key_id)
+            }
+            _ => Err(anyhow!("Not a GCP target")),
+        }
+    }
+
+    fn vault_request(&self, method: reqwest::Method, url: &str) -> Result<reqwest::RequestBuilder> {
+        match &self.target {
+            SecretManagerTarget::HashicorpVault { token_key_id, namespace, .. } => {
+                let mut builder = self
+                    .client
+                    .request(method, url)
+                    .header("X-Vault-Token", self.credentials.get(token_key_id)?);
+                if let Some(ns) = namespace {
+                    builder = builder.header("X-Vault-Namespace", ns);
+                }
+                Ok(builder)
+            }
+            _ => Err(anyhow!("Not a Vault target")),
+        }
+    }
+
+    /// Perform a SigV4-signed call against the Secrets Manager JSON API
+    async fn aws_call(
+        &self,
+        operation: &str,
+        body: &serde_json::Value,
+    ) -> Result<(reqwest::StatusCode, serde_json::Value)> {
+        let (region, access_key_id, secret_access_key, session_token) = match &self.target {
+            SecretManagerTarget::AwsSecretsManager {
+                region,
+                access_key_id_key_id,
+                secret_access_key_key_id,
+                session_token_key_id,
+            } => (
+                region.as_str(),
+                self.credentials.get(access_key_id_key_id)?,
+                self.credentials.get(secret_access_key_key_id)?,
+                match session_token_key_id {
+                    Some(id) => Some(self.credentials.get(id)?),
+                    None => None,
+                },
+            ),
+            _ => return Err(anyhow!("Not an AWS target")),
+        };
+
+        let host = format!("secretsmanager.{}.amazonaws.com", region);
+        let target = format!("secretsmanager.{}", operation);
+        let payload = body.to_string();
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date_stamp = now.format("%Y%m%d").to_string();
+
+        let mut headers: Vec<(&str, String)> = vec![
+            ("content-type", "application/x-amz-json-1.1".to_string()),
+            ("host", host.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = session_token {
+            headers.push(("x-amz-security-token", token.to_string()));
+        }
+        headers.push(("x-amz-target", target.clone()));
+
+        let authorization = sign_aws_request(
+            &headers,
+            &payload,
+            region,
+            "secretsmanager",
+            access_key_id,
+            secret_access_key,
+            &amz_date,
+            &date_stamp,
+        )?;
+
+        let mut request = self
+            .client
+            .post(format!("https://{}/", host))
+            .header("Authorization", authorization);
+        for (name, value) in &headers {
+            if *name != "host" {
+                request = request.header(*name, value);
+            }
+        }
+
+        debug!("AWS Secrets Manager call: {}", operation);
+        let response = request.body(payload).send().await.context("AWS request failed")?;
+        let status = response.status();
+        let json = response.json::<serde_json::Value>().await.unwrap_or(serde_json::Value::Null);
+        Ok((status, json))
+    }
+}
+
+/// Build an AWS Signature Version 4 `Authorization` header.
+/// `headers` must be lowercase and sorted by name.
+#[allow(clippy::too_many_arguments)]
+fn sign_aws_request(
+    headers: &[(&str, String)],
+    payload: &str,
+    region: &str,
+    service: &str,
+    access_key_id: &str,
+    secret_access_key: &str,
+    amz_date: &str,
+    date_stamp: &str,
+) -> Result<String> {
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+    let canonical_request = format!(
+        "POST\n/\n\n{}\n{}\n{}",
+        canonical_headers,
+        signed_headers,
+        hash_secret_value(payload)
+    );
+
+    let scope = format!("{}/{}/{}/aws4_request", date_stamp, region, service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hash_secret_value(&canonical_request)
+    );
+
+    let k_date = hmac_sha256(format!("AWS4{}", secret_access_key).as_bytes(), date_stamp.as_bytes())?;
+    let k_region = hmac_sha256(&k_date, region.as_bytes())?;
+    let k_service = hmac_sha256(&k_region, service.as_bytes())?;
+    let k_signing = hmac_sha256(&k_service, b"aws4_request")?;
+    let signature: String = hmac_sha256(&k_signing, string_to_sign.as_bytes())?
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+
+    Ok(format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        access_key_id, scope, signed_headers, signature
+    ))
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+    let mut mac = HmacSha256::new_from_slice(key).map_err(|e| anyhow!("Invalid HMAC key: {}", e))?;
+    mac.update(data);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+/// Decide what a push would do for a single key
+pub fn plan_item(
+    key_id: &str,
+    key_name: &str,
+    value: &str,
+    remote_name: &str,
+    provider: &str,
+    remote_id: Option<String>,
+    existing_refs: &[RemoteSecretRef],
+) -> PushPlanItem {
+    let previous = existing_refs
+        .iter()
+        .find(|r| r.provider == provider && r.remote_name == remote_name);
+
+    let (action, reason) = match (&remote_id, previous) {
+        (None, _) => (PushAction::Create, "Secret does not exist remotely".to_string()),
+        (Some(_), Some(prev)) if prev.value_hash == hash_secret_value(value) => (
+            PushAction::Unchanged,
+            format!("Value unchanged since last push at {}", prev.pushed_at),
+        ),
+        (Some(_), Some(_)) => (PushAction::Update, "Local value changed since last push".to_string()),
+        (Some(_), None) => (
+            PushAction::Update,
+            "Secret exists remotely but was not pushed by KeyKeeper".to_string(),
+        ),
+    };
+
+    info!("Push plan for {} -> {}: {:?}", key_id, remote_name, action);
+
+    PushPlanItem {
+        key_id: key_id.to_string(),
+        key_name: key_name.to_string(),
+        remote_name: remote_name.to_string(),
+        remote_id,
+        action,
+        reason,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remote_names_are_checked_per_provider() {
+        let gcp = SecretManagerTarget::GcpSecretManager {
+            project_id: "shop".to_string(),
+            access_token_key_id: "token".to_string(),
+        };
+        assert!(gcp.validate_remote_name("stripe-live_key").is_ok());
+        for name in ["", "stripe/live", "../other", "a?b", &"a".repeat(256)] {
+            assert!(gcp.validate_remote_name(name).is_err(), "{}", name);
+        }
+
+        let vault = SecretManagerTarget::HashicorpVault {
+            address: "https://vault.example.com".to_string(),
+            mount: "secret".to_string(),
+            token_key_id: "token".to_string(),
+            namespace: None,
+        };
+        assert!(vault.validate_remote_name("apps/shop/stripe").is_ok());
+        for name in ["", "/apps", "apps/", "apps//shop", "apps/../admin", "./shop"] {
+            assert!(vault.validate_remote_name(name).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn path_segments_are_encoded_but_slashes_kept() {
+        assert_eq!(encode_path("apps/shop key"), "apps/shop%20key");
+        assert_eq!(encode_path("a?b#c/d"), "a%3Fb%23c/d");
+    }
+}
@@ -0,0 +1,159 @@
+use crate::ApiKeyVault;
+use std::alloc::{alloc_zeroed, handle_alloc_error, Layout};
+use std::ops::Deref;
+use std::ptr::NonNull;
+use std::sync::Mutex;
+use tracing::debug;
+use zeroize::Zeroize;
+
+const KEY_LEN: usize = 32;
+
+/// Free key slots, by address. mlock works on whole pages and doesn't
+/// count, so unlocking one key's memory would unlock every other key on its
+/// page. Slots are carved instead from dedicated page-aligned pages that are
+/// locked once and never unlocked or freed; a dropped key's slot is wiped and
+/// reused.
+static FREE_SLOTS: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+fn allocate_slot() -> NonNull<[u8; KEY_LEN]> {
+    let mut free = FREE_SLOTS.lock().unwrap_or_else(|e| e.into_inner());
+    if free.is_empty() {
+        let page_size = region::page::size();
+        let layout =
+            Layout::from_size_align(page_size, page_size).expect("page size is a power of two");
+        // SAFETY: `layout` has a non-zero size
+        let page = unsafe { alloc_zeroed(layout) };
+        if page.is_null() {
+            handle_alloc_error(layout);
+        }
+        lock_pages(page, page_size);
+        free.extend(
+            (0..page_size / KEY_LEN)
+                .rev()
+                .map(|i| page as usize + i * KEY_LEN),
+        );
+    }
+    let address = free.pop().expect("a page holds at least one slot");
+    NonNull::new(address as *mut [u8; KEY_LEN]).expect("slots are never null")
+}
+
+/// A 256-bit key kept in memory locked into RAM where the platform allows
+/// (mlock / VirtualLock) and wiped when dropped.
+pub struct SecretKey {
+    slot: NonNull<[u8; KEY_LEN]>,
+}
+
+// SAFETY: a `SecretKey` owns its slot exclusively, like a `Box`
+unsafe impl Send for SecretKey {}
+unsafe impl Sync for SecretKey {}
+
+impl SecretKey {
+    pub fn zeroed() -> Self {
+        Self {
+            slot: allocate_slot(),
+        }
+    }
+
+    pub fn as_mut_bytes(&mut self) -> &mut [u8; KEY_LEN] {
+        // SAFETY: the slot is valid for as long as `self` and only `self` uses it
+        unsafe { self.slot.as_mut() }
+    }
+}
+
+impl Deref for SecretKey {
+    type Target = [u8; KEY_LEN];
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: see `as_mut_bytes`
+        unsafe { self.slot.as_ref() }
+    }
+}
+
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        // Freed slots are all zero, which `zeroed` relies on
+        self.as_mut_bytes().zeroize();
+        FREE_SLOTS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(self.slot.as_ptr() as usize);
+    }
+}
+
+/// Best-effort mlock. Failure (e.g. RLIMIT_MEMLOCK exhausted) is not fatal:
+/// keys are still zeroized on drop, they just may be swapped out.
+fn lock_pages(address: *const u8, len: usize) {
+    match region::lock(address, len) {
+        // Never unlocked, the pages live as long as the process
+        Ok(guard) => std::mem::forget(guard),
+        Err(e) => debug!("Could not lock secret memory: {}", e),
+    }
+}
+
+/// Overwrite every decrypted value held by the vault. Used on lock so key
+/// material doesn't linger in freed heap memory.
+pub fn wipe_vault_secrets(vault: &mut ApiKeyVault) {
+    for key in vault.keys.values_mut() {
+        key.key.zeroize();
+        for value in key.values.values_mut() {
+            value.zeroize();
+        }
+        if let Some(passphrase) = key.credential.as_mut().and_then(|c| c.passphrase.as_mut()) {
+            passphrase.zeroize();
+        }
+    }
+    if let Some(encryption_key) = vault.encryption_key.as_mut() {
+        encryption_key.zeroize();
+    }
+    for token in vault.recovery_tokens.iter_mut() {
+        token.token_hash.zeroize();
+    }
+    if let Some(kit) = vault.recovery_kit.as_mut() {
+        kit.recovery_key.zeroize();
+    }
+    if let Some(settings) = vault.email_delivery.as_mut() {
+        if let crate::email::EmailTransport::Smtp {
+            password: Some(password),
+            ..
+        } = &mut settings.transport
+        {
+            password.zeroize();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_get_their_own_zeroed_slots_and_wipe_them_on_drop() {
+        let page_size = region::page::size();
+        // More than a page's worth, so a second page is carved
+        let mut keys: Vec<SecretKey> = (0..page_size / KEY_LEN + 2)
+            .map(|_| SecretKey::zeroed())
+            .collect();
+        for (i, key) in keys.iter_mut().enumerate() {
+            assert_eq!(**key, [0u8; KEY_LEN]);
+            key.as_mut_bytes().fill(i as u8 | 1);
+        }
+        let mut addresses: Vec<usize> = keys.iter().map(|k| k.as_ptr() as usize).collect();
+        addresses.sort_unstable();
+        addresses.dedup();
+        assert_eq!(addresses.len(), keys.len());
+        assert!(addresses.windows(2).all(|w| w[1] - w[0] >= KEY_LEN));
+
+        drop(keys.pop());
+        let free = FREE_SLOTS.lock().unwrap();
+        // SAFETY: slot pages are never freed
+        assert!(free
+            .iter()
+            .all(|&address| unsafe { *(address as *const [u8; KEY_LEN]) } == [0u8; KEY_LEN]));
+        drop(free);
+        // Earlier keys are untouched by the drop
+        assert!(keys
+            .iter()
+            .enumerate()
+            .all(|(i, k)| **k == [i as u8 | 1; KEY_LEN]));
+    }
+}
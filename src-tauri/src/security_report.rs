@@ -0,0 +1,254 @@
+//! Security report: the issues that need attention across the vault and its
+//! projects, most severe first. It is computed from what the vault already
+//! records (expiry dates, rotation schedules, the latest git history scan of
+//! each project, sync policy violations in the audit log, access reviews)
+//! and never contains key values. Values are only read to
+//! tell live keys kept outside production, see `key_environment`.
+
+use crate::api_generator::ApiProvider;
+use crate::errors::KeyKeeperError;
+use crate::git_history::LeakKind;
+use crate::{
+    access_review, env_import, env_values, guards, key_environment, rotation, sync_policy,
+    ApiKeyVault, AppState,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::State;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueCategory {
+    ExpiredKey,
+    RotationOverdue,
+    CommittedSecret,
+    EnvironmentMismatch,
+    /// Production key synced outside production, see sync_policy.rs
+    PolicyViolation,
+    AccessReviewOverdue,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityIssue {
+    pub severity: Severity,
+    pub category: IssueCategory,
+    pub title: String,
+    pub detail: String,
+    pub key_id: Option<String>,
+    pub project_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitScanSummary {
+    pub project_path: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub scanned_at: DateTime<Utc>,
+    pub commits_scanned: usize,
+    pub findings: usize,
+    pub remediation: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityReport {
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub generated_at: DateTime<Utc>,
+    pub issues: Vec<SecurityIssue>,
+    pub git_scans: Vec<GitScanSummary>,
+}
+
+pub fn build_report(
+    vault: &ApiKeyVault,
+    providers: &[ApiProvider],
+    now: DateTime<Utc>,
+) -> SecurityReport {
+    let mut issues = Vec::new();
+
+    for key in vault.keys.values().filter(|k| k.is_active) {
+        if let Some(expiry) = key.expires_at.filter(|e| *e <= now) {
+            issues.push(SecurityIssue {
+                severity: Severity::High,
+                category: IssueCategory::ExpiredKey,
+                title: format!("{} has expired", key.name),
+                detail: format!("{} key expired on {}", key.service, expiry.date_naive()),
+                key_id: Some(key.id.clone()),
+                project_path: key.project_path.clone(),
+            });
+        }
+        if rotation::is_overdue(key, now) {
+            let due = rotation::next_due(key).unwrap_or(now);
+            issues.push(SecurityIssue {
+                severity: Severity::Medium,
+                category: IssueCategory::RotationOverdue,
+                title: format!("{} is overdue for rotation", key.name),
+                detail: format!(
+                    "Rotation was due on {} ({} days ago)",
+                    due.date_naive(),
+                    (now - due).num_days()
+                ),
+                key_id: Some(key.id.clone()),
+                project_path: key.project_path.clone(),
+            });
+        }
+        for environment in env_values::environments(key) {
+            let Some(inference) = env_values::value_for(key, &environment)
+                .and_then(|value| key_environment::infer(value, providers))
+            else {
+                continue;
+            };
+            if let Some(warning) = key_environment::mismatch(&inference, &environment) {
+                issues.push(SecurityIssue {
+                    severity: if key_environment::is_live(&inference) {
+                        Severity::High
+                    } else {
+                        Severity::Medium
+                    },
+                    category: IssueCategory::EnvironmentMismatch,
+                    title: format!("{} has a key for the wrong environment", key.name),
+                    detail: warning,
+                    key_id: Some(key.id.clone()),
+                    project_path: key.project_path.clone(),
+                });
+            }
+        }
+    }
+
+    for scan in &vault.git_history_scans {
+        // One issue per leaked secret, listing the commits it appears in
+        let mut leaks: BTreeMap<(String, Option<String>), Vec<&str>> = BTreeMap::new();
+        for finding in &scan.findings {
+            let what = match (&finding.key_name, &finding.pattern) {
+                (Some(name), _) => name.clone(),
+                (None, Some(pattern)) => pattern.clone(),
+                (None, None) => "Secret".to_string(),
+            };
+            let key_id = match finding.kind {
+                LeakKind::VaultKey => finding.key_id.clone(),
+                LeakKind::Pattern => None,
+            };
+            leaks
+                .entry((what, key_id))
+                .or_default()
+                .push(&finding.commit);
+        }
+        for ((what, key_id), mut commits) in leaks {
+            commits.dedup();
+            let short: Vec<&str> = commits.iter().map(|c| &c[..c.len().min(8)]).collect();
+            issues.push(SecurityIssue {
+                severity: if key_id.is_some() {
+                    Severity::Critical
+                } else {
+                    Severity::High
+                },
+                category: IssueCategory::CommittedSecret,
+                title: format!("{} committed to git history", what),
+                detail: format!("Found in commit(s) {}", short.join(", ")),
+                key_id,
+                project_path: Some(scan.project_path.clone()),
+            });
+        }
+    }
+
+    // One issue per key, counting the syncs that went ahead and were refused
+    let mut violations: BTreeMap<&str, (usize, usize, DateTime<Utc>)> = BTreeMap::new();
+    for log in vault
+        .audit_logs
+        .iter()
+        .filter(|log| log.action == sync_policy::VIOLATION_ACTION)
+    {
+        let Some(key_id) = log.resource_id.as_deref() else {
+            continue;
+        };
+        let entry = violations.entry(key_id).or_insert((0, 0, log.timestamp));
+        if log.success {
+            entry.0 += 1;
+        } else {
+            entry.1 += 1;
+        }
+        entry.2 = entry.2.max(log.timestamp);
+    }
+    for (key_id, (synced, refused, last)) in violations {
+        let key = vault.keys.get(key_id);
+        issues.push(SecurityIssue {
+            severity: if synced > 0 {
+                Severity::High
+            } else {
+                Severity::Low
+            },
+            category: IssueCategory::PolicyViolation,
+            title: format!(
+                "{} was synced outside production",
+                key.map_or(key_id, |k| k.name.as_str())
+            ),
+            detail: format!(
+                "Synced {} time(s), refused {} time(s), last on {}",
+                synced,
+                refused,
+                last.date_naive()
+            ),
+            key_id: Some(key_id.to_string()),
+            project_path: key.and_then(|k| k.project_path.clone()),
+        });
+    }
+
+    if access_review::is_overdue(vault, now) {
+        let due = access_review::next_due(vault).unwrap_or(now);
+        let detail = match access_review::open_review(vault) {
+            Some(review) => {
+                let (attested, total) = review.progress();
+                format!(
+                    "Due on {}, {} of {} entries attested",
+                    due.date_naive(),
+                    attested,
+                    total
+                )
+            }
+            None => format!("The next review was due on {}", due.date_naive()),
+        };
+        issues.push(SecurityIssue {
+            severity: Severity::Medium,
+            category: IssueCategory::AccessReviewOverdue,
+            title: "Access review is overdue".to_string(),
+            detail,
+            key_id: None,
+            project_path: None,
+        });
+    }
+
+    issues.sort_by_key(|issue| std::cmp::Reverse(issue.severity));
+    SecurityReport {
+        generated_at: now,
+        issues,
+        git_scans: vault
+            .git_history_scans
+            .iter()
+            .map(|scan| GitScanSummary {
+                project_path: scan.project_path.clone(),
+                scanned_at: scan.scanned_at,
+                commits_scanned: scan.commits_scanned,
+                findings: scan.findings.len(),
+                remediation: scan.remediation.clone(),
+            })
+            .collect(),
+    }
+}
+
+#[tauri::command]
+pub async fn get_security_report(
+    state: State<'_, AppState>,
+) -> Result<SecurityReport, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let providers = env_import::providers(&state).await;
+    let vault_guard = state.vault.lock().await;
+    Ok(build_report(&vault_guard, &providers, Utc::now()))
+}
@@ -0,0 +1,323 @@
+use crate::lock_state::{self, LockReason};
+use crate::{devices, retention};
+use crate::errors::KeyKeeperError;
+use crate::{
+    get_device_id, get_device_platform, guards, log_audit_event, save_vault, ApiKeyVault, AppState,
+    PersistentSession,
+};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+const SWEEP_INTERVAL_SECS: u64 = 60;
+/// Unlock session length when there's no user account to read it from
+const DEFAULT_SESSION_MINUTES: u64 = 60;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionKind {
+    Biometric,
+    Persistent,
+}
+
+/// Session as shown in the sessions list, whichever store it comes from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub session_id: String,
+    pub kind: SessionKind,
+    pub user_id: String,
+    pub device_id: Option<String>,
+    pub device_info: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub last_accessed: Option<DateTime<Utc>>,
+    pub is_remember_me: bool,
+    /// The session backing the current unlock
+    pub is_current: bool,
+}
+
+/// Deactivate expired sessions and prune inactive ones past the retention
+/// policy. Returns whether the vault changed.
+pub fn sweep_expired(vault: &mut ApiKeyVault, now: DateTime<Utc>) -> bool {
+    let mut changed = false;
+
+    for session in vault.biometric_sessions.iter_mut() {
+        if session.is_active && session.expires_at <= now {
+            session.is_active = false;
+            changed = true;
+        }
+    }
+    for session in vault.persistent_sessions.iter_mut() {
+        if session.is_active && session.expires_at <= now {
+            session.is_active = false;
+            changed = true;
+        }
+    }
+
+    let purged = retention::purge(vault, retention::DataClass::Sessions, now);
+    changed || purged > 0
+}
+
+pub fn is_session_active(vault: &ApiKeyVault, session_id: &str, now: DateTime<Utc>) -> bool {
+    vault
+        .biometric_sessions
+        .iter()
+        .any(|s| s.session_id == session_id && s.is_active && s.expires_at > now)
+        || vault
+            .persistent_sessions
+            .iter()
+            .any(|s| s.session_id == session_id && s.is_active && s.expires_at > now)
+}
+
+fn deactivate(vault: &mut ApiKeyVault, session_id: &str) {
+    for session in vault
+        .biometric_sessions
+        .iter_mut()
+        .filter(|s| s.session_id == session_id)
+    {
+        session.is_active = false;
+    }
+    for session in vault
+        .persistent_sessions
+        .iter_mut()
+        .filter(|s| s.session_id == session_id)
+    {
+        session.is_active = false;
+    }
+}
+
+fn active_sessions(
+    vault: &ApiKeyVault,
+    current: Option<&str>,
+    now: DateTime<Utc>,
+) -> Vec<SessionInfo> {
+    let biometric = vault
+        .biometric_sessions
+        .iter()
+        .filter(|s| s.is_active && s.expires_at > now)
+        .map(|s| SessionInfo {
+            session_id: s.session_id.clone(),
+            kind: SessionKind::Biometric,
+            user_id: s.user_id.clone(),
+            device_id: Some(s.device_id.clone()),
+            device_info: get_device_platform(),
+            created_at: s.created_at,
+            expires_at: s.expires_at,
+            last_accessed: None,
+            is_remember_me: false,
+            is_current: current == Some(s.session_id.as_str()),
+        });
+    let persistent = vault
+        .persistent_sessions
+        .iter()
+        .filter(|s| s.is_active && s.expires_at > now)
+        .map(|s| SessionInfo {
+            session_id: s.session_id.clone(),
+            kind: SessionKind::Persistent,
+            user_id: s.user_id.clone(),
+            device_id: s.device_id.clone(),
+            device_info: s.device_info.clone(),
+            created_at: s.created_at,
+            expires_at: s.expires_at,
+            last_accessed: Some(s.last_accessed),
+            is_remember_me: s.is_remember_me,
+            is_current: current == Some(s.session_id.as_str()),
+        });
+
+    let mut sessions: Vec<SessionInfo> = biometric.chain(persistent).collect();
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+    sessions
+}
+
+/// Start the session backing a password unlock. Its lifetime comes from the
+/// account's `session_timeout`, or the `vault.auto_lock_minutes` config;
+/// when it runs out the sweeper locks the vault.
+pub async fn begin_unlock_session(state: &State<'_, AppState>) -> Result<String, String> {
+    let now = Utc::now();
+    let session_id = format!("session_{}", Uuid::new_v4());
+    {
+        let mut vault_guard = state.vault.lock().await;
+        // Sessions are only readable once decrypted, so this is the first
+        // chance to expire what ran out while the app was closed
+        sweep_expired(&mut vault_guard, now);
+
+        let (user_id, minutes) = vault_guard
+            .user_account
+            .as_ref()
+            .map(|a| (a.id.clone(), a.session_timeout))
+            .unwrap_or_else(|| ("local".to_string(), DEFAULT_SESSION_MINUTES));
+        let minutes = state
+            .config
+            .effective()
+            .vault
+            .auto_lock_minutes
+            .unwrap_or(minutes);
+        vault_guard.persistent_sessions.push(PersistentSession {
+            session_id: session_id.clone(),
+            user_id,
+            created_at: now,
+            expires_at: now + ChronoDuration::minutes(minutes.max(1) as i64),
+            last_accessed: now,
+            device_info: get_device_platform(),
+            device_id: Some(get_device_id()),
+            is_remember_me: false,
+            is_active: true,
+        });
+        devices::touch(&mut vault_guard, &get_device_id(), now);
+    }
+    set_unlock_session(state, &session_id).await;
+    save_vault(state).await.map_err(|e| e.to_string())?;
+    Ok(session_id)
+}
+
+/// Tie the unlock state to an already created session (e.g. biometric)
+pub async fn set_unlock_session(state: &State<'_, AppState>, session_id: &str) {
+    let previous = state
+        .unlock_session
+        .lock()
+        .await
+        .replace(session_id.to_string());
+    if let Some(previous) = previous.filter(|p| p != session_id) {
+        deactivate(&mut *state.vault.lock().await, &previous);
+    }
+    // The tray lists the pinned keys again once the bus passes this on
+    lock_state::set_unlocked();
+}
+
+/// End the unlock session when the vault is locked. The change is persisted
+/// by the caller's save.
+pub async fn end_unlock_session(state: &State<'_, AppState>) {
+    if let Some(session_id) = state.unlock_session.lock().await.take() {
+        deactivate(&mut *state.vault.lock().await, &session_id);
+    }
+}
+
+/// Periodically expire sessions and lock the vault once its unlock session
+/// has expired or been revoked, or the app has been idle for
+/// `vault.inactivity_lock_minutes`
+pub async fn run_session_sweeper(app: AppHandle) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(SWEEP_INTERVAL_SECS)).await;
+
+        let state: State<'_, AppState> = app.state();
+        if !*state.is_unlocked.lock().await {
+            continue;
+        }
+
+        let now = Utc::now();
+        let current = state.unlock_session.lock().await.clone();
+        let (changed, session_valid) = {
+            let mut vault_guard = state.vault.lock().await;
+            let changed = sweep_expired(&mut vault_guard, now);
+            let valid = current
+                .as_deref()
+                .is_none_or(|id| is_session_active(&vault_guard, id, now));
+            (changed, valid)
+        };
+
+        if !session_valid {
+            info!("Unlock session ended, locking vault");
+            log_audit_event(
+                &state,
+                "session_expired",
+                "session",
+                current.as_deref(),
+                true,
+                None,
+            )
+            .await;
+            if let Err(e) = crate::lock_vault_for(app.state(), LockReason::SessionExpired).await {
+                warn!("Failed to lock vault after session expiry: {}", e);
+            }
+            continue;
+        }
+
+        let idle_limit = state.config.effective().vault.inactivity_lock_minutes;
+        if idle_limit.is_some_and(|minutes| lock_state::inactive_for(minutes, now)) {
+            info!("No activity in the app, locking vault");
+            log_audit_event(&state, "inactivity_lock", "vault", None, true, None).await;
+            if let Err(e) = crate::lock_vault_for(app.state(), LockReason::Inactivity).await {
+                warn!("Failed to lock inactive vault: {}", e);
+            }
+        } else if changed {
+            if let Err(e) = save_vault(&state).await {
+                warn!("Failed to save swept sessions: {}", e);
+            }
+        }
+    }
+}
+
+/// Active biometric and persistent sessions with their device info
+#[tauri::command]
+pub async fn list_active_sessions(
+    state: State<'_, AppState>,
+) -> Result<Vec<SessionInfo>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let current = state.unlock_session.lock().await.clone();
+    let vault_guard = state.vault.lock().await;
+    Ok(active_sessions(
+        &vault_guard,
+        current.as_deref(),
+        Utc::now(),
+    ))
+}
+
+/// Deactivate every biometric and persistent session but `keep`, returning
+/// how many were
+pub fn deactivate_all(vault: &mut ApiKeyVault, keep: Option<&str>) -> usize {
+    let mut revoked = 0;
+    for session in vault
+        .biometric_sessions
+        .iter_mut()
+        .filter(|s| s.is_active && keep != Some(s.session_id.as_str()))
+    {
+        session.is_active = false;
+        revoked += 1;
+    }
+    for session in vault
+        .persistent_sessions
+        .iter_mut()
+        .filter(|s| s.is_active && keep != Some(s.session_id.as_str()))
+    {
+        session.is_active = false;
+        revoked += 1;
+    }
+    revoked
+}
+
+/// Revoke every biometric and persistent session. With `keep_current` the
+/// session backing this unlock survives; otherwise the vault is locked too.
+#[tauri::command]
+pub async fn revoke_all_sessions(
+    keep_current: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<usize, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let keep = if keep_current.unwrap_or(false) {
+        state.unlock_session.lock().await.clone()
+    } else {
+        None
+    };
+    let revoked = deactivate_all(&mut *state.vault.lock().await, keep.as_deref());
+
+    save_vault(&state).await?;
+    log_audit_event(
+        &state,
+        "revoke_all_sessions",
+        "session",
+        None,
+        true,
+        Some(&format!("{} sessions revoked", revoked)),
+    )
+    .await;
+
+    if keep.is_none() {
+        crate::lock_vault_for(state, LockReason::SessionsRevoked).await?;
+    }
+    Ok(revoked)
+}
@@ -0,0 +1,76 @@
+//! Orderly exit.
+//!
+//! Quitting from the UI, the tray or the OS runs the same sequence: stop
+//! accepting HTTP connections, wait for requests and saves already in
+//! flight or queued, lock the vault (which writes it one last time), save
+//! the window state, hide the tray icon and stop the remaining background
+//! tasks. Each wait is bounded so a stuck step can't keep the app from
+//! exiting.
+
+use crate::{lock_vault, AppState, HTTP_SERVER_TASK, TRAY_ID};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_window_state::{AppHandleExt, StateFlags};
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+/// How long in-flight HTTP requests and saves may take to finish
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+const TASK_STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+static COMPLETE: AtomicBool = AtomicBool::new(false);
+static FINISHED: Notify = Notify::const_new();
+
+/// Whether the sequence has run, so the exit it ends with can go ahead
+pub fn is_complete() -> bool {
+    COMPLETE.load(Ordering::SeqCst)
+}
+
+/// Run the shutdown sequence once; later callers wait for the first one
+pub async fn run(app: &AppHandle) {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        let finished = FINISHED.notified();
+        if !is_complete() {
+            finished.await;
+        }
+        return;
+    }
+
+    info!("Shutting down");
+    let state: State<'_, AppState> = app.state();
+
+    // Stop accepting connections; the listener socket closes with the task
+    state.vscode_server_running.store(false, Ordering::SeqCst);
+    if state.tasks.cancel(HTTP_SERVER_TASK).unwrap_or(false) {
+        state.health.set_server_running(false);
+    }
+
+    // Requests being answered and saves being written
+    if !state.health.wait_idle(DRAIN_TIMEOUT).await {
+        warn!(
+            "Shutting down with pending tasks: {:?}",
+            state.health.pending_task_names()
+        );
+    }
+
+    if *state.is_unlocked.lock().await {
+        if let Err(e) = lock_vault(app.state()).await {
+            warn!("Failed to lock the vault on exit: {}", e);
+        }
+    }
+
+    if let Err(e) = app.save_window_state(StateFlags::all()) {
+        warn!("Failed to save window state: {}", e);
+    }
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_visible(false);
+    }
+
+    state.tasks.shutdown(TASK_STOP_TIMEOUT).await;
+
+    COMPLETE.store(true, Ordering::SeqCst);
+    FINISHED.notify_waiters();
+    info!("Shutdown complete");
+}
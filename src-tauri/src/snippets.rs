@@ -0,0 +1,357 @@
+//! Code snippets tied to providers and keys.
+//!
+//! A snippet is a piece of code with `{{variable}}` placeholders. Rendering it
+//! for a key fills the built-in variables with that key's details, so the
+//! inserted code reads the right environment variable. The key value itself
+//! is never substituted.
+
+use crate::errors::KeyKeeperError;
+use crate::{
+    env_var_name_for_key, guards, log_audit_event, save_vault, ApiKey, ApiKeyVault, AppState,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::State;
+
+/// Variables filled from the key a snippet is rendered for
+pub const BUILTIN_VARIABLES: &[&str] = &[
+    "env_var",
+    "env_access",
+    "key_name",
+    "service",
+    "environment",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnippetVariable {
+    pub name: String,
+    pub description: Option<String>,
+    pub default_value: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snippet {
+    pub id: String,
+    pub title: String,
+    pub description: Option<String>,
+    /// Programming language, e.g. "typescript" or "python"
+    pub language: String,
+    pub code: String,
+    /// Provider the snippet is for; matched against key services
+    pub provider_id: Option<String>,
+    /// Custom variables besides the built-in ones
+    pub variables: Vec<SnippetVariable>,
+    pub tags: Vec<String>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedSnippet {
+    pub snippet_id: String,
+    pub key_id: String,
+    pub language: String,
+    pub code: String,
+    pub variables: HashMap<String, String>,
+    /// Placeholders left in the code because no value was available
+    pub missing_variables: Vec<String>,
+}
+
+/// Expression reading `var` from the environment in `language`
+pub fn env_access_expression(language: &str, var: &str) -> String {
+    match language.to_lowercase().as_str() {
+        "javascript" | "typescript" | "js" | "ts" | "jsx" | "tsx" => {
+            format!("process.env.{}", var)
+        }
+        "python" | "py" => format!("os.environ[\"{}\"]", var),
+        "rust" | "rs" => format!("std::env::var(\"{}\")?", var),
+        "go" => format!("os.Getenv(\"{}\")", var),
+        "ruby" | "rb" => format!("ENV[\"{}\"]", var),
+        "php" => format!("getenv('{}')", var),
+        "java" | "kotlin" => format!("System.getenv(\"{}\")", var),
+        "csharp" | "c#" => format!("Environment.GetEnvironmentVariable(\"{}\")", var),
+        "shell" | "bash" | "sh" | "zsh" => format!("${}", var),
+        _ => var.to_string(),
+    }
+}
+
+/// Names of the `{{name}}` placeholders in `code`, in order of appearance
+pub fn placeholders(code: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = code;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else { break };
+        let name = after[..end].trim();
+        if !name.is_empty() && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+        rest = &after[end + 2..];
+    }
+    names
+}
+
+fn builtin_values(snippet: &Snippet, key: &ApiKey) -> HashMap<String, String> {
+    let env_var = env_var_name_for_key(key);
+    HashMap::from([
+        (
+            "env_access".to_string(),
+            env_access_expression(&snippet.language, &env_var),
+        ),
+        ("env_var".to_string(), env_var),
+        ("key_name".to_string(), key.name.clone()),
+        ("service".to_string(), key.service.clone()),
+        ("environment".to_string(), key.environment.clone()),
+    ])
+}
+
+/// Substitute the snippet's placeholders for `key`. Explicit `values` win
+/// over built-ins, which win over the variables' defaults.
+pub fn render(
+    snippet: &Snippet,
+    key: &ApiKey,
+    values: &HashMap<String, String>,
+) -> RenderedSnippet {
+    let mut variables = builtin_values(snippet, key);
+    for variable in &snippet.variables {
+        if let Some(default) = &variable.default_value {
+            variables
+                .entry(variable.name.clone())
+                .or_insert_with(|| default.clone());
+        }
+    }
+    variables.extend(values.clone());
+
+    let mut code = snippet.code.clone();
+    let mut missing_variables = Vec::new();
+    for name in placeholders(&snippet.code) {
+        match variables.get(&name) {
+            Some(value) => {
+                code = code
+                    .replace(&format!("{{{{{}}}}}", name), value)
+                    .replace(&format!("{{{{ {} }}}}", name), value);
+            }
+            None => missing_variables.push(name),
+        }
+    }
+
+    RenderedSnippet {
+        snippet_id: snippet.id.clone(),
+        key_id: key.id.clone(),
+        language: snippet.language.clone(),
+        code,
+        variables,
+        missing_variables,
+    }
+}
+
+/// Snippets for a provider, or all of them
+pub fn list(vault: &ApiKeyVault, provider_id: Option<&str>) -> Vec<Snippet> {
+    let mut snippets: Vec<Snippet> = vault
+        .snippets
+        .values()
+        .filter(|s| {
+            provider_id.is_none_or(|p| {
+                s.provider_id
+                    .as_deref()
+                    .is_some_and(|sp| sp.eq_ignore_ascii_case(p))
+            })
+        })
+        .cloned()
+        .collect();
+    snippets.sort_by(|a, b| a.title.cmp(&b.title));
+    snippets
+}
+
+/// Render a stored snippet for a stored key
+pub fn render_stored(
+    vault: &ApiKeyVault,
+    snippet_id: &str,
+    key_id: &str,
+    values: &HashMap<String, String>,
+) -> Result<RenderedSnippet, KeyKeeperError> {
+    let snippet = vault
+        .snippets
+        .get(snippet_id)
+        .ok_or_else(|| KeyKeeperError::not_found("Snippet"))?;
+    let key = vault
+        .keys
+        .get(key_id)
+        .ok_or_else(|| KeyKeeperError::not_found("API key"))?;
+    Ok(render(snippet, key, values))
+}
+
+fn validate(title: &str, code: &str, variables: &[SnippetVariable]) -> Result<(), KeyKeeperError> {
+    if title.trim().is_empty() {
+        return Err(KeyKeeperError::Validation(
+            "Snippet title cannot be empty".to_string(),
+        ));
+    }
+    if code.trim().is_empty() {
+        return Err(KeyKeeperError::Validation(
+            "Snippet code cannot be empty".to_string(),
+        ));
+    }
+    if let Some(variable) = variables
+        .iter()
+        .find(|v| BUILTIN_VARIABLES.contains(&v.name.as_str()))
+    {
+        return Err(KeyKeeperError::Validation(format!(
+            "'{}' is a built-in variable",
+            variable.name
+        )));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn create_snippet(
+    title: String,
+    code: String,
+    language: String,
+    provider_id: Option<String>,
+    description: Option<String>,
+    variables: Option<Vec<SnippetVariable>>,
+    tags: Option<Vec<String>>,
+    state: State<'_, AppState>,
+) -> Result<Snippet, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    let variables = variables.unwrap_or_default();
+    validate(&title, &code, &variables)?;
+
+    let now = Utc::now();
+    let snippet = Snippet {
+        id: format!("snippet_{}", uuid::Uuid::new_v4()),
+        title,
+        description,
+        language,
+        code,
+        provider_id,
+        variables,
+        tags: tags.unwrap_or_default(),
+        created_at: now,
+        updated_at: now,
+    };
+
+    let mut vault_guard = state.vault.lock().await;
+    vault_guard
+        .snippets
+        .insert(snippet.id.clone(), snippet.clone());
+    drop(vault_guard);
+    save_vault(&state).await?;
+
+    log_audit_event(
+        &state,
+        "create_snippet",
+        "snippet",
+        Some(&snippet.id),
+        true,
+        None,
+    )
+    .await;
+    Ok(snippet)
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn update_snippet(
+    id: String,
+    title: Option<String>,
+    code: Option<String>,
+    language: Option<String>,
+    provider_id: Option<String>,
+    description: Option<String>,
+    variables: Option<Vec<SnippetVariable>>,
+    tags: Option<Vec<String>>,
+    state: State<'_, AppState>,
+) -> Result<Snippet, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let snippet = vault_guard
+        .snippets
+        .get_mut(&id)
+        .ok_or_else(|| KeyKeeperError::not_found("Snippet"))?;
+    validate(
+        title.as_deref().unwrap_or(&snippet.title),
+        code.as_deref().unwrap_or(&snippet.code),
+        variables.as_deref().unwrap_or(&snippet.variables),
+    )?;
+    if let Some(title) = title {
+        snippet.title = title;
+    }
+    if let Some(code) = code {
+        snippet.code = code;
+    }
+    if let Some(language) = language {
+        snippet.language = language;
+    }
+    if provider_id.is_some() {
+        snippet.provider_id = provider_id;
+    }
+    if description.is_some() {
+        snippet.description = description;
+    }
+    if let Some(variables) = variables {
+        snippet.variables = variables;
+    }
+    if let Some(tags) = tags {
+        snippet.tags = tags;
+    }
+    snippet.updated_at = Utc::now();
+    let updated = snippet.clone();
+    drop(vault_guard);
+    save_vault(&state).await?;
+
+    log_audit_event(&state, "update_snippet", "snippet", Some(&id), true, None).await;
+    Ok(updated)
+}
+
+#[tauri::command]
+pub async fn delete_snippet(id: String, state: State<'_, AppState>) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    if vault_guard.snippets.remove(&id).is_none() {
+        return Err(KeyKeeperError::not_found("Snippet"));
+    }
+    drop(vault_guard);
+    save_vault(&state).await?;
+
+    log_audit_event(&state, "delete_snippet", "snippet", Some(&id), true, None).await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_snippets(
+    provider_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<Snippet>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    Ok(list(&vault_guard, provider_id.as_deref()))
+}
+
+/// Render a snippet with the variable names of `key_id`, ready to insert
+#[tauri::command]
+pub async fn render_snippet(
+    snippet_id: String,
+    key_id: String,
+    values: Option<HashMap<String, String>>,
+    state: State<'_, AppState>,
+) -> Result<RenderedSnippet, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    render_stored(
+        &vault_guard,
+        &snippet_id,
+        &key_id,
+        &values.unwrap_or_default(),
+    )
+}
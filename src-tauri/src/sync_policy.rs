@@ -0,0 +1,152 @@
+//! Policy on syncing production keys outside production.
+//!
+//! A sync path writing a production key's value into a `.env.development`
+//! file, into the `.env` of a project whose default environment isn't
+//! production or into a non-production GitHub environment reports the write
+//! as a `PolicyViolation`. Before writing, the violations are confirmed like
+//! other risky actions (`sync_production_key`) and each is audited as
+//! `sync_policy_violation`, whether it went ahead or not, for the security
+//! report to aggregate.
+//!
+//! Values a key has for the file's own environment are fine: only the key's
+//! own value, written where no per-environment value applies, can be a
+//! production one.
+
+use crate::confirmations::{self, ConfirmationBroker, RiskyAction};
+use crate::docker_env::normalize_environment;
+use crate::errors::KeyKeeperError;
+use crate::vault_storage::VaultStorage;
+use crate::{create_audit_log, env_values, retention, ApiKey, ApiKeyVault, Project};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::sync::Mutex as AsyncMutex;
+use tracing::warn;
+
+/// Audit action of a production key synced, or refused, into a
+/// non-production target
+pub const VIOLATION_ACTION: &str = "sync_policy_violation";
+
+const PRODUCTION: &str = "production";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PolicyViolation {
+    pub key_id: String,
+    pub key_name: String,
+    /// File, or GitHub repository, written to
+    pub target: String,
+    /// Environment of the target
+    pub environment: String,
+}
+
+impl PolicyViolation {
+    fn describe(&self) -> String {
+        format!(
+            "{} into {} ({})",
+            self.key_name, self.target, self.environment
+        )
+    }
+}
+
+/// Environment the file at `path` is for: the one its name tells, else the
+/// default environment of its project
+pub fn target_environment(project: Option<&Project>, path: &Path) -> Option<String> {
+    env_values::file_environment(&path.to_string_lossy())
+        .or_else(|| project.map(|p| normalize_environment(&p.settings.default_environment)))
+}
+
+/// Violation when `key`'s own value is written to `target`, which is for
+/// `environment`
+pub fn check(key: &ApiKey, target: &str, environment: Option<&str>) -> Option<PolicyViolation> {
+    let environment = normalize_environment(environment?);
+    if normalize_environment(&key.environment) != PRODUCTION || environment == PRODUCTION {
+        return None;
+    }
+    Some(PolicyViolation {
+        key_id: key.id.clone(),
+        key_name: key.name.clone(),
+        target: target.to_string(),
+        environment,
+    })
+}
+
+/// Ask `client` to confirm `violations` before they're written, and audit
+/// each one. Fails with `Denied` when refused; nothing to confirm is fine.
+pub async fn confirm(
+    broker: &ConfirmationBroker,
+    vault: &AsyncMutex<ApiKeyVault>,
+    storage: &dyn VaultStorage,
+    client: &str,
+    violations: &[PolicyViolation],
+) -> Result<(), KeyKeeperError> {
+    let Some(first) = violations.first() else {
+        return Ok(());
+    };
+    let summary = match violations.len() {
+        1 => format!("Sync the production key {}", first.describe()),
+        n => format!("Sync {} production keys outside production", n),
+    };
+    let details = violations.iter().map(PolicyViolation::describe).collect();
+    let result = confirmations::confirm(
+        broker,
+        vault,
+        storage,
+        client,
+        RiskyAction::SyncProductionKey,
+        summary,
+        details,
+    )
+    .await;
+
+    let synced = result.is_ok();
+    let mut vault_guard = vault.lock().await;
+    for violation in violations {
+        vault_guard.audit_logs.push(create_audit_log(
+            VIOLATION_ACTION,
+            "api_key",
+            Some(&violation.key_id),
+            synced,
+            Some(&format!(
+                "{}: {}",
+                violation.describe(),
+                if synced { "synced" } else { "refused" }
+            )),
+        ));
+    }
+    retention::cap_audit_logs(&mut vault_guard);
+    if let Err(e) = crate::save_vault_to(storage, &vault_guard) {
+        warn!("Failed to save sync policy audit: {}", e);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault_tests::test_key;
+
+    #[test]
+    fn production_keys_are_flagged_outside_production() {
+        let dev_file = "/app/.env.development";
+        let environment = target_environment(None, Path::new(dev_file));
+        assert_eq!(environment.as_deref(), Some("development"));
+
+        let mut key = test_key("k1")
+            .name("Stripe secret")
+            .secret("sk_live_1")
+            .environment("prod")
+            .build();
+        let violation = check(&key, dev_file, environment.as_deref()).unwrap();
+        assert_eq!(violation.environment, "development");
+        assert_eq!(
+            violation.describe(),
+            "Stripe secret into /app/.env.development (development)"
+        );
+
+        key.environment = "production".to_string();
+        assert!(check(&key, "/app/.env", Some("production")).is_none());
+        key.environment = "development".to_string();
+        assert!(check(&key, dev_file, Some("development")).is_none());
+        // A plain .env outside any project has no known environment
+        assert_eq!(target_environment(None, Path::new("/app/.env")), None);
+    }
+}
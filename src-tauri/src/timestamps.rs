@@ -0,0 +1,94 @@
+//! Serde helpers for `DateTime<Utc>` fields of the vault model.
+//!
+//! Timestamps are written as RFC 3339, like the `String` fields they replaced.
+//! Reading is lenient so vaults written before the migration still load:
+//! besides RFC 3339 we accept plain dates (`2024-12-31`, as produced by the
+//! expiry date picker) and naive date-times, all interpreted as UTC.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// Parse any timestamp format a vault may contain
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    for format in [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M:%S",
+    ] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(dt.and_utc());
+        }
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc())
+}
+
+/// `#[serde(with = "timestamps::rfc3339")]` for required timestamps.
+/// Unparseable legacy values load as the Unix epoch rather than failing the
+/// whole vault.
+pub mod rfc3339 {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        value: &DateTime<Utc>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_rfc3339())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<Utc>, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        Ok(parse_timestamp(&raw).unwrap_or_else(|| {
+            tracing::warn!("Unparseable timestamp {:?}, defaulting to epoch", raw);
+            DateTime::<Utc>::UNIX_EPOCH
+        }))
+    }
+}
+
+/// `#[serde(default, with = "timestamps::rfc3339_option")]` for optional
+/// timestamps. Empty or unparseable values load as `None`.
+pub mod rfc3339_option {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<DateTime<Utc>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(dt) => serializer.serialize_some(&dt.to_rfc3339()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<DateTime<Utc>>, D::Error> {
+        let raw = Option::<String>::deserialize(deserializer)?;
+        Ok(raw.as_deref().and_then(parse_timestamp))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn parses_legacy_formats() {
+        let midnight = Utc.with_ymd_and_hms(2024, 12, 31, 0, 0, 0).unwrap();
+        assert_eq!(parse_timestamp("2024-12-31"), Some(midnight));
+        assert_eq!(parse_timestamp("2024-12-31T00:00:00+00:00"), Some(midnight));
+        assert_eq!(parse_timestamp("2024-12-31T01:00:00+01:00"), Some(midnight));
+        assert_eq!(parse_timestamp("2024-12-31T00:00"), Some(midnight));
+        assert_eq!(parse_timestamp(""), None);
+        assert_eq!(parse_timestamp("never"), None);
+    }
+}
@@ -0,0 +1,238 @@
+//! Tray icon and menu reflecting the vault's state.
+//!
+//! The icon carries a coloured badge: grey while locked, green while
+//! unlocked, blue while a sync is running and orange when something needs
+//! attention, i.e. keys that expired or expire soon, or secrets that drifted
+//! from GitHub. The menu lists those counts above the pinned keys, and the
+//! tooltip says the same in words.
+//!
+//! The tray is rebuilt through the event bus. Lock changes, sync activity
+//! and deleted pinned keys always rebuild it. Saves and audited actions only do when the
+//! summary differs from the one shown, since most don't change it.
+
+use crate::events::{self, EventPayload};
+use crate::{notifications, pinning, AppState};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use tauri::image::Image;
+use tauri::tray::TrayIcon;
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayStatus {
+    Locked,
+    Unlocked,
+    Syncing,
+    Attention,
+}
+
+impl TrayStatus {
+    fn badge_color(self) -> [u8; 3] {
+        match self {
+            TrayStatus::Locked => [0x8e, 0x8e, 0x93],
+            TrayStatus::Unlocked => [0x34, 0xc7, 0x59],
+            TrayStatus::Syncing => [0x0a, 0x84, 0xff],
+            TrayStatus::Attention => [0xff, 0x9f, 0x0a],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraySummary {
+    pub status: TrayStatus,
+    /// Expired or expiring within the warning window
+    pub expiring_keys: usize,
+    pub drifted_secrets: usize,
+}
+
+impl TraySummary {
+    fn new(unlocked: bool, syncing: bool, expiring_keys: usize, drifted_secrets: usize) -> Self {
+        let status = if !unlocked {
+            TrayStatus::Locked
+        } else if syncing {
+            TrayStatus::Syncing
+        } else if expiring_keys > 0 || drifted_secrets > 0 {
+            TrayStatus::Attention
+        } else {
+            TrayStatus::Unlocked
+        };
+        TraySummary {
+            status,
+            expiring_keys,
+            drifted_secrets,
+        }
+    }
+
+    /// Lines at the top of the menu, also joined into the tooltip
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![match self.status {
+            TrayStatus::Locked => "Vault locked".to_string(),
+            TrayStatus::Syncing => "Syncing…".to_string(),
+            TrayStatus::Unlocked | TrayStatus::Attention => "Vault unlocked".to_string(),
+        }];
+        if self.expiring_keys > 0 {
+            lines.push(plural(self.expiring_keys, "key expiring", "keys expiring"));
+        }
+        if self.drifted_secrets > 0 {
+            lines.push(plural(
+                self.drifted_secrets,
+                "secret drifted from GitHub",
+                "secrets drifted from GitHub",
+            ));
+        }
+        lines
+    }
+}
+
+fn plural(count: usize, one: &str, many: &str) -> String {
+    format!("{} {}", count, if count == 1 { one } else { many })
+}
+
+static SYNCS_RUNNING: AtomicUsize = AtomicUsize::new(0);
+/// Summary the tray shows
+static SHOWN: Mutex<Option<TraySummary>> = Mutex::new(None);
+
+/// Marks a sync as running until dropped
+pub struct SyncActivity(());
+
+/// Call at the start of a sync and keep the guard until it's done
+pub fn begin_sync() -> SyncActivity {
+    if SYNCS_RUNNING.fetch_add(1, Ordering::SeqCst) == 0 {
+        events::publish(EventPayload::Syncing { running: true });
+    }
+    SyncActivity(())
+}
+
+impl Drop for SyncActivity {
+    fn drop(&mut self) {
+        if SYNCS_RUNNING.fetch_sub(1, Ordering::SeqCst) == 1 {
+            events::publish(EventPayload::Syncing { running: false });
+        }
+    }
+}
+
+pub async fn summary(state: &AppState) -> TraySummary {
+    let unlocked = *state.is_unlocked.lock().await;
+    let syncing = SYNCS_RUNNING.load(Ordering::SeqCst) > 0;
+    if !unlocked {
+        return TraySummary::new(false, syncing, 0, 0);
+    }
+    let digest = notifications::build_digest(&*state.vault.lock().await);
+    TraySummary::new(
+        true,
+        syncing,
+        digest.expired_keys + digest.expiring_keys,
+        digest.drifted_secrets,
+    )
+}
+
+/// The app icon with a badge in the status colour in its lower right corner
+fn badged(icon: &Image<'_>, status: TrayStatus) -> Image<'static> {
+    let (width, height) = (icon.width(), icon.height());
+    let mut rgba = icon.rgba().to_vec();
+    draw_badge(&mut rgba, width, height, status.badge_color());
+    Image::new_owned(rgba, width, height)
+}
+
+fn draw_badge(rgba: &mut [u8], width: u32, height: u32, color: [u8; 3]) {
+    let radius = (width.min(height) as f32) * 0.22;
+    let (cx, cy) = (width as f32 - radius - 0.5, height as f32 - radius - 0.5);
+    for y in 0..height {
+        for x in 0..width {
+            let (dx, dy) = (x as f32 - cx, y as f32 - cy);
+            if dx * dx + dy * dy <= radius * radius {
+                let i = ((y * width + x) * 4) as usize;
+                rgba[i..i + 4].copy_from_slice(&[color[0], color[1], color[2], 0xff]);
+            }
+        }
+    }
+}
+
+fn apply(tray: &TrayIcon, summary: &TraySummary, pinned: &[(String, String)]) {
+    let app = tray.app_handle();
+    match crate::build_tray_menu(app, &summary.lines(), pinned) {
+        Ok(menu) => {
+            if let Err(e) = tray.set_menu(Some(menu)) {
+                warn!("Failed to update the tray menu: {}", e);
+            }
+        }
+        Err(e) => warn!("Failed to build the tray menu: {}", e),
+    }
+    if let Some(icon) = app.default_window_icon() {
+        if let Err(e) = tray.set_icon(Some(badged(icon, summary.status))) {
+            warn!("Failed to update the tray icon: {}", e);
+        }
+    }
+    let tooltip = format!("KeyKeeper - {}", summary.lines().join(", "));
+    if let Err(e) = tray.set_tooltip(Some(tooltip)) {
+        warn!("Failed to update the tray tooltip: {}", e);
+    }
+}
+
+/// Rebuild the tray: status, counts and, while unlocked, the pinned keys
+pub async fn refresh(state: &AppState) {
+    let summary = summary(state).await;
+    let pinned: Vec<(String, String)> = if summary.status == TrayStatus::Locked {
+        Vec::new()
+    } else {
+        let vault_guard = state.vault.lock().await;
+        let ids = state.vault_index.lock().await.pinned_ids(&vault_guard);
+        pinning::pinned_keys(&vault_guard, &ids)
+            .into_iter()
+            .map(|key| (key.id, key.name))
+            .collect()
+    };
+
+    let tray_guard = state.tray_handle.lock().await;
+    let Some(tray) = tray_guard.as_ref() else {
+        return;
+    };
+    apply(tray, &summary, &pinned);
+    *SHOWN.lock().unwrap_or_else(|e| e.into_inner()) = Some(summary);
+}
+
+/// Rebuild the tray if its summary changed
+pub async fn refresh_if_changed(state: &AppState) {
+    let summary = summary(state).await;
+    let shown = SHOWN.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    if shown.as_ref() != Some(&summary) {
+        refresh(state).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_follows_lock_sync_and_pending_issues() {
+        let locked = TraySummary::new(false, true, 0, 0);
+        assert_eq!(locked.status, TrayStatus::Locked);
+        assert_eq!(locked.lines(), vec!["Vault locked"]);
+        assert_eq!(
+            TraySummary::new(true, true, 3, 0).status,
+            TrayStatus::Syncing
+        );
+        assert_eq!(
+            TraySummary::new(true, false, 0, 0).status,
+            TrayStatus::Unlocked
+        );
+
+        let attention = TraySummary::new(true, false, 1, 2);
+        assert_eq!(attention.status, TrayStatus::Attention);
+        assert_eq!(
+            attention.lines(),
+            vec![
+                "Vault unlocked",
+                "1 key expiring",
+                "2 secrets drifted from GitHub"
+            ]
+        );
+
+        let mut rgba = vec![0u8; 16 * 16 * 4];
+        draw_badge(&mut rgba, 16, 16, [1, 2, 3]);
+        let pixel = |x: usize, y: usize| &rgba[(y * 16 + x) * 4..(y * 16 + x) * 4 + 4];
+        assert_eq!(pixel(13, 13), &[1, 2, 3, 0xff]);
+        assert_eq!(pixel(2, 2), &[0, 0, 0, 0]);
+    }
+}
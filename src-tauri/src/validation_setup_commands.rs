@@ -1,4 +1,5 @@
 use crate::enhanced_types::{GeneratedTemplateFile as GeneratedFile, GenerationContext};
+use crate::errors::KeyKeeperError;
 use crate::validation_engine::{ValidationEngine, validate_environment_compatibility};
 use crate::setup_generator::{SetupGenerator, DeploymentTarget, CIPlatform};
 use anyhow::{Context, Result as AnyhowResult};
@@ -99,7 +100,7 @@ pub struct ScalingOptions {
 #[tauri::command]
 pub async fn validate_generated_configuration(
     request: ValidationRequest,
-) -> Result<crate::validation_engine::ValidationResult, String> {
+) -> Result<crate::validation_engine::ValidationResult, KeyKeeperError> {
     info!("Starting configuration validation for {} files", request.generated_files.len());
     
     let validation_engine = ValidationEngine::new();
@@ -111,7 +112,7 @@ pub async fn validate_generated_configuration(
         },
         Err(e) => {
             error!("Validation failed: {}", e);
-            Err(format!("Validation failed: {}", e))
+            Err(KeyKeeperError::Internal(format!("Validation failed: {}", e)))
         }
     }
 }
@@ -120,7 +121,7 @@ pub async fn validate_generated_configuration(
 #[tauri::command]
 pub async fn generate_setup_scripts(
     request: SetupGenerationRequest,
-) -> Result<crate::setup_generator::SetupPackage, String> {
+) -> Result<crate::setup_generator::SetupPackage, KeyKeeperError> {
     info!("Generating setup scripts for framework: {}", request.context.framework);
     
     let setup_generator = SetupGenerator::new();
@@ -168,7 +169,7 @@ pub async fn generate_setup_scripts(
         },
         Err(e) => {
             error!("Setup generation failed: {}", e);
-            Err(format!("Setup generation failed: {}", e))
+            Err(KeyKeeperError::Internal(format!("Setup generation failed: {}", e)))
         }
     }
 }
@@ -177,7 +178,7 @@ pub async fn generate_setup_scripts(
 #[tauri::command]
 pub async fn check_environment_compatibility(
     request: EnvironmentCheckRequest,
-) -> Result<Vec<crate::validation_engine::ValidationIssue>, String> {
+) -> Result<Vec<crate::validation_engine::ValidationIssue>, KeyKeeperError> {
     info!("Checking environment compatibility for framework: {}", request.framework);
     
     match validate_environment_compatibility(
@@ -191,7 +192,7 @@ pub async fn check_environment_compatibility(
         },
         Err(e) => {
             error!("Environment compatibility check failed: {}", e);
-            Err(format!("Environment compatibility check failed: {}", e))
+            Err(KeyKeeperError::Internal(format!("Environment compatibility check failed: {}", e)))
         }
     }
 }
@@ -200,7 +201,7 @@ pub async fn check_environment_compatibility(
 #[tauri::command]
 pub async fn validate_security_configuration(
     request: SecurityValidationRequest,
-) -> Result<Vec<crate::validation_engine::ValidationIssue>, String> {
+) -> Result<Vec<crate::validation_engine::ValidationIssue>, KeyKeeperError> {
     info!("Running security validation on {} files", request.files.len());
     
     let validation_engine = ValidationEngine::new();
@@ -212,7 +213,7 @@ pub async fn validate_security_configuration(
             Ok(mut issues) => all_issues.append(&mut issues),
             Err(e) => {
                 error!("Security validation failed for file {}: {}", file.path, e);
-                return Err(format!("Security validation failed: {}", e));
+                return Err(KeyKeeperError::Internal(format!("Security validation failed: {}", e)));
             }
         }
     }
@@ -225,7 +226,7 @@ pub async fn validate_security_configuration(
 #[tauri::command]
 pub async fn generate_deployment_config(
     request: DeploymentConfigRequest,
-) -> Result<crate::setup_generator::DeploymentConfig, String> {
+) -> Result<crate::setup_generator::DeploymentConfig, KeyKeeperError> {
     info!("Generating deployment configuration for platform: {}", request.target_platform);
     
     let setup_generator = SetupGenerator::new();
@@ -277,14 +278,14 @@ pub async fn generate_deployment_config(
         },
         Err(e) => {
             error!("Deployment configuration generation failed: {}", e);
-            Err(format!("Deployment configuration generation failed: {}", e))
+            Err(KeyKeeperError::Internal(format!("Deployment configuration generation failed: {}", e)))
         }
     }
 }
 
 /// Get validation engine status and configuration
 #[tauri::command]
-pub async fn get_validation_engine_status() -> Result<ValidationEngineStatus, String> {
+pub async fn get_validation_engine_status() -> Result<ValidationEngineStatus, KeyKeeperError> {
     info!("Getting validation engine status");
     
     let validation_engine = ValidationEngine::new();
@@ -312,7 +313,7 @@ pub async fn get_validation_engine_status() -> Result<ValidationEngineStatus, St
 
 /// Get setup generator capabilities
 #[tauri::command]
-pub async fn get_setup_generator_capabilities() -> Result<SetupGeneratorCapabilities, String> {
+pub async fn get_setup_generator_capabilities() -> Result<SetupGeneratorCapabilities, KeyKeeperError> {
     info!("Getting setup generator capabilities");
     
     let capabilities = SetupGeneratorCapabilities {
@@ -359,7 +360,7 @@ pub async fn get_setup_generator_capabilities() -> Result<SetupGeneratorCapabili
 #[tauri::command]
 pub async fn batch_validate_configurations(
     requests: Vec<ValidationRequest>,
-) -> Result<Vec<crate::validation_engine::ValidationResult>, String> {
+) -> Result<Vec<crate::validation_engine::ValidationResult>, KeyKeeperError> {
     info!("Starting batch validation for {} configurations", requests.len());
     
     let validation_engine = ValidationEngine::new();
@@ -372,7 +373,7 @@ pub async fn batch_validate_configurations(
             Ok(result) => results.push(result),
             Err(e) => {
                 error!("Batch validation failed for configuration {}: {}", index + 1, e);
-                return Err(format!("Batch validation failed for configuration {}: {}", index + 1, e));
+                return Err(KeyKeeperError::Internal(format!("Batch validation failed for configuration {}: {}", index + 1, e)));
             }
         }
     }
@@ -383,7 +384,7 @@ pub async fn batch_validate_configurations(
 
 /// Get validation and setup statistics
 #[tauri::command]
-pub async fn get_validation_setup_statistics() -> Result<ValidationSetupStats, String> {
+pub async fn get_validation_setup_statistics() -> Result<ValidationSetupStats, KeyKeeperError> {
     info!("Getting validation and setup statistics");
     
     // This would typically come from a persistent store or database
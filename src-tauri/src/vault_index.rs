@@ -0,0 +1,361 @@
+use crate::archive::is_archived;
+use crate::credentials::CredentialKind;
+use crate::docker_env::normalize_environment;
+use crate::errors::KeyKeeperError;
+use crate::masking::MaskedApiKey;
+use crate::{guards, project_groups, ApiKey, ApiKeyVault};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Sort orders supported by paginated key listings
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySort {
+    #[default]
+    NameAsc,
+    NameDesc,
+    CreatedDesc,
+    CreatedAsc,
+    UpdatedDesc,
+    Service,
+}
+
+impl KeySort {
+    pub fn compare(&self, a: &ApiKey, b: &ApiKey) -> Ordering {
+        let ordering = match self {
+            KeySort::NameAsc => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            KeySort::NameDesc => b.name.to_lowercase().cmp(&a.name.to_lowercase()),
+            KeySort::CreatedDesc => b.created_at.cmp(&a.created_at),
+            KeySort::CreatedAsc => a.created_at.cmp(&b.created_at),
+            KeySort::UpdatedDesc => b.updated_at.cmp(&a.updated_at),
+            KeySort::Service => a
+                .service
+                .to_lowercase()
+                .cmp(&b.service.to_lowercase())
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase())),
+        };
+        // Tie-break on id so the order is total and pages are stable
+        ordering.then_with(|| a.id.cmp(&b.id))
+    }
+}
+
+/// Secondary lookups, each mapping a value to the ids of matching keys
+#[derive(Debug, Default)]
+struct SecondaryIndexes {
+    by_project: HashMap<String, Vec<String>>,
+    unassigned: Vec<String>,
+    by_service: HashMap<String, Vec<String>>,
+    by_environment: HashMap<String, Vec<String>>,
+    by_tag: HashMap<String, Vec<String>>,
+    by_kind: HashMap<CredentialKind, Vec<String>>,
+    pinned: Vec<String>,
+}
+
+impl SecondaryIndexes {
+    fn build(vault: &ApiKeyVault) -> Self {
+        let mut indexes = SecondaryIndexes::default();
+        for key in vault.keys.values() {
+            match &key.project_path {
+                Some(path) => indexes
+                    .by_project
+                    .entry(path.clone())
+                    .or_default()
+                    .push(key.id.clone()),
+                None => indexes.unassigned.push(key.id.clone()),
+            }
+            indexes
+                .by_service
+                .entry(key.service.to_lowercase())
+                .or_default()
+                .push(key.id.clone());
+            for environment in crate::env_values::environments(key) {
+                indexes
+                    .by_environment
+                    .entry(environment)
+                    .or_default()
+                    .push(key.id.clone());
+            }
+            indexes
+                .by_kind
+                .entry(key.kind)
+                .or_default()
+                .push(key.id.clone());
+            if key.is_pinned {
+                indexes.pinned.push(key.id.clone());
+            }
+            for tag in &key.tags {
+                indexes
+                    .by_tag
+                    .entry(tag.to_lowercase())
+                    .or_default()
+                    .push(key.id.clone());
+            }
+        }
+        indexes
+    }
+}
+
+/// In-memory indexes over the vault keys: sorted orders for pagination and
+/// lookups by project, service, environment and tag. Everything is rebuilt
+/// lazily after `invalidate`. The index can't tell on its own that the vault
+/// changed, so `SaveQueue` invalidates it when a save is asked for and again
+/// once it's written, and code that replaces the vault without saving (unlock,
+/// lock, moving the vault) invalidates it itself.
+#[derive(Debug, Default)]
+pub struct VaultIndex {
+    sorted: HashMap<KeySort, Vec<String>>,
+    secondary: Option<SecondaryIndexes>,
+}
+
+impl VaultIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop all cached indexes; called on every vault mutation
+    pub fn invalidate(&mut self) {
+        self.sorted.clear();
+        self.secondary = None;
+        // Also covers a vault replaced without a save, e.g. reloaded on lock
+        crate::etag::bump();
+    }
+
+    /// Ids of all keys in `sort` order
+    pub fn sorted_ids(&mut self, vault: &ApiKeyVault, sort: KeySort) -> &[String] {
+        self.sorted.entry(sort).or_insert_with(|| {
+            let mut keys: Vec<&ApiKey> = vault.keys.values().collect();
+            keys.sort_by(|a, b| sort.compare(a, b));
+            keys.into_iter().map(|k| k.id.clone()).collect()
+        })
+    }
+
+    fn secondary(&mut self, vault: &ApiKeyVault) -> &SecondaryIndexes {
+        self.secondary
+            .get_or_insert_with(|| SecondaryIndexes::build(vault))
+    }
+
+    pub fn ids_by_project(&mut self, vault: &ApiKeyVault, project_path: &str) -> Vec<String> {
+        lookup(&self.secondary(vault).by_project, project_path)
+    }
+
+    pub fn unassigned_ids(&mut self, vault: &ApiKeyVault) -> Vec<String> {
+        self.secondary(vault).unassigned.clone()
+    }
+
+    pub fn ids_by_service(&mut self, vault: &ApiKeyVault, service: &str) -> Vec<String> {
+        lookup(&self.secondary(vault).by_service, &service.to_lowercase())
+    }
+
+    pub fn ids_by_environment(&mut self, vault: &ApiKeyVault, environment: &str) -> Vec<String> {
+        lookup(
+            &self.secondary(vault).by_environment,
+            &normalize_environment(environment),
+        )
+    }
+
+    pub fn ids_by_tag(&mut self, vault: &ApiKeyVault, tag: &str) -> Vec<String> {
+        lookup(&self.secondary(vault).by_tag, &tag.to_lowercase())
+    }
+
+    /// Ids of pinned keys, in no particular order (see pinning.rs)
+    pub fn pinned_ids(&mut self, vault: &ApiKeyVault) -> Vec<String> {
+        self.secondary(vault).pinned.clone()
+    }
+
+    pub fn ids_by_kind(&mut self, vault: &ApiKeyVault, kind: CredentialKind) -> Vec<String> {
+        self.secondary(vault)
+            .by_kind
+            .get(&kind)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+fn lookup(index: &HashMap<String, Vec<String>>, value: &str) -> Vec<String> {
+    index.get(value).cloned().unwrap_or_default()
+}
+
+/// Resolve indexed ids to cloned keys, skipping ids that vanished
+pub fn collect_keys(vault: &ApiKeyVault, ids: &[String]) -> Vec<ApiKey> {
+    ids.iter()
+        .filter_map(|id| vault.keys.get(id).cloned())
+        .collect()
+}
+
+/// Filters accepted by `find_api_keys` and the `/api/keys` query string
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeyFilter {
+    pub project_path: Option<String>,
+    pub service: Option<String>,
+    pub environment: Option<String>,
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub kind: Option<CredentialKind>,
+    /// Keys of the projects in this group and its subgroups
+    #[serde(default)]
+    pub group_id: Option<String>,
+    /// Archived keys are left out unless set
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+impl KeyFilter {
+    pub fn is_empty(&self) -> bool {
+        self.project_path.is_none()
+            && self.service.is_none()
+            && self.environment.is_none()
+            && self.tag.is_none()
+            && self.kind.is_none()
+            && self.group_id.is_none()
+    }
+}
+
+impl VaultIndex {
+    /// Ids matching every filter, intersecting the individual indexes
+    pub fn filter_ids(&mut self, vault: &ApiKeyVault, filter: &KeyFilter) -> Vec<String> {
+        let mut candidates: Vec<Vec<String>> = Vec::new();
+        if let Some(path) = &filter.project_path {
+            candidates.push(self.ids_by_project(vault, path));
+        }
+        if let Some(service) = &filter.service {
+            candidates.push(self.ids_by_service(vault, service));
+        }
+        if let Some(environment) = &filter.environment {
+            candidates.push(self.ids_by_environment(vault, environment));
+        }
+        if let Some(tag) = &filter.tag {
+            candidates.push(self.ids_by_tag(vault, tag));
+        }
+        if let Some(kind) = filter.kind {
+            candidates.push(self.ids_by_kind(vault, kind));
+        }
+        if let Some(group_id) = &filter.group_id {
+            let paths: Vec<String> = project_groups::projects_in(vault, group_id)
+                .map(|projects| projects.iter().map(|p| p.path.clone()).collect())
+                .unwrap_or_default();
+            candidates.push(
+                paths
+                    .iter()
+                    .flat_map(|path| self.ids_by_project(vault, path))
+                    .collect(),
+            );
+        }
+        let mut result = if candidates.is_empty() {
+            vault.keys.keys().cloned().collect()
+        } else {
+            // Start from the smallest set to keep the intersection cheap
+            candidates.sort_by_key(|ids| ids.len());
+            let mut result = candidates.remove(0);
+            for other in candidates {
+                let other: std::collections::HashSet<String> = other.into_iter().collect();
+                result.retain(|id| other.contains(id));
+            }
+            result
+        };
+        if !filter.include_archived {
+            result.retain(|id| vault.keys.get(id).is_some_and(|key| !is_archived(key)));
+        }
+        result
+    }
+}
+
+/// Look up keys by project, service, environment and tag using the in-memory indexes
+#[tauri::command]
+pub async fn find_api_keys(
+    filter: KeyFilter,
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<Vec<MaskedApiKey>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    let ids = state
+        .vault_index
+        .lock()
+        .await
+        .filter_ids(&vault_guard, &filter);
+    Ok(crate::masking::mask_all(collect_keys(&vault_guard, &ids)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault_tests::test_key;
+
+    fn vault(keys: Vec<ApiKey>) -> ApiKeyVault {
+        let mut vault = ApiKeyVault::default();
+        for key in keys {
+            vault.keys.insert(key.id.clone(), key);
+        }
+        vault
+    }
+
+    #[test]
+    fn edits_that_keep_the_key_count_show_up_once_invalidated() {
+        let mut vault = vault(vec![
+            test_key("a").name("Alpha").build(),
+            test_key("b").name("Beta").service("GitHub").build(),
+        ]);
+        let mut index = VaultIndex::new();
+        assert_eq!(index.sorted_ids(&vault, KeySort::NameAsc), ["a", "b"]);
+        assert_eq!(index.ids_by_service(&vault, "stripe"), ["a"]);
+
+        let alpha = vault.keys.get_mut("a").unwrap();
+        alpha.name = "Zulu".to_string();
+        alpha.service = "OpenAI".to_string();
+        alpha.tags = vec!["Billing".to_string()];
+        index.invalidate();
+
+        assert_eq!(index.sorted_ids(&vault, KeySort::NameAsc), ["b", "a"]);
+        assert!(index.ids_by_service(&vault, "Stripe").is_empty());
+        assert_eq!(index.ids_by_service(&vault, "OPENAI"), ["a"]);
+        assert_eq!(index.ids_by_tag(&vault, "billing"), ["a"]);
+    }
+
+    #[test]
+    fn a_replaced_vault_is_indexed_once_invalidated() {
+        let mut vault = vault(vec![test_key("a").name("Alpha").build()]);
+        let mut index = VaultIndex::new();
+        assert_eq!(index.sorted_ids(&vault, KeySort::Service), ["a"]);
+
+        // Same number of keys, different ids, as after unlocking another vault
+        vault.keys.clear();
+        vault
+            .keys
+            .insert("z".to_string(), test_key("z").name("Zeta").build());
+        index.invalidate();
+
+        assert_eq!(index.sorted_ids(&vault, KeySort::Service), ["z"]);
+        assert_eq!(index.ids_by_service(&vault, "stripe"), ["z"]);
+        assert_eq!(
+            collect_keys(&vault, &["a".to_string(), "z".to_string()]).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn filters_intersect_and_leave_out_archived_keys() {
+        let mut vault = vault(vec![
+            test_key("a").name("Alpha").build(),
+            test_key("b").name("Beta").build(),
+            test_key("c").name("Gamma").service("GitHub").build(),
+        ]);
+        vault.keys.get_mut("a").unwrap().tags = vec!["prod".to_string()];
+        vault.keys.get_mut("b").unwrap().tags = vec!["prod".to_string()];
+        vault.keys.get_mut("b").unwrap().archived_at = Some(chrono::Utc::now());
+        let mut index = VaultIndex::new();
+
+        let filter = KeyFilter {
+            service: Some("stripe".to_string()),
+            tag: Some("PROD".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(index.filter_ids(&vault, &filter), ["a"]);
+
+        let mut with_archived = filter.clone();
+        with_archived.include_archived = true;
+        let mut ids = index.filter_ids(&vault, &with_archived);
+        ids.sort();
+        assert_eq!(ids, ["a", "b"]);
+    }
+}
@@ -0,0 +1,458 @@
+//! Vault integrity verification.
+//!
+//! Checks the vault file on disk rather than the in-memory copy: that it
+//! matches the checksum recorded in the metadata file and decrypts with the
+//! current key, that the metadata agrees with its contents, and that records
+//! inside the vault only reference things that exist. Nothing is repaired
+//! automatically; each issue carries a suggestion instead.
+
+use crate::errors::KeyKeeperError;
+use crate::vault_location::{decrypt_with_key, metadata_path};
+use crate::{guards, log_audit_event, ApiKeyVault, AppState, DocsStore, VaultMetadata};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use tauri::State;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueSeverity {
+    /// Data is missing or can't be read
+    Error,
+    /// Stale or dangling data that doesn't prevent use of the vault
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueCategory {
+    File,
+    Metadata,
+    References,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityIssue {
+    pub severity: IssueSeverity,
+    pub category: IssueCategory,
+    pub message: String,
+    /// Key, project or association the issue is about
+    pub resource_id: Option<String>,
+    pub suggestion: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub vault_path: String,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub checked_at: DateTime<Utc>,
+    /// SHA-256 of the vault and metadata files as read
+    pub vault_checksum: Option<String>,
+    pub metadata_checksum: Option<String>,
+    pub decrypted: bool,
+    pub key_count: usize,
+    pub issues: Vec<IntegrityIssue>,
+    /// No errors were found; warnings don't count
+    pub ok: bool,
+}
+
+pub fn checksum(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+fn issue(
+    severity: IssueSeverity,
+    category: IssueCategory,
+    resource_id: Option<&str>,
+    message: String,
+    suggestion: &str,
+) -> IntegrityIssue {
+    IntegrityIssue {
+        severity,
+        category,
+        message,
+        resource_id: resource_id.map(str::to_string),
+        suggestion: suggestion.to_string(),
+    }
+}
+
+/// Compare the metadata file with the decrypted vault
+pub fn check_metadata(metadata: &VaultMetadata, vault: &ApiKeyVault) -> Vec<IntegrityIssue> {
+    use IssueCategory::Metadata;
+    use IssueSeverity::{Error, Warning};
+    let mut issues = Vec::new();
+
+    if metadata.salt != vault.salt {
+        issues.push(issue(
+            Error,
+            Metadata,
+            None,
+            "Salt in the metadata file differs from the vault's".to_string(),
+            "Save any change to rewrite the metadata; keep a backup, as unlocking relies on this salt",
+        ));
+    }
+    if metadata.master_password_hash != vault.master_password_hash {
+        issues.push(issue(
+            Warning,
+            Metadata,
+            None,
+            "Master password hash in the metadata file differs from the vault's".to_string(),
+            "Save any change to rewrite the metadata",
+        ));
+    }
+    if metadata.schema_version != vault.schema_version {
+        issues.push(issue(
+            Warning,
+            Metadata,
+            None,
+            format!(
+                "Metadata schema v{} doesn't match vault schema v{}",
+                metadata.schema_version, vault.schema_version
+            ),
+            "Save any change to rewrite the metadata",
+        ));
+    }
+
+    let listed: HashSet<&str> = metadata
+        .api_keys_metadata
+        .iter()
+        .map(|k| k.id.as_str())
+        .collect();
+    if metadata.api_keys_metadata.len() != vault.keys.len() {
+        issues.push(issue(
+            Warning,
+            Metadata,
+            None,
+            format!(
+                "Metadata lists {} keys but the vault holds {}",
+                metadata.api_keys_metadata.len(),
+                vault.keys.len()
+            ),
+            "Save any change to rewrite the metadata",
+        ));
+    }
+    for id in vault.keys.keys().filter(|id| !listed.contains(id.as_str())) {
+        issues.push(issue(
+            Warning,
+            Metadata,
+            Some(id),
+            format!("Key {} is missing from the metadata file", id),
+            "Save any change to rewrite the metadata",
+        ));
+    }
+    for id in listed.iter().filter(|id| !vault.keys.contains_key(**id)) {
+        issues.push(issue(
+            Error,
+            Metadata,
+            Some(id),
+            format!("Metadata lists key {} which isn't in the vault", id),
+            "Restore the vault from a backup if the key should exist, otherwise save any change to drop it from the metadata",
+        ));
+    }
+    issues
+}
+
+/// Records inside the vault (and the docs store) that point at things that
+/// no longer exist
+pub fn check_references(vault: &ApiKeyVault, docs: Option<&DocsStore>) -> Vec<IntegrityIssue> {
+    use IssueCategory::References;
+    use IssueSeverity::{Error, Warning};
+    let mut issues = Vec::new();
+
+    for (id, key) in &vault.keys {
+        if &key.id != id {
+            issues.push(issue(
+                Error,
+                References,
+                Some(id),
+                format!("Key stored under {} has id {}", id, key.id),
+                "Export the key, delete it and import it again",
+            ));
+        }
+        let Some(project_path) = &key.project_path else {
+            continue;
+        };
+        match vault.projects.values().find(|p| &p.path == project_path) {
+            None => issues.push(issue(
+                Warning,
+                References,
+                Some(id),
+                format!(
+                    "Key '{}' is assigned to {}, which isn't a project",
+                    key.name, project_path
+                ),
+                "Create a project for that folder or assign the key to an existing one",
+            )),
+            Some(project) => {
+                if let Some(package) = &key.package_path {
+                    if !project.packages.iter().any(|p| &p.path == package) {
+                        issues.push(issue(
+                            Warning,
+                            References,
+                            Some(id),
+                            format!(
+                                "Key '{}' is assigned to package {} which project '{}' doesn't have",
+                                key.name, package, project.name
+                            ),
+                            "Re-analyze the project or assign the key without a package",
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    for assoc in &vault.env_associations {
+        if !Path::new(&assoc.project_path).is_dir() {
+            issues.push(issue(
+                Warning,
+                References,
+                Some(&assoc.id),
+                format!(
+                    "Env association points at missing folder {}",
+                    assoc.project_path
+                ),
+                "Remove the association or move the project back",
+            ));
+        } else if !Path::new(&assoc.env_file_path).is_file() {
+            issues.push(issue(
+                Warning,
+                References,
+                Some(&assoc.id),
+                format!("Env file {} no longer exists", assoc.env_file_path),
+                "Sync the project's keys to recreate it or remove the association",
+            ));
+        }
+    }
+
+    for mapping in &vault.github_secret_mappings {
+        if !vault.keys.contains_key(&mapping.key_id) {
+            issues.push(issue(
+                Warning,
+                References,
+                Some(&mapping.key_id),
+                format!(
+                    "GitHub secret {} in {} is mapped to a deleted key",
+                    mapping.secret_name, mapping.repo
+                ),
+                "Unlink the secret from GitHub sync",
+            ));
+        }
+    }
+
+    if let Some(docs) = docs {
+        for doc in docs.docs.values() {
+            if let Some(project_id) = &doc.project_id {
+                if !vault.projects.contains_key(project_id) {
+                    issues.push(issue(
+                        Warning,
+                        References,
+                        Some(&doc.id),
+                        format!("Document '{}' belongs to a deleted project", doc.title),
+                        "Detach the document from its project or delete it",
+                    ));
+                }
+            }
+        }
+    }
+    issues
+}
+
+/// Verify the vault file, its metadata and the references inside it
+#[tauri::command]
+pub async fn verify_vault_integrity(
+    state: State<'_, AppState>,
+) -> Result<IntegrityReport, KeyKeeperError> {
+    use IssueCategory::{File, Metadata};
+    use IssueSeverity::Error;
+
+    guards::unlocked(&state).await?;
+
+    let path = state.vault_path.path();
+    let meta_path = metadata_path(&path);
+    let mut issues = Vec::new();
+
+    let contents = fs::read(&path).map_err(|e| {
+        KeyKeeperError::Io(format!("Failed to read vault {}: {}", path.display(), e))
+    })?;
+    let vault_checksum = checksum(&contents);
+    let encrypted = !crate::is_plaintext_vault(&String::from_utf8_lossy(&contents));
+
+    let metadata_raw = fs::read(&meta_path).ok();
+    let metadata_checksum = metadata_raw.as_deref().map(checksum);
+    let metadata = match &metadata_raw {
+        Some(raw) => match serde_json::from_slice::<VaultMetadata>(raw) {
+            Ok(metadata) => Some(metadata),
+            Err(e) => {
+                issues.push(issue(
+                    Error,
+                    Metadata,
+                    None,
+                    format!("Metadata file can't be parsed: {}", e),
+                    "Save any change to rewrite the metadata while the vault is unlocked",
+                ));
+                None
+            }
+        },
+        None if encrypted => {
+            issues.push(issue(
+                Error,
+                Metadata,
+                None,
+                "Encrypted vault has no metadata file, so it can't be unlocked after locking"
+                    .to_string(),
+                "Save any change now to recreate it, before locking the vault",
+            ));
+            None
+        }
+        None => None,
+    };
+
+    if let Some(recorded) = metadata.as_ref().and_then(|m| m.vault_checksum.as_ref()) {
+        if recorded != &vault_checksum {
+            issues.push(issue(
+                Error,
+                File,
+                None,
+                "Vault file doesn't match the checksum recorded when it was saved".to_string(),
+                "The file was modified or corrupted outside KeyKeeper; restore it from a backup if it doesn't decrypt",
+            ));
+        }
+    }
+
+    let key = {
+        let vault_guard = state.vault.lock().await;
+        vault_guard
+            .encryption_key
+            .clone()
+            .filter(|k| k != "[ENCRYPTED]")
+    };
+    let decrypted = match (encrypted, key) {
+        (true, None) => {
+            issues.push(issue(
+                Error,
+                File,
+                None,
+                "Vault file is encrypted but no key is loaded".to_string(),
+                "Lock and unlock the vault, then verify again",
+            ));
+            None
+        }
+        (_, key) => {
+            let result = match key {
+                Some(key) => decrypt_with_key(&path, &key),
+                None => crate::migrations::load_vault_document(
+                    &String::from_utf8_lossy(&contents),
+                    &crate::vault_storage::FileStorage::new(&path),
+                ),
+            };
+            match result {
+                Ok(vault) => Some(vault),
+                Err(e) => {
+                    issues.push(issue(
+                        Error,
+                        File,
+                        None,
+                        format!("Vault file doesn't decrypt cleanly: {}", e),
+                        "Don't lock the vault; save any change to rewrite the file from memory, or restore a backup",
+                    ));
+                    None
+                }
+            }
+        }
+    };
+
+    if let Some(vault) = &decrypted {
+        if let Some(metadata) = &metadata {
+            issues.extend(check_metadata(metadata, vault));
+        }
+        let docs = state.docs_store.lock().await;
+        issues.extend(check_references(vault, Some(&docs)));
+    }
+
+    let ok = !issues.iter().any(|i| i.severity == Error);
+    let report = IntegrityReport {
+        vault_path: path.to_string_lossy().to_string(),
+        checked_at: Utc::now(),
+        vault_checksum: Some(vault_checksum),
+        metadata_checksum,
+        decrypted: decrypted.is_some(),
+        key_count: decrypted.as_ref().map_or(0, |v| v.keys.len()),
+        issues,
+        ok,
+    };
+
+    log_audit_event(
+        &state,
+        "verify_vault_integrity",
+        "vault",
+        None,
+        report.ok,
+        Some(&format!("{} issue(s)", report.issues.len())),
+    )
+    .await;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ApiKeyMetadata;
+
+    #[test]
+    fn metadata_mismatches_are_reported() {
+        let mut vault = ApiKeyVault {
+            salt: Some("salt".to_string()),
+            ..Default::default()
+        };
+        let key: crate::ApiKey = serde_json::from_value(serde_json::json!({
+            "id": "k1", "name": "Stripe", "key": "sk", "service": "stripe",
+            "description": null, "environment": "dev", "quota": null,
+            "expires_at": null, "scopes": [], "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z", "tags": [], "is_active": true,
+            "project_path": "/nowhere"
+        }))
+        .unwrap();
+        vault.keys.insert("k1".to_string(), key);
+
+        let ghost: ApiKeyMetadata = serde_json::from_value(serde_json::json!({
+            "id": "k2", "name": "Old", "key": "[ENCRYPTED]", "service": "x",
+            "description": null, "environment": "dev", "quota": null,
+            "expires_at": null, "scopes": [], "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z", "tags": [], "is_active": true
+        }))
+        .unwrap();
+        let metadata = VaultMetadata {
+            master_password_hash: None,
+            salt: Some("other".to_string()),
+            created_at: Utc::now(),
+            version: "test".to_string(),
+            schema_version: vault.schema_version,
+            api_keys_metadata: vec![ghost],
+            vault_checksum: None,
+            recovery: None,
+            hardware_unlock: None,
+            vault_id: None,
+            webhooks: Vec::new(),
+            failed_unlocks: Vec::new(),
+            notifications: None,
+        };
+
+        let issues = check_metadata(&metadata, &vault);
+        let errors: Vec<_> = issues
+            .iter()
+            .filter(|i| i.severity == IssueSeverity::Error)
+            .collect();
+        assert_eq!(errors.len(), 2);
+        assert!(issues
+            .iter()
+            .any(|i| i.resource_id.as_deref() == Some("k1")));
+
+        let references = check_references(&vault, None);
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].resource_id.as_deref(), Some("k1"));
+    }
+}
@@ -0,0 +1,539 @@
+//! Vault file location.
+//!
+//! The vault lives in the app data dir unless the user moves it, e.g. into a
+//! Dropbox or iCloud folder to share it between machines. The chosen path is
+//! kept in a small bootstrap file in the app data dir, since it has to be
+//! known before the vault can be read.
+//!
+//! Synced folders are treated as read-mostly: the file is reloaded when it
+//! changes on disk, a write is refused if it would overwrite changes that
+//! haven't been loaded yet, and a lock file marks the device writing it. Sync
+//! services give no atomicity, so the lock narrows conflicts rather than
+//! ruling them out; locks older than `STALE_LOCK_SECS` are taken over.
+
+use crate::errors::KeyKeeperError;
+use crate::vault_storage::{FileStorage, VaultStorage};
+use crate::{
+    decrypt_data, get_app_data_dir, get_device_id, guards, load_vault, log_audit_event, save_vault,
+    ApiKeyVault, AppState,
+};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tracing::{info, warn};
+use zeroize::Zeroizing;
+
+pub const DEFAULT_VAULT_FILE: &str = "vault.json";
+pub const BOOTSTRAP_FILE: &str = "bootstrap.json";
+/// A lock not refreshed for this long is assumed left behind by a crash
+const STALE_LOCK_SECS: i64 = 120;
+const WATCH_INTERVAL_SECS: u64 = 10;
+/// Path fragments of folders managed by a sync client
+const SYNCED_FOLDER_MARKERS: &[&str] = &[
+    "dropbox",
+    "com~apple~clouddocs",
+    "icloud drive",
+    "onedrive",
+    "google drive",
+    "googledrive",
+    "library/cloudstorage",
+    "nextcloud",
+    "pcloud",
+];
+
+/// Settings needed before the vault can be opened
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BootstrapConfig {
+    /// Custom vault file; the app data dir is used when unset
+    #[serde(default)]
+    pub vault_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultLock {
+    pub device_id: String,
+    pub pid: u32,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub acquired_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultLocationInfo {
+    pub path: String,
+    pub is_default: bool,
+    pub is_synced_folder: bool,
+    /// Lock left by another device, if any
+    pub foreign_lock: Option<VaultLock>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+fn file_stamp(path: &Path) -> Option<FileStamp> {
+    fs::metadata(path).ok().map(|m| FileStamp {
+        modified: m.modified().ok(),
+        len: m.len(),
+    })
+}
+
+pub fn metadata_path(vault_path: &Path) -> PathBuf {
+    vault_path.with_extension("metadata.json")
+}
+
+fn lock_path(vault_path: &Path) -> PathBuf {
+    vault_path.with_extension("lock")
+}
+
+pub fn is_synced_folder(path: &Path) -> bool {
+    let normalized = path.to_string_lossy().replace('\\', "/").to_lowercase();
+    SYNCED_FOLDER_MARKERS.iter().any(|m| normalized.contains(m))
+}
+
+/// Write through a temporary file in the same directory and rename it over
+/// `path`, so readers (and sync clients) never see a partial file
+pub fn write_atomic(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(format!(".{}.tmp", std::process::id()));
+    let tmp = path.with_file_name(tmp_name);
+    let written = fs::File::create(&tmp).and_then(|mut file| {
+        file.write_all(data)?;
+        file.sync_all()
+    });
+    let result = written.and_then(|_| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+pub fn read_bootstrap(app_data_dir: &Path) -> BootstrapConfig {
+    fs::read_to_string(app_data_dir.join(BOOTSTRAP_FILE))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn write_bootstrap(app_data_dir: &Path, config: &BootstrapConfig) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to serialize bootstrap config: {}", e))?;
+    write_atomic(&app_data_dir.join(BOOTSTRAP_FILE), json.as_bytes())
+        .map_err(|e| format!("Failed to write bootstrap config: {}", e))
+}
+
+/// Vault file to open: the configured one, or `vault.json` in the app data dir
+pub fn resolve_vault_path(app_data_dir: &Path) -> PathBuf {
+    match read_bootstrap(app_data_dir).vault_path {
+        Some(path) => {
+            if !path.parent().is_some_and(Path::exists) {
+                // Most likely a sync folder that isn't mounted yet; opening
+                // the default vault instead would silently hide this one
+                warn!("Configured vault folder {} is unavailable", path.display());
+            }
+            path
+        }
+        None => app_data_dir.join(DEFAULT_VAULT_FILE),
+    }
+}
+
+fn read_lock(vault_path: &Path) -> Option<VaultLock> {
+    fs::read_to_string(lock_path(vault_path))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+}
+
+/// Lock held by another device that isn't stale yet
+fn foreign_lock(vault_path: &Path, device_id: &str) -> Option<VaultLock> {
+    read_lock(vault_path).filter(|lock| {
+        lock.device_id != device_id
+            && Utc::now() - lock.acquired_at < ChronoDuration::seconds(STALE_LOCK_SECS)
+    })
+}
+
+fn acquire_lock(vault_path: &Path) -> Result<(), KeyKeeperError> {
+    let device_id = get_device_id();
+    if let Some(lock) = foreign_lock(vault_path, &device_id) {
+        return Err(KeyKeeperError::Conflict(format!(
+            "The vault is being written by {}; try again shortly",
+            lock.device_id
+        )));
+    }
+    if let Some(stale) = read_lock(vault_path).filter(|l| l.device_id != device_id) {
+        warn!("Taking over stale vault lock of {}", stale.device_id);
+    }
+    let lock = VaultLock {
+        device_id,
+        pid: std::process::id(),
+        acquired_at: Utc::now(),
+    };
+    let json = serde_json::to_string(&lock).map_err(|e| KeyKeeperError::Internal(e.to_string()))?;
+    write_atomic(&lock_path(vault_path), json.as_bytes())
+        .map_err(|e| KeyKeeperError::Io(format!("Failed to write vault lock: {}", e)))
+}
+
+fn release_lock(vault_path: &Path) {
+    if read_lock(vault_path).is_some_and(|l| l.device_id == get_device_id()) {
+        let _ = fs::remove_file(lock_path(vault_path));
+    }
+}
+
+/// Where the vault is stored, shared by the app and the HTTP server so a
+/// move is seen everywhere
+#[derive(Debug, Clone)]
+pub struct VaultLocation {
+    path: Arc<RwLock<PathBuf>>,
+    /// The vault file as last loaded or written by this process
+    known: Arc<Mutex<Option<FileStamp>>>,
+    last_saved: Arc<Mutex<Option<DateTime<Utc>>>>,
+}
+
+impl VaultLocation {
+    pub fn new(path: PathBuf) -> Self {
+        let stamp = file_stamp(&path);
+        Self {
+            path: Arc::new(RwLock::new(path)),
+            known: Arc::new(Mutex::new(stamp)),
+            last_saved: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.path.read().unwrap().clone()
+    }
+
+    pub fn metadata_path(&self) -> PathBuf {
+        metadata_path(&self.path())
+    }
+
+    pub fn is_synced(&self) -> bool {
+        is_synced_folder(&self.path())
+    }
+
+    /// Last successful write by this process
+    pub fn last_saved(&self) -> Option<DateTime<Utc>> {
+        *self.last_saved.lock().unwrap()
+    }
+
+    fn set_known(&self, stamp: Option<FileStamp>) {
+        *self.known.lock().unwrap() = stamp;
+    }
+
+    /// Whether the file was changed by someone else since it was last loaded
+    /// or written here
+    pub fn changed_on_disk(&self) -> bool {
+        *self.known.lock().unwrap() != file_stamp(&self.path())
+    }
+}
+
+/// The vault file at the current location. Saves into a synced folder are
+/// refused if they would overwrite changes that haven't been loaded, and hold
+/// the lock file while writing.
+impl VaultStorage for VaultLocation {
+    fn read_vault(&self) -> Result<Option<String>, String> {
+        FileStorage::new(self.path()).read_vault()
+    }
+
+    fn write_vault(&self, contents: &str) -> Result<(), String> {
+        FileStorage::new(self.path()).write_vault(contents)
+    }
+
+    fn read_metadata(&self) -> Result<Option<String>, String> {
+        FileStorage::new(self.path()).read_metadata()
+    }
+
+    fn write_metadata(&self, contents: &str) -> Result<(), String> {
+        FileStorage::new(self.path()).write_metadata(contents)
+    }
+
+    fn backup(&self, schema_version: u32) -> Result<(), String> {
+        FileStorage::new(self.path()).backup(schema_version)
+    }
+
+    fn begin_save(&self) -> Result<(), KeyKeeperError> {
+        let path = self.path();
+        if is_synced_folder(&path) {
+            if self.changed_on_disk() {
+                return Err(KeyKeeperError::Conflict("The vault file was changed on another device and will be reloaded; try again shortly".to_string()));
+            }
+            acquire_lock(&path)?;
+        }
+        Ok(())
+    }
+
+    fn end_save(&self, written: bool) {
+        let path = self.path();
+        if is_synced_folder(&path) {
+            release_lock(&path);
+        }
+        if written {
+            self.set_known(file_stamp(&path));
+            *self.last_saved.lock().unwrap() = Some(Utc::now());
+        }
+    }
+}
+
+/// Load the vault file with the key of the unlocked vault
+pub fn decrypt_with_key(path: &Path, key: &str) -> Result<ApiKeyVault, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read vault: {}", e))?;
+    if crate::is_plaintext_vault(&contents) {
+        return crate::migrations::load_vault_document(
+            &contents,
+            &crate::vault_storage::FileStorage::new(path),
+        );
+    }
+    let key_bytes = Zeroizing::new(
+        general_purpose::STANDARD
+            .decode(key)
+            .map_err(|e| format!("Failed to decode encryption key: {}", e))?,
+    );
+    let json = Zeroizing::new(decrypt_data(&contents, &key_bytes)?);
+    crate::migrations::load_vault_document(&json, &crate::vault_storage::FileStorage::new(path))
+}
+
+/// Replace the in-memory vault with the file on disk
+async fn reload(state: &AppState) -> Result<(), String> {
+    let path = state.vault_path.path();
+    let stamp = file_stamp(&path);
+    let unlocked = *state.is_unlocked.lock().await;
+
+    let mut vault_guard = state.vault.lock().await;
+    let key = vault_guard
+        .encryption_key
+        .clone()
+        .filter(|k| k != "[ENCRYPTED]");
+    let reloaded = match key {
+        Some(key) if unlocked => decrypt_with_key(&path, &key).map_err(|e| {
+            format!(
+                "Failed to reload the changed vault, lock and unlock to open it: {}",
+                e
+            )
+        })?,
+        _ => load_vault(&path)?,
+    };
+    crate::secure_memory::wipe_vault_secrets(&mut vault_guard);
+    *vault_guard = reloaded;
+    drop(vault_guard);
+
+    state.vault_path.set_known(stamp);
+    state.vault_index.lock().await.invalidate();
+    Ok(())
+}
+
+/// Reload the vault whenever its file is changed from outside, e.g. by a
+/// sync client
+pub async fn run_vault_file_watcher(app: AppHandle) {
+    let mut failed: Option<FileStamp> = None;
+    loop {
+        tokio::time::sleep(Duration::from_secs(WATCH_INTERVAL_SECS)).await;
+
+        let state: State<'_, AppState> = app.state();
+        let path = state.vault_path.path();
+        let current = file_stamp(&path);
+        if current.is_none() || !state.vault_path.changed_on_disk() || failed == current {
+            continue;
+        }
+
+        let _task = state.health.begin_task("vault_reload");
+        match reload(&state).await {
+            Ok(()) => {
+                info!("Vault file changed on disk, reloaded {}", path.display());
+                failed = None;
+                let _ = app.emit("vault-file-changed", path.to_string_lossy().to_string());
+            }
+            Err(e) => {
+                warn!("{}", e);
+                failed = current;
+            }
+        }
+    }
+}
+
+/// Copy `from` next to `to` and check the copy before renaming it into place
+fn copy_verified(from: &Path, to: &Path, key: Option<&str>) -> Result<(), KeyKeeperError> {
+    let original = fs::read(from)?;
+    let mut tmp_name = to.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".moving");
+    let tmp = to.with_file_name(tmp_name);
+    fs::write(&tmp, &original)?;
+
+    let copied = fs::read(&tmp)?;
+    let verified = if Sha256::digest(&copied) != Sha256::digest(&original) {
+        Err(KeyKeeperError::Io(format!(
+            "Copy of {} does not match the original",
+            from.display()
+        )))
+    } else {
+        Ok(())
+    };
+    let verified = verified.and_then(|_| match key {
+        // The vault itself must still open with the current key
+        Some(key) => decrypt_with_key(&tmp, key)
+            .map(|_| ())
+            .map_err(KeyKeeperError::Crypto),
+        None => Ok(()),
+    });
+    if let Err(e) = verified {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    fs::rename(&tmp, to)?;
+    Ok(())
+}
+
+fn location_info(path: &Path, app_data_dir: Option<&Path>) -> VaultLocationInfo {
+    VaultLocationInfo {
+        path: path.to_string_lossy().to_string(),
+        is_default: app_data_dir.is_some_and(|dir| path == dir.join(DEFAULT_VAULT_FILE)),
+        is_synced_folder: is_synced_folder(path),
+        foreign_lock: foreign_lock(path, &get_device_id()),
+    }
+}
+
+#[tauri::command]
+pub async fn get_vault_location(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<VaultLocationInfo, KeyKeeperError> {
+    let app_data_dir = get_app_data_dir(&app).ok();
+    Ok(location_info(
+        &state.vault_path.path(),
+        app_data_dir.as_deref(),
+    ))
+}
+
+/// Move the vault to `path` (a file, or a folder to put `vault.json` in).
+/// The copy is verified before the app switches to it and the old files are
+/// removed.
+#[tauri::command]
+pub async fn set_vault_location(
+    path: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<VaultLocationInfo, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    if state.config.effective().vault.path.is_some() {
+        return Err(KeyKeeperError::Validation(
+            "The vault path is pinned by keykeeper.toml or KEYKEEPER_VAULT_PATH".to_string(),
+        ));
+    }
+    let _task = state.health.begin_task("vault_move");
+
+    let mut target = PathBuf::from(path.trim());
+    if !target.is_absolute() {
+        return Err(KeyKeeperError::Validation(
+            "Vault location must be an absolute path".to_string(),
+        ));
+    }
+    if target.is_dir() {
+        target = target.join(DEFAULT_VAULT_FILE);
+    } else if target.extension().is_none_or(|e| e != "json") {
+        return Err(KeyKeeperError::Validation(
+            "Vault file must have a .json extension".to_string(),
+        ));
+    }
+    if !target.parent().is_some_and(Path::is_dir) {
+        return Err(KeyKeeperError::NotFound(format!(
+            "Folder {} does not exist",
+            target.parent().unwrap_or(&target).display()
+        )));
+    }
+
+    let app_data_dir = get_app_data_dir(&app)?;
+    let current = state.vault_path.path();
+    if target == current {
+        return Ok(location_info(&current, Some(&app_data_dir)));
+    }
+    if target.exists() || metadata_path(&target).exists() {
+        return Err(KeyKeeperError::Validation(format!(
+            "A vault already exists at {}",
+            target.display()
+        )));
+    }
+
+    // Flush pending changes, then hold the vault so nothing is saved to the
+    // old location mid-move
+    save_vault(&state).await?;
+    let vault_guard = state.vault.lock().await;
+    let key = vault_guard
+        .encryption_key
+        .clone()
+        .filter(|k| k != "[ENCRYPTED]");
+
+    let old_metadata = metadata_path(&current);
+    if old_metadata.exists() {
+        copy_verified(&old_metadata, &metadata_path(&target), None)?;
+    }
+    if let Err(e) = copy_verified(&current, &target, key.as_deref()) {
+        let _ = fs::remove_file(metadata_path(&target));
+        return Err(e);
+    }
+
+    let config = BootstrapConfig {
+        vault_path: (target != app_data_dir.join(DEFAULT_VAULT_FILE)).then(|| target.clone()),
+    };
+    if let Err(e) = write_bootstrap(&app_data_dir, &config) {
+        let _ = fs::remove_file(&target);
+        let _ = fs::remove_file(metadata_path(&target));
+        return Err(KeyKeeperError::Io(e));
+    }
+    *state.vault_path.path.write().unwrap() = target.clone();
+    state.vault_path.set_known(file_stamp(&target));
+    drop(vault_guard);
+
+    release_lock(&current);
+    for old in [&current, &old_metadata] {
+        if old.exists() {
+            if let Err(e) = fs::remove_file(old) {
+                warn!("Failed to remove old vault file {}: {}", old.display(), e);
+            }
+        }
+    }
+
+    let message = format!("{} -> {}", current.display(), target.display());
+    log_audit_event(
+        &state,
+        "set_vault_location",
+        "vault",
+        None,
+        true,
+        Some(&message),
+    )
+    .await;
+    Ok(location_info(&target, Some(&app_data_dir)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_external_changes_and_synced_folders() {
+        let dir = std::env::temp_dir().join(format!("kk-location-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(DEFAULT_VAULT_FILE);
+        write_atomic(&path, b"{}").unwrap();
+
+        let location = VaultLocation::new(path.clone());
+        assert!(!location.changed_on_disk());
+        fs::write(&path, br#"{"keys": {}}"#).unwrap();
+        assert!(location.changed_on_disk());
+
+        assert!(is_synced_folder(Path::new(
+            "/Users/me/Dropbox/keys/vault.json"
+        )));
+        assert!(is_synced_folder(Path::new(
+            "/Users/me/Library/Mobile Documents/com~apple~CloudDocs/vault.json"
+        )));
+        assert!(!location.is_synced());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,315 @@
+//! Read-only snapshot of the vault for reporting tools.
+//!
+//! A snapshot is the vault's inventory as JSON: the keys' metadata, the
+//! projects, the statistics and a summary of the audit log. Secret values
+//! are never part of it, not even masked, so BI tooling can consume it
+//! without being trusted with the vault. `create_vault_snapshot` writes it
+//! to a new read-only file, or returns it, and `GET /api/vault/snapshot`
+//! serves it over the HTTP API.
+//!
+//! Each snapshot carries a SHA-256 `checksum` of its content, computed with
+//! the checksum left empty, so a copy can be told apart from an edited one.
+
+use crate::errors::KeyKeeperError;
+use crate::vault_stats::{compute_vault_statistics, VaultStatistics};
+use crate::{guards, log_audit_event, ApiKey, ApiKeyVault, AppState, Project};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use tauri::State;
+
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Days of audit log summarized by default
+const DEFAULT_AUDIT_DAYS: u32 = 30;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SnapshotOptions {
+    /// File to write the snapshot to; it must not exist yet
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub include_archived: bool,
+    /// Days of audit log to summarize, 0 to leave the summary out
+    #[serde(default)]
+    pub audit_days: Option<u32>,
+}
+
+impl SnapshotOptions {
+    /// Options of a `GET /api/vault/snapshot` query
+    pub fn from_params(params: &crate::batch::Params) -> Self {
+        SnapshotOptions {
+            path: None,
+            include_archived: params.get("include_archived").is_some_and(|v| v == "true"),
+            audit_days: params.get("audit_days").and_then(|v| v.parse().ok()),
+        }
+    }
+}
+
+/// A key without its value
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeySnapshot {
+    pub id: String,
+    pub name: String,
+    pub service: String,
+    pub environment: String,
+    /// Environments with a value of their own
+    pub environments: Vec<String>,
+    pub project_path: Option<String>,
+    pub package_path: Option<String>,
+    pub tags: Vec<String>,
+    pub scopes: Vec<String>,
+    pub is_active: bool,
+    pub archived: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub last_rotated_at: Option<DateTime<Utc>>,
+    pub rotation_interval_days: Option<u32>,
+}
+
+impl From<&ApiKey> for KeySnapshot {
+    fn from(key: &ApiKey) -> Self {
+        KeySnapshot {
+            id: key.id.clone(),
+            name: key.name.clone(),
+            service: key.service.clone(),
+            environment: key.environment.clone(),
+            environments: key.values.keys().cloned().collect(),
+            project_path: key.project_path.clone(),
+            package_path: key.package_path.clone(),
+            tags: key.tags.clone(),
+            scopes: key.scopes.clone(),
+            is_active: key.is_active,
+            archived: key.archived_at.is_some(),
+            created_at: key.created_at,
+            updated_at: key.updated_at,
+            expires_at: key.expires_at,
+            last_used_at: key.last_used_at,
+            last_rotated_at: key.last_rotated_at,
+            rotation_interval_days: key.rotation_interval_days,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectSnapshot {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub group_id: Option<String>,
+    pub default_environment: String,
+    pub key_count: usize,
+    pub env_file_count: usize,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ProjectSnapshot {
+    fn new(project: &Project, vault: &ApiKeyVault) -> Self {
+        ProjectSnapshot {
+            id: project.id.clone(),
+            name: project.name.clone(),
+            path: project.path.clone(),
+            group_id: project.group_id.clone(),
+            default_environment: project.settings.default_environment.clone(),
+            key_count: vault
+                .keys
+                .values()
+                .filter(|k| k.project_path.as_deref() == Some(project.path.as_str()))
+                .count(),
+            env_file_count: vault
+                .env_associations
+                .iter()
+                .filter(|a| a.project_path == project.path)
+                .count(),
+            created_at: project.created_at,
+            updated_at: project.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditSummary {
+    pub since: DateTime<Utc>,
+    pub total: usize,
+    pub failed: usize,
+    pub by_action: BTreeMap<String, usize>,
+    pub last_event_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultSnapshot {
+    pub format_version: u32,
+    pub snapshot_id: String,
+    pub generated_at: DateTime<Utc>,
+    pub vault_id: String,
+    pub schema_version: u32,
+    pub keys: Vec<KeySnapshot>,
+    pub projects: Vec<ProjectSnapshot>,
+    pub stats: VaultStatistics,
+    pub audit: Option<AuditSummary>,
+    /// SHA-256 of the snapshot with this field empty
+    pub checksum: String,
+}
+
+impl VaultSnapshot {
+    fn digest(&self) -> Result<String, KeyKeeperError> {
+        let unsigned = VaultSnapshot {
+            checksum: String::new(),
+            ..self.clone()
+        };
+        Ok(format!(
+            "{:x}",
+            Sha256::digest(serde_json::to_vec(&unsigned)?)
+        ))
+    }
+}
+
+fn audit_summary(vault: &ApiKeyVault, since: DateTime<Utc>) -> AuditSummary {
+    let mut summary = AuditSummary {
+        since,
+        total: 0,
+        failed: 0,
+        by_action: BTreeMap::new(),
+        last_event_at: None,
+    };
+    for log in vault.audit_logs.iter().filter(|l| l.timestamp >= since) {
+        summary.total += 1;
+        if !log.success {
+            summary.failed += 1;
+        }
+        *summary.by_action.entry(log.action.clone()).or_default() += 1;
+        summary.last_event_at = summary.last_event_at.max(Some(log.timestamp));
+    }
+    summary
+}
+
+pub fn build(
+    vault: &ApiKeyVault,
+    docs_count: usize,
+    options: &SnapshotOptions,
+    now: DateTime<Utc>,
+) -> Result<VaultSnapshot, KeyKeeperError> {
+    let mut keys: Vec<KeySnapshot> = vault
+        .keys
+        .values()
+        .filter(|k| options.include_archived || k.archived_at.is_none())
+        .map(KeySnapshot::from)
+        .collect();
+    keys.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+
+    let mut projects: Vec<ProjectSnapshot> = vault
+        .projects
+        .values()
+        .map(|p| ProjectSnapshot::new(p, vault))
+        .collect();
+    projects.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+
+    let audit = match options.audit_days.unwrap_or(DEFAULT_AUDIT_DAYS) {
+        0 => None,
+        days => Some(audit_summary(vault, now - Duration::days(days.into()))),
+    };
+
+    let mut snapshot = VaultSnapshot {
+        format_version: FORMAT_VERSION,
+        snapshot_id: uuid::Uuid::new_v4().to_string(),
+        generated_at: now,
+        vault_id: vault.vault_id.clone(),
+        schema_version: vault.schema_version,
+        keys,
+        projects,
+        stats: compute_vault_statistics(vault, docs_count),
+        audit,
+        checksum: String::new(),
+    };
+    snapshot.checksum = snapshot.digest()?;
+    Ok(snapshot)
+}
+
+/// Write `snapshot` to a new file at `path` and make it read-only
+fn write(snapshot: &VaultSnapshot, path: &str) -> Result<(), KeyKeeperError> {
+    let content = serde_json::to_vec_pretty(snapshot)?;
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(|e| KeyKeeperError::Io(format!("Failed to create {}: {}", path, e)))?;
+    file.write_all(&content)
+        .map_err(|e| KeyKeeperError::Io(format!("Failed to write {}: {}", path, e)))?;
+
+    let mut permissions = file
+        .metadata()
+        .map_err(|e| KeyKeeperError::Io(e.to_string()))?
+        .permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(path, permissions)
+        .map_err(|e| KeyKeeperError::Io(format!("Failed to protect {}: {}", path, e)))
+}
+
+/// Snapshot the vault's inventory, writing it to `options.path` when set
+#[tauri::command]
+pub async fn create_vault_snapshot(
+    options: Option<SnapshotOptions>,
+    state: State<'_, AppState>,
+) -> Result<VaultSnapshot, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let options = options.unwrap_or_default();
+    let docs_count = state.docs_store.lock().await.docs.len();
+    let snapshot = build(&*state.vault.lock().await, docs_count, &options, Utc::now())?;
+    if let Some(path) = &options.path {
+        write(&snapshot, path)?;
+    }
+
+    log_audit_event(
+        &state,
+        "create_vault_snapshot",
+        "vault",
+        Some(&snapshot.snapshot_id),
+        true,
+        options.path.as_deref(),
+    )
+    .await;
+    Ok(snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault_tests::test_key;
+
+    #[test]
+    fn snapshots_leave_secret_values_out() {
+        let mut vault = ApiKeyVault::default();
+        let key = test_key("k1")
+            .name("Stripe secret")
+            .secret("sk_live_do_not_leak")
+            .environment("production")
+            .value("development", "sk_test_do_not_leak")
+            .build();
+        vault.keys.insert(key.id.clone(), key);
+        vault.audit_logs.push(crate::create_audit_log(
+            "create_key",
+            "api_key",
+            Some("k1"),
+            true,
+            None,
+        ));
+
+        let snapshot = build(&vault, 0, &SnapshotOptions::default(), Utc::now()).unwrap();
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert!(!json.contains("do_not_leak"));
+        assert_eq!(snapshot.keys[0].environments, ["development"]);
+        assert_eq!(snapshot.audit.as_ref().unwrap().by_action["create_key"], 1);
+        assert_eq!(snapshot.digest().unwrap(), snapshot.checksum);
+
+        let mut edited = snapshot.clone();
+        edited.keys[0].environment = "development".to_string();
+        assert_ne!(edited.digest().unwrap(), snapshot.checksum);
+    }
+}
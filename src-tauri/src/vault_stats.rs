@@ -0,0 +1,149 @@
+use crate::errors::KeyKeeperError;
+use crate::notifications::EXPIRY_WARNING_DAYS;
+use crate::save_queue::SaveMetrics;
+use crate::{guards, ApiKeyVault, AppState};
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tauri::State;
+
+const ACTIVITY_WEEKS: i64 = 12;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklyActivity {
+    /// ISO week, e.g. "2024-W07"
+    pub week: String,
+    pub events: usize,
+    pub key_usages: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultStatistics {
+    pub total_keys: usize,
+    pub active_keys: usize,
+    pub inactive_keys: usize,
+    pub expired_keys: usize,
+    pub expiring_soon: usize,
+    /// Active keys past their rotation date
+    pub rotation_overdue: usize,
+    pub keys_by_service: BTreeMap<String, usize>,
+    pub keys_by_environment: BTreeMap<String, usize>,
+    /// Keyed by project name; keys without a project are counted under "unassigned"
+    pub keys_by_project: BTreeMap<String, usize>,
+    pub total_projects: usize,
+    pub docs_count: usize,
+    pub activity_by_week: Vec<WeeklyActivity>,
+    /// Save counts and latencies since launch; only `get_vault_statistics`
+    /// fills this in
+    pub saves: Option<SaveMetrics>,
+    pub generated_at: String,
+}
+
+fn iso_week(timestamp: &DateTime<Utc>) -> String {
+    let week = timestamp.iso_week();
+    format!("{}-W{:02}", week.year(), week.week())
+}
+
+/// Compute statistics over the vault without exposing any key values
+pub fn compute_vault_statistics(vault: &ApiKeyVault, docs_count: usize) -> VaultStatistics {
+    let now = Utc::now();
+    let warning_cutoff = now + ChronoDuration::days(EXPIRY_WARNING_DAYS);
+
+    let project_names: HashMap<&str, &str> = vault
+        .projects
+        .values()
+        .map(|p| (p.path.as_str(), p.name.as_str()))
+        .collect();
+
+    let mut stats = VaultStatistics {
+        total_keys: vault.keys.len(),
+        active_keys: 0,
+        inactive_keys: 0,
+        expired_keys: 0,
+        expiring_soon: 0,
+        rotation_overdue: crate::rotation::count_overdue(vault, now),
+        keys_by_service: BTreeMap::new(),
+        keys_by_environment: BTreeMap::new(),
+        keys_by_project: BTreeMap::new(),
+        total_projects: vault.projects.len(),
+        docs_count,
+        activity_by_week: Vec::new(),
+        saves: None,
+        generated_at: now.to_rfc3339(),
+    };
+
+    for key in vault.keys.values() {
+        if key.is_active {
+            stats.active_keys += 1;
+        } else {
+            stats.inactive_keys += 1;
+        }
+        match key.expires_at {
+            Some(expiry) if expiry <= now => stats.expired_keys += 1,
+            Some(expiry) if expiry <= warning_cutoff => stats.expiring_soon += 1,
+            _ => {}
+        }
+        *stats
+            .keys_by_service
+            .entry(key.service.clone())
+            .or_default() += 1;
+        *stats
+            .keys_by_environment
+            .entry(key.environment.clone())
+            .or_default() += 1;
+        let project = key
+            .project_path
+            .as_deref()
+            .map(|path| project_names.get(path).copied().unwrap_or(path))
+            .unwrap_or("unassigned");
+        *stats
+            .keys_by_project
+            .entry(project.to_string())
+            .or_default() += 1;
+    }
+
+    // Pre-fill the window so weeks without activity still show up
+    let mut weeks: BTreeMap<String, WeeklyActivity> = (0..ACTIVITY_WEEKS)
+        .map(|i| {
+            let week = iso_week(&(now - ChronoDuration::weeks(i)));
+            (
+                week.clone(),
+                WeeklyActivity {
+                    week,
+                    events: 0,
+                    key_usages: 0,
+                },
+            )
+        })
+        .collect();
+    for log in &vault.audit_logs {
+        if let Some(bucket) = weeks.get_mut(&iso_week(&log.timestamp)) {
+            bucket.events += 1;
+        }
+    }
+    for activity in &vault.recent_activity {
+        if activity.activity_type == "key_used" {
+            if let Some(bucket) = weeks.get_mut(&iso_week(&activity.timestamp)) {
+                bucket.key_usages += 1;
+            }
+        }
+    }
+    stats.activity_by_week = weeks.into_values().collect();
+
+    stats
+}
+
+/// Counts and breakdowns for the dashboard, computed without shipping the vault
+#[tauri::command]
+pub async fn get_vault_statistics(
+    state: State<'_, AppState>,
+) -> Result<VaultStatistics, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let docs_count = state.docs_store.lock().await.docs.len();
+    let vault_guard = state.vault.lock().await;
+    Ok(VaultStatistics {
+        saves: Some(state.saves.metrics()),
+        ..compute_vault_statistics(&vault_guard, docs_count)
+    })
+}
@@ -0,0 +1,112 @@
+//! Where the vault document and its metadata are kept.
+//!
+//! Persistence (`save_vault_to`, `load_vault_from`, `decrypt_vault_from`)
+//! works on a `VaultStorage` rather than on paths. The app holds one in
+//! `AppState::storage`, normally the `VaultLocation` (the vault file wherever
+//! the user keeps it); `FileStorage` is a vault file at a fixed path and
+//! `MemoryStorage` keeps both documents in memory so the encryption and
+//! loading logic can be tested without touching the filesystem.
+
+use crate::errors::KeyKeeperError;
+use crate::vault_location::{metadata_path, write_atomic};
+use std::fs;
+use std::path::PathBuf;
+
+pub trait VaultStorage: Send + Sync {
+    /// The vault document (JSON, or base64 ciphertext); `None` if there's none
+    fn read_vault(&self) -> Result<Option<String>, String>;
+    fn write_vault(&self, contents: &str) -> Result<(), String>;
+    fn read_metadata(&self) -> Result<Option<String>, String>;
+    fn write_metadata(&self, contents: &str) -> Result<(), String>;
+    /// Keep a copy of the current documents before a schema migration
+    /// rewrites them
+    fn backup(&self, schema_version: u32) -> Result<(), String>;
+
+    /// Called before the documents of a save are written; a backend shared
+    /// with other writers can refuse the save here
+    fn begin_save(&self) -> Result<(), KeyKeeperError> {
+        Ok(())
+    }
+
+    /// Called after a save that passed `begin_save`, `written` if it succeeded
+    fn end_save(&self, _written: bool) {}
+}
+
+/// The vault file, with its metadata in `<name>.metadata.json`
+pub struct FileStorage {
+    path: PathBuf,
+}
+
+impl FileStorage {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+fn read_optional(path: &std::path::Path) -> Result<Option<String>, String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+    }
+}
+
+impl VaultStorage for FileStorage {
+    fn read_vault(&self) -> Result<Option<String>, String> {
+        read_optional(&self.path)
+    }
+
+    fn write_vault(&self, contents: &str) -> Result<(), String> {
+        write_atomic(&self.path, contents.as_bytes())
+            .map_err(|e| format!("Failed to save vault: {}", e))
+    }
+
+    fn read_metadata(&self) -> Result<Option<String>, String> {
+        read_optional(&metadata_path(&self.path))
+    }
+
+    fn write_metadata(&self, contents: &str) -> Result<(), String> {
+        write_atomic(&metadata_path(&self.path), contents.as_bytes())
+            .map_err(|e| format!("Failed to save metadata: {}", e))
+    }
+
+    fn backup(&self, schema_version: u32) -> Result<(), String> {
+        crate::migrations::backup_before_migrate(&self.path, schema_version)
+    }
+}
+
+/// Vault kept in memory, for tests
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryStorage {
+    pub vault: std::sync::Mutex<Option<String>>,
+    pub metadata: std::sync::Mutex<Option<String>>,
+    /// Schema versions backed up before migrations
+    pub backups: std::sync::Mutex<Vec<u32>>,
+}
+
+#[cfg(test)]
+impl VaultStorage for MemoryStorage {
+    fn read_vault(&self) -> Result<Option<String>, String> {
+        Ok(self.vault.lock().unwrap().clone())
+    }
+
+    fn write_vault(&self, contents: &str) -> Result<(), String> {
+        *self.vault.lock().unwrap() = Some(contents.to_string());
+        Ok(())
+    }
+
+    fn read_metadata(&self) -> Result<Option<String>, String> {
+        Ok(self.metadata.lock().unwrap().clone())
+    }
+
+    fn write_metadata(&self, contents: &str) -> Result<(), String> {
+        *self.metadata.lock().unwrap() = Some(contents.to_string());
+        Ok(())
+    }
+
+    fn backup(&self, schema_version: u32) -> Result<(), String> {
+        self.backups.lock().unwrap().push(schema_version);
+        Ok(())
+    }
+}
@@ -0,0 +1,560 @@
+//! Tests around vault encryption and persistence, run against
+//! `MemoryStorage` so nothing touches the real vault file. Also home to
+//! `test_key`, the `ApiKey` fixture the other modules' tests build on.
+
+use crate::vault_storage::{MemoryStorage, VaultStorage};
+use crate::*;
+use proptest::prelude::*;
+
+const PASSWORD: &str = "correct horse battery staple";
+const SALT: [u8; 16] = [7; 16];
+
+/// `ApiKey` fixture for tests anywhere in the crate: a plain development
+/// Stripe key created on 2024-01-01, changed through the setters a test
+/// cares about.
+pub(crate) fn test_key(id: &str) -> TestKey {
+    let created_at = timestamp("2024-01-01T00:00:00Z");
+    TestKey(ApiKey {
+        id: id.to_string(),
+        name: id.to_string(),
+        service: "Stripe".to_string(),
+        key: "sk".to_string(),
+        description: None,
+        environment: "development".to_string(),
+        quota: None,
+        cost: None,
+        expires_at: None,
+        scopes: vec![],
+        created_at,
+        updated_at: created_at,
+        tags: vec![],
+        is_active: true,
+        source_type: None,
+        env_file_path: None,
+        project_path: None,
+        env_file_name: None,
+        remote_secrets: vec![],
+        package_path: None,
+        rotation_interval_days: None,
+        last_rotated_at: None,
+        kind: Default::default(),
+        credential: None,
+        oauth: None,
+        values: Default::default(),
+        is_pinned: false,
+        archived_at: None,
+        last_used_at: None,
+        backend: None,
+    })
+}
+
+fn timestamp(rfc3339: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(rfc3339)
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+pub(crate) struct TestKey(ApiKey);
+
+impl TestKey {
+    pub fn name(mut self, name: &str) -> Self {
+        self.0.name = name.to_string();
+        self
+    }
+
+    pub fn service(mut self, service: &str) -> Self {
+        self.0.service = service.to_string();
+        self
+    }
+
+    /// The value in the key's own environment
+    pub fn secret(mut self, secret: &str) -> Self {
+        self.0.key = secret.to_string();
+        self
+    }
+
+    pub fn environment(mut self, environment: &str) -> Self {
+        self.0.environment = environment.to_string();
+        self
+    }
+
+    /// The value in another environment
+    pub fn value(mut self, environment: &str, value: &str) -> Self {
+        self.0
+            .values
+            .insert(environment.to_string(), value.to_string());
+        self
+    }
+
+    pub fn project(mut self, project_path: Option<&str>) -> Self {
+        self.0.project_path = project_path.map(str::to_string);
+        self
+    }
+
+    pub fn tags(mut self, tags: &[&str]) -> Self {
+        self.0.tags = tags.iter().map(|t| t.to_string()).collect();
+        self
+    }
+
+    pub fn pinned(mut self, pinned: bool) -> Self {
+        self.0.is_pinned = pinned;
+        self
+    }
+
+    pub fn created_at(mut self, rfc3339: &str) -> Self {
+        self.0.created_at = timestamp(rfc3339);
+        self
+    }
+
+    pub fn updated_at(mut self, rfc3339: &str) -> Self {
+        self.0.updated_at = timestamp(rfc3339);
+        self
+    }
+
+    pub fn last_used_at(mut self, rfc3339: Option<&str>) -> Self {
+        self.0.last_used_at = rfc3339.map(timestamp);
+        self
+    }
+
+    pub fn archived_at(mut self, rfc3339: &str) -> Self {
+        self.0.archived_at = Some(timestamp(rfc3339));
+        self
+    }
+
+    pub fn cost(mut self, cost: Option<costs::KeyCost>) -> Self {
+        self.0.cost = cost;
+        self
+    }
+
+    pub fn rotation_interval_days(mut self, days: Option<u32>) -> Self {
+        self.0.rotation_interval_days = days;
+        self
+    }
+
+    pub fn build(self) -> ApiKey {
+        self.0
+    }
+}
+
+fn sample_key(id: &str) -> ApiKey {
+    test_key(id)
+        .name("STRIPE_SECRET_KEY")
+        .secret("sk_test_4eC39HqLyjWDarjtT1zdp7dc")
+        .tags(&["payments"])
+        .build()
+}
+
+/// An unlocked vault with a master password, as `set_master_password` leaves it
+fn encrypted_vault() -> ApiKeyVault {
+    let key = derive_key_from_password(PASSWORD, &SALT);
+    let mut vault = ApiKeyVault {
+        master_password_hash: Some(bcrypt::hash(PASSWORD, 4).unwrap()),
+        encryption_key: Some(general_purpose::STANDARD.encode(*key)),
+        salt: Some(general_purpose::STANDARD.encode(SALT)),
+        ..ApiKeyVault::default()
+    };
+    vault.keys.insert("k1".to_string(), sample_key("k1"));
+    vault
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn encrypt_round_trips(data in any::<String>(), key in any::<[u8; 32]>()) {
+        let encrypted = encrypt_data(&data, &key).unwrap();
+        prop_assert_eq!(decrypt_data(&encrypted, &key).unwrap(), data);
+    }
+
+    #[test]
+    fn wrong_key_is_rejected(
+        data in any::<String>(),
+        key in any::<[u8; 32]>(),
+        other in any::<[u8; 32]>(),
+    ) {
+        prop_assume!(key != other);
+        let encrypted = encrypt_data(&data, &key).unwrap();
+        prop_assert!(decrypt_data(&encrypted, &other).is_err());
+    }
+
+    #[test]
+    fn tampered_ciphertext_is_rejected(
+        data in any::<String>(),
+        key in any::<[u8; 32]>(),
+        index in any::<prop::sample::Index>(),
+        flip in 1u8..,
+    ) {
+        let encrypted = encrypt_data(&data, &key).unwrap();
+        let mut bytes = general_purpose::STANDARD.decode(encrypted).unwrap();
+        let at = index.index(bytes.len());
+        bytes[at] ^= flip;
+        let tampered = general_purpose::STANDARD.encode(bytes);
+        prop_assert!(decrypt_data(&tampered, &key).is_err());
+    }
+
+    #[test]
+    fn arbitrary_input_never_panics(
+        data in ".*",
+        key in prop::collection::vec(any::<u8>(), 0..48),
+    ) {
+        let _ = decrypt_data(&data, &key);
+        let _ = encrypt_data(&data, &key);
+    }
+}
+
+#[test]
+fn derived_key_depends_on_password_and_salt() {
+    let key = derive_key_from_password(PASSWORD, &SALT);
+    assert_eq!(*key, *derive_key_from_password(PASSWORD, &SALT));
+    assert_ne!(*key, *derive_key_from_password(PASSWORD, &[8; 16]));
+    assert_ne!(*key, *derive_key_from_password("correct horse", &SALT));
+}
+
+#[test]
+fn plaintext_vault_round_trips() {
+    let storage = MemoryStorage::default();
+    let mut vault = ApiKeyVault::default();
+    vault.keys.insert("k1".to_string(), sample_key("k1"));
+
+    save_vault_to(&storage, &vault).unwrap();
+    assert!(storage.read_metadata().unwrap().is_none());
+
+    let loaded = load_vault_from(&storage).unwrap();
+    assert_eq!(loaded.keys["k1"].key, vault.keys["k1"].key);
+    assert!(storage.backups.lock().unwrap().is_empty());
+}
+
+#[test]
+fn encrypted_vault_round_trips() {
+    let storage = MemoryStorage::default();
+    let vault = encrypted_vault();
+    save_vault_to(&storage, &vault).unwrap();
+
+    let contents = storage.read_vault().unwrap().unwrap();
+    assert!(!is_plaintext_vault(&contents));
+    assert!(!contents.contains("sk_test_"));
+
+    // Without the password only the metadata is readable
+    let locked = load_vault_from(&storage).unwrap();
+    assert_eq!(locked.encryption_key.as_deref(), Some("[ENCRYPTED]"));
+    assert_eq!(locked.keys["k1"].key, "[ENCRYPTED]");
+    assert_eq!(locked.keys["k1"].name, "STRIPE_SECRET_KEY");
+
+    let unlocked = decrypt_vault_from(&storage, PASSWORD).unwrap();
+    assert_eq!(unlocked.keys["k1"].key, vault.keys["k1"].key);
+    assert!(decrypt_vault_from(&storage, "wrong password").is_err());
+}
+
+#[test]
+fn keys_saved_before_newer_fields_still_load() {
+    // Only the fields the first vault format had
+    let legacy: ApiKey = serde_json::from_value(serde_json::json!({
+        "id": "k1", "name": "k1", "service": "Stripe", "key": "sk",
+        "description": null, "environment": "development", "scopes": [],
+        "created_at": "2024-01-01T00:00:00Z", "updated_at": "2024-01-01T00:00:00Z",
+        "tags": [], "is_active": true, "source_type": null, "env_file_path": null,
+        "project_path": null, "env_file_name": null
+    }))
+    .unwrap();
+    assert_eq!(
+        serde_json::to_value(&legacy).unwrap(),
+        serde_json::to_value(test_key("k1").build()).unwrap()
+    );
+}
+
+#[test]
+fn unusual_keys_survive_an_encrypted_round_trip() {
+    let storage = MemoryStorage::default();
+    let mut vault = encrypted_vault();
+    let key = test_key("k2")
+        .name("Clé d'API «prod» 🔑")
+        .service("")
+        .secret("-----BEGIN KEY-----\n\"quoted\" \\ [ENCRYPTED]\n-----END KEY-----")
+        .environment("production")
+        .value("staging", "")
+        .value("development", "sk_test_ünïcode")
+        .project(Some("/work/my shop"))
+        .tags(&["", "Prod", "prod"])
+        .pinned(true)
+        .updated_at("2024-02-29T23:59:59.999Z")
+        .last_used_at(Some("2024-03-01T00:00:00+02:00"))
+        .archived_at("2024-03-01T00:00:00Z")
+        .build();
+    vault.keys.insert(key.id.clone(), key.clone());
+    save_vault_to(&storage, &vault).unwrap();
+
+    let contents = storage.read_vault().unwrap().unwrap();
+    let metadata = storage.read_metadata().unwrap().unwrap();
+    for leaked in ["BEGIN KEY", "sk_test_ünïcode"] {
+        assert!(!contents.contains(leaked) && !metadata.contains(leaked));
+    }
+
+    let locked = load_vault_from(&storage).unwrap();
+    let locked_key = &locked.keys["k2"];
+    assert_eq!(locked_key.name, key.name);
+    assert_eq!(locked_key.key, "[ENCRYPTED]");
+    assert!(locked_key.values.values().all(|v| v == "[ENCRYPTED]"));
+    assert!(locked_key.is_pinned && locked_key.archived_at == key.archived_at);
+
+    let unlocked = decrypt_vault_from(&storage, PASSWORD).unwrap();
+    assert_eq!(
+        serde_json::to_value(&unlocked.keys["k2"]).unwrap(),
+        serde_json::to_value(&key).unwrap()
+    );
+}
+
+#[test]
+fn metadata_leaves_remote_secret_hashes_out() {
+    let storage = MemoryStorage::default();
+    let mut vault = encrypted_vault();
+    let value_hash = secret_manager_export::hash_secret_value(&vault.keys["k1"].key);
+    vault.keys.get_mut("k1").unwrap().remote_secrets =
+        vec![secret_manager_export::RemoteSecretRef {
+            provider: "aws_secrets_manager".to_string(),
+            remote_id: "arn:aws:secretsmanager:eu-west-1:1:secret:stripe".to_string(),
+            remote_name: "stripe".to_string(),
+            pushed_at: Utc::now(),
+            value_hash: value_hash.clone(),
+        }];
+    save_vault_to(&storage, &vault).unwrap();
+
+    let metadata = storage.read_metadata().unwrap().unwrap();
+    assert!(!metadata.contains(&value_hash));
+    assert!(load_vault_from(&storage).unwrap().keys["k1"]
+        .remote_secrets
+        .is_empty());
+    let unlocked = decrypt_vault_from(&storage, PASSWORD).unwrap();
+    assert_eq!(unlocked.keys["k1"].remote_secrets[0].value_hash, value_hash);
+}
+
+#[test]
+fn wipe_clears_environment_values() {
+    let mut vault = encrypted_vault();
+    let key = vault.keys.get_mut("k1").unwrap();
+    key.values
+        .insert("production".to_string(), "sk_live_prod".to_string());
+    key.values
+        .insert("staging".to_string(), "sk_test_staging".to_string());
+
+    secure_memory::wipe_vault_secrets(&mut vault);
+
+    let key = &vault.keys["k1"];
+    assert!(key.key.is_empty());
+    assert!(key.values.values().all(String::is_empty));
+}
+
+#[test]
+fn wipe_clears_credential_passphrases() {
+    let mut vault = encrypted_vault();
+    vault.keys.get_mut("k1").unwrap().credential = Some(credentials::CredentialParts {
+        public_part: "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIJ1 dev@laptop".to_string(),
+        passphrase: Some("hunter2".to_string()),
+        fingerprint: "SHA256:abc".to_string(),
+        algorithm: Some("ssh-ed25519".to_string()),
+        comment: None,
+        subject: None,
+        issuer: None,
+        not_before: None,
+    });
+
+    secure_memory::wipe_vault_secrets(&mut vault);
+
+    let credential = vault.keys["k1"].credential.as_ref().unwrap();
+    assert_eq!(credential.passphrase.as_deref(), Some(""));
+}
+
+#[test]
+fn failed_unlocks_reach_webhooks_while_locked() {
+    let storage = MemoryStorage::default();
+    let mut vault = encrypted_vault();
+    for (id, event) in [
+        ("alerts", webhooks::WebhookEvent::AnomalyDetected),
+        ("ci", webhooks::WebhookEvent::KeyCreated),
+    ] {
+        vault.webhooks.push(webhooks::WebhookEndpoint {
+            id: id.to_string(),
+            url: format!("https://hooks.example.com/{}", id),
+            description: None,
+            events: vec![event],
+            secret: "5ec2e7".to_string(),
+            enabled: true,
+            created_at: Utc::now(),
+        });
+    }
+    save_vault_to(&storage, &vault).unwrap();
+
+    // Each attempt starts from the metadata, as after a restart
+    let start = Utc::now();
+    let mut anomalies = Vec::new();
+    for attempt in 0..3 {
+        let mut locked = load_vault_from(&storage).unwrap();
+        let ids: Vec<_> = locked.webhooks.iter().map(|w| w.id.as_str()).collect();
+        assert_eq!(ids, ["alerts"]);
+        let at = start + chrono::Duration::seconds(attempt);
+        anomalies.push(webhooks::events_for_audit(
+            &mut locked,
+            "unlock_vault",
+            false,
+            at,
+        ));
+        webhooks::persist_failed_unlocks(&storage, &locked.failed_unlocks).unwrap();
+    }
+    assert!(anomalies[0].is_empty() && anomalies[1].is_empty());
+    assert_eq!(anomalies[2], vec![webhooks::WebhookEvent::AnomalyDetected]);
+
+    // Unlocking still works and starts the count over
+    let unlocked = decrypt_vault_from(&storage, PASSWORD).unwrap();
+    assert_eq!(unlocked.webhooks.len(), 2);
+    save_vault_to(&storage, &unlocked).unwrap();
+    assert!(load_vault_from(&storage).unwrap().failed_unlocks.is_empty());
+}
+
+#[test]
+fn notification_preferences_are_readable_while_locked() {
+    let storage = MemoryStorage::default();
+    let mut vault = encrypted_vault();
+    let mut account: UserAccount = serde_json::from_value(serde_json::json!({
+        "id": "user_1", "email": "dev@example.com", "username": "dev",
+        "password_hash": "", "created_at": "2024-01-01T00:00:00Z",
+        "updated_at": "2024-01-01T00:00:00Z", "verified": true,
+        "recovery_codes": [], "two_factor_enabled": false, "backup_email": null,
+        "biometric_enabled": false, "passkey_credentials": [],
+        "session_timeout": 60, "login_attempts": 0,
+        "preferences": {
+            "theme": "system", "language": "en", "auto_lock_timeout": 15,
+            "clipboard_clear_timeout": 30, "show_notifications": true,
+            "audit_logging": true, "biometric_unlock": false,
+            "auto_backup": true, "encryption_level": "enhanced"
+        }
+    }))
+    .unwrap();
+    account.preferences.notifications.failed_unlock = false;
+    vault.user_account = Some(account);
+    save_vault_to(&storage, &vault).unwrap();
+
+    let locked = load_vault_from(&storage).unwrap();
+    assert!(locked.user_account.is_none());
+    let preferences = locked.locked_notifications.unwrap();
+    assert!(preferences.enabled);
+    assert!(!preferences.settings.failed_unlock);
+    assert!(preferences.settings.sync_errors);
+}
+
+#[test]
+fn metadata_only_vault_is_never_written() {
+    let storage = MemoryStorage::default();
+    save_vault_to(&storage, &encrypted_vault()).unwrap();
+    let before = storage.read_vault().unwrap();
+
+    let locked = load_vault_from(&storage).unwrap();
+    save_vault_to(&storage, &locked).unwrap();
+    assert_eq!(storage.read_vault().unwrap(), before);
+}
+
+#[test]
+fn truncated_ciphertext_fails_to_decrypt() {
+    let storage = MemoryStorage::default();
+    save_vault_to(&storage, &encrypted_vault()).unwrap();
+    let contents = storage.read_vault().unwrap().unwrap();
+    storage
+        .write_vault(&contents[..contents.len() / 2])
+        .unwrap();
+
+    assert!(decrypt_vault_from(&storage, PASSWORD).is_err());
+}
+
+#[test]
+fn corrupt_plaintext_vault_is_an_error() {
+    let storage = MemoryStorage::default();
+    storage.write_vault("{\"keys\": {").unwrap();
+    assert!(load_vault_from(&storage).is_err());
+}
+
+#[test]
+fn corrupt_metadata_keeps_vault_locked() {
+    let storage = MemoryStorage::default();
+    save_vault_to(&storage, &encrypted_vault()).unwrap();
+    let before = storage.read_vault().unwrap();
+    storage.write_metadata("not json").unwrap();
+
+    let loaded = load_vault_from(&storage).unwrap();
+    assert_eq!(loaded.encryption_key.as_deref(), Some("[ENCRYPTED]"));
+    assert!(decrypt_vault_from(&storage, PASSWORD).is_err());
+
+    // Saving what was loaded must not replace the encrypted file
+    save_vault_to(&storage, &loaded).unwrap();
+    assert_eq!(storage.read_vault().unwrap(), before);
+}
+
+#[test]
+fn legacy_schema_is_migrated_with_backup() {
+    let storage = MemoryStorage::default();
+    let mut document = serde_json::to_value(ApiKeyVault::default()).unwrap();
+    document.as_object_mut().unwrap().remove("schema_version");
+    storage.write_vault(&document.to_string()).unwrap();
+
+    load_vault_from(&storage).unwrap();
+    assert_eq!(
+        *storage.backups.lock().unwrap(),
+        vec![migrations::legacy_schema_version()]
+    );
+}
+
+#[test]
+fn legacy_plaintext_password_is_accepted_for_migration() {
+    let hashed = bcrypt::hash(PASSWORD, 4).unwrap();
+    assert_eq!(
+        check_master_password(&hashed, PASSWORD),
+        PasswordCheck::Valid
+    );
+    assert_eq!(
+        check_master_password(&hashed, "wrong"),
+        PasswordCheck::Invalid
+    );
+    assert_eq!(
+        check_master_password(PASSWORD, PASSWORD),
+        PasswordCheck::LegacyPlaintext
+    );
+    assert_eq!(
+        check_master_password(PASSWORD, "wrong"),
+        PasswordCheck::Invalid
+    );
+}
+
+#[test]
+fn removed_keys_leave_no_usage_or_costs_behind() {
+    let mut vault = ApiKeyVault::default();
+    let now = Utc::now();
+    for id in ["gone", "kept"] {
+        vault.keys.insert(id.to_string(), test_key(id).build());
+        quotas::consume(&mut vault, id, &quotas::UsageMetadata::default(), now);
+        let cost = costs::CostEntryInput {
+            key_id: id.to_string(),
+            period: None,
+            amount: 12.5,
+            currency: None,
+            note: None,
+        };
+        costs::record(&mut vault, cost, costs::CostSource::Manual, now).unwrap();
+    }
+
+    let removed = remove_key(&mut vault, "gone", activity::Actor::ui()).unwrap();
+    assert_eq!(removed.id, "gone");
+    assert!(remove_key(&mut vault, "gone", activity::Actor::ui()).is_none());
+
+    assert_eq!(vault.keys.keys().collect::<Vec<_>>(), ["kept"]);
+    assert_eq!(vault.quota_usage.keys().collect::<Vec<_>>(), ["kept"]);
+    assert!(vault
+        .cost_entries
+        .iter()
+        .all(|entry| entry.key_id == "kept"));
+    assert_eq!(vault.cost_entries.len(), 1);
+    assert!(vault
+        .recent_activity
+        .iter()
+        .any(|a| a.activity_type == "key_deleted" && a.key_id == "gone"));
+    assert!(vault
+        .audit_logs
+        .iter()
+        .any(|log| log.action == "delete_api_key" && log.resource_id.as_deref() == Some("gone")));
+}
@@ -0,0 +1,555 @@
+use crate::errors::KeyKeeperError;
+use crate::vault_storage::VaultStorage;
+use crate::{
+    get_utc_timestamp, guards, log_audit_event, save_vault, ApiKeyVault, AppState, VaultMetadata,
+};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::State;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const MAX_ATTEMPTS: u32 = 5;
+const INITIAL_BACKOFF_SECS: u64 = 2;
+const MAX_DELIVERY_LOG: usize = 500;
+/// Failed unlocks within this window that trigger `anomaly_detected`
+const FAILED_UNLOCK_THRESHOLD: usize = 3;
+const FAILED_UNLOCK_WINDOW_MINUTES: i64 = 15;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    KeyCreated,
+    KeyRotated,
+    VaultUnlocked,
+    AnomalyDetected,
+    /// Sent by `test_webhook`, delivered regardless of the endpoint filter
+    Ping,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::KeyCreated => "key_created",
+            WebhookEvent::KeyRotated => "key_rotated",
+            WebhookEvent::VaultUnlocked => "vault_unlocked",
+            WebhookEvent::AnomalyDetected => "anomaly_detected",
+            WebhookEvent::Ping => "ping",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEndpoint {
+    pub id: String,
+    pub url: String,
+    pub description: Option<String>,
+    pub events: Vec<WebhookEvent>,
+    /// HMAC-SHA256 signing secret, hex encoded
+    pub secret: String,
+    pub enabled: bool,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl WebhookEndpoint {
+    fn accepts(&self, event: &WebhookEvent) -> bool {
+        self.enabled && (*event == WebhookEvent::Ping || self.events.contains(event))
+    }
+
+    /// Copy of the endpoint safe to hand to the frontend
+    fn redacted(&self) -> Self {
+        let mut endpoint = self.clone();
+        endpoint.secret = format!("{}…", &self.secret[..8.min(self.secret.len())]);
+        endpoint
+    }
+}
+
+/// One delivery attempt of an event to an endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub endpoint_id: String,
+    pub event: WebhookEvent,
+    pub attempt: u32,
+    #[serde(with = "crate::timestamps::rfc3339")]
+    pub timestamp: DateTime<Utc>,
+    pub success: bool,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    pub id: String,
+    pub event: WebhookEvent,
+    pub timestamp: String,
+    pub data: serde_json::Value,
+}
+
+fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// `sha256=<hex>` signature of `body`, sent as `X-KeyKeeper-Signature`
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    let digest: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("sha256={}", digest)
+}
+
+fn validate_url(url: &str) -> Result<(), KeyKeeperError> {
+    let parsed = url::Url::parse(url)
+        .map_err(|e| KeyKeeperError::Validation(format!("Invalid webhook URL: {}", e)))?;
+    match parsed.scheme() {
+        "https" => Ok(()),
+        "http" if matches!(parsed.host_str(), Some("localhost") | Some("127.0.0.1")) => Ok(()),
+        _ => Err(KeyKeeperError::Validation(
+            "Webhook URLs must use https (http is allowed for localhost only)".to_string(),
+        )),
+    }
+}
+
+/// Map an audit event to the webhook events it should emit, counting failed
+/// unlocks in `vault.failed_unlocks`. Called from `log_audit_event` with the
+/// vault already locked.
+pub fn events_for_audit(
+    vault: &mut ApiKeyVault,
+    action: &str,
+    success: bool,
+    now: DateTime<Utc>,
+) -> Vec<WebhookEvent> {
+    let mut events = Vec::new();
+    match (action, success) {
+        ("add_api_key", true) => events.push(WebhookEvent::KeyCreated),
+        ("rotate_api_key", true) => events.push(WebhookEvent::KeyRotated),
+        ("unlock_vault", true) => events.push(WebhookEvent::VaultUnlocked),
+        ("unlock_vault", false) => {
+            let cutoff = now - ChronoDuration::minutes(FAILED_UNLOCK_WINDOW_MINUTES);
+            vault.failed_unlocks.retain(|at| *at >= cutoff);
+            vault.failed_unlocks.push(now);
+            let failures = vault.failed_unlocks.len();
+            if failures.is_multiple_of(FAILED_UNLOCK_THRESHOLD) {
+                events.push(WebhookEvent::AnomalyDetected);
+            }
+        }
+        _ => {}
+    }
+    events
+}
+
+/// Endpoints kept in the metadata, so failed unlocks reach them while the
+/// vault is locked. Their signing secrets are readable with that file.
+pub fn locked_endpoints(endpoints: &[WebhookEndpoint]) -> Vec<WebhookEndpoint> {
+    endpoints
+        .iter()
+        .filter(|endpoint| endpoint.accepts(&WebhookEvent::AnomalyDetected))
+        .cloned()
+        .collect()
+}
+
+/// Write the failed unlocks into the metadata of a locked vault, the one
+/// document that can be written without the password
+pub fn persist_failed_unlocks(
+    storage: &dyn VaultStorage,
+    failed_unlocks: &[DateTime<Utc>],
+) -> Result<(), String> {
+    let Some(contents) = storage.read_metadata()? else {
+        return Ok(());
+    };
+    let mut metadata: VaultMetadata =
+        serde_json::from_str(&contents).map_err(|e| format!("Failed to read metadata: {}", e))?;
+    metadata.failed_unlocks = failed_unlocks.to_vec();
+    let contents = serde_json::to_string_pretty(&metadata)
+        .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
+    storage.write_metadata(&contents)
+}
+
+/// Deliver `event` to every matching endpoint in the background
+pub async fn dispatch(
+    vault: Arc<Mutex<ApiKeyVault>>,
+    event: WebhookEvent,
+    data: serde_json::Value,
+) {
+    let endpoints: Vec<WebhookEndpoint> = {
+        let vault_guard = vault.lock().await;
+        vault_guard
+            .webhooks
+            .iter()
+            .filter(|endpoint| endpoint.accepts(&event))
+            .cloned()
+            .collect()
+    };
+    if endpoints.is_empty() {
+        return;
+    }
+
+    let payload = WebhookPayload {
+        id: uuid::Uuid::new_v4().to_string(),
+        event,
+        timestamp: get_utc_timestamp(),
+        data,
+    };
+
+    for endpoint in endpoints {
+        let vault = vault.clone();
+        let payload = payload.clone();
+        tokio::spawn(async move {
+            deliver_with_retry(vault, endpoint, payload).await;
+        });
+    }
+}
+
+async fn deliver_with_retry(
+    vault: Arc<Mutex<ApiKeyVault>>,
+    endpoint: WebhookEndpoint,
+    payload: WebhookPayload,
+) -> bool {
+    let body = match serde_json::to_vec(&payload) {
+        Ok(body) => body,
+        Err(e) => {
+            warn!("Failed to serialize webhook payload: {}", e);
+            return false;
+        }
+    };
+    let signature = sign_payload(&endpoint.secret, &body);
+    let client = reqwest::Client::builder()
+        .user_agent("KeyKeeper-Webhooks/1.0")
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap();
+
+    let mut backoff = Duration::from_secs(INITIAL_BACKOFF_SECS);
+    for attempt in 1..=MAX_ATTEMPTS {
+        let started = std::time::Instant::now();
+        let result = client
+            .post(&endpoint.url)
+            .header("Content-Type", "application/json")
+            .header("X-KeyKeeper-Event", payload.event.as_str())
+            .header("X-KeyKeeper-Delivery", &payload.id)
+            .header("X-KeyKeeper-Signature", &signature)
+            .body(body.clone())
+            .send()
+            .await;
+
+        let (success, status_code, error) = match result {
+            Ok(response) => {
+                let status = response.status();
+                let error = (!status.is_success()).then(|| format!("HTTP {}", status));
+                (status.is_success(), Some(status.as_u16()), error)
+            }
+            Err(e) => (false, None, Some(e.to_string())),
+        };
+
+        record_delivery(
+            &vault,
+            WebhookDelivery {
+                id: uuid::Uuid::new_v4().to_string(),
+                endpoint_id: endpoint.id.clone(),
+                event: payload.event.clone(),
+                attempt,
+                timestamp: Utc::now(),
+                success,
+                status_code,
+                error: error.clone(),
+                duration_ms: started.elapsed().as_millis() as u64,
+            },
+        )
+        .await;
+
+        if success {
+            info!(
+                "Delivered {} webhook to {}",
+                payload.event.as_str(),
+                endpoint.url
+            );
+            return true;
+        }
+        // Client errors other than 429 won't succeed on retry
+        if matches!(status_code, Some(code) if (400..500).contains(&code) && code != 429) {
+            break;
+        }
+        if attempt < MAX_ATTEMPTS {
+            warn!(
+                "Webhook delivery to {} failed (attempt {}): {:?}, retrying in {:?}",
+                endpoint.url, attempt, error, backoff
+            );
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+
+    warn!(
+        "Giving up on {} webhook to {}",
+        payload.event.as_str(),
+        endpoint.url
+    );
+    false
+}
+
+async fn record_delivery(vault: &Arc<Mutex<ApiKeyVault>>, delivery: WebhookDelivery) {
+    let mut vault_guard = vault.lock().await;
+    vault_guard.webhook_deliveries.push(delivery);
+    if vault_guard.webhook_deliveries.len() > MAX_DELIVERY_LOG {
+        let excess = vault_guard.webhook_deliveries.len() - MAX_DELIVERY_LOG;
+        vault_guard.webhook_deliveries.drain(..excess);
+    }
+}
+
+/// Register a webhook endpoint. The returned endpoint includes the signing secret;
+/// later listings only show a prefix of it.
+#[tauri::command]
+pub async fn create_webhook(
+    url: String,
+    events: Vec<WebhookEvent>,
+    description: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<WebhookEndpoint, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    validate_url(&url)?;
+    if events.is_empty() {
+        return Err(KeyKeeperError::Validation(
+            "At least one event is required".to_string(),
+        ));
+    }
+
+    let endpoint = WebhookEndpoint {
+        id: uuid::Uuid::new_v4().to_string(),
+        url,
+        description,
+        events,
+        secret: generate_secret(),
+        enabled: true,
+        created_at: Utc::now(),
+    };
+
+    let mut vault_guard = state.vault.lock().await;
+    vault_guard.webhooks.push(endpoint.clone());
+    drop(vault_guard);
+    save_vault(&state).await?;
+
+    log_audit_event(
+        &state,
+        "create_webhook",
+        "webhook",
+        Some(&endpoint.id),
+        true,
+        None,
+    )
+    .await;
+    Ok(endpoint)
+}
+
+#[tauri::command]
+pub async fn get_webhooks(
+    state: State<'_, AppState>,
+) -> Result<Vec<WebhookEndpoint>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    Ok(vault_guard.webhooks.iter().map(|w| w.redacted()).collect())
+}
+
+#[tauri::command]
+pub async fn update_webhook(
+    id: String,
+    url: Option<String>,
+    events: Option<Vec<WebhookEvent>>,
+    description: Option<String>,
+    enabled: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<WebhookEndpoint, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+    if let Some(url) = &url {
+        validate_url(url)?;
+    }
+
+    let mut vault_guard = state.vault.lock().await;
+    let endpoint = vault_guard
+        .webhooks
+        .iter_mut()
+        .find(|w| w.id == id)
+        .ok_or_else(|| KeyKeeperError::not_found("Webhook"))?;
+    if let Some(url) = url {
+        endpoint.url = url;
+    }
+    if let Some(events) = events {
+        endpoint.events = events;
+    }
+    if description.is_some() {
+        endpoint.description = description;
+    }
+    if let Some(enabled) = enabled {
+        endpoint.enabled = enabled;
+    }
+    let updated = endpoint.redacted();
+    drop(vault_guard);
+    save_vault(&state).await?;
+
+    log_audit_event(&state, "update_webhook", "webhook", Some(&id), true, None).await;
+    Ok(updated)
+}
+
+#[tauri::command]
+pub async fn delete_webhook(id: String, state: State<'_, AppState>) -> Result<(), KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let mut vault_guard = state.vault.lock().await;
+    let before = vault_guard.webhooks.len();
+    vault_guard.webhooks.retain(|w| w.id != id);
+    if vault_guard.webhooks.len() == before {
+        return Err(KeyKeeperError::not_found("Webhook"));
+    }
+    vault_guard
+        .webhook_deliveries
+        .retain(|d| d.endpoint_id != id);
+    drop(vault_guard);
+    save_vault(&state).await?;
+
+    log_audit_event(&state, "delete_webhook", "webhook", Some(&id), true, None).await;
+    Ok(())
+}
+
+/// Send a `ping` event to one endpoint and wait for the outcome
+#[tauri::command]
+pub async fn test_webhook(id: String, state: State<'_, AppState>) -> Result<bool, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let endpoint = {
+        let vault_guard = state.vault.lock().await;
+        vault_guard
+            .webhooks
+            .iter()
+            .find(|w| w.id == id)
+            .cloned()
+            .ok_or_else(|| KeyKeeperError::not_found("Webhook"))?
+    };
+    let payload = WebhookPayload {
+        id: uuid::Uuid::new_v4().to_string(),
+        event: WebhookEvent::Ping,
+        timestamp: get_utc_timestamp(),
+        data: serde_json::json!({ "message": "KeyKeeper webhook test" }),
+    };
+    Ok(deliver_with_retry(state.vault.clone(), endpoint, payload).await)
+}
+
+/// Delivery log, newest first
+#[tauri::command]
+pub async fn get_webhook_deliveries(
+    endpoint_id: Option<String>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<WebhookDelivery>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    let vault_guard = state.vault.lock().await;
+    Ok(vault_guard
+        .webhook_deliveries
+        .iter()
+        .rev()
+        .filter(|d| endpoint_id.as_ref().is_none_or(|id| &d.endpoint_id == id))
+        .take(limit.unwrap_or(100))
+        .cloned()
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(id: &str, events: Vec<WebhookEvent>, enabled: bool) -> WebhookEndpoint {
+        WebhookEndpoint {
+            id: id.to_string(),
+            url: format!("https://hooks.example.com/{}", id),
+            description: None,
+            events,
+            secret: generate_secret(),
+            enabled,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn every_third_failed_unlock_in_the_window_is_an_anomaly() {
+        let mut vault = ApiKeyVault::default();
+        let start = Utc::now();
+        let mut fail = |minutes: i64| {
+            events_for_audit(
+                &mut vault,
+                "unlock_vault",
+                false,
+                start + ChronoDuration::minutes(minutes),
+            )
+        };
+        assert!(fail(0).is_empty());
+        assert!(fail(1).is_empty());
+        // The first two have left the window by now
+        assert!(fail(30).is_empty());
+        assert!(fail(31).is_empty());
+        assert_eq!(fail(32), vec![WebhookEvent::AnomalyDetected]);
+        assert!(fail(33).is_empty());
+
+        let events = events_for_audit(&mut vault, "unlock_vault", true, start);
+        assert_eq!(events, vec![WebhookEvent::VaultUnlocked]);
+        assert_eq!(vault.failed_unlocks.len(), 4);
+        assert!(events_for_audit(&mut vault, "get_api_keys", false, start).is_empty());
+    }
+
+    #[test]
+    fn only_enabled_anomaly_endpoints_are_kept_for_the_locked_vault() {
+        let endpoints = vec![
+            endpoint("alerts", vec![WebhookEvent::AnomalyDetected], true),
+            endpoint("paused", vec![WebhookEvent::AnomalyDetected], false),
+            endpoint("ci", vec![WebhookEvent::KeyCreated], true),
+        ];
+        let kept: Vec<_> = locked_endpoints(&endpoints)
+            .into_iter()
+            .map(|endpoint| endpoint.id)
+            .collect();
+        assert_eq!(kept, vec!["alerts"]);
+    }
+
+    #[test]
+    fn payloads_are_signed_with_hmac_sha256() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            sign_payload("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn webhook_urls_must_use_https_except_on_localhost() {
+        assert!(validate_url("https://hooks.example.com/keykeeper").is_ok());
+        assert!(validate_url("http://localhost:8080/hook").is_ok());
+        assert!(validate_url("http://127.0.0.1/hook").is_ok());
+        for url in [
+            "http://hooks.example.com/keykeeper",
+            "ftp://localhost/hook",
+            "not a url",
+        ] {
+            assert!(
+                matches!(validate_url(url), Err(KeyKeeperError::Validation(_))),
+                "{}",
+                url
+            );
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! The app's windows besides confirmations.
+//!
+//! Besides the main window there are two small ones, each opened at most
+//! once: the quick palette, a frameless search over the keys that hides when
+//! it loses focus, and the approvals window, where requests for secrets from
+//! external clients are answered. Commands open, focus and close them by
+//! kind rather than by label.
+//!
+//! Their position and size are kept by the window-state plugin, restricted
+//! to these windows so confirmation windows, which get a new label each
+//! time, aren't recorded. The plugin only writes its file on exit, so the
+//! state is also saved whenever one of them closes or hides.
+
+use crate::errors::KeyKeeperError;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder, WindowEvent};
+use tauri_plugin_window_state::{AppHandleExt, StateFlags};
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowKind {
+    Main,
+    Palette,
+    Approvals,
+}
+
+struct WindowSpec {
+    label: &'static str,
+    /// Page of the frontend
+    route: &'static str,
+    title: &'static str,
+    size: (f64, f64),
+    decorations: bool,
+    always_on_top: bool,
+}
+
+impl WindowKind {
+    const ALL: [WindowKind; 3] = [WindowKind::Main, WindowKind::Palette, WindowKind::Approvals];
+
+    fn spec(self) -> WindowSpec {
+        match self {
+            WindowKind::Main => WindowSpec {
+                label: "main",
+                route: "index.html",
+                title: "KeyKeeper",
+                size: (1000.0, 700.0),
+                decorations: true,
+                always_on_top: false,
+            },
+            WindowKind::Palette => WindowSpec {
+                label: "palette",
+                route: "palette",
+                title: "Quick Palette - KeyKeeper",
+                size: (640.0, 380.0),
+                decorations: false,
+                always_on_top: true,
+            },
+            WindowKind::Approvals => WindowSpec {
+                label: "approvals",
+                route: "approvals",
+                title: "Approve access - KeyKeeper",
+                size: (520.0, 480.0),
+                decorations: true,
+                always_on_top: true,
+            },
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        self.spec().label
+    }
+
+    pub fn from_label(label: &str) -> Option<WindowKind> {
+        WindowKind::ALL
+            .into_iter()
+            .find(|kind| kind.label() == label)
+    }
+
+    /// Hidden instead of closed, so it opens again instantly
+    fn hides_on_close(self) -> bool {
+        matches!(self, WindowKind::Main | WindowKind::Palette)
+    }
+}
+
+/// Whether the window-state plugin keeps the geometry of `label`
+pub fn persists_geometry(label: &str) -> bool {
+    WindowKind::from_label(label).is_some()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowInfo {
+    pub kind: WindowKind,
+    pub label: String,
+    pub visible: bool,
+    pub focused: bool,
+}
+
+fn save_geometry(app: &AppHandle) {
+    if let Err(e) = app.save_window_state(StateFlags::all()) {
+        warn!("Failed to save window state: {}", e);
+    }
+}
+
+fn build(app: &AppHandle, kind: WindowKind) -> tauri::Result<WebviewWindow> {
+    let spec = kind.spec();
+    let (width, height) = spec.size;
+    // The plugin moves it to its saved place once created
+    WebviewWindowBuilder::new(app, spec.label, WebviewUrl::App(spec.route.into()))
+        .title(spec.title)
+        .inner_size(width, height)
+        .decorations(spec.decorations)
+        .always_on_top(spec.always_on_top)
+        .skip_taskbar(kind == WindowKind::Palette)
+        .center()
+        .visible(false)
+        .build()
+}
+
+/// Show the window of `kind`, creating it if needed, and focus it
+pub fn open(app: &AppHandle, kind: WindowKind) -> Result<WebviewWindow, KeyKeeperError> {
+    let window = match app.get_webview_window(kind.label()) {
+        Some(window) => window,
+        None => build(app, kind).map_err(|e| format!("Failed to open the window: {}", e))?,
+    };
+    let _ = window.unminimize();
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())?;
+    Ok(window)
+}
+
+pub fn close(app: &AppHandle, kind: WindowKind) -> Result<(), KeyKeeperError> {
+    let Some(window) = app.get_webview_window(kind.label()) else {
+        return Ok(());
+    };
+    save_geometry(app);
+    if kind.hides_on_close() {
+        window.hide().map_err(|e| e.to_string())?;
+    } else {
+        window.close().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Bring the main window forward on a key, as a pinned key in the tray does
+pub fn open_key(app: &AppHandle, key_id: &str) -> Result<(), KeyKeeperError> {
+    let window = open(app, WindowKind::Main)?;
+    let detail = serde_json::to_string(key_id)?;
+    window
+        .eval(format!(
+            "window.dispatchEvent(new CustomEvent('tauri-open-key', {{ detail: {} }}))",
+            detail
+        ))
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Window events of the managed windows, before the app's own handling
+pub fn on_window_event(window: &tauri::Window, event: &WindowEvent) {
+    let Some(kind) = WindowKind::from_label(window.label()) else {
+        return;
+    };
+    match event {
+        WindowEvent::CloseRequested { api, .. } if kind == WindowKind::Palette => {
+            api.prevent_close();
+            save_geometry(window.app_handle());
+            let _ = window.hide();
+        }
+        WindowEvent::CloseRequested { .. } if kind == WindowKind::Approvals => {
+            save_geometry(window.app_handle());
+        }
+        WindowEvent::Focused(false) if kind == WindowKind::Palette => {
+            save_geometry(window.app_handle());
+            let _ = window.hide();
+        }
+        _ => {}
+    }
+}
+
+#[tauri::command]
+pub async fn open_window(app: AppHandle, kind: WindowKind) -> Result<(), KeyKeeperError> {
+    open(&app, kind).map(|_| ())
+}
+
+#[tauri::command]
+pub async fn close_window(app: AppHandle, kind: WindowKind) -> Result<(), KeyKeeperError> {
+    close(&app, kind)
+}
+
+/// Focus a window that is already open
+#[tauri::command]
+pub async fn focus_window(app: AppHandle, kind: WindowKind) -> Result<(), KeyKeeperError> {
+    let window = app
+        .get_webview_window(kind.label())
+        .ok_or_else(|| KeyKeeperError::not_found("Window"))?;
+    let _ = window.unminimize();
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_windows(app: AppHandle) -> Result<Vec<WindowInfo>, KeyKeeperError> {
+    Ok(WindowKind::ALL
+        .into_iter()
+        .filter_map(|kind| {
+            let window = app.get_webview_window(kind.label())?;
+            Some(WindowInfo {
+                kind,
+                label: kind.label().to_string(),
+                visible: window.is_visible().unwrap_or(false),
+                focused: window.is_focused().unwrap_or(false),
+            })
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn open_key_in_main_window(app: AppHandle, key_id: String) -> Result<(), KeyKeeperError> {
+    open_key(&app, &key_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_managed_windows_keep_their_geometry() {
+        for kind in WindowKind::ALL {
+            assert_eq!(WindowKind::from_label(kind.label()), Some(kind));
+            assert!(persists_geometry(kind.label()));
+        }
+        assert!(!persists_geometry("confirm-4f1c"));
+        assert_eq!(
+            serde_json::to_value(WindowKind::Palette).unwrap(),
+            "palette"
+        );
+    }
+}
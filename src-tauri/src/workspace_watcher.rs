@@ -0,0 +1,382 @@
+//! Editor workspace detection.
+//!
+//! The VSCode extension pushes its open folders, but without it the project
+//! status would never change. This watcher finds running VSCode-family
+//! editors by inspecting processes and reads their workspace storage to see
+//! which folders are open, refreshing `vscode_workspaces` and the status of
+//! env associations on a timer.
+
+use crate::errors::KeyKeeperError;
+use crate::{guards, save_vault, ApiKeyVault, AppState, VSCodeWorkspace};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager, State};
+use tracing::{debug, warn};
+
+const WATCH_INTERVAL_SECS: u64 = 30;
+/// A workspace whose storage was written this recently is considered open
+const RECENT_STORAGE_SECS: u64 = 15 * 60;
+/// Folders pushed by the extension are dropped once it stops refreshing them
+const PUSHED_TTL_MINUTES: i64 = 10;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceSource {
+    /// Pushed by the VSCode extension
+    #[default]
+    Extension,
+    /// Found by the native watcher
+    Detected,
+}
+
+struct Editor {
+    /// Directory name under the platform's config dir
+    data_dir: &'static str,
+    /// Executable names, without `.exe`
+    executables: &'static [&'static str],
+    /// macOS application bundle
+    app_bundle: &'static str,
+}
+
+const EDITORS: &[Editor] = &[
+    Editor {
+        data_dir: "Code",
+        executables: &["code"],
+        app_bundle: "visual studio code.app",
+    },
+    Editor {
+        data_dir: "Code - Insiders",
+        executables: &["code-insiders", "code - insiders"],
+        app_bundle: "visual studio code - insiders.app",
+    },
+    Editor {
+        data_dir: "VSCodium",
+        executables: &["codium", "vscodium"],
+        app_bundle: "vscodium.app",
+    },
+    Editor {
+        data_dir: "Cursor",
+        executables: &["cursor"],
+        app_bundle: "cursor.app",
+    },
+    Editor {
+        data_dir: "Windsurf",
+        executables: &["windsurf"],
+        app_bundle: "windsurf.app",
+    },
+];
+
+/// Lowercased command names of running processes
+fn process_names() -> Vec<String> {
+    #[cfg(target_os = "windows")]
+    let output = std::process::Command::new("tasklist")
+        .args(["/fo", "csv", "/nh"])
+        .output();
+    #[cfg(not(target_os = "windows"))]
+    let output = std::process::Command::new("ps")
+        .args(["-A", "-o", "comm="])
+        .output();
+
+    let Ok(output) = output else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        // tasklist rows are `"Code.exe","1234",...`
+        .filter_map(|line| line.split(',').next())
+        .map(|name| name.trim().trim_matches('"').to_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+fn is_running(editor: &Editor, processes: &[String]) -> bool {
+    processes.iter().any(|process| {
+        let basename = process.rsplit(['/', '\\']).next().unwrap_or(process);
+        let basename = basename.strip_suffix(".exe").unwrap_or(basename);
+        editor.executables.contains(&basename) || process.contains(editor.app_bundle)
+    })
+}
+
+/// Platform directory holding each editor's data dir
+pub(crate) fn config_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    }
+}
+
+/// Local path of a `file://` URI; remote and virtual workspaces are skipped
+fn path_from_uri(uri: &str) -> Option<String> {
+    let encoded = uri.strip_prefix("file://")?;
+    let decoded = urlencoding::decode(encoded).ok()?.into_owned();
+    // file:///c:/Users/... on Windows
+    let is_drive = decoded.len() > 2
+        && decoded.starts_with('/')
+        && decoded.as_bytes()[2] == b':'
+        && decoded.as_bytes()[1].is_ascii_alphabetic();
+    let path = if is_drive {
+        decoded[1..].replace('/', "\\")
+    } else {
+        decoded
+    };
+    Some(path.trim_end_matches(['/', '\\']).to_string())
+}
+
+/// Folder of a `folder` or `.code-workspace` entry
+fn folder_of(value: &serde_json::Value) -> Option<String> {
+    if let Some(folder) = value["folder"].as_str() {
+        return path_from_uri(folder);
+    }
+    let workspace = value["workspace"]
+        .as_str()
+        .or_else(|| value["workspaceIdentifier"]["configURIPath"].as_str())?;
+    let file = path_from_uri(workspace)?;
+    Path::new(&file)
+        .parent()
+        .map(|parent| parent.to_string_lossy().to_string())
+}
+
+fn modified_within(path: &Path, window: Duration, now: SystemTime) -> bool {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .is_ok_and(|modified| now.duration_since(modified).unwrap_or_default() <= window)
+}
+
+/// Folders open in the editor whose user data lives in `user_dir`: the
+/// windows listed in its global storage, plus workspaces whose storage was
+/// written recently
+pub fn open_folders(user_dir: &Path, now: SystemTime) -> Vec<String> {
+    let mut folders = Vec::new();
+
+    for storage in [
+        user_dir.join("globalStorage").join("storage.json"),
+        // Versions before 1.72 kept it next to the User dir
+        user_dir.with_file_name("storage.json"),
+    ] {
+        let Some(state) = fs::read_to_string(&storage)
+            .ok()
+            .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        else {
+            continue;
+        };
+        let windows = &state["windowsState"];
+        folders.extend(folder_of(&windows["lastActiveWindow"]));
+        if let Some(opened) = windows["openedWindows"].as_array() {
+            folders.extend(opened.iter().filter_map(folder_of));
+        }
+    }
+
+    let window = Duration::from_secs(RECENT_STORAGE_SECS);
+    if let Ok(entries) = fs::read_dir(user_dir.join("workspaceStorage")) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let dir = entry.path();
+            let active = ["state.vscdb", "state.vscdb-journal", "state.vscdb.backup"]
+                .iter()
+                .any(|file| modified_within(&dir.join(file), window, now));
+            if !active {
+                continue;
+            }
+            let folder = fs::read_to_string(dir.join("workspace.json"))
+                .ok()
+                .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+                .and_then(|v| folder_of(&v));
+            folders.extend(folder);
+        }
+    }
+
+    let mut seen = HashSet::new();
+    folders.retain(|f| !f.is_empty() && seen.insert(f.clone()));
+    folders
+}
+
+/// Folders open in any running VSCode-family editor
+pub fn detect_open_folders() -> Vec<String> {
+    let Some(config_dir) = config_dir() else {
+        return Vec::new();
+    };
+    let processes = process_names();
+    let now = SystemTime::now();
+
+    let mut folders = Vec::new();
+    for editor in EDITORS.iter().filter(|e| is_running(e, &processes)) {
+        let user_dir = config_dir.join(editor.data_dir).join("User");
+        for folder in open_folders(&user_dir, now) {
+            if !folders.contains(&folder) {
+                folders.push(folder);
+            }
+        }
+    }
+    folders
+}
+
+fn workspace(path: String, source: WorkspaceSource, at: DateTime<Utc>) -> VSCodeWorkspace {
+    let name = Path::new(&path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    VSCodeWorkspace {
+        path,
+        name,
+        is_open: true,
+        last_updated: at,
+        source,
+    }
+}
+
+/// Recompute the editor status of every env association. Returns whether
+/// any status changed.
+pub fn refresh_statuses(vault: &mut ApiKeyVault, at: DateTime<Utc>) -> bool {
+    let workspaces = vault.vscode_workspaces.clone();
+    let mut changed = false;
+    for assoc in &mut vault.env_associations {
+        let is_open = workspaces
+            .iter()
+            .any(|ws| ws.path == assoc.project_path || assoc.project_path.starts_with(&ws.path));
+        let status = if is_open { "open" } else { "closed" };
+        changed |= assoc.vscode_status.as_deref() != Some(status);
+        assoc.vscode_status = Some(status.to_string());
+        assoc.last_vscode_check = Some(at);
+    }
+    changed
+}
+
+/// Replace the folders pushed by the extension. Detected folders it doesn't
+/// list are kept until the watcher sees them close.
+pub fn apply_pushed(vault: &mut ApiKeyVault, paths: Vec<String>, at: DateTime<Utc>) {
+    vault
+        .vscode_workspaces
+        .retain(|ws| ws.source == WorkspaceSource::Detected && !paths.contains(&ws.path));
+    for path in paths {
+        vault
+            .vscode_workspaces
+            .push(workspace(path, WorkspaceSource::Extension, at));
+    }
+    refresh_statuses(vault, at);
+}
+
+/// Replace the detected folders, dropping pushed ones the extension stopped
+/// refreshing. Returns whether the open set or any status changed.
+pub fn apply_detected(vault: &mut ApiKeyVault, paths: Vec<String>, at: DateTime<Utc>) -> bool {
+    let before: HashSet<String> = vault
+        .vscode_workspaces
+        .iter()
+        .map(|ws| ws.path.clone())
+        .collect();
+
+    let pushed_cutoff = at - ChronoDuration::minutes(PUSHED_TTL_MINUTES);
+    vault
+        .vscode_workspaces
+        .retain(|ws| ws.source == WorkspaceSource::Extension && ws.last_updated >= pushed_cutoff);
+    for path in paths {
+        if !vault.vscode_workspaces.iter().any(|ws| ws.path == path) {
+            vault
+                .vscode_workspaces
+                .push(workspace(path, WorkspaceSource::Detected, at));
+        }
+    }
+
+    let after: HashSet<String> = vault
+        .vscode_workspaces
+        .iter()
+        .map(|ws| ws.path.clone())
+        .collect();
+    let statuses_changed = refresh_statuses(vault, at);
+    before != after || statuses_changed
+}
+
+async fn scan(state: &AppState) -> Result<bool, KeyKeeperError> {
+    let folders = tauri::async_runtime::spawn_blocking(detect_open_folders)
+        .await
+        .map_err(|e| KeyKeeperError::Internal(format!("Workspace scan failed: {}", e)))?;
+    let mut vault_guard = state.vault.lock().await;
+    Ok(apply_detected(&mut vault_guard, folders, Utc::now()))
+}
+
+/// Periodically detect open editor workspaces while the vault is unlocked
+pub async fn run_workspace_watcher(app: AppHandle) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(WATCH_INTERVAL_SECS)).await;
+
+        let state: State<'_, AppState> = app.state();
+        if !*state.is_unlocked.lock().await {
+            continue;
+        }
+
+        match scan(&state).await {
+            Ok(true) => {
+                debug!("Editor workspaces changed");
+                if let Err(e) = save_vault(&state).await {
+                    warn!("Failed to save detected workspaces: {}", e);
+                }
+            }
+            Ok(false) => {}
+            Err(e) => warn!("{}", e),
+        }
+    }
+}
+
+/// Detect open editor workspaces now instead of waiting for the watcher
+#[tauri::command]
+pub async fn refresh_vscode_workspaces(
+    state: State<'_, AppState>,
+) -> Result<Vec<VSCodeWorkspace>, KeyKeeperError> {
+    guards::unlocked(&state).await?;
+
+    if scan(&state).await? {
+        save_vault(&state).await?;
+    }
+    let vault_guard = state.vault.lock().await;
+    Ok(vault_guard.vscode_workspaces.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_open_windows_and_recent_workspace_storage() {
+        let user = std::env::temp_dir()
+            .join(format!("kk-workspaces-{}", uuid::Uuid::new_v4()))
+            .join("User");
+        fs::create_dir_all(user.join("globalStorage")).unwrap();
+        fs::write(
+            user.join("globalStorage/storage.json"),
+            r#"{"windowsState": {
+                "lastActiveWindow": {"folder": "file:///home/dev/my%20app"},
+                "openedWindows": [
+                    {"folder": "file:///home/dev/my%20app/"},
+                    {"folder": "vscode-remote://ssh-remote%2Bbox/srv/api"}
+                ]
+            }}"#,
+        )
+        .unwrap();
+        let recent = user.join("workspaceStorage/abc");
+        fs::create_dir_all(&recent).unwrap();
+        fs::write(
+            recent.join("workspace.json"),
+            r#"{"workspace": "file:///home/dev/mono/mono.code-workspace"}"#,
+        )
+        .unwrap();
+        fs::write(recent.join("state.vscdb"), "").unwrap();
+        let stale = user.join("workspaceStorage/def");
+        fs::create_dir_all(&stale).unwrap();
+        fs::write(stale.join("workspace.json"), r#"{"folder": "file:///old"}"#).unwrap();
+
+        let folders = open_folders(&user, SystemTime::now());
+        assert_eq!(folders, vec!["/home/dev/my app", "/home/dev/mono"]);
+        assert_eq!(
+            path_from_uri("file:///c%3A/Users/dev/app").as_deref(),
+            Some("c:\\Users\\dev\\app")
+        );
+        fs::remove_dir_all(user.parent().unwrap()).unwrap();
+    }
+}